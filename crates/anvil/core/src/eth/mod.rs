@@ -1,9 +1,9 @@
 use crate::{
-    eth::subscription::SubscriptionId,
-    types::{EvmMineOptions, Forking, Index},
+    eth::subscription::{AnvilSubscriptionKind, SubscriptionId},
+    types::{EvmMineOptions, Forking, Index, L1BlockValues},
 };
 use alloy_primitives::{Address, Bytes, TxHash, B256, B64, U256};
-use alloy_rpc_trace_types::geth::{GethDebugTracingOptions, GethDefaultTracingOptions};
+use alloy_rpc_trace_types::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions};
 use alloy_rpc_types::{
     pubsub::{Params as SubscriptionParams, SubscriptionKind},
     request::TransactionRequest,
@@ -274,7 +274,21 @@ pub enum EthRequest {
     DebugTraceCall(
         TransactionRequest,
         #[cfg_attr(feature = "serde", serde(default))] Option<BlockId>,
-        #[cfg_attr(feature = "serde", serde(default))] GethDefaultTracingOptions,
+        #[cfg_attr(feature = "serde", serde(default))] GethDebugTracingCallOptions,
+    ),
+
+    /// geth's `debug_traceBlockByNumber`  endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "debug_traceBlockByNumber"))]
+    DebugTraceBlockByNumber(
+        BlockNumber,
+        #[cfg_attr(feature = "serde", serde(default))] GethDebugTracingOptions,
+    ),
+
+    /// geth's `debug_traceBlockByHash`  endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "debug_traceBlockByHash"))]
+    DebugTraceBlockByHash(
+        B256,
+        #[cfg_attr(feature = "serde", serde(default))] GethDebugTracingOptions,
     ),
 
     /// Trace transaction endpoint for parity's `trace_transaction`
@@ -293,15 +307,14 @@ pub enum EthRequest {
 
     // Custom endpoints, they're not extracted to a separate type out of serde convenience
     /// send transactions impersonating specific account and contract addresses.
+    ///
+    /// The optional balance is the floor the account is topped up to whenever it can't pay for a
+    /// transaction it sends.
     #[cfg_attr(
         feature = "serde",
-        serde(
-            rename = "anvil_impersonateAccount",
-            alias = "hardhat_impersonateAccount",
-            with = "sequence"
-        )
+        serde(rename = "anvil_impersonateAccount", alias = "hardhat_impersonateAccount")
     )]
-    ImpersonateAccount(Address),
+    ImpersonateAccount(Address, #[cfg_attr(feature = "serde", serde(default))] Option<U256>),
     /// Stops impersonating an account if previously set with `anvil_impersonateAccount`
     #[cfg_attr(
         feature = "serde",
@@ -431,6 +444,10 @@ pub enum EthRequest {
     #[cfg_attr(feature = "serde", serde(rename = "anvil_setChainId", with = "sequence"))]
     SetChainId(u64),
 
+    /// Sets the values of the op-stack `L1Block` predeploy
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_setL1BlockValues", with = "sequence"))]
+    SetL1BlockValues(L1BlockValues),
+
     /// Enable or disable logging
     #[cfg_attr(
         feature = "serde",
@@ -464,6 +481,10 @@ pub enum EthRequest {
     )]
     SetNextBlockBaseFeePerGas(U256),
 
+    /// Pins the base fee of all following blocks until it is cleared with `null`
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_setBaseFee", with = "sequence"))]
+    SetBaseFee(Option<U256>),
+
     /// Sets the specific timestamp
     /// Accepts timestamp (Unix epoch) with millisecond precision and returns the number of seconds
     /// between the given timestamp and the current time.
@@ -734,6 +755,10 @@ pub enum EthPubSub {
     /// Unsubscribe from an eth subscription
     #[cfg_attr(feature = "serde", serde(rename = "eth_unsubscribe", with = "sequence"))]
     EthUnSubscribe(SubscriptionId),
+
+    /// Subscribe to an anvil specific subscription
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_subscribe", with = "sequence"))]
+    AnvilSubscribe(AnvilSubscriptionKind),
 }
 
 /// Container type for either a request or a pub sub
@@ -748,6 +773,7 @@ pub enum EthRpcCall {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_primitives::U64;
 
     #[test]
     fn test_web3_client_version() {
@@ -825,6 +851,17 @@ mod tests {
 ["0xd84de507f3fada7df80908082d3239466db55a71"]}"#;
         let value: serde_json::Value = serde_json::from_str(s).unwrap();
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "anvil_impersonateAccount", "params":
+["0xd84de507f3fada7df80908082d3239466db55a71", "0xde0b6b3a7640000"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::ImpersonateAccount(_, min_balance) => {
+                assert_eq!(min_balance, Some(U256::from(1_000_000_000_000_000_000u64)));
+            }
+            _ => unreachable!(),
+        }
     }
 
     #[test]
@@ -1097,6 +1134,22 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_set_l1_block_values() {
+        let s = r#"{"method": "anvil_setL1BlockValues", "params": [{"number": "0x1", "baseFee": "0x3b9aca00", "l1FeeScalar": "0xa6fe0"}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::SetL1BlockValues(values) => {
+                assert_eq!(values.number, Some(U64::from(1)));
+                assert_eq!(values.base_fee, Some(U256::from(1_000_000_000u64)));
+                assert_eq!(values.l1_fee_scalar, Some(U256::from(684_000u64)));
+                assert_eq!(values.hash, None);
+            }
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn test_serde_custom_set_storage_at() {
         let s = r#"{"method": "anvil_setStorageAt", "params":
@@ -1399,6 +1452,14 @@ mod tests {
         let _req = serde_json::from_value::<EthPubSub>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_anvil_subscribe() {
+        let s = r#"{"id": 1, "method": "anvil_subscribe", "params": ["node"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthPubSub>(value).unwrap();
+        assert_eq!(req, EthPubSub::AnvilSubscribe(AnvilSubscriptionKind::Node));
+    }
+
     #[test]
     fn test_serde_debug_trace_transaction() {
         let s = r#"{"method": "debug_traceTransaction", "params":
@@ -1441,6 +1502,36 @@ true}]}"#;
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_debug_trace_call_with_tracer() {
+        let s = r#"{"method": "debug_traceCall", "params": [{"data":"0xcfae3217","from":"0xd84de507f3fada7df80908082d3239466db55a71","to":"0xcbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"}, "latest", {"tracer": "callTracer", "tracerConfig": {"onlyTopCall": true}, "stateOverrides": {"0xd84de507f3fada7df80908082d3239466db55a71": {"balance": "0x1"}}, "blockOverrides": {"number": "0x10"}}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::DebugTraceCall(_, _, opts) => {
+                assert!(opts.tracing_options.tracer.is_some());
+                assert!(opts.state_overrides.is_some());
+                assert!(opts.block_overrides.is_some());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_debug_trace_block() {
+        let s = r#"{"method": "debug_traceBlockByNumber", "params": ["0x1", {"tracer": "prestateTracer", "tracerConfig": {"diffMode": true}}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "debug_traceBlockByNumber", "params": ["latest"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "debug_traceBlockByHash", "params": ["0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3", {"disableMemory": true}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_eth_storage() {
         let s = r#"{"method": "eth_getStorageAt", "params":
@@ -41,6 +41,15 @@ impl fmt::Debug for SubscriptionId {
     }
 }
 
+/// Anvil specific subscriptions, via `anvil_subscribe`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum AnvilSubscriptionKind {
+    /// Node events, like mined blocks, pool changes, impersonations and snapshots
+    Node,
+}
+
 /// Provides random hex identifier with a certain length
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct HexIdProvider {
@@ -1,7 +1,7 @@
 //! Transaction related types
 
 use crate::eth::{
-    transaction::optimism::{DepositTransaction, DepositTransactionRequest},
+    transaction::optimism::{DepositTransaction, DepositTransactionRequest, DEPOSIT_TX_TYPE_ID},
     utils::eip_to_revm_access_list,
 };
 use alloy_consensus::{ReceiptWithBloom, TxEip1559, TxEip2930, TxLegacy};
@@ -9,7 +9,7 @@ use alloy_network::{Signed, Transaction, TxKind};
 use alloy_primitives::{Address, Bloom, Bytes, Log, Signature, TxHash, B256, U128, U256, U64};
 use alloy_rlp::{Decodable, Encodable};
 use alloy_rpc_types::{
-    request::TransactionRequest, AccessList, Signature as RpcSignature,
+    other::OtherFields, request::TransactionRequest, AccessList, Signature as RpcSignature,
     Transaction as RpcTransaction,
 };
 use foundry_evm::traces::CallTraceNode;
@@ -316,28 +316,40 @@ pub fn to_alloy_transaction_with_hash_and_sender(
             blob_versioned_hashes: vec![],
             other: Default::default(),
         },
-        TypedTransaction::Deposit(t) => RpcTransaction {
-            hash,
-            nonce: U64::from(t.nonce),
-            block_hash: None,
-            block_number: None,
-            transaction_index: None,
-            from,
-            to: None,
-            value: t.value,
-            gas_price: None,
-            max_fee_per_gas: None,
-            max_priority_fee_per_gas: None,
-            gas: U256::from(t.gas_limit),
-            input: t.input.clone().0.into(),
-            chain_id: t.chain_id().map(U64::from),
-            signature: None,
-            access_list: None,
-            transaction_type: None,
-            max_fee_per_blob_gas: None,
-            blob_versioned_hashes: vec![],
-            other: Default::default(),
-        },
+        TypedTransaction::Deposit(t) => {
+            let mut other = OtherFields::default();
+            other.insert(
+                "sourceHash".to_string(),
+                serde_json::to_value(t.source_hash).expect("Infallible"),
+            );
+            other.insert("mint".to_string(), serde_json::to_value(t.mint).expect("Infallible"));
+            other.insert(
+                "isSystemTx".to_string(),
+                serde_json::to_value(t.is_system_tx).expect("Infallible"),
+            );
+            RpcTransaction {
+                hash,
+                nonce: U64::from(t.nonce),
+                block_hash: None,
+                block_number: None,
+                transaction_index: None,
+                from,
+                to: None,
+                value: t.value,
+                gas_price: None,
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
+                gas: U256::from(t.gas_limit),
+                input: t.input.clone().0.into(),
+                chain_id: t.chain_id().map(U64::from),
+                signature: None,
+                access_list: None,
+                transaction_type: Some(U64::from(DEPOSIT_TX_TYPE_ID)),
+                max_fee_per_blob_gas: None,
+                blob_versioned_hashes: vec![],
+                other,
+            }
+        }
     }
 }
 
@@ -391,6 +403,12 @@ impl PendingTransaction {
             }
         }
 
+        // the op-stack L1 data fee is charged based on the size of the enveloped transaction
+        let enveloped = || OptimismFields {
+            enveloped_tx: Some(self.transaction.transaction.encoded_2718()),
+            ..Default::default()
+        };
+
         let caller = *self.sender();
         match &self.transaction.transaction {
             TypedTransaction::Legacy(tx) => {
@@ -407,6 +425,7 @@ impl PendingTransaction {
                     gas_priority_fee: None,
                     gas_limit: *gas_limit,
                     access_list: vec![],
+                    optimism: enveloped(),
                     ..Default::default()
                 }
             }
@@ -433,6 +452,7 @@ impl PendingTransaction {
                     gas_priority_fee: None,
                     gas_limit: *gas_limit,
                     access_list: eip_to_revm_access_list(access_list.0.clone()),
+                    optimism: enveloped(),
                     ..Default::default()
                 }
             }
@@ -460,13 +480,13 @@ impl PendingTransaction {
                     gas_priority_fee: Some(U256::from(*max_priority_fee_per_gas)),
                     gas_limit: *gas_limit,
                     access_list: eip_to_revm_access_list(access_list.0.clone()),
+                    optimism: enveloped(),
                     ..Default::default()
                 }
             }
             TypedTransaction::Deposit(tx) => {
                 let chain_id = tx.chain_id();
                 let DepositTransaction {
-                    nonce,
                     source_hash,
                     gas_limit,
                     value,
@@ -481,7 +501,8 @@ impl PendingTransaction {
                     transact_to: transact_to(kind),
                     data: alloy_primitives::Bytes(input.0.clone()),
                     chain_id,
-                    nonce: Some(nonce.to::<u64>()),
+                    // deposits are not subject to nonce checks
+                    nonce: None,
                     value: *value,
                     gas_price: U256::ZERO,
                     gas_priority_fee: None,
@@ -672,6 +693,38 @@ impl TypedTransaction {
         }
     }
 
+    /// Returns the [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) encoding of the
+    /// transaction, as accepted by `eth_sendRawTransaction`.
+    pub fn encoded_2718(&self) -> Bytes {
+        let mut out = Vec::new();
+        Encodable::encode(self, &mut out);
+        if let TypedTransaction::Legacy(_) = self {
+            return out.into()
+        }
+        // typed transactions are wrapped in a rlp string header
+        let mut buf = out.as_slice();
+        match alloy_rlp::Header::decode(&mut buf) {
+            Ok(_) => buf.to_vec().into(),
+            Err(_) => out.into(),
+        }
+    }
+
+    /// Decodes a transaction from its [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718)
+    /// encoding.
+    pub fn decode_2718(data: &[u8]) -> alloy_rlp::Result<Self> {
+        let first = *data.first().ok_or(alloy_rlp::Error::InputTooShort)?;
+        if first > 0x7f {
+            // legacy transaction
+            return <Signed<TxLegacy> as Decodable>::decode(&mut &data[..])
+                .map(TypedTransaction::Legacy)
+        }
+        // the [TypedTransaction] requires a valid rlp input, but typed transactions prepend a
+        // version byte, so we need to encode the data first to get a valid rlp and then rlp
+        // decode impl of `TypedTransaction` will remove and check the version byte
+        let extend = alloy_rlp::encode(data);
+        Self::decode(&mut &extend[..])
+    }
+
     /// Returns true if the transaction was impersonated (using the impersonate Signature)
     #[cfg(feature = "impersonated-tx")]
     pub fn is_impersonated(&self) -> bool {
@@ -736,7 +789,11 @@ impl Encodable for TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.encode(out),
             TypedTransaction::EIP2930(tx) => tx.encode(out),
             TypedTransaction::EIP1559(tx) => tx.encode(out),
-            TypedTransaction::Deposit(tx) => tx.encode(out),
+            TypedTransaction::Deposit(tx) => {
+                alloy_rlp::Header { list: false, payload_length: tx.encoded_len_2718() }
+                    .encode(out);
+                tx.encode_2718(out);
+            }
         }
     }
 }
@@ -1017,6 +1074,30 @@ mod tests {
         let _decoded = TypedTransaction::decode(&mut &tx_bytes[..]).unwrap();
     }
 
+    #[test]
+    fn deposit_roundtrip() {
+        let tx = TypedTransaction::Deposit(DepositTransaction {
+            nonce: U256::ZERO,
+            source_hash: B256::with_last_byte(1),
+            from: Address::with_last_byte(2),
+            kind: TxKind::Call(Address::with_last_byte(3)),
+            mint: U256::from(100),
+            value: U256::from(10),
+            gas_limit: U256::from(21_000),
+            is_system_tx: false,
+            input: Bytes::new(),
+        });
+
+        let encoded = tx.encoded_2718();
+        assert_eq!(encoded[0], DEPOSIT_TX_TYPE_ID);
+        assert_eq!(tx.hash(), alloy_primitives::keccak256(&encoded));
+
+        let decoded = TypedTransaction::decode_2718(&encoded).unwrap();
+        assert_eq!(decoded, tx);
+        let decoded = TypedTransaction::decode(&mut &alloy_rlp::encode(&tx)[..]).unwrap();
+        assert_eq!(decoded, tx);
+    }
+
     #[test]
     fn can_recover_sender() {
         // random mainnet tx: https://etherscan.io/tx/0x86718885c4b4218c6af87d3d0b0d83e3cc465df2a05c048aa4db9f1a6f9de91f
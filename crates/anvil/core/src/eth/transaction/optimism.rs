@@ -1,11 +1,18 @@
 use alloy_consensus::TxType;
 use alloy_network::{Transaction, TxKind};
-use alloy_primitives::{Address, Bytes, ChainId, Signature, B256, U256};
+use alloy_primitives::{address, Address, Bytes, ChainId, Signature, B256, U256};
 use alloy_rlp::{
     length_of_length, Decodable, Encodable, Error as DecodeError, Header as RlpHeader,
 };
 use std::mem;
 
+/// The [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) type of op-stack deposit transactions.
+pub const DEPOSIT_TX_TYPE_ID: u8 = 0x7E;
+
+/// The address of the op-stack `L1Block` predeploy, which holds the L1 values used to compute the
+/// L1 data fee.
+pub const L1_BLOCK_ADDRESS: Address = address!("4200000000000000000000000000000000000015");
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DepositTransactionRequest {
     pub source_hash: B256,
@@ -281,8 +288,25 @@ impl DepositTransaction {
         &self.nonce
     }
 
+    /// Returns the hash of the transaction, computed over its [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) envelope.
     pub fn hash(&self) -> B256 {
-        B256::from_slice(alloy_primitives::keccak256(alloy_rlp::encode(self)).as_slice())
+        let mut buf = Vec::with_capacity(self.encoded_len_2718());
+        self.encode_2718(&mut buf);
+        alloy_primitives::keccak256(&buf)
+    }
+
+    /// Encodes the transaction as `0x7E || rlp([source_hash, from, to, mint, value, gas_limit,
+    /// is_system_tx, input])`.
+    ///
+    /// The nonce is not part of the encoding, it is assigned when the deposit is executed.
+    pub fn encode_2718(&self, out: &mut dyn alloy_rlp::BufMut) {
+        out.put_u8(DEPOSIT_TX_TYPE_ID);
+        self.encode(out);
+    }
+
+    /// Returns the length of the [EIP-2718](Self::encode_2718) encoding.
+    pub fn encoded_len_2718(&self) -> usize {
+        1 + self.length()
     }
 
     // /// Recovers the Ethereum address which was used to sign the transaction.
@@ -296,7 +320,6 @@ impl DepositTransaction {
 
     /// Encodes only the transaction's fields into the desired buffer, without a RLP header.
     pub(crate) fn encode_fields(&self, out: &mut dyn alloy_rlp::BufMut) {
-        self.nonce.encode(out);
         self.source_hash.encode(out);
         self.from.encode(out);
         self.kind.encode(out);
@@ -354,6 +377,11 @@ impl Encodable for DepositTransaction {
         RlpHeader { list: true, payload_length: self.fields_len() }.encode(out);
         self.encode_fields(out);
     }
+
+    fn length(&self) -> usize {
+        let payload_length = self.fields_len();
+        length_of_length(payload_length) + payload_length
+    }
 }
 
 impl Decodable for DepositTransaction {
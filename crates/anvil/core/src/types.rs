@@ -1,4 +1,5 @@
 use alloy_primitives::{TxHash, B256, U256, U64};
+use alloy_rpc_trace_types::geth::GethTrace;
 use revm::primitives::SpecId;
 use std::collections::BTreeMap;
 
@@ -219,6 +220,10 @@ pub struct AnvilMetadata {
     pub latest_block_hash: B256,
     pub forked_network: Option<ForkedNetwork>,
     pub snapshots: BTreeMap<U256, (u64, B256)>,
+    pub hard_fork: SpecId,
+    pub base_fee: U256,
+    pub gas_limit: U256,
+    pub mining_mode: MiningModeInfo,
 }
 
 /// Information about the forked network.
@@ -230,6 +235,57 @@ pub struct ForkedNetwork {
     pub chain_id: u64,
     pub fork_block_number: u64,
     pub fork_block_hash: TxHash,
+    /// The fork url, with any credentials redacted
+    pub fork_url: String,
+}
+
+/// The mining mode of the Anvil instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "camelCase"))]
+pub enum MiningModeInfo {
+    /// Blocks are only mined on request
+    None,
+    /// A block is mined for every transaction
+    Auto,
+    /// Blocks are mined every `block_time` seconds
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    Interval { block_time: u64 },
+}
+
+/// The trace of a single transaction of a block, as returned by `debug_traceBlockByNumber` and
+/// `debug_traceBlockByHash`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BlockTraceResult {
+    pub tx_hash: B256,
+    pub result: GethTrace,
+}
+
+/// Values to write to the op-stack `L1Block` predeploy, see `anvil_setL1BlockValues`.
+///
+/// Fields that are not set are left unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
+pub struct L1BlockValues {
+    /// The latest L1 block number known by the L2 system
+    pub number: Option<U64>,
+    /// The latest L1 timestamp known by the L2 system
+    pub timestamp: Option<U64>,
+    /// The latest L1 base fee
+    pub base_fee: Option<U256>,
+    /// The latest L1 block hash
+    pub hash: Option<B256>,
+    /// The number of L2 blocks in the same epoch
+    pub sequence_number: Option<U64>,
+    /// The versioned hash to authenticate the batcher by
+    pub batcher_hash: Option<B256>,
+    /// The overhead value applied to the L1 portion of the transaction fee
+    pub l1_fee_overhead: Option<U256>,
+    /// The scalar value applied to the L1 portion of the transaction fee
+    pub l1_fee_scalar: Option<U256>,
 }
 
 #[cfg(test)]
@@ -24,7 +24,7 @@ pub trait PubSubRpcHandler: Clone + Send + Sync + Unpin + 'static {
     /// The request type to expect
     type Request: DeserializeOwned + Send + Sync + fmt::Debug;
     /// The identifier to use for subscriptions
-    type SubscriptionId: Hash + PartialEq + Eq + Send + Sync + fmt::Debug;
+    type SubscriptionId: Clone + Hash + PartialEq + Eq + Send + Sync + fmt::Debug;
     /// The subscription type this handle may create
     type Subscription: Stream<Item = serde_json::Value> + Send + Sync + Unpin;
 
@@ -38,6 +38,8 @@ type Subscriptions<SubscriptionId, Subscription> = Arc<Mutex<Vec<(SubscriptionId
 pub struct PubSubContext<Handler: PubSubRpcHandler> {
     /// all active subscriptions `id -> Stream`
     subscriptions: Subscriptions<Handler::SubscriptionId, Handler::Subscription>,
+    /// subscriptions removed since the connection last checked, whose unsent events are discarded
+    removed: Arc<Mutex<Vec<Handler::SubscriptionId>>>,
 }
 
 // === impl PubSubContext ===
@@ -70,7 +72,9 @@ impl<Handler: PubSubRpcHandler> PubSubContext<Handler> {
         let mut subscriptions = self.subscriptions.lock();
         if let Some(idx) = subscriptions.iter().position(|(i, _)| id == i) {
             trace!(target: "rpc", ?id,  "removed subscription");
-            return Some(subscriptions.swap_remove(idx).1)
+            let (id, subscription) = subscriptions.swap_remove(idx);
+            self.removed.lock().push(id);
+            return Some(subscription)
         }
         None
     }
@@ -78,13 +82,16 @@ impl<Handler: PubSubRpcHandler> PubSubContext<Handler> {
 
 impl<Handler: PubSubRpcHandler> Clone for PubSubContext<Handler> {
     fn clone(&self) -> Self {
-        Self { subscriptions: Arc::clone(&self.subscriptions) }
+        Self { subscriptions: Arc::clone(&self.subscriptions), removed: Arc::clone(&self.removed) }
     }
 }
 
 impl<Handler: PubSubRpcHandler> Default for PubSubContext<Handler> {
     fn default() -> Self {
-        Self { subscriptions: Arc::new(Mutex::new(Vec::new())) }
+        Self {
+            subscriptions: Arc::new(Mutex::new(Vec::new())),
+            removed: Arc::new(Mutex::new(Vec::new())),
+        }
     }
 }
 
@@ -121,8 +128,8 @@ pub struct PubSubConnection<Handler: PubSubRpcHandler, Connection> {
     connection: Connection,
     /// currently in progress requests
     processing: Vec<Pin<Box<dyn Future<Output = Response> + Send>>>,
-    /// pending messages to send
-    pending: VecDeque<String>,
+    /// pending messages to send, with the subscription they're an event of
+    pending: VecDeque<(Option<Handler::SubscriptionId>, String)>,
 }
 
 // === impl PubSubConnection ===
@@ -173,7 +180,7 @@ where
             // drive the websocket
             while let Poll::Ready(Ok(())) = pin.connection.poll_ready_unpin(cx) {
                 // only start sending if socket is ready
-                if let Some(msg) = pin.pending.pop_front() {
+                if let Some((_, msg)) = pin.pending.pop_front() {
                     if let Err(err) = pin.connection.start_send_unpin(msg) {
                         error!(target: "rpc", ?err, "Failed to send message");
                     }
@@ -229,7 +236,7 @@ where
                 match req.poll_unpin(cx) {
                     Poll::Ready(resp) => {
                         if let Ok(text) = serde_json::to_string(&resp) {
-                            pin.pending.push_back(text);
+                            pin.pending.push_back((None, text));
                             progress = true;
                         }
                     }
@@ -237,6 +244,13 @@ where
                 }
             }
 
+            // discard the unsent events of removed subscriptions, so no event is delivered after
+            // the response to `eth_unsubscribe`
+            let removed = std::mem::take(&mut *pin.context.removed.lock());
+            if !removed.is_empty() {
+                pin.pending.retain(|(id, _)| id.as_ref().map_or(true, |id| !removed.contains(id)));
+            }
+
             {
                 // process subscription events
                 let mut subscriptions = pin.context.subscriptions.lock();
@@ -246,7 +260,7 @@ where
                         match sub.poll_next_unpin(cx) {
                             Poll::Ready(Some(res)) => {
                                 if let Ok(text) = serde_json::to_string(&res) {
-                                    pin.pending.push_back(text);
+                                    pin.pending.push_back((Some(id.clone()), text));
                                     progress = true;
                                 }
                             }
@@ -1,15 +1,20 @@
 use crate::{
     config::DEFAULT_MNEMONIC,
-    eth::{backend::db::SerializableState, pool::transactions::TransactionOrder, EthApi},
+    eth::{
+        backend::{db::SerializableState, genesis::HardforkSchedule},
+        pool::transactions::TransactionOrder,
+        EthApi,
+    },
     AccountGenerator, Hardfork, NodeConfig, CHAIN_ID,
 };
 use alloy_genesis::Genesis;
-use alloy_primitives::{utils::Unit, U256};
+use alloy_primitives::{utils::Unit, B256, U256};
 use alloy_signer::coins_bip39::{English, Mnemonic};
 use anvil_server::ServerConfig;
 use clap::Parser;
 use core::fmt;
 use foundry_config::{Chain, Config};
+use foundry_evm::precompiles::PrecompileSpec;
 use futures::FutureExt;
 use rand::{rngs::StdRng, SeedableRng};
 use std::{
@@ -75,6 +80,12 @@ pub struct NodeArgs {
     #[arg(long)]
     pub silent: bool,
 
+    /// Log versioned NDJSON node events instead of the human readable output.
+    ///
+    /// The same events are available via the `anvil_subscribe("node")` websocket subscription.
+    #[arg(long, conflicts_with = "silent")]
+    pub json_logs: bool,
+
     /// The EVM hardfork to use.
     ///
     /// Choose the hardfork by name, e.g. `shanghai`, `paris`, `london`, etc...
@@ -205,6 +216,7 @@ impl NodeArgs {
                     .fork_block_number
                     .or_else(|| self.evm_opts.fork_url.as_ref().and_then(|f| f.block)),
             )
+            .with_fork_transaction_hash(self.evm_opts.fork_transaction_hash)
             .with_fork_headers(self.evm_opts.fork_headers)
             .with_fork_chain_id(self.evm_opts.fork_chain_id.map(u64::from).map(U256::from))
             .fork_request_timeout(self.evm_opts.fork_request_timeout.map(Duration::from_millis))
@@ -213,16 +225,23 @@ impl NodeArgs {
             .fork_compute_units_per_second(compute_units_per_second)
             .with_eth_rpc_url(self.evm_opts.fork_url.map(|fork| fork.url))
             .with_base_fee(self.evm_opts.block_base_fee_per_gas.map(U256::from))
+            .disable_base_fee_adjustment(self.evm_opts.disable_base_fee_adjustment)
             .with_storage_caching(self.evm_opts.no_storage_caching)
             .with_server_config(self.server_config)
             .with_host(self.host)
             .set_silent(self.silent)
+            .with_json_logs(self.json_logs)
             .set_config_out(self.config_out)
             .with_chain_id(self.evm_opts.chain_id)
             .with_transaction_order(self.order)
             .with_genesis(self.init)
             .with_steps_tracing(self.evm_opts.steps_tracing)
             .with_auto_impersonate(self.evm_opts.auto_impersonate)
+            .with_impersonate_min_balance(
+                self.evm_opts
+                    .impersonate_min_balance
+                    .map(|balance| Unit::ETHER.wei().saturating_mul(U256::from(balance))),
+            )
             .with_ipc(self.ipc)
             .with_code_size_limit(self.evm_opts.code_size_limit)
             .set_pruned_history(self.prune_history)
@@ -230,6 +249,7 @@ impl NodeArgs {
             .with_transaction_block_keeper(self.transaction_block_keeper)
             .with_optimism(self.evm_opts.optimism)
             .with_disable_default_create2_deployer(self.evm_opts.disable_default_create2_deployer)
+            .with_precompiles(self.evm_opts.precompiles)
     }
 
     fn account_generator(&self) -> AccountGenerator {
@@ -269,7 +289,8 @@ impl NodeArgs {
         let dump_interval =
             self.state_interval.map(Duration::from_secs).unwrap_or(DEFAULT_DUMP_INTERVAL);
 
-        let (api, mut handle) = crate::spawn(self.into_node_config()).await;
+        let (api, mut handle) =
+            crate::try_spawn(self.into_node_config()).await.map_err(|err| format!("{err:#}"))?;
 
         // sets the signal handler to gracefully shutdown.
         let mut fork = api.get_fork();
@@ -388,6 +409,21 @@ pub struct AnvilEvmArgs {
     #[arg(long, requires = "fork_url", value_name = "BLOCK", help_heading = "Fork config")]
     pub fork_block_number: Option<u64>,
 
+    /// Fetch state from right before a specific transaction hash over a remote endpoint.
+    ///
+    /// The fork is created off the parent of the transaction's block, and the transactions
+    /// preceding it in its block are replayed on top.
+    ///
+    /// See --fork-url.
+    #[arg(
+        long,
+        requires = "fork_url",
+        value_name = "TRANSACTION",
+        help_heading = "Fork config",
+        conflicts_with = "fork_block_number"
+    )]
+    pub fork_transaction_hash: Option<B256>,
+
     /// Initial retry backoff on encountering errors.
     ///
     /// See --fork-url.
@@ -396,6 +432,9 @@ pub struct AnvilEvmArgs {
 
     /// Specify chain id to skip fetching it from remote endpoint. This enables offline-start mode.
     ///
+    /// This is the chain ID of the forked chain, which keys its cached state. Use `--chain-id` to
+    /// run the fork under a different chain ID.
+    ///
     /// You still must pass both `--fork-url` and `--fork-block-number`, and already have your
     /// required state cached on disk, anything missing locally would be fetched from the
     /// remote.
@@ -439,12 +478,18 @@ pub struct AnvilEvmArgs {
 
     /// Explicitly disables the use of RPC caching.
     ///
-    /// All storage slots are read entirely from the endpoint.
+    /// All storage slots are read entirely from the endpoint. Otherwise the state fetched for a
+    /// fork pinned with --fork-block-number is cached on disk, shared with forge.
     ///
     /// This flag overrides the project's configuration file.
     ///
     /// See --fork-url.
-    #[arg(long, requires = "fork_url", help_heading = "Fork config")]
+    #[arg(
+        long,
+        visible_alias = "no-rpc-cache",
+        requires = "fork_url",
+        help_heading = "Fork config"
+    )]
     pub no_storage_caching: bool,
 
     /// The block gas limit.
@@ -479,7 +524,14 @@ pub struct AnvilEvmArgs {
     )]
     pub block_base_fee_per_gas: Option<u64>,
 
+    /// Keep the base fee constant instead of adjusting it based on how full blocks are.
+    #[arg(long, help_heading = "Environment config")]
+    pub disable_base_fee_adjustment: bool,
+
     /// The chain ID.
+    ///
+    /// When forking, this overrides the chain ID of the forked chain for `eth_chainId`, the
+    /// validation of transaction signatures and the `CHAINID` opcode.
     #[arg(long, alias = "chain", help_heading = "Environment config")]
     pub chain_id: Option<Chain>,
 
@@ -491,6 +543,13 @@ pub struct AnvilEvmArgs {
     #[arg(long, visible_alias = "auto-impersonate")]
     pub auto_impersonate: bool,
 
+    /// Top up impersonated accounts to this balance in Ether whenever they can't pay for a
+    /// transaction they send.
+    ///
+    /// The funds are not removed again when the impersonation stops.
+    #[arg(long, value_name = "NUM")]
+    pub impersonate_min_balance: Option<u64>,
+
     /// Run an Optimism chain
     #[arg(long, visible_alias = "optimism")]
     pub optimism: bool,
@@ -498,6 +557,15 @@ pub struct AnvilEvmArgs {
     /// Disable the default create2 deployer
     #[arg(long, visible_alias = "no-create2")]
     pub disable_default_create2_deployer: bool,
+
+    /// Registers a custom precompile at the given address, backed by a native plugin library or
+    /// a WebAssembly module.
+    ///
+    /// Can be specified multiple times, e.g. `--precompile 0x100=./bls.so`.
+    ///
+    /// See the `foundry_evm::precompiles` module documentation for the plugin ABI.
+    #[arg(long = "precompile", value_name = "ADDRESS=PATH", help_heading = "Environment config")]
+    pub precompiles: Vec<PrecompileSpec>,
 }
 
 /// Resolves an alias passed as fork-url to the matching url defined in the rpc_endpoints section
@@ -667,9 +735,24 @@ impl FromStr for ForkUrl {
     }
 }
 
-/// Clap's value parser for genesis. Loads a genesis.json file.
+/// Clap's value parser for genesis. Loads and validates a genesis.json file.
 fn read_genesis_file(path: &str) -> Result<Genesis, String> {
-    foundry_common::fs::read_json_file(path.as_ref()).map_err(|err| err.to_string())
+    let genesis: Genesis =
+        foundry_common::fs::read_json_file(path.as_ref()).map_err(|err| err.to_string())?;
+    validate_genesis(&genesis).map_err(|err| format!("invalid genesis file {path}: {err}"))?;
+    Ok(genesis)
+}
+
+/// Checks the fields of a genesis.json that can't be used to initialize the node
+fn validate_genesis(genesis: &Genesis) -> Result<(), String> {
+    if genesis.gas_limit == 0 {
+        return Err("`gasLimit` must be greater than 0".to_string())
+    }
+    if genesis.config.chain_id == 0 {
+        return Err("`config.chainId` must be greater than 0".to_string())
+    }
+    HardforkSchedule::from_chain_config(&genesis.config)?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -749,6 +832,29 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn can_parse_no_rpc_cache() {
+        let args = NodeArgs::parse_from([
+            "anvil",
+            "--fork-url",
+            "http://localhost:8545",
+            "--no-rpc-cache",
+        ]);
+        assert!(args.evm_opts.no_storage_caching);
+    }
+
+    #[test]
+    fn can_parse_impersonate_min_balance() {
+        let args = NodeArgs::parse_from(["anvil", "--impersonate-min-balance", "10"]);
+        assert_eq!(args.evm_opts.impersonate_min_balance, Some(10));
+    }
+
+    #[test]
+    fn can_parse_disable_base_fee_adjustment() {
+        let args = NodeArgs::parse_from(["anvil", "--disable-base-fee-adjustment"]);
+        assert!(args.evm_opts.disable_base_fee_adjustment);
+    }
+
     #[test]
     fn can_parse_host() {
         let args = NodeArgs::parse_from(["anvil"]);
@@ -4,7 +4,7 @@ use crate::{
         backend::{
             db::{Db, SerializableState},
             fork::{ClientFork, ClientForkConfig},
-            genesis::GenesisConfig,
+            genesis::{GenesisConfig, HardforkSchedule},
             mem::fork_db::ForkedDatabase,
             time::duration_since_unix_epoch,
         },
@@ -16,14 +16,15 @@ use crate::{
     FeeManager, Hardfork,
 };
 use alloy_genesis::Genesis;
-use alloy_primitives::{hex, utils::Unit, U256};
+use alloy_primitives::{hex, utils::Unit, Bytes, B256, U256};
 use alloy_providers::provider::TempProvider;
-use alloy_rpc_types::BlockNumberOrTag;
+use alloy_rpc_types::{BlockNumberOrTag, BlockTransactions};
 use alloy_signer::{
     coins_bip39::{English, Mnemonic},
     LocalWallet, MnemonicBuilder, Signer as AlloySigner,
 };
 use alloy_transport::TransportError;
+use anvil_core::eth::transaction::TypedTransaction;
 use anvil_server::ServerConfig;
 use foundry_common::{
     provider::alloy::ProviderBuilder, ALCHEMY_FREE_TIER_CUPS, NON_ARCHIVE_NODE_WARNING,
@@ -33,6 +34,7 @@ use foundry_config::Config;
 use foundry_evm::{
     constants::DEFAULT_CREATE2_DEPLOYER,
     fork::{BlockchainDb, BlockchainDbMeta, SharedBackend},
+    precompiles::{CustomPrecompiles, PrecompileSpec},
     revm,
     revm::primitives::{BlockEnv, CfgEnv, SpecId, TxEnv},
     utils::apply_chain_and_block_specific_env_changes,
@@ -55,6 +57,8 @@ use yansi::Paint;
 pub const NODE_PORT: u16 = 8545;
 /// Default chain id of the node
 pub const CHAIN_ID: u64 = 31337;
+/// Default block gas limit of the node
+pub const DEFAULT_GAS_LIMIT: u64 = 30_000_000;
 /// Default mnemonic for dev accounts
 pub const DEFAULT_MNEMONIC: &str = "test test test test test test test test test test test junk";
 
@@ -91,13 +95,15 @@ pub struct NodeConfig {
     /// Chain ID of the EVM chain
     pub chain_id: Option<u64>,
     /// Default gas limit for all txs
-    pub gas_limit: U256,
+    pub gas_limit: Option<U256>,
     /// If set to `true`, disables the block gas limit
     pub disable_block_gas_limit: bool,
     /// Default gas price for all txs
     pub gas_price: Option<U256>,
     /// Default base fee
     pub base_fee: Option<U256>,
+    /// If set to `true`, the base fee stays constant regardless of how full blocks are
+    pub disable_base_fee_adjustment: bool,
     /// The hardfork to use
     pub hardfork: Option<Hardfork>,
     /// Signer accounts that will be initialised with `genesis_balance` in the genesis block
@@ -118,10 +124,16 @@ pub struct NodeConfig {
     pub max_transactions: usize,
     /// don't print anything on startup
     pub silent: bool,
+    /// log versioned NDJSON node events instead of the human readable output
+    pub json_logs: bool,
     /// url of the rpc server that should be used for any rpc calls
     pub eth_rpc_url: Option<String>,
     /// pins the block number for the state fork
     pub fork_block_number: Option<u64>,
+    /// pins the state fork right before this transaction
+    pub fork_transaction_hash: Option<B256>,
+    /// transactions preceding `fork_transaction_hash` in its block, replayed on top of the fork
+    pub fork_transactions: Vec<TypedTransaction>,
     /// headers to use with `eth_rpc_url`
     pub fork_headers: Vec<String>,
     /// specifies chain id for cache to skip fetching from remote in offline-start mode
@@ -156,6 +168,8 @@ pub struct NodeConfig {
     pub enable_steps_tracing: bool,
     /// Enable auto impersonation of accounts on startup
     pub enable_auto_impersonate: bool,
+    /// Balance impersonated accounts are topped up to if they can't pay for a transaction
+    pub impersonate_min_balance: Option<U256>,
     /// Configure the code size limit
     pub code_size_limit: Option<usize>,
     /// Configures how to remove historic state.
@@ -170,6 +184,8 @@ pub struct NodeConfig {
     pub disable_default_create2_deployer: bool,
     /// Enable Optimism deposit transaction
     pub enable_optimism: bool,
+    /// User-defined precompiles backed by native plugin libraries or WebAssembly modules
+    pub precompiles: Vec<PrecompileSpec>,
 }
 
 impl NodeConfig {
@@ -242,7 +258,10 @@ Chain ID:       {}
                 fork.block_hash(),
                 fork.chain_id()
             );
-        } else {
+        }
+
+        // the chain ID of the node differs from the forked chain's with `--chain-id`
+        if fork.map_or(true, |fork| fork.chain_id() != self.get_chain_id()) {
             let _ = write!(
                 config_string,
                 r#"
@@ -284,7 +303,7 @@ Gas Limit
 ==================
 {}
 "#,
-            Paint::green(format!("\n{}", self.gas_limit))
+            Paint::green(format!("\n{}", self.get_gas_limit()))
         );
 
         let _ = write!(
@@ -325,11 +344,12 @@ Genesis Timestamp
               "endpoint": fork.eth_rpc_url(),
               "block_number": fork.block_number(),
               "block_hash": fork.block_hash(),
-              "chain_id": fork.chain_id(),
+              "chain_id": self.get_chain_id(),
+              "fork_chain_id": fork.chain_id(),
               "wallet": wallet_description,
               "base_fee": format!("{}", self.get_base_fee()),
               "gas_price": format!("{}", self.get_gas_price()),
-              "gas_limit": format!("{}", self.gas_limit),
+              "gas_limit": format!("{}", self.get_gas_limit()),
             })
         } else {
             json!({
@@ -338,7 +358,7 @@ Genesis Timestamp
               "wallet": wallet_description,
               "base_fee": format!("{}", self.get_base_fee()),
               "gas_price": format!("{}", self.get_gas_price()),
-              "gas_limit": format!("{}", self.gas_limit),
+              "gas_limit": format!("{}", self.get_gas_limit()),
               "genesis_timestamp": format!("{}", self.get_genesis_timestamp()),
             })
         }
@@ -362,7 +382,7 @@ impl Default for NodeConfig {
         let genesis_accounts = AccountGenerator::new(10).phrase(DEFAULT_MNEMONIC).gen();
         Self {
             chain_id: None,
-            gas_limit: U256::from(30_000_000),
+            gas_limit: None,
             disable_block_gas_limit: false,
             gas_price: None,
             hardfork: None,
@@ -377,13 +397,18 @@ impl Default for NodeConfig {
             // TODO make this something dependent on block capacity
             max_transactions: 1_000,
             silent: false,
+            json_logs: false,
             eth_rpc_url: None,
             fork_block_number: None,
+            fork_transaction_hash: None,
+            fork_transactions: vec![],
             account_generator: None,
             base_fee: None,
+            disable_base_fee_adjustment: false,
             enable_tracing: true,
             enable_steps_tracing: false,
             enable_auto_impersonate: false,
+            impersonate_min_balance: None,
             no_storage_caching: false,
             server_config: Default::default(),
             host: vec![IpAddr::V4(Ipv4Addr::LOCALHOST)],
@@ -404,6 +429,7 @@ impl Default for NodeConfig {
             transaction_block_keeper: None,
             disable_default_create2_deployer: false,
             enable_optimism: false,
+            precompiles: vec![],
         }
     }
 }
@@ -423,7 +449,21 @@ impl NodeConfig {
 
     /// Returns the base fee to use
     pub fn get_hardfork(&self) -> Hardfork {
-        self.hardfork.unwrap_or_default()
+        if let Some(hardfork) = self.hardfork {
+            return hardfork
+        }
+        self.genesis_hardfork_schedule()
+            .map(|schedule| {
+                let number = self.genesis.as_ref().and_then(|g| g.number).unwrap_or_default();
+                schedule.hardfork_at(number, self.get_genesis_timestamp())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the hardfork activations configured in the `genesis.json`, if any
+    fn genesis_hardfork_schedule(&self) -> Option<HardforkSchedule> {
+        let genesis = self.genesis.as_ref()?;
+        HardforkSchedule::from_chain_config(&genesis.config).ok().filter(|s| !s.is_empty())
     }
 
     /// Sets a custom code size limit
@@ -476,12 +516,19 @@ impl NodeConfig {
     /// Sets the gas limit
     #[must_use]
     pub fn with_gas_limit(mut self, gas_limit: Option<U256>) -> Self {
-        if let Some(gas_limit) = gas_limit {
-            self.gas_limit = gas_limit;
-        }
+        self.gas_limit = gas_limit;
         self
     }
 
+    /// Returns the block gas limit to use
+    ///
+    /// An explicitly configured gas limit takes precedence over the one of the `genesis.json`.
+    pub fn get_gas_limit(&self) -> U256 {
+        self.gas_limit
+            .or_else(|| self.genesis.as_ref().map(|genesis| U256::from(genesis.gas_limit)))
+            .unwrap_or(U256::from(DEFAULT_GAS_LIMIT))
+    }
+
     /// Disable block gas limit check
     ///
     /// If set to `true` block gas limit will not be enforced
@@ -522,6 +569,15 @@ impl NodeConfig {
         self
     }
 
+    /// Disables the base fee adjustment between blocks
+    ///
+    /// If set to `true` the base fee stays constant regardless of how full blocks are
+    #[must_use]
+    pub fn disable_base_fee_adjustment(mut self, disable_base_fee_adjustment: bool) -> Self {
+        self.disable_base_fee_adjustment = disable_base_fee_adjustment;
+        self
+    }
+
     /// Sets the init genesis (genesis.json)
     #[must_use]
     pub fn with_genesis(mut self, genesis: Option<Genesis>) -> Self {
@@ -615,6 +671,13 @@ impl NodeConfig {
         self
     }
 
+    /// Logs NDJSON node events instead of the human readable output
+    #[must_use]
+    pub fn with_json_logs(mut self, json_logs: bool) -> Self {
+        self.json_logs = json_logs;
+        self
+    }
+
     /// Sets the ipc path to use
     ///
     /// Note: this is a double Option for
@@ -660,6 +723,16 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the `fork_transaction_hash` to fork off from, right before the transaction is executed
+    #[must_use]
+    pub fn with_fork_transaction_hash<U: Into<B256>>(
+        mut self,
+        fork_transaction_hash: Option<U>,
+    ) -> Self {
+        self.fork_transaction_hash = fork_transaction_hash.map(Into::into);
+        self
+    }
+
     /// Sets the `fork_chain_id` to use to fork off local cache from
     #[must_use]
     pub fn with_fork_chain_id(mut self, fork_chain_id: Option<U256>) -> Self {
@@ -733,6 +806,13 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the balance floor of impersonated accounts
+    #[must_use]
+    pub fn with_impersonate_min_balance(mut self, min_balance: Option<U256>) -> Self {
+        self.impersonate_min_balance = min_balance;
+        self
+    }
+
     #[must_use]
     pub fn with_server_config(mut self, config: ServerConfig) -> Self {
         self.server_config = config;
@@ -770,7 +850,7 @@ impl NodeConfig {
             )
             .expect("Failed writing json");
         }
-        if self.silent {
+        if self.silent || self.json_logs {
             return;
         }
 
@@ -779,14 +859,20 @@ impl NodeConfig {
 
     /// Returns the path where the cache file should be stored
     ///
+    /// The cache is shared with forge and only used if the fork block is pinned, since the cached
+    /// data is only immutable for a fixed block.
+    ///
+    /// The cache is keyed by the chain ID of the forked chain, which may differ from the chain ID
+    /// of the node.
+    ///
     /// See also [ Config::foundry_block_cache_file()]
-    pub fn block_cache_path(&self, block: u64) -> Option<PathBuf> {
-        if self.no_storage_caching || self.eth_rpc_url.is_none() {
+    pub fn block_cache_path(&self, fork_chain_id: u64, block: u64) -> Option<PathBuf> {
+        if self.no_storage_caching || self.eth_rpc_url.is_none() || self.fork_block_number.is_none()
+        {
             return None;
         }
-        let chain_id = self.get_chain_id();
 
-        Config::foundry_block_cache_file(chain_id, block)
+        Config::foundry_block_cache_file(fork_chain_id, block)
     }
 
     /// Sets whether to enable optimism support
@@ -803,11 +889,20 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the user-defined precompiles
+    #[must_use]
+    pub fn with_precompiles(mut self, precompiles: Vec<PrecompileSpec>) -> Self {
+        self.precompiles = precompiles;
+        self
+    }
+
     /// Configures everything related to env, backend and database and returns the
     /// [Backend](mem::Backend)
     ///
     /// *Note*: only memory based backend for now
-    pub(crate) async fn setup(&mut self) -> mem::Backend {
+    pub(crate) async fn setup(&mut self) -> eyre::Result<mem::Backend> {
+        let precompiles = CustomPrecompiles::load(&self.precompiles)?;
+
         // configure the revm environment
 
         let mut cfg = CfgEnv::default();
@@ -824,13 +919,14 @@ impl NodeConfig {
         let mut env = revm::primitives::Env {
             cfg,
             block: BlockEnv {
-                gas_limit: self.gas_limit,
+                gas_limit: self.get_gas_limit(),
                 basefee: self.get_base_fee(),
                 ..Default::default()
             },
             tx: TxEnv { chain_id: self.get_chain_id().into(), ..Default::default() },
         };
-        let fees = FeeManager::new(env.cfg.spec_id, self.get_base_fee(), self.get_gas_price());
+        let fees = FeeManager::new(env.cfg.spec_id, self.get_base_fee(), self.get_gas_price())
+            .with_base_fee_adjustment(!self.disable_base_fee_adjustment);
 
         let (db, fork): (Arc<tokio::sync::RwLock<Box<dyn Db>>>, Option<ClientFork>) =
             if let Some(eth_rpc_url) = self.eth_rpc_url.clone() {
@@ -839,12 +935,26 @@ impl NodeConfig {
                 (Arc::new(tokio::sync::RwLock::new(Box::<MemDb>::default())), None)
             };
 
-        // if provided use all settings of `genesis.json`
+        // if provided use all settings of `genesis.json`, explicitly configured values take
+        // precedence
         if let Some(ref genesis) = self.genesis {
-            env.cfg.chain_id = genesis.config.chain_id;
-            env.block.timestamp = U256::from(genesis.timestamp);
-            if let Some(base_fee) = genesis.base_fee_per_gas {
-                env.block.basefee = U256::from(base_fee);
+            if self.chain_id.map_or(false, |id| id != genesis.config.chain_id) {
+                warn!(target: "node", "--chain-id overrides chainId {} of genesis.json", genesis.config.chain_id);
+            }
+            if self.genesis_timestamp.map_or(false, |ts| ts != genesis.timestamp) {
+                warn!(target: "node", "--timestamp overrides timestamp {} of genesis.json", genesis.timestamp);
+            }
+            env.block.timestamp = U256::from(self.get_genesis_timestamp());
+            if let (Some(_), Some(base_fee)) = (self.base_fee, genesis.base_fee_per_gas) {
+                if self.base_fee != Some(U256::from(base_fee)) {
+                    warn!(target: "node", "--base-fee overrides baseFeePerGas {base_fee} of genesis.json");
+                }
+            }
+            if self.gas_limit.map_or(false, |limit| limit != U256::from(genesis.gas_limit)) {
+                warn!(target: "node", "--gas-limit overrides gasLimit {} of genesis.json", genesis.gas_limit);
+            }
+            if self.hardfork.is_some() && self.genesis_hardfork_schedule().is_some() {
+                warn!(target: "node", "--hardfork overrides the fork schedule of genesis.json");
             }
             if let Some(number) = genesis.number {
                 env.block.number = U256::from(number);
@@ -858,6 +968,11 @@ impl NodeConfig {
             accounts: self.genesis_accounts.iter().map(|acc| acc.address()).collect(),
             fork_genesis_account_infos: Arc::new(Default::default()),
             genesis_init: self.genesis.clone(),
+            hardfork_schedule: if self.hardfork.is_some() {
+                None
+            } else {
+                self.genesis_hardfork_schedule()
+            },
         };
 
         // only memory based backend for now
@@ -872,6 +987,7 @@ impl NodeConfig {
             self.transaction_block_keeper,
             self.block_time,
             Arc::new(tokio::sync::RwLock::new(self.clone())),
+            precompiles,
         )
         .await;
 
@@ -888,7 +1004,14 @@ impl NodeConfig {
             backend.load_state(state).await.expect("Failed to load init state");
         }
 
-        backend
+        if !self.fork_transactions.is_empty() {
+            backend
+                .replay_transactions(self.fork_transactions.clone())
+                .await
+                .expect("Failed to replay fork transactions");
+        }
+
+        Ok(backend)
     }
 
     /// Configures everything related to forking based on the passed `eth_rpc_url`:
@@ -939,6 +1062,15 @@ impl NodeConfig {
                 .expect("Failed to establish provider to fork url"),
         );
 
+        if let Some(tx_hash) = self.fork_transaction_hash {
+            let (block_number, transactions) = fetch_fork_transactions(&provider, tx_hash)
+                .await
+                .expect("Failed to fetch fork transaction");
+            // fork off the parent block, the preceding transactions are replayed on top of it
+            self.fork_block_number = Some(block_number.saturating_sub(1));
+            self.fork_transactions = transactions;
+        }
+
         let (fork_block_number, fork_chain_id) = if let Some(fork_block_number) =
             self.fork_block_number
         {
@@ -1043,28 +1175,32 @@ latest block number: {latest_block}"
 
         let block_hash = block.header.hash.unwrap_or_default();
 
-        let chain_id = if let Some(chain_id) = self.chain_id {
-            chain_id
+        // the chain ID of the forked chain, used for the requests to the fork and its cache
+        let fork_chain_id = if let Some(fork_chain_id) = fork_chain_id {
+            fork_chain_id.to::<u64>()
         } else {
-            let chain_id = if let Some(fork_chain_id) = fork_chain_id {
-                fork_chain_id.to::<u64>()
-            } else {
-                provider.get_chain_id().await.unwrap().to::<u64>()
-            };
-
-            // need to update the dev signers and env with the chain id
-            self.set_chain_id(Some(chain_id));
-            env.cfg.chain_id = chain_id;
-            env.tx.chain_id = chain_id.into();
-            chain_id
+            provider.get_chain_id().await.expect("Failed to fetch network chain ID").to::<u64>()
         };
-        let override_chain_id = self.chain_id;
 
-        let meta = BlockchainDbMeta::new(env.clone(), eth_rpc_url.clone());
+        // an explicit `--chain-id` is the chain ID of the node, i.e. the one returned by
+        // `eth_chainId`, expected in transactions and returned by the `CHAINID` opcode
+        let override_chain_id = self.chain_id;
+        let chain_id = override_chain_id.unwrap_or(fork_chain_id);
+
+        // need to update the dev signers and env with the chain id
+        self.set_chain_id(Some(chain_id));
+        env.cfg.chain_id = chain_id;
+        env.tx.chain_id = chain_id.into();
+
+        // the cached data belongs to the forked chain
+        let mut meta_env = env.clone();
+        meta_env.cfg.chain_id = fork_chain_id;
+        let meta = BlockchainDbMeta::new(meta_env, eth_rpc_url.clone());
+        let block_cache_path = self.block_cache_path(fork_chain_id, fork_block_number);
         let block_chain_db = if self.fork_chain_id.is_some() {
-            BlockchainDb::new_skip_check(meta, self.block_cache_path(fork_block_number))
+            BlockchainDb::new_skip_check(meta, block_cache_path)
         } else {
-            BlockchainDb::new(meta, self.block_cache_path(fork_block_number))
+            BlockchainDb::new(meta, block_cache_path)
         };
 
         // This will spawn the background thread that will use the provider to fetch
@@ -1080,7 +1216,7 @@ latest block number: {latest_block}"
             block_number: fork_block_number,
             block_hash,
             provider,
-            chain_id,
+            chain_id: fork_chain_id,
             override_chain_id,
             timestamp: block.header.timestamp.to::<u64>(),
             base_fee: block.header.base_fee_per_gas,
@@ -1219,6 +1355,40 @@ async fn find_latest_fork_block<P: TempProvider>(provider: P) -> Result<u64, Tra
     Ok(num)
 }
 
+/// Fetches the block number of the given transaction and the transactions that precede it in its
+/// block.
+async fn fetch_fork_transactions<P: TempProvider>(
+    provider: P,
+    tx_hash: B256,
+) -> eyre::Result<(u64, Vec<TypedTransaction>)> {
+    let tx = provider.get_transaction_by_hash(tx_hash).await?;
+    let block_number = tx
+        .block_number
+        .ok_or_else(|| eyre::eyre!("transaction {tx_hash} is still pending"))?
+        .to::<u64>();
+    let index = tx.transaction_index.unwrap_or_default().to::<usize>();
+
+    let block = provider
+        .get_block(BlockNumberOrTag::Number(block_number).into(), false)
+        .await?
+        .ok_or_else(|| eyre::eyre!("block {block_number} not found"))?;
+    let hashes = match block.transactions {
+        BlockTransactions::Hashes(hashes) => hashes,
+        BlockTransactions::Full(txs) => txs.into_iter().map(|tx| tx.hash).collect(),
+        _ => vec![],
+    };
+
+    let mut transactions = Vec::with_capacity(index);
+    for hash in hashes.into_iter().take(index) {
+        let raw: Bytes = provider.raw_request("eth_getRawTransactionByHash", (hash,)).await?;
+        let tx = TypedTransaction::decode_2718(&raw)
+            .map_err(|err| eyre::eyre!("failed to decode transaction {hash}: {err}"))?;
+        transactions.push(tx);
+    }
+
+    Ok((block_number, transactions))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
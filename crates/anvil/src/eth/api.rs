@@ -8,7 +8,7 @@ use crate::{
         backend::{
             db::SerializableState,
             mem::{MIN_CREATE_GAS, MIN_TRANSACTION_GAS},
-            notifications::NewBlockNotifications,
+            notifications::{NewBlockNotifications, NodeEventNotifications},
             validate::TransactionValidator,
         },
         error::{
@@ -25,19 +25,18 @@ use crate::{
         },
         sign,
         sign::Signer,
+        util::redact_rpc_url,
     },
     filter::{EthFilter, Filters, LogsFilter},
     mem::transaction_build,
     revm::primitives::Output,
     ClientFork, LoggingManager, Miner, MiningMode, StorageInfo,
 };
-use alloy_consensus::TxLegacy;
 use alloy_dyn_abi::TypedData;
-use alloy_network::{Signed, TxKind};
+use alloy_network::TxKind;
 use alloy_primitives::{Address, Bytes, TxHash, B256, B64, U256, U64};
-use alloy_rlp::Decodable;
 use alloy_rpc_trace_types::{
-    geth::{DefaultFrame, GethDebugTracingOptions, GethDefaultTracingOptions, GethTrace},
+    geth::{GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace},
     parity::LocalizedTransactionTrace,
 };
 use alloy_rpc_types::{
@@ -53,14 +52,14 @@ use anvil_core::{
     eth::{
         block::BlockInfo,
         transaction::{
-            transaction_request_to_typed, PendingTransaction, TypedTransaction,
-            TypedTransactionRequest,
+            optimism::L1_BLOCK_ADDRESS, transaction_request_to_typed, PendingTransaction,
+            TypedTransaction, TypedTransactionRequest,
         },
         EthRequest,
     },
     types::{
-        AnvilMetadata, EvmMineOptions, ForkedNetwork, Forking, Index, NodeEnvironment,
-        NodeForkConfig, NodeInfo, Work,
+        AnvilMetadata, BlockTraceResult, EvmMineOptions, ForkedNetwork, Forking, Index,
+        L1BlockValues, NodeEnvironment, NodeForkConfig, NodeInfo, Work,
     },
 };
 use anvil_rpc::{error::RpcError, response::ResponseResult};
@@ -71,7 +70,7 @@ use foundry_evm::{
     revm::{
         db::DatabaseRef,
         interpreter::{return_ok, return_revert, InstructionResult},
-        primitives::BlockEnv,
+        primitives::{calc_blob_gasprice, calc_excess_blob_gas, BlockEnv},
     },
 };
 use futures::channel::{mpsc::Receiver, oneshot};
@@ -274,11 +273,18 @@ impl EthApi {
             EthRequest::DebugTraceCall(tx, block, opts) => {
                 self.debug_trace_call(tx, block, opts).await.to_rpc_result()
             }
+            EthRequest::DebugTraceBlockByNumber(block, opts) => {
+                self.debug_trace_block_by_number(block, opts).await.to_rpc_result()
+            }
+            EthRequest::DebugTraceBlockByHash(hash, opts) => {
+                self.debug_trace_block_by_hash(hash, opts).await.to_rpc_result()
+            }
             EthRequest::TraceTransaction(tx) => self.trace_transaction(tx).await.to_rpc_result(),
             EthRequest::TraceBlock(block) => self.trace_block(block).await.to_rpc_result(),
-            EthRequest::ImpersonateAccount(addr) => {
-                self.anvil_impersonate_account(addr).await.to_rpc_result()
-            }
+            EthRequest::ImpersonateAccount(addr, min_balance) => self
+                .anvil_impersonate_account_with_min_balance(addr, min_balance)
+                .await
+                .to_rpc_result(),
             EthRequest::StopImpersonatingAccount(addr) => {
                 self.anvil_stop_impersonating_account(addr).await.to_rpc_result()
             }
@@ -315,6 +321,9 @@ impl EthApi {
             }
             EthRequest::SetCoinbase(addr) => self.anvil_set_coinbase(addr).await.to_rpc_result(),
             EthRequest::SetChainId(id) => self.anvil_set_chain_id(id).await.to_rpc_result(),
+            EthRequest::SetL1BlockValues(values) => {
+                self.anvil_set_l1_block_values(values).await.to_rpc_result()
+            }
             EthRequest::SetLogging(log) => self.anvil_set_logging(log).await.to_rpc_result(),
             EthRequest::SetMinGasPrice(gas) => {
                 self.anvil_set_min_gas_price(gas).await.to_rpc_result()
@@ -322,6 +331,7 @@ impl EthApi {
             EthRequest::SetNextBlockBaseFeePerGas(gas) => {
                 self.anvil_set_next_block_base_fee_per_gas(gas).await.to_rpc_result()
             }
+            EthRequest::SetBaseFee(fee) => self.anvil_set_base_fee(fee).await.to_rpc_result(),
             EthRequest::DumpState(_) => self.anvil_dump_state().await.to_rpc_result(),
             EthRequest::LoadState(buf) => self.anvil_load_state(buf).await.to_rpc_result(),
             EthRequest::NodeInfo(_) => self.anvil_node_info().await.to_rpc_result(),
@@ -890,6 +900,7 @@ impl EthApi {
             let bypass_signature = self.backend.cheats().bypass_signature();
             let transaction = sign::build_typed_transaction(request, bypass_signature)?;
             self.ensure_typed_transaction_supported(&transaction)?;
+            self.ensure_impersonated_funds(from, &transaction).await?;
             trace!(target : "node", ?from, "eth_sendTransaction: impersonating");
             PendingTransaction::with_impersonated(transaction, from)
         } else {
@@ -912,30 +923,15 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_sendRawTransaction`
     pub async fn send_raw_transaction(&self, tx: Bytes) -> Result<TxHash> {
         node_info!("eth_sendRawTransaction");
-        let mut data = tx.as_ref();
+        let data = tx.as_ref();
         if data.is_empty() {
             return Err(BlockchainError::EmptyRawTransactionData);
         }
-        let transaction = if data[0] > 0x7f {
-            // legacy transaction
-            match Signed::<TxLegacy>::decode(&mut data) {
-                Ok(transaction) => TypedTransaction::Legacy(transaction),
-                Err(_) => return Err(BlockchainError::FailedToDecodeSignedTransaction),
-            }
-        } else {
-            // the [TypedTransaction] requires a valid rlp input,
-            // but EIP-1559 prepends a version byte, so we need to encode the data first to get a
-            // valid rlp and then rlp decode impl of `TypedTransaction` will remove and check the
-            // version byte
-            let extend = alloy_rlp::encode(data);
-            let tx = match TypedTransaction::decode(&mut &extend[..]) {
-                Ok(transaction) => transaction,
-                Err(_) => return Err(BlockchainError::FailedToDecodeSignedTransaction),
-            };
-
-            self.ensure_typed_transaction_supported(&tx)?;
-            tx
+        let transaction = match TypedTransaction::decode_2718(data) {
+            Ok(transaction) => transaction,
+            Err(_) => return Err(BlockchainError::FailedToDecodeSignedTransaction),
         };
+        self.ensure_typed_transaction_supported(&transaction)?;
         let pending_transaction = PendingTransaction::new(transaction)?;
 
         // pre-validate
@@ -1087,20 +1083,7 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_getTransactionByHash`
     pub async fn transaction_by_hash(&self, hash: B256) -> Result<Option<Transaction>> {
         node_info!("eth_getTransactionByHash");
-        let mut tx = self.pool.get_transaction(hash).map(|pending| {
-            let from = *pending.sender();
-            let mut tx = transaction_build(
-                Some(*pending.hash()),
-                pending.transaction,
-                None,
-                None,
-                Some(self.backend.base_fee()),
-            );
-            // we set the from field here explicitly to the set sender of the pending transaction,
-            // in case the transaction is impersonated.
-            tx.from = from;
-            tx
-        });
+        let mut tx = self.storage_info().transaction(&self.pool, hash);
         if tx.is_none() {
             tx = self.backend.transaction_by_hash(hash).await?
         }
@@ -1270,11 +1253,22 @@ impl EthApi {
         }
 
         const MAX_BLOCK_COUNT: u64 = 1024u64;
-        let block_count = block_count.to::<u64>().max(MAX_BLOCK_COUNT);
+        let block_count = block_count.saturating_to::<u64>().min(MAX_BLOCK_COUNT);
+
+        if number > current {
+            return Err(FeeHistoryError::BlockBeyondHead { requested: number, head: current }.into())
+        }
+
+        // percentiles must be in [0, 100] and monotonically increasing
+        if reward_percentiles.iter().any(|p| !(0.0..=100.0).contains(p)) ||
+            reward_percentiles.windows(2).any(|w| w[0] > w[1])
+        {
+            return Err(FeeHistoryError::InvalidRewardPercentiles(reward_percentiles).into())
+        }
 
         // highest and lowest block num in the requested range
         let highest = number;
-        let lowest = highest.saturating_sub(block_count.saturating_sub(1));
+        let mut lowest = highest.saturating_sub(block_count.saturating_sub(1));
 
         // only support ranges that are in cache range
         if lowest < self.backend.best_number().saturating_sub(self.fee_history_limit) {
@@ -1289,45 +1283,77 @@ impl EthApi {
             base_fee_per_blob_gas: Default::default(),
             blob_gas_used_ratio: Default::default(),
         };
+        if block_count == 0 {
+            return Ok(response)
+        }
+
         let mut rewards = Vec::new();
+        let mut base_fee_per_blob_gas = Vec::new();
+        let mut blob_gas_used_ratio = Vec::new();
+        let next_base_fee;
+        let next_base_fee_per_blob_gas;
 
         {
             let fee_history = self.fee_history_cache.lock();
 
+            // if more blocks are requested than are available, the range starts at the oldest
+            // block we know of, e.g. genesis or the fork block
+            if let Some(oldest) = fee_history.keys().next() {
+                lowest = lowest.max(*oldest);
+                response.oldest_block = U256::from(lowest);
+            }
+
+            // the base fee of the block after the range is known if it was already mined, or if
+            // it's the pending block, and includes any overrides
+            next_base_fee = match fee_history.get(&(highest + 1)) {
+                Some(block) => Some(U256::from(block.base_fee)),
+                None => (highest == self.backend.best_number()).then(|| self.backend.base_fee()),
+            };
+            next_base_fee_per_blob_gas = match fee_history.get(&(highest + 1)) {
+                Some(block) => block.base_fee_per_blob_gas,
+                None => self.backend.get_block(highest).and_then(|block| {
+                    let excess_blob_gas = block.header.excess_blob_gas?;
+                    let blob_gas_used = block.header.blob_gas_used.unwrap_or_default();
+                    Some(calc_blob_gasprice(calc_excess_blob_gas(excess_blob_gas, blob_gas_used)))
+                }),
+            };
+
             // iter over the requested block range
             for n in lowest..=highest {
                 // <https://eips.ethereum.org/EIPS/eip-1559>
                 if let Some(block) = fee_history.get(&n) {
                     response.base_fee_per_gas.push(U256::from(block.base_fee));
                     response.gas_used_ratio.push(block.gas_used_ratio);
+                    base_fee_per_blob_gas
+                        .push(block.base_fee_per_blob_gas.map(U256::from).unwrap_or_default());
+                    blob_gas_used_ratio.push(block.blob_gas_used_ratio);
 
                     // requested percentiles
                     if !reward_percentiles.is_empty() {
-                        let mut block_rewards = Vec::new();
-                        let resolution_per_percentile: f64 = 2.0;
-                        for p in &reward_percentiles {
-                            let p = p.clamp(0.0, 100.0);
-                            let index = ((p.round() / 2f64) * 2f64) * resolution_per_percentile;
-                            let reward = if let Some(r) = block.rewards.get(index as usize) {
-                                U256::from(*r)
-                            } else {
-                                U256::ZERO
-                            };
-                            block_rewards.push(reward);
-                        }
-                        rewards.push(block_rewards);
+                        rewards.push(block.rewards(&reward_percentiles));
                     }
                 }
             }
         }
 
+        // the blob fields are only included once the range contains post Cancun blocks
+        if base_fee_per_blob_gas.iter().any(|fee| !fee.is_zero()) {
+            base_fee_per_blob_gas
+                .push(next_base_fee_per_blob_gas.map(U256::from).unwrap_or_default());
+            response.base_fee_per_blob_gas = base_fee_per_blob_gas;
+            response.blob_gas_used_ratio = blob_gas_used_ratio;
+        }
+
         response.reward = Some(rewards);
 
         // calculate next base fee
         // The spec states that `base_fee_per_gas` "[..] includes the next block after the
         // newest of the returned range, because this value can be derived from the
         // newest block"
-        if let (Some(last_gas_used), Some(last_fee_per_gas)) =
+        if let Some(next_base_fee) = next_base_fee.filter(|_| !response.base_fee_per_gas.is_empty())
+        {
+            response.base_fee_per_gas.push(next_base_fee);
+        } else if let (Some(last_gas_used), Some(last_fee_per_gas)) =
             (response.gas_used_ratio.last(), response.base_fee_per_gas.last())
         {
             let elasticity = self.backend.elasticity();
@@ -1446,8 +1472,8 @@ impl EthApi {
         &self,
         request: TransactionRequest,
         block_number: Option<BlockId>,
-        opts: GethDefaultTracingOptions,
-    ) -> Result<DefaultFrame> {
+        opts: GethDebugTracingCallOptions,
+    ) -> Result<GethTrace> {
         node_info!("debug_traceCall");
         let block_request = self.block_request(block_number).await?;
         let fees = FeeDetails::new(
@@ -1460,6 +1486,30 @@ impl EthApi {
         self.backend.call_with_tracing(request, fees, Some(block_request), opts).await
     }
 
+    /// Returns traces for all transactions of the block for geth's tracing endpoint
+    ///
+    /// Handler for RPC call: `debug_traceBlockByNumber`
+    pub async fn debug_trace_block_by_number(
+        &self,
+        block: BlockNumber,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<BlockTraceResult>> {
+        node_info!("debug_traceBlockByNumber");
+        self.backend.debug_trace_block(block.into(), opts).await
+    }
+
+    /// Returns traces for all transactions of the block for geth's tracing endpoint
+    ///
+    /// Handler for RPC call: `debug_traceBlockByHash`
+    pub async fn debug_trace_block_by_hash(
+        &self,
+        hash: B256,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<BlockTraceResult>> {
+        node_info!("debug_traceBlockByHash");
+        self.backend.debug_trace_block(hash.into(), opts).await
+    }
+
     /// Returns traces for the transaction hash via parity's tracing endpoint
     ///
     /// Handler for RPC call: `trace_transaction`
@@ -1482,10 +1532,27 @@ impl EthApi {
 impl EthApi {
     /// Send transactions impersonating specific account and contract addresses.
     ///
-    /// Handler for ETH RPC call: `anvil_impersonateAccount`
+    /// See also [Self::anvil_impersonate_account_with_min_balance]
     pub async fn anvil_impersonate_account(&self, address: Address) -> Result<()> {
+        self.anvil_impersonate_account_with_min_balance(address, None).await
+    }
+
+    /// Send transactions impersonating specific account and contract addresses, topping up the
+    /// account's balance to `min_balance` whenever it can't pay for a transaction it sends.
+    ///
+    /// Funds added this way are kept when the impersonation is stopped.
+    ///
+    /// Handler for ETH RPC call: `anvil_impersonateAccount`
+    pub async fn anvil_impersonate_account_with_min_balance(
+        &self,
+        address: Address,
+        min_balance: Option<U256>,
+    ) -> Result<()> {
         node_info!("anvil_impersonateAccount");
         self.backend.impersonate(address).await?;
+        if let Some(min_balance) = min_balance {
+            self.backend.cheats().set_impersonated_min_balance(address, min_balance);
+        }
         Ok(())
     }
 
@@ -1648,6 +1715,55 @@ impl EthApi {
         Ok(true)
     }
 
+    /// Sets the values of the op-stack `L1Block` predeploy, which are used to compute the L1 data
+    /// fee of transactions. Fields that are not set are left unchanged.
+    ///
+    /// Handler for RPC call: `anvil_setL1BlockValues`
+    pub async fn anvil_set_l1_block_values(&self, values: L1BlockValues) -> Result<()> {
+        node_info!("anvil_setL1BlockValues");
+        if !self.backend.is_optimism() {
+            return Err(BlockchainError::OptimismUnsupported);
+        }
+
+        let L1BlockValues {
+            number,
+            timestamp,
+            base_fee,
+            hash,
+            sequence_number,
+            batcher_hash,
+            l1_fee_overhead,
+            l1_fee_scalar,
+        } = values;
+
+        // `number` and `timestamp` are packed into slot 0, `sequenceNumber` shares slot 3 with
+        // the ecotone fee scalars
+        let packed = [(0u64, 0usize, number), (0, 8, timestamp), (3, 0, sequence_number)];
+        for (slot, offset, value) in packed {
+            let Some(value) = value else { continue };
+            let slot = U256::from(slot);
+            let mut word = self.backend.storage_at(L1_BLOCK_ADDRESS, slot, None).await?;
+            let end = 32 - offset;
+            word[end - 8..end].copy_from_slice(&value.to::<u64>().to_be_bytes());
+            self.backend.set_storage_at(L1_BLOCK_ADDRESS, slot, word).await?;
+        }
+
+        let words = [
+            (1u64, base_fee.map(B256::from)),
+            (2, hash),
+            (4, batcher_hash),
+            (5, l1_fee_overhead.map(B256::from)),
+            (6, l1_fee_scalar.map(B256::from)),
+        ];
+        for (slot, value) in words {
+            if let Some(value) = value {
+                self.backend.set_storage_at(L1_BLOCK_ADDRESS, U256::from(slot), value).await?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Enable or disable logging.
     ///
     /// Handler for RPC call: `anvil_setLoggingEnabled`
@@ -1687,6 +1803,24 @@ impl EthApi {
         Ok(())
     }
 
+    /// Sets the base fee of all following blocks, until it is cleared by passing `None`.
+    ///
+    /// Unlike `anvil_setNextBlockBaseFeePerGas`, the base fee is not adjusted between blocks while
+    /// the override is set.
+    ///
+    /// Handler for RPC call: `anvil_setBaseFee`
+    pub async fn anvil_set_base_fee(&self, basefee: Option<U256>) -> Result<()> {
+        node_info!("anvil_setBaseFee");
+        if !self.backend.is_eip1559() {
+            return Err(RpcError::invalid_params(
+                "anvil_setBaseFee is only supported when EIP-1559 is active",
+            )
+            .into());
+        }
+        self.backend.set_base_fee_override(basefee);
+        Ok(())
+    }
+
     /// Sets the coinbase address.
     ///
     /// Handler for RPC call: `anvil_setCoinbase`
@@ -1776,8 +1910,13 @@ impl EthApi {
                 chain_id: cfg.chain_id(),
                 fork_block_number: cfg.block_number(),
                 fork_block_hash: cfg.block_hash(),
+                fork_url: redact_rpc_url(&cfg.eth_rpc_url()),
             }),
             snapshots,
+            hard_fork: self.backend.env().read().cfg.spec_id,
+            base_fee: self.backend.base_fee(),
+            gas_limit: self.backend.gas_limit(),
+            mining_mode: self.miner.mode_info(),
         })
     }
 
@@ -1985,6 +2124,7 @@ impl EthApi {
         let transaction = sign::build_typed_transaction(request, bypass_signature)?;
 
         self.ensure_typed_transaction_supported(&transaction)?;
+        self.ensure_impersonated_funds(from, &transaction).await?;
 
         let pending_transaction = PendingTransaction::with_impersonated(transaction, from);
 
@@ -2038,7 +2178,7 @@ impl EthApi {
             entry.insert(key, convert(pending));
         }
         for queued in self.pool.pending_transactions() {
-            let entry = inspect.pending.entry(*queued.pending_transaction.sender()).or_default();
+            let entry = inspect.queued.entry(*queued.pending_transaction.sender()).or_default();
             let key = queued.pending_transaction.nonce().to_string();
             entry.insert(key, convert(queued));
         }
@@ -2050,7 +2190,7 @@ impl EthApi {
     ///
     /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_content) for more details
     ///
-    /// Handler for ETH RPC call: `txpool_inspect`
+    /// Handler for ETH RPC call: `txpool_content`
     pub async fn txpool_content(&self) -> Result<TxpoolContent> {
         node_info!("txpool_content");
         let mut content = TxpoolContent::default();
@@ -2076,7 +2216,7 @@ impl EthApi {
             entry.insert(key, convert(pending));
         }
         for queued in self.pool.pending_transactions() {
-            let entry = content.pending.entry(*queued.pending_transaction.sender()).or_default();
+            let entry = content.queued.entry(*queued.pending_transaction.sender()).or_default();
             let key = queued.pending_transaction.nonce().to_string();
             entry.insert(key, convert(queued));
         }
@@ -2408,11 +2548,21 @@ impl EthApi {
         self.backend.new_block_notifications()
     }
 
+    /// Returns a new stream of the node events
+    pub fn node_event_notifications(&self) -> NodeEventNotifications {
+        self.backend.node_events().subscribe()
+    }
+
     /// Returns a new listeners for ready transactions
     pub fn new_ready_transactions(&self) -> Receiver<TxHash> {
         self.pool.add_ready_listener()
     }
 
+    /// Returns the transaction pool
+    pub fn pool(&self) -> &Arc<Pool> {
+        &self.pool
+    }
+
     /// Returns a new accessor for certain storage elements
     pub fn storage_info(&self) -> StorageInfo {
         StorageInfo::new(Arc::clone(&self.backend))
@@ -2589,6 +2739,24 @@ impl EthApi {
             TypedTransaction::Legacy(_) => Ok(()),
         }
     }
+
+    /// Tops up the balance of the impersonated `from` account to its balance floor if it can't pay
+    /// for the transaction.
+    ///
+    /// The balance is written to the current state, so `eth_getBalance` already returns the
+    /// topped up balance before the transaction is mined.
+    async fn ensure_impersonated_funds(&self, from: Address, tx: &TypedTransaction) -> Result<()> {
+        let Some(min_balance) = self.backend.cheats().impersonation_min_balance(from) else {
+            return Ok(())
+        };
+        let balance = self.backend.get_balance(from, None).await?;
+        let cost = tx.max_cost().saturating_add(tx.value());
+        if balance < cost && balance < min_balance {
+            node_info!("Topping up balance of impersonated account {from} to {min_balance}");
+            self.backend.set_balance(from, min_balance).await?;
+        }
+        Ok(())
+    }
 }
 
 fn required_marker(provided_nonce: U256, on_chain_nonce: U256, from: Address) -> Vec<TxMarker> {
@@ -1,8 +1,8 @@
 //! Support for "cheat codes" / bypass functions
 
-use alloy_primitives::{Address, Signature};
+use alloy_primitives::{Address, Signature, U256};
 use anvil_core::eth::transaction::impersonated_signature;
-use foundry_evm::hashbrown::HashSet;
+use foundry_evm::hashbrown::{HashMap, HashSet};
 use parking_lot::RwLock;
 use std::sync::Arc;
 
@@ -39,7 +39,30 @@ impl CheatsManager {
     /// Removes the account that from the impersonated set
     pub fn stop_impersonating(&self, addr: &Address) {
         trace!(target: "cheats", "Stop impersonating {:?}", addr);
-        self.state.write().impersonated_accounts.remove(addr);
+        let mut state = self.state.write();
+        state.impersonated_accounts.remove(addr);
+        state.impersonated_min_balances.remove(addr);
+    }
+
+    /// Sets the balance floor of an impersonated account, overriding the node-wide floor
+    pub fn set_impersonated_min_balance(&self, addr: Address, min_balance: U256) {
+        trace!(target: "cheats", "Balance floor of {:?} set to {}", addr, min_balance);
+        self.state.write().impersonated_min_balances.insert(addr, min_balance);
+    }
+
+    /// Sets the balance floor of all impersonated accounts that don't have their own
+    pub fn set_impersonation_min_balance(&self, min_balance: Option<U256>) {
+        self.state.write().impersonation_min_balance = min_balance;
+    }
+
+    /// Returns the balance the account is topped up to if it is impersonated and can't pay for a
+    /// transaction
+    pub fn impersonation_min_balance(&self, addr: Address) -> Option<U256> {
+        if !self.is_impersonated(addr) {
+            return None
+        }
+        let state = self.state.read();
+        state.impersonated_min_balances.get(&addr).copied().or(state.impersonation_min_balance)
     }
 
     /// Returns true if the `addr` is currently impersonated
@@ -78,6 +101,10 @@ pub struct CheatsState {
     pub bypass_signature: Signature,
     /// If set to true will make the `is_impersonated` function always return true
     pub auto_impersonate_accounts: bool,
+    /// Balance floors of individual impersonated accounts
+    pub impersonated_min_balances: HashMap<Address, U256>,
+    /// Balance floor of all impersonated accounts without their own floor
+    pub impersonation_min_balance: Option<U256>,
 }
 
 impl Default for CheatsState {
@@ -86,6 +113,8 @@ impl Default for CheatsState {
             impersonated_accounts: Default::default(),
             bypass_signature: impersonated_signature(),
             auto_impersonate_accounts: false,
+            impersonated_min_balances: Default::default(),
+            impersonation_min_balance: None,
         }
     }
 }
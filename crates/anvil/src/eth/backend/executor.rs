@@ -16,6 +16,7 @@ use anvil_core::eth::{
 use foundry_evm::{
     backend::DatabaseError,
     inspectors::{TracingInspector, TracingInspectorConfig},
+    precompiles::CustomPrecompiles,
     revm,
     revm::{
         interpreter::InstructionResult,
@@ -113,6 +114,8 @@ pub struct TransactionExecutor<'a, Db: ?Sized, Validator: TransactionValidator>
     /// Cumulative gas used by all executed transactions
     pub gas_used: U256,
     pub enable_steps_tracing: bool,
+    /// User-defined precompiles
+    pub precompiles: CustomPrecompiles,
 }
 
 impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'a, DB, Validator> {
@@ -274,7 +277,8 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
         evm.database(&mut self.db);
 
         // records all call and step traces
-        let mut inspector = Inspector::default().with_tracing();
+        let mut inspector =
+            Inspector::default().with_tracing().with_precompiles(self.precompiles.clone());
         if self.enable_steps_tracing {
             inspector = inspector.with_steps_tracing();
         }
@@ -13,6 +13,7 @@ use alloy_rpc_types::{
     Filter, Log, Transaction, TransactionReceipt,
 };
 use alloy_transport::TransportError;
+use anvil_core::types::BlockTraceResult;
 use foundry_common::provider::alloy::{ProviderBuilder, RetryProvider};
 use parking_lot::{
     lock_api::{RwLockReadGuard, RwLockWriteGuard},
@@ -62,12 +63,7 @@ impl ClientFork {
 
         if let Some(url) = url {
             self.config.write().update_url(url)?;
-            let override_chain_id = self.config.read().override_chain_id;
-            let chain_id = if let Some(chain_id) = override_chain_id {
-                chain_id
-            } else {
-                self.provider().get_chain_id().await?.to::<u64>()
-            };
+            let chain_id = self.provider().get_chain_id().await?.to::<u64>();
             self.config.write().chain_id = chain_id;
         }
 
@@ -130,10 +126,17 @@ impl ClientFork {
         self.config.read().eth_rpc_url.clone()
     }
 
+    /// Returns the chain ID of the forked chain.
     pub fn chain_id(&self) -> u64 {
         self.config.read().chain_id
     }
 
+    /// Returns the chain ID of the node, which is the forked chain's unless overridden.
+    pub fn node_chain_id(&self) -> u64 {
+        let config = self.config.read();
+        config.override_chain_id.unwrap_or(config.chain_id)
+    }
+
     fn provider(&self) -> Arc<RetryProvider> {
         self.config.read().provider.clone()
     }
@@ -352,6 +355,22 @@ impl ClientFork {
         Ok(trace)
     }
 
+    /// Returns the geth traces of all transactions of the block from the remote endpoint
+    pub async fn debug_trace_block(
+        &self,
+        block: BlockId,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<BlockTraceResult>, TransportError> {
+        match block {
+            BlockId::Hash(hash) => {
+                self.provider().raw_request("debug_traceBlockByHash", (hash.block_hash, opts)).await
+            }
+            BlockId::Number(number) => {
+                self.provider().raw_request("debug_traceBlockByNumber", (number, opts)).await
+            }
+        }
+    }
+
     pub async fn trace_block(&self, number: u64) -> Result<Vec<Trace>, TransportError> {
         if let Some(traces) = self.storage_read().block_traces.get(&number).cloned() {
             return Ok(traces);
@@ -565,7 +584,9 @@ pub struct ClientForkConfig {
     pub block_hash: B256,
     // TODO make provider agnostic
     pub provider: Arc<RetryProvider>,
+    /// The chain ID of the forked chain
     pub chain_id: u64,
+    /// The chain ID of the node set with `--chain-id`, overriding the forked chain's
     pub override_chain_id: Option<u64>,
     /// The timestamp for the forked block
     pub timestamp: u64,
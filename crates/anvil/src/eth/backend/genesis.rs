@@ -1,7 +1,10 @@
 //! Genesis settings
 
-use crate::eth::backend::db::{Db, MaybeHashDatabase};
-use alloy_genesis::{Genesis, GenesisAccount};
+use crate::{
+    eth::backend::db::{Db, MaybeHashDatabase},
+    Hardfork,
+};
+use alloy_genesis::{ChainConfig, Genesis, GenesisAccount};
 use alloy_primitives::{Address, B256, U256};
 use foundry_evm::{
     backend::{DatabaseError, DatabaseResult, StateSnapshot},
@@ -30,6 +33,8 @@ pub struct GenesisConfig {
     pub fork_genesis_account_infos: Arc<Mutex<Vec<AccountInfo>>>,
     /// The `genesis.json` if provided
     pub genesis_init: Option<Genesis>,
+    /// The hardfork activations of the `genesis.json` chain config, if they should be followed
+    pub hardfork_schedule: Option<HardforkSchedule>,
 }
 
 // === impl GenesisConfig ===
@@ -94,6 +99,88 @@ impl GenesisConfig {
     }
 }
 
+/// The hardfork activations of a `genesis.json` chain config.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HardforkSchedule {
+    /// Block activated hardforks, in activation order
+    blocks: Vec<(u64, Hardfork)>,
+    /// Timestamp activated hardforks, in activation order
+    timestamps: Vec<(u64, Hardfork)>,
+}
+
+// === impl HardforkSchedule ===
+
+impl HardforkSchedule {
+    /// Collects the hardfork activations of the chain config.
+    ///
+    /// Returns an error naming the offending field if the activations are out of order.
+    pub fn from_chain_config(config: &ChainConfig) -> Result<Self, String> {
+        let blocks = [
+            ("homesteadBlock", config.homestead_block, Hardfork::Homestead),
+            ("eip150Block", config.eip150_block, Hardfork::Tangerine),
+            ("eip158Block", config.eip158_block, Hardfork::SpuriousDragon),
+            ("byzantiumBlock", config.byzantium_block, Hardfork::Byzantium),
+            ("constantinopleBlock", config.constantinople_block, Hardfork::Constantinople),
+            ("petersburgBlock", config.petersburg_block, Hardfork::Petersburg),
+            ("istanbulBlock", config.istanbul_block, Hardfork::Istanbul),
+            ("muirGlacierBlock", config.muir_glacier_block, Hardfork::Muirglacier),
+            ("berlinBlock", config.berlin_block, Hardfork::Berlin),
+            ("londonBlock", config.london_block, Hardfork::London),
+            ("arrowGlacierBlock", config.arrow_glacier_block, Hardfork::ArrowGlacier),
+            ("grayGlacierBlock", config.gray_glacier_block, Hardfork::GrayGlacier),
+            ("mergeNetsplitBlock", config.merge_netsplit_block, Hardfork::Paris),
+        ];
+        let timestamps = [
+            ("shanghaiTime", config.shanghai_time, Hardfork::Shanghai),
+            ("cancunTime", config.cancun_time, Hardfork::Cancun),
+        ];
+
+        let mut schedule = Self {
+            blocks: ordered_activations(&blocks)?,
+            timestamps: ordered_activations(&timestamps)?,
+        };
+        // chains that launched post merge don't necessarily set a netsplit block
+        if config.terminal_total_difficulty_passed &&
+            !schedule.blocks.iter().any(|(_, fork)| *fork == Hardfork::Paris)
+        {
+            let block = schedule.blocks.last().map(|(block, _)| *block).unwrap_or_default();
+            schedule.blocks.push((block, Hardfork::Paris));
+        }
+        Ok(schedule)
+    }
+
+    /// Returns `true` if the chain config doesn't activate any hardforks
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.timestamps.is_empty()
+    }
+
+    /// Returns the hardfork that is active at the given block number and timestamp
+    pub fn hardfork_at(&self, number: u64, timestamp: u64) -> Hardfork {
+        let by_time = self.timestamps.iter().rev().find(|(time, _)| *time <= timestamp);
+        let by_block = self.blocks.iter().rev().find(|(block, _)| *block <= number);
+        by_time.or(by_block).map(|(_, fork)| *fork).unwrap_or(Hardfork::Frontier)
+    }
+}
+
+/// Returns the configured activations, ensuring they are not activated before a preceding one.
+fn ordered_activations(
+    activations: &[(&str, Option<u64>, Hardfork)],
+) -> Result<Vec<(u64, Hardfork)>, String> {
+    let mut ordered: Vec<(&str, u64, Hardfork)> = Vec::new();
+    for (name, activation, fork) in activations {
+        let Some(activation) = *activation else { continue };
+        if let Some((prev_name, prev_activation, _)) = ordered.last() {
+            if activation < *prev_activation {
+                return Err(format!(
+                    "`{name}` ({activation}) is activated before `{prev_name}` ({prev_activation})"
+                ))
+            }
+        }
+        ordered.push((name, activation, *fork));
+    }
+    Ok(ordered.into_iter().map(|(_, activation, fork)| (activation, fork)).collect())
+}
+
 /// A Database implementation that is at the genesis state.
 ///
 /// This is only used in forking mode where we either need to fetch the state from remote if the
@@ -151,3 +238,46 @@ impl<'a> MaybeHashDatabase for AtGenesisStateDb<'a> {
         self.db.init_from_snapshot(snapshot)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hardfork_schedule() {
+        let config: ChainConfig = serde_json::from_str(
+            r#"{
+                "chainId": 1337,
+                "homesteadBlock": 0,
+                "eip150Block": 0,
+                "eip155Block": 0,
+                "eip158Block": 0,
+                "byzantiumBlock": 0,
+                "constantinopleBlock": 0,
+                "petersburgBlock": 0,
+                "istanbulBlock": 0,
+                "berlinBlock": 0,
+                "londonBlock": 10,
+                "mergeNetsplitBlock": 20,
+                "terminalTotalDifficulty": 0,
+                "terminalTotalDifficultyPassed": true,
+                "shanghaiTime": 1000
+            }"#,
+        )
+        .unwrap();
+        let schedule = HardforkSchedule::from_chain_config(&config).unwrap();
+        assert_eq!(schedule.hardfork_at(0, 0), Hardfork::Berlin);
+        assert_eq!(schedule.hardfork_at(10, 0), Hardfork::London);
+        assert_eq!(schedule.hardfork_at(25, 999), Hardfork::Paris);
+        assert_eq!(schedule.hardfork_at(25, 1000), Hardfork::Shanghai);
+    }
+
+    #[test]
+    fn hardfork_schedule_out_of_order() {
+        let config: ChainConfig =
+            serde_json::from_str(r#"{"chainId": 1337, "berlinBlock": 10, "londonBlock": 5}"#)
+                .unwrap();
+        let err = HardforkSchedule::from_chain_config(&config).unwrap_err();
+        assert!(err.contains("londonBlock"), "{err}");
+    }
+}
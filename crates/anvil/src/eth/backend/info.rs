@@ -1,8 +1,11 @@
 //! Handler that can get current storage related data
 
-use crate::mem::Backend;
+use crate::{
+    eth::pool::Pool,
+    mem::{transaction_build, Backend},
+};
 use alloy_primitives::B256;
-use alloy_rpc_types::Block as AlloyBlock;
+use alloy_rpc_types::{Block as AlloyBlock, Transaction as AlloyTransaction};
 use anvil_core::eth::{block::Block, transaction::TypedReceipt};
 use std::{fmt, sync::Arc};
 
@@ -48,6 +51,26 @@ impl StorageInfo {
         let block = self.block(hash)?;
         Some(self.backend.convert_block(block))
     }
+
+    /// Returns the transaction with the given hash in the format of the ethereum API, if it's
+    /// pending in the `pool` or was mined
+    pub fn transaction(&self, pool: &Pool, hash: B256) -> Option<AlloyTransaction> {
+        let Some(pending) = pool.get_transaction(hash) else {
+            return self.backend.mined_transaction_by_hash(hash)
+        };
+        let from = *pending.sender();
+        let mut tx = transaction_build(
+            Some(*pending.hash()),
+            pending.transaction,
+            None,
+            None,
+            Some(self.backend.base_fee()),
+        );
+        // we set the from field here explicitly to the set sender of the pending transaction,
+        // in case the transaction is impersonated.
+        tx.from = from;
+        Some(tx)
+    }
 }
 
 impl fmt::Debug for StorageInfo {
@@ -6,6 +6,7 @@ use foundry_evm::{
     call_inspectors,
     decode::decode_console_logs,
     inspectors::{LogCollector, TracingInspector},
+    precompiles::CustomPrecompiles,
     revm,
     revm::{
         interpreter::{CallInputs, CreateInputs, Gas, InstructionResult, Interpreter},
@@ -21,6 +22,8 @@ pub struct Inspector {
     pub tracer: Option<TracingInspector>,
     /// collects all `console.sol` logs
     pub log_collector: LogCollector,
+    /// user-defined precompiles that calls are dispatched to
+    pub precompiles: CustomPrecompiles,
 }
 
 // === impl Inspector ===
@@ -39,6 +42,12 @@ impl Inspector {
         self
     }
 
+    /// Configures the user-defined precompiles
+    pub fn with_precompiles(mut self, precompiles: CustomPrecompiles) -> Self {
+        self.precompiles = precompiles;
+        self
+    }
+
     /// Enables steps recording for `Tracer`.
     pub fn with_steps_tracing(mut self) -> Self {
         self.tracer = Some(TracingInspector::new(TracingInspectorConfig::all()));
@@ -91,6 +100,10 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
             inspector.call(data, call);
         });
 
+        if let Some(precompile) = self.precompiles.get(&call.contract) {
+            return precompile.call(&call.input, call.gas_limit);
+        }
+
         (InstructionResult::Continue, Gas::new(call.gas_limit), Bytes::new())
     }
 
@@ -9,7 +9,10 @@ use crate::{
             fork::ClientFork,
             genesis::GenesisConfig,
             mem::storage::MinedTransactionReceipt,
-            notifications::{NewBlockNotification, NewBlockNotifications},
+            notifications::{
+                MinedTransactionEvent, NewBlockNotification, NewBlockNotifications, NodeEvent,
+                NodeEvents,
+            },
             time::{utc_from_secs, TimeManager},
             validate::TransactionValidator,
         },
@@ -19,6 +22,7 @@ use crate::{
         pool::transactions::PoolTransaction,
         util::get_precompiles_for,
     },
+    filter::log_matches,
     mem::{
         inspector::Inspector,
         storage::{BlockchainStorage, InMemoryBlockStates, MinedBlockOutcome},
@@ -34,14 +38,17 @@ use alloy_network::Sealable;
 use alloy_primitives::{keccak256, Address, Bytes, TxHash, B256, B64, U128, U256, U64, U8};
 use alloy_rlp::Decodable;
 use alloy_rpc_trace_types::{
-    geth::{DefaultFrame, GethDebugTracingOptions, GethDefaultTracingOptions, GethTrace},
+    geth::{
+        GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingCallOptions,
+        GethDebugTracingOptions, GethTrace,
+    },
     parity::LocalizedTransactionTrace,
 };
 use alloy_rpc_types::{
     request::TransactionRequest, state::StateOverride, AccessList, Block as AlloyBlock, BlockId,
     BlockNumberOrTag as BlockNumber, EIP1186AccountProofResponse as AccountProof,
-    EIP1186StorageProof as StorageProof, Filter, FilteredParams, Header as AlloyHeader, Log,
-    Transaction, TransactionReceipt,
+    EIP1186StorageProof as StorageProof, Filter, Header as AlloyHeader, Log, Transaction,
+    TransactionReceipt,
 };
 use anvil_core::{
     eth::{
@@ -54,7 +61,7 @@ use anvil_core::{
         trie::RefTrieDB,
         utils::{alloy_to_revm_access_list, meets_eip155},
     },
-    types::{Forking, Index},
+    types::{BlockTraceResult, Forking, Index},
 };
 use anvil_rpc::error::RpcError;
 use flate2::{read::GzDecoder, write::GzEncoder, Compression};
@@ -64,6 +71,7 @@ use foundry_evm::{
     constants::DEFAULT_CREATE2_DEPLOYER_RUNTIME_CODE,
     decode::RevertDecoder,
     inspectors::AccessListTracer,
+    precompiles::CustomPrecompiles,
     revm::{
         self,
         db::CacheDB,
@@ -72,6 +80,7 @@ use foundry_evm::{
             BlockEnv, CreateScheme, EVMError, Env, ExecutionResult, InvalidHeader, Output, SpecId,
             TransactTo, TxEnv, KECCAK_EMPTY,
         },
+        DatabaseCommit,
     },
     traces::{TracingInspector, TracingInspectorConfig},
     utils::{eval_to_instruction_result, halt_to_instruction_result},
@@ -160,6 +169,8 @@ pub struct Backend {
     genesis: GenesisConfig,
     /// listeners for new blocks that get notified when a new block was imported
     new_block_listeners: Arc<Mutex<Vec<UnboundedSender<NewBlockNotification>>>>,
+    /// emits the node events, to the log and to `anvil_subscribe` subscribers
+    node_events: NodeEvents,
     /// keeps track of active snapshots at a specific block
     active_snapshots: Arc<Mutex<HashMap<U256, (u64, B256)>>>,
     enable_steps_tracing: bool,
@@ -168,6 +179,8 @@ pub struct Backend {
     /// max number of blocks with transactions in memory
     transaction_block_keeper: Option<usize>,
     node_config: Arc<AsyncRwLock<NodeConfig>>,
+    /// User-defined precompiles, dispatched to instead of executing the account's code
+    custom_precompiles: CustomPrecompiles,
 }
 
 impl Backend {
@@ -184,6 +197,7 @@ impl Backend {
         transaction_block_keeper: Option<usize>,
         automine_block_time: Option<Duration>,
         node_config: Arc<AsyncRwLock<NodeConfig>>,
+        custom_precompiles: CustomPrecompiles,
     ) -> Self {
         // if this is a fork then adjust the blockchain storage
         let blockchain = if let Some(fork) = fork.read().as_ref() {
@@ -194,6 +208,7 @@ impl Backend {
                 &env.read(),
                 fees.is_eip1559().then(|| fees.base_fee()),
                 genesis.timestamp,
+                genesis.genesis_init.as_ref().map(|g| g.extra_data.clone()).unwrap_or_default(),
             )
         };
 
@@ -214,6 +229,7 @@ impl Backend {
             Default::default()
         };
 
+        let node_events = NodeEvents::new(node_config.read().await.json_logs);
         let backend = Self {
             db,
             blockchain,
@@ -223,6 +239,7 @@ impl Backend {
             time: TimeManager::new(start_timestamp),
             cheats: Default::default(),
             new_block_listeners: Default::default(),
+            node_events,
             fees,
             genesis,
             active_snapshots: Arc::new(Mutex::new(Default::default())),
@@ -230,6 +247,7 @@ impl Backend {
             prune_state_history_config,
             transaction_block_keeper,
             node_config,
+            custom_precompiles,
         };
 
         if let Some(interval_block_time) = automine_block_time {
@@ -315,13 +333,18 @@ impl Backend {
         // Ensure EIP-3607 is disabled
         let mut env = self.env.write();
         env.cfg.disable_eip3607 = true;
-        Ok(self.cheats.impersonate(addr))
+        let impersonated = self.cheats.impersonate(addr);
+        self.node_events.emit(NodeEvent::ImpersonationStarted { address: addr });
+        Ok(impersonated)
     }
 
     /// Removes the account that from the impersonated set
     ///
     /// If the impersonated `addr` is a contract then we also reset the code here
     pub async fn stop_impersonating(&self, addr: Address) -> DatabaseResult<()> {
+        if self.cheats.impersonated_accounts().contains(&addr) {
+            self.node_events.emit(NodeEvent::ImpersonationStopped { address: addr });
+        }
         self.cheats.stop_impersonating(&addr);
         Ok(())
     }
@@ -329,6 +352,12 @@ impl Backend {
     /// If set to true will make every account impersonated
     pub async fn auto_impersonate_account(&self, enabled: bool) {
         self.cheats.set_auto_impersonate_account(enabled);
+        self.node_events.emit(NodeEvent::AutoImpersonationChanged { enabled });
+    }
+
+    /// Returns the emitter of the node events
+    pub fn node_events(&self) -> &NodeEvents {
+        &self.node_events
     }
 
     /// Returns the configured fork, if any
@@ -351,8 +380,16 @@ impl Backend {
         self.fork.read().is_some()
     }
 
+    /// Returns the addresses of all precompiles, including user-defined ones
     pub fn precompiles(&self) -> Vec<Address> {
-        get_precompiles_for(self.env.read().cfg.spec_id)
+        let mut precompiles = get_precompiles_for(self.env.read().cfg.spec_id);
+        precompiles.extend(self.custom_precompiles.addresses().copied());
+        precompiles
+    }
+
+    /// Returns the [Inspector] to use when transacting, with the user-defined precompiles applied
+    fn inspector(&self) -> Inspector {
+        Inspector::default().with_precompiles(self.custom_precompiles.clone())
     }
 
     /// Resets the fork to a fresh state
@@ -398,7 +435,7 @@ impl Backend {
             // update all settings related to the forked block
             {
                 let mut env = self.env.write();
-                env.cfg.chain_id = fork.chain_id();
+                env.cfg.chain_id = fork.node_chain_id();
 
                 env.block = BlockEnv {
                     number: rU256::from(fork_block_number),
@@ -554,6 +591,17 @@ impl Backend {
         self.env.read().cfg.spec_id
     }
 
+    /// Returns the [SpecId] that is active at the given block number and timestamp
+    ///
+    /// This only differs from the current [SpecId] if the `genesis.json` chain config schedules
+    /// hardfork activations.
+    pub fn spec_id_at(&self, number: u64, timestamp: u64) -> SpecId {
+        match &self.genesis.hardfork_schedule {
+            Some(schedule) => schedule.hardfork_at(number, timestamp).into(),
+            None => self.spec_id(),
+        }
+    }
+
     /// Returns true for post London
     pub fn is_eip1559(&self) -> bool {
         (self.spec_id() as u8) >= (SpecId::LONDON as u8)
@@ -618,6 +666,11 @@ impl Backend {
         self.fees.set_base_fee(basefee)
     }
 
+    /// Pins the basefee of all following blocks, or clears the override if `None`
+    pub fn set_base_fee_override(&self, basefee: Option<U256>) {
+        self.fees.set_base_fee_override(basefee)
+    }
+
     /// Returns the current gas price
     pub fn gas_price(&self) -> U256 {
         self.fees.gas_price()
@@ -654,6 +707,7 @@ impl Backend {
         let id = self.db.write().await.snapshot();
         trace!(target: "backend", "creating snapshot {} at {}", id, num);
         self.active_snapshots.lock().insert(id, (num, hash));
+        self.node_events.emit(NodeEvent::SnapshotTaken { id, block_number: num });
         id
     }
 
@@ -702,7 +756,9 @@ impl Backend {
                 ..Default::default()
             };
         }
-        Ok(self.db.write().await.revert(id, RevertSnapshotAction::RevertRemove))
+        let reverted = self.db.write().await.revert(id, RevertSnapshotAction::RevertRemove);
+        self.node_events.emit(NodeEvent::SnapshotReverted { id, success: reverted });
+        Ok(reverted)
     }
 
     pub fn list_snapshots(&self) -> BTreeMap<U256, (u64, B256)> {
@@ -786,7 +842,7 @@ impl Backend {
         let mut env = self.next_env();
         env.tx = tx.pending_transaction.to_revm_tx_env();
         let db = self.db.read().await;
-        let mut inspector = Inspector::default();
+        let mut inspector = self.inspector();
 
         let mut evm = revm::EVM::new();
         evm.env = env;
@@ -847,6 +903,7 @@ impl Backend {
             parent_hash: storage.best_hash,
             gas_used: U256::ZERO,
             enable_steps_tracing: self.enable_steps_tracing,
+            precompiles: self.custom_precompiles.clone(),
         };
 
         // create a new pending block
@@ -865,6 +922,33 @@ impl Backend {
         self.do_mine_block(pool_transactions).await
     }
 
+    /// Mines a block with the given transactions, in order.
+    ///
+    /// This is used to replay the transactions that precede the transaction a fork was pinned to,
+    /// see `--fork-transaction-hash`.
+    pub async fn replay_transactions(
+        &self,
+        transactions: Vec<TypedTransaction>,
+    ) -> Result<MinedBlockOutcome, BlockchainError> {
+        let pool_transactions = transactions
+            .into_iter()
+            .map(|tx| {
+                Ok(Arc::new(PoolTransaction {
+                    pending_transaction: PendingTransaction::new(tx)?,
+                    requires: vec![],
+                    provides: vec![],
+                    priority: Default::default(),
+                }))
+            })
+            .collect::<Result<Vec<_>, BlockchainError>>()?;
+
+        let outcome = self.do_mine_block(pool_transactions).await;
+        for tx in &outcome.invalid {
+            warn!(target: "backend", "failed to replay fork transaction {:?}", tx.hash());
+        }
+        Ok(outcome)
+    }
+
     async fn do_mine_block(
         &self,
         pool_transactions: Vec<Arc<PoolTransaction>>,
@@ -887,6 +971,24 @@ impl Backend {
             env.block.basefee = current_base_fee;
             env.block.timestamp = rU256::from(self.time.next_timestamp());
 
+            // follow the hardfork activations of the `genesis.json` chain config
+            if self.genesis.hardfork_schedule.is_some() {
+                let spec_id =
+                    self.spec_id_at(env.block.number.to::<u64>(), env.block.timestamp.to::<u64>());
+                if spec_id != env.cfg.spec_id {
+                    node_info!("Activating {spec_id:?} at block {}", env.block.number);
+                    let was_eip1559 = self.fees.is_eip1559();
+                    env.cfg.spec_id = spec_id;
+                    self.env.write().cfg.spec_id = spec_id;
+                    self.fees.set_spec_id(spec_id);
+                    if !was_eip1559 && self.fees.is_eip1559() {
+                        // the fork block uses the initial base fee
+                        self.fees.set_base_fee(self.node_config.read().await.get_base_fee());
+                    }
+                    env.block.basefee = self.base_fee();
+                }
+            }
+
             let best_hash = self.blockchain.storage.read().best_hash;
 
             if self.prune_state_history_config.is_state_history_supported() {
@@ -906,6 +1008,7 @@ impl Backend {
                     parent_hash: best_hash,
                     gas_used: U256::ZERO,
                     enable_steps_tracing: self.enable_steps_tracing,
+                    precompiles: self.custom_precompiles.clone(),
                 };
                 let executed_tx = executor.execute();
 
@@ -946,6 +1049,8 @@ impl Backend {
             storage.hashes.insert(block_number, block_hash);
 
             node_info!("");
+            let mut mined_txs = Vec::with_capacity(transactions.len());
+            let mut cumulative_gas_used = U256::ZERO;
             // insert all transactions
             for (info, receipt) in transactions.into_iter().zip(receipts) {
                 // log some tx info
@@ -954,15 +1059,28 @@ impl Backend {
                     node_info!("    Contract created: {contract:?}");
                 }
                 node_info!("    Gas used: {}", receipt.gas_used());
+                let mut revert_reason = None;
                 if !info.exit.is_ok() {
                     let r = RevertDecoder::new().decode(
                         info.out.as_ref().map(|b| &b[..]).unwrap_or_default(),
                         Some(info.exit),
                     );
                     node_info!("    Error: reverted with: {r}");
+                    revert_reason = Some(r);
                 }
                 node_info!("");
 
+                // receipts only record the cumulative gas used in the block
+                let gas_used = receipt.gas_used().saturating_sub(cumulative_gas_used);
+                cumulative_gas_used = receipt.gas_used();
+                mined_txs.push(MinedTransactionEvent {
+                    hash: info.transaction_hash,
+                    gas_used,
+                    success: info.exit.is_ok(),
+                    contract_address: info.contract_address,
+                    revert_reason,
+                });
+
                 let mined_tx = MinedTransaction {
                     info,
                     receipt,
@@ -993,6 +1111,13 @@ impl Backend {
             node_info!("    Block Number: {}", block_number);
             node_info!("    Block Hash: {:?}", block_hash);
             node_info!("    Block Time: {:?}\n", timestamp.to_rfc2822());
+            self.node_events.emit(NodeEvent::BlockMined {
+                number: block_number.to::<u64>(),
+                hash: block_hash,
+                timestamp: header.timestamp,
+                gas_used: U256::from(header.gas_used),
+                transactions: mined_txs,
+            });
 
             let outcome = MinedBlockOutcome { block_number, included, invalid };
 
@@ -1099,7 +1224,7 @@ impl Backend {
     where
         D: DatabaseRef<Error = DatabaseError>,
     {
-        let mut inspector = Inspector::default();
+        let mut inspector = self.inspector();
         let mut evm = revm::EVM::new();
         evm.env = self.build_call_env(request, fee_details, block_env);
         evm.database(state);
@@ -1141,40 +1266,91 @@ impl Backend {
         request: TransactionRequest,
         fee_details: FeeDetails,
         block_request: Option<BlockRequest>,
-        opts: GethDefaultTracingOptions,
-    ) -> Result<DefaultFrame, BlockchainError> {
-        self.with_database_at(block_request, |state, block| {
-            let mut inspector = Inspector::default().with_steps_tracing();
-            let block_number = block.number;
-            let mut evm = revm::EVM::new();
-            evm.env = self.build_call_env(request, fee_details, block);
-            evm.database(state);
-            let result_and_state =
-                match evm.inspect_ref(&mut inspector) {
-                    Ok(result_and_state) => result_and_state,
-                    Err(e) => return Err(e.into()),
-                };
-            let (exit_reason, gas_used, out, ) = match result_and_state.result {
-                ExecutionResult::Success { reason, gas_used, output, .. } => {
-                    (eval_to_instruction_result(reason), gas_used, Some(output), )
-                },
-                ExecutionResult::Revert { gas_used, output} => {
-                    (InstructionResult::Revert, gas_used, Some(Output::Call(output)))
-                },
-                ExecutionResult::Halt { reason, gas_used } => {
-                    (halt_to_instruction_result(reason), gas_used, None)
-                },
-            };
-            let res = inspector.tracer.unwrap_or(TracingInspector::new(TracingInspectorConfig::all())).into_geth_builder().geth_traces(gas_used, match &out {
-                Some(out) => out.data().clone(),
-                None => Bytes::new()
-            }, opts);
-            trace!(target: "backend", "trace call return {:?} out: {:?} gas {} on block {}", exit_reason, out, gas_used, block_number);
-            Ok(res)
+        opts: GethDebugTracingCallOptions,
+    ) -> Result<GethTrace, BlockchainError> {
+        let GethDebugTracingCallOptions { tracing_options, state_overrides, block_overrides } =
+            opts;
+        self.with_database_at(block_request, |state, mut block| {
+            if let Some(overrides) = block_overrides {
+                state::apply_block_overrides(overrides, &mut block);
+            }
+            let mut state =
+                state::apply_state_override(state_overrides.unwrap_or_default(), state)?;
+            let env = self.build_call_env(request, fee_details, block);
+            let (trace, _) = self.trace_with_env(&mut state, env, &tracing_options)?;
+            Ok(trace)
         })
         .await?
     }
 
+    /// Executes the transaction of the `env` on top of the `state` and traces it with the geth
+    /// tracer configured in `opts`.
+    ///
+    /// Returns the trace and the state changes of the transaction, which are not committed.
+    fn trace_with_env<D>(
+        &self,
+        state: &mut CacheDB<D>,
+        env: Env,
+        opts: &GethDebugTracingOptions,
+    ) -> Result<(GethTrace, State), BlockchainError>
+    where
+        D: DatabaseRef<Error = DatabaseError>,
+    {
+        let config = match opts.tracer {
+            None => TracingInspectorConfig::from_geth_config(&opts.config),
+            Some(_) => TracingInspectorConfig::all().set_steps(false),
+        };
+        let mut inspector = self.inspector();
+        inspector.tracer = Some(TracingInspector::new(config));
+
+        let result_and_state = {
+            let mut evm = revm::EVM::new();
+            evm.env = env;
+            evm.database(&mut *state);
+            evm.inspect(&mut inspector)?
+        };
+        let gas_used = result_and_state.result.gas_used();
+        let builder = inspector.tracer.take().expect("tracer is set").into_geth_builder();
+
+        let trace = match &opts.tracer {
+            None => GethTrace::Default(builder.geth_traces(
+                gas_used,
+                result_and_state.result.output().cloned().unwrap_or_default(),
+                opts.config.clone(),
+            )),
+            Some(GethDebugTracerType::BuiltInTracer(tracer)) => match tracer {
+                GethDebugBuiltInTracerType::CallTracer => {
+                    let config = opts
+                        .tracer_config
+                        .clone()
+                        .into_call_config()
+                        .map_err(|err| BlockchainError::InvalidTracerConfig(err.to_string()))?;
+                    GethTrace::CallTracer(builder.geth_call_traces(config, gas_used))
+                }
+                GethDebugBuiltInTracerType::PreStateTracer => {
+                    let config = opts
+                        .tracer_config
+                        .clone()
+                        .into_pre_state_config()
+                        .map_err(|err| BlockchainError::InvalidTracerConfig(err.to_string()))?;
+                    GethTrace::PreStateTracer(builder.geth_prestate_traces(
+                        &result_and_state,
+                        config,
+                        &*state,
+                    )?)
+                }
+                GethDebugBuiltInTracerType::NoopTracer => GethTrace::NoopTracer(Default::default()),
+                tracer => return Err(BlockchainError::UnsupportedTracer(format!("{tracer:?}"))),
+            },
+            Some(GethDebugTracerType::JsTracer(_)) => {
+                return Err(BlockchainError::UnsupportedTracer("JavaScript tracer".to_string()))
+            }
+        };
+        inspector.print_logs();
+
+        Ok((trace, result_and_state.state))
+    }
+
     pub fn build_access_list_with_state<D>(
         &self,
         state: D,
@@ -1255,7 +1431,6 @@ impl Backend {
 
     /// Returns all `Log`s mined by the node that were emitted in the `block` and match the `Filter`
     fn mined_logs_for_block(&self, filter: Filter, block: Block) -> Vec<Log> {
-        let params = FilteredParams::new(Some(filter.clone()));
         let mut all_logs = Vec::new();
         let block_hash = block.header.hash();
         let mut block_log_index = 0u32;
@@ -1285,20 +1460,7 @@ impl Backend {
                     log_index: None,
                     removed: false,
                 };
-                let mut is_match: bool = true;
-                if !filter.address.is_empty() && filter.has_topics() {
-                    if !params.filter_address(&log) || !params.filter_topics(&log) {
-                        is_match = false;
-                    }
-                } else if !filter.address.is_empty() {
-                    if !params.filter_address(&log) {
-                        is_match = false;
-                    }
-                } else if filter.has_topics() && !params.filter_topics(&log) {
-                    is_match = false;
-                }
-
-                if is_match {
+                if log_matches(&filter, &log.address, &log.topics) {
                     log.block_hash = Some(block_hash);
                     log.block_number = Some(block.header.number.to_alloy());
                     log.transaction_hash = Some(transaction_hash);
@@ -1875,8 +2037,22 @@ impl Backend {
         hash: B256,
         opts: GethDebugTracingOptions,
     ) -> Result<GethTrace, BlockchainError> {
-        if let Some(traces) = self.mined_geth_trace_transaction(hash, opts.clone()) {
-            return Ok(GethTrace::Default(traces));
+        if let Some(tx) = self.mined_transaction(hash) {
+            let block =
+                self.get_block_by_hash(tx.block_hash).ok_or(BlockchainError::BlockNotFound)?;
+            match self.trace_mined_block(block, Some(hash), opts.clone()).await {
+                Ok(traces) => {
+                    if let Some(trace) = traces.into_iter().next() {
+                        return Ok(trace.result)
+                    }
+                }
+                // the state of the parent block is no longer available, so fall back to the
+                // traces recorded when the transaction was mined
+                Err(BlockchainError::BlockOutOfRange(..)) if opts.tracer.is_none() => {
+                    return Ok(GethTrace::Default(tx.geth_trace(opts.config)))
+                }
+                Err(err) => return Err(err),
+            }
         }
 
         if let Some(fork) = self.get_fork() {
@@ -1886,12 +2062,92 @@ impl Backend {
         Ok(GethTrace::Default(Default::default()))
     }
 
-    fn mined_geth_trace_transaction(
+    /// Returns the geth traces of all transactions in the given block
+    pub async fn debug_trace_block(
         &self,
-        hash: B256,
+        id: BlockId,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<BlockTraceResult>, BlockchainError> {
+        if let Some(block) = self.get_block(id) {
+            return self.trace_mined_block(block, None, opts).await
+        }
+
+        if let Some(fork) = self.get_fork() {
+            let predates_fork = match id {
+                BlockId::Hash(_) => true,
+                BlockId::Number(number) => {
+                    fork.predates_fork_inclusive(self.convert_block_number(Some(number)))
+                }
+            };
+            if predates_fork {
+                return Ok(fork.debug_trace_block(id, opts).await?)
+            }
+        }
+
+        Err(BlockchainError::BlockNotFound)
+    }
+
+    /// Replays the transactions of the given mined block on top of the state of its parent and
+    /// traces them with the geth tracer configured in `opts`.
+    ///
+    /// If a `target` transaction is given only that one is traced, but all transactions preceding
+    /// it in the block are still executed, so it observes the same state as when it was mined.
+    async fn trace_mined_block(
+        &self,
+        block: Block,
+        target: Option<B256>,
         opts: GethDebugTracingOptions,
-    ) -> Option<DefaultFrame> {
-        self.blockchain.storage.read().transactions.get(&hash).map(|tx| tx.geth_trace(opts.config))
+    ) -> Result<Vec<BlockTraceResult>, BlockchainError> {
+        if block.transactions.is_empty() {
+            return Ok(vec![])
+        }
+        let block_env = BlockEnv {
+            number: rU256::from(block.header.number),
+            coinbase: block.header.beneficiary,
+            timestamp: rU256::from(block.header.timestamp),
+            difficulty: block.header.difficulty,
+            prevrandao: Some(block.header.mix_hash),
+            basefee: rU256::from(block.header.base_fee_per_gas.unwrap_or_default()),
+            gas_limit: rU256::from(block.header.gas_limit),
+            ..Default::default()
+        };
+        let parent = BlockRequest::Number(block.header.number.saturating_sub(1));
+
+        self.with_database_at(Some(parent), |state, _| {
+            let mut env = self.env.read().clone();
+            // replay with the rules the block was mined with
+            env.cfg.spec_id = self.spec_id_at(block.header.number, block.header.timestamp);
+            env.block = block_env;
+            if env.block.basefee.is_zero() {
+                env.cfg.disable_base_fee = true;
+            }
+
+            let mut state = CacheDB::new(state);
+            let mut traces = Vec::new();
+            for tx in block.transactions {
+                let tx = match tx.impersonated_sender {
+                    Some(sender) => PendingTransaction::with_impersonated(tx.transaction, sender),
+                    None => PendingTransaction::new(tx.transaction)?,
+                };
+                env.tx = tx.to_revm_tx_env();
+
+                if target.map_or(true, |target| target == *tx.hash()) {
+                    let (result, changes) = self.trace_with_env(&mut state, env.clone(), &opts)?;
+                    traces.push(BlockTraceResult { tx_hash: *tx.hash(), result });
+                    if target.is_some() {
+                        break
+                    }
+                    state.commit(changes);
+                } else {
+                    let mut evm = revm::EVM::new();
+                    evm.env = env.clone();
+                    evm.database(&mut state);
+                    evm.inspect_commit(&mut self.inspector())?;
+                }
+            }
+            Ok(traces)
+        })
+        .await?
     }
 
     /// Returns the traces for the given block
@@ -2158,7 +2414,7 @@ impl Backend {
         Ok(None)
     }
 
-    fn mined_transaction_by_hash(&self, hash: B256) -> Option<Transaction> {
+    pub(crate) fn mined_transaction_by_hash(&self, hash: B256) -> Option<Transaction> {
         let (info, block) = {
             let storage = self.blockchain.storage.read();
             let MinedTransaction { info, block_hash, .. } =
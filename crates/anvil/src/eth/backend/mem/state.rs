@@ -3,14 +3,14 @@
 use crate::eth::{backend::db::AsHashDB, error::BlockchainError};
 use alloy_primitives::{Address, Bytes, B256, U256};
 use alloy_rlp::Encodable;
-use alloy_rpc_types::state::StateOverride;
+use alloy_rpc_types::{state::StateOverride, BlockOverrides};
 use anvil_core::eth::trie::RefSecTrieDBMut;
 use foundry_evm::{
     backend::DatabaseError,
     hashbrown::HashMap as Map,
     revm::{
         db::{CacheDB, DatabaseRef, DbAccount},
-        primitives::{AccountInfo, Bytecode},
+        primitives::{AccountInfo, BlockEnv, Bytecode},
     },
 };
 use memory_db::HashKey;
@@ -83,6 +83,33 @@ pub fn trie_account_rlp(info: &AccountInfo, storage: &Map<U256, U256>) -> Bytes
     out.into()
 }
 
+/// Applies the given block overrides to the block environment
+pub fn apply_block_overrides(overrides: BlockOverrides, env: &mut BlockEnv) {
+    let BlockOverrides { number, difficulty, time, gas_limit, coinbase, random, base_fee, .. } =
+        overrides;
+    if let Some(number) = number {
+        env.number = number;
+    }
+    if let Some(difficulty) = difficulty {
+        env.difficulty = difficulty;
+    }
+    if let Some(time) = time {
+        env.timestamp = U256::from(time);
+    }
+    if let Some(gas_limit) = gas_limit {
+        env.gas_limit = U256::from(gas_limit);
+    }
+    if let Some(coinbase) = coinbase {
+        env.coinbase = coinbase;
+    }
+    if let Some(random) = random {
+        env.prevrandao = Some(random);
+    }
+    if let Some(base_fee) = base_fee {
+        env.basefee = base_fee;
+    }
+}
+
 /// Applies the given state overrides to the state, returning a new CacheDB state
 pub fn apply_state_override<D>(
     overrides: StateOverride,
@@ -228,10 +228,11 @@ pub struct BlockchainStorage {
 
 impl BlockchainStorage {
     /// Creates a new storage with a genesis block
-    pub fn new(env: &Env, base_fee: Option<U256>, timestamp: u64) -> Self {
+    pub fn new(env: &Env, base_fee: Option<U256>, timestamp: u64, extra_data: Bytes) -> Self {
         // create a dummy genesis block
         let partial_header = PartialHeader {
             timestamp,
+            extra_data,
             base_fee: base_fee.map(|b| b.to::<u64>()),
             gas_limit: env.block.gas_limit.to::<u64>(),
             beneficiary: env.block.coinbase,
@@ -339,8 +340,12 @@ pub struct Blockchain {
 
 impl Blockchain {
     /// Creates a new storage with a genesis block
-    pub fn new(env: &Env, base_fee: Option<U256>, timestamp: u64) -> Self {
-        Self { storage: Arc::new(RwLock::new(BlockchainStorage::new(env, base_fee, timestamp))) }
+    pub fn new(env: &Env, base_fee: Option<U256>, timestamp: u64, extra_data: Bytes) -> Self {
+        Self {
+            storage: Arc::new(RwLock::new(BlockchainStorage::new(
+                env, base_fee, timestamp, extra_data,
+            ))),
+        }
     }
 
     pub fn forked(block_number: u64, block_hash: B256, total_difficulty: U256) -> Self {
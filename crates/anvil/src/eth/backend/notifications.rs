@@ -1,8 +1,11 @@
 //! Notifications emitted from the backed
 
+use crate::logging::NODE_EVENT_LOG_TARGET;
 use alloy_consensus::Header;
-use alloy_primitives::B256;
-use futures::channel::mpsc::UnboundedReceiver;
+use alloy_primitives::{Address, TxHash, B256, U256};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use parking_lot::Mutex;
+use serde::Serialize;
 use std::sync::Arc;
 
 /// A notification that's emitted when a new block was imported
@@ -16,3 +19,149 @@ pub struct NewBlockNotification {
 
 /// Type alias for a receiver that receives [NewBlockNotification]
 pub type NewBlockNotifications = UnboundedReceiver<NewBlockNotification>;
+
+/// The version of the [NodeEvent] schema, bumped on every breaking change of the events
+pub const NODE_EVENT_VERSION: u64 = 1;
+
+/// An event of the node, emitted as NDJSON with `--json-logs` and to `anvil_subscribe("node")`
+/// subscriptions
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum NodeEvent {
+    /// The node started listening on the given addresses
+    NodeStarted { addresses: Vec<String>, chain_id: u64 },
+    /// A new block was mined
+    BlockMined {
+        number: u64,
+        hash: B256,
+        timestamp: u64,
+        gas_used: U256,
+        transactions: Vec<MinedTransactionEvent>,
+    },
+    /// A transaction was added to the pool, `ready` if it can be included in the next block
+    TransactionAdded { hash: TxHash, ready: bool },
+    /// A transaction was removed from the pool without being mined
+    TransactionDropped { hash: TxHash, reason: DropReason },
+    /// An account is now impersonated
+    ImpersonationStarted { address: Address },
+    /// An account is no longer impersonated
+    ImpersonationStopped { address: Address },
+    /// Impersonation of all accounts was enabled or disabled
+    AutoImpersonationChanged { enabled: bool },
+    /// A snapshot of the state was taken
+    SnapshotTaken { id: U256, block_number: u64 },
+    /// The state was reverted to a snapshot, `success` is false if the snapshot didn't exist
+    SnapshotReverted { id: U256, success: bool },
+}
+
+/// A transaction included in a [NodeEvent::BlockMined] event
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinedTransactionEvent {
+    pub hash: TxHash,
+    pub gas_used: U256,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<Address>,
+    /// The decoded revert reason, if the transaction reverted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+}
+
+/// Why a transaction was dropped from the pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DropReason {
+    /// Removed via `anvil_dropTransaction`
+    Removed,
+    /// Replaced by a transaction with the same nonce
+    Replaced,
+    /// Became invalid while mining a block
+    Invalid,
+}
+
+/// A [NodeEvent] with the version of its schema, as it is serialized
+#[derive(Serialize)]
+struct VersionedNodeEvent<'a> {
+    version: u64,
+    #[serde(flatten)]
+    event: &'a NodeEvent,
+}
+
+/// Type alias for a receiver that receives [NodeEvent]s
+pub type NodeEventNotifications = UnboundedReceiver<serde_json::Value>;
+
+/// Emits [NodeEvent]s to the log, if JSON logs are enabled, and to all subscribers
+#[derive(Clone, Debug, Default)]
+pub struct NodeEvents {
+    /// whether the events are logged as NDJSON
+    json_logs: bool,
+    listeners: Arc<Mutex<Vec<UnboundedSender<serde_json::Value>>>>,
+}
+
+// === impl NodeEvents ===
+
+impl NodeEvents {
+    pub fn new(json_logs: bool) -> Self {
+        Self { json_logs, listeners: Default::default() }
+    }
+
+    /// Returns true if the events replace the human readable logs
+    pub fn is_json_logs(&self) -> bool {
+        self.json_logs
+    }
+
+    /// Returns a new stream of the versioned events
+    pub fn subscribe(&self) -> NodeEventNotifications {
+        let (tx, rx) = unbounded();
+        self.listeners.lock().push(tx);
+        rx
+    }
+
+    /// Emits the event
+    pub fn emit(&self, event: NodeEvent) {
+        let mut listeners = self.listeners.lock();
+        listeners.retain(|tx| !tx.is_closed());
+        if !self.json_logs && listeners.is_empty() {
+            return;
+        }
+
+        let event =
+            serde_json::to_value(VersionedNodeEvent { version: NODE_EVENT_VERSION, event: &event })
+                .expect("can't fail;");
+        if self.json_logs {
+            info!(target: NODE_EVENT_LOG_TARGET, "{event}");
+        }
+        listeners.retain(|tx| tx.unbounded_send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_versioned_events() {
+        let event =
+            NodeEvent::TransactionDropped { hash: TxHash::ZERO, reason: DropReason::Invalid };
+        let value =
+            serde_json::to_value(VersionedNodeEvent { version: NODE_EVENT_VERSION, event: &event })
+                .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "version": 1,
+                "event": "transactionDropped",
+                "hash": TxHash::ZERO,
+                "reason": "invalid",
+            })
+        );
+
+        let events = NodeEvents::default();
+        let mut rx = events.subscribe();
+        events.emit(NodeEvent::SnapshotTaken { id: U256::from(1), block_number: 2 });
+        let value = rx.try_next().unwrap().unwrap();
+        assert_eq!(value["event"], "snapshotTaken");
+        assert_eq!(value["blockNumber"], 2);
+    }
+}
@@ -75,6 +75,10 @@ pub enum BlockchainError {
     StateOverrideError(String),
     #[error("Timestamp error: {0}")]
     TimestampError(String),
+    #[error("Unsupported tracer: {0}")]
+    UnsupportedTracer(String),
+    #[error("Invalid tracer config: {0}")]
+    InvalidTracerConfig(String),
     #[error(transparent)]
     DatabaseError(#[from] DatabaseError),
     #[error("EIP-1559 style fee params (maxFeePerGas or maxPriorityFeePerGas) received but they are not supported by the current hardfork.\n\nYou can use them by running anvil with '--hardfork london' or later.")]
@@ -83,6 +87,10 @@ pub enum BlockchainError {
     EIP2930TransactionUnsupportedAtHardfork,
     #[error("op-stack deposit tx received but is not supported.\n\nYou can use it by running anvil with '--optimism'.")]
     DepositTransactionUnsupported,
+    #[error(
+        "op-stack support is not enabled.\n\nYou can use it by running anvil with '--optimism'."
+    )]
+    OptimismUnsupported,
     #[error("Excess blob gas not set.")]
     ExcessBlobGasNotSet,
 }
@@ -126,6 +134,10 @@ pub enum PoolError {
 pub enum FeeHistoryError {
     #[error("Requested block range is out of bounds")]
     InvalidBlockRange,
+    #[error("Requested block {requested} is beyond the head block {head}")]
+    BlockBeyondHead { requested: u64, head: u64 },
+    #[error("Invalid reward percentiles {0:?}, must be in [0, 100] and monotonically increasing")]
+    InvalidRewardPercentiles(Vec<f64>),
 }
 
 #[derive(Debug)]
@@ -393,6 +405,12 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                 err @ BlockchainError::StateOverrideError(_) => {
                     RpcError::invalid_params(err.to_string())
                 }
+                err @ BlockchainError::UnsupportedTracer(_) => {
+                    RpcError::invalid_params(err.to_string())
+                }
+                err @ BlockchainError::InvalidTracerConfig(_) => {
+                    RpcError::invalid_params(err.to_string())
+                }
                 err @ BlockchainError::TimestampError(_) => {
                     RpcError::invalid_params(err.to_string())
                 }
@@ -408,6 +426,9 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                 err @ BlockchainError::DepositTransactionUnsupported => {
                     RpcError::invalid_params(err.to_string())
                 }
+                err @ BlockchainError::OptimismUnsupported => {
+                    RpcError::invalid_params(err.to_string())
+                }
                 err @ BlockchainError::ExcessBlobGasNotSet => {
                     RpcError::invalid_params(err.to_string())
                 }
@@ -4,7 +4,7 @@ use crate::eth::{
 };
 use alloy_primitives::{B256, U256};
 use anvil_core::eth::transaction::TypedTransaction;
-use foundry_evm::revm::primitives::SpecId;
+use foundry_evm::revm::primitives::{calc_blob_gasprice, SpecId, MAX_BLOB_GAS_PER_BLOCK};
 use futures::StreamExt;
 use parking_lot::{Mutex, RwLock};
 use std::{
@@ -39,7 +39,9 @@ pub fn default_elasticity() -> f64 {
 #[derive(Clone, Debug)]
 pub struct FeeManager {
     /// Hardfork identifier
-    spec_id: SpecId,
+    ///
+    /// This changes if the chain config activates a hardfork after genesis
+    spec_id: Arc<RwLock<SpecId>>,
     /// Tracks the base fee for the next block post London
     ///
     /// This value will be updated after a new block was mined
@@ -48,6 +50,10 @@ pub struct FeeManager {
     ///
     /// This will be constant value unless changed manually
     gas_price: Arc<RwLock<U256>>,
+    /// A base fee that is used for all following blocks until it is cleared
+    base_fee_override: Arc<RwLock<Option<U256>>>,
+    /// Whether the base fee is adjusted based on the gas used by the previous block
+    adjust_base_fee: bool,
     elasticity: Arc<RwLock<f64>>,
 }
 
@@ -56,20 +62,40 @@ pub struct FeeManager {
 impl FeeManager {
     pub fn new(spec_id: SpecId, base_fee: U256, gas_price: U256) -> Self {
         Self {
-            spec_id,
+            spec_id: Arc::new(RwLock::new(spec_id)),
             base_fee: Arc::new(RwLock::new(base_fee)),
             gas_price: Arc::new(RwLock::new(gas_price)),
+            base_fee_override: Default::default(),
+            adjust_base_fee: true,
             elasticity: Arc::new(RwLock::new(default_elasticity())),
         }
     }
 
+    /// Sets whether the base fee is adjusted between blocks, if disabled the base fee stays
+    /// constant regardless of how full blocks are.
+    #[must_use]
+    pub fn with_base_fee_adjustment(mut self, adjust_base_fee: bool) -> Self {
+        self.adjust_base_fee = adjust_base_fee;
+        self
+    }
+
     pub fn elasticity(&self) -> f64 {
         *self.elasticity.read()
     }
 
     /// Returns true for post London
     pub fn is_eip1559(&self) -> bool {
-        (self.spec_id as u8) >= (SpecId::LONDON as u8)
+        (*self.spec_id.read() as u8) >= (SpecId::LONDON as u8)
+    }
+
+    /// Returns true for post Cancun
+    pub fn is_eip4844(&self) -> bool {
+        (*self.spec_id.read() as u8) >= (SpecId::CANCUN as u8)
+    }
+
+    /// Sets the hardfork identifier
+    pub fn set_spec_id(&self, spec_id: SpecId) {
+        *self.spec_id.write() = spec_id;
     }
 
     /// Calculates the current gas price
@@ -114,6 +140,21 @@ impl FeeManager {
         *base = fee;
     }
 
+    /// Returns the base fee override, if any
+    pub fn base_fee_override(&self) -> Option<U256> {
+        *self.base_fee_override.read()
+    }
+
+    /// Pins the base fee of all following blocks to the given value, or clears the override if
+    /// `None`
+    pub fn set_base_fee_override(&self, fee: Option<U256>) {
+        trace!(target: "backend::fees", "updated base fee override {:?}", fee);
+        *self.base_fee_override.write() = fee;
+        if let Some(fee) = fee {
+            self.set_base_fee(fee);
+        }
+    }
+
     /// Calculates the base fee for the next block
     pub fn get_next_block_base_fee_per_gas(
         &self,
@@ -121,6 +162,12 @@ impl FeeManager {
         gas_limit: U256,
         last_fee_per_gas: U256,
     ) -> u64 {
+        if let Some(fee) = self.base_fee_override() {
+            return fee.saturating_to()
+        }
+        if !self.adjust_base_fee {
+            return last_fee_per_gas.saturating_to()
+        }
         // It's naturally impossible for base fee to be 0;
         // It means it was set by the user deliberately and therefore we treat it as a constant.
         // Therefore, we skip the base fee calculation altogether and we return 0.
@@ -206,25 +253,13 @@ impl FeeHistoryService {
 
     /// Create a new history entry for the block
     fn create_cache_entry(&self, hash: B256) -> (FeeHistoryCacheItem, Option<u64>) {
-        let elasticity = self.fees.elasticity();
-        // percentile list from 0.0 to 100.0 with a 0.5 resolution.
-        // this will create 200 percentile points
-        let reward_percentiles: Vec<f64> = {
-            let mut percentile: f64 = 0.0;
-            (0..=200)
-                .map(|_| {
-                    let val = percentile;
-                    percentile += 0.5;
-                    val
-                })
-                .collect()
-        };
-
         let mut block_number: Option<u64> = None;
-        let base_fee = self.fees.base_fee();
         let mut item = FeeHistoryCacheItem {
-            base_fee: base_fee.to::<u64>(),
+            base_fee: self.fees.base_fee().to::<u64>(),
             gas_used_ratio: 0f64,
+            base_fee_per_blob_gas: None,
+            blob_gas_used_ratio: 0f64,
+            gas_used: 0,
             rewards: Vec::new(),
         };
 
@@ -234,14 +269,26 @@ impl FeeHistoryService {
         if let (Some(block), Some(receipts)) = (current_block, current_receipts) {
             block_number = Some(block.header.number);
 
-            let gas_used = block.header.gas_used as f64;
-            let gas_limit = block.header.gas_limit as f64;
+            // the effective rewards are relative to the base fee of this block
+            let base_fee = block.header.base_fee_per_gas.unwrap_or_default();
+            item.base_fee = base_fee;
 
-            let gas_target = gas_limit / elasticity;
-            item.gas_used_ratio = gas_used / (gas_target * elasticity);
+            let gas_used = block.header.gas_used;
+            let gas_limit = block.header.gas_limit;
+            if gas_limit > 0 {
+                item.gas_used_ratio = gas_used as f64 / gas_limit as f64;
+            }
+
+            if self.fees.is_eip4844() {
+                let excess_blob_gas = block.header.excess_blob_gas.unwrap_or_default();
+                let blob_gas_used = block.header.blob_gas_used.unwrap_or_default();
+                item.base_fee_per_blob_gas = Some(calc_blob_gasprice(excess_blob_gas));
+                item.blob_gas_used_ratio = blob_gas_used as f64 / MAX_BLOB_GAS_PER_BLOCK as f64;
+            }
 
+            let base_fee = U256::from(base_fee);
             // extract useful tx info (gas_used, effective_reward)
-            let mut transactions: Vec<(u64, u64)> = receipts
+            let mut transactions: Vec<(u64, u128)> = receipts
                 .iter()
                 .enumerate()
                 .map(|(i, receipt)| {
@@ -249,15 +296,15 @@ impl FeeHistoryService {
                     let effective_reward = match block.transactions.get(i).map(|tx| &tx.transaction)
                     {
                         Some(TypedTransaction::Legacy(t)) => {
-                            U256::from(t.gas_price).saturating_sub(base_fee).to::<u64>()
+                            U256::from(t.gas_price).saturating_sub(base_fee).to::<u128>()
                         }
                         Some(TypedTransaction::EIP2930(t)) => {
-                            U256::from(t.gas_price).saturating_sub(base_fee).to::<u64>()
+                            U256::from(t.gas_price).saturating_sub(base_fee).to::<u128>()
                         }
                         Some(TypedTransaction::EIP1559(t)) => {
                             U256::from(t.max_priority_fee_per_gas)
                                 .min(U256::from(t.max_fee_per_gas).saturating_sub(base_fee))
-                                .to::<u64>()
+                                .to::<u128>()
                         }
                         Some(TypedTransaction::Deposit(_)) => 0,
                         None => 0,
@@ -270,23 +317,8 @@ impl FeeHistoryService {
             // sort by effective reward asc
             transactions.sort_by(|(_, a), (_, b)| a.cmp(b));
 
-            // calculate percentile rewards
-            item.rewards = reward_percentiles
-                .into_iter()
-                .filter_map(|p| {
-                    let target_gas = (p * gas_used / 100f64) as u64;
-                    let mut sum_gas = 0;
-                    for (gas_used, effective_reward) in transactions.iter().cloned() {
-                        sum_gas += gas_used;
-                        if target_gas <= sum_gas {
-                            return Some(effective_reward)
-                        }
-                    }
-                    None
-                })
-                .collect();
-        } else {
-            item.rewards = reward_percentiles.iter().map(|_| 0).collect();
+            item.gas_used = gas_used;
+            item.rewards = transactions;
         }
         (item, block_number)
     }
@@ -330,11 +362,46 @@ impl Future for FeeHistoryService {
 pub type FeeHistoryCache = Arc<Mutex<BTreeMap<u64, FeeHistoryCacheItem>>>;
 
 /// A single item in the whole fee history cache
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct FeeHistoryCacheItem {
     pub base_fee: u64,
     pub gas_used_ratio: f64,
-    pub rewards: Vec<u64>,
+    /// The blob base fee of the block, only set post Cancun
+    pub base_fee_per_blob_gas: Option<u128>,
+    pub blob_gas_used_ratio: f64,
+    /// Total gas used by the block
+    pub gas_used: u64,
+    /// `(gas_used, effective_reward)` of all transactions in the block, sorted by reward
+    pub rewards: Vec<(u64, u128)>,
+}
+
+// === impl FeeHistoryCacheItem ===
+
+impl FeeHistoryCacheItem {
+    /// Returns the effective priority fee at the given percentiles of the block's gas used.
+    ///
+    /// This follows geth: transactions are sorted by their reward and each percentile is the
+    /// reward of the transaction at which the cumulative gas used reaches `percentile * gas_used`.
+    /// Blocks without transactions yield zero rewards.
+    pub fn rewards(&self, percentiles: &[f64]) -> Vec<U256> {
+        if self.rewards.is_empty() {
+            return vec![U256::ZERO; percentiles.len()]
+        }
+
+        percentiles
+            .iter()
+            .map(|p| {
+                let threshold = (self.gas_used as f64 * p / 100f64) as u64;
+                let mut tx_index = 0;
+                let mut sum_gas_used = self.rewards[0].0;
+                while sum_gas_used < threshold && tx_index < self.rewards.len() - 1 {
+                    tx_index += 1;
+                    sum_gas_used += self.rewards[tx_index].0;
+                }
+                U256::from(self.rewards[tx_index].1)
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone, Default)]
@@ -2,6 +2,7 @@
 
 use crate::eth::pool::{transactions::PoolTransaction, Pool};
 use alloy_primitives::TxHash;
+use anvil_core::types::MiningModeInfo;
 use futures::{
     channel::mpsc::Receiver,
     stream::{Fuse, Stream, StreamExt},
@@ -51,6 +52,17 @@ impl Miner {
         matches!(*mode, MiningMode::FixedBlockTime(_))
     }
 
+    /// Returns a description of the current mining mode
+    pub fn mode_info(&self) -> MiningModeInfo {
+        match &*self.mode.read() {
+            MiningMode::None => MiningModeInfo::None,
+            MiningMode::Auto(_) => MiningModeInfo::Auto,
+            MiningMode::FixedBlockTime(miner) => {
+                MiningModeInfo::Interval { block_time: miner.block_time().as_secs() }
+            }
+        }
+    }
+
     /// Sets the mining mode to operate in
     pub fn set_mining_mode(&self, mode: MiningMode) {
         let new_mode = format!("{mode:?}");
@@ -160,6 +172,11 @@ impl FixedBlockTimeMiner {
         Self { interval: tokio::time::interval_at(start, duration) }
     }
 
+    /// Returns the block time this miner operates with
+    pub fn block_time(&self) -> Duration {
+        self.interval.period()
+    }
+
     fn poll(&mut self, pool: &Arc<Pool>, cx: &mut Context<'_>) -> Poll<Vec<Arc<PoolTransaction>>> {
         if self.interval.poll_tick(cx).is_ready() {
             // drain the pool
@@ -28,6 +28,7 @@
 
 use crate::{
     eth::{
+        backend::notifications::{DropReason, NodeEvent, NodeEvents},
         error::PoolError,
         pool::transactions::{
             PendingPoolTransaction, PendingTransactions, PoolTransaction, ReadyTransactions,
@@ -52,11 +53,18 @@ pub struct Pool {
     inner: RwLock<PoolInner>,
     /// listeners for new ready transactions
     transaction_listener: Mutex<Vec<Sender<TxHash>>>,
+    /// emits the pool's node events
+    node_events: NodeEvents,
 }
 
 // == impl Pool ==
 
 impl Pool {
+    /// Creates a new pool that emits its changes to the given node events
+    pub fn new(node_events: NodeEvents) -> Self {
+        Self { node_events, ..Default::default() }
+    }
+
     /// Returns an iterator that yields all transactions that are currently ready
     pub fn ready_transactions(&self) -> TransactionsIterator {
         self.inner.read().ready_transactions()
@@ -112,7 +120,16 @@ impl Pool {
     /// Adds a new transaction to the pool
     pub fn add_transaction(&self, tx: PoolTransaction) -> Result<AddedTransaction, PoolError> {
         let added = self.inner.write().add_transaction(tx)?;
+        self.node_events.emit(NodeEvent::TransactionAdded {
+            hash: *added.hash(),
+            ready: matches!(added, AddedTransaction::Ready(_)),
+        });
         if let AddedTransaction::Ready(ref ready) = added {
+            self.emit_dropped(ready.removed.iter(), DropReason::Replaced);
+            for hash in ready.discarded.iter().copied() {
+                self.node_events
+                    .emit(NodeEvent::TransactionDropped { hash, reason: DropReason::Invalid });
+            }
             self.notify_listener(ready.hash);
             // also notify promoted transactions
             for promoted in ready.promoted.iter().copied() {
@@ -138,7 +155,9 @@ impl Pool {
 
     /// Remove the given transactions from the pool
     pub fn remove_invalid(&self, tx_hashes: Vec<TxHash>) -> Vec<Arc<PoolTransaction>> {
-        self.inner.write().remove_invalid(tx_hashes)
+        let removed = self.inner.write().remove_invalid(tx_hashes);
+        self.emit_dropped(removed.iter(), DropReason::Invalid);
+        removed
     }
 
     /// Removes a single transaction from the pool
@@ -150,9 +169,13 @@ impl Pool {
         trace!(target: "txpool", "Dropping transaction: [{:?}]", tx);
         let removed = {
             let mut pool = self.inner.write();
-            pool.ready_transactions.remove_with_markers(vec![tx], None)
+            let mut removed = pool.ready_transactions.remove_with_markers(vec![tx], None);
+            // the transaction may also still be waiting for a nonce gap to be filled
+            removed.extend(pool.pending_transactions.remove(vec![tx]));
+            removed
         };
         trace!(target: "txpool", "Dropped transactions: {:?}", removed);
+        self.emit_dropped(removed.iter(), DropReason::Removed);
 
         let mut dropped = None;
         if !removed.is_empty() {
@@ -161,6 +184,17 @@ impl Pool {
         dropped
     }
 
+    /// Emits a [NodeEvent::TransactionDropped] event for every removed transaction
+    fn emit_dropped<'a>(
+        &self,
+        removed: impl IntoIterator<Item = &'a Arc<PoolTransaction>>,
+        reason: DropReason,
+    ) {
+        for tx in removed {
+            self.node_events.emit(NodeEvent::TransactionDropped { hash: tx.hash(), reason });
+        }
+    }
+
     /// notifies all listeners about the transaction
     fn notify_listener(&self, hash: TxHash) {
         let mut listener = self.transaction_listener.lock();
@@ -4,7 +4,7 @@ use crate::{
     pubsub::filter_logs,
     StorageInfo,
 };
-use alloy_primitives::TxHash;
+use alloy_primitives::{Address, TxHash, B256};
 use alloy_rpc_types::{Filter, FilteredParams, Log as AlloyLog};
 use anvil_core::eth::subscription::SubscriptionId;
 use anvil_rpc::response::ResponseResult;
@@ -181,3 +181,44 @@ impl LogsFilter {
         logs
     }
 }
+
+/// Returns whether a log emitted by `address` with the given `topics` matches the `filter`.
+///
+/// An empty address list or topic position matches any value, multiple addresses or values in a
+/// topic position match any of them, and the positions must all match.
+pub fn log_matches(filter: &Filter, address: &Address, topics: &[B256]) -> bool {
+    filter.address.matches(address) &&
+        filter.topics.iter().enumerate().all(|(i, topic)| {
+            topic.is_empty() || topics.get(i).map_or(false, |value| topic.matches(value))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_logs() {
+        let (a, b, c) = (Address::with_last_byte(1), Address::with_last_byte(2), Address::ZERO);
+        let (t0, t1, t2) = (B256::with_last_byte(1), B256::with_last_byte(2), B256::ZERO);
+
+        let filter = Filter::new().address(vec![a, b]);
+        assert!(log_matches(&filter, &a, &[]));
+        assert!(log_matches(&filter, &b, &[t0]));
+        assert!(!log_matches(&filter, &c, &[t0]));
+
+        // topics in the same position are OR-ed
+        let filter = Filter::new().event_signature(vec![t0, t1]);
+        assert!(log_matches(&filter, &c, &[t0]));
+        assert!(log_matches(&filter, &c, &[t1, t2]));
+        assert!(!log_matches(&filter, &c, &[t2, t0]));
+        assert!(!log_matches(&filter, &c, &[]));
+
+        // empty positions are wildcards, positions are AND-ed
+        let filter = Filter::new().address(a).topic2(vec![t1, t2]);
+        assert!(log_matches(&filter, &a, &[t0, t0, t2]));
+        assert!(log_matches(&filter, &a, &[t1, t2, t1]));
+        assert!(!log_matches(&filter, &a, &[t0, t0]));
+        assert!(!log_matches(&filter, &b, &[t0, t0, t2]));
+    }
+}
@@ -3,7 +3,7 @@ extern crate tracing;
 
 use crate::{
     eth::{
-        backend::{info::StorageInfo, mem},
+        backend::{info::StorageInfo, mem, notifications::NodeEvent},
         fees::{FeeHistoryService, FeeManager},
         miner::{Miner, MiningMode},
         pool::Pool,
@@ -86,15 +86,23 @@ pub mod cmd;
 /// handle.await.unwrap();
 /// # }
 /// ```
-pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
+pub async fn spawn(config: NodeConfig) -> (EthApi, NodeHandle) {
+    try_spawn(config).await.expect("failed to spawn node")
+}
+
+/// Creates the node and runs the server, like [spawn], but returns an error if the node's
+/// configuration can't be applied, e.g. if a custom precompile plugin fails to load.
+pub async fn try_spawn(mut config: NodeConfig) -> eyre::Result<(EthApi, NodeHandle)> {
     let logger = if config.enable_tracing { init_tracing() } else { Default::default() };
     logger.set_enabled(!config.silent);
+    logger.set_json(config.json_logs);
 
-    let backend = Arc::new(config.setup().await);
+    let backend = Arc::new(config.setup().await?);
 
     if config.enable_auto_impersonate {
         backend.auto_impersonate_account(true).await;
     }
+    backend.cheats().set_impersonation_min_balance(config.impersonate_min_balance);
 
     let fork = backend.get_fork();
 
@@ -110,7 +118,8 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
         ..
     } = config.clone();
 
-    let pool = Arc::new(Pool::default());
+    let node_events = backend.node_events().clone();
+    let pool = Arc::new(Pool::new(node_events.clone()));
 
     let mode = if let Some(block_time) = block_time {
         MiningMode::interval(block_time)
@@ -202,8 +211,12 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
     };
 
     handle.print(fork.as_ref());
+    node_events.emit(NodeEvent::NodeStarted {
+        addresses: handle.addresses.iter().map(ToString::to_string).collect(),
+        chain_id: handle.config.get_chain_id(),
+    });
 
-    (api, handle)
+    Ok((api, handle))
 }
 
 type IpcTask = JoinHandle<io::Result<()>>;
@@ -236,7 +249,7 @@ impl NodeHandle {
     /// Prints the launch info
     pub(crate) fn print(&self, fork: Option<&ClientFork>) {
         self.config.print(fork);
-        if !self.config.silent {
+        if !self.config.silent && !self.config.json_logs {
             println!(
                 "Listening on {}",
                 self.addresses
@@ -8,10 +8,14 @@ use tracing_subscriber::{layer::Context, Layer};
 /// The target that identifies the events intended to be logged to stdout
 pub(crate) const NODE_USER_LOG_TARGET: &str = "node::user";
 
+/// The target of the NDJSON node events that replace the user facing logs with `--json-logs`
+pub(crate) const NODE_EVENT_LOG_TARGET: &str = "node::event";
+
 /// A logger that listens for node related events and displays them.
 ///
-/// This layer is intended to be used as filter for `NODE_USER_LOG_TARGET` events that will
-/// eventually be logged to stdout
+/// This layer is intended to be used as filter for `NODE_USER_LOG_TARGET` events, or
+/// `NODE_EVENT_LOG_TARGET` events if JSON logs are enabled, that will eventually be logged to
+/// stdout
 #[derive(Clone, Debug, Default)]
 pub struct NodeLogLayer {
     state: LoggingManager,
@@ -26,21 +30,23 @@ impl NodeLogLayer {
     }
 }
 
-// use `Layer`'s filter function to globally enable/disable `NODE_USER_LOG_TARGET` events
+// use `Layer`'s filter function to globally enable/disable `NODE_USER_LOG_TARGET` and
+// `NODE_EVENT_LOG_TARGET` events
 impl<S> Layer<S> for NodeLogLayer
 where
     S: tracing::Subscriber,
 {
     fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
-        if self.state.is_enabled() && metadata.target() == NODE_USER_LOG_TARGET {
-            Interest::always()
+        // the enabled target can change at runtime, so it's decided per event
+        if metadata.target() == NODE_USER_LOG_TARGET || metadata.target() == NODE_EVENT_LOG_TARGET {
+            Interest::sometimes()
         } else {
             Interest::never()
         }
     }
 
     fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
-        self.state.is_enabled() && metadata.target() == NODE_USER_LOG_TARGET
+        self.state.is_enabled() && metadata.target() == self.state.target()
     }
 }
 
@@ -49,6 +55,8 @@ where
 pub struct LoggingManager {
     /// Whether the logger is currently enabled
     pub enabled: Arc<RwLock<bool>>,
+    /// Whether NDJSON node events are logged instead of the human readable logs
+    pub json: Arc<RwLock<bool>>,
 }
 
 // === impl LoggingManager ===
@@ -64,10 +72,30 @@ impl LoggingManager {
         let mut current = self.enabled.write();
         *current = enabled;
     }
+
+    /// Returns true if NDJSON node events are logged
+    pub fn is_json(&self) -> bool {
+        *self.json.read()
+    }
+
+    /// Updates the `json` state
+    pub fn set_json(&self, json: bool) {
+        let mut current = self.json.write();
+        *current = json;
+    }
+
+    /// Returns the target of the events that are logged
+    fn target(&self) -> &'static str {
+        if self.is_json() {
+            NODE_EVENT_LOG_TARGET
+        } else {
+            NODE_USER_LOG_TARGET
+        }
+    }
 }
 
 impl Default for LoggingManager {
     fn default() -> Self {
-        Self { enabled: Arc::new(RwLock::new(true)) }
+        Self { enabled: Arc::new(RwLock::new(true)), json: Arc::new(RwLock::new(false)) }
     }
 }
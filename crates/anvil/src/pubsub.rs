@@ -1,5 +1,10 @@
 use crate::{
-    eth::{backend::notifications::NewBlockNotifications, error::to_rpc_result},
+    eth::{
+        backend::notifications::{NewBlockNotifications, NodeEventNotifications},
+        error::to_rpc_result,
+        pool::Pool,
+    },
+    filter::log_matches,
     StorageInfo,
 };
 use alloy_consensus::ReceiptWithBloom;
@@ -12,7 +17,9 @@ use futures::{channel::mpsc::Receiver, ready, Stream, StreamExt};
 use serde::Serialize;
 use std::{
     collections::VecDeque,
+    fmt,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
@@ -63,6 +70,43 @@ impl LogsSubscription {
     }
 }
 
+/// Listens for new ready transactions and returns them in full, for `newPendingTransactions`
+/// subscriptions with the `true` param
+pub struct FullPendingTransactionsSubscription {
+    pub transactions: Receiver<TxHash>,
+    pub pool: Arc<Pool>,
+    pub storage: StorageInfo,
+    pub id: SubscriptionId,
+}
+
+// === impl FullPendingTransactionsSubscription ===
+
+impl FullPendingTransactionsSubscription {
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Option<EthSubscriptionResponse>> {
+        loop {
+            let Some(hash) = ready!(self.transactions.poll_next_unpin(cx)) else {
+                return Poll::Ready(None);
+            };
+            // the transaction is skipped if it was dropped from the pool in the meantime
+            if let Some(tx) = self.storage.transaction(&self.pool, hash) {
+                let params = EthSubscriptionParams {
+                    subscription: self.id.clone(),
+                    result: to_rpc_result(tx),
+                };
+                return Poll::Ready(Some(EthSubscriptionResponse::new(params)));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for FullPendingTransactionsSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FullPendingTransactionsSubscription")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct EthSubscriptionResponse {
     jsonrpc: Version,
@@ -76,6 +120,11 @@ impl EthSubscriptionResponse {
     pub fn new(params: EthSubscriptionParams) -> Self {
         Self { jsonrpc: Version::V2, method: "eth_subscription", params }
     }
+
+    /// Creates the response of an `anvil_subscribe` subscription
+    pub fn anvil(params: EthSubscriptionParams) -> Self {
+        Self { jsonrpc: Version::V2, method: "anvil_subscription", params }
+    }
 }
 
 /// Represents the `params` field of an `eth_subscription` event
@@ -92,6 +141,9 @@ pub enum EthSubscription {
     Logs(Box<LogsSubscription>),
     Header(NewBlockNotifications, StorageInfo, SubscriptionId),
     PendingTransactions(Receiver<TxHash>, SubscriptionId),
+    FullPendingTransactions(Box<FullPendingTransactionsSubscription>),
+    /// `anvil_subscribe("node")` subscription to the node events
+    Node(NodeEventNotifications, SubscriptionId),
 }
 
 // === impl EthSubscription ===
@@ -128,6 +180,17 @@ impl EthSubscription {
                     });
                 Poll::Ready(res)
             }
+            EthSubscription::FullPendingTransactions(listener) => listener.poll(cx),
+            EthSubscription::Node(events, id) => {
+                let res = ready!(events.poll_next_unpin(cx)).map(|event| {
+                    let params = EthSubscriptionParams {
+                        subscription: id.clone(),
+                        result: to_rpc_result(event),
+                    };
+                    EthSubscriptionResponse::anvil(params)
+                });
+                Poll::Ready(res)
+            }
         }
     }
 }
@@ -152,28 +215,10 @@ pub fn filter_logs(
 ) -> Vec<AlloyLog> {
     /// Determines whether to add this log
     fn add_log(block_hash: B256, l: &Log, block: &Block, params: &FilteredParams) -> bool {
-        let log = AlloyLog {
-            address: l.address,
-            topics: l.topics().to_vec(),
-            data: l.data.data.clone(),
-            block_hash: None,
-            block_number: None,
-            transaction_hash: None,
-            transaction_index: None,
-            log_index: None,
-            removed: false,
-        };
-        if params.filter.is_some() {
-            let block_number = block.header.number;
-            if !params.filter_block_range(block_number) ||
-                !params.filter_block_hash(block_hash) ||
-                !params.filter_address(&log) ||
-                !params.filter_topics(&log)
-            {
-                return false;
-            }
-        }
-        true
+        let Some(filter) = &params.filter else { return true };
+        params.filter_block_range(block.header.number) &&
+            params.filter_block_hash(block_hash) &&
+            log_matches(filter, &l.address, l.topics())
     }
 
     let block_hash = block.header.hash();
@@ -1,14 +1,17 @@
 //! Contains RPC handlers
 use crate::{
     eth::error::to_rpc_result,
-    pubsub::{EthSubscription, LogsSubscription},
+    pubsub::{EthSubscription, FullPendingTransactionsSubscription, LogsSubscription},
     EthApi,
 };
 use alloy_rpc_types::{
     pubsub::{Params, SubscriptionKind},
     FilteredParams,
 };
-use anvil_core::eth::{subscription::SubscriptionId, EthPubSub, EthRequest, EthRpcCall};
+use anvil_core::eth::{
+    subscription::{AnvilSubscriptionKind, SubscriptionId},
+    EthPubSub, EthRequest, EthRpcCall,
+};
 use anvil_rpc::{error::RpcError, response::ResponseResult};
 use anvil_server::{PubSubContext, PubSubRpcHandler, RpcHandler};
 
@@ -61,9 +64,14 @@ impl PubSubEthRpcHandler {
                 ResponseResult::Success(canceled.into())
             }
             EthPubSub::EthSubscribe(kind, params) => {
+                let mut full_transactions = false;
                 let filter = match *params {
                     Params::None => None,
                     Params::Logs(filter) => Some(*filter),
+                    Params::Bool(full) if kind == SubscriptionKind::NewPendingTransactions => {
+                        full_transactions = full;
+                        None
+                    }
                     Params::Bool(_) => {
                         return ResponseResult::Error(RpcError::invalid_params(
                             "Expected params for logs subscription",
@@ -91,6 +99,17 @@ impl PubSubEthRpcHandler {
                         let storage = self.api.storage_info();
                         EthSubscription::Header(blocks, storage, id.clone())
                     }
+                    SubscriptionKind::NewPendingTransactions if full_transactions => {
+                        trace!(target: "rpc::ws", "received full pending transactions subscription");
+                        EthSubscription::FullPendingTransactions(Box::new(
+                            FullPendingTransactionsSubscription {
+                                transactions: self.api.new_ready_transactions(),
+                                pool: self.api.pool().clone(),
+                                storage: self.api.storage_info(),
+                                id: id.clone(),
+                            },
+                        ))
+                    }
                     SubscriptionKind::NewPendingTransactions => {
                         trace!(target: "rpc::ws", "received pending transactions subscription");
                         EthSubscription::PendingTransactions(
@@ -108,6 +127,19 @@ impl PubSubEthRpcHandler {
                 trace!(target: "rpc::ws", "created new subscription: {:?}", id);
                 to_rpc_result(id)
             }
+            EthPubSub::AnvilSubscribe(kind) => {
+                let subscription = match kind {
+                    AnvilSubscriptionKind::Node => {
+                        trace!(target: "rpc::ws", "received node events subscription");
+                        EthSubscription::Node(self.api.node_event_notifications(), id.clone())
+                    }
+                };
+
+                cx.add_subscription(id.clone(), subscription);
+
+                trace!(target: "rpc::ws", "created new anvil subscription: {:?}", id);
+                to_rpc_result(id)
+            }
         }
     }
 }
@@ -1,10 +1,14 @@
 //! tests for custom anvil endpoints
 use crate::{abi::*, fork::fork_config, utils::ethers_http_provider};
+use alloy_primitives::U256 as rU256;
 use alloy_rpc_types::BlockNumberOrTag;
 use anvil::{eth::api::CLIENT_VERSION, spawn, Hardfork, NodeConfig};
 use anvil_core::{
     eth::EthRequest,
-    types::{AnvilMetadata, ForkedNetwork, Forking, NodeEnvironment, NodeForkConfig, NodeInfo},
+    types::{
+        AnvilMetadata, ForkedNetwork, Forking, MiningModeInfo, NodeEnvironment, NodeForkConfig,
+        NodeInfo,
+    },
 };
 use ethers::{
     abi::{ethereum_types::BigEndianHash, AbiDecode},
@@ -101,6 +105,58 @@ async fn can_impersonate_account() {
     res.unwrap_err();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_impersonate_account_with_min_balance() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    let impersonate = Address::random();
+    let to = Address::random();
+    let val = 1337u64;
+    let min_balance = U256::from(1e18 as u64);
+
+    api.anvil_impersonate_account_with_min_balance(
+        impersonate.to_alloy(),
+        Some(min_balance.to_alloy()),
+    )
+    .await
+    .unwrap();
+    // not topped up before it sends a transaction
+    assert_eq!(api.balance(impersonate.to_alloy(), None).await.unwrap(), rU256::ZERO);
+
+    let tx = TransactionRequest::new().from(impersonate).to(to).value(val).gas(21000u64);
+    let res = provider.send_transaction(tx.clone(), None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(res.from, impersonate);
+
+    let balance = provider.get_balance(to, None).await.unwrap();
+    assert_eq!(balance, val.into());
+    let balance = provider.get_balance(impersonate, None).await.unwrap();
+    assert!(balance < min_balance && balance > U256::zero());
+
+    // the funds are kept when the impersonation stops
+    api.anvil_stop_impersonating_account(impersonate.to_alloy()).await.unwrap();
+    assert_eq!(provider.get_balance(impersonate, None).await.unwrap(), balance);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_impersonate_with_node_min_balance() {
+    let min_balance = rU256::from(10u64).pow(rU256::from(18u64));
+    let (api, handle) =
+        spawn(NodeConfig::test().with_impersonate_min_balance(Some(min_balance))).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    let impersonate = Address::random();
+    let tx = TransactionRequest::new().from(impersonate).to(Address::random()).gas(21000u64);
+
+    // accounts that aren't impersonated are never topped up
+    provider.send_transaction(tx.clone(), None).await.unwrap_err();
+    assert_eq!(api.balance(impersonate.to_alloy(), None).await.unwrap(), rU256::ZERO);
+
+    api.anvil_impersonate_account(impersonate.to_alloy()).await.unwrap();
+    provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert!(api.balance(impersonate.to_alloy(), None).await.unwrap() > rU256::ZERO);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_auto_impersonate_account() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -479,11 +535,47 @@ async fn can_get_metadata() {
         instance_id: api.instance_id(),
         forked_network: None,
         snapshots: Default::default(),
+        hard_fork: SpecId::SHANGHAI,
+        base_fee: U256::from_str("0x3b9aca00").unwrap().to_alloy(),
+        gas_limit: U256::from_str("0x1c9c380").unwrap().to_alloy(),
+        mining_mode: MiningModeInfo::Auto,
     };
 
     assert_eq!(metadata, expected_metadata);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn metadata_response_shape() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    api.evm_snapshot().await.unwrap();
+    api.anvil_set_interval_mining(2).unwrap();
+
+    let metadata: serde_json::Value = provider.request("anvil_metadata", ()).await.unwrap();
+    let mut keys = metadata.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            "baseFee",
+            "chainId",
+            "clientVersion",
+            "forkedNetwork",
+            "gasLimit",
+            "hardFork",
+            "instanceId",
+            "latestBlockHash",
+            "latestBlockNumber",
+            "miningMode",
+            "snapshots",
+        ]
+    );
+    assert_eq!(metadata["miningMode"], serde_json::json!({ "type": "interval", "blockTime": 2 }));
+    assert_eq!(metadata["forkedNetwork"], serde_json::Value::Null);
+    assert_eq!(metadata["snapshots"].as_object().unwrap().len(), 1);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_get_metadata_on_fork() {
     let (api, handle) =
@@ -506,8 +598,13 @@ async fn can_get_metadata_on_fork() {
             chain_id,
             fork_block_number: block_number,
             fork_block_hash: block.hash.unwrap().to_alloy(),
+            fork_url: "https://bsc-dataseed.binance.org/".to_string(),
         }),
         snapshots: Default::default(),
+        hard_fork: metadata.hard_fork,
+        base_fee: metadata.base_fee,
+        gas_limit: metadata.gas_limit,
+        mining_mode: MiningModeInfo::Auto,
     };
 
     assert_eq!(metadata, expected_metadata);
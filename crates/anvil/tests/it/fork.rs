@@ -18,8 +18,8 @@ use ethers::{
     providers::{Http, Provider},
     signers::Signer,
     types::{
-        transaction::eip2718::TypedTransaction, Address, BlockNumber, Chain, TransactionRequest,
-        U256,
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Chain,
+        Eip1559TransactionRequest, TransactionRequest, U256,
     },
 };
 use foundry_common::{
@@ -1050,6 +1050,61 @@ async fn can_override_fork_chain_id() {
     assert_eq!(chain_id.as_u64(), chain_id_override);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_chain_id_separation() {
+    let chain_id = 3145u64;
+    let (api, handle) = spawn(fork_config().with_chain_id(Some(chain_id))).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    // the node reports its own chain ID, and the forked network the upstream one
+    assert_eq!(provider.get_chainid().await.unwrap().as_u64(), chain_id);
+    let metadata = api.anvil_metadata().await.unwrap();
+    assert_eq!(metadata.chain_id, chain_id);
+    assert_eq!(metadata.forked_network.unwrap().chain_id, Chain::Mainnet as u64);
+
+    // `CHAINID` returned by the init code `CHAINID PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN`
+    let tx = TypedTransaction::Legacy(
+        TransactionRequest::new()
+            .data(Bytes::from_static(&[0x46, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3])),
+    );
+    let res = provider.call(&tx, None).await.unwrap();
+    assert_eq!(U256::from_big_endian(&res), chain_id.into());
+
+    // transactions signed locally with the chain ID of the node are accepted
+    let wallet = handle.dev_wallets().next().unwrap().to_ethers();
+    assert_eq!(wallet.chain_id(), chain_id);
+    let client = SignerMiddleware::new(provider.clone(), wallet.clone());
+    let to = Address::random();
+    let legacy = TransactionRequest::new().to(to).value(1337u64);
+    let receipt = client.send_transaction(legacy, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()));
+    let eip1559 = Eip1559TransactionRequest::new().to(to).value(1337u64);
+    let receipt = client.send_transaction(eip1559, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()));
+    let tx = provider.get_transaction(receipt.transaction_hash).await.unwrap().unwrap();
+    assert_eq!(tx.chain_id, Some(chain_id.into()));
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), U256::from(2 * 1337u64));
+
+    // transactions signed with the chain ID of the forked chain are rejected
+    let mainnet_client =
+        SignerMiddleware::new(provider.clone(), wallet.with_chain_id(Chain::Mainnet as u64));
+    let eip1559 = Eip1559TransactionRequest::new().to(to).value(1337u64);
+    assert!(mainnet_client.send_transaction(eip1559, None).await.is_err());
+
+    // resetting the fork keeps the chain ID of the node
+    api.anvil_reset(Some(Forking {
+        json_rpc_url: Some(rpc::next_http_archive_rpc_endpoint()),
+        block_number: Some(BLOCK_NUMBER),
+    }))
+    .await
+    .unwrap();
+    assert_eq!(provider.get_chainid().await.unwrap().as_u64(), chain_id);
+    assert_eq!(
+        api.anvil_metadata().await.unwrap().forked_network.unwrap().chain_id,
+        Chain::Mainnet as u64
+    );
+}
+
 // <https://github.com/foundry-rs/foundry/issues/6485>
 #[tokio::test(flavor = "multi_thread")]
 async fn test_fork_reset_moonbeam() {
@@ -2,6 +2,7 @@
 
 use crate::utils::ethers_http_provider;
 use alloy_primitives::U256;
+use alloy_rpc_types::BlockNumberOrTag;
 use anvil::{eth::fees::INITIAL_BASE_FEE, spawn, NodeConfig};
 use ethers::{
     prelude::Middleware,
@@ -10,7 +11,7 @@ use ethers::{
         TransactionRequest,
     },
 };
-use foundry_common::types::ToAlloy;
+use foundry_common::types::{ToAlloy, ToEthers};
 
 const GAS_TRANSFER: u64 = 21_000u64;
 
@@ -77,6 +78,57 @@ async fn test_basefee_empty_block() {
     assert!(next_base_fee < base_fee);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_basefee_adjustment_disabled() {
+    let (_api, handle) = spawn(
+        NodeConfig::test()
+            .with_base_fee(Some(INITIAL_BASE_FEE.to_alloy()))
+            .with_gas_limit(Some(GAS_TRANSFER.to_alloy()))
+            .disable_base_fee_adjustment(true),
+    )
+    .await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+    for _ in 0..2 {
+        let tx = TransactionRequest::new().to(Address::random()).value(1337u64);
+        provider.send_transaction(tx.clone(), None).await.unwrap().await.unwrap().unwrap();
+    }
+    let next_base_fee =
+        provider.get_block(BlockNumber::Latest).await.unwrap().unwrap().base_fee_per_gas.unwrap();
+
+    // unchanged, even though the block was full
+    assert_eq!(next_base_fee.as_u64(), INITIAL_BASE_FEE);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_basefee_override() {
+    let (api, handle) =
+        spawn(NodeConfig::test().with_base_fee(Some(INITIAL_BASE_FEE.to_alloy()))).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    let base_fee = 5 * INITIAL_BASE_FEE;
+    api.anvil_set_base_fee(Some(U256::from(base_fee))).await.unwrap();
+
+    // the override persists across empty blocks
+    api.mine_one().await;
+    api.mine_one().await;
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.base_fee_per_gas.unwrap().as_u64(), base_fee);
+
+    let gas_price = provider.get_gas_price().await.unwrap();
+    assert_eq!(gas_price.as_u64(), base_fee + api.max_priority_fee_per_gas().unwrap().to::<u64>());
+
+    let fee_history = provider.fee_history(2u64, BlockNumber::Latest, &[]).await.unwrap();
+    // both the latest and the pending block use the override
+    assert!(fee_history.base_fee_per_gas.iter().rev().take(2).all(|fee| fee.as_u64() == base_fee));
+
+    // once cleared, the base fee is adjusted again
+    api.anvil_set_base_fee(None).await.unwrap();
+    api.mine_one().await;
+    api.mine_one().await;
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert!(block.base_fee_per_gas.unwrap().as_u64() < base_fee);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_respect_base_fee() {
     let base_fee = 50u64;
@@ -116,3 +168,69 @@ async fn test_tip_above_fee_cap() {
         .to_string()
         .contains("max priority fee per gas higher than max fee per gas"));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fee_history_rewards() {
+    let (api, handle) =
+        spawn(NodeConfig::test().with_base_fee(Some(INITIAL_BASE_FEE.to_alloy()))).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+    let accounts: Vec<_> = handle.dev_accounts().collect();
+
+    // include three transfers with different tips in the same block
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let tips = [1_000_000_000u64, 2_000_000_000, 3_000_000_000];
+    for (from, tip) in accounts.iter().zip(tips) {
+        let tx = Eip1559TransactionRequest::new()
+            .from(from.to_ethers())
+            .to(Address::random())
+            .value(1337u64)
+            .max_fee_per_gas(100 * INITIAL_BASE_FEE)
+            .max_priority_fee_per_gas(tip);
+        provider.send_transaction(tx, None).await.unwrap();
+    }
+    api.mine_one().await;
+    // and an empty block
+    api.mine_one().await;
+
+    let history = api
+        .fee_history(U256::from(2), BlockNumberOrTag::Latest, vec![0.0, 50.0, 100.0])
+        .await
+        .unwrap();
+    assert_eq!(history.oldest_block, U256::from(1));
+    assert_eq!(history.base_fee_per_gas.len(), 3);
+    assert_eq!(history.gas_used_ratio.len(), 2);
+    let rewards = history.reward.clone().unwrap();
+    assert_eq!(rewards[0], tips.map(U256::from).to_vec());
+    assert_eq!(rewards[1], vec![U256::ZERO; 3]);
+
+    // matches geth's response for `eth_feeHistory(2, "latest", [0, 50, 100])` on the same chain
+    let geth: serde_json::Value = serde_json::from_str(
+        r#"{
+            "oldestBlock": "0x1",
+            "baseFeePerGas": ["0x3b9aca00", "0x342f7388", "0x2da98517"],
+            "gasUsedRatio": [0.0021, 0.0],
+            "reward": [
+                ["0x3b9aca00", "0x77359400", "0xb2d05e00"],
+                ["0x0", "0x0", "0x0"]
+            ]
+        }"#,
+    )
+    .unwrap();
+    let anvil = serde_json::to_value(&history).unwrap();
+    for (key, value) in geth.as_object().unwrap() {
+        assert_eq!(&anvil[key], value, "{key}");
+    }
+
+    // more blocks than available are capped at the oldest known block
+    let history =
+        api.fee_history(U256::from(5000), BlockNumberOrTag::Latest, vec![]).await.unwrap();
+    assert_eq!(history.oldest_block, U256::ZERO);
+    assert_eq!(history.gas_used_ratio.len(), 3);
+    assert_eq!(history.base_fee_per_gas.len(), 4);
+
+    // percentiles must be increasing
+    assert!(api
+        .fee_history(U256::from(1), BlockNumberOrTag::Latest, vec![50.0, 10.0])
+        .await
+        .is_err());
+}
@@ -3,9 +3,11 @@
 use std::str::FromStr;
 
 use alloy_genesis::Genesis;
-use alloy_primitives::{Address, U256, U64};
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
 use alloy_providers::provider::TempProvider;
+use alloy_rpc_types::BlockNumberOrTag;
 use anvil::{spawn, NodeConfig};
+use foundry_evm::revm::primitives::SpecId;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn can_apply_genesis() {
@@ -49,3 +51,89 @@ async fn can_apply_genesis() {
     let expected: U256 = U256::from_str_radix("ffffffffffffffffffffffffff", 16).unwrap();
     assert_eq!(balance, expected);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_apply_genesis_alloc_and_chain_config() {
+    let genesis = r#"{
+  "config": {
+    "chainId": 1337,
+    "homesteadBlock": 0,
+    "eip150Block": 0,
+    "eip155Block": 0,
+    "eip158Block": 0,
+    "byzantiumBlock": 0,
+    "constantinopleBlock": 0,
+    "petersburgBlock": 0,
+    "istanbulBlock": 0,
+    "berlinBlock": 0,
+    "londonBlock": 2
+  },
+  "timestamp": "0x0",
+  "extraData": "0x1234",
+  "gasLimit": "0x1312d00",
+  "difficulty": "0x0",
+  "coinbase": "0x0000000000000000000000000000000000000000",
+  "alloc": {
+    "0x1000000000000000000000000000000000000001": {
+      "balance": "0x0",
+      "nonce": "0x5",
+      "code": "0x6001600055",
+      "storage": {
+        "0x0000000000000000000000000000000000000000000000000000000000000001": "0x000000000000000000000000000000000000000000000000000000000000002a"
+      }
+    }
+  }
+}
+"#;
+    let genesis: Genesis = serde_json::from_str(genesis).unwrap();
+    let (api, _handle) = spawn(NodeConfig::test().with_genesis(Some(genesis))).await;
+
+    let addr = Address::from_str("1000000000000000000000000000000000000001").unwrap();
+    assert_eq!(
+        api.get_code(addr, None).await.unwrap(),
+        Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55])
+    );
+    assert_eq!(
+        api.storage_at(addr, U256::from(1), None).await.unwrap(),
+        B256::from(U256::from(42))
+    );
+    assert_eq!(api.transaction_count(addr, None).await.unwrap(), U256::from(5));
+    assert_eq!(api.gas_limit(), U256::from(20_000_000u64));
+
+    let block = api.block_by_number(BlockNumberOrTag::Number(0)).await.unwrap().unwrap();
+    assert_eq!(block.header.extra_data, Bytes::from_static(&[0x12, 0x34]));
+
+    // london is activated at block 2
+    assert_eq!(api.anvil_metadata().await.unwrap().hard_fork, SpecId::BERLIN);
+    api.mine_one().await;
+    assert_eq!(api.anvil_metadata().await.unwrap().hard_fork, SpecId::BERLIN);
+    api.mine_one().await;
+    assert_eq!(api.anvil_metadata().await.unwrap().hard_fork, SpecId::LONDON);
+    let block = api.block_by_number(BlockNumberOrTag::Number(2)).await.unwrap().unwrap();
+    assert!(block.header.base_fee_per_gas.is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn explicit_gas_limit_overrides_genesis() {
+    let genesis: Genesis = serde_json::from_str(
+        r#"{
+  "config": { "chainId": 1337 },
+  "gasLimit": "0x1312d00",
+  "difficulty": "0x0",
+  "alloc": {}
+}"#,
+    )
+    .unwrap();
+
+    // an explicitly configured limit is honored, even if it matches the default one
+    let (api, _handle) = spawn(
+        NodeConfig::test()
+            .with_genesis(Some(genesis.clone()))
+            .with_gas_limit(Some(U256::from(30_000_000u64))),
+    )
+    .await;
+    assert_eq!(api.gas_limit(), U256::from(30_000_000u64));
+
+    let (api, _handle) = spawn(NodeConfig::test().with_genesis(Some(genesis))).await;
+    assert_eq!(api.gas_limit(), U256::from(20_000_000u64));
+}
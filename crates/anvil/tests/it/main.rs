@@ -10,6 +10,7 @@ mod geth;
 mod ipc;
 mod logs;
 mod optimism;
+mod precompile;
 mod proof;
 mod pubsub;
 // mod revert; // TODO uncomment <https://github.com/gakonst/ethers-rs/issues/2186>
@@ -1,7 +1,9 @@
 //! Tests for OP chain support.
 
 use crate::utils::ethers_http_provider;
+use alloy_primitives::{U256 as rU256, U64};
 use anvil::{spawn, Hardfork, NodeConfig};
+use anvil_core::{eth::transaction::optimism::L1_BLOCK_ADDRESS, types::L1BlockValues};
 use ethers::{
     abi::Address,
     providers::Middleware,
@@ -144,3 +146,87 @@ async fn test_send_value_raw_deposit_transaction() {
     let balance = provider.get_balance(to_addr, None).await.unwrap();
     assert_eq!(balance, send_value);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deposit_transaction_fields() {
+    let (api, handle) =
+        spawn(NodeConfig::test().with_optimism(true).with_hardfork(Some(Hardfork::Paris))).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    let from_addr: Address = "cf7f9e66af820a19257a2108375b180b0ec49167".parse().unwrap();
+    let to_addr: Address = "71562b71999873db5b286df957af199ec94617f7".parse().unwrap();
+    let source_hash =
+        H256::from_str("0x0000000000000000000000000000000000000000000000000000000000000001")
+            .unwrap();
+
+    let deposit_tx: TypedTransaction = TypedTransaction::DepositTransaction(DepositTransaction {
+        tx: TransactionRequest {
+            chain_id: None,
+            from: Some(from_addr),
+            to: Some(ethers::types::NameOrAddress::Address(to_addr)),
+            value: Some(U256::from(1234)),
+            gas: Some(U256::from(21000)),
+            gas_price: None,
+            data: Some(Bytes::default()),
+            nonce: None,
+        },
+        source_hash,
+        // the sender is not funded, the minted value covers the transfer
+        mint: Some(U256::from(1234)),
+        is_system_tx: false,
+    });
+
+    let pending = provider.send_raw_transaction(deposit_tx.rlp()).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+
+    let tx = provider.get_transaction(pending.tx_hash()).await.unwrap().unwrap();
+    assert_eq!(tx.transaction_type, Some(ethers::types::U64::from(0x7E)));
+    assert_eq!(tx.other.get_deserialized::<H256>("sourceHash").unwrap().unwrap(), source_hash);
+    assert_eq!(tx.other.get_deserialized::<U256>("mint").unwrap().unwrap(), U256::from(1234));
+    assert!(!tx.other.get_deserialized::<bool>("isSystemTx").unwrap().unwrap());
+
+    let balance = provider.get_balance(to_addr, None).await.unwrap();
+    assert_eq!(balance, U256::from(1234));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_set_l1_block_values() {
+    let (api, _handle) =
+        spawn(NodeConfig::test().with_optimism(true).with_hardfork(Some(Hardfork::Paris))).await;
+
+    let values = L1BlockValues {
+        number: Some(U64::from(19_000_000)),
+        timestamp: Some(U64::from(1_700_000_000)),
+        base_fee: Some(rU256::from(30_000_000_000u64)),
+        l1_fee_overhead: Some(rU256::from(188)),
+        l1_fee_scalar: Some(rU256::from(684_000)),
+        ..Default::default()
+    };
+    api.anvil_set_l1_block_values(values).await.unwrap();
+
+    let slot = |slot: u64| api.storage_at(L1_BLOCK_ADDRESS, rU256::from(slot), None);
+    let packed = rU256::from_be_bytes(slot(0).await.unwrap().0);
+    assert_eq!(packed & rU256::from(u64::MAX), rU256::from(19_000_000));
+    assert_eq!(packed >> 64, rU256::from(1_700_000_000));
+    assert_eq!(rU256::from_be_bytes(slot(1).await.unwrap().0), rU256::from(30_000_000_000u64));
+    assert_eq!(rU256::from_be_bytes(slot(5).await.unwrap().0), rU256::from(188));
+    assert_eq!(rU256::from_be_bytes(slot(6).await.unwrap().0), rU256::from(684_000));
+
+    // untouched values are left as is
+    api.anvil_set_l1_block_values(L1BlockValues {
+        timestamp: Some(U64::from(1_700_000_012)),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let packed = rU256::from_be_bytes(slot(0).await.unwrap().0);
+    assert_eq!(packed & rU256::from(u64::MAX), rU256::from(19_000_000));
+    assert_eq!(packed >> 64, rU256::from(1_700_000_012));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_set_l1_block_values_requires_optimism() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let err = api.anvil_set_l1_block_values(L1BlockValues::default()).await.unwrap_err();
+    assert!(err.to_string().contains("--optimism"));
+}
@@ -0,0 +1,68 @@
+//! custom precompile tests
+
+use alloy_primitives::{address, Address, Bytes};
+use alloy_rpc_types::request::{TransactionInput, TransactionRequest};
+use anvil::{spawn, try_spawn, NodeConfig};
+use foundry_evm::precompiles::PrecompileSpec;
+use std::path::Path;
+
+const PRECOMPILE: Address = address!("0000000000000000000000000000000000000100");
+
+/// Echoes its input, reverts on empty input, and costs 100 gas plus 1 per input byte.
+const ECHO_PLUGIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (func (export "foundry_precompile_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "foundry_precompile_free") (param i32 i32))
+  (func (export "foundry_precompile_gas") (param $input i32) (param $len i32) (result i64)
+    (i64.add (i64.const 100) (i64.extend_i32_u (local.get $len))))
+  (func (export "foundry_precompile_run")
+    (param $input i32) (param $len i32) (param $out i32) (param $out_len i32) (result i32)
+    (if (i32.eqz (local.get $len)) (then (return (i32.const 1))))
+    (i32.store (local.get $out) (local.get $input))
+    (i32.store (local.get $out_len) (local.get $len))
+    (i32.const 0)))
+"#;
+
+fn echo_plugin(dir: &Path) -> PrecompileSpec {
+    let path = dir.join("echo.wasm");
+    std::fs::write(&path, wat::parse_str(ECHO_PLUGIN).unwrap()).unwrap();
+    PrecompileSpec { address: PRECOMPILE, path }
+}
+
+fn call_request(input: &'static [u8]) -> TransactionRequest {
+    TransactionRequest {
+        to: Some(PRECOMPILE),
+        input: TransactionInput::maybe_input(Some(Bytes::from_static(input))),
+        ..Default::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_call_custom_precompile() {
+    let dir = tempfile::tempdir().unwrap();
+    let (api, _handle) =
+        spawn(NodeConfig::test().with_precompiles(vec![echo_plugin(dir.path())])).await;
+
+    let output = api.call(call_request(b"hello"), None, None).await.unwrap();
+    assert_eq!(output, Bytes::from_static(b"hello"));
+
+    // the precompile's code isn't executed, and its gas cost is charged
+    let gas = api.estimate_gas(call_request(b"hello"), None, None).await.unwrap();
+    assert!(gas.to::<u64>() >= 21_000 + 105, "{gas}");
+
+    assert!(api.call(call_request(b""), None, None).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fails_to_spawn_with_invalid_precompile() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = PrecompileSpec { address: PRECOMPILE, path: dir.path().join("missing.wasm") };
+    let err = try_spawn(NodeConfig::test().with_precompiles(vec![missing])).await.unwrap_err();
+    assert!(format!("{err:#}").contains("missing.wasm"), "{err:#}");
+}
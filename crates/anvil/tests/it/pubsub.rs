@@ -8,11 +8,14 @@ use ethers::{
     prelude::{Middleware, Ws},
     providers::{JsonRpcClient, PubsubClient},
     signers::Signer,
-    types::{Address, Block, Filter, TransactionRequest, TxHash, ValueOrArray, U256},
+    types::{
+        Address, Block, Filter, Transaction, TransactionRequest, TxHash, ValueOrArray, H256, U256,
+    },
+    utils::keccak256,
 };
 use foundry_common::types::{ToAlloy, ToEthers};
 use futures::StreamExt;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_sub_new_heads() {
@@ -274,3 +277,131 @@ async fn test_sub_new_heads_fast() {
     let numbers = (1..=num).collect::<Vec<_>>();
     assert_eq!(block_numbers, numbers);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sub_node_events() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+    let events = api.node_event_notifications();
+
+    let snapshot = api.evm_snapshot().await.unwrap();
+    let from = handle.dev_accounts().next().unwrap().to_ethers();
+    let tx = TransactionRequest::new().from(from).to(Address::random()).value(1u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert!(api.evm_revert(snapshot).await.unwrap());
+
+    let events = events.take(4).collect::<Vec<_>>().await;
+    let kinds = events.iter().map(|event| event["event"].as_str().unwrap()).collect::<Vec<_>>();
+    assert_eq!(kinds, ["snapshotTaken", "transactionAdded", "blockMined", "snapshotReverted"]);
+    assert!(events.iter().all(|event| event["version"] == 1));
+
+    let mined = &events[2]["transactions"][0];
+    assert_eq!(mined["hash"], serde_json::json!(receipt.transaction_hash));
+    assert_eq!(mined["gasUsed"], "0x5208");
+    assert_eq!(mined["success"], true);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sub_logs_filters_match_get_logs() {
+    abigen!(EmitLogs, "test-data/emit_logs.json");
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let provider = ethers_ws_provider(&handle.ws_endpoint());
+
+    let mut wallets = handle.dev_wallets().map(|wallet| wallet.to_ethers());
+    let (alice, bob) = (wallets.next().unwrap(), wallets.next().unwrap());
+    let alice_client = Arc::new(SignerMiddleware::new(provider.clone(), alice.clone()));
+    let bob_client = Arc::new(SignerMiddleware::new(provider.clone(), bob.clone()));
+
+    let first = EmitLogs::deploy(Arc::clone(&alice_client), "first".to_string())
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    let second = EmitLogs::deploy(Arc::clone(&alice_client), "second".to_string())
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    let from_block = provider.get_block_number().await.unwrap() + 1;
+
+    let value_changed = H256::from(keccak256("ValueChanged(address,string,string)"));
+    let (alice, bob) = (H256::from(alice.address()), H256::from(bob.address()));
+    let filters = [
+        // address lists are OR-ed
+        Filter::new().address(ValueOrArray::Array(vec![first.address(), second.address()])),
+        // values in a topic position are OR-ed
+        Filter::new().topic0(ValueOrArray::Array(vec![H256::random(), value_changed])),
+        // unset positions are wildcards
+        Filter::new().topic1(ValueOrArray::Array(vec![alice, bob])),
+        // positions are AND-ed
+        Filter::new().address(second.address()).topic0(value_changed).topic1(bob),
+        // logs without a topic in a set position don't match
+        Filter::new().topic1(alice).topic2(ValueOrArray::Array(vec![alice, bob])),
+    ];
+    let mut subscriptions = Vec::new();
+    for filter in &filters {
+        subscriptions.push(provider.subscribe_logs(filter).await.unwrap());
+    }
+
+    for client in [&alice_client, &bob_client] {
+        for contract in [&first, &second] {
+            EmitLogs::new(contract.address(), Arc::clone(client))
+                .set_value("Next Message".to_string())
+                .send()
+                .await
+                .unwrap()
+                .await
+                .unwrap();
+        }
+    }
+
+    let mut counts = Vec::new();
+    for (filter, subscription) in filters.into_iter().zip(subscriptions) {
+        let expected = provider.get_logs(&filter.from_block(from_block)).await.unwrap();
+        let logs = subscription.take(expected.len()).collect::<Vec<_>>().await;
+        assert_eq!(logs, expected);
+        counts.push(expected.len());
+    }
+    assert_eq!(counts, [4, 4, 4, 1, 0]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sub_full_pending_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let ws = Ws::connect(handle.ws_endpoint()).await.unwrap();
+
+    let sub_id: U256 = ws.request("eth_subscribe", ("newPendingTransactions", true)).await.unwrap();
+    let mut stream = ws.subscribe(sub_id).unwrap();
+
+    let provider = ethers_http_provider(&handle.http_endpoint());
+    let from = handle.dev_accounts().next().unwrap().to_ethers();
+    let tx = TransactionRequest::new().from(from).to(Address::random()).value(1u64);
+    let pending = provider.send_transaction(tx, None).await.unwrap();
+
+    let item = stream.next().await.unwrap();
+    let tx: Transaction = serde_json::from_str(item.get()).unwrap();
+    assert_eq!(tx.hash, *pending);
+    assert_eq!(tx.from, from);
+    assert_eq!(tx.block_number, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unsubscribe_stops_events() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let ws = Ws::connect(handle.ws_endpoint()).await.unwrap();
+
+    let sub_id: U256 = ws.request("eth_subscribe", ["newHeads"]).await.unwrap();
+    let mut stream = ws.subscribe(sub_id).unwrap();
+
+    api.mine_one().await;
+    assert!(stream.next().await.is_some());
+
+    let unsubscribed: bool = ws.request("eth_unsubscribe", [sub_id]).await.unwrap();
+    assert!(unsubscribed);
+
+    api.mine_one().await;
+    let next = tokio::time::timeout(Duration::from_millis(500), stream.next()).await;
+    assert!(next.is_err(), "received an event after unsubscribing");
+}
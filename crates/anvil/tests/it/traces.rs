@@ -3,6 +3,10 @@ use crate::{
     utils::{ethers_http_provider, ethers_ws_provider},
 };
 use alloy_primitives::U256;
+use alloy_rpc_trace_types::geth::{
+    GethDebugTracingOptions, GethTrace as AlloyGethTrace, PreStateFrame,
+};
+use alloy_rpc_types::BlockNumberOrTag;
 use anvil::{spawn, NodeConfig};
 use ethers::{
     contract::ContractInstance,
@@ -161,6 +165,93 @@ contract Contract {
     }
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_trace_block_with_tracers() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let accounts = handle.dev_wallets().collect::<Vec<_>>().to_ethers();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+    let tx = TransactionRequest::new().to(to).value(1000u64).from(from);
+    let first = provider.send_transaction(tx.clone().nonce(0u64), None).await.unwrap().tx_hash();
+    let second = provider.send_transaction(tx.nonce(1u64), None).await.unwrap().tx_hash();
+    api.mine_one().await;
+
+    let opts: GethDebugTracingOptions = serde_json::from_value(serde_json::json!({
+        "tracer": "callTracer",
+        "tracerConfig": { "onlyTopCall": true, "withLog": true }
+    }))
+    .unwrap();
+    match api.debug_trace_transaction(second.to_alloy(), opts).await.unwrap() {
+        AlloyGethTrace::CallTracer(frame) => {
+            assert_eq!(frame.from, from.to_alloy());
+            assert_eq!(frame.to, Some(to.to_alloy()));
+            assert_eq!(frame.value, Some(U256::from(1000u64)));
+            assert!(frame.calls.is_empty());
+        }
+        _ => unreachable!("unexpected trace"),
+    }
+
+    let opts: GethDebugTracingOptions =
+        serde_json::from_value(serde_json::json!({ "tracer": "prestateTracer" })).unwrap();
+    let traces = api.debug_trace_block_by_number(BlockNumberOrTag::Latest, opts).await.unwrap();
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces[0].tx_hash, first.to_alloy());
+    assert_eq!(traces[1].tx_hash, second.to_alloy());
+    match &traces[1].result {
+        AlloyGethTrace::PreStateTracer(PreStateFrame::Default(prestate)) => {
+            // the second transfer observes the state after the first one
+            assert_eq!(prestate.0[&from.to_alloy()].nonce, Some(1));
+        }
+        _ => unreachable!("unexpected trace"),
+    }
+
+    let block = api.block_by_number(BlockNumberOrTag::Latest).await.unwrap().unwrap();
+    let opts: GethDebugTracingOptions =
+        serde_json::from_value(serde_json::json!({ "disableMemory": true })).unwrap();
+    let traces = api.debug_trace_block_by_hash(block.header.hash.unwrap(), opts).await.unwrap();
+    assert_eq!(traces.len(), 2);
+    match &traces[0].result {
+        AlloyGethTrace::Default(frame) => assert!(!frame.failed),
+        _ => unreachable!("unexpected trace"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_trace_call_with_overrides() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let accounts = handle.dev_wallets().collect::<Vec<_>>();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+
+    let request = serde_json::from_value(serde_json::json!({
+        "from": from,
+        "to": to,
+        "value": "0x1000",
+    }))
+    .unwrap();
+    let opts = serde_json::from_value(serde_json::json!({
+        "tracer": "callTracer",
+        "stateOverrides": { (from.to_string()): { "balance": "0x10000" } },
+        "blockOverrides": { "number": "0x100" }
+    }))
+    .unwrap();
+    match api.debug_trace_call(request, None, opts).await.unwrap() {
+        AlloyGethTrace::CallTracer(frame) => {
+            assert_eq!(frame.from, from);
+            assert_eq!(frame.value, Some(U256::from(0x1000)));
+            assert!(frame.error.is_none());
+        }
+        _ => unreachable!("unexpected trace"),
+    }
+
+    let opts = serde_json::from_value(serde_json::json!({ "tracer": "4byteTracer" })).unwrap();
+    let request = serde_json::from_value(serde_json::json!({ "from": from, "to": to })).unwrap();
+    assert!(api.debug_trace_call(request, None, opts).await.is_err());
+}
+
 // <https://github.com/foundry-rs/foundry/issues/2656>
 #[tokio::test(flavor = "multi_thread")]
 async fn test_trace_address_fork() {
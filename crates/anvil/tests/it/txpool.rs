@@ -4,8 +4,9 @@ use crate::utils::ethers_http_provider;
 use anvil::{spawn, NodeConfig};
 use ethers::{
     prelude::Middleware,
-    types::{TransactionRequest, U256},
+    types::{Address, TransactionRequest, U256},
 };
+use foundry_common::types::ToAlloy;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn geth_txpool() {
@@ -49,3 +50,65 @@ async fn geth_txpool() {
         assert!(content.contains_key(&nonce.to_string()));
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn geth_txpool_queued() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let account = provider.get_accounts().await.unwrap()[0];
+    let tx = TransactionRequest::new().to(account).from(account).value(42u64);
+
+    // nonce 0 is executable, nonce 2 is gapped
+    let ready = provider.send_transaction(tx.clone().nonce(0u64), None).await.unwrap();
+    let gapped = provider.send_transaction(tx.clone().nonce(2u64), None).await.unwrap();
+
+    let status = provider.txpool_status().await.unwrap();
+    assert_eq!(status.pending.as_u64(), 1);
+    assert_eq!(status.queued.as_u64(), 1);
+
+    let inspect = provider.txpool_inspect().await.unwrap();
+    assert!(inspect.pending.get(&account).unwrap().contains_key("0"));
+    assert!(inspect.queued.get(&account).unwrap().contains_key("2"));
+
+    let content = provider.txpool_content().await.unwrap();
+    assert_eq!(content.pending.get(&account).unwrap().get("0").unwrap().hash, *ready);
+    assert_eq!(content.queued.get(&account).unwrap().get("2").unwrap().hash, *gapped);
+
+    // dropping the gapped transaction removes it from the queue
+    assert_eq!(
+        api.anvil_drop_transaction(gapped.to_alloy()).await.unwrap(),
+        Some(gapped.to_alloy())
+    );
+    let status = provider.txpool_status().await.unwrap();
+    assert_eq!(status.queued.as_u64(), 0);
+
+    // mining removes the executable transaction
+    api.mine_one().await;
+    let status = provider.txpool_status().await.unwrap();
+    assert_eq!(status.pending.as_u64(), 0);
+    assert!(provider.txpool_content().await.unwrap().pending.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn geth_txpool_impersonated() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let impersonate = Address::random();
+    api.anvil_set_balance(impersonate.to_alloy(), U256::exp10(18).to_alloy()).await.unwrap();
+    api.anvil_impersonate_account(impersonate.to_alloy()).await.unwrap();
+
+    let tx = TransactionRequest::new().to(Address::random()).from(impersonate).value(42u64);
+    let tx_hash = provider.send_transaction(tx, None).await.unwrap();
+
+    let content = provider.txpool_content().await.unwrap();
+    let tx = content.pending.get(&impersonate).unwrap().get("0").unwrap();
+    assert_eq!(tx.hash, *tx_hash);
+    assert_eq!(tx.from, impersonate);
+
+    let inspect = provider.txpool_inspect().await.unwrap();
+    assert!(inspect.pending.get(&impersonate).unwrap().contains_key("0"));
+}
@@ -152,7 +152,7 @@ impl CallArgs {
                         Err(evm_err) => TraceResult::try_from(evm_err)?,
                     };
 
-                    handle_traces(trace, &config, chain, labels, debug).await?;
+                    handle_traces(trace, &config, chain, labels, debug, false).await?;
 
                     return Ok(());
                 }
@@ -184,7 +184,7 @@ impl CallArgs {
                         tx.value().copied().unwrap_or_default().to_alloy(),
                     )?);
 
-                    handle_traces(trace, &config, chain, labels, debug).await?;
+                    handle_traces(trace, &config, chain, labels, debug, false).await?;
 
                     return Ok(());
                 }
@@ -0,0 +1,235 @@
+use alloy_dyn_abi::TypedData;
+use alloy_primitives::B256;
+use clap::{Parser, ValueEnum};
+use eyre::{Context, Result};
+use serde_json::{json, Map, Value};
+
+/// CLI arguments for `cast eip712`.
+#[derive(Debug, Parser)]
+pub enum Eip712Subcommands {
+    /// Get the type hash of an EIP-712 struct type.
+    ///
+    /// Referenced struct types are appended to the type, in any order, e.g.
+    /// 'Mail(Person from,Person to,string contents)Person(string name,address wallet)'.
+    #[command(visible_alias = "ht")]
+    HashType {
+        /// The encoded struct type, the first struct is the primary type.
+        #[arg(value_name = "TYPE")]
+        ty: String,
+    },
+
+    /// Get the struct hash, the domain separator and the signing digest of EIP-712 typed data.
+    #[command(visible_alias = "hs")]
+    HashStruct {
+        /// The struct types, as JSON or a path to a JSON file, in the `eth_signTypedData`
+        /// format.
+        #[arg(long, value_name = "JSON|PATH")]
+        types: String,
+
+        /// The name of the struct type of the data.
+        #[arg(long, value_name = "TYPE")]
+        primary: String,
+
+        /// The data to hash, as JSON or a path to a JSON file.
+        #[arg(long, value_name = "JSON|PATH")]
+        data: String,
+
+        /// The domain, as JSON or a path to a JSON file.
+        #[arg(long, value_name = "JSON|PATH")]
+        domain: Option<String>,
+
+        /// Only print the given hash.
+        #[arg(long, value_enum)]
+        only: Option<Eip712Hash>,
+    },
+}
+
+/// The hashes of EIP-712 typed data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Eip712Hash {
+    /// The hash of the data, `hashStruct(message)`
+    Struct,
+    /// The hash of the domain, `hashStruct(eip712Domain)`
+    Domain,
+    /// The digest that is signed, `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))`
+    Digest,
+}
+
+impl Eip712Subcommands {
+    pub fn run(self) -> Result<()> {
+        match self {
+            Eip712Subcommands::HashType { ty } => {
+                println!("{}", hash_type(&ty)?);
+            }
+            Eip712Subcommands::HashStruct { types, primary, data, domain, only } => {
+                let domain = domain.map(|domain| read_json(&domain)).transpose()?;
+                let typed_data =
+                    typed_data(read_json(&types)?, &primary, read_json(&data)?, domain)?;
+                let hashes = [
+                    (Eip712Hash::Struct, "structHash", typed_data.hash_struct()?),
+                    (Eip712Hash::Domain, "domainSeparator", typed_data.domain.separator()),
+                    (Eip712Hash::Digest, "digest", typed_data.eip712_signing_hash()?),
+                ];
+                for (hash, name, value) in hashes {
+                    match only {
+                        Some(only) if only == hash => println!("{value}"),
+                        Some(_) => {}
+                        None => println!("{name}: {value}"),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the type hash of an encoded struct type, e.g. `Permit(address owner,...)`.
+///
+/// Referenced struct types may be appended in any order, they're sorted as per the spec.
+pub fn hash_type(encoded: &str) -> Result<B256> {
+    let (primary, types) = parse_encoded_type(encoded)?;
+    Ok(typed_data(types, &primary, json!({}), None)?.type_hash()?)
+}
+
+/// Builds [TypedData] from its parts in the `eth_signTypedData` format.
+fn typed_data(
+    types: Value,
+    primary: &str,
+    message: Value,
+    domain: Option<Value>,
+) -> Result<TypedData> {
+    let typed_data = json!({
+        "types": types,
+        "primaryType": primary,
+        "domain": domain.unwrap_or_else(|| json!({})),
+        "message": message,
+    });
+    serde_json::from_value(typed_data).wrap_err("invalid EIP-712 typed data")
+}
+
+/// Parses an encoded struct type, e.g. `Mail(Person from,string contents)Person(string name)`,
+/// to the primary type and the types in the `eth_signTypedData` format.
+fn parse_encoded_type(encoded: &str) -> Result<(String, Value)> {
+    let mut types = Map::new();
+    let mut primary = None;
+    let mut rest = encoded.trim();
+    while !rest.is_empty() {
+        let (name, tail) =
+            rest.split_once('(').ok_or_else(|| eyre::eyre!("expected `(` in `{rest}`"))?;
+        let (fields, tail) =
+            tail.split_once(')').ok_or_else(|| eyre::eyre!("expected `)` in `{rest}`"))?;
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$') {
+            eyre::bail!("invalid struct name `{name}`");
+        }
+
+        let mut members = Vec::new();
+        for field in fields.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+            let (ty, field_name) = field
+                .rsplit_once(' ')
+                .ok_or_else(|| eyre::eyre!("expected `<type> <name>`, found `{field}`"))?;
+            members.push(json!({ "name": field_name.trim(), "type": ty.trim() }));
+        }
+        types.insert(name.to_string(), Value::Array(members));
+        primary.get_or_insert_with(|| name.to_string());
+        rest = tail.trim_start();
+    }
+    let primary = primary.ok_or_else(|| eyre::eyre!("empty struct type"))?;
+    Ok((primary, Value::Object(types)))
+}
+
+/// Parses the argument as JSON, or reads it from the file it points to.
+fn read_json(arg: &str) -> Result<Value> {
+    let trimmed = arg.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        serde_json::from_str(arg).wrap_err("invalid JSON")
+    } else {
+        foundry_common::fs::read_json_file(arg.as_ref())
+            .wrap_err_with(|| format!("failed to read JSON from {arg}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{b256, keccak256};
+
+    // https://github.com/ethereum/EIPs/blob/master/assets/eip-712/Example.js
+    fn mail() -> TypedData {
+        let types = json!({
+            "Person": [
+                { "name": "name", "type": "string" },
+                { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+                { "name": "from", "type": "Person" },
+                { "name": "to", "type": "Person" },
+                { "name": "contents", "type": "string" }
+            ]
+        });
+        let domain = json!({
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        });
+        let message = json!({
+            "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+            "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+            "contents": "Hello, Bob!"
+        });
+        typed_data(types, "Mail", message, Some(domain)).unwrap()
+    }
+
+    #[test]
+    fn can_hash_eip712_example() {
+        let mail = mail();
+        assert_eq!(
+            mail.type_hash().unwrap(),
+            b256!("a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2")
+        );
+        assert_eq!(
+            mail.hash_struct().unwrap(),
+            b256!("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+        );
+        assert_eq!(
+            mail.domain.separator(),
+            b256!("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+        );
+        assert_eq!(
+            mail.eip712_signing_hash().unwrap(),
+            b256!("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+        );
+    }
+
+    #[test]
+    fn can_hash_type() {
+        // ERC-2612
+        assert_eq!(
+            hash_type(
+                "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)"
+            )
+            .unwrap(),
+            b256!("6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c9")
+        );
+
+        // the referenced types are sorted by name
+        assert_eq!(
+            hash_type(
+                "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+            )
+            .unwrap(),
+            mail().type_hash().unwrap()
+        );
+
+        // Permit2
+        let permit_batch = "PermitBatch(PermitDetails[] details,address spender,uint256 sigDeadline)PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)";
+        assert_eq!(hash_type(permit_batch).unwrap(), keccak256(permit_batch));
+        let permit_witness = "PermitWitnessTransferFrom(TokenPermissions permitted,address spender,uint256 nonce,uint256 deadline,Witness witness)TokenPermissions(address token,uint256 amount)Witness(address user)";
+        let unsorted = "PermitWitnessTransferFrom(TokenPermissions permitted,address spender,uint256 nonce,uint256 deadline,Witness witness)Witness(address user)TokenPermissions(address token,uint256 amount)";
+        assert_eq!(hash_type(unsorted).unwrap(), keccak256(permit_witness));
+
+        assert!(hash_type("Permit(address owner").is_err());
+        assert!(hash_type("Permit(address)").is_err());
+    }
+}
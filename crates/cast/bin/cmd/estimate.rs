@@ -1,3 +1,4 @@
+use crate::tx;
 use alloy_primitives::U256;
 use cast::{Cast, TxBuilder};
 use clap::Parser;
@@ -7,6 +8,7 @@ use foundry_cli::{
     opts::{EtherscanOpts, RpcOpts},
     utils::{self, parse_ether_value},
 };
+use foundry_compilers::EvmVersion;
 use foundry_config::{figment::Figment, Config};
 use std::str::FromStr;
 
@@ -56,7 +58,8 @@ pub enum EstimateSubcommands {
     /// Estimate gas cost to deploy a smart contract
     #[command(name = "--create")]
     Create {
-        /// The bytecode of contract
+        /// The bytecode of contract, or its name in the project (`ContractName` or
+        /// `path:ContractName`)
         code: String,
 
         /// The signature of the constructor
@@ -72,6 +75,18 @@ pub enum EstimateSubcommands {
         /// Examples: 1ether, 10gwei, 0.01ether
         #[arg(long, value_parser = parse_ether_value)]
         value: Option<U256>,
+
+        /// The addresses to link the libraries of the contract with.
+        ///
+        /// Only with a contract name, in the form `<path>:<name>:<address>`.
+        #[arg(long, value_name = "LIBRARIES")]
+        libraries: Vec<String>,
+
+        /// The constructor arguments of the contract.
+        ///
+        /// Only with a contract name, whose constructor is read from its artifact.
+        #[arg(long, num_args(1..), value_name = "ARGS")]
+        constructor_args: Vec<String>,
     },
 }
 
@@ -79,6 +94,20 @@ impl EstimateArgs {
     pub async fn run(self) -> Result<()> {
         let EstimateArgs { from, to, sig, args, value, rpc, etherscan, command } = self;
 
+        // Load the contract before connecting, so that its errors are reported first.
+        let creation_code = match &command {
+            Some(EstimateSubcommands::Create {
+                code, sig, libraries, constructor_args, ..
+            }) => {
+                let creation_code = tx::contract_creation_code(code, libraries, constructor_args)?;
+                if creation_code.is_some() && sig.is_some() {
+                    eyre::bail!("the constructor of {code} is read from its artifact, pass its arguments with `--constructor-args`")
+                }
+                creation_code
+            }
+            None => None,
+        };
+
         let figment = Figment::from(Config::figment()).merge(etherscan).merge(rpc);
         let config = Config::try_from(figment)?;
 
@@ -90,17 +119,21 @@ impl EstimateArgs {
         builder.etherscan_api_key(api_key);
 
         match command {
-            Some(EstimateSubcommands::Create { code, sig, args, value }) => {
+            Some(EstimateSubcommands::Create { code, sig, args, value, .. }) => {
                 builder.value(value);
 
-                let mut data = hex::decode(code)?;
+                if let Some(creation_code) = &creation_code {
+                    builder.set_data(creation_code.clone());
+                } else {
+                    let mut data = hex::decode(code)?;
 
-                if let Some(s) = sig {
-                    let (mut sigdata, _func) = builder.create_args(&s, args).await?;
-                    data.append(&mut sigdata);
-                }
+                    if let Some(s) = sig {
+                        let (mut sigdata, _func) = builder.create_args(&s, args).await?;
+                        data.append(&mut sigdata);
+                    }
 
-                builder.set_data(data);
+                    builder.set_data(data);
+                }
             }
             _ => {
                 let sig = sig.ok_or_else(|| eyre::eyre!("Function signature must be provided."))?;
@@ -111,10 +144,31 @@ impl EstimateArgs {
         let builder_output = builder.peek();
         let gas = Cast::new(&provider).estimate(builder_output).await?;
         println!("{gas}");
+
+        if let Some(code) = creation_code {
+            let intrinsic = create_intrinsic_gas(&code, config.evm_version);
+            let calldata = calldata_gas(&code);
+            println!("  intrinsic: {intrinsic}");
+            println!("  calldata: {calldata}");
+            println!("  execution: {}", gas.saturating_sub(U256::from(intrinsic + calldata)));
+        }
         Ok(())
     }
 }
 
+/// Returns the intrinsic gas of a contract creation, with the [EIP-3860](https://eips.ethereum.org/EIPS/eip-3860) cost of its
+/// initcode words from Shanghai on.
+fn create_intrinsic_gas(code: &[u8], evm_version: EvmVersion) -> u64 {
+    let initcode_gas =
+        if evm_version >= EvmVersion::Shanghai { 2 * (code.len() as u64).div_ceil(32) } else { 0 };
+    21_000 + 32_000 + initcode_gas
+}
+
+/// Returns the gas charged for the bytes of the transaction data.
+fn calldata_gas(data: &[u8]) -> u64 {
+    data.iter().map(|byte| if *byte == 0 { 4 } else { 16 }).sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +178,34 @@ mod tests {
         let args: EstimateArgs = EstimateArgs::parse_from(["foundry-cli", "--value", "100"]);
         assert!(args.value.is_some());
     }
+
+    #[test]
+    fn parse_estimate_create_contract() {
+        let args: EstimateArgs = EstimateArgs::parse_from([
+            "foundry-cli",
+            "--create",
+            "src/Token.sol:Token",
+            "--libraries",
+            "src/Math.sol:Math:0x5FbDB2315678afecb367f032d93F642f64180aa3",
+            "--constructor-args",
+            "100",
+            "Token",
+        ]);
+        match args.command {
+            Some(EstimateSubcommands::Create { code, libraries, constructor_args, .. }) => {
+                assert_eq!(code, "src/Token.sol:Token");
+                assert_eq!(libraries.len(), 1);
+                assert_eq!(constructor_args, ["100", "Token"]);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn computes_create_gas_breakdown() {
+        let code = [0x60, 0x80, 0x00, 0x00];
+        assert_eq!(create_intrinsic_gas(&code, EvmVersion::Shanghai), 53_002);
+        assert_eq!(create_intrinsic_gas(&code, EvmVersion::Paris), 53_000);
+        assert_eq!(calldata_gas(&code), 40);
+    }
 }
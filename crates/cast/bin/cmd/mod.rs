@@ -9,11 +9,14 @@ pub mod access_list;
 pub mod bind;
 pub mod call;
 pub mod create2;
+pub mod eip712;
 pub mod estimate;
 pub mod find_block;
 pub mod interface;
 pub mod logs;
 pub mod mktx;
+pub mod nonce_gaps;
+pub mod pretty_calldata;
 pub mod rpc;
 pub mod run;
 pub mod send;
@@ -0,0 +1,195 @@
+use alloy_primitives::{Address, U256};
+use clap::Parser;
+use ethers_core::types::{BlockNumber, NameOrAddress, Transaction};
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_common::types::{ToAlloy, ToEthers};
+use foundry_config::Config;
+use std::{collections::BTreeMap, str::FromStr};
+
+/// CLI arguments for `cast nonce-gaps`.
+#[derive(Debug, Parser)]
+pub struct NonceGapsArgs {
+    /// The address to check.
+    #[arg(value_parser = NameOrAddress::from_str)]
+    who: NameOrAddress,
+
+    #[command(flatten)]
+    rpc: RpcOpts,
+}
+
+impl NonceGapsArgs {
+    pub async fn run(self) -> Result<()> {
+        let NonceGapsArgs { who, rpc } = self;
+
+        let config = Config::from(&rpc);
+        let provider = utils::get_provider(&config)?;
+        let address = match who {
+            NameOrAddress::Name(name) => provider.resolve_name(&name).await?,
+            NameOrAddress::Address(address) => address,
+        };
+
+        let gaps = NonceGaps::fetch(&provider, address.to_alloy()).await?;
+        println!("{}", gaps.report());
+        if gaps.recovery_nonce().is_some() {
+            println!(
+                "\nSend a self-transfer at the lowest missing nonce with `cast send --fill-nonce-gap --from {}`",
+                address.to_alloy()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The nonces of an account's transactions that are confirmed, and waiting in the transaction
+/// pool.
+#[derive(Clone, Debug, Default)]
+pub struct NonceGaps {
+    /// The nonce of the next transaction to be mined, from the `latest` block.
+    pub latest: u64,
+    /// The account's nonce including the transactions executable in the pending block.
+    pub pending: u64,
+    /// The account's transactions in the pool, pending or queued, by nonce. `None` if the node
+    /// doesn't support `txpool_content`.
+    pub pool: Option<BTreeMap<u64, Transaction>>,
+}
+
+impl NonceGaps {
+    /// Fetches the nonces of `address`, and its transactions in the pool if the node exposes it.
+    pub async fn fetch<M: Middleware>(provider: &M, address: Address) -> Result<Self>
+    where
+        M::Error: 'static,
+    {
+        let who = address.to_ethers();
+        let latest = provider
+            .get_transaction_count(who, Some(BlockNumber::Latest.into()))
+            .await
+            .wrap_err("could not get the latest nonce")?
+            .as_u64();
+        let pending = provider
+            .get_transaction_count(who, Some(BlockNumber::Pending.into()))
+            .await
+            .wrap_err("could not get the pending nonce")?
+            .as_u64();
+
+        let pool = provider.txpool_content().await.ok().map(|content| {
+            content
+                .pending
+                .get(&who)
+                .into_iter()
+                .chain(content.queued.get(&who))
+                .flat_map(|txs| txs.values())
+                .filter(|tx| tx.nonce.as_u64() >= latest)
+                .map(|tx| (tx.nonce.as_u64(), tx.clone()))
+                .collect()
+        });
+
+        Ok(Self { latest, pending, pool })
+    }
+
+    /// Returns the nonces missing between the latest nonce and the highest nonce in the pool.
+    ///
+    /// Always empty if the pool is unknown.
+    pub fn gaps(&self) -> Vec<u64> {
+        let Some(pool) = &self.pool else { return vec![] };
+        let Some(highest) = pool.keys().next_back() else { return vec![] };
+        (self.latest..*highest).filter(|nonce| !pool.contains_key(nonce)).collect()
+    }
+
+    /// Returns the nonce to send a transaction at to unblock the account: the lowest missing
+    /// nonce, or the latest nonce if a transaction is stuck there.
+    pub fn recovery_nonce(&self) -> Option<u64> {
+        if let Some(gap) = self.gaps().first() {
+            return Some(*gap)
+        }
+        let stuck = match &self.pool {
+            Some(pool) => !pool.is_empty(),
+            None => self.pending > self.latest,
+        };
+        stuck.then_some(self.latest)
+    }
+
+    /// Returns the transaction in the pool at `nonce`, if any.
+    pub fn pooled(&self, nonce: u64) -> Option<&Transaction> {
+        self.pool.as_ref()?.get(&nonce)
+    }
+
+    /// Returns a human readable summary of the nonces.
+    pub fn report(&self) -> String {
+        let mut report = format!("Latest nonce:  {}\nPending nonce: {}", self.latest, self.pending);
+        match &self.pool {
+            None => {
+                report.push_str(
+                    "\nThe node doesn't expose its transaction pool, gaps can't be detected.",
+                );
+                if self.pending > self.latest {
+                    report.push_str(&format!(
+                        "\n{} transactions are waiting to be mined.",
+                        self.pending - self.latest
+                    ));
+                }
+            }
+            Some(pool) if pool.is_empty() => report.push_str("\nNo transactions in the pool."),
+            Some(pool) => {
+                report.push_str("\nTransactions in the pool:");
+                for (nonce, tx) in pool {
+                    report.push_str(&format!("\n  {nonce}: {:?}", tx.hash));
+                }
+                let gaps = self.gaps();
+                if gaps.is_empty() {
+                    report.push_str("\nNo nonce gaps.");
+                } else {
+                    let gaps = gaps.iter().map(u64::to_string).collect::<Vec<_>>().join(", ");
+                    report.push_str(&format!("\nMissing nonces: {gaps}"));
+                }
+            }
+        }
+        report
+    }
+}
+
+/// Returns the lowest fee that replaces a transaction paying `fee`.
+///
+/// Nodes require a bump of at least 10% to replace a transaction with the same nonce.
+pub fn replacement_fee(fee: U256) -> U256 {
+    fee * U256::from(11) / U256::from(10) + U256::from(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pooled(nonces: &[u64]) -> Option<BTreeMap<u64, Transaction>> {
+        Some(
+            nonces
+                .iter()
+                .map(|nonce| (*nonce, Transaction { nonce: (*nonce).into(), ..Default::default() }))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn finds_nonce_gaps() {
+        let gaps = NonceGaps { latest: 41, pending: 41, pool: pooled(&[42, 43, 45]) };
+        assert_eq!(gaps.gaps(), vec![41, 44]);
+        assert_eq!(gaps.recovery_nonce(), Some(41));
+
+        let stuck = NonceGaps { latest: 41, pending: 42, pool: pooled(&[41, 42]) };
+        assert!(stuck.gaps().is_empty());
+        assert_eq!(stuck.recovery_nonce(), Some(41));
+
+        let unknown = NonceGaps { latest: 41, pending: 43, pool: None };
+        assert!(unknown.gaps().is_empty());
+        assert_eq!(unknown.recovery_nonce(), Some(41));
+
+        let idle = NonceGaps { latest: 41, pending: 41, pool: pooled(&[]) };
+        assert_eq!(idle.recovery_nonce(), None);
+    }
+
+    #[test]
+    fn bumps_replacement_fee() {
+        assert_eq!(replacement_fee(U256::from(100)), U256::from(111));
+        assert_eq!(replacement_fee(U256::ZERO), U256::from(1));
+    }
+}
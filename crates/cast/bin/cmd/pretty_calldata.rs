@@ -0,0 +1,578 @@
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::{Function, JsonAbi, Param};
+use alloy_primitives::{hex, keccak256, Address, Bytes, Selector};
+use clap::Parser;
+use eyre::Result;
+use foundry_cli::{
+    opts::{EtherscanOpts, RpcOpts},
+    stdin, utils,
+};
+use foundry_common::{
+    abi::get_func, etherscan::EtherscanClient, fmt::format_token, fs, selectors::SignEthClient,
+};
+use foundry_config::{
+    figment::{self, value::Dict, Metadata, Profile},
+    impl_figment_convert_cast, Config,
+};
+use futures::future::{FutureExt, LocalBoxFuture};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+use yansi::Paint;
+
+/// The maximum depth of wrapped calls that are decoded.
+const MAX_DEPTH: usize = 8;
+
+/// Calls that wrap other calls, by signature.
+const WRAPPERS: &[(&str, Wrapped)] = &[
+    // Multicall, Multicall2
+    ("aggregate((address,bytes)[])", Wrapped::Calls { arg: 0, target: 0, data: 1 }),
+    ("tryAggregate(bool,(address,bytes)[])", Wrapped::Calls { arg: 1, target: 0, data: 1 }),
+    ("blockAndAggregate((address,bytes)[])", Wrapped::Calls { arg: 0, target: 0, data: 1 }),
+    (
+        "tryBlockAndAggregate(bool,(address,bytes)[])",
+        Wrapped::Calls { arg: 1, target: 0, data: 1 },
+    ),
+    // Multicall3
+    ("aggregate3((address,bool,bytes)[])", Wrapped::Calls { arg: 0, target: 0, data: 2 }),
+    (
+        "aggregate3Value((address,bool,uint256,bytes)[])",
+        Wrapped::Calls { arg: 0, target: 0, data: 3 },
+    ),
+    // Uniswap and OpenZeppelin style self multicalls
+    ("multicall(bytes[])", Wrapped::SelfCalls { arg: 0 }),
+    ("multicall(uint256,bytes[])", Wrapped::SelfCalls { arg: 1 }),
+    ("multicall(bytes32,bytes[])", Wrapped::SelfCalls { arg: 1 }),
+    // Gnosis Safe
+    (
+        "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)",
+        Wrapped::Call { target: 0, data: 2 },
+    ),
+    // ERC-4337 EntryPoint v0.6
+    (
+        "handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)",
+        Wrapped::Calls { arg: 0, target: 0, data: 3 },
+    ),
+    // ERC-4337 EntryPoint v0.7
+    (
+        "handleOps((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes)[],address)",
+        Wrapped::Calls { arg: 0, target: 0, data: 3 },
+    ),
+];
+
+/// CLI arguments for `cast pretty-calldata`.
+#[derive(Clone, Debug, Parser)]
+pub struct PrettyCalldataArgs {
+    /// The calldata.
+    calldata: Option<String>,
+
+    /// Skip the https://openchain.xyz and Etherscan lookups.
+    #[arg(long, short)]
+    offline: bool,
+
+    /// The address the calldata is sent to.
+    ///
+    /// The verified ABI of the contract is fetched from Etherscan to decode the calldata with
+    /// the parameter names.
+    #[arg(long, value_name = "ADDRESS")]
+    to: Option<Address>,
+
+    /// Print the decoded calldata as JSON.
+    #[arg(long, short)]
+    json: bool,
+
+    #[command(flatten)]
+    rpc: RpcOpts,
+
+    #[command(flatten)]
+    etherscan: EtherscanOpts,
+}
+
+impl_figment_convert_cast!(PrettyCalldataArgs);
+
+impl figment::Provider for PrettyCalldataArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("PrettyCalldataArgs")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let mut dict = self.rpc.dict();
+        dict.extend(self.etherscan.dict());
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+impl PrettyCalldataArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self);
+        let calldata = hex::decode(stdin::unwrap_line(self.calldata)?)?;
+        if calldata.len() < 4 {
+            eyre::bail!("calldata cannot be less than 4 bytes")
+        }
+
+        let mut resolver = Resolver { local: local_functions(&config), ..Default::default() };
+        if !self.offline {
+            resolver.openchain = SignEthClient::new().ok();
+            if self.to.is_some() {
+                let chain = match config.chain {
+                    Some(chain) => chain,
+                    None if config.eth_rpc_url.is_some() => {
+                        utils::get_chain(None, utils::get_provider(&config)?).await?
+                    }
+                    None => Default::default(),
+                };
+                resolver.etherscan = EtherscanClient::from_config(&config, Some(chain))?;
+            }
+        }
+
+        let decoded = resolver.decode(self.to, &calldata, 0).await;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&decoded)?);
+        } else {
+            print!("{decoded}");
+        }
+        Ok(())
+    }
+}
+
+/// How the calls wrapped by a call are found in its arguments.
+#[derive(Clone, Copy, Debug)]
+enum Wrapped {
+    /// An array of tuples at `arg`, with the target and the calldata of each call at `target`
+    /// and `data`.
+    Calls { arg: usize, target: usize, data: usize },
+    /// An array of calldata to the same contract at `arg`.
+    SelfCalls { arg: usize },
+    /// A single call, with its target and calldata at `target` and `data`.
+    Call { target: usize, data: usize },
+}
+
+impl Wrapped {
+    /// Returns the target and the calldata of the wrapped calls.
+    fn calls(&self, args: &[DynSolValue], to: Option<Address>) -> Vec<(Option<Address>, Bytes)> {
+        match *self {
+            Self::Calls { arg, target, data } => args
+                .get(arg)
+                .and_then(DynSolValue::as_array)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|call| {
+                    let call = call.as_tuple()?;
+                    Some((
+                        call.get(target)?.as_address(),
+                        call.get(data)?.as_bytes()?.to_vec().into(),
+                    ))
+                })
+                .collect(),
+            Self::SelfCalls { arg } => args
+                .get(arg)
+                .and_then(DynSolValue::as_array)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|data| Some((to, data.as_bytes()?.to_vec().into())))
+                .collect(),
+            Self::Call { target, data } => (|| {
+                Some(vec![(
+                    args.get(target)?.as_address(),
+                    args.get(data)?.as_bytes()?.to_vec().into(),
+                )])
+            })()
+            .unwrap_or_default(),
+        }
+    }
+}
+
+/// Where the ABI used to decode calldata comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolutionSource {
+    /// The artifacts of the local project.
+    Local,
+    /// The verified source of the target contract on Etherscan.
+    Etherscan,
+    /// A signature of https://openchain.xyz, without parameter names.
+    Openchain,
+}
+
+impl fmt::Display for ResolutionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => f.write_str("local artifacts"),
+            Self::Etherscan => f.write_str("Etherscan"),
+            Self::Openchain => f.write_str("openchain.xyz"),
+        }
+    }
+}
+
+/// A decoded argument of a call.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DecodedArg {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub value: String,
+}
+
+/// Calldata decoded into its function, arguments and wrapped calls.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedCalldata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    pub selector: Selector,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<ResolutionSource>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<DecodedArg>,
+    /// The encoded arguments, if the calldata couldn't be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Bytes>,
+    /// The decoded wrapped calls, e.g. the calls of a multicall.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inner: Vec<DecodedCalldata>,
+}
+
+impl DecodedCalldata {
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        let pad = " ".repeat(indent);
+        if let Some(to) = self.to {
+            writeln!(f, "{pad} To: {to}")?;
+        }
+        match (&self.signature, self.source) {
+            (Some(signature), Some(ResolutionSource::Openchain)) => writeln!(
+                f,
+                "{pad} Method: {signature} {}",
+                Paint::yellow(
+                    "(guessed from openchain.xyz, the ABI of the contract was not found)"
+                )
+            )?,
+            (Some(signature), Some(source)) => {
+                writeln!(f, "{pad} Method: {signature} (from {source})")?
+            }
+            _ => writeln!(f, "{pad} Method: {}", self.selector)?,
+        }
+
+        writeln!(f, "{pad} ------------")?;
+        for (i, arg) in self.args.iter().enumerate() {
+            let name = if arg.name.is_empty() { format!("[{i}]") } else { arg.name.clone() };
+            writeln!(f, "{pad} {name} ({}): {}", arg.ty, arg.value)?;
+        }
+        if let Some(raw) = &self.raw {
+            for (i, row) in raw.chunks(32).enumerate() {
+                writeln!(f, "{pad} [{:03x}]: {}", i * 32, hex::encode(row))?;
+            }
+        }
+
+        for (i, inner) in self.inner.iter().enumerate() {
+            writeln!(f, "\n{pad} Inner call {i}:")?;
+            inner.fmt_indented(f, indent + 2)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DecodedCalldata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f)?;
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Resolves selectors to functions, from the local artifacts, then the verified ABI of the target
+/// contract on Etherscan, then https://openchain.xyz.
+#[derive(Default)]
+struct Resolver {
+    local: HashMap<Selector, Vec<Function>>,
+    etherscan: Option<EtherscanClient>,
+    etherscan_abis: HashMap<Address, Option<JsonAbi>>,
+    openchain: Option<SignEthClient>,
+}
+
+impl Resolver {
+    /// Decodes the calldata sent to `to`, and the calls it wraps.
+    fn decode<'a>(
+        &'a mut self,
+        to: Option<Address>,
+        calldata: &'a [u8],
+        depth: usize,
+    ) -> LocalBoxFuture<'a, DecodedCalldata> {
+        async move {
+            let (selector, data) = calldata.split_at(4);
+            let selector = Selector::from_slice(selector);
+            let mut decoded = DecodedCalldata {
+                to,
+                selector,
+                signature: None,
+                source: None,
+                args: vec![],
+                raw: None,
+                inner: vec![],
+            };
+
+            let Some((function, values, source)) = self.resolve(to, selector, data).await else {
+                decoded.raw = Some(data.to_vec().into());
+                return decoded
+            };
+            decoded.signature = Some(function.signature());
+            decoded.source = Some(source);
+            decoded.args = function
+                .inputs
+                .iter()
+                .zip(&values)
+                .map(|(param, value)| DecodedArg {
+                    name: param.name.clone(),
+                    ty: param.selector_type().into_owned(),
+                    value: format_value(value, param),
+                })
+                .collect();
+
+            if depth < MAX_DEPTH {
+                if let Some((_, wrapped)) =
+                    WRAPPERS.iter().find(|(signature, _)| signature_selector(signature) == selector)
+                {
+                    for (target, calldata) in wrapped.calls(&values, to) {
+                        if calldata.len() >= 4 {
+                            decoded.inner.push(self.decode(target, &calldata, depth + 1).await);
+                        }
+                    }
+                }
+            }
+            decoded
+        }
+        .boxed_local()
+    }
+
+    /// Returns the function with the given selector that decodes `data`, along with the decoded
+    /// arguments and where the function was found.
+    async fn resolve(
+        &mut self,
+        to: Option<Address>,
+        selector: Selector,
+        data: &[u8],
+    ) -> Option<(Function, Vec<DynSolValue>, ResolutionSource)> {
+        let decode = |function: &Function| function.abi_decode_input(data, false).ok();
+
+        if let Some(found) = self.local.get(&selector).and_then(|functions| {
+            functions.iter().find_map(|f| Some((f.clone(), decode(f)?, ResolutionSource::Local)))
+        }) {
+            return Some(found)
+        }
+
+        if let Some(to) = to {
+            if let Some(abi) = self.etherscan_abi(to).await {
+                if let Some(found) = abi
+                    .functions()
+                    .filter(|f| f.selector() == selector)
+                    .find_map(|f| Some((f.clone(), decode(f)?, ResolutionSource::Etherscan)))
+                {
+                    return Some(found)
+                }
+            }
+        }
+
+        let openchain = self.openchain.as_ref()?;
+        let signatures =
+            openchain.decode_function_selector(&hex::encode(selector)).await.unwrap_or_default();
+        signatures.iter().filter_map(|signature| get_func(signature).ok()).find_map(|f| {
+            let values = decode(&f)?;
+            Some((f, values, ResolutionSource::Openchain))
+        })
+    }
+
+    /// Returns the verified ABI of the contract at `address`, or of its implementation if it is a
+    /// proxy.
+    async fn etherscan_abi(&mut self, address: Address) -> Option<&JsonAbi> {
+        if !self.etherscan_abis.contains_key(&address) {
+            let abi = match &self.etherscan {
+                Some(client) => fetch_abi(client, address).await,
+                None => None,
+            };
+            self.etherscan_abis.insert(address, abi);
+        }
+        self.etherscan_abis.get(&address)?.as_ref()
+    }
+}
+
+/// Returns the function of the verified ABI of the contract at `address` on Etherscan designated
+/// by `sig`, its name or its signature.
+pub async fn etherscan_function(
+    address: Address,
+    sig: &str,
+    etherscan: &EtherscanOpts,
+) -> Result<Function> {
+    let config = Config::from(etherscan);
+    let Some(client) = EtherscanClient::from_config(&config, config.chain)? else {
+        eyre::bail!("an Etherscan API key is required to fetch the ABI of {address}")
+    };
+    let abi = fetch_abi(&client, address).await.ok_or_else(|| {
+        eyre::eyre!("could not fetch the verified ABI of {address} from Etherscan")
+    })?;
+    let selector = if sig.contains('(') { Some(get_func(sig)?.selector()) } else { None };
+    let functions = abi
+        .functions()
+        .filter(|function| match selector {
+            Some(selector) => function.selector() == selector,
+            None => function.name == sig,
+        })
+        .collect::<Vec<_>>();
+    match functions.as_slice() {
+        [function] => Ok((*function).clone()),
+        [] => eyre::bail!("no function `{sig}` in the verified ABI of {address}"),
+        _ => eyre::bail!(
+            "`{sig}` is ambiguous in the verified ABI of {address}, pass one of: {}",
+            functions.iter().map(|function| function.signature()).format(", ")
+        ),
+    }
+}
+
+/// Fetches the verified ABI of the contract at `address` from Etherscan, following proxies once.
+async fn fetch_abi(client: &EtherscanClient, address: Address) -> Option<JsonAbi> {
+    let source = client
+        .request(|client| client.contract_source_code(address))
+        .await
+        .map_err(|err| warn!(%address, %err, "failed to fetch the ABI from Etherscan"))
+        .ok()?;
+    let metadata = source.items.first()?;
+    if metadata.proxy != 0 {
+        if let Some(implementation) = metadata.implementation {
+            if let Ok(source) =
+                client.request(|client| client.contract_source_code(implementation)).await
+            {
+                if let Some(abi) = source.items.first().and_then(|item| item.abi().ok()) {
+                    return Some(abi)
+                }
+            }
+        }
+    }
+    metadata.abi().ok()
+}
+
+/// The ABI of an artifact of the local project.
+#[derive(Deserialize)]
+struct ArtifactAbi {
+    abi: JsonAbi,
+}
+
+/// Returns the functions of the artifacts of the local project, by selector.
+fn local_functions(config: &Config) -> HashMap<Selector, Vec<Function>> {
+    let mut functions = HashMap::<Selector, Vec<Function>>::new();
+    let build_info = config.out.join("build-info");
+    for path in fs::json_files(&config.out) {
+        if path.starts_with(&build_info) {
+            continue
+        }
+        let Ok(artifact) = fs::read_json_file::<ArtifactAbi>(&path) else { continue };
+        for function in artifact.abi.functions() {
+            let known = functions.entry(function.selector()).or_default();
+            if !known.contains(function) {
+                known.push(function.clone());
+            }
+        }
+    }
+    functions
+}
+
+/// Returns the selector of a function signature.
+fn signature_selector(signature: &str) -> Selector {
+    Selector::from_slice(&keccak256(signature)[..4])
+}
+
+/// Formats a decoded value, with the names of the tuple components.
+fn format_value(value: &DynSolValue, param: &Param) -> String {
+    match value {
+        DynSolValue::Tuple(values) if values.len() == param.components.len() => format!(
+            "({})",
+            values
+                .iter()
+                .zip(&param.components)
+                .map(|(value, param)| {
+                    let value = format_value(value, param);
+                    if param.name.is_empty() {
+                        value
+                    } else {
+                        format!("{}: {value}", param.name)
+                    }
+                })
+                .format(", ")
+        ),
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values)
+            if !param.components.is_empty() =>
+        {
+            format!("[{}]", values.iter().map(|value| format_value(value, param)).format(", "))
+        }
+        _ => format_token(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+
+    #[tokio::test]
+    async fn decodes_wrapped_calls() {
+        let aggregate3 = get_func(
+            "function aggregate3((address target, bool allowFailure, bytes callData)[] calls)",
+        )
+        .unwrap();
+        let transfer = get_func("function transfer(address to, uint256 amount)").unwrap();
+        let mut resolver = Resolver::default();
+        for function in [&aggregate3, &transfer] {
+            resolver.local.insert(function.selector(), vec![function.clone()]);
+        }
+
+        let token = Address::repeat_byte(1);
+        let transfer = transfer
+            .abi_encode_input(&[
+                DynSolValue::Address(Address::repeat_byte(2)),
+                DynSolValue::Uint(U256::from(1), 256),
+            ])
+            .unwrap();
+        let unknown = vec![0xde, 0xad, 0xbe, 0xef];
+        let calls = |data: Vec<u8>| {
+            DynSolValue::Tuple(vec![
+                DynSolValue::Address(token),
+                DynSolValue::Bool(false),
+                DynSolValue::Bytes(data),
+            ])
+        };
+        let calldata = aggregate3
+            .abi_encode_input(&[DynSolValue::Array(vec![calls(transfer), calls(unknown)])])
+            .unwrap();
+
+        let decoded = resolver.decode(None, &calldata, 0).await;
+        assert_eq!(decoded.signature.as_deref(), Some("aggregate3((address,bool,bytes)[])"));
+        assert_eq!(decoded.source, Some(ResolutionSource::Local));
+        assert!(decoded.args[0]
+            .value
+            .starts_with(&format!("[(target: {token}, allowFailure: false")));
+        assert_eq!(decoded.inner.len(), 2);
+
+        let inner = &decoded.inner[0];
+        assert_eq!(inner.to, Some(token));
+        assert_eq!(inner.signature.as_deref(), Some("transfer(address,uint256)"));
+        assert_eq!(
+            inner.args,
+            [
+                DecodedArg {
+                    name: "to".to_string(),
+                    ty: "address".to_string(),
+                    value: Address::repeat_byte(2).to_string()
+                },
+                DecodedArg {
+                    name: "amount".to_string(),
+                    ty: "uint256".to_string(),
+                    value: "1".to_string()
+                },
+            ]
+        );
+
+        let inner = &decoded.inner[1];
+        assert_eq!(inner.selector, Selector::from([0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(inner.source, None);
+        assert_eq!(inner.raw, Some(Bytes::new()));
+    }
+}
@@ -1,7 +1,9 @@
-use alloy_primitives::U256;
+use alloy_primitives::{Address, Bytes, B256, U256};
 use alloy_providers::provider::TempProvider;
-use alloy_rpc_types::BlockTransactions;
+use alloy_rpc_types::{request::TransactionRequest, AccessList, BlockTransactions};
+use cast::SimpleCast;
 use clap::Parser;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
 use eyre::{Result, WrapErr};
 use foundry_cli::{
     init_progress,
@@ -9,20 +11,56 @@ use foundry_cli::{
     update_progress,
     utils::{handle_traces, TraceResult},
 };
-use foundry_common::{is_known_system_sender, SYSTEM_TRANSACTION_TYPE};
+use foundry_common::{fs, is_known_system_sender, types::ToAlloy, SYSTEM_TRANSACTION_TYPE};
 use foundry_compilers::EvmVersion;
 use foundry_config::{find_project_root_path, Config};
 use foundry_evm::{
-    executors::{EvmError, TracingExecutor},
+    executors::{EvmError, RawCallResult, TracingExecutor},
     opts::EvmOpts,
+    revm::{
+        primitives::{BlockEnv, Env, TransactTo},
+        DatabaseCommit, DatabaseRef,
+    },
     utils::configure_tx_env,
 };
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 /// CLI arguments for `cast run`.
 #[derive(Clone, Debug, Parser)]
 pub struct RunArgs {
     /// The transaction hash.
-    tx_hash: String,
+    #[arg(required_unless_present = "bundle")]
+    tx_hash: Option<String>,
+
+    /// Executes a bundle of transactions in order, with shared state, instead of a mined
+    /// transaction.
+    ///
+    /// The file contains a JSON array of raw signed transactions and transaction requests.
+    /// Requests are executed as impersonated calls from their `from` address.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["tx_hash", "quick", "debug"])]
+    bundle: Option<PathBuf>,
+
+    /// The block whose state the bundle is executed on.
+    ///
+    /// The bundle is executed in the block after it. Defaults to the latest block.
+    #[arg(long, requires = "bundle")]
+    block: Option<u64>,
+
+    /// Overrides fields of the header of the simulated block.
+    ///
+    /// Example: timestamp=1700000000,basefee=10000000000,number=18600000
+    #[arg(long, value_name = "FIELD=VALUE,...")]
+    block_override: Option<BlockOverrides>,
+
+    /// Print the transactions, logs, traces and state diff as JSON.
+    #[arg(long, requires = "bundle")]
+    json: bool,
 
     /// Opens the transaction in the debugger.
     #[arg(long, short)]
@@ -32,6 +70,10 @@ pub struct RunArgs {
     #[arg(long, short)]
     trace_printer: bool,
 
+    /// Decode the internal function calls of the verified contracts in the trace.
+    #[arg(long, conflicts_with = "debug")]
+    decode_internal: bool,
+
     /// Executes the transaction only with the state from the previous block.
     ///
     /// May result in different results than the live execution!
@@ -95,7 +137,12 @@ impl RunArgs {
         .compute_units_per_second_opt(compute_units_per_second)
         .build()?;
 
-        let tx_hash = self.tx_hash.parse().wrap_err("invalid tx hash")?;
+        if let Some(bundle) = &self.bundle {
+            return self.run_bundle(bundle, &provider, config, evm_opts).await
+        }
+
+        let tx_hash = self.tx_hash.as_deref().unwrap_or_default();
+        let tx_hash = tx_hash.parse().wrap_err("invalid tx hash")?;
         let tx = provider
             .get_transaction_by_hash(tx_hash)
             .await
@@ -134,6 +181,9 @@ impl RunArgs {
             env.block.basefee = block.header.base_fee_per_gas.unwrap_or_default();
             env.block.gas_limit = block.header.gas_limit;
         }
+        if let Some(overrides) = &self.block_override {
+            overrides.apply(&mut env.block);
+        }
 
         // Set the state to the moment right before the transaction
         if !self.quick {
@@ -198,6 +248,7 @@ impl RunArgs {
         // Execute our transaction
         let result = {
             executor.set_trace_printer(self.trace_printer);
+            executor.inspector.tracing_internal_calls(self.decode_internal);
 
             configure_tx_env(&mut env, &tx);
 
@@ -213,8 +264,378 @@ impl RunArgs {
             }
         };
 
-        handle_traces(result, &config, chain, self.label, self.debug).await?;
+        handle_traces(result, &config, chain, self.label, self.debug, self.decode_internal).await?;
+
+        Ok(())
+    }
+
+    /// Executes the transactions of the bundle in order on top of the base block, printing the
+    /// result of every transaction and the state diff of the whole bundle.
+    async fn run_bundle(
+        &self,
+        path: &Path,
+        provider: &foundry_common::provider::alloy::RetryProvider,
+        mut config: Config,
+        evm_opts: EvmOpts,
+    ) -> Result<()> {
+        let entries: Vec<BundleEntry> = fs::read_json_file(path)?;
+
+        let base_block = match self.block {
+            Some(block) => block,
+            None => provider.get_block_number().await?.to::<u64>(),
+        };
+        config.fork_block_number = Some(base_block);
+
+        let (mut env, fork, chain) = TracingExecutor::get_fork_material(&config, evm_opts).await?;
+        let mut executor = TracingExecutor::new(env.clone(), fork, self.evm_version, false);
+        executor.set_trace_printer(self.trace_printer);
+        executor.inspector.tracing_internal_calls(self.decode_internal);
+
+        env.block.number = U256::from(base_block + 1);
+        if let Some(overrides) = &self.block_override {
+            overrides.apply(&mut env.block);
+        }
+
+        let mut transactions = Vec::with_capacity(entries.len());
+        let mut diff = StateDiff::default();
+        for (index, entry) in entries.into_iter().enumerate() {
+            let mut tx_env = env.clone();
+            let hash = entry
+                .configure_env(&mut tx_env)
+                .wrap_err_with(|| format!("invalid bundle transaction {index}"))?;
+            let from = tx_env.tx.caller;
+            let to = match tx_env.tx.transact_to {
+                TransactTo::Call(to) => Some(to),
+                TransactTo::Create(_) => None,
+            };
+
+            let mut result = executor
+                .call_raw_with_env(tx_env)
+                .wrap_err_with(|| format!("failed to execute bundle transaction {index}"))?;
+            if let Some(changeset) = result.state_changeset.take() {
+                diff.record(&executor.backend, &changeset)?;
+                executor.backend.commit(changeset);
+            }
+
+            if self.json {
+                transactions.push(bundle_tx_json(index, hash, from, to, &result));
+            } else {
+                let status = if result.reverted { "reverted" } else { "succeeded" };
+                let name = hash.map_or_else(|| format!("from {from}"), |hash| hash.to_string());
+                println!("Transaction {index} ({name}) {status}, gas used: {}", result.gas_used);
+                handle_traces(
+                    TraceResult::from(result),
+                    &config,
+                    chain,
+                    self.label.clone(),
+                    false,
+                    self.decode_internal,
+                )
+                .await?;
+            }
+        }
+
+        if self.json {
+            let output = json!({ "transactions": transactions, "stateDiff": diff.to_json() });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            diff.print();
+        }
+
+        Ok(())
+    }
+}
+
+/// Overrides of the header fields of the simulated block, parsed from `field=value` pairs.
+#[derive(Clone, Debug, Default)]
+pub struct BlockOverrides {
+    number: Option<U256>,
+    timestamp: Option<U256>,
+    basefee: Option<U256>,
+}
+
+impl BlockOverrides {
+    fn apply(&self, block: &mut BlockEnv) {
+        if let Some(number) = self.number {
+            block.number = number;
+        }
+        if let Some(timestamp) = self.timestamp {
+            block.timestamp = timestamp;
+        }
+        if let Some(basefee) = self.basefee {
+            block.basefee = basefee;
+        }
+    }
+}
+
+impl FromStr for BlockOverrides {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut overrides = Self::default();
+        for pair in s.split(',') {
+            let (field, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected `field=value`, got `{pair}`"))?;
+            let value = U256::from_str(value.trim())
+                .map_err(|err| format!("invalid value for `{field}`: {err}"))?;
+            match field.trim() {
+                "number" => overrides.number = Some(value),
+                "timestamp" => overrides.timestamp = Some(value),
+                "basefee" => overrides.basefee = Some(value),
+                field => {
+                    return Err(format!(
+                        "unknown block field `{field}`, expected `number`, `timestamp` or `basefee`"
+                    ))
+                }
+            }
+        }
+        Ok(overrides)
+    }
+}
+
+/// A transaction of a bundle: either a raw signed transaction, or a request executed as an
+/// impersonated call.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum BundleEntry {
+    Raw(String),
+    Request(TransactionRequest),
+}
+
+impl BundleEntry {
+    /// Configures the transaction of `env`, returning the hash of a raw transaction.
+    fn configure_env(self, env: &mut Env) -> Result<Option<B256>> {
+        match self {
+            Self::Raw(raw) => {
+                let (tx, sig) = SimpleCast::decode_raw_transaction(&raw)?;
+                let from = sig.recover(tx.sighash())?;
+                env.tx.caller = from.to_alloy();
+                env.tx.gas_limit = tx.gas().map_or(env.block.gas_limit.to(), |gas| gas.as_u64());
+                env.tx.gas_price = tx.gas_price().unwrap_or_default().to_alloy();
+                env.tx.gas_priority_fee = match &tx {
+                    TypedTransaction::Eip1559(tx) => {
+                        tx.max_priority_fee_per_gas.map(|fee| fee.to_alloy())
+                    }
+                    _ => None,
+                };
+                env.tx.nonce = tx.nonce().map(|nonce| nonce.as_u64());
+                env.tx.chain_id = tx.chain_id().map(|id| id.as_u64());
+                env.tx.value = tx.value().copied().unwrap_or_default().to_alloy();
+                env.tx.data = tx.data().cloned().unwrap_or_default().to_alloy();
+                env.tx.access_list =
+                    revm_access_list(tx.access_list().cloned().unwrap_or_default().to_alloy());
+                env.tx.transact_to = tx
+                    .to_addr()
+                    .map(|to| TransactTo::Call(to.to_alloy()))
+                    .unwrap_or_else(TransactTo::create);
+                Ok(Some(tx.hash(&sig).to_alloy()))
+            }
+            Self::Request(request) => {
+                let TransactionRequest {
+                    from,
+                    to,
+                    gas,
+                    gas_price,
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                    value,
+                    input,
+                    nonce,
+                    access_list,
+                    ..
+                } = request;
+                env.tx.caller = from.ok_or_else(|| eyre::eyre!("missing `from` address"))?;
+                env.tx.gas_limit = gas.unwrap_or(env.block.gas_limit).to();
+                env.tx.gas_price = gas_price.or(max_fee_per_gas).unwrap_or_default();
+                env.tx.gas_priority_fee = max_priority_fee_per_gas;
+                env.tx.nonce = nonce.map(|nonce| nonce.to());
+                env.tx.chain_id = None;
+                env.tx.value = value.unwrap_or_default();
+                env.tx.data = input.into_input().unwrap_or_default();
+                env.tx.access_list = revm_access_list(access_list.unwrap_or_default());
+                env.tx.transact_to = to.map(TransactTo::Call).unwrap_or_else(TransactTo::create);
+                // impersonated calls aren't priced unless requested
+                env.cfg.disable_base_fee = env.tx.gas_price.is_zero();
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn revm_access_list(list: AccessList) -> Vec<(Address, Vec<U256>)> {
+    list.0
+        .into_iter()
+        .map(|item| {
+            let keys =
+                item.storage_keys.into_iter().map(|key| U256::from_be_bytes(key.0)).collect();
+            (item.address, keys)
+        })
+        .collect()
+}
+
+/// The changes a bundle made to an account, from before its first transaction to after its last.
+#[derive(Debug, Default)]
+struct AccountDiff {
+    balance: (U256, U256),
+    nonce: (u64, u64),
+    storage: BTreeMap<U256, (U256, U256)>,
+}
+
+/// The state changes of a bundle.
+#[derive(Debug, Default)]
+struct StateDiff {
+    accounts: BTreeMap<Address, AccountDiff>,
+}
 
+impl StateDiff {
+    /// Records the changes of a transaction, reading the previous values of newly changed
+    /// accounts from `db` before the changes are committed to it.
+    fn record<DB: DatabaseRef>(
+        &mut self,
+        db: &DB,
+        changeset: &foundry_evm::utils::StateChangeset,
+    ) -> Result<()>
+    where
+        DB::Error: std::error::Error + Send + Sync + 'static,
+    {
+        for (address, account) in changeset {
+            if !account.is_touched() {
+                continue
+            }
+            let diff = match self.accounts.entry(*address) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let info = db.basic_ref(*address)?.unwrap_or_default();
+                    entry.insert(AccountDiff {
+                        balance: (info.balance, info.balance),
+                        nonce: (info.nonce, info.nonce),
+                        ..Default::default()
+                    })
+                }
+            };
+            diff.balance.1 = account.info.balance;
+            diff.nonce.1 = account.info.nonce;
+            for (slot, value) in account.storage.iter().filter(|(_, value)| value.is_changed()) {
+                diff.storage
+                    .entry(*slot)
+                    .or_insert((value.original_value, value.present_value))
+                    .1 = value.present_value;
+            }
+        }
         Ok(())
     }
+
+    /// Returns the accounts whose balance, nonce or storage changed.
+    fn changed(&self) -> impl Iterator<Item = (&Address, &AccountDiff)> {
+        self.accounts.iter().filter(|(_, diff)| {
+            diff.balance.0 != diff.balance.1 ||
+                diff.nonce.0 != diff.nonce.1 ||
+                diff.storage.values().any(|(old, new)| old != new)
+        })
+    }
+
+    fn print(&self) {
+        println!("State diff:");
+        for (address, diff) in self.changed() {
+            println!("  {address}:");
+            if diff.balance.0 != diff.balance.1 {
+                println!("    balance: {} -> {}", diff.balance.0, diff.balance.1);
+            }
+            if diff.nonce.0 != diff.nonce.1 {
+                println!("    nonce: {} -> {}", diff.nonce.0, diff.nonce.1);
+            }
+            for (slot, (old, new)) in diff.storage.iter().filter(|(_, (old, new))| old != new) {
+                println!("    {}: {} -> {}", B256::from(*slot), B256::from(*old), B256::from(*new));
+            }
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let accounts = self
+            .changed()
+            .map(|(address, diff)| {
+                let storage = diff
+                    .storage
+                    .iter()
+                    .filter(|(_, (old, new))| old != new)
+                    .map(|(slot, (old, new))| {
+                        (
+                            B256::from(*slot).to_string(),
+                            json!({ "from": B256::from(*old), "to": B256::from(*new) }),
+                        )
+                    })
+                    .collect::<serde_json::Map<_, _>>();
+                let diff = json!({
+                    "balance": { "from": diff.balance.0, "to": diff.balance.1 },
+                    "nonce": { "from": diff.nonce.0, "to": diff.nonce.1 },
+                    "storage": storage,
+                });
+                (address.to_string(), diff)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        Value::Object(accounts)
+    }
+}
+
+/// Returns the result of a bundle transaction, with its logs and call traces, as JSON.
+fn bundle_tx_json(
+    index: usize,
+    hash: Option<B256>,
+    from: Address,
+    to: Option<Address>,
+    result: &RawCallResult,
+) -> Value {
+    let logs = result
+        .logs
+        .iter()
+        .map(|log| json!({ "address": log.address, "topics": log.topics(), "data": log.data.data }))
+        .collect::<Vec<_>>();
+    let traces = result
+        .traces
+        .iter()
+        .flat_map(|arena| arena.nodes())
+        .map(|node| {
+            let trace = &node.trace;
+            json!({
+                "depth": trace.depth,
+                "kind": format!("{:?}", trace.kind),
+                "caller": trace.caller,
+                "address": trace.address,
+                "value": trace.value,
+                "input": Bytes::from(trace.data.to_vec()),
+                "output": Bytes::from(trace.output.to_vec()),
+                "gasUsed": trace.gas_used,
+                "success": trace.success,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "index": index,
+        "hash": hash,
+        "from": from,
+        "to": to,
+        "success": !result.reverted,
+        "gasUsed": result.gas_used,
+        "output": result.result,
+        "logs": logs,
+        "traces": traces,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_block_overrides() {
+        let overrides: BlockOverrides =
+            "timestamp=1700000000, basefee=0x10,number=5".parse().unwrap();
+        assert_eq!(overrides.timestamp, Some(U256::from(1_700_000_000)));
+        assert_eq!(overrides.basefee, Some(U256::from(16)));
+        assert_eq!(overrides.number, Some(U256::from(5)));
+
+        assert!("gaslimit=1".parse::<BlockOverrides>().is_err());
+        assert!("timestamp".parse::<BlockOverrides>().is_err());
+    }
 }
@@ -1,4 +1,8 @@
-use crate::tx;
+use crate::{
+    cmd::nonce_gaps::{replacement_fee, NonceGaps},
+    tx,
+};
+use alloy_primitives::{Address, U256};
 use cast::Cast;
 use clap::Parser;
 use ethers_core::types::NameOrAddress;
@@ -48,6 +52,17 @@ pub struct SendTxArgs {
     #[arg(long, conflicts_with = "nonce")]
     resend: bool,
 
+    /// Send the transaction at the lowest missing nonce of the sender, with a fee high enough to
+    /// replace a transaction stuck at that nonce.
+    ///
+    /// Without a recipient, a zero-value transfer to the sender is sent.
+    #[arg(long, conflicts_with_all = &["nonce", "resend"])]
+    fill_nonce_gap: bool,
+
+    /// Skip the confirmation required to fill a nonce gap on mainnet.
+    #[arg(long, visible_alias = "confirm", requires = "fill_nonce_gap")]
+    yes: bool,
+
     #[command(subcommand)]
     command: Option<SendTxSubcommands>,
 
@@ -67,7 +82,8 @@ pub enum SendTxSubcommands {
     /// Use to deploy raw contract bytecode.
     #[command(name = "--create")]
     Create {
-        /// The bytecode of the contract to deploy.
+        /// The bytecode of the contract to deploy, or its name in the project (`ContractName` or
+        /// `path:ContractName`).
         code: String,
 
         /// The signature of the function to call.
@@ -75,6 +91,18 @@ pub enum SendTxSubcommands {
 
         /// The arguments of the function to call.
         args: Vec<String>,
+
+        /// The addresses to link the libraries of the contract with.
+        ///
+        /// Only with a contract name, in the form `<path>:<name>:<address>`.
+        #[arg(long, value_name = "LIBRARIES")]
+        libraries: Vec<String>,
+
+        /// The constructor arguments of the contract.
+        ///
+        /// Only with a contract name, whose constructor is read from its artifact.
+        #[arg(long, num_args(1..), value_name = "ARGS")]
+        constructor_args: Vec<String>,
     },
 }
 
@@ -82,7 +110,7 @@ impl SendTxArgs {
     pub async fn run(self) -> Result<()> {
         let SendTxArgs {
             eth,
-            to,
+            mut to,
             mut sig,
             cast_async,
             mut args,
@@ -90,6 +118,8 @@ impl SendTxArgs {
             confirmations,
             json: to_json,
             resend,
+            fill_nonce_gap,
+            yes,
             command,
             unlocked,
         } = self;
@@ -97,17 +127,32 @@ impl SendTxArgs {
         let code = if let Some(SendTxSubcommands::Create {
             code,
             sig: constructor_sig,
-            args: constructor_args,
+            args: constructor_sig_args,
+            libraries,
+            constructor_args,
         }) = command
         {
-            sig = constructor_sig;
-            args = constructor_args;
-            Some(code)
+            if let Some(creation_code) =
+                tx::contract_creation_code(&code, &libraries, &constructor_args)?
+            {
+                if constructor_sig.is_some() {
+                    eyre::bail!("the constructor of {code} is read from its artifact, pass its arguments with `--constructor-args`")
+                }
+                sig = None;
+                args = vec![];
+                Some(hex::encode(creation_code))
+            } else {
+                sig = constructor_sig;
+                args = constructor_sig_args;
+                Some(code)
+            }
         } else {
             None
         };
 
-        tx::validate_to_address(&code, &to)?;
+        if !fill_nonce_gap {
+            tx::validate_to_address(&code, &to)?;
+        }
 
         let config = Config::from(&eth);
         let provider = utils::get_provider(&config)?;
@@ -147,6 +192,10 @@ impl SendTxArgs {
                 );
             }
 
+            if fill_nonce_gap {
+                prepare_nonce_gap_fill(&provider, config.sender, &mut to, &code, &mut tx).await?;
+            }
+
             cast_send(
                 provider,
                 config.sender.to_ethers(),
@@ -160,6 +209,8 @@ impl SendTxArgs {
                 cast_async,
                 confirmations,
                 to_json,
+                fill_nonce_gap,
+                yes,
             )
             .await
         // Case 2:
@@ -177,6 +228,10 @@ impl SendTxArgs {
                 tx.nonce = Some(provider.get_transaction_count(from, None).await?.to_alloy());
             }
 
+            if fill_nonce_gap {
+                prepare_nonce_gap_fill(&provider, from.to_alloy(), &mut to, &code, &mut tx).await?;
+            }
+
             let provider = SignerMiddleware::new_with_provider_chain(provider, signer).await?;
 
             cast_send(
@@ -192,6 +247,8 @@ impl SendTxArgs {
                 cast_async,
                 confirmations,
                 to_json,
+                fill_nonce_gap,
+                yes,
             )
             .await
         }
@@ -212,6 +269,8 @@ async fn cast_send<M: Middleware, F: Into<NameOrAddress>, T: Into<NameOrAddress>
     cast_async: bool,
     confs: usize,
     to_json: bool,
+    preview: bool,
+    yes: bool,
 ) -> Result<()>
 where
     M::Error: 'static,
@@ -219,6 +278,13 @@ where
     let builder_output =
         tx::build_tx(&provider, from, to, code, sig, args, tx, chain, etherscan_api_key).await?;
 
+    if preview {
+        eprintln!("Transaction to send:\n{}", serde_json::to_string_pretty(&builder_output.0)?);
+        if chain.id() == 1 && !yes {
+            eyre::bail!("refusing to send on mainnet without confirmation, review the transaction above and pass `--yes` to send it")
+        }
+    }
+
     let cast = Cast::new(provider);
 
     let pending_tx = cast.send(builder_output).await?;
@@ -233,3 +299,49 @@ where
 
     Ok(())
 }
+
+/// Sets the nonce of `tx` to the lowest missing nonce of `from`, and its fees high enough to
+/// replace the transaction stuck at that nonce, unless they were given.
+///
+/// Without a recipient or code, the transaction is a transfer to `from`.
+async fn prepare_nonce_gap_fill<M: Middleware>(
+    provider: &M,
+    from: Address,
+    to: &mut Option<NameOrAddress>,
+    code: &Option<String>,
+    tx: &mut TransactionOpts,
+) -> Result<()>
+where
+    M::Error: 'static,
+{
+    let gaps = NonceGaps::fetch(provider, from).await?;
+    eprintln!("{}", gaps.report());
+    let nonce = gaps
+        .recovery_nonce()
+        .ok_or_else(|| eyre::eyre!("{from} has no nonce gap or stuck transaction"))?;
+    tx.nonce = Some(U256::from(nonce));
+
+    if to.is_none() && code.is_none() {
+        *to = Some(from.to_ethers().into());
+    }
+
+    match gaps.pooled(nonce) {
+        Some(stuck) => {
+            let stuck_fee = stuck.max_fee_per_gas.or(stuck.gas_price).unwrap_or_default();
+            let stuck_tip = stuck.max_priority_fee_per_gas.unwrap_or(stuck_fee);
+            let network_fee = provider.get_gas_price().await?.to_alloy();
+            tx.gas_price.get_or_insert(replacement_fee(stuck_fee.to_alloy()).max(network_fee));
+            if !tx.legacy {
+                tx.priority_gas_price.get_or_insert(replacement_fee(stuck_tip.to_alloy()));
+            }
+            eprintln!("Replacing transaction {:?} at nonce {nonce}", stuck.hash);
+        }
+        None if gaps.pool.is_none() && tx.gas_price.is_none() => {
+            cli_warn!(
+                "The fee of a transaction stuck at nonce {nonce} is unknown, pass `--gas-price` and `--priority-gas-price` to outbid it"
+            );
+        }
+        None => eprintln!("Filling nonce gap at nonce {nonce}"),
+    }
+    Ok(())
+}
@@ -25,6 +25,10 @@ pub struct ListArgs {
     #[arg(long)]
     aws: bool,
 
+    /// List the accounts of the given GCP KMS key versions.
+    #[arg(long, value_name = "KEY_RESOURCE_NAMES", value_delimiter = ',')]
+    gcp_kms: Option<Vec<String>>,
+
     /// List all configured accounts.
     #[arg(long, group = "hw-wallets")]
     all: bool,
@@ -37,16 +41,20 @@ pub struct ListArgs {
 impl ListArgs {
     pub async fn run(self) -> Result<()> {
         // list local accounts as files in keystore dir, no need to unlock / provide password
-        if self.dir.is_some() || self.all || (!self.ledger && !self.trezor && !self.aws) {
+        if self.dir.is_some() ||
+            self.all ||
+            (!self.ledger && !self.trezor && !self.aws && self.gcp_kms.is_none())
+        {
             let _ = self.list_local_senders();
         }
 
-        // Create options for multi wallet - ledger, trezor and AWS
+        // Create options for multi wallet - ledger, trezor, AWS and GCP
         let list_opts = MultiWalletOptsBuilder::default()
             .ledger(self.ledger || self.all)
             .mnemonic_indexes(Some(vec![0]))
             .trezor(self.trezor || self.all)
             .aws(self.aws || self.all)
+            .gcp_kms(self.gcp_kms.clone())
             .interactives(0)
             .build()
             .expect("build multi wallet");
@@ -76,6 +84,7 @@ impl ListArgs {
         list_senders!(list_opts.ledgers(), "Ledger");
         list_senders!(list_opts.trezors(), "Trezor");
         list_senders!(list_opts.aws_signers(), "AWS");
+        list_senders!(list_opts.gcp_signers(), "GCP");
 
         Ok(())
     }
@@ -94,6 +94,7 @@ pub enum WalletSubcommands {
         /// Use --data --from-file to denote the message is a file name containing typed data.
         /// The data will be combined and hashed using the EIP712 specification before signing.
         /// The data should be formatted as JSON.
+        /// Use --eip712 as a shorthand for --data --from-file.
         message: String,
 
         /// Treat the message as JSON typed data.
@@ -108,6 +109,10 @@ pub enum WalletSubcommands {
         #[arg(long, conflicts_with = "data")]
         no_hash: bool,
 
+        /// Treat the message as a file containing JSON typed data.
+        #[arg(long, conflicts_with_all = ["data", "no_hash"])]
+        eip712: bool,
+
         #[command(flatten)]
         wallet: WalletOpts,
     },
@@ -246,7 +251,7 @@ impl WalletSubcommands {
                 }
             }
             WalletSubcommands::Vanity(cmd) => {
-                cmd.run()?;
+                cmd.run().await?;
             }
             WalletSubcommands::Address { wallet, private_key_override } => {
                 let wallet = private_key_override
@@ -260,10 +265,10 @@ impl WalletSubcommands {
                 let addr = wallet.address();
                 println!("{}", addr.to_alloy().to_checksum(None));
             }
-            WalletSubcommands::Sign { message, data, from_file, no_hash, wallet } => {
+            WalletSubcommands::Sign { message, data, from_file, no_hash, eip712, wallet } => {
                 let wallet = wallet.signer().await?;
-                let sig = if data {
-                    let typed_data: TypedData = if from_file {
+                let sig = if data || eip712 {
+                    let typed_data: TypedData = if from_file || eip712 {
                         // data is a file name, read json from file
                         foundry_common::fs::read_json_file(message.as_ref())?
                     } else {
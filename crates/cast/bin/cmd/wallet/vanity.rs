@@ -1,15 +1,26 @@
 use alloy_primitives::Address;
 use alloy_signer::{k256::ecdsa::SigningKey, utils::secret_key_to_address, LocalWallet, Signer};
 use clap::{builder::TypedValueParser, Parser};
-use eyre::Result;
+use eyre::{Result, WrapErr};
 use rayon::iter::{self, ParallelIterator};
 use regex::Regex;
+use regex_automata::{
+    dfa::{dense, Automaton},
+    nfa::thompson,
+    util::primitives::StateID,
+    Anchored, Input,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     fs,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     time::Instant,
 };
+use tokio::signal::ctrl_c;
 
 /// Type alias for the result of [generate_wallet].
 pub type GeneratedWallet = (SigningKey, Address);
@@ -20,7 +31,7 @@ pub struct VanityArgs {
     /// Prefix for the vanity address.
     #[arg(
         long,
-        required_unless_present = "ends_with",
+        required_unless_present_any = ["ends_with", "matching"],
         value_parser = HexAddressValidator,
         value_name = "HEX"
     )]
@@ -30,22 +41,42 @@ pub struct VanityArgs {
     #[arg(long, value_parser = HexAddressValidator, value_name = "HEX")]
     pub ends_with: Option<String>,
 
+    /// Regex that the checksummed vanity address, including the `0x` prefix, must match.
+    ///
+    /// Matching is case sensitive, e.g. `^0xC0FFEE`.
+    #[arg(long, value_name = "REGEX")]
+    pub matching: Option<Regex>,
+
     // 2^64-1 is max possible nonce per [eip-2681](https://eips.ethereum.org/EIPS/eip-2681).
     /// Generate a vanity contract address created by the generated keypair with the specified
     /// nonce.
-    #[arg(long)]
+    #[arg(long, visible_alias = "contract-nonce")]
     pub nonce: Option<u64>,
 
+    /// Number of threads to generate keys on.
+    ///
+    /// Defaults to the number of logical cores.
+    #[arg(long, short, value_name = "JOBS")]
+    pub jobs: Option<usize>,
+
     /// Path to save the generated vanity contract address to.
     ///
-    /// If provided, the generated vanity addresses will appended to a JSON array in the specified
-    /// file.
+    /// If the path is a directory, or doesn't exist and isn't a `.json` file, the wallet is saved
+    /// to an encrypted JSON keystore in that directory, which is created if needed, and its
+    /// private key isn't printed. Otherwise, the generated vanity addresses will appended to a
+    /// JSON array in the specified file.
     #[arg(
         long,
-        value_hint = clap::ValueHint::FilePath,
+        value_hint = clap::ValueHint::AnyPath,
         value_name = "PATH",
     )]
     pub save_path: Option<PathBuf>,
+
+    /// Password for the JSON keystore in cleartext.
+    ///
+    /// This is UNSAFE to use, the password is prompted for if not provided.
+    #[arg(long, requires = "save_path", env = "CAST_PASSWORD", value_name = "PASSWORD")]
+    pub unsafe_password: Option<String>,
 }
 
 /// WalletData contains address and private_key information for a wallet.
@@ -71,99 +102,155 @@ impl WalletData {
 }
 
 impl VanityArgs {
-    pub fn run(self) -> Result<LocalWallet> {
-        let Self { starts_with, ends_with, nonce, save_path } = self;
+    pub async fn run(self) -> Result<LocalWallet> {
+        let matcher = self.matcher()?;
+        let Self { nonce, jobs, save_path, unsafe_password, .. } = self;
+
+        // prompt for the password before the search, which can take a while
+        let keystore = match &save_path {
+            Some(dir) if is_keystore_dir(dir) => {
+                fs::create_dir_all(dir).wrap_err_with(|| {
+                    format!("failed to create keystore directory {}", dir.display())
+                })?;
+                let password = match unsafe_password {
+                    Some(password) => password,
+                    None => rpassword::prompt_password("Enter secret: ")?,
+                };
+                Some((dir.clone(), password))
+            }
+            _ => None,
+        };
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs.unwrap_or_default()).build()?;
+        println!(
+            "Starting to generate vanity address on {} threads...",
+            pool.current_num_threads()
+        );
+        let timer = Instant::now();
+
+        let search = Arc::new(VanitySearch::new(matcher, nonce));
+        let mut handle = tokio::task::spawn_blocking({
+            let search = Arc::clone(&search);
+            move || pool.install(|| search.find())
+        });
+        let found = tokio::select! {
+            found = &mut handle => found?,
+            _ = ctrl_c() => {
+                search.stop();
+                handle.await?
+            }
+        };
+
+        let elapsed = timer.elapsed();
+        let attempts = search.attempts();
+        let rate = attempts as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        let stats = format!("{attempts} attempts, {rate:.0} attempts/s");
+
+        let Some((key, _)) = found else {
+            println!("\nInterrupted after {} seconds ({stats}).", elapsed.as_secs());
+            let Some((score, key)) = search.best() else { eyre::bail!("no partial match found") };
+            println!("Best partial match, {score} matching characters:");
+            output(&key.into(), nonce, keystore.as_ref())?;
+            eyre::bail!("vanity address search interrupted")
+        };
+
+        let wallet = key.into();
+        println!("Successfully found vanity address in {} seconds ({stats}).", elapsed.as_secs());
+        // If a save path is provided, save the generated vanity wallet to the specified path.
+        if let (None, Some(save_path)) = (&keystore, &save_path) {
+            save_wallet_to_file(&wallet, save_path)?;
+        }
+        output(&wallet, nonce, keystore.as_ref())?;
+
+        Ok(wallet)
+    }
+
+    /// Returns the matcher of the vanity address patterns.
+    fn matcher(&self) -> Result<Box<dyn VanityMatcher>> {
         let mut left_exact_hex = None;
         let mut left_regex = None;
         let mut right_exact_hex = None;
         let mut right_regex = None;
 
-        if let Some(prefix) = starts_with {
-            if let Ok(decoded) = hex::decode(&prefix) {
+        if let Some(prefix) = &self.starts_with {
+            if let Ok(decoded) = hex::decode(prefix) {
                 left_exact_hex = Some(decoded)
             } else {
-                left_regex = Some(Regex::new(&format!(r"^{prefix}"))?);
+                left_regex = Some(ScoredRegex::new(&format!(r"^{prefix}"))?);
             }
         }
 
-        if let Some(suffix) = ends_with {
-            if let Ok(decoded) = hex::decode(&suffix) {
+        if let Some(suffix) = &self.ends_with {
+            if let Ok(decoded) = hex::decode(suffix) {
                 right_exact_hex = Some(decoded)
             } else {
-                right_regex = Some(Regex::new(&format!(r"{suffix}$"))?);
+                right_regex = Some(ScoredRegex::new(&format!(r"{suffix}$"))?);
             }
         }
 
-        macro_rules! find_vanity {
-            ($m:ident, $nonce: ident) => {
-                if let Some(nonce) = $nonce {
-                    find_vanity_address_with_nonce($m, nonce)
-                } else {
-                    find_vanity_address($m)
+        let matcher: Option<Box<dyn VanityMatcher>> =
+            match (left_exact_hex, left_regex, right_exact_hex, right_regex) {
+                (Some(left), _, Some(right), _) => Some(Box::new(HexMatcher { left, right })),
+                (Some(left), _, _, Some(right)) => {
+                    Some(Box::new(LeftExactRightRegexMatcher { left, right }))
                 }
+                (_, Some(left), _, Some(right)) => Some(Box::new(RegexMatcher { left, right })),
+                (_, Some(left), Some(right), _) => {
+                    Some(Box::new(LeftRegexRightExactMatcher { left, right }))
+                }
+                (Some(left), None, None, None) => Some(Box::new(LeftHexMatcher { left })),
+                (None, None, Some(right), None) => Some(Box::new(RightHexMatcher { right })),
+                (None, Some(re), None, None) => Some(Box::new(SingleRegexMatcher { re })),
+                (None, None, None, Some(re)) => Some(Box::new(SingleRegexMatcher { re })),
+                _ => None,
             };
-        }
 
-        println!("Starting to generate vanity address...");
-        let timer = Instant::now();
-
-        let wallet = match (left_exact_hex, left_regex, right_exact_hex, right_regex) {
-            (Some(left), _, Some(right), _) => {
-                let matcher = HexMatcher { left, right };
-                find_vanity!(matcher, nonce)
-            }
-            (Some(left), _, _, Some(right)) => {
-                let matcher = LeftExactRightRegexMatcher { left, right };
-                find_vanity!(matcher, nonce)
-            }
-            (_, Some(left), _, Some(right)) => {
-                let matcher = RegexMatcher { left, right };
-                find_vanity!(matcher, nonce)
-            }
-            (_, Some(left), Some(right), _) => {
-                let matcher = LeftRegexRightExactMatcher { left, right };
-                find_vanity!(matcher, nonce)
-            }
-            (Some(left), None, None, None) => {
-                let matcher = LeftHexMatcher { left };
-                find_vanity!(matcher, nonce)
+        let checksum = self
+            .matching
+            .as_ref()
+            .map(|re| ScoredRegex::new(re.as_str()).map(|re| ChecksumRegexMatcher { re }))
+            .transpose()?;
+        Ok(match (matcher, checksum) {
+            (Some(matcher), Some(checksum)) => {
+                Box::new(AllMatcher { matchers: vec![matcher, Box::new(checksum)] })
             }
-            (None, None, Some(right), None) => {
-                let matcher = RightHexMatcher { right };
-                find_vanity!(matcher, nonce)
-            }
-            (None, Some(re), None, None) => {
-                let matcher = SingleRegexMatcher { re };
-                find_vanity!(matcher, nonce)
-            }
-            (None, None, None, Some(re)) => {
-                let matcher = SingleRegexMatcher { re };
-                find_vanity!(matcher, nonce)
-            }
-            _ => unreachable!(),
-        }
-        .expect("failed to generate vanity wallet");
-
-        // If a save path is provided, save the generated vanity wallet to the specified path.
-        if let Some(save_path) = save_path {
-            save_wallet_to_file(&wallet, &save_path)?;
-        }
-
-        println!(
-            "Successfully found vanity address in {} seconds.{}{}\nAddress: {}\nPrivate Key: 0x{}",
-            timer.elapsed().as_secs(),
-            if nonce.is_some() { "\nContract address: " } else { "" },
-            if nonce.is_some() {
-                wallet.address().create(nonce.unwrap()).to_checksum(None)
-            } else {
-                String::new()
-            },
-            wallet.address().to_checksum(None),
-            hex::encode(wallet.signer().to_bytes()),
-        );
+            (Some(matcher), None) => matcher,
+            (None, Some(checksum)) => Box::new(checksum),
+            (None, None) => unreachable!(),
+        })
+    }
+}
 
-        Ok(wallet)
+/// Prints the addresses of the `wallet`, and its private key unless it's saved to the encrypted
+/// `keystore`, given as the directory and password.
+fn output(
+    wallet: &LocalWallet,
+    nonce: Option<u64>,
+    keystore: Option<&(PathBuf, String)>,
+) -> Result<()> {
+    println!("Address: {}", wallet.address().to_checksum(None));
+    if let Some(nonce) = nonce {
+        println!("Contract address: {}", wallet.address().create(nonce).to_checksum(None));
+    }
+    if let Some((dir, password)) = keystore {
+        let uuid = eth_keystore::encrypt_key(
+            dir,
+            &mut rand::thread_rng(),
+            wallet.signer().to_bytes(),
+            password,
+            None,
+        )?;
+        println!("Created new encrypted keystore file: {}", dir.join(uuid).display());
+    } else {
+        println!("Private Key: 0x{}", hex::encode(wallet.signer().to_bytes()));
     }
+    Ok(())
+}
+
+/// Returns whether `path` is the directory of a keystore rather than a JSON file of wallets.
+fn is_keystore_dir(path: &Path) -> bool {
+    path.is_dir() ||
+        (!path.exists() && path.extension().map_or(true, |extension| extension != "json"))
 }
 
 /// Saves the specified `wallet` to a 'vanity_addresses.json' file at the given `save_path`.
@@ -230,9 +317,129 @@ pub fn generate_wallet() -> GeneratedWallet {
     (key, address)
 }
 
+/// Searches for a wallet matching a [VanityMatcher] on the current rayon thread pool, counting the
+/// attempts and keeping track of the best partial match, until a match is found or the search is
+/// stopped.
+pub struct VanitySearch {
+    matcher: Box<dyn VanityMatcher>,
+    /// Matches the contract address created at the nonce instead of the wallet address.
+    nonce: Option<u64>,
+    attempts: AtomicU64,
+    stopped: AtomicBool,
+    best_score: AtomicUsize,
+    best: Mutex<Option<(usize, SigningKey)>>,
+}
+
+impl VanitySearch {
+    pub fn new(matcher: Box<dyn VanityMatcher>, nonce: Option<u64>) -> Self {
+        Self {
+            matcher,
+            nonce,
+            attempts: AtomicU64::new(0),
+            stopped: AtomicBool::new(false),
+            best_score: AtomicUsize::new(0),
+            best: Mutex::new(None),
+        }
+    }
+
+    /// Generates random wallets until one matches, returning `None` if the search was stopped.
+    pub fn find(&self) -> Option<GeneratedWallet> {
+        wallet_generator()
+            .find_any(|wallet| self.stopped() || self.check(wallet))
+            .filter(|_| !self.stopped())
+    }
+
+    /// Stops the search.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the number of generated wallets.
+    pub fn attempts(&self) -> u64 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    /// Returns the best partial match and its score, the number of matching hex characters.
+    pub fn best(&self) -> Option<(usize, SigningKey)> {
+        self.best.lock().unwrap().clone()
+    }
+
+    fn stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    fn check(&self, (key, addr): &GeneratedWallet) -> bool {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+        let addr = self.nonce.map_or(*addr, |nonce| addr.create(nonce));
+        if self.matcher.is_match(&addr) {
+            return true
+        }
+        let score = self.matcher.score(&addr);
+        if score > self.best_score.load(Ordering::Relaxed) {
+            let mut best = self.best.lock().unwrap();
+            if best.as_ref().map_or(true, |(best, _)| score > *best) {
+                *best = Some((score, key.clone()));
+                self.best_score.store(score, Ordering::Relaxed);
+            }
+        }
+        false
+    }
+}
+
 /// A trait to match vanity addresses.
 pub trait VanityMatcher: Send + Sync {
     fn is_match(&self, addr: &Address) -> bool;
+
+    /// Returns how close the address is to a match, the number of matching hex characters.
+    ///
+    /// Regex patterns count the characters they accept from the start, or from the end, of the
+    /// address before they can no longer match.
+    fn score(&self, _addr: &Address) -> usize {
+        0
+    }
+}
+
+impl<T: VanityMatcher + ?Sized> VanityMatcher for Box<T> {
+    #[inline]
+    fn is_match(&self, addr: &Address) -> bool {
+        (**self).is_match(addr)
+    }
+
+    fn score(&self, addr: &Address) -> usize {
+        (**self).score(addr)
+    }
+}
+
+/// Returns the number of leading hex characters of `bytes` that match `prefix`.
+fn matching_prefix_len(bytes: &[u8], prefix: &[u8]) -> usize {
+    let mut len = 0;
+    for (byte, expected) in bytes.iter().zip(prefix) {
+        if byte == expected {
+            len += 2;
+            continue
+        }
+        if byte >> 4 == expected >> 4 {
+            len += 1;
+        }
+        break
+    }
+    len
+}
+
+/// Returns the number of trailing hex characters of `bytes` that match `suffix`.
+fn matching_suffix_len(bytes: &[u8], suffix: &[u8]) -> usize {
+    let mut len = 0;
+    for (byte, expected) in bytes.iter().rev().zip(suffix.iter().rev()) {
+        if byte == expected {
+            len += 2;
+            continue
+        }
+        if byte & 0xf == expected & 0xf {
+            len += 1;
+        }
+        break
+    }
+    len
 }
 
 /// Matches start and end hex.
@@ -247,6 +454,11 @@ impl VanityMatcher for HexMatcher {
         let bytes = addr.as_slice();
         bytes.starts_with(&self.left) && bytes.ends_with(&self.right)
     }
+
+    fn score(&self, addr: &Address) -> usize {
+        matching_prefix_len(addr.as_slice(), &self.left) +
+            matching_suffix_len(addr.as_slice(), &self.right)
+    }
 }
 
 /// Matches only start hex.
@@ -260,6 +472,10 @@ impl VanityMatcher for LeftHexMatcher {
         let bytes = addr.as_slice();
         bytes.starts_with(&self.left)
     }
+
+    fn score(&self, addr: &Address) -> usize {
+        matching_prefix_len(addr.as_slice(), &self.left)
+    }
 }
 
 /// Matches only end hex.
@@ -273,12 +489,85 @@ impl VanityMatcher for RightHexMatcher {
         let bytes = addr.as_slice();
         bytes.ends_with(&self.right)
     }
+
+    fn score(&self, addr: &Address) -> usize {
+        matching_suffix_len(addr.as_slice(), &self.right)
+    }
+}
+
+/// A regex that can score how close a string is to matching it.
+pub struct ScoredRegex {
+    pub re: Regex,
+    /// Runs the pattern from the start of the string.
+    forward: Option<dense::DFA<Vec<u32>>>,
+    /// Runs the pattern backwards from the end of the string.
+    reverse: Option<dense::DFA<Vec<u32>>>,
+}
+
+impl ScoredRegex {
+    /// Compiles the `pattern`.
+    ///
+    /// Patterns that can't be run as a DFA, e.g. with Unicode word boundaries, still match but
+    /// always score 0.
+    pub fn new(pattern: &str) -> Result<Self> {
+        let re = Regex::new(pattern)?;
+        let forward = dense::DFA::new(pattern).ok();
+        let reverse = dense::Builder::new()
+            .thompson(thompson::Config::new().reverse(true))
+            .build(pattern)
+            .ok();
+        Ok(Self { re, forward, reverse })
+    }
+
+    #[inline]
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.re.is_match(haystack)
+    }
+
+    /// Returns the number of characters at the start of `haystack` the pattern accepts.
+    pub fn prefix_score(&self, haystack: &str) -> usize {
+        let Some(dfa) = &self.forward else { return 0 };
+        let input = Input::new(haystack).anchored(Anchored::Yes);
+        let Ok(start) = dfa.start_state_forward(&input) else { return 0 };
+        accepted_len(dfa, start, haystack.bytes())
+    }
+
+    /// Returns the number of characters at the end of `haystack` the pattern accepts.
+    pub fn suffix_score(&self, haystack: &str) -> usize {
+        let Some(dfa) = &self.reverse else { return 0 };
+        let input = Input::new(haystack).anchored(Anchored::Yes);
+        let Ok(start) = dfa.start_state_reverse(&input) else { return 0 };
+        accepted_len(dfa, start, haystack.bytes().rev())
+    }
+
+    /// Returns the number of characters accepted from either end of `haystack`.
+    pub fn score(&self, haystack: &str) -> usize {
+        self.prefix_score(haystack).max(self.suffix_score(haystack))
+    }
+}
+
+/// Returns the number of `bytes` the `dfa` steps through from `state` before it can no longer
+/// match.
+fn accepted_len(
+    dfa: &dense::DFA<Vec<u32>>,
+    mut state: StateID,
+    bytes: impl Iterator<Item = u8>,
+) -> usize {
+    let mut len = 0;
+    for byte in bytes {
+        state = dfa.next_state(state, byte);
+        if dfa.is_dead_state(state) || dfa.is_quit_state(state) {
+            break
+        }
+        len += 1;
+    }
+    len
 }
 
 /// Matches start hex and end regex.
 pub struct LeftExactRightRegexMatcher {
     pub left: Vec<u8>,
-    pub right: Regex,
+    pub right: ScoredRegex,
 }
 
 impl VanityMatcher for LeftExactRightRegexMatcher {
@@ -287,11 +576,16 @@ impl VanityMatcher for LeftExactRightRegexMatcher {
         let bytes = addr.as_slice();
         bytes.starts_with(&self.left) && self.right.is_match(&hex::encode(bytes))
     }
+
+    fn score(&self, addr: &Address) -> usize {
+        let bytes = addr.as_slice();
+        matching_prefix_len(bytes, &self.left) + self.right.suffix_score(&hex::encode(bytes))
+    }
 }
 
 /// Matches start regex and end hex.
 pub struct LeftRegexRightExactMatcher {
-    pub left: Regex,
+    pub left: ScoredRegex,
     pub right: Vec<u8>,
 }
 
@@ -301,11 +595,16 @@ impl VanityMatcher for LeftRegexRightExactMatcher {
         let bytes = addr.as_slice();
         bytes.ends_with(&self.right) && self.left.is_match(&hex::encode(bytes))
     }
+
+    fn score(&self, addr: &Address) -> usize {
+        let bytes = addr.as_slice();
+        self.left.prefix_score(&hex::encode(bytes)) + matching_suffix_len(bytes, &self.right)
+    }
 }
 
 /// Matches a single regex.
 pub struct SingleRegexMatcher {
-    pub re: Regex,
+    pub re: ScoredRegex,
 }
 
 impl VanityMatcher for SingleRegexMatcher {
@@ -314,12 +613,16 @@ impl VanityMatcher for SingleRegexMatcher {
         let addr = hex::encode(addr);
         self.re.is_match(&addr)
     }
+
+    fn score(&self, addr: &Address) -> usize {
+        self.re.score(&hex::encode(addr))
+    }
 }
 
 /// Matches start and end regex.
 pub struct RegexMatcher {
-    pub left: Regex,
-    pub right: Regex,
+    pub left: ScoredRegex,
+    pub right: ScoredRegex,
 }
 
 impl VanityMatcher for RegexMatcher {
@@ -328,6 +631,45 @@ impl VanityMatcher for RegexMatcher {
         let addr = hex::encode(addr);
         self.left.is_match(&addr) && self.right.is_match(&addr)
     }
+
+    fn score(&self, addr: &Address) -> usize {
+        let addr = hex::encode(addr);
+        self.left.prefix_score(&addr) + self.right.suffix_score(&addr)
+    }
+}
+
+/// Matches a regex against the checksummed address.
+pub struct ChecksumRegexMatcher {
+    pub re: ScoredRegex,
+}
+
+impl VanityMatcher for ChecksumRegexMatcher {
+    #[inline]
+    fn is_match(&self, addr: &Address) -> bool {
+        self.re.is_match(&addr.to_checksum(None))
+    }
+
+    fn score(&self, addr: &Address) -> usize {
+        // The `0x` prefix isn't a matching hex character.
+        let addr = addr.to_checksum(None);
+        self.re.prefix_score(&addr).saturating_sub(2).max(self.re.suffix_score(&addr))
+    }
+}
+
+/// Matches all of the matchers.
+pub struct AllMatcher {
+    pub matchers: Vec<Box<dyn VanityMatcher>>,
+}
+
+impl VanityMatcher for AllMatcher {
+    #[inline]
+    fn is_match(&self, addr: &Address) -> bool {
+        self.matchers.iter().all(|matcher| matcher.is_match(addr))
+    }
+
+    fn score(&self, addr: &Address) -> usize {
+        self.matchers.iter().map(|matcher| matcher.score(addr)).sum()
+    }
 }
 
 /// Parse 40 byte addresses
@@ -360,35 +702,35 @@ impl TypedValueParser for HexAddressValidator {
 mod tests {
     use super::*;
 
-    #[test]
-    fn find_simple_vanity_start() {
+    #[tokio::test]
+    async fn find_simple_vanity_start() {
         let args: VanityArgs = VanityArgs::parse_from(["foundry-cli", "--starts-with", "00"]);
-        let wallet = args.run().unwrap();
+        let wallet = args.run().await.unwrap();
         let addr = wallet.address();
         let addr = format!("{addr:x}");
         assert!(addr.starts_with("00"));
     }
 
-    #[test]
-    fn find_simple_vanity_start2() {
+    #[tokio::test]
+    async fn find_simple_vanity_start2() {
         let args: VanityArgs = VanityArgs::parse_from(["foundry-cli", "--starts-with", "9"]);
-        let wallet = args.run().unwrap();
+        let wallet = args.run().await.unwrap();
         let addr = wallet.address();
         let addr = format!("{addr:x}");
         assert!(addr.starts_with('9'));
     }
 
-    #[test]
-    fn find_simple_vanity_end() {
+    #[tokio::test]
+    async fn find_simple_vanity_end() {
         let args: VanityArgs = VanityArgs::parse_from(["foundry-cli", "--ends-with", "00"]);
-        let wallet = args.run().unwrap();
+        let wallet = args.run().await.unwrap();
         let addr = wallet.address();
         let addr = format!("{addr:x}");
         assert!(addr.ends_with("00"));
     }
 
-    #[test]
-    fn save_path() {
+    #[tokio::test]
+    async fn save_path() {
         let tmp = tempfile::NamedTempFile::new().unwrap();
         let args: VanityArgs = VanityArgs::parse_from([
             "foundry-cli",
@@ -397,10 +739,90 @@ mod tests {
             "--save-path",
             tmp.path().to_str().unwrap(),
         ]);
-        args.run().unwrap();
+        args.run().await.unwrap();
         assert!(tmp.path().exists());
         let s = fs::read_to_string(tmp.path()).unwrap();
         let wallets: Wallets = serde_json::from_str(&s).unwrap();
         assert!(!wallets.wallets.is_empty());
     }
+
+    #[tokio::test]
+    async fn find_checksum_vanity_contract_address() {
+        let args: VanityArgs = VanityArgs::parse_from([
+            "foundry-cli",
+            "--matching",
+            "^0x[A-F]",
+            "--contract-nonce",
+            "3",
+            "--jobs",
+            "2",
+        ]);
+        let wallet = args.run().await.unwrap();
+        let addr = wallet.address().create(3).to_checksum(None);
+        assert!(addr[2..].starts_with(|c: char| c.is_ascii_uppercase()), "{addr}");
+    }
+
+    #[tokio::test]
+    async fn save_keystore() {
+        let dir = tempfile::tempdir().unwrap();
+        let args: VanityArgs = VanityArgs::parse_from([
+            "foundry-cli",
+            "--ends-with",
+            "0",
+            "--save-path",
+            dir.path().to_str().unwrap(),
+            "--unsafe-password",
+            "secret",
+        ]);
+        let wallet = args.run().await.unwrap();
+        let keystore = fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap().path();
+        let key = eth_keystore::decrypt_key(keystore, "secret").unwrap();
+        assert_eq!(key, wallet.signer().to_bytes().to_vec());
+    }
+
+    #[tokio::test]
+    async fn save_keystore_creates_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore_dir = dir.path().join("keystores");
+        let args: VanityArgs = VanityArgs::parse_from([
+            "foundry-cli",
+            "--ends-with",
+            "0",
+            "--save-path",
+            keystore_dir.to_str().unwrap(),
+            "--unsafe-password",
+            "secret",
+        ]);
+        let wallet = args.run().await.unwrap();
+        let keystore = fs::read_dir(&keystore_dir).unwrap().next().unwrap().unwrap().path();
+        let key = eth_keystore::decrypt_key(keystore, "secret").unwrap();
+        assert_eq!(key, wallet.signer().to_bytes().to_vec());
+    }
+
+    #[test]
+    fn scores_partial_matches() {
+        let addr = Address::from_slice(&[
+            0xab, 0xcd, 0xef, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x12, 0x34,
+        ]);
+        assert_eq!(LeftHexMatcher { left: vec![0xab, 0xce] }.score(&addr), 3);
+        assert_eq!(LeftHexMatcher { left: vec![0xbb] }.score(&addr), 0);
+        assert_eq!(RightHexMatcher { right: vec![0x02, 0x34] }.score(&addr), 3);
+        assert_eq!(HexMatcher { left: vec![0xab], right: vec![0x34] }.score(&addr), 4);
+
+        let re = |pattern| ScoredRegex::new(pattern).unwrap();
+        assert_eq!(SingleRegexMatcher { re: re("^ab[a-f]e") }.score(&addr), 3);
+        assert_eq!(SingleRegexMatcher { re: re("f[0-9]234$") }.score(&addr), 4);
+        assert_eq!(RegexMatcher { left: re("^a[0-9]"), right: re("f34$") }.score(&addr), 3);
+        assert_eq!(
+            LeftExactRightRegexMatcher { left: vec![0xab], right: re("9[0-9]{3}4$") }.score(&addr),
+            6
+        );
+
+        let search = VanitySearch::new(Box::new(LeftHexMatcher { left: vec![0xff; 20] }), None);
+        let wallet = generate_wallet();
+        assert!(!search.check(&wallet));
+        assert_eq!(search.attempts(), 1);
+        let score = matching_prefix_len(wallet.1.as_slice(), &[0xff; 20]);
+        assert_eq!(search.best().map(|(score, _)| score), (score > 0).then_some(score));
+    }
 }
@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate tracing;
 
+use alloy_dyn_abi::{FunctionExt, JsonAbiExt};
 use alloy_primitives::{keccak256, Address, B256};
 use cast::{Cast, SimpleCast, TxBuilder};
 use clap::{CommandFactory, Parser};
@@ -11,23 +12,24 @@ use eyre::Result;
 use foundry_cli::{handler, prompt, stdin, utils};
 use foundry_common::{
     abi::get_event,
+    etherscan::EtherscanClient,
     fmt::format_tokens,
     fs,
     runtime_client::RuntimeClient,
     selectors::{
         decode_calldata, decode_event_topic, decode_function_selector, decode_selectors,
-        import_selectors, parse_signatures, pretty_calldata, ParsedSignatures, SelectorImportData,
-        SelectorType,
+        import_selectors, parse_signatures, ParsedSignatures, SelectorImportData, SelectorType,
     },
     types::{ToAlloy, ToEthers},
 };
-use foundry_config::Config;
+use foundry_config::{etherscan::ResolvedEtherscanConfig, Config};
 use std::time::Instant;
 
 pub mod cmd;
 pub mod opts;
 pub mod tx;
 
+use cmd::pretty_calldata::etherscan_function;
 use opts::{Cast as Opts, CastSubcommand, ToBaseArgs};
 
 #[tokio::main]
@@ -156,8 +158,19 @@ async fn main() -> Result<()> {
         }
 
         // ABI encoding & decoding
-        CastSubcommand::AbiDecode { sig, calldata, input } => {
-            let tokens = SimpleCast::abi_decode(&sig, &calldata, input)?;
+        CastSubcommand::AbiDecode { sig, calldata, input, sig_from_etherscan, etherscan } => {
+            let tokens = match sig_from_etherscan {
+                Some(address) => {
+                    let function = etherscan_function(address, &sig, &etherscan).await?;
+                    let data = hex::decode(calldata)?;
+                    if input {
+                        function.abi_decode_input(&data, false)?
+                    } else {
+                        function.abi_decode_output(&data, false)?
+                    }
+                }
+                None => SimpleCast::abi_decode(&sig, &calldata, input)?,
+            };
             let tokens = format_tokens(&tokens);
             tokens.for_each(|t| println!("{t}"));
         }
@@ -178,10 +191,7 @@ async fn main() -> Result<()> {
         }
         CastSubcommand::Interface(cmd) => cmd.run().await?,
         CastSubcommand::Bind(cmd) => cmd.run().await?,
-        CastSubcommand::PrettyCalldata { calldata, offline } => {
-            let calldata = stdin::unwrap_line(calldata)?;
-            println!("{}", pretty_calldata(&calldata, offline).await?);
-        }
+        CastSubcommand::PrettyCalldata(cmd) => cmd.run().await?,
         CastSubcommand::Sig { sig, optimize } => {
             let sig = stdin::unwrap_line(sig)?;
             match optimize {
@@ -353,13 +363,14 @@ async fn main() -> Result<()> {
             let provider = utils::get_provider(&config)?;
             println!("{}", Cast::new(provider).nonce(who, block).await?);
         }
-        CastSubcommand::Proof { address, slots, rpc, block } => {
+        CastSubcommand::NonceGaps(cmd) => cmd.run().await?,
+        CastSubcommand::Proof { address, slots, rpc, block, json, encode_for_verifier } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
-            let value = provider
-                .get_proof(address, slots.into_iter().map(|s| s.to_ethers()).collect(), block)
-                .await?;
-            println!("{}", serde_json::to_string(&value)?);
+            println!(
+                "{}",
+                Cast::new(provider).proof(address, slots, block, json, encode_for_verifier).await?
+            );
         }
         CastSubcommand::Rpc(cmd) => cmd.run().await?,
         CastSubcommand::Storage(cmd) => cmd.run().await?,
@@ -524,20 +535,28 @@ async fn main() -> Result<()> {
         CastSubcommand::EtherscanSource { address, directory, etherscan } => {
             let config = Config::from(&etherscan);
             let chain = config.chain.unwrap_or_default();
-            let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
+            let client = match EtherscanClient::from_config(&config, Some(chain))? {
+                Some(client) => client,
+                // fall back to the keyless API of the chain
+                None => EtherscanClient::new(
+                    ResolvedEtherscanConfig::create("", chain)
+                        .ok_or_else(|| eyre::eyre!("No known Etherscan API for chain {chain}"))?,
+                    config.etherscan_requests_per_second,
+                )?,
+            };
             match directory {
                 Some(dir) => {
-                    SimpleCast::expand_etherscan_source_to_directory(chain, address, api_key, dir)
-                        .await?
+                    SimpleCast::expand_etherscan_source_to_directory(&client, address, dir).await?
                 }
                 None => {
-                    println!("{}", SimpleCast::etherscan_source(chain, address, api_key).await?);
+                    println!("{}", SimpleCast::etherscan_source(&client, address).await?);
                 }
             }
         }
         CastSubcommand::Create2(cmd) => {
             cmd.run()?;
         }
+        CastSubcommand::Eip712 { command } => command.run()?,
         CastSubcommand::Wallet { command } => command.run().await?,
         CastSubcommand::Completions { shell } => {
             generate(shell, &mut Opts::command(), "cast", &mut std::io::stdout())
@@ -1,8 +1,9 @@
 use crate::cmd::{
     access_list::AccessListArgs, bind::BindArgs, call::CallArgs, create2::Create2Args,
-    estimate::EstimateArgs, find_block::FindBlockArgs, interface::InterfaceArgs, logs::LogsArgs,
-    mktx::MakeTxArgs, rpc::RpcArgs, run::RunArgs, send::SendTxArgs, storage::StorageArgs,
-    wallet::WalletSubcommands,
+    eip712::Eip712Subcommands, estimate::EstimateArgs, find_block::FindBlockArgs,
+    interface::InterfaceArgs, logs::LogsArgs, mktx::MakeTxArgs, nonce_gaps::NonceGapsArgs,
+    pretty_calldata::PrettyCalldataArgs, rpc::RpcArgs, run::RunArgs, send::SendTxArgs,
+    storage::StorageArgs, wallet::WalletSubcommands,
 };
 use alloy_primitives::{Address, B256, U256};
 use clap::{Parser, Subcommand, ValueHint};
@@ -480,6 +481,9 @@ pub enum CastSubcommand {
     #[command(name = "abi-decode", visible_aliases = &["ad", "--abi-decode"])]
     AbiDecode {
         /// The function signature in the format `<name>(<in-types>)(<out-types>)`.
+        ///
+        /// With `--sig-from-etherscan`, the name or the signature of a function of the verified
+        /// contract.
         sig: String,
 
         /// The ABI-encoded calldata.
@@ -488,6 +492,14 @@ pub enum CastSubcommand {
         /// Whether to decode the input or output data.
         #[arg(long, short, help_heading = "Decode input data instead of output data")]
         input: bool,
+
+        /// Decode with the function of the verified ABI of the contract at this address on
+        /// Etherscan.
+        #[arg(long, value_name = "ADDRESS")]
+        sig_from_etherscan: Option<Address>,
+
+        #[command(flatten)]
+        etherscan: EtherscanOpts,
     },
 
     /// ABI encode the given function argument, excluding the selector.
@@ -592,16 +604,11 @@ pub enum CastSubcommand {
 
     /// Pretty print calldata.
     ///
-    /// Tries to decode the calldata using https://openchain.xyz unless --offline is passed.
+    /// Resolves the selector against the local artifacts, the verified ABI of the `--to` contract
+    /// on Etherscan and https://openchain.xyz, and recursively decodes the calls wrapped by
+    /// multicalls, Gnosis Safe transactions and ERC-4337 user operations.
     #[command(visible_alias = "pc")]
-    PrettyCalldata {
-        /// The calldata.
-        calldata: Option<String>,
-
-        /// Skip the https://openchain.xyz lookup.
-        #[arg(long, short)]
-        offline: bool,
-    },
+    PrettyCalldata(PrettyCalldataArgs),
 
     /// Get the timestamp of a block.
     #[command(visible_alias = "a")]
@@ -744,7 +751,9 @@ pub enum CastSubcommand {
     #[command(visible_alias = "st")]
     Storage(StorageArgs),
 
-    /// Generate a storage proof for a given storage slot.
+    /// Generate the EIP-1186 account proof and storage proofs of an account.
+    ///
+    /// The proofs are verified against the state root of the block.
     #[command(visible_alias = "pr")]
     Proof {
         /// The contract address.
@@ -761,6 +770,16 @@ pub enum CastSubcommand {
         #[arg(long, short = 'B')]
         block: Option<BlockId>,
 
+        /// Print the proofs as JSON.
+        #[arg(long, short, help_heading = "Display options")]
+        json: bool,
+
+        /// Print the RLP nodes of the proofs ABI-encoded as `(bytes[], bytes[][])`: the account
+        /// proof, and the proof of each slot, as Solidity Merkle Patricia trie verifiers take
+        /// them.
+        #[arg(long, conflicts_with = "json", help_heading = "Display options")]
+        encode_for_verifier: bool,
+
         #[command(flatten)]
         rpc: RpcOpts,
     },
@@ -782,6 +801,13 @@ pub enum CastSubcommand {
         rpc: RpcOpts,
     },
 
+    /// Detect nonce gaps and stuck transactions of an account.
+    ///
+    /// Compares the latest and pending nonces, and the transactions in the pool if the node
+    /// exposes it.
+    #[command(visible_alias = "ng")]
+    NonceGaps(NonceGapsArgs),
+
     /// Get the source code of a contract from Etherscan.
     #[command(visible_aliases = &["et", "src"])]
     EtherscanSource {
@@ -827,6 +853,13 @@ pub enum CastSubcommand {
     #[command(visible_alias = "c2")]
     Create2(Create2Args),
 
+    /// EIP-712 hashing utilities.
+    #[command(visible_alias = "712")]
+    Eip712 {
+        #[command(subcommand)]
+        command: Eip712Subcommands,
+    },
+
     /// Get the block number closest to the provided timestamp.
     #[command(visible_alias = "f")]
     FindBlock(FindBlockArgs),
@@ -1,10 +1,14 @@
-use alloy_primitives::Address;
+use alloy_dyn_abi::{JsonAbiExt, ResolveSolType};
+use alloy_primitives::{keccak256, Address};
 use cast::{TxBuilder, TxBuilderOutput};
 use ethers_core::types::NameOrAddress;
 use ethers_providers::Middleware;
-use eyre::Result;
-use foundry_cli::opts::TransactionOpts;
-use foundry_config::Chain;
+use eyre::{Result, WrapErr};
+use foundry_cli::{opts::TransactionOpts, utils::remove_contract};
+use foundry_common::{compile::ProjectCompiler, fmt::parse_tokens};
+use foundry_compilers::{info::ContractInfo, utils::canonicalized, Libraries};
+use foundry_config::{Chain, Config};
+use std::{path::Path, str::FromStr};
 
 /// Prevents a misconfigured hwlib from sending a transaction that defies user-specified --from
 pub fn validate_from_address(
@@ -71,3 +75,120 @@ pub async fn build_tx<M: Middleware, F: Into<NameOrAddress>, T: Into<NameOrAddre
     let builder_output = builder.build();
     Ok(builder_output)
 }
+
+/// Returns whether `contract` names a contract (`ContractName` or `path:ContractName`) rather than
+/// being bytecode.
+///
+/// Bytecode which could be mistaken for a name, e.g. `facade`, can be passed with a `0x` prefix.
+fn is_contract_name(contract: &str) -> bool {
+    if contract.contains(':') {
+        return true
+    }
+    let mut chars = contract.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$') &&
+        chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Returns the creation code of the contract named by `contract` (`ContractName` or
+/// `path:ContractName`) in the project of the current directory, or `None` if `contract` is
+/// bytecode.
+///
+/// The creation code is linked with the `libraries`, given as `<path>:<name>:<address>`, and
+/// followed by the ABI-encoded `constructor_args`.
+pub fn contract_creation_code(
+    contract: &str,
+    libraries: &[String],
+    constructor_args: &[String],
+) -> Result<Option<Vec<u8>>> {
+    if !is_contract_name(contract) {
+        if !libraries.is_empty() || !constructor_args.is_empty() {
+            eyre::bail!(
+                "`--libraries` and `--constructor-args` require a contract name, not bytecode"
+            )
+        }
+        return Ok(None)
+    }
+
+    let mut info = ContractInfo::from_str(contract)?;
+    let project = Config::load().project()?;
+    let mut output = ProjectCompiler::new().quiet(true).compile(&project)?;
+    if let Some(ref mut path) = info.path {
+        // paths are absolute in the project's output
+        *path = canonicalized(project.root().join(&path)).to_string_lossy().to_string();
+    }
+    let (abi, mut bytecode, _) = remove_contract(&mut output, &info)?;
+
+    let libraries = Libraries::parse(libraries)?.with_applied_remappings(&project.paths);
+    for (file, libs) in &libraries.libs {
+        for (name, address) in libs {
+            let address = Address::from_str(address)
+                .wrap_err_with(|| format!("invalid address of library {name}: {address}"))?;
+            bytecode.link(file.to_string_lossy(), name, address);
+        }
+    }
+
+    let Some(code) = bytecode.object.as_bytes() else {
+        let unresolved = bytecode
+            .link_references
+            .iter()
+            .flat_map(|(file, libs)| {
+                let path = Path::new(file).strip_prefix(project.root()).unwrap_or(Path::new(file));
+                libs.keys().map(move |name| {
+                    let placeholder = hex::encode(keccak256(format!("{file}:{name}")));
+                    format!("\t__${}$__: {}:{name}", &placeholder[..34], path.display())
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        eyre::bail!(
+            "missing library addresses for {contract}, provide them with `--libraries <path>:<name>:<address>`:\n{unresolved}"
+        )
+    };
+    let mut code = code.to_vec();
+
+    match &abi.constructor {
+        Some(constructor) => {
+            if constructor.inputs.len() != constructor_args.len() {
+                eyre::bail!(
+                    "the constructor of {contract} expects {} arguments, got {}",
+                    constructor.inputs.len(),
+                    constructor_args.len()
+                )
+            }
+            let params = constructor
+                .inputs
+                .iter()
+                .zip(constructor_args)
+                .map(|(input, arg)| {
+                    let ty = input.resolve().wrap_err_with(|| {
+                        format!("could not resolve constructor arg: input={input}")
+                    })?;
+                    Ok((ty, arg.as_str()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let values = parse_tokens(params.iter().map(|(ty, arg)| (ty, *arg)))?;
+            code.extend(constructor.abi_encode_input(&values)?);
+        }
+        None if !constructor_args.is_empty() => {
+            eyre::bail!("{contract} has no constructor arguments")
+        }
+        None => {}
+    }
+
+    Ok(Some(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distinguishes_contract_names_from_bytecode() {
+        assert!(is_contract_name("Facade"));
+        assert!(is_contract_name("Counter"));
+        assert!(is_contract_name("src/Counter.sol:Counter"));
+        assert!(!is_contract_name("6080604052"));
+        assert!(!is_contract_name("0xfacade"));
+        assert!(!is_contract_name("0x6080604052"));
+    }
+}
@@ -5,6 +5,7 @@ use alloy_primitives::{
     Address, Bytes, Keccak256, B256, I256, U256,
 };
 use alloy_rlp::Decodable;
+use alloy_rpc_types::EIP1186AccountProofResponse;
 use base::{Base, NumberWithBase, ToBase};
 use chrono::NaiveDateTime;
 use ethers_core::{
@@ -20,7 +21,9 @@ use eyre::{Context, ContextCompat, Result};
 use foundry_block_explorers::Client;
 use foundry_common::{
     abi::{encode_function_args, get_func},
+    etherscan::EtherscanClient,
     fmt::*,
+    proof,
     types::{ToAlloy, ToEthers},
     TransactionReceiptWithRevertReason,
 };
@@ -799,6 +802,63 @@ where
         Ok(format!("{:?}", self.provider.get_storage_at(from, slot, block).await?))
     }
 
+    /// Returns the EIP-1186 account proof and storage proofs of an account at `block`, after
+    /// verifying them against the state root of the block.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use alloy_primitives::B256;
+    /// use cast::Cast;
+    /// use ethers_core::types::Address;
+    /// use ethers_providers::{Http, Provider};
+    /// use std::str::FromStr;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let addr = Address::from_str("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")?;
+    /// let proof = cast.proof(addr, vec![B256::ZERO], None, false, false).await?;
+    /// println!("{}", proof);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn proof<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        who: T,
+        slots: Vec<B256>,
+        block: Option<BlockId>,
+        to_json: bool,
+        encode_for_verifier: bool,
+    ) -> Result<String> {
+        // pin the block, so the proof and the state root are of the same block
+        let block = block.unwrap_or_else(|| BlockNumber::Latest.into());
+        let block = self
+            .provider
+            .get_block(block)
+            .await?
+            .ok_or_else(|| eyre::eyre!("block {:?} not found", block))?;
+        let number = block.number.wrap_err("pending blocks have no state root")?;
+
+        let slots = slots.into_iter().map(|slot| slot.to_ethers()).collect();
+        let proof = self
+            .provider
+            .get_proof(who, slots, Some(BlockId::Number(number.into())))
+            .await?
+            .to_alloy();
+        proof::verify_account_proof(block.state_root.to_alloy(), &proof).wrap_err_with(|| {
+            format!("the proof doesn't match the state root of block {number}")
+        })?;
+
+        Ok(if encode_for_verifier {
+            proof::encode_for_verifier(&proof).to_string()
+        } else if to_json {
+            serde_json::to_string(&proof)?
+        } else {
+            pretty_proof(&proof)
+        })
+    }
+
     pub async fn filter_logs(&self, filter: Filter, to_json: bool) -> Result<String> {
         let logs = self.provider.get_logs(&filter).await?;
 
@@ -1861,31 +1921,29 @@ impl SimpleCast {
     ///
     /// ```
     /// # use cast::SimpleCast as Cast;
-    /// # use foundry_config::NamedChain;
+    /// # use foundry_common::etherscan::EtherscanClient;
+    /// # use foundry_config::{etherscan::ResolvedEtherscanConfig, NamedChain};
     /// # async fn foo() -> eyre::Result<()> {
+    /// let config = ResolvedEtherscanConfig::create("<etherscan_api_key>", NamedChain::Mainnet);
+    /// let client = EtherscanClient::new(config.unwrap(), 5)?;
     /// assert_eq!(
     ///     "/*
     ///             - Bytecode Verification performed was compared on second iteration -
     ///             This file is part of the DAO.....",
-    ///     Cast::etherscan_source(
-    ///         NamedChain::Mainnet.into(),
-    ///         "0xBB9bc244D798123fDe783fCc1C72d3Bb8C189413".to_string(),
-    ///         "<etherscan_api_key>".to_string()
-    ///     )
-    ///     .await
-    ///     .unwrap()
-    ///     .as_str()
+    ///     Cast::etherscan_source(&client, "0xBB9bc244D798123fDe783fCc1C72d3Bb8C189413".to_string())
+    ///         .await
+    ///         .unwrap()
+    ///         .as_str()
     /// );
     /// # Ok(())
     /// # }
     /// ```
     pub async fn etherscan_source(
-        chain: Chain,
+        client: &EtherscanClient,
         contract_address: String,
-        etherscan_api_key: String,
     ) -> Result<String> {
-        let client = Client::new(chain, etherscan_api_key)?;
-        let metadata = client.contract_source_code(contract_address.parse()?).await?;
+        let address = contract_address.parse()?;
+        let metadata = client.request(|client| client.contract_source_code(address)).await?;
         Ok(metadata.source_code())
     }
 
@@ -1896,13 +1954,15 @@ impl SimpleCast {
     ///
     /// ```
     /// # use cast::SimpleCast as Cast;
-    /// # use foundry_config::NamedChain;
+    /// # use foundry_common::etherscan::EtherscanClient;
+    /// # use foundry_config::{etherscan::ResolvedEtherscanConfig, NamedChain};
     /// # use std::path::PathBuf;
     /// # async fn expand() -> eyre::Result<()> {
+    /// let config = ResolvedEtherscanConfig::create("<etherscan_api_key>", NamedChain::Mainnet);
+    /// let client = EtherscanClient::new(config.unwrap(), 5)?;
     /// Cast::expand_etherscan_source_to_directory(
-    ///     NamedChain::Mainnet.into(),
+    ///     &client,
     ///     "0xBB9bc244D798123fDe783fCc1C72d3Bb8C189413".to_string(),
-    ///     "<etherscan_api_key>".to_string(),
     ///     PathBuf::from("output_dir"),
     /// )
     /// .await?;
@@ -1910,13 +1970,12 @@ impl SimpleCast {
     /// # }
     /// ```
     pub async fn expand_etherscan_source_to_directory(
-        chain: Chain,
+        client: &EtherscanClient,
         contract_address: String,
-        etherscan_api_key: String,
         output_directory: PathBuf,
     ) -> eyre::Result<()> {
-        let client = Client::new(chain, etherscan_api_key)?;
-        let meta = client.contract_source_code(contract_address.parse()?).await?;
+        let address = contract_address.parse()?;
+        let meta = client.request(|client| client.contract_source_code(address)).await?;
         let source_tree = meta.source_tree();
         source_tree.write_to(&output_directory)?;
         Ok(())
@@ -2036,6 +2095,27 @@ fn strip_0x(s: &str) -> &str {
     s.strip_prefix("0x").unwrap_or(s)
 }
 
+/// Formats an account proof, with a node of the proofs per line.
+fn pretty_proof(proof: &EIP1186AccountProofResponse) -> String {
+    let mut s = format!(
+        "address              {}\nbalance              {}\nnonce                {}\ncodeHash             {}\nstorageHash          {}\naccountProof",
+        proof.address, proof.balance, proof.nonce, proof.code_hash, proof.storage_hash
+    );
+    for node in &proof.account_proof {
+        s.push_str(&format!("\n  {node}"));
+    }
+    for storage in &proof.storage_proof {
+        s.push_str(&format!(
+            "\nstorageProof\n  key                {}\n  value              {}\n  proof",
+            storage.key.0, storage.value
+        ));
+        for node in &storage.proof {
+            s.push_str(&format!("\n    {node}"));
+        }
+    }
+    s
+}
+
 #[cfg(test)]
 mod tests {
     use super::SimpleCast as Cast;
@@ -1,7 +1,13 @@
 //! Contains various tests for checking cast commands
 
+use alloy_primitives::U256;
+use anvil::{spawn, NodeConfig};
 use foundry_common::rpc::{next_http_rpc_endpoint, next_ws_rpc_endpoint};
-use foundry_test_utils::{casttest, util::OutputExt};
+use foundry_test_utils::{
+    casttest,
+    foundry_compilers::PathStyle,
+    util::{setup_cast, OutputExt},
+};
 use std::{fs, io::Write, path::Path};
 
 // tests `--help` is printed to std out
@@ -131,6 +137,53 @@ casttest!(wallet_sign_typed_data_file, |_prj, cmd| {
     assert_eq!(output.trim(), "0x06c18bdc8163219fddc9afaf5a0550e381326474bb757c86dc32317040cf384e07a2c72ce66c1a0626b6750ca9b6c035bf6f03e7ed67ae2d1134171e9085c0b51b");
 });
 
+// tests that `cast wallet sign --eip712` signs the typed data of a JSON file
+casttest!(wallet_sign_typed_data_eip712, |_prj, cmd| {
+    cmd.args([
+        "wallet",
+        "sign",
+        "--private-key",
+        "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "--eip712",
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/sign_typed_data.json")
+            .into_os_string()
+            .into_string()
+            .unwrap()
+            .as_str(),
+    ]);
+    let output = cmd.stdout_lossy();
+    assert_eq!(output.trim(), "0x06c18bdc8163219fddc9afaf5a0550e381326474bb757c86dc32317040cf384e07a2c72ce66c1a0626b6750ca9b6c035bf6f03e7ed67ae2d1134171e9085c0b51b");
+});
+
+// tests that `cast eip712` outputs the EIP-712 hashes of the spec's example
+casttest!(eip712_hash_example, |_prj, cmd| {
+    cmd.args([
+        "eip712",
+        "hash-type",
+        "Mail(Person from,Person to,string contents)Person(string name,address wallet)",
+    ]);
+    let output = cmd.stdout_lossy();
+    assert_eq!(output.trim(), "0xa0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2");
+
+    cmd.cast_fuse().args([
+        "eip712",
+        "hash-struct",
+        "--types",
+        r#"{"Person":[{"name":"name","type":"string"},{"name":"wallet","type":"address"}],"Mail":[{"name":"from","type":"Person"},{"name":"to","type":"Person"},{"name":"contents","type":"string"}]}"#,
+        "--primary",
+        "Mail",
+        "--data",
+        r#"{"from":{"name":"Cow","wallet":"0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},"to":{"name":"Bob","wallet":"0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},"contents":"Hello, Bob!"}"#,
+        "--domain",
+        r#"{"name":"Ether Mail","version":"1","chainId":1,"verifyingContract":"0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"}"#,
+        "--only",
+        "digest",
+    ]);
+    let output = cmd.stdout_lossy();
+    assert_eq!(output.trim(), "0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2");
+});
+
 // tests that `cast wallet list` outputs the local accounts
 casttest!(wallet_list_local_accounts, |prj, cmd| {
     let keystore_path = prj.root().join("keystore");
@@ -191,6 +244,36 @@ casttest!(estimate_contract_deploy_gas, |_prj, cmd| {
     assert!(gas > 0);
 });
 
+// tests that `cast estimate --create` lists the libraries of a project contract left unlinked
+casttest!(estimate_contract_deploy_missing_libraries, |prj, cmd| {
+    prj.add_source(
+        "Token",
+        r#"
+pragma solidity 0.8.23;
+
+library Math {
+    function double(uint256 x) public pure returns (uint256) {
+        return x * 2;
+    }
+}
+
+contract Token {
+    uint256 public supply;
+
+    constructor(uint256 _supply) {
+        supply = Math.double(_supply);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["estimate", "--create", "Token", "--constructor-args", "100"]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("missing library addresses for Token"), "{err}");
+    assert!(err.contains("src/Token.sol:Math"), "{err}");
+});
+
 // tests that the `cast upload-signatures` command works correctly
 casttest!(upload_signatures, |_prj, cmd| {
     // test no prefix is accepted as function
@@ -336,6 +419,50 @@ casttest!(calldata_array, |_prj, cmd| {
     );
 });
 
+// tests that `cast pretty-calldata` decodes the calls wrapped by a multicall with the ABIs of the
+// local artifacts
+casttest!(pretty_calldata_decodes_wrapped_calls, |prj, cmd| {
+    let artifacts = prj.root().join("out/Token.sol");
+    fs::create_dir_all(&artifacts).unwrap();
+    fs::write(
+        artifacts.join("Token.json"),
+        r#"{"abi":[
+            {"type":"function","name":"aggregate3","inputs":[{"name":"calls","type":"tuple[]","components":[{"name":"target","type":"address"},{"name":"allowFailure","type":"bool"},{"name":"callData","type":"bytes"}]}],"outputs":[],"stateMutability":"payable"},
+            {"type":"function","name":"transfer","inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"outputs":[{"name":"","type":"bool"}],"stateMutability":"nonpayable"}
+        ]}"#,
+    )
+    .unwrap();
+
+    let token = "0x0101010101010101010101010101010101010101";
+    let recipient = "0x0202020202020202020202020202020202020202";
+    cmd.args(["calldata", "transfer(address,uint256)", recipient, "1"]);
+    let transfer = cmd.stdout_lossy().trim().to_string();
+    cmd.cast_fuse().args([
+        "calldata",
+        "aggregate3((address,bool,bytes)[])",
+        &format!("[({token},false,{transfer})]"),
+    ]);
+    let calldata = cmd.stdout_lossy().trim().to_string();
+
+    cmd.cast_fuse().args(["pretty-calldata", "--offline", "--json", &calldata]);
+    let decoded: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(decoded["signature"], "aggregate3((address,bool,bytes)[])");
+    assert_eq!(decoded["source"], "local");
+    assert_eq!(decoded["args"][0]["name"], "calls");
+    let inner = &decoded["inner"][0];
+    assert_eq!(inner["to"].as_str().unwrap().to_lowercase(), token);
+    assert_eq!(inner["signature"], "transfer(address,uint256)");
+    assert_eq!(inner["source"], "local");
+    assert_eq!(inner["args"][0]["name"], "to");
+    assert_eq!(inner["args"][1]["value"], "1");
+
+    cmd.cast_fuse().args(["pretty-calldata", "--offline", &calldata]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("Method: aggregate3((address,bool,bytes)[]) (from local artifacts)"));
+    assert!(output.contains("Inner call 0:"), "{output}");
+    assert!(output.contains("amount (uint256): 1"), "{output}");
+});
+
 // <https://github.com/foundry-rs/foundry/issues/2705>
 casttest!(run_succeeds, |_prj, cmd| {
     let rpc = next_http_rpc_endpoint();
@@ -352,6 +479,38 @@ casttest!(run_succeeds, |_prj, cmd| {
     assert!(!output.contains("Revert"));
 });
 
+casttest!(run_bundle_succeeds, |prj, cmd| {
+    let rpc = next_http_rpc_endpoint();
+    let bundle = prj.root().join("bundle.json");
+    fs::write(
+        &bundle,
+        r#"[
+            {"from": "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045", "to": "0x000000000000000000000000000000000000bEEF", "value": "0x1"},
+            {"from": "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045", "to": "0x000000000000000000000000000000000000bEEF", "value": "0x2"}
+        ]"#,
+    )
+    .unwrap();
+    cmd.args([
+        "run",
+        "--bundle",
+        bundle.to_str().unwrap(),
+        "--block",
+        "18000000",
+        "--block-override",
+        "timestamp=1700000000,basefee=0",
+        "--json",
+        "--rpc-url",
+        rpc.as_str(),
+    ]);
+    let output: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let transactions = output["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert!(transactions.iter().all(|tx| tx["success"] == true));
+    let beef = &output["stateDiff"]["0x000000000000000000000000000000000000bEEF"];
+    let balance = |field: &str| beef["balance"][field].as_str().unwrap().parse::<U256>().unwrap();
+    assert_eq!(balance("to") - balance("from"), U256::from(3));
+});
+
 // tests that `cast --to-base` commands are working correctly.
 casttest!(to_base, |_prj, cmd| {
     let values = [
@@ -687,6 +846,27 @@ casttest!(storage, |_prj, cmd| {
     assert_eq!(cmd.stdout_lossy().trim(), issued);
 });
 
+// tests that `cast proof` verifies the proofs and prints them in each format
+casttest!(proof, |_prj, cmd| {
+    let rpc = next_http_rpc_endpoint();
+    let usdt = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+    let decimals_slot = "0x09";
+    let args = ["proof", usdt, decimals_slot, "--rpc-url", &rpc, "--block", "18332681"];
+
+    cmd.cast_fuse().args(args);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("storageProof"), "{output}");
+    assert!(output.contains("value              6"), "{output}");
+
+    cmd.cast_fuse().args(args).arg("--json");
+    let proof: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(proof["storageProof"][0]["value"], "0x6");
+
+    cmd.cast_fuse().args(args).arg("--encode-for-verifier");
+    let encoded = cmd.stdout_lossy();
+    assert!(encoded.trim().starts_with("0x"), "{encoded}");
+});
+
 // <https://github.com/foundry-rs/foundry/issues/6319>
 casttest!(storage_layout, |_prj, cmd| {
     cmd.cast_fuse().args([
@@ -757,3 +937,47 @@ casttest!(balance, |_prj, cmd| {
     assert_ne!(usdt_result, "0x0000000000000000000000000000000000000000000000000000000000000000");
     assert_eq!(alias_result, usdt_result);
 });
+
+// tests that a nonce gap in anvil's transaction pool is detected and filled
+#[tokio::test(flavor = "multi_thread")]
+async fn nonce_gaps_fill() {
+    let (_prj, mut cmd) = setup_cast("nonce_gaps_fill", PathStyle::Dapptools);
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let rpc = handle.http_endpoint();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let from = format!("{:?}", wallet.address());
+    let pk = hex::encode(wallet.signer().to_bytes());
+
+    // nonce 0 is never sent, the transaction at nonce 1 waits in the pool
+    cmd.cast_fuse().args([
+        "send",
+        "0x000000000000000000000000000000000000dEaD",
+        "--value",
+        "1",
+        "--nonce",
+        "1",
+        "--async",
+        "--private-key",
+        &pk,
+        "--rpc-url",
+        &rpc,
+    ]);
+    cmd.assert_non_empty_stdout();
+
+    cmd.cast_fuse().args(["nonce-gaps", &from, "--rpc-url", &rpc]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Latest nonce:  0"), "{stdout}");
+    assert!(stdout.contains("Missing nonces: 0"), "{stdout}");
+
+    cmd.cast_fuse().args(["send", "--fill-nonce-gap", "--private-key", &pk, "--rpc-url", &rpc]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("Filling nonce gap at nonce 0"), "{stdout}\n{stderr}");
+
+    // the self-transfer unblocks the queued transaction
+    cmd.cast_fuse().args(["nonce", &from, "--rpc-url", &rpc]);
+    assert_eq!(cmd.stdout_lossy().trim(), "2");
+
+    cmd.cast_fuse().args(["nonce-gaps", &from, "--rpc-url", &rpc]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("No transactions in the pool."), "{stdout}");
+}
@@ -76,13 +76,19 @@ impl Cheatcodes<'static> {
                 Vm::Log::STRUCT.clone(),
                 Vm::Rpc::STRUCT.clone(),
                 Vm::EthGetLogs::STRUCT.clone(),
+                Vm::EthGetProof::STRUCT.clone(),
+                Vm::EthStorageProof::STRUCT.clone(),
                 Vm::DirEntry::STRUCT.clone(),
                 Vm::FsMetadata::STRUCT.clone(),
                 Vm::Wallet::STRUCT.clone(),
                 Vm::FfiResult::STRUCT.clone(),
+                Vm::FfiEnvVar::STRUCT.clone(),
+                Vm::FfiRequest::STRUCT.clone(),
+                Vm::FfiExtResult::STRUCT.clone(),
                 Vm::ChainInfo::STRUCT.clone(),
                 Vm::AccountAccess::STRUCT.clone(),
                 Vm::StorageAccess::STRUCT.clone(),
+                Vm::TxContext::STRUCT.clone(),
             ]),
             enums: Cow::Owned(vec![
                 Vm::CallerMode::ENUM.clone(),
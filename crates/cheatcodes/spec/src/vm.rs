@@ -103,6 +103,34 @@ interface Vm {
         bool removed;
     }
 
+    /// An EIP-1186 account proof. Returned by `getProof`.
+    struct EthGetProof {
+        /// The address of the account.
+        address account;
+        /// The balance of the account.
+        uint256 balance;
+        /// The nonce of the account.
+        uint256 nonce;
+        /// The hash of the code of the account.
+        bytes32 codeHash;
+        /// The root of the storage trie of the account.
+        bytes32 storageHash;
+        /// The RLP-encoded nodes of the state trie, from the state root to the account.
+        bytes[] accountProof;
+        /// The proofs of the requested storage slots.
+        EthStorageProof[] storageProof;
+    }
+
+    /// An EIP-1186 storage proof. Part of `EthGetProof`.
+    struct EthStorageProof {
+        /// The storage slot.
+        bytes32 key;
+        /// The value of the slot.
+        uint256 value;
+        /// The RLP-encoded nodes of the storage trie, from the storage root to the slot.
+        bytes[] proof;
+    }
+
     /// A single entry in a directory listing. Returned by `readDir`.
     struct DirEntry {
         /// The error message, if any.
@@ -161,6 +189,40 @@ interface Vm {
         bytes stderr;
     }
 
+    /// An environment variable of an `ffiExt` call.
+    struct FfiEnvVar {
+        /// The name of the variable.
+        string name;
+        /// The value of the variable.
+        string value;
+    }
+
+    /// The command to run with `ffiExt`.
+    struct FfiRequest {
+        /// The command and its arguments.
+        string[] commandInput;
+        /// The data written to the `stdin` of the command.
+        bytes stdin;
+        /// The maximum runtime of the command in milliseconds, or zero for no limit.
+        uint64 timeoutMs;
+        /// The working directory, relative to the project root. Empty for the project root.
+        string workingDir;
+        /// The environment of the command. Only `PATH` is inherited from the current environment.
+        FfiEnvVar[] env;
+    }
+
+    /// The result of an `ffiExt` call.
+    struct FfiExtResult {
+        /// The exit code of the call.
+        int32 exitCode;
+        /// The raw `stdout` data.
+        bytes stdout;
+        /// The `stderr` data.
+        bytes stderr;
+        /// True if the command was killed because it ran longer than the timeout.
+        bool timedOut;
+    }
+
     /// Information on the chain and fork.
     struct ChainInfo {
         /// The fork identifier. Set to zero if no fork is active.
@@ -223,6 +285,29 @@ interface Vm {
         bool reverted;
     }
 
+    /// The transaction and block properties as the EVM currently sees them.
+    /// Returned by `getTxContext` and applied by `setTxContext`.
+    struct TxContext {
+        /// The `tx.origin`.
+        address origin;
+        /// The `tx.gasprice`.
+        uint256 gasPrice;
+        /// The `block.basefee`.
+        uint256 basefee;
+        /// The `block.blobbasefee`.
+        uint256 blobBaseFee;
+        /// The `block.coinbase`.
+        address coinbase;
+        /// The `block.prevrandao`, or `block.difficulty` before the Paris hard fork.
+        bytes32 prevrandao;
+        /// The `block.chainid`.
+        uint256 chainId;
+        /// The `block.number`.
+        uint256 blockNumber;
+        /// The `block.timestamp`.
+        uint256 timestamp;
+    }
+
     // ======== EVM ========
 
     /// Gets the address for a given private key.
@@ -349,6 +434,16 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Safe)]
     function getBlockTimestamp() external view returns (uint256 timestamp);
 
+    /// Gets the current transaction and block properties.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function getTxContext() external view returns (TxContext memory context);
+
+    /// Sets all the transaction and block properties at once.
+    /// Fields set to their maximum value (`address(type(uint160).max)`, `type(uint256).max` or
+    /// `bytes32(type(uint256).max)`) are left unchanged.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function setTxContext(TxContext calldata context) external;
+
     // -------- Account State --------
 
     /// Sets an address' balance.
@@ -359,6 +454,13 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function etch(address target, bytes calldata newRuntimeBytecode) external;
 
+    /// Registers a precompile at `target`. Calls to `target` bypass its code and are handled by
+    /// the plugin at `artifactOrPluginPath`, a native library or a WebAssembly module, or by the
+    /// deployed bytecode of the given contract artifact, executed without access to the state.
+    /// Loading native plugins requires FFI to be enabled.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function etchPrecompile(address target, string calldata artifactOrPluginPath) external;
+
     /// Resets the nonce of an account to 0 for EOAs and 1 for contract accounts.
     #[cheatcode(group = Evm, safety = Unsafe)]
     function resetNonce(address account) external;
@@ -407,6 +509,16 @@ interface Vm {
     function mockCallRevert(address callee, uint256 msgValue, bytes calldata data, bytes calldata revertData)
         external;
 
+    /// Mocks multiple calls to an address, returning the return data at the same index as the
+    /// calldata. Both arrays must have the same length.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function mockCalls(address callee, bytes[] calldata data, bytes[] calldata returnData) external;
+
+    /// Mocks a call to any address with the given selector, returning specified data.
+    /// Has the lowest precedence: only applies to callees without a mock matching the call.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function mockCallAny(bytes4 selector, bytes calldata returnData) external;
+
     // --- Impersonation (pranks) ---
 
     /// Sets the *next* call's `msg.sender` to be the input address.
@@ -536,6 +648,16 @@ interface Vm {
         external
         returns (EthGetLogs[] memory logs);
 
+    /// Gets the EIP-1186 account and storage proofs of `account` at the block of the active fork,
+    /// verified against the state root of the block.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function getProof(address account, bytes32[] calldata slots) external returns (EthGetProof memory proof);
+
+    /// Fetches `accounts` and their `slots` from the active fork into its cache at once, e.g. in
+    /// `setUp`, instead of one request per account and slot as they're accessed.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function prefetch(address[] calldata accounts, bytes32[][] calldata slots) external;
+
     // --- Behavior ---
 
     /// In forking mode, explicitly grant the given address cheatcode access.
@@ -589,6 +711,21 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Safe)]
     function resumeGasMetering() external;
 
+    /// Starts measuring the gas used by the caller, recorded under `name` in the gas snapshot of
+    /// the test once stopped with `stopSnapshotGas`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function startSnapshotGas(string calldata name) external;
+
+    /// Stops measuring the gas started with `startSnapshotGas`, and returns the gas used in
+    /// between, excluding the calls to the cheatcodes.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function stopSnapshotGas(string calldata name) external returns (uint256 gasUsed);
+
+    /// Records the gas used by the last external call under `name` in the gas snapshot of the
+    /// test, and returns it.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function snapshotGasLastCall(string calldata name) external returns (uint256 gasUsed);
+
     // ======== Test Assertions and Utilities ========
 
     /// If the condition is false, discard this run's fuzz inputs and generate new ones.
@@ -686,6 +823,37 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectRevert(bytes calldata revertData) external;
 
+    /// Expects an error with any revert data on next call, originating from the `reverter` address.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(address reverter) external;
+
+    /// Expects an error on next call that starts with the revert data, originating from the `reverter` address.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(bytes4 revertData, address reverter) external;
+
+    /// Expects an error on next call that exactly matches the revert data, originating from the `reverter` address.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(bytes calldata revertData, address reverter) external;
+
+    /// Expects an error that starts with the revert data on the next call made `depth` calls below the next call,
+    /// e.g. `1` for the first call of a helper wrapping the reverting call.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(bytes4 revertData, uint64 depth) external;
+
+    /// Expects an error that exactly matches the revert data on the next call made `depth` calls below the next call,
+    /// e.g. `1` for the first call of a helper wrapping the reverting call.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(bytes calldata revertData, uint64 depth) external;
+
+    /// Expects an error on next call whose selector matches `revertData`, regardless of the error's arguments.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectPartialRevert(bytes4 revertData) external;
+
+    /// Expects an error on next call whose selector matches `revertData`, regardless of the error's arguments,
+    /// originating from the `reverter` address.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectPartialRevert(bytes4 revertData, address reverter) external;
+
     /// Expects an error on next cheatcode call with any revert data.
     #[cheatcode(group = Testing, safety = Unsafe, status = Internal)]
     function _expectCheatcodeRevert() external;
@@ -713,6 +881,20 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectSafeMemoryCall(uint64 min, uint64 max) external;
 
+    /// Expects that the next call, including all of its subcalls, writes to `slot` of `target`. Any other
+    /// write to the storage of `target` fails the expectation.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectStorageWrite(address target, bytes32 slot) external;
+
+    /// Expects that the next call, including all of its subcalls, sets `slot` of `target` to `newValue`.
+    /// Any other write to the storage of `target` fails the expectation.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectStorageWrite(address target, bytes32 slot, bytes32 newValue) external;
+
+    /// Expects that the next call, including all of its subcalls, doesn't write to the storage of `target`.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectNoStorageWrites(address target) external;
+
     /// Marks a test as skipped. Must be called at the top of the test.
     #[cheatcode(group = Testing, safety = Unsafe)]
     function skip(bool skipTest) external;
@@ -1399,6 +1581,12 @@ interface Vm {
     #[cheatcode(group = Filesystem)]
     function tryFfi(string[] calldata commandInput) external returns (FfiResult memory result);
 
+    /// Performs a foreign function call with the given `stdin`, timeout, working directory and
+    /// environment, and returns the exit code, the raw `stdout` and `stderr`, and whether it timed out.
+    /// The command and the processes it spawned are killed on timeout.
+    #[cheatcode(group = Filesystem)]
+    function ffiExt(FfiRequest calldata request) external returns (FfiExtResult memory result);
+
     // ======== Environment Variables ========
 
     /// Sets environment variables.
@@ -1587,6 +1775,12 @@ interface Vm {
     #[cheatcode(group = Scripting)]
     function stopBroadcast() external;
 
+    /// Asserts that the balance of `account` is at least `minBalance` once all collected transactions
+    /// are broadcast, accounting for their worst-case gas costs.
+    /// Checked against the on-chain simulation, the script isn't broadcast if it fails.
+    #[cheatcode(group = Scripting)]
+    function assertBroadcastBalanceAbove(address account, uint256 minBalance) external;
+
     // ======== Utilities ========
 
     // -------- Strings --------
@@ -1878,6 +2072,22 @@ interface Vm {
     #[cheatcode(group = Utilities)]
     function getLabel(address account) external view returns (string memory currentLabel);
 
+    /// Gets the address labeled `label` on the current chain in the address book, and labels it.
+    ///
+    /// The address book is a JSON file mapping chain IDs to labeled addresses, e.g.
+    /// `{"1": {"WETH": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"}}`. Its path is set with the
+    /// `address_book` config value, `addresses.json` by default.
+    #[cheatcode(group = Utilities)]
+    function getAddress(string calldata label) external returns (address addr);
+
+    /// Gets the address labeled `label` on the given chain in the address book, and labels it.
+    #[cheatcode(group = Utilities)]
+    function getAddressOn(uint256 chainId, string calldata label) external returns (address addr);
+
+    /// Sets the path of the address book resolved by `getAddress` and `getAddressOn`.
+    #[cheatcode(group = Utilities)]
+    function setAddressBookPath(string calldata path) external;
+
     /// Compute the address a contract will be deployed at for a given deployer address and nonce.
     #[cheatcode(group = Utilities)]
     function computeCreateAddress(address deployer, uint256 nonce) external pure returns (address);
@@ -7,7 +7,7 @@ use foundry_config::{
     cache::StorageCachingConfig, fs_permissions::FsAccessKind, Config, FsPermissions,
     ResolvedRpcEndpoints,
 };
-use foundry_evm_core::opts::EvmOpts;
+use foundry_evm_core::{decode::RevertDecoder, opts::EvmOpts};
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
@@ -38,8 +38,12 @@ pub struct CheatsConfig {
     pub evm_opts: EvmOpts,
     /// Address labels from config
     pub labels: HashMap<Address, String>,
+    /// The address book resolved by the `getAddress` cheatcodes
+    pub address_book: PathBuf,
     /// Script wallets
     pub script_wallets: Option<ScriptWallets>,
+    /// Decoder of the project's custom errors, used in the failure messages of `expectRevert`
+    pub revert_decoder: RevertDecoder,
 }
 
 impl CheatsConfig {
@@ -63,7 +67,9 @@ impl CheatsConfig {
             allowed_paths,
             evm_opts,
             labels: config.labels.clone(),
+            address_book: config.__root.0.join(&config.address_book),
             script_wallets,
+            revert_decoder: Default::default(),
         }
     }
 
@@ -179,7 +185,9 @@ impl Default for CheatsConfig {
             allowed_paths: vec![],
             evm_opts: Default::default(),
             labels: Default::default(),
+            address_book: Default::default(),
             script_wallets: None,
+            revert_decoder: Default::default(),
         }
     }
 }
@@ -4,7 +4,17 @@ use crate::{string, Cheatcode, Cheatcodes, Error, Result, Vm::*};
 use alloy_dyn_abi::DynSolType;
 use alloy_primitives::Bytes;
 use alloy_sol_types::SolValue;
-use std::env;
+use std::{collections::BTreeSet, env, sync::Mutex};
+
+/// The names of the environment variables read with the `env*` cheatcodes by any [`Cheatcodes`]
+/// instance of the process.
+static PROCESS_ENV_VARS_READ: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Returns the names of the environment variables read with the `env*` cheatcodes so far, by all
+/// the tests and scripts executed in the process rather than a single one.
+pub fn process_env_vars_read() -> BTreeSet<String> {
+    PROCESS_ENV_VARS_READ.lock().unwrap().clone()
+}
 
 impl Cheatcode for setEnvCall {
     fn apply(&self, _state: &mut Cheatcodes) -> Result {
@@ -254,6 +264,7 @@ fn env_array_default<T: SolValue>(key: &str, delim: &str, default: &T, ty: &DynS
 }
 
 fn get_env(key: &str) -> Result<String> {
+    PROCESS_ENV_VARS_READ.lock().unwrap().insert(key.to_string());
     match env::var(key) {
         Ok(val) => Ok(val),
         Err(env::VarError::NotPresent) => Err(fmt_err!("environment variable {key:?} not found")),
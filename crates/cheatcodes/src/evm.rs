@@ -6,21 +6,29 @@ use alloy_primitives::{Address, Bytes, B256, U256};
 use alloy_signer::Signer;
 use alloy_sol_types::SolValue;
 use foundry_common::fs::{read_json_file, write_json_file};
+use foundry_config::fs_permissions::FsAccessKind;
 use foundry_evm_core::{
     backend::{DatabaseExt, RevertSnapshotAction},
     constants::{CALLER, CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS, TEST_CONTRACT_ADDRESS},
+    precompiles::CustomPrecompile,
 };
 use revm::{
-    primitives::{Account, Bytecode, SpecId, KECCAK_EMPTY},
+    primitives::{Account, BlobExcessGasAndPrice, Bytecode, SpecId, KECCAK_EMPTY},
     EVMData,
 };
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
 
 mod fork;
 pub(crate) mod mapping;
 pub(crate) mod mock;
 pub(crate) mod prank;
 
+/// File extensions of native precompile plugins accepted by `etchPrecompile`.
+const NATIVE_PLUGIN_EXTENSIONS: &[&str] = &["so", "dylib", "dll"];
+
 /// Records storage slots reads and writes.
 #[derive(Clone, Debug, Default)]
 pub struct RecordAccess {
@@ -41,6 +49,47 @@ pub struct DealRecord {
     pub new_balance: U256,
 }
 
+/// Named gas measurements of a test, taken with the `snapshotGas` cheatcodes.
+#[derive(Clone, Debug, Default)]
+pub struct GasSnapshots {
+    /// The gas used, by snapshot name.
+    pub recorded: BTreeMap<String, u64>,
+    /// The measurements started with `startSnapshotGas`, by snapshot name.
+    pub active: HashMap<String, ActiveGasSnapshot>,
+    /// The gas used by the last external call of each frame, by frame depth. Calls to the
+    /// cheatcode and console addresses are not recorded.
+    pub last_call_gas: HashMap<u64, u64>,
+}
+
+/// A gas measurement started with `startSnapshotGas`.
+#[derive(Clone, Debug, Default)]
+pub struct ActiveGasSnapshot {
+    /// The depth of the measured frame.
+    pub depth: u64,
+    /// The gas spent by the frame right after the call to `startSnapshotGas`.
+    pub start: Option<u64>,
+    /// The gas spent by the frame before its current opcode.
+    pub last: u64,
+}
+
+impl GasSnapshots {
+    /// Updates the measurements of the frame at `depth`, which has spent `spent` gas before its
+    /// current opcode.
+    pub fn step(&mut self, depth: u64, spent: u64) {
+        for snapshot in self.active.values_mut().filter(|snapshot| snapshot.depth == depth) {
+            snapshot.start.get_or_insert(spent);
+            snapshot.last = spent;
+        }
+    }
+
+    /// Records the gas used under `name`, which must be unique within the test.
+    fn record(&mut self, name: &str, gas_used: u64) -> Result {
+        ensure!(!self.recorded.contains_key(name), "gas snapshot `{name}` was already recorded");
+        self.recorded.insert(name.to_string(), gas_used);
+        Ok(U256::from(gas_used).abi_encode())
+    }
+}
+
 impl Cheatcode for addrCall {
     fn apply(&self, _state: &mut Cheatcodes) -> Result {
         let Self { privateKey } = self;
@@ -210,6 +259,45 @@ impl Cheatcode for resumeGasMeteringCall {
     }
 }
 
+impl Cheatcode for startSnapshotGasCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { name } = self;
+        let snapshots = &mut ccx.state.gas_snapshots;
+        ensure!(
+            !snapshots.recorded.contains_key(name) && !snapshots.active.contains_key(name),
+            "gas snapshot `{name}` was already recorded"
+        );
+        let depth = ccx.data.journaled_state.depth();
+        snapshots.active.insert(name.clone(), ActiveGasSnapshot { depth, ..Default::default() });
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for stopSnapshotGasCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        let snapshot = state
+            .gas_snapshots
+            .active
+            .remove(name)
+            .ok_or_else(|| fmt_err!("gas snapshot `{name}` was not started"))?;
+        let gas_used = snapshot.start.map_or(0, |start| snapshot.last.saturating_sub(start));
+        state.gas_snapshots.record(name, gas_used)
+    }
+}
+
+impl Cheatcode for snapshotGasLastCallCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { name } = self;
+        let depth = ccx.data.journaled_state.depth();
+        let snapshots = &mut ccx.state.gas_snapshots;
+        let Some(&gas_used) = snapshots.last_call_gas.get(&depth) else {
+            bail!("no external call was made before `snapshotGasLastCall`")
+        };
+        snapshots.record(name, gas_used)
+    }
+}
+
 impl Cheatcode for chainIdCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { newChainId } = self;
@@ -299,6 +387,91 @@ impl Cheatcode for getBlockTimestampCall {
     }
 }
 
+impl Cheatcode for getTxContextCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self {} = self;
+        let env = &ccx.data.env;
+        let prevrandao = if env.cfg.spec_id >= SpecId::MERGE {
+            env.block.prevrandao.unwrap_or_default()
+        } else {
+            env.block.difficulty.into()
+        };
+        let blob_base_fee = env.block.get_blob_gasprice().map(U256::from).unwrap_or_default();
+        Ok(TxContext {
+            origin: env.tx.caller,
+            gasPrice: env.tx.gas_price,
+            basefee: env.block.basefee,
+            blobBaseFee: blob_base_fee,
+            coinbase: env.block.coinbase,
+            prevrandao,
+            chainId: U256::from(env.cfg.chain_id),
+            blockNumber: env.block.number,
+            timestamp: env.block.timestamp,
+        }
+        .abi_encode())
+    }
+}
+
+impl Cheatcode for setTxContextCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { context } = self;
+        let TxContext {
+            origin,
+            gasPrice,
+            basefee,
+            blobBaseFee,
+            coinbase,
+            prevrandao,
+            chainId,
+            blockNumber,
+            timestamp,
+        } = context;
+        let set = |value: &U256| (*value != U256::MAX).then_some(*value);
+        let set_address =
+            |value: &Address| (*value != Address::repeat_byte(0xff)).then_some(*value);
+
+        if let Some(chain_id) = set(chainId) {
+            ensure!(chain_id <= U256::from(u64::MAX), "chain ID must be less than 2^64 - 1");
+            ccx.data.env.cfg.chain_id = chain_id.to();
+        }
+        let env = &mut ccx.data.env;
+        if let Some(origin) = set_address(origin) {
+            env.tx.caller = origin;
+        }
+        if let Some(gas_price) = set(gasPrice) {
+            env.tx.gas_price = gas_price;
+        }
+        if let Some(basefee) = set(basefee) {
+            env.block.basefee = basefee;
+        }
+        if let Some(blob_base_fee) = set(blobBaseFee) {
+            let excess_blob_gas =
+                env.block.blob_excess_gas_and_price.as_ref().map_or(0, |blob| blob.excess_blob_gas);
+            env.block.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice {
+                excess_blob_gas,
+                blob_gasprice: blob_base_fee.saturating_to(),
+            });
+        }
+        if let Some(coinbase) = set_address(coinbase) {
+            env.block.coinbase = coinbase;
+        }
+        if let Some(prevrandao) = set(&U256::from_be_bytes(prevrandao.0)) {
+            if env.cfg.spec_id >= SpecId::MERGE {
+                env.block.prevrandao = Some(prevrandao.into());
+            } else {
+                env.block.difficulty = prevrandao;
+            }
+        }
+        if let Some(number) = set(blockNumber) {
+            env.block.number = number;
+        }
+        if let Some(timestamp) = set(timestamp) {
+            env.block.timestamp = timestamp;
+        }
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for dealCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { account: address, newBalance: new_balance } = *self;
@@ -321,6 +494,37 @@ impl Cheatcode for etchCall {
     }
 }
 
+impl Cheatcode for etchPrecompileCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { target, artifactOrPluginPath: path } = self;
+        ensure_not_precompile!(target, ccx);
+        let plugin = Path::new(path);
+        let name = plugin.file_stem().map_or_else(|| path.clone(), |s| s.to_string_lossy().into());
+        let extension = plugin.extension().unwrap_or_default();
+        let precompile = if extension.eq_ignore_ascii_case("wasm") {
+            // WebAssembly plugins are sandboxed, unlike native ones
+            let plugin = ccx.state.config.ensure_path_allowed(plugin, FsAccessKind::Read)?;
+            CustomPrecompile::load(plugin)?
+        } else if NATIVE_PLUGIN_EXTENSIONS.iter().any(|e| extension.eq_ignore_ascii_case(e)) {
+            ensure!(
+                ccx.state.config.ffi,
+                "FFI is disabled; add the `--ffi` flag to allow tests to load precompile plugins"
+            );
+            let plugin = ccx.state.config.ensure_path_allowed(plugin, FsAccessKind::Read)?;
+            CustomPrecompile::load(plugin)?
+        } else {
+            let object = super::fs::read_bytecode(ccx.state, path)?;
+            let Some(code) = object.deployed_bytecode else {
+                bail!("no deployed bytecode for precompile artifact {path}")
+            };
+            CustomPrecompile::from_bytecode(path, code, ccx.data.env.cfg.spec_id)
+        };
+        ccx.state.custom_precompiles.insert(*target, precompile);
+        ccx.state.labels.insert(*target, format!("Precompile({name})"));
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for resetNonceCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { account } = self;
@@ -1,10 +1,12 @@
 use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*};
 use alloy_primitives::{B256, U256};
 use alloy_providers::provider::TempProvider;
-use alloy_rpc_types::Filter;
+use alloy_rpc_types::{BlockId, BlockNumberOrTag, Filter};
 use alloy_sol_types::SolValue;
 use eyre::WrapErr;
-use foundry_common::{provider::alloy::ProviderBuilder, types::ToEthers};
+use foundry_common::{
+    proof::verify_account_proof, provider::alloy::ProviderBuilder, types::ToEthers,
+};
 use foundry_compilers::utils::RuntimeOrHandle;
 use foundry_evm_core::fork::CreateFork;
 
@@ -290,6 +292,78 @@ impl Cheatcode for eth_getLogsCall {
     }
 }
 
+impl Cheatcode for getProofCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { account, slots } = self;
+        let url =
+            ccx.data.db.active_fork_url().ok_or_else(|| fmt_err!("no active fork URL found"))?;
+        let provider = ProviderBuilder::new(&url).build()?;
+        // the state of the fork is the state at its pinned block, regardless of `vm.roll`
+        let fork_id = ccx.data.db.active_fork_id().ok_or_else(|| fmt_err!("no active fork"))?;
+        let number = ccx
+            .data
+            .db
+            .ensure_fork_id(fork_id)?
+            .block_number()
+            .ok_or_else(|| fmt_err!("the active fork isn't pinned to a block"))?;
+        let block = BlockId::Number(BlockNumberOrTag::Number(number));
+
+        let runtime = RuntimeOrHandle::new();
+        let header = runtime
+            .block_on(provider.get_block(block, false))
+            .wrap_err("failed to get the block")?
+            .ok_or_else(|| fmt_err!("block {number} not found"))?
+            .header;
+        let proof = runtime
+            .block_on(provider.get_proof(*account, slots.clone(), Some(block)))
+            .wrap_err("failed to get the proof")?;
+        verify_account_proof(header.state_root, &proof).map_err(|err| {
+            fmt_err!("the proof doesn't match the state root of block {number}: {err:#}")
+        })?;
+
+        let proof = EthGetProof {
+            account: proof.address,
+            balance: proof.balance,
+            nonce: U256::from(proof.nonce.to::<u64>()),
+            codeHash: proof.code_hash,
+            storageHash: proof.storage_hash,
+            accountProof: proof.account_proof,
+            storageProof: proof
+                .storage_proof
+                .into_iter()
+                .map(|storage| EthStorageProof {
+                    key: storage.key.0,
+                    value: storage.value,
+                    proof: storage.proof,
+                })
+                .collect(),
+        };
+        Ok(proof.abi_encode())
+    }
+}
+
+impl Cheatcode for prefetchCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { accounts, slots } = self;
+        ensure!(
+            accounts.len() == slots.len(),
+            "accounts and slots must have the same length: {} != {}",
+            accounts.len(),
+            slots.len()
+        );
+        ensure!(ccx.data.db.is_forked_mode(), "no active fork to prefetch from");
+        let accounts = accounts
+            .iter()
+            .zip(slots)
+            .map(|(account, slots)| {
+                (*account, slots.iter().map(|slot| U256::from_be_bytes(slot.0)).collect())
+            })
+            .collect::<Vec<_>>();
+        ccx.data.db.prefetch(&accounts)?;
+        Ok(Default::default())
+    }
+}
+
 /// Creates and then also selects the new fork
 fn create_select_fork<DB: DatabaseExt>(
     ccx: &mut CheatsCtxt<DB>,
@@ -42,6 +42,7 @@ impl Cheatcode for clearMockedCallsCall {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self {} = self;
         state.mocked_calls = Default::default();
+        state.mocked_selectors = Default::default();
         Ok(Default::default())
     }
 }
@@ -49,17 +50,39 @@ impl Cheatcode for clearMockedCallsCall {
 impl Cheatcode for mockCall_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { callee, data, returnData } = self;
-        let (acc, _) = ccx.data.journaled_state.load_account(*callee, ccx.data.db)?;
+        etch_if_empty(ccx, callee)?;
+        mock_call(ccx.state, callee, data, None, returnData, InstructionResult::Return);
+        Ok(Default::default())
+    }
+}
 
-        // Etches a single byte onto the account if it is empty to circumvent the `extcodesize`
-        // check Solidity might perform.
-        let empty_bytecode = acc.info.code.as_ref().map_or(true, Bytecode::is_empty);
-        if empty_bytecode {
-            let code = Bytecode::new_raw(Bytes::from_static(&[0u8])).to_checked();
-            ccx.data.journaled_state.set_code(*callee, code);
+impl Cheatcode for mockCallsCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { callee, data, returnData } = self;
+        ensure!(
+            data.len() == returnData.len(),
+            "calldata and return data lengths don't match: {} != {}",
+            data.len(),
+            returnData.len()
+        );
+        etch_if_empty(ccx, callee)?;
+        for (data, return_data) in data.iter().zip(returnData) {
+            mock_call(ccx.state, callee, data, None, return_data, InstructionResult::Return);
         }
+        Ok(Default::default())
+    }
+}
 
-        mock_call(ccx.state, callee, data, None, returnData, InstructionResult::Return);
+impl Cheatcode for mockCallAnyCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { selector, returnData } = self;
+        state.mocked_selectors.insert(
+            *selector,
+            MockCallReturnData {
+                ret_type: InstructionResult::Return,
+                data: Bytes::copy_from_slice(returnData),
+            },
+        );
         Ok(Default::default())
     }
 }
@@ -89,6 +112,18 @@ impl Cheatcode for mockCallRevert_1Call {
     }
 }
 
+/// Etches a single byte onto the account if it is empty to circumvent the `extcodesize` check
+/// Solidity might perform.
+fn etch_if_empty<DB: DatabaseExt>(ccx: &mut CheatsCtxt<DB>, callee: &Address) -> Result<()> {
+    let (acc, _) = ccx.data.journaled_state.load_account(*callee, ccx.data.db)?;
+    let empty_bytecode = acc.info.code.as_ref().map_or(true, Bytecode::is_empty);
+    if empty_bytecode {
+        let code = Bytecode::new_raw(Bytes::from_static(&[0u8])).to_checked();
+        ccx.data.journaled_state.set_code(*callee, code);
+    }
+    Ok(())
+}
+
 #[allow(clippy::ptr_arg)] // Not public API, doesn't matter
 fn mock_call(
     state: &mut Cheatcodes,
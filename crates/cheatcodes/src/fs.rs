@@ -8,13 +8,19 @@ use foundry_common::{fs, get_artifact_path};
 use foundry_config::fs_permissions::FsAccessKind;
 use std::{
     collections::hash_map::Entry,
-    io::{BufRead, BufReader, Write},
+    io::{BufRead, BufReader, Read, Write},
     path::Path,
-    process::Command,
-    time::{SystemTime, UNIX_EPOCH},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use walkdir::WalkDir;
 
+/// How long the pipes of an FFI command are still read after it exited, in case the processes it
+/// left in the background keep them open.
+const FFI_PIPE_GRACE_PERIOD: Duration = Duration::from_millis(100);
+
 impl Cheatcode for existsCall {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { path } = self;
@@ -267,7 +273,7 @@ impl Cheatcode for getDeployedCodeCall {
 }
 
 /// Reads the bytecode object(s) from the matching artifact
-fn read_bytecode(state: &Cheatcodes, path: &str) -> Result<ContractObject> {
+pub(super) fn read_bytecode(state: &Cheatcodes, path: &str) -> Result<ContractObject> {
     let path = get_artifact_path(&state.config.paths, path);
     let path = state.config.ensure_path_allowed(path, FsAccessKind::Read)?;
     let data = fs::read_to_string(path)?;
@@ -296,6 +302,13 @@ impl Cheatcode for tryFfiCall {
     }
 }
 
+impl Cheatcode for ffiExtCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { request } = self;
+        ffi_ext(state, request).map(|res| res.abi_encode())
+    }
+}
+
 pub(super) fn write_file(state: &Cheatcodes, path: &Path, contents: &[u8]) -> Result {
     let path = state.config.ensure_path_allowed(path, FsAccessKind::Write)?;
     // write access to foundry.toml is not allowed
@@ -370,6 +383,134 @@ fn ffi(state: &Cheatcodes, input: &[String]) -> Result<FfiResult> {
     })
 }
 
+fn ffi_ext(state: &Cheatcodes, request: &FfiRequest) -> Result<FfiExtResult> {
+    let input = &request.commandInput;
+    ensure!(
+        state.config.ffi,
+        "FFI is disabled; add the `--ffi` flag to allow tests to call external commands"
+    );
+    ensure!(!input.is_empty() && !input[0].is_empty(), "can't execute empty command");
+    let mut cmd = Command::new(&input[0]);
+    cmd.args(&input[1..])
+        .current_dir(state.config.root.join(&request.workingDir))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // only `PATH` is inherited, so that the command can still be found
+    cmd.env_clear();
+    if let Some(path) = std::env::var_os("PATH") {
+        cmd.env("PATH", path);
+    }
+    cmd.envs(request.env.iter().map(|var| (&var.name, &var.value)));
+
+    // run the command in its own process group, so that its children can be killed with it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+
+    debug!(target: "cheatcodes", ?cmd, timeout_ms = request.timeoutMs, "invoking ffi");
+
+    let mut child =
+        cmd.spawn().map_err(|err| fmt_err!("failed to execute command {cmd:?}: {err}"))?;
+
+    // write and read the pipes concurrently, so that the command can't block on a full pipe
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let data = request.stdin.clone();
+    let writer = thread::spawn(move || {
+        // the command may exit without reading all of its input
+        let _ = stdin.write_all(&data);
+    });
+    let stdout = PipeReader::spawn(child.stdout.take().expect("stdout is piped"));
+    let stderr = PipeReader::spawn(child.stderr.take().expect("stderr is piped"));
+
+    let (status, timed_out) = if request.timeoutMs == 0 {
+        (child.wait()?, false)
+    } else {
+        wait_timeout(&mut child, Duration::from_millis(request.timeoutMs))?
+    };
+    if timed_out {
+        debug!(target: "cheatcodes", ?cmd, "ffi command timed out");
+    }
+
+    // the processes left by the command, e.g. those that escaped its process group, may keep the
+    // pipes open, so they're only read for a grace period and the writer isn't waited for
+    drop(writer);
+    let deadline = Instant::now() + FFI_PIPE_GRACE_PERIOD;
+    Ok(FfiExtResult {
+        exitCode: status.code().unwrap_or(69),
+        stdout: stdout.finish(deadline),
+        stderr: stderr.finish(deadline),
+        timedOut: timed_out,
+    })
+}
+
+/// A pipe read to the end in a separate thread.
+struct PipeReader {
+    output: Arc<Mutex<Vec<u8>>>,
+    closed: mpsc::Receiver<()>,
+}
+
+impl PipeReader {
+    fn spawn(mut pipe: impl Read + Send + 'static) -> Self {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let (tx, closed) = mpsc::channel();
+        let buf = output.clone();
+        thread::spawn(move || {
+            let mut chunk = [0; 8192];
+            loop {
+                match pipe.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => buf.lock().unwrap().extend_from_slice(&chunk[..n]),
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(_) => break,
+                }
+            }
+            let _ = tx.send(());
+        });
+        Self { output, closed }
+    }
+
+    /// Returns the output read until the pipe was closed, or until the deadline if it's still
+    /// open.
+    fn finish(self, deadline: Instant) -> Vec<u8> {
+        let _ = self.closed.recv_timeout(deadline.saturating_duration_since(Instant::now()));
+        std::mem::take(&mut *self.output.lock().unwrap())
+    }
+}
+
+/// Waits for the child to exit, killing it and the processes it spawned once the timeout elapses.
+///
+/// Returns whether the child was killed.
+fn wait_timeout(child: &mut Child, timeout: Duration) -> std::io::Result<(ExitStatus, bool)> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, false))
+        }
+        if Instant::now() >= deadline {
+            kill_process_tree(child);
+            return Ok((child.wait()?, true))
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Kills the child and the processes it spawned.
+fn kill_process_tree(child: &mut Child) {
+    // the child leads its own process group
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    #[cfg(windows)]
+    let _ = Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -403,6 +544,69 @@ mod tests {
         assert_eq!(output.stdout, msg.as_bytes());
     }
 
+    #[test]
+    fn test_ffi_ext_stdin_and_env() {
+        let cheats = cheats();
+        let request = FfiRequest {
+            commandInput: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "cat; echo $GM; echo $HOME".to_string(),
+            ],
+            stdin: b"gm\n".to_vec(),
+            timeoutMs: 0,
+            workingDir: String::new(),
+            env: vec![FfiEnvVar { name: "GM".to_string(), value: "wagmi".to_string() }],
+        };
+        let output = ffi_ext(&cheats, &request).unwrap();
+        assert_eq!(output.exitCode, 0);
+        assert!(!output.timedOut);
+        // `HOME` isn't inherited
+        assert_eq!(output.stdout, b"gm\nwagmi\n\n");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_ffi_ext_timeout() {
+        let cheats = cheats();
+        let request = FfiRequest {
+            commandInput: vec!["sh".to_string(), "-c".to_string(), "sleep 10 & wait".to_string()],
+            stdin: Vec::new(),
+            timeoutMs: 100,
+            workingDir: String::new(),
+            env: Vec::new(),
+        };
+        let start = Instant::now();
+        let output = ffi_ext(&cheats, &request).unwrap();
+        assert!(output.timedOut);
+        // the pipes are closed once the background `sleep` is killed too
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_ffi_ext_background_process() {
+        let cheats = cheats();
+        let request = FfiRequest {
+            commandInput: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "sleep 10 & echo gm".to_string(),
+            ],
+            stdin: Vec::new(),
+            timeoutMs: 0,
+            workingDir: String::new(),
+            env: Vec::new(),
+        };
+        let start = Instant::now();
+        let output = ffi_ext(&cheats, &request).unwrap();
+        assert_eq!(output.exitCode, 0);
+        assert!(!output.timedOut);
+        assert_eq!(output.stdout, b"gm\n");
+        // the background `sleep` holding the pipes isn't waited for
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
     #[test]
     fn test_artifact_parsing() {
         let s = include_str!("../../evm/test-data/solc-obj.json");
@@ -5,23 +5,25 @@ use crate::{
         mapping::{self, MappingSlots},
         mock::{MockCallDataContext, MockCallReturnData},
         prank::Prank,
-        DealRecord, RecordAccess,
+        DealRecord, GasSnapshots, RecordAccess,
     },
     script::{Broadcast, ScriptWallets},
     test::expect::{
         self, ExpectedCallData, ExpectedCallTracker, ExpectedCallType, ExpectedEmit,
-        ExpectedRevert, ExpectedRevertKind,
+        ExpectedRevert, ExpectedRevertKind, ExpectedStorageWrites,
     },
+    utils::AddressBook,
     CheatsConfig, CheatsCtxt, Error, Result, Vm,
     Vm::AccountAccess,
 };
-use alloy_primitives::{Address, Bytes, B256, U256, U64};
+use alloy_primitives::{Address, Bytes, Selector, B256, U256, U64};
 use alloy_rpc_types::request::{TransactionInput, TransactionRequest};
 use alloy_sol_types::{SolInterface, SolValue};
 use foundry_common::{evm::Breakpoints, provider::alloy::RpcUrl};
 use foundry_evm_core::{
     backend::{DatabaseError, DatabaseExt, RevertDiagnostic},
     constants::{CHEATCODE_ADDRESS, DEFAULT_CREATE2_DEPLOYER, HARDHAT_CONSOLE_ADDRESS},
+    precompiles::CustomPrecompiles,
 };
 use itertools::Itertools;
 use revm::{
@@ -84,6 +86,18 @@ pub struct BroadcastableTransaction {
 /// List of transactions that can be broadcasted.
 pub type BroadcastableTransactions = VecDeque<BroadcastableTransaction>;
 
+/// A minimum balance of an account after the broadcast, asserted with
+/// `assertBroadcastBalanceAbove`.
+#[derive(Clone, Debug)]
+pub struct BroadcastBalanceAssertion {
+    /// The RPC URL of the fork the assertion was made on.
+    pub rpc: Option<RpcUrl>,
+    /// The account whose balance is checked.
+    pub account: Address,
+    /// The minimum balance.
+    pub min_balance: U256,
+}
+
 /// An EVM inspector that handles calls to various cheatcodes, each with their own behavior.
 ///
 /// Cheatcodes can be called by contracts during execution to modify the VM environment, such as
@@ -118,6 +132,9 @@ pub struct Cheatcodes {
     /// Address labels
     pub labels: HashMap<Address, String>,
 
+    /// The address book resolved by the `getAddress` cheatcodes
+    pub address_book: AddressBook,
+
     /// Remembered private keys
     pub script_wallets: Option<ScriptWallets>,
 
@@ -146,11 +163,20 @@ pub struct Cheatcodes {
     /// Mocked calls
     // **Note**: inner must a BTreeMap because of special `Ord` impl for `MockCallDataContext`
     pub mocked_calls: HashMap<Address, BTreeMap<MockCallDataContext, MockCallReturnData>>,
+    /// Calls mocked on any callee with `mockCallAny`, by selector
+    pub mocked_selectors: HashMap<Selector, MockCallReturnData>,
+    /// Whether the last call was answered by a mock, so its trace can be tagged
+    pub answered_by_mock: bool,
+
+    /// User-defined precompiles registered with `etchPrecompile`
+    pub custom_precompiles: CustomPrecompiles,
 
     /// Expected calls
     pub expected_calls: ExpectedCallTracker,
     /// Expected emits
     pub expected_emits: VecDeque<ExpectedEmit>,
+    /// Expected storage writes of the next call
+    pub expected_storage_writes: Option<ExpectedStorageWrites>,
 
     /// Map of context depths to memory offset ranges that may be written to within the call depth.
     pub allowed_mem_writes: HashMap<u64, Vec<Range<u64>>>,
@@ -165,6 +191,9 @@ pub struct Cheatcodes {
     /// Scripting based transactions
     pub broadcastable_transactions: BroadcastableTransactions,
 
+    /// Balances asserted to hold after the scripting based transactions are broadcast
+    pub broadcast_balance_assertions: Vec<BroadcastBalanceAssertion>,
+
     /// Additional, user configurable context this Inspector has access to when inspecting a call
     pub config: Arc<CheatsConfig>,
 
@@ -195,6 +224,9 @@ pub struct Cheatcodes {
     /// paused and creating new contracts.
     pub gas_metering_create: Option<Option<Gas>>,
 
+    /// Named gas measurements of the test taken with the `snapshotGas` cheatcodes
+    pub gas_snapshots: GasSnapshots,
+
     /// Mapping slots.
     pub mapping_slots: Option<HashMap<Address, MappingSlots>>,
 
@@ -285,6 +317,62 @@ impl Cheatcodes {
             }
         }
     }
+
+    /// Exits a frame for the storage write expectations, checking them once the call they were
+    /// declared for returns.
+    fn handle_expected_storage_writes<DB: DatabaseExt>(
+        &mut self,
+        data: &EVMData<'_, DB>,
+        status: InstructionResult,
+    ) -> Result<(), String> {
+        let Some(expected) = &mut self.expected_storage_writes else { return Ok(()) };
+        let depth = data.journaled_state.depth();
+        if depth < expected.depth {
+            return Ok(())
+        }
+        expected.exit(depth, !status.is_ok());
+        if depth == expected.depth {
+            self.expected_storage_writes.take().unwrap().check()?;
+        }
+        Ok(())
+    }
+
+    /// Checks the expected revert against a call or create that returned at `depth`, returning the
+    /// encoded error on mismatch.
+    ///
+    /// A mismatch below the depth `expectRevert` was called at is also raised when the outer call
+    /// returns, as a wrapper could swallow it.
+    fn handle_expected_revert(
+        &mut self,
+        depth: u64,
+        is_create: bool,
+        status: InstructionResult,
+        retdata: Bytes,
+    ) -> Result<(Option<Address>, Bytes), Bytes> {
+        let mut expected_revert = self.expected_revert.take().unwrap();
+        if let Some(failure) = expected_revert.failure.take() {
+            return Err(failure)
+        }
+        match expect::handle_expect_revert(
+            is_create,
+            &expected_revert,
+            status,
+            retdata,
+            &self.config.revert_decoder,
+        ) {
+            Ok(success) => Ok(success),
+            Err(error) => {
+                trace!(expected=?expected_revert, ?error, ?status, "Expected revert mismatch");
+                let error: Bytes = error.abi_encode().into();
+                if depth > expected_revert.call_depth {
+                    expected_revert.failure = Some(error.clone());
+                    expected_revert.depth = expected_revert.call_depth;
+                    self.expected_revert = Some(expected_revert);
+                }
+                Err(error)
+            }
+        }
+    }
 }
 
 impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
@@ -360,6 +448,12 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
             _ => {}
         }
 
+        // Measure the gas of the frames snapshotted with `startSnapshotGas`
+        if !self.gas_snapshots.active.is_empty() {
+            let spent = interpreter.gas.limit().saturating_sub(interpreter.gas.remaining());
+            self.gas_snapshots.step(data.journaled_state.depth(), spent);
+        }
+
         // Record writes and reads if `record` has been called
         if let Some(storage_accesses) = &mut self.accesses {
             match interpreter.current_opcode() {
@@ -676,6 +770,33 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
             )
         }
 
+        // Record writes to the storage of targets of `expectStorageWrite` and
+        // `expectNoStorageWrites`
+        if let Some(expected) = &mut self.expected_storage_writes {
+            if interpreter.current_opcode() == opcode::SSTORE {
+                let address = interpreter.contract().address;
+                let slot = try_or_continue!(interpreter.stack().peek(0));
+                let new = try_or_continue!(interpreter.stack().peek(1));
+                // Read the old value without loading the slot, which would make the SSTORE warm
+                let old = match data
+                    .journaled_state
+                    .state
+                    .get(&address)
+                    .and_then(|account| account.storage.get(&slot))
+                {
+                    Some(value) => value.present_value,
+                    None => data.db.storage(address, slot).unwrap_or_default(),
+                };
+                expected.record(
+                    data.journaled_state.depth(),
+                    address,
+                    slot.into(),
+                    old.into(),
+                    new.into(),
+                );
+            }
+        }
+
         // Record writes with sstore (and sha3) if `StartMappingRecording` has been called
         if let Some(mapping_slots) = &mut self.mapping_slots {
             mapping::step(mapping_slots, interpreter);
@@ -715,6 +836,14 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
             return (InstructionResult::Continue, gas, Bytes::new());
         }
 
+        // Track the call path for `expectStorageWrite` and `expectNoStorageWrites`
+        if let Some(expected) = &mut self.expected_storage_writes {
+            let code_address = call.context.code_address;
+            let name = self.labels.get(&code_address).cloned().unwrap_or(code_address.to_string());
+            let selector = call.input.get(..4).map(hex::encode_prefixed).unwrap_or_default();
+            expected.enter(data.journaled_state.depth(), format!("{name}::{selector}"));
+        }
+
         // Handle expected calls
 
         // Grab the different calldatas expected.
@@ -740,23 +869,41 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
             }
         }
 
-        // Handle mocked calls
-        if let Some(mocks) = self.mocked_calls.get(&call.contract) {
-            let ctx = MockCallDataContext {
-                calldata: call.input.clone(),
-                value: Some(call.transfer.value),
-            };
-            if let Some(return_data) = mocks.get(&ctx).or_else(|| {
-                mocks
-                    .iter()
-                    .find(|(mock, _)| {
-                        call.input.get(..mock.calldata.len()) == Some(&mock.calldata[..]) &&
-                            mock.value.map_or(true, |value| value == call.transfer.value)
-                    })
-                    .map(|(_, v)| v)
-            }) {
-                return (return_data.ret_type, gas, return_data.data.clone());
-            }
+        // Handle user-defined precompiles
+        if let Some(precompile) = self.custom_precompiles.get(&call.contract) {
+            return precompile.call(&call.input, call.gas_limit);
+        }
+
+        // Handle mocked calls: exact calldata, then the most specific partial calldata of the
+        // callee, then the selectors mocked on any callee
+        let mocked = self
+            .mocked_calls
+            .get(&call.contract)
+            .and_then(|mocks| {
+                let ctx = MockCallDataContext {
+                    calldata: call.input.clone(),
+                    value: Some(call.transfer.value),
+                };
+                mocks.get(&ctx).or_else(|| {
+                    mocks
+                        .iter()
+                        .find(|(mock, _)| {
+                            call.input.get(..mock.calldata.len()) == Some(&mock.calldata[..]) &&
+                                mock.value.map_or(true, |value| value == call.transfer.value)
+                        })
+                        .map(|(_, v)| v)
+                })
+            })
+            .or_else(|| {
+                if data.precompiles.contains(&call.contract) {
+                    return None;
+                }
+                self.mocked_selectors.get(&Selector::try_from(call.input.get(..4)?).ok()?)
+            });
+        if let Some(return_data) = mocked {
+            let (ret_type, retdata) = (return_data.ret_type, return_data.data.clone());
+            self.answered_by_mock = true;
+            return (ret_type, gas, retdata);
         }
 
         // Apply our prank
@@ -913,6 +1060,12 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
         // it for cheatcode calls because they are not appplied for cheatcodes in the `call` hook.
         // This should be placed before the revert handling, because we might exit early there
         if !cheatcode_call {
+            // Remember the gas used for `snapshotGasLastCall` by the calling frame
+            self.gas_snapshots.last_call_gas.insert(
+                data.journaled_state.depth(),
+                remaining_gas.limit().saturating_sub(remaining_gas.remaining()),
+            );
+
             // Clean up pranks
             if let Some(prank) = &self.prank {
                 if data.journaled_state.depth() == prank.depth {
@@ -938,8 +1091,24 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
             }
         }
 
+        // Handle expected storage writes before expected reverts, whose calls return early
+        if !cheatcode_call {
+            if let Err(msg) = self.handle_expected_storage_writes(data, status) {
+                return (InstructionResult::Revert, remaining_gas, Error::encode(msg));
+            }
+        }
+
         // Handle expected reverts
-        if let Some(expected_revert) = &self.expected_revert {
+        if let Some(expected_revert) = &mut self.expected_revert {
+            // Track the reverting calls to find the contract the revert originated from
+            if !status.is_ok() {
+                expected_revert.record_revert(
+                    data.journaled_state.depth(),
+                    call.contract,
+                    &retdata,
+                );
+            }
+
             if data.journaled_state.depth() <= expected_revert.depth {
                 let needs_processing: bool = match expected_revert.kind {
                     ExpectedRevertKind::Default => !cheatcode_call,
@@ -951,17 +1120,9 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                 };
 
                 if needs_processing {
-                    let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
-                    return match expect::handle_expect_revert(
-                        false,
-                        expected_revert.reason.as_deref(),
-                        status,
-                        retdata,
-                    ) {
-                        Err(error) => {
-                            trace!(expected=?expected_revert, ?error, ?status, "Expected revert mismatch");
-                            (InstructionResult::Revert, remaining_gas, error.abi_encode().into())
-                        }
+                    let depth = data.journaled_state.depth();
+                    return match self.handle_expected_revert(depth, false, status, retdata) {
+                        Err(error) => (InstructionResult::Revert, remaining_gas, error),
                         Ok((_, retdata)) => (InstructionResult::Return, remaining_gas, retdata),
                     };
                 }
@@ -1153,6 +1314,12 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                 };
                 return (InstructionResult::Revert, remaining_gas, Error::encode(msg));
             }
+
+            // The expected storage writes are checked once the next call returns
+            if self.expected_storage_writes.take().is_some() {
+                let msg = "expected storage writes, but no call was made afterwards";
+                return (InstructionResult::Revert, remaining_gas, Error::encode(msg));
+            }
         }
 
         (status, remaining_gas, retdata)
@@ -1165,6 +1332,11 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
     ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
         let gas = Gas::new(call.gas_limit);
 
+        // Track the call path for `expectStorageWrite` and `expectNoStorageWrites`
+        if let Some(expected) = &mut self.expected_storage_writes {
+            expected.enter(data.journaled_state.depth(), "create".to_string());
+        }
+
         // Apply our prank
         if let Some(prank) = &self.prank {
             if data.journaled_state.depth() >= prank.depth && call.caller == prank.prank_caller {
@@ -1312,24 +1484,26 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
             }
         }
 
+        // Handle expected storage writes
+        if let Err(msg) = self.handle_expected_storage_writes(data, status) {
+            return (InstructionResult::Revert, None, remaining_gas, Error::encode(msg));
+        }
+
         // Handle expected reverts
-        if let Some(expected_revert) = &self.expected_revert {
+        if let Some(expected_revert) = &mut self.expected_revert {
+            if let Some(address) = address.filter(|_| !status.is_ok()) {
+                expected_revert.record_revert(data.journaled_state.depth(), address, &retdata);
+            }
+
             if data.journaled_state.depth() <= expected_revert.depth &&
                 matches!(expected_revert.kind, ExpectedRevertKind::Default)
             {
-                let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
-                return match expect::handle_expect_revert(
-                    true,
-                    expected_revert.reason.as_deref(),
-                    status,
-                    retdata,
-                ) {
+                let depth = data.journaled_state.depth();
+                return match self.handle_expected_revert(depth, true, status, retdata) {
                     Ok((address, retdata)) => {
                         (InstructionResult::Return, address, remaining_gas, retdata)
                     }
-                    Err(err) => {
-                        (InstructionResult::Revert, None, remaining_gas, err.abi_encode().into())
-                    }
+                    Err(error) => (InstructionResult::Revert, None, remaining_gas, error),
                 };
             }
         }
@@ -24,10 +24,14 @@ mod config;
 pub use config::CheatsConfig;
 
 mod inspector;
-pub use inspector::{BroadcastableTransaction, BroadcastableTransactions, Cheatcodes, Context};
+pub use inspector::{
+    BroadcastBalanceAssertion, BroadcastableTransaction, BroadcastableTransactions, Cheatcodes,
+    Context,
+};
 
 mod base64;
 mod env;
+pub use env::process_env_vars_read;
 mod evm;
 mod fs;
 mod json;
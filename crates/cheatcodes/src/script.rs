@@ -1,6 +1,6 @@
 //! Implementations of [`Scripting`](crate::Group::Scripting) cheatcodes.
 
-use crate::{Cheatcode, CheatsCtxt, DatabaseExt, Result, Vm::*};
+use crate::{BroadcastBalanceAssertion, Cheatcode, CheatsCtxt, DatabaseExt, Result, Vm::*};
 use alloy_primitives::{Address, U256};
 use alloy_signer::{LocalWallet, Signer};
 use foundry_config::Config;
@@ -61,6 +61,18 @@ impl Cheatcode for stopBroadcastCall {
     }
 }
 
+impl Cheatcode for assertBroadcastBalanceAboveCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { account, minBalance } = self;
+        ccx.state.broadcast_balance_assertions.push(BroadcastBalanceAssertion {
+            rpc: ccx.data.db.active_fork_url(),
+            account: *account,
+            min_balance: *minBalance,
+        });
+        Ok(Default::default())
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Broadcast {
     /// Address of the transaction origin
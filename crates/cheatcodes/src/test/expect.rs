@@ -1,9 +1,10 @@
 use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*};
 use alloy_primitives::{address, Address, Bytes, LogData as RawLog, B256, U256};
 use alloy_sol_types::{SolError, SolValue};
+use foundry_evm_core::decode::RevertDecoder;
 use revm::interpreter::{return_ok, InstructionResult};
 use spec::Vm;
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap};
 
 /// For some cheatcodes we may internally change the status of the call, i.e. in `expectRevert`.
 /// Solidity will see a successful call and attempt to decode the return data. Therefore, we need
@@ -72,8 +73,40 @@ pub struct ExpectedRevert {
     pub reason: Option<Vec<u8>>,
     /// The depth at which the revert is expected
     pub depth: u64,
+    /// The depth `expectRevert` was called at, lower than `depth` if the revert is expected from a
+    /// nested call
+    pub call_depth: u64,
+    /// The encoded error of a mismatch in a nested call, raised again when the outer call returns
+    pub failure: Option<Bytes>,
     /// The type of expected revert.
     pub kind: ExpectedRevertKind,
+    /// Whether only the selector of the revert data has to match
+    pub partial_match: bool,
+    /// The contract the revert is expected to originate from, if any
+    pub reverter: Option<Address>,
+    /// The depth, address and revert data of the last reverting call, used to find the contract
+    /// the revert originated from
+    pub reverted_by: Option<(u64, Address, Bytes)>,
+}
+
+impl ExpectedRevert {
+    /// Records that the call to `address` at `depth` reverted with `retdata`.
+    ///
+    /// A revert bubbled up from the last reverting call keeps that call as the origin.
+    pub(crate) fn record_revert(&mut self, depth: u64, address: Address, retdata: &Bytes) {
+        if self.reverter.is_none() {
+            return
+        }
+        let origin = match &self.reverted_by {
+            Some((last_depth, origin, last_retdata))
+                if *last_depth == depth + 1 && last_retdata == retdata =>
+            {
+                *origin
+            }
+            _ => address,
+        };
+        self.reverted_by = Some((depth, origin, retdata.clone()));
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -95,6 +128,120 @@ pub struct ExpectedEmit {
     pub found: bool,
 }
 
+/// Storage writes expected with `expectStorageWrite` and `expectNoStorageWrites`.
+#[derive(Clone, Debug, Default)]
+pub struct ExpectedStorageWrites {
+    /// The depth at which the storage writes are expected, i.e. the depth of the next call's
+    /// caller
+    pub depth: u64,
+    /// The slots expected to be written per target, with the value they should end up with
+    pub expected: BTreeMap<Address, BTreeMap<B256, Option<B256>>>,
+    /// The targets whose storage must not be written
+    pub forbidden: BTreeSet<Address>,
+    /// The call path to the current frame, one entry per depth below `depth`
+    path: Vec<String>,
+    /// The writes to the targets that haven't been reverted, with the depth they're attributed to
+    writes: Vec<(u64, StorageWrite)>,
+}
+
+/// A write to the storage of a target of [ExpectedStorageWrites].
+#[derive(Clone, Debug)]
+struct StorageWrite {
+    address: Address,
+    slot: B256,
+    old: B256,
+    new: B256,
+    /// The call path of the frame the write was made in
+    path: String,
+}
+
+impl ExpectedStorageWrites {
+    fn is_target(&self, address: &Address) -> bool {
+        self.expected.contains_key(address) || self.forbidden.contains(address)
+    }
+
+    /// Enters a frame called from `depth`.
+    pub(crate) fn enter(&mut self, depth: u64, frame: String) {
+        if depth < self.depth {
+            return
+        }
+        self.path.truncate((depth - self.depth) as usize);
+        self.path.push(frame);
+    }
+
+    /// Records an `SSTORE` executed at `depth`.
+    pub(crate) fn record(
+        &mut self,
+        depth: u64,
+        address: Address,
+        slot: B256,
+        old: B256,
+        new: B256,
+    ) {
+        if depth <= self.depth || !self.is_target(&address) {
+            return
+        }
+        let path = self.path.join(" -> ");
+        self.writes.push((depth, StorageWrite { address, slot, old, new, path }));
+    }
+
+    /// Exits a frame called from `depth`, discarding its writes if it reverted, or attributing
+    /// them to its caller otherwise.
+    pub(crate) fn exit(&mut self, depth: u64, reverted: bool) {
+        if reverted {
+            self.writes.retain(|(write_depth, _)| *write_depth <= depth);
+        } else {
+            for (write_depth, _) in &mut self.writes {
+                *write_depth = (*write_depth).min(depth);
+            }
+        }
+    }
+
+    /// Checks the writes of the call against the expectations, listing every mismatch.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        let mut mismatches = vec![];
+        for (_, write) in &self.writes {
+            let expected = self
+                .expected
+                .get(&write.address)
+                .map_or(false, |slots| slots.contains_key(&write.slot));
+            if !expected {
+                mismatches.push(format!(
+                    "unexpected write to slot {} of {}: {} -> {} (in {})",
+                    write.slot, write.address, write.old, write.new, write.path
+                ));
+            }
+        }
+        for (address, slots) in &self.expected {
+            for (slot, value) in slots {
+                let last = self
+                    .writes
+                    .iter()
+                    .rev()
+                    .find(|(_, write)| write.address == *address && write.slot == *slot);
+                match (last, value) {
+                    (None, _) => mismatches.push(format!(
+                        "expected a write to slot {slot} of {address}, but none was made"
+                    )),
+                    (Some((_, write)), Some(value)) if write.new != *value => mismatches
+                        .push(format!(
+                        "expected slot {slot} of {address} to be set to {value}, but it was set \
+                             to {} (in {})",
+                        write.new, write.path
+                    )),
+                    _ => {}
+                }
+            }
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("storage writes != expected storage writes:\n{}", mismatches.join("\n")))
+        }
+    }
+}
+
 impl Cheatcode for expectCall_0Call {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { callee, data } = self;
@@ -237,41 +384,103 @@ impl Cheatcode for expectEmit_3Call {
 impl Cheatcode for expectRevert_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self {} = self;
-        expect_revert(ccx.state, None, ccx.data.journaled_state.depth(), false)
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, None, depth, 0, false, false, None)
     }
 }
 
 impl Cheatcode for expectRevert_1Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { revertData } = self;
-        expect_revert(ccx.state, Some(revertData.as_ref()), ccx.data.journaled_state.depth(), false)
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData.as_ref()), depth, 0, false, false, None)
     }
 }
 
 impl Cheatcode for expectRevert_2Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { revertData } = self;
-        expect_revert(ccx.state, Some(revertData), ccx.data.journaled_state.depth(), false)
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData), depth, 0, false, false, None)
+    }
+}
+
+impl Cheatcode for expectRevert_3Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { reverter } = *self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, None, depth, 0, false, false, Some(reverter))
+    }
+}
+
+impl Cheatcode for expectRevert_4Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, reverter } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData.as_ref()), depth, 0, false, false, Some(*reverter))
+    }
+}
+
+impl Cheatcode for expectRevert_5Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, reverter } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData), depth, 0, false, false, Some(*reverter))
+    }
+}
+
+impl Cheatcode for expectRevert_6Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, depth: nested } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData.as_ref()), depth, *nested, false, false, None)
+    }
+}
+
+impl Cheatcode for expectRevert_7Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, depth: nested } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData), depth, *nested, false, false, None)
+    }
+}
+
+impl Cheatcode for expectPartialRevert_0Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData.as_ref()), depth, 0, false, true, None)
+    }
+}
+
+impl Cheatcode for expectPartialRevert_1Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, reverter } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData.as_ref()), depth, 0, false, true, Some(*reverter))
     }
 }
 
 impl Cheatcode for _expectCheatcodeRevert_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
-        expect_revert(ccx.state, None, ccx.data.journaled_state.depth(), true)
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, None, depth, 0, true, false, None)
     }
 }
 
 impl Cheatcode for _expectCheatcodeRevert_1Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { revertData } = self;
-        expect_revert(ccx.state, Some(revertData.as_ref()), ccx.data.journaled_state.depth(), true)
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData.as_ref()), depth, 0, true, false, None)
     }
 }
 
 impl Cheatcode for _expectCheatcodeRevert_2Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { revertData } = self;
-        expect_revert(ccx.state, Some(revertData), ccx.data.journaled_state.depth(), true)
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert(ccx.state, Some(revertData), depth, 0, true, false, None)
     }
 }
 
@@ -297,6 +506,27 @@ impl Cheatcode for expectSafeMemoryCallCall {
     }
 }
 
+impl Cheatcode for expectStorageWrite_0Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { target, slot } = *self;
+        expect_storage_writes(ccx, target, Some((slot, None)))
+    }
+}
+
+impl Cheatcode for expectStorageWrite_1Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { target, slot, newValue } = *self;
+        expect_storage_writes(ccx, target, Some((slot, Some(newValue))))
+    }
+}
+
+impl Cheatcode for expectNoStorageWritesCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { target } = *self;
+        expect_storage_writes(ccx, target, None)
+    }
+}
+
 /// Handles expected calls specified by the `expectCall` cheatcodes.
 ///
 /// It can handle calls in two ways:
@@ -477,7 +707,10 @@ fn expect_revert(
     state: &mut Cheatcodes,
     reason: Option<&[u8]>,
     depth: u64,
+    nested: u64,
     cheatcode: bool,
+    partial_match: bool,
+    reverter: Option<Address>,
 ) -> Result {
     ensure!(
         state.expected_revert.is_none(),
@@ -485,21 +718,27 @@ fn expect_revert(
     );
     state.expected_revert = Some(ExpectedRevert {
         reason: reason.map(<[_]>::to_vec),
-        depth,
+        depth: depth + nested,
+        call_depth: depth,
+        failure: None,
         kind: if cheatcode {
             ExpectedRevertKind::Cheatcode { pending_processing: true }
         } else {
             ExpectedRevertKind::Default
         },
+        partial_match,
+        reverter,
+        reverted_by: None,
     });
     Ok(Default::default())
 }
 
 pub(crate) fn handle_expect_revert(
     is_create: bool,
-    expected_revert: Option<&[u8]>,
+    expected_revert: &ExpectedRevert,
     status: InstructionResult,
     retdata: Bytes,
+    decoder: &RevertDecoder,
 ) -> Result<(Option<Address>, Bytes)> {
     let success_return = || {
         if is_create {
@@ -511,15 +750,30 @@ pub(crate) fn handle_expect_revert(
 
     ensure!(!matches!(status, return_ok!()), "call did not revert as expected");
 
+    if let Some(reverter) = expected_revert.reverter {
+        match &expected_revert.reverted_by {
+            Some((_, actual, _)) if *actual == reverter => {}
+            Some((_, actual, _)) => {
+                bail!("Reverter != expected reverter: {actual} != {reverter}")
+            }
+            None => bail!("call reverted as expected, but the reverting contract is unknown"),
+        }
+    }
+
     // If None, accept any revert
-    let Some(expected_revert) = expected_revert else {
+    let Some(expected_reason) = expected_revert.reason.as_deref() else {
         return Ok(success_return());
     };
 
-    if !expected_revert.is_empty() && retdata.is_empty() {
+    if !expected_reason.is_empty() && retdata.is_empty() {
         bail!("call reverted as expected, but without data");
     }
 
+    // Only the selector has to match for partial reverts
+    if expected_revert.partial_match && retdata.starts_with(expected_reason) {
+        return Ok(success_return());
+    }
+
     let mut actual_revert: Vec<u8> = retdata.into();
 
     // Try decoding as known errors
@@ -532,19 +786,21 @@ pub(crate) fn handle_expect_revert(
         }
     }
 
-    if actual_revert == expected_revert {
+    if !expected_revert.partial_match && actual_revert == expected_reason {
         Ok(success_return())
     } else {
+        // Custom errors are decoded with the project's ABIs, e.g. `InsufficientBalance(1, 2)`
         let stringify = |data: &[u8]| {
-            String::abi_decode(data, false)
-                .ok()
+            decoder
+                .decode_custom_error(data)
+                .or_else(|| String::abi_decode(data, false).ok())
                 .or_else(|| std::str::from_utf8(data).ok().map(ToOwned::to_owned))
                 .unwrap_or_else(|| hex::encode_prefixed(data))
         };
         Err(fmt_err!(
             "Error != expected error: {} != {}",
             stringify(&actual_revert),
-            stringify(expected_revert),
+            stringify(expected_reason),
         ))
     }
 }
@@ -556,3 +812,35 @@ fn expect_safe_memory(state: &mut Cheatcodes, start: u64, end: u64, depth: u64)
     offsets.push(start..end);
     Ok(Default::default())
 }
+
+/// Expects the next call to write `slot` of `target`, or not to write to `target` at all if `slot`
+/// is `None`.
+fn expect_storage_writes<DB: DatabaseExt>(
+    ccx: &mut CheatsCtxt<DB>,
+    target: Address,
+    slot: Option<(B256, Option<B256>)>,
+) -> Result {
+    let depth = ccx.data.journaled_state.depth();
+    let expected = ccx
+        .state
+        .expected_storage_writes
+        .get_or_insert_with(|| ExpectedStorageWrites { depth, ..Default::default() });
+    ensure!(expected.depth == depth, "storage writes were already expected at a different depth");
+    match slot {
+        Some((slot, value)) => {
+            ensure!(
+                !expected.forbidden.contains(&target),
+                "storage writes to {target} were already expected not to be made"
+            );
+            expected.expected.entry(target).or_default().insert(slot, value);
+        }
+        None => {
+            ensure!(
+                !expected.expected.contains_key(&target),
+                "storage writes to {target} were already expected to be made"
+            );
+            expected.forbidden.insert(target);
+        }
+    }
+    Ok(Default::default())
+}
@@ -1,7 +1,7 @@
 //! Implementations of [`Utils`](crate::Group::Utils) cheatcodes.
 
 use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*};
-use alloy_primitives::{keccak256, B256, U256};
+use alloy_primitives::{keccak256, Address, B256, U256};
 use alloy_signer::{
     coins_bip39::{
         ChineseSimplified, ChineseTraditional, Czech, English, French, Italian, Japanese, Korean,
@@ -10,6 +10,7 @@ use alloy_signer::{
     LocalWallet, MnemonicBuilder, Signer, SignerSync,
 };
 use alloy_sol_types::SolValue;
+use foundry_config::fs_permissions::FsAccessKind;
 use foundry_evm_core::constants::DEFAULT_CREATE2_DEPLOYER;
 use k256::{
     ecdsa::SigningKey,
@@ -17,6 +18,11 @@ use k256::{
     Secp256k1,
 };
 use p256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey as P256SigningKey};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 /// The BIP32 default derivation path prefix.
 const DEFAULT_DERIVATION_PATH_PREFIX: &str = "m/44'/60'/0'/0/";
@@ -114,6 +120,32 @@ impl Cheatcode for getLabelCall {
     }
 }
 
+impl Cheatcode for getAddressCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { label } = self;
+        let chain_id = ccx.data.env.cfg.chain_id;
+        resolve_address(ccx.state, chain_id, label)
+    }
+}
+
+impl Cheatcode for getAddressOnCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { chainId, label } = self;
+        let chain_id =
+            u64::try_from(*chainId).map_err(|_| fmt_err!("invalid chain ID: {chainId}"))?;
+        resolve_address(state, chain_id, label)
+    }
+}
+
+impl Cheatcode for setAddressBookPathCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { path } = self;
+        let path = state.config.ensure_path_allowed(path, FsAccessKind::Read)?;
+        state.address_book = AddressBook { path: Some(path), addresses: None };
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for computeCreateAddressCall {
     fn apply(&self, _state: &mut Cheatcodes) -> Result {
         let Self { nonce, deployer } = self;
@@ -136,6 +168,66 @@ impl Cheatcode for computeCreate2Address_1Call {
     }
 }
 
+/// Labeled addresses by chain ID, resolved by the `getAddress` cheatcodes.
+#[derive(Clone, Debug, Default)]
+pub struct AddressBook {
+    /// The path set with `setAddressBookPath`, overriding the configured one.
+    pub path: Option<PathBuf>,
+    /// The addresses of the file, loaded at first use.
+    pub addresses: Option<Arc<HashMap<u64, HashMap<String, Address>>>>,
+}
+
+fn resolve_address(state: &mut Cheatcodes, chain_id: u64, label: &str) -> Result {
+    let path = state.address_book.path.clone().unwrap_or_else(|| state.config.address_book.clone());
+    let addresses = match &state.address_book.addresses {
+        Some(addresses) => addresses.clone(),
+        None => {
+            let addresses = Arc::new(load_address_book(&path)?);
+            state.address_book.addresses = Some(addresses.clone());
+            addresses
+        }
+    };
+    let address =
+        addresses.get(&chain_id).and_then(|labels| labels.get(label)).ok_or_else(|| {
+            fmt_err!(
+                "no address labeled `{label}` on chain {chain_id} in address book {}",
+                path.display()
+            )
+        })?;
+    state.labels.insert(*address, label.to_string());
+    Ok(address.abi_encode())
+}
+
+/// Reads the address book at `path`, a JSON object of labeled addresses by chain ID.
+fn load_address_book(path: &Path) -> Result<HashMap<u64, HashMap<String, Address>>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| fmt_err!("failed to read address book {}: {e}", path.display()))?;
+    parse_address_book(&content)
+        .map_err(|e| fmt_err!("invalid address book {}: {e}", path.display()))
+}
+
+/// Parses an address book, reporting the JSON path of the first invalid entry.
+fn parse_address_book(content: &str) -> Result<HashMap<u64, HashMap<String, Address>>> {
+    let value: serde_json::Value = serde_json::from_str(content)?;
+    let chains = value.as_object().ok_or_else(|| fmt_err!("`$` must be an object"))?;
+    let mut addresses = HashMap::with_capacity(chains.len());
+    for (chain, labels) in chains {
+        let chain_id =
+            chain.parse::<u64>().map_err(|_| fmt_err!("invalid chain ID at `$.{chain}`"))?;
+        let labels = labels.as_object().ok_or_else(|| fmt_err!("`$.{chain}` must be an object"))?;
+        let mut chain_addresses = HashMap::with_capacity(labels.len());
+        for (label, address) in labels {
+            let address = address
+                .as_str()
+                .and_then(|address| address.parse::<Address>().ok())
+                .ok_or_else(|| fmt_err!("invalid address {address} at `$.{chain}.{label}`"))?;
+            chain_addresses.insert(label.clone(), address);
+        }
+        addresses.insert(chain_id, chain_addresses);
+    }
+    Ok(addresses)
+}
+
 /// Using a given private key, return its public ETH address, its public key affine x and y
 /// coordinates, and its private key (see the 'Wallet' struct)
 ///
@@ -298,4 +390,23 @@ mod tests {
         let result = sign_p256(&U256::ZERO, &digest, &mut cheats);
         assert_eq!(result.err().unwrap().to_string(), "private key cannot be 0");
     }
+
+    #[test]
+    fn test_parse_address_book() {
+        let addresses = parse_address_book(
+            r#"{"1": {"WETH": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"}, "10": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            addresses[&1]["WETH"],
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".parse::<Address>().unwrap()
+        );
+        assert!(addresses[&10].is_empty());
+
+        let err = |content: &str| parse_address_book(content).unwrap_err().to_string();
+        assert_eq!(err("[]"), "`$` must be an object");
+        assert_eq!(err(r#"{"mainnet": {}}"#), "invalid chain ID at `$.mainnet`");
+        assert_eq!(err(r#"{"1": []}"#), "`$.1` must be an object");
+        assert_eq!(err(r#"{"1": {"WETH": "0x12"}}"#), r#"invalid address "0x12" at `$.1.WETH`"#);
+    }
 }
@@ -53,6 +53,13 @@ pub struct Chisel {
     #[arg(long, help_heading = "REPL options")]
     pub prelude: Option<PathBuf>,
 
+    /// Path to a Solidity file to load into the session.
+    ///
+    /// The file is compiled with the project's remappings and its contracts, libraries, structs
+    /// and free functions are available in the REPL. Can be reloaded with `!reload`.
+    #[arg(long, value_name = "PATH", help_heading = "REPL options")]
+    pub include: Vec<PathBuf>,
+
     /// Disable the default `Vm` import.
     #[arg(long, help_heading = "REPL options", long_help = format!(
         "Disable the default `Vm` import.\n\n\
@@ -114,6 +121,7 @@ async fn main() -> eyre::Result<()> {
     let mut dispatcher = ChiselDispatcher::new(chisel::session_source::SessionSourceConfig {
         // Enable traces if any level of verbosity was passed
         traces: config.verbosity > 0,
+        debug: false,
         foundry_config: config,
         no_vm: args.no_vm,
         evm_opts,
@@ -124,6 +132,16 @@ async fn main() -> eyre::Result<()> {
     // Execute prelude Solidity source files
     evaluate_prelude(&mut dispatcher, args.prelude).await?;
 
+    // Load included Solidity files
+    for path in &args.include {
+        match dispatcher.load_file(path) {
+            DispatchResult::CommandSuccess(Some(msg)) => println!("{}", Paint::green(msg)),
+            DispatchResult::CommandFailed(e) => eyre::bail!("{e}"),
+            DispatchResult::FileIoError(e) => eyre::bail!("{e}"),
+            _ => panic!("Unexpected result! Please report this bug."),
+        }
+    }
+
     // Check for chisel subcommands
     match &args.cmd {
         Some(ChiselSubcommand::List) => {
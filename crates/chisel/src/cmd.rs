@@ -21,12 +21,14 @@ pub enum ChiselCommand {
     /// Save the current session to the cache
     /// Takes: [session-id]
     Save,
-    /// Load a previous session from cache
-    /// Takes: <session-id>
+    /// Load a previous session from cache, or a Solidity file into the current session
+    /// Takes: <session-id|path>
     ///
-    /// WARNING: This will overwrite the current session (though the current session will be
-    /// optimistically cached)
+    /// WARNING: Loading a session will overwrite the current session (though the current session
+    /// will be optimistically cached)
     Load,
+    /// Reload all Solidity files loaded into the current session
+    Reload,
     /// List all cached sessions
     ListSessions,
     /// Clear the cache of all stored sessions
@@ -36,13 +38,31 @@ pub enum ChiselCommand {
     Fork,
     /// Enable / disable traces for the current session
     Traces,
+    /// Show the traces of the last executed statement
+    /// Takes: [-v]
+    Trace,
+    /// Open the debugger for the last executed statement
+    Debug,
+    /// Decode the storage of a contract with its storage layout
+    /// Takes: <contractVarOrAddr> [slot|varName]
+    Storage,
+    /// Compute and read the storage slot of a mapping value
+    /// Takes: <var> <key>
+    Mapping,
     /// Set calldata (`msg.data`) for the current session (appended after function selector)
     Calldata,
+    /// Prank all following calls of the session as the given address
+    /// Takes: [address]
+    Prank,
+    /// Set the balance of an address
+    /// Takes: <address> <amount>
+    Deal,
     /// Dump the raw memory
     MemDump,
     /// Dump the raw stack
     StackDump,
-    /// Export the current REPL session source to a Script file
+    /// Export the current REPL session source to a Script or Test file
+    /// Takes: [script|test] [path]
     Export,
     /// Fetch an interface of a verified contract on Etherscan
     /// Takes: <addr> <interface-name>
@@ -68,10 +88,17 @@ impl FromStr for ChiselCommand {
             "save" | "s" => Ok(ChiselCommand::Save),
             "list" | "ls" => Ok(ChiselCommand::ListSessions),
             "load" | "l" => Ok(ChiselCommand::Load),
+            "reload" | "rl" => Ok(ChiselCommand::Reload),
             "clearcache" | "cc" => Ok(ChiselCommand::ClearCache),
             "fork" | "f" => Ok(ChiselCommand::Fork),
             "traces" | "t" => Ok(ChiselCommand::Traces),
+            "trace" | "tr" => Ok(ChiselCommand::Trace),
+            "debug" | "db" => Ok(ChiselCommand::Debug),
+            "storage" | "st" => Ok(ChiselCommand::Storage),
+            "mapping" | "map" => Ok(ChiselCommand::Mapping),
             "calldata" | "cd" => Ok(ChiselCommand::Calldata),
+            "prank" | "p" => Ok(ChiselCommand::Prank),
+            "deal" => Ok(ChiselCommand::Deal),
             "memdump" | "md" => Ok(ChiselCommand::MemDump),
             "stackdump" | "sd" => Ok(ChiselCommand::StackDump),
             "export" | "ex" => Ok(ChiselCommand::Export),
@@ -127,18 +154,25 @@ impl From<ChiselCommand> for CmdDescriptor {
             ChiselCommand::Clear => (&["clear", "c"], "Clear current session source", CmdCategory::Session),
             ChiselCommand::Source => (&["source", "so"], "Display the source code of the current session", CmdCategory::Session),
             ChiselCommand::Save => (&["save [id]", "s [id]"], "Save the current session to cache", CmdCategory::Session),
-            ChiselCommand::Load => (&["load <id>", "l <id>"], "Load a previous session ID from cache", CmdCategory::Session),
+            ChiselCommand::Load => (&["load <id|path>", "l <id|path>"], "Load a previous session ID from cache, or a Solidity file into the current session", CmdCategory::Session),
+            ChiselCommand::Reload => (&["reload", "rl"], "Reload all Solidity files loaded into the current session", CmdCategory::Session),
             ChiselCommand::ListSessions => (&["list", "ls"], "List all cached sessions", CmdCategory::Session),
             ChiselCommand::ClearCache => (&["clearcache", "cc"], "Clear the chisel cache of all stored sessions", CmdCategory::Session),
-            ChiselCommand::Export => (&["export", "ex"], "Export the current session source to a script file", CmdCategory::Session),
+            ChiselCommand::Export => (&["export [script|test] [path]", "ex [script|test] [path]"], "Export the current session source to a script or test file", CmdCategory::Session),
             ChiselCommand::Fetch => (&["fetch <addr> <name>", "fe <addr> <name>"], "Fetch the interface of a verified contract on Etherscan", CmdCategory::Session),
             // Environment
             ChiselCommand::Fork => (&["fork <url>", "f <url>"], "Fork an RPC for the current session. Supply 0 arguments to return to a local network", CmdCategory::Env),
             ChiselCommand::Traces => (&["traces", "t"], "Enable / disable traces for the current session", CmdCategory::Env),
             ChiselCommand::Calldata => (&["calldata [data]", "cd [data]"], "Set calldata (`msg.data`) for the current session (appended after function selector). Clears it if no argument provided.", CmdCategory::Env),
+            ChiselCommand::Prank => (&["prank [addr]", "p [addr]"], "Prank all following calls of the session as the given address. Stops the prank if no argument provided.", CmdCategory::Env),
+            ChiselCommand::Deal => (&["deal <addr> <amount>"], "Set the balance of an address, e.g. `!deal <addr> 100 ether`", CmdCategory::Env),
             // Debug
             ChiselCommand::MemDump => (&["memdump", "md"], "Dump the raw memory of the current state", CmdCategory::Debug),
             ChiselCommand::StackDump => (&["stackdump", "sd"], "Dump the raw stack of the current state", CmdCategory::Debug),
+            ChiselCommand::Trace => (&["trace [-v]", "tr [-v]"], "Show the traces of the last executed statement. Includes storage accesses with `-v`.", CmdCategory::Debug),
+            ChiselCommand::Debug => (&["debug", "db"], "Open the debugger for the last executed statement", CmdCategory::Debug),
+            ChiselCommand::Storage => (&["storage <contractVarOrAddr> [slot|varName]", "st <contractVarOrAddr> [slot|varName]"], "Decode the storage of a contract deployed in the session or on the fork. Use `this` for the REPL contract's state variables.", CmdCategory::Debug),
+            ChiselCommand::Mapping => (&["mapping <var> <key>", "map <var> <key>"], "Compute the storage slot of a mapping value and read it, e.g. `!mapping token.balances 0x...`", CmdCategory::Debug),
             ChiselCommand::Edit => (&["edit"], "Open the current session in an editor", CmdCategory::Session),
             ChiselCommand::RawStack => (&["rawstack <var>", "rs <var>"], "Display the raw value of a variable's stack allocation. For variables that are > 32 bytes in length, this will display their memory pointer.", CmdCategory::Debug),
         }
@@ -13,12 +13,14 @@ use crate::{
 use alloy_json_abi::JsonAbi;
 use alloy_primitives::{hex, Address};
 use forge_fmt::FormatterConfig;
+use foundry_common::{compact_to_contract, compile::ContractSources};
 use foundry_config::{Config, RpcEndpoint};
+use foundry_debugger::Debugger;
 use foundry_evm::{
     decode::decode_console_logs,
     traces::{
         identifier::{EtherscanIdentifier, SignaturesIdentifier},
-        render_trace_arena, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind,
+        render_trace_arena, CallKind, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind,
     },
 };
 use once_cell::sync::Lazy;
@@ -60,6 +62,10 @@ static ADDRESS_RE: Lazy<Regex> = Lazy::new(|| {
 pub struct ChiselDispatcher {
     /// A Chisel Session
     pub session: ChiselSession,
+    /// The sender of a `vm.startPrank` that was still active after the last execution
+    pub prank: Option<Address>,
+    /// The session source with the last executed statement, even if it failed
+    pub last_source: Option<SessionSource>,
 }
 
 /// Chisel dispatch result variants
@@ -136,7 +142,7 @@ pub fn format_source(source: &str, config: FormatterConfig) -> eyre::Result<Stri
 impl ChiselDispatcher {
     /// Associated public function to create a new Dispatcher instance
     pub fn new(config: SessionSourceConfig) -> eyre::Result<Self> {
-        ChiselSession::new(config).map(|session| Self { session })
+        ChiselSession::new(config).map(|session| Self { session, prank: None, last_source: None })
     }
 
     /// Returns the optional ID of the current session.
@@ -161,6 +167,45 @@ impl ChiselDispatcher {
         )
     }
 
+    /// Loads a Solidity file into the current session, if it compiles.
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> DispatchResult {
+        let mut new_source = self.source().shallow_clone();
+        let path = match new_source.include_file(path) {
+            Ok(path) => path,
+            Err(e) => return DispatchResult::FileIoError(e.into()),
+        };
+        match new_source.build() {
+            Ok(_) => {
+                *self.source_mut() = new_source;
+                DispatchResult::CommandSuccess(Some(format!("Loaded {}!", path.display())))
+            }
+            Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+        }
+    }
+
+    /// Adds a cheatcode call to the session and executes it.
+    async fn execute_cheatcode(&mut self, code: &str) -> DispatchResult {
+        if self.source().config.no_vm {
+            return DispatchResult::CommandFailed(Self::make_error(
+                "Cheatcodes are not available, the `Vm` import is disabled.",
+            ))
+        }
+
+        let mut new_source = self.source().shallow_clone();
+        new_source.with_run_code(code);
+        match new_source.execute().await {
+            Ok((_, res)) if res.success => {
+                self.prank = res.prank;
+                *self.source_mut() = new_source;
+                DispatchResult::CommandSuccess(Some(format!("Added `{code}` to the session")))
+            }
+            Ok(_) => DispatchResult::CommandFailed(Self::make_error(format!(
+                "Failed to execute `{code}`!"
+            ))),
+            Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+        }
+    }
+
     /// Returns the prompt based on the current status of the Dispatcher
     pub fn get_prompt(&self) -> Cow<'static, str> {
         match self.session.id.as_deref() {
@@ -268,6 +313,12 @@ impl ChiselDispatcher {
 
                 // Use args as the name
                 let name = args[0];
+
+                // Solidity files are loaded into the current session
+                if Path::new(name).extension().is_some_and(|ext| ext == "sol") {
+                    return self.load_file(name)
+                }
+
                 // Try to save the current session before loading another
                 // Don't save an empty session
                 if !self.source().run_code.is_empty() {
@@ -301,6 +352,36 @@ impl ChiselDispatcher {
                     DispatchResult::CommandFailed(Self::make_error("Failed to load session!"))
                 }
             }
+            ChiselCommand::Reload => {
+                let num_files = self.source().included_sources.len();
+                if num_files == 0 {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "No files loaded! Load a Solidity file with `!load <path>`.",
+                    ))
+                }
+
+                let mut new_source = self.source().shallow_clone();
+                if let Err(e) = new_source.reload_included_files() {
+                    return DispatchResult::FileIoError(e.into())
+                }
+                match new_source.drain_unresolved_run_code() {
+                    Ok(removed) => {
+                        for statement in removed {
+                            println!(
+                                "{}",
+                                Paint::yellow(format!(
+                                    "Cleared `{statement}` as it no longer compiles"
+                                ))
+                            );
+                        }
+                        *self.source_mut() = new_source;
+                        DispatchResult::CommandSuccess(Some(format!(
+                            "Reloaded {num_files} file(s)!"
+                        )))
+                    }
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                }
+            }
             ChiselCommand::ListSessions => match ChiselSession::list_sessions() {
                 Ok(sessions) => DispatchResult::CommandSuccess(Some(format!(
                     "{}\n{}",
@@ -391,6 +472,109 @@ impl ChiselDispatcher {
                     if self.source_mut().config.traces { "Enabled" } else { "Disabled" }
                 )))
             }
+            ChiselCommand::Trace => {
+                let verbose = match args {
+                    [] => false,
+                    ["-v"] => true,
+                    _ => {
+                        return DispatchResult::CommandFailed(Self::make_error(
+                            "Unexpected arguments supplied! Expected: [-v]",
+                        ))
+                    }
+                };
+                let Some(mut source) = self.last_source.as_ref().map(SessionSource::shallow_clone)
+                else {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "No statement has been executed yet!",
+                    ))
+                };
+
+                match source.execute().await {
+                    Ok((address, mut res)) => {
+                        let decoder = match Self::decode_session_traces(&source, address, &mut res)
+                        {
+                            Ok(decoder) => decoder,
+                            Err(e) => return DispatchResult::CommandFailed(Self::make_error(e)),
+                        };
+                        if let Err(e) = Self::show_traces(&decoder, &mut res).await {
+                            return DispatchResult::CommandFailed(Self::make_error(e))
+                        }
+                        if verbose {
+                            Self::show_storage_accesses(&decoder, &res);
+                        }
+                        DispatchResult::CommandSuccess(None)
+                    }
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                }
+            }
+            ChiselCommand::Debug => {
+                let Some(mut source) = self.last_source.as_ref().map(SessionSource::shallow_clone)
+                else {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "No statement has been executed yet!",
+                    ))
+                };
+                source.config.debug = true;
+
+                let (address, mut res) = match source.execute().await {
+                    Ok(res) => res,
+                    Err(e) => return DispatchResult::CommandFailed(Self::make_error(e)),
+                };
+                let Some(arena) = res.debug.take() else {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "No debug information was collected!",
+                    ))
+                };
+                let decoder = match Self::decode_session_traces(&source, address, &mut res) {
+                    Ok(decoder) => decoder,
+                    Err(e) => return DispatchResult::CommandFailed(Self::make_error(e)),
+                };
+                let sources = match Self::debugger_sources(&source) {
+                    Ok(sources) => sources,
+                    Err(e) => return DispatchResult::CommandFailed(Self::make_error(e)),
+                };
+
+                let mut debugger = Debugger::builder()
+                    .debug_arena(&arena)
+                    .decoder(&decoder)
+                    .sources(sources)
+                    .build();
+                match debugger.try_run() {
+                    Ok(_) => DispatchResult::CommandSuccess(None),
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                }
+            }
+            ChiselCommand::Storage => {
+                let (target, filter) = match args {
+                    [target] => (*target, None),
+                    [target, filter] => (*target, Some(*filter)),
+                    _ => {
+                        return DispatchResult::CommandFailed(Self::make_error(
+                            "Invalid arguments! Expected: <contractVarOrAddr> [slot|varName]",
+                        ))
+                    }
+                };
+                match self.source().format_storage(target, filter).await {
+                    Ok(formatted) => DispatchResult::CommandSuccess(Some(formatted)),
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                }
+            }
+            ChiselCommand::Mapping => {
+                let [var, key @ ..] = args else {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "Invalid arguments! Expected: <var> <key>",
+                    ))
+                };
+                if key.is_empty() {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "Invalid arguments! Expected: <var> <key>",
+                    ))
+                }
+                match self.source().format_mapping(var, &key.join(" ")).await {
+                    Ok(formatted) => DispatchResult::CommandSuccess(Some(formatted)),
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                }
+            }
             ChiselCommand::Calldata => {
                 // remove empty space, double quotes, and 0x prefix
                 let arg = args
@@ -419,6 +603,47 @@ impl ChiselDispatcher {
                     ))),
                 }
             }
+            ChiselCommand::Prank => {
+                if args.len() > 1 {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "Too many arguments supplied! Expected: [address]",
+                    ))
+                }
+
+                let code = match args.first() {
+                    Some(addr) => match addr.parse::<Address>() {
+                        Ok(addr) => format!("vm.startPrank({});", addr.to_checksum(None)),
+                        Err(_) => {
+                            return DispatchResult::CommandFailed(Self::make_error(format!(
+                                "Invalid address \"{addr}\""
+                            )))
+                        }
+                    },
+                    None => "vm.stopPrank();".to_string(),
+                };
+                self.execute_cheatcode(&code).await
+            }
+            ChiselCommand::Deal => {
+                if args.len() < 2 {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "Incorrect number of arguments supplied. Expected: <address> <amount>",
+                    ))
+                }
+
+                let addr = match args[0].parse::<Address>() {
+                    Ok(addr) => addr,
+                    Err(_) => {
+                        return DispatchResult::CommandFailed(Self::make_error(format!(
+                            "Invalid address \"{}\"",
+                            args[0]
+                        )))
+                    }
+                };
+                // the amount may include a unit, e.g. `100 ether`
+                let amount = args[1..].join(" ");
+                self.execute_cheatcode(&format!("vm.deal({}, {amount});", addr.to_checksum(None)))
+                    .await
+            }
             ChiselCommand::MemDump | ChiselCommand::StackDump => {
                 match self.source_mut().execute().await {
                     Ok((_, res)) => {
@@ -453,33 +678,57 @@ impl ChiselDispatcher {
                 }
             }
             ChiselCommand::Export => {
-                // Check if the current session inherits `Script.sol` before exporting
-
-                // Check if the pwd is a foundry project
-                if !Path::new("foundry.toml").exists() {
+                if args.len() > 2 {
                     return DispatchResult::CommandFailed(Self::make_error(
-                        "Must be in a foundry project to export source to script.",
-                    ));
+                        "Too many arguments supplied! Expected: [script|test] [path]",
+                    ))
                 }
 
-                // Create "script" dir if it does not already exist.
-                if !Path::new("script").exists() {
-                    if let Err(e) = std::fs::create_dir_all("script") {
+                // Stop a prank that is still active at the end of the session
+                let mut source = self.source().shallow_clone();
+                if self.prank.is_some() {
+                    source.with_run_code("vm.stopPrank();");
+                }
+
+                let (source, default_path) = match args.first().copied().unwrap_or("script") {
+                    "script" => (source.to_script_source(), "script/REPL.s.sol"),
+                    "test" => (source.to_test_source(), "test/REPL.t.sol"),
+                    kind => {
+                        return DispatchResult::CommandFailed(Self::make_error(format!(
+                            "Unknown export kind \"{kind}\", expected `script` or `test`."
+                        )))
+                    }
+                };
+
+                let path = match args.get(1) {
+                    Some(path) => PathBuf::from(path),
+                    None => {
+                        // Check if the pwd is a foundry project
+                        if !Path::new("foundry.toml").exists() {
+                            return DispatchResult::CommandFailed(Self::make_error(
+                                "Must be in a foundry project to export source without a path.",
+                            ));
+                        }
+                        PathBuf::from(default_path)
+                    }
+                };
+
+                // Create the parent dir if it does not already exist.
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    if let Err(e) = std::fs::create_dir_all(parent) {
                         return DispatchResult::CommandFailed(Self::make_error(e.to_string()))
                     }
                 }
 
-                match self.format_source() {
+                match format_source(&source, self.source().config.foundry_config.fmt.clone()) {
                     Ok(formatted_source) => {
-                        // Write session source to `script/REPL.s.sol`
-                        if let Err(e) =
-                            std::fs::write(PathBuf::from("script/REPL.s.sol"), formatted_source)
-                        {
+                        if let Err(e) = std::fs::write(&path, formatted_source) {
                             return DispatchResult::CommandFailed(Self::make_error(e.to_string()))
                         }
 
-                        DispatchResult::CommandSuccess(Some(String::from(
-                            "Exported session source to script/REPL.s.sol!",
+                        DispatchResult::CommandSuccess(Some(format!(
+                            "Exported session source to {}!",
+                            path.display()
                         )))
                     }
                     Err(_) => DispatchResult::CommandFailed(String::from(
@@ -810,9 +1059,12 @@ impl ChiselDispatcher {
             }
         };
 
+        // Keep the source around so the statement can be traced or debugged, even if it fails
+        self.last_source = Some(new_source.shallow_clone());
+
         // TODO: Cloning / parsing the session source twice on non-inspected inputs kinda sucks.
         // Should change up how this works.
-        match source.inspect(input).await {
+        match self.source().inspect(input).await {
             // Continue and print
             Ok((true, Some(res))) => println!("{res}"),
             Ok((true, None)) => {}
@@ -859,6 +1111,7 @@ impl ChiselDispatcher {
                     }
 
                     // Replace the old session source with the new version
+                    self.prank = res.prank;
                     *self.source_mut() = new_source;
 
                     DispatchResult::Success(None)
@@ -913,6 +1166,88 @@ impl ChiselDispatcher {
         Ok(decoder)
     }
 
+    /// Decodes the traces of a REPL execution with the ABIs of all contracts compiled in the
+    /// session, on top of [Self::decode_traces].
+    fn decode_session_traces(
+        source: &SessionSource,
+        address: Address,
+        result: &mut ChiselResult,
+    ) -> eyre::Result<CallTraceDecoder> {
+        let mut decoder = Self::decode_traces(&source.config, result)?;
+        if let Some(output) = &source.generated_output {
+            for (_, contract) in output.compiler_output.contracts_iter() {
+                let Some(abi) = &contract.abi else { continue };
+                abi.functions().cloned().for_each(|func| decoder.push_function(func));
+                abi.events().cloned().for_each(|event| decoder.push_event(event));
+                abi.errors().cloned().for_each(|error| decoder.push_error(error));
+            }
+        }
+        decoder.contracts.insert(address, source.contract_name.clone());
+        Ok(decoder)
+    }
+
+    /// Returns the REPL contract's source and bytecode for the debugger.
+    fn debugger_sources(source: &SessionSource) -> eyre::Result<ContractSources> {
+        let output = source
+            .generated_output
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Session source was not compiled"))?;
+        let file = source.file_name.to_string_lossy();
+        let file_id = output
+            .compiler_output
+            .sources
+            .get(file.as_ref())
+            .map(|source| source.id)
+            .ok_or_else(|| eyre::eyre!("Could not find the REPL source"))?;
+        let contract = output
+            .compiler_output
+            .contracts
+            .get(file.as_ref())
+            .and_then(|contracts| contracts.get(&source.contract_name))
+            .ok_or_else(|| eyre::eyre!("Could not find the REPL contract"))?;
+
+        let mut sources = ContractSources::default();
+        sources.ids_by_name.insert(source.contract_name.clone(), vec![file_id]);
+        sources.sources_by_id.insert(
+            file_id,
+            (source.to_repl_source(), compact_to_contract(contract.clone().into())?),
+        );
+        sources.paths_by_id.insert(file_id, source.file_name.clone());
+        Ok(sources)
+    }
+
+    /// Display the storage slots read and written by a REPL execution.
+    fn show_storage_accesses(decoder: &CallTraceDecoder, result: &ChiselResult) {
+        let Some(changeset) = &result.state_changeset else { return };
+        let mut accounts =
+            changeset.iter().filter(|(_, account)| !account.storage.is_empty()).collect::<Vec<_>>();
+        if accounts.is_empty() {
+            return
+        }
+        accounts.sort_by_key(|(address, _)| **address);
+
+        println!("{}", Paint::green("Storage accesses:"));
+        for (address, account) in accounts {
+            match decoder.contracts.get(address).or_else(|| decoder.labels.get(address)) {
+                Some(name) => println!("  {address} ({name})"),
+                None => println!("  {address}"),
+            }
+
+            let mut slots = account.storage.iter().collect::<Vec<_>>();
+            slots.sort_by_key(|(slot, _)| **slot);
+            for (slot, value) in slots {
+                if value.is_changed() {
+                    println!(
+                        "    @ {slot:#x}: {:#x} → {:#x}",
+                        value.previous_or_original_value, value.present_value
+                    );
+                } else {
+                    println!("    @ {slot:#x}: {:#x} (read)", value.present_value);
+                }
+            }
+        }
+    }
+
     /// Display the gathered traces of a REPL execution.
     ///
     /// ### Takes
@@ -939,6 +1274,36 @@ impl ChiselDispatcher {
             }
         }
 
+        // Calls made under a prank have a different sender than the address that made them
+        let label = |addr: &Address| {
+            decoder.labels.get(addr).cloned().unwrap_or_else(|| addr.to_checksum(None))
+        };
+        let pranked = result
+            .traces
+            .iter()
+            .flat_map(|(_, arena)| {
+                let nodes = arena.nodes();
+                nodes.iter().filter(move |node| {
+                    let Some(parent) = node.parent.map(|idx| &nodes[idx]) else { return false };
+                    let delegated = |kind: CallKind| {
+                        matches!(kind, CallKind::DelegateCall | CallKind::CallCode)
+                    };
+                    !delegated(node.trace.kind) &&
+                        !delegated(parent.trace.kind) &&
+                        node.trace.caller != parent.trace.address
+                })
+            })
+            .map(|node| {
+                format!("  {} called by {}", label(&node.trace.address), label(&node.trace.caller))
+            })
+            .collect::<Vec<_>>();
+        if !pranked.is_empty() {
+            println!("{}", Paint::green("Pranked calls:"));
+            for line in pranked {
+                println!("{line}");
+            }
+        }
+
         Ok(())
     }
 
@@ -10,6 +10,7 @@ use alloy_json_abi::EventParam;
 use alloy_primitives::{hex, Address, U256};
 use core::fmt::Debug;
 use eyre::{Result, WrapErr};
+use forge_fmt::solang_ext::SafeUnwrap;
 use foundry_compilers::Artifact;
 use foundry_evm::{
     backend::Backend, decode::decode_console_logs, executors::ExecutorBuilder,
@@ -234,19 +235,55 @@ impl SessionSource {
             }
         };
 
-        // the file compiled correctly, thus the last stack item must be the memory offset of
-        // the `bytes memory inspectoor` value
-        let mut offset = stack.data().last().unwrap().to::<usize>();
-        let mem_offset = &memory[offset..offset + 32];
-        let len = U256::try_from_be_slice(mem_offset).unwrap().to::<usize>();
-        offset += 32;
-        let data = &memory[offset..offset + len];
         // `tokens` is guaranteed to have the same length as the provided types
-        let token =
-            DynSolType::abi_decode(&ty, data).wrap_err("Could not decode inspected values")?;
+        let token = DynSolType::abi_decode(&ty, inspectoor_data(stack, memory))
+            .wrap_err("Could not decode inspected values")?;
+
+        // Structs are decoded as plain tuples, so name their fields using the compiled types
+        if contains_struct(contract_expr, &generated_output.intermediate) {
+            let literal = format_solidity_literal(
+                &token,
+                Some(contract_expr),
+                &generated_output.intermediate,
+            );
+            return Ok((
+                should_continue(contract_expr),
+                Some(format!(
+                    "Type: {}\n└ Value: {}",
+                    Paint::red(contract_expr.to_string()),
+                    Paint::cyan(literal)
+                )),
+            ))
+        }
         Ok((should_continue(contract_expr), Some(format_token(token))))
     }
 
+    /// Evaluates an expression after all of the session's statements have run, and decodes it
+    /// as the given type.
+    ///
+    /// ### Takes
+    ///
+    /// A solidity expression and the [DynSolType] it is expected to `abi.encode` to
+    ///
+    /// ### Returns
+    ///
+    /// The decoded [DynSolValue]
+    pub async fn evaluate(&self, expr: &str, ty: &DynSolType) -> Result<DynSolValue> {
+        let line = format!("bytes memory inspectoor = abi.encode({expr});");
+        let (mut source, _) = self.clone_with_new_line(line)?;
+        let (_, res) = source.execute().await?;
+        let Some((stack, memory, _)) = &res.state else {
+            eyre::bail!("Failed to evaluate `{expr}`")
+        };
+
+        let data = inspectoor_data(stack, memory);
+        match ty {
+            DynSolType::Tuple(_) => ty.abi_decode_params(data),
+            _ => ty.abi_decode(data),
+        }
+        .wrap_err_with(|| format!("Could not decode `{expr}`"))
+    }
+
     /// Gracefully attempts to extract the type of the expression within the `abi.encode(...)`
     /// call inserted by the inspect function.
     ///
@@ -302,7 +339,7 @@ impl SessionSource {
         // Build a new executor
         let executor = ExecutorBuilder::new()
             .inspectors(|stack| {
-                stack.chisel_state(final_pc).trace(true).cheatcodes(
+                stack.chisel_state(final_pc).trace(true).debug(self.config.debug).cheatcodes(
                     CheatsConfig::new(
                         &self.config.foundry_config,
                         self.config.evm_opts.clone(),
@@ -321,6 +358,106 @@ impl SessionSource {
     }
 }
 
+/// Returns the contents of the `bytes memory inspectoor` value appended by [SessionSource::inspect]
+/// and [SessionSource::evaluate].
+///
+/// The source compiled correctly, thus the last stack item must be the memory offset of the value.
+fn inspectoor_data<'a>(stack: &revm::interpreter::Stack, memory: &'a [u8]) -> &'a [u8] {
+    let mut offset = stack.data().last().unwrap().to::<usize>();
+    let mem_offset = &memory[offset..offset + 32];
+    let len = U256::try_from_be_slice(mem_offset).unwrap().to::<usize>();
+    offset += 32;
+    &memory[offset..offset + len]
+}
+
+/// Returns the struct definition that a type expression refers to, if any.
+///
+/// Unqualified struct names are looked up in the REPL contract first.
+fn struct_definition<'a>(
+    ty: &pt::Expression,
+    intermediate: &'a IntermediateOutput,
+) -> Option<&'a pt::StructDefinition> {
+    let contracts = &intermediate.intermediate_contracts;
+    let strukt = match ty {
+        pt::Expression::Variable(ident) => contracts
+            .get("REPL")
+            .and_then(|contract| contract.struct_definitions.get(&ident.name))
+            .or_else(|| {
+                contracts.values().find_map(|contract| contract.struct_definitions.get(&ident.name))
+            }),
+        pt::Expression::MemberAccess(_, expr, member) => match expr.as_ref() {
+            pt::Expression::Variable(contract) => {
+                contracts.get(&contract.name)?.struct_definitions.get(&member.name)
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    strukt.map(AsRef::as_ref)
+}
+
+/// Returns whether a type expression is a struct or an array of structs.
+fn contains_struct(ty: &pt::Expression, intermediate: &IntermediateOutput) -> bool {
+    match ty {
+        pt::Expression::ArraySubscript(_, inner, _) => contains_struct(inner, intermediate),
+        ty => struct_definition(ty, intermediate).is_some(),
+    }
+}
+
+/// Formats a [DynSolValue] as a Solidity literal, so that it can be pasted back into the REPL.
+///
+/// If the value's type expression is known, tuples that are structs are formatted with their
+/// field names, e.g. `Point({x: 1, y: 2})`.
+pub fn format_solidity_literal(
+    value: &DynSolValue,
+    ty: Option<&pt::Expression>,
+    intermediate: &IntermediateOutput,
+) -> String {
+    let join = |values: &[DynSolValue], ty: Option<&pt::Expression>| {
+        values
+            .iter()
+            .map(|value| format_solidity_literal(value, ty, intermediate))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match value {
+        DynSolValue::Tuple(values) => match ty.and_then(|ty| struct_definition(ty, intermediate)) {
+            Some(strukt) if strukt.fields.len() == values.len() => {
+                let fields = strukt
+                    .fields
+                    .iter()
+                    .zip(values)
+                    .map(|(field, value)| {
+                        format!(
+                            "{}: {}",
+                            field.name.safe_unwrap().name,
+                            format_solidity_literal(value, Some(&field.ty), intermediate)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{}({{{fields}}})", strukt.name.safe_unwrap().name)
+            }
+            _ => format!("({})", join(values, None)),
+        },
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values) => {
+            let inner = match ty {
+                Some(pt::Expression::ArraySubscript(_, inner, _)) => Some(inner.as_ref()),
+                _ => None,
+            };
+            format!("[{}]", join(values, inner))
+        }
+        DynSolValue::Address(address) => address.to_string(),
+        DynSolValue::Bool(b) => b.to_string(),
+        DynSolValue::Int(i, _) => i.to_string(),
+        DynSolValue::Uint(u, _) => u.to_string(),
+        DynSolValue::FixedBytes(word, size) => hex::encode_prefixed(&word[..*size]),
+        DynSolValue::Bytes(bytes) => format!("hex\"{}\"", hex::encode(bytes)),
+        DynSolValue::String(s) => format!("{s:?}"),
+        value => format!("{value:?}"),
+    }
+}
+
 /// Formats a [Token] into an inspection message
 ///
 /// ### Takes
@@ -1405,6 +1542,7 @@ impl<'a> Iterator for InstructionIter<'a> {
 mod tests {
     use super::*;
     use foundry_compilers::{error::SolcError, Solc};
+    use foundry_config::{RpcEndpoint, RpcEndpoints};
     use std::sync::Mutex;
 
     #[test]
@@ -1677,6 +1815,150 @@ mod tests {
         generic_type_test(&mut source(), global_variables);
     }
 
+    #[test]
+    fn test_include_file_and_reload() {
+        let dir = std::env::temp_dir().join(format!("chisel-include-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("Counter.sol");
+        std::fs::write(
+            &file,
+            "pragma solidity ^0.8.0;\nstruct Point { uint256 x; }\ncontract Counter { uint256 public count; }\nfunction double(uint256 x) pure returns (uint256) { return 2 * x; }",
+        )
+        .unwrap();
+
+        let mut s = source();
+        s.include_file(&file).unwrap();
+        s.with_run_code("Point memory p = Point(double(2));");
+        s.with_run_code("Counter c = new Counter();");
+        s.build().unwrap();
+        assert!(s.to_repl_source().contains(&format!("import \"{}\";", file.display())));
+
+        // `double` is removed, bindings that no longer typecheck are cleared
+        std::fs::write(
+            &file,
+            "pragma solidity ^0.8.0;\nstruct Point { uint256 x; }\ncontract Counter { uint256 public count; }",
+        )
+        .unwrap();
+        s.reload_included_files().unwrap();
+        let removed = s.drain_unresolved_run_code().unwrap();
+        assert_eq!(removed, vec!["Point memory p = Point(double(2));".to_string()]);
+        assert!(s.run_code.contains("Counter c = new Counter();"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_export_sources() {
+        let mut s = source();
+        s.with_global_code(
+            "interface IERC20 { function totalSupply() external view returns (uint256); }",
+        );
+        s.with_run_code("uint256 a = 1;");
+        s.config.evm_opts.fork_url = Some("http://localhost:8545".to_string());
+        s.config.evm_opts.fork_block_number = Some(100);
+
+        let script = s.to_script_source();
+        assert!(script.contains("contract REPL is Script"));
+        assert!(script.contains("interface IERC20"));
+        assert!(script.contains("vm.createSelectFork(\"http://localhost:8545\", 100);"));
+
+        let test = s.to_test_source();
+        assert!(test.contains("contract REPLTest is Test"));
+        assert!(test.contains("function setUp() public"));
+        assert!(test.contains("function test_Session() public"));
+        assert!(test.contains("uint256 a = 1;"));
+
+        for source in [script, test] {
+            solang_parser::parse(&source, 0).expect("exported source should parse");
+        }
+
+        // selecting the fork requires `vm`
+        s.config.no_vm = true;
+        assert!(s.to_script_source().contains("contract REPL is Script"));
+
+        // credentials are not written to the exported source
+        s.config.evm_opts.fork_url =
+            Some("https://eth-mainnet.g.alchemy.com/v2/secret".to_string());
+        let script = s.to_script_source();
+        assert!(!script.contains("secret"));
+        assert!(script.contains("vm.createSelectFork(vm.envString(\"FORK_URL\"), 100);"));
+
+        // but the `[rpc_endpoints]` alias is
+        s.config.foundry_config.rpc_endpoints = RpcEndpoints::new([(
+            "mainnet",
+            RpcEndpoint::Url("https://eth-mainnet.g.alchemy.com/v2/secret".to_string()),
+        )]);
+        let script = s.to_script_source();
+        assert!(!script.contains("secret"));
+        assert!(script.contains("vm.createSelectFork(\"mainnet\", 100);"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_export_script_round_trip() {
+        let mut s = source();
+        s.config.no_vm = true;
+        s.with_top_level_code("uint256 public counter;");
+        s.with_run_code("counter += 20;");
+        s.with_run_code("counter *= 2;");
+        let (_, expected) = s.inspect("counter").await.unwrap();
+
+        // running the exported script reproduces the session's state
+        let script = s
+            .to_script_source()
+            .lines()
+            .filter(|line| !line.starts_with("// SPDX") && !line.starts_with("pragma"))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .replace("contract REPL", "contract Exported");
+        let mut exported = source();
+        exported.config.no_vm = true;
+        exported.with_global_code(&script);
+        exported.with_run_code("Exported e = new Exported();");
+        exported.with_run_code("e.run();");
+        let (_, actual) = exported.inspect("e.counter()").await.unwrap();
+
+        assert!(expected.is_some());
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_prank_across_statements() {
+        let mut s = source();
+        s.with_global_code(
+            "contract Sender { function get() external view returns (address) { return msg.sender; } }",
+        );
+        s.with_run_code("vm.startPrank(address(0xbeef));");
+        s.with_run_code("Sender sender = new Sender();");
+        s.with_run_code("require(sender.get() == address(0xbeef));");
+
+        let (_, res) = s.execute().await.unwrap();
+        assert!(res.success);
+        let pranked = "0x000000000000000000000000000000000000bEEF".parse().unwrap();
+        assert_eq!(res.prank, Some(pranked));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_inspect_structs_and_storage() {
+        let mut s = source();
+        s.with_top_level_code(
+            "struct Point { uint256 x; uint256 y; }\nmapping(address => uint256) balances;\nint8 small = -7;",
+        );
+        s.with_run_code("Point memory p = Point(1, 2);");
+        s.with_run_code("balances[address(1)] = 100;");
+
+        let (_, inspected) = s.inspect("p").await.unwrap();
+        assert!(inspected.unwrap().contains("Point({x: 1, y: 2})"));
+
+        let value = s
+            .format_mapping("balances", "0x0000000000000000000000000000000000000001")
+            .await
+            .unwrap();
+        assert!(value.contains("100"));
+
+        let storage = s.format_storage("this", Some("small")).await.unwrap();
+        assert!(storage.contains("-7"));
+    }
+
     #[track_caller]
     fn source() -> SessionSource {
         // synchronize solc install
@@ -24,6 +24,9 @@ pub mod runner;
 /// REPL contract executor
 pub mod executor;
 
+/// Storage layout decoding for session contracts
+pub mod storage;
+
 /// A Solidity Helper module for rustyline
 pub mod solidity_helper;
 
@@ -6,8 +6,10 @@
 use alloy_primitives::{Address, Bytes, Log, U256};
 use eyre::Result;
 use foundry_evm::{
+    debug::DebugArena,
     executors::{DeployResult, Executor, RawCallResult},
-    traces::{CallTraceArena, TraceKind},
+    traces::{TraceKind, Traces},
+    utils::StateChangeset,
 };
 use revm::interpreter::{return_ok, InstructionResult};
 use std::collections::HashMap;
@@ -39,7 +41,7 @@ pub struct ChiselResult {
     /// Transaction logs
     pub logs: Vec<Log>,
     /// Call traces
-    pub traces: Vec<(TraceKind, CallTraceArena)>,
+    pub traces: Traces,
     /// Amount of gas used in the transaction
     pub gas_used: u64,
     /// Map of addresses to their labels
@@ -50,6 +52,12 @@ pub struct ChiselResult {
     pub address: Option<Address>,
     /// EVM State at the final instruction of the `run()` function
     pub state: Option<(revm::interpreter::Stack, Vec<u8>, InstructionResult)>,
+    /// The sender of a `vm.startPrank` that is still active at the end of the `run()` function
+    pub prank: Option<Address>,
+    /// The debug arena, if the debugger was enabled
+    pub debug: Option<DebugArena>,
+    /// All accounts and storage slots touched by the `run()` function
+    pub state_changeset: Option<StateChangeset>,
 }
 
 /// ChiselRunner implementation
@@ -192,7 +200,22 @@ impl ChiselRunner {
             res = self.executor.call_raw_committing(from, to, calldata, value)?;
         }
 
-        let RawCallResult { result, reverted, logs, traces, labels, chisel_state, .. } = res;
+        let RawCallResult {
+            result,
+            reverted,
+            logs,
+            traces,
+            labels,
+            chisel_state,
+            cheatcodes,
+            debug,
+            state_changeset,
+            ..
+        } = res;
+        let prank = cheatcodes
+            .and_then(|cheatcodes| cheatcodes.prank)
+            .filter(|prank| !prank.single_call)
+            .map(|prank| prank.new_caller);
 
         Ok(ChiselResult {
             returned: result,
@@ -209,6 +232,9 @@ impl ChiselRunner {
             labeled_addresses: labels,
             address: None,
             state: chisel_state,
+            prank,
+            debug,
+            state_changeset,
         })
     }
 }
@@ -7,15 +7,22 @@
 use eyre::Result;
 use forge_fmt::solang_ext::SafeUnwrap;
 use foundry_compilers::{
-    artifacts::{Source, Sources},
+    artifacts::{output_selection::ContractOutputSelection, Source, Sources},
     CompilerInput, CompilerOutput, Solc,
 };
-use foundry_config::{Config, SolcReq};
+use foundry_config::{redact_url, Config, SolcReq};
 use foundry_evm::{backend::Backend, opts::EvmOpts};
 use semver::Version;
 use serde::{Deserialize, Serialize};
-use solang_parser::{diagnostics::Diagnostic, pt};
-use std::{collections::HashMap, fs, path::PathBuf};
+use solang_parser::{
+    diagnostics::Diagnostic,
+    pt::{self, CodeLocation},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
 use yansi::Paint;
 
 /// The minimum Solidity version of the `Vm` interface.
@@ -79,6 +86,9 @@ pub struct SessionSourceConfig {
     pub backend: Option<Backend>,
     /// Optionally enable traces for the REPL contract execution
     pub traces: bool,
+    /// Optionally enable the debugger inspector for the REPL contract execution
+    #[serde(skip)]
+    pub debug: bool,
     /// Optionally set calldata for the REPL contract execution
     pub calldata: Option<Vec<u8>>,
 }
@@ -162,6 +172,11 @@ pub struct SessionSource {
     pub top_level_code: String,
     /// Code existing within the "run()" function's scope
     pub run_code: String,
+    /// Solidity files loaded into the session, keyed by their absolute path
+    ///
+    /// The contents are read when the file is loaded and only refreshed on reload.
+    #[serde(default)]
+    pub included_sources: BTreeMap<PathBuf, String>,
     /// The generated output
     pub generated_output: Option<GeneratedOutput>,
     /// Session Source configuration
@@ -200,6 +215,7 @@ impl SessionSource {
             global_code: Default::default(),
             top_level_code: Default::default(),
             run_code: Default::default(),
+            included_sources: Default::default(),
             generated_output: None,
         }
     }
@@ -218,6 +234,7 @@ impl SessionSource {
             global_code: self.global_code.clone(),
             top_level_code: self.top_level_code.clone(),
             run_code: self.run_code.clone(),
+            included_sources: self.included_sources.clone(),
             generated_output: None,
             config: self.config.clone(),
         }
@@ -308,6 +325,115 @@ impl SessionSource {
         self
     }
 
+    // Included files
+
+    /// Loads a Solidity file into the source, making its definitions available to the session.
+    ///
+    /// Relative paths are resolved against the project root.
+    ///
+    /// ### Returns
+    ///
+    /// The absolute path of the loaded file.
+    pub fn include_file(&mut self, path: impl AsRef<Path>) -> Result<PathBuf> {
+        let path = self.config.foundry_config.__root.0.join(path);
+        let path = fs::canonicalize(&path)
+            .map_err(|err| eyre::eyre!("Could not find {}: {err}", path.display()))?;
+        let content = fs::read_to_string(&path)?;
+        self.included_sources.insert(path.clone(), content);
+        self.generated_output = None;
+        Ok(path)
+    }
+
+    /// Re-reads all loaded files from disk
+    pub fn reload_included_files(&mut self) -> Result<&mut Self> {
+        for (path, content) in self.included_sources.iter_mut() {
+            *content = fs::read_to_string(path)
+                .map_err(|err| eyre::eyre!("Could not reload {}: {err}", path.display()))?;
+        }
+        self.generated_output = None;
+        Ok(self)
+    }
+
+    /// Rebuilds the source, removing all statements of the "run()" function that no longer
+    /// compile, e.g. because a loaded file changed.
+    ///
+    /// ### Returns
+    ///
+    /// The removed statements, or an error if the source doesn't compile without the "run()"
+    /// function's code.
+    pub fn drain_unresolved_run_code(&mut self) -> Result<Vec<String>> {
+        if self.build().is_ok() {
+            return Ok(Vec::new())
+        }
+
+        let statements = self.run_code_statements();
+        self.drain_run();
+        self.build()?;
+
+        // re-add statements one by one, dropping those that don't compile anymore including
+        // any that depend on them
+        let mut removed = Vec::new();
+        for statement in statements {
+            let mut new_source = self.shallow_clone();
+            new_source.with_run_code(&statement);
+            if new_source.build().is_ok() {
+                *self = new_source;
+            } else {
+                removed.push(statement);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Returns the source of all statements in the "run()" function
+    fn run_code_statements(&self) -> Vec<String> {
+        let source = self.to_repl_source();
+        let Ok((pt::SourceUnit(parts), _)) = solang_parser::parse(&source, 0) else {
+            return Vec::new()
+        };
+
+        let body = parts
+            .into_iter()
+            .filter_map(|part| match part {
+                pt::SourceUnitPart::ContractDefinition(cd)
+                    if cd.name.as_ref().is_some_and(|name| name.name == self.contract_name) =>
+                {
+                    Some(cd.parts)
+                }
+                _ => None,
+            })
+            .flatten()
+            .find_map(|part| match part {
+                pt::ContractPart::FunctionDefinition(def)
+                    if def.name.as_ref().is_some_and(|name| name.name == "run") =>
+                {
+                    def.body
+                }
+                _ => None,
+            });
+
+        let Some(pt::Statement::Block { statements, .. }) = body else { return Vec::new() };
+        statements
+            .iter()
+            .map(|statement| {
+                let loc = statement.loc();
+                let mut code = source[loc.start()..loc.end()].to_string();
+                if !code.ends_with(';') && !code.ends_with('}') {
+                    code.push(';');
+                }
+                code
+            })
+            .collect()
+    }
+
+    /// Returns the import directives for all loaded files
+    fn included_imports(&self) -> String {
+        self.included_sources
+            .keys()
+            .map(|path| format!("import \"{}\";\n", path.display()))
+            .collect()
+    }
+
     /// Generates and foundry_compilers::CompilerInput from the source
     ///
     /// ### Returns
@@ -317,6 +443,9 @@ impl SessionSource {
     pub fn compiler_input(&self) -> CompilerInput {
         let mut sources = Sources::new();
         sources.insert(self.file_name.clone(), Source::new(self.to_repl_source()));
+        for (path, content) in &self.included_sources {
+            sources.insert(path.clone(), Source::new(content.clone()));
+        }
 
         let remappings = self.config.foundry_config.get_all_remappings().collect::<Vec<_>>();
 
@@ -337,6 +466,10 @@ impl SessionSource {
         // We also need to enforce the EVM version that the user has specified.
         compiler_input.settings.evm_version = Some(self.config.foundry_config.evm_version);
 
+        // The storage layout is needed to decode storage with `!storage` and `!mapping`
+        compiler_input.settings =
+            compiler_input.settings.with_extra_output([ContractOutputSelection::StorageLayout]);
+
         compiler_input
     }
 
@@ -441,6 +574,8 @@ impl SessionSource {
 
     /// Convert the [SessionSource] to a valid Script contract
     ///
+    /// If the session is forked, the fork is selected at the start of the script.
+    ///
     /// ### Returns
     ///
     /// The [SessionSource] represented as a Forge Script contract.
@@ -448,8 +583,15 @@ impl SessionSource {
         let Version { major, minor, patch, .. } = self.solc.version().unwrap();
         let Self { contract_name, global_code, top_level_code, run_code, config, .. } = self;
 
-        let script_import =
-            if !config.no_vm { "import {Script} from \"forge-std/Script.sol\";\n" } else { "" };
+        let fork = self.select_fork_code();
+        // selecting the fork requires `vm`, even if the session doesn't use it otherwise
+        let (script_import, inherits) = if !config.no_vm || fork.is_some() {
+            ("import {Script} from \"forge-std/Script.sol\";\n", " is Script")
+        } else {
+            ("", "")
+        };
+        let included_imports = self.included_imports();
+        let fork = fork.unwrap_or_default();
 
         format!(
             r#"
@@ -457,19 +599,83 @@ impl SessionSource {
 pragma solidity ^{major}.{minor}.{patch};
 
 {script_import}
+{included_imports}
 {global_code}
 
-contract {contract_name} is Script {{
+contract {contract_name}{inherits} {{
     {top_level_code}
   
     /// @notice Script entry point
     function run() public {{
+        {fork}
+        {run_code}
+    }}
+}}"#,
+        )
+    }
+
+    /// Convert the [SessionSource] to a valid Test contract
+    ///
+    /// If the session is forked, the fork is selected in `setUp()`.
+    ///
+    /// ### Returns
+    ///
+    /// The [SessionSource] represented as a Forge Test contract.
+    pub fn to_test_source(&self) -> String {
+        let Version { major, minor, patch, .. } = self.solc.version().unwrap();
+        let Self { contract_name, global_code, top_level_code, run_code, .. } = self;
+
+        let included_imports = self.included_imports();
+        let set_up = self
+            .select_fork_code()
+            .map(|fork| format!("function setUp() public {{\n        {fork}\n    }}\n"))
+            .unwrap_or_default();
+
+        format!(
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^{major}.{minor}.{patch};
+
+import {{Test}} from "forge-std/Test.sol";
+{included_imports}
+{global_code}
+
+contract {contract_name}Test is Test {{
+    {top_level_code}
+
+    {set_up}
+    function test_Session() public {{
         {run_code}
     }}
 }}"#,
         )
     }
 
+    /// Returns the cheatcode call that selects the session's fork, if it is forked
+    ///
+    /// The fork is selected by its `[rpc_endpoints]` alias if it has one. Otherwise URLs that may
+    /// contain credentials are read from the `FORK_URL` environment variable instead of being
+    /// written to the exported source.
+    fn select_fork_code(&self) -> Option<String> {
+        let fork_url = self.config.evm_opts.fork_url.as_ref()?;
+        let alias =
+            self.config.foundry_config.rpc_endpoints.iter().find_map(|(alias, endpoint)| {
+                (endpoint.clone().resolve().ok().as_ref() == Some(fork_url)).then_some(alias)
+            });
+        let redacted = redact_url(fork_url);
+        let (comment, url) = match alias {
+            Some(alias) => (String::new(), format!("\"{alias}\"")),
+            None if redacted == fork_url.trim_end_matches('/') => {
+                (String::new(), format!("\"{fork_url}\""))
+            }
+            None => (format!("// {redacted}\n        "), "vm.envString(\"FORK_URL\")".to_string()),
+        };
+        Some(match self.config.evm_opts.fork_block_number {
+            Some(block) => format!("{comment}vm.createSelectFork({url}, {block});"),
+            None => format!("{comment}vm.createSelectFork({url});"),
+        })
+    }
+
     /// Convert the [SessionSource] to a valid REPL contract
     ///
     /// ### Returns
@@ -487,6 +693,7 @@ contract {contract_name} is Script {{
         } else {
             ("", "")
         };
+        let included_imports = self.included_imports();
 
         format!(
             r#"
@@ -494,6 +701,7 @@ contract {contract_name} is Script {{
 pragma solidity ^{major}.{minor}.{patch};
 
 {vm_import}
+{included_imports}
 {global_code}
 
 contract {contract_name} {{
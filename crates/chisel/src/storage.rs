@@ -0,0 +1,442 @@
+//! Storage
+//!
+//! This module contains helpers to read and decode the storage of contracts deployed in, or
+//! forked by, a [SessionSource], using the storage layouts emitted by the compiler.
+
+use crate::prelude::SessionSource;
+use alloy_dyn_abi::DynSolType;
+use alloy_primitives::{hex, keccak256, Address, B256, I256, U256};
+use eyre::Result;
+use foundry_common::strip_bytecode_metadata;
+use foundry_compilers::{
+    artifacts::{Storage, StorageLayout, StorageType},
+    Artifact,
+};
+use solang_parser::pt;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    str::FromStr,
+};
+use yansi::Paint;
+
+/// The maximum number of elements of a static array, or words of a long string, that are decoded.
+const MAX_DECODED_WORDS: usize = 32;
+
+impl SessionSource {
+    /// Decodes the storage of a contract using its storage layout.
+    ///
+    /// ### Takes
+    ///
+    /// - `target` - `this`, a session variable of a contract type, or an address
+    /// - `filter` - Optionally, a slot number or the name of a state variable
+    ///
+    /// ### Returns
+    ///
+    /// One line per state variable, formatted as `name (type) @ slot: value`
+    pub async fn format_storage(&self, target: &str, filter: Option<&str>) -> Result<String> {
+        let (source, address, contract) = self.storage_target(target).await?;
+        let layout = source.storage_layout(&contract)?;
+
+        let slot_filter = filter.and_then(|filter| U256::from_str(filter).ok());
+        let vars = layout
+            .storage
+            .iter()
+            .filter(|var| match (filter, slot_filter) {
+                (None, _) => true,
+                (_, Some(slot)) => U256::from_str(&var.slot).ok() == Some(slot),
+                (Some(name), None) => var.label == name,
+            })
+            .collect::<Vec<_>>();
+
+        if vars.is_empty() {
+            // Slots that don't belong to a state variable are still worth peeking at
+            if let Some(slot) = slot_filter {
+                let word = self.load_storage(address, [slot]).await?.remove(&slot);
+                return Ok(format!(
+                    "{} @ slot {slot}: {}",
+                    Paint::yellow(address),
+                    Paint::cyan(word.unwrap_or_default())
+                ))
+            }
+            eyre::bail!("`{contract}` has no state variable matching `{}`", filter.unwrap_or(""))
+        }
+
+        let mut decoder = StorageDecoder::new(&layout.types);
+        let values = self
+            .decode_storage(address, &mut decoder, |decoder| {
+                vars.iter().map(|var| decoder.decode_var(var)).collect::<Vec<_>>()
+            })
+            .await?;
+
+        let lines = vars
+            .iter()
+            .zip(values)
+            .map(|(var, value)| {
+                let label = layout.types.get(&var.storage_type).map_or("?", |ty| &ty.label);
+                let offset =
+                    if var.offset > 0 { format!(", offset {}", var.offset) } else { String::new() };
+                format!(
+                    "{} ({}) @ slot {}{offset}: {}",
+                    var.label,
+                    Paint::red(label),
+                    Paint::yellow(&var.slot),
+                    Paint::cyan(value)
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(format!("{} ({contract})\n{}", Paint::green(address), lines.join("\n")))
+    }
+
+    /// Computes the storage slot of a mapping's value and decodes it.
+    ///
+    /// ### Takes
+    ///
+    /// - `var` - The name of a mapping of the REPL contract, or `<target>.<name>` for a mapping of
+    ///   another contract. See [SessionSource::format_storage] for the supported targets.
+    /// - `key` - The mapping key, which is parsed as the mapping's key type
+    ///
+    /// ### Returns
+    ///
+    /// The value formatted as `name[key] @ slot: value`
+    pub async fn format_mapping(&self, var: &str, key: &str) -> Result<String> {
+        let (target, name) = var.rsplit_once('.').unwrap_or(("this", var));
+        let (source, address, contract) = self.storage_target(target).await?;
+        let layout = source.storage_layout(&contract)?;
+
+        let storage = layout
+            .storage
+            .iter()
+            .find(|storage| storage.label == name)
+            .ok_or_else(|| eyre::eyre!("`{name}` is not a state variable of `{contract}`"))?;
+        let ty = layout
+            .types
+            .get(&storage.storage_type)
+            .filter(|ty| ty.encoding == "mapping")
+            .ok_or_else(|| eyre::eyre!("`{name}` is not a mapping"))?;
+        let (Some(key_ty), Some(value_ty)) = (&ty.key, &ty.value) else {
+            eyre::bail!("Storage layout of `{name}` is missing its key or value type")
+        };
+        let key_label = layout.types.get(key_ty).map_or(key_ty.as_str(), |ty| &ty.label);
+
+        let slot = mapping_slot(key_label, key, U256::from_str(&storage.slot)?)?;
+        let mut decoder = StorageDecoder::new(&layout.types);
+        let value = self
+            .decode_storage(address, &mut decoder, |decoder| decoder.decode(value_ty, slot, 0))
+            .await?;
+
+        let value_label = layout.types.get(value_ty).map_or("?", |ty| &ty.label);
+        Ok(format!(
+            "{name}[{key}] ({}) @ slot {}: {}",
+            Paint::red(value_label),
+            Paint::yellow(format!("{slot:#x}")),
+            Paint::cyan(value)
+        ))
+    }
+
+    /// Resolves the address and contract name of a storage target, along with a built clone of
+    /// the session source.
+    ///
+    /// Contracts that are not bound to a session variable are identified by their runtime code.
+    async fn storage_target(&self, target: &str) -> Result<(SessionSource, Address, String)> {
+        let mut source = self.shallow_clone();
+        let output = source.build()?;
+
+        let address_expr = format!("address({target})");
+        let address = source
+            .evaluate(&address_expr, &DynSolType::Address)
+            .await?
+            .as_address()
+            .ok_or_else(|| eyre::eyre!("`{target}` is not an address"))?;
+
+        let bound_contract = match output.intermediate.repl_contract_expressions.get(target) {
+            _ if target == "this" => Some(source.contract_name.clone()),
+            Some(pt::Expression::Variable(ident)) => Some(ident.name.clone()),
+            _ => None,
+        };
+        if let Some(contract) = bound_contract.filter(|name| source.storage_layout(name).is_ok()) {
+            return Ok((source, address, contract))
+        }
+
+        let code = source.evaluate(&format!("{address_expr}.code"), &DynSolType::Bytes).await?;
+        let code = code.as_bytes().unwrap_or_default();
+        let contract = output
+            .compiler_output
+            .contracts
+            .values()
+            .flatten()
+            .find(|(_, contract)| {
+                contract.get_deployed_bytecode_bytes().is_some_and(|deployed| {
+                    !deployed.is_empty() &&
+                        strip_bytecode_metadata(&deployed) == strip_bytecode_metadata(code)
+                })
+            })
+            .map(|(name, _)| name.clone())
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "Could not find the contract deployed at {address}. Load its source with `!load` and bind it to a variable, e.g. `Token token = Token({address});`"
+                )
+            })?;
+        Ok((source, address, contract))
+    }
+
+    /// Returns the storage layout of a compiled contract.
+    fn storage_layout(&self, contract: &str) -> Result<&StorageLayout> {
+        self.generated_output
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Session source was not compiled"))?
+            .compiler_output
+            .contracts
+            .values()
+            .find_map(|contracts| contracts.get(contract))
+            .map(|contract| &contract.storage_layout)
+            .ok_or_else(|| eyre::eyre!("Could not find the storage layout of `{contract}`"))
+    }
+
+    /// Runs `decode` until all of the storage slots it reads have been loaded.
+    async fn decode_storage<T>(
+        &self,
+        address: Address,
+        decoder: &mut StorageDecoder<'_>,
+        decode: impl Fn(&mut StorageDecoder<'_>) -> T,
+    ) -> Result<T> {
+        loop {
+            let decoded = decode(decoder);
+            if decoder.missing.is_empty() {
+                return Ok(decoded)
+            }
+            let slots = std::mem::take(&mut decoder.missing);
+            decoder.words.extend(self.load_storage(address, slots).await?);
+        }
+    }
+
+    /// Reads storage slots of an account after all of the session's statements have run.
+    async fn load_storage(
+        &self,
+        address: Address,
+        slots: impl IntoIterator<Item = U256>,
+    ) -> Result<HashMap<U256, B256>> {
+        if self.config.no_vm {
+            eyre::bail!("Reading storage requires the `Vm` interface, which is disabled by --no-vm")
+        }
+
+        let slots = slots.into_iter().collect::<Vec<_>>();
+        let expr = slots
+            .iter()
+            .map(|slot| format!("vm.load({address}, bytes32(uint256({slot})))"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let ty = DynSolType::Tuple(vec![DynSolType::FixedBytes(32); slots.len()]);
+        let words = self.evaluate(&expr, &ty).await?;
+        let words = words.as_fixed_seq().unwrap_or_default();
+
+        Ok(slots
+            .into_iter()
+            .zip(words)
+            .filter_map(|(slot, word)| Some((slot, B256::from_slice(word.as_fixed_bytes()?.0))))
+            .collect())
+    }
+}
+
+/// Decodes storage values from their storage types.
+///
+/// Slots that have not been loaded yet are decoded as zero and recorded in `missing`, so that
+/// decoding can be repeated once they are loaded.
+struct StorageDecoder<'a> {
+    types: &'a BTreeMap<String, StorageType>,
+    words: HashMap<U256, B256>,
+    missing: BTreeSet<U256>,
+}
+
+impl<'a> StorageDecoder<'a> {
+    fn new(types: &'a BTreeMap<String, StorageType>) -> Self {
+        Self { types, words: HashMap::new(), missing: BTreeSet::new() }
+    }
+
+    fn word(&mut self, slot: U256) -> B256 {
+        match self.words.get(&slot) {
+            Some(word) => *word,
+            None => {
+                self.missing.insert(slot);
+                B256::ZERO
+            }
+        }
+    }
+
+    fn decode_var(&mut self, var: &Storage) -> String {
+        match U256::from_str(&var.slot) {
+            Ok(slot) => self.decode(&var.storage_type, slot, var.offset as usize),
+            Err(_) => "?".to_string(),
+        }
+    }
+
+    /// Decodes the value of a storage type starting at `slot`, `offset` bytes from the right.
+    fn decode(&mut self, ty: &str, slot: U256, offset: usize) -> String {
+        let Some(ty) = self.types.get(ty) else { return self.word(slot).to_string() };
+        let size = ty.number_of_bytes.parse::<usize>().unwrap_or(32).clamp(1, 32);
+
+        match ty.encoding.as_str() {
+            "mapping" => ty.label.clone(),
+            "dynamic_array" => {
+                let len = U256::from_be_bytes(self.word(slot).0);
+                format!("{} (length: {len})", ty.label)
+            }
+            "bytes" => self.decode_bytes(&ty.label, slot),
+            _ => {
+                if let Some(members) = ty.other.get("members") {
+                    let members =
+                        serde_json::from_value::<Vec<Storage>>(members.clone()).unwrap_or_default();
+                    return self.decode_struct(&ty.label, &members, slot)
+                }
+                if let Some(base) = ty.other.get("base").and_then(|base| base.as_str()) {
+                    return self.decode_static_array(&ty.label, base, slot)
+                }
+                let word = self.word(slot);
+                let end = 32 - offset.min(32 - size);
+                decode_value(&ty.label, &word[end - size..end])
+            }
+        }
+    }
+
+    fn decode_struct(&mut self, label: &str, members: &[Storage], slot: U256) -> String {
+        let name = label.trim_start_matches("struct ").rsplit('.').next().unwrap_or(label);
+        let fields = members
+            .iter()
+            .map(|member| {
+                let member_slot = slot + U256::from_str(&member.slot).unwrap_or_default();
+                let value = self.decode(&member.storage_type, member_slot, member.offset as usize);
+                format!("{}: {value}", member.label)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{name}({{{fields}}})")
+    }
+
+    fn decode_static_array(&mut self, label: &str, base: &str, slot: U256) -> String {
+        let len = label
+            .rsplit_once('[')
+            .and_then(|(_, len)| len.trim_end_matches(']').parse::<usize>().ok())
+            .unwrap_or_default();
+        let base_size = self
+            .types
+            .get(base)
+            .and_then(|ty| ty.number_of_bytes.parse::<usize>().ok())
+            .unwrap_or(32)
+            .max(1);
+
+        let mut elements = (0..len.min(MAX_DECODED_WORDS))
+            .map(|i| {
+                // Elements smaller than a word are packed, larger ones start at a new slot
+                let (element_slot, offset) = if base_size < 32 {
+                    let per_slot = 32 / base_size;
+                    (slot + U256::from(i / per_slot), (i % per_slot) * base_size)
+                } else {
+                    (slot + U256::from(i * base_size.div_ceil(32)), 0)
+                };
+                self.decode(base, element_slot, offset)
+            })
+            .collect::<Vec<_>>();
+        if len > MAX_DECODED_WORDS {
+            elements.push("...".to_string());
+        }
+        format!("[{}]", elements.join(", "))
+    }
+
+    fn decode_bytes(&mut self, label: &str, slot: U256) -> String {
+        let word = self.word(slot);
+        let bytes = if word[31] & 1 == 0 {
+            // Short values are stored in the slot itself, with twice their length in the last byte
+            word[..(word[31] / 2) as usize].to_vec()
+        } else {
+            let len = (U256::from_be_bytes(word.0) / U256::from(2)).saturating_to::<usize>();
+            let words = len.div_ceil(32);
+            if words > MAX_DECODED_WORDS {
+                return format!("{label} (length: {len})")
+            }
+            let data_slot = U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0);
+            let mut bytes =
+                (0..words).flat_map(|i| self.word(data_slot + U256::from(i)).0).collect::<Vec<_>>();
+            bytes.truncate(len);
+            bytes
+        };
+
+        if label == "string" {
+            format!("{:?}", String::from_utf8_lossy(&bytes))
+        } else {
+            format!("hex\"{}\"", hex::encode(bytes))
+        }
+    }
+}
+
+/// Decodes a value type from its bytes in a storage slot.
+fn decode_value(label: &str, bytes: &[u8]) -> String {
+    if label == "bool" {
+        return (bytes.iter().any(|b| *b != 0)).to_string()
+    }
+    if bytes.len() == 20 &&
+        (label.starts_with("address") ||
+            label.starts_with("contract ") ||
+            label.starts_with("interface "))
+    {
+        return Address::from_slice(bytes).to_string()
+    }
+    if label.starts_with("uint") || label.starts_with("enum ") {
+        return U256::from_be_slice(bytes).to_string()
+    }
+    if label.starts_with("int") {
+        // Sign-extend to a full word
+        let fill = if bytes.first().is_some_and(|b| b & 0x80 != 0) { 0xff } else { 0 };
+        let mut word = [fill; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+        return I256::from_be_bytes(word).to_string()
+    }
+    hex::encode_prefixed(bytes)
+}
+
+/// Computes the storage slot of a mapping's value from its key and the mapping's slot.
+fn mapping_slot(key_label: &str, key: &str, slot: U256) -> Result<U256> {
+    let mut preimage = match key_label {
+        "string" => key.trim_matches('"').as_bytes().to_vec(),
+        "bytes" => hex::decode(key.trim_start_matches("hex").trim_matches('"'))?,
+        label => {
+            let ty = if label.starts_with("address") ||
+                label.starts_with("contract ") ||
+                label.starts_with("interface ")
+            {
+                DynSolType::Address
+            } else if label.starts_with("enum ") {
+                DynSolType::Uint(8)
+            } else {
+                DynSolType::parse(label)?
+            };
+            ty.coerce_str(key)?.abi_encode()
+        }
+    };
+    preimage.extend_from_slice(&slot.to_be_bytes::<32>());
+    Ok(U256::from_be_bytes(keccak256(preimage).0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping_slot() {
+        // keccak256(abi.encode(address(1), uint256(0)))
+        let slot =
+            mapping_slot("address", "0x0000000000000000000000000000000000000001", U256::ZERO)
+                .unwrap();
+        assert_eq!(
+            slot,
+            U256::from_str("0xada5013122d395ba3c54772283fb069b10426056ef8ca54750cb9bb552a59e7d")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_value() {
+        assert_eq!(decode_value("int8", &[0xff]), "-1");
+        assert_eq!(decode_value("uint16", &[0x01, 0x00]), "256");
+        assert_eq!(decode_value("bool", &[0x01]), "true");
+        assert_eq!(decode_value("bytes2", &[0xab, 0xcd]), "0xabcd");
+    }
+}
@@ -17,7 +17,8 @@ use foundry_evm::{
     opts::EvmOpts,
     traces::{
         identifier::{EtherscanIdentifier, SignaturesIdentifier},
-        render_trace_arena, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind, Traces,
+        internal_functions, render_trace_arena, CallTraceDecoder, CallTraceDecoderBuilder,
+        TraceKind, Traces,
     },
 };
 use std::{fmt::Write, path::PathBuf, str::FromStr};
@@ -376,6 +377,7 @@ pub async fn handle_traces(
     chain: Option<Chain>,
     labels: Vec<String>,
     debug: bool,
+    decode_internal: bool,
 ) -> Result<()> {
     let labels = labels.iter().filter_map(|label_str| {
         let mut iter = label_str.split(':');
@@ -410,6 +412,10 @@ pub async fn handle_traces(
             .build();
         debugger.try_run()?;
     } else {
+        if decode_internal {
+            let sources = etherscan_identifier.get_compiled_contracts().await?;
+            decoder.internal_functions.extend(internal_functions(&sources));
+        }
         print_traces(&mut result, &decoder).await?;
     }
 
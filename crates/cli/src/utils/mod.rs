@@ -530,6 +530,29 @@ https://github.com/foundry-rs/foundry/issues/new/choose"
         self.cmd().arg("tag").get_stdout_lossy()
     }
 
+    /// Returns the absolute paths of the files that changed since `revision`, including the
+    /// uncommitted and untracked ones
+    pub fn changed_files(self, revision: &str) -> Result<Vec<PathBuf>> {
+        let toplevel = Self::root_of(self.root)?;
+        let toplevel = foundry_compilers::utils::canonicalize(&toplevel).unwrap_or(toplevel);
+        let changed = self
+            .cmd()
+            .args(["diff", "--name-only", "--no-renames"])
+            .arg(revision)
+            .arg("--")
+            .get_stdout_lossy()?;
+        let untracked = self
+            .cmd()
+            .args(["ls-files", "--others", "--exclude-standard", "--full-name"])
+            .get_stdout_lossy()?;
+        Ok(changed
+            .lines()
+            .chain(untracked.lines())
+            .filter(|line| !line.is_empty())
+            .map(|path| toplevel.join(path))
+            .collect())
+    }
+
     pub fn has_missing_dependencies<I, S>(self, paths: I) -> Result<bool>
     where
         I: IntoIterator<Item = S>,
@@ -5,14 +5,16 @@ use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Color, Table};
 use eyre::{Context, Result};
 use foundry_block_explorers::contract::Metadata;
 use foundry_compilers::{
-    artifacts::{BytecodeObject, CompactContractBytecode, ContractBytecodeSome},
+    artifacts::{BytecodeObject, CompactContractBytecode, ContractBytecodeSome, Source},
+    cache::SolFilesCache,
     remappings::Remapping,
     report::{BasicStdoutReporter, NoReporter, Report},
     Artifact, ArtifactId, FileFilter, Graph, Project, ProjectCompileOutput, ProjectPathsConfig,
     Solc, SolcConfig,
 };
+use semver::{Version, VersionReq};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{btree_map::Entry, BTreeMap, HashMap},
     convert::Infallible,
     fmt::Display,
     io::IsTerminal,
@@ -37,17 +39,26 @@ pub struct ProjectCompiler {
     /// Whether to also print contract sizes.
     print_sizes: Option<bool>,
 
+    /// Whether to skip checking the initcode size against the EIP-3860 limit.
+    ignore_eip_3860: Option<bool>,
+
+    /// The assumed size of the constructor arguments, added to the initcode size.
+    constructor_args_size: Option<usize>,
+
     /// Whether to print anything at all. Overrides other `print` options.
     quiet: Option<bool>,
 
     /// Whether to bail on compiler errors.
     bail: Option<bool>,
 
-    /// Files to exclude.
+    /// Files to exclude, along with the files that are only imported by them.
     filter: Option<Box<dyn FileFilter>>,
 
     /// Extra files to include, that are not necessarily in the project's source dir.
     files: Vec<PathBuf>,
+
+    /// The solc versions to compile the sources with, see [SolcVersionGroups].
+    solc_versions: Vec<Version>,
 }
 
 impl Default for ProjectCompiler {
@@ -65,10 +76,13 @@ impl ProjectCompiler {
             verify: None,
             print_names: None,
             print_sizes: None,
+            ignore_eip_3860: None,
+            constructor_args_size: None,
             quiet: Some(crate::shell::verbosity().is_silent()),
             bail: None,
             filter: None,
             files: Vec::new(),
+            solc_versions: Vec::new(),
         }
     }
 
@@ -93,6 +107,21 @@ impl ProjectCompiler {
         self
     }
 
+    /// Sets whether to skip checking the initcode size against the EIP-3860 limit when printing
+    /// contract sizes.
+    #[inline]
+    pub fn ignore_eip_3860(mut self, yes: bool) -> Self {
+        self.ignore_eip_3860 = Some(yes);
+        self
+    }
+
+    /// Sets the assumed size of the constructor arguments when printing contract sizes.
+    #[inline]
+    pub fn constructor_args_size(mut self, size: usize) -> Self {
+        self.constructor_args_size = Some(size);
+        self
+    }
+
     /// Sets whether to print anything at all. Overrides other `print` options.
     #[inline]
     #[doc(alias = "silent")]
@@ -118,6 +147,10 @@ impl ProjectCompiler {
     }
 
     /// Sets the filter to use.
+    ///
+    /// Only the input files that match the filter and their imports are compiled, so files that
+    /// are only imported by excluded ones aren't compiled either. The cache entries of the
+    /// excluded files are kept, so a later full compilation reuses their artifacts.
     #[inline]
     pub fn filter(mut self, filter: Box<dyn FileFilter>) -> Self {
         self.filter = Some(filter);
@@ -131,6 +164,17 @@ impl ProjectCompiler {
         self
     }
 
+    /// Sets the solc versions to compile the sources with.
+    ///
+    /// Every file is compiled with the newest of these versions that satisfies its pragma, see
+    /// [SolcVersionGroups]. This is ignored if the project has a fixed solc version, e.g. if
+    /// `--use` was passed.
+    #[inline]
+    pub fn solc_versions(mut self, versions: impl IntoIterator<Item = Version>) -> Self {
+        self.solc_versions.extend(versions);
+        self
+    }
+
     /// Compiles the project.
     pub fn compile(mut self, project: &Project) -> Result<ProjectCompileOutput> {
         // TODO: Avoid process::exit
@@ -143,15 +187,33 @@ impl ProjectCompiler {
         // Taking is fine since we don't need these in `compile_with`.
         let filter = std::mem::take(&mut self.filter);
         let files = std::mem::take(&mut self.files);
+        let versions = std::mem::take(&mut self.solc_versions);
         self.compile_with(|| {
-            if !files.is_empty() {
-                project.compile_files(files)
-            } else if let Some(filter) = filter {
-                project.compile_sparse(filter)
+            // the compiler only keeps the cache entries of the files it compiles
+            let cache = match &filter {
+                Some(_) if project.cached => SolFilesCache::read_joined(&project.paths).ok(),
+                _ => None,
+            };
+
+            let output = if project.auto_detect && !versions.is_empty() && files.is_empty() {
+                let graph = Graph::resolve(&project.paths)?;
+                let mut groups = SolcVersionGroups::resolve(&graph, &versions)?;
+                if let Some(filter) = &filter {
+                    groups.retain(|file| filter.is_match(file));
+                }
+                groups.compile(project)?
+            } else if !files.is_empty() {
+                project.compile_files(files)?
+            } else if let Some(filter) = &filter {
+                project.compile_files(sparse_input_files(project, filter.as_ref()))?
             } else {
-                project.compile()
+                project.compile()?
+            };
+
+            if let Some(cache) = cache {
+                restore_cache_entries(project, cache)?;
             }
-            .map_err(Into::into)
+            Ok(output)
         })
     }
 
@@ -243,24 +305,11 @@ impl ProjectCompiler {
                 println!();
             }
 
-            let mut size_report = SizeReport { contracts: BTreeMap::new() };
-            let artifacts: BTreeMap<_, _> = output.artifacts().collect();
-            for (name, artifact) in artifacts {
-                let size = deployed_contract_size(artifact).unwrap_or_default();
-
-                let dev_functions =
-                    artifact.abi.as_ref().map(|abi| abi.functions()).into_iter().flatten().filter(
-                        |func| {
-                            func.name.is_test() ||
-                                func.name.eq("IS_TEST") ||
-                                func.name.eq("IS_SCRIPT")
-                        },
-                    );
-
-                let is_dev_contract = dev_functions.count() > 0;
-                size_report.contracts.insert(name, ContractInfo { size, is_dev_contract });
-            }
-
+            let size_report = SizeReport::new(
+                output,
+                self.ignore_eip_3860.unwrap_or(false),
+                self.constructor_args_size.unwrap_or(0),
+            );
             println!("{size_report}");
 
             // TODO: avoid process::exit
@@ -272,6 +321,202 @@ impl ProjectCompiler {
     }
 }
 
+/// Returns the input files of the project that match the filter.
+///
+/// Compiling them pulls in their imports, which skips the files that are only reachable from the
+/// excluded ones.
+fn sparse_input_files(project: &Project, filter: &dyn FileFilter) -> Vec<PathBuf> {
+    project.paths.input_files().into_iter().filter(|file| filter.is_match(file)).collect()
+}
+
+/// Restores the entries of the previous cache whose files weren't part of a sparse compilation,
+/// as long as the files and their artifacts still exist.
+fn restore_cache_entries(project: &Project, previous: SolFilesCache) -> Result<()> {
+    let mut cache = SolFilesCache::read_joined(&project.paths)?;
+    let mut restored = false;
+    for (file, entry) in previous.files {
+        if cache.files.contains_key(&file) ||
+            !file.exists() ||
+            !entry.artifacts().all(|artifact| artifact.exists())
+        {
+            continue
+        }
+        cache.files.insert(file, entry);
+        restored = true;
+    }
+
+    if restored {
+        cache.strip_entries_prefix(project.root());
+        cache.strip_artifact_files_prefixes(project.artifacts_path());
+        cache.write(&project.paths.cache)?;
+    }
+    Ok(())
+}
+
+/// The files of a project, grouped by the solc version they're compiled with.
+///
+/// This allows compiling a project with a fixed set of solc versions, e.g. legacy `0.7.6`
+/// contracts alongside `0.8.x` ones, instead of whatever version satisfies each file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolcVersionGroups(pub BTreeMap<Version, Vec<PathBuf>>);
+
+impl SolcVersionGroups {
+    /// Assigns every file of the graph to the newest of the versions that satisfies its pragma
+    /// and the pragmas of all the files it imports, directly or not.
+    ///
+    /// Returns an error listing the conflicting pragmas if a file can't be compiled with any of
+    /// the versions, e.g. because it imports a file that only compiles with another version.
+    pub fn resolve(graph: &Graph, versions: &[Version]) -> Result<Self> {
+        let mut files = graph.files().iter().collect::<Vec<_>>();
+        files.sort();
+
+        // the pragmas parsed by the graph, which skips the ones in comments
+        let mut pragmas = HashMap::new();
+        for (_, index) in files.iter() {
+            let pragma = graph.node(**index).version().as_ref().and_then(|version| {
+                let req = version.data().trim();
+                Some((req.to_string(), parse_solidity_version_req(req)?))
+            });
+            pragmas.insert(**index, pragma);
+        }
+        let satisfies = |index: usize, version: &Version| {
+            pragmas[&index]
+                .as_ref()
+                .map_or(true, |(_, reqs)| reqs.iter().any(|r| r.matches(version)))
+        };
+
+        let mut groups = BTreeMap::<Version, Vec<PathBuf>>::new();
+        let mut conflicts = Vec::new();
+        for (path, index) in files {
+            let imports = graph.all_imported_nodes(*index).collect::<Vec<_>>();
+            let version = versions
+                .iter()
+                .filter(|version| {
+                    satisfies(*index, version) && imports.iter().all(|i| satisfies(*i, version))
+                })
+                .max();
+            if let Some(version) = version {
+                groups.entry(version.clone()).or_default().push(path.clone());
+                continue
+            }
+
+            // report the imports that rule out the versions the file itself is compatible with
+            let own = versions.iter().filter(|v| satisfies(*index, v)).collect::<Vec<_>>();
+            let pragma = |index: usize| {
+                let pragma = pragmas[&index].as_ref().map_or("*", |(req, _)| req.as_str());
+                format!("{} ({pragma})", graph.node(index).path().display())
+            };
+            let mut conflict = format!("{} imports:", pragma(*index));
+            for import in imports {
+                if pragmas[&import].is_some() && !own.iter().any(|v| satisfies(import, v)) {
+                    conflict.push_str(&format!("\n    {}", pragma(import)));
+                }
+            }
+            conflicts.push(conflict);
+        }
+
+        if !conflicts.is_empty() {
+            let versions = versions.iter().map(ToString::to_string).collect::<Vec<_>>();
+            eyre::bail!(
+                "Found files that can't be compiled with any of the configured solc versions ({}):\n  {}",
+                versions.join(", "),
+                conflicts.join("\n  ")
+            )
+        }
+        Ok(Self(groups))
+    }
+
+    /// Retains only the files matching the predicate.
+    pub fn retain(&mut self, mut f: impl FnMut(&Path) -> bool) {
+        self.0.values_mut().for_each(|files| files.retain(|file| f(file)));
+        self.0.retain(|_, files| !files.is_empty());
+    }
+
+    /// Compiles every group with its solc version, installing it unless the project is offline,
+    /// and merges the outputs.
+    ///
+    /// Files that are imported from several groups are compiled with every version, and their
+    /// artifacts are tracked by version in the cache.
+    pub fn compile(&self, project: &Project) -> Result<ProjectCompileOutput> {
+        let mut output = ProjectCompileOutput::default();
+        // every compilation only keeps the cache entries of its group, so they're collected and
+        // the merged cache is written once all the groups are compiled
+        let mut cache: Option<SolFilesCache> = None;
+        for (version, files) in self.0.iter() {
+            let solc = if project.offline {
+                Solc::find_svm_installed_version(version.to_string())?.ok_or_else(|| {
+                    eyre::eyre!("can't install missing solc {version} in offline mode")
+                })?
+            } else {
+                Solc::find_or_install_svm_version(version.to_string())?
+            };
+            let sources = Source::read_all(files.iter().cloned())?;
+            output.merge(project.compile_with_version(&solc, sources)?);
+
+            if project.cached {
+                let group = SolFilesCache::read_joined(&project.paths)?;
+                match &mut cache {
+                    Some(cache) => merge_cache_entries(cache, group),
+                    None => cache = Some(group),
+                }
+            }
+        }
+
+        if let Some(mut cache) = cache {
+            cache.strip_entries_prefix(project.root());
+            cache.strip_artifact_files_prefixes(project.artifacts_path());
+            cache.write(&project.paths.cache)?;
+        }
+        Ok(output)
+    }
+}
+
+/// Merges the cache entries of a compilation into the cache, keeping the artifacts of every solc
+/// version a file was compiled with.
+fn merge_cache_entries(cache: &mut SolFilesCache, other: SolFilesCache) {
+    for (file, entry) in other.files {
+        match cache.files.entry(file) {
+            Entry::Occupied(mut existing) => {
+                let artifacts = &mut existing.get_mut().artifacts;
+                for (name, versions) in entry.artifacts {
+                    artifacts.entry(name).or_default().extend(versions);
+                }
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(entry);
+            }
+        }
+    }
+}
+
+/// Converts the version requirement of a solidity pragma to [VersionReq]s, one per `||`
+/// alternative.
+///
+/// Unlike semver, solidity separates comparators with spaces and treats a bare version as an
+/// exact requirement.
+fn parse_solidity_version_req(req: &str) -> Option<Vec<VersionReq>> {
+    req.split("||")
+        .map(|alternative| {
+            let mut comparators = Vec::new();
+            let mut op = String::new();
+            for token in alternative.split_whitespace() {
+                if token.chars().all(|c| "<>=^~".contains(c)) {
+                    // `>= 0.8.0`
+                    op.push_str(token);
+                    continue
+                }
+                let comparator = std::mem::take(&mut op) + token;
+                if comparator.starts_with(|c: char| c.is_ascii_digit()) {
+                    comparators.push(format!("={comparator}"));
+                } else {
+                    comparators.push(comparator);
+                }
+            }
+            VersionReq::parse(&comparators.join(",")).ok()
+        })
+        .collect()
+}
+
 /// Contract source code and bytecode.
 #[derive(Clone, Debug, Default)]
 pub struct ContractSources {
@@ -279,6 +524,8 @@ pub struct ContractSources {
     pub ids_by_name: HashMap<String, Vec<u32>>,
     /// Map over file_id -> (source code, contract)
     pub sources_by_id: HashMap<u32, (String, ContractBytecodeSome)>,
+    /// Map over file_id -> source file path, relative to the project root
+    pub paths_by_id: HashMap<u32, PathBuf>,
 }
 
 impl ContractSources {
@@ -318,6 +565,7 @@ impl ContractSources {
     ) {
         self.ids_by_name.entry(artifact_id.name.clone()).or_default().push(file_id);
         self.sources_by_id.insert(file_id, (source, bytecode));
+        self.paths_by_id.insert(file_id, artifact_id.source.clone());
     }
 
     /// Returns the source for a contract by file ID.
@@ -346,13 +594,47 @@ impl ContractSources {
 // https://eips.ethereum.org/EIPS/eip-170
 const CONTRACT_SIZE_LIMIT: usize = 24576;
 
+// https://eips.ethereum.org/EIPS/eip-3860
+const CONTRACT_INITCODE_SIZE_LIMIT: usize = 49152;
+
 /// Contracts with info about their size
 pub struct SizeReport {
     /// `contract name -> info`
     pub contracts: BTreeMap<String, ContractInfo>,
+    /// Whether to skip checking the initcode size against the EIP-3860 limit.
+    pub ignore_eip_3860: bool,
 }
 
 impl SizeReport {
+    /// Creates a new size report for the artifacts of the compile output.
+    ///
+    /// `constructor_args_size` is the assumed size of the constructor arguments that are appended
+    /// to the creation bytecode of every contract.
+    pub fn new(
+        output: &ProjectCompileOutput,
+        ignore_eip_3860: bool,
+        constructor_args_size: usize,
+    ) -> Self {
+        let mut contracts = BTreeMap::new();
+        for (name, artifact) in output.artifacts() {
+            let size = deployed_contract_size(artifact).unwrap_or_default();
+            let init_size = contract_initcode_size(artifact)
+                .map(|size| size + constructor_args_size)
+                .unwrap_or_default();
+
+            let dev_functions =
+                artifact.abi.as_ref().map(|abi| abi.functions()).into_iter().flatten().filter(
+                    |func| {
+                        func.name.is_test() || func.name.eq("IS_TEST") || func.name.eq("IS_SCRIPT")
+                    },
+                );
+
+            let is_dev_contract = dev_functions.count() > 0;
+            contracts.insert(name, ContractInfo { size, init_size, is_dev_contract });
+        }
+        Self { contracts, ignore_eip_3860 }
+    }
+
     /// Returns the size of the largest contract, excluding test contracts.
     pub fn max_size(&self) -> usize {
         let mut max_size = 0;
@@ -364,9 +646,44 @@ impl SizeReport {
         max_size
     }
 
-    /// Returns true if any contract exceeds the size limit, excluding test contracts.
+    /// Returns the initcode size of the largest contract, excluding test contracts.
+    pub fn max_init_size(&self) -> usize {
+        self.contracts
+            .values()
+            .filter(|contract| !contract.is_dev_contract)
+            .map(|contract| contract.init_size)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns true if any contract exceeds the size limit or, unless ignored, the initcode size
+    /// limit, excluding test contracts.
     pub fn exceeds_size_limit(&self) -> bool {
-        self.max_size() > CONTRACT_SIZE_LIMIT
+        self.max_size() > CONTRACT_SIZE_LIMIT ||
+            (!self.ignore_eip_3860 && self.max_init_size() > CONTRACT_INITCODE_SIZE_LIMIT)
+    }
+
+    /// Returns the sizes and margins of the contracts, excluding test contracts, as JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        let contracts = self
+            .contracts
+            .iter()
+            .filter(|(_, c)| !c.is_dev_contract && c.size > 0)
+            .map(|(name, contract)| {
+                let value = serde_json::json!({
+                    "runtime_size": contract.size,
+                    "init_size": contract.init_size,
+                    "runtime_margin": CONTRACT_SIZE_LIMIT as isize - contract.size as isize,
+                    "init_margin":
+                        CONTRACT_INITCODE_SIZE_LIMIT as isize - contract.init_size as isize,
+                    "exceeds_size_limit": contract.size > CONTRACT_SIZE_LIMIT,
+                    "exceeds_init_size_limit": !self.ignore_eip_3860 &&
+                        contract.init_size > CONTRACT_INITCODE_SIZE_LIMIT,
+                });
+                (name.clone(), value)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(contracts)
     }
 }
 
@@ -376,23 +693,41 @@ impl Display for SizeReport {
         table.load_preset(ASCII_MARKDOWN);
         table.set_header([
             Cell::new("Contract").add_attribute(Attribute::Bold).fg(Color::Blue),
-            Cell::new("Size (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
-            Cell::new("Margin (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Runtime Size (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Initcode Size (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Runtime Margin (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Initcode Margin (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
         ]);
 
         let contracts = self.contracts.iter().filter(|(_, c)| !c.is_dev_contract && c.size > 0);
         for (name, contract) in contracts {
             let margin = CONTRACT_SIZE_LIMIT as isize - contract.size as isize;
+            let init_margin = CONTRACT_INITCODE_SIZE_LIMIT as isize - contract.init_size as isize;
             let color = match contract.size {
                 0..=17999 => Color::Reset,
                 18000..=CONTRACT_SIZE_LIMIT => Color::Yellow,
                 _ => Color::Red,
             };
+            let init_color = match contract.init_size {
+                _ if self.ignore_eip_3860 => Color::Reset,
+                0..=35999 => Color::Reset,
+                36000..=CONTRACT_INITCODE_SIZE_LIMIT => Color::Yellow,
+                _ => Color::Red,
+            };
+            let name_color = if color == Color::Red || init_color == Color::Red {
+                Color::Red
+            } else if color == Color::Yellow || init_color == Color::Yellow {
+                Color::Yellow
+            } else {
+                Color::Reset
+            };
 
             table.add_row([
-                Cell::new(name).fg(color),
+                Cell::new(name).fg(name_color),
                 Cell::new(contract.size as f64 / 1000.0).fg(color),
+                Cell::new(contract.init_size as f64 / 1000.0).fg(init_color),
                 Cell::new(margin as f64 / 1000.0).fg(color),
+                Cell::new(init_margin as f64 / 1000.0).fg(init_color),
             ]);
         }
 
@@ -420,11 +755,26 @@ pub fn deployed_contract_size<T: Artifact>(artifact: &T) -> Option<usize> {
     Some(size)
 }
 
+/// Returns the size of the contract's creation bytecode, without constructor arguments
+pub fn contract_initcode_size<T: Artifact>(artifact: &T) -> Option<usize> {
+    let bytecode = artifact.get_bytecode_object()?;
+    let size = match bytecode.as_ref() {
+        BytecodeObject::Bytecode(bytes) => bytes.len(),
+        BytecodeObject::Unlinked(unlinked) => {
+            // library placeholders have the same size as an address, see `deployed_contract_size`
+            unlinked.strip_prefix("0x").unwrap_or(unlinked).len() / 2
+        }
+    };
+    Some(size)
+}
+
 /// How big the contract is and whether it is a dev contract where size limits can be neglected
 #[derive(Clone, Copy, Debug)]
 pub struct ContractInfo {
     /// size of the contract in bytes
     pub size: usize,
+    /// size of the contract's initcode in bytes, including the assumed constructor arguments
+    pub init_size: usize,
     /// A development contract is either a Script or a Test contract.
     pub is_dev_contract: bool,
 }
@@ -615,6 +965,21 @@ fn is_match_exclude(matcher: &GlobMatcher, path: &Path) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn can_parse_solidity_version_req() {
+        let matches = |req: &str, version: &str| {
+            let version = Version::parse(version).unwrap();
+            parse_solidity_version_req(req).unwrap().iter().any(|req| req.matches(&version))
+        };
+        assert!(matches("0.7.6", "0.7.6"));
+        assert!(!matches("0.8.0", "0.8.19"));
+        assert!(matches("^0.8.0", "0.8.19"));
+        assert!(matches(">= 0.6.2 <0.9.0", "0.8.19"));
+        assert!(!matches(">=0.6.2 <0.8.0", "0.8.19"));
+        assert!(matches("0.7.6 || ^0.8.0", "0.8.19"));
+        assert!(parse_solidity_version_req("not a version").is_none());
+    }
+
     #[test]
     fn test_build_filter() {
         let tests = SkipBuildFilter::Tests.compile().unwrap();
@@ -12,6 +12,9 @@ pub const SELECTOR_LEN: usize = 4;
 /// Maximum size in bytes (0x6000) that a contract can have.
 pub const CONTRACT_MAX_SIZE: usize = 24576;
 
+/// Maximum size in bytes (0xc000) that the initcode of a contract can have, see EIP-3860.
+pub const CONTRACT_MAX_INITCODE_SIZE: usize = 49152;
+
 /// Default request timeout for http requests
 ///
 /// Note: this is only used so that connections, that are discarded on the server side won't stay
@@ -47,6 +47,14 @@ impl ContractsByArtifact {
         Ok(contracts.first().cloned())
     }
 
+    /// Returns the identifiers of the linkable libraries, recognized by the call protection which
+    /// prefixes their deployed bytecode.
+    pub fn library_identifiers(&self) -> impl Iterator<Item = String> + '_ {
+        self.iter()
+            .filter(|(_, (_, code))| has_call_protection(code))
+            .map(|(id, _)| id.identifier())
+    }
+
     /// Flattens the contracts into functions, events and errors.
     pub fn flatten(&self) -> (BTreeMap<Selector, Function>, BTreeMap<B256, Event>, JsonAbi) {
         let mut funcs = BTreeMap::new();
@@ -135,6 +143,30 @@ unsafe fn count_different_bytes(a: &[u8], b: &[u8]) -> usize {
     sum
 }
 
+/// Returns `true` if the deployed bytecode starts with the `PUSH20 <address> ADDRESS EQ` call
+/// protection that solc prepends to libraries, which reverts calls to their non-view functions that
+/// aren't delegatecalls.
+pub fn has_call_protection(code: &[u8]) -> bool {
+    code.len() > 22 && code[0] == 0x73 && code[21] == 0x30 && code[22] == 0x14
+}
+
+/// Returns the length of the CBOR-encoded metadata that solc appends to the bytecode, including the
+/// two bytes that encode its length, or `0` if the code is too short to contain it.
+pub fn metadata_len(code: &[u8]) -> usize {
+    let [.., a, b] = code else { return 0 };
+    let len = u16::from_be_bytes([*a, *b]) as usize + 2;
+    if len <= code.len() {
+        len
+    } else {
+        0
+    }
+}
+
+/// Strips the CBOR-encoded metadata appended to the bytecode by solc, see [`metadata_len`].
+pub fn strip_bytecode_metadata(code: &[u8]) -> &[u8] {
+    &code[..code.len() - metadata_len(code)]
+}
+
 /// Flattens the contracts into  (`id` -> (`JsonAbi`, `Vec<u8>`)) pairs
 pub fn flatten_contracts(
     contracts: &BTreeMap<ArtifactId, ContractBytecodeSome>,
@@ -194,8 +226,12 @@ pub fn get_artifact_path(paths: &ProjectPathsConfig, path: &str) -> PathBuf {
     } else {
         let parts: Vec<&str> = path.split(':').collect();
         let file = parts[0];
-        let contract_name =
-            if parts.len() == 1 { parts[0].replace(".sol", "") } else { parts[1].to_string() };
+        let contract_name = if parts.len() == 1 {
+            let file = parts[0].replace(".sol", "");
+            file.strip_suffix(".vy").map(str::to_string).unwrap_or(file)
+        } else {
+            parts[1].to_string()
+        };
         paths.artifacts.join(format!("{file}/{contract_name}.json"))
     }
 }
@@ -231,4 +267,26 @@ mod tests {
         let a_99 = &b"a".repeat(99)[..];
         assert!(bytecode_diff_score(a_100, a_99) <= 0.01);
     }
+
+    #[test]
+    fn detects_library_call_protection() {
+        let mut code = vec![0x73];
+        code.extend([0; 20]);
+        code.extend([0x30, 0x14, 0x60, 0x80]);
+        assert!(has_call_protection(&code));
+        assert!(!has_call_protection(&code[..22]));
+        assert!(!has_call_protection(&[0x60, 0x80, 0x60, 0x40, 0x52]));
+    }
+
+    #[test]
+    fn can_strip_bytecode_metadata() {
+        // `a2 64 'ipfs' <34 bytes> 64 'solc' <3 bytes>`, 51 bytes long
+        let metadata = "a264697066735822122044c49ad11d4d07e4e1e5f0e5ac6a3b09ab9b28f7bfed6ceaf26a6d26a40a3da764736f6c63430008130033";
+        let code = hex::decode(format!("6080604052{metadata}")).unwrap();
+        assert_eq!(metadata_len(&code), 53);
+        assert_eq!(strip_bytecode_metadata(&code), hex::decode("6080604052").unwrap());
+
+        assert_eq!(strip_bytecode_metadata(&[0x60]), &[0x60]);
+        assert_eq!(strip_bytecode_metadata(&[0x60, 0x00, 0xff]), &[0x60, 0x00, 0xff]);
+    }
 }
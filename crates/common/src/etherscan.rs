@@ -0,0 +1,178 @@
+//! Etherscan clients shared within the process and throttled per API key.
+
+use crate::shell;
+use foundry_block_explorers::{errors::EtherscanError, Client};
+use foundry_config::{etherscan::ResolvedEtherscanConfig, Chain, Config};
+use once_cell::sync::Lazy;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    future::Future,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The maximum number of times a rate limited request is retried.
+const MAX_RATE_LIMITED_RETRIES: u32 = 5;
+
+/// The clients, by API URL and key.
+static CLIENTS: Lazy<Mutex<HashMap<(String, String), Arc<Client>>>> = Lazy::new(Default::default);
+
+/// The rate limiters, by API key.
+static RATE_LIMITERS: Lazy<Mutex<HashMap<String, Arc<RateLimiter>>>> = Lazy::new(Default::default);
+
+/// An Etherscan client which waits for the rate limit of its API key before each request, and
+/// retries the requests Etherscan rate limits.
+///
+/// The clients with the same API URL and key, and the rate limit of an API key, are shared by the
+/// whole process, so that e.g. a v2 key used for several chains is throttled as a whole.
+#[derive(Clone, Debug)]
+pub struct EtherscanClient {
+    client: Arc<Client>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl EtherscanClient {
+    /// Returns the client of the resolved config, allowing `requests_per_second` requests per
+    /// second with its API key.
+    pub fn new(
+        config: ResolvedEtherscanConfig,
+        requests_per_second: u32,
+    ) -> Result<Self, EtherscanError> {
+        let limiter = rate_limiter(&config.key, requests_per_second);
+        let client =
+            match CLIENTS.lock().unwrap().entry((config.api_url.clone(), config.key.clone())) {
+                Entry::Occupied(entry) => entry.get().clone(),
+                Entry::Vacant(entry) => entry.insert(Arc::new(config.into_client()?)).clone(),
+            };
+        Ok(Self { client, limiter })
+    }
+
+    /// Returns the client of the Etherscan config of `chain`, if any.
+    pub fn from_config(config: &Config, chain: Option<Chain>) -> eyre::Result<Option<Self>> {
+        let Some(etherscan) = config.get_etherscan_config_with_chain(chain)? else {
+            return Ok(None)
+        };
+        Ok(Some(Self::new(etherscan, config.etherscan_requests_per_second)?))
+    }
+
+    /// Wraps a client built elsewhere, e.g. with a custom verifier URL, sharing the rate limit of
+    /// its API key.
+    pub fn with_client(client: Client, api_key: &str, requests_per_second: u32) -> Self {
+        Self { client: Arc::new(client), limiter: rate_limiter(api_key, requests_per_second) }
+    }
+
+    /// Sends a request once the rate limit allows it, retrying it with an exponential backoff if
+    /// Etherscan rate limits it anyway.
+    pub async fn request<'a, T, F, Fut>(&'a self, request: F) -> Result<T, EtherscanError>
+    where
+        F: Fn(&'a Client) -> Fut,
+        Fut: Future<Output = Result<T, EtherscanError>>,
+    {
+        let mut delay = Duration::from_secs(1);
+        let mut retries = 0;
+        loop {
+            self.limiter.acquire().await;
+            match request(&self.client).await {
+                Err(err) if is_rate_limited(&err) && retries < MAX_RATE_LIMITED_RETRIES => {
+                    let _ = shell::eprintln(format!(
+                        "Etherscan: rate limited, retrying in {}s",
+                        delay.as_secs()
+                    ));
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    retries += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+impl Deref for EtherscanClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+/// Returns whether Etherscan rejected a request because of the rate limit of its API key.
+pub fn is_rate_limited(err: &EtherscanError) -> bool {
+    match err {
+        EtherscanError::RateLimitExceeded => true,
+        EtherscanError::Reqwest(err) => {
+            err.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS)
+        }
+        _ => false,
+    }
+}
+
+/// Returns the rate limiter of the API key, created with `requests_per_second` if it's the first
+/// client of the key.
+fn rate_limiter(api_key: &str, requests_per_second: u32) -> Arc<RateLimiter> {
+    RATE_LIMITERS
+        .lock()
+        .unwrap()
+        .entry(api_key.to_string())
+        .or_insert_with(|| Arc::new(RateLimiter::new(requests_per_second)))
+        .clone()
+}
+
+/// A token bucket allowing bursts of up to a second of requests.
+#[derive(Debug)]
+struct RateLimiter {
+    requests_per_second: f64,
+    /// The available tokens, negative if requests are waiting for tokens, and when they were last
+    /// refilled.
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        let requests_per_second = requests_per_second.max(1) as f64;
+        Self { requests_per_second, state: Mutex::new((requests_per_second, Instant::now())) }
+    }
+
+    /// Takes a token, and returns how long to wait for it to be available.
+    fn reserve(&self) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let (tokens, refilled) = &mut *state;
+        let now = Instant::now();
+        let refill = now.duration_since(*refilled).as_secs_f64() * self.requests_per_second;
+        *tokens = (*tokens + refill).min(self.requests_per_second) - 1.;
+        *refilled = now;
+        if *tokens >= 0. {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-*tokens / self.requests_per_second)
+        }
+    }
+
+    /// Waits until a request can be sent.
+    async fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            trace!(target: "etherscan", ?wait, "waiting for the rate limit");
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_allows_bursts() {
+        let limiter = RateLimiter::new(2);
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+
+        // the next requests wait for their own token
+        let wait = limiter.reserve();
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+        let wait = limiter.reserve();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+    }
+}
@@ -108,7 +108,8 @@ pub struct EvmArgs {
     /// - 2: Print logs for all tests
     /// - 3: Print execution traces for failing tests
     /// - 4: Print execution traces for all tests, and setup traces for failing tests
-    /// - 5: Print execution and setup traces for all tests
+    /// - 5: Print execution and setup traces for all tests, with the storage writes, balance
+    ///   changes and undecoded logs of each call
     #[arg(long, short, verbatim_doc_comment, action = ArgAction::Count)]
     #[serde(skip)]
     pub verbosity: u8,
@@ -151,6 +152,14 @@ pub struct EvmArgs {
     #[arg(long)]
     #[serde(skip)]
     pub isolate: bool,
+
+    /// Execute every isolated transaction in a new block, advancing the block timestamp by the
+    /// given number of seconds.
+    ///
+    /// See --isolate.
+    #[arg(long, requires = "isolate", value_name = "SECONDS")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isolate_block_advance: Option<u64>,
 }
 
 // Make this set of options a `figment::Provider` so that it can be merged into the `Config`
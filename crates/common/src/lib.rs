@@ -13,10 +13,12 @@ pub mod compile;
 pub mod constants;
 pub mod contracts;
 pub mod errors;
+pub mod etherscan;
 pub mod evm;
 pub mod fmt;
 pub mod fs;
 pub mod glob;
+pub mod proof;
 pub mod provider;
 pub mod retry;
 pub mod rpc;
@@ -28,6 +30,7 @@ pub mod term;
 pub mod traits;
 pub mod transactions;
 pub mod types;
+pub mod vyper;
 
 pub use constants::*;
 pub use contracts::*;
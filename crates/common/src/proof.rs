@@ -0,0 +1,291 @@
+//! Verification and encoding of EIP-1186 account and storage proofs, as returned by
+//! `eth_getProof`.
+
+use alloy_primitives::{b256, keccak256, Bytes, B256, U256};
+use alloy_rlp::{Decodable, Encodable, Header};
+use alloy_rpc_types::EIP1186AccountProofResponse;
+use alloy_sol_types::SolValue;
+use eyre::{ensure, Result, WrapErr};
+
+/// The root of an empty Merkle Patricia trie, `keccak256(rlp(""))`.
+pub const EMPTY_ROOT_HASH: B256 =
+    b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// The code hash of an account without code, `keccak256("")`.
+pub const KECCAK_EMPTY: B256 =
+    b256!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+
+/// Verifies the account proof against `state_root`, and the storage proofs against the storage
+/// root of the account.
+pub fn verify_account_proof(state_root: B256, proof: &EIP1186AccountProofResponse) -> Result<()> {
+    let address = proof.address;
+    let account = proven_value(state_root, keccak256(address).as_slice(), &proof.account_proof)
+        .wrap_err_with(|| format!("invalid account proof of {address}"))?;
+    match account {
+        Some(account) => ensure!(
+            account == encode_account(proof),
+            "the account proof of {address} doesn't match its nonce, balance, storage hash or code hash"
+        ),
+        None => ensure!(
+            is_empty_account(proof),
+            "the account proof of {address} proves that the account doesn't exist"
+        ),
+    }
+
+    for storage in &proof.storage_proof {
+        let slot = storage.key.0;
+        let value = proven_value(proof.storage_hash, keccak256(slot).as_slice(), &storage.proof)
+            .wrap_err_with(|| format!("invalid storage proof of slot {slot}"))?;
+        let proven = value.map(|mut value| U256::decode(&mut value)).transpose()?;
+        let proven = proven.unwrap_or_default();
+        ensure!(
+            proven == storage.value,
+            "the storage proof of slot {slot} proves {proven}, not {}",
+            storage.value
+        );
+    }
+    Ok(())
+}
+
+/// ABI-encodes the RLP nodes of the proofs as `(bytes[] accountProof, bytes[][] storageProofs)`,
+/// the layout Solidity Merkle Patricia trie verifiers take, e.g.
+/// `abi.decode(data, (bytes[], bytes[][]))`.
+pub fn encode_for_verifier(proof: &EIP1186AccountProofResponse) -> Bytes {
+    let storage_proofs =
+        proof.storage_proof.iter().map(|storage| storage.proof.clone()).collect::<Vec<_>>();
+    (proof.account_proof.clone(), storage_proofs).abi_encode_params().into()
+}
+
+/// Returns the value that `proof` proves at `key` in the trie with `root`, or `None` if it proves
+/// that the trie doesn't have the key.
+pub fn proven_value<'a>(root: B256, key: &[u8], proof: &'a [Bytes]) -> Result<Option<&'a [u8]>> {
+    if proof.is_empty() && (root == EMPTY_ROOT_HASH || root.is_zero()) {
+        return Ok(None)
+    }
+
+    let path = to_nibbles(key);
+    let mut offset = 0;
+    let mut nodes = proof.iter();
+    let mut node = next_node(&mut nodes, root)?;
+    loop {
+        let items = decode_node(node)?;
+        let child = match items.as_slice() {
+            [children @ .., value] if children.len() == 16 => {
+                let Some(&nibble) = path.get(offset) else {
+                    return Ok(Some(decode_string(value)?).filter(|value| !value.is_empty()))
+                };
+                offset += 1;
+                children[nibble as usize]
+            }
+            [encoded_path, item] => {
+                let (node_path, is_leaf) = decode_path(decode_string(encoded_path)?)?;
+                let rest = &path[offset..];
+                if is_leaf {
+                    return (rest == node_path).then(|| decode_string(item)).transpose()
+                }
+                if !rest.starts_with(&node_path) {
+                    return Ok(None)
+                }
+                offset += node_path.len();
+                *item
+            }
+            items => eyre::bail!("invalid trie node with {} items", items.len()),
+        };
+
+        // children shorter than 32 bytes are inlined in their parent
+        let mut buf = child;
+        let header = Header::decode(&mut buf)?;
+        node = match header.payload_length {
+            _ if header.list => child,
+            0 => return Ok(None),
+            32 => next_node(&mut nodes, B256::from_slice(&buf[..32]))?,
+            len => eyre::bail!("invalid trie node reference of {len} bytes"),
+        };
+    }
+}
+
+/// Returns the next node of the proof, which must hash to `hash`.
+fn next_node<'a>(nodes: &mut impl Iterator<Item = &'a Bytes>, hash: B256) -> Result<&'a [u8]> {
+    let node = nodes.next().ok_or_else(|| eyre::eyre!("the proof is missing node {hash}"))?;
+    ensure!(keccak256(node) == hash, "the proof node {node} doesn't hash to {hash}");
+    Ok(node)
+}
+
+/// Splits an RLP-encoded trie node into the RLP encoding of its items.
+fn decode_node(node: &[u8]) -> Result<Vec<&[u8]>> {
+    let mut buf = node;
+    let header = Header::decode(&mut buf)?;
+    ensure!(header.list && buf.len() == header.payload_length, "invalid trie node {node:?}");
+
+    let mut items = vec![];
+    while !buf.is_empty() {
+        let mut item = buf;
+        let header = Header::decode(&mut item)?;
+        let len = buf.len() - item.len() + header.payload_length;
+        ensure!(len <= buf.len(), "invalid trie node {node:?}");
+        let (item, rest) = buf.split_at(len);
+        items.push(item);
+        buf = rest;
+    }
+    Ok(items)
+}
+
+/// Returns the payload of an RLP-encoded string.
+fn decode_string(item: &[u8]) -> Result<&[u8]> {
+    let mut buf = item;
+    let header = Header::decode(&mut buf)?;
+    ensure!(!header.list, "expected a string in trie node, found a list");
+    Ok(&buf[..header.payload_length])
+}
+
+/// Decodes the hex-prefix encoded path of an extension or leaf node, and returns whether the node
+/// is a leaf.
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool)> {
+    let nibbles = to_nibbles(encoded);
+    let Some(&flag) = nibbles.first() else { eyre::bail!("empty trie node path") };
+    ensure!(flag <= 3, "invalid trie node path flag {flag}");
+    // odd paths have their first nibble in the flag byte
+    let skip = if flag & 1 == 1 { 1 } else { 2 };
+    Ok((nibbles[skip..].to_vec(), flag & 2 == 2))
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect()
+}
+
+/// Encodes the account of the proof as it's stored in the state trie,
+/// `rlp([nonce, balance, storageRoot, codeHash])`.
+fn encode_account(proof: &EIP1186AccountProofResponse) -> Vec<u8> {
+    let nonce = proof.nonce.to::<u64>();
+    let payload_length = nonce.length() +
+        proof.balance.length() +
+        proof.storage_hash.length() +
+        proof.code_hash.length();
+    let mut out = vec![];
+    Header { list: true, payload_length }.encode(&mut out);
+    nonce.encode(&mut out);
+    proof.balance.encode(&mut out);
+    proof.storage_hash.encode(&mut out);
+    proof.code_hash.encode(&mut out);
+    out
+}
+
+/// Returns `true` if the account of the proof is empty, as returned for accounts that don't exist.
+fn is_empty_account(proof: &EIP1186AccountProofResponse) -> bool {
+    proof.nonce.is_zero() &&
+        proof.balance.is_zero() &&
+        (proof.code_hash == KECCAK_EMPTY || proof.code_hash.is_zero()) &&
+        (proof.storage_hash == EMPTY_ROOT_HASH || proof.storage_hash.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, U64};
+    use alloy_rpc_types::{EIP1186StorageProof, JsonStorageKey};
+
+    /// RLP-encodes a list of already encoded items.
+    fn list(items: &[&[u8]]) -> Vec<u8> {
+        let payload_length = items.iter().map(|item| item.len()).sum();
+        let mut out = vec![];
+        Header { list: true, payload_length }.encode(&mut out);
+        items.iter().for_each(|item| out.extend_from_slice(item));
+        out
+    }
+
+    fn string(bytes: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        bytes.encode(&mut out);
+        out
+    }
+
+    /// Returns a trie with a branch at the root and a leaf per value, keyed by the hashed slot.
+    fn storage_trie(slots: &[(B256, U256)]) -> (B256, Vec<Vec<Bytes>>) {
+        let mut children = vec![string(&[]); 17];
+        let mut leaves = vec![];
+        for (slot, value) in slots {
+            let path = keccak256(slot);
+            let mut encoded_path = vec![0x30 | (path[0] & 0x0f)];
+            encoded_path.extend_from_slice(&path[1..]);
+            let leaf = list(&[&string(&encoded_path), &string(&alloy_rlp::encode(value))]);
+            children[(path[0] >> 4) as usize] = string(keccak256(&leaf).as_slice());
+            leaves.push(leaf);
+        }
+        let branch = list(&children.iter().map(Vec::as_slice).collect::<Vec<_>>());
+        let root = keccak256(&branch);
+        let proofs = leaves
+            .into_iter()
+            .map(|leaf| vec![Bytes::from(branch.clone()), Bytes::from(leaf)])
+            .collect();
+        (root, proofs)
+    }
+
+    #[test]
+    fn verifies_storage_proofs() {
+        // the hashed slots start with different nibbles
+        let (a, b) = (B256::with_last_byte(0), B256::with_last_byte(1));
+        assert_ne!(keccak256(a)[0] >> 4, keccak256(b)[0] >> 4);
+
+        let (root, proofs) = storage_trie(&[(a, U256::from(42)), (b, U256::from(7))]);
+        let value = proven_value(root, keccak256(a).as_slice(), &proofs[0]).unwrap().unwrap();
+        assert_eq!(U256::decode(&mut &value[..]).unwrap(), U256::from(42));
+
+        // the leaf of `a` proves that a key with another suffix isn't in the trie
+        let mut other = keccak256(a);
+        other[31] ^= 1;
+        assert_eq!(proven_value(root, other.as_slice(), &proofs[0]).unwrap(), None);
+
+        // a tampered node doesn't hash to its reference
+        let mut tampered = proofs[0].clone();
+        let mut leaf = tampered[1].to_vec();
+        *leaf.last_mut().unwrap() = 43;
+        tampered[1] = leaf.into();
+        assert!(proven_value(root, keccak256(a).as_slice(), &tampered).is_err());
+        assert!(proven_value(B256::ZERO, keccak256(a).as_slice(), &proofs[0]).is_err());
+    }
+
+    #[test]
+    fn verifies_account_proofs() {
+        let slot = B256::with_last_byte(2);
+        let (storage_hash, storage_proofs) = storage_trie(&[(slot, U256::from(1))]);
+        let mut proof = EIP1186AccountProofResponse {
+            address: address!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            balance: U256::from(100),
+            code_hash: KECCAK_EMPTY,
+            nonce: U64::from(1),
+            storage_hash,
+            account_proof: vec![],
+            storage_proof: vec![EIP1186StorageProof {
+                key: JsonStorageKey(slot),
+                value: U256::from(1),
+                proof: storage_proofs[0].clone(),
+            }],
+        };
+
+        // a state trie with the account as its single leaf
+        let path = keccak256(proof.address);
+        let mut encoded_path = vec![0x20];
+        encoded_path.extend_from_slice(path.as_slice());
+        let leaf = list(&[&string(&encoded_path), &string(&encode_account(&proof))]);
+        let state_root = keccak256(&leaf);
+        proof.account_proof = vec![leaf.into()];
+        verify_account_proof(state_root, &proof).unwrap();
+
+        let mut wrong_value = proof.clone();
+        wrong_value.storage_proof[0].value = U256::from(2);
+        let err = verify_account_proof(state_root, &wrong_value).unwrap_err();
+        assert!(err.to_string().contains("proves 1, not 2"), "{err}");
+
+        let mut wrong_balance = proof.clone();
+        wrong_balance.balance = U256::from(101);
+        assert!(verify_account_proof(state_root, &wrong_balance).is_err());
+
+        assert!(verify_account_proof(B256::ZERO, &proof).is_err());
+
+        let encoded = encode_for_verifier(&proof);
+        let (account_proof, storage_proofs) =
+            <(Vec<Bytes>, Vec<Vec<Bytes>>)>::abi_decode_params(&encoded, true).unwrap();
+        assert_eq!(account_proof, proof.account_proof);
+        assert_eq!(storage_proofs, vec![proof.storage_proof[0].proof.clone()]);
+    }
+}
@@ -46,6 +46,9 @@ pub fn get_http_provider(builder: impl AsRef<str>) -> RetryProvider {
 
 /// Constructs a provider with a 100 millisecond interval poll if it's a localhost URL (most likely
 /// an anvil or other dev node) and with the default, or 7 second otherwise.
+///
+/// Despite its name, the provider also connects to `ws://`, `wss://` and IPC endpoints, given as
+/// a filesystem path, see [`ProviderBuilder::new`].
 #[inline]
 pub fn try_get_http_provider(builder: impl AsRef<str>) -> Result<RetryProvider> {
     ProviderBuilder::new(builder.as_ref()).build()
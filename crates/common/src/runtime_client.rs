@@ -12,7 +12,16 @@ use reqwest::{
     Url,
 };
 use serde::{de::DeserializeOwned, Serialize};
-use std::{fmt::Debug, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use thiserror::Error;
 use tokio::sync::RwLock;
 
@@ -49,6 +58,10 @@ pub enum RuntimeClientError {
     /// Invalid file path
     #[error("Invalid IPC file path: {0}")]
     BadPath(String),
+
+    /// Subscribed before the client connected
+    #[error("The client is not connected")]
+    NotConnected,
 }
 
 impl RpcError for RuntimeClientError {
@@ -81,6 +94,8 @@ impl From<RuntimeClientError> for ProviderError {
 #[derive(Clone, Debug, Error)]
 pub struct RuntimeClient {
     client: Arc<RwLock<Option<InnerClient>>>,
+    /// The number of connections made, only updated with the lock of the client held
+    connections: Arc<AtomicUsize>,
     url: Url,
     max_retry: u32,
     timeout_retry: u32,
@@ -125,6 +140,11 @@ fn build_auth(jwt: String) -> eyre::Result<Authorization> {
 }
 
 impl RuntimeClient {
+    /// Returns `true` if the client connects over a WebSocket or IPC, which support subscriptions.
+    pub fn is_pubsub(&self) -> bool {
+        matches!(self.url.scheme(), "ws" | "wss" | "file")
+    }
+
     async fn connect(&self) -> Result<InnerClient, RuntimeClientError> {
         match self.url.scheme() {
             "http" | "https" => {
@@ -199,6 +219,49 @@ impl RuntimeClient {
             _ => Err(RuntimeClientError::BadScheme(self.url.to_string())),
         }
     }
+
+    /// Replaces the connection, unless it was already replaced since `connection` connections
+    /// were made.
+    ///
+    /// The requests that lost the same connection at once only reconnect once.
+    async fn reconnect(&self, connection: usize) -> Result<(), RuntimeClientError> {
+        let mut client = self.client.write().await;
+        if self.connections.load(Ordering::SeqCst) == connection {
+            *client = Some(
+                self.connect().await.map_err(|e| RuntimeClientError::ProviderError(e.into()))?,
+            );
+            self.connections.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    /// Sends the request over the current connection, along with the number of connections made
+    /// before it.
+    async fn request_inner<T, R>(
+        &self,
+        method: &str,
+        params: &T,
+    ) -> (usize, Result<R, RuntimeClientError>)
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let client = self.client.read().await;
+        let connection = self.connections.load(Ordering::SeqCst);
+        let res = match client.as_ref() {
+            None => Err(RuntimeClientError::NotConnected),
+            Some(InnerClient::Http(http)) => RetryClient::request(http, method, params)
+                .await
+                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
+            Some(InnerClient::Ws(ws)) => JsonRpcClient::request(ws, method, params)
+                .await
+                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
+            Some(InnerClient::Ipc(ipc)) => JsonRpcClient::request(ipc, method, params)
+                .await
+                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
+        };
+        (connection, res)
+    }
 }
 
 impl RuntimeClientBuilder {
@@ -240,6 +303,7 @@ impl RuntimeClientBuilder {
     pub fn build(self) -> RuntimeClient {
         RuntimeClient {
             client: Arc::new(RwLock::new(None)),
+            connections: Arc::new(AtomicUsize::new(0)),
             url: self.url,
             max_retry: self.max_retry,
             timeout_retry: self.timeout_retry,
@@ -283,27 +347,38 @@ impl JsonRpcClient for RuntimeClient {
         R: DeserializeOwned + Send,
     {
         if self.client.read().await.is_none() {
-            let mut w = self.client.write().await;
-            *w = Some(
-                self.connect().await.map_err(|e| RuntimeClientError::ProviderError(e.into()))?,
-            );
+            self.reconnect(0).await?;
         }
 
-        let res = match self.client.read().await.as_ref().unwrap() {
-            InnerClient::Http(http) => RetryClient::request(http, method, params)
-                .await
-                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
-            InnerClient::Ws(ws) => JsonRpcClient::request(ws, method, params)
-                .await
-                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
-            InnerClient::Ipc(ipc) => JsonRpcClient::request(ipc, method, params)
-                .await
-                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
-        }?;
-        Ok(res)
+        match self.request_inner(method, &params).await {
+            // The WebSocket or IPC connection was lost, e.g. during a long broadcast: reconnect and
+            // retry reads once. The subscriptions of the lost connection end, and must be
+            // resubscribed.
+            (connection, Err(err)) if self.is_pubsub() && is_connection_error(&err) => {
+                warn!(target: "runtime_client", %err, url=%self.url, "reconnecting");
+                self.reconnect(connection).await?;
+                if !is_idempotent(method) {
+                    return Err(err)
+                }
+                self.request_inner(method, &params).await.1
+            }
+            (_, res) => res,
+        }
     }
 }
 
+/// Returns `true` if the request failed because of the transport, rather than with an error
+/// response of the node.
+fn is_connection_error(err: &RuntimeClientError) -> bool {
+    err.as_error_response().is_none() && err.as_serde_error().is_none()
+}
+
+/// Returns `true` if the request can be sent again, i.e. it doesn't send a transaction, which may
+/// have reached the node before the connection was lost.
+fn is_idempotent(method: &str) -> bool {
+    !method.starts_with("eth_send") && !method.starts_with("personal_send")
+}
+
 // We can also implement [`PubsubClient`] for our dynamic provider.
 impl PubsubClient for RuntimeClient {
     // Since both `Ws` and `Ipc`'s `NotificationStream` associated type is the same,
@@ -311,7 +386,13 @@ impl PubsubClient for RuntimeClient {
     type NotificationStream = <Ws as PubsubClient>::NotificationStream;
 
     fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
-        match self.client.try_read().map_err(|_| RuntimeClientError::LockError)?.as_ref().unwrap() {
+        match self
+            .client
+            .try_read()
+            .map_err(|_| RuntimeClientError::LockError)?
+            .as_ref()
+            .ok_or(RuntimeClientError::NotConnected)?
+        {
             InnerClient::Http(_) => {
                 Err(RuntimeClientError::ProviderError(ProviderError::UnsupportedRPC))
             }
@@ -323,7 +404,12 @@ impl PubsubClient for RuntimeClient {
     }
 
     fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
-        match self.client.try_read().map_err(|_| (RuntimeClientError::LockError))?.as_ref().unwrap()
+        match self
+            .client
+            .try_read()
+            .map_err(|_| RuntimeClientError::LockError)?
+            .as_ref()
+            .ok_or(RuntimeClientError::NotConnected)?
         {
             InnerClient::Http(_) => {
                 Err(RuntimeClientError::ProviderError(ProviderError::UnsupportedRPC))
@@ -335,3 +421,94 @@ impl PubsubClient for RuntimeClient {
         }
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixListener,
+    };
+
+    /// Serves IPC connections, dropping the first connection on its first request and answering
+    /// the requests of the next ones with `0x2a`. Returns the methods received by connection.
+    fn serve(listener: UnixListener) -> Arc<Mutex<Vec<(usize, String)>>> {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            let mut connection = 0;
+            while let Ok((mut stream, _)) = listener.accept().await {
+                connection += 1;
+                let requests = requests.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0; 4096];
+                    while let Ok(n @ 1..) = stream.read(&mut buf).await {
+                        let request: Value = serde_json::from_slice(&buf[..n]).unwrap();
+                        let method = request["method"].as_str().unwrap().to_string();
+                        requests.lock().unwrap().push((connection, method));
+                        if connection == 1 {
+                            return
+                        }
+                        let response =
+                            json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x2a"});
+                        stream.write_all(response.to_string().as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+        received
+    }
+
+    fn ipc_client(path: &std::path::Path) -> RuntimeClient {
+        let url = Url::from_file_path(path).unwrap();
+        RuntimeClientBuilder::new(url, 0, 0, 0, Duration::from_secs(5), 0).build()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reconnects_and_retries_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node.ipc");
+        let requests = serve(UnixListener::bind(&path).unwrap());
+        let client = ipc_client(&path);
+
+        let number: U256 = client.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(number, U256::from(42));
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [(1, "eth_blockNumber".to_string()), (2, "eth_blockNumber".to_string())]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reconnects_without_resending_transactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node.ipc");
+        let requests = serve(UnixListener::bind(&path).unwrap());
+        let client = ipc_client(&path);
+
+        let res: Result<U256, _> = client.request("eth_sendRawTransaction", ["0x01"]).await;
+        assert!(res.is_err());
+        let number: U256 = client.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(number, U256::from(42));
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [(1, "eth_sendRawTransaction".to_string()), (2, "eth_blockNumber".to_string())]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reconnects_once_for_concurrent_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node.ipc");
+        let _requests = serve(UnixListener::bind(&path).unwrap());
+        let client = ipc_client(&path);
+        client.reconnect(0).await.unwrap();
+
+        client.reconnect(1).await.unwrap();
+        // the connection was already replaced since
+        client.reconnect(1).await.unwrap();
+        assert_eq!(client.connections.load(Ordering::SeqCst), 2);
+    }
+}
@@ -4,11 +4,11 @@
 
 use crate::abi::abi_decode_calldata;
 use alloy_json_abi::JsonAbi;
+use alloy_primitives::keccak256;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     collections::HashMap,
-    fmt,
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
@@ -18,6 +18,10 @@ use std::{
 
 const SELECTOR_LOOKUP_URL: &str = "https://api.openchain.xyz/signature-database/v1/lookup";
 const SELECTOR_IMPORT_URL: &str = "https://api.openchain.xyz/signature-database/v1/import";
+const FOURBYTE_IMPORT_URL: &str = "https://www.4byte.directory/api/v1/import-abi/";
+
+/// The maximum number of signatures sent in a single lookup or import request
+const MAX_SIGNATURES_PER_REQUEST: usize = 100;
 
 /// The standard request timeout for API requests
 const REQ_TIMEOUT: Duration = Duration::from_secs(15);
@@ -263,58 +267,6 @@ impl SignEthClient {
         self.decode_selector(topic, SelectorType::Event).await
     }
 
-    /// Pretty print calldata and if available, fetch possible function signatures
-    ///
-    /// ```no_run
-    /// use foundry_common::selectors::SignEthClient;
-    ///
-    /// # async fn foo() -> eyre::Result<()> {
-    /// let pretty_data = SignEthClient::new()?
-    ///     .pretty_calldata(
-    ///         "0x70a08231000000000000000000000000d0074f4e6490ae3f888d1d4f7e3e43326bd3f0f5"
-    ///             .to_string(),
-    ///         false,
-    ///     )
-    ///     .await?;
-    /// println!("{}", pretty_data);
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub async fn pretty_calldata(
-        &self,
-        calldata: impl AsRef<str>,
-        offline: bool,
-    ) -> eyre::Result<PossibleSigs> {
-        let mut possible_info = PossibleSigs::new();
-        let calldata = calldata.as_ref().trim_start_matches("0x");
-
-        let selector =
-            calldata.get(..8).ok_or_else(|| eyre::eyre!("calldata cannot be less that 4 bytes"))?;
-
-        let sigs = if offline {
-            vec![]
-        } else {
-            self.decode_function_selector(selector).await.unwrap_or_default().into_iter().collect()
-        };
-        let (_, data) = calldata.split_at(8);
-
-        if data.len() % 64 != 0 {
-            eyre::bail!("\nInvalid calldata size")
-        }
-
-        let row_length = data.len() / 64;
-
-        for row in 0..row_length {
-            possible_info.data.push(data[64 * row..64 * (row + 1)].to_string());
-        }
-        if sigs.is_empty() {
-            possible_info.method = SelectorOrSig::Selector(selector.to_string());
-        } else {
-            possible_info.method = SelectorOrSig::Sig(sigs);
-        }
-        Ok(possible_info)
-    }
-
     /// uploads selectors to https://api.openchain.xyz using the given data
     pub async fn import_selectors(
         &self,
@@ -350,45 +302,78 @@ impl SignEthClient {
 
         Ok(self.post_json(SELECTOR_IMPORT_URL, &request).await?)
     }
-}
 
-pub enum SelectorOrSig {
-    Selector(String),
-    Sig(Vec<String>),
-}
-
-pub struct PossibleSigs {
-    method: SelectorOrSig,
-    data: Vec<String>,
-}
+    /// Uploads the signatures to https://api.openchain.xyz, in batches of at most
+    /// [MAX_SIGNATURES_PER_REQUEST] signatures
+    pub async fn import_signatures(
+        &self,
+        signatures: RawSelectorImportData,
+    ) -> eyre::Result<Vec<SelectorImportResponse>> {
+        let mut responses = Vec::new();
+        for batch in signatures.batches(MAX_SIGNATURES_PER_REQUEST) {
+            responses.push(self.import_selectors(SelectorImportData::Raw(batch)).await?);
+        }
+        Ok(responses)
+    }
 
-impl PossibleSigs {
-    fn new() -> Self {
-        PossibleSigs { method: SelectorOrSig::Selector("0x00000000".to_string()), data: vec![] }
+    /// Splits the signatures into the ones that are already registered in
+    /// https://api.openchain.xyz and the ones that aren't, in that order
+    pub async fn partition_registered(
+        &self,
+        signatures: RawSelectorImportData,
+    ) -> eyre::Result<(RawSelectorImportData, RawSelectorImportData)> {
+        let (known_functions, function) =
+            self.partition_by_lookup(SelectorType::Function, signatures.function).await?;
+        let (known_events, event) =
+            self.partition_by_lookup(SelectorType::Event, signatures.event).await?;
+        // errors are registered as functions
+        let (known_errors, error) =
+            self.partition_by_lookup(SelectorType::Function, signatures.error).await?;
+
+        let known = RawSelectorImportData {
+            function: known_functions,
+            event: known_events,
+            error: known_errors,
+        };
+        Ok((known, RawSelectorImportData { function, event, error }))
     }
-}
 
-impl fmt::Display for PossibleSigs {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.method {
-            SelectorOrSig::Selector(selector) => {
-                writeln!(f, "\n Method: {selector}")?;
-            }
-            SelectorOrSig::Sig(sigs) => {
-                writeln!(f, "\n Possible methods:")?;
-                for sig in sigs {
-                    writeln!(f, " - {sig}")?;
+    async fn partition_by_lookup(
+        &self,
+        selector_type: SelectorType,
+        signatures: Vec<String>,
+    ) -> eyre::Result<(Vec<String>, Vec<String>)> {
+        let (mut known, mut unknown) = (Vec::new(), Vec::new());
+        for batch in signatures.chunks(MAX_SIGNATURES_PER_REQUEST) {
+            let selectors = batch.iter().map(|signature| {
+                let hash = keccak256(signature);
+                match selector_type {
+                    SelectorType::Function => hex::encode_prefixed(&hash[..4]),
+                    SelectorType::Event => hex::encode_prefixed(hash),
+                }
+            });
+            let decoded = self.decode_selectors(selector_type, selectors).await?;
+            for (signature, decoded) in batch.iter().zip(decoded) {
+                // a selector can be registered for a colliding signature
+                if decoded.map_or(false, |decoded| decoded.contains(signature)) {
+                    known.push(signature.clone());
+                } else {
+                    unknown.push(signature.clone());
                 }
             }
         }
+        Ok((known, unknown))
+    }
 
-        writeln!(f, " ------------")?;
-        for (i, row) in self.data.iter().enumerate() {
-            let row_label_decimal = i * 32;
-            let row_label_hex = format!("{row_label_decimal:03x}");
-            writeln!(f, " [{row_label_hex}]: {row}")?;
+    /// Uploads the signatures of the ABI to https://www.4byte.directory
+    pub async fn import_fourbyte(&self, abi: &JsonAbi) -> eyre::Result<FourByteImportResponse> {
+        #[derive(Serialize)]
+        struct ImportRequest {
+            contract_abi: String,
         }
-        Ok(())
+
+        let request = ImportRequest { contract_abi: serde_json::to_string(abi)? };
+        Ok(self.post_json(FOURBYTE_IMPORT_URL, &request).await?)
     }
 }
 
@@ -429,28 +414,6 @@ pub async fn decode_event_topic(topic: &str) -> eyre::Result<Vec<String>> {
     SignEthClient::new()?.decode_event_topic(topic).await
 }
 
-/// Pretty print calldata and if available, fetch possible function signatures
-///
-/// ```no_run
-/// use foundry_common::selectors::pretty_calldata;
-///
-/// # async fn foo() -> eyre::Result<()> {
-/// let pretty_data = pretty_calldata(
-///     "0x70a08231000000000000000000000000d0074f4e6490ae3f888d1d4f7e3e43326bd3f0f5".to_string(),
-///     false,
-/// )
-/// .await?;
-/// println!("{}", pretty_data);
-/// # Ok(())
-/// # }
-/// ```
-pub async fn pretty_calldata(
-    calldata: impl AsRef<str>,
-    offline: bool,
-) -> eyre::Result<PossibleSigs> {
-    SignEthClient::new()?.pretty_calldata(calldata, offline).await
-}
-
 #[derive(Debug, Default, PartialEq, Eq, Serialize)]
 pub struct RawSelectorImportData {
     pub function: Vec<String>,
@@ -462,6 +425,36 @@ impl RawSelectorImportData {
     pub fn is_empty(&self) -> bool {
         self.function.is_empty() && self.event.is_empty() && self.error.is_empty()
     }
+
+    /// Returns the total number of signatures
+    pub fn len(&self) -> usize {
+        self.function.len() + self.event.len() + self.error.len()
+    }
+
+    /// Splits the signatures into batches of at most `size` signatures
+    pub fn batches(self, size: usize) -> Vec<Self> {
+        type Field = fn(&mut RawSelectorImportData) -> &mut Vec<String>;
+        let fields: [(Vec<String>, Field); 3] = [
+            (self.function, |data| &mut data.function),
+            (self.event, |data| &mut data.event),
+            (self.error, |data| &mut data.error),
+        ];
+
+        let mut batches = Vec::new();
+        let mut batch = Self::default();
+        for (signatures, field) in fields {
+            for signature in signatures {
+                if batch.len() == size {
+                    batches.push(std::mem::take(&mut batch));
+                }
+                field(&mut batch).push(signature);
+            }
+        }
+        if !batch.is_empty() {
+            batches.push(batch);
+        }
+        batches
+    }
 }
 
 #[derive(Serialize)]
@@ -495,6 +488,16 @@ pub struct SelectorImportResponse {
 }
 
 impl SelectorImportResponse {
+    /// Returns the number of newly imported signatures
+    pub fn imported(&self) -> usize {
+        self.result.function.imported.len() + self.result.event.imported.len()
+    }
+
+    /// Returns the number of signatures that were already known
+    pub fn duplicated(&self) -> usize {
+        self.result.function.duplicated.len() + self.result.event.duplicated.len()
+    }
+
     /// Print info about the functions which were uploaded or already known
     pub fn describe(&self) {
         self.result
@@ -518,6 +521,15 @@ impl SelectorImportResponse {
     }
 }
 
+/// The result of an ABI import to https://www.4byte.directory
+#[derive(Debug, Default, Deserialize)]
+pub struct FourByteImportResponse {
+    pub num_processed: usize,
+    pub num_imported: usize,
+    pub num_duplicates: usize,
+    pub num_ignored: usize,
+}
+
 /// uploads selectors to https://api.openchain.xyz using the given data
 pub async fn import_selectors(data: SelectorImportData) -> eyre::Result<SelectorImportResponse> {
     SignEthClient::new()?.import_selectors(data).await
@@ -587,6 +599,33 @@ pub fn parse_signatures(tokens: Vec<String>) -> ParsedSignatures {
 mod tests {
     use super::*;
 
+    #[test]
+    fn can_batch_signatures() {
+        let data = RawSelectorImportData {
+            function: vec!["a()".to_string(), "b()".to_string(), "c()".to_string()],
+            event: vec!["E()".to_string()],
+            error: vec!["Err()".to_string()],
+        };
+        assert_eq!(data.len(), 5);
+
+        let batches = data.batches(2);
+        assert_eq!(
+            batches,
+            vec![
+                RawSelectorImportData {
+                    function: vec!["a()".to_string(), "b()".to_string()],
+                    ..Default::default()
+                },
+                RawSelectorImportData {
+                    function: vec!["c()".to_string()],
+                    event: vec!["E()".to_string()],
+                    ..Default::default()
+                },
+                RawSelectorImportData { error: vec!["Err()".to_string()], ..Default::default() },
+            ]
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_decode_selector() {
         let sigs = decode_function_selector("0xa9059cbb").await;
@@ -4,15 +4,16 @@ use alloy_primitives::{Address, Bloom, Bytes, B256, B64, I256, U128, U256, U64};
 use alloy_rpc_types::{
     other::OtherFields,
     request::{TransactionInput, TransactionRequest as CallRequest},
-    AccessList, AccessListItem, Signature, Transaction,
+    AccessList, AccessListItem, EIP1186AccountProofResponse, EIP1186StorageProof, JsonStorageKey,
+    Signature, Transaction,
 };
 use alloy_signer::{LocalWallet, Signer};
 use ethers_core::types::{
     transaction::eip2930::{
         AccessList as EthersAccessList, AccessListItem as EthersAccessListItem,
     },
-    Bloom as EthersBloom, Bytes as EthersBytes, TransactionRequest, H160, H256, H64,
-    I256 as EthersI256, U256 as EthersU256, U64 as EthersU64,
+    Bloom as EthersBloom, Bytes as EthersBytes, EIP1186ProofResponse, StorageProof,
+    TransactionRequest, H160, H256, H64, I256 as EthersI256, U256 as EthersU256, U64 as EthersU64,
 };
 
 /// Conversion trait to easily convert from Ethers types to Alloy types.
@@ -207,6 +208,34 @@ impl ToAlloy for EthersAccessListItem {
     }
 }
 
+impl ToAlloy for EIP1186ProofResponse {
+    type To = EIP1186AccountProofResponse;
+
+    fn to_alloy(self) -> Self::To {
+        EIP1186AccountProofResponse {
+            address: self.address.to_alloy(),
+            balance: self.balance.to_alloy(),
+            code_hash: self.code_hash.to_alloy(),
+            nonce: self.nonce.to_alloy(),
+            storage_hash: self.storage_hash.to_alloy(),
+            account_proof: self.account_proof.into_iter().map(ToAlloy::to_alloy).collect(),
+            storage_proof: self.storage_proof.into_iter().map(ToAlloy::to_alloy).collect(),
+        }
+    }
+}
+
+impl ToAlloy for StorageProof {
+    type To = EIP1186StorageProof;
+
+    fn to_alloy(self) -> Self::To {
+        EIP1186StorageProof {
+            key: JsonStorageKey(self.key.to_alloy()),
+            value: self.value.to_alloy(),
+            proof: self.proof.into_iter().map(ToAlloy::to_alloy).collect(),
+        }
+    }
+}
+
 /// Conversion trait to easily convert from Alloy types to Ethers types.
 pub trait ToEthers {
     /// The corresponding Ethers type.
@@ -0,0 +1,475 @@
+//! Compiling the Vyper sources of a project.
+//!
+//! The `.vy` files of the source, test and script directories are compiled with the configured
+//! vyper binary into artifacts in the same format as the solc ones, so they can be deployed with
+//! `deployCode` and decoded in traces like any other contract.
+
+use alloy_primitives::keccak256;
+use eyre::{Context, Result};
+use foundry_compilers::{
+    info::ContractInfo, ArtifactId, ConfigurableContractArtifact, ProjectPathsConfig,
+};
+use foundry_config::{Config, VyperConfig, VyperOptimizationMode};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The extensions of Vyper sources, `.vyi` files are interfaces which aren't compiled on their own.
+pub const VYPER_EXTENSIONS: &[&str] = &["vy", "vyi"];
+
+/// The name of the cache file of the Vyper sources, next to the solc one.
+pub const VYPER_CACHE_FILE: &str = "vyper-files-cache.json";
+
+/// Returns `true` if the path is a Vyper source or interface.
+pub fn is_vyper_file(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| VYPER_EXTENSIONS.contains(&ext))
+}
+
+/// Returns the Vyper sources and interfaces in the source, test and script directories.
+pub fn vyper_sources(paths: &ProjectPathsConfig) -> Vec<PathBuf> {
+    let mut files = [&paths.sources, &paths.tests, &paths.scripts]
+        .into_iter()
+        .flat_map(|dir| walkdir::WalkDir::new(dir).into_iter().filter_map(|entry| entry.ok()))
+        .filter(|entry| entry.file_type().is_file() && is_vyper_file(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect::<Vec<_>>();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// A vyper compiler binary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vyper {
+    pub path: PathBuf,
+    pub version: Version,
+}
+
+impl Vyper {
+    /// Creates a new instance from the path of a binary, reading its version.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let output = Command::new(&path)
+            .arg("--version")
+            .output()
+            .wrap_err_with(|| format!("failed to run vyper at \"{}\"", path.display()))?;
+        let version = String::from_utf8_lossy(&output.stdout);
+        let version = Version::parse(version.trim())
+            .wrap_err_with(|| format!("unexpected vyper version `{}`", version.trim()))?;
+        Ok(Self { path, version })
+    }
+
+    /// Returns the configured vyper: the binary at `path`, the `version` installed in the Foundry
+    /// directory, which is downloaded if missing, or else the `vyper` binary on `PATH`.
+    pub async fn find_or_install(config: &VyperConfig) -> Result<Self> {
+        if let Some(path) = &config.path {
+            return Self::new(path)
+        }
+        let Some(version) = &config.version else {
+            return Self::new("vyper").wrap_err(
+                "vyper is not installed, set `version` in the `[vyper]` config to install it",
+            )
+        };
+
+        let path = Self::install_path(version)?;
+        if !path.exists() {
+            Self::install(version, &path).await?;
+        }
+        Self::new(path)
+    }
+
+    /// Returns the path a version is installed at.
+    fn install_path(version: &Version) -> Result<PathBuf> {
+        let dir = Config::foundry_dir().ok_or_else(|| eyre::eyre!("no home directory"))?;
+        let bin = if cfg!(windows) { "vyper.exe" } else { "vyper" };
+        Ok(dir.join("vyper").join(version.to_string()).join(bin))
+    }
+
+    /// Downloads the binary of the version from its GitHub release.
+    ///
+    /// The binary is checked against the SHA-256 digest of the release asset and written to a
+    /// temporary file first, so an interrupted download never leaves a broken binary installed.
+    async fn install(version: &Version, path: &Path) -> Result<()> {
+        let platform = if cfg!(target_os = "macos") {
+            ".darwin"
+        } else if cfg!(windows) {
+            ".windows.exe"
+        } else {
+            ".linux"
+        };
+
+        let client = reqwest::Client::builder().user_agent("foundry").build()?;
+        let release = client
+            .get(format!("https://api.github.com/repos/vyperlang/vyper/releases/tags/v{version}"))
+            .send()
+            .await?
+            .error_for_status()
+            .wrap_err_with(|| format!("vyper {version} was not released"))?
+            .text()
+            .await?;
+        let release: GithubRelease = serde_json::from_str(&release)?;
+        let asset = release
+            .assets
+            .into_iter()
+            .find(|asset| asset.name.ends_with(platform))
+            .ok_or_else(|| eyre::eyre!("vyper {version} has no binary for this platform"))?;
+
+        let expected =
+            asset.digest.as_deref().and_then(|digest| digest.strip_prefix("sha256:")).ok_or_else(
+                || {
+                    eyre::eyre!(
+                        "the release of vyper {version} has no checksum for {}, set `path` in the \
+                     `[vyper]` config to use a local binary",
+                        asset.name
+                    )
+                },
+            )?;
+
+        let binary = client
+            .get(&asset.browser_download_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let checksum = hex::encode(Sha256::digest(&binary));
+        if !checksum.eq_ignore_ascii_case(expected) {
+            eyre::bail!(
+                "checksum mismatch for vyper {version}: expected {expected}, got {checksum}"
+            )
+        }
+
+        let dir = path.parent().unwrap();
+        crate::fs::create_dir_all(dir)?;
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        file.write_all(&binary)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.as_file().set_permissions(std::fs::Permissions::from_mode(0o755))?;
+        }
+        file.persist(path)
+            .wrap_err_with(|| format!("failed to install vyper at \"{}\"", path.display()))?;
+        Ok(())
+    }
+
+    /// Compiles a single file, returning the `combined_json` output of the contract.
+    pub fn compile(
+        &self,
+        root: &Path,
+        file: &Path,
+        optimize: Option<VyperOptimizationMode>,
+    ) -> Result<Map<String, Value>> {
+        let mut cmd = Command::new(&self.path);
+        cmd.current_dir(root).arg("-f").arg("combined_json").arg("-p").arg(root);
+        if let Some(optimize) = optimize {
+            cmd.arg("--optimize").arg(optimize.to_string());
+        }
+        let output = cmd.arg(file).output()?;
+        if !output.status.success() {
+            eyre::bail!(
+                "failed to compile \"{}\":\n{}",
+                file.display(),
+                String::from_utf8_lossy(&output.stderr)
+            )
+        }
+
+        let output: Map<String, Value> = serde_json::from_slice(&output.stdout)?;
+        // the output is keyed by the path of the file, besides the compiler version
+        output
+            .into_iter()
+            .find_map(|(_, value)| match value {
+                Value::Object(contract) => Some(contract),
+                _ => None,
+            })
+            .ok_or_else(|| eyre::eyre!("vyper didn't output \"{}\"", file.display()))
+    }
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+    /// The digest of the asset, e.g. `sha256:<hex>`
+    digest: Option<String>,
+}
+
+/// The compiled Vyper contracts of a project.
+#[derive(Clone, Debug)]
+pub struct VyperOutput {
+    pub version: Version,
+    /// The artifacts, with the source and artifact paths relative to the project root.
+    pub artifacts: Vec<(ArtifactId, ConfigurableContractArtifact)>,
+    /// Whether the artifacts were read from the cache.
+    pub unchanged: bool,
+}
+
+impl VyperOutput {
+    /// Returns the artifact of the contract, whose path is either absolute or relative to the
+    /// project root.
+    pub fn find_contract(
+        &self,
+        root: &Path,
+        contract: &ContractInfo,
+    ) -> Option<&ConfigurableContractArtifact> {
+        self.artifacts
+            .iter()
+            .find(|(id, _)| {
+                id.name == contract.name &&
+                    contract
+                        .path
+                        .as_ref()
+                        .map_or(true, |path| relative(root, Path::new(path)) == id.source)
+            })
+            .map(|(_, artifact)| artifact)
+    }
+}
+
+/// The cache of the Vyper sources, which are all recompiled if any of them, the compiler version or
+/// the optimization mode changes.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct VyperCache {
+    version: Option<Version>,
+    optimize: Option<VyperOptimizationMode>,
+    /// The content hashes of the sources, by path relative to the project root
+    files: BTreeMap<PathBuf, String>,
+}
+
+/// Compiles the Vyper sources of the project, if any, writing their artifacts to the output
+/// directory.
+pub async fn compile_vyper(config: &Config, quiet: bool) -> Result<Option<VyperOutput>> {
+    let paths = config.project_paths();
+    let root = &paths.root;
+    let sources = vyper_sources(&paths);
+    if sources.is_empty() {
+        return Ok(None)
+    }
+
+    let vyper = Vyper::find_or_install(&config.vyper).await?;
+    let optimize = config.vyper.optimize;
+    let cache_path = paths.cache.with_file_name(VYPER_CACHE_FILE);
+
+    let mut cache =
+        VyperCache { version: Some(vyper.version.clone()), optimize, ..Default::default() };
+    for file in &sources {
+        let content = crate::fs::read(file)?;
+        cache.files.insert(relative(root, file), hex::encode(keccak256(content)));
+    }
+
+    let contracts = sources
+        .iter()
+        .filter(|file| file.extension().map_or(false, |ext| ext == "vy"))
+        .map(|file| {
+            let name = file.file_stem().unwrap().to_string_lossy().into_owned();
+            let artifact =
+                paths.artifacts.join(file.file_name().unwrap()).join(format!("{name}.json"));
+            (file, name, artifact)
+        })
+        .collect::<Vec<_>>();
+
+    let unchanged = std::fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|cached| serde_json::from_str::<VyperCache>(&cached).ok())
+        .map_or(false, |cached| cached == cache) &&
+        contracts.iter().all(|(_, _, artifact)| artifact.exists());
+
+    if !quiet {
+        if unchanged {
+            println!("No Vyper files changed, compilation skipped");
+        } else {
+            println!("Compiling {} Vyper files with {}", contracts.len(), vyper.version);
+        }
+    }
+
+    let mut artifacts = Vec::with_capacity(contracts.len());
+    for (file, name, artifact_path) in contracts {
+        let artifact = if unchanged {
+            crate::fs::read_json_file(&artifact_path)?
+        } else {
+            let output = vyper.compile(root, file, optimize)?;
+            let artifact = vyper_artifact(&relative(root, file), &name, output);
+            crate::fs::create_dir_all(artifact_path.parent().unwrap())?;
+            crate::fs::write_json_file(&artifact_path, &artifact)?;
+            artifact
+        };
+        let id = ArtifactId {
+            path: relative(root, &artifact_path),
+            name,
+            source: relative(root, file),
+            version: vyper.version.clone(),
+        };
+        artifacts.push((id, serde_json::from_value(artifact)?));
+    }
+
+    if !unchanged {
+        crate::fs::create_dir_all(cache_path.parent().unwrap())?;
+        crate::fs::write_json_file(&cache_path, &cache)?;
+    }
+
+    Ok(Some(VyperOutput { version: vyper.version, artifacts, unchanged }))
+}
+
+/// Returns the standard JSON input of the Vyper sources, as used for verification.
+pub fn standard_json_input(
+    root: &Path,
+    sources: &[PathBuf],
+    optimize: Option<VyperOptimizationMode>,
+) -> Result<Value> {
+    let mut input_sources = Map::new();
+    for file in sources {
+        let content = crate::fs::read_to_string(file)?;
+        input_sources.insert(
+            relative(root, file).to_string_lossy().into_owned(),
+            json!({ "content": content }),
+        );
+    }
+    let mut settings = json!({ "outputSelection": { "*": ["evm.bytecode"] } });
+    if let Some(optimize) = optimize {
+        settings["optimize"] = json!(optimize.to_string());
+    }
+    Ok(json!({ "language": "Vyper", "sources": input_sources, "settings": settings }))
+}
+
+fn relative(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
+
+/// Converts the `combined_json` output of a contract to a solc-like artifact.
+fn vyper_artifact(source: &Path, name: &str, mut output: Map<String, Value>) -> Value {
+    let mut artifact = Map::new();
+    if let Some(Value::Array(abi)) = output.remove("abi") {
+        artifact.insert("abi".to_string(), Value::Array(abi.into_iter().map(abi_item).collect()));
+    }
+    for (key, field) in [("bytecode", "bytecode"), ("bytecode_runtime", "deployedBytecode")] {
+        if let Some(object) = output.remove(key) {
+            artifact.insert(field.to_string(), json!({ "object": object, "linkReferences": {} }));
+        }
+    }
+    // vyper prefixes the selectors with `0x`, unlike solc
+    if let Some(Value::Object(ids)) = output.remove("method_identifiers") {
+        let ids = ids
+            .into_iter()
+            .map(|(signature, selector)| {
+                let selector = selector.as_str().unwrap_or_default().trim_start_matches("0x");
+                (signature, Value::String(selector.to_string()))
+            })
+            .collect();
+        artifact.insert("methodIdentifiers".to_string(), Value::Object(ids));
+    }
+    if let Some(layout) = output.remove("layout") {
+        let contract = format!("{}:{name}", source.display());
+        artifact.insert("storageLayout".to_string(), storage_layout(&contract, &layout));
+    }
+    for (key, field) in [("userdoc", "userdoc"), ("devdoc", "devdoc")] {
+        if let Some(doc) = output.remove(key) {
+            artifact.insert(field.to_string(), doc);
+        }
+    }
+    Value::Object(artifact)
+}
+
+/// Removes the fields of older vyper ABIs that aren't part of the JSON ABI spec, like the gas
+/// estimates and the outputs of constructors.
+fn abi_item(mut item: Value) -> Value {
+    if let Value::Object(item) = &mut item {
+        item.remove("gas");
+        if item.get("type").and_then(Value::as_str) == Some("constructor") {
+            item.remove("name");
+            item.remove("outputs");
+        }
+    }
+    item
+}
+
+/// Converts the vyper storage layout, `{"storage_layout": {"<name>": {"type", "slot"}}}`, to the
+/// solc format.
+///
+/// Vyper doesn't pack variables, every one starts at its own slot.
+fn storage_layout(contract: &str, layout: &Value) -> Value {
+    let mut storage = Vec::new();
+    let mut types = Map::new();
+    let variables = layout.get("storage_layout").and_then(Value::as_object);
+    for (label, variable) in variables.into_iter().flatten() {
+        let (Some(ty), Some(slot)) =
+            (variable.get("type").and_then(Value::as_str), variable.get("slot"))
+        else {
+            continue
+        };
+        let type_id = format!(
+            "t_{}",
+            ty.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect::<String>()
+        );
+        let slot = match slot {
+            Value::String(slot) => slot.clone(),
+            slot => slot.to_string(),
+        };
+        let slots = variable.get("n_slots").and_then(Value::as_u64).unwrap_or(1);
+        storage.push(json!({
+            "astId": 0,
+            "contract": contract,
+            "label": label,
+            "offset": 0,
+            "slot": slot,
+            "type": type_id,
+        }));
+        types.insert(
+            type_id,
+            json!({
+                "encoding": "inplace",
+                "label": ty,
+                "numberOfBytes": (slots * 32).to_string(),
+            }),
+        );
+    }
+    json!({ "storage": storage, "types": types })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_compilers::artifacts::StorageLayout;
+
+    #[test]
+    fn converts_vyper_output() {
+        let output = json!({
+            "abi": [
+                { "type": "constructor", "name": "__init__", "inputs": [{ "name": "start", "type": "uint256" }], "outputs": [], "stateMutability": "nonpayable" },
+                { "type": "function", "name": "number", "inputs": [], "outputs": [{ "name": "", "type": "uint256" }], "stateMutability": "view", "gas": 2400 }
+            ],
+            "bytecode": "0x6001",
+            "bytecode_runtime": "0x6002",
+            "method_identifiers": { "number()": "0x8381f58a" },
+            "layout": { "storage_layout": { "number": { "type": "uint256", "slot": 0 } } }
+        });
+        let Value::Object(output) = output else { unreachable!() };
+
+        let artifact = vyper_artifact(Path::new("src/Counter.vy"), "Counter", output);
+        assert_eq!(artifact["methodIdentifiers"]["number()"], "8381f58a");
+        assert!(artifact["abi"][0].get("outputs").is_none());
+        assert!(artifact["abi"][1].get("gas").is_none());
+
+        let layout: StorageLayout =
+            serde_json::from_value(artifact["storageLayout"].clone()).unwrap();
+        assert_eq!(layout.storage[0].label, "number");
+        assert_eq!(layout.storage[0].slot, "0");
+        assert_eq!(layout.types["t_uint256"].label, "uint256");
+
+        let artifact: ConfigurableContractArtifact = serde_json::from_value(artifact).unwrap();
+        assert!(artifact.abi.unwrap().constructor.is_some());
+    }
+}
@@ -3,7 +3,14 @@
 use crate::Chain;
 use number_prefix::NumberPrefix;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::{fmt, fmt::Formatter, str::FromStr};
+use std::{
+    fmt,
+    fmt::Formatter,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
 
 /// Settings to configure caching of remote
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -181,35 +188,23 @@ pub struct Cache {
 impl fmt::Display for Cache {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         for chain in &self.chains {
-            match NumberPrefix::decimal(
-                chain.block_explorer as f32 + chain.blocks.iter().map(|x| x.1).sum::<u64>() as f32,
-            ) {
-                NumberPrefix::Standalone(size) => {
-                    writeln!(f, "- {} ({size:.1} B)", chain.name)?;
-                }
-                NumberPrefix::Prefixed(prefix, size) => {
-                    writeln!(f, "- {} ({size:.1} {prefix}B)", chain.name)?;
-                }
-            }
-            match NumberPrefix::decimal(chain.block_explorer as f32) {
-                NumberPrefix::Standalone(size) => {
-                    writeln!(f, "\t- Block Explorer ({size:.1} B)\n")?;
-                }
-                NumberPrefix::Prefixed(prefix, size) => {
-                    writeln!(f, "\t- Block Explorer ({size:.1} {prefix}B)\n")?;
-                }
-            }
+            writeln!(
+                f,
+                "- {} ({}, {} blocks)",
+                chain.name,
+                format_size(chain.size()),
+                chain.blocks.len()
+            )?;
+            writeln!(f, "\t- Block Explorer ({})\n", format_size(chain.block_explorer))?;
             for block in &chain.blocks {
-                match NumberPrefix::decimal(block.1 as f32) {
-                    NumberPrefix::Standalone(size) => {
-                        writeln!(f, "\t- Block {} ({size:.1} B)", block.0)?;
-                    }
-                    NumberPrefix::Prefixed(prefix, size) => {
-                        writeln!(f, "\t- Block {} ({size:.1} {prefix}B)", block.0)?;
-                    }
-                }
+                writeln!(f, "\t- Block {} ({})", block.0, format_size(block.1))?;
             }
         }
+        if self.chains.len() > 1 {
+            let size = self.chains.iter().map(ChainCache::size).sum();
+            let blocks = self.chains.iter().map(|chain| chain.blocks.len()).sum::<usize>();
+            writeln!(f, "Total: {} ({blocks} blocks)", format_size(size))?;
+        }
         Ok(())
     }
 }
@@ -227,6 +222,116 @@ pub struct ChainCache {
     pub block_explorer: u64,
 }
 
+impl ChainCache {
+    /// The size of the chain's cached blocks and block explorer data in bytes
+    pub fn size(&self) -> u64 {
+        self.block_explorer + self.blocks.iter().map(|block| block.1).sum::<u64>()
+    }
+}
+
+/// The cached state of a block, `~/.foundry/cache/rpc/<chain>/<block>`
+#[derive(Clone, Debug)]
+pub struct BlockCache {
+    pub chain: Chain,
+    pub block: u64,
+    /// The block directory, or the legacy block file
+    pub path: PathBuf,
+    /// The size of the cached state in bytes
+    pub size: u64,
+    /// When the cached state was last accessed, or modified if access times aren't available
+    pub last_used: SystemTime,
+}
+
+/// Returns the cached blocks in the RPC cache directory, of the `chain` or of all chains.
+pub fn list_block_caches(
+    rpc_cache_dir: &Path,
+    chain: Option<Chain>,
+) -> eyre::Result<Vec<BlockCache>> {
+    if !rpc_cache_dir.exists() {
+        return Ok(vec![])
+    }
+
+    let mut blocks = vec![];
+    for entry in rpc_cache_dir.read_dir()?.flatten().filter(|x| x.path().is_dir()) {
+        let Ok(entry_chain) = Chain::from_str(&entry.file_name().to_string_lossy()) else {
+            continue
+        };
+        if chain.map_or(false, |chain| chain != entry_chain) {
+            continue
+        }
+        for block in entry.path().read_dir()?.flatten() {
+            let Ok(number) = block.file_name().to_string_lossy().parse::<u64>() else { continue };
+            let path = block.path();
+            // the storage file is what's read and written when the block is used, the access
+            // time of its directory doesn't change
+            let storage = if path.is_dir() { path.join("storage.json") } else { path.clone() };
+            // skip blocks whose cache was never written, which only contain the lock file
+            // guarding concurrent access
+            if !storage.is_file() {
+                continue
+            }
+            let metadata = fs::metadata(&storage)?;
+            let modified = metadata.modified()?;
+            blocks.push(BlockCache {
+                chain: entry_chain,
+                block: number,
+                size: dir_size(&path)?,
+                last_used: metadata.accessed().map_or(modified, |accessed| accessed.max(modified)),
+                path,
+            });
+        }
+    }
+    blocks.sort_by_key(|block| (block.chain.id(), block.block));
+    Ok(blocks)
+}
+
+/// Evicts the least recently used of the block caches until their total size is at most
+/// `max_size` bytes.
+///
+/// Returns the evicted block caches, which are only removed if `dry_run` is false.
+pub fn evict_block_caches(
+    mut blocks: Vec<BlockCache>,
+    max_size: u64,
+    dry_run: bool,
+) -> eyre::Result<Vec<BlockCache>> {
+    let mut size = blocks.iter().map(|block| block.size).sum::<u64>();
+    blocks.sort_by_key(|block| block.last_used);
+
+    let mut evicted = vec![];
+    for block in blocks {
+        if size <= max_size {
+            break
+        }
+        if !dry_run {
+            if block.path.is_dir() {
+                fs::remove_dir_all(&block.path)?;
+            } else {
+                fs::remove_file(&block.path)?;
+            }
+        }
+        size -= block.size;
+        evicted.push(block);
+    }
+    Ok(evicted)
+}
+
+/// Returns the size of the file, or of all the files in the directory, in bytes.
+pub fn dir_size(path: &Path) -> eyre::Result<u64> {
+    let metadata = fs::metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len())
+    }
+    fs::read_dir(path)?.try_fold(0, |acc, entry| Ok(acc + dir_size(&entry?.path())?))
+}
+
+/// Formats a size in bytes with a decimal unit prefix, e.g. `4.2 MB`.
+pub fn format_size(size: u64) -> String {
+    match NumberPrefix::decimal(size as f32) {
+        NumberPrefix::Standalone(size) => format!("{size:.1} B"),
+        NumberPrefix::Prefixed(prefix, size) => format!("{size:.1} {prefix}B"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -291,22 +396,60 @@ mod tests {
         };
 
         let expected = "\
-            - mainnet (503.0 B)\n\t\
+            - mainnet (503.0 B, 2 blocks)\n\t\
                 - Block Explorer (500.0 B)\n\n\t\
                 - Block 1 (1.0 B)\n\t\
                 - Block 2 (2.0 B)\n\
-            - ropsten (4.6 kB)\n\t\
+            - ropsten (4.6 kB, 2 blocks)\n\t\
                 - Block Explorer (4.6 kB)\n\n\t\
                 - Block 1 (1.0 B)\n\t\
                 - Block 2 (2.0 B)\n\
-            - rinkeby (6.2 MB)\n\t\
+            - rinkeby (6.2 MB, 2 blocks)\n\t\
                 - Block Explorer (4.2 MB)\n\n\t\
                 - Block 1 (1.0 kB)\n\t\
                 - Block 2 (2.0 MB)\n\
-            - mumbai (3.0 B)\n\t\
+            - mumbai (3.0 B, 2 blocks)\n\t\
                 - Block Explorer (0.0 B)\n\n\t\
                 - Block 1 (1.0 B)\n\t\
-                - Block 2 (2.0 B)\n";
+                - Block 2 (2.0 B)\n\
+            Total: 6.2 MB (8 blocks)\n";
         assert_str_eq!(format!("{cache}"), expected);
     }
+
+    #[test]
+    fn evicts_least_recently_used_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let block = |number: u64, size: usize, last_used: u64| {
+            let path = dir.path().join("mainnet").join(number.to_string());
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("storage.json"), vec![b'0'; size]).unwrap();
+            let time = now - std::time::Duration::from_secs(last_used);
+            let times = fs::FileTimes::new().set_accessed(time).set_modified(time);
+            fs::File::options()
+                .write(true)
+                .open(path.join("storage.json"))
+                .unwrap()
+                .set_times(times)
+                .unwrap();
+            path
+        };
+        let oldest = block(1, 100, 300);
+        let newest = block(2, 100, 100);
+        let middle = block(3, 100, 200);
+        // only contains the lock file
+        fs::create_dir_all(dir.path().join("mainnet").join("4")).unwrap();
+
+        let blocks = list_block_caches(dir.path(), None).unwrap();
+        assert_eq!(blocks.iter().map(|block| block.block).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(list_block_caches(dir.path(), Some(Chain::optimism_mainnet())).unwrap().is_empty());
+
+        let evicted = evict_block_caches(blocks.clone(), 150, true).unwrap();
+        assert_eq!(evicted.iter().map(|block| block.block).collect::<Vec<_>>(), vec![1, 3]);
+        assert!(oldest.exists() && middle.exists());
+
+        let evicted = evict_block_caches(blocks, 250, false).unwrap();
+        assert_eq!(evicted.iter().map(|block| block.block).collect::<Vec<_>>(), vec![1]);
+        assert!(!oldest.exists() && middle.exists() && newest.exists());
+    }
 }
@@ -0,0 +1,60 @@
+//! Per-chain overrides of the profile settings
+
+use crate::{Chain, GasLimit};
+use foundry_compilers::EvmVersion;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Settings that override the ones of the profile when the chain is known, e.g. from the fork or
+/// `--chain`, keyed by chain name or EIP-155 chain ID:
+///
+/// ```toml
+/// [profile.default.chains.mainnet]
+/// evm_version = "shanghai"
+///
+/// [profile.default.chains.42161]
+/// gas_multiplier = 200
+/// verifier = "blockscout"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ChainsConfig(pub BTreeMap<String, ChainConfig>);
+
+impl ChainsConfig {
+    /// Returns the overrides of the given chain, if any.
+    pub fn get(&self, chain: impl Into<Chain>) -> Option<&ChainConfig> {
+        let chain = chain.into();
+        self.0.iter().find(|(key, _)| key.parse::<Chain>().ok() == Some(chain)).map(|(_, c)| c)
+    }
+
+    /// Returns `true` if no chain has overrides.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// The settings of a single chain, unset values fall back to the profile.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// The EVM version to compile and execute with, e.g. for chains that don't support `PUSH0`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evm_version: Option<EvmVersion>,
+    /// The block gas limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<GasLimit>,
+    /// The RPC endpoint of the chain, used when no other RPC URL is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eth_rpc_url: Option<String>,
+    /// Whether to send legacy transactions instead of EIP-1559 ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legacy: Option<bool>,
+    /// The percentage `forge script` multiplies the estimated gas of the transactions by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_multiplier: Option<u64>,
+    /// The contract verification provider, e.g. `etherscan`, `sourcify` or `blockscout`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<String>,
+    /// The URL of the verification provider's API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier_url: Option<String>,
+}
@@ -21,6 +21,21 @@ use std::{
 /// The user agent to use when querying the etherscan API.
 pub const ETHERSCAN_USER_AGENT: &str = concat!("foundry/", env!("CARGO_PKG_VERSION"));
 
+/// The URL of the Etherscan v2 API, which serves all chains with the chain passed as the `chainid`
+/// query parameter.
+pub const ETHERSCAN_V2_API_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// The version of the Etherscan API the `etherscan_api_key` is used with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EtherscanApiVersion {
+    /// A key per chain explorer, with the API URL of the explorer of the chain.
+    #[default]
+    V1,
+    /// A single key for all chains, with the chain passed as a query parameter.
+    V2,
+}
+
 /// A [Provider] that provides Etherscan API key from the environment if it's not empty.
 ///
 /// This prevents `ETHERSCAN_API_KEY=""` if it's set but empty
@@ -277,6 +292,17 @@ impl ResolvedEtherscanConfig {
         })
     }
 
+    /// Creates a new instance for the Etherscan v2 API, which uses the same API key for all chains
+    pub fn create_v2(api_key: impl Into<String>, chain: impl Into<Chain>) -> Self {
+        let chain = chain.into();
+        Self {
+            api_url: format!("{ETHERSCAN_V2_API_URL}?chainid={}", chain.id()),
+            browser_url: chain.etherscan_urls().map(|(_, url)| url.to_string()),
+            key: api_key.into(),
+            chain: Some(chain),
+        }
+    }
+
     /// Sets the chain value and consumes the type
     ///
     /// This is only used to set derive the appropriate Cache path for the etherscan client
@@ -1,6 +1,7 @@
 //! Configuration specific to the `forge fmt` command and the `forge_fmt` package
 
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 /// Contains the config and rule set
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -32,7 +33,93 @@ pub struct FormatterConfig {
     /// Add new line at start and end of contract declarations
     pub contract_new_lines: bool,
     /// Sort import statements alphabetically in groups (a group is separated by a newline).
+    ///
+    /// Within a group, `forge-std` imports come first, followed by dependencies and then project
+    /// files. Named imports are sorted by symbol.
     pub sort_imports: bool,
+    /// Write functions with a single statement body on a single line if they fit in
+    /// `line_length`
+    pub allow_single_line_functions: bool,
+    /// Options that apply to the files matching the override paths
+    pub overrides: Vec<FormatterOverride>,
+}
+
+impl FormatterConfig {
+    /// Returns the config for the file at the given path, with the options of all matching
+    /// [FormatterOverride]s applied in order.
+    ///
+    /// Override paths are globs relative to the project root.
+    pub fn for_path(&self, path: &Path, root: &Path) -> FormatterConfig {
+        let path = path.strip_prefix(root).unwrap_or(path);
+        let mut config = self.clone();
+        for item in self.overrides.iter().filter(|item| item.matches(path)) {
+            item.apply(&mut config);
+        }
+        config
+    }
+}
+
+/// Defines [FormatterOverride] with an optional field for each overridable [FormatterConfig]
+/// option.
+macro_rules! formatter_override {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        /// Formatter options that apply to the files matching the path globs, e.g.
+        ///
+        /// ```toml
+        /// [[fmt.overrides]]
+        /// paths = ["test/**"]
+        /// line_length = 160
+        /// ```
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+        pub struct FormatterOverride {
+            /// Globs of the paths the options apply to, relative to the project root
+            pub paths: Vec<String>,
+            $(
+                #[doc = concat!("Overrides [FormatterConfig::", stringify!($field), "]")]
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub $field: Option<$ty>,
+            )*
+        }
+
+        impl FormatterOverride {
+            /// Applies the overridden options to the config.
+            fn apply(&self, config: &mut FormatterConfig) {
+                $(
+                    if let Some(value) = &self.$field {
+                        config.$field = value.clone();
+                    }
+                )*
+            }
+        }
+    };
+}
+
+formatter_override! {
+    line_length: usize,
+    tab_width: usize,
+    bracket_spacing: bool,
+    int_types: IntTypes,
+    multiline_func_header: MultilineFuncHeaderStyle,
+    quote_style: QuoteStyle,
+    number_underscore: NumberUnderscore,
+    hex_underscore: HexUnderscore,
+    single_line_statement_blocks: SingleLineBlockStyle,
+    override_spacing: bool,
+    wrap_comments: bool,
+    contract_new_lines: bool,
+    sort_imports: bool,
+    allow_single_line_functions: bool,
+}
+
+impl FormatterOverride {
+    /// Returns `true` if the path, relative to the project root, matches any of the globs.
+    ///
+    /// Invalid globs never match.
+    pub fn matches(&self, path: &Path) -> bool {
+        self.paths.iter().any(|glob| {
+            globset::Glob::new(glob).map_or(false, |glob| glob.compile_matcher().is_match(path))
+        })
+    }
 }
 
 /// Style of uint/int256 types
@@ -179,6 +266,8 @@ impl Default for FormatterConfig {
             ignore: vec![],
             contract_new_lines: false,
             sort_imports: false,
+            allow_single_line_functions: false,
+            overrides: vec![],
         }
     }
 }
@@ -0,0 +1,206 @@
+//! Configuration specific to the `forge geiger` command
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, path::Path};
+
+/// Contains the cheatcode policies, e.g.
+///
+/// ```toml
+/// [geiger.cheatcodes]
+/// state = "warn"
+/// etch = "error"
+///
+/// [[geiger.overrides]]
+/// paths = ["test/**"]
+/// cheatcodes = { state = "allow", etch = "allow" }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeigerConfig {
+    /// Severities keyed by cheatcode name or [CheatcodeCategory].
+    ///
+    /// A cheatcode name takes precedence over its category. Cheatcodes without a policy are
+    /// allowed.
+    pub cheatcodes: BTreeMap<String, GeigerSeverity>,
+    /// Policies that apply to the files matching the override paths
+    pub overrides: Vec<GeigerOverride>,
+}
+
+impl GeigerConfig {
+    /// Returns the severity of the cheatcode when it's used in the file at the given path,
+    /// relative to the project root.
+    ///
+    /// Matching [GeigerOverride]s take precedence over the base policies, later ones over earlier
+    /// ones.
+    pub fn severity(&self, cheatcode: &str, path: &Path) -> GeigerSeverity {
+        let category = CheatcodeCategory::of(cheatcode).map(|category| category.to_string());
+        let lookup = |policies: &BTreeMap<String, GeigerSeverity>| {
+            policies
+                .get(cheatcode)
+                .or_else(|| category.as_ref().and_then(|category| policies.get(category)))
+                .copied()
+        };
+
+        self.overrides
+            .iter()
+            .rev()
+            .filter(|item| item.matches(path))
+            .find_map(|item| lookup(&item.cheatcodes))
+            .or_else(|| lookup(&self.cheatcodes))
+            .unwrap_or_default()
+    }
+}
+
+impl Default for GeigerConfig {
+    fn default() -> Self {
+        Self {
+            cheatcodes: BTreeMap::from([
+                (CheatcodeCategory::Ffi.to_string(), GeigerSeverity::Error),
+                (CheatcodeCategory::Fs.to_string(), GeigerSeverity::Warn),
+                (CheatcodeCategory::Env.to_string(), GeigerSeverity::Warn),
+                ("deriveKey".to_string(), GeigerSeverity::Warn),
+            ]),
+            overrides: vec![],
+        }
+    }
+}
+
+/// Cheatcode policies that apply to the files matching the path globs
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeigerOverride {
+    /// Globs of the paths the policies apply to, relative to the project root
+    pub paths: Vec<String>,
+    /// Overrides [GeigerConfig::cheatcodes]
+    #[serde(default)]
+    pub cheatcodes: BTreeMap<String, GeigerSeverity>,
+}
+
+impl GeigerOverride {
+    /// Returns `true` if the path, relative to the project root, matches any of the globs.
+    ///
+    /// Invalid globs never match.
+    pub fn matches(&self, path: &Path) -> bool {
+        self.paths.iter().any(|glob| {
+            globset::Glob::new(glob).map_or(false, |glob| glob.compile_matcher().is_match(path))
+        })
+    }
+}
+
+/// How a cheatcode usage is reported
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum GeigerSeverity {
+    /// Not reported
+    #[default]
+    Allow,
+    /// Reported as a warning
+    Warn,
+    /// Reported as an error
+    Error,
+}
+
+impl fmt::Display for GeigerSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeigerSeverity::Allow => f.write_str("allow"),
+            GeigerSeverity::Warn => f.write_str("warn"),
+            GeigerSeverity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// Groups of cheatcodes that can be configured together
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CheatcodeCategory {
+    /// Cheatcodes that access the file system
+    Fs,
+    /// Cheatcodes that read or write environment variables
+    Env,
+    /// Cheatcodes that execute arbitrary commands
+    Ffi,
+    /// Cheatcodes that modify the state of the EVM
+    State,
+}
+
+impl CheatcodeCategory {
+    /// Returns the category of the cheatcode, if any.
+    pub fn of(cheatcode: &str) -> Option<Self> {
+        let category = match cheatcode {
+            "ffi" | "tryFfi" => Self::Ffi,
+            "readFile" | "readFileBinary" | "readLine" | "writeFile" | "writeFileBinary" |
+            "writeLine" | "closeFile" | "removeFile" | "copyFile" | "createDir" | "removeDir" |
+            "readDir" | "readLink" | "fsMetadata" | "isFile" | "isDir" | "exists" |
+            "writeJson" | "writeToml" => Self::Fs,
+            "setEnv" => Self::Env,
+            name if name.starts_with("env") => Self::Env,
+            "etch" | "store" | "deal" | "prank" | "startPrank" | "stopPrank" | "roll" |
+            "warp" | "fee" | "chainId" | "coinbase" | "difficulty" | "prevrandao" |
+            "blobBaseFee" | "txGasPrice" | "setNonce" | "setNonceUnsafe" | "resetNonce" |
+            "mockCall" | "mockCallRevert" | "clearMockedCalls" | "snapshot" | "revertTo" |
+            "loadAllocs" | "makePersistent" | "allowCheatcodes" => Self::State,
+            _ => return None,
+        };
+        Some(category)
+    }
+}
+
+impl fmt::Display for CheatcodeCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheatcodeCategory::Fs => f.write_str("fs"),
+            CheatcodeCategory::Env => f.write_str("env"),
+            CheatcodeCategory::Ffi => f.write_str("ffi"),
+            CheatcodeCategory::State => f.write_str("state"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_severity() {
+        let config = GeigerConfig {
+            cheatcodes: BTreeMap::from([
+                ("state".to_string(), GeigerSeverity::Warn),
+                ("etch".to_string(), GeigerSeverity::Error),
+            ]),
+            overrides: vec![GeigerOverride {
+                paths: vec!["test/**".to_string()],
+                cheatcodes: BTreeMap::from([("state".to_string(), GeigerSeverity::Allow)]),
+            }],
+        };
+
+        assert_eq!(
+            config.severity("etch", Path::new("script/Deploy.s.sol")),
+            GeigerSeverity::Error
+        );
+        assert_eq!(
+            config.severity("store", Path::new("script/Deploy.s.sol")),
+            GeigerSeverity::Warn
+        );
+        assert_eq!(config.severity("ffi", Path::new("script/Deploy.s.sol")), GeigerSeverity::Allow);
+        // overrides take precedence over the base policies
+        assert_eq!(config.severity("etch", Path::new("test/Vault.t.sol")), GeigerSeverity::Allow);
+    }
+
+    #[test]
+    fn deserializes_partial_config() {
+        let config: GeigerConfig = toml::from_str(
+            r#"
+            [[overrides]]
+            paths = ["test/**"]
+            cheatcodes = { ffi = "allow" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.cheatcodes, GeigerConfig::default().cheatcodes);
+        assert_eq!(config.severity("ffi", Path::new("test/Vault.t.sol")), GeigerSeverity::Allow);
+
+        let config: GeigerConfig = toml::from_str("[cheatcodes]\netch = \"error\"").unwrap();
+        assert!(config.overrides.is_empty());
+    }
+}
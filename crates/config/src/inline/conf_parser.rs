@@ -1,6 +1,13 @@
 use super::{remove_whitespaces, InlineConfigParserError};
-use crate::{inline::INLINE_CONFIG_PREFIX, InlineConfigError, NatSpec};
+use crate::{
+    inline::{
+        INLINE_CONFIG_EVM_VERSION_KEY, INLINE_CONFIG_PREFIX, INLINE_CONFIG_PREFIX_SELECTED_PROFILE,
+    },
+    InlineConfigError, NatSpec,
+};
+use foundry_compilers::EvmVersion;
 use regex::Regex;
+use std::collections::BTreeSet;
 
 /// This trait is intended to parse configurations from
 /// structured text. Foundry users can annotate Solidity test functions,
@@ -131,15 +138,67 @@ pub fn parse_config_u32(key: String, value: String) -> Result<u32, InlineConfigP
     value.parse().map_err(|_| InlineConfigParserError::ParseInt(key, value))
 }
 
+/// Tries to parse a `u64` from `value`. The `key` argument is used to give details
+/// in the case of an error.
+pub fn parse_config_u64(key: String, value: String) -> Result<u64, InlineConfigParserError> {
+    value.parse().map_err(|_| InlineConfigParserError::ParseInt(key, value))
+}
+
 /// Tries to parse a `bool` from `value`. The `key` argument is used to give details
 /// in the case of an error.
 pub fn parse_config_bool(key: String, value: String) -> Result<bool, InlineConfigParserError> {
     value.parse().map_err(|_| InlineConfigParserError::ParseBool(key, value))
 }
 
+/// Returns the EVM version set by the configuration lines, e.g.
+/// `forge-config: default.evm_version = "paris"`, if any.
+///
+/// The EVM version also selects the hardfork the test runs with.
+pub fn parse_config_evm_version(
+    config_lines: &[String],
+) -> Result<Option<EvmVersion>, InlineConfigParserError> {
+    let re =
+        Regex::new(&format!("^{INLINE_CONFIG_PREFIX}:[^.]+\\.{INLINE_CONFIG_EVM_VERSION_KEY}="))
+            .unwrap();
+    let mut evm_version = None;
+    for line in config_lines.iter().map(|l| remove_whitespaces(l)) {
+        let Some(key) = re.find(&line) else { continue };
+        let value = line[key.end()..].trim_matches('"');
+        let parsed = value.parse().map_err(|_| {
+            InlineConfigParserError::ParseEvmVersion(
+                INLINE_CONFIG_EVM_VERSION_KEY.into(),
+                value.into(),
+            )
+        })?;
+        evm_version = Some(parsed);
+    }
+    Ok(evm_version)
+}
+
+/// Returns the EVM versions set for the current profile by the inline configurations of a
+/// Solidity source, without compiling it.
+///
+/// This finds the sources which can't be compiled for the EVM version of the profile, invalid
+/// configurations are left to be reported from the compiled natspecs.
+pub fn source_evm_versions(source: &str) -> BTreeSet<EvmVersion> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let line = remove_whitespaces(line);
+            let config = &line[line.find(INLINE_CONFIG_PREFIX)?..];
+            if !config.starts_with(INLINE_CONFIG_PREFIX_SELECTED_PROFILE.as_str()) {
+                return None
+            }
+            parse_config_evm_version(&[config.to_string()]).ok().flatten()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{parse_config_evm_version, source_evm_versions};
     use crate::{inline::conf_parser::validate_profiles, NatSpec};
+    use foundry_compilers::EvmVersion;
 
     #[test]
     fn can_reject_invalid_profiles() {
@@ -176,4 +235,40 @@ mod tests {
         let result = validate_profiles(&natspec, &profiles);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn can_parse_evm_version() {
+        let lines = [
+            "forge-config: default.fuzz.runs = 100".to_string(),
+            "forge-config: default.evm_version = \"paris\"".to_string(),
+        ];
+        assert_eq!(parse_config_evm_version(&lines), Ok(Some(EvmVersion::Paris)));
+        assert_eq!(parse_config_evm_version(&lines[..1]), Ok(None));
+
+        let lines = ["forge-config: ci.evm_version = shanghai".to_string()];
+        assert_eq!(parse_config_evm_version(&lines), Ok(Some(EvmVersion::Shanghai)));
+
+        let lines = ["forge-config: default.evm_version = \"frontier\"".to_string()];
+        assert!(parse_config_evm_version(&lines).is_err());
+    }
+
+    #[test]
+    fn can_find_source_evm_versions() {
+        let source = r#"
+/// forge-config: default.evm_version = "cancun"
+contract TransientTest {
+    /// forge-config: default.evm_version = "shanghai"
+    /// forge-config: ci.evm_version = "paris"
+    function test() public {}
+
+    /// forge-config: default.evm_version = "frontier"
+    function testInvalid() public {}
+}
+"#;
+        assert_eq!(
+            source_evm_versions(source).into_iter().collect::<Vec<_>>(),
+            [EvmVersion::Shanghai, EvmVersion::Cancun]
+        );
+        assert!(source_evm_versions("contract Test {}").is_empty());
+    }
 }
@@ -14,6 +14,15 @@ pub enum InlineConfigParserError {
     /// An error occurred while trying to parse a boolean configuration value
     #[error("Invalid config value for key '{0}'. Unable to parse '{1}' into a boolean value")]
     ParseBool(String, String),
+    /// An error occurred while trying to parse an EVM version configuration value
+    #[error("Invalid config value for key '{0}'. Unable to parse '{1}' into an EVM version")]
+    ParseEvmVersion(String, String),
+    /// The tests of a contract are configured to run with different EVM versions
+    #[error(
+        "The tests of a contract share an EVM, but they're configured with different EVM versions: {0}. \
+         Set `evm_version` on the contract instead"
+    )]
+    MixedEvmVersions(String),
 }
 
 /// Wrapper error struct that catches config parsing
@@ -1,5 +1,8 @@
 use crate::Config;
-pub use conf_parser::{parse_config_bool, parse_config_u32, validate_profiles, InlineConfigParser};
+pub use conf_parser::{
+    parse_config_bool, parse_config_evm_version, parse_config_u32, parse_config_u64,
+    source_evm_versions, validate_profiles, InlineConfigParser,
+};
 pub use error::{InlineConfigError, InlineConfigParserError};
 pub use natspec::NatSpec;
 use once_cell::sync::Lazy;
@@ -11,6 +14,7 @@ mod natspec;
 
 pub const INLINE_CONFIG_FUZZ_KEY: &str = "fuzz";
 pub const INLINE_CONFIG_INVARIANT_KEY: &str = "invariant";
+pub const INLINE_CONFIG_EVM_VERSION_KEY: &str = "evm_version";
 const INLINE_CONFIG_PREFIX: &str = "forge-config";
 
 static INLINE_CONFIG_PREFIX_SELECTED_PROFILE: Lazy<String> = Lazy::new(|| {
@@ -30,18 +34,22 @@ pub struct InlineConfig<T> {
 
 impl<T> InlineConfig<T> {
     /// Returns an inline configuration, if any, for a test function.
-    /// Configuration is identified by the pair "contract", "function".
+    /// Configuration is identified by the pair "contract", "function", and falls back to the
+    /// configuration of the contract, inserted with an empty function name.
     pub fn get<C, F>(&self, contract_id: C, fn_name: F) -> Option<&T>
     where
         C: Into<String>,
         F: Into<String>,
     {
         // TODO use borrow
-        let key = InlineConfigKey {
+        let mut key = InlineConfigKey {
             contract: Cow::Owned(contract_id.into()),
             function: Cow::Owned(fn_name.into()),
         };
-        self.configs.get(&key)
+        self.configs.get(&key).or_else(|| {
+            key.function = Cow::Borrowed("");
+            self.configs.get(&key)
+        })
     }
 
     /// Inserts an inline configuration, for a test function.
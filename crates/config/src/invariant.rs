@@ -3,8 +3,8 @@
 use crate::{
     fuzz::FuzzDictionaryConfig,
     inline::{
-        parse_config_bool, parse_config_u32, InlineConfigParser, InlineConfigParserError,
-        INLINE_CONFIG_INVARIANT_KEY,
+        parse_config_bool, parse_config_u32, parse_config_u64, InlineConfigParser,
+        InlineConfigParserError, INLINE_CONFIG_INVARIANT_KEY,
     },
 };
 use serde::{Deserialize, Serialize};
@@ -35,6 +35,16 @@ pub struct InvariantConfig {
     /// The maximum number of rejects via `vm.assume` which can be encountered during a single
     /// invariant run.
     pub max_assume_rejects: u32,
+    /// The maximum number of seconds the block timestamp is advanced by before each call. The
+    /// jumps are random, `0` disables them.
+    pub time_jump_max: u64,
+    /// The maximum number of blocks the block number is advanced by before each call. The jumps
+    /// are random, `0` disables them.
+    pub block_jump_max: u64,
+    /// Generates calls to the targeted contracts from within the token receiver callbacks (eg.
+    /// `onERC721Received`) of the targeted contracts, before the callbacks run, to find
+    /// reentrancy through callbacks.
+    pub call_from_callbacks: bool,
 }
 
 impl Default for InvariantConfig {
@@ -49,6 +59,9 @@ impl Default for InvariantConfig {
             shrink_run_limit: 2usize.pow(18_u32),
             preserve_state: false,
             max_assume_rejects: 65536,
+            time_jump_max: 0,
+            block_jump_max: 0,
+            call_from_callbacks: false,
         }
     }
 }
@@ -78,6 +91,11 @@ impl InlineConfigParser for InvariantConfig {
                 "call-override" => conf_clone.call_override = parse_config_bool(key, value)?,
                 "shrink-sequence" => conf_clone.shrink_sequence = parse_config_bool(key, value)?,
                 "preserve-state" => conf_clone.preserve_state = parse_config_bool(key, value)?,
+                "time-jump-max" => conf_clone.time_jump_max = parse_config_u64(key, value)?,
+                "block-jump-max" => conf_clone.block_jump_max = parse_config_u64(key, value)?,
+                "call-from-callbacks" => {
+                    conf_clone.call_from_callbacks = parse_config_bool(key, value)?
+                }
                 _ => Err(InlineConfigParserError::InvalidConfigProperty(key.to_string()))?,
             }
         }
@@ -108,6 +126,20 @@ mod tests {
         assert_eq!(merged.runs, 42424242);
     }
 
+    #[test]
+    fn merge_block_jumps() {
+        let configs = &[
+            "forge-config: default.invariant.time-jump-max = 86400".to_string(),
+            "forge-config: default.invariant.block-jump-max = 100".to_string(),
+            "forge-config: default.invariant.call-from-callbacks = true".to_string(),
+        ];
+        let base_config = InvariantConfig::default();
+        let merged: InvariantConfig = base_config.try_merge(configs).expect("No errors").unwrap();
+        assert_eq!(merged.time_jump_max, 86400);
+        assert_eq!(merged.block_jump_max, 100);
+        assert!(merged.call_from_callbacks);
+    }
+
     #[test]
     fn merge_is_none() {
         let empty_config = &[];
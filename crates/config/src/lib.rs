@@ -31,7 +31,7 @@ use semver::Version;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     path::{Path, PathBuf},
     str::FromStr,
@@ -47,15 +47,15 @@ pub use crate::utils::*;
 mod endpoints;
 pub use endpoints::{ResolvedRpcEndpoints, RpcEndpoint, RpcEndpoints};
 
-mod etherscan;
+pub mod etherscan;
 mod resolve;
 pub use resolve::UnresolvedEnvVarError;
 
 pub mod cache;
-use cache::{Cache, ChainCache};
+use cache::{BlockCache, Cache, ChainCache};
 
 pub mod fmt;
-pub use fmt::FormatterConfig;
+pub use fmt::{FormatterConfig, FormatterOverride};
 
 pub mod fs_permissions;
 pub use crate::fs_permissions::FsPermissions;
@@ -66,6 +66,21 @@ pub use error::SolidityErrorCode;
 pub mod doc;
 pub use doc::DocConfig;
 
+pub mod geiger;
+pub use geiger::{CheatcodeCategory, GeigerConfig, GeigerOverride, GeigerSeverity};
+
+pub mod vyper;
+pub use vyper::{VyperConfig, VyperOptimizationMode};
+
+pub mod script;
+pub use script::{ExpectedEvent, GasOverrides, ScriptChecksConfig, ScriptSettings};
+
+pub mod chains;
+pub use chains::{ChainConfig, ChainsConfig};
+
+pub mod watch;
+pub use watch::WatchConfig;
+
 mod warning;
 pub use warning::*;
 
@@ -81,7 +96,9 @@ pub mod providers;
 
 use crate::{
     error::ExtractConfigError,
-    etherscan::{EtherscanConfigError, EtherscanConfigs, ResolvedEtherscanConfig},
+    etherscan::{
+        EtherscanApiVersion, EtherscanConfigError, EtherscanConfigs, ResolvedEtherscanConfig,
+    },
 };
 use providers::*;
 
@@ -91,11 +108,18 @@ pub use fuzz::{FuzzConfig, FuzzDictionaryConfig};
 mod invariant;
 use crate::fs_permissions::PathPermission;
 pub use invariant::InvariantConfig;
-use providers::remappings::RemappingsProvider;
+use providers::remappings::{find_conflicting_remapping, RemappingsProvider};
+pub use providers::remappings::{RemappingConflict, RemappingOrigin};
+
+mod provenance;
+pub use provenance::Provenance;
 
 mod inline;
 use crate::etherscan::EtherscanEnvProvider;
-pub use inline::{validate_profiles, InlineConfig, InlineConfigError, InlineConfigParser, NatSpec};
+pub use inline::{
+    parse_config_evm_version, source_evm_versions, validate_profiles, InlineConfig,
+    InlineConfigError, InlineConfigParser, InlineConfigParserError, NatSpec,
+};
 
 /// Foundry configuration
 ///
@@ -183,6 +207,11 @@ pub struct Config {
     pub solc: Option<SolcReq>,
     /// whether to autodetect the solc compiler version to use
     pub auto_detect_solc: bool,
+    /// The solc versions to compile the sources with when auto-detecting.
+    ///
+    /// If set, every file is compiled with the newest of these versions that satisfies its
+    /// pragma and the pragmas of its imports, instead of the newest satisfying release.
+    pub solc_versions: Vec<Version>,
     /// Offline mode, if set, network access (downloading solc) is disallowed.
     ///
     /// Relationship with `auto_detect_solc`:
@@ -211,6 +240,13 @@ pub struct Config {
     /// Multiple etherscan api configs and their aliases
     #[serde(default, skip_serializing_if = "EtherscanConfigs::is_empty")]
     pub etherscan: EtherscanConfigs,
+    /// The version of the Etherscan API the `etherscan_api_key` is used with
+    ///
+    /// With `v2`, the same key is used for all chains. Entries of the `etherscan` table still take
+    /// precedence.
+    pub etherscan_api_version: EtherscanApiVersion,
+    /// The maximum number of Etherscan requests per second made with the same API key
+    pub etherscan_requests_per_second: u32,
     /// list of solidity error codes to always silence in the compiler output
     pub ignored_error_codes: Vec<SolidityErrorCode>,
     /// list of file paths to ignore
@@ -327,6 +363,10 @@ pub struct Config {
     /// Disables storage caching entirely. This overrides any settings made in
     /// `rpc_storage_caching`
     pub no_storage_caching: bool,
+    /// The maximum size of the RPC storage cache in megabytes.
+    ///
+    /// If exceeded at the end of a test run, the least recently used block caches are evicted.
+    pub rpc_cache_max_size_mb: Option<u64>,
     /// Disables rate limiting entirely. This overrides any settings made in
     /// `compute_units_per_second`
     pub no_rpc_rate_limit: bool,
@@ -363,6 +403,14 @@ pub struct Config {
     pub fmt: FormatterConfig,
     /// Configuration for `forge doc`
     pub doc: DocConfig,
+    /// Configuration for `forge geiger`
+    pub geiger: GeigerConfig,
+    /// Configuration for compiling Vyper sources
+    pub vyper: VyperConfig,
+    /// Configuration for `forge script`, set in the `[script]` section
+    pub script_settings: ScriptSettings,
+    /// Configuration of the file watcher
+    pub watch: WatchConfig,
     /// Configures the permissions of cheat codes that touch the file system.
     ///
     /// This includes what operations can be executed (read, write)
@@ -379,12 +427,27 @@ pub struct Config {
     /// Useful for more correct gas accounting and EVM behavior in general.
     pub isolate: bool,
 
+    /// If set, every isolated transaction is executed in a new block, advancing the block number
+    /// by one and the timestamp by this number of seconds.
+    ///
+    /// Only used when `isolate` is enabled.
+    pub isolate_block_advance: Option<u64>,
+
     /// Whether to disable the block gas limit.
     pub disable_block_gas_limit: bool,
 
     /// Address labels
     pub labels: HashMap<Address, String>,
 
+    /// The JSON file of labeled addresses by chain ID, resolved by the `getAddress` cheatcodes,
+    /// relative to the project root.
+    ///
+    /// Can be overridden with `FOUNDRY_ADDRESS_BOOK`.
+    pub address_book: PathBuf,
+
+    /// Overrides of the settings for specific chains, see [`Config::apply_chain_overrides`]
+    pub chains: ChainsConfig,
+
     /// The root path where the config detection started from, `Config::with_root`
     #[doc(hidden)]
     //  We're skipping serialization here, so it won't be included in the [`Config::to_string()`]
@@ -427,8 +490,25 @@ impl Config {
     pub const PROFILE_SECTION: &'static str = "profile";
 
     /// Standalone sections in the config which get integrated into the selected profile
-    pub const STANDALONE_SECTIONS: &'static [&'static str] =
-        &["rpc_endpoints", "etherscan", "fmt", "doc", "fuzz", "invariant", "labels"];
+    pub const STANDALONE_SECTIONS: &'static [&'static str] = &[
+        "rpc_endpoints",
+        "etherscan",
+        "fmt",
+        "doc",
+        "geiger",
+        "vyper",
+        "script",
+        "watch",
+        "fuzz",
+        "invariant",
+        "labels",
+    ];
+
+    /// Standalone sections whose key in the profile isn't the name of the section, as it's taken
+    /// by another key: `[script]` configures `forge script`, while `script` is the path of the
+    /// scripts directory.
+    pub const RENAMED_STANDALONE_SECTIONS: &'static [(&'static str, &'static str)] =
+        &[("script", "script_settings")];
 
     /// File name of config toml file
     pub const FILE_NAME: &'static str = "foundry.toml";
@@ -500,9 +580,39 @@ impl Config {
         let figment = Figment::from(provider);
         let mut config = figment.extract::<Self>().map_err(ExtractConfigError::new)?;
         config.profile = figment.profile().clone();
+        if let Some(chain) = config.chain {
+            config.apply_chain_overrides(chain);
+        }
         Ok(config)
     }
 
+    /// Applies the settings of `[profile.<name>.chains.<chain>]` which override the ones of the
+    /// profile, e.g. the EVM version of a chain that doesn't support `PUSH0`.
+    ///
+    /// This is done on load if the chain is configured, and again by commands that learn it later,
+    /// e.g. from a fork. The settings that only apply to transactions, like `legacy`, are read
+    /// from [`Config::chains`] directly.
+    pub fn apply_chain_overrides(&mut self, chain: impl Into<Chain>) {
+        let chain = chain.into();
+        let Some(overrides) = self.chains.get(chain).cloned() else { return };
+
+        if let Some(evm_version) = overrides.evm_version {
+            if evm_version != self.evm_version {
+                debug!(%chain, profile=?self.evm_version, ?evm_version, "chain overrides evm_version");
+                self.evm_version = evm_version;
+            }
+        }
+        if let Some(gas_limit) = overrides.gas_limit {
+            if gas_limit != self.gas_limit {
+                debug!(%chain, profile=self.gas_limit.0, gas_limit=gas_limit.0, "chain overrides gas_limit");
+                self.gas_limit = gas_limit;
+            }
+        }
+        if self.eth_rpc_url.is_none() {
+            self.eth_rpc_url = overrides.eth_rpc_url;
+        }
+    }
+
     /// The config supports relative paths and tracks the root path separately see
     /// `Config::with_root`
     ///
@@ -811,6 +921,61 @@ impl Config {
         self.remappings.iter().map(|m| m.clone().into())
     }
 
+    /// Returns the remappings alongside their [`RemappingOrigin`], and the inferred remappings that
+    /// were discarded because they conflict with configured ones.
+    ///
+    /// Remappings are inferred again from the configured `libs`, so a configured remapping that is
+    /// identical to an inferred one is reported as inferred.
+    pub fn remappings_with_origin(
+        &self,
+    ) -> (Vec<(RelativeRemapping, RemappingOrigin)>, Vec<RemappingConflict>) {
+        let root = &self.__root.0;
+        let inferred = RemappingsProvider {
+            auto_detect_remappings: self.auto_detect_remappings,
+            lib_paths: Cow::Borrowed(&self.libs),
+            root,
+            remappings: Ok(vec![]),
+        }
+        .inferred_remappings()
+        .into_iter()
+        .map(|r| {
+            let relative = RelativeRemapping::new(r.clone(), root);
+            (relative.to_string(), (r, relative))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+        let remappings = self
+            .remappings
+            .iter()
+            .map(|r| {
+                let origin = if inferred.contains_key(&r.to_string()) {
+                    RemappingOrigin::Inferred
+                } else {
+                    RemappingOrigin::Configured
+                };
+                (r.clone(), origin)
+            })
+            .collect::<Vec<_>>();
+
+        let configured = remappings
+            .iter()
+            .filter(|(_, origin)| *origin == RemappingOrigin::Configured)
+            .map(|(r, _)| Remapping::from(r.clone()))
+            .collect::<Vec<_>>();
+        let conflicts = inferred
+            .values()
+            .filter_map(|(r, relative)| {
+                let configured = find_conflicting_remapping(&configured, r, root)?;
+                Some(RemappingConflict {
+                    configured: RelativeRemapping::new(configured.clone(), root),
+                    inferred: relative.clone(),
+                })
+            })
+            .collect();
+
+        (remappings, conflicts)
+    }
+
     /// Returns the configured rpc jwt secret
     ///
     /// Returns:
@@ -944,7 +1109,7 @@ impl Config {
         // if no chain provided, we assume mainnet
         let chain = self.chain.unwrap_or(Chain::mainnet());
         let api_key = self.etherscan_api_key.as_ref()?;
-        ResolvedEtherscanConfig::create(api_key, chain).map(Ok)
+        self.etherscan_config_from_key(api_key, chain).map(Ok)
     }
 
     /// Same as [`Self::get_etherscan_config()`] but optionally updates the config with the given
@@ -952,7 +1117,7 @@ impl Config {
     ///
     /// If not matching alias was found, then this will try to find the first entry in the table
     /// with a matching chain id. If an etherscan_api_key is already set it will take precedence
-    /// over the chain's entry in the table.
+    /// over the chain's entry in the table, unless it is a v2 key.
     pub fn get_etherscan_config_with_chain(
         &self,
         chain: Option<Chain>,
@@ -968,13 +1133,16 @@ impl Config {
             chain.and_then(|chain| self.etherscan.clone().resolved().find_chain(chain))
         {
             match (res, self.etherscan_api_key.as_ref()) {
-                (Ok(mut config), Some(key)) => {
+                (Ok(mut config), Some(key))
+                    if self.etherscan_api_version == EtherscanApiVersion::V1 =>
+                {
                     // we update the key, because if an etherscan_api_key is set, it should take
                     // precedence over the entry, since this is usually set via env var or CLI args.
                     config.key = key.clone();
                     return Ok(Some(config))
                 }
-                (Ok(config), None) => return Ok(Some(config)),
+                // a v2 key is shared by all chains, so the entry of the chain takes precedence
+                (Ok(config), _) => return Ok(Some(config)),
                 (Err(err), None) => return Err(err),
                 (Err(_), Some(_)) => {
                     // use the etherscan key as fallback
@@ -985,12 +1153,25 @@ impl Config {
         // etherscan fallback via API key
         if let Some(key) = self.etherscan_api_key.as_ref() {
             let chain = chain.or(self.chain).unwrap_or_default();
-            return Ok(ResolvedEtherscanConfig::create(key, chain))
+            return Ok(self.etherscan_config_from_key(key, chain))
         }
 
         Ok(None)
     }
 
+    /// Returns the `ResolvedEtherscanConfig` of the `etherscan_api_key` for the given chain,
+    /// depending on the `etherscan_api_version`
+    fn etherscan_config_from_key(
+        &self,
+        key: &str,
+        chain: Chain,
+    ) -> Option<ResolvedEtherscanConfig> {
+        match self.etherscan_api_version {
+            EtherscanApiVersion::V1 => ResolvedEtherscanConfig::create(key, chain),
+            EtherscanApiVersion::V2 => Some(ResolvedEtherscanConfig::create_v2(key, chain)),
+        }
+    }
+
     /// Helper function to just get the API key
     pub fn get_etherscan_api_key(&self, chain: Option<Chain>) -> Option<String> {
         self.get_etherscan_config_with_chain(chain).ok().flatten().map(|c| c.key)
@@ -1269,6 +1450,15 @@ impl Config {
         })
     }
 
+    /// Returns the key of the standalone section in the profile, see
+    /// [`Config::RENAMED_STANDALONE_SECTIONS`].
+    pub fn standalone_section_key(section: &str) -> &str {
+        Self::RENAMED_STANDALONE_SECTIONS
+            .iter()
+            .find(|(renamed, _)| *renamed == section)
+            .map_or(section, |(_, key)| key)
+    }
+
     /// Serialize the config type as a String of TOML.
     ///
     /// This serializes to a table with the name of the profile
@@ -1289,8 +1479,8 @@ impl Config {
         let standalone_sections = Config::STANDALONE_SECTIONS
             .iter()
             .filter_map(|section| {
-                let section = section.to_string();
-                value_table.remove(&section).map(|value| (section, value))
+                let key = Config::standalone_section_key(section);
+                value_table.remove(key).map(|value| (section.to_string(), value))
             })
             .collect::<Vec<_>>();
         // wrap inner table in [profile.<profile>]
@@ -1518,6 +1708,22 @@ impl Config {
         }
     }
 
+    /// Returns the cached blocks of the `chain`, or of all chains.
+    pub fn list_foundry_block_caches(chain: Option<Chain>) -> eyre::Result<Vec<BlockCache>> {
+        let Some(rpc_cache_dir) = Config::foundry_rpc_cache_dir() else {
+            eyre::bail!("failed to get foundry_rpc_cache_dir");
+        };
+        cache::list_block_caches(&rpc_cache_dir, chain)
+    }
+
+    /// Evicts the least recently used block caches until the RPC storage cache is at most
+    /// `max_size` bytes.
+    ///
+    /// Returns the evicted block caches, which are only removed if `dry_run` is false.
+    pub fn evict_foundry_rpc_cache(max_size: u64, dry_run: bool) -> eyre::Result<Vec<BlockCache>> {
+        cache::evict_block_caches(Self::list_foundry_block_caches(None)?, max_size, dry_run)
+    }
+
     //The path provided to this function should point to a cached chain folder
     fn get_cached_blocks(chain_path: &Path) -> eyre::Result<Vec<(String, u64)>> {
         let mut blocks = vec![];
@@ -1528,7 +1734,12 @@ impl Config {
             let file_type = block.file_type()?;
             let file_name = block.file_name();
             let filepath = if file_type.is_dir() {
-                block.path().join("storage.json")
+                let filepath = block.path().join("storage.json");
+                // the block dir may only contain the lock file of a cache that was never written
+                if !filepath.is_file() {
+                    continue
+                }
+                filepath
             } else if file_type.is_file() &&
                 file_name.to_string_lossy().chars().all(char::is_numeric)
             {
@@ -1546,19 +1757,7 @@ impl Config {
         if !chain_path.exists() {
             return Ok(0)
         }
-
-        fn dir_size_recursive(mut dir: fs::ReadDir) -> eyre::Result<u64> {
-            dir.try_fold(0, |acc, file| {
-                let file = file?;
-                let size = match file.metadata()? {
-                    data if data.is_dir() => dir_size_recursive(fs::read_dir(file.path())?)?,
-                    data => data.len(),
-                };
-                Ok(acc + size)
-            })
-        }
-
-        dir_size_recursive(fs::read_dir(chain_path)?)
+        cache::dir_size(chain_path)
     }
 
     fn merge_toml_provider(
@@ -1599,7 +1798,11 @@ impl Config {
                         .wrap(profile.clone(), standalone_key),
                 );
             } else {
-                figment = figment.merge(provider.wrap(profile.clone(), standalone_key));
+                figment = figment.merge(
+                    provider
+                        .wrap(profile.clone(), standalone_key)
+                        .with_key(Config::standalone_section_key(standalone_key)),
+                );
             }
         }
         // merge the profile
@@ -1672,10 +1875,11 @@ impl From<Config> for Figment {
                     .ignore(&["PROFILE", "REMAPPINGS", "LIBRARIES", "FFI", "FS_PERMISSIONS"])
                     .map(|key| {
                         let key = key.as_str();
-                        if Config::STANDALONE_SECTIONS.iter().any(|section| {
+                        if let Some(section) = Config::STANDALONE_SECTIONS.iter().find(|section| {
                             key.starts_with(&format!("{}_", section.to_ascii_uppercase()))
                         }) {
-                            key.replacen('_', ".", 1).into()
+                            let section_key = Config::standalone_section_key(section);
+                            format!("{section_key}.{}", &key[section.len() + 1..]).into()
                         } else {
                             key.into()
                         }
@@ -1841,6 +2045,7 @@ impl Default for Config {
             fs_permissions: FsPermissions::new([PathPermission::read("out")]),
             cancun: false,
             isolate: false,
+            isolate_block_advance: None,
             __root: Default::default(),
             src: "src".into(),
             test: "test".into(),
@@ -1858,6 +2063,7 @@ impl Default for Config {
             gas_reports_ignore: vec![],
             solc: None,
             auto_detect_solc: true,
+            solc_versions: vec![],
             offline: false,
             optimizer: true,
             optimizer_runs: 200,
@@ -1914,7 +2120,10 @@ impl Default for Config {
             rpc_storage_caching: Default::default(),
             rpc_endpoints: Default::default(),
             etherscan: Default::default(),
+            etherscan_api_version: Default::default(),
+            etherscan_requests_per_second: 5,
             no_storage_caching: false,
+            rpc_cache_max_size_mb: None,
             no_rpc_rate_limit: false,
             use_literal_content: false,
             bytecode_hash: BytecodeHash::Ipfs,
@@ -1925,7 +2134,13 @@ impl Default for Config {
             build_info_path: None,
             fmt: Default::default(),
             doc: Default::default(),
+            geiger: Default::default(),
+            vyper: Default::default(),
+            script_settings: Default::default(),
+            watch: Default::default(),
             labels: Default::default(),
+            address_book: "addresses.json".into(),
+            chains: Default::default(),
             __non_exhaustive: (),
             __warnings: vec![],
         }
@@ -2423,15 +2638,27 @@ impl<P: Provider> Provider for UnwrapProfileProvider<P> {
 /// [wrapping_key.profile]
 /// key = "value"
 /// ```
+///
+/// The profile is wrapped under its snake case name, unless another key is set with
+/// [`WrapProfileProvider::with_key`].
 struct WrapProfileProvider<P> {
     provider: P,
     wrapping_key: Profile,
     profile: Profile,
+    key: String,
 }
 
 impl<P> WrapProfileProvider<P> {
     pub fn new(provider: P, wrapping_key: impl Into<Profile>, profile: impl Into<Profile>) -> Self {
-        Self { provider, wrapping_key: wrapping_key.into(), profile: profile.into() }
+        let profile = profile.into();
+        let key = profile.to_string().to_snake_case();
+        Self { provider, wrapping_key: wrapping_key.into(), profile, key }
+    }
+
+    /// Sets the key the profile is wrapped under.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
     }
 }
 
@@ -2442,7 +2669,7 @@ impl<P: Provider> Provider for WrapProfileProvider<P> {
     fn data(&self) -> Result<Map<Profile, Dict>, Error> {
         if let Some(inner) = self.provider.data()?.remove(&self.profile) {
             let value = Value::from(inner);
-            let dict = [(self.profile.to_string().to_snake_case(), value)].into_iter().collect();
+            let dict = [(self.key.clone(), value)].into_iter().collect();
             Ok(self.wrapping_key.collect(dict))
         } else {
             Ok(Default::default())
@@ -2947,6 +3174,82 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_infer_nested_node_modules_remappings() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file("node_modules/a/A.sol", "")?;
+            jail.create_file("node_modules/a/node_modules/b/B.sol", "")?;
+            jail.create_file("node_modules/b/B.sol", "")?;
+            jail.create_file("node_modules/c/C.sol", "")?;
+
+            let has_remapping = |config: &Config, remapping: &str| {
+                config.remappings.iter().any(|r| r.to_string() == remapping)
+            };
+
+            let config = Config::load();
+            assert!(has_remapping(&config, "b/=node_modules/b/"));
+            assert!(has_remapping(&config, "node_modules/a/:b/=node_modules/a/node_modules/b/"));
+
+            let (remappings, conflicts) = config.remappings_with_origin();
+            assert!(remappings.iter().all(|(_, origin)| *origin == RemappingOrigin::Inferred));
+            assert!(conflicts.is_empty());
+
+            // configured remappings take precedence over inferred ones
+            jail.create_file("remappings.txt", "b/=lib/b/\nc/=node_modules/c/")?;
+            let config = Config::load();
+            assert!(has_remapping(&config, "b/=lib/b/"));
+            assert!(!has_remapping(&config, "b/=node_modules/b/"));
+            assert!(has_remapping(&config, "node_modules/a/:b/=node_modules/a/node_modules/b/"));
+
+            let (_, conflicts) = config.remappings_with_origin();
+            assert_eq!(conflicts.len(), 1);
+            assert_eq!(conflicts[0].configured.to_string(), "b/=lib/b/");
+            assert_eq!(conflicts[0].inferred.to_string(), "b/=node_modules/b/");
+
+            Ok(())
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_infer_pnpm_remappings() {
+        use std::os::unix::fs::symlink;
+
+        figment::Jail::expect_with(|jail| {
+            jail.create_file("node_modules/.pnpm/@scope+a@1.0.0/node_modules/@scope/a/A.sol", "")?;
+            jail.create_file("node_modules/.pnpm/b@1.0.0/node_modules/b/B.sol", "")?;
+            jail.create_file("node_modules/.pnpm/b@2.0.0/node_modules/b/B.sol", "")?;
+
+            let root = jail.directory();
+            symlink(
+                root.join("node_modules/.pnpm/b@1.0.0/node_modules/b"),
+                root.join("node_modules/.pnpm/@scope+a@1.0.0/node_modules/b"),
+            )
+            .unwrap();
+            fs::create_dir_all(root.join("node_modules/@scope")).unwrap();
+            symlink(
+                root.join("node_modules/.pnpm/@scope+a@1.0.0/node_modules/@scope/a"),
+                root.join("node_modules/@scope/a"),
+            )
+            .unwrap();
+            symlink(
+                root.join("node_modules/.pnpm/b@2.0.0/node_modules/b"),
+                root.join("node_modules/b"),
+            )
+            .unwrap();
+
+            let config = Config::load();
+            let remappings = config.remappings.iter().map(ToString::to_string).collect::<Vec<_>>();
+            assert!(remappings.contains(
+                &"node_modules/@scope/a/:b/=node_modules/.pnpm/@scope+a@1.0.0/node_modules/b/"
+                    .to_string()
+            ));
+            assert!(remappings.iter().all(|r| !r.starts_with(".pnpm")));
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_can_update_libs() {
         figment::Jail::expect_with(|jail| {
@@ -3019,6 +3322,52 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_chain_overrides() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                evm_version = "cancun"
+                gas_limit = 100
+
+                [profile.default.chains.mainnet]
+                evm_version = "paris"
+                eth_rpc_url = "https://eth.example.com"
+
+                [profile.default.chains.42161]
+                gas_limit = 30000000
+                legacy = true
+                gas_multiplier = 200
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert_eq!(config.evm_version, EvmVersion::Cancun);
+            assert_eq!(config.chains.get(NamedChain::Arbitrum).unwrap().gas_multiplier, Some(200));
+            assert_eq!(config.chains.get(Chain::from_id(42161)).unwrap().legacy, Some(true));
+            assert!(config.chains.get(NamedChain::Optimism).is_none());
+
+            let mut mainnet = config.clone();
+            mainnet.apply_chain_overrides(NamedChain::Mainnet);
+            assert_eq!(mainnet.evm_version, EvmVersion::Paris);
+            assert_eq!(mainnet.gas_limit, 100.into());
+            assert_eq!(mainnet.eth_rpc_url, Some("https://eth.example.com".to_string()));
+
+            let mut arbitrum = config;
+            arbitrum.apply_chain_overrides(42161u64);
+            assert_eq!(arbitrum.evm_version, EvmVersion::Cancun);
+            assert_eq!(arbitrum.gas_limit, 30_000_000.into());
+
+            jail.set_env("FOUNDRY_CHAIN_ID", "1");
+            let config = Config::load();
+            assert_eq!(config.evm_version, EvmVersion::Paris);
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_resolve_etherscan_with_chain() {
         figment::Jail::expect_with(|jail| {
@@ -3067,6 +3416,43 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_resolve_etherscan_v2_key() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                etherscan_api_key = "V2KEY"
+                etherscan_api_version = "v2"
+
+                [etherscan]
+                bsc = { key = "BSCKEY", url = "https://api.bscscan.com/api" }
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert_eq!(config.etherscan_api_version, EtherscanApiVersion::V2);
+
+            let optimism = config
+                .get_etherscan_config_with_chain(Some(NamedChain::Optimism.into()))
+                .unwrap()
+                .unwrap();
+            assert_eq!(optimism.api_url, "https://api.etherscan.io/v2/api?chainid=10");
+            assert_eq!(optimism.key, "V2KEY");
+
+            // entries of the `etherscan` table take precedence
+            let bsc = config
+                .get_etherscan_config_with_chain(Some(NamedChain::BinanceSmartChain.into()))
+                .unwrap()
+                .unwrap();
+            assert_eq!(bsc.api_url, "https://api.bscscan.com/api");
+            assert_eq!(bsc.key, "BSCKEY");
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_resolve_etherscan() {
         figment::Jail::expect_with(|jail| {
@@ -4340,6 +4726,119 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_script_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                script = "scripts"
+
+                [script]
+                encrypt_sequences = true
+
+                [script.checks]
+                sender_balance = true
+
+                [script.gas_overrides.324]
+                intrinsic_gas = 0
+                estimate_via_rpc_only = true
+
+                [[script.expected_events]]
+                transaction = "new Proxy"
+                event = "Upgraded(address)"
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(loaded.script, PathBuf::from("scripts"));
+            assert!(loaded.script_settings.encrypt_sequences);
+            assert!(loaded.script_settings.checks.sender_balance);
+            assert_eq!(
+                loaded.script_settings.gas_overrides(324u64),
+                Some(&GasOverrides {
+                    intrinsic_gas: Some(0),
+                    estimate_via_rpc_only: true,
+                    ..Default::default()
+                })
+            );
+            assert!(loaded.script_settings.gas_overrides(NamedChain::Mainnet).is_none());
+            assert_eq!(
+                loaded.script_settings.expected_events,
+                [ExpectedEvent {
+                    transaction: "new Proxy".to_string(),
+                    event: "Upgraded(address)".to_string(),
+                    emitter: None,
+                }]
+            );
+
+            let toml = loaded.to_string_pretty().unwrap();
+            assert!(toml.contains("[script.checks]"), "{toml}");
+            assert!(toml.contains("[script.gas_overrides.324]"), "{toml}");
+            assert!(toml.contains("[[script.expected_events]]"), "{toml}");
+            assert!(!toml.contains("script_settings"), "{toml}");
+
+            jail.set_env("FOUNDRY_SCRIPT", "deploy");
+            let loaded = Config::load();
+            assert_eq!(loaded.script, PathBuf::from("deploy"));
+            assert!(loaded.script_settings.checks.sender_balance);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_fmt_overrides() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [fmt]
+                line_length = 100
+
+                [[fmt.overrides]]
+                paths = ["test/**"]
+                line_length = 140
+                allow_single_line_functions = true
+
+                [[fmt.overrides]]
+                paths = ["test/invariant/**", "script/*.s.sol"]
+                line_length = 160
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(loaded.fmt.overrides.len(), 2);
+            assert_eq!(
+                loaded.fmt.overrides[0],
+                FormatterOverride {
+                    paths: vec!["test/**".to_string()],
+                    line_length: Some(140),
+                    allow_single_line_functions: Some(true),
+                    ..Default::default()
+                }
+            );
+
+            let root = loaded.__root.0.clone();
+            let src = loaded.fmt.for_path(&root.join("src/Counter.sol"), &root);
+            assert_eq!(src.line_length, 100);
+            assert!(!src.allow_single_line_functions);
+
+            let test = loaded.fmt.for_path(&root.join("test/Counter.t.sol"), &root);
+            assert_eq!(test.line_length, 140);
+            assert!(test.allow_single_line_functions);
+
+            let invariant = loaded.fmt.for_path(Path::new("test/invariant/Handler.sol"), &root);
+            assert_eq!(invariant.line_length, 160);
+            assert!(invariant.allow_single_line_functions);
+
+            let script = loaded.fmt.for_path(&root.join("script/Deploy.s.sol"), &root);
+            assert_eq!(script.line_length, 160);
+            assert!(!script.allow_single_line_functions);
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_invariant_config() {
         figment::Jail::expect_with(|jail| {
@@ -4590,6 +5089,10 @@ mod tests {
         // Pollution file that should not show up in the cached block
         let mut pol_file = File::create(chain_dir.path().join("pol.txt")).unwrap();
         writeln!(pol_file, "{}", [' '; 10].iter().collect::<String>()).unwrap();
+        // Lock files guarding concurrent access should not show up either
+        File::create(chain_dir.path().join("1").join("storage.lock")).unwrap();
+        fs::create_dir(chain_dir.path().join("4")).unwrap();
+        File::create(chain_dir.path().join("4").join("storage.lock")).unwrap();
 
         let result = Config::get_cached_blocks(chain_dir.path())?;
 
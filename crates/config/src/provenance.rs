@@ -0,0 +1,239 @@
+//! Support for finding where the effective value of a config key comes from.
+
+use crate::Config;
+use figment::{Figment, Profile, Source};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Where the effective value of a config key comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Provenance {
+    /// The default value.
+    Default,
+    /// Detected from the project, e.g. the remappings of the installed libraries.
+    Detected,
+    /// A `foundry.toml` file.
+    File {
+        path: PathBuf,
+        /// The line of the key, if it was found.
+        line: Option<usize>,
+        /// The profile that overrides the key, if it's not set in the default profile.
+        profile: Option<Profile>,
+    },
+    /// An environment variable.
+    Env(String),
+    /// A command line flag.
+    Cli,
+}
+
+impl Provenance {
+    /// Returns where the effective value of the dotted `key`, e.g. `fuzz.runs`, comes from in the
+    /// figment, if the key is set.
+    pub fn find(figment: &Figment, key: &str) -> Option<Self> {
+        let value = figment.find_value(key).ok()?;
+        let metadata = figment.get_metadata(value.tag())?;
+        if let Some(Source::File(path)) = &metadata.source {
+            let (line, profile) = find_toml_key(path, figment.profile(), key)
+                .map_or((None, None), |(line, profile)| (Some(line), profile));
+            return Some(Self::File { path: path.clone(), line, profile })
+        }
+
+        let name = metadata.name.as_ref();
+        let provenance = match name {
+            "Foundry Config" => Self::Default,
+            "Remapping Provider" | "Dapp Hardhat dir compat" => Self::Detected,
+            // merged as a `(key, value)` tuple
+            name if name.starts_with('(') => Self::Detected,
+            "Dapp env compat" => Self::Env("DAPP_*".to_string()),
+            name if name.ends_with("environment variable(s)") => {
+                // e.g. "`FOUNDRY_` environment variable(s)"
+                let prefix = name.split('`').nth(1).unwrap_or_default();
+                Self::Env(format!("{prefix}{}", key.replace('.', "_").to_ascii_uppercase()))
+            }
+            // the remaining providers are the arguments of the command
+            _ => Self::Cli,
+        };
+        Some(provenance)
+    }
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::Detected => f.write_str("detected"),
+            Self::File { path, line, profile } => {
+                write!(f, "{}", path.display())?;
+                if let Some(line) = line {
+                    write!(f, ":{line}")?;
+                }
+                if let Some(profile) = profile {
+                    write!(f, " (profile `{profile}`)")?;
+                }
+                Ok(())
+            }
+            Self::Env(var) => write!(f, "env `{var}`"),
+            Self::Cli => f.write_str("CLI flag"),
+        }
+    }
+}
+
+/// Returns the line of the dotted `key` in the toml file, and the profile overriding it.
+///
+/// The tables are searched like the config merges them: the selected profile takes precedence over
+/// the standalone sections, e.g. `[fuzz]`, which take precedence over the default profile. A key
+/// set in an inline table is found at the line of the table.
+fn find_toml_key(path: &Path, profile: &Profile, key: &str) -> Option<(usize, Option<Profile>)> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let keys = toml_key_lines(&content);
+    let mut key = normalize_key(key);
+
+    let mut candidates = Vec::with_capacity(3);
+    let section = key.split('.').next().unwrap_or_default().to_string();
+    if let Some((standalone, _)) =
+        Config::RENAMED_STANDALONE_SECTIONS.iter().find(|(_, renamed)| *renamed == section)
+    {
+        // a renamed section can only be set in its standalone table
+        key = key.replacen(&section, standalone, 1);
+        candidates.push((String::new(), None));
+    } else {
+        if *profile != Config::DEFAULT_PROFILE {
+            candidates.push((format!("profile.{profile}"), Some(profile.clone())));
+        }
+        if Config::STANDALONE_SECTIONS.contains(&section.as_str()) {
+            candidates.push((String::new(), None));
+        }
+        candidates.push((format!("profile.{}", Config::DEFAULT_PROFILE), None));
+    }
+
+    for (table, profile) in candidates {
+        let mut path = key.as_str();
+        loop {
+            let full = if table.is_empty() { path.to_string() } else { format!("{table}.{path}") };
+            if let Some(line) = keys.get(&full) {
+                return Some((*line, profile))
+            }
+            match path.rsplit_once('.') {
+                Some((parent, _)) => path = parent,
+                None => break,
+            }
+        }
+    }
+    None
+}
+
+/// Returns the first line of each `key = value` pair in the toml content, by dotted path.
+fn toml_key_lines(content: &str) -> HashMap<String, usize> {
+    let mut keys = HashMap::new();
+    let mut table = String::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.split('#').next().unwrap_or_default();
+            table = normalize_key(header.trim().trim_matches(['[', ']']));
+            continue
+        }
+        let Some((key, _)) = line.split_once('=') else { continue };
+        let key = key.trim();
+        // skips comments and the lines of multiline values, e.g. remappings
+        if key.is_empty() ||
+            !key.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '"' | ' '))
+        {
+            continue
+        }
+        let key = normalize_key(key);
+        let full = if table.is_empty() { key } else { format!("{table}.{key}") };
+        keys.entry(full).or_insert(i + 1);
+    }
+    keys
+}
+
+/// Normalizes a dotted toml key, since keys are converted to snake case when loaded.
+fn normalize_key(key: &str) -> String {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').replace('-', "_"))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use figment::Jail;
+
+    #[test]
+    fn finds_toml_key_lines() {
+        let content = r#"
+[profile.default]
+src = "src"
+remappings = [
+    "ds-test/=lib/ds-test/src/",
+]
+
+[profile.default.fuzz]
+runs = 1000
+
+[profile.ci]
+optimizer-runs = 20
+
+[rpc_endpoints]
+mainnet = "https://eth.llamarpc.com"
+"#;
+        let keys = toml_key_lines(content);
+        assert_eq!(keys.get("profile.default.src"), Some(&3));
+        assert_eq!(keys.get("profile.default.remappings"), Some(&4));
+        assert_eq!(keys.get("profile.default.fuzz.runs"), Some(&9));
+        assert_eq!(keys.get("profile.ci.optimizer_runs"), Some(&12));
+        assert_eq!(keys.get("rpc_endpoints.mainnet"), Some(&15));
+        assert_eq!(keys.len(), 5);
+    }
+
+    #[test]
+    fn finds_provenance() {
+        Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                optimizer_runs = 1000
+
+                [profile.ci]
+                verbosity = 3
+
+                [fuzz]
+                runs = 512
+            "#,
+            )?;
+            jail.set_env("FOUNDRY_PROFILE", "ci");
+            jail.set_env("FOUNDRY_FUZZ_SEED", "0x1");
+            let toml = jail.directory().join("foundry.toml");
+
+            let figment = Config::figment_with_root(jail.directory());
+            assert_eq!(Provenance::find(&figment, "via_ir"), Some(Provenance::Default));
+            assert_eq!(
+                Provenance::find(&figment, "optimizer_runs"),
+                Some(Provenance::File { path: toml.clone(), line: Some(3), profile: None })
+            );
+            assert_eq!(
+                Provenance::find(&figment, "verbosity"),
+                Some(Provenance::File {
+                    path: toml.clone(),
+                    line: Some(6),
+                    profile: Some(Profile::new("ci"))
+                })
+            );
+            assert_eq!(
+                Provenance::find(&figment, "fuzz.runs"),
+                Some(Provenance::File { path: toml, line: Some(9), profile: None })
+            );
+            assert_eq!(
+                Provenance::find(&figment, "fuzz.seed"),
+                Some(Provenance::Env("FOUNDRY_FUZZ_SEED".to_string()))
+            );
+            Ok(())
+        });
+    }
+}
@@ -6,11 +6,67 @@ use figment::{
 use foundry_compilers::remappings::{RelativeRemapping, Remapping};
 use std::{
     borrow::Cow,
-    collections::{btree_map::Entry, BTreeMap, HashSet},
-    fs,
+    collections::{btree_map::Entry, BTreeMap, HashSet, VecDeque},
+    fmt, fs,
     path::{Path, PathBuf},
 };
 
+/// The maximum number of packages that are visited when walking a `node_modules` directory.
+const MAX_NODE_MODULES_PACKAGES: usize = 10_000;
+
+/// Where a remapping comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemappingOrigin {
+    /// Set via environment variables, `remappings.txt`, the config file or the CLI.
+    Configured,
+    /// Auto-detected from the library directories.
+    Inferred,
+}
+
+impl fmt::Display for RemappingOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemappingOrigin::Configured => f.write_str("configured"),
+            RemappingOrigin::Inferred => f.write_str("inferred"),
+        }
+    }
+}
+
+/// An inferred remapping that was discarded because a configured remapping resolves the same
+/// imports to a different location.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemappingConflict {
+    /// The configured remapping that takes precedence.
+    pub configured: RelativeRemapping,
+    /// The discarded inferred remapping.
+    pub inferred: RelativeRemapping,
+}
+
+impl fmt::Display for RemappingConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "inferred `{}` conflicts with configured `{}`", self.inferred, self.configured)
+    }
+}
+
+/// Returns the configured remapping that resolves the imports matched by the `inferred` remapping
+/// to a different location, if any.
+///
+/// Only remappings of the same context are compared, since a context-scoped remapping is meant to
+/// override the global one.
+pub fn find_conflicting_remapping<'a>(
+    configured: &'a [Remapping],
+    inferred: &Remapping,
+    root: &Path,
+) -> Option<&'a Remapping> {
+    configured.iter().find(|configured| {
+        if configured.context != inferred.context {
+            return false
+        }
+        let Some(rest) = inferred.name.strip_prefix(&configured.name) else { return false };
+        root.join(&configured.path).join(rest) != root.join(&inferred.path)
+    })
+}
+
 /// Wrapper types over a `Vec<Remapping>` that only appends unique remappings.
 #[derive(Clone, Debug, Default)]
 pub struct Remappings {
@@ -71,7 +127,8 @@ impl Remappings {
 /// up the fs via
 ///   - `DAPP_REMAPPINGS` || `FOUNDRY_REMAPPINGS` env var
 ///   - `<root>/remappings.txt` file
-///   - `Remapping::find_many`.
+///   - `Remapping::find_many`
+///   - the nested dependencies of the packages in `node_modules` directories.
 pub struct RemappingsProvider<'a> {
     /// Whether to auto detect remappings from the `lib_paths`
     pub auto_detect_remappings: bool,
@@ -98,29 +155,11 @@ impl<'a> RemappingsProvider<'a> {
     /// - `remappings.txt`
     /// - Environment variables
     /// - CLI parameters
+    ///
+    /// Inferred remappings never override configured ones: an inferred remapping is discarded if
+    /// a configured remapping of the same context resolves its imports to a different location.
     fn get_remappings(&self, remappings: Vec<Remapping>) -> Result<Vec<Remapping>, Error> {
         trace!("get all remappings from {:?}", self.root);
-        /// prioritizes remappings that are closer: shorter `path`
-        ///   - ("a", "1/2") over ("a", "1/2/3")
-        /// grouped by remapping context
-        fn insert_closest(
-            mappings: &mut BTreeMap<Option<String>, BTreeMap<String, PathBuf>>,
-            context: Option<String>,
-            key: String,
-            path: PathBuf,
-        ) {
-            let context_mappings = mappings.entry(context).or_default();
-            match context_mappings.entry(key) {
-                Entry::Occupied(mut e) => {
-                    if e.get().components().count() > path.components().count() {
-                        e.insert(path);
-                    }
-                }
-                Entry::Vacant(e) => {
-                    e.insert(path);
-                }
-            }
-        }
 
         // Let's first just extend the remappings with the ones that were passed in,
         // without any filtering.
@@ -145,52 +184,151 @@ impl<'a> RemappingsProvider<'a> {
         }
 
         user_remappings.extend(remappings);
+
+        let inferred = self
+            .inferred_remappings()
+            .into_iter()
+            .filter(|r| {
+                let conflict = find_conflicting_remapping(&user_remappings, r, self.root);
+                if let Some(configured) = conflict {
+                    trace!(target: "forge", "- skipping {r} in favor of {configured}");
+                }
+                conflict.is_none()
+            })
+            .collect();
+
         // Let's now use the wrapper to conditionally extend the remappings with the autodetected
         // ones. We want to avoid duplicates, and the wrapper will handle this for us.
         let mut all_remappings = Remappings::new_with_remappings(user_remappings);
+        all_remappings.extend(inferred);
+
+        Ok(all_remappings.into_inner())
+    }
+
+    /// Returns the remappings auto-detected from the library directories, with absolute paths.
+    ///
+    /// Returns an empty vector if `auto_detect_remappings` is disabled.
+    pub fn inferred_remappings(&self) -> Vec<Remapping> {
+        /// prioritizes remappings that are closer: shorter `path`
+        ///   - ("a", "1/2") over ("a", "1/2/3")
+        /// grouped by remapping context
+        fn insert_closest(
+            mappings: &mut BTreeMap<Option<String>, BTreeMap<String, PathBuf>>,
+            context: Option<String>,
+            key: String,
+            path: PathBuf,
+        ) {
+            let context_mappings = mappings.entry(context).or_default();
+            match context_mappings.entry(key) {
+                Entry::Occupied(mut e) => {
+                    if e.get().components().count() > path.components().count() {
+                        e.insert(path);
+                    }
+                }
+                Entry::Vacant(e) => {
+                    e.insert(path);
+                }
+            }
+        }
 
         // scan all library dirs and autodetect remappings
         // todo: if a lib specifies contexts for remappings manually, we need to figure out how to
         // resolve that
-        if self.auto_detect_remappings {
-            let mut lib_remappings = BTreeMap::new();
-            // find all remappings of from libs that use a foundry.toml
-            for r in self.lib_foundry_toml_remappings() {
-                insert_closest(&mut lib_remappings, r.context, r.name, r.path.into());
+        if !self.auto_detect_remappings {
+            return vec![]
+        }
+
+        let mut lib_remappings = BTreeMap::new();
+        // find all remappings of from libs that use a foundry.toml
+        for r in self.lib_foundry_toml_remappings() {
+            insert_closest(&mut lib_remappings, r.context, r.name, r.path.into());
+        }
+        // use auto detection for all libs
+        for r in self
+            .lib_paths
+            .iter()
+            .map(|lib| self.root.join(lib))
+            .inspect(|lib| {
+                trace!("find all remappings in lib path: {:?}", lib);
+            })
+            .flat_map(Remapping::find_many)
+        {
+            // this is an additional safety check for weird auto-detected remappings, and for
+            // hidden directories like pnpm's `.pnpm` store
+            if ["lib/", "src/", "contracts/"].contains(&r.name.as_str()) || r.name.starts_with('.')
+            {
+                trace!(target: "forge", "- skipping the remapping");
+                continue
             }
-            // use auto detection for all libs
-            for r in self
-                .lib_paths
-                .iter()
-                .map(|lib| self.root.join(lib))
-                .inspect(|lib| {
-                    trace!("find all remappings in lib path: {:?}", lib);
+            insert_closest(&mut lib_remappings, r.context, r.name, r.path.into());
+        }
+        // scope the dependencies of npm packages to the packages that depend on them
+        for r in self
+            .lib_paths
+            .iter()
+            .map(|lib| self.root.join(lib))
+            .filter(|lib| lib.file_name().map_or(false, |name| name == "node_modules"))
+            .flat_map(|lib| self.node_modules_remappings(&lib))
+        {
+            insert_closest(&mut lib_remappings, r.context, r.name, r.path.into());
+        }
+
+        lib_remappings
+            .into_iter()
+            .flat_map(|(context, remappings)| {
+                remappings.into_iter().map(move |(name, path)| Remapping {
+                    context: context.clone(),
+                    name,
+                    path: path.to_string_lossy().into(),
                 })
-                .flat_map(Remapping::find_many)
-            {
-                // this is an additional safety check for weird auto-detected remappings
-                if ["lib/", "src/", "contracts/"].contains(&r.name.as_str()) {
-                    trace!(target: "forge", "- skipping the remapping");
+            })
+            .collect()
+    }
+
+    /// Returns context-scoped remappings for the dependencies of the packages in a `node_modules`
+    /// directory that don't resolve to the top-level package of the same name.
+    ///
+    /// This supports both npm's nested `node_modules` directories and pnpm's layout, where the
+    /// top-level packages are symlinks into the `.pnpm` store and the dependencies of a package
+    /// are its siblings in the store. Dependencies are walked recursively, so that every package
+    /// imports the version of a dependency it was installed with.
+    ///
+    /// The contexts are relative to the root, since they're matched against source unit names.
+    fn node_modules_remappings(&self, node_modules: &Path) -> Vec<Remapping> {
+        let top_level = node_modules_packages(node_modules);
+        let hoisted = top_level
+            .iter()
+            .filter_map(|(name, path)| Some((name.clone(), path.canonicalize().ok()?)))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut remappings = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue = top_level.into_values().collect::<VecDeque<_>>();
+        while let Some(package) = queue.pop_front() {
+            if visited.len() >= MAX_NODE_MODULES_PACKAGES {
+                warn!("stopped inferring remappings after {MAX_NODE_MODULES_PACKAGES} packages");
+                break
+            }
+            if !visited.insert(package.clone()) {
+                continue
+            }
+
+            for (name, dependency) in package_dependencies(&package) {
+                let resolved = dependency.canonicalize().ok();
+                if resolved.is_some() && resolved.as_ref() == hoisted.get(&name) {
                     continue
                 }
-                insert_closest(&mut lib_remappings, r.context, r.name, r.path.into());
+                let context = package.strip_prefix(self.root).unwrap_or(&package);
+                remappings.push(Remapping {
+                    context: Some(format!("{}/", context.display())),
+                    name: format!("{name}/"),
+                    path: format!("{}/", dependency.display()),
+                });
+                queue.push_back(dependency);
             }
-
-            all_remappings.extend(
-                lib_remappings
-                    .into_iter()
-                    .flat_map(|(context, remappings)| {
-                        remappings.into_iter().map(move |(name, path)| Remapping {
-                            context: context.clone(),
-                            name,
-                            path: path.to_string_lossy().into(),
-                        })
-                    })
-                    .collect(),
-            );
         }
 
-        Ok(all_remappings.into_inner())
+        remappings
     }
 
     /// Returns all remappings declared in foundry.toml files of libraries
@@ -241,6 +379,64 @@ impl<'a> RemappingsProvider<'a> {
     }
 }
 
+/// Returns the packages in the `node_modules` directory, keyed by their name.
+///
+/// Scoped packages are named `@scope/name`. Hidden directories like `.bin` and `.pnpm` are skipped.
+fn node_modules_packages(node_modules: &Path) -> BTreeMap<String, PathBuf> {
+    let mut packages = BTreeMap::new();
+    for (name, path) in dir_entries(node_modules) {
+        if name.starts_with('.') {
+            continue
+        }
+        if name.starts_with('@') {
+            for (package, path) in dir_entries(&path) {
+                packages.insert(format!("{name}/{package}"), path);
+            }
+        } else {
+            packages.insert(name, path);
+        }
+    }
+    packages
+}
+
+/// Returns the dependencies installed for the package, keyed by their name.
+///
+/// These are the packages in the package's own `node_modules` directory and, if the package lives
+/// in pnpm's `.pnpm` store, its siblings in the store.
+fn package_dependencies(package: &Path) -> BTreeMap<String, PathBuf> {
+    let mut dependencies = BTreeMap::new();
+
+    if let Some(resolved) = package.canonicalize().ok().filter(|path| path != package) {
+        let store = resolved.ancestors().skip(1).find(|dir| {
+            dir.file_name().map_or(false, |name| name == "node_modules") &&
+                dir.components().any(|c| c.as_os_str() == ".pnpm")
+        });
+        if let Some(store) = store {
+            let own_name = resolved.strip_prefix(store).ok().map(|name| name.to_string_lossy());
+            for (name, path) in node_modules_packages(store) {
+                if Some(name.as_str()) != own_name.as_deref() {
+                    dependencies.insert(name, path);
+                }
+            }
+        }
+    }
+
+    // nested dependencies take precedence, like they do in node's module resolution
+    dependencies.extend(node_modules_packages(&package.join("node_modules")));
+    dependencies
+}
+
+/// Returns the names and paths of the directories in `dir`, following symlinks.
+fn dir_entries(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else { return vec![] };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| Some((path.file_name()?.to_str()?.to_string(), path)))
+        .collect()
+}
+
 impl<'a> Provider for RemappingsProvider<'a> {
     fn metadata(&self) -> Metadata {
         Metadata::named("Remapping Provider")
@@ -0,0 +1,207 @@
+//! Configuration for `forge script`
+
+use crate::Chain;
+use foundry_compilers::EvmVersion;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// Contains the configuration of `forge script`, set in the `[script]` section, e.g.
+///
+/// ```toml
+/// [script]
+/// encrypt_sequences = true
+///
+/// [script.checks]
+/// sender_balance = true
+///
+/// [script.gas_overrides.324]
+/// estimate_via_rpc_only = true
+///
+/// [[script.expected_events]]
+/// transaction = "new Proxy"
+/// event = "Upgraded(address)"
+/// ```
+///
+/// Unlike the other standalone sections, it can't be set in a profile, where `script` is the path
+/// of the scripts directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptSettings {
+    /// Whether to encrypt the transaction sequences `forge script` saves, with the passphrase of
+    /// `FOUNDRY_SEQUENCE_PASSWORD`.
+    #[serde(default)]
+    pub encrypt_sequences: bool,
+    /// The checks that must pass before broadcasting
+    #[serde(default)]
+    pub checks: ScriptChecksConfig,
+    /// The gas costs of the chains whose gas accounting differs from the EVM's, keyed by chain
+    /// name or EIP-155 chain ID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub gas_overrides: BTreeMap<String, GasOverrides>,
+    /// The events the broadcast transactions must emit, `forge script` fails after broadcasting
+    /// if one of them is missing from the receipts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_events: Vec<ExpectedEvent>,
+}
+
+impl ScriptSettings {
+    /// Returns the gas overrides of the given chain, if any.
+    pub fn gas_overrides(&self, chain: impl Into<Chain>) -> Option<&GasOverrides> {
+        let chain = chain.into();
+        self.gas_overrides
+            .iter()
+            .find(|(key, _)| key.parse::<Chain>().ok() == Some(chain))
+            .map(|(_, overrides)| overrides)
+    }
+}
+
+/// Gas costs that replace the ones of the EVM when `forge script` computes the gas limit of a
+/// transaction from its simulation, unset costs are the EVM's.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasOverrides {
+    /// The base cost of a transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intrinsic_gas: Option<u64>,
+    /// The cost of a zero byte of calldata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calldata_zero_byte_cost: Option<u64>,
+    /// The cost of a non-zero byte of calldata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calldata_nonzero_byte_cost: Option<u64>,
+    /// Whether to ignore the gas of the simulation and use `eth_estimateGas`, multiplied by the
+    /// gas estimate multiplier, for every transaction.
+    #[serde(default)]
+    pub estimate_via_rpc_only: bool,
+}
+
+impl GasOverrides {
+    /// The base cost of a transaction in the EVM.
+    pub const INTRINSIC_GAS: u64 = 21_000;
+    /// The cost of a zero byte of calldata in the EVM.
+    pub const CALLDATA_ZERO_BYTE_COST: u64 = 4;
+    /// The cost of a non-zero byte of calldata in the EVM.
+    pub const CALLDATA_NONZERO_BYTE_COST: u64 = 16;
+    /// The additional base cost of a contract creation in the EVM.
+    pub const CREATE_GAS: u64 = 32_000;
+    /// The cost of a 32-byte word of initcode in the EVM, from Shanghai on (EIP-3860).
+    pub const INITCODE_WORD_COST: u64 = 2;
+
+    /// Returns the gas used by a transaction with `calldata`, the initcode if it's a contract
+    /// creation, measured with the costs of the EVM, with the costs of the overrides instead.
+    pub fn apply(
+        &self,
+        gas_used: u64,
+        calldata: &[u8],
+        is_create: bool,
+        evm_version: EvmVersion,
+    ) -> u64 {
+        let evm = Self::default().intrinsic_gas(calldata, is_create, evm_version);
+        let chain = self.intrinsic_gas(calldata, is_create, evm_version);
+        gas_used.saturating_sub(evm) + chain
+    }
+
+    /// Returns the intrinsic gas of a transaction with `calldata` with the costs of the overrides.
+    ///
+    /// Contract creations are charged the creation cost on top of the base cost, and the cost of
+    /// their initcode words if `evm_version` charges it.
+    pub fn intrinsic_gas(&self, calldata: &[u8], is_create: bool, evm_version: EvmVersion) -> u64 {
+        let zeros = calldata.iter().filter(|byte| **byte == 0).count() as u64;
+        let non_zeros = calldata.len() as u64 - zeros;
+        let mut gas = self.intrinsic_gas.unwrap_or(Self::INTRINSIC_GAS) +
+            zeros * self.calldata_zero_byte_cost.unwrap_or(Self::CALLDATA_ZERO_BYTE_COST) +
+            non_zeros *
+                self.calldata_nonzero_byte_cost.unwrap_or(Self::CALLDATA_NONZERO_BYTE_COST);
+        if is_create {
+            gas += Self::CREATE_GAS;
+            if evm_version >= EvmVersion::Shanghai {
+                gas += (calldata.len() as u64).div_ceil(32) * Self::INITCODE_WORD_COST;
+            }
+        }
+        gas
+    }
+}
+
+impl fmt::Display for GasOverrides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut overrides = vec![];
+        if let Some(gas) = self.intrinsic_gas {
+            overrides.push(format!("intrinsic_gas = {gas}"));
+        }
+        if let Some(cost) = self.calldata_zero_byte_cost {
+            overrides.push(format!("calldata_zero_byte_cost = {cost}"));
+        }
+        if let Some(cost) = self.calldata_nonzero_byte_cost {
+            overrides.push(format!("calldata_nonzero_byte_cost = {cost}"));
+        }
+        if self.estimate_via_rpc_only {
+            overrides.push("estimate_via_rpc_only = true".to_string());
+        }
+        f.write_str(&overrides.join(", "))
+    }
+}
+
+/// Contains the opt-in checks of the simulated transactions that must pass before `forge script`
+/// broadcasts them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptChecksConfig {
+    /// Whether to refuse broadcasting if the value sent by a sender plus the worst-case gas cost
+    /// of its transactions exceeds its current balance.
+    #[serde(default)]
+    pub sender_balance: bool,
+}
+
+/// An event a broadcast transaction must emit, e.g.
+///
+/// ```toml
+/// [[script.expected_events]]
+/// transaction = "new Proxy"
+/// event = "Upgraded(address indexed implementation)"
+/// emitter = "Proxy"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedEvent {
+    /// The label of the transaction, `new <Contract>` for a deployment or
+    /// `<Contract>::<signature>` for a call, e.g. `Counter::increment()`.
+    pub transaction: String,
+    /// The signature of the event, e.g. `OwnershipTransferred(address,address)`, parameter names
+    /// and `indexed` are allowed.
+    pub event: String,
+    /// The address or the name of the contract emitting the event, any if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emitter: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_gas_overrides() {
+        let calldata = [0, 0, 1, 2];
+        let evm_gas = 21_000 + 2 * 4 + 2 * 16 + 5_000;
+
+        let paris = EvmVersion::Paris;
+        assert_eq!(GasOverrides::default().apply(evm_gas, &calldata, false, paris), evm_gas);
+
+        let overrides = GasOverrides {
+            intrinsic_gas: Some(0),
+            calldata_nonzero_byte_cost: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(overrides.apply(evm_gas, &calldata, false, paris), 2 * 4 + 2 * 100 + 5_000);
+        assert_eq!(overrides.to_string(), "intrinsic_gas = 0, calldata_nonzero_byte_cost = 100");
+
+        // The creation and initcode costs are kept, only the overridden costs change.
+        let shanghai = EvmVersion::Shanghai;
+        let create_gas = 53_000 + 2 * 4 + 2 * 16 + 2 + 5_000;
+        assert_eq!(
+            GasOverrides::default().intrinsic_gas(&calldata, true, shanghai),
+            create_gas - 5_000
+        );
+        assert_eq!(
+            overrides.apply(create_gas, &calldata, true, shanghai),
+            32_000 + 2 * 4 + 2 * 100 + 2 + 5_000
+        );
+        // An execution cheaper than the EVM's creation cost doesn't underflow.
+        assert_eq!(overrides.apply(0, &calldata, true, paris), 32_000 + 2 * 4 + 2 * 100);
+    }
+}
@@ -0,0 +1,47 @@
+//! Configuration for compiling Vyper sources
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf};
+
+/// Contains the config for compiling the `.vy` sources of the project, e.g.
+///
+/// ```toml
+/// [vyper]
+/// version = "0.3.10"
+/// optimize = "gas"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VyperConfig {
+    /// The vyper version to install and compile with.
+    ///
+    /// If neither this nor `path` are set, the `vyper` binary on `PATH` is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    /// The path to a vyper binary, takes precedence over `version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// The optimization mode, the compiler's default if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub optimize: Option<VyperOptimizationMode>,
+}
+
+/// The `--optimize` modes of vyper
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VyperOptimizationMode {
+    None,
+    Gas,
+    Codesize,
+}
+
+impl fmt::Display for VyperOptimizationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self {
+            Self::None => "none",
+            Self::Gas => "gas",
+            Self::Codesize => "codesize",
+        };
+        f.write_str(mode)
+    }
+}
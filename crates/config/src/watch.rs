@@ -0,0 +1,26 @@
+//! Configuration of the watch modes, `forge watch` and the `--watch` flag of other commands
+
+use serde::{Deserialize, Serialize};
+
+/// Contains the config of the file watcher, e.g.
+///
+/// ```toml
+/// [watch]
+/// delay = "500ms"
+/// ignore = ["test/fixtures/**", "**/*.json"]
+/// clear = true
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// The debounce delay of file change events, overridden by `--watch-delay`.
+    ///
+    /// Parses as decimal seconds, or as milliseconds with the `ms` suffix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<String>,
+    /// Globs of the paths, relative to the project root, whose changes don't trigger a run
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Whether to clear the screen before every run
+    #[serde(default)]
+    pub clear: bool,
+}
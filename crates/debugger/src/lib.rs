@@ -10,4 +10,4 @@ extern crate tracing;
 mod op;
 
 mod tui;
-pub use tui::{Debugger, DebuggerBuilder, ExitReason};
+pub use tui::{Debugger, DebuggerBuilder, ExitReason, BREAKPOINTS_FILE};
@@ -0,0 +1,510 @@
+//! Source-level and conditional breakpoints set from the debugger.
+
+use super::Debugger;
+use alloy_primitives::{Selector, U256};
+use eyre::Result;
+use foundry_compilers::sourcemap::SourceMap;
+use foundry_evm_core::debug::{DebugNodeFlat, DebugStep};
+use revm_inspectors::tracing::types::CallKind;
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The name of the file in the cache directory that the breakpoints are persisted to.
+pub const BREAKPOINTS_FILE: &str = "debugger-breakpoints.json";
+
+/// A breakpoint set from the debugger, e.g. `src/Counter.sol:12 if stack[0] == 0`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SourceBreakpoint {
+    pub(crate) location: BreakpointLocation,
+    pub(crate) condition: Option<Condition>,
+}
+
+impl SourceBreakpoint {
+    /// Returns whether the breakpoint refers to a source file or contract of the loaded artifacts.
+    pub(crate) fn is_resolved(&self, debugger: &Debugger) -> bool {
+        match &self.location {
+            BreakpointLocation::Line { path, line } => {
+                debugger.contracts_sources.paths_by_id.iter().any(|(id, source_path)| {
+                    paths_match(source_path, path) &&
+                        debugger
+                            .contracts_sources
+                            .get(*id)
+                            .is_some_and(|(source, _)| *line <= source.lines().count())
+                })
+            }
+            BreakpointLocation::Function { contract, function } => {
+                debugger.identified_contracts.values().any(|name| name == contract) &&
+                    debugger.functions.values().flatten().any(|f| f.name == *function)
+            }
+        }
+    }
+
+    /// Returns whether the breakpoint is hit at the given step of the call.
+    ///
+    /// Line breakpoints are only hit on the first opcode of a run of opcodes mapped to the line,
+    /// function breakpoints on the first opcode of a call to the function.
+    pub(crate) fn is_hit(
+        &self,
+        locator: &mut SourceLocator<'_>,
+        node: &DebugNodeFlat,
+        step: usize,
+    ) -> bool {
+        let debugger = locator.debugger;
+        let hit = match &self.location {
+            BreakpointLocation::Line { path, line } => {
+                let Some(location) = locator.location(node, node.steps[step].pc) else {
+                    return false;
+                };
+                location.1 == *line &&
+                    debugger
+                        .contracts_sources
+                        .paths_by_id
+                        .get(&location.0)
+                        .is_some_and(|source_path| paths_match(source_path, path)) &&
+                    (step == 0 ||
+                        locator.location(node, node.steps[step - 1].pc) != Some(location))
+            }
+            BreakpointLocation::Function { contract, function } => {
+                step == 0 &&
+                    !matches!(node.kind, CallKind::Create | CallKind::Create2) &&
+                    debugger.identified_contracts.get(&node.address) == Some(contract) &&
+                    node.steps[0]
+                        .calldata
+                        .get(..4)
+                        .and_then(|selector| Selector::try_from(selector).ok())
+                        .and_then(|selector| debugger.functions.get(&selector))
+                        .is_some_and(|functions| functions.iter().any(|f| f.name == *function))
+            }
+        };
+        hit && self.condition.as_ref().map_or(true, |condition| condition.eval(&node.steps[step]))
+    }
+}
+
+impl FromStr for SourceBreakpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (location, condition) = match s.split_once(" if ") {
+            Some((location, condition)) => (location, Some(condition.parse()?)),
+            None => (s, None),
+        };
+        Ok(Self { location: location.parse()?, condition })
+    }
+}
+
+impl fmt::Display for SourceBreakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.location.fmt(f)?;
+        if let Some(condition) = &self.condition {
+            write!(f, " if {condition}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a [SourceBreakpoint] stops.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum BreakpointLocation {
+    /// `path/to/File.sol:123`, the path may be any suffix of the source path.
+    Line { path: PathBuf, line: usize },
+    /// `Contract.function` or `Contract::function`, stops on external calls to the function.
+    Function { contract: String, function: String },
+}
+
+impl FromStr for BreakpointLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some((path, line)) = s.rsplit_once(':').filter(|(path, _)| !path.ends_with(':')) {
+            let line = line
+                .trim()
+                .parse()
+                .ok()
+                .filter(|line| *line > 0)
+                .ok_or_else(|| format!("invalid line number `{line}`"))?;
+            if path.is_empty() {
+                return Err("missing source file path".to_string());
+            }
+            return Ok(Self::Line { path: path.into(), line });
+        }
+
+        let err = || format!("expected `<path>:<line>` or `<contract>.<function>`, found `{s}`");
+        if s.ends_with(".sol") {
+            return Err(err());
+        }
+        let (contract, function) =
+            s.split_once("::").or_else(|| s.split_once('.')).ok_or_else(err)?;
+        if !is_identifier(contract) || !is_identifier(function) {
+            return Err(err());
+        }
+        Ok(Self::Function { contract: contract.to_string(), function: function.to_string() })
+    }
+}
+
+impl fmt::Display for BreakpointLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Line { path, line } => write!(f, "{}:{line}", path.display()),
+            Self::Function { contract, function } => write!(f, "{contract}.{function}"),
+        }
+    }
+}
+
+/// A comparison of two operands, e.g. `stack[0] == 0`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Condition {
+    lhs: Operand,
+    op: CmpOp,
+    rhs: Operand,
+}
+
+impl Condition {
+    /// Evaluates the condition at the given step, operands that are out of bounds never match.
+    pub(crate) fn eval(&self, step: &DebugStep) -> bool {
+        let (Some(lhs), Some(rhs)) = (self.lhs.eval(step), self.rhs.eval(step)) else {
+            return false;
+        };
+        match self.op {
+            CmpOp::Eq => lhs == rhs,
+            CmpOp::Ne => lhs != rhs,
+            CmpOp::Lt => lhs < rhs,
+            CmpOp::Le => lhs <= rhs,
+            CmpOp::Gt => lhs > rhs,
+            CmpOp::Ge => lhs >= rhs,
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, op) = CmpOp::ALL
+            .iter()
+            .filter_map(|op| Some((s.find(op.as_str())?, *op)))
+            // on ties, the two-char operators come first
+            .min_by_key(|(index, _)| *index)
+            .ok_or_else(|| format!("expected a comparison, e.g. `stack[0] == 0`, found `{s}`"))?;
+        let lhs = s[..index].parse()?;
+        let rhs = s[index + op.as_str().len()..].parse()?;
+        Ok(Self { lhs, op, rhs })
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.lhs, self.op.as_str(), self.rhs)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Le,
+    Ge,
+    Lt,
+    Gt,
+}
+
+impl CmpOp {
+    const ALL: [Self; 6] = [Self::Eq, Self::Ne, Self::Le, Self::Ge, Self::Lt, Self::Gt];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Le => "<=",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Gt => ">",
+        }
+    }
+}
+
+/// An operand of a [Condition].
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Operand {
+    /// `stack[i]`, indexed from the top of the stack like the stack pane.
+    Stack(usize),
+    /// `memory[offset]`, the 32 byte word at the offset.
+    Memory(usize),
+    Pc,
+    GasUsed,
+    Msize,
+    CalldataSize,
+    ReturndataSize,
+    Literal(U256),
+}
+
+impl Operand {
+    fn eval(&self, step: &DebugStep) -> Option<U256> {
+        Some(match *self {
+            Self::Stack(i) => *step.stack.iter().rev().nth(i)?,
+            Self::Memory(offset) => {
+                let mut word = [0u8; 32];
+                let memory = step.memory.get(offset..).unwrap_or_default();
+                let len = memory.len().min(32);
+                word[..len].copy_from_slice(&memory[..len]);
+                U256::from_be_bytes(word)
+            }
+            Self::Pc => U256::from(step.pc),
+            Self::GasUsed => U256::from(step.total_gas_used),
+            Self::Msize => U256::from(step.memory.len()),
+            Self::CalldataSize => U256::from(step.calldata.len()),
+            Self::ReturndataSize => U256::from(step.returndata.len()),
+            Self::Literal(value) => value,
+        })
+    }
+}
+
+impl FromStr for Operand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let index = |inner: &str| {
+            inner
+                .parse::<U256>()
+                .ok()
+                .and_then(|index| usize::try_from(index).ok())
+                .ok_or_else(|| format!("invalid index in `{s}`"))
+        };
+        Ok(match s {
+            "pc" => Self::Pc,
+            "gas_used" => Self::GasUsed,
+            "msize" => Self::Msize,
+            "calldatasize" => Self::CalldataSize,
+            "returndatasize" => Self::ReturndataSize,
+            _ => {
+                if let Some(inner) = s.strip_prefix("stack[").and_then(|s| s.strip_suffix(']')) {
+                    Self::Stack(index(inner)?)
+                } else if let Some(inner) =
+                    s.strip_prefix("memory[").and_then(|s| s.strip_suffix(']'))
+                {
+                    Self::Memory(index(inner)?)
+                } else {
+                    Self::Literal(s.parse().map_err(|_| {
+                        format!(
+                            "invalid operand `{s}`, expected a number, `stack[i]`, `memory[offset]`, \
+                             `pc`, `gas_used`, `msize`, `calldatasize` or `returndatasize`"
+                        )
+                    })?)
+                }
+            }
+        })
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stack(i) => write!(f, "stack[{i}]"),
+            Self::Memory(offset) => write!(f, "memory[{offset:#x}]"),
+            Self::Pc => f.write_str("pc"),
+            Self::GasUsed => f.write_str("gas_used"),
+            Self::Msize => f.write_str("msize"),
+            Self::CalldataSize => f.write_str("calldatasize"),
+            Self::ReturndataSize => f.write_str("returndatasize"),
+            Self::Literal(value) => value.fmt(f),
+        }
+    }
+}
+
+/// The breakpoints set from the debugger, persisted across sessions in a JSON file.
+#[derive(Debug, Default)]
+pub(crate) struct SourceBreakpoints {
+    pub(crate) list: Vec<SourceBreakpoint>,
+    file: Option<PathBuf>,
+}
+
+impl SourceBreakpoints {
+    /// Loads the breakpoints from the file, if it exists.
+    pub(crate) fn load(file: Option<PathBuf>) -> Self {
+        let list = file
+            .as_deref()
+            .filter(|file| file.exists())
+            .and_then(|file| match foundry_common::fs::read_json_file::<Vec<String>>(file) {
+                Ok(list) => Some(list),
+                Err(err) => {
+                    warn!(%err, "failed to read the debugger breakpoints");
+                    None
+                }
+            })
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|breakpoint| match breakpoint.parse() {
+                Ok(breakpoint) => Some(breakpoint),
+                Err(err) => {
+                    warn!(%err, breakpoint, "skipping invalid debugger breakpoint");
+                    None
+                }
+            })
+            .collect();
+        Self { list, file }
+    }
+
+    /// Writes the breakpoints to the file.
+    pub(crate) fn save(&self) -> Result<()> {
+        let Some(file) = &self.file else { return Ok(()) };
+        if let Some(parent) = file.parent() {
+            foundry_common::fs::create_dir_all(parent)?;
+        }
+        let list: Vec<_> = self.list.iter().map(ToString::to_string).collect();
+        foundry_common::fs::write_json_file(file, &list)?;
+        Ok(())
+    }
+}
+
+/// Maps the steps of the debug arena to the source file and line they were compiled from.
+pub(crate) struct SourceLocator<'a> {
+    debugger: &'a Debugger,
+    source_maps: HashMap<(&'a str, bool), Vec<SourceMap>>,
+    line_starts: HashMap<u32, Vec<usize>>,
+}
+
+impl<'a> SourceLocator<'a> {
+    pub(crate) fn new(debugger: &'a Debugger) -> Self {
+        Self { debugger, source_maps: HashMap::new(), line_starts: HashMap::new() }
+    }
+
+    /// Returns the file ID and the 1-based line of the opcode at `pc` in the call.
+    pub(crate) fn location(&mut self, node: &DebugNodeFlat, pc: usize) -> Option<(u32, usize)> {
+        let debugger = self.debugger;
+        let contract_name = debugger.identified_contracts.get(&node.address)?;
+        let is_create = matches!(node.kind, CallKind::Create | CallKind::Create2);
+        let (create_map, rt_map) = debugger.pc_ic_maps.get(contract_name)?;
+        let ic = if is_create { create_map } else { rt_map }.get(pc)?;
+
+        let source_maps =
+            self.source_maps.entry((contract_name.as_str(), is_create)).or_insert_with(|| {
+                debugger
+                    .contracts_sources
+                    .get_sources(contract_name)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(_, (_, contract))| {
+                        let bytecode = if is_create {
+                            &contract.bytecode
+                        } else {
+                            contract.deployed_bytecode.bytecode.as_ref()?
+                        };
+                        bytecode.source_map()?.ok()
+                    })
+                    .collect()
+            });
+        let element = source_maps
+            .iter()
+            .find_map(|source_map| source_map.get(ic).filter(|element| element.index.is_some()))?;
+
+        let file_id = element.index?;
+        let (source, _) = debugger.contracts_sources.get(file_id)?;
+        let line_starts = self.line_starts.entry(file_id).or_insert_with(|| line_starts(source));
+        Some((file_id, line_starts.partition_point(|start| *start <= element.offset)))
+    }
+}
+
+/// Returns the byte offsets at which the lines of the source start.
+fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1)).collect()
+}
+
+/// Returns whether the path of a source file matches the path of a breakpoint, either may be a
+/// suffix of the other.
+fn paths_match(source_path: &Path, path: &Path) -> bool {
+    source_path.ends_with(path) || path.ends_with(source_path)
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() &&
+        !s.starts_with(|c: char| c.is_ascii_digit()) &&
+        s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_breakpoints() {
+        let breakpoint: SourceBreakpoint = "src/Counter.sol:12".parse().unwrap();
+        assert_eq!(
+            breakpoint,
+            SourceBreakpoint {
+                location: BreakpointLocation::Line { path: "src/Counter.sol".into(), line: 12 },
+                condition: None
+            }
+        );
+
+        let breakpoint: SourceBreakpoint =
+            "Counter::increment if stack[0] >= 0x10".parse().unwrap();
+        assert_eq!(
+            breakpoint,
+            SourceBreakpoint {
+                location: BreakpointLocation::Function {
+                    contract: "Counter".to_string(),
+                    function: "increment".to_string()
+                },
+                condition: Some(Condition {
+                    lhs: Operand::Stack(0),
+                    op: CmpOp::Ge,
+                    rhs: Operand::Literal(U256::from(16))
+                })
+            }
+        );
+        assert_eq!(breakpoint.to_string(), "Counter.increment if stack[0] >= 16");
+
+        for s in ["src/A.sol:12 if memory[0x40] != 0x80", "C:\\src\\A.sol:3 if pc < 10"] {
+            let breakpoint: SourceBreakpoint = s.parse().unwrap();
+            assert_eq!(breakpoint.to_string().parse::<SourceBreakpoint>().unwrap(), breakpoint);
+        }
+
+        for s in [
+            "src/Counter.sol",
+            "src/Counter.sol:0",
+            ":12",
+            "Counter",
+            "Counter.1nc",
+            "src/A.sol:1 if stack[0]",
+            "src/A.sol:1 if foo == 1",
+        ] {
+            assert!(s.parse::<SourceBreakpoint>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn can_eval_conditions() {
+        let step = DebugStep {
+            stack: vec![U256::from(1), U256::from(2)],
+            memory: [vec![0; 31], vec![0x80]].concat(),
+            pc: 7,
+            ..Default::default()
+        };
+        let eval = |s: &str| s.parse::<Condition>().unwrap().eval(&step);
+        assert!(eval("stack[0] == 2"));
+        assert!(eval("stack[1]<stack[0]"));
+        assert!(eval("memory[0] == 0x80"));
+        assert!(eval("memory[1] == 0x8000"));
+        assert!(eval("msize <= 32"));
+        assert!(eval("pc > 6"));
+        assert!(!eval("pc != 7"));
+        // out of bounds
+        assert!(!eval("stack[2] == 0"));
+    }
+
+    #[test]
+    fn can_find_lines() {
+        let starts = line_starts("a\nbc\n\nd");
+        assert_eq!(starts, [0, 2, 5, 6]);
+        assert_eq!(starts.partition_point(|start| *start <= 0), 1);
+        assert_eq!(starts.partition_point(|start| *start <= 3), 2);
+        assert_eq!(starts.partition_point(|start| *start <= 6), 4);
+        assert!(paths_match(Path::new("src/Counter.sol"), Path::new("Counter.sol")));
+        assert!(!paths_match(Path::new("src/Counter.sol"), Path::new("ounter.sol")));
+    }
+}
@@ -1,11 +1,16 @@
 //! TUI debugger builder.
 
+use super::SourceBreakpoints;
 use crate::Debugger;
-use alloy_primitives::Address;
+use alloy_json_abi::Function;
+use alloy_primitives::{Address, Selector};
 use foundry_common::{compile::ContractSources, evm::Breakpoints, get_contract_name};
-use foundry_evm_core::debug::{DebugArena, DebugNodeFlat};
+use foundry_evm_core::{
+    debug::{DebugArena, DebugNodeFlat},
+    decode::RevertDecoder,
+};
 use foundry_evm_traces::CallTraceDecoder;
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
 /// Debugger builder.
 #[derive(Debug, Default)]
@@ -15,10 +20,16 @@ pub struct DebuggerBuilder {
     debug_arena: Vec<DebugNodeFlat>,
     /// Identified contracts.
     identified_contracts: HashMap<Address, String>,
+    /// Known functions, used to decode calldata and returndata.
+    functions: HashMap<Selector, Vec<Function>>,
+    /// Decoder of revert data and custom errors.
+    revert_decoder: RevertDecoder,
     /// Map of source files.
     sources: ContractSources,
     /// Map of the debugger breakpoints.
     breakpoints: Breakpoints,
+    /// File to persist the breakpoints set from the debugger to.
+    breakpoints_file: Option<PathBuf>,
 }
 
 impl DebuggerBuilder {
@@ -53,9 +64,22 @@ impl DebuggerBuilder {
         self
     }
 
-    /// Extends the identified contracts from a decoder.
+    /// Extends the identified contracts, the known functions and errors from a decoder.
     #[inline]
-    pub fn decoder(self, decoder: &CallTraceDecoder) -> Self {
+    pub fn decoder(mut self, decoder: &CallTraceDecoder) -> Self {
+        for (selector, functions) in &decoder.functions {
+            let known = self.functions.entry(*selector).or_default();
+            for function in functions {
+                if !known.contains(function) {
+                    known.push(function.clone());
+                }
+            }
+        }
+        for error in decoder.revert_decoder.errors.values().flatten() {
+            if !self.revert_decoder.errors.values().flatten().any(|known| known == error) {
+                self.revert_decoder.push_error(error.clone());
+            }
+        }
         let c = decoder.contracts.iter().map(|(k, v)| (*k, get_contract_name(v).to_string()));
         self.identified_contracts(c)
     }
@@ -84,10 +108,29 @@ impl DebuggerBuilder {
         self
     }
 
+    /// Sets the file that the breakpoints set from the debugger are loaded from and saved to.
+    #[inline]
+    pub fn breakpoints_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.breakpoints_file = Some(file.into());
+        self
+    }
+
     /// Builds the debugger.
     #[inline]
     pub fn build(self) -> Debugger {
-        let Self { debug_arena, identified_contracts, sources, breakpoints } = self;
-        Debugger::new(debug_arena, identified_contracts, sources, breakpoints)
+        let Self {
+            debug_arena,
+            identified_contracts,
+            functions,
+            revert_decoder,
+            sources,
+            breakpoints,
+            breakpoints_file,
+        } = self;
+        let mut debugger = Debugger::new(debug_arena, identified_contracts, sources, breakpoints);
+        debugger.functions = functions;
+        debugger.revert_decoder = revert_decoder;
+        debugger.source_breakpoints = SourceBreakpoints::load(breakpoints_file);
+        debugger
     }
 }
@@ -1,11 +1,12 @@
 //! Debugger context and event handler implementation.
 
+use super::breakpoints::{SourceBreakpoint, SourceLocator};
 use crate::{Debugger, ExitReason};
-use alloy_primitives::Address;
+use alloy_primitives::{Address, B256, U256};
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 use foundry_evm_core::debug::{DebugNodeFlat, DebugStep};
 use revm_inspectors::tracing::types::CallKind;
-use std::{cell::RefCell, ops::ControlFlow};
+use std::{cell::RefCell, fmt, ops::ControlFlow};
 
 /// This is currently used to remember last scroll position so screen doesn't wiggle as much.
 #[derive(Default)]
@@ -45,6 +46,61 @@ impl BufferKind {
     }
 }
 
+/// How the words of the active buffer are decoded, next to their hex bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum WordFormat {
+    #[default]
+    Hex,
+    Utf8,
+    Uint,
+    Address,
+}
+
+impl WordFormat {
+    /// Helper to cycle through the formats.
+    pub(crate) fn next(self) -> Self {
+        match self {
+            WordFormat::Hex => WordFormat::Utf8,
+            WordFormat::Utf8 => WordFormat::Uint,
+            WordFormat::Uint => WordFormat::Address,
+            WordFormat::Address => WordFormat::Hex,
+        }
+    }
+
+    /// Decodes a word of the buffer, which is shorter than 32 bytes at the end of the buffer.
+    ///
+    /// Returns `None` for [WordFormat::Hex], since the bytes are always displayed.
+    pub(crate) fn decode(self, word: &[u8]) -> Option<String> {
+        match self {
+            WordFormat::Hex => None,
+            WordFormat::Utf8 => Some(
+                word.chunks(4)
+                    .map(|chunk| match std::str::from_utf8(chunk) {
+                        Ok(s) => s.replace('\0', "."),
+                        Err(_) => ".".to_string(),
+                    })
+                    .collect(),
+            ),
+            WordFormat::Uint => Some(U256::from_be_slice(word).to_string()),
+            WordFormat::Address => Some(match word.len() {
+                32 => Address::from_word(B256::from_slice(word)).to_string(),
+                _ => "-".to_string(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for WordFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WordFormat::Hex => f.write_str("hex"),
+            WordFormat::Utf8 => f.write_str("utf8"),
+            WordFormat::Uint => f.write_str("uint"),
+            WordFormat::Address => f.write_str("address"),
+        }
+    }
+}
+
 pub(crate) struct DebuggerContext<'a> {
     pub(crate) debugger: &'a mut Debugger,
 
@@ -57,11 +113,19 @@ pub(crate) struct DebuggerContext<'a> {
     pub(crate) last_index: usize,
 
     pub(crate) stack_labels: bool,
-    /// Whether to decode active buffer as utf8 or not.
-    pub(crate) buf_utf: bool,
+    /// How to decode the words of the active buffer.
+    pub(crate) buf_format: WordFormat,
+    /// Whether to decode the calldata and returndata against the current function.
+    pub(crate) buf_decoded: bool,
     pub(crate) show_shortcuts: bool,
     /// The currently active buffer (memory, calldata, returndata) to be drawn.
     pub(crate) active_buffer: BufferKind,
+    /// Whether to show the breakpoints panel.
+    pub(crate) show_breakpoints: bool,
+    /// The command being typed after `:`, e.g. `break src/Counter.sol:12`.
+    pub(crate) command: Option<String>,
+    /// The outcome of the last command.
+    pub(crate) command_status: Option<String>,
 }
 
 impl<'a> DebuggerContext<'a> {
@@ -76,9 +140,13 @@ impl<'a> DebuggerContext<'a> {
             last_index: 0,
 
             stack_labels: false,
-            buf_utf: false,
+            buf_format: WordFormat::Hex,
+            buf_decoded: false,
             show_shortcuts: true,
             active_buffer: BufferKind::Memory,
+            show_breakpoints: false,
+            command: None,
+            command_status: None,
         }
     }
 
@@ -146,6 +214,12 @@ impl DebuggerContext<'_> {
     }
 
     fn handle_key_event(&mut self, event: KeyEvent) -> ControlFlow<ExitReason> {
+        if self.command.is_some() {
+            self.handle_command_key(event);
+            return ControlFlow::Continue(());
+        }
+        self.command_status = None;
+
         if let KeyCode::Char(c) = event.code {
             if c.is_alphabetic() && self.key_buffer.starts_with('\'') {
                 self.handle_breakpoint(c);
@@ -228,9 +302,12 @@ impl DebuggerContext<'_> {
                 self.current_step = self.debug_steps().len() - 1;
                 self.key_buffer.clear();
             }
-            // Go to next call
+            // Go to the next breakpoint, or to the next call if none is hit
             KeyCode::Char('C') => {
-                if self.debug_arena().len() > self.draw_memory.inner_call_index + 1 {
+                if let Some((call, step)) = self.next_source_breakpoint() {
+                    self.draw_memory.inner_call_index = call;
+                    self.current_step = step;
+                } else if self.debug_arena().len() > self.draw_memory.inner_call_index + 1 {
                     self.draw_memory.inner_call_index += 1;
                     self.current_step = 0;
                 }
@@ -291,10 +368,20 @@ impl DebuggerContext<'_> {
             }
             // toggle stack labels
             KeyCode::Char('t') => self.stack_labels = !self.stack_labels,
-            // toggle memory utf8 decoding
-            KeyCode::Char('m') => self.buf_utf = !self.buf_utf,
+            // cycle the decoding of the buffer words
+            KeyCode::Char('m') => self.buf_format = self.buf_format.next(),
+            // toggle calldata and returndata ABI decoding
+            KeyCode::Char('d') => self.buf_decoded = !self.buf_decoded,
             // toggle help notice
             KeyCode::Char('h') => self.show_shortcuts = !self.show_shortcuts,
+            // toggle breakpoints panel
+            KeyCode::Char('B') => self.show_breakpoints = !self.show_breakpoints,
+            // start typing a command
+            KeyCode::Char(':') => {
+                self.command = Some(String::new());
+                self.show_breakpoints = true;
+                self.key_buffer.clear();
+            }
             KeyCode::Char(
                 other @ ('0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' | '\''),
             ) => self.key_buffer.push(other),
@@ -321,6 +408,85 @@ impl DebuggerContext<'_> {
         self.key_buffer.clear();
     }
 
+    fn handle_command_key(&mut self, event: KeyEvent) {
+        let Some(command) = &mut self.command else { return };
+        match event.code {
+            KeyCode::Char(c) => command.push(c),
+            KeyCode::Backspace => {
+                if command.pop().is_none() {
+                    self.command = None;
+                }
+            }
+            KeyCode::Esc => self.command = None,
+            KeyCode::Enter => {
+                let command = self.command.take().unwrap_or_default();
+                self.command_status = Some(self.run_command(&command).unwrap_or_else(|e| e));
+            }
+            _ => {}
+        }
+    }
+
+    /// Runs a command typed after `:`, returning its outcome.
+    fn run_command(&mut self, command: &str) -> Result<String, String> {
+        let (name, args) = command.trim().split_once(' ').unwrap_or((command.trim(), ""));
+        let status = match name {
+            "b" | "break" => {
+                let breakpoint: SourceBreakpoint = args.parse()?;
+                let resolved = breakpoint.is_resolved(self.debugger);
+                let status = format!(
+                    "Breakpoint {} set at {breakpoint}{}",
+                    self.debugger.source_breakpoints.list.len() + 1,
+                    if resolved { "" } else { " (unresolved)" }
+                );
+                self.debugger.source_breakpoints.list.push(breakpoint);
+                status
+            }
+            "d" | "delete" => {
+                let index = args
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|index| {
+                        (1..=self.debugger.source_breakpoints.list.len()).contains(index)
+                    })
+                    .ok_or_else(|| format!("No breakpoint `{}`", args.trim()))?;
+                let breakpoint = self.debugger.source_breakpoints.list.remove(index - 1);
+                format!("Deleted breakpoint {index} at {breakpoint}")
+            }
+            "clear" => {
+                self.debugger.source_breakpoints.list.clear();
+                "Deleted all breakpoints".to_string()
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown command `{name}`, expected `break <path>:<line>|<contract>.<function> \
+                     [if <condition>]`, `delete <n>` or `clear`"
+                ))
+            }
+        };
+        self.debugger
+            .source_breakpoints
+            .save()
+            .map_err(|e| format!("{status}, but failed to save the breakpoints: {e}"))?;
+        Ok(status)
+    }
+
+    /// Returns the call and step of the next breakpoint hit after the current step.
+    fn next_source_breakpoint(&self) -> Option<(usize, usize)> {
+        let breakpoints = &self.debugger.source_breakpoints.list;
+        if breakpoints.is_empty() {
+            return None;
+        }
+        let mut locator = SourceLocator::new(self.debugger);
+        let (current_call, current_step) = (self.draw_memory.inner_call_index, self.current_step);
+        self.debug_arena().iter().enumerate().skip(current_call).find_map(|(call, node)| {
+            let first = if call == current_call { current_step + 1 } else { 0 };
+            (first..node.steps.len())
+                .find(|&step| breakpoints.iter().any(|bp| bp.is_hit(&mut locator, node, step)))
+                .map(|step| (call, step))
+        })
+    }
+
     fn handle_mouse_event(&mut self, event: MouseEvent) -> ControlFlow<ExitReason> {
         match event.kind {
             MouseEventKind::ScrollUp => {
@@ -357,3 +523,22 @@ fn buffer_as_number(s: &str, default_value: usize) -> usize {
         _ => default_value,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_decode_words() {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(&[0x11; 20]);
+        assert_eq!(WordFormat::Hex.decode(&word), None);
+        assert_eq!(
+            WordFormat::Address.decode(&word).unwrap(),
+            Address::repeat_byte(0x11).to_string()
+        );
+        assert_eq!(WordFormat::Address.decode(&word[..4]).unwrap(), "-");
+        assert_eq!(WordFormat::Uint.decode(&[0x01, 0x00]).unwrap(), "256");
+        assert_eq!(WordFormat::Utf8.decode(b"abcd\0\0\0\0").unwrap(), "abcd....");
+    }
+}
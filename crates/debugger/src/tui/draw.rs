@@ -1,8 +1,11 @@
 //! TUI draw implementation.
 
-use super::context::{BufferKind, DebuggerContext};
+use super::context::{BufferKind, DebuggerContext, WordFormat};
 use crate::op::OpcodeParam;
-use alloy_primitives::U256;
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::{Function, Param};
+use alloy_primitives::{hex, Selector, U256};
+use foundry_common::fmt::format_token;
 use foundry_compilers::sourcemap::SourceElement;
 use foundry_evm_core::debug::Instruction;
 use ratatui::{
@@ -117,6 +120,10 @@ impl DebuggerContext<'_> {
         if self.show_shortcuts {
             self.draw_footer(f, footer);
         }
+        let (src_pane, breakpoints_pane) = self.split_breakpoints_pane(src_pane);
+        if let Some(breakpoints_pane) = breakpoints_pane {
+            self.draw_breakpoints(f, breakpoints_pane);
+        }
         self.draw_src(f, src_pane);
         self.draw_op_list(f, op_pane);
         self.draw_stack(f, stack_pane);
@@ -177,15 +184,34 @@ impl DebuggerContext<'_> {
         if self.show_shortcuts {
             self.draw_footer(f, footer);
         }
+        let (src_pane, breakpoints_pane) = self.split_breakpoints_pane(src_pane);
+        if let Some(breakpoints_pane) = breakpoints_pane {
+            self.draw_breakpoints(f, breakpoints_pane);
+        }
         self.draw_src(f, src_pane);
         self.draw_op_list(f, op_pane);
         self.draw_stack(f, stack_pane);
         self.draw_buffer(f, memory_pane);
     }
 
+    /// Splits the breakpoints panel off the bottom of the source pane, if it's shown.
+    fn split_breakpoints_pane(&self, area: Rect) -> (Rect, Option<Rect>) {
+        if !self.show_breakpoints {
+            return (area, None);
+        }
+        let [src_pane, breakpoints_pane] = Layout::new()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Ratio(3, 4), Constraint::Ratio(1, 4)])
+            .split(area)[..]
+        else {
+            unreachable!()
+        };
+        (src_pane, Some(breakpoints_pane))
+    }
+
     fn draw_footer(&self, f: &mut Frame<'_>, area: Rect) {
-        let l1 = "[q]: quit | [k/j]: prev/next op | [a/s]: prev/next jump | [c/C]: prev/next call | [g/G]: start/end | [b]: cycle memory/calldata/returndata buffers";
-        let l2 = "[t]: stack labels | [m]: buffer decoding | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll buffer | ['<char>]: goto breakpoint | [h] toggle help";
+        let l1 = "[q]: quit | [k/j]: prev/next op | [a/s]: prev/next jump | [c/C]: prev call/next call or breakpoint | [g/G]: start/end | [b]: cycle memory/calldata/returndata buffers";
+        let l2 = "[t]: stack labels | [m]: cycle hex/utf8/uint/address words | [d]: decode calldata/returndata | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll buffer | ['<char>]: goto breakpoint | [:]: break/delete/clear breakpoints | [B]: toggle breakpoints | [h] toggle help";
         let dimmed = Style::new().add_modifier(Modifier::DIM);
         let lines =
             vec![Line::from(Span::styled(l1, dimmed)), Line::from(Span::styled(l2, dimmed))];
@@ -194,6 +220,39 @@ impl DebuggerContext<'_> {
         f.render_widget(paragraph, area);
     }
 
+    fn draw_breakpoints(&self, f: &mut Frame<'_>, area: Rect) {
+        let breakpoints = &self.debugger.source_breakpoints.list;
+        let mut lines: Vec<Line> = breakpoints
+            .iter()
+            .enumerate()
+            .map(|(i, breakpoint)| {
+                let mut spans = vec![
+                    Span::styled(format!("{:>2}| ", i + 1), Style::new().fg(Color::White)),
+                    Span::raw(breakpoint.to_string()),
+                ];
+                if !breakpoint.is_resolved(self.debugger) {
+                    spans.push(Span::styled(" (unresolved)", Style::new().fg(Color::Red)));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No breakpoints, set one with `:break <path>:<line>|<contract>.<function> [if <condition>]`",
+                Style::new().add_modifier(Modifier::DIM),
+            )));
+        }
+
+        let title = match (&self.command, &self.command_status) {
+            (Some(command), _) => format!("Breakpoints | :{command}█"),
+            (None, Some(status)) => format!("Breakpoints | {status}"),
+            (None, None) => format!("Breakpoints: {}", breakpoints.len()),
+        };
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+    }
+
     fn draw_src(&self, f: &mut Frame<'_>, area: Rect) {
         let text_output = self.src_text(area);
         let title = match self.call_kind() {
@@ -501,6 +560,11 @@ impl DebuggerContext<'_> {
     }
 
     fn draw_buffer(&self, f: &mut Frame<'_>, area: Rect) {
+        if self.buf_decoded && self.active_buffer != BufferKind::Memory {
+            self.draw_decoded_buffer(f, area);
+            return;
+        }
+
         let step = self.current_step();
         let buf = match self.active_buffer {
             BufferKind::Memory => &step.memory,
@@ -555,6 +619,15 @@ impl DebuggerContext<'_> {
             }
         }
 
+        // Label the memory regions referenced by the stack arguments of the current op. These are
+        // derived from the current step alone, so they stay correct when stepping backwards.
+        let regions = match step.instruction {
+            Instruction::OpCode(op) if self.active_buffer == BufferKind::Memory => {
+                memory_regions(op, &step.stack)
+            }
+            _ => Vec::new(),
+        };
+
         let height = area.height as usize;
         let end_line = self.draw_memory.current_buf_startline + height;
 
@@ -612,15 +685,22 @@ impl DebuggerContext<'_> {
                     Style::new().fg(byte_color)
                 });
 
-                if self.buf_utf {
+                if let Some(decoded) = self.buf_format.decode(buf_word) {
                     spans.push(Span::raw("|"));
-                    for utf in buf_word.chunks(4) {
-                        if let Ok(utf_str) = std::str::from_utf8(utf) {
-                            spans.push(Span::raw(utf_str.replace('\0', ".")));
-                        } else {
-                            spans.push(Span::raw("."));
-                        }
-                    }
+                    spans.push(Span::raw(decoded));
+                }
+
+                let word = i * 32..i * 32 + buf_word.len();
+                let labels: Vec<_> = regions
+                    .iter()
+                    .filter(|region| region.overlaps(&word))
+                    .map(|region| region.label)
+                    .collect();
+                if !labels.is_empty() {
+                    spans.push(Span::styled(
+                        format!(" ◀ {}", labels.join(", ")),
+                        Style::new().fg(Color::Magenta),
+                    ));
                 }
 
                 spans.push(Span::raw("\n"));
@@ -629,11 +709,169 @@ impl DebuggerContext<'_> {
             })
             .collect();
 
-        let title = self.active_buffer.title(buf.len());
+        let mut title = self.active_buffer.title(buf.len());
+        for region in &regions {
+            write!(title, " | {region}").unwrap();
+        }
+        if self.buf_format != WordFormat::Hex {
+            write!(title, " | words: {}", self.buf_format).unwrap();
+        }
         let block = Block::default().title(title).borders(Borders::ALL);
         let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
         f.render_widget(paragraph, area);
     }
+
+    /// Draws the calldata or the returned data ABI-decoded against the current function.
+    fn draw_decoded_buffer(&self, f: &mut Frame<'_>, area: Rect) {
+        let (title, lines) = match self.active_buffer {
+            BufferKind::Returndata => ("Returned data (decoded)", self.decoded_returndata()),
+            _ => ("Calldata (decoded)", self.decoded_calldata()),
+        };
+        let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+        f.render_widget(paragraph, area);
+    }
+
+    /// Returns the function of the current call, resolved from the selector of its calldata.
+    fn current_function(&self) -> Option<&Function> {
+        if matches!(self.call_kind(), CallKind::Create | CallKind::Create2) {
+            return None;
+        }
+        let calldata = &self.current_step().calldata;
+        let selector = Selector::try_from(calldata.get(..4)?).ok()?;
+        let functions = self.debugger.functions.get(&selector)?;
+        // prefer the function that can decode the calldata on selector collisions
+        functions
+            .iter()
+            .find(|function| function.abi_decode_input(&calldata[4..], false).is_ok())
+            .or_else(|| functions.first())
+    }
+
+    fn decoded_calldata(&self) -> Vec<String> {
+        if matches!(self.call_kind(), CallKind::Create | CallKind::Create2) {
+            return vec!["Contract creation, the calldata is the init code".to_string()];
+        }
+        let calldata = &self.current_step().calldata;
+        if calldata.len() < 4 {
+            return vec!["No function selector in calldata".to_string()];
+        }
+        let Some(function) = self.current_function() else {
+            return vec![format!(
+                "Unknown function selector {}",
+                hex::encode_prefixed(&calldata[..4])
+            )];
+        };
+
+        let mut lines = vec![function.signature()];
+        match function.abi_decode_input(&calldata[4..], false) {
+            Ok(values) => lines.extend(decoded_params(&function.inputs, &values)),
+            Err(err) => lines.push(format!("Failed to decode calldata: {err}")),
+        }
+        lines
+    }
+
+    fn decoded_returndata(&self) -> Vec<String> {
+        let step = self.current_step();
+        let Instruction::OpCode(op @ (opcode::RETURN | opcode::REVERT)) = step.instruction else {
+            return vec!["The returned data is decoded on RETURN and REVERT".to_string()];
+        };
+        let Some((_, access)) = get_buffer_accesses(op, &step.stack).and_then(|a| a.read) else {
+            return vec!["Stack underflow".to_string()];
+        };
+        let data = memory_slice(&step.memory, access.offset, access.size);
+
+        if op == opcode::REVERT {
+            return vec![format!("Revert: {}", self.debugger.revert_decoder.decode(data, None))];
+        }
+        if matches!(self.call_kind(), CallKind::Create | CallKind::Create2) {
+            return vec![format!("Deployed {} bytes of runtime code", data.len())];
+        }
+        let Some(function) = self.current_function() else {
+            return vec![format!("Unknown function, returned {}", hex::encode_prefixed(data))];
+        };
+
+        let mut lines = vec![format!("{} returns", function.signature())];
+        match function.abi_decode_output(data, false) {
+            Ok(values) => lines.extend(decoded_params(&function.outputs, &values)),
+            Err(err) => lines.push(format!("Failed to decode returned data: {err}")),
+        }
+        lines
+    }
+}
+
+/// Formats decoded values, one per line, named after their parameters.
+fn decoded_params<'a>(
+    params: &'a [Param],
+    values: &'a [DynSolValue],
+) -> impl Iterator<Item = String> + 'a {
+    params.iter().zip(values).enumerate().map(|(i, (param, value))| {
+        let value = format_token(value);
+        if param.name.is_empty() {
+            format!("  [{i}]: {value}")
+        } else {
+            format!("  {}: {value}", param.name)
+        }
+    })
+}
+
+/// Returns the part of `[offset, offset + size)` that's in memory.
+///
+/// Data is always written to memory before it's returned, so the rest would be zeroes.
+fn memory_slice(memory: &[u8], offset: usize, size: usize) -> &[u8] {
+    let start = offset.min(memory.len());
+    let end = offset.saturating_add(size).min(memory.len());
+    &memory[start..end]
+}
+
+/// A region of memory referenced by the stack arguments of an opcode.
+#[derive(Debug, PartialEq, Eq)]
+struct MemoryRegion {
+    label: &'static str,
+    offset: usize,
+    size: usize,
+}
+
+impl MemoryRegion {
+    fn overlaps(&self, range: &std::ops::Range<usize>) -> bool {
+        self.size > 0 &&
+            self.offset < range.end &&
+            range.start < self.offset.saturating_add(self.size)
+    }
+}
+
+impl std::fmt::Display for MemoryRegion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {:#x}..{:#x}",
+            self.label,
+            self.offset,
+            self.offset.saturating_add(self.size)
+        )
+    }
+}
+
+/// Returns the memory regions referenced by the stack arguments of the given opcode, labeled after
+/// the [OpcodeParam]s they're made of.
+fn memory_regions(op: u8, stack: &[U256]) -> Vec<MemoryRegion> {
+    let Some(accesses) = get_buffer_accesses(op, stack) else { return Vec::new() };
+    let (read_label, write_label) = match op {
+        opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL => {
+            ("argsOffset", "retOffset")
+        }
+        opcode::MSTORE | opcode::MSTORE8 => ("offset", "offset"),
+        _ => ("offset", "destOffset"),
+    };
+
+    let mut regions = Vec::with_capacity(2);
+    if let Some((BufferKind::Memory, read)) = accesses.read {
+        regions.push(MemoryRegion { label: read_label, offset: read.offset, size: read.size });
+    }
+    if let Some(write) = accesses.write {
+        regions.push(MemoryRegion { label: write_label, offset: write.offset, size: write.size });
+    }
+    regions
 }
 
 /// Wrapper around a list of [`Line`]s that prepends the line number on each new line.
@@ -707,8 +945,10 @@ fn get_buffer_accesses(op: u8, stack: &[U256]) -> Option<BufferAccesses> {
             (Some((BufferKind::Memory, 1, 2)), None)
         }
         opcode::CREATE | opcode::CREATE2 => (Some((BufferKind::Memory, 2, 3)), None),
-        opcode::CALL | opcode::CALLCODE => (Some((BufferKind::Memory, 4, 5)), None),
-        opcode::DELEGATECALL | opcode::STATICCALL => (Some((BufferKind::Memory, 3, 4)), None),
+        opcode::CALL | opcode::CALLCODE => (Some((BufferKind::Memory, 4, 5)), Some((6, 7))),
+        opcode::DELEGATECALL | opcode::STATICCALL => {
+            (Some((BufferKind::Memory, 3, 4)), Some((5, 6)))
+        }
         opcode::MCOPY => (Some((BufferKind::Memory, 2, 3)), Some((1, 3))),
         _ => Default::default(),
     };
@@ -769,6 +1009,8 @@ fn hex_digits(n: usize) -> usize {
 
 #[cfg(test)]
 mod tests {
+    use alloy_dyn_abi::DynSolValue;
+
     #[test]
     fn decimal_digits() {
         assert_eq!(super::decimal_digits(0), 1);
@@ -802,4 +1044,56 @@ mod tests {
         assert_eq!(super::hex_digits(0x100), 3);
         assert_eq!(super::hex_digits(0x101), 3);
     }
+
+    #[test]
+    fn memory_regions() {
+        use super::{memory_regions, MemoryRegion};
+        use alloy_primitives::U256;
+        use revm::interpreter::opcode;
+
+        // CALL(gas, address, value, argsOffset, argsSize, retOffset, retSize), top of stack last
+        let stack: Vec<U256> = [0x20u64, 0, 0x44, 0x80, 0, 0, 0x5208].map(U256::from).to_vec();
+        assert_eq!(
+            memory_regions(opcode::CALL, &stack),
+            [
+                MemoryRegion { label: "argsOffset", offset: 0x80, size: 0x44 },
+                MemoryRegion { label: "retOffset", offset: 0, size: 0x20 },
+            ]
+        );
+        assert_eq!(
+            memory_regions(opcode::MSTORE, &[U256::from(1), U256::from(0x40)]),
+            [MemoryRegion { label: "offset", offset: 0x40, size: 32 }]
+        );
+        // stack underflow
+        assert!(memory_regions(opcode::RETURN, &[U256::ZERO]).is_empty());
+        assert!(memory_regions(opcode::ADD, &stack).is_empty());
+
+        let region = MemoryRegion { label: "offset", offset: 0x30, size: 0x20 };
+        assert!(region.overlaps(&(0..0x20)) && region.overlaps(&(0x40..0x60)));
+        assert!(!region.overlaps(&(0x60..0x80)));
+        assert!(!MemoryRegion { label: "offset", offset: 0, size: 0 }.overlaps(&(0..0x20)));
+        assert_eq!(region.to_string(), "offset: 0x30..0x50");
+    }
+
+    #[test]
+    fn memory_slice() {
+        let memory = [1, 2, 3, 4];
+        assert_eq!(super::memory_slice(&memory, 1, 2), [2, 3]);
+        assert_eq!(super::memory_slice(&memory, 2, 10), [3, 4]);
+        assert!(super::memory_slice(&memory, 8, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn decoded_params() {
+        use alloy_json_abi::Function;
+        use alloy_primitives::U256;
+
+        let function = Function::parse("transfer(address to, uint256)").unwrap();
+        let values = [
+            DynSolValue::Address(alloy_primitives::Address::ZERO),
+            DynSolValue::Uint(U256::from(100), 256),
+        ];
+        let lines: Vec<_> = super::decoded_params(&function.inputs, &values).collect();
+        assert_eq!(lines, ["  to: 0x0000000000000000000000000000000000000000", "  [1]: 100"]);
+    }
 }
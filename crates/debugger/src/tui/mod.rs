@@ -1,6 +1,7 @@
 //! The TUI implementation.
 
-use alloy_primitives::Address;
+use alloy_json_abi::Function;
+use alloy_primitives::{Address, Selector};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
@@ -8,7 +9,7 @@ use crossterm::{
 };
 use eyre::Result;
 use foundry_common::{compile::ContractSources, evm::Breakpoints};
-use foundry_evm_core::{debug::DebugNodeFlat, utils::PcIcMap};
+use foundry_evm_core::{debug::DebugNodeFlat, decode::RevertDecoder, utils::PcIcMap};
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
@@ -23,6 +24,10 @@ use std::{
     time::{Duration, Instant},
 };
 
+mod breakpoints;
+use breakpoints::SourceBreakpoints;
+pub use breakpoints::BREAKPOINTS_FILE;
+
 mod builder;
 pub use builder::DebuggerBuilder;
 
@@ -44,11 +49,17 @@ pub enum ExitReason {
 pub struct Debugger {
     debug_arena: Vec<DebugNodeFlat>,
     identified_contracts: HashMap<Address, String>,
+    /// Known functions, used to decode calldata and returndata
+    functions: HashMap<Selector, Vec<Function>>,
+    /// Decoder of revert data and custom errors
+    revert_decoder: RevertDecoder,
     /// Source map of contract sources
     contracts_sources: ContractSources,
     /// A mapping of source -> (PC -> IC map for deploy code, PC -> IC map for runtime code)
     pc_ic_maps: BTreeMap<String, (PcIcMap, PcIcMap)>,
     breakpoints: Breakpoints,
+    /// Breakpoints set from the debugger by source line or function
+    source_breakpoints: SourceBreakpoints,
 }
 
 impl Debugger {
@@ -77,7 +88,16 @@ impl Debugger {
                 ))
             })
             .collect();
-        Self { debug_arena, identified_contracts, contracts_sources, pc_ic_maps, breakpoints }
+        Self {
+            debug_arena,
+            identified_contracts,
+            functions: HashMap::new(),
+            revert_decoder: RevertDecoder::default(),
+            contracts_sources,
+            pc_ic_maps,
+            breakpoints,
+            source_breakpoints: SourceBreakpoints::default(),
+        }
     }
 
     /// Starts the debugger TUI. Terminates the current process on failure or user exit.
@@ -9,6 +9,7 @@ use foundry_config::DocConfig;
 use itertools::Itertools;
 use mdbook::MDBook;
 use rayon::prelude::*;
+use solang_parser::pt::{ContractTy, FunctionAttribute, VariableAttribute, Visibility};
 use std::{
     cmp::Ordering,
     collections::HashMap,
@@ -38,6 +39,8 @@ pub struct DocBuilder {
     pub fmt: FormatterConfig,
     /// Whether to include libraries to the output.
     pub include_libraries: bool,
+    /// Whether to include internal and private functions and state variables to the output.
+    pub include_internal: bool,
 }
 
 // TODO: consider using `tfio`
@@ -59,6 +62,7 @@ impl DocBuilder {
             sources,
             libraries,
             include_libraries,
+            include_internal: false,
             should_build: false,
             config: DocConfig::default(),
             preprocessors: Default::default(),
@@ -78,6 +82,12 @@ impl DocBuilder {
         self
     }
 
+    /// Set `include_internal` flag on the builder.
+    pub fn with_include_internal(mut self, include_internal: bool) -> Self {
+        self.include_internal = include_internal;
+        self
+    }
+
     /// Set formatter config on the builder.
     pub fn with_fmt(mut self, fmt: FormatterConfig) -> Self {
         self.fmt = fmt;
@@ -150,9 +160,14 @@ impl DocBuilder {
                     .visit(&mut doc)
                     .map_err(|err| eyre::eyre!("Failed to parse source: {err}"))?;
 
+                // Remove internal items unless requested
+                let mut items = doc.items();
+                if !self.include_internal {
+                    items.iter_mut().for_each(Self::remove_internal_items);
+                }
+
                 // Split the parsed items on top-level constants and rest.
-                let (items, consts): (Vec<ParseItem>, Vec<ParseItem>) = doc
-                    .items()
+                let (items, consts): (Vec<ParseItem>, Vec<ParseItem>) = items
                     .into_iter()
                     .partition(|item| !matches!(item.source, ParseSource::Variable(_)));
 
@@ -272,6 +287,36 @@ impl DocBuilder {
         Ok(())
     }
 
+    /// Removes the internal and private functions and state variables of a contract.
+    ///
+    /// Internal library functions and constants are kept, since they make up the library's API.
+    fn remove_internal_items(item: &mut ParseItem) {
+        let Some(contract) = item.as_contract() else { return };
+        let is_library = matches!(contract.ty, ContractTy::Library(_));
+        let is_hidden = |visibility: &Visibility| match visibility {
+            Visibility::Private(_) => true,
+            Visibility::Internal(_) => !is_library,
+            Visibility::Public(_) | Visibility::External(_) => false,
+        };
+
+        item.children.retain(|ch| {
+            match &ch.source {
+            ParseSource::Function(func) => !func.attributes.iter().any(|attr| {
+                matches!(attr, FunctionAttribute::Visibility(visibility) if is_hidden(visibility))
+            }),
+            // State variables are internal by default
+            ParseSource::Variable(var) => {
+                let visibility = var.attrs.iter().find_map(|attr| match attr {
+                    VariableAttribute::Visibility(visibility) => Some(visibility),
+                    _ => None,
+                });
+                !visibility.map_or(!is_library, is_hidden)
+            }
+            _ => true,
+        }
+        });
+    }
+
     fn write_mdbook(&self, documents: Vec<Document>) -> eyre::Result<()> {
         let out_dir = self.out_dir();
         let out_dir_src = out_dir.join(Self::SRC);
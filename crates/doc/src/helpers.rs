@@ -1,3 +1,6 @@
+use alloy_primitives::{keccak256, Selector, B256};
+use itertools::Itertools;
+use solang_parser::pt::{ErrorDefinition, EventDefinition, Expression, FunctionDefinition, Type};
 use toml::{value::Table, Value};
 
 /// Merge original toml table with the override.
@@ -26,3 +29,137 @@ pub(crate) fn merge_toml_table(table: &mut Table, override_table: Table) {
         };
     }
 }
+
+/// Returns the canonical ABI type of an elementary type, e.g. `address payable` -> `address`.
+///
+/// Returns [None] for user-defined types, since they can't be resolved from the parse tree alone.
+pub(crate) fn canonical_type(ty: &Expression) -> Option<String> {
+    match ty {
+        Expression::Type(_, Type::AddressPayable) => Some("address".to_owned()),
+        Expression::Type(
+            _,
+            ty @ (Type::Address |
+            Type::Bool |
+            Type::String |
+            Type::Int(_) |
+            Type::Uint(_) |
+            Type::Bytes(_) |
+            Type::DynamicBytes),
+        ) => Some(ty.to_string()),
+        Expression::ArraySubscript(_, ty, size) => {
+            let ty = canonical_type(ty)?;
+            match size {
+                Some(size) => Some(format!("{ty}[{size}]")),
+                None => Some(format!("{ty}[]")),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the ABI signature of an item, e.g. `Transfer(address,address,uint256)`.
+///
+/// Returns [None] if any of the types is not elementary.
+pub(crate) fn abi_signature<'a>(
+    name: &str,
+    types: impl IntoIterator<Item = &'a Expression>,
+) -> Option<String> {
+    let types = types.into_iter().map(canonical_type).collect::<Option<Vec<_>>>()?;
+    Some(format!("{name}({})", types.join(",")))
+}
+
+/// Returns the signature of a function, which disambiguates overloads, e.g.
+/// `transfer(address,uint256)`.
+///
+/// Unlike [abi_signature], user-defined types are included as they are written.
+pub(crate) fn function_signature(func: &FunctionDefinition) -> String {
+    let name = func.name.as_ref().map_or(func.ty.to_string(), |n| n.name.to_owned());
+    let params = func
+        .params
+        .iter()
+        .filter_map(|(_, param)| param.as_ref())
+        .map(|param| canonical_type(&param.ty).unwrap_or_else(|| param.ty.to_string()))
+        .join(",");
+    format!("{name}({params})")
+}
+
+/// Returns the selector of an error.
+pub(crate) fn error_selector(error: &ErrorDefinition) -> Option<Selector> {
+    let name = &error.name.as_ref()?.name;
+    let signature = abi_signature(name, error.fields.iter().map(|field| &field.ty))?;
+    Some(Selector::from_slice(&keccak256(signature)[..4]))
+}
+
+/// Returns the topic0 of an event. Anonymous events don't have one.
+pub(crate) fn event_topic(event: &EventDefinition) -> Option<B256> {
+    if event.anonymous {
+        return None
+    }
+    let name = &event.name.as_ref()?.name;
+    let signature = abi_signature(name, event.fields.iter().map(|field| &field.ty))?;
+    Some(keccak256(signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+    use solang_parser::pt::{ContractPart, SourceUnitPart};
+
+    fn parse_contract_parts(src: &str) -> Vec<ContractPart> {
+        let (source, _) = solang_parser::parse(src, 0).expect("failed to parse source");
+        source
+            .0
+            .into_iter()
+            .flat_map(|part| match part {
+                SourceUnitPart::ContractDefinition(contract) => contract.parts,
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn can_compute_selectors() {
+        let parts = parse_contract_parts(
+            r"
+            contract Token {
+                event Transfer(address indexed from, address indexed to, uint value);
+                event Anonymous(uint256 value) anonymous;
+                error InsufficientBalance(address payable account, uint256[] amounts);
+                error Custom(Info info);
+                function transfer(address to, uint amount, Info memory info) external {}
+            }
+        ",
+        );
+
+        let mut topics = vec![];
+        let mut selectors = vec![];
+        for part in parts.iter() {
+            match part {
+                ContractPart::EventDefinition(event) => topics.push(event_topic(event)),
+                ContractPart::ErrorDefinition(error) => selectors.push(error_selector(error)),
+                ContractPart::FunctionDefinition(func) => {
+                    assert_eq!(function_signature(func), "transfer(address,uint256,Info)")
+                }
+                _ => {}
+            }
+        }
+
+        assert_eq!(
+            topics,
+            vec![
+                Some(b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")),
+                None
+            ]
+        );
+        assert_eq!(
+            selectors,
+            vec![
+                Some(Selector::from_slice(
+                    &keccak256("InsufficientBalance(address,uint256[])")[..4]
+                )),
+                None
+            ]
+        );
+    }
+}
@@ -119,28 +119,35 @@ impl Comments {
     ref_fn!(pub fn contains_tag(&self, tag: &Comment) -> bool);
     ref_fn!(pub fn find_inheritdoc_base(&self) -> Option<&'_ str>);
 
-    /// Attempt to lookup
+    /// Merges the inherited comments into the collection.
     ///
-    /// Merges two comments collections by inserting [CommentTag] from the second collection
-    /// into the first unless they are present.
+    /// If the comments contain an `@inheritdoc` tag, the comments are looked up by the
+    /// `{base}.{signature}` key. Otherwise, the `signature` key of implicitly inherited comments is
+    /// used.
     pub fn merge_inheritdoc(
         &self,
-        ident: &str,
+        signature: &str,
         inheritdocs: Option<HashMap<String, Comments>>,
     ) -> Comments {
-        let mut result = Comments(Vec::from_iter(self.iter().cloned()));
-
-        if let (Some(inheritdocs), Some(base)) = (inheritdocs, self.find_inheritdoc_base()) {
-            let key = format!("{base}.{ident}");
-            if let Some(other) = inheritdocs.get(&key) {
-                for comment in other.iter() {
-                    if !result.contains_tag(comment) {
-                        result.push(comment.clone());
-                    }
-                }
-            }
+        let key = match self.find_inheritdoc_base() {
+            Some(base) => format!("{base}.{signature}"),
+            None => signature.to_owned(),
+        };
+        match inheritdocs.as_ref().and_then(|inheritdocs| inheritdocs.get(&key)) {
+            Some(other) => self.merge(other),
+            None => self.clone(),
         }
+    }
 
+    /// Merges two comments collections by inserting [CommentTag] from the second collection
+    /// into the first unless they are present. [CommentTag::Inheritdoc] tags are not inserted.
+    pub fn merge(&self, other: &Comments) -> Comments {
+        let mut result = self.clone();
+        for comment in other.iter() {
+            if comment.tag != CommentTag::Inheritdoc && !result.contains_tag(comment) {
+                result.push(comment.clone());
+            }
+        }
         result
     }
 }
@@ -227,4 +234,40 @@ mod tests {
         assert_eq!(CommentTag::from_str("custom"), None);
         assert_eq!(CommentTag::from_str("sometag"), None);
     }
+
+    #[test]
+    fn merge_inheritdoc() {
+        let comment = |tag, value: &str| Comment::new(tag, value.to_owned());
+        let base = Comments(vec![
+            comment(CommentTag::Notice, "Transfers tokens."),
+            comment(CommentTag::Param, "to The recipient."),
+            comment(CommentTag::Param, "amount The amount."),
+        ]);
+        let inheritdocs = HashMap::from([
+            ("IToken.transfer(address,uint256)".to_owned(), base.clone()),
+            ("approve(address,uint256)".to_owned(), base.clone()),
+        ]);
+
+        // Explicit inheritance keeps the own comments
+        let comments = Comments(vec![
+            comment(CommentTag::Inheritdoc, "IToken"),
+            comment(CommentTag::Param, "to The account receiving the tokens."),
+        ]);
+        let merged =
+            comments.merge_inheritdoc("transfer(address,uint256)", Some(inheritdocs.clone()));
+        assert_eq!(
+            merged,
+            Comments(vec![
+                comment(CommentTag::Inheritdoc, "IToken"),
+                comment(CommentTag::Param, "to The account receiving the tokens."),
+                comment(CommentTag::Notice, "Transfers tokens."),
+                comment(CommentTag::Param, "amount The amount."),
+            ])
+        );
+
+        // Implicit inheritance
+        let merged =
+            Comments::default().merge_inheritdoc("approve(address,uint256)", Some(inheritdocs));
+        assert_eq!(merged, base);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{error::ParserResult, Comments};
+use crate::{error::ParserResult, helpers::function_signature, Comments};
 use forge_fmt::{
     solang_ext::SafeUnwrap, Comments as FmtComments, Formatter, FormatterConfig, InlineConfig,
     Visitor,
@@ -183,4 +183,15 @@ impl ParseSource {
             ParseSource::Type(ty) => ty.name.name.to_owned(),
         }
     }
+
+    /// Get the signature of the source.
+    ///
+    /// For functions, this is the identity followed by the parameter types, which disambiguates
+    /// overloads, e.g. `transfer(address,uint256)`. For other sources, this is the identity.
+    pub fn signature(&self) -> String {
+        match self {
+            ParseSource::Function(func) => function_signature(func),
+            _ => self.ident(),
+        }
+    }
 }
@@ -1,17 +1,26 @@
 use super::{Preprocessor, PreprocessorId};
 use crate::{
-    document::DocumentContent, Comments, Document, ParseItem, ParseSource, PreprocessorOutput,
+    document::DocumentContent, helpers::canonical_type, Comments, Document, ParseItem, ParseSource,
+    PreprocessorOutput,
 };
 use forge_fmt::solang_ext::SafeUnwrap;
+use solang_parser::pt::{ContractDefinition, FunctionDefinition, FunctionTy};
 use std::collections::HashMap;
 
-/// [ContractInheritance] preprocessor id.
+/// [Inheritdoc] preprocessor id.
 pub const INHERITDOC_ID: PreprocessorId = PreprocessorId("inheritdoc");
 
+/// The maximum depth of the inheritance graph that is searched for inherited comments.
+/// Guards against cyclic inheritance in unparseable or invalid sources.
+const MAX_INHERITANCE_DEPTH: usize = 64;
+
 /// The inheritdoc preprocessor.
 /// Traverses the documents and attempts to find inherited
 /// comments for inheritdoc comment tags.
 ///
+/// Functions and public state variables without any comments implicitly inherit the comments of
+/// the item they override, like the Solidity compiler does.
+///
 /// This preprocessor writes to [Document]'s context.
 #[derive(Debug, Default)]
 #[non_exhaustive]
@@ -37,56 +46,190 @@ impl Preprocessor for Inheritdoc {
 }
 
 impl Inheritdoc {
-    fn visit_item(&self, item: &ParseItem, documents: &Vec<Document>) -> HashMap<String, Comments> {
+    fn visit_item(&self, item: &ParseItem, documents: &[Document]) -> HashMap<String, Comments> {
         let mut context = HashMap::default();
 
         // Match for the item first.
-        let matched = item
-            .comments
-            .find_inheritdoc_base()
-            .and_then(|base| self.try_match_inheritdoc(base, &item.source, documents));
-        if let Some((key, comments)) = matched {
-            context.insert(key, comments);
+        if let Some(base) = item.comments.find_inheritdoc_base() {
+            if let Some(comments) = self.try_match_inheritdoc(base, &item.source, documents, 0) {
+                context.insert(format!("{base}.{}", item.source.signature()), comments);
+            }
         }
 
         // Match item's children.
+        let bases = item.as_contract().map(base_names).unwrap_or_default();
         for ch in item.children.iter() {
-            let matched = ch
-                .comments
-                .find_inheritdoc_base()
-                .and_then(|base| self.try_match_inheritdoc(base, &ch.source, documents));
-            if let Some((key, comments)) = matched {
-                context.insert(key, comments);
+            if let Some(base) = ch.comments.find_inheritdoc_base() {
+                if let Some(comments) = self.try_match_inheritdoc(base, &ch.source, documents, 0) {
+                    context.insert(format!("{base}.{}", ch.source.signature()), comments);
+                }
+            } else if ch.comments.is_empty() && can_inherit_comments(&ch.source) {
+                let inherited = bases
+                    .iter()
+                    .find_map(|base| self.try_match_inheritdoc(base, &ch.source, documents, 0));
+                if let Some(comments) = inherited {
+                    context.insert(ch.source.signature(), comments);
+                }
             }
         }
 
         context
     }
 
+    /// Attempts to find the comments of the item matching the source in the base contract.
+    ///
+    /// Comments inherited by the matched item are resolved recursively. If the base contract
+    /// doesn't document the item, its own bases are searched in order.
     fn try_match_inheritdoc(
         &self,
         base: &str,
         source: &ParseSource,
-        documents: &Vec<Document>,
-    ) -> Option<(String, Comments)> {
-        for candidate in documents {
-            if let DocumentContent::Single(ref item) = candidate.content {
-                if let ParseSource::Contract(ref contract) = item.source {
-                    if base == contract.name.safe_unwrap().name {
-                        // Not matched for the contract because it's a noop
-                        // https://docs.soliditylang.org/en/v0.8.17/natspec-format.html#tags
-
-                        for children in item.children.iter() {
-                            // TODO: improve matching logic
-                            if source.ident() == children.source.ident() {
-                                let key = format!("{}.{}", base, source.ident());
-                                return Some((key, children.comments.clone()))
-                            }
-                        }
-                    }
-                }
+        documents: &[Document],
+        depth: usize,
+    ) -> Option<Comments> {
+        if depth > MAX_INHERITANCE_DEPTH {
+            return None
+        }
+
+        // Not matched for the contract because it's a noop
+        // https://docs.soliditylang.org/en/v0.8.17/natspec-format.html#tags
+        let (item, contract) = documents.iter().find_map(|candidate| match candidate.content {
+            DocumentContent::Single(ref item) => item
+                .as_contract()
+                .filter(|contract| base == contract.name.safe_unwrap().name)
+                .map(|contract| (item, contract)),
+            _ => None,
+        })?;
+
+        if let Some(matched) = item.children.iter().find(|ch| is_same_item(source, &ch.source)) {
+            if let Some(next) = matched.comments.find_inheritdoc_base() {
+                let inherited = self.try_match_inheritdoc(next, source, documents, depth + 1);
+                return Some(match inherited {
+                    Some(inherited) => matched.comments.merge(&inherited),
+                    None => matched.comments.clone(),
+                })
             }
+            if !matched.comments.is_empty() {
+                return Some(matched.comments.clone())
+            }
+        }
+
+        base_names(contract)
+            .iter()
+            .find_map(|base| self.try_match_inheritdoc(base, source, documents, depth + 1))
+    }
+}
+
+/// Returns the names of the contract's direct bases.
+fn base_names(contract: &ContractDefinition) -> Vec<String> {
+    contract
+        .base
+        .iter()
+        .filter_map(|base| base.name.identifiers.last().map(|ident| ident.name.clone()))
+        .collect()
+}
+
+/// Returns `true` if the item can implicitly inherit the comments of the item it overrides.
+fn can_inherit_comments(source: &ParseSource) -> bool {
+    match source {
+        ParseSource::Function(func) => !matches!(func.ty, FunctionTy::Constructor),
+        ParseSource::Variable(_) => true,
+        _ => false,
+    }
+}
+
+/// Returns `true` if the candidate is the overridden version of the item.
+///
+/// Public state variables can override functions with the same name.
+fn is_same_item(source: &ParseSource, candidate: &ParseSource) -> bool {
+    match (source, candidate) {
+        (ParseSource::Function(func), ParseSource::Function(other)) => {
+            func.ty == other.ty && is_same_signature(func, other)
         }
-        None
+        _ => source.ident() == candidate.ident(),
+    }
+}
+
+/// Returns `true` if the functions have the same name and parameter types.
+fn is_same_signature(func: &FunctionDefinition, other: &FunctionDefinition) -> bool {
+    let types = |func: &FunctionDefinition| {
+        func.params
+            .iter()
+            .map(|(_, param)| {
+                param
+                    .as_ref()
+                    .map(|param| canonical_type(&param.ty).unwrap_or_else(|| param.ty.to_string()))
+            })
+            .collect::<Vec<_>>()
+    };
+    func.name.as_ref().map(|n| &n.name) == other.name.as_ref().map(|n| &n.name) &&
+        types(func) == types(other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{document::read_context, Parser};
+    use forge_fmt::Visitable;
+    use std::path::PathBuf;
+
+    fn parse_documents(src: &str) -> Vec<Document> {
+        let (mut source, comments) = solang_parser::parse(src, 0).expect("failed to parse source");
+        let mut doc = Parser::new(comments, src.to_owned());
+        source.visit(&mut doc).expect("failed to visit source");
+        doc.items()
+            .into_iter()
+            .map(|item| {
+                let ident = item.source.ident();
+                Document::new(PathBuf::new(), PathBuf::new(), false, PathBuf::new())
+                    .with_content(DocumentContent::Single(item), ident)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn can_inherit_comments_through_bases() {
+        let documents = parse_documents(
+            r"
+            interface IToken {
+                /// @notice Transfers tokens.
+                /// @param to The recipient.
+                function transfer(address to, uint256 amount) external returns (bool);
+
+                /// @notice Transfers tokens with data.
+                function transfer(address to, uint256 amount, bytes calldata data) external;
+
+                /// @notice The total supply.
+                function totalSupply() external view returns (uint256);
+            }
+
+            abstract contract Token is IToken {
+                function transfer(address to, uint256 amount) public virtual returns (bool) {}
+            }
+
+            contract MyToken is Token {
+                /// @inheritdoc IToken
+                /// @param amount The amount.
+                function transfer(address to, uint256 amount, bytes calldata data) external {}
+
+                function transfer(address to, uint amount) public override returns (bool) {}
+
+                uint256 public totalSupply;
+
+                constructor() {}
+            }
+        ",
+        );
+        let documents = Inheritdoc.preprocess(documents).unwrap();
+
+        let token = documents.iter().find(|doc| doc.identity == "MyToken").unwrap();
+        let context = read_context!(token, INHERITDOC_ID, Inheritdoc).unwrap();
+        assert_eq!(context.len(), 3);
+
+        let notice =
+            |key: &str| context[key].include_tag(crate::CommentTag::Notice)[0].value.clone();
+        assert_eq!(notice("IToken.transfer(address,uint256,bytes)"), "Transfers tokens with data.");
+        assert_eq!(notice("transfer(address,uint256)"), "Transfers tokens.");
+        assert_eq!(notice("totalSupply"), "The total supply.");
     }
 }
@@ -12,6 +12,9 @@ pub use inheritdoc::{Inheritdoc, INHERITDOC_ID};
 mod infer_hyperlinks;
 pub use infer_hyperlinks::{InferInlineHyperlinks, INFER_INLINE_HYPERLINKS_ID};
 
+mod type_hyperlinks;
+pub use type_hyperlinks::{TypeHyperlinks, TYPE_HYPERLINKS_ID};
+
 mod git_source;
 pub use git_source::{GitSource, GIT_SOURCE_ID};
 
@@ -33,6 +36,9 @@ pub enum PreprocessorOutput {
     /// The inheritdoc output.
     /// The map of inherited item keys to their comments.
     Inheritdoc(HashMap<String, Comments>),
+    /// The type hyperlinks output.
+    /// The map of referenced type names to the links to their definitions.
+    TypeHyperlinks(HashMap<String, String>),
     /// The git source output.
     /// The git url of the item path.
     GitSource(String),
@@ -0,0 +1,141 @@
+use super::{Preprocessor, PreprocessorId};
+use crate::{Document, ParseItem, ParseSource, PreprocessorOutput};
+use solang_parser::pt::{Expression, Type};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+/// [TypeHyperlinks] preprocessor id.
+pub const TYPE_HYPERLINKS_ID: PreprocessorId = PreprocessorId("type hyperlinks");
+
+/// The type hyperlinks preprocessor.
+/// It collects the user-defined types, i.e. contracts, structs, enums and user-defined value
+/// types, and links the types referenced by the items of every document to their definitions.
+///
+/// Types defined in the same contract are linked to their section of the contract document.
+/// Types whose names are ambiguous across documents are not linked.
+///
+/// This preprocessor writes to [Document]'s context.
+#[derive(Debug, Default)]
+pub struct TypeHyperlinks {
+    /// Whether to link types defined in libraries.
+    pub include_libraries: bool,
+}
+
+impl Preprocessor for TypeHyperlinks {
+    fn id(&self) -> PreprocessorId {
+        TYPE_HYPERLINKS_ID
+    }
+
+    fn preprocess(&self, documents: Vec<Document>) -> Result<Vec<Document>, eyre::Error> {
+        // Collect the links to all type definitions
+        let mut definitions: HashMap<String, HashSet<String>> = HashMap::default();
+        for document in documents.iter() {
+            if document.from_library && !self.include_libraries {
+                continue
+            }
+            let path = Path::new("/").join(document.relative_output_path());
+            for item in document.content.iter_items() {
+                if !is_type_definition(&item.source) {
+                    continue
+                }
+                let ident = item.source.ident();
+                definitions.entry(ident.clone()).or_default().insert(path.display().to_string());
+
+                for ch in item.children.iter().filter(|ch| is_type_definition(&ch.source)) {
+                    let link = format!("{}#{}", path.display(), ch.source.ident().to_lowercase());
+                    definitions
+                        .entry(format!("{ident}.{}", ch.source.ident()))
+                        .or_default()
+                        .insert(link.clone());
+                    definitions.entry(ch.source.ident()).or_default().insert(link);
+                }
+            }
+        }
+
+        for document in documents.iter() {
+            let mut links = HashMap::default();
+            for item in document.content.iter_items() {
+                let mut names = vec![];
+                collect_item_types(item, &mut names);
+                for ch in item.children.iter() {
+                    collect_item_types(ch, &mut names);
+                }
+
+                for name in names {
+                    // Prefer the definitions in the same contract
+                    let local = item
+                        .children
+                        .iter()
+                        .find(|ch| is_type_definition(&ch.source) && ch.source.ident() == name);
+                    let link = match local {
+                        Some(ch) => Some(format!("#{}", ch.source.ident().to_lowercase())),
+                        None => definitions
+                            .get(&name)
+                            .filter(|links| links.len() == 1)
+                            .and_then(|links| links.iter().next().cloned()),
+                    };
+                    if let Some(link) = link {
+                        links.insert(name, link);
+                    }
+                }
+            }
+
+            if !links.is_empty() {
+                document.add_context(self.id(), PreprocessorOutput::TypeHyperlinks(links));
+            }
+        }
+
+        Ok(documents)
+    }
+}
+
+/// Returns `true` if the source defines a type that can be referenced by other items.
+fn is_type_definition(source: &ParseSource) -> bool {
+    matches!(
+        source,
+        ParseSource::Contract(_) |
+            ParseSource::Struct(_) |
+            ParseSource::Enum(_) |
+            ParseSource::Type(_)
+    )
+}
+
+/// Collects the names of the user-defined types referenced by the item.
+fn collect_item_types(item: &ParseItem, names: &mut Vec<String>) {
+    match &item.source {
+        ParseSource::Function(func) => {
+            for (_, param) in func.params.iter().chain(func.returns.iter()) {
+                if let Some(param) = param {
+                    collect_types(&param.ty, names);
+                }
+            }
+        }
+        ParseSource::Variable(var) => collect_types(&var.ty, names),
+        ParseSource::Event(event) => {
+            event.fields.iter().for_each(|field| collect_types(&field.ty, names))
+        }
+        ParseSource::Error(error) => {
+            error.fields.iter().for_each(|field| collect_types(&field.ty, names))
+        }
+        ParseSource::Struct(structure) => {
+            structure.fields.iter().for_each(|field| collect_types(&field.ty, names))
+        }
+        ParseSource::Contract(_) | ParseSource::Enum(_) | ParseSource::Type(_) => {}
+    }
+}
+
+/// Collects the names of the user-defined types referenced by the type expression.
+fn collect_types(ty: &Expression, names: &mut Vec<String>) {
+    match ty {
+        Expression::Variable(ident) => names.push(ident.name.clone()),
+        Expression::MemberAccess(..) => names.push(ty.to_string()),
+        Expression::ArraySubscript(_, ty, _) => collect_types(ty, names),
+        Expression::Type(_, Type::Mapping { key, value, .. }) => {
+            collect_types(key, names);
+            collect_types(value, names);
+        }
+        _ => {}
+    }
+}
@@ -1,13 +1,14 @@
 use crate::{
     document::{read_context, DocumentContent},
+    helpers::{error_selector, event_topic, function_signature},
     parser::ParseSource,
     writer::BufWriter,
     CommentTag, Comments, CommentsRef, Document, Markdown, PreprocessorOutput,
-    CONTRACT_INHERITANCE_ID, DEPLOYMENTS_ID, GIT_SOURCE_ID, INHERITDOC_ID,
+    CONTRACT_INHERITANCE_ID, DEPLOYMENTS_ID, GIT_SOURCE_ID, INHERITDOC_ID, TYPE_HYPERLINKS_ID,
 };
 use forge_fmt::solang_ext::SafeUnwrap;
 use itertools::Itertools;
-use solang_parser::pt::{Base, FunctionDefinition};
+use solang_parser::pt::{Base, FunctionDefinition, FunctionTy};
 use std::path::{Path, PathBuf};
 
 /// The result of [Asdoc::as_doc] method.
@@ -71,7 +72,9 @@ impl AsDoc for Base {
 
 impl AsDoc for Document {
     fn as_doc(&self) -> AsDocResult {
-        let mut writer = BufWriter::default();
+        let mut writer = BufWriter::default().with_type_links(
+            read_context!(self, TYPE_HYPERLINKS_ID, TypeHyperlinks).unwrap_or_default(),
+        );
 
         match &self.content {
             DocumentContent::OverloadedFunctions(items) => {
@@ -177,10 +180,22 @@ impl AsDoc for Document {
                         }
 
                         if let Some(funcs) = item.functions() {
-                            writer.write_subtitle("Functions")?;
+                            let (modifiers, funcs): (Vec<_>, Vec<_>) = funcs
+                                .into_iter()
+                                .partition(|(func, _, _)| matches!(func.ty, FunctionTy::Modifier));
+
+                            if !funcs.is_empty() {
+                                writer.write_subtitle("Functions")?;
+                                for (func, comments, code) in funcs.iter() {
+                                    self.write_function(&mut writer, func, comments, code)?;
+                                }
+                            }
 
-                            for (func, comments, code) in funcs.iter() {
-                                self.write_function(&mut writer, func, comments, code)?;
+                            if !modifiers.is_empty() {
+                                writer.write_subtitle("Modifiers")?;
+                                for (func, comments, code) in modifiers.iter() {
+                                    self.write_function(&mut writer, func, comments, code)?;
+                                }
                             }
                         }
 
@@ -189,6 +204,9 @@ impl AsDoc for Document {
                             events.into_iter().try_for_each(|(item, comments, code)| {
                                 writer.write_heading(&item.name.safe_unwrap().name)?;
                                 writer.write_section(comments, code)?;
+                                if let Some(topic) = event_topic(item) {
+                                    writer.write_selector("Topic0", topic)?;
+                                }
                                 writer.try_write_events_table(&item.fields, comments)
                             })?;
                         }
@@ -198,6 +216,9 @@ impl AsDoc for Document {
                             errors.into_iter().try_for_each(|(item, comments, code)| {
                                 writer.write_heading(&item.name.safe_unwrap().name)?;
                                 writer.write_section(comments, code)?;
+                                if let Some(selector) = error_selector(item) {
+                                    writer.write_selector("Selector", selector)?;
+                                }
                                 writer.try_write_errors_table(&item.fields, comments)
                             })?;
                         }
@@ -253,10 +274,16 @@ impl AsDoc for Document {
                     }
                     ParseSource::Event(ev) => {
                         writer.write_section(&item.comments, &item.code)?;
+                        if let Some(topic) = event_topic(ev) {
+                            writer.write_selector("Topic0", topic)?;
+                        }
                         writer.try_write_events_table(&ev.fields, &item.comments)?;
                     }
                     ParseSource::Error(err) => {
                         writer.write_section(&item.comments, &item.code)?;
+                        if let Some(selector) = error_selector(err) {
+                            writer.write_selector("Selector", selector)?;
+                        }
                         writer.try_write_errors_table(&err.fields, &item.comments)?;
                     }
                     ParseSource::Variable(_) | ParseSource::Enum(_) | ParseSource::Type(_) => {
@@ -286,8 +313,10 @@ impl Document {
         code: &str,
     ) -> Result<(), std::fmt::Error> {
         let func_name = func.name.as_ref().map_or(func.ty.to_string(), |n| n.name.to_owned());
-        let comments =
-            comments.merge_inheritdoc(&func_name, read_context!(self, INHERITDOC_ID, Inheritdoc));
+        let comments = comments.merge_inheritdoc(
+            &function_signature(func),
+            read_context!(self, INHERITDOC_ID, Inheritdoc),
+        );
 
         // Write function name
         writer.write_heading(&func_name)?;
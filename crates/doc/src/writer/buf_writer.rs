@@ -2,7 +2,10 @@ use crate::{writer::traits::ParamLike, AsDoc, CommentTag, Comments, Deployment,
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use solang_parser::pt::{ErrorParameter, EventParameter, Parameter, VariableDeclaration};
-use std::fmt::{self, Display, Write};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Write},
+};
 
 /// Solidity language name.
 const SOLIDITY: &str = "solidity";
@@ -22,12 +25,21 @@ static DEPLOYMENTS_TABLE_SEPARATOR: Lazy<String> =
 #[derive(Debug, Default)]
 pub struct BufWriter {
     buf: String,
+    /// The links to the definitions of the types referenced by the written items.
+    type_links: HashMap<String, String>,
 }
 
 impl BufWriter {
     /// Create new instance of [BufWriter] from [ToString].
     pub fn new(content: impl ToString) -> Self {
-        Self { buf: content.to_string() }
+        Self { buf: content.to_string(), type_links: HashMap::default() }
+    }
+
+    /// Set the links to type definitions on the [BufWriter].
+    /// Types in parameter tables are linked to their definitions.
+    pub fn with_type_links(mut self, type_links: HashMap<String, String>) -> Self {
+        self.type_links = type_links;
+        self
     }
 
     /// Returns true if the buffer is empty.
@@ -114,6 +126,28 @@ impl BufWriter {
         self.writeln()
     }
 
+    /// Writes the selector of an error or the topic of an event to the buffer.
+    pub fn write_selector(&mut self, label: &str, selector: impl Display) -> fmt::Result {
+        writeln!(
+            self.buf,
+            "{} {}",
+            Markdown::Bold(&format!("{label}:")),
+            Markdown::Code(&selector.to_string())
+        )?;
+        self.writeln()
+    }
+
+    /// Formats the type name as code, linked to the type's definition if it's known.
+    fn type_doc(&self, type_name: &str) -> Result<String, fmt::Error> {
+        let code = Markdown::Code(type_name).as_doc()?;
+        // Arrays are linked to the definition of their element type
+        let element = type_name.split('[').next().unwrap_or(type_name);
+        match self.type_links.get(element) {
+            Some(link) => Markdown::Link(&code, link).as_doc(),
+            None => Ok(code),
+        }
+    }
+
     /// Tries to write the table to the buffer.
     /// Doesn't write anything if either params or comments are empty.
     fn try_write_table<T>(
@@ -154,7 +188,7 @@ impl BufWriter {
 
             let row = [
                 Markdown::Code(param_name.unwrap_or("<none>")).as_doc()?,
-                Markdown::Code(&param.type_name()).as_doc()?,
+                self.type_doc(&param.type_name())?,
                 comment.unwrap_or_default().replace('\n', " "),
             ];
             self.write_piped(&row.join("|"))?;
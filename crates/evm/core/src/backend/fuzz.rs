@@ -191,6 +191,10 @@ impl<'a> DatabaseExt for FuzzBackendWrapper<'a> {
         self.backend.active_fork_url()
     }
 
+    fn prefetch(&self, accounts: &[(Address, Vec<U256>)]) -> Result<(), DatabaseError> {
+        self.backend.prefetch(accounts)
+    }
+
     fn ensure_fork(&self, id: Option<LocalForkId>) -> eyre::Result<LocalForkId> {
         self.backend.ensure_fork(id)
     }
@@ -201,6 +201,11 @@ pub trait DatabaseExt: Database<Error = DatabaseError> {
     /// Returns the Fork url that's currently used in the database, if fork mode is on
     fn active_fork_url(&self) -> Option<String>;
 
+    /// Fetches the given accounts and storage slots of the active fork into its cache at once.
+    ///
+    /// Does nothing if fork mode is off.
+    fn prefetch(&self, accounts: &[(Address, Vec<U256>)]) -> Result<(), DatabaseError>;
+
     /// Whether the database is currently in forked mode.
     fn is_forked_mode(&self) -> bool {
         self.active_fork_id().is_some()
@@ -1245,6 +1250,13 @@ impl DatabaseExt for Backend {
         self.forks.get_fork_url(fork.clone()).ok()?
     }
 
+    fn prefetch(&self, accounts: &[(Address, Vec<U256>)]) -> Result<(), DatabaseError> {
+        match self.active_fork_db() {
+            Some(db) => db.database().db.prefetch(accounts),
+            None => Ok(()),
+        }
+    }
+
     fn ensure_fork(&self, id: Option<LocalForkId>) -> eyre::Result<LocalForkId> {
         if let Some(id) = id {
             if self.inner.issued_local_fork_ids.contains_key(&id) {
@@ -152,17 +152,9 @@ impl RevertDecoder {
         }
 
         // Custom errors.
-        if let Some(errors) = self.errors.get(selector) {
-            for error in errors {
-                // If we don't decode, don't return an error, try to decode as a string later.
-                if let Ok(decoded) = error.abi_decode_input(data, false) {
-                    return Some(format!(
-                        "{}({})",
-                        error.name,
-                        decoded.iter().map(foundry_common::fmt::format_token).format(", ")
-                    ));
-                }
-            }
+        // If we don't decode, don't return an error, try to decode as a string later.
+        if let Some(decoded) = self.decode_custom_error(err) {
+            return Some(decoded);
         }
 
         // ABI-encoded `string`.
@@ -182,6 +174,29 @@ impl RevertDecoder {
             std::str::from_utf8(data).map_or_else(|_| trimmed_hex(data), String::from)
         ))
     }
+
+    /// Tries to decode the given revert bytes as one of the known custom errors.
+    ///
+    /// If the bytes are only a selector, e.g. the expected revert of `vm.expectPartialRevert`,
+    /// returns the signature of the matching error.
+    pub fn decode_custom_error(&self, err: &[u8]) -> Option<String> {
+        if err.len() < SELECTOR_LEN {
+            return None;
+        }
+        let (selector, data) = err.split_at(SELECTOR_LEN);
+        let selector: &[u8; 4] = selector.try_into().unwrap();
+        let errors = self.errors.get(selector)?;
+        for error in errors {
+            if let Ok(decoded) = error.abi_decode_input(data, false) {
+                return Some(format!(
+                    "{}({})",
+                    error.name,
+                    decoded.iter().map(foundry_common::fmt::format_token).format(", ")
+                ));
+            }
+        }
+        data.is_empty().then(|| errors[0].signature())
+    }
 }
 
 fn trimmed_hex(s: &[u8]) -> String {
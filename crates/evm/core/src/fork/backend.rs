@@ -19,19 +19,65 @@ use revm::{
     primitives::{AccountInfo, Bytecode, KECCAK_EMPTY},
 };
 use std::{
+    cell::Cell,
     collections::{hash_map::Entry, HashMap, VecDeque},
     pin::Pin,
     sync::{
         mpsc::{channel as oneshot_channel, Sender as OneshotSender},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
+/// The minimum number of slots of an account requested at once that are fetched with a single
+/// `eth_getProof` request instead of a request per slot.
+const MIN_BATCHED_SLOTS: usize = 3;
+
+/// How long requested storage slots are held back before they're fetched, so that the slots of
+/// the same account requested concurrently can be fetched together.
+const STORAGE_BATCH_WINDOW: Duration = Duration::from_millis(2);
+
+thread_local! {
+    static FETCH_STATS: Cell<FetchStats> = Cell::new(FetchStats::default());
+}
+
+/// The values the [`SharedBackend`]s of the current thread fetched from the remote endpoint, and
+/// the time they waited for them.
+///
+/// Each test is executed on a single thread, so these can be attributed to the test.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FetchStats {
+    /// The number of accounts, storage slots and block hashes that missed the cache
+    pub fetches: u64,
+    /// The time spent waiting for them
+    pub wait_time: Duration,
+}
+
+impl FetchStats {
+    /// Returns the stats of the current thread, and resets them.
+    pub fn take() -> Self {
+        FETCH_STATS.with(|stats| stats.take())
+    }
+
+    /// Records `fetches` fetched by the current thread in `wait_time`.
+    fn record(fetches: u64, wait_time: Duration) {
+        FETCH_STATS.with(|stats| {
+            let current = stats.get();
+            stats.set(Self {
+                fetches: current.fetches + fetches,
+                wait_time: current.wait_time + wait_time,
+            })
+        })
+    }
+}
+
 // Various future/request type aliases
 
 type AccountFuture<Err> =
     Pin<Box<dyn Future<Output = (Result<(U256, U256, Bytes), Err>, Address)> + Send>>;
 type StorageFuture<Err> = Pin<Box<dyn Future<Output = (Result<U256, Err>, Address, U256)> + Send>>;
+type StorageBatchFuture<Err> =
+    Pin<Box<dyn Future<Output = (Result<Vec<U256>, Err>, Address, Vec<U256>)> + Send>>;
 type BlockHashFuture<Err> = Pin<Box<dyn Future<Output = (Result<B256, Err>, u64)> + Send>>;
 type FullBlockFuture<Err> =
     Pin<Box<dyn Future<Output = (FullBlockSender, Result<Option<Block>, Err>, BlockId)> + Send>>;
@@ -48,6 +94,7 @@ type TransactionSender = OneshotSender<DatabaseResult<Transaction>>;
 enum ProviderRequest<Err> {
     Account(AccountFuture<Err>),
     Storage(StorageFuture<Err>),
+    StorageBatch(StorageBatchFuture<Err>),
     BlockHash(BlockHashFuture<Err>),
     FullBlock(FullBlockFuture<Err>),
     Transaction(TransactionFuture<Err>),
@@ -60,6 +107,8 @@ enum BackendRequest {
     Basic(Address, AccountInfoSender),
     /// Fetch a storage slot
     Storage(Address, U256, StorageSender),
+    /// Fetch accounts and their storage slots at once
+    Prefetch(Vec<(Address, AccountInfoSender, Vec<(U256, StorageSender)>)>),
     /// Fetch a block hash
     BlockHash(u64, BlockHashSender),
     /// Fetch an entire block with transactions
@@ -68,6 +117,8 @@ enum BackendRequest {
     Transaction(B256, TransactionSender),
     /// Sets the pinned block to fetch data from
     SetPinnedBlock(BlockId),
+    /// Drops the requests in progress, failing their listeners
+    AbortPending,
 }
 
 /// Handles an internal provider and listens for requests.
@@ -85,6 +136,10 @@ pub struct BackendHandler<P> {
     account_requests: HashMap<Address, Vec<AccountInfoSender>>,
     /// Listeners that wait for a `get_storage_at` response
     storage_requests: HashMap<(Address, U256), Vec<StorageSender>>,
+    /// Storage slots requested during the current batching window, not fetched yet
+    unsent_storage_requests: HashMap<Address, Vec<U256>>,
+    /// The end of the current batching window, see [`STORAGE_BATCH_WINDOW`]
+    storage_batch_window: Option<Pin<Box<tokio::time::Sleep>>>,
     /// Listeners that wait for a `get_block` response
     block_requests: HashMap<u64, Vec<BlockHashSender>>,
     /// Incoming commands.
@@ -112,6 +167,8 @@ where
             pending_requests: Default::default(),
             account_requests: Default::default(),
             storage_requests: Default::default(),
+            unsent_storage_requests: Default::default(),
+            storage_batch_window: None,
             block_requests: Default::default(),
             queued_requests: Default::default(),
             incoming: rx,
@@ -131,8 +188,10 @@ where
                 trace!(target: "backendhandler", "received request basic address={:?}", addr);
                 let acc = self.db.accounts().read().get(&addr).cloned();
                 if let Some(basic) = acc {
+                    trace!(target: "backendhandler", %addr, "cache hit for account");
                     let _ = sender.send(Ok(basic));
                 } else {
+                    trace!(target: "backendhandler", %addr, "cache miss for account");
                     self.request_account(addr, sender);
                 }
             }
@@ -155,36 +214,101 @@ where
                 let value =
                     self.db.storage().read().get(&addr).and_then(|acc| acc.get(&idx).copied());
                 if let Some(value) = value {
+                    trace!(target: "backendhandler", %addr, %idx, "cache hit for storage");
                     let _ = sender.send(Ok(value));
                 } else {
+                    trace!(target: "backendhandler", %addr, %idx, "cache miss for storage");
                     // account present but not storage -> fetch storage
                     self.request_account_storage(addr, idx, sender);
                 }
             }
+            BackendRequest::Prefetch(accounts) => {
+                for (addr, sender, slots) in accounts {
+                    self.on_request(BackendRequest::Basic(addr, sender));
+                    for (idx, sender) in slots {
+                        self.on_request(BackendRequest::Storage(addr, idx, sender));
+                    }
+                }
+                // all the slots are known already, no need to wait for more
+                self.send_storage_requests();
+            }
             BackendRequest::SetPinnedBlock(block_id) => {
                 self.block_id = Some(block_id);
             }
+            BackendRequest::AbortPending => {
+                trace!(target: "backendhandler", "aborting pending requests");
+                // the listeners are dropped with their senders, some are held by the requests
+                self.pending_requests.clear();
+                self.account_requests.clear();
+                self.storage_requests.clear();
+                self.unsent_storage_requests.clear();
+                self.block_requests.clear();
+            }
         }
     }
 
     /// process a request for account's storage
+    ///
+    /// The slot is only fetched by [`Self::send_storage_requests`] once the batching window ends,
+    /// so that the slots of the same account requested concurrently can be fetched together.
     fn request_account_storage(&mut self, address: Address, idx: U256, listener: StorageSender) {
         match self.storage_requests.entry((address, idx)) {
             Entry::Occupied(mut entry) => {
                 entry.get_mut().push(listener);
             }
             Entry::Vacant(entry) => {
-                trace!(target: "backendhandler", %address, %idx, "preparing storage request");
                 entry.insert(vec![listener]);
-                let provider = self.provider.clone();
-                let block_id = self.block_id;
-                let fut = Box::pin(async move {
-                    let storage =
-                        provider.get_storage_at(address, idx, block_id).await.map_err(Into::into);
-                    (storage, address, idx)
-                });
-                self.pending_requests.push(ProviderRequest::Storage(fut));
+                self.unsent_storage_requests.entry(address).or_default().push(idx);
+            }
+        }
+    }
+
+    /// Fetches the storage slots requested during the batching window, with a single
+    /// `eth_getProof` request per account if there are at least [`MIN_BATCHED_SLOTS`] of its
+    /// slots.
+    fn send_storage_requests(&mut self) {
+        self.storage_batch_window = None;
+        for (address, slots) in std::mem::take(&mut self.unsent_storage_requests) {
+            let provider = self.provider.clone();
+            let block_id = self.block_id;
+            if slots.len() < MIN_BATCHED_SLOTS {
+                for idx in slots {
+                    trace!(target: "backendhandler", %address, %idx, "preparing storage request");
+                    let provider = provider.clone();
+                    let fut = Box::pin(async move {
+                        let storage = provider
+                            .get_storage_at(address, idx, block_id)
+                            .await
+                            .map_err(Into::into);
+                        (storage, address, idx)
+                    });
+                    self.pending_requests.push(ProviderRequest::Storage(fut));
+                }
+                continue
             }
+
+            trace!(target: "backendhandler", %address, slots = slots.len(), "preparing storage batch request");
+            let fut = Box::pin(async move {
+                let keys = slots.iter().map(|idx| B256::from(*idx)).collect();
+                let storage = match provider.get_proof(address, keys, block_id).await {
+                    Ok(proof) if proof.storage_proof.len() == slots.len() => {
+                        Ok(proof.storage_proof.into_iter().map(|proof| proof.value).collect())
+                    }
+                    // not every node serves `eth_getProof`, fall back to a request per slot
+                    res => {
+                        trace!(target: "backendhandler", %address, ok = res.is_ok(), "storage batch request failed");
+                        futures::future::try_join_all(
+                            slots
+                                .iter()
+                                .map(|idx| provider.get_storage_at(address, *idx, block_id)),
+                        )
+                        .await
+                        .map_err(Into::into)
+                    }
+                };
+                (storage, address, slots)
+            });
+            self.pending_requests.push(ProviderRequest::StorageBatch(fut));
         }
     }
 
@@ -296,6 +420,16 @@ where
                 pin.on_request(req)
             }
 
+            // fetch the requested storage slots once the batching window ends
+            if !pin.unsent_storage_requests.is_empty() {
+                let window = pin
+                    .storage_batch_window
+                    .get_or_insert_with(|| Box::pin(tokio::time::sleep(STORAGE_BATCH_WINDOW)));
+                if window.poll_unpin(cx).is_ready() {
+                    pin.send_storage_requests();
+                }
+            }
+
             // receive new requests to delegate to the underlying provider
             loop {
                 match Pin::new(&mut pin.incoming).poll_next(cx) {
@@ -392,6 +526,49 @@ where
                             continue;
                         }
                     }
+                    ProviderRequest::StorageBatch(fut) => {
+                        if let Poll::Ready((resp, addr, slots)) = fut.poll_unpin(cx) {
+                            let values = match resp {
+                                Ok(values) => values,
+                                Err(err) => {
+                                    // notify all listeners
+                                    let err = Arc::new(err);
+                                    for idx in slots {
+                                        if let Some(listeners) =
+                                            pin.storage_requests.remove(&(addr, idx))
+                                        {
+                                            listeners.into_iter().for_each(|l| {
+                                                let _ = l.send(Err(DatabaseError::GetStorage(
+                                                    addr,
+                                                    idx,
+                                                    Arc::clone(&err),
+                                                )));
+                                            })
+                                        }
+                                    }
+                                    continue;
+                                }
+                            };
+
+                            // update the cache
+                            pin.db
+                                .storage()
+                                .write()
+                                .entry(addr)
+                                .or_default()
+                                .extend(slots.iter().copied().zip(values.iter().copied()));
+
+                            // notify all listeners
+                            for (idx, value) in slots.into_iter().zip(values) {
+                                if let Some(listeners) = pin.storage_requests.remove(&(addr, idx)) {
+                                    listeners.into_iter().for_each(|l| {
+                                        let _ = l.send(Ok(value));
+                                    })
+                                }
+                            }
+                            continue;
+                        }
+                    }
                     ProviderRequest::BlockHash(fut) => {
                         if let Poll::Ready((block_hash, number)) = fut.poll_unpin(cx) {
                             let value = match block_hash {
@@ -573,6 +750,12 @@ impl SharedBackend {
         self.backend.clone().try_send(req).map_err(|e| eyre::eyre!("{:?}", e))
     }
 
+    /// Aborts the requests in progress, which then fail with an error
+    pub fn abort_pending_requests(&self) -> eyre::Result<()> {
+        let req = BackendRequest::AbortPending;
+        self.backend.clone().try_send(req).map_err(|e| eyre::eyre!("{:?}", e))
+    }
+
     /// Returns the full block for the given block identifier
     pub fn get_full_block(&self, block: impl Into<BlockId>) -> DatabaseResult<Block> {
         tokio::task::block_in_place(|| {
@@ -593,31 +776,94 @@ impl SharedBackend {
         })
     }
 
+    /// Fetches the given accounts and storage slots that aren't cached yet, at once.
+    ///
+    /// The slots of an account are fetched with a single request if there are enough of them, see
+    /// [`MIN_BATCHED_SLOTS`].
+    pub fn prefetch(&self, accounts: &[(Address, Vec<U256>)]) -> DatabaseResult<()> {
+        let db = self.cache.0.db();
+        let mut requests = Vec::with_capacity(accounts.len());
+        let mut account_receivers = Vec::new();
+        let mut storage_receivers = Vec::new();
+        {
+            let (cached_accounts, cached_storage) = (db.accounts.read(), db.storage.read());
+            for (address, slots) in accounts {
+                let cached_slots = cached_storage.get(address);
+                let slots = slots
+                    .iter()
+                    .filter(|idx| cached_slots.map_or(true, |cached| !cached.contains_key(idx)))
+                    .map(|idx| {
+                        let (sender, rx) = oneshot_channel();
+                        storage_receivers.push(rx);
+                        (*idx, sender)
+                    })
+                    .collect::<Vec<_>>();
+                if slots.is_empty() && cached_accounts.contains_key(address) {
+                    continue
+                }
+                let (sender, rx) = oneshot_channel();
+                if !cached_accounts.contains_key(address) {
+                    account_receivers.push(rx);
+                }
+                requests.push((*address, sender, slots));
+            }
+        }
+        if requests.is_empty() {
+            return Ok(())
+        }
+
+        let fetches = (account_receivers.len() + storage_receivers.len()) as u64;
+        let start = Instant::now();
+        let res = tokio::task::block_in_place(|| {
+            self.backend.clone().try_send(BackendRequest::Prefetch(requests))?;
+            for rx in account_receivers {
+                rx.recv()??;
+            }
+            for rx in storage_receivers {
+                rx.recv()??;
+            }
+            Ok(())
+        });
+        FetchStats::record(fetches, start.elapsed());
+        res
+    }
+
     fn do_get_basic(&self, address: Address) -> DatabaseResult<Option<AccountInfo>> {
-        tokio::task::block_in_place(|| {
-            let (sender, rx) = oneshot_channel();
-            let req = BackendRequest::Basic(address, sender);
-            self.backend.clone().try_send(req)?;
-            rx.recv()?.map(Some)
-        })
+        if let Some(info) = self.cache.0.db().accounts.read().get(&address).cloned() {
+            return Ok(Some(info))
+        }
+        self.fetch(|sender| BackendRequest::Basic(address, sender)).map(Some)
     }
 
     fn do_get_storage(&self, address: Address, index: U256) -> DatabaseResult<U256> {
-        tokio::task::block_in_place(|| {
-            let (sender, rx) = oneshot_channel();
-            let req = BackendRequest::Storage(address, index, sender);
-            self.backend.clone().try_send(req)?;
-            rx.recv()?
-        })
+        let db = self.cache.0.db();
+        if let Some(value) = db.storage.read().get(&address).and_then(|s| s.get(&index).copied()) {
+            return Ok(value)
+        }
+        self.fetch(|sender| BackendRequest::Storage(address, index, sender))
     }
 
     fn do_get_block_hash(&self, number: u64) -> DatabaseResult<B256> {
-        tokio::task::block_in_place(|| {
+        if let Some(hash) = self.cache.0.db().block_hashes.read().get(&U256::from(number)).copied()
+        {
+            return Ok(hash)
+        }
+        self.fetch(|sender| BackendRequest::BlockHash(number, sender))
+    }
+
+    /// Sends the request for a value that isn't cached to the `BackendHandler`, and waits for it.
+    fn fetch<T>(
+        &self,
+        req: impl FnOnce(OneshotSender<DatabaseResult<T>>) -> BackendRequest,
+    ) -> DatabaseResult<T> {
+        let start = Instant::now();
+        let res = tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
-            let req = BackendRequest::BlockHash(number, sender);
-            self.backend.clone().try_send(req)?;
+            self.backend.clone().try_send(req(sender))?;
             rx.recv()?
-        })
+        });
+        FetchStats::record(1, start.elapsed());
+        res
     }
 
     /// Flushes the DB to disk if caching is enabled
@@ -731,6 +977,39 @@ mod tests {
         assert_eq!(slots.len() as u64, max_slots);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_prefetch() {
+        let Some(endpoint) = ENDPOINT else { return };
+
+        let provider = get_http_provider(endpoint);
+        let meta = BlockchainDbMeta {
+            cfg_env: Default::default(),
+            block_env: Default::default(),
+            hosts: BTreeSet::from([endpoint.to_string()]),
+        };
+
+        let db = BlockchainDb::new(meta, None);
+        let backend = SharedBackend::spawn_backend(Arc::new(provider), db.clone(), None).await;
+
+        // some rng contract from etherscan
+        let address: Address = "63091244180ae240c87d1f528f5f269134cb07b3".parse().unwrap();
+        let slots = (0..5u64).map(U256::from).collect::<Vec<_>>();
+
+        FetchStats::take();
+        backend.prefetch(&[(address, slots.clone())]).unwrap();
+        assert_eq!(FetchStats::take().fetches, 1 + slots.len() as u64);
+        assert!(db.accounts().read().contains_key(&address));
+        assert_eq!(db.storage().read().get(&address).unwrap().len(), slots.len());
+
+        // everything is cached now
+        backend.prefetch(&[(address, slots.clone())]).unwrap();
+        for idx in slots {
+            backend.storage_ref(address, idx).unwrap();
+        }
+        backend.basic_ref(address).unwrap();
+        assert_eq!(FetchStats::take(), FetchStats::default());
+    }
+
     #[test]
     fn can_read_cache() {
         let cache_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-data/storage.json");
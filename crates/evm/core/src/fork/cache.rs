@@ -11,7 +11,7 @@ use std::{
     collections::BTreeSet,
     fs,
     io::{BufWriter, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use url::Url;
@@ -141,10 +141,20 @@ impl BlockchainDbMeta {
 }
 
 // ignore hosts to not invalidate the cache when different endpoints are used, as it's commonly the
-// case for http vs ws endpoints
+// case for http vs ws endpoints.
+//
+// Only the chain and the forked block identify the cached data, settings that configure how
+// transactions are executed locally (spec id, gas limit, disabled checks, ...) commonly differ
+// between anvil and forge forking the same block and are ignored to allow both to share the cache
 impl PartialEq for BlockchainDbMeta {
     fn eq(&self, other: &Self) -> bool {
-        self.cfg_env == other.cfg_env && self.block_env == other.block_env
+        let (block, other_block) = (&self.block_env, &other.block_env);
+        self.cfg_env.chain_id == other.cfg_env.chain_id &&
+            block.number == other_block.number &&
+            block.timestamp == other_block.timestamp &&
+            block.coinbase == other_block.coinbase &&
+            block.difficulty == other_block.difficulty &&
+            block.prevrandao == other_block.prevrandao
     }
 }
 
@@ -357,10 +367,18 @@ impl JsonBlockCacheDB {
     pub fn load(path: impl Into<PathBuf>) -> eyre::Result<Self> {
         let path = path.into();
         trace!(target: "cache", ?path, "reading json cache");
-        let contents = std::fs::read_to_string(&path).map_err(|err| {
-            warn!(?err, ?path, "Failed to read cache file");
-            err
-        })?;
+        // don't leave a lock file behind for caches that don't exist
+        if !path.is_file() {
+            eyre::bail!("cache file {} does not exist", path.display());
+        }
+        let contents = {
+            let mut lock = cache_lock(&path)?;
+            let _guard = lock.read()?;
+            std::fs::read_to_string(&path).map_err(|err| {
+                warn!(?err, ?path, "Failed to read cache file");
+                err
+            })?
+        };
         let data = serde_json::from_str(&contents).map_err(|err| {
             warn!(target: "cache", ?err, ?path, "Failed to deserialize cache data");
             err
@@ -393,7 +411,19 @@ impl JsonBlockCacheDB {
             let _ = fs::create_dir_all(parent);
         }
 
-        let file = match fs::File::create(path) {
+        // the cache file is shared by all forge and anvil processes forking the same block
+        let mut lock = match cache_lock(path) {
+            Ok(lock) => lock,
+            Err(e) => return warn!(target: "cache", %e, "Failed to open json cache lock"),
+        };
+        let _guard = match lock.write() {
+            Ok(guard) => guard,
+            Err(e) => return warn!(target: "cache", %e, "Failed to lock json cache"),
+        };
+
+        // write to a temporary file first so readers never observe a partially written cache
+        let tmp_path = path.with_extension("json.tmp");
+        let file = match fs::File::create(&tmp_path) {
             Ok(file) => file,
             Err(e) => return warn!(target: "cache", %e, "Failed to open json cache for writing"),
         };
@@ -405,11 +435,26 @@ impl JsonBlockCacheDB {
         if let Err(e) = writer.flush() {
             return warn!(target: "cache", %e, "Failed to flush to json cache")
         }
+        drop(writer);
+        if let Err(e) = fs::rename(&tmp_path, path) {
+            return warn!(target: "cache", %e, "Failed to replace json cache")
+        }
 
         trace!(target: "cache", "saved json cache");
     }
 }
 
+/// Returns the lock guarding concurrent access to the cache file at `path`
+fn cache_lock(path: &Path) -> std::io::Result<fd_lock::RwLock<fs::File>> {
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path.with_extension("lock"))?;
+    Ok(fd_lock::RwLock::new(file))
+}
+
 /// The Data the [JsonBlockCacheDB] can read and flush
 ///
 /// This will be deserialized in a JSON object with the keys:
@@ -481,6 +526,50 @@ impl Drop for FlushJsonBlockCacheDB {
 mod tests {
     use super::*;
 
+    #[test]
+    fn can_share_cache_across_envs() {
+        let dir = std::env::temp_dir().join(format!("foundry-rpc-cache-{}", std::process::id()));
+        let path = dir.join("storage.json");
+
+        let mut env = revm::primitives::Env::default();
+        env.block.number = U256::from(1);
+        let meta = BlockchainDbMeta::new(env.clone(), "http://localhost:8545".to_string());
+        let db = BlockchainDb::new(meta, Some(path.clone()));
+        db.accounts().write().insert(Address::ZERO, AccountInfo::default());
+        db.cache().flush();
+        assert!(path.exists());
+        assert!(!path.with_extension("json.tmp").exists());
+
+        // a different env at the same block, e.g. anvil's, reuses the cached data
+        env.block.gas_limit = U256::from(1);
+        env.cfg.disable_eip3607 = true;
+        let meta = BlockchainDbMeta::new(env.clone(), "ws://localhost:8546".to_string());
+        let db = BlockchainDb::new(meta, Some(path.clone()));
+        assert!(db.accounts().read().contains_key(&Address::ZERO));
+
+        // but not at another block
+        env.block.number = U256::from(2);
+        let meta = BlockchainDbMeta::new(env.clone(), "http://localhost:8545".to_string());
+        let db = BlockchainDb::new(meta, Some(path.clone()));
+        assert!(db.accounts().read().is_empty());
+
+        // or with a different block env
+        env.block.number = U256::from(1);
+        env.block.timestamp = U256::from(1);
+        let meta = BlockchainDbMeta::new(env.clone(), "http://localhost:8545".to_string());
+        let db = BlockchainDb::new(meta, Some(path.clone()));
+        assert!(db.accounts().read().is_empty());
+
+        // or on another chain
+        env.block.timestamp = U256::ZERO;
+        env.cfg.chain_id = 10;
+        let meta = BlockchainDbMeta::new(env, "http://localhost:8545".to_string());
+        let db = BlockchainDb::new(meta, Some(path));
+        assert!(db.accounts().read().is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
     #[test]
     fn can_deserialize_cache() {
         let s = r#"{
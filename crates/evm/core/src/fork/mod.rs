@@ -2,7 +2,7 @@ use super::opts::EvmOpts;
 use revm::primitives::Env;
 
 mod backend;
-pub use backend::{BackendHandler, SharedBackend};
+pub use backend::{BackendHandler, FetchStats, SharedBackend};
 
 mod init;
 pub use init::environment;
@@ -48,6 +48,12 @@ impl ForkId {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Returns the block number the fork is pinned to, unless it's at the `latest` block.
+    pub fn block_number(&self) -> Option<u64> {
+        let (_, num) = self.0.rsplit_once('@')?;
+        u64::from_str_radix(num.strip_prefix("0x")?, 16).ok()
+    }
 }
 
 impl fmt::Display for ForkId {
@@ -167,6 +173,14 @@ impl MultiFork {
         self.handler.clone().try_send(req).map_err(|e| eyre::eyre!("{:?}", e))?;
         Ok(rx.recv()?)
     }
+
+    /// Aborts the forks being created and the in-flight RPC requests of all forks, whose callers
+    /// get an error
+    pub fn abort_pending_requests(&self) -> eyre::Result<()> {
+        trace!(target: "fork::multi", "aborting pending requests");
+        let req = Request::AbortPendingRequests;
+        self.handler.clone().try_send(req).map_err(|e| eyre::eyre!("{:?}", e))
+    }
 }
 
 type Handler = BackendHandler<Arc<Provider<BoxTransport>>>;
@@ -191,6 +205,8 @@ enum Request {
     ShutDown(OneshotSender<()>),
     /// Returns the Fork Url for the `ForkId` if it exists
     GetForkUrl(ForkId, OneshotSender<Option<String>>),
+    /// Aborts the forks being created and the in-flight requests of all forks
+    AbortPendingRequests,
 }
 
 enum ForkTask {
@@ -319,6 +335,13 @@ impl MultiForkHandler {
                 let fork = self.forks.get(&fork_id).map(|f| f.opts.url.clone());
                 let _ = sender.send(fork);
             }
+            Request::AbortPendingRequests => {
+                // dropping the senders of the forks being created fails their receivers
+                self.pending_tasks.clear();
+                for fork in self.forks.values() {
+                    let _ = fork.backend.abort_pending_requests();
+                }
+            }
         }
     }
 }
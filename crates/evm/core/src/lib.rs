@@ -16,5 +16,6 @@ pub mod debug;
 pub mod decode;
 pub mod fork;
 pub mod opts;
+pub mod precompiles;
 pub mod snapshot;
 pub mod utils;
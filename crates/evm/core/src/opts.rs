@@ -65,6 +65,10 @@ pub struct EvmOpts {
     /// Whether to enable isolation of calls.
     pub isolate: bool,
 
+    /// The number of seconds the block timestamp is advanced by for every isolated transaction,
+    /// which is then executed in a new block.
+    pub isolate_block_advance: Option<u64>,
+
     /// Whether to disable block gas limit checks.
     pub disable_block_gas_limit: bool,
 }
@@ -0,0 +1,520 @@
+//! User-defined precompiles loaded from plugins or compiled artifacts.
+//!
+//! A native plugin is a shared library (`.so`, `.dylib` or `.dll`) exporting the following C ABI:
+//!
+//! ```c
+//! // Returns the gas cost of executing the precompile with the given input.
+//! uint64_t foundry_precompile_gas(const uint8_t *input, size_t input_len);
+//!
+//! // Executes the precompile. On return `*out` points to a buffer of `*out_len` bytes owned by
+//! // the plugin, or is left `NULL` for empty output. Returns `0` on success; any other value
+//! // reverts the call, using the output as revert data.
+//! int32_t foundry_precompile_run(const uint8_t *input, size_t input_len, uint8_t **out,
+//!                                size_t *out_len);
+//!
+//! // Releases a buffer previously returned by `foundry_precompile_run`.
+//! void foundry_precompile_free(uint8_t *out, size_t out_len);
+//! ```
+//!
+//! A WebAssembly plugin (`.wasm`) is a module without imports exporting its linear memory as
+//! `memory` and the same functions, with 32-bit pointers into that memory, plus an allocator for
+//! the buffers passed to it:
+//!
+//! ```text
+//! foundry_precompile_alloc(len: i32) -> i32
+//! foundry_precompile_free(ptr: i32, len: i32)
+//! foundry_precompile_gas(input: i32, input_len: i32) -> i64
+//! foundry_precompile_run(input: i32, input_len: i32, out: i32, out_len: i32) -> i32
+//! ```
+//!
+//! The input and the `out`/`out_len` slots of `foundry_precompile_run` are allocated with
+//! `foundry_precompile_alloc` and released with `foundry_precompile_free` after each call. A trap
+//! while computing the gas cost exhausts the gas of the call, a trap while executing reverts it
+//! with empty revert data.
+//!
+//! Calls to an address with a registered precompile never execute the account's code: the gas
+//! returned by `foundry_precompile_gas` is charged up front and the call halts with
+//! `PrecompileOOG` if it exceeds the gas limit of the call.
+//!
+//! A precompile can also be implemented by the runtime bytecode of a compiled contract. It is
+//! executed in an empty state, like native precompiles it can't read or write the state of the
+//! chain, and is charged the gas its execution used.
+
+use alloy_primitives::{address, Address, Bytes, U256};
+use eyre::{Result, WrapErr};
+use parking_lot::Mutex;
+use revm::{
+    db::{CacheDB, EmptyDB},
+    interpreter::{Gas, InstructionResult},
+    primitives::{
+        AccountInfo, Bytecode, ExecutionResult, ResultAndState, SpecId, TransactTo, TxEnv,
+    },
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+type GasFn = unsafe extern "C" fn(*const u8, usize) -> u64;
+type RunFn = unsafe extern "C" fn(*const u8, usize, *mut *mut u8, *mut usize) -> i32;
+type FreeFn = unsafe extern "C" fn(*mut u8, usize);
+
+/// The address the bytecode of an artifact precompile is executed at.
+const BYTECODE_PRECOMPILE_ADDRESS: Address = address!("c0dec0dec0dec0dec0dec0dec0dec0dec0dec0de");
+
+/// A user-defined precompile.
+pub struct CustomPrecompile {
+    path: PathBuf,
+    kind: PrecompileKind,
+}
+
+enum PrecompileKind {
+    Native(NativePlugin),
+    Wasm(Mutex<WasmPlugin>),
+    Bytecode { code: Bytecode, spec_id: SpecId },
+}
+
+impl fmt::Debug for CustomPrecompile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            PrecompileKind::Native(_) => "native",
+            PrecompileKind::Wasm(_) => "wasm",
+            PrecompileKind::Bytecode { .. } => "bytecode",
+        };
+        f.debug_struct("CustomPrecompile")
+            .field("path", &self.path)
+            .field("kind", &kind)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CustomPrecompile {
+    /// Loads the plugin at the given path, a WebAssembly module if its extension is `.wasm` and
+    /// a native library otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let kind = if path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("wasm")) {
+            PrecompileKind::Wasm(Mutex::new(WasmPlugin::load(path)?))
+        } else {
+            PrecompileKind::Native(NativePlugin::load(path)?)
+        };
+        Ok(Self { path: path.to_path_buf(), kind })
+    }
+
+    /// Creates a precompile executing the runtime bytecode of the artifact at the given path.
+    pub fn from_bytecode(path: impl Into<PathBuf>, code: Bytes, spec_id: SpecId) -> Self {
+        let code = Bytecode::new_raw(code).to_checked();
+        Self { path: path.into(), kind: PrecompileKind::Bytecode { code, spec_id } }
+    }
+
+    /// Returns the path of the plugin or artifact.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Executes the precompile as the target of a call with the given gas limit.
+    ///
+    /// Returns the outcome in the shape expected by [`revm::Inspector::call`].
+    pub fn call(&self, input: &[u8], gas_limit: u64) -> (InstructionResult, Gas, Bytes) {
+        let (cost, result) = match &self.kind {
+            PrecompileKind::Native(plugin) => {
+                let cost = plugin.gas_cost(input);
+                (cost, (cost <= gas_limit).then(|| plugin.execute(input)))
+            }
+            PrecompileKind::Wasm(plugin) => {
+                let mut plugin = plugin.lock();
+                let cost = plugin.gas_cost(input).unwrap_or(u64::MAX);
+                (cost, (cost <= gas_limit).then(|| plugin.execute(input)))
+            }
+            PrecompileKind::Bytecode { code, spec_id } => {
+                return execute_bytecode(code, *spec_id, input, gas_limit)
+            }
+        };
+
+        let mut gas = Gas::new(gas_limit);
+        match result {
+            None => {
+                gas.record_cost(gas_limit);
+                (InstructionResult::PrecompileOOG, gas, Bytes::new())
+            }
+            Some(result) => {
+                gas.record_cost(cost);
+                match result {
+                    Ok(output) => (InstructionResult::Return, gas, output),
+                    Err(output) => (InstructionResult::Revert, gas, output),
+                }
+            }
+        }
+    }
+}
+
+/// A precompile implemented by a native plugin library.
+struct NativePlugin {
+    gas: GasFn,
+    run: RunFn,
+    free: FreeFn,
+    /// Keeps the symbols above alive, must outlive them.
+    _library: libloading::Library,
+}
+
+impl NativePlugin {
+    fn load(path: &Path) -> Result<Self> {
+        // SAFETY: loading a library runs its initialization routines; plugins are explicitly
+        // provided by the user.
+        let library = unsafe { libloading::Library::new(path) }
+            .wrap_err_with(|| format!("failed to load precompile plugin {}", path.display()))?;
+
+        // SAFETY: the symbol types are the documented plugin ABI.
+        let (gas, run, free) = unsafe {
+            (
+                *library.get::<GasFn>(b"foundry_precompile_gas\0")?,
+                *library.get::<RunFn>(b"foundry_precompile_run\0")?,
+                *library.get::<FreeFn>(b"foundry_precompile_free\0")?,
+            )
+        };
+
+        Ok(Self { gas, run, free, _library: library })
+    }
+
+    /// Returns the gas cost of executing the precompile with the given input.
+    fn gas_cost(&self, input: &[u8]) -> u64 {
+        // SAFETY: the input buffer is valid for `input.len()` bytes.
+        unsafe { (self.gas)(input.as_ptr(), input.len()) }
+    }
+
+    /// Executes the precompile, returning `Err` with the revert data if it failed.
+    fn execute(&self, input: &[u8]) -> Result<Bytes, Bytes> {
+        let mut out = std::ptr::null_mut();
+        let mut out_len = 0usize;
+        // SAFETY: the input buffer is valid and the out pointers point to valid locals.
+        let status = unsafe { (self.run)(input.as_ptr(), input.len(), &mut out, &mut out_len) };
+        let output = if out.is_null() {
+            Bytes::new()
+        } else {
+            // SAFETY: the plugin returned a buffer of `out_len` bytes which we release right
+            // after copying it.
+            unsafe {
+                let output = Bytes::copy_from_slice(std::slice::from_raw_parts(out, out_len));
+                (self.free)(out, out_len);
+                output
+            }
+        };
+        if status == 0 {
+            Ok(output)
+        } else {
+            Err(output)
+        }
+    }
+}
+
+/// A precompile implemented by a WebAssembly module.
+struct WasmPlugin {
+    store: wasmi::Store<()>,
+    memory: wasmi::Memory,
+    alloc: wasmi::TypedFunc<u32, u32>,
+    free: wasmi::TypedFunc<(u32, u32), ()>,
+    gas: wasmi::TypedFunc<(u32, u32), u64>,
+    run: wasmi::TypedFunc<(u32, u32, u32, u32), i32>,
+}
+
+impl WasmPlugin {
+    fn load(path: &Path) -> Result<Self> {
+        let wasm = std::fs::read(path)
+            .wrap_err_with(|| format!("failed to read precompile plugin {}", path.display()))?;
+        Self::new(&wasm)
+            .wrap_err_with(|| format!("failed to load precompile plugin {}", path.display()))
+    }
+
+    fn new(wasm: &[u8]) -> Result<Self> {
+        let engine = wasmi::Engine::default();
+        let module = wasmi::Module::new(&engine, wasm)?;
+        let mut store = wasmi::Store::new(&engine, ());
+        let instance = wasmi::Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)?
+            .start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| eyre::eyre!("the module doesn't export its memory as `memory`"))?;
+        Ok(Self {
+            memory,
+            alloc: instance.get_typed_func(&store, "foundry_precompile_alloc")?,
+            free: instance.get_typed_func(&store, "foundry_precompile_free")?,
+            gas: instance.get_typed_func(&store, "foundry_precompile_gas")?,
+            run: instance.get_typed_func(&store, "foundry_precompile_run")?,
+            store,
+        })
+    }
+
+    /// Copies the input into a buffer allocated by the module.
+    fn write_input(&mut self, input: &[u8]) -> Result<(u32, u32)> {
+        let len = u32::try_from(input.len())?;
+        let ptr = self.alloc.call(&mut self.store, len)?;
+        self.memory
+            .write(&mut self.store, ptr as usize, input)
+            .map_err(|err| eyre::eyre!("failed to write the input: {err}"))?;
+        Ok((ptr, len))
+    }
+
+    /// Returns the gas cost of executing the precompile with the given input.
+    fn gas_cost(&mut self, input: &[u8]) -> Result<u64> {
+        let (ptr, len) = self.write_input(input)?;
+        let cost = self.gas.call(&mut self.store, (ptr, len));
+        self.free.call(&mut self.store, (ptr, len))?;
+        Ok(cost?)
+    }
+
+    /// Executes the precompile, returning `Err` with the revert data if it failed.
+    fn execute(&mut self, input: &[u8]) -> Result<Bytes, Bytes> {
+        self.try_execute(input).unwrap_or_else(|err| {
+            trace!(%err, "wasm precompile trapped");
+            Err(Bytes::new())
+        })
+    }
+
+    fn try_execute(&mut self, input: &[u8]) -> Result<Result<Bytes, Bytes>> {
+        let (ptr, len) = self.write_input(input)?;
+        // the `out` and `out_len` slots
+        let slots = self.alloc.call(&mut self.store, 8)?;
+        self.memory
+            .write(&mut self.store, slots as usize, &[0; 8])
+            .map_err(|err| eyre::eyre!("failed to write the output slots: {err}"))?;
+        let status = self.run.call(&mut self.store, (ptr, len, slots, slots + 4))?;
+
+        let mut words = [0; 8];
+        self.memory
+            .read(&self.store, slots as usize, &mut words)
+            .map_err(|err| eyre::eyre!("failed to read the output slots: {err}"))?;
+        let out = u32::from_le_bytes(words[..4].try_into().unwrap());
+        let out_len = u32::from_le_bytes(words[4..].try_into().unwrap());
+        let mut output = vec![0; out_len as usize];
+        if out != 0 {
+            self.memory
+                .read(&self.store, out as usize, &mut output)
+                .map_err(|err| eyre::eyre!("failed to read the output: {err}"))?;
+            self.free.call(&mut self.store, (out, out_len))?;
+        }
+        self.free.call(&mut self.store, (slots, 8))?;
+        self.free.call(&mut self.store, (ptr, len))?;
+
+        let output = Bytes::from(output);
+        Ok(if status == 0 { Ok(output) } else { Err(output) })
+    }
+}
+
+/// Executes the bytecode of an artifact precompile in an empty state.
+///
+/// The call is run as a transaction, whose intrinsic gas isn't charged to the precompile.
+fn execute_bytecode(
+    code: &Bytecode,
+    spec_id: SpecId,
+    input: &[u8],
+    gas_limit: u64,
+) -> (InstructionResult, Gas, Bytes) {
+    let intrinsic_gas =
+        21_000 + input.iter().map(|&byte| if byte == 0 { 4 } else { 16 }).sum::<u64>();
+
+    let mut db = CacheDB::new(EmptyDB::default());
+    db.insert_account_info(
+        BYTECODE_PRECOMPILE_ADDRESS,
+        AccountInfo { code_hash: code.hash_slow(), code: Some(code.clone()), ..Default::default() },
+    );
+    let mut evm = revm::EVM::new();
+    evm.env.cfg.spec_id = spec_id;
+    evm.env.tx = TxEnv {
+        transact_to: TransactTo::Call(BYTECODE_PRECOMPILE_ADDRESS),
+        data: Bytes::copy_from_slice(input),
+        gas_limit: gas_limit.saturating_add(intrinsic_gas),
+        gas_price: U256::ZERO,
+        ..Default::default()
+    };
+    evm.database(db);
+
+    let mut gas = Gas::new(gas_limit);
+    let (result, used, output) = match evm.transact_ref() {
+        Ok(ResultAndState { result, .. }) => match result {
+            ExecutionResult::Success { gas_used, gas_refunded, output, .. } => {
+                (InstructionResult::Return, gas_used + gas_refunded, output.into_data())
+            }
+            ExecutionResult::Revert { gas_used, output } => {
+                (InstructionResult::Revert, gas_used, output)
+            }
+            ExecutionResult::Halt { .. } => {
+                (InstructionResult::PrecompileError, u64::MAX, Bytes::new())
+            }
+        },
+        Err(_) => (InstructionResult::PrecompileError, u64::MAX, Bytes::new()),
+    };
+    gas.record_cost(used.saturating_sub(intrinsic_gas).min(gas_limit));
+    (result, gas, output)
+}
+
+/// A set of user-defined precompiles, keyed by address.
+#[derive(Clone, Debug, Default)]
+pub struct CustomPrecompiles {
+    inner: HashMap<Address, Arc<CustomPrecompile>>,
+}
+
+impl CustomPrecompiles {
+    /// Loads all the given `(address, plugin path)` pairs.
+    pub fn load<'a>(specs: impl IntoIterator<Item = &'a PrecompileSpec>) -> Result<Self> {
+        let mut precompiles = Self::default();
+        for spec in specs {
+            precompiles.insert(spec.address, CustomPrecompile::load(&spec.path)?);
+        }
+        Ok(precompiles)
+    }
+
+    /// Registers a precompile at the given address, replacing any existing one.
+    pub fn insert(&mut self, address: Address, precompile: CustomPrecompile) {
+        self.inner.insert(address, Arc::new(precompile));
+    }
+
+    /// Returns the precompile registered at the given address, if any.
+    pub fn get(&self, address: &Address) -> Option<&Arc<CustomPrecompile>> {
+        self.inner.get(address)
+    }
+
+    /// Returns `true` if a precompile is registered at the given address.
+    pub fn contains(&self, address: &Address) -> bool {
+        self.inner.contains_key(address)
+    }
+
+    /// Returns the addresses of all registered precompiles.
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.inner.keys()
+    }
+
+    /// Returns `true` if no precompiles are registered.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// A `<address>=<path>` precompile registration, as passed on the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecompileSpec {
+    /// The address the precompile is registered at.
+    pub address: Address,
+    /// The path to the plugin library.
+    pub path: PathBuf,
+}
+
+impl FromStr for PrecompileSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, path) =
+            s.split_once('=').ok_or_else(|| format!("expected <address>=<path>, got `{s}`"))?;
+        let address = parse_precompile_address(address.trim())?;
+        let path = path.trim();
+        if path.is_empty() {
+            return Err(format!("missing plugin path for precompile at {address}"));
+        }
+        Ok(Self { address, path: path.into() })
+    }
+}
+
+/// Parses a precompile address, accepting short hex forms such as `0x100`.
+fn parse_precompile_address(s: &str) -> Result<Address, String> {
+    if let Ok(address) = s.parse::<Address>() {
+        return Ok(address);
+    }
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    if hex.is_empty() || hex.len() > 40 {
+        return Err(format!("invalid precompile address `{s}`"));
+    }
+    let padded = format!("{hex:0>40}");
+    padded.parse().map_err(|_| format!("invalid precompile address `{s}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn parse_precompile_spec() {
+        let spec: PrecompileSpec = "0x100=./bls.so".parse().unwrap();
+        assert_eq!(spec.address, address!("0000000000000000000000000000000000000100"));
+        assert_eq!(spec.path, PathBuf::from("./bls.so"));
+
+        let spec: PrecompileSpec =
+            "0x0000000000000000000000000000000000000103=/tmp/oracle.dylib".parse().unwrap();
+        assert_eq!(spec.address, address!("0000000000000000000000000000000000000103"));
+
+        assert!("0x100".parse::<PrecompileSpec>().is_err());
+        assert!("0x100=".parse::<PrecompileSpec>().is_err());
+        assert!("zz=./a.so".parse::<PrecompileSpec>().is_err());
+    }
+
+    /// Echoes its input, reverts on empty input, and costs 100 gas plus 1 per input byte.
+    const ECHO_PLUGIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (func (export "foundry_precompile_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "foundry_precompile_free") (param i32 i32))
+  (func (export "foundry_precompile_gas") (param $input i32) (param $len i32) (result i64)
+    (i64.add (i64.const 100) (i64.extend_i32_u (local.get $len))))
+  (func (export "foundry_precompile_run")
+    (param $input i32) (param $len i32) (param $out i32) (param $out_len i32) (result i32)
+    (if (i32.eqz (local.get $len)) (then (return (i32.const 1))))
+    (i32.store (local.get $out) (local.get $input))
+    (i32.store (local.get $out_len) (local.get $len))
+    (i32.const 0)))
+"#;
+
+    #[test]
+    fn loads_wasm_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("echo.wasm");
+        std::fs::write(&path, wat::parse_str(ECHO_PLUGIN).unwrap()).unwrap();
+        let spec: PrecompileSpec = format!("0x100={}", path.display()).parse().unwrap();
+        let precompiles = CustomPrecompiles::load([&spec]).unwrap();
+        let precompile = precompiles.get(&spec.address).unwrap();
+
+        let (result, gas, output) = precompile.call(b"hello", 1_000);
+        assert_eq!(result, InstructionResult::Return);
+        assert_eq!(gas.spend(), 105);
+        assert_eq!(output, Bytes::from_static(b"hello"));
+
+        let (result, _, output) = precompile.call(b"", 1_000);
+        assert_eq!(result, InstructionResult::Revert);
+        assert!(output.is_empty());
+
+        let (result, gas, _) = precompile.call(b"hello", 104);
+        assert_eq!(result, InstructionResult::PrecompileOOG);
+        assert_eq!(gas.spend(), 104);
+    }
+
+    #[test]
+    fn rejects_invalid_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.wasm");
+        std::fs::write(&path, wat::parse_str("(module)").unwrap()).unwrap();
+        let err = CustomPrecompile::load(&path).unwrap_err();
+        assert!(format!("{err:#}").contains("memory"), "{err:#}");
+
+        assert!(CustomPrecompile::load(dir.path().join("missing.so")).is_err());
+    }
+
+    #[test]
+    fn executes_bytecode_precompiles() {
+        // returns the calldata size: CALLDATASIZE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+        let code = Bytes::from_static(&[0x36, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+        let precompile = CustomPrecompile::from_bytecode("Size.json", code, SpecId::CANCUN);
+
+        let (result, gas, output) = precompile.call(&[1, 2, 3], 100_000);
+        assert_eq!(result, InstructionResult::Return);
+        assert_eq!(output, Bytes::from(U256::from(3).to_be_bytes::<32>().to_vec()));
+        // 2 + 3 + 3 (+3 memory expansion) + 3 + 3 + 0
+        assert_eq!(gas.spend(), 17);
+    }
+}
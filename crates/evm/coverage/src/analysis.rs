@@ -78,11 +78,10 @@ impl<'a> ContractVisitor<'a> {
 
         match node.body.take() {
             Some(body) => {
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Function { name },
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_item(CoverageItem::new(
+                    CoverageItemKind::Function { name },
+                    self.source_location_for(&node.src),
+                ));
                 self.visit_block(*body)
             }
             _ => Ok(()),
@@ -120,21 +119,19 @@ impl<'a> ContractVisitor<'a> {
             NodeType::YulBreak |
             NodeType::YulContinue |
             NodeType::YulLeave => {
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Statement,
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_item(CoverageItem::new(
+                    CoverageItemKind::Statement,
+                    self.source_location_for(&node.src),
+                ));
                 Ok(())
             }
 
             // Return with eventual subcall
             NodeType::Return => {
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Statement,
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_item(CoverageItem::new(
+                    CoverageItemKind::Statement,
+                    self.source_location_for(&node.src),
+                ));
                 if let Some(expr) = node.attribute("expression") {
                     self.visit_expression(expr)?;
                 }
@@ -143,11 +140,10 @@ impl<'a> ContractVisitor<'a> {
 
             // Variable declaration
             NodeType::VariableDeclarationStatement => {
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Statement,
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_item(CoverageItem::new(
+                    CoverageItemKind::Statement,
+                    self.source_location_for(&node.src),
+                ));
                 if let Some(expr) = node.attribute("initialValue") {
                     self.visit_expression(expr)?;
                 }
@@ -248,11 +244,10 @@ impl<'a> ContractVisitor<'a> {
                 // branch ID as we do
                 self.branch_id += 1;
 
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Branch { branch_id, path_id: 0 },
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_item(CoverageItem::new(
+                    CoverageItemKind::Branch { branch_id, path_id: 0 },
+                    self.source_location_for(&node.src),
+                ));
                 self.visit_block(*body)?;
 
                 Ok(())
@@ -282,19 +277,17 @@ impl<'a> ContractVisitor<'a> {
         //  yulfunctioncall
         match node.node_type {
             NodeType::Assignment | NodeType::UnaryOperation => {
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Statement,
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_item(CoverageItem::new(
+                    CoverageItemKind::Statement,
+                    self.source_location_for(&node.src),
+                ));
                 Ok(())
             }
             NodeType::BinaryOperation => {
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Statement,
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_item(CoverageItem::new(
+                    CoverageItemKind::Statement,
+                    self.source_location_for(&node.src),
+                ));
 
                 // visit left and right expressions
                 // There could possibly a function call in the left or right expression
@@ -310,11 +303,10 @@ impl<'a> ContractVisitor<'a> {
                 Ok(())
             }
             NodeType::FunctionCall => {
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Statement,
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_item(CoverageItem::new(
+                    CoverageItemKind::Statement,
+                    self.source_location_for(&node.src),
+                ));
 
                 let expr: Option<Node> = node.attribute("expression");
                 match expr.as_ref().map(|expr| &expr.node_type) {
@@ -341,11 +333,10 @@ impl<'a> ContractVisitor<'a> {
                 Ok(())
             }
             NodeType::Conditional => {
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Statement,
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_item(CoverageItem::new(
+                    CoverageItemKind::Statement,
+                    self.source_location_for(&node.src),
+                ));
                 Ok(())
             }
             // Does not count towards coverage
@@ -395,11 +386,7 @@ impl<'a> ContractVisitor<'a> {
         if matches!(item.kind, CoverageItemKind::Statement | CoverageItemKind::Branch { .. }) &&
             self.last_line < source_location.line
         {
-            self.items.push(CoverageItem {
-                kind: CoverageItemKind::Line,
-                loc: source_location.clone(),
-                hits: 0,
-            });
+            self.items.push(CoverageItem::new(CoverageItemKind::Line, source_location.clone()));
             self.last_line = source_location.line;
         }
 
@@ -417,16 +404,14 @@ impl<'a> ContractVisitor<'a> {
     }
 
     fn push_branches(&mut self, loc: &ast::LowFidelitySourceLocation, branch_id: usize) {
-        self.push_item(CoverageItem {
-            kind: CoverageItemKind::Branch { branch_id, path_id: 0 },
-            loc: self.source_location_for(loc),
-            hits: 0,
-        });
-        self.push_item(CoverageItem {
-            kind: CoverageItemKind::Branch { branch_id, path_id: 1 },
-            loc: self.source_location_for(loc),
-            hits: 0,
-        });
+        self.push_item(CoverageItem::new(
+            CoverageItemKind::Branch { branch_id, path_id: 0 },
+            self.source_location_for(loc),
+        ));
+        self.push_item(CoverageItem::new(
+            CoverageItemKind::Branch { branch_id, path_id: 1 },
+            self.source_location_for(loc),
+        ));
     }
 }
 
@@ -46,6 +46,68 @@ pub fn find_anchors(
         .collect()
 }
 
+/// Attempts to find anchors for the given items in bytecode compiled with the optimizer.
+///
+/// The optimizer may duplicate the code of an item or split it up, so every contiguous region of
+/// instructions that lies within the source range of an item is anchored. Items without such
+/// instructions, e.g. because their code was merged with identical code elsewhere, are anchored to
+/// the instructions overlapping their source range instead and are only partially attributable,
+/// as are branches whose jumps can't be found.
+///
+/// Returns the anchors and the IDs of the partially attributable items.
+pub fn find_optimized_anchors(
+    bytecode: &Bytes,
+    source_map: &SourceMap,
+    ic_pc_map: &IcPcMap,
+    item_ids: &[usize],
+    items: &[CoverageItem],
+) -> (Vec<ItemAnchor>, Vec<usize>) {
+    let mut anchors = Vec::new();
+    let mut partial = Vec::new();
+    for &item_id in item_ids {
+        let Some(item) = items.get(item_id) else { continue };
+
+        if let CoverageItemKind::Branch { path_id, .. } = item.kind {
+            match find_anchor_branch(bytecode, source_map, item_id, &item.loc) {
+                Ok((first, second)) => anchors.push(if path_id == 0 { first } else { second }),
+                Err(e) => {
+                    warn!("Could not find anchor for item: {}, error: {e}", item);
+                    partial.push(item_id);
+                }
+            }
+            continue
+        }
+
+        let mut regions =
+            find_regions(source_map, |element| is_within_source_range(element, &item.loc));
+        if regions.is_empty() {
+            partial.push(item_id);
+            regions = find_regions(source_map, |element| is_in_source_range(element, &item.loc));
+        }
+        anchors.extend(
+            regions
+                .into_iter()
+                .filter_map(|ic| Some(ItemAnchor { instruction: ic_pc_map.get(ic)?, item_id })),
+        );
+    }
+    (anchors, partial)
+}
+
+/// Returns the first instruction counter of every contiguous run of instructions whose source
+/// element matches `predicate`.
+fn find_regions(source_map: &SourceMap, predicate: impl Fn(&SourceElement) -> bool) -> Vec<usize> {
+    let mut regions = Vec::new();
+    let mut in_region = false;
+    for (ic, element) in source_map.iter().enumerate() {
+        let matches = predicate(element);
+        if matches && !in_region {
+            regions.push(ic);
+        }
+        in_region = matches;
+    }
+    regions
+}
+
 /// Find an anchor representing the first opcode within the given source range.
 pub fn find_anchor_simple(
     source_map: &SourceMap,
@@ -176,3 +238,46 @@ fn is_in_source_range(element: &SourceElement, location: &SourceLocation) -> boo
 
     source_ids_match && is_within_start && start_of_ranges <= end_of_ranges
 }
+
+/// Calculates whether `element` lies entirely within the range of the target `location`.
+fn is_within_source_range(element: &SourceElement, location: &SourceLocation) -> bool {
+    let source_ids_match = element.index.map_or(false, |a| a as usize == location.source_id);
+    let end = location.start + location.length.unwrap_or_default();
+
+    source_ids_match && element.offset >= location.start && element.offset + element.length <= end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_compilers::sourcemap::parse;
+
+    fn statement(start: usize, length: usize) -> CoverageItem {
+        CoverageItem::new(
+            CoverageItemKind::Statement,
+            SourceLocation {
+                source_id: 0,
+                contract_name: "C".to_string(),
+                start,
+                length: Some(length),
+                line: 1,
+            },
+        )
+    }
+
+    #[test]
+    fn anchors_optimized_regions() {
+        // A statement duplicated at IC 1 and IC 4, one at IC 3, and one merged into IC 5.
+        let source_map = parse("0:100:0:-;10:5;12:2;40:5;15:3;62:20").unwrap();
+        let bytecode = Bytes::from(vec![opcode::JUMPDEST; 6]);
+        let ic_pc_map = IcPcMap::new(SpecId::LATEST, &bytecode);
+        let items = vec![statement(10, 10), statement(40, 5), statement(60, 10)];
+
+        let (anchors, partial) =
+            find_optimized_anchors(&bytecode, &source_map, &ic_pc_map, &[0, 1, 2], &items);
+        let anchors =
+            anchors.iter().map(|anchor| (anchor.item_id, anchor.instruction)).collect::<Vec<_>>();
+        assert_eq!(anchors, [(0, 1), (0, 4), (1, 3), (2, 5)]);
+        assert_eq!(partial, [2]);
+    }
+}
@@ -138,16 +138,25 @@ impl CoverageReport {
         ))?;
 
         // Add source level hits
+        //
+        // An item can have several anchors if the optimizer duplicated or split up its code, in
+        // which case the most hit anchor is counted so that a single execution of the item isn't
+        // counted more than once.
         if let Some(anchors) = self.anchors.get(contract_id) {
+            let mut item_hits: HashMap<usize, u64> = HashMap::new();
             for anchor in anchors {
                 if let Some(hits) = hit_map.hits.get(&anchor.instruction) {
-                    self.items
-                        .get_mut(&contract_id.version)
-                        .and_then(|items| items.get_mut(anchor.item_id))
-                        .expect("Anchor refers to non-existent coverage item")
-                        .hits += hits;
+                    let item_hits = item_hits.entry(anchor.item_id).or_default();
+                    *item_hits = (*item_hits).max(*hits);
                 }
             }
+            for (item_id, hits) in item_hits {
+                self.items
+                    .get_mut(&contract_id.version)
+                    .and_then(|items| items.get_mut(item_id))
+                    .expect("Anchor refers to non-existent coverage item")
+                    .hits += hits;
+            }
         }
         Ok(())
     }
@@ -293,6 +302,16 @@ pub struct CoverageItem {
     pub loc: SourceLocation,
     /// The number of times this item was hit.
     pub hits: u64,
+    /// Whether the hits can only partially be attributed to this item, since the optimizer merged
+    /// its code with other code or removed it.
+    pub partial: bool,
+}
+
+impl CoverageItem {
+    /// Creates a new item which wasn't hit yet.
+    pub fn new(kind: CoverageItemKind, loc: SourceLocation) -> Self {
+        Self { kind, loc, hits: 0, partial: false }
+    }
 }
 
 impl Display for CoverageItem {
@@ -311,7 +330,11 @@ impl Display for CoverageItem {
                 write!(f, r#"Function "{name}""#)?;
             }
         }
-        write!(f, " (location: {}, hits: {})", self.loc, self.hits)
+        write!(f, " (location: {}, hits: {})", self.loc, self.hits)?;
+        if self.partial {
+            write!(f, " (partially attributable)")?;
+        }
+        Ok(())
     }
 }
 
@@ -361,6 +384,8 @@ pub struct CoverageSummary {
     pub function_count: usize,
     /// The number of functions hit.
     pub function_hits: usize,
+    /// The number of items whose hits are only partially attributable.
+    pub partial_count: usize,
 }
 
 impl AddAssign<&Self> for CoverageSummary {
@@ -373,11 +398,15 @@ impl AddAssign<&Self> for CoverageSummary {
         self.branch_hits += other.branch_hits;
         self.function_count += other.function_count;
         self.function_hits += other.function_hits;
+        self.partial_count += other.partial_count;
     }
 }
 
 impl AddAssign<&CoverageItem> for CoverageSummary {
     fn add_assign(&mut self, item: &CoverageItem) {
+        if item.partial {
+            self.partial_count += 1;
+        }
         match item.kind {
             CoverageItemKind::Line => {
                 self.line_count += 1;
@@ -409,6 +438,9 @@ impl AddAssign<&CoverageItem> for CoverageSummary {
 
 impl AddAssign<&CoverageItem> for &mut CoverageSummary {
     fn add_assign(&mut self, item: &CoverageItem) {
+        if item.partial {
+            self.partial_count += 1;
+        }
         match item.kind {
             CoverageItemKind::Line => {
                 self.line_count += 1;
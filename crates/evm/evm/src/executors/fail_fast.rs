@@ -0,0 +1,79 @@
+use foundry_evm_core::fork::MultiFork;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Shared state used to stop running tests after the first failure, see `forge test --fail-fast`.
+///
+/// Clones share the same state, so a failure recorded by any test cancels the others.
+#[derive(Clone, Debug, Default)]
+pub struct FailFast {
+    /// Set on the first failure. `None` if fail-fast is disabled.
+    failed: Option<Arc<AtomicBool>>,
+    /// The forks of the running tests, whose in-flight RPC requests are aborted on the first
+    /// failure.
+    forks: Option<MultiFork>,
+}
+
+impl FailFast {
+    /// The reason of the tests cancelled after a failure.
+    pub const REASON: &'static str = "fail-fast";
+
+    /// Creates a new instance, which never cancels tests if `enabled` is `false`.
+    pub fn new(enabled: bool) -> Self {
+        Self { failed: enabled.then(Default::default), forks: None }
+    }
+
+    /// Sets the forks whose in-flight RPC requests are aborted on the first failure, if enabled.
+    pub fn set_forks(&mut self, forks: Option<MultiFork>) {
+        if self.is_enabled() {
+            self.forks = forks;
+        }
+    }
+
+    /// Returns `true` if fail-fast is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.failed.is_some()
+    }
+
+    /// Records a test failure, which cancels the remaining tests if enabled.
+    ///
+    /// Returns `false` if another test already failed, in which case the failure may have been
+    /// caused by the cancellation.
+    pub fn record_fail(&self) -> bool {
+        let Some(failed) = &self.failed else { return true };
+        if failed.swap(true, Ordering::Relaxed) {
+            return false
+        }
+        if let Some(forks) = &self.forks {
+            let _ = forks.abort_pending_requests();
+        }
+        true
+    }
+
+    /// Returns `true` if a test failed and the remaining tests should stop.
+    pub fn should_stop(&self) -> bool {
+        self.failed.as_ref().map_or(false, |failed| failed.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancels_clones() {
+        let disabled = FailFast::default();
+        assert!(disabled.record_fail());
+        assert!(disabled.record_fail());
+        assert!(!disabled.should_stop());
+
+        let fail_fast = FailFast::new(true);
+        let clone = fail_fast.clone();
+        assert!(!fail_fast.should_stop());
+        assert!(clone.record_fail());
+        assert!(fail_fast.should_stop());
+        assert!(!fail_fast.record_fail());
+    }
+}
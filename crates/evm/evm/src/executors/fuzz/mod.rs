@@ -1,4 +1,4 @@
-use crate::executors::{Executor, RawCallResult};
+use crate::executors::{Executor, FailFast, RawCallResult};
 use alloy_dyn_abi::JsonAbiExt;
 use alloy_json_abi::Function;
 use alloy_primitives::{Address, Bytes, U256};
@@ -16,9 +16,9 @@ use foundry_evm_fuzz::{
     },
     BaseCounterExample, CounterExample, FuzzCase, FuzzError, FuzzTestResult,
 };
-use foundry_evm_traces::CallTraceArena;
+use foundry_evm_traces::TraceArena;
 use proptest::test_runner::{TestCaseError, TestError, TestRunner};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 mod types;
 pub use types::{CaseOutcome, CounterExampleOutcome, FuzzOutcome};
@@ -37,6 +37,8 @@ pub struct FuzzedExecutor {
     sender: Address,
     /// The fuzz configuration
     config: FuzzConfig,
+    /// Cancels the campaign when another test fails
+    fail_fast: FailFast,
 }
 
 impl FuzzedExecutor {
@@ -46,8 +48,9 @@ impl FuzzedExecutor {
         runner: TestRunner,
         sender: Address,
         config: FuzzConfig,
+        fail_fast: FailFast,
     ) -> Self {
-        Self { executor, runner, sender, config }
+        Self { executor, runner, sender, config, fail_fast }
     }
 
     /// Fuzzes the provided function, assuming it is available at the contract at `address`
@@ -72,11 +75,14 @@ impl FuzzedExecutor {
         let counterexample: RefCell<(Bytes, RawCallResult)> = RefCell::default();
 
         // Stores the last successful call trace
-        let traces: RefCell<Option<CallTraceArena>> = RefCell::default();
+        let traces: RefCell<Option<TraceArena>> = RefCell::default();
 
         // Stores coverage information for all fuzz cases
         let coverage: RefCell<Option<HitMaps>> = RefCell::default();
 
+        // Whether the campaign was cancelled because another test failed
+        let cancelled = Cell::new(false);
+
         let state = self.build_fuzz_state();
 
         let mut weights = vec![];
@@ -94,6 +100,12 @@ impl FuzzedExecutor {
         let strat = proptest::strategy::Union::new_weighted(weights);
         debug!(func=?func.name, should_fail, "fuzzing");
         let run_result = self.runner.clone().run(&strat, |calldata| {
+            // Stop as soon as another test failed, unless we're shrinking our own failure
+            if self.fail_fast.should_stop() && counterexample.borrow().0.is_empty() {
+                cancelled.set(true);
+                return Err(TestCaseError::fail(FailFast::REASON))
+            }
+
             let fuzz_res = self.single_fuzz(&state, address, should_fail, calldata)?;
 
             match fuzz_res {
@@ -148,8 +160,14 @@ impl FuzzedExecutor {
             labeled_addresses: call.labels,
             traces: if run_result.is_ok() { traces.into_inner() } else { call.traces.clone() },
             coverage: coverage.into_inner(),
+            cancelled: cancelled.get(),
         };
 
+        if result.cancelled {
+            result.reason = Some(FailFast::REASON.to_string());
+            return result
+        }
+
         match run_result {
             // Currently the only operation that can trigger proptest global rejects is the
             // `vm.assume` cheatcode, thus we surface this info to the user when the fuzz test
@@ -176,6 +194,8 @@ impl FuzzedExecutor {
                     addr: None,
                     signature: None,
                     contract_name: None,
+                    warp: None,
+                    roll: None,
                     traces: call.traces,
                     calldata,
                     args,
@@ -4,7 +4,7 @@ use foundry_common::evm::Breakpoints;
 use foundry_evm_core::debug::DebugArena;
 use foundry_evm_coverage::HitMaps;
 use foundry_evm_fuzz::FuzzCase;
-use foundry_evm_traces::CallTraceArena;
+use foundry_evm_traces::TraceArena;
 use revm::interpreter::InstructionResult;
 
 /// Returned by a single fuzz in the case of a successful run
@@ -13,7 +13,7 @@ pub struct CaseOutcome {
     /// Data of a single fuzz test case
     pub case: FuzzCase,
     /// The traces of the call
-    pub traces: Option<CallTraceArena>,
+    pub traces: Option<TraceArena>,
     /// The coverage info collected during the call
     pub coverage: Option<HitMaps>,
     /// The debug nodes of the call
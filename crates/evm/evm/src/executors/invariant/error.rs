@@ -1,4 +1,4 @@
-use super::{BasicTxDetails, InvariantContract};
+use super::{apply_block_jump, BasicTxDetails, BlockJump, InvariantContract, InvariantTx};
 use crate::executors::{Executor, RawCallResult};
 use alloy_json_abi::Function;
 use alloy_primitives::{Address, Bytes, Log};
@@ -6,7 +6,7 @@ use eyre::Result;
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
 use foundry_evm_core::{constants::CALLER, decode::RevertDecoder};
 use foundry_evm_fuzz::{BaseCounterExample, CounterExample, FuzzedCases, Reason};
-use foundry_evm_traces::{load_contracts, CallTraceArena, TraceKind, Traces};
+use foundry_evm_traces::{load_contracts, TraceArena, TraceKind, Traces};
 use itertools::Itertools;
 use parking_lot::RwLock;
 use proptest::test_runner::TestError;
@@ -49,7 +49,11 @@ pub struct InvariantFuzzTestResult {
 
     /// The entire inputs of the last run of the invariant campaign, used for
     /// replaying the run for collecting traces.
-    pub last_run_inputs: Vec<BasicTxDetails>,
+    pub last_run_inputs: Vec<InvariantTx>,
+    /// The seconds the block timestamp was advanced by across all runs.
+    pub time_elapsed: u64,
+    /// Whether the campaign was cancelled by the failure of another test, see `--fail-fast`.
+    pub cancelled: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -75,9 +79,9 @@ impl InvariantFuzzError {
 #[derive(Clone, Debug)]
 pub struct FailedInvariantCaseData {
     pub logs: Vec<Log>,
-    pub traces: Option<CallTraceArena>,
+    pub traces: Option<TraceArena>,
     /// The proptest error occurred as a result of a test case.
-    pub test_error: TestError<Vec<BasicTxDetails>>,
+    pub test_error: TestError<Vec<InvariantTx>>,
     /// The return reason of the offending call.
     pub return_reason: Reason,
     /// The revert string of the offending call.
@@ -98,7 +102,7 @@ impl FailedInvariantCaseData {
     pub fn new(
         invariant_contract: &InvariantContract<'_>,
         error_func: Option<&Function>,
-        calldata: &[BasicTxDetails],
+        calldata: &[InvariantTx],
         call_result: RawCallResult,
         inner_sequence: &[Option<BasicTxDetails>],
         shrink: bool,
@@ -147,7 +151,7 @@ impl FailedInvariantCaseData {
         };
 
         if self.shrink {
-            calls = self.try_shrinking(&calls, &executor).into_iter().cloned().collect();
+            calls = self.try_shrinking(&calls, &executor);
         } else {
             trace!(target: "forge::test", "Shrinking disabled.");
         }
@@ -158,7 +162,9 @@ impl FailedInvariantCaseData {
         set_up_inner_replay(&mut executor, &self.inner_sequence);
 
         // Replay each call from the sequence until we break the invariant.
-        for (sender, (addr, bytes)) in calls.iter() {
+        for (jump, (sender, (addr, bytes))) in calls.iter() {
+            apply_block_jump(&mut executor, *jump);
+
             let call_result = executor
                 .call_raw_committing(*sender, *addr, bytes.clone(), U256::ZERO)
                 .expect("bad call to evm");
@@ -172,13 +178,16 @@ impl FailedInvariantCaseData {
                 known_contracts,
             ));
 
-            counterexample_sequence.push(BaseCounterExample::create(
-                *sender,
-                *addr,
-                bytes,
-                &ided_contracts,
-                call_result.traces,
-            ));
+            counterexample_sequence.push(
+                BaseCounterExample::create(
+                    *sender,
+                    *addr,
+                    bytes,
+                    &ided_contracts,
+                    call_result.traces,
+                )
+                .with_block_jump(*jump),
+            );
 
             // Checks the invariant.
             if let Some(func) = &self.func {
@@ -204,7 +213,7 @@ impl FailedInvariantCaseData {
     fn set_fails_successfully(
         &self,
         mut executor: Executor,
-        calls: &[BasicTxDetails],
+        calls: &[InvariantTx],
         use_calls: &[usize],
         curr_seq: Arc<RwLock<Vec<usize>>>,
     ) {
@@ -227,9 +236,9 @@ impl FailedInvariantCaseData {
             }
         }
 
-        for (seq_idx, call_index) in new_sequence.iter().enumerate() {
-            let (sender, (addr, bytes)) = &calls[*call_index];
-
+        let new_calls = fold_block_jumps(calls, &new_sequence);
+        for (seq_idx, (jump, (sender, (addr, bytes)))) in new_calls.iter().enumerate() {
+            apply_block_jump(&mut executor, *jump);
             executor
                 .call_raw_committing(*sender, *addr, bytes.clone(), U256::ZERO)
                 .expect("bad call to evm");
@@ -256,14 +265,39 @@ impl FailedInvariantCaseData {
         }
     }
 
+    /// Checks that the provided calls fail the invariant test.
+    fn fails(&self, mut executor: Executor, calls: &[InvariantTx]) -> bool {
+        let Some(func) = &self.func else { return false };
+        for (jump, (sender, (addr, bytes))) in calls {
+            apply_block_jump(&mut executor, *jump);
+            executor
+                .call_raw_committing(*sender, *addr, bytes.clone(), U256::ZERO)
+                .expect("bad call to evm");
+
+            let mut call_result = executor
+                .call_raw(CALLER, self.addr, func.clone(), U256::ZERO)
+                .expect("bad call to evm");
+            let is_success = executor.is_raw_call_success(
+                self.addr,
+                call_result.state_changeset.take().unwrap(),
+                &call_result,
+                false,
+            );
+            if !is_success {
+                return true
+            }
+        }
+        false
+    }
+
     /// Tries to shrink the failure case to its smallest sequence of calls.
     ///
     /// If the number of calls is small enough, we can guarantee maximal shrinkage
-    fn try_shrinking<'a>(
-        &self,
-        calls: &'a [BasicTxDetails],
-        executor: &Executor,
-    ) -> Vec<&'a BasicTxDetails> {
+    ///
+    /// The block jumps of the removed calls are added to the next kept call, so the sequence
+    /// runs at the same timestamps and block numbers. The jumps that aren't needed to reproduce
+    /// the failure are then dropped.
+    fn try_shrinking(&self, calls: &[InvariantTx], executor: &Executor) -> Vec<InvariantTx> {
         trace!(target: "forge::test", "Shrinking.");
 
         // Special case test: the invariant is *unsatisfiable* - it took 0 calls to
@@ -286,7 +320,21 @@ impl FailedInvariantCaseData {
         // 2. Bob calls transferOwnership to Alice
         // 3. Alice calls acceptOwnership and test fails
         // we shrink to indices of [2, 1] and we recreate call sequence in same order.
-        shrunk_call_indices.iter().map(|idx| &calls[*idx]).collect()
+        let mut shrunk_calls = fold_block_jumps(calls, &shrunk_call_indices);
+
+        // Drops the warps and rolls that aren't needed to reproduce the failure.
+        let drops: [fn(&mut BlockJump); 2] = [|jump| jump.warp = 0, |jump| jump.roll = 0];
+        for index in 0..shrunk_calls.len() {
+            for drop in drops {
+                let jump = shrunk_calls[index].0;
+                drop(&mut shrunk_calls[index].0);
+                if shrunk_calls[index].0 != jump && !self.fails(executor.clone(), &shrunk_calls) {
+                    shrunk_calls[index].0 = jump;
+                }
+            }
+        }
+
+        shrunk_calls
     }
 
     /// We try to construct a [powerset](https://en.wikipedia.org/wiki/Power_set) of the sequence if
@@ -303,7 +351,7 @@ impl FailedInvariantCaseData {
     /// shrunk the call sequence.
     fn try_shrinking_recurse(
         &self,
-        calls: &[BasicTxDetails],
+        calls: &[InvariantTx],
         executor: &Executor,
         runs: usize,
         retries: usize,
@@ -393,17 +441,9 @@ impl FailedInvariantCaseData {
                 // We construct a *new* subset of calls using the `shrunk_call_indices` of the
                 // passed in calls i.e. if shrunk_call_indices == [1, 3], and calls
                 // is: [call0, call1, call2, call3] then new_calls == [call1, call3]
-                let new_calls: Vec<_> = calls
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(i, call)| {
-                        if shrunk_call_indices.contains(&i) {
-                            Some(call.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
+                let kept_indices: Vec<_> =
+                    (0..calls.len()).filter(|i| shrunk_call_indices.contains(i)).collect();
+                let new_calls = fold_block_jumps(calls, &kept_indices);
 
                 // We rerun this algorithm as if the new smaller subset above were the original
                 // calls. i.e. if [call0, call1, call2, call3] got reduced to
@@ -415,20 +455,9 @@ impl FailedInvariantCaseData {
                     self.try_shrinking_recurse(&new_calls, executor, runs + new_runs, 0);
 
                 // Notably, the indices returned above are relative to `new_calls`, *not* the
-                // originally passed in `calls`. So we map back by filtering
-                // `new_calls` by index if the index was returned above, and finding the position
-                // of the `new_call` in the passed in `call`
-                new_calls
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(idx, new_call)| {
-                        if !new_calls_idxs.contains(&idx) {
-                            None
-                        } else {
-                            calls.iter().position(|r| r == new_call)
-                        }
-                    })
-                    .collect()
+                // originally passed in `calls`. So we map back to the index in `calls` each
+                // `new_call` was taken from.
+                new_calls_idxs.iter().map(|idx| kept_indices[*idx]).collect()
             }
             _ => {
                 // The computation budget has been hit or no retries remaining, stop trying to make
@@ -439,6 +468,22 @@ impl FailedInvariantCaseData {
     }
 }
 
+/// Returns the calls at the ascending `indices`, each with the block jumps of the calls skipped
+/// before it added to its own.
+fn fold_block_jumps(calls: &[InvariantTx], indices: &[usize]) -> Vec<InvariantTx> {
+    let mut next = 0;
+    indices
+        .iter()
+        .map(|&index| {
+            let jump = calls[next..=index]
+                .iter()
+                .fold(BlockJump::default(), |acc, (jump, _)| acc.saturating_add(*jump));
+            next = index + 1;
+            (jump, calls[index].1.clone())
+        })
+        .collect()
+}
+
 /// Sets up the calls generated by the internal fuzzer, if they exist.
 fn set_up_inner_replay(executor: &mut Executor, inner_sequence: &[Option<BasicTxDetails>]) {
     if let Some(fuzzer) = &mut executor.inspector.fuzzer {
@@ -6,9 +6,9 @@ use alloy_primitives::Log;
 use foundry_common::{ContractsByAddress, ContractsByArtifact};
 use foundry_evm_core::constants::CALLER;
 use foundry_evm_coverage::HitMaps;
-use foundry_evm_fuzz::invariant::{BasicTxDetails, InvariantContract};
+use foundry_evm_fuzz::invariant::{BlockJump, InvariantContract, InvariantTx};
 use foundry_evm_traces::{load_contracts, TraceKind, Traces};
-use revm::primitives::U256;
+use revm::primitives::{BlockEnv, U256};
 
 /// Given the executor state, asserts that no invariant has been broken. Otherwise, it fills the
 /// external `invariant_failures.failed_invariant` map and returns a generic error.
@@ -16,7 +16,7 @@ use revm::primitives::U256;
 pub fn assert_invariants(
     invariant_contract: &InvariantContract<'_>,
     executor: &Executor,
-    calldata: &[BasicTxDetails],
+    calldata: &[InvariantTx],
     invariant_failures: &mut InvariantFailures,
     shrink_sequence: bool,
     shrink_run_limit: usize,
@@ -78,7 +78,7 @@ pub fn replay_run(
     traces: &mut Traces,
     coverage: &mut Option<HitMaps>,
     func: Function,
-    inputs: Vec<BasicTxDetails>,
+    inputs: Vec<InvariantTx>,
 ) {
     // We want traces for a failed case.
     executor.set_tracing(true);
@@ -86,7 +86,9 @@ pub fn replay_run(
     // set_up_inner_replay(&mut executor, &inputs);
 
     // Replay each call from the sequence until we break the invariant.
-    for (sender, (addr, bytes)) in inputs.iter() {
+    for (jump, (sender, (addr, bytes))) in inputs.iter() {
+        apply_block_jump(&mut executor, *jump);
+
         let call_result = executor
             .call_raw_committing(*sender, *addr, bytes.clone(), U256::ZERO)
             .expect("bad call to evm");
@@ -129,3 +131,28 @@ pub fn replay_run(
         logs.extend(error_call_result.logs);
     }
 }
+
+/// Advances the block timestamp and number of the executor's next calls by `jump`.
+pub(crate) fn apply_block_jump(executor: &mut Executor, jump: BlockJump) {
+    update_block(executor, |block| {
+        block.timestamp = block.timestamp.saturating_add(U256::from(jump.warp));
+        block.number = block.number.saturating_add(U256::from(jump.roll));
+    });
+}
+
+/// Reverts a jump previously applied with [apply_block_jump].
+pub(crate) fn revert_block_jump(executor: &mut Executor, jump: BlockJump) {
+    update_block(executor, |block| {
+        block.timestamp = block.timestamp.saturating_sub(U256::from(jump.warp));
+        block.number = block.number.saturating_sub(U256::from(jump.roll));
+    });
+}
+
+/// Updates the block environment of the executor, and the one the cheatcodes overwrite it with
+/// after a `vm.warp` or `vm.roll`.
+fn update_block(executor: &mut Executor, f: impl Fn(&mut BlockEnv)) {
+    f(&mut executor.env.block);
+    if let Some(block) = executor.inspector.cheatcodes.as_mut().and_then(|c| c.block.as_mut()) {
+        f(block);
+    }
+}
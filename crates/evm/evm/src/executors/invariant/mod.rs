@@ -1,5 +1,5 @@
 use crate::{
-    executors::{Executor, RawCallResult},
+    executors::{Executor, FailFast, RawCallResult},
     inspectors::Fuzzer,
 };
 use alloy_dyn_abi::DynSolValue;
@@ -14,8 +14,8 @@ use foundry_evm_core::{
 };
 use foundry_evm_fuzz::{
     invariant::{
-        ArtifactFilters, BasicTxDetails, FuzzRunIdentifiedContracts, InvariantContract,
-        RandomCallGenerator, SenderFilters, TargetedContracts,
+        ArtifactFilters, BasicTxDetails, BlockJump, FuzzRunIdentifiedContracts, InvariantContract,
+        InvariantTx, RandomCallGenerator, SenderFilters, TargetedContracts,
     },
     strategies::{
         build_initial_state, collect_created_contracts, collect_state_from_call, invariant_strat,
@@ -28,14 +28,20 @@ use proptest::{
     strategy::{BoxedStrategy, Strategy, ValueTree},
     test_runner::{TestCaseError, TestRunner},
 };
+use rand::Rng;
 use revm::{primitives::HashMap, DatabaseCommit};
-use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    sync::Arc,
+};
 
 mod error;
 pub use error::{InvariantFailures, InvariantFuzzError, InvariantFuzzTestResult};
 use foundry_evm_fuzz::strategies::CalldataFuzzDictionary;
 
 mod funcs;
+use funcs::{apply_block_jump, revert_block_jump};
 pub use funcs::{assert_invariants, replay_run};
 
 use self::error::FailedInvariantCaseData;
@@ -80,6 +86,8 @@ pub struct InvariantExecutor<'a> {
     project_contracts: &'a ContractsByArtifact,
     /// Filters contracts to be fuzzed through their artifact identifiers.
     artifact_filters: ArtifactFilters,
+    /// Cancels the campaign when another test fails.
+    fail_fast: FailFast,
 }
 
 impl<'a> InvariantExecutor<'a> {
@@ -90,6 +98,7 @@ impl<'a> InvariantExecutor<'a> {
         config: InvariantConfig,
         setup_contracts: &'a ContractsByAddress,
         project_contracts: &'a ContractsByArtifact,
+        fail_fast: FailFast,
     ) -> Self {
         Self {
             executor,
@@ -98,6 +107,7 @@ impl<'a> InvariantExecutor<'a> {
             setup_contracts,
             project_contracts,
             artifact_filters: ArtifactFilters::default(),
+            fail_fast,
         }
     }
 
@@ -121,7 +131,13 @@ impl<'a> InvariantExecutor<'a> {
         let failures = RefCell::new(InvariantFailures::new());
 
         // Stores the calldata in the last run.
-        let last_run_calldata: RefCell<Vec<BasicTxDetails>> = RefCell::new(vec![]);
+        let last_run_calldata: RefCell<Vec<InvariantTx>> = RefCell::new(vec![]);
+
+        // Stores the seconds warped by the block jumps of all runs.
+        let time_elapsed = Cell::new(0u64);
+
+        // Whether the campaign was cancelled because another test failed.
+        let cancelled = Cell::new(false);
 
         // Let's make sure the invariant is sound before actually starting the run:
         // We'll assert the invariant in its initial state, and if it fails, we'll
@@ -146,7 +162,7 @@ impl<'a> InvariantExecutor<'a> {
         // values.
         let branch_runner = RefCell::new(self.runner.clone());
         let _ = self.runner.run(&strat, |first_input| {
-            let mut inputs = vec![first_input];
+            let mut inputs = vec![(self.block_jump(&mut branch_runner.borrow_mut()), first_input)];
 
             // We stop the run immediately if we have reverted, and `fail_on_revert` is set.
             if self.config.fail_on_revert && failures.borrow().reverts > 0 {
@@ -166,7 +182,17 @@ impl<'a> InvariantExecutor<'a> {
             let mut assume_rejects_counter = 0;
 
             while current_run < self.config.depth {
-                let (sender, (address, calldata)) = inputs.last().expect("no input generated");
+                // Stop as soon as another test failed, unless this one is already failing.
+                if self.fail_fast.should_stop() && failures.borrow().error.is_none() {
+                    cancelled.set(true);
+                    return Err(TestCaseError::fail(FailFast::REASON))
+                }
+
+                let (jump, (sender, (address, calldata))) =
+                    inputs.last().expect("no input generated");
+
+                // Advances the chain before the call, if the block jumps are enabled.
+                apply_block_jump(&mut executor, *jump);
 
                 // Executes the call from the randomly generated sequence.
                 let call_result = if self.config.preserve_state {
@@ -180,6 +206,8 @@ impl<'a> InvariantExecutor<'a> {
                 };
 
                 if call_result.result.as_ref() == MAGIC_ASSUME {
+                    // The rejected call isn't part of the sequence, neither is its jump.
+                    revert_block_jump(&mut executor, *jump);
                     inputs.pop();
                     assume_rejects_counter += 1;
                     if assume_rejects_counter > self.config.max_assume_rejects {
@@ -215,6 +243,8 @@ impl<'a> InvariantExecutor<'a> {
                     // Commit changes to the database.
                     executor.backend.commit(state_changeset.clone());
 
+                    time_elapsed.set(time_elapsed.get().saturating_add(jump.warp));
+
                     fuzz_runs.push(FuzzCase {
                         calldata: calldata.clone(),
                         gas: call_result.gas_used,
@@ -249,12 +279,11 @@ impl<'a> InvariantExecutor<'a> {
 
                 // Generates the next call from the run using the recently updated
                 // dictionary.
-                inputs.push(
-                    strat
-                        .new_tree(&mut branch_runner.borrow_mut())
-                        .map_err(|_| TestCaseError::Fail("Could not generate case".into()))?
-                        .current(),
-                );
+                let input = strat
+                    .new_tree(&mut branch_runner.borrow_mut())
+                    .map_err(|_| TestCaseError::Fail("Could not generate case".into()))?
+                    .current();
+                inputs.push((self.block_jump(&mut branch_runner.borrow_mut()), input));
             }
 
             // We clear all the targeted contracts created during this run.
@@ -280,9 +309,25 @@ impl<'a> InvariantExecutor<'a> {
             cases: fuzz_cases.into_inner(),
             reverts,
             last_run_inputs: last_run_calldata.take(),
+            time_elapsed: time_elapsed.get(),
+            cancelled: cancelled.get(),
         })
     }
 
+    /// Generates the random block jump applied before a call, up to the configured
+    /// `time_jump_max` and `block_jump_max`.
+    fn block_jump(&self, runner: &mut TestRunner) -> BlockJump {
+        let mut jump = BlockJump::default();
+        // The runner's rng is only used if enabled, so the sequences of a seed don't change.
+        if self.config.time_jump_max > 0 {
+            jump.warp = runner.rng().gen_range(0..=self.config.time_jump_max);
+        }
+        if self.config.block_jump_max > 0 {
+            jump.roll = runner.rng().gen_range(0..=self.config.block_jump_max);
+        }
+        jump
+    }
+
     /// Prepares certain structures to execute the invariant tests:
     /// * Fuzz dictionary
     /// * Targeted contracts
@@ -326,7 +371,7 @@ impl<'a> InvariantExecutor<'a> {
         // Allows `override_call_strat` to use the address given by the Fuzzer inspector during
         // EVM execution.
         let mut call_generator = None;
-        if self.config.call_override {
+        if self.config.call_override || self.config.call_from_callbacks {
             let target_contract_ref = Arc::new(RwLock::new(Address::ZERO));
 
             call_generator = Some(RandomCallGenerator::new(
@@ -339,6 +384,8 @@ impl<'a> InvariantExecutor<'a> {
                     calldata_fuzz_config.clone(),
                 ),
                 target_contract_ref,
+                self.config.call_override,
+                self.config.call_from_callbacks,
             ));
         }
 
@@ -757,7 +804,7 @@ fn can_continue(
     invariant_contract: &InvariantContract<'_>,
     call_result: RawCallResult,
     executor: &Executor,
-    calldata: &[BasicTxDetails],
+    calldata: &[InvariantTx],
     failures: &mut InvariantFailures,
     targeted_contracts: &FuzzRunIdentifiedContracts,
     state_changeset: StateChangeset,
@@ -23,7 +23,7 @@ use foundry_evm_core::{
     utils::{eval_to_instruction_result, halt_to_instruction_result, StateChangeset},
 };
 use foundry_evm_coverage::HitMaps;
-use foundry_evm_traces::CallTraceArena;
+use foundry_evm_traces::TraceArena;
 use revm::{
     db::{DatabaseCommit, DatabaseRef},
     interpreter::{return_ok, CreateScheme, InstructionResult, Stack},
@@ -31,11 +31,14 @@ use revm::{
         BlockEnv, Bytecode, Env, ExecutionResult, Output, ResultAndState, SpecId, TransactTo, TxEnv,
     },
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 mod builder;
 pub use builder::ExecutorBuilder;
 
+mod fail_fast;
+pub use fail_fast::FailFast;
+
 pub mod fuzz;
 pub use fuzz::FuzzedExecutor;
 
@@ -585,7 +588,7 @@ pub struct ExecutionErr {
     pub gas_refunded: u64,
     pub stipend: u64,
     pub logs: Vec<Log>,
-    pub traces: Option<CallTraceArena>,
+    pub traces: Option<TraceArena>,
     pub debug: Option<DebugArena>,
     pub labels: HashMap<Address, String>,
     pub transactions: Option<BroadcastableTransactions>,
@@ -620,7 +623,7 @@ pub struct DeployResult {
     /// The logs emitted during the deployment
     pub logs: Vec<Log>,
     /// The traces of the deployment
-    pub traces: Option<CallTraceArena>,
+    pub traces: Option<TraceArena>,
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
     /// The `revm::Env` after deployment
@@ -648,7 +651,7 @@ pub struct CallResult {
     /// The labels assigned to addresses during the call
     pub labels: HashMap<Address, String>,
     /// The traces of the call
-    pub traces: Option<CallTraceArena>,
+    pub traces: Option<TraceArena>,
     /// The coverage info collected during the call
     pub coverage: Option<HitMaps>,
     /// The debug nodes of the call
@@ -664,6 +667,8 @@ pub struct CallResult {
     pub env: Env,
     /// breakpoints
     pub breakpoints: Breakpoints,
+    /// The named gas measurements taken with the `snapshotGas` cheatcodes
+    pub gas_snapshots: BTreeMap<String, u64>,
 }
 
 /// The result of a raw call.
@@ -691,7 +696,7 @@ pub struct RawCallResult {
     /// The labels assigned to addresses during the call
     pub labels: HashMap<Address, String>,
     /// The traces of the call
-    pub traces: Option<CallTraceArena>,
+    pub traces: Option<TraceArena>,
     /// The coverage info collected during the call
     pub coverage: Option<HitMaps>,
     /// The debug nodes of the call
@@ -826,10 +831,10 @@ fn convert_call_result(
         ..
     } = call_result;
 
-    let breakpoints = if let Some(c) = call_result.cheatcodes {
-        c.breakpoints
+    let (breakpoints, gas_snapshots) = if let Some(c) = call_result.cheatcodes {
+        (c.breakpoints, c.gas_snapshots.recorded)
     } else {
-        std::collections::HashMap::new()
+        (std::collections::HashMap::new(), BTreeMap::new())
     };
 
     match status {
@@ -856,6 +861,7 @@ fn convert_call_result(
                 state_changeset,
                 env,
                 breakpoints,
+                gas_snapshots,
                 skipped: false,
             })
         }
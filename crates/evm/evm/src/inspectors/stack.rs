@@ -5,21 +5,25 @@ use super::{
 use alloy_primitives::{Address, Bytes, Log, B256, U256};
 use foundry_evm_core::{
     backend::DatabaseExt,
+    constants::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
     debug::DebugArena,
     utils::{eval_to_instruction_result, halt_to_instruction_result},
 };
 use foundry_evm_coverage::HitMaps;
-use foundry_evm_traces::CallTraceArena;
+use foundry_evm_traces::TraceArena;
 use revm::{
     evm_inner,
     interpreter::{
-        return_revert, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult, Interpreter,
-        Stack,
+        opcode, return_revert, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult,
+        Interpreter, Stack,
     },
     primitives::{BlockEnv, Env, ExecutionResult, Output, State, TransactTo},
     DatabaseCommit, EVMData, Inspector,
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
 
 #[derive(Clone, Debug, Default)]
 #[must_use = "builders do nothing unless you call `build` on them"]
@@ -40,6 +44,8 @@ pub struct InspectorStackBuilder {
     pub fuzzer: Option<Fuzzer>,
     /// Whether to enable tracing.
     pub trace: Option<bool>,
+    /// Whether the tracer should record the storage changes of each call.
+    pub trace_state_changes: Option<bool>,
     /// Whether to enable the debugger.
     pub debug: Option<bool>,
     /// Whether logs should be collected.
@@ -54,6 +60,8 @@ pub struct InspectorStackBuilder {
     /// In isolation mode all top-level calls are executed as a separate transaction in a separate
     /// EVM context, enabling more precise gas accounting and transaction state changes.
     pub enable_isolation: bool,
+    /// The number of seconds the block timestamp is advanced by for every isolated transaction.
+    pub isolate_block_advance: Option<u64>,
 }
 
 impl InspectorStackBuilder {
@@ -133,6 +141,15 @@ impl InspectorStackBuilder {
         self
     }
 
+    /// Set whether the tracer should record the storage changes of each call.
+    ///
+    /// This enables the tracer as well.
+    #[inline]
+    pub fn trace_state_changes(mut self, yes: bool) -> Self {
+        self.trace_state_changes = Some(yes);
+        self
+    }
+
     /// Set whether to enable the call isolation.
     /// For description of call isolation, see [`InspectorStack::enable_isolation`].
     #[inline]
@@ -141,6 +158,14 @@ impl InspectorStackBuilder {
         self
     }
 
+    /// Set the number of seconds the block timestamp is advanced by for every isolated
+    /// transaction, which is then executed in a new block.
+    #[inline]
+    pub fn isolate_block_advance(mut self, seconds: Option<u64>) -> Self {
+        self.isolate_block_advance = seconds;
+        self
+    }
+
     /// Builds the stack of inspectors to use when transacting/committing on the EVM.
     ///
     /// See also [`revm::Evm::inspect_ref`] and [`revm::Evm::commit_ref`].
@@ -151,12 +176,14 @@ impl InspectorStackBuilder {
             cheatcodes,
             fuzzer,
             trace,
+            trace_state_changes,
             debug,
             logs,
             coverage,
             print,
             chisel_state,
             enable_isolation,
+            isolate_block_advance,
         } = self;
         let mut stack = InspectorStack::new();
 
@@ -175,8 +202,10 @@ impl InspectorStackBuilder {
         stack.enable_debugger(debug.unwrap_or(false));
         stack.print(print.unwrap_or(false));
         stack.tracing(trace.unwrap_or(false));
+        stack.tracing_state_changes(trace_state_changes.unwrap_or(false));
 
         stack.enable_isolation(enable_isolation);
+        stack.isolate_block_advance = isolate_block_advance;
 
         // environment, must come after all of the inspectors
         if let Some(block) = block {
@@ -233,11 +262,24 @@ fn update_state<DB: DatabaseExt>(state: &mut State, db: &mut DB) {
     }
 }
 
+/// The config of the tracer, optionally recording the storage changes of each step.
+fn tracing_config(state_changes: bool) -> TracingInspectorConfig {
+    TracingInspectorConfig {
+        record_steps: state_changes,
+        record_memory_snapshots: false,
+        record_stack_snapshots: StackSnapshotType::None,
+        record_state_diff: state_changes,
+        exclude_precompile_calls: false,
+        record_call_return_data: true,
+        record_logs: true,
+    }
+}
+
 /// The collected results of [`InspectorStack`].
 pub struct InspectorData {
     pub logs: Vec<Log>,
     pub labels: HashMap<Address, String>,
-    pub traces: Option<CallTraceArena>,
+    pub traces: Option<TraceArena>,
     pub debug: Option<DebugArena>,
     pub coverage: Option<HitMaps>,
     pub cheatcodes: Option<Cheatcodes>,
@@ -258,8 +300,9 @@ pub struct InnerContextData {
     original_sender_nonce: u64,
     /// Origin of the transaction in the outer EVM context.
     original_origin: Address,
-    /// Whether the inner context was created by a CREATE transaction.
-    is_create: bool,
+    /// Whether the nonce of the sender is restored after the inner context, because the call is
+    /// broadcast and the nonce was already incremented by the cheatcodes.
+    restore_sender_nonce: bool,
 }
 
 /// An inspector that calls multiple inspectors in sequence.
@@ -276,7 +319,12 @@ pub struct InspectorStack {
     pub log_collector: Option<LogCollector>,
     pub printer: Option<TracePrinter>,
     pub tracer: Option<TracingInspector>,
+    /// The indexes of the trace nodes of the calls answered by a mock
+    pub mocked_calls: BTreeSet<usize>,
     pub enable_isolation: bool,
+    /// If set, every isolated transaction is executed in a new block, with the timestamp advanced
+    /// by this number of seconds.
+    pub isolate_block_advance: Option<u64>,
 
     /// Flag marking if we are in the inner EVM context.
     pub in_inner_context: bool,
@@ -368,17 +416,30 @@ impl InspectorStack {
     /// Set whether to enable the tracer.
     #[inline]
     pub fn tracing(&mut self, yes: bool) {
-        self.tracer = yes.then(|| {
-            TracingInspector::new(TracingInspectorConfig {
-                record_steps: false,
-                record_memory_snapshots: false,
-                record_stack_snapshots: StackSnapshotType::None,
-                record_state_diff: false,
-                exclude_precompile_calls: false,
-                record_call_return_data: true,
-                record_logs: true,
-            })
-        });
+        self.tracer = yes.then(|| TracingInspector::new(tracing_config(false)));
+    }
+
+    /// Set whether the tracer should record the storage changes of each call, enabling the tracer
+    /// if it isn't already.
+    #[inline]
+    pub fn tracing_state_changes(&mut self, yes: bool) {
+        if yes {
+            self.tracer = Some(TracingInspector::new(tracing_config(true)));
+        }
+    }
+
+    /// Set whether the tracer should record the stack of the steps to decode internal function
+    /// calls, enabling the tracer if it isn't already.
+    ///
+    /// This also records the storage changes of each call.
+    #[inline]
+    pub fn tracing_internal_calls(&mut self, yes: bool) {
+        if yes {
+            self.tracer = Some(TracingInspector::new(TracingInspectorConfig {
+                record_stack_snapshots: StackSnapshotType::Full,
+                ..tracing_config(true)
+            }));
+        }
     }
 
     /// Collects all the data gathered during inspection into a single struct.
@@ -393,7 +454,26 @@ impl InspectorStack {
                     cheatcodes.labels.clone().into_iter().map(|l| (l.0, l.1)).collect()
                 })
                 .unwrap_or_default(),
-            traces: self.tracer.map(|tracer| tracer.get_traces().clone()),
+            traces: self.tracer.map(|mut tracer| {
+                // only the steps that changed storage are needed to annotate the traces, and the
+                // jumps, logs and calls to place internal function calls if the stack was recorded
+                for node in tracer.traces_mut().nodes_mut() {
+                    node.trace.steps.retain(|step| match step.op.get() {
+                        opcode::SSTORE => step.storage_change.is_some(),
+                        opcode::JUMP |
+                        opcode::JUMPDEST |
+                        opcode::LOG0..=opcode::LOG4 |
+                        opcode::CALL |
+                        opcode::CALLCODE |
+                        opcode::DELEGATECALL |
+                        opcode::STATICCALL |
+                        opcode::CREATE |
+                        opcode::CREATE2 => step.stack.is_some(),
+                        _ => false,
+                    });
+                }
+                TraceArena { arena: tracer.get_traces().clone(), mocked_calls: self.mocked_calls }
+            }),
             debug: self.debugger.map(|debugger| debugger.arena),
             coverage: self.coverage.map(|coverage| coverage.maps),
             cheatcodes: self.cheatcodes,
@@ -460,6 +540,11 @@ impl InspectorStack {
 
         let cached_env = data.env.clone();
 
+        if let Some(seconds) = self.isolate_block_advance {
+            data.env.block.number += U256::from(1);
+            data.env.block.timestamp += U256::from(seconds);
+        }
+
         data.env.block.basefee = U256::ZERO;
         data.env.tx.caller = caller;
         data.env.tx.transact_to = transact_to.clone();
@@ -480,7 +565,11 @@ impl InspectorStack {
             sender: data.env.tx.caller,
             original_origin: cached_env.tx.caller,
             original_sender_nonce: nonce,
-            is_create: matches!(transact_to, TransactTo::Create(_)),
+            restore_sender_nonce: matches!(transact_to, TransactTo::Call(_)) &&
+                self.cheatcodes
+                    .as_ref()
+                    .and_then(|cheats| cheats.broadcast.as_ref())
+                    .is_some_and(|broadcast| broadcast.new_origin == caller),
         });
         self.in_inner_context = true;
         let res = evm_inner(data.env, data.db, Some(self)).transact();
@@ -541,8 +630,8 @@ impl InspectorStack {
 
     /// Adjusts the EVM data for the inner EVM context.
     /// Should be called on the top-level call of inner context (depth == 0 &&
-    /// self.in_inner_context) Decreases sender nonce for CALLs to keep backwards compatibility
-    /// Updates tx.origin to the value before entering inner context
+    /// self.in_inner_context) Decreases sender nonce for broadcast CALLs, as the cheatcodes already
+    /// incremented it. Updates tx.origin to the value before entering inner context
     fn adjust_evm_data_for_inner_context<DB: DatabaseExt>(&mut self, data: &mut EVMData<'_, DB>) {
         let inner_context_data =
             self.inner_context_data.as_ref().expect("should be called in inner context");
@@ -551,7 +640,7 @@ impl InspectorStack {
             .state
             .get_mut(&inner_context_data.sender)
             .expect("failed to load sender");
-        if !inner_context_data.is_create {
+        if inner_context_data.restore_sender_nonce {
             sender_acc.info.nonce = inner_context_data.original_sender_nonce;
         }
         data.env.tx.caller = inner_context_data.original_origin;
@@ -666,6 +755,8 @@ impl<DB: DatabaseExt + DatabaseCommit> Inspector<DB> for InspectorStack {
             return (InstructionResult::Continue, Gas::new(call.gas_limit), Bytes::new());
         }
 
+        let caller = call.context.caller;
+        let traces_len = self.tracer.as_ref().map(|tracer| tracer.get_traces().nodes().len());
         call_inspectors_adjust_depth!(
             [
                 &mut self.fuzzer,
@@ -690,8 +781,25 @@ impl<DB: DatabaseExt + DatabaseCommit> Inspector<DB> for InspectorStack {
             data
         );
 
+        // The tracer runs before the cheatcodes, so record the pranked caller on the trace that
+        // was started for this call, if any
+        if call.context.caller != caller {
+            if let Some(tracer) = self.tracer.as_mut() {
+                let nodes = tracer.traces_mut().nodes_mut();
+                if traces_len.map_or(false, |len| nodes.len() == len + 1) {
+                    if let Some(node) = nodes.last_mut().filter(|node| {
+                        node.trace.caller == caller && node.trace.address == call.contract
+                    }) {
+                        node.trace.caller = call.context.caller;
+                    }
+                }
+            }
+        }
+
         if self.enable_isolation &&
             call.context.scheme == CallScheme::Call &&
+            call.contract != CHEATCODE_ADDRESS &&
+            call.contract != HARDHAT_CONSOLE_ADDRESS &&
             !self.in_inner_context &&
             data.journaled_state.depth == 1
         {
@@ -723,7 +831,22 @@ impl<DB: DatabaseExt + DatabaseCommit> Inspector<DB> for InspectorStack {
             return (status, remaining_gas, retdata);
         }
 
+        let mocked = self
+            .cheatcodes
+            .as_mut()
+            .map_or(false, |cheatcodes| std::mem::take(&mut cheatcodes.answered_by_mock));
+
         let res = self.do_call_end(data, call, remaining_gas, status, retdata);
+
+        // A mocked call has no subcalls, so its trace is the last one
+        if mocked {
+            if let Some(tracer) = &self.tracer {
+                if let Some(idx) = tracer.get_traces().nodes().len().checked_sub(1) {
+                    self.mocked_calls.insert(idx);
+                }
+            }
+        }
+
         if matches!(res.0, return_revert!()) {
             // Encountered a revert, since cheatcodes may have altered the evm state in such a way
             // that violates some constraints, e.g. `deal`, we need to manually roll back on revert
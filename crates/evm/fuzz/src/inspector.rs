@@ -1,7 +1,13 @@
-use crate::{invariant::RandomCallGenerator, strategies::EvmFuzzState};
+use crate::{
+    invariant::{
+        callback_wrapper_code, is_token_callback, RandomCallGenerator, CALLBACK_WRAPPER_ADDRESS,
+    },
+    strategies::EvmFuzzState,
+};
 use alloy_primitives::Bytes;
 use revm::{
     interpreter::{CallInputs, CallScheme, Gas, InstructionResult, Interpreter},
+    primitives::Bytecode,
     Database, EVMData, Inspector,
 };
 
@@ -34,7 +40,7 @@ impl<DB: Database> Inspector<DB> for Fuzzer {
     ) -> (InstructionResult, Gas, Bytes) {
         // We don't want to override the very first call made to the test contract.
         if self.call_generator.is_some() && data.env.tx.caller != call.context.caller {
-            self.override_call(call);
+            self.override_call(data, call);
         }
 
         // We only collect `stack` and `memory` data before and after calls.
@@ -47,20 +53,24 @@ impl<DB: Database> Inspector<DB> for Fuzzer {
     #[inline]
     fn call_end(
         &mut self,
-        _: &mut EVMData<'_, DB>,
+        data: &mut EVMData<'_, DB>,
         _: &CallInputs,
         remaining_gas: Gas,
         status: InstructionResult,
         retdata: Bytes,
     ) -> (InstructionResult, Gas, Bytes) {
-        if let Some(ref mut call_generator) = self.call_generator {
-            call_generator.used = false;
-        }
-
         // We only collect `stack` and `memory` data before and after calls.
         // this will be turned off on the next `step`
         self.collect = true;
 
+        if let Some(ref mut call_generator) = self.call_generator {
+            call_generator.used = false;
+
+            if call_generator.wrapped_callback == Some(data.journaled_state.depth()) {
+                call_generator.wrapped_callback = None;
+            }
+        }
+
         (status, remaining_gas, retdata)
     }
 }
@@ -84,24 +94,52 @@ impl Fuzzer {
     }
 
     /// Overrides an external call and tries to call any method of msg.sender.
-    fn override_call(&mut self, call: &mut CallInputs) {
+    ///
+    /// Token receiver callbacks aren't replaced: they're run by a wrapper which first makes the
+    /// generated call from the receiver.
+    fn override_call<DB: Database>(&mut self, data: &mut EVMData<'_, DB>, call: &mut CallInputs) {
         if let Some(ref mut call_generator) = self.call_generator {
+            let is_callback = call_generator.override_callbacks && is_token_callback(&call.input);
+
             // We only override external calls which are not coming from the test contract.
             if call.context.caller != call_generator.test_address &&
                 call.context.scheme == CallScheme::Call &&
-                !call_generator.used
+                !call_generator.used &&
+                call_generator.wrapped_callback.is_none() &&
+                (call_generator.override_calls || is_callback)
             {
                 // There's only a 30% chance that an override happens.
                 if let Some((sender, (contract, input))) =
                     call_generator.next(call.context.caller, call.contract)
                 {
-                    *call.input = input.0;
-                    call.context.caller = sender;
-                    call.contract = contract;
+                    if is_callback {
+                        let Some(code) = callback_wrapper_code(contract, &input) else { return };
+                        if data
+                            .journaled_state
+                            .load_account(CALLBACK_WRAPPER_ADDRESS, data.db)
+                            .is_err()
+                        {
+                            return
+                        }
+                        data.journaled_state.set_code(
+                            CALLBACK_WRAPPER_ADDRESS,
+                            Bytecode::new_raw(code.0).to_checked(),
+                        );
+
+                        // The wrapper runs in the context of the receiver, which makes the
+                        // generated call.
+                        call.contract = CALLBACK_WRAPPER_ADDRESS;
+                        call.context.code_address = CALLBACK_WRAPPER_ADDRESS;
+                        call_generator.wrapped_callback = Some(data.journaled_state.depth());
+                    } else {
+                        *call.input = input.0;
+                        call.context.caller = sender;
+                        call.contract = contract;
 
-                    // TODO: in what scenarios can the following be problematic
-                    call.context.code_address = contract;
-                    call.context.address = contract;
+                        // TODO: in what scenarios can the following be problematic
+                        call.context.code_address = contract;
+                        call.context.address = contract;
+                    }
 
                     call_generator.used = true;
                 }
@@ -1,13 +1,72 @@
 use super::BasicTxDetails;
-use alloy_primitives::{Address, Bytes};
+use alloy_primitives::{address, Address, Bytes};
 use parking_lot::{Mutex, RwLock};
 use proptest::{
     option::weighted,
     strategy::{SBoxedStrategy, Strategy, ValueTree},
     test_runner::TestRunner,
 };
+use revm::interpreter::opcode::{
+    ADDRESS, CALL, CALLDATACOPY, CALLDATASIZE, CODECOPY, DELEGATECALL, GAS, JUMPDEST, JUMPI, POP,
+    PUSH1, PUSH2, PUSH20, RETURN, RETURNDATACOPY, RETURNDATASIZE, REVERT,
+};
 use std::sync::Arc;
 
+/// The selectors of the token receiver callbacks.
+const TOKEN_CALLBACKS: [[u8; 4]; 5] = [
+    // onERC721Received(address,address,uint256,bytes)
+    [0x15, 0x0b, 0x7a, 0x02],
+    // onERC1155Received(address,address,uint256,uint256,bytes)
+    [0xf2, 0x3a, 0x6e, 0x61],
+    // onERC1155BatchReceived(address,address,uint256[],uint256[],bytes)
+    [0xbc, 0x19, 0x7c, 0x81],
+    // onTransferReceived(address,address,uint256,bytes), ERC1363
+    [0x88, 0xa7, 0xca, 0x5c],
+    // tokensReceived(address,address,address,uint256,bytes,bytes), ERC777
+    [0x00, 0x23, 0xde, 0x29],
+];
+
+/// The address the code wrapping an overridden token receiver callback is placed at.
+pub const CALLBACK_WRAPPER_ADDRESS: Address = address!("c0ffee00000000000000000000000000000ca11b");
+
+/// Length of the wrapper code, before the appended calldata of the generated call.
+const CALLBACK_WRAPPER_LEN: u16 = 79;
+
+/// Returns whether `input` calls a token receiver callback.
+pub fn is_token_callback(input: &[u8]) -> bool {
+    input.get(..4).is_some_and(|selector| TOKEN_CALLBACKS.iter().any(|cb| cb == selector))
+}
+
+/// Returns the code which, executed in the context of the receiver of a token callback, makes the
+/// generated call to `target` and then runs the original callback by delegatecalling the
+/// receiver, returning or reverting with its result.
+///
+/// Returns `None` if `calldata` is too large to be appended to the code.
+pub fn callback_wrapper_code(target: Address, calldata: &[u8]) -> Option<Bytes> {
+    let len = u16::try_from(calldata.len()).ok()?.to_be_bytes();
+    let offset = CALLBACK_WRAPPER_LEN.to_be_bytes();
+    // Position of the `JUMPDEST` of the successful callback path.
+    let success = (CALLBACK_WRAPPER_LEN - 5).to_be_bytes();
+
+    let mut code = Vec::with_capacity(CALLBACK_WRAPPER_LEN as usize + calldata.len());
+    // Copy the calldata of the generated call to memory, and make the call, ignoring its result.
+    code.extend([PUSH2, len[0], len[1], PUSH2, offset[0], offset[1], PUSH1, 0, CODECOPY]);
+    code.extend([PUSH1, 0, PUSH1, 0, PUSH2, len[0], len[1], PUSH1, 0, PUSH1, 0, PUSH20]);
+    code.extend_from_slice(target.as_slice());
+    code.extend([GAS, CALL, POP]);
+    // Run the callback with its original calldata.
+    code.extend([CALLDATASIZE, PUSH1, 0, PUSH1, 0, CALLDATACOPY]);
+    code.extend([PUSH1, 0, PUSH1, 0, CALLDATASIZE, PUSH1, 0, ADDRESS, GAS, DELEGATECALL]);
+    code.extend([RETURNDATASIZE, PUSH1, 0, PUSH1, 0, RETURNDATACOPY]);
+    code.extend([PUSH2, success[0], success[1], JUMPI]);
+    code.extend([RETURNDATASIZE, PUSH1, 0, REVERT]);
+    code.extend([JUMPDEST, RETURNDATASIZE, PUSH1, 0, RETURN]);
+    debug_assert_eq!(code.len(), CALLBACK_WRAPPER_LEN as usize);
+
+    code.extend_from_slice(calldata);
+    Some(code.into())
+}
+
 /// Given a TestRunner and a strategy, it generates calls. Used inside the Fuzzer inspector to
 /// override external calls to test for potential reentrancy vulnerabilities..
 #[derive(Clone, Debug)]
@@ -22,6 +81,13 @@ pub struct RandomCallGenerator {
     pub target_reference: Arc<RwLock<Address>>,
     /// Flag to know if a call has been overridden. Don't allow nesting for now.
     pub used: bool,
+    /// If set, any external call can be overridden. Otherwise, only token receiver callbacks are.
+    pub override_calls: bool,
+    /// If set, token receiver callbacks make the generated call before running their own logic.
+    pub override_callbacks: bool,
+    /// The depth of the wrapped token receiver callback being executed. No other call is
+    /// overridden until it ends.
+    pub wrapped_callback: Option<u64>,
     /// If set to `true`, consumes the next call from `last_sequence`, otherwise queries it from
     /// the strategy.
     pub replay: bool,
@@ -35,6 +101,8 @@ impl RandomCallGenerator {
         runner: TestRunner,
         strategy: SBoxedStrategy<(Address, Bytes)>,
         target_reference: Arc<RwLock<Address>>,
+        override_calls: bool,
+        override_callbacks: bool,
     ) -> Self {
         let strategy = weighted(0.9, strategy).sboxed();
 
@@ -46,6 +114,9 @@ impl RandomCallGenerator {
             last_sequence: Arc::new(RwLock::new(vec![])),
             replay: false,
             used: false,
+            override_calls,
+            override_callbacks,
+            wrapped_callback: None,
         }
     }
 
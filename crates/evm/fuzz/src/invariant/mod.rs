@@ -4,7 +4,9 @@ use parking_lot::Mutex;
 use std::{collections::BTreeMap, sync::Arc};
 
 mod call_override;
-pub use call_override::RandomCallGenerator;
+pub use call_override::{
+    callback_wrapper_code, is_token_callback, RandomCallGenerator, CALLBACK_WRAPPER_ADDRESS,
+};
 
 mod filters;
 pub use filters::{ArtifactFilters, SenderFilters};
@@ -15,6 +17,33 @@ pub type FuzzRunIdentifiedContracts = Arc<Mutex<TargetedContracts>>;
 /// (Sender, (TargetContract, Calldata))
 pub type BasicTxDetails = (Address, (Address, Bytes));
 
+/// (BlockJump, (Sender, (TargetContract, Calldata)))
+pub type InvariantTx = (BlockJump, BasicTxDetails);
+
+/// The seconds and blocks the chain is advanced by before an invariant call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BlockJump {
+    /// Seconds added to the block timestamp.
+    pub warp: u64,
+    /// Blocks added to the block number.
+    pub roll: u64,
+}
+
+impl BlockJump {
+    /// Returns `true` if the jump doesn't advance the chain.
+    pub fn is_empty(&self) -> bool {
+        self.warp == 0 && self.roll == 0
+    }
+
+    /// Returns the sum of both jumps.
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self {
+            warp: self.warp.saturating_add(other.warp),
+            roll: self.roll.saturating_add(other.roll),
+        }
+    }
+}
+
 /// Test contract which is testing its invariants.
 #[derive(Clone, Debug)]
 pub struct InvariantContract<'a> {
@@ -11,7 +11,8 @@ use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
 use alloy_primitives::{Address, Bytes, Log};
 use foundry_common::{calc, contracts::ContractsByAddress};
 use foundry_evm_coverage::HitMaps;
-use foundry_evm_traces::CallTraceArena;
+use foundry_evm_traces::TraceArena;
+use invariant::BlockJump;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt};
@@ -47,9 +48,15 @@ pub struct BaseCounterExample {
     pub signature: Option<String>,
     /// Contract name if it exists
     pub contract_name: Option<String>,
+    /// Seconds the block timestamp was advanced by before the call, if it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warp: Option<u64>,
+    /// Blocks the block number was advanced by before the call, if it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roll: Option<u64>,
     /// Traces
     #[serde(skip)]
-    pub traces: Option<CallTraceArena>,
+    pub traces: Option<TraceArena>,
     #[serde(skip)]
     pub args: Vec<DynSolValue>,
 }
@@ -60,7 +67,7 @@ impl BaseCounterExample {
         addr: Address,
         bytes: &Bytes,
         contracts: &ContractsByAddress,
-        traces: Option<CallTraceArena>,
+        traces: Option<TraceArena>,
     ) -> Self {
         if let Some((name, abi)) = &contracts.get(&addr) {
             if let Some(func) = abi.functions().find(|f| f.selector() == bytes[..4]) {
@@ -72,6 +79,8 @@ impl BaseCounterExample {
                         calldata: bytes.clone(),
                         signature: Some(func.signature()),
                         contract_name: Some(name.clone()),
+                        warp: None,
+                        roll: None,
                         traces,
                         args,
                     };
@@ -85,10 +94,19 @@ impl BaseCounterExample {
             calldata: bytes.clone(),
             signature: None,
             contract_name: None,
+            warp: None,
+            roll: None,
             traces,
             args: vec![],
         }
     }
+
+    /// Sets the block jump applied before the call.
+    pub fn with_block_jump(mut self, jump: BlockJump) -> Self {
+        self.warp = (jump.warp > 0).then_some(jump.warp);
+        self.roll = (jump.roll > 0).then_some(jump.roll);
+        self
+    }
 }
 
 impl fmt::Display for BaseCounterExample {
@@ -111,7 +129,17 @@ impl fmt::Display for BaseCounterExample {
             write!(f, "calldata={}", self.calldata)?
         }
 
-        write!(f, " args=[{}]", foundry_common::fmt::format_tokens(&self.args).format(", "))
+        write!(f, " args=[{}]", foundry_common::fmt::format_tokens(&self.args).format(", "))?;
+
+        if let Some(warp) = self.warp {
+            write!(f, " warp={warp}")?
+        }
+
+        if let Some(roll) = self.roll {
+            write!(f, " roll={roll}")?
+        }
+
+        Ok(())
     }
 }
 
@@ -148,10 +176,13 @@ pub struct FuzzTestResult {
     ///
     /// **Note** We only store a single trace of a successful fuzz call, otherwise we would get
     /// `num(fuzz_cases)` traces, one for each run, which is neither helpful nor performant.
-    pub traces: Option<CallTraceArena>,
+    pub traces: Option<TraceArena>,
 
     /// Raw coverage info
     pub coverage: Option<HitMaps>,
+
+    /// Whether the campaign was cancelled by the failure of another test, see `--fail-fast`
+    pub cancelled: bool,
 }
 
 impl FuzzTestResult {
@@ -2,12 +2,15 @@ use crate::{
     identifier::{
         AddressIdentity, LocalTraceIdentifier, SingleSignaturesIdentifier, TraceIdentifier,
     },
+    internal::InternalFunctions,
+    state::{self, DecodedStorageWrite, StorageWrite},
     CallTrace, CallTraceArena, CallTraceNode, DecodedCallData, DecodedCallLog, DecodedCallTrace,
 };
 use alloy_dyn_abi::{DecodedEvent, DynSolValue, EventExt, FunctionExt, JsonAbiExt};
 use alloy_json_abi::{Error, Event, Function, JsonAbi};
-use alloy_primitives::{Address, LogData, Selector, B256};
+use alloy_primitives::{Address, LogData, Selector, B256, U256};
 use foundry_common::{abi::get_indexed_event, fmt::format_token, SELECTOR_LEN};
+use foundry_compilers::artifacts::StorageLayout;
 use foundry_evm_core::{
     abi::{Console, HardhatConsole, Vm, HARDHAT_CONSOLE_SELECTOR_PATCHES},
     constants::{
@@ -68,6 +71,34 @@ impl CallTraceDecoderBuilder {
         self
     }
 
+    /// Sets the gas price the top-level calls paid for their gas, see [crate::CallStateChanges].
+    #[inline]
+    pub fn with_gas_price(mut self, gas_price: U256) -> Self {
+        self.decoder.gas_price = gas_price;
+        self
+    }
+
+    /// Add the storage layouts of known contracts, keyed by contract identifier, to the decoder.
+    #[inline]
+    pub fn with_storage_layouts(
+        mut self,
+        layouts: impl IntoIterator<Item = (String, StorageLayout)>,
+    ) -> Self {
+        self.decoder.storage_layouts.extend(layouts);
+        self
+    }
+
+    /// Add the internal functions of known contracts, keyed by contract identifier, to the
+    /// decoder.
+    #[inline]
+    pub fn with_internal_functions(
+        mut self,
+        functions: impl IntoIterator<Item = (String, InternalFunctions)>,
+    ) -> Self {
+        self.decoder.internal_functions.extend(functions);
+        self
+    }
+
     /// Sets the signature identifier for events and functions.
     #[inline]
     pub fn with_signature_identifier(mut self, identifier: SingleSignaturesIdentifier) -> Self {
@@ -106,11 +137,17 @@ pub struct CallTraceDecoder {
     pub events: BTreeMap<(B256, usize), Vec<Event>>,
     /// Revert decoder. Contains all known custom errors.
     pub revert_decoder: RevertDecoder,
+    /// Storage layouts of known contracts, keyed by the same identifiers as [Self::contracts].
+    pub storage_layouts: HashMap<String, StorageLayout>,
+    /// Internal functions of known contracts, keyed by the same identifiers as [Self::contracts].
+    pub internal_functions: HashMap<String, InternalFunctions>,
 
     /// A signature identifier for events and functions.
     pub signature_identifier: Option<SingleSignaturesIdentifier>,
     /// Verbosity level
     pub verbosity: u8,
+    /// The gas price of the traced transactions.
+    pub gas_price: U256,
 }
 
 impl CallTraceDecoder {
@@ -170,9 +207,12 @@ impl CallTraceDecoder {
                 .map(|event| ((event.selector(), indexed_inputs(&event)), vec![event]))
                 .collect(),
             revert_decoder: Default::default(),
+            storage_layouts: Default::default(),
+            internal_functions: Default::default(),
 
             signature_identifier: None,
             verbosity: 0,
+            gas_price: U256::ZERO,
         }
     }
 
@@ -536,19 +576,47 @@ impl CallTraceDecoder {
         DecodedCallLog::Raw(log)
     }
 
+    /// Decodes a storage write with the storage layout of the contract at `code_address`, the
+    /// contract whose code made the write.
+    ///
+    /// Returns an empty vector if the layout is unknown or the slot isn't a variable of it.
+    pub fn decode_storage_write(
+        &self,
+        code_address: &Address,
+        write: &StorageWrite,
+    ) -> Vec<DecodedStorageWrite> {
+        self.contracts
+            .get(code_address)
+            .and_then(|contract| self.storage_layouts.get(contract))
+            .map(|layout| state::decode_storage_write(layout, write))
+            .unwrap_or_default()
+    }
+
+    /// Returns the internal functions of the contract at the given address, if known.
+    pub fn internal_functions_at(&self, address: &Address) -> Option<&InternalFunctions> {
+        self.internal_functions.get(self.contracts.get(address)?)
+    }
+
     /// Prefetches function and event signatures into the identifier cache
-    pub async fn prefetch_signatures(&self, nodes: &[CallTraceNode]) {
+    ///
+    /// The unknown signatures of all the `nodes` are looked up at once, so the nodes of several
+    /// traces can be passed to batch their lookups.
+    pub async fn prefetch_signatures<'a, I>(&self, nodes: I)
+    where
+        I: IntoIterator<Item = &'a CallTraceNode>,
+        I::IntoIter: Clone,
+    {
         let Some(identifier) = &self.signature_identifier else { return };
+        let nodes = nodes.into_iter();
 
         let events_it = nodes
-            .iter()
+            .clone()
             .flat_map(|node| node.logs.iter().filter_map(|log| log.topics().first()))
             .unique();
         identifier.write().await.identify_events(events_it).await;
 
         const DEFAULT_CREATE2_DEPLOYER_BYTES: [u8; 20] = DEFAULT_CREATE2_DEPLOYER.0 .0;
         let funcs_it = nodes
-            .iter()
             .filter_map(|n| match n.trace.address.0 .0 {
                 DEFAULT_CREATE2_DEPLOYER_BYTES => None,
                 [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01..=0x0a] => None,
@@ -4,7 +4,10 @@ use foundry_block_explorers::{
     contract::{ContractMetadata, Metadata},
     errors::EtherscanError,
 };
-use foundry_common::compile::{self, ContractSources};
+use foundry_common::{
+    compile::{self, ContractSources},
+    etherscan::EtherscanClient,
+};
 use foundry_config::{Chain, Config};
 use foundry_evm_core::utils::RuntimeOrHandle;
 use futures::{
@@ -22,13 +25,12 @@ use std::{
         Arc,
     },
 };
-use tokio::time::{Duration, Interval};
 
 /// A trace identifier that tries to identify addresses using Etherscan.
 #[derive(Default)]
 pub struct EtherscanIdentifier {
     /// The Etherscan client
-    client: Option<Arc<foundry_block_explorers::Client>>,
+    client: Option<EtherscanClient>,
     /// Tracks whether the API key provides was marked as invalid
     ///
     /// After the first [EtherscanError::InvalidApiKey] this will get set to true, so we can
@@ -40,15 +42,15 @@ pub struct EtherscanIdentifier {
 
 impl EtherscanIdentifier {
     /// Creates a new Etherscan identifier with the given client
-    pub fn new(config: &Config, chain: Option<Chain>) -> eyre::Result<Self> {
-        if config.offline {
+    pub fn new(cfg: &Config, chain: Option<Chain>) -> eyre::Result<Self> {
+        if cfg.offline {
             // offline mode, don't use etherscan
             return Ok(Default::default())
         }
-        if let Some(config) = config.get_etherscan_config_with_chain(chain)? {
+        if let Some(config) = cfg.get_etherscan_config_with_chain(chain)? {
             trace!(target: "etherscanidentifier", chain=?config.chain, url=?config.api_url, "using etherscan identifier");
             Ok(Self {
-                client: Some(Arc::new(config.into_client()?)),
+                client: Some(EtherscanClient::new(config, cfg.etherscan_requests_per_second)?),
                 invalid_api_key: Arc::new(Default::default()),
                 contracts: BTreeMap::new(),
                 sources: BTreeMap::new(),
@@ -111,12 +113,7 @@ impl TraceIdentifier for EtherscanIdentifier {
             return Vec::new()
         }
 
-        let mut fetcher = EtherscanFetcher::new(
-            client,
-            Duration::from_secs(1),
-            5,
-            Arc::clone(&self.invalid_api_key),
-        );
+        let mut fetcher = EtherscanFetcher::new(client, 5, Arc::clone(&self.invalid_api_key));
 
         for (addr, _) in addresses {
             if !self.contracts.contains_key(addr) {
@@ -147,16 +144,13 @@ impl TraceIdentifier for EtherscanIdentifier {
 type EtherscanFuture =
     Pin<Box<dyn Future<Output = (Address, Result<ContractMetadata, EtherscanError>)>>>;
 
-/// A rate limit aware Etherscan client.
+/// Fetches information about multiple addresses concurrently.
 ///
-/// Fetches information about multiple addresses concurrently, while respecting rate limits.
+/// The client throttles the requests to the rate limit of its API key, and retries the rate
+/// limited ones.
 struct EtherscanFetcher {
     /// The Etherscan client
-    client: Arc<foundry_block_explorers::Client>,
-    /// The time we wait if we hit the rate limit
-    timeout: Duration,
-    /// The interval we are currently waiting for before making a new request
-    backoff: Option<Interval>,
+    client: EtherscanClient,
     /// The maximum amount of requests to send concurrently
     concurrency: usize,
     /// The addresses we have yet to make requests for
@@ -168,16 +162,9 @@ struct EtherscanFetcher {
 }
 
 impl EtherscanFetcher {
-    fn new(
-        client: Arc<foundry_block_explorers::Client>,
-        timeout: Duration,
-        concurrency: usize,
-        invalid_api_key: Arc<AtomicBool>,
-    ) -> Self {
+    fn new(client: EtherscanClient, concurrency: usize, invalid_api_key: Arc<AtomicBool>) -> Self {
         Self {
             client,
-            timeout,
-            backoff: None,
             concurrency,
             queue: Vec::new(),
             in_progress: FuturesUnordered::new(),
@@ -192,10 +179,10 @@ impl EtherscanFetcher {
     fn queue_next_reqs(&mut self) {
         while self.in_progress.len() < self.concurrency {
             if let Some(addr) = self.queue.pop() {
-                let client = Arc::clone(&self.client);
+                let client = self.client.clone();
                 trace!(target: "etherscanidentifier", "fetching info for {:?}", addr);
                 self.in_progress.push(Box::pin(async move {
-                    let res = client.contract_source_code(addr).await;
+                    let res = client.request(|client| client.contract_source_code(addr)).await;
                     (addr, res)
                 }));
             } else {
@@ -212,13 +199,6 @@ impl Stream for EtherscanFetcher {
         let pin = self.get_mut();
 
         loop {
-            if let Some(mut backoff) = pin.backoff.take() {
-                if backoff.poll_tick(cx).is_pending() {
-                    pin.backoff = Some(backoff);
-                    return Poll::Pending
-                }
-            }
-
             pin.queue_next_reqs();
 
             let mut made_progress_this_iter = false;
@@ -233,11 +213,6 @@ impl Stream for EtherscanFetcher {
                                 return Poll::Ready(Some((addr, item)))
                             }
                         }
-                        Err(EtherscanError::RateLimitExceeded) => {
-                            warn!(target: "etherscanidentifier", "rate limit exceeded on attempt");
-                            pin.backoff = Some(tokio::time::interval(pin.timeout));
-                            pin.queue.push(addr);
-                        }
                         Err(EtherscanError::InvalidApiKey) => {
                             warn!(target: "etherscanidentifier", "invalid api key");
                             // mark key as invalid
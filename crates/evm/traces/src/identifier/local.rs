@@ -1,9 +1,9 @@
 use super::{AddressIdentity, TraceIdentifier};
 use alloy_json_abi::JsonAbi;
-use alloy_primitives::Address;
+use alloy_primitives::{keccak256, Address, B256};
 use foundry_common::contracts::{bytecode_diff_score, ContractsByArtifact};
 use foundry_compilers::ArtifactId;
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
 /// A trace identifier that tries to identify addresses using local contracts.
 pub struct LocalTraceIdentifier<'a> {
@@ -11,6 +11,10 @@ pub struct LocalTraceIdentifier<'a> {
     known_contracts: &'a ContractsByArtifact,
     /// Vector of pairs of artifact ID and the code length of the given artifact.
     ordered_ids: Vec<(&'a ArtifactId, usize)>,
+    /// The artifacts already matched against, by code hash.
+    ///
+    /// The same contracts are usually deployed by every test, so each code is only diffed once.
+    identified: HashMap<B256, Option<(&'a ArtifactId, &'a JsonAbi)>>,
 }
 
 impl<'a> LocalTraceIdentifier<'a> {
@@ -20,7 +24,7 @@ impl<'a> LocalTraceIdentifier<'a> {
         let mut ordered_ids =
             known_contracts.iter().map(|(id, contract)| (id, contract.1.len())).collect::<Vec<_>>();
         ordered_ids.sort_by_key(|(_, len)| *len);
-        Self { known_contracts, ordered_ids }
+        Self { known_contracts, ordered_ids, identified: HashMap::new() }
     }
 
     /// Returns the known contracts.
@@ -113,7 +117,16 @@ impl TraceIdentifier for LocalTraceIdentifier<'_> {
                 let _span = trace_span!(target: "evm::traces", "identify", %address).entered();
 
                 trace!(target: "evm::traces", "identifying");
-                let (id, abi) = self.identify_code(code?)?;
+                let code = code?;
+                let hash = keccak256(code);
+                let (id, abi) = match self.identified.get(&hash) {
+                    Some(identified) => (*identified)?,
+                    None => {
+                        let identified = self.identify_code(code);
+                        self.identified.insert(hash, identified);
+                        identified?
+                    }
+                };
                 trace!(target: "evm::traces", id=%id.identifier(), "identified");
 
                 Some(AddressIdentity {
@@ -5,12 +5,23 @@ use foundry_common::{
     selectors::{SelectorType, SignEthClient},
 };
 use hashbrown::HashSet;
+use itertools::Itertools;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::{Arc, Mutex, Weak},
+};
 use tokio::sync::RwLock;
 
 pub type SingleSignaturesIdentifier = Arc<RwLock<SignaturesIdentifier>>;
 
+/// The live identifiers, by cache path and offline mode.
+static IDENTIFIERS: Lazy<
+    Mutex<HashMap<(Option<PathBuf>, bool), Weak<RwLock<SignaturesIdentifier>>>>,
+> = Lazy::new(Default::default);
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct CachedSignatures {
     events: BTreeMap<String, String>,
@@ -34,10 +45,30 @@ pub struct SignaturesIdentifier {
 }
 
 impl SignaturesIdentifier {
+    /// Returns the identifier of the signature cache at `cache_path`.
+    ///
+    /// The identifier is shared with the other live users of the same cache, e.g. the decoders of
+    /// a script before and after relinking, so the cache is read once and a selector is only
+    /// looked up once. The cache is saved when the last user drops it.
     #[instrument(target = "evm::traces")]
     pub fn new(
         cache_path: Option<PathBuf>,
         offline: bool,
+    ) -> eyre::Result<SingleSignaturesIdentifier> {
+        let mut identifiers = IDENTIFIERS.lock().unwrap_or_else(|err| err.into_inner());
+        let key = (cache_path.clone(), offline);
+        if let Some(identifier) = identifiers.get(&key).and_then(Weak::upgrade) {
+            return Ok(identifier)
+        }
+        let identifier = Self::open(cache_path, offline)?;
+        identifiers.insert(key, Arc::downgrade(&identifier));
+        Ok(identifier)
+    }
+
+    /// Reads the signature cache at `cache_path` into a new identifier.
+    fn open(
+        cache_path: Option<PathBuf>,
+        offline: bool,
     ) -> eyre::Result<SingleSignaturesIdentifier> {
         let sign_eth_api = SignEthClient::new()?;
 
@@ -111,6 +142,7 @@ impl SignaturesIdentifier {
                 .iter()
                 .filter(|v| !cache.contains_key(v.as_str()))
                 .filter(|v| !self.unavailable.contains(v.as_str()))
+                .unique()
                 .collect();
 
             if let Ok(res) = self.sign_eth_api.decode_selectors(selector_type, query.clone()).await
@@ -200,4 +232,15 @@ mod tests {
         assert_eq!(sigs.read().await.cached.events.len(), 1);
         assert_eq!(sigs.read().await.cached.functions.len(), 1);
     }
+
+    #[test]
+    fn shares_live_identifiers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        let shared = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        assert!(Arc::ptr_eq(&sigs, &shared));
+
+        let online = SignaturesIdentifier::new(Some(tmp.path().into()), false).unwrap();
+        assert!(!Arc::ptr_eq(&sigs, &online));
+    }
 }
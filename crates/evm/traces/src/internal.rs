@@ -0,0 +1,410 @@
+//! Internal function calls, decoded from the steps of a call with the source map of its code.
+
+use crate::{state::format_value, CallTraceNode, RETURN};
+use alloy_primitives::U256;
+use foundry_common::compile::ContractSources;
+use foundry_compilers::{
+    artifacts::ConfigurableContractArtifact,
+    sourcemap::{Jump, SourceElement, SourceMap},
+    ArtifactId,
+};
+use revm::interpreter::opcode;
+use revm_inspectors::tracing::types::LogCallOrder;
+use std::collections::HashMap;
+
+/// The internal functions of a contract, and the jumps into and out of them.
+#[derive(Clone, Debug, Default)]
+pub struct InternalFunctions {
+    /// Whether the `JUMP` at a program counter jumps into (`true`) or out of (`false`) a function.
+    jumps: HashMap<usize, bool>,
+    /// The functions by the program counter of the `JUMPDEST` they start at.
+    entries: HashMap<usize, InternalFunction>,
+}
+
+impl InternalFunctions {
+    /// Finds the internal functions of the runtime code with its source map.
+    ///
+    /// `sources` maps the source indices of the source map to the source code.
+    pub fn new(code: &[u8], source_map: &SourceMap, sources: &HashMap<u32, &str>) -> Self {
+        let mut functions = Self::default();
+        for (pc, op, element) in instructions(code, source_map) {
+            match op {
+                opcode::JUMP if matches!(element.jump, Jump::In | Jump::Out) => {
+                    functions.jumps.insert(pc, matches!(element.jump, Jump::In));
+                }
+                opcode::JUMPDEST => {
+                    let function = element
+                        .index
+                        .and_then(|index| sources.get(&index))
+                        .and_then(|source| source.get(element.offset..))
+                        .and_then(InternalFunction::parse);
+                    if let Some(function) = function {
+                        functions.entries.insert(pc, function);
+                    }
+                }
+                _ => {}
+            }
+        }
+        functions
+    }
+
+    /// Returns the logs, subcalls and internal function calls of the call in execution order.
+    ///
+    /// Falls back to the logs and subcalls if the stack of the steps wasn't recorded. The
+    /// outermost internal call to `external`, the function called by the call, is skipped.
+    pub(crate) fn trace_items(
+        &self,
+        node: &CallTraceNode,
+        external: Option<&str>,
+    ) -> Vec<TraceItem> {
+        let steps = &node.trace.steps;
+        if !steps.iter().any(|step| step.stack.is_some()) {
+            return TraceItem::from_ordering(node);
+        }
+
+        let mut items = Vec::new();
+        let (mut logs, mut calls) = (0, 0);
+        // the open internal calls, and whether they're displayed
+        let mut frames: Vec<Option<&InternalFunction>> = Vec::new();
+        for (i, step) in steps.iter().enumerate() {
+            let stack = step.stack.as_deref().unwrap_or_default();
+            match step.op.get() {
+                opcode::JUMP => match self.jumps.get(&step.pc) {
+                    Some(true) => {
+                        let function = steps
+                            .get(i + 1)
+                            .and_then(|next| self.entries.get(&next.pc))
+                            .filter(|function| {
+                                !frames.is_empty() || external != Some(function.name.as_str())
+                            });
+                        if let Some(function) = function {
+                            items.push(TraceItem::Enter(function.format_call(stack)));
+                        }
+                        frames.push(function);
+                    }
+                    Some(false) => {
+                        if let Some(Some(function)) = frames.pop() {
+                            items.push(TraceItem::Exit(function.format_return(stack)));
+                        }
+                    }
+                    None => {}
+                },
+                opcode::LOG0..=opcode::LOG4 if logs < node.logs.len() => {
+                    items.push(TraceItem::Log(logs));
+                    logs += 1;
+                }
+                op if is_call(op) && calls < node.children.len() => {
+                    items.push(TraceItem::Call(calls));
+                    calls += 1;
+                }
+                _ => {}
+            }
+        }
+        items.extend((logs..node.logs.len()).map(TraceItem::Log));
+        items.extend((calls..node.children.len()).map(TraceItem::Call));
+        items
+    }
+}
+
+/// Collects the internal functions of the runtime code of all the contracts, by contract name.
+///
+/// Use [artifact_internal_functions] for artifacts that are identified by their artifact ID.
+pub fn internal_functions(sources: &ContractSources) -> HashMap<String, InternalFunctions> {
+    let source_codes = source_codes(sources);
+    sources
+        .entries()
+        .filter_map(|(name, (_, contract))| {
+            let bytecode = contract.deployed_bytecode.bytecode.as_ref()?;
+            let code = bytecode.bytes()?;
+            let source_map = bytecode.source_map()?.ok()?;
+            Some((name, InternalFunctions::new(code, &source_map, &source_codes)))
+        })
+        .collect()
+}
+
+/// Collects the internal functions of the runtime code of the artifacts, by artifact identifier.
+///
+/// `sources` holds the source code of the files referred to by the source maps.
+pub fn artifact_internal_functions<'a>(
+    artifacts: impl IntoIterator<Item = (ArtifactId, &'a ConfigurableContractArtifact)>,
+    sources: &ContractSources,
+) -> HashMap<String, InternalFunctions> {
+    let source_codes = source_codes(sources);
+    artifacts
+        .into_iter()
+        .filter_map(|(id, artifact)| {
+            let bytecode = artifact.deployed_bytecode.as_ref()?.bytecode.as_ref()?;
+            let code = bytecode.object.as_bytes()?;
+            let source_map = bytecode.source_map()?.ok()?;
+            Some((id.identifier(), InternalFunctions::new(code, &source_map, &source_codes)))
+        })
+        .collect()
+}
+
+/// Returns the source codes by source file ID.
+fn source_codes(sources: &ContractSources) -> HashMap<u32, &str> {
+    sources.sources_by_id.iter().map(|(id, (source, _))| (*id, source.as_str())).collect()
+}
+
+/// Iterates over the instructions of the code with their source map elements, as
+/// `(pc, opcode, element)`.
+pub(crate) fn instructions<'a>(
+    code: &'a [u8],
+    source_map: &'a SourceMap,
+) -> impl Iterator<Item = (usize, u8, &'a SourceElement)> + 'a {
+    let mut pc = 0;
+    source_map.iter().map_while(move |element| {
+        let op = *code.get(pc)?;
+        let instruction = (pc, op, element);
+        pc += 1;
+        if (opcode::PUSH1..=opcode::PUSH32).contains(&op) {
+            pc += (op - opcode::PUSH1 + 1) as usize;
+        }
+        Some(instruction)
+    })
+}
+
+/// Returns `true` if the opcode starts a subcall, i.e. a call or a create.
+pub(crate) fn is_call(op: u8) -> bool {
+    matches!(
+        op,
+        opcode::CALL |
+            opcode::CALLCODE |
+            opcode::DELEGATECALL |
+            opcode::STATICCALL |
+            opcode::CREATE |
+            opcode::CREATE2
+    )
+}
+
+/// A log, subcall or internal function call of a call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TraceItem {
+    /// The index of a log of the call.
+    Log(usize),
+    /// The index of a child of the call.
+    Call(usize),
+    /// The entry of an internal function call, rendered.
+    Enter(String),
+    /// The exit of an internal function call, rendered.
+    Exit(String),
+}
+
+impl TraceItem {
+    /// Returns the logs and subcalls of the call in execution order.
+    pub(crate) fn from_ordering(node: &CallTraceNode) -> Vec<Self> {
+        node.ordering
+            .iter()
+            .map(|order| match order {
+                LogCallOrder::Log(index) => Self::Log(*index),
+                LogCallOrder::Call(index) => Self::Call(*index),
+            })
+            .collect()
+    }
+}
+
+/// An internal function, parsed from its definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct InternalFunction {
+    name: String,
+    params: Vec<Variable>,
+    returns: Vec<Variable>,
+}
+
+impl InternalFunction {
+    /// Parses the function from the source code starting at its definition, e.g.
+    /// `function add(uint256 a, uint256 b) internal pure returns (uint256) { ... }`.
+    fn parse(source: &str) -> Option<Self> {
+        let rest = source.strip_prefix("function")?;
+        let (name, rest) = rest.split_once('(')?;
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$') {
+            return None;
+        }
+        let (params, rest) = split_parenthesized(rest)?;
+        let header = &rest[..rest.find(['{', ';']).unwrap_or(rest.len())];
+        let returns = match header.find("returns") {
+            Some(i) => split_parenthesized(header[i + 7..].trim_start().strip_prefix('(')?)?.0,
+            None => "",
+        };
+        Some(Self {
+            name: name.to_string(),
+            params: Variable::parse_list(params),
+            returns: Variable::parse_list(returns),
+        })
+    }
+
+    /// Formats the call with the arguments on the stack below the jump destination.
+    fn format_call(&self, stack: &[U256]) -> String {
+        let args = match Variable::format_list(&self.params, stack, 1) {
+            Some(values) => values.join(", "),
+            None => {
+                self.params.iter().map(|param| param.name.as_str()).collect::<Vec<_>>().join(", ")
+            }
+        };
+        format!("{}({args}) [internal]", self.name)
+    }
+
+    /// Formats the return with the values on the stack below the return address.
+    fn format_return(&self, stack: &[U256]) -> String {
+        let values = Variable::format_list(&self.returns, stack, 1).unwrap_or_default();
+        format!("{RETURN}{}", if values.is_empty() { "()".to_string() } else { values.join(", ") })
+    }
+}
+
+/// A parameter or return variable of an internal function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Variable {
+    ty: String,
+    name: String,
+    location: Option<String>,
+}
+
+impl Variable {
+    /// Parses a comma separated list of variable declarations.
+    fn parse_list(list: &str) -> Vec<Self> {
+        let mut variables = Vec::new();
+        let (mut depth, mut start) = (0, 0);
+        for (i, c) in list.char_indices().chain(std::iter::once((list.len(), ','))) {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    variables.extend(Self::parse(&list[start..i]));
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        variables
+    }
+
+    /// Parses a variable declaration, e.g. `uint256[] memory amounts`.
+    fn parse(declaration: &str) -> Option<Self> {
+        let mut tokens: Vec<&str> = declaration.split_whitespace().collect();
+        let location = tokens
+            .iter()
+            .position(|token| matches!(*token, "memory" | "storage" | "calldata"))
+            .map(|i| tokens.remove(i).to_string());
+        let name = if tokens.len() > 1 && tokens.last() != Some(&"payable") {
+            tokens.pop().unwrap().to_string()
+        } else {
+            String::new()
+        };
+        (!tokens.is_empty()).then(|| Self { ty: tokens.join(" "), name, location })
+    }
+
+    /// Returns the number of stack slots the variable takes.
+    fn slots(&self) -> usize {
+        let is_dynamic = self.ty.ends_with("[]") || self.ty == "bytes" || self.ty == "string";
+        let is_external_function = self.ty.starts_with("function") && self.ty.contains("external");
+        if (self.location.as_deref() == Some("calldata") && is_dynamic) || is_external_function {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Formats the values of the variables, which are on the stack below the `skip` top items.
+    ///
+    /// Returns `None` if the stack is too small to hold them.
+    fn format_list(variables: &[Self], stack: &[U256], skip: usize) -> Option<Vec<String>> {
+        let slots = variables.iter().map(Self::slots).sum::<usize>();
+        let end = stack.len().checked_sub(skip)?;
+        let mut words = &stack[end.checked_sub(slots)?..end];
+        let mut values = Vec::with_capacity(variables.len());
+        for variable in variables {
+            let (value, rest) = words.split_at(variable.slots());
+            values.push(variable.format(value[0]));
+            words = rest;
+        }
+        Some(values)
+    }
+
+    /// Formats the value of the variable from its first stack slot.
+    ///
+    /// Only value types can be recovered from the stack, references are formatted as their data
+    /// location.
+    fn format(&self, word: U256) -> String {
+        if let Some(location) = &self.location {
+            return format!("<{location}>");
+        }
+        let ty = self.ty.as_str();
+        if let Some(size) = ty.strip_prefix("bytes").and_then(|size| size.parse::<usize>().ok()) {
+            // fixed bytes are left aligned on the stack
+            if (1..=32).contains(&size) {
+                return format_value(word >> ((32 - size) * 8), size, ty);
+            }
+        }
+        let size = ty
+            .trim_start_matches("uint")
+            .trim_start_matches("int")
+            .parse::<usize>()
+            .map_or(32, |bits| (bits / 8).clamp(1, 32));
+        format_value(word, size, ty)
+    }
+}
+
+/// Splits the source after an opening parenthesis at the matching closing one.
+fn split_parenthesized(s: &str) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some((&s[..i], &s[i + 1..])),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_internal_functions() {
+        let function = InternalFunction::parse(
+            "function _transfer(address from, uint256[] calldata ids, bytes memory data) internal returns (bool ok, int8) {\n    return (true, -1);\n}",
+        )
+        .unwrap();
+        assert_eq!(function.name, "_transfer");
+        assert_eq!(
+            function.params.iter().map(|param| param.name.as_str()).collect::<Vec<_>>(),
+            ["from", "ids", "data"]
+        );
+        assert_eq!(function.params[1].slots(), 2);
+        assert_eq!(
+            function.returns[1],
+            Variable { ty: "int8".into(), name: "".into(), location: None }
+        );
+
+        assert_eq!(InternalFunction::parse("modifier onlyOwner() {"), None);
+        assert_eq!(InternalFunction::parse("function() external payable {"), None);
+    }
+
+    #[test]
+    fn formats_internal_calls_from_the_stack() {
+        let function = InternalFunction::parse(
+            "function f(bool flag, bytes2 tag, string calldata name, int16 delta) private returns (uint256)",
+        )
+        .unwrap();
+
+        // return address, arguments, jump destination
+        let stack = [
+            U256::from(0x42),
+            U256::from(1),
+            U256::from(0xabcd) << 240,
+            U256::from(0x80),
+            U256::from(4),
+            U256::MAX,
+            U256::from(0x1234),
+        ];
+        assert_eq!(function.format_call(&stack), "f(true, 0xabcd, <calldata>, -1) [internal]");
+        assert_eq!(function.format_call(&stack[4..]), "f(flag, tag, name, delta) [internal]");
+
+        // return values, return address
+        assert_eq!(function.format_return(&[U256::from(7), U256::from(0x42)]), "← 7");
+    }
+}
@@ -7,12 +7,16 @@
 #[macro_use]
 extern crate tracing;
 
-use alloy_primitives::LogData;
+use alloy_primitives::{Address, LogData, Selector};
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
 use foundry_evm_core::constants::CHEATCODE_ADDRESS;
 use futures::{future::BoxFuture, FutureExt};
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt::Write};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    ops::{Deref, DerefMut},
+};
 use yansi::{Color, Paint};
 
 /// Call trace address identifiers.
@@ -24,14 +28,63 @@ use identifier::LocalTraceIdentifier;
 mod decoder;
 pub use decoder::{CallTraceDecoder, CallTraceDecoderBuilder};
 
-use revm_inspectors::tracing::types::LogCallOrder;
+mod internal;
+use internal::TraceItem;
+pub use internal::{artifact_internal_functions, internal_functions, InternalFunctions};
+
+mod stack_trace;
+pub use stack_trace::{StackFrame, StackTraces};
+
+mod state;
+pub use state::{
+    decode_storage_write, state_changes, BalanceChange, CallStateChanges, DecodedStorageWrite,
+    StorageWrite,
+};
+
 pub use revm_inspectors::tracing::{
     types::{CallKind, CallTrace, CallTraceNode},
     CallTraceArena, GethTraceBuilder, ParityTraceBuilder, StackSnapshotType, TracingInspector,
     TracingInspectorConfig,
 };
 
-pub type Traces = Vec<(TraceKind, CallTraceArena)>;
+pub type Traces = Vec<(TraceKind, TraceArena)>;
+
+/// A [CallTraceArena] along with the nodes of the calls that were answered by a mock.
+///
+/// Mocked calls don't execute, so they're tracked apart from the recorded traces.
+#[derive(Clone, Debug, Default)]
+pub struct TraceArena {
+    pub arena: CallTraceArena,
+    /// The indexes of the nodes of the mocked calls
+    pub mocked_calls: BTreeSet<usize>,
+}
+
+impl TraceArena {
+    /// Returns `true` if the call of the node at `idx` was answered by a mock.
+    pub fn is_mocked(&self, idx: usize) -> bool {
+        self.mocked_calls.contains(&idx)
+    }
+}
+
+impl From<CallTraceArena> for TraceArena {
+    fn from(arena: CallTraceArena) -> Self {
+        Self { arena, mocked_calls: BTreeSet::new() }
+    }
+}
+
+impl Deref for TraceArena {
+    type Target = CallTraceArena;
+
+    fn deref(&self) -> &Self::Target {
+        &self.arena
+    }
+}
+
+impl DerefMut for TraceArena {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.arena
+    }
+}
 
 #[derive(Default, Debug, Eq, PartialEq)]
 pub struct DecodedCallData {
@@ -68,13 +121,13 @@ const RETURN: &str = "← ";
 ///
 /// The traces will be decoded using the given decoder, if possible.
 pub async fn render_trace_arena(
-    arena: &CallTraceArena,
+    arena: &TraceArena,
     decoder: &CallTraceDecoder,
 ) -> Result<String, std::fmt::Error> {
     decoder.prefetch_signatures(arena.nodes()).await;
 
     fn inner<'a>(
-        arena: &'a [CallTraceNode],
+        arena: &'a TraceArena,
         decoder: &'a CallTraceDecoder,
         s: &'a mut String,
         idx: usize,
@@ -82,19 +135,42 @@ pub async fn render_trace_arena(
         child: &'a str,
     ) -> BoxFuture<'a, Result<(), std::fmt::Error>> {
         async move {
-            let node = &arena[idx];
+            let node = &arena.nodes()[idx];
 
             // Display trace header
             let (trace, return_data) = render_trace(&node.trace, decoder).await?;
-            writeln!(s, "{left}{}", trace)?;
+            if arena.is_mocked(idx) {
+                writeln!(s, "{left}{trace}{}", Paint::yellow(" [mocked]"))?;
+            } else {
+                writeln!(s, "{left}{trace}")?;
+            }
 
-            // Display logs and subcalls
+            // Display logs, subcalls and internal function calls
+            let items = match decoder.internal_functions_at(&node.trace.address) {
+                Some(functions) if !node.trace.kind.is_any_create() => {
+                    let external = node
+                        .trace
+                        .data
+                        .get(..4)
+                        .and_then(|selector| decoder.functions.get(&Selector::from_slice(selector)))
+                        .and_then(|functions| functions.first())
+                        .map(|function| function.name.as_str());
+                    functions.trace_items(node, external)
+                }
+                _ => TraceItem::from_ordering(node),
+            };
             let left_prefix = format!("{child}{BRANCH}");
-            let right_prefix = format!("{child}{PIPE}");
-            for child in &node.ordering {
-                match child {
-                    LogCallOrder::Log(index) => {
-                        let log = render_trace_log(&node.logs[*index], decoder).await?;
+            let mut undecoded_logs = 0;
+            // the prefixes of the lines below the open internal calls
+            let mut frames = vec![child.to_string()];
+            for item in items {
+                let child = frames.last().unwrap();
+                let left_prefix = format!("{child}{BRANCH}");
+                let right_prefix = format!("{child}{PIPE}");
+                match item {
+                    TraceItem::Log(index) => {
+                        let (log, decoded) = render_trace_log(&node.logs[index], decoder).await?;
+                        undecoded_logs += usize::from(!decoded);
 
                         // Prepend our tree structure symbols to each line of the displayed log
                         log.lines().enumerate().try_for_each(|(i, line)| {
@@ -106,20 +182,31 @@ pub async fn render_trace_arena(
                             )
                         })?;
                     }
-                    LogCallOrder::Call(index) => {
-                        inner(
-                            arena,
-                            decoder,
-                            s,
-                            node.children[*index],
-                            &left_prefix,
-                            &right_prefix,
-                        )
-                        .await?;
+                    TraceItem::Call(index) => {
+                        inner(arena, decoder, s, node.children[index], &left_prefix, &right_prefix)
+                            .await?;
+                    }
+                    TraceItem::Enter(call) => {
+                        writeln!(s, "{left_prefix}{}", Paint::default(call).dimmed())?;
+                        frames.push(right_prefix);
+                    }
+                    TraceItem::Exit(ret) => {
+                        if frames.len() > 1 {
+                            let child = frames.pop().unwrap();
+                            writeln!(s, "{child}{EDGE}{}", Paint::default(ret).dimmed())?;
+                        }
                     }
                 }
             }
 
+            // Display the state changes made by the call itself
+            if decoder.verbosity >= 5 {
+                let changes = CallStateChanges::new(arena.nodes(), idx, decoder.gas_price);
+                for line in render_state_changes(node, &changes, undecoded_logs, decoder) {
+                    writeln!(s, "{left_prefix}{line}")?;
+                }
+            }
+
             // Display trace return data
             let color = trace_color(&node.trace);
             write!(s, "{child}{EDGE}{}", color.paint(RETURN))?;
@@ -146,7 +233,7 @@ pub async fn render_trace_arena(
     }
 
     let mut s = String::new();
-    inner(arena.nodes(), decoder, &mut s, 0, "  ", "  ").await?;
+    inner(arena, decoder, &mut s, 0, "  ", "  ").await?;
     Ok(s)
 }
 
@@ -214,13 +301,14 @@ pub async fn render_trace(
     Ok((s, decoded.return_data))
 }
 
-/// Render a trace log.
+/// Render a trace log, and whether it could be decoded.
 async fn render_trace_log(
     log: &LogData,
     decoder: &CallTraceDecoder,
-) -> Result<String, std::fmt::Error> {
+) -> Result<(String, bool), std::fmt::Error> {
     let mut s = String::new();
     let decoded = decoder.decode_event(log).await;
+    let is_decoded = matches!(decoded, DecodedCallLog::Decoded(..));
 
     match decoded {
         DecodedCallLog::Raw(log) => {
@@ -246,7 +334,66 @@ async fn render_trace_log(
         }
     }
 
-    Ok(s)
+    Ok((s, is_decoded))
+}
+
+/// Render the storage writes, balance changes and undecoded logs of a call, one per line.
+fn render_state_changes(
+    node: &CallTraceNode,
+    changes: &CallStateChanges,
+    undecoded_logs: usize,
+    decoder: &CallTraceDecoder,
+) -> Vec<String> {
+    let label = |address: &Address| {
+        decoder.labels.get(address).cloned().unwrap_or_else(|| address.to_checksum(None))
+    };
+    let rolled_back = if changes.rolled_back {
+        format!(" {}", Paint::red("(rolled back)"))
+    } else {
+        String::new()
+    };
+
+    let mut lines = Vec::new();
+    for write in &changes.storage {
+        // delegate calls write to the storage of the caller
+        let account = if write.address == node.trace.address {
+            String::new()
+        } else {
+            format!(" @ {}", label(&write.address))
+        };
+        let decoded = decoder.decode_storage_write(&node.trace.address, write);
+        if decoded.is_empty() {
+            lines.push(format!(
+                "{} slot {:#x}{account}: {:#x} → {:#x}{rolled_back}",
+                Paint::magenta("storage:"),
+                write.slot,
+                write.old,
+                write.new
+            ));
+        }
+        for var in decoded {
+            lines.push(format!(
+                "{} {}{account}: {} → {}{rolled_back}",
+                Paint::magenta("storage:"),
+                var.label,
+                var.old,
+                var.new
+            ));
+        }
+    }
+    for change in &changes.balances {
+        let sign = if change.delta.is_negative() { "" } else { "+" };
+        lines.push(format!(
+            "{} {}: {sign}{}{rolled_back}",
+            Paint::magenta("balance:"),
+            label(&change.address),
+            change.delta
+        ));
+    }
+    if undecoded_logs > 0 {
+        lines.push(format!("{} {undecoded_logs}", Paint::magenta("undecoded logs:")));
+    }
+    lines
 }
 
 /// Specifies the kind of trace.
@@ -0,0 +1,401 @@
+//! Solidity stack traces of failed calls, resolved from the steps of the calls with the source
+//! maps of their code.
+
+use crate::{
+    internal::{instructions, is_call},
+    CallTraceArena, CallTraceDecoder, CallTraceNode,
+};
+use alloy_primitives::Selector;
+use foundry_common::{compile::ContractSources, get_contract_name, SELECTOR_LEN};
+use foundry_compilers::{
+    artifacts::{CompactBytecode, ConfigurableContractArtifact},
+    sourcemap::Jump,
+    ArtifactId,
+};
+use revm::interpreter::opcode;
+use std::{collections::HashMap, fmt, path::PathBuf};
+
+/// The source locations of the code of all the contracts, used to resolve stack traces.
+#[derive(Clone, Debug, Default)]
+pub struct StackTraces {
+    /// The code of the contracts, by artifact identifier.
+    contracts: HashMap<String, ContractCode>,
+    /// The path, relative to the project root, and the source code of the source files, by
+    /// source index.
+    files: HashMap<u32, (PathBuf, String)>,
+}
+
+impl StackTraces {
+    /// Collects the source locations of the creation and runtime code of the artifacts, by artifact
+    /// identifier.
+    ///
+    /// `sources` holds the source code of the files referred to by the source maps.
+    pub fn new<'a>(
+        artifacts: impl IntoIterator<Item = (ArtifactId, &'a ConfigurableContractArtifact)>,
+        sources: &ContractSources,
+    ) -> Self {
+        let files = sources
+            .sources_by_id
+            .iter()
+            .filter_map(|(id, (source, _))| {
+                Some((*id, (sources.paths_by_id.get(id)?.clone(), source.clone())))
+            })
+            .collect();
+        let contracts = artifacts
+            .into_iter()
+            .map(|(id, artifact)| {
+                let creation =
+                    artifact.bytecode.as_ref().and_then(CodeLocations::new).unwrap_or_default();
+                let runtime = artifact
+                    .deployed_bytecode
+                    .as_ref()
+                    .and_then(|deployed| deployed.bytecode.as_ref())
+                    .and_then(CodeLocations::new)
+                    .unwrap_or_default();
+                (id.identifier(), ContractCode { creation, runtime })
+            })
+            .collect();
+        Self { contracts, files }
+    }
+
+    /// Returns the stack trace of the revert of the root call of the arena, innermost frame
+    /// first.
+    ///
+    /// The revert is followed down the failed subcalls it was bubbled up from. Returns an empty
+    /// stack trace if the root call succeeded.
+    pub fn frames(&self, arena: &CallTraceArena, decoder: &CallTraceDecoder) -> Vec<StackFrame> {
+        let nodes = arena.nodes();
+        let Some(mut node) = nodes.first() else { return Vec::new() };
+        if node.trace.success {
+            return Vec::new();
+        }
+
+        // the failed calls and the index of the failed subcall they reverted with
+        let mut calls = Vec::new();
+        loop {
+            let failed = node.children.iter().enumerate().rev().find(|&(_, &child)| {
+                let child = &nodes[child].trace;
+                !child.success && child.output == node.trace.output
+            });
+            match failed {
+                Some((index, &child)) => {
+                    calls.push((node, Some(index)));
+                    node = &nodes[child];
+                }
+                None => {
+                    calls.push((node, None));
+                    break;
+                }
+            }
+        }
+
+        let mut frames = Vec::new();
+        for (node, subcall) in calls.into_iter().rev() {
+            frames.extend(self.call_frames(node, subcall, decoder));
+        }
+        frames
+    }
+
+    /// Returns the frames of a failed call, innermost first.
+    ///
+    /// The call failed at the subcall with the given index, or at its last step if `None`.
+    fn call_frames(
+        &self,
+        node: &CallTraceNode,
+        subcall: Option<usize>,
+        decoder: &CallTraceDecoder,
+    ) -> Vec<StackFrame> {
+        let trace = &node.trace;
+        let id = decoder.contracts.get(&trace.address);
+        let name = id.map(|id| get_contract_name(id));
+        let code = id.and_then(|id| self.contracts.get(id)).map(|contract| {
+            if trace.kind.is_any_create() {
+                &contract.creation
+            } else {
+                &contract.runtime
+            }
+        });
+        let steps = &trace.steps;
+        let site = match subcall {
+            Some(index) => steps
+                .iter()
+                .enumerate()
+                .filter(|(_, step)| is_call(step.op.get()))
+                .nth(index)
+                .map(|(i, _)| i),
+            None => steps.len().checked_sub(1),
+        };
+
+        let mut frames: Vec<StackFrame> = Vec::new();
+        if let (Some(name), Some(code), Some(site)) = (name, code, site) {
+            // the program counters of the jumps into the internal functions still open at the site
+            let mut jumps = Vec::new();
+            for step in &steps[..site] {
+                if step.op.get() != opcode::JUMP {
+                    continue;
+                }
+                match code.jumps.get(&step.pc) {
+                    Some(true) => jumps.push(step.pc),
+                    Some(false) => {
+                        jumps.pop();
+                    }
+                    None => {}
+                }
+            }
+
+            let pcs = std::iter::once(steps[site].pc).chain(jumps.into_iter().rev());
+            for (i, pc) in pcs.enumerate() {
+                let Some(frame) = self.resolve(code, pc, name) else { continue };
+                // jumps from outside of a function, e.g. from the dispatcher, aren't frames
+                if i > 0 && frame.function.is_none() {
+                    continue;
+                }
+                if frames.last() != Some(&frame) {
+                    frames.push(frame);
+                }
+            }
+        }
+
+        if frames.is_empty() {
+            let contract = decoder
+                .labels
+                .get(&trace.address)
+                .cloned()
+                .unwrap_or_else(|| trace.address.to_string());
+            let function = if trace.kind.is_any_create() {
+                Some("constructor".to_string())
+            } else {
+                trace.data.get(..SELECTOR_LEN).map(|selector| {
+                    decoder
+                        .functions
+                        .get(&Selector::from_slice(selector))
+                        .and_then(|functions| functions.first())
+                        .map_or_else(|| hex::encode_prefixed(selector), |f| f.name.clone())
+                })
+            };
+            frames.push(StackFrame { contract, function, location: None });
+        }
+        frames
+    }
+
+    /// Resolves the frame of the instruction at the program counter, if its source is known.
+    ///
+    /// Code outside of a contract definition, e.g. in a free function, is attributed to
+    /// `contract`.
+    fn resolve(&self, code: &CodeLocations, pc: usize, contract: &str) -> Option<StackFrame> {
+        let &(index, offset) = code.locations.get(&pc)?;
+        let (path, source) = self.files.get(&index)?;
+        let offset = offset.min(source.len());
+        let (enclosing_contract, function) = enclosing_definitions(source, offset);
+        let line = source.as_bytes()[..offset].iter().filter(|&&b| b == b'\n').count() + 1;
+        Some(StackFrame {
+            contract: enclosing_contract.unwrap_or(contract).to_string(),
+            function: function.map(str::to_string),
+            location: Some((path.clone(), line)),
+        })
+    }
+}
+
+/// The creation and runtime code of a contract.
+#[derive(Clone, Debug, Default)]
+struct ContractCode {
+    creation: CodeLocations,
+    runtime: CodeLocations,
+}
+
+/// The source locations of the instructions of a bytecode.
+#[derive(Clone, Debug, Default)]
+struct CodeLocations {
+    /// The source index and offset of the instructions, by program counter.
+    locations: HashMap<usize, (u32, usize)>,
+    /// Whether the `JUMP` at a program counter jumps into (`true`) or out of (`false`) a function.
+    jumps: HashMap<usize, bool>,
+}
+
+impl CodeLocations {
+    fn new(bytecode: &CompactBytecode) -> Option<Self> {
+        let code = bytecode.object.as_bytes()?;
+        let source_map = bytecode.source_map()?.ok()?;
+        let mut locations = Self::default();
+        for (pc, op, element) in instructions(code, &source_map) {
+            if let Some(index) = element.index {
+                locations.locations.insert(pc, (index, element.offset));
+            }
+            if op == opcode::JUMP && matches!(element.jump, Jump::In | Jump::Out) {
+                locations.jumps.insert(pc, matches!(element.jump, Jump::In));
+            }
+        }
+        Some(locations)
+    }
+}
+
+/// A frame of a stack trace, e.g. `Vault.withdraw (src/Vault.sol:42)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StackFrame {
+    /// The name of the contract, or its label or address if its source isn't known.
+    pub contract: String,
+    /// The name of the function or modifier, or the selector of the call if its source isn't
+    /// known.
+    pub function: Option<String>,
+    /// The source file, relative to the project root, and the line of the frame.
+    pub location: Option<(PathBuf, usize)>,
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.contract)?;
+        if let Some(function) = &self.function {
+            write!(f, ".{function}")?;
+        }
+        if let Some((path, line)) = &self.location {
+            write!(f, " ({}:{line})", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the innermost contract and function definitions enclosing the offset in the source.
+///
+/// Modifiers, constructors, `fallback` and `receive` count as functions.
+fn enclosing_definitions(source: &str, offset: usize) -> (Option<&str>, Option<&str>) {
+    enum Definition<'a> {
+        Contract(&'a str),
+        Function(&'a str),
+    }
+
+    let bytes = source.as_bytes();
+    let end = offset.min(bytes.len());
+    // the definitions of the open blocks, and the definition whose block comes next
+    let mut blocks: Vec<Option<Definition<'_>>> = Vec::new();
+    let mut pending = None;
+    let mut i = 0;
+    while i < end {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = source[i..].find('\n').map_or(bytes.len(), |n| i + n);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = source[i + 2..].find("*/").map_or(bytes.len(), |n| i + n + 4);
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'{' => blocks.push(pending.take()),
+            b'}' => {
+                blocks.pop();
+            }
+            b';' => pending = None,
+            b if b.is_ascii_alphabetic() || b == b'_' || b == b'$' => {
+                let word = identifier(&source[i..]);
+                let rest = source[i + word.len()..].trim_start();
+                match word {
+                    "contract" | "library" | "interface" => {
+                        pending = Some(Definition::Contract(identifier(rest)));
+                    }
+                    "function" | "modifier" => {
+                        let name = identifier(rest);
+                        // function types have no name
+                        if !name.is_empty() {
+                            pending = Some(Definition::Function(name));
+                        }
+                    }
+                    "constructor" | "fallback" | "receive" if rest.starts_with('(') => {
+                        pending = Some(Definition::Function(word));
+                    }
+                    _ => {}
+                }
+                i += word.len();
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let mut definitions = blocks.iter().rev().flatten();
+    let function = definitions.clone().find_map(|definition| match definition {
+        Definition::Function(name) => Some(*name),
+        Definition::Contract(_) => None,
+    });
+    let contract = definitions.find_map(|definition| match definition {
+        Definition::Contract(name) => Some(*name),
+        Definition::Function(_) => None,
+    });
+    (contract, function)
+}
+
+/// Returns the identifier at the start of the source.
+fn identifier(source: &str) -> &str {
+    let len = source
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(source.len());
+    &source[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_enclosing_definitions() {
+        let source = r#"
+interface IVault {
+    function withdraw(uint256 amount) external;
+}
+
+// contract Commented { function commented() {
+abstract contract Base {
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not the owner }");
+        _;
+    }
+}
+
+contract Vault is Base, IVault {
+    struct Position { uint256 amount; }
+
+    event Received(address from);
+
+    function withdraw(uint256 amount) external onlyOwner {
+        /* { */ _withdraw(amount, this.withdraw);
+    }
+
+    function _withdraw(uint256 amount, function(uint256) external callback) internal {
+        if (amount > 0) { revert(); }
+    }
+
+    receive() external payable {
+        emit Received(msg.sender);
+    }
+}
+"#;
+        let at = |needle: &str| enclosing_definitions(source, source.find(needle).unwrap());
+        assert_eq!(at("require"), (Some("Base"), Some("onlyOwner")));
+        assert_eq!(at("_withdraw(amount"), (Some("Vault"), Some("withdraw")));
+        assert_eq!(at("revert()"), (Some("Vault"), Some("_withdraw")));
+        assert_eq!(at("struct"), (Some("Vault"), None));
+        assert_eq!(at("emit"), (Some("Vault"), Some("receive")));
+        assert_eq!(at("interface"), (None, None));
+    }
+
+    #[test]
+    fn formats_stack_frames() {
+        let frame = StackFrame {
+            contract: "Vault".to_string(),
+            function: Some("_withdraw".to_string()),
+            location: Some(("src/Vault.sol".into(), 21)),
+        };
+        assert_eq!(frame.to_string(), "Vault._withdraw (src/Vault.sol:21)");
+
+        let frame = StackFrame {
+            contract: "0x0000000000000000000000000000000000000001".to_string(),
+            function: Some("0x2e1a7d4d".to_string()),
+            location: None,
+        };
+        assert_eq!(frame.to_string(), "0x0000000000000000000000000000000000000001.0x2e1a7d4d");
+    }
+}
@@ -0,0 +1,327 @@
+//! Storage and balance changes made by the calls of a trace.
+
+use crate::{CallKind, CallTraceArena, CallTraceNode};
+use alloy_primitives::{Address, B256, I256, U256};
+use foundry_compilers::artifacts::StorageLayout;
+use revm::interpreter::opcode;
+use serde::{Deserialize, Serialize};
+
+/// The net state changes made by a single call, excluding the ones made by its subcalls.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallStateChanges {
+    /// The index of the call in the trace arena.
+    pub call: usize,
+    /// The net storage writes, in the order the slots were first written to.
+    pub storage: Vec<StorageWrite>,
+    /// The balance changes caused by the value sent with the call, and by the gas paid for the
+    /// transaction if the call is the top-level one.
+    pub balances: Vec<BalanceChange>,
+    /// Whether the changes were rolled back because the call, or one of its callers, reverted.
+    pub rolled_back: bool,
+}
+
+/// The net change of a storage slot.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageWrite {
+    /// The account whose storage was written to.
+    pub address: Address,
+    pub slot: U256,
+    pub old: U256,
+    pub new: U256,
+}
+
+/// The change of an account balance.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceChange {
+    pub address: Address,
+    pub delta: I256,
+}
+
+/// A storage variable changed by a [StorageWrite], decoded with a storage layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedStorageWrite {
+    pub label: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl CallStateChanges {
+    /// Collects the state changes made by the call at `idx`, the top-level call paying for its gas
+    /// at `gas_price`.
+    ///
+    /// Storage writes are only known if the tracer recorded the storage changes of its steps.
+    pub fn new(nodes: &[CallTraceNode], idx: usize, gas_price: U256) -> Self {
+        let node = &nodes[idx];
+        let trace = &node.trace;
+
+        // DELEGATECALL and CALLCODE run the code of `address` on the storage of `caller`
+        let storage_address = match trace.kind {
+            CallKind::DelegateCall | CallKind::CallCode => trace.caller,
+            _ => trace.address,
+        };
+        let storage = net_storage_writes(
+            storage_address,
+            trace
+                .steps
+                .iter()
+                .filter(|step| step.op.get() == opcode::SSTORE)
+                .filter_map(|step| step.storage_change.as_ref())
+                .map(|change| (change.key, change.had_value, change.value)),
+        );
+
+        let mut balances = Vec::new();
+        // CALLCODE and DELEGATECALL don't move the value to another account
+        let transfers_value =
+            matches!(trace.kind, CallKind::Call | CallKind::Create | CallKind::Create2);
+        if transfers_value && trace.caller != trace.address {
+            if let Ok(value) = I256::try_from(trace.value) {
+                if !value.is_zero() {
+                    balances.push(BalanceChange { address: trace.caller, delta: -value });
+                    balances.push(BalanceChange { address: trace.address, delta: value });
+                }
+            }
+        }
+        // the sender of the transaction pays for the gas used by the top-level call, whether it
+        // reverted or not
+        let gas_cost = U256::from(trace.gas_used).saturating_mul(gas_price);
+        if node.parent.is_none() && !gas_cost.is_zero() {
+            let gas_cost = I256::try_from(gas_cost).unwrap_or(I256::MAX);
+            match balances.iter_mut().find(|change| change.address == trace.caller) {
+                Some(change) => change.delta = change.delta.saturating_sub(gas_cost),
+                None => {
+                    balances.insert(0, BalanceChange { address: trace.caller, delta: -gas_cost })
+                }
+            }
+        }
+
+        let mut rolled_back = !trace.success;
+        let mut parent = node.parent;
+        while let Some(idx) = parent {
+            rolled_back |= !nodes[idx].trace.success;
+            parent = nodes[idx].parent;
+        }
+
+        Self { call: idx, storage, balances, rolled_back }
+    }
+
+    /// Returns `true` if the call didn't change any state.
+    pub fn is_empty(&self) -> bool {
+        self.storage.is_empty() && self.balances.is_empty()
+    }
+}
+
+/// Aggregates the `(slot, previous value, new value)` storage writes of a call to the net writes
+/// per slot, dropping the ones that restored the original value.
+fn net_storage_writes(
+    address: Address,
+    writes: impl IntoIterator<Item = (U256, Option<U256>, U256)>,
+) -> Vec<StorageWrite> {
+    let mut storage = Vec::<StorageWrite>::new();
+    for (slot, had_value, value) in writes {
+        match storage.iter_mut().find(|write| write.slot == slot) {
+            Some(write) => write.new = value,
+            None => storage.push(StorageWrite {
+                address,
+                slot,
+                old: had_value.unwrap_or_default(),
+                new: value,
+            }),
+        }
+    }
+    storage.retain(|write| write.old != write.new);
+    storage
+}
+
+/// Collects the state changes of all the calls of the arena that changed any state, the top-level
+/// call paying for its gas at `gas_price`.
+pub fn state_changes(arena: &CallTraceArena, gas_price: U256) -> Vec<CallStateChanges> {
+    let nodes = arena.nodes();
+    (0..nodes.len())
+        .map(|idx| CallStateChanges::new(nodes, idx, gas_price))
+        .filter(|changes| !changes.is_empty())
+        .collect()
+}
+
+/// Decodes the variables of the storage layout that were changed by the write.
+///
+/// Only variables at fixed slots can be decoded, mapping values and dynamic array items can't.
+pub fn decode_storage_write(
+    layout: &StorageLayout,
+    write: &StorageWrite,
+) -> Vec<DecodedStorageWrite> {
+    layout
+        .storage
+        .iter()
+        .filter(|storage| storage.slot.parse::<U256>().ok() == Some(write.slot))
+        .filter_map(|storage| {
+            let ty = layout.types.get(&storage.storage_type);
+            let size = ty
+                .and_then(|ty| ty.number_of_bytes.parse::<usize>().ok())
+                .unwrap_or(32)
+                .clamp(1, 32);
+            let offset =
+                usize::try_from(storage.offset).ok().filter(|offset| offset + size <= 32)?;
+            let old = extract_value(write.old, offset, size);
+            let new = extract_value(write.new, offset, size);
+            let label = ty.map(|ty| ty.label.as_str()).unwrap_or_default();
+            (old != new).then(|| DecodedStorageWrite {
+                label: storage.label.clone(),
+                old: format_value(old, size, label),
+                new: format_value(new, size, label),
+            })
+        })
+        .collect()
+}
+
+/// Extracts the `size` bytes at `offset`, counted from the right, of a storage word.
+fn extract_value(word: U256, offset: usize, size: usize) -> U256 {
+    let value = word >> (offset * 8);
+    if size == 32 {
+        value
+    } else {
+        value & ((U256::from(1) << (size * 8)) - U256::from(1))
+    }
+}
+
+/// Formats a value of a storage variable by its type label, e.g. `uint256` or `contract Counter`.
+pub(crate) fn format_value(value: U256, size: usize, ty: &str) -> String {
+    if ty == "bool" {
+        (!value.is_zero()).to_string()
+    } else if ty.starts_with("address") || ty.starts_with("contract ") {
+        Address::from_word(B256::from(value)).to_checksum(None)
+    } else if ty.starts_with("uint") || ty.starts_with("enum ") {
+        value.to_string()
+    } else if ty.starts_with("int") {
+        // sign extend the packed value
+        let bits = size * 8;
+        let value =
+            if bits < 256 && value.bit(bits - 1) { value | (U256::MAX << bits) } else { value };
+        I256::from_raw(value).to_string()
+    } else {
+        hex::encode_prefixed(&value.to_be_bytes::<32>()[32 - size..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CallTrace;
+
+    fn node(parent: Option<usize>, success: bool) -> CallTraceNode {
+        CallTraceNode {
+            parent,
+            trace: CallTrace { success, ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn collects_net_storage_writes() {
+        let [zero, one, two, five, six] = [0, 1, 2, 5, 6].map(U256::from);
+        let writes = net_storage_writes(
+            Address::ZERO,
+            [
+                (zero, Some(zero), one),
+                (one, Some(five), six),
+                (zero, Some(one), two),
+                (one, Some(six), five),
+            ],
+        );
+        assert_eq!(
+            writes,
+            vec![StorageWrite { address: Address::ZERO, slot: zero, old: zero, new: two }]
+        );
+    }
+
+    #[test]
+    fn marks_changes_of_reverted_calls() {
+        let nodes = [node(None, false), node(Some(0), true), node(Some(1), true)];
+        assert!(CallStateChanges::new(&nodes, 2, U256::ZERO).rolled_back);
+
+        let nodes = [node(None, true), node(Some(0), true)];
+        assert!(!CallStateChanges::new(&nodes, 1, U256::ZERO).rolled_back);
+    }
+
+    #[test]
+    fn counts_gas_costs_in_balance_changes() {
+        let (sender, target) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let call = |parent: Option<usize>| CallTraceNode {
+            parent,
+            trace: CallTrace {
+                kind: CallKind::Call,
+                success: true,
+                caller: sender,
+                address: target,
+                value: U256::from(100),
+                gas_used: 21_000,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let nodes = [call(None), call(Some(0))];
+
+        let changes = CallStateChanges::new(&nodes, 0, U256::from(2));
+        assert_eq!(
+            changes.balances,
+            vec![
+                BalanceChange {
+                    address: sender,
+                    delta: -I256::try_from(U256::from(42_100)).unwrap()
+                },
+                BalanceChange { address: target, delta: I256::try_from(U256::from(100)).unwrap() },
+            ]
+        );
+        // only the top-level call pays for gas
+        let changes = CallStateChanges::new(&nodes, 1, U256::from(2));
+        assert_eq!(changes.balances[0].delta, -I256::try_from(U256::from(100)).unwrap());
+        // the gas is paid even if no value is sent
+        let mut nodes = nodes;
+        nodes[0].trace.value = U256::ZERO;
+        let changes = CallStateChanges::new(&nodes, 0, U256::from(2));
+        assert_eq!(
+            changes.balances,
+            vec![BalanceChange {
+                address: sender,
+                delta: -I256::try_from(U256::from(42_000)).unwrap()
+            }]
+        );
+    }
+
+    #[test]
+    fn decodes_packed_storage_writes() {
+        let layout: StorageLayout = serde_json::from_str(
+            r#"{
+                "storage": [
+                    { "astId": 1, "contract": "C.sol:C", "label": "owner", "offset": 0, "slot": "0", "type": "t_address" },
+                    { "astId": 2, "contract": "C.sol:C", "label": "delta", "offset": 20, "slot": "0", "type": "t_int8" },
+                    { "astId": 3, "contract": "C.sol:C", "label": "count", "offset": 0, "slot": "1", "type": "t_uint256" }
+                ],
+                "types": {
+                    "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                    "t_int8": { "encoding": "inplace", "label": "int8", "numberOfBytes": "1" },
+                    "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        // only `delta` changes, from 0 to -1
+        let write = StorageWrite {
+            address: Address::ZERO,
+            slot: U256::ZERO,
+            old: U256::from(1),
+            new: U256::from(1) | (U256::from(0xff) << 160),
+        };
+        assert_eq!(
+            decode_storage_write(&layout, &write),
+            vec![DecodedStorageWrite {
+                label: "delta".to_string(),
+                old: "0".to_string(),
+                new: "-1".to_string(),
+            }]
+        );
+
+        let write = StorageWrite { slot: U256::from(2), ..write };
+        assert!(decode_storage_write(&layout, &write).is_empty());
+    }
+}
@@ -1420,6 +1420,30 @@ impl<'a, W: Write> Formatter<'a, W> {
         Ok(())
     }
 
+    /// Write a function with a single statement body on a single line.
+    /// Fails if the function has any other body.
+    fn write_single_line_function(
+        &mut self,
+        func: &mut FunctionDefinition,
+        body_loc: Option<Loc>,
+    ) -> Result<()> {
+        if !matches!(
+            &func.body,
+            Some(Statement::Block { unchecked: false, statements, .. }) if statements.len() == 1
+        ) {
+            bail!(FormatterError::fmt())
+        }
+
+        self.write_function_header(func, body_loc, false)?;
+        if let Some(Statement::Block { loc, statements, .. }) = &mut func.body {
+            self.write_whitespace_separator(false)?;
+            if !self.visit_block(*loc, statements, true, false)? {
+                bail!(FormatterError::fmt())
+            }
+        }
+        Ok(())
+    }
+
     /// Write the function header
     fn write_function_header(
         &mut self,
@@ -1694,11 +1718,14 @@ impl<'a, W: Write> Formatter<'a, W> {
             }
 
             import_directives.sort_by_cached_key(|item| match item {
-                SourceUnitPart::ImportDirective(import) => match import {
-                    Import::Plain(path, _) => path.to_string(),
-                    Import::GlobalSymbol(path, _, _) => path.to_string(),
-                    Import::Rename(path, _, _) => path.to_string(),
-                },
+                SourceUnitPart::ImportDirective(import) => {
+                    let path = match import {
+                        Import::Plain(path, _) => path.to_string(),
+                        Import::GlobalSymbol(path, _, _) => path.to_string(),
+                        Import::Rename(path, _, _) => path.to_string(),
+                    };
+                    (ImportKind::of(&path), path)
+                }
                 _ => {
                     unreachable!("import group contains non-import statement")
                 }
@@ -1707,6 +1734,34 @@ impl<'a, W: Write> Formatter<'a, W> {
     }
 }
 
+/// The kind of an import, which determines its order within a sorted import group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ImportKind {
+    /// The forge standard library
+    Std,
+    /// Dependencies, e.g. `solmate/` or `@openzeppelin/` imports
+    Dependency,
+    /// Project files, i.e. relative imports or imports of the project directories
+    Project,
+}
+
+impl ImportKind {
+    /// Returns the kind of the import path.
+    fn of(path: &str) -> Self {
+        let path = path.trim_matches(|c| c == '"' || c == '\'');
+        if path.starts_with("forge-std/") || path.starts_with("ds-test/") {
+            ImportKind::Std
+        } else if ["./", "../", "src/", "test/", "script/"]
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+        {
+            ImportKind::Project
+        } else {
+            ImportKind::Dependency
+        }
+    }
+}
+
 // Traverse the Solidity Parse Tree and write to the code formatter
 impl<'a, W: Write> Visitor for Formatter<'a, W> {
     type Error = FormatterError;
@@ -3006,6 +3061,14 @@ impl<'a, W: Write> Visitor for Formatter<'a, W> {
             fmt.write_prefix_comments_before(func.loc.start())?;
 
             let body_loc = func.body.as_ref().map(CodeLocation::loc);
+
+            // Attempt to write the whole function on a single line
+            if fmt.config.allow_single_line_functions &&
+                fmt.try_on_single_line(|fmt| fmt.write_single_line_function(func, body_loc))?
+            {
+                return Ok(())
+            }
+
             let mut attrs_multiline = false;
             let fits_on_single = fmt.try_on_single_line(|fmt| {
                 fmt.write_function_header(func, body_loc, false)?;
@@ -0,0 +1,17 @@
+// config: allow_single_line_functions = true
+contract SingleLineFunctions {
+    uint256 internal value;
+
+    function get() external view returns (uint256) { return value; }
+
+    function set(uint256 newValue) external { value = newValue; }
+
+    function reset() external {
+        value = 0;
+        emit Reset();
+    }
+
+    function empty() external {}
+
+    modifier onlyOwner() { _; }
+}
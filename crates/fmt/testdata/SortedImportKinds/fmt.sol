@@ -0,0 +1,7 @@
+// config: sort_imports = true
+import {Test, console2} from "forge-std/Test.sol";
+import {Vm} from "forge-std/Vm.sol";
+import {Ownable} from "@openzeppelin/contracts/access/Ownable.sol";
+import {ERC20} from "solmate/tokens/ERC20.sol";
+import {Counter} from "../src/Counter.sol";
+import {Utils} from "./Utils.sol";
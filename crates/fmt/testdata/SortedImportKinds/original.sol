@@ -0,0 +1,6 @@
+import {Counter} from "../src/Counter.sol";
+import {console2, Test} from "forge-std/Test.sol";
+import {ERC20} from "solmate/tokens/ERC20.sol";
+import {Ownable} from "@openzeppelin/contracts/access/Ownable.sol";
+import {Vm} from "forge-std/Vm.sol";
+import {Utils} from "./Utils.sol";
@@ -1,5 +1,10 @@
 use forge_fmt::{format_to, parse, solang_ext::AstEq, FormatterConfig};
+use foundry_config::fmt::{
+    HexUnderscore, IntTypes, MultilineFuncHeaderStyle, NumberUnderscore, QuoteStyle,
+    SingleLineBlockStyle,
+};
 use itertools::Itertools;
+use proptest::prelude::*;
 use std::{fs, path::PathBuf};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
@@ -232,6 +237,112 @@ test_directories! {
     BlockComments,
     BlockCommentsFunction,
     EnumVariants,
+    SingleLineFunctions,
 }
 
 test_dir!(SortedImports, TestConfig::skip_compare_ast_eq());
+test_dir!(SortedImportKinds, TestConfig::skip_compare_ast_eq());
+
+/// Test directories whose sources are formatted with random configs to check idempotency.
+const IDEMPOTENCY_TEST_DIRS: &[&str] = &[
+    "FunctionDefinition",
+    "ModifierDefinition",
+    "StatementBlock",
+    "IfStatement",
+    "ImportDirective",
+    "SortedImports",
+    "SortedImportKinds",
+    "SingleLineFunctions",
+];
+
+fn format(source: &str, config: FormatterConfig) -> Option<String> {
+    let parsed = parse(source).ok()?;
+    let mut formatted = String::new();
+    format_to(&mut formatted, parsed, config).ok()?;
+    Some(formatted)
+}
+
+fn formatter_config() -> impl Strategy<Value = FormatterConfig> {
+    let styles = (
+        prop_oneof![Just(IntTypes::Long), Just(IntTypes::Short), Just(IntTypes::Preserve)],
+        prop_oneof![
+            Just(MultilineFuncHeaderStyle::ParamsFirst),
+            Just(MultilineFuncHeaderStyle::AttributesFirst),
+            Just(MultilineFuncHeaderStyle::All),
+        ],
+        prop_oneof![Just(QuoteStyle::Double), Just(QuoteStyle::Single), Just(QuoteStyle::Preserve)],
+        prop_oneof![
+            Just(NumberUnderscore::Preserve),
+            Just(NumberUnderscore::Remove),
+            Just(NumberUnderscore::Thousands),
+        ],
+        prop_oneof![
+            Just(HexUnderscore::Preserve),
+            Just(HexUnderscore::Remove),
+            Just(HexUnderscore::Bytes),
+        ],
+        prop_oneof![
+            Just(SingleLineBlockStyle::Single),
+            Just(SingleLineBlockStyle::Multi),
+            Just(SingleLineBlockStyle::Preserve),
+        ],
+    );
+    let flags =
+        (any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>());
+    (40usize..=160, 2usize..=8, styles, flags).prop_map(
+        |(
+            line_length,
+            tab_width,
+            (
+                int_types,
+                multiline_func_header,
+                quote_style,
+                number_underscore,
+                hex_underscore,
+                single_line_statement_blocks,
+            ),
+            (
+                bracket_spacing,
+                override_spacing,
+                wrap_comments,
+                contract_new_lines,
+                sort_imports,
+                allow_single_line_functions,
+            ),
+        )| FormatterConfig {
+            line_length,
+            tab_width,
+            bracket_spacing,
+            int_types,
+            multiline_func_header,
+            quote_style,
+            number_underscore,
+            hex_underscore,
+            single_line_statement_blocks,
+            override_spacing,
+            wrap_comments,
+            contract_new_lines,
+            sort_imports,
+            allow_single_line_functions,
+            ..Default::default()
+        },
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn format_is_idempotent(
+        config in formatter_config(),
+        dir in proptest::sample::select(IDEMPOTENCY_TEST_DIRS),
+    ) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata").join(dir);
+        let source = fs::read_to_string(path.join("original.sol")).unwrap();
+
+        // Sources that can't be formatted with the config are not relevant
+        let Some(formatted) = format(&source, config.clone()) else { return Ok(()) };
+        let reformatted = format(&formatted, config);
+        prop_assert_eq!(Some(formatted), reformatted, "formatting {} is not idempotent", dir);
+    }
+}
@@ -0,0 +1,127 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use forge::{
+    result::SuiteResult,
+    traces::{
+        identifier::LocalTraceIdentifier, render_trace_arena, CallTraceDecoderBuilder, TraceKind,
+    },
+    MultiContractRunnerBuilder, TestFilter, TestOptionsBuilder,
+};
+use foundry_common::{compile::ProjectCompiler, ContractsByArtifact};
+use foundry_config::Config;
+use foundry_evm::opts::{Env, EvmOpts};
+use foundry_test_utils::util::setup_forge_remote;
+use futures::executor::block_on;
+use std::{collections::BTreeMap, path::Path};
+
+/// Runs every test.
+struct MatchAll;
+
+impl TestFilter for MatchAll {
+    fn matches_test(&self, _test_name: &str) -> bool {
+        true
+    }
+
+    fn matches_contract(&self, _contract_name: &str) -> bool {
+        true
+    }
+
+    fn matches_path(&self, _path: &Path) -> bool {
+        true
+    }
+}
+
+/// Runs the tests of a cloned `solmate` project with tracing enabled, returning the known
+/// contracts and the results with their traces.
+fn solmate_results() -> (ContractsByArtifact, BTreeMap<String, SuiteResult>) {
+    let (prj, _) = setup_forge_remote("transmissions11/solmate");
+    let config = Config::load_with_root(prj.root());
+    let project = config.project().unwrap();
+    let output = ProjectCompiler::new().quiet(true).compile(&project).unwrap();
+
+    let evm_opts = EvmOpts {
+        env: Env {
+            gas_limit: u64::MAX,
+            tx_origin: config.sender,
+            block_number: 1,
+            block_timestamp: 1,
+            ..Default::default()
+        },
+        sender: config.sender,
+        initial_balance: config.initial_balance,
+        memory_limit: config.memory_limit,
+        verbosity: 4,
+        ..Default::default()
+    };
+    let test_options = TestOptionsBuilder::default()
+        .fuzz(config.fuzz)
+        .invariant(config.invariant)
+        .build(&output, project.root())
+        .unwrap();
+    let mut runner = MultiContractRunnerBuilder::default()
+        .sender(evm_opts.sender)
+        .evm_spec(config.evm_spec_id())
+        .with_test_options(test_options)
+        .build(project.root(), output, evm_opts.local_evm_env(), evm_opts)
+        .unwrap();
+    let known_contracts = runner.known_contracts.clone();
+    (known_contracts, runner.test_collect(&MatchAll))
+}
+
+/// Decodes the traces printed by `forge test -vvvv` the way it did before sharing its caches: with
+/// a new decoder and local identifier for every suite, identifying every trace of every test.
+fn decode_per_suite(
+    known_contracts: &ContractsByArtifact,
+    results: &BTreeMap<String, SuiteResult>,
+) {
+    for suite in results.values() {
+        let mut local_identifier = LocalTraceIdentifier::new(known_contracts);
+        let mut decoder =
+            CallTraceDecoderBuilder::new().with_local_identifier_abis(&local_identifier).build();
+        for result in suite.test_results.values() {
+            decoder.clear_addresses();
+            for (kind, arena) in &result.traces {
+                decoder.identify(arena, &mut local_identifier);
+                if *kind == TraceKind::Execution {
+                    block_on(render_trace_arena(arena, &decoder)).unwrap();
+                }
+            }
+        }
+    }
+}
+
+/// Decodes the traces printed by `forge test -vvvv` with a single decoder and local identifier
+/// for the whole run, identifying only the printed traces.
+fn decode_shared(known_contracts: &ContractsByArtifact, results: &BTreeMap<String, SuiteResult>) {
+    let mut local_identifier = LocalTraceIdentifier::new(known_contracts);
+    let mut decoder =
+        CallTraceDecoderBuilder::new().with_local_identifier_abis(&local_identifier).build();
+    for suite in results.values() {
+        for result in suite.test_results.values() {
+            decoder.clear_addresses();
+            for (kind, arena) in &result.traces {
+                if *kind == TraceKind::Execution {
+                    decoder.identify(arena, &mut local_identifier);
+                    block_on(render_trace_arena(arena, &decoder)).unwrap();
+                }
+            }
+        }
+    }
+}
+
+/// Compares decoding the traces of every `solmate` test before and after sharing the decoder and
+/// the identification caches across suites.
+fn forge_test_traces_benchmark(c: &mut Criterion) {
+    let (known_contracts, results) = solmate_results();
+
+    let mut group = c.benchmark_group("forge test trace decoding");
+    group.sample_size(10);
+    group.bench_function("per suite", |b| {
+        b.iter(|| decode_per_suite(&known_contracts, &results));
+    });
+    group.bench_function("shared", |b| {
+        b.iter(|| decode_shared(&known_contracts, &results));
+    });
+}
+
+criterion_group!(benches, forge_test_traces_benchmark);
+criterion_main!(benches);
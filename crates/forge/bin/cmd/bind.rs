@@ -1,9 +1,11 @@
+use alloy_json_abi::JsonAbi;
 use clap::{Parser, ValueHint};
-use ethers_contract::{Abigen, ContractFilter, ExcludeContracts, MultiAbigen, SelectContracts};
+use ethers_contract::{Abigen, MultiAbigen};
 use eyre::{Result, WrapErr};
 use foundry_cli::{opts::CoreBuildArgs, utils::LoadConfig};
 use foundry_common::{compile::ProjectCompiler, fs::json_files};
-use foundry_config::impl_figment_convert;
+use foundry_config::{impl_figment_convert, Config};
+use regex::Regex;
 use std::{
     fs,
     path::{Path, PathBuf},
@@ -14,6 +16,21 @@ impl_figment_convert!(BindArgs, build_args);
 const DEFAULT_CRATE_NAME: &str = "foundry-contracts";
 const DEFAULT_CRATE_VERSION: &str = "0.1.0";
 
+/// The `alloy-sol-types` version that `--alloy-sol-macro` crates depend on by default.
+const ALLOY_SOL_TYPES_VERSION: &str = "0.6.3";
+
+/// Contract names that are excluded by default, which are all Test/Script and forge-std contracts.
+const DEFAULT_SKIP: &[&str] = &[
+    ".*Test.*",
+    ".*Script",
+    "console[2]?",
+    "CommonBase",
+    "Components",
+    "[Ss]td(Chains|Math|Error|Json|Utils|Cheats|Style|Invariant|Assertions|Storage(Safe)?)",
+    "[Vv]m.*",
+    "^IMulticall3$",
+];
+
 /// CLI arguments for `forge bind`.
 #[derive(Clone, Debug, Parser)]
 pub struct BindArgs {
@@ -26,11 +43,13 @@ pub struct BindArgs {
     )]
     pub bindings: Option<PathBuf>,
 
-    /// Create bindings only for contracts whose names match the specified filter(s)
+    /// Create bindings only for contracts whose names or source paths match the specified
+    /// filter(s)
     #[arg(long)]
     pub select: Vec<regex::Regex>,
 
-    /// Create bindings only for contracts whose names do not match the specified filter(s)
+    /// Create bindings only for contracts whose names or source paths do not match the specified
+    /// filter(s)
     #[arg(long, conflicts_with = "select")]
     pub skip: Vec<regex::Regex>,
 
@@ -54,6 +73,13 @@ pub struct BindArgs {
     #[arg(long, default_value = DEFAULT_CRATE_VERSION, value_name = "VERSION")]
     crate_version: String,
 
+    /// Additional dependencies of the generated crate, in `Cargo.toml` syntax.
+    ///
+    /// A dependency with the same name as a default one replaces it, e.g.
+    /// `--dependencies 'alloy-sol-types = { version = "=0.6.3", features = ["json"] }'`.
+    #[arg(long, value_name = "DEPENDENCY")]
+    dependencies: Vec<String>,
+
     /// Generate the bindings as a module instead of a crate.
     #[arg(long)]
     module: bool,
@@ -69,6 +95,17 @@ pub struct BindArgs {
     #[arg(long)]
     single_file: bool,
 
+    /// Wrap the `sol!` invocation of each contract in its own module.
+    ///
+    /// Ethers bindings always use a module per contract, unless `--single-file` is passed.
+    #[arg(long, requires = "alloy_sol_macro")]
+    module_per_contract: bool,
+
+    /// Generate a single Rust file of alloy `sol!` macro invocations, with the ABIs inlined,
+    /// instead of ethers bindings.
+    #[arg(long)]
+    alloy_sol_macro: bool,
+
     /// Skip Cargo.toml consistency checks.
     #[arg(long)]
     skip_cargo_toml: bool,
@@ -85,6 +122,19 @@ pub struct BindArgs {
     build_args: CoreBuildArgs,
 }
 
+/// A contract artifact to generate bindings for.
+///
+/// Artifacts are ordered by name and source, so that bindings are generated deterministically.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct BindArtifact {
+    /// The name of the contract
+    name: String,
+    /// The contract's source file, relative to the project root
+    source: String,
+    /// The path to the artifact file
+    path: PathBuf,
+}
+
 impl BindArgs {
     pub fn run(self) -> Result<()> {
         if !self.skip_build {
@@ -93,11 +143,12 @@ impl BindArgs {
             let _ = ProjectCompiler::new().compile(&project)?;
         }
 
-        let artifacts = self.try_load_config_emit_warnings()?.out;
+        let config = self.try_load_config_emit_warnings()?;
+        let artifacts = config.out.clone();
 
         if !self.overwrite && self.bindings_exist(&artifacts) {
             println!("Bindings found. Checking for consistency.");
-            return self.check_existing_bindings(&artifacts)
+            return self.check_existing_bindings(&config)
         }
 
         if self.overwrite && self.bindings_exist(&artifacts) {
@@ -105,7 +156,7 @@ impl BindArgs {
             fs::remove_dir_all(self.bindings_root(&artifacts))?;
         }
 
-        self.generate_bindings(&artifacts)?;
+        self.generate_bindings(&config)?;
 
         println!(
             "Bindings have been output to {}",
@@ -124,48 +175,95 @@ impl BindArgs {
         self.bindings_root(artifacts).is_dir()
     }
 
-    /// Returns the filter to use for `MultiAbigen`
-    fn get_filter(&self) -> ContractFilter {
+    /// Returns `true` if bindings should be generated for the artifact
+    fn is_selected(&self, artifact: &BindArtifact) -> bool {
+        let matches = |re: &Regex| re.is_match(&artifact.name) || re.is_match(&artifact.source);
         if self.select_all {
-            return ContractFilter::All
+            return true
         }
         if !self.select.is_empty() {
-            return SelectContracts::default().extend_regex(self.select.clone()).into()
+            return self.select.iter().any(matches)
         }
         if !self.skip.is_empty() {
-            return ExcludeContracts::default().extend_regex(self.skip.clone()).into()
+            return !self.skip.iter().any(matches)
         }
-        // This excludes all Test/Script and forge-std contracts
-        ExcludeContracts::default()
-            .extend_pattern([
-                ".*Test.*",
-                ".*Script",
-                "console[2]?",
-                "CommonBase",
-                "Components",
-                "[Ss]td(Chains|Math|Error|Json|Utils|Cheats|Style|Invariant|Assertions|Storage(Safe)?)",
-                "[Vv]m.*",
-            ])
-            .extend_names(["IMulticall3"])
-            .into()
+        !DEFAULT_SKIP.iter().any(|pattern| {
+            Regex::new(pattern).expect("valid default pattern").is_match(&artifact.name)
+        })
+    }
+
+    /// Collects the selected artifacts, sorted by contract name.
+    ///
+    /// The source of each artifact is looked up in the cache. Without a cache, the artifact's
+    /// directory in the output folder, which is named after its source file, is used instead.
+    fn get_artifacts(&self, config: &Config) -> Result<Vec<BindArtifact>> {
+        let project = config.project()?;
+        let root = &config.__root.0;
+
+        let mut artifacts = if config.cache && project.cache_path().exists() {
+            let cache = project.read_cache_file()?;
+            cache
+                .files
+                .iter()
+                .flat_map(|(source, entry)| {
+                    let source = source.strip_prefix(root).unwrap_or(source);
+                    entry.artifacts.iter().filter_map(move |(name, versions)| {
+                        // Use the artifact of the latest compiler version
+                        let (_, path) = versions.iter().next_back()?;
+                        Some(BindArtifact {
+                            name: name.clone(),
+                            source: source.to_string_lossy().into_owned(),
+                            path: config.out.join(path),
+                        })
+                    })
+                })
+                .filter(|artifact| artifact.path.exists())
+                .collect::<Vec<_>>()
+        } else {
+            json_files(&config.out)
+                .into_iter()
+                .filter_map(|path| {
+                    // we don't want `.metadata.json files
+                    let name = path.file_stem()?.to_str()?.to_string();
+                    if name.ends_with(".metadata") {
+                        return None
+                    }
+                    let source = path.parent()?.file_name()?.to_string_lossy().into_owned();
+                    Some(BindArtifact { name, source, path })
+                })
+                .collect()
+        };
+
+        artifacts.retain(|artifact| self.is_selected(artifact));
+        artifacts.sort();
+        artifacts.dedup_by(|duplicate, artifact| {
+            let is_duplicate = duplicate.name == artifact.name;
+            if is_duplicate {
+                eprintln!(
+                    "Warning: skipping `{}` from {}, a contract with the same name is defined in {}",
+                    duplicate.name, duplicate.source, artifact.source
+                );
+            }
+            is_duplicate
+        });
+
+        eyre::ensure!(
+            !artifacts.is_empty(),
+            r#"
+No contract artifacts found. Hint: Have you built your contracts yet? `forge bind` does not currently invoke `forge build`, although this is planned for future versions.
+            "#
+        );
+        Ok(artifacts)
     }
 
     /// Instantiate the multi-abigen
-    fn get_multi(&self, artifacts: impl AsRef<Path>) -> Result<MultiAbigen> {
-        let abigens = json_files(artifacts.as_ref())
-            .into_iter()
-            .filter_map(|path| {
-                // we don't want `.metadata.json files
-                let stem = path.file_stem()?;
-                if stem.to_str()?.ends_with(".metadata") {
-                    None
-                } else {
-                    Some(path)
-                }
-            })
-            .map(|path| {
+    fn get_multi(&self, artifacts: &[BindArtifact]) -> Result<MultiAbigen> {
+        let abigens = artifacts
+            .iter()
+            .map(|artifact| {
+                let path = &artifact.path;
                 trace!(?path, "parsing Abigen from file");
-                let abi = Abigen::from_file(&path)
+                let abi = Abigen::from_file(path)
                     .wrap_err_with(|| format!("failed to parse Abigen from file: {:?}", path));
                 if !self.skip_extra_derives {
                     abi?.add_derive("serde::Serialize")?.add_derive("serde::Deserialize")
@@ -174,27 +272,48 @@ impl BindArgs {
                 }
             })
             .collect::<Result<Vec<_>, _>>()?;
-        let multi = MultiAbigen::from_abigens(abigens).with_filter(self.get_filter());
+        Ok(MultiAbigen::from_abigens(abigens))
+    }
 
-        eyre::ensure!(
-            !multi.is_empty(),
-            r#"
-No contract artifacts found. Hint: Have you built your contracts yet? `forge bind` does not currently invoke `forge build`, although this is planned for future versions.
-            "#
-        );
-        Ok(multi)
+    /// Returns the dependencies of the generated crate, with the `--dependencies` overrides.
+    fn crate_dependencies(&self, defaults: Vec<String>) -> Vec<String> {
+        let name =
+            |dependency: &str| dependency.split('=').next().unwrap_or_default().trim().to_string();
+        let mut dependencies = defaults
+            .into_iter()
+            .filter(|default| !self.dependencies.iter().any(|dep| name(dep) == name(default)))
+            .collect::<Vec<_>>();
+        dependencies.extend(self.dependencies.iter().cloned());
+        dependencies
     }
 
     /// Check that the existing bindings match the expected abigen output
-    fn check_existing_bindings(&self, artifacts: impl AsRef<Path>) -> Result<()> {
+    fn check_existing_bindings(&self, config: &Config) -> Result<()> {
+        let artifacts = self.get_artifacts(config)?;
+        println!("Checking bindings for {} contracts.", artifacts.len());
+        if self.alloy_sol_macro {
+            for (path, contents) in self.sol_macro_files(config, &artifacts)? {
+                if self.skip_cargo_toml && path.ends_with("Cargo.toml") {
+                    continue
+                }
+                let existing = fs::read_to_string(&path).unwrap_or_default();
+                eyre::ensure!(
+                    existing == contents,
+                    "`{}` is not up to date. Run `forge bind --overwrite` to regenerate it.",
+                    path.display()
+                );
+            }
+            println!("OK.");
+            return Ok(())
+        }
+
         let bindings = self.get_multi(&artifacts)?.build()?;
-        println!("Checking bindings for {} contracts.", bindings.len());
         if !self.module {
-            bindings
+            self.with_dependencies(bindings)
                 .ensure_consistent_crate(
                     &self.crate_name,
                     &self.crate_version,
-                    self.bindings_root(&artifacts),
+                    self.bindings_root(&config.out),
                     self.single_file,
                     !self.skip_cargo_toml,
                 )
@@ -206,30 +325,173 @@ No contract artifacts found. Hint: Have you built your contracts yet? `forge bin
                     }
                 })?;
         } else {
-            bindings.ensure_consistent_module(self.bindings_root(&artifacts), self.single_file)?;
+            bindings.ensure_consistent_module(self.bindings_root(&config.out), self.single_file)?;
         }
         println!("OK.");
         Ok(())
     }
 
     /// Generate the bindings
-    fn generate_bindings(&self, artifacts: impl AsRef<Path>) -> Result<()> {
-        let mut bindings = self.get_multi(&artifacts)?.build()?;
-        println!("Generating bindings for {} contracts", bindings.len());
+    fn generate_bindings(&self, config: &Config) -> Result<()> {
+        let artifacts = self.get_artifacts(config)?;
+        println!("Generating bindings for {} contracts", artifacts.len());
+        if self.alloy_sol_macro {
+            trace!(module = self.module, "generating sol! macro bindings");
+            for (path, contents) in self.sol_macro_files(config, &artifacts)? {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, contents)?;
+            }
+            return Ok(())
+        }
+
+        let bindings = self.get_multi(&artifacts)?.build()?;
         if !self.module {
             trace!(single_file = self.single_file, "generating crate");
-            if !self.skip_extra_derives {
-                bindings = bindings.dependencies([r#"serde = "1""#])
-            }
-            bindings.write_to_crate(
+            self.with_dependencies(bindings).write_to_crate(
                 &self.crate_name,
                 &self.crate_version,
-                self.bindings_root(&artifacts),
+                self.bindings_root(&config.out),
                 self.single_file,
             )
         } else {
             trace!(single_file = self.single_file, "generating module");
-            bindings.write_to_module(self.bindings_root(&artifacts), self.single_file)
+            bindings.write_to_module(self.bindings_root(&config.out), self.single_file)
         }
     }
+
+    /// Adds the dependencies of the generated ethers crate
+    fn with_dependencies(
+        &self,
+        bindings: ethers_contract::MultiBindings,
+    ) -> ethers_contract::MultiBindings {
+        let defaults = if self.skip_extra_derives { vec![] } else { vec![r#"serde = "1""#.into()] };
+        bindings.dependencies(self.crate_dependencies(defaults))
+    }
+
+    /// Returns the files of the `sol!` macro bindings and their contents.
+    ///
+    /// As a module, this is a single `mod.rs` file. As a crate, this is a `Cargo.toml` and a
+    /// `src/lib.rs` file.
+    fn sol_macro_files(
+        &self,
+        config: &Config,
+        artifacts: &[BindArtifact],
+    ) -> Result<Vec<(PathBuf, String)>> {
+        let root = self.bindings_root(&config.out);
+        let bindings = self.sol_macro_bindings(artifacts)?;
+        if self.module {
+            return Ok(vec![(root.join("mod.rs"), bindings)])
+        }
+
+        let default = format!(
+            r#"alloy-sol-types = {{ version = "{ALLOY_SOL_TYPES_VERSION}", features = ["json"] }}"#
+        );
+        let dependencies = self.crate_dependencies(vec![default]).join("\n");
+        let cargo_toml = format!(
+            r#"[package]
+name = "{}"
+version = "{}"
+edition = "2021"
+
+[dependencies]
+{dependencies}
+"#,
+            self.crate_name, self.crate_version
+        );
+        Ok(vec![(root.join("Cargo.toml"), cargo_toml), (root.join("src/lib.rs"), bindings)])
+    }
+
+    /// Generates a `sol!` macro invocation with the inlined ABI for each artifact.
+    fn sol_macro_bindings(&self, artifacts: &[BindArtifact]) -> Result<String> {
+        let mut out = String::from(
+            "//! Autogenerated contract bindings. Do not edit manually.\n//!\n//! Generated with `forge bind --alloy-sol-macro`.\n\n#![allow(clippy::all)]\n",
+        );
+        if !self.module_per_contract {
+            out.push_str("\nuse alloy_sol_types::sol;\n");
+        }
+
+        for artifact in artifacts {
+            let contents: serde_json::Value = foundry_common::fs::read_json_file(&artifact.path)?;
+            let abi: JsonAbi = serde_json::from_value(contents["abi"].clone())
+                .wrap_err_with(|| format!("failed to parse ABI of {}", artifact.path.display()))?;
+            let abi = serde_json::to_string(&abi)?;
+
+            // Use enough `#`s for the raw string to not be terminated by the ABI
+            let mut hashes = String::from("#");
+            while abi.contains(&format!("\"{hashes}")) {
+                hashes.push('#');
+            }
+            let sol =
+                format!("sol!(\n    {},\n    r{hashes}\"{abi}\"{hashes}\n);\n", artifact.name);
+
+            out.push('\n');
+            if self.module_per_contract {
+                let sol = sol.lines().map(|line| format!("    {line}")).collect::<Vec<_>>();
+                out.push_str(&format!(
+                    "pub mod {} {{\n    use alloy_sol_types::sol;\n\n{}\n}}\n",
+                    module_name(&artifact.name),
+                    sol.join("\n")
+                ));
+            } else {
+                out.push_str(&sol);
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Converts a contract name to a snake case module name.
+fn module_name(name: &str) -> String {
+    let mut module = String::with_capacity(name.len() + 4);
+    let mut prev: Option<char> = None;
+    for c in name.chars() {
+        if c.is_uppercase() && prev.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()) {
+            module.push('_');
+        }
+        module.extend(c.to_lowercase());
+        prev = Some(c);
+    }
+    // Avoid clashing with Rust keywords
+    if matches!(
+        module.as_str(),
+        "as" | "async" |
+            "await" |
+            "box" |
+            "const" |
+            "crate" |
+            "dyn" |
+            "enum" |
+            "fn" |
+            "impl" |
+            "loop" |
+            "match" |
+            "mod" |
+            "ref" |
+            "self" |
+            "static" |
+            "struct" |
+            "super" |
+            "trait" |
+            "type" |
+            "use" |
+            "where"
+    ) {
+        module.push('_');
+    }
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_module_names() {
+        assert_eq!(module_name("Counter"), "counter");
+        assert_eq!(module_name("ERC20Mock"), "erc20_mock");
+        assert_eq!(module_name("IUniswapV3Pool"), "iuniswap_v3_pool");
+        assert_eq!(module_name("Type"), "type_");
+    }
 }
@@ -1,8 +1,14 @@
 use super::{install, watch::WatchArgs};
 use clap::Parser;
 use eyre::Result;
-use foundry_cli::{opts::CoreBuildArgs, utils::LoadConfig};
-use foundry_common::compile::{ProjectCompiler, SkipBuildFilter, SkipBuildFilters};
+use foundry_cli::{
+    opts::CoreBuildArgs,
+    utils::{self, LoadConfig},
+};
+use foundry_common::{
+    compile::{ProjectCompiler, SizeReport, SkipBuildFilter, SkipBuildFilters},
+    vyper::compile_vyper,
+};
 use foundry_compilers::{Project, ProjectCompileOutput};
 use foundry_config::{
     figment::{
@@ -11,7 +17,7 @@ use foundry_config::{
         value::{Dict, Map, Value},
         Metadata, Profile, Provider,
     },
-    Config,
+    Chain, Config,
 };
 use serde::Serialize;
 use watchexec::config::{InitConfig, RuntimeConfig};
@@ -48,10 +54,30 @@ pub struct BuildArgs {
     pub names: bool,
 
     /// Print compiled contract sizes.
+    ///
+    /// Both the runtime size and the initcode size are checked against their limits, see EIP-170
+    /// and EIP-3860.
     #[arg(long)]
     #[serde(skip)]
     pub sizes: bool,
 
+    /// Don't check the initcode size against the EIP-3860 limit, for chains that don't enforce it.
+    #[arg(long, requires = "sizes")]
+    #[serde(skip)]
+    pub ignore_eip_3860: bool,
+
+    /// The assumed size of the constructor arguments in bytes, added to the initcode size.
+    #[arg(long, requires = "sizes", value_name = "BYTES", default_value_t = 0)]
+    #[serde(skip)]
+    pub constructor_args_size: usize,
+
+    /// Print the contract sizes as JSON, along with `--sizes`.
+    ///
+    /// It's also the `--json` flag of `forge config`, which flattens these arguments.
+    #[arg(long, conflicts_with_all = ["names", "format_json"])]
+    #[serde(skip)]
+    pub json: bool,
+
     /// Skip building files whose names contain the given filter.
     ///
     /// `test` and `script` are aliases for `.t.sol` and `.s.sol`.
@@ -59,6 +85,28 @@ pub struct BuildArgs {
     #[serde(skip)]
     pub skip: Option<Vec<SkipBuildFilter>>,
 
+    /// Skip building test files, same as `--skip tests`.
+    ///
+    /// Files that are only imported by tests aren't built either.
+    #[arg(long)]
+    #[serde(skip)]
+    pub skip_tests: bool,
+
+    /// Skip building script files, same as `--skip scripts`.
+    ///
+    /// Files that are only imported by scripts aren't built either.
+    #[arg(long)]
+    #[serde(skip)]
+    pub skip_scripts: bool,
+
+    /// The chain to compile for, see `forge build --chain`.
+    ///
+    /// Not a CLI argument here since the commands that embed these arguments get `--chain` from
+    /// their EVM arguments.
+    #[arg(skip)]
+    #[serde(skip)]
+    pub chain: Option<Chain>,
+
     #[command(flatten)]
     #[serde(flatten)]
     pub args: CoreBuildArgs,
@@ -76,6 +124,10 @@ pub struct BuildArgs {
 
 impl BuildArgs {
     pub fn run(self) -> Result<ProjectCompileOutput> {
+        if self.json && !self.sizes {
+            eyre::bail!("`--json` requires `--sizes`")
+        }
+
         let mut config = self.try_load_config_emit_warnings()?;
         let mut project = config.project()?;
 
@@ -90,19 +142,41 @@ impl BuildArgs {
         let mut compiler = ProjectCompiler::new()
             .print_names(self.names)
             .print_sizes(self.sizes)
-            .quiet(self.format_json)
-            .bail(!self.format_json);
-        if let Some(skip) = self.skip {
-            if !skip.is_empty() {
-                compiler = compiler.filter(Box::new(SkipBuildFilters::new(skip)?));
-            }
+            .ignore_eip_3860(self.ignore_eip_3860)
+            .constructor_args_size(self.constructor_args_size)
+            .quiet(self.format_json || self.json)
+            .bail(!self.format_json)
+            .solc_versions(config.solc_versions.clone());
+        let mut skip = self.skip.unwrap_or_default();
+        if self.skip_tests {
+            skip.push(SkipBuildFilter::Tests);
+        }
+        if self.skip_scripts {
+            skip.push(SkipBuildFilter::Scripts);
+        }
+        if !skip.is_empty() {
+            compiler = compiler.filter(Box::new(SkipBuildFilters::new(skip)?));
         }
         let output = compiler.compile(&project)?;
 
+        // The Vyper sources aren't part of the solc project, their artifacts are written alongside
+        utils::block_on(compile_vyper(&config, self.format_json || self.json))?;
+
         if self.format_json {
             println!("{}", serde_json::to_string_pretty(&output.clone().output())?);
         }
 
+        if self.json {
+            let size_report =
+                SizeReport::new(&output, self.ignore_eip_3860, self.constructor_args_size);
+            println!("{}", serde_json::to_string_pretty(&size_report.to_json())?);
+
+            // exit with error if any contract exceeds the size limit, excluding test contracts.
+            if size_report.exceeds_size_limit() {
+                std::process::exit(1);
+            }
+        }
+
         Ok(output)
     }
 
@@ -122,11 +196,10 @@ impl BuildArgs {
 
     /// Returns the [`watchexec::InitConfig`] and [`watchexec::RuntimeConfig`] necessary to
     /// bootstrap a new [`watchexe::Watchexec`] loop.
-    pub(crate) fn watchexec_config(&self) -> Result<(InitConfig, RuntimeConfig)> {
+    pub(crate) fn watchexec_config(&self, config: &Config) -> Result<(InitConfig, RuntimeConfig)> {
         // use the path arguments or if none where provided the `src` dir
-        self.watch.watchexec_config(|| {
-            let config = Config::from(self);
-            vec![config.src, config.test, config.script]
+        self.watch.watchexec_config(config, || {
+            vec![config.src.clone(), config.test.clone(), config.script.clone()]
         })
     }
 }
@@ -150,6 +223,11 @@ impl Provider for BuildArgs {
             dict.insert("sizes".to_string(), true.into());
         }
 
+        // The chain's overrides are applied when the config is extracted
+        if let Some(chain) = self.chain {
+            dict.insert("chain_id".to_string(), chain.id().into());
+        }
+
         Ok(Map::from([(Config::selected_profile(), dict)]))
     }
 }
@@ -172,6 +250,10 @@ mod tests {
 
         let args: BuildArgs = BuildArgs::parse_from(["foundry-cli", "--skip", "tests", "scripts"]);
         assert_eq!(args.skip, Some(vec![SkipBuildFilter::Tests, SkipBuildFilter::Scripts]));
+
+        let args: BuildArgs =
+            BuildArgs::parse_from(["foundry-cli", "--skip-tests", "--skip-scripts"]);
+        assert!(args.skip_tests && args.skip_scripts);
     }
 
     #[test]
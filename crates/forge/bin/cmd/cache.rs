@@ -1,11 +1,17 @@
-use cache::Cache;
+use cache::{dir_size, format_size, BlockCache, Cache};
 use clap::{
     builder::{PossibleValuesParser, TypedValueParser},
     Arg, Command, Parser, Subcommand,
 };
 use eyre::Result;
 use foundry_config::{cache, Chain, Config, NamedChain};
-use std::{ffi::OsStr, str::FromStr};
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
 use strum::VariantNames;
 
 /// CLI arguments for `forge cache`.
@@ -22,6 +28,9 @@ pub enum CacheSubcommands {
 
     /// Shows cached data from the global foundry directory.
     Ls(LsArgs),
+
+    /// Evicts the least recently used blocks from the RPC cache until it fits the maximum size.
+    Evict(EvictArgs),
 }
 
 /// CLI arguments for `forge clean`.
@@ -48,25 +57,59 @@ pub struct CleanArgs {
     )]
     blocks: Vec<u64>,
 
+    /// Only clean the blocks that haven't been used for at least this long, e.g. `30d`.
+    ///
+    /// Supported units are `s`, `m`, `h`, `d` and `w`.
+    #[arg(long, value_name = "AGE", value_parser = parse_age, conflicts_with = "etherscan")]
+    older_than: Option<Duration>,
+
     /// Whether to clean the Etherscan cache.
     #[arg(long, group = "etherscan-blocks")]
     etherscan: bool,
+
+    /// Print what would be removed, without removing anything.
+    #[arg(long)]
+    dry_run: bool,
 }
 
 impl CleanArgs {
     pub fn run(self) -> Result<()> {
-        let CleanArgs { chains, blocks, etherscan } = self;
+        let CleanArgs { chains, blocks, older_than, etherscan, dry_run } = self;
+
+        if !blocks.is_empty() || older_than.is_some() {
+            let now = SystemTime::now();
+            let is_old = |block: &BlockCache| {
+                older_than.map_or(true, |age| {
+                    now.duration_since(block.last_used).map_or(false, |unused| unused >= age)
+                })
+            };
+            for chain_or_all in chains {
+                let chain = match chain_or_all {
+                    ChainOrAll::NamedChain(chain) => Some(chain.into()),
+                    ChainOrAll::All => None,
+                };
+                for block in Config::list_foundry_block_caches(chain)? {
+                    if (blocks.is_empty() || blocks.contains(&block.block)) && is_old(&block) {
+                        remove_cache(Some(block.path), dry_run)?;
+                    }
+                }
+            }
+            return Ok(())
+        }
 
         for chain_or_all in chains {
             match chain_or_all {
                 ChainOrAll::NamedChain(chain) => {
-                    clean_chain_cache(chain, blocks.to_vec(), etherscan)?
+                    remove_cache(Config::foundry_etherscan_chain_cache_dir(chain), dry_run)?;
+                    if !etherscan {
+                        remove_cache(Config::foundry_chain_cache_dir(chain), dry_run)?;
+                    }
                 }
                 ChainOrAll::All => {
                     if etherscan {
-                        Config::clean_foundry_etherscan_cache()?;
+                        remove_cache(Config::foundry_etherscan_cache_dir(), dry_run)?;
                     } else {
-                        Config::clean_foundry_cache()?
+                        remove_cache(Config::foundry_cache_dir(), dry_run)?;
                     }
                 }
             }
@@ -87,11 +130,19 @@ pub struct LsArgs {
         value_parser = ChainOrAllValueParser::default(),
     )]
     chains: Vec<ChainOrAll>,
+
+    /// Only list the Etherscan cache.
+    #[arg(long)]
+    etherscan: bool,
 }
 
 impl LsArgs {
     pub fn run(self) -> Result<()> {
-        let LsArgs { chains } = self;
+        let LsArgs { chains, etherscan } = self;
+        if etherscan {
+            return list_etherscan_cache(chains)
+        }
+
         let mut cache = Cache::default();
         for chain_or_all in chains {
             match chain_or_all {
@@ -106,6 +157,106 @@ impl LsArgs {
     }
 }
 
+/// CLI arguments for `forge cache evict`.
+#[derive(Debug, Parser)]
+pub struct EvictArgs {
+    /// The maximum size of the RPC cache in megabytes.
+    ///
+    /// Defaults to `rpc_cache_max_size_mb` from the config.
+    #[arg(long, value_name = "MB")]
+    max_size_mb: Option<u64>,
+
+    /// Print what would be evicted, without removing anything.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl EvictArgs {
+    pub fn run(self) -> Result<()> {
+        let Some(max_size) = self.max_size_mb.or_else(|| Config::load().rpc_cache_max_size_mb)
+        else {
+            eyre::bail!("No maximum size set, pass `--max-size-mb` or set `rpc_cache_max_size_mb`")
+        };
+        let evicted = Config::evict_foundry_rpc_cache(max_size * 1_000_000, self.dry_run)?;
+        for block in evicted.iter() {
+            print_removed(&block.path, block.size, self.dry_run);
+        }
+        let size = evicted.iter().map(|block| block.size).sum();
+        println!("Evicted {} blocks ({})", evicted.len(), format_size(size));
+        Ok(())
+    }
+}
+
+/// Removes the cache file or directory, or prints what would be removed if `dry_run` is set.
+fn remove_cache(path: Option<PathBuf>, dry_run: bool) -> Result<()> {
+    let Some(path) = path else { eyre::bail!("failed to get the foundry cache dir") };
+    if !path.exists() {
+        return Ok(())
+    }
+    if dry_run {
+        print_removed(&path, dir_size(&path)?, dry_run);
+    } else if path.is_dir() {
+        fs::remove_dir_all(&path)?;
+    } else {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+fn print_removed(path: &Path, size: u64, dry_run: bool) {
+    let action = if dry_run { "Would remove" } else { "Removed" };
+    println!("{action} {} ({})", path.display(), format_size(size));
+}
+
+fn list_etherscan_cache(chains: Vec<ChainOrAll>) -> Result<()> {
+    let Some(cache_dir) = Config::foundry_etherscan_cache_dir() else {
+        eyre::bail!("failed to get foundry_etherscan_cache_dir")
+    };
+    let mut listed = Vec::new();
+    for chain_or_all in chains {
+        match chain_or_all {
+            ChainOrAll::NamedChain(chain) => listed.push(Chain::from(chain)),
+            ChainOrAll::All if cache_dir.exists() => {
+                for entry in cache_dir.read_dir()?.flatten() {
+                    if let Ok(chain) = Chain::from_str(&entry.file_name().to_string_lossy()) {
+                        listed.push(chain);
+                    }
+                }
+            }
+            ChainOrAll::All => {}
+        }
+    }
+
+    let mut total = 0;
+    for chain in listed.iter() {
+        let path = cache_dir.join(chain.to_string());
+        let size = if path.exists() { dir_size(&path)? } else { 0 };
+        total += size;
+        println!("- {chain} ({})", format_size(size));
+    }
+    if listed.len() > 1 {
+        println!("Total: {}", format_size(total));
+    }
+    Ok(())
+}
+
+/// Parses a duration like `30d`.
+fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value = value.parse::<u64>().map_err(|_| format!("invalid age: {s}"))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("invalid age unit `{unit}`, expected one of s, m, h, d, w")),
+    };
+    Ok(Duration::from_secs(value * seconds))
+}
+
 #[derive(Clone, Debug)]
 pub enum ChainOrAll {
     NamedChain(NamedChain),
@@ -126,22 +277,6 @@ impl FromStr for ChainOrAll {
     }
 }
 
-fn clean_chain_cache(chain: impl Into<Chain>, blocks: Vec<u64>, etherscan: bool) -> Result<()> {
-    let chain = chain.into();
-    if blocks.is_empty() {
-        Config::clean_foundry_etherscan_chain_cache(chain)?;
-        if etherscan {
-            return Ok(())
-        }
-        Config::clean_foundry_chain_cache(chain)?;
-    } else {
-        for block in blocks {
-            Config::clean_foundry_block_cache(chain, block)?;
-        }
-    }
-    Ok(())
-}
-
 /// The value parser for `ChainOrAll`
 #[derive(Clone, Debug)]
 pub struct ChainOrAllValueParser {
@@ -185,4 +320,12 @@ mod tests {
         let args: CacheArgs = CacheArgs::parse_from(["cache", "ls"]);
         assert!(matches!(args.sub, CacheSubcommands::Ls(_)));
     }
+
+    #[test]
+    fn can_parse_cache_clean_older_than() {
+        let args = CacheArgs::parse_from(["cache", "clean", "mainnet", "--older-than", "30d"]);
+        let CacheSubcommands::Clean(args) = args.sub else { panic!("expected clean") };
+        assert_eq!(args.older_than, Some(Duration::from_secs(30 * 24 * 60 * 60)));
+        assert!(parse_age("2y").is_err());
+    }
 }
@@ -0,0 +1,538 @@
+use alloy_primitives::{Address, Bytes, B256};
+use clap::{Parser, ValueHint};
+use ethers_providers::Middleware;
+use eyre::{Context, Result};
+use foundry_block_explorers::{contract::Metadata, Client};
+use foundry_cli::{
+    opts::{EtherscanOpts, RpcOpts},
+    p_println,
+    utils::{self, Git},
+};
+use foundry_common::{
+    compile::ProjectCompiler,
+    fs, strip_bytecode_metadata,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_compilers::{artifacts::Settings, info::ContractInfo, EvmVersion};
+use foundry_config::{
+    figment::{self, value::Dict, Metadata as FigmentMetadata, Profile},
+    impl_figment_convert_cast, Chain, Config,
+};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Write,
+    path::{Component, Path, PathBuf},
+};
+use yansi::Paint;
+
+/// The default Sourcify server.
+const SOURCIFY_SERVER: &str = "https://sourcify.dev/server";
+
+/// The name of the file that records where the project was cloned from.
+pub const CLONE_METADATA_FILE: &str = ".clone.meta";
+
+/// CLI arguments for `forge clone`.
+#[derive(Clone, Debug, Parser)]
+pub struct CloneArgs {
+    /// The address of the verified contract to clone.
+    address: Address,
+
+    /// The root directory of the cloned project.
+    #[arg(value_hint = ValueHint::DirPath, default_value = ".", value_name = "PATH")]
+    root: PathBuf,
+
+    /// Do not initialize a git repository and commit the cloned sources.
+    #[arg(long)]
+    no_commit: bool,
+
+    /// Do not print any messages.
+    #[arg(long, short)]
+    quiet: bool,
+
+    #[command(flatten)]
+    etherscan: EtherscanOpts,
+
+    /// The RPC endpoint used to check that the cloned project compiles to the on-chain code.
+    #[command(flatten)]
+    rpc: RpcOpts,
+}
+
+impl_figment_convert_cast!(CloneArgs);
+
+impl figment::Provider for CloneArgs {
+    fn metadata(&self) -> FigmentMetadata {
+        FigmentMetadata::named("CloneArgs")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let mut dict = self.etherscan.dict();
+        dict.extend(self.rpc.dict());
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+impl CloneArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self);
+        let chain = self.etherscan.chain.or(config.chain).unwrap_or_default();
+        let quiet = self.quiet;
+
+        if !self.root.exists() {
+            fs::create_dir_all(&self.root)?;
+        }
+        let root = dunce::canonicalize(&self.root)?;
+        if root.read_dir().map_or(false, |mut i| i.next().is_some()) {
+            eyre::bail!("Cannot clone into a non-empty directory: {}", root.display());
+        }
+
+        let client = config
+            .get_etherscan_api_key(Some(chain))
+            .map(|key| Client::new(chain, key))
+            .transpose()?;
+
+        p_println!(!quiet => "Downloading the source code of {} from {chain}...", self.address);
+        let source = match &client {
+            Some(client) => match ClonedSource::from_etherscan(client, self.address).await {
+                Ok(source) => source,
+                Err(err) => {
+                    p_println!(!quiet => "Etherscan failed ({err}), falling back to Sourcify...");
+                    ClonedSource::from_sourcify(chain, self.address).await?
+                }
+            },
+            None => ClonedSource::from_sourcify(chain, self.address).await?,
+        };
+
+        source.write_to(&root)?;
+
+        let mut meta = CloneMetadata {
+            path: source.target.clone(),
+            target_contract: source.name.clone(),
+            address: self.address,
+            chain_id: chain.id(),
+            constructor_arguments: source.constructor_arguments.clone(),
+            deployer: None,
+            creation_transaction: None,
+        };
+        if let Some(client) = &client {
+            if let Ok(creation) = client.contract_creation_data(self.address).await {
+                meta.deployer = Some(creation.contract_creator);
+                meta.creation_transaction = Some(creation.transaction_hash);
+            }
+        }
+        fs::write_json_file(&root.join(CLONE_METADATA_FILE), &meta)?;
+
+        // make sure the cloned project compiles, and matches the on-chain code if possible
+        let project_config = Config::load_with_root(&root).sanitized();
+        let output = ProjectCompiler::new().quiet(true).compile(&project_config.project()?)?;
+        if self.rpc.url.is_some() {
+            let provider = utils::get_provider(&config)?;
+            let code = provider.get_code(self.address.to_ethers(), None).await?.to_alloy();
+            let target = ContractInfo {
+                path: Some(root.join(&source.target).to_string_lossy().into_owned()),
+                name: source.name.clone(),
+            };
+            let artifact = output
+                .find_contract(&target)
+                .ok_or_else(|| eyre::eyre!("Could not find artifact `{}`", source.name))?;
+            let deployed = artifact
+                .deployed_bytecode
+                .as_ref()
+                .ok_or_else(|| eyre::eyre!("Artifact `{}` has no runtime code", source.name))?;
+            let local = deployed
+                .bytecode
+                .as_ref()
+                .and_then(|bytecode| bytecode.object.as_bytes())
+                .ok_or_else(|| eyre::eyre!("Artifact `{}` has unlinked libraries", source.name))?;
+
+            // immutables are only known after deployment
+            let mut code = code.to_vec();
+            for offsets in deployed.immutable_references.values().flatten() {
+                let (start, end) =
+                    (offsets.start as usize, (offsets.start + offsets.length) as usize);
+                if let Some(bytes) = code.get_mut(start..end) {
+                    bytes.fill(0);
+                }
+            }
+            if strip_bytecode_metadata(&code) != strip_bytecode_metadata(local) {
+                eyre::bail!(
+                    "The cloned project doesn't compile to the runtime code of {}",
+                    self.address
+                );
+            }
+            p_println!(!quiet => "The cloned project matches the on-chain runtime code");
+        }
+
+        if !self.no_commit {
+            let git = Git::new(&root).quiet(quiet);
+            git.init()?;
+            fs::write(root.join(".gitignore"), include_str!("../../assets/.gitignoreTemplate"))?;
+            git.add(Some("--all"))?;
+            git.commit(&format!("chore: forge clone {}", self.address))?;
+        }
+
+        p_println!(!quiet => "    {} `{}` into {}", Paint::green("Cloned"), source.name, root.display());
+        Ok(())
+    }
+}
+
+/// The deployment metadata of a cloned contract, written to [CLONE_METADATA_FILE].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneMetadata {
+    /// The path of the source file that defines the contract, relative to the project root.
+    pub path: PathBuf,
+    /// The name of the contract.
+    pub target_contract: String,
+    /// The address of the contract.
+    pub address: Address,
+    /// The chain the contract is deployed on.
+    pub chain_id: u64,
+    /// The ABI-encoded constructor arguments.
+    pub constructor_arguments: Bytes,
+    /// The account that deployed the contract, if known.
+    pub deployer: Option<Address>,
+    /// The hash of the transaction that created the contract, if known.
+    pub creation_transaction: Option<B256>,
+}
+
+/// The verified sources and compiler settings of a contract.
+#[derive(Clone, Debug, Default)]
+struct ClonedSource {
+    /// The name of the contract.
+    name: String,
+    /// The path of the source file that defines the contract.
+    target: PathBuf,
+    /// The source files, keyed by their source unit name.
+    sources: Vec<(PathBuf, String)>,
+    compiler_version: Option<Version>,
+    optimizer: bool,
+    optimizer_runs: u64,
+    evm_version: Option<EvmVersion>,
+    via_ir: bool,
+    /// Remappings, relative to the project root.
+    remappings: Vec<String>,
+    /// Linked libraries in the `<path>:<name>:<address>` format.
+    libraries: Vec<String>,
+    constructor_arguments: Bytes,
+}
+
+impl ClonedSource {
+    async fn from_etherscan(client: &Client, address: Address) -> Result<Self> {
+        let source = client.contract_source_code(address).await?;
+        let metadata = source.items.first().ok_or_else(|| eyre::eyre!("No source found"))?;
+        if metadata.is_vyper() {
+            eyre::bail!("Cloning Vyper contracts is not supported")
+        }
+        if metadata.source_code().is_empty() {
+            eyre::bail!("Contract source code is not verified")
+        }
+
+        let mut cloned = Self {
+            name: metadata.contract_name.clone(),
+            compiler_version: metadata.compiler_version().ok(),
+            optimizer: metadata.optimization_used == 1,
+            optimizer_runs: metadata.runs,
+            evm_version: metadata.evm_version.parse().ok(),
+            constructor_arguments: metadata.constructor_arguments.clone(),
+            ..Default::default()
+        };
+
+        cloned.sources = metadata
+            .source_tree()
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let path = entry
+                    .path
+                    .strip_prefix(&metadata.contract_name)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(entry.path);
+                (path, entry.contents)
+            })
+            .collect();
+        if let Some(settings) = metadata.source_code.settings()? {
+            cloned.apply_settings(&settings);
+        }
+        cloned.target = find_target(&cloned.sources, &cloned.name, None)?;
+        cloned.libraries.extend(etherscan_libraries(metadata, &cloned.sources, &cloned.target));
+        Ok(cloned)
+    }
+
+    async fn from_sourcify(chain: Chain, address: Address) -> Result<Self> {
+        let url = format!("{SOURCIFY_SERVER}/files/any/{}/{address}", chain.id());
+        let response = reqwest::get(&url).await?;
+        if !response.status().is_success() {
+            eyre::bail!("Contract is not verified on Sourcify ({})", response.status())
+        }
+        let files: SourcifyFiles = response.json().await.wrap_err("Invalid Sourcify response")?;
+
+        let metadata = files
+            .files
+            .iter()
+            .find(|file| file.name == "metadata.json")
+            .ok_or_else(|| eyre::eyre!("Sourcify response doesn't contain the metadata"))?;
+        let metadata: SourcifyMetadata = serde_json::from_str(&metadata.content)?;
+        let (target, name) = metadata
+            .settings
+            .compilation_target
+            .iter()
+            .next()
+            .map(|(path, name)| (PathBuf::from(path), name.clone()))
+            .ok_or_else(|| eyre::eyre!("Sourcify metadata doesn't contain the target"))?;
+
+        let mut cloned = Self {
+            name,
+            compiler_version: Version::parse(metadata.compiler.version.trim_start_matches('v'))
+                .ok(),
+            ..Default::default()
+        };
+        cloned.sources = files
+            .files
+            .into_iter()
+            .filter_map(|file| Some((sourcify_source_path(&file.path)?, file.content)))
+            .collect();
+        cloned.apply_settings(&metadata.settings.settings);
+        cloned.target = find_target(&cloned.sources, &cloned.name, Some(target))?;
+        Ok(cloned)
+    }
+
+    fn apply_settings(&mut self, settings: &Settings) {
+        self.optimizer = settings.optimizer.enabled.unwrap_or_default();
+        self.optimizer_runs = settings.optimizer.runs.unwrap_or(200) as u64;
+        self.evm_version = settings.evm_version.or(self.evm_version);
+        self.via_ir = settings.via_ir.unwrap_or_default();
+        self.remappings = settings
+            .remappings
+            .iter()
+            .map(|remapping| {
+                let mut remapping = remapping.clone();
+                // absolute paths of the original environment can only be relative to the root
+                remapping.path = remapping.path.trim_start_matches('/').to_string();
+                remapping.to_string()
+            })
+            .collect();
+        for (path, libs) in settings.libraries.libs.iter() {
+            for (name, address) in libs {
+                self.libraries.push(format!("{}:{name}:{address}", path.display()));
+            }
+        }
+    }
+
+    /// Writes the sources and the `foundry.toml` to the project root.
+    fn write_to(&self, root: &Path) -> Result<()> {
+        for (path, contents) in self.sources.iter() {
+            // don't write outside of the project root
+            if path.components().any(|c| !matches!(c, Component::Normal(_))) {
+                eyre::bail!("Invalid source path: {}", path.display())
+            }
+            let path = root.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, contents)?;
+        }
+        fs::write(root.join(Config::FILE_NAME), self.foundry_toml())?;
+        Ok(())
+    }
+
+    /// Returns the `foundry.toml` that reproduces the verified compilation.
+    fn foundry_toml(&self) -> String {
+        let src = match self.target.components().next() {
+            Some(Component::Normal(dir)) if self.target.components().count() > 1 => {
+                Path::new(dir).to_path_buf()
+            }
+            _ => PathBuf::from("."),
+        };
+
+        let mut toml = String::from("[profile.default]\n");
+        let _ = writeln!(toml, "src = {:?}", src.to_string_lossy());
+        let _ = writeln!(toml, "out = \"out\"");
+        let _ = writeln!(toml, "libs = [\"lib\"]");
+        if let Some(version) = &self.compiler_version {
+            let _ =
+                writeln!(toml, "solc = \"{}.{}.{}\"", version.major, version.minor, version.patch);
+        }
+        let _ = writeln!(toml, "optimizer = {}", self.optimizer);
+        let _ = writeln!(toml, "optimizer_runs = {}", self.optimizer_runs);
+        if let Some(evm_version) = &self.evm_version {
+            let _ = writeln!(toml, "evm_version = \"{evm_version}\"");
+        }
+        if self.via_ir {
+            let _ = writeln!(toml, "via_ir = true");
+        }
+        let _ = writeln!(toml, "auto_detect_remappings = false");
+        let _ = writeln!(toml, "remappings = {:?}", self.remappings);
+        if !self.libraries.is_empty() {
+            let _ = writeln!(toml, "libraries = {:?}", self.libraries);
+        }
+        toml.push_str(
+            "\n# See more config options https://github.com/foundry-rs/foundry/blob/master/crates/config/README.md#all-options\n",
+        );
+        toml
+    }
+}
+
+/// Returns the linked libraries of an Etherscan verification.
+///
+/// Etherscan only records the library names, so each library is mapped to the source file that
+/// defines it, or to the target if none does.
+fn etherscan_libraries(
+    metadata: &Metadata,
+    sources: &[(PathBuf, String)],
+    target: &Path,
+) -> Vec<String> {
+    metadata
+        .library
+        .split(';')
+        .filter_map(|library| {
+            let (name, address) = library.split_once(':')?;
+            let (name, address) = (name.trim(), address.trim());
+            let address = if address.starts_with("0x") {
+                address.to_string()
+            } else {
+                format!("0x{address}")
+            };
+            let path = find_definition(sources, "library", name).unwrap_or(target);
+            Some(format!("{}:{name}:{address}", path.display()))
+        })
+        .collect()
+}
+
+/// Returns the path of the source file that defines the contract.
+fn find_target(
+    sources: &[(PathBuf, String)],
+    name: &str,
+    target: Option<PathBuf>,
+) -> Result<PathBuf> {
+    if let Some(target) = target.filter(|target| sources.iter().any(|(path, _)| path == target)) {
+        return Ok(target)
+    }
+    find_definition(sources, "(abstract\\s+)?contract", name)
+        .or_else(|| sources.first().map(|(path, _)| path.as_path()))
+        .map(Path::to_path_buf)
+        .ok_or_else(|| eyre::eyre!("No source files found"))
+}
+
+/// Returns the path of the source file that defines `name` as the given kind of contract, e.g.
+/// `library`.
+fn find_definition<'a>(
+    sources: &'a [(PathBuf, String)],
+    kind: &str,
+    name: &str,
+) -> Option<&'a Path> {
+    let definition =
+        regex::Regex::new(&format!(r"(?m)^\s*{kind}\s+{}\b", regex::escape(name))).ok()?;
+    sources
+        .iter()
+        .find(|(_, contents)| definition.is_match(contents))
+        .map(|(path, _)| path.as_path())
+}
+
+/// Returns the source unit name of a file returned by Sourcify, whose path looks like
+/// `/<repository>/contracts/full_match/<chain>/<address>/sources/<source unit name>`.
+fn sourcify_source_path(path: &str) -> Option<PathBuf> {
+    let (_, source) = path.split_once("/sources/")?;
+    Some(PathBuf::from(source))
+}
+
+#[derive(Deserialize)]
+struct SourcifyFiles {
+    files: Vec<SourcifyFile>,
+}
+
+#[derive(Deserialize)]
+struct SourcifyFile {
+    name: String,
+    path: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct SourcifyMetadata {
+    compiler: SourcifyCompiler,
+    settings: SourcifySettings,
+}
+
+#[derive(Deserialize)]
+struct SourcifyCompiler {
+    version: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SourcifySettings {
+    compilation_target: std::collections::BTreeMap<String, String>,
+    #[serde(flatten)]
+    settings: Settings,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_find_target() {
+        let sources = vec![
+            (PathBuf::from("lib/Ownable.sol"), "abstract contract Ownable {}".to_string()),
+            (PathBuf::from("src/Token.sol"), "contract Token is Ownable {}".to_string()),
+        ];
+        assert_eq!(find_target(&sources, "Token", None).unwrap(), PathBuf::from("src/Token.sol"));
+        assert_eq!(
+            find_target(&sources, "Token", Some("lib/Ownable.sol".into())).unwrap(),
+            PathBuf::from("lib/Ownable.sol")
+        );
+        assert_eq!(find_definition(&sources, "library", "Token"), None);
+        assert_eq!(
+            sourcify_source_path(
+                "/data/repository/contracts/full_match/1/0xabc/sources/src/Token.sol"
+            ),
+            Some(PathBuf::from("src/Token.sol"))
+        );
+    }
+
+    #[test]
+    fn can_find_library_definitions() {
+        let sources = vec![
+            (PathBuf::from("src/Token.sol"), "contract Token {}".to_string()),
+            (PathBuf::from("src/libraries/Math.sol"), "library Math {}".to_string()),
+            (PathBuf::from("src/libraries/SafeMath.sol"), "library SafeMath {}".to_string()),
+        ];
+        assert_eq!(
+            find_definition(&sources, "library", "Math"),
+            Some(Path::new("src/libraries/Math.sol"))
+        );
+        assert_eq!(
+            find_definition(&sources, "library", "SafeMath"),
+            Some(Path::new("src/libraries/SafeMath.sol"))
+        );
+        assert_eq!(find_definition(&sources, "library", "Strings"), None);
+    }
+
+    #[test]
+    fn can_write_foundry_toml() {
+        let source = ClonedSource {
+            target: "src/Token.sol".into(),
+            compiler_version: Some(Version::new(0, 8, 19)),
+            optimizer: true,
+            optimizer_runs: 10_000,
+            evm_version: Some(EvmVersion::Paris),
+            remappings: vec!["@openzeppelin/=lib/openzeppelin-contracts/".to_string()],
+            ..Default::default()
+        };
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join(Config::FILE_NAME), source.foundry_toml()).unwrap();
+
+        let config = Config::load_with_root(root.path());
+        assert_eq!(config.src, PathBuf::from("src"));
+        assert_eq!(config.solc, Some(foundry_config::SolcReq::Version(Version::new(0, 8, 19))));
+        assert!(config.optimizer);
+        assert_eq!(config.optimizer_runs, 10_000);
+        assert_eq!(config.evm_version, EvmVersion::Paris);
+        assert!(!config.auto_detect_remappings);
+        assert_eq!(
+            config.remappings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["@openzeppelin/=lib/openzeppelin-contracts/".to_string()]
+        );
+    }
+}
@@ -1,9 +1,11 @@
 use super::build::BuildArgs;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use eyre::Result;
 use foundry_cli::utils::LoadConfig;
 use foundry_common::{evm::EvmArgs, term::cli_warn};
-use foundry_config::fix::fix_tomls;
+use foundry_config::{figment::Figment, fix::fix_tomls, redact_url, Config, Provenance};
+use serde_json::Value;
+use std::collections::HashMap;
 
 foundry_config::impl_figment_convert!(ConfigArgs, opts, evm_opts);
 
@@ -14,15 +16,25 @@ pub struct ConfigArgs {
     #[arg(long)]
     basic: bool,
 
-    /// Print currently set config values as JSON.
-    #[arg(long)]
-    json: bool,
-
     /// Attempt to fix any configuration warnings.
     #[arg(long)]
     fix: bool,
 
-    // support nested build arguments
+    /// Print only the values that differ from the defaults, as dotted keys.
+    #[arg(long, conflicts_with = "basic")]
+    diff: bool,
+
+    /// Annotate each value with where it comes from: the default, a line of `foundry.toml`, an
+    /// environment variable or a CLI flag.
+    ///
+    /// Secrets, like API keys, are masked.
+    #[arg(long, conflicts_with = "basic")]
+    provenance: bool,
+
+    #[command(subcommand)]
+    command: Option<ConfigSubcommand>,
+
+    // support nested build arguments, along with their `--json` flag to print the values as JSON
     #[command(flatten)]
     opts: BuildArgs,
 
@@ -30,6 +42,17 @@ pub struct ConfigArgs {
     evm_opts: EvmArgs,
 }
 
+#[derive(Clone, Debug, Subcommand)]
+pub enum ConfigSubcommand {
+    /// Print the effective value of a key.
+    ///
+    /// Strings are printed as is, the other values as JSON. Secrets, like API keys, are masked.
+    Get {
+        /// The dotted key, e.g. `fuzz.runs` or `rpc_endpoints.mainnet`.
+        key: String,
+    },
+}
+
 impl ConfigArgs {
     pub fn run(self) -> Result<()> {
         if self.fix {
@@ -44,14 +67,30 @@ impl ConfigArgs {
             // we explicitly normalize the version, so mimic the behavior when invoking solc
             .normalized_evm_version();
 
+        if let Some(ConfigSubcommand::Get { key }) = &self.command {
+            let mut value = serde_json::to_value(&config)?;
+            let Some(value) = value.pointer_mut(&format!("/{}", key.replace('.', "/"))) else {
+                eyre::bail!("Unknown config key `{key}`")
+            };
+            match mask_secrets(key, value.take()) {
+                Value::String(s) => println!("{s}"),
+                value => println!("{}", serde_json::to_string_pretty(value)?),
+            }
+            return Ok(())
+        }
+
+        if self.diff || self.provenance {
+            return self.print_annotated(&config)
+        }
+
         let s = if self.basic {
             let config = config.into_basic();
-            if self.json {
+            if self.opts.json {
                 serde_json::to_string_pretty(&config)?
             } else {
                 config.to_string_pretty()?
             }
-        } else if self.json {
+        } else if self.opts.json {
             serde_json::to_string_pretty(&config)?
         } else {
             config.to_string_pretty()?
@@ -60,4 +99,103 @@ impl ConfigArgs {
         println!("{s}");
         Ok(())
     }
+
+    /// Prints the values as dotted keys, only those that differ from the defaults with `--diff`,
+    /// and with where they come from with `--provenance`.
+    fn print_annotated(&self, config: &Config) -> Result<()> {
+        let mut values = Vec::new();
+        flatten_value("", serde_json::to_value(config)?, &mut values);
+
+        if self.diff {
+            let mut defaults = Vec::new();
+            let default_config = Config::with_root(&config.__root.0).normalized_evm_version();
+            flatten_value("", serde_json::to_value(default_config)?, &mut defaults);
+            let defaults = defaults.into_iter().collect::<HashMap<_, _>>();
+            values.retain(|(key, value)| defaults.get(key) != Some(value));
+        }
+
+        let figment: Figment = self.into();
+        let values = values
+            .into_iter()
+            .map(|(key, value)| {
+                let value = mask_secret(&key, value);
+                let provenance = self
+                    .provenance
+                    .then(|| Provenance::find(&figment, &key).unwrap_or(Provenance::Default));
+                (key, value, provenance)
+            })
+            .collect::<Vec<_>>();
+
+        if self.opts.json {
+            let map = values
+                .into_iter()
+                .map(|(key, value, provenance)| {
+                    let value = match provenance {
+                        Some(provenance) => serde_json::json!({
+                            "value": value,
+                            "source": provenance.to_string(),
+                        }),
+                        None => value,
+                    };
+                    (key, value)
+                })
+                .collect::<serde_json::Map<_, _>>();
+            println!("{}", serde_json::to_string_pretty(&map)?);
+            return Ok(())
+        }
+
+        for (key, value, provenance) in values {
+            match provenance {
+                Some(provenance) => println!("{key} = {value} # {provenance}"),
+                None => println!("{key} = {value}"),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Flattens the value into its dotted keys and leaf values.
+fn flatten_value(prefix: &str, value: Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                // internal values, e.g. the root and the warnings
+                if key.starts_with("__") {
+                    continue
+                }
+                let key = if prefix.is_empty() { key } else { format!("{prefix}.{key}") };
+                flatten_value(&key, value, out);
+            }
+        }
+        value => out.push((prefix.to_string(), value)),
+    }
+}
+
+/// Masks the secrets of the value of the key, and of its nested values.
+fn mask_secrets(key: &str, value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(name, value)| {
+                    let value = mask_secrets(&format!("{key}.{name}"), value);
+                    (name, value)
+                })
+                .collect(),
+        ),
+        value => mask_secret(key, value),
+    }
+}
+
+/// Masks the API and private keys, and the credentials in the RPC URLs.
+fn mask_secret(key: &str, value: Value) -> Value {
+    let name = key.rsplit('.').next().unwrap_or(key);
+    let is_secret =
+        ["key", "secret", "password", "jwt", "mnemonic"].iter().any(|secret| name.contains(secret));
+    match value {
+        Value::String(s) if is_secret && !s.is_empty() => Value::String("<redacted>".to_string()),
+        Value::String(s) if key.starts_with("rpc_endpoints.") || name == "eth_rpc_url" => {
+            Value::String(redact_url(&s))
+        }
+        value => value,
+    }
 }
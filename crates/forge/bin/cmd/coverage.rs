@@ -4,8 +4,10 @@ use clap::{Parser, ValueEnum, ValueHint};
 use eyre::{Context, Result};
 use forge::{
     coverage::{
-        analysis::SourceAnalyzer, anchors::find_anchors, BytecodeReporter, ContractId,
-        CoverageReport, CoverageReporter, DebugReporter, ItemAnchor, LcovReporter, SummaryReporter,
+        analysis::SourceAnalyzer,
+        anchors::{find_anchors, find_optimized_anchors},
+        BytecodeReporter, ContractId, CoverageReport, CoverageReporter, DebugReporter, ItemAnchor,
+        LcovReporter, SummaryReporter,
     },
     inspectors::CheatsConfig,
     opts::EvmOpts,
@@ -52,6 +54,14 @@ pub struct CoverageArgs {
     #[arg(long)]
     ir_minimum: bool,
 
+    /// Keep the optimizer and viaIR settings of the project instead of disabling the optimizer.
+    ///
+    /// Hits are attributed to the source code through the optimized source maps. Since the
+    /// optimizer duplicates, reorders and merges code, hit counts are lower bounds, and items
+    /// whose code can't be told apart from other code are reported as partially attributable.
+    #[arg(long, conflicts_with = "ir_minimum")]
+    optimized: bool,
+
     /// The path to output the report.
     ///
     /// If not specified, the report will be stored in the root of the project.
@@ -131,6 +141,12 @@ impl CoverageArgs {
             // https://github.com/ethereum/solidity/issues/13972#issuecomment-1628632202
             project.solc_config.settings =
                 project.solc_config.settings.with_via_ir_minimum_optimization()
+        } else if self.optimized {
+            let msg = Paint::yellow(
+                "Warning! \"--optimized\" keeps the optimizer enabled, hit counts are lower bounds \
+                 and some items may only be partially attributable to the source code.",
+            );
+            p_println!(!self.opts.silent => "{msg}");
         } else {
             project.solc_config.settings.optimizer.disable();
             project.solc_config.settings.optimizer.runs = None;
@@ -246,7 +262,7 @@ impl CoverageArgs {
 
         // Add coverage items
         for (version, asts) in versioned_asts.into_iter() {
-            let source_analysis = SourceAnalyzer::new(
+            let mut source_analysis = SourceAnalyzer::new(
                 version.clone(),
                 asts,
                 versioned_sources.remove(&version).ok_or_else(|| {
@@ -256,23 +272,40 @@ impl CoverageArgs {
                 })?,
             )?
             .analyze()?;
+            let mut partial_items = Vec::new();
             let anchors: HashMap<ContractId, Vec<ItemAnchor>> = source_analysis
                 .contract_items
                 .iter()
                 .filter_map(|(contract_id, item_ids)| {
                     // TODO: Creation source map/bytecode as well
-                    Some((
-                        contract_id.clone(),
+                    let bytecode = &bytecodes.get(contract_id)?.1;
+                    let source_map = &source_maps.get(contract_id)?.1;
+                    let ic_pc_map = &ic_pc_maps.get(contract_id)?.1;
+                    let anchors = if self.optimized {
+                        let (anchors, partial) = find_optimized_anchors(
+                            bytecode,
+                            source_map,
+                            ic_pc_map,
+                            item_ids,
+                            &source_analysis.items,
+                        );
+                        partial_items.extend(partial);
+                        anchors
+                    } else {
                         find_anchors(
-                            &bytecodes.get(contract_id)?.1,
-                            &source_maps.get(contract_id)?.1,
-                            &ic_pc_maps.get(contract_id)?.1,
+                            bytecode,
+                            source_map,
+                            ic_pc_map,
                             item_ids,
                             &source_analysis.items,
-                        ),
-                    ))
+                        )
+                    };
+                    Some((contract_id.clone(), anchors))
                 })
                 .collect();
+            for item_id in partial_items {
+                source_analysis.items[item_id].partial = true;
+            }
             report.add_items(version, source_analysis.items);
             report.add_anchors(anchors);
         }
@@ -410,6 +443,6 @@ fn dummy_link_bytecode(mut obj: CompactBytecode) -> Option<Bytes> {
 /// Helper function that will link references in unlinked bytecode to the 0 address.
 ///
 /// This is needed in order to analyze the bytecode for contracts that use libraries.
-fn dummy_link_deployed_bytecode(obj: CompactDeployedBytecode) -> Option<Bytes> {
+pub(crate) fn dummy_link_deployed_bytecode(obj: CompactDeployedBytecode) -> Option<Bytes> {
     obj.bytecode.and_then(dummy_link_bytecode)
 }
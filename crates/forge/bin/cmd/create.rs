@@ -1,6 +1,6 @@
 use alloy_dyn_abi::{DynSolValue, JsonAbiExt, ResolveSolType};
 use alloy_json_abi::{Constructor, JsonAbi};
-use alloy_primitives::{Address, Bytes};
+use alloy_primitives::{Address, Bytes, B256};
 use clap::{Parser, ValueHint};
 use ethers_contract::ContractError;
 use ethers_core::{
@@ -23,8 +23,10 @@ use foundry_common::{
     fmt::parse_tokens,
     provider::ethers::estimate_eip1559_fees,
     types::{ToAlloy, ToEthers},
+    vyper::{compile_vyper, is_vyper_file},
 };
 use foundry_compilers::{artifacts::BytecodeObject, info::ContractInfo, utils::canonicalized};
+use foundry_evm::constants::DEFAULT_CREATE2_DEPLOYER;
 use serde_json::json;
 use std::{borrow::Borrow, marker::PhantomData, path::PathBuf, sync::Arc};
 
@@ -51,6 +53,24 @@ pub struct CreateArgs {
     )]
     constructor_args_path: Option<PathBuf>,
 
+    /// Deploy the contract deterministically through the CREATE2 deployer with the given salt.
+    #[arg(long, value_name = "SALT")]
+    salt: Option<B256>,
+
+    /// The address of the CREATE2 deployer used with `--salt`.
+    #[arg(
+        long,
+        requires = "salt",
+        value_name = "ADDRESS",
+        default_value_t = DEFAULT_CREATE2_DEPLOYER,
+    )]
+    create2_deployer: Address,
+
+    /// Only print the address the contract would be deployed to with `--salt`, without sending
+    /// the transaction.
+    #[arg(long, requires = "salt")]
+    dry_run: bool,
+
     /// Print the deployment information as JSON.
     #[arg(long, help_heading = "Display options")]
     json: bool,
@@ -91,15 +111,33 @@ impl CreateArgs {
     pub async fn run(mut self) -> Result<()> {
         // Find Project & Compile
         let project = self.opts.project()?;
-        let mut output =
-            ProjectCompiler::new().quiet_if(self.json || self.opts.silent).compile(&project)?;
+        let (abi, bin) = if self.contract.path.as_ref().map_or(false, is_vyper_file) {
+            let config = self.opts.try_load_config_emit_warnings()?;
+            let output = compile_vyper(&config, self.json || self.opts.silent).await?;
+            let artifact = output
+                .as_ref()
+                .and_then(|output| output.find_contract(project.root(), &self.contract))
+                .ok_or_else(|| eyre::eyre!("could not find artifact: `{}`", self.contract))?;
+            let abi = artifact
+                .abi
+                .clone()
+                .ok_or_else(|| eyre::eyre!("contract {} does not contain abi", self.contract))?;
+            let bin = artifact.bytecode.clone().ok_or_else(|| {
+                eyre::eyre!("contract {} does not contain bytecode", self.contract)
+            })?;
+            (abi, bin)
+        } else {
+            let mut output =
+                ProjectCompiler::new().quiet_if(self.json || self.opts.silent).compile(&project)?;
 
-        if let Some(ref mut path) = self.contract.path {
-            // paths are absolute in the project's output
-            *path = canonicalized(project.root().join(&path)).to_string_lossy().to_string();
-        }
+            if let Some(ref mut path) = self.contract.path {
+                // paths are absolute in the project's output
+                *path = canonicalized(project.root().join(&path)).to_string_lossy().to_string();
+            }
 
-        let (abi, bin, _) = remove_contract(&mut output, &self.contract)?;
+            let (abi, bin, _) = remove_contract(&mut output, &self.contract)?;
+            (abi, bin)
+        };
 
         let bin = match bin.object {
             BytecodeObject::Bytecode(_) => bin.object,
@@ -193,6 +231,7 @@ impl CreateArgs {
             evm_version: self.opts.compiler.evm_version,
             show_standard_json_input: self.show_standard_json_input,
             guess_constructor_args: false,
+            preflight: false,
         };
 
         // Check config for Etherscan API Keys to avoid preflight check failing if no
@@ -201,8 +240,7 @@ impl CreateArgs {
         verify.etherscan.key =
             config.get_etherscan_config_with_chain(Some(chain.into()))?.map(|c| c.key);
 
-        verify.verification_provider()?.preflight_check(verify).await?;
-        Ok(())
+        verify.preflight_check().await
     }
 
     /// Deploys the contract
@@ -235,6 +273,38 @@ impl CreateArgs {
             Chain::try_from(chain).map(|x| Chain::is_legacy(&x)).unwrap_or_default();
         let mut deployer = if is_legacy { deployer.legacy() } else { deployer };
 
+        // route the deployment through the CREATE2 deployer, which deploys the init code appended
+        // to the salt
+        let predicted_address = if let Some(salt) = self.salt {
+            let init_code = deployer.tx.data().cloned().unwrap_or_default();
+            let address = self.create2_deployer.create2_from_code(salt, &init_code);
+            self.check_create2_deployment(&*provider, address, chain).await?;
+
+            if self.json {
+                if self.dry_run {
+                    let output = json!({
+                        "deployer": deployer_address.to_alloy().to_string(),
+                        "predictedAddress": address.to_string(),
+                        "salt": salt.to_string(),
+                        "create2Deployer": self.create2_deployer.to_string(),
+                    });
+                    println!("{output}");
+                }
+            } else {
+                println!("Predicted address: {address}");
+            }
+            if self.dry_run {
+                return Ok(())
+            }
+
+            let data: Bytes = salt.iter().copied().chain(init_code.iter().copied()).collect();
+            deployer.tx.set_to(self.create2_deployer.to_ethers());
+            deployer.tx.set_data(data.to_ethers());
+            Some(address)
+        } else {
+            None
+        };
+
         // set tx value if specified
         if let Some(value) = self.tx.value {
             deployer.tx.set_value(value.to_ethers());
@@ -299,15 +369,32 @@ impl CreateArgs {
         }
 
         // Deploy the actual contract
-        let (deployed_contract, receipt) = deployer.send_with_receipt().await?;
+        let (address, receipt) = if let Some(address) = predicted_address {
+            let receipt = deployer.send().await?;
+            if receipt.status == Some(0u64.into()) {
+                eyre::bail!(
+                    "CREATE2 deployment reverted in transaction {:?}",
+                    receipt.transaction_hash
+                );
+            }
+            if provider.get_code(address.to_ethers(), None).await?.is_empty() {
+                eyre::bail!("no code was deployed at the predicted address {address}");
+            }
+            (address, receipt)
+        } else {
+            deployer.send_with_receipt().await?
+        };
 
-        let address = deployed_contract;
         if self.json {
-            let output = json!({
+            let mut output = json!({
                 "deployer": deployer_address.to_alloy().to_string(),
                 "deployedTo": address.to_string(),
                 "transactionHash": receipt.transaction_hash
             });
+            if let Some(salt) = self.salt {
+                output["salt"] = salt.to_string().into();
+                output["create2Deployer"] = self.create2_deployer.to_string().into();
+            }
             println!("{output}");
         } else {
             println!("Deployer: {}", deployer_address.to_alloy());
@@ -344,11 +431,49 @@ impl CreateArgs {
             evm_version: self.opts.compiler.evm_version,
             show_standard_json_input: self.show_standard_json_input,
             guess_constructor_args: false,
+            preflight: false,
         };
-        println!("Waiting for {} to detect contract deployment...", verify.verifier.verifier);
+        println!("Waiting for {} to detect contract deployment...", verify.verifier.primary());
         verify.run().await
     }
 
+    /// Ensures the CREATE2 deployer exists on the chain and nothing is deployed at the address yet.
+    async fn check_create2_deployment<M: Middleware>(
+        &self,
+        provider: &M,
+        address: Address,
+        chain: u64,
+    ) -> Result<()> {
+        let deployer_code = provider
+            .get_code(self.create2_deployer.to_ethers(), None)
+            .await
+            .map_err(|err| eyre::eyre!("failed to get code: {err}"))?;
+        if deployer_code.is_empty() {
+            let mut msg = format!(
+                "CREATE2 deployer {} is not deployed on chain {chain}",
+                self.create2_deployer
+            );
+            if self.create2_deployer == DEFAULT_CREATE2_DEPLOYER {
+                msg.push_str(&format!(
+                    ".\nIt can be deployed by funding {CREATE2_DEPLOYER_SIGNER} with 0.01 ether and \
+                     publishing its presigned deployment transaction:\n\n\
+                     cast publish {CREATE2_DEPLOYER_PRESIGNED_TX}\n\n\
+                     See https://github.com/Arachnid/deterministic-deployment-proxy"
+                ));
+            }
+            eyre::bail!(msg);
+        }
+
+        let code = provider
+            .get_code(address.to_ethers(), None)
+            .await
+            .map_err(|err| eyre::eyre!("failed to get code: {err}"))?;
+        if !code.is_empty() {
+            eyre::bail!("a contract is already deployed at the predicted address {address}");
+        }
+        Ok(())
+    }
+
     /// Parses the given constructor arguments into a vector of `DynSolValue`s, by matching them
     /// against the constructor's input params.
     ///
@@ -371,6 +496,12 @@ impl CreateArgs {
     }
 }
 
+/// The signer of the presigned deployment transaction of [`DEFAULT_CREATE2_DEPLOYER`].
+const CREATE2_DEPLOYER_SIGNER: &str = "0x3fab184622dc19b6109349b94811493bf2a45362";
+
+/// The presigned deployment transaction of [`DEFAULT_CREATE2_DEPLOYER`], valid on any chain.
+const CREATE2_DEPLOYER_PRESIGNED_TX: &str = "0xf8a58085174876e800830186a08080b853604580600e600039806000f350fe7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf31ba02222222222222222222222222222222222222222222222222222222222222222a02222222222222222222222222222222222222222222222222222222222222222";
+
 /// `ContractFactory` is a [`DeploymentTxFactory`] object with an
 /// [`Arc`] middleware. This type alias exists to preserve backwards
 /// compatibility with less-abstract Contracts.
@@ -455,13 +586,9 @@ where
         self
     }
 
-    /// Broadcasts the contract deployment transaction and after waiting for it to
-    /// be sufficiently confirmed (default: 1), it returns a tuple with
-    /// the [`Contract`](crate::Contract) struct at the deployed contract's address
-    /// and the corresponding [`TransactionReceipt`].
-    pub async fn send_with_receipt(
-        self,
-    ) -> Result<(Address, TransactionReceipt), ContractError<M>> {
+    /// Broadcasts the deployment transaction and returns its [`TransactionReceipt`] after waiting
+    /// for it to be sufficiently confirmed (default: 1).
+    pub async fn send(self) -> Result<TransactionReceipt, ContractError<M>> {
         let pending_tx = self
             .client
             .borrow()
@@ -469,13 +596,23 @@ where
             .await
             .map_err(ContractError::from_middleware_error)?;
 
-        // TODO: Should this be calculated "optimistically" by address/nonce?
-        let receipt = pending_tx
+        pending_tx
             .confirmations(self.confs)
             .await
             .ok()
             .flatten()
-            .ok_or(ContractError::ContractNotDeployed)?;
+            .ok_or(ContractError::ContractNotDeployed)
+    }
+
+    /// Broadcasts the contract deployment transaction and after waiting for it to
+    /// be sufficiently confirmed (default: 1), it returns a tuple with
+    /// the [`Contract`](crate::Contract) struct at the deployed contract's address
+    /// and the corresponding [`TransactionReceipt`].
+    pub async fn send_with_receipt(
+        self,
+    ) -> Result<(Address, TransactionReceipt), ContractError<M>> {
+        // TODO: Should this be calculated "optimistically" by address/nonce?
+        let receipt = self.send().await?;
         let address = receipt.contract_address.ok_or(ContractError::ContractNotDeployed)?;
 
         Ok((address.to_alloy(), receipt))
@@ -630,6 +767,24 @@ mod tests {
         assert_eq!(args.chain_id(), Some(9999));
     }
 
+    #[test]
+    fn can_parse_salt() {
+        let args: CreateArgs = CreateArgs::parse_from([
+            "foundry-cli",
+            "src/Domains.sol:Domains",
+            "--salt",
+            "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "--dry-run",
+        ]);
+        assert_eq!(args.salt, Some(B256::with_last_byte(1)));
+        assert_eq!(args.create2_deployer, DEFAULT_CREATE2_DEPLOYER);
+        assert!(args.dry_run);
+
+        let res =
+            CreateArgs::try_parse_from(["foundry-cli", "src/Domains.sol:Domains", "--dry-run"]);
+        assert!(res.is_err());
+    }
+
     #[test]
     fn test_parse_constructor_args() {
         let args: CreateArgs = CreateArgs::parse_from([
@@ -2,6 +2,7 @@ use clap::{Parser, ValueHint};
 use eyre::Result;
 use forge_doc::{
     ContractInheritance, Deployments, DocBuilder, GitSource, InferInlineHyperlinks, Inheritdoc,
+    TypeHyperlinks,
 };
 use foundry_cli::opts::GH_REPO_PREFIX_REGEX;
 use foundry_config::{find_project_root_path, load_config_with_root};
@@ -58,6 +59,12 @@ pub struct DocArgs {
     /// Whether to create docs for external libraries.
     #[arg(long, short)]
     include_libraries: bool,
+
+    /// Whether to create docs for internal and private functions and state variables.
+    ///
+    /// Internal library functions are always documented.
+    #[arg(long)]
+    include_internal: bool,
 }
 
 impl DocArgs {
@@ -96,11 +103,13 @@ impl DocArgs {
             self.include_libraries,
         )
         .with_should_build(self.build)
+        .with_include_internal(self.include_internal)
         .with_config(doc_config.clone())
         .with_fmt(config.fmt)
         .with_preprocessor(ContractInheritance { include_libraries: self.include_libraries })
         .with_preprocessor(Inheritdoc::default())
         .with_preprocessor(InferInlineHyperlinks::default())
+        .with_preprocessor(TypeHyperlinks { include_libraries: self.include_libraries })
         .with_preprocessor(GitSource {
             root: root.clone(),
             commit,
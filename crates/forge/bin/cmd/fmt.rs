@@ -114,8 +114,14 @@ impl FmtArgs {
                 }
             }
 
+            // Apply the overrides of the path, so that `--check` matches formatting
+            let fmt = match path {
+                Some(path) => config.fmt.for_path(&cwd.join(path), &config.__root.0),
+                None => config.fmt.clone(),
+            };
+
             let mut output = String::new();
-            format_to(&mut output, parsed, config.fmt.clone()).unwrap();
+            format_to(&mut output, parsed, fmt).unwrap();
 
             solang_parser::parse(&output, 0).map_err(|diags| {
                 eyre::eyre!(
@@ -0,0 +1,271 @@
+use super::snapshot::change_color;
+use clap::{Parser, ValueEnum, ValueHint};
+use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Table};
+use eyre::{Context, Result};
+use forge::gas_report::{GasJson, GasReportJson};
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
+
+/// The name of the deployment entry of a contract in a diff.
+const DEPLOYMENT: &str = "(deployment)";
+
+/// CLI arguments for `forge gas-diff`.
+#[derive(Clone, Debug, Parser)]
+pub struct GasDiffArgs {
+    /// The baseline gas report, written with `forge test --gas-report --gas-report-format json`.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub old: PathBuf,
+
+    /// The gas report to compare with the baseline.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub new: PathBuf,
+
+    /// The gas metric of the functions to compare.
+    #[arg(long, value_enum, default_value = "median")]
+    pub metric: GasMetric,
+
+    /// Exit with code 1 if the gas of any function or deployment increased by more than the
+    /// given percentage.
+    #[arg(long, value_name = "PERCENT")]
+    pub threshold: Option<f64>,
+
+    /// Include the functions whose gas didn't change in the diff.
+    #[arg(long)]
+    pub include_unchanged: bool,
+}
+
+impl GasDiffArgs {
+    /// Prints the diff, returning whether any gas increase exceeds the threshold.
+    pub fn run(self) -> Result<bool> {
+        let old = read_report(&self.old)?;
+        let new = read_report(&self.new)?;
+        let diff = GasReportDiff::new(&old, &new, self.metric, self.include_unchanged);
+        print!("{diff}");
+
+        let Some(threshold) = self.threshold else { return Ok(false) };
+        let exceeded = diff
+            .diffs
+            .iter()
+            .flat_map(|(contract, diffs)| diffs.iter().map(move |diff| (contract, diff)))
+            .filter(|(_, diff)| diff.pct_change() * 100.0 > threshold)
+            .collect::<Vec<_>>();
+        for (contract, diff) in &exceeded {
+            eprintln!(
+                "{} {contract}::{} increased by {:.3}%, above the threshold of {threshold}%",
+                Paint::red("error:").bold(),
+                diff.name,
+                diff.pct_change() * 100.0
+            );
+        }
+        Ok(!exceeded.is_empty())
+    }
+}
+
+/// The gas metric compared by `forge gas-diff`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum GasMetric {
+    Min,
+    Mean,
+    #[default]
+    Median,
+    Max,
+}
+
+impl GasMetric {
+    fn get(self, gas: &GasJson) -> u64 {
+        match self {
+            Self::Min => gas.min,
+            Self::Mean => gas.mean,
+            Self::Median => gas.median,
+            Self::Max => gas.max,
+        }
+    }
+}
+
+fn read_report(path: &Path) -> Result<GasReportJson> {
+    let content = fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read gas report \"{}\"", path.display()))?;
+    serde_json::from_str(&content)
+        .wrap_err_with(|| format!("failed to parse gas report \"{}\"", path.display()))
+}
+
+/// Returns the compared gas of each function and deployment in the report, by contract and name.
+fn entries(report: &GasReportJson, metric: GasMetric) -> BTreeMap<(String, String), u64> {
+    let mut entries = BTreeMap::new();
+    for contract in &report.contracts {
+        if let Some(deployment) = &contract.deployment {
+            entries.insert(
+                (contract.contract.clone(), DEPLOYMENT.to_string()),
+                metric.get(&deployment.gas),
+            );
+        }
+        for function in &contract.functions {
+            entries.insert(
+                (contract.contract.clone(), function.signature.clone()),
+                metric.get(&function.gas),
+            );
+        }
+    }
+    entries
+}
+
+/// The gas change of a function or deployment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct GasDiff {
+    name: String,
+    old: u64,
+    new: u64,
+}
+
+impl GasDiff {
+    fn change(&self) -> i128 {
+        self.new as i128 - self.old as i128
+    }
+
+    fn pct_change(&self) -> f64 {
+        if self.old == 0 {
+            return 0.0
+        }
+        self.change() as f64 / self.old as f64
+    }
+}
+
+/// The diff between two gas reports, grouped by contract.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct GasReportDiff {
+    /// Diffs of the entries in both reports, sorted by absolute gas change
+    diffs: BTreeMap<String, Vec<GasDiff>>,
+    /// Entries only in the new report
+    added: Vec<(String, String)>,
+    /// Entries only in the old report
+    removed: Vec<(String, String)>,
+}
+
+impl GasReportDiff {
+    fn new(
+        old: &GasReportJson,
+        new: &GasReportJson,
+        metric: GasMetric,
+        include_unchanged: bool,
+    ) -> Self {
+        let mut old = entries(old, metric);
+        let mut report = Self::default();
+        for (key, new_gas) in entries(new, metric) {
+            let Some(old_gas) = old.remove(&key) else {
+                report.added.push(key);
+                continue
+            };
+            if include_unchanged || old_gas != new_gas {
+                let (contract, name) = key;
+                report.diffs.entry(contract).or_default().push(GasDiff {
+                    name,
+                    old: old_gas,
+                    new: new_gas,
+                });
+            }
+        }
+        report.removed = old.into_keys().collect();
+
+        for diffs in report.diffs.values_mut() {
+            diffs.sort_by_key(|diff| Reverse(diff.change().unsigned_abs()));
+        }
+        report
+    }
+}
+
+impl fmt::Display for GasReportDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.diffs.is_empty() {
+            writeln!(f, "No gas changes.\n")?;
+        }
+        for (contract, diffs) in &self.diffs {
+            let mut table = Table::new();
+            table.load_preset(ASCII_MARKDOWN);
+            table.set_header(
+                ["Function", "Old gas", "New gas", "Δ", "%"]
+                    .map(|header| Cell::new(header).add_attribute(Attribute::Bold)),
+            );
+            for diff in diffs {
+                let color = change_color(diff.change());
+                table.add_row([
+                    Cell::new(&diff.name),
+                    Cell::new(diff.old),
+                    Cell::new(diff.new),
+                    Cell::new(format!("{:+}", diff.change())).fg(color),
+                    Cell::new(format!("{:+.3}%", diff.pct_change() * 100.0)).fg(color),
+                ]);
+            }
+            writeln!(f, "{}", Paint::new(contract).bold())?;
+            writeln!(f, "{table}\n")?;
+        }
+        for (title, entries) in
+            [("New functions", &self.added), ("Removed functions", &self.removed)]
+        {
+            if entries.is_empty() {
+                continue
+            }
+            writeln!(f, "{}", Paint::new(title).bold())?;
+            for (contract, name) in entries {
+                writeln!(f, "  {contract}::{name}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(json: &str) -> GasReportJson {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn diffs_gas_reports() {
+        let old = report(
+            r#"{"contracts": [{
+                "contract": "src/Counter.sol:Counter",
+                "kind": "contract",
+                "deployment": {"min": 100, "mean": 100, "median": 100, "max": 100, "calls": 1, "size": 10},
+                "functions": [
+                    {"signature": "increment()", "min": 10, "mean": 20, "median": 20, "max": 30, "calls": 3},
+                    {"signature": "reset()", "min": 5, "mean": 5, "median": 5, "max": 5, "calls": 1}
+                ]
+            }]}"#,
+        );
+        let new = report(
+            r#"{"contracts": [{
+                "contract": "src/Counter.sol:Counter",
+                "kind": "contract",
+                "deployment": {"min": 90, "mean": 90, "median": 90, "max": 90, "calls": 1, "size": 10},
+                "functions": [
+                    {"signature": "increment()", "min": 10, "mean": 20, "median": 25, "max": 30, "calls": 3},
+                    {"signature": "set(uint256)", "min": 5, "mean": 5, "median": 5, "max": 5, "calls": 1}
+                ]
+            }]}"#,
+        );
+
+        let diff = GasReportDiff::new(&old, &new, GasMetric::Median, false);
+        let counter = "src/Counter.sol:Counter".to_string();
+        assert_eq!(
+            diff.diffs[&counter],
+            vec![
+                GasDiff { name: DEPLOYMENT.to_string(), old: 100, new: 90 },
+                GasDiff { name: "increment()".to_string(), old: 20, new: 25 },
+            ]
+        );
+        assert_eq!(diff.added, vec![(counter.clone(), "set(uint256)".to_string())]);
+        assert_eq!(diff.removed, vec![(counter.clone(), "reset()".to_string())]);
+
+        // the mean of `increment()` didn't change
+        let diff = GasReportDiff::new(&old, &new, GasMetric::Mean, false);
+        assert_eq!(diff.diffs[&counter].len(), 1);
+    }
+}
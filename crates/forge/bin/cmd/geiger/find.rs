@@ -1,168 +1,329 @@
-use super::{error::ScanFileError, visitor::CheatcodeVisitor};
-use eyre::Result;
-use forge_fmt::{offset_to_line_column, parse, Visitable};
-use foundry_common::fs;
-use solang_parser::{diagnostics::Diagnostic, pt::Loc};
+use foundry_config::GeigerSeverity;
+use serde::Serialize;
+use serde_json::Value;
 use std::{
+    collections::{BTreeSet, HashMap},
     fmt,
     path::{Path, PathBuf},
 };
 use yansi::Paint;
 
-/// Scan a single file for `unsafe` cheatcode usage.
-pub fn find_cheatcodes_in_file(path: &Path) -> Result<SolFileMetrics, ScanFileError> {
-    let contents = fs::read_to_string(path)?;
-    let cheatcodes = find_cheatcodes_in_string(&contents)
-        .map_err(|diagnostic| ScanFileError::ParseSol(diagnostic, path.to_path_buf()))?;
-    Ok(SolFileMetrics { contents, cheatcodes, file: path.to_path_buf() })
+/// The names of the interfaces that declare the cheatcodes.
+const CHEATCODE_INTERFACES: &[&str] = &["Vm", "VmSafe"];
+
+/// The cheatcodes that can be reached from the functions of a compilation, keyed by AST node ID.
+///
+/// Cheatcode declarations are resolved through the AST, so aliased imports of `Vm` and functions
+/// that wrap cheatcodes are found too.
+#[derive(Clone, Debug, Default)]
+pub struct CheatcodeResolver {
+    /// The cheatcodes declared in the [CHEATCODE_INTERFACES].
+    cheatcodes: HashMap<usize, String>,
+    /// The names of all other functions and the cheatcodes they call, directly or not.
+    wrappers: HashMap<usize, (String, BTreeSet<String>)>,
 }
 
-/// Scan a string for unsafe cheatcodes.
-pub fn find_cheatcodes_in_string(src: &str) -> Result<UnsafeCheatcodes, Vec<Diagnostic>> {
-    let mut parsed = parse(src)?;
-    let mut visitor = CheatcodeVisitor::default();
-    parsed.pt.visit(&mut visitor).unwrap();
-    Ok(visitor.cheatcodes)
+impl CheatcodeResolver {
+    /// Resolves the cheatcodes of the ASTs of a single compilation, since AST node IDs are only
+    /// unique within one.
+    pub fn new<'a>(asts: impl IntoIterator<Item = &'a Value> + Clone) -> Self {
+        let mut cheatcodes = HashMap::new();
+        let mut calls = HashMap::new();
+        for ast in asts.clone() {
+            walk(ast, &mut |node| match node_type(node) {
+                Some("ContractDefinition")
+                    if node["name"]
+                        .as_str()
+                        .map_or(false, |n| CHEATCODE_INTERFACES.contains(&n)) =>
+                {
+                    for function in node["nodes"].as_array().into_iter().flatten() {
+                        if node_type(function) == Some("FunctionDefinition") {
+                            if let (Some(id), Some(name)) =
+                                (node_id(function), function["name"].as_str())
+                            {
+                                cheatcodes.insert(id, name.to_string());
+                            }
+                        }
+                    }
+                }
+                Some("FunctionDefinition" | "ModifierDefinition") => {
+                    if let (Some(id), Some(name)) = (node_id(node), node["name"].as_str()) {
+                        let mut callees = Vec::new();
+                        walk(&node["body"], &mut |node| {
+                            if let Some(callee) = callee(node) {
+                                callees.push(callee);
+                            }
+                        });
+                        calls.insert(id, (name.to_string(), callees));
+                    }
+                }
+                _ => {}
+            });
+        }
+        // cheatcode declarations don't have bodies
+        calls.retain(|id, _| !cheatcodes.contains_key(id));
+
+        // propagate the cheatcodes through the call graph
+        let mut wrappers: HashMap<usize, (String, BTreeSet<String>)> =
+            calls.iter().map(|(id, (name, _))| (*id, (name.clone(), BTreeSet::new()))).collect();
+        loop {
+            let mut changed = false;
+            for (id, (_, callees)) in calls.iter() {
+                let mut reached = BTreeSet::new();
+                for callee in callees {
+                    if let Some(cheatcode) = cheatcodes.get(callee) {
+                        reached.insert(cheatcode.clone());
+                    } else if let Some((_, wrapped)) = wrappers.get(callee) {
+                        reached.extend(wrapped.iter().cloned());
+                    }
+                }
+                let (_, wrapped) = wrappers.get_mut(id).unwrap();
+                if reached.len() > wrapped.len() {
+                    wrapped.extend(reached);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break
+            }
+        }
+        wrappers.retain(|_, (_, wrapped)| !wrapped.is_empty());
+
+        Self { cheatcodes, wrappers }
+    }
+
+    /// Finds the calls to cheatcodes, or to functions that wrap them, in the AST of a file.
+    pub fn find_calls(&self, ast: &Value, source: &str) -> Vec<CheatcodeCall> {
+        let mut found = Vec::new();
+        walk(ast, &mut |node| {
+            let Some(callee) = callee(node) else { return };
+            let Some((start, length)) = node["src"].as_str().and_then(parse_src) else { return };
+            let (line, column) = line_column(source, start);
+            let snippet = source.get(start..start + length).unwrap_or_default().to_string();
+
+            let mut push = |cheatcode: &str, via: Option<&str>| {
+                found.push(CheatcodeCall {
+                    cheatcode: cheatcode.to_string(),
+                    via: via.map(str::to_string),
+                    line,
+                    column,
+                    snippet: snippet.clone(),
+                })
+            };
+            if let Some(cheatcode) = self.cheatcodes.get(&callee) {
+                push(cheatcode, None);
+            } else if let Some((name, wrapped)) = self.wrappers.get(&callee) {
+                for cheatcode in wrapped {
+                    push(cheatcode, Some(name));
+                }
+            }
+        });
+        found
+    }
+}
+
+/// A call to a cheatcode found in a source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheatcodeCall {
+    /// The name of the cheatcode
+    pub cheatcode: String,
+    /// The name of the function that calls the cheatcode, if it isn't called directly.
+    pub via: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    /// The source code of the call.
+    pub snippet: String,
+}
+
+/// A [CheatcodeCall] and its configured severity.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// The file, relative to the project root.
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub cheatcode: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+    pub severity: GeigerSeverity,
+    #[serde(skip)]
+    pub snippet: String,
+}
+
+impl Finding {
+    pub fn new(file: &Path, call: CheatcodeCall, severity: GeigerSeverity) -> Self {
+        let CheatcodeCall { cheatcode, via, line, column, snippet } = call;
+        Self { file: file.to_path_buf(), line, column, cheatcode, via, severity, snippet }
+    }
 }
 
 /// Scan result for a single Solidity file.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct SolFileMetrics {
-    /// The Solidity file
+    /// The Solidity file, relative to the project root.
     pub file: PathBuf,
 
-    /// The file's contents.
-    pub contents: String,
-
-    /// The unsafe cheatcodes found.
-    pub cheatcodes: UnsafeCheatcodes,
+    /// The cheatcode calls found, excluding the allowed ones unless requested.
+    pub findings: Vec<Finding>,
 }
 
-/// Formats the metrics for a single file using [`fmt::Display`].
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct SolFileMetricsPrinter<'a, 'b> {
-    pub metrics: &'a SolFileMetrics,
-    pub root: &'b Path,
+impl SolFileMetrics {
+    /// The highest severity of the findings.
+    pub fn severity(&self) -> Option<GeigerSeverity> {
+        self.findings.iter().map(|finding| finding.severity).max()
+    }
 }
 
-impl<'a, 'b> fmt::Display for SolFileMetricsPrinter<'a, 'b> {
+impl fmt::Display for SolFileMetrics {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let SolFileMetricsPrinter { metrics, root } = *self;
-
-        let file = metrics.file.strip_prefix(root).unwrap_or(&metrics.file);
-
-        macro_rules! print_unsafe_fn {
-            ($($name:literal => $field:ident),*) => {$(
-                let $field = &metrics.cheatcodes.$field[..];
-                if !$field.is_empty() {
-                    writeln!(f, "  {}  {}", Paint::red(metrics.cheatcodes.$field.len()), Paint::red($name))?;
-
-                    for &loc in $field {
-                        let content = &metrics.contents[loc.range()];
-                        let (line, col) = offset_to_line_column(&metrics.contents, loc.start());
-                        let pos = format!("  --> {}:{}:{}", file.display(), line, col);
-                        writeln!(f,"{}", Paint::red(pos))?;
-                        for line in content.lines() {
-                            writeln!(f, "      {}", Paint::red(line))?;
-                        }
-                    }
-                }
-               )*};
-        }
+        let paint = |severity: Option<GeigerSeverity>, s: String| match severity {
+            Some(GeigerSeverity::Error) => Paint::red(s).to_string(),
+            Some(GeigerSeverity::Warn) => Paint::yellow(s).to_string(),
+            _ => s,
+        };
 
-        if !metrics.cheatcodes.is_empty() {
-            writeln!(
-                f,
-                "{}    {}",
-                Paint::red(metrics.cheatcodes.len()),
-                Paint::red(file.display())
-            )?;
-            print_unsafe_fn!(
-                "ffi" => ffi,
-                "readFile" => read_file,
-                "readLine" => read_line,
-                "writeFile" => write_file,
-                "writeLine" => write_line,
-                "removeFile" => remove_file,
-                "closeFile" => close_file,
-                "setEnv" => set_env,
-                "deriveKey" => derive_key
-            );
-        } else {
-            writeln!(f, "0    {}", file.display())?
+        let file = self.file.display();
+        writeln!(f, "{}", paint(self.severity(), format!("{}    {file}", self.findings.len())))?;
+        for finding in self.findings.iter() {
+            let severity = Some(finding.severity);
+            let via = finding.via.as_ref().map(|via| format!(" (via {via})")).unwrap_or_default();
+            let name = format!("  {}: {}{via}", finding.severity, finding.cheatcode);
+            writeln!(f, "{}", paint(severity, name))?;
+            let pos = format!("  --> {file}:{}:{}", finding.line, finding.column);
+            writeln!(f, "{}", paint(severity, pos))?;
+            for line in finding.snippet.lines() {
+                writeln!(f, "      {}", paint(severity, line.to_string()))?;
+            }
         }
-
         Ok(())
     }
 }
 
-/// Unsafe usage metrics collection.
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub struct UnsafeCheatcodes {
-    pub ffi: Vec<Loc>,
-    pub read_file: Vec<Loc>,
-    pub read_line: Vec<Loc>,
-    pub write_file: Vec<Loc>,
-    pub write_line: Vec<Loc>,
-    pub remove_file: Vec<Loc>,
-    pub close_file: Vec<Loc>,
-    pub set_env: Vec<Loc>,
-    pub derive_key: Vec<Loc>,
+/// Calls `f` for every AST node, depth-first.
+fn walk<'a>(value: &'a Value, f: &mut impl FnMut(&'a Value)) {
+    match value {
+        Value::Object(map) => {
+            if map.contains_key("nodeType") {
+                f(value);
+            }
+            map.values().for_each(|value| walk(value, f));
+        }
+        Value::Array(values) => values.iter().for_each(|value| walk(value, f)),
+        _ => {}
+    }
 }
 
-impl UnsafeCheatcodes {
-    /// Whether there are any unsafe calls.
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
-    }
+fn node_type(node: &Value) -> Option<&str> {
+    node["nodeType"].as_str()
+}
 
-    /// The total number of unsafe calls.
-    pub fn len(&self) -> usize {
-        self.ffi.len() +
-            self.read_file.len() +
-            self.read_line.len() +
-            self.write_file.len() +
-            self.write_line.len() +
-            self.close_file.len() +
-            self.set_env.len() +
-            self.derive_key.len() +
-            self.remove_file.len()
+fn node_id(node: &Value) -> Option<usize> {
+    node["id"].as_u64().map(|id| id as usize)
+}
+
+/// Returns the ID of the declaration called by a `FunctionCall` node.
+fn callee(node: &Value) -> Option<usize> {
+    if node_type(node) != Some("FunctionCall") {
+        return None
     }
+    let mut expression = &node["expression"];
+    // `vm.ffi{gas: 1}(args)`
+    if node_type(expression) == Some("FunctionCallOptions") {
+        expression = &expression["expression"];
+    }
+    expression["referencedDeclaration"].as_u64().map(|id| id as usize)
+}
+
+/// Returns the 1-based line and column of the byte offset.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = source.get(..offset).unwrap_or(source);
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (before.matches('\n').count() + 1, before.len() - line_start + 1)
+}
+
+/// Parses the `start:length:index` source location of a node.
+fn parse_src(src: &str) -> Option<(usize, usize)> {
+    let mut parts = src.split(':');
+    let start = parts.next()?.parse().ok()?;
+    let length = parts.next()?.parse().ok()?;
+    Some((start, length))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
-    #[test]
-    fn can_find_calls() {
-        let s = r"
-        contract A is Test {
-            function do_ffi() public {
-                string[] memory inputs = new string[](1);
-                vm.ffi(inputs);
-            }
-        }
-        ";
-
-        let count = find_cheatcodes_in_string(s).unwrap();
-        assert_eq!(count.ffi.len(), 1);
-        assert!(!count.is_empty());
+    fn call(id: usize, src: &str, referenced: usize) -> Value {
+        json!({
+            "id": id,
+            "nodeType": "FunctionCall",
+            "src": src,
+            "expression": {
+                "id": id + 1,
+                "nodeType": "MemberAccess",
+                "referencedDeclaration": referenced,
+            },
+        })
     }
 
     #[test]
-    fn can_find_call_in_assignment() {
-        let s = r"
-        contract A is Test {
-            function do_ffi() public {
-                string[] memory inputs = new string[](1);
-                bytes stuff = vm.ffi(inputs);
-            }
-        }
-        ";
+    fn can_find_aliased_and_wrapped_calls() {
+        let vm = json!({
+            "nodeType": "SourceUnit",
+            "nodes": [{
+                "id": 1,
+                "nodeType": "ContractDefinition",
+                "name": "Vm",
+                "nodes": [
+                    { "id": 2, "nodeType": "FunctionDefinition", "name": "ffi" },
+                    { "id": 3, "nodeType": "FunctionDefinition", "name": "etch" },
+                ],
+            }],
+        });
+        let source = "hevm.ffi(cmd);\n_etch(a, c);\n";
+        let test = json!({
+            "nodeType": "SourceUnit",
+            "nodes": [{
+                "id": 10,
+                "nodeType": "ContractDefinition",
+                "name": "Test",
+                "nodes": [
+                    {
+                        "id": 11,
+                        "nodeType": "FunctionDefinition",
+                        "name": "_etch",
+                        "body": { "nodeType": "Block", "statements": [call(12, "0:0:0", 3)] },
+                    },
+                    {
+                        "id": 20,
+                        "nodeType": "FunctionDefinition",
+                        "name": "test",
+                        "body": {
+                            "nodeType": "Block",
+                            "statements": [call(21, "0:13:0", 2), call(23, "15:11:0", 11)],
+                        },
+                    },
+                ],
+            }],
+        });
 
-        let count = find_cheatcodes_in_string(s).unwrap();
-        assert_eq!(count.ffi.len(), 1);
-        assert!(!count.is_empty());
+        let resolver = CheatcodeResolver::new([&vm, &test]);
+        let calls = resolver.find_calls(&test, source);
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].cheatcode, "etch");
+        assert_eq!(
+            calls[1],
+            CheatcodeCall {
+                cheatcode: "ffi".to_string(),
+                via: None,
+                line: 1,
+                column: 1,
+                snippet: "hevm.ffi(cmd)".to_string(),
+            }
+        );
+        assert_eq!(calls[2].cheatcode, "etch");
+        assert_eq!(calls[2].via.as_deref(), Some("_etch"));
+        assert_eq!(calls[2].line, 2);
     }
 }
@@ -1,19 +1,18 @@
 use clap::{Parser, ValueHint};
 use eyre::{Result, WrapErr};
 use foundry_cli::utils::LoadConfig;
-use foundry_compilers::Graph;
-use foundry_config::{impl_figment_convert_basic, Config};
+use foundry_common::{compile::ProjectCompiler, fs};
+use foundry_compilers::{artifacts::output_selection::OutputSelection, Graph};
+use foundry_config::{impl_figment_convert_basic, Config, GeigerSeverity};
 use itertools::Itertools;
-use rayon::prelude::*;
-use std::path::PathBuf;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
 use yansi::Paint;
 
-mod error;
-
 mod find;
-use find::{find_cheatcodes_in_file, SolFileMetricsPrinter};
-
-mod visitor;
+use find::{CheatcodeResolver, Finding, SolFileMetrics};
 
 /// CLI arguments for `forge geiger`.
 #[derive(Clone, Debug, Parser)]
@@ -36,7 +35,8 @@ pub struct GeigerArgs {
 
     /// Run in "check" mode.
     ///
-    /// The exit code of the program will be the number of unsafe cheatcodes found.
+    /// The exit code of the program will be 1 if the highest severity found is `warn`, and 2 if
+    /// it is `error`.
     #[arg(long)]
     pub check: bool,
 
@@ -50,8 +50,14 @@ pub struct GeigerArgs {
     ignore: Vec<PathBuf>,
 
     /// Print a report of all files, even if no unsafe functions are found.
+    ///
+    /// Allowed cheatcodes are reported as well.
     #[arg(long)]
     full: bool,
+
+    /// Print the cheatcodes found as JSON.
+    #[arg(long)]
+    json: bool,
 }
 
 impl_figment_convert_basic!(GeigerArgs);
@@ -86,34 +92,88 @@ impl GeigerArgs {
         Ok(sources)
     }
 
-    pub fn run(self) -> Result<usize> {
+    /// Scans the sources and returns the highest severity of the cheatcodes found, if any.
+    pub fn run(self) -> Result<Option<GeigerSeverity>> {
         let config = self.try_load_config_emit_warnings()?;
         let sources = self.sources(&config).wrap_err("Failed to resolve files")?;
 
-        if config.ffi {
+        if config.ffi && !self.json {
             eprintln!("{}\n", Paint::red("ffi enabled"));
         }
 
-        let root = config.__root.0;
-
-        let sum = sources
-            .par_iter()
-            .map(|file| match find_cheatcodes_in_file(file) {
-                Ok(metrics) => {
-                    let len = metrics.cheatcodes.len();
-                    let printer = SolFileMetricsPrinter { metrics: &metrics, root: &root };
-                    if self.full || len == 0 {
-                        eprint!("{printer}");
-                    }
-                    len
+        let root = config.__root.0.clone();
+        let cwd = std::env::current_dir()?;
+        let sources = sources
+            .into_iter()
+            .map(|path| dunce::canonicalize(cwd.join(&path)).unwrap_or(path))
+            .collect::<Vec<_>>();
+
+        // only the ASTs are needed, so the sources are analyzed without generating code or writing
+        // artifacts
+        let mut project = config.ephemeral_no_artifacts_project()?;
+        project.solc_config.settings.output_selection = OutputSelection::from(BTreeMap::from([(
+            "*".to_string(),
+            BTreeMap::from([
+                (String::new(), vec!["ast".to_string()]),
+                ("*".to_string(), vec!["abi".to_string()]),
+            ]),
+        )]));
+        let mut compiler = ProjectCompiler::new().quiet(true);
+        if !self.paths.is_empty() {
+            compiler = compiler.files(sources.clone());
+        }
+        let output = compiler.compile(&project)?;
+
+        // node IDs are only unique within a compilation, so the ASTs are grouped by solc version
+        let mut compilations = HashMap::<_, BTreeMap<_, _>>::new();
+        for (id, artifact) in output.artifact_ids() {
+            let Some(ast) = &artifact.ast else { continue };
+            let asts = compilations.entry(id.version.clone()).or_default();
+            let path = root.join(&id.source);
+            if !asts.contains_key(&path) {
+                asts.insert(path, serde_json::to_value(ast)?);
+            }
+        }
+
+        let mut metrics = BTreeMap::<PathBuf, SolFileMetrics>::new();
+        for asts in compilations.values() {
+            let resolver = CheatcodeResolver::new(asts.values());
+            for (path, ast) in asts.iter() {
+                let file = path.strip_prefix(&root).unwrap_or(path).to_path_buf();
+                // a file compiled with multiple versions is only scanned once
+                if !sources.contains(path) || metrics.contains_key(&file) {
+                    continue
                 }
-                Err(err) => {
-                    eprintln!("{err}");
-                    0
+
+                let contents = fs::read_to_string(path)?;
+                let findings = resolver
+                    .find_calls(ast, &contents)
+                    .into_iter()
+                    .map(|call| {
+                        let severity = config.geiger.severity(&call.cheatcode, &file);
+                        Finding::new(&file, call, severity)
+                    })
+                    .filter(|finding| self.full || finding.severity > GeigerSeverity::Allow)
+                    .collect();
+                metrics.insert(file.clone(), SolFileMetrics { file, findings });
+            }
+        }
+
+        if self.json {
+            let findings = metrics.values().flat_map(|metrics| metrics.findings.iter());
+            println!("{}", serde_json::to_string_pretty(&findings.collect::<Vec<_>>())?);
+        } else {
+            for file_metrics in metrics.values() {
+                if self.full || !file_metrics.findings.is_empty() {
+                    eprint!("{file_metrics}");
                 }
-            })
-            .sum();
+            }
+        }
 
-        Ok(sum)
+        Ok(metrics
+            .values()
+            .filter_map(SolFileMetrics::severity)
+            .max()
+            .filter(|severity| *severity > GeigerSeverity::Allow))
     }
 }
@@ -5,9 +5,16 @@ use foundry_cli::{p_println, utils::Git};
 use foundry_common::fs;
 use foundry_compilers::remappings::Remapping;
 use foundry_config::Config;
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use template::{apply_template, parse_var, run_hooks, BuiltinTemplate, TemplateManifest};
 use yansi::Paint;
 
+mod template;
+
 /// CLI arguments for `forge init`.
 #[derive(Clone, Debug, Parser)]
 pub struct InitArgs {
@@ -16,25 +23,45 @@ pub struct InitArgs {
     root: PathBuf,
 
     /// The template to start from.
+    ///
+    /// Either a git repository, e.g. `foundry-rs/forge-template`, or a template bundled with
+    /// forge: `minimal`, `library` or `script`.
+    ///
+    /// If the repository has a `template.toml`, its `*.tmpl` files are rendered with the
+    /// template variables: `{{project_name}}`, `{{author}}`, and the variables it declares.
     #[arg(long, short)]
     template: Option<String>,
 
+    /// Set a template variable, e.g. `--var license=MIT`.
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_var, requires = "template")]
+    vars: Vec<(String, String)>,
+
+    /// Run the post-init hooks declared by the template, which can only run forge, cast and git.
+    #[arg(long, requires = "template")]
+    allow_hooks: bool,
+
     /// Branch argument that can only be used with template option.
     /// If not specified, the default branch is used.
     #[arg(long, short, requires = "template")]
     branch: Option<String>,
 
     /// Do not install dependencies from the network.
-    #[arg(long, conflicts_with = "template", visible_alias = "no-deps")]
+    ///
+    /// Can't be used with a git template.
+    #[arg(long, visible_alias = "no-deps")]
     offline: bool,
 
     /// Create the project even if the specified root directory is not empty.
-    #[arg(long, conflicts_with = "template")]
+    ///
+    /// Can't be used with a git template.
+    #[arg(long)]
     force: bool,
 
     /// Create a .vscode/settings.json file with Solidity settings, and generate a remappings.txt
     /// file.
-    #[arg(long, conflicts_with = "template")]
+    ///
+    /// Can't be used with a git template.
+    #[arg(long)]
     vscode: bool,
 
     #[command(flatten)]
@@ -43,20 +70,31 @@ pub struct InitArgs {
 
 impl InitArgs {
     pub fn run(self) -> Result<()> {
-        let InitArgs { root, template, branch, opts, offline, force, vscode } = self;
+        let InitArgs { root, template, vars, allow_hooks, branch, opts, offline, force, vscode } =
+            self;
         let DependencyInstallOpts { shallow, no_git, no_commit, quiet } = opts;
 
+        let builtin = template.as_deref().and_then(BuiltinTemplate::find);
+        if template.is_some() && builtin.is_none() && (offline || force || vscode) {
+            eyre::bail!("`--offline`, `--force` and `--vscode` can't be used with a git template")
+        }
+        if branch.is_some() && builtin.is_some() {
+            eyre::bail!("`--branch` can't be used with a bundled template")
+        }
+
         // create the root dir if it does not exist
         if !root.exists() {
             fs::create_dir_all(&root)?;
         }
         let root = dunce::canonicalize(root)?;
         let git = Git::new(&root).quiet(quiet).shallow(shallow);
+        let vars = template_vars(&root, vars);
+        let mut manifest = None;
 
-        // if a template is provided, then this command initializes a git repo,
+        // if a git template is provided, then this command initializes a git repo,
         // fetches the template repo, and resets the git history to the head of the fetched
         // repo with no other history
-        if let Some(template) = template {
+        if let Some(template) = template.filter(|_| builtin.is_none()) {
             let template = if template.contains("://") {
                 template
             } else {
@@ -86,6 +124,13 @@ impl InitArgs {
                 // if not shallow, initialize and clone submodules (without fetching latest)
                 git.submodule_update(false, false, true, true, std::iter::empty::<PathBuf>())?;
             }
+
+            // render the template, if it has a `template.toml`
+            manifest = apply_template(&root, vars)?;
+            if manifest.is_some() && !no_commit {
+                git.add(Some("--all"))?;
+                git.commit("chore: render template")?;
+            }
         } else {
             // if target is not empty
             if root.read_dir().map_or(false, |mut i| i.next().is_some()) {
@@ -116,18 +161,24 @@ impl InitArgs {
             let script = root.join("script");
             fs::create_dir_all(&script)?;
 
-            // write the contract file
-            let contract_path = src.join("Counter.sol");
-            fs::write(contract_path, include_str!("../../assets/CounterTemplate.sol"))?;
-            // write the tests
-            let contract_path = test.join("Counter.t.sol");
-            fs::write(contract_path, include_str!("../../assets/CounterTemplate.t.sol"))?;
-            // write the script
-            let contract_path = script.join("Counter.s.sol");
-            fs::write(contract_path, include_str!("../../assets/CounterTemplate.s.sol"))?;
-            // Write the default README file
-            let readme_path = root.join("README.md");
-            fs::write(readme_path, include_str!("../../assets/README.md"))?;
+            if let Some(builtin) = builtin {
+                // write and render the bundled template
+                builtin.write(&root)?;
+                manifest = apply_template(&root, vars)?;
+            } else {
+                // write the contract file
+                let contract_path = src.join("Counter.sol");
+                fs::write(contract_path, include_str!("../../../assets/CounterTemplate.sol"))?;
+                // write the tests
+                let contract_path = test.join("Counter.t.sol");
+                fs::write(contract_path, include_str!("../../../assets/CounterTemplate.t.sol"))?;
+                // write the script
+                let contract_path = script.join("Counter.s.sol");
+                fs::write(contract_path, include_str!("../../../assets/CounterTemplate.s.sol"))?;
+                // Write the default README file
+                let readme_path = root.join("README.md");
+                fs::write(readme_path, include_str!("../../../assets/README.md"))?;
+            }
 
             // write foundry.toml, if it doesn't exist already
             let dest = root.join(Config::FILE_NAME);
@@ -159,11 +210,35 @@ impl InitArgs {
             }
         }
 
+        if let Some(TemplateManifest { hooks, .. }) = manifest {
+            run_hooks(&root, &hooks, allow_hooks)?;
+        }
+
         p_println!(!quiet => "    {} forge project",  Paint::green("Initialized"));
         Ok(())
     }
 }
 
+/// Returns the template variables: the project name, the git author, and the `--var`s.
+fn template_vars(root: &Path, vars: Vec<(String, String)>) -> BTreeMap<String, String> {
+    let mut template_vars = BTreeMap::new();
+    if let Some(name) = root.file_name() {
+        template_vars.insert("project_name".to_string(), name.to_string_lossy().into_owned());
+    }
+    let author = Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|author| !author.is_empty());
+    if let Some(author) = author {
+        template_vars.insert("author".to_string(), author);
+    }
+    template_vars.extend(vars);
+    template_vars
+}
+
 /// Returns the commit hash of the project if it exists
 pub fn get_commit_hash(root: &Path) -> Option<String> {
     Git::new(root).commit_hash(true, "HEAD").ok()
@@ -183,14 +258,14 @@ fn init_git_repo(git: Git<'_>, no_commit: bool) -> Result<()> {
     // .gitignore
     let gitignore = git.root.join(".gitignore");
     if !gitignore.exists() {
-        fs::write(gitignore, include_str!("../../assets/.gitignoreTemplate"))?;
+        fs::write(gitignore, include_str!("../../../assets/.gitignoreTemplate"))?;
     }
 
     // github workflow
     let workflow = git.root.join(".github/workflows/test.yml");
     if !workflow.exists() {
         fs::create_dir_all(workflow.parent().unwrap())?;
-        fs::write(workflow, include_str!("../../assets/workflowTemplate.yml"))?;
+        fs::write(workflow, include_str!("../../../assets/workflowTemplate.yml"))?;
     }
 
     // commit everything
@@ -0,0 +1,326 @@
+//! Templates of `forge init --template`.
+//!
+//! A template is a git repository, or one of the templates bundled with forge. If it has a
+//! `template.toml` at its root, the `*.tmpl` files are rendered with the template variables,
+//! e.g. `{{project_name}}`, and saved without their extension.
+
+use eyre::Result;
+use foundry_common::fs;
+use foundry_config::figment::{
+    providers::{Format, Toml},
+    Figment,
+};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The manifest of a template.
+pub const MANIFEST: &str = "template.toml";
+
+/// The extension of the files rendered with the template variables.
+const TEMPLATE_EXTENSION: &str = "tmpl";
+
+/// The programs the post-init hooks of a template are allowed to run.
+const HOOK_PROGRAMS: &[&str] = &["forge", "cast", "git"];
+
+/// The declarations of `template.toml`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct TemplateManifest {
+    /// The variables of the template, in addition to `project_name` and `author`.
+    #[serde(default)]
+    pub variables: BTreeMap<String, TemplateVariable>,
+    /// The commands run after the project is initialized, only with `--allow-hooks`.
+    ///
+    /// Only commands of the programs in [HOOK_PROGRAMS] are allowed.
+    #[serde(default)]
+    pub hooks: Vec<String>,
+}
+
+/// A variable of a template, required unless it has a default value.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct TemplateVariable {
+    pub description: Option<String>,
+    pub default: Option<String>,
+}
+
+/// A template bundled with forge, available offline.
+#[derive(Clone, Copy, Debug)]
+pub struct BuiltinTemplate {
+    pub name: &'static str,
+    /// The files of the template, by path relative to the project root.
+    files: &'static [(&'static str, &'static str)],
+}
+
+/// The templates bundled with forge.
+pub const BUILTIN_TEMPLATES: &[BuiltinTemplate] = &[
+    BuiltinTemplate {
+        name: "minimal",
+        files: &[
+            (MANIFEST, include_str!("../../../assets/templates/minimal/template.toml")),
+            ("README.md.tmpl", include_str!("../../../assets/templates/minimal/README.md.tmpl")),
+            (
+                "src/Contract.sol.tmpl",
+                include_str!("../../../assets/templates/minimal/Contract.sol.tmpl"),
+            ),
+        ],
+    },
+    BuiltinTemplate {
+        name: "library",
+        files: &[
+            (MANIFEST, include_str!("../../../assets/templates/library/template.toml")),
+            ("README.md.tmpl", include_str!("../../../assets/templates/library/README.md.tmpl")),
+            ("src/Math.sol.tmpl", include_str!("../../../assets/templates/library/Math.sol.tmpl")),
+            (
+                "test/Math.t.sol.tmpl",
+                include_str!("../../../assets/templates/library/Math.t.sol.tmpl"),
+            ),
+        ],
+    },
+    BuiltinTemplate {
+        name: "script",
+        files: &[
+            (MANIFEST, include_str!("../../../assets/templates/script/template.toml")),
+            ("README.md.tmpl", include_str!("../../../assets/templates/script/README.md.tmpl")),
+            (".env.example", include_str!("../../../assets/templates/script/env.example")),
+            (
+                "src/Counter.sol.tmpl",
+                include_str!("../../../assets/templates/script/Counter.sol.tmpl"),
+            ),
+            (
+                "test/Counter.t.sol.tmpl",
+                include_str!("../../../assets/templates/script/Counter.t.sol.tmpl"),
+            ),
+            (
+                "script/Deploy.s.sol.tmpl",
+                include_str!("../../../assets/templates/script/Deploy.s.sol.tmpl"),
+            ),
+            (
+                "script/Interact.s.sol.tmpl",
+                include_str!("../../../assets/templates/script/Interact.s.sol.tmpl"),
+            ),
+        ],
+    },
+];
+
+impl BuiltinTemplate {
+    /// Returns the bundled template with the given name.
+    pub fn find(name: &str) -> Option<&'static Self> {
+        BUILTIN_TEMPLATES.iter().find(|template| template.name == name)
+    }
+
+    /// Writes the files of the template into `root`.
+    pub fn write(&self, root: &Path) -> Result<()> {
+        for (path, content) in self.files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, content)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the template written into `root` with `vars`, and returns its manifest.
+///
+/// Does nothing if `root` has no `template.toml`, which is removed after rendering.
+pub fn apply_template(
+    root: &Path,
+    mut vars: BTreeMap<String, String>,
+) -> Result<Option<TemplateManifest>> {
+    let manifest_path = root.join(MANIFEST);
+    if !manifest_path.is_file() {
+        return Ok(None)
+    }
+    let manifest: TemplateManifest = Figment::from(Toml::file(&manifest_path))
+        .extract()
+        .map_err(|err| eyre::eyre!("invalid {MANIFEST}: {err}"))?;
+
+    let mut missing = vec![];
+    for (name, variable) in &manifest.variables {
+        if vars.contains_key(name) {
+            continue
+        }
+        match &variable.default {
+            Some(default) => {
+                vars.insert(name.clone(), default.clone());
+            }
+            None => missing.push(match &variable.description {
+                Some(description) => format!("  {name}: {description}"),
+                None => format!("  {name}"),
+            }),
+        }
+    }
+    if !missing.is_empty() {
+        eyre::bail!(
+            "missing template variables, pass them with `--var name=value`:\n{}",
+            missing.join("\n")
+        )
+    }
+
+    for path in template_files(root) {
+        let content = fs::read_to_string(&path)?;
+        let rendered = render(&content, &vars)
+            .map_err(|err| eyre::eyre!("could not render {}: {err}", path.display()))?;
+        fs::write(path.with_extension(""), rendered)?;
+        fs::remove_file(&path)?;
+    }
+    fs::remove_file(&manifest_path)?;
+
+    Ok(Some(manifest))
+}
+
+/// Runs the hooks of the template in `root`, or lists them if they're not allowed.
+///
+/// Fails without running any of them if a hook runs a program outside of [HOOK_PROGRAMS].
+pub fn run_hooks(root: &Path, hooks: &[String], allow: bool) -> Result<()> {
+    if hooks.is_empty() {
+        return Ok(())
+    }
+    let disallowed = hooks
+        .iter()
+        .filter(|hook| {
+            hook.split_whitespace()
+                .next()
+                .map_or(false, |program| !HOOK_PROGRAMS.contains(&program))
+        })
+        .map(|hook| format!("\n  {hook}"))
+        .collect::<String>();
+    if !disallowed.is_empty() {
+        eyre::bail!(
+            "the post-init hooks of the template can only run {}, found:{disallowed}",
+            HOOK_PROGRAMS.join(", ")
+        )
+    }
+    if !allow {
+        println!(
+            "Skipping the post-init hooks of the template, pass `--allow-hooks` to run them:\n  {}",
+            hooks.join("\n  ")
+        );
+        return Ok(())
+    }
+    for hook in hooks {
+        let mut args = hook.split_whitespace();
+        let Some(program) = args.next() else { continue };
+        println!("Running `{hook}`...");
+        let status = Command::new(program).args(args).current_dir(root).status()?;
+        if !status.success() {
+            eyre::bail!("post-init hook `{hook}` failed with {status}")
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the `{{name}}` placeholders of `content` with the value of the variable.
+pub fn render(content: &str, vars: &BTreeMap<String, String>) -> Result<String> {
+    let mut rendered = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else { break };
+        let name = rest[start + 2..start + end].trim();
+        let value =
+            vars.get(name).ok_or_else(|| eyre::eyre!("undefined template variable `{name}`"))?;
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(value);
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Parses a `--var name=value` template variable.
+pub fn parse_var(s: &str) -> Result<(String, String)> {
+    let (name, value) =
+        s.split_once('=').ok_or_else(|| eyre::eyre!("expected `name=value`, got `{s}`"))?;
+    Ok((name.trim().to_string(), value.to_string()))
+}
+
+/// Returns the `*.tmpl` files in `root`, outside of `.git` and `lib`.
+fn template_files(root: &Path) -> Vec<PathBuf> {
+    let (git, lib) = (root.join(".git"), root.join("lib"));
+    fs::files_with_ext(root, TEMPLATE_EXTENSION)
+        .into_iter()
+        .filter(|path| !path.starts_with(&git) && !path.starts_with(&lib))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(vars: &[(&str, &str)]) -> BTreeMap<String, String> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn renders_variables() {
+        let vars = vars(&[("project_name", "counter"), ("license", "MIT")]);
+        assert_eq!(
+            render("// SPDX-License-Identifier: {{license}}\n# {{ project_name }}", &vars).unwrap(),
+            "// SPDX-License-Identifier: MIT\n# counter"
+        );
+        assert_eq!(render("no placeholders {{", &vars).unwrap(), "no placeholders {{");
+        assert!(render("{{author}}", &vars).is_err());
+    }
+
+    #[test]
+    fn parses_vars() {
+        assert_eq!(parse_var("token=ABC").unwrap(), ("token".to_string(), "ABC".to_string()));
+        assert_eq!(parse_var("url=a=b").unwrap(), ("url".to_string(), "a=b".to_string()));
+        assert!(parse_var("token").is_err());
+    }
+
+    #[test]
+    fn applies_builtin_templates() {
+        for template in BUILTIN_TEMPLATES {
+            let root = tempfile::tempdir().unwrap();
+            template.write(root.path()).unwrap();
+            let vars = vars(&[("project_name", "counter"), ("author", "alice")]);
+            let manifest = apply_template(root.path(), vars).unwrap();
+            assert!(manifest.is_some(), "{}", template.name);
+
+            assert!(!root.path().join(MANIFEST).exists());
+            assert!(template_files(root.path()).is_empty());
+            let readme = fs::read_to_string(root.path().join("README.md")).unwrap();
+            assert!(readme.contains("## counter"), "{readme}");
+        }
+    }
+
+    #[test]
+    fn requires_variables() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join(MANIFEST),
+            "hooks = [\"forge build\"]\n\n[variables.token]\ndescription = \"The token symbol\"\n",
+        )
+        .unwrap();
+        fs::write(root.path().join("Token.sol.tmpl"), "// {{token}}").unwrap();
+
+        let err = apply_template(root.path(), BTreeMap::new()).unwrap_err();
+        assert!(err.to_string().contains("token: The token symbol"), "{err}");
+
+        let manifest = apply_template(root.path(), vars(&[("token", "ABC")])).unwrap().unwrap();
+        assert_eq!(manifest.hooks, ["forge build"]);
+        assert_eq!(fs::read_to_string(root.path().join("Token.sol")).unwrap(), "// ABC");
+    }
+
+    #[test]
+    fn ignores_plain_repositories() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("README.md.tmpl"), "{{project_name}}").unwrap();
+        assert_eq!(apply_template(root.path(), BTreeMap::new()).unwrap(), None);
+        assert!(root.path().join("README.md.tmpl").exists());
+    }
+
+    #[test]
+    fn rejects_disallowed_hooks() {
+        let root = tempfile::tempdir().unwrap();
+        let hooks = ["git init".to_string(), "touch hooked".to_string()];
+        let err = run_hooks(root.path(), &hooks, true).unwrap_err();
+        assert!(err.to_string().contains("touch hooked"), "{err}");
+        assert!(!err.to_string().contains("git init"), "{err}");
+        assert!(!root.path().join(".git").exists());
+        assert!(!root.path().join("hooked").exists());
+    }
+}
@@ -1,8 +1,14 @@
 use clap::Parser;
 use comfy_table::{presets::ASCII_MARKDOWN, Table};
 use eyre::Result;
-use foundry_cli::opts::{CompilerArgs, CoreBuildArgs};
-use foundry_common::compile::ProjectCompiler;
+use foundry_cli::{
+    opts::{CompilerArgs, CoreBuildArgs},
+    utils::{self, LoadConfig},
+};
+use foundry_common::{
+    compile::ProjectCompiler,
+    vyper::{compile_vyper, is_vyper_file},
+};
 use foundry_compilers::{
     artifacts::{
         output_selection::{
@@ -62,16 +68,24 @@ impl InspectArgs {
 
         // Build the project
         let project = modified_build_args.project()?;
-        let mut compiler = ProjectCompiler::new().quiet(true);
-        if let Some(contract_path) = &mut contract.path {
-            let target_path = canonicalize(&*contract_path)?;
-            *contract_path = target_path.to_string_lossy().to_string();
-            compiler = compiler.files([target_path]);
-        }
-        let output = compiler.compile(&project)?;
+        let (solc_output, vyper_output);
+        let artifact = if contract.path.as_ref().map_or(false, is_vyper_file) {
+            let config = modified_build_args.try_load_config_emit_warnings()?;
+            vyper_output = utils::block_on(compile_vyper(&config, true))?;
+            vyper_output.as_ref().and_then(|output| output.find_contract(project.root(), &contract))
+        } else {
+            let mut compiler = ProjectCompiler::new().quiet(true);
+            if let Some(contract_path) = &mut contract.path {
+                let target_path = canonicalize(&*contract_path)?;
+                *contract_path = target_path.to_string_lossy().to_string();
+                compiler = compiler.files([target_path]);
+            }
+            solc_output = compiler.compile(&project)?;
+            solc_output.find_contract(&contract)
+        };
 
         // Find the artifact
-        let artifact = output.find_contract(&contract).ok_or_else(|| {
+        let artifact = artifact.ok_or_else(|| {
             eyre::eyre!("Could not find artifact `{contract}` in the compiled artifacts")
         })?;
 
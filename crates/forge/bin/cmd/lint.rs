@@ -0,0 +1,325 @@
+use super::tree::normalize;
+use clap::{Parser, ValueEnum};
+use eyre::Result;
+use foundry_cli::{opts::ProjectPathsArgs, utils::LoadConfig};
+use foundry_common::fs;
+use foundry_compilers::{remappings::Remapping, utils::source_files_iter};
+use foundry_config::Config;
+use itertools::Itertools;
+use solang_parser::pt::{Import, ImportPath, SourceUnitPart};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+/// CLI arguments for `forge lint`.
+///
+/// Rewrites the imports that don't resolve to the file with the same name in the project. An
+/// import is only rewritten if a single file matches its name, or the end of its path.
+#[derive(Clone, Debug, Parser)]
+pub struct LintArgs {
+    /// The style of the rewritten import paths.
+    #[arg(long, value_enum, default_value_t = ImportStyle::Relative)]
+    style: ImportStyle,
+
+    /// Only report the imports that would be rewritten, and exit with 1 if any import is broken.
+    #[arg(long)]
+    check: bool,
+
+    /// Also rewrite the imports of the files outside of the source, script and test directories.
+    #[arg(long)]
+    all: bool,
+
+    #[command(flatten)]
+    opts: ProjectPathsArgs,
+}
+
+foundry_config::impl_figment_convert!(LintArgs, opts);
+
+/// The style of a rewritten import path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ImportStyle {
+    /// Relative to the importing file, e.g. `../src/Counter.sol`.
+    #[default]
+    Relative,
+    /// Through the longest matching remapping, or relative to the project root otherwise, e.g.
+    /// `@openzeppelin/contracts/token/ERC20/ERC20.sol` or `src/Counter.sol`.
+    Remapped,
+}
+
+impl LintArgs {
+    /// Fixes the imports, and returns whether the check failed.
+    pub fn run(self) -> Result<bool> {
+        let config = self.try_load_config_emit_warnings()?;
+        let root = &config.__root.0;
+        let project_dirs = [&config.src, &config.script, &config.test];
+
+        let mut files = project_dirs
+            .iter()
+            .flat_map(|dir| source_files_iter(root.join(dir)))
+            .map(normalize)
+            .collect_vec();
+        let mut candidates = files.clone();
+        for lib in config.libs.iter() {
+            let lib_files = source_files_iter(root.join(lib)).map(normalize).collect_vec();
+            if self.all {
+                files.extend(lib_files.iter().cloned());
+            }
+            candidates.extend(lib_files);
+        }
+        files.sort();
+        files.dedup();
+        candidates.sort();
+        candidates.dedup();
+
+        let mut by_name: HashMap<&OsStr, Vec<&PathBuf>> = HashMap::new();
+        for candidate in candidates.iter() {
+            if let Some(name) = candidate.file_name() {
+                by_name.entry(name).or_default().push(candidate);
+            }
+        }
+
+        let resolver = ImportResolver::new(&config);
+        let display = |path: &Path| path.strip_prefix(root).unwrap_or(path).display().to_string();
+        let (mut rewritten, mut ambiguous, mut unresolvable) = (Vec::new(), Vec::new(), Vec::new());
+        let mut unparsable = Vec::new();
+        for file in files.iter() {
+            let content = fs::read_to_string(file)?;
+            let Some(imports) = import_paths(&content) else {
+                unparsable.push(display(file));
+                continue
+            };
+            let mut fixed = String::with_capacity(content.len());
+            let mut last = 0;
+            for (range, import) in imports {
+                if resolver.resolve(file, import).exists() {
+                    continue
+                }
+
+                let matches = best_matches(import, &by_name, file);
+                let target = match matches.as_slice() {
+                    [target] => target,
+                    [] => {
+                        unresolvable.push(format!("{}: \"{import}\"", display(file)));
+                        continue
+                    }
+                    _ => {
+                        ambiguous.push(format!(
+                            "{}: \"{import}\" matches {}",
+                            display(file),
+                            matches.iter().map(|path| display(path)).join(", ")
+                        ));
+                        continue
+                    }
+                };
+
+                let new_import = match self.style {
+                    ImportStyle::Relative => relative_import(file, target),
+                    ImportStyle::Remapped => remapped_import(&config, file, target),
+                };
+                rewritten.push(format!("{}: \"{import}\" -> \"{new_import}\"", display(file)));
+                fixed.push_str(&content[last..range.start]);
+                fixed.push_str(&new_import);
+                last = range.end;
+            }
+
+            if last > 0 && !self.check {
+                fixed.push_str(&content[last..]);
+                fs::write(file, fixed)?;
+            }
+        }
+
+        let verb = if self.check { "Would rewrite" } else { "Rewrote" };
+        println!("{verb} {} import(s).", rewritten.len());
+        for line in rewritten.iter() {
+            println!("  {line}");
+        }
+        if !ambiguous.is_empty() {
+            println!("{} ambiguous import(s):", ambiguous.len());
+            for line in ambiguous.iter() {
+                println!("  {line}");
+            }
+        }
+        if !unresolvable.is_empty() {
+            println!("{} unresolvable import(s):", unresolvable.len());
+            for line in unresolvable.iter() {
+                println!("  {line}");
+            }
+        }
+        if !unparsable.is_empty() {
+            println!("Skipped {} file(s) that failed to parse:", unparsable.len());
+            for line in unparsable.iter() {
+                println!("  {line}");
+            }
+        }
+
+        Ok(self.check && !(rewritten.is_empty() && ambiguous.is_empty() && unresolvable.is_empty()))
+    }
+}
+
+/// Returns the paths of the import directives of the source, along with their byte ranges, or
+/// `None` if it fails to parse.
+///
+/// The source is parsed rather than matched, so imports in comments and strings are left alone.
+fn import_paths(src: &str) -> Option<Vec<(Range<usize>, &str)>> {
+    let (unit, _) = solang_parser::parse(src, 0).ok()?;
+    let imports = unit
+        .0
+        .iter()
+        .filter_map(|part| {
+            let SourceUnitPart::ImportDirective(import) = part else { return None };
+            let path = match import {
+                Import::Plain(path, _) => path,
+                Import::GlobalSymbol(path, _, _) => path,
+                Import::Rename(path, _, _) => path,
+            };
+            let ImportPath::Filename(literal) = path else { return None };
+            // the location of the literal includes its quotes
+            let quoted = src.get(literal.loc.start()..literal.loc.end())?;
+            let start = literal.loc.start() + quoted.find(literal.string.as_str())?;
+            let range = start..start + literal.string.len();
+            Some((range.clone(), &src[range]))
+        })
+        .collect();
+    Some(imports)
+}
+
+/// Returns the files named like the import, other than the importing file, that share the longest
+/// end of its path.
+fn best_matches<'a>(
+    import: &str,
+    by_name: &HashMap<&OsStr, Vec<&'a PathBuf>>,
+    importer: &Path,
+) -> Vec<&'a PathBuf> {
+    let import = Path::new(import);
+    let Some(candidates) = import.file_name().and_then(|name| by_name.get(name)) else {
+        return Vec::new()
+    };
+    let shared_suffix = |path: &Path| {
+        path.components().rev().zip(import.components().rev()).take_while(|(a, b)| a == b).count()
+    };
+    let candidates =
+        candidates.iter().copied().filter(|path| path.as_path() != importer).collect_vec();
+    let Some(longest) = candidates.iter().map(|path| shared_suffix(path)).max() else {
+        return Vec::new()
+    };
+    candidates.into_iter().filter(|path| shared_suffix(path) == longest).collect()
+}
+
+/// Returns the path of `target` relative to the directory of `importer`.
+fn relative_import(importer: &Path, target: &Path) -> String {
+    let dir = importer.parent().unwrap_or(importer);
+    let common = dir.components().zip(target.components()).take_while(|(a, b)| a == b).count();
+    let ups = dir.components().count() - common;
+    let mut path = if ups == 0 { PathBuf::from(".") } else { PathBuf::new() };
+    path.extend(std::iter::repeat(Component::ParentDir).take(ups));
+    path.extend(target.components().skip(common));
+    to_import(&path)
+}
+
+/// Returns the import of `target` through the remapping with the longest path that contains it, or
+/// its path relative to the project root.
+fn remapped_import(config: &Config, importer: &Path, target: &Path) -> String {
+    let root = &config.__root.0;
+    let relative_importer = importer.strip_prefix(root).unwrap_or(importer);
+    let remapped = config
+        .remappings
+        .iter()
+        .map(|remapping| Remapping::from(remapping.clone()))
+        .filter(|remapping| {
+            remapping.context.as_ref().map_or(true, |context| {
+                relative_importer.to_string_lossy().starts_with(context.as_str())
+            })
+        })
+        .filter_map(|remapping| {
+            let path = normalize(root.join(&remapping.path));
+            let rest = target.strip_prefix(&path).ok()?;
+            Some((path.components().count(), format!("{}{}", remapping.name, to_import(rest))))
+        })
+        .max_by_key(|(len, _)| *len);
+    match remapped {
+        Some((_, import)) => import,
+        None => to_import(target.strip_prefix(root).unwrap_or(target)),
+    }
+}
+
+/// Formats a path as an import path, with forward slashes.
+fn to_import(path: &Path) -> String {
+    path.components().map(|component| component.as_os_str().to_string_lossy()).join("/")
+}
+
+/// Resolves import paths like solc and the compiler's graph resolver.
+struct ImportResolver {
+    root: PathBuf,
+    libs: Vec<PathBuf>,
+    remappings: Vec<Remapping>,
+}
+
+impl ImportResolver {
+    fn new(config: &Config) -> Self {
+        Self {
+            root: config.__root.0.clone(),
+            libs: config.libs.clone(),
+            remappings: config.remappings.iter().map(|r| r.clone().into()).collect(),
+        }
+    }
+
+    /// Returns the resolved path of the import.
+    fn resolve(&self, importer: &Path, import: &str) -> PathBuf {
+        let importer_dir = importer.parent().unwrap_or(&self.root);
+        if import.starts_with("./") || import.starts_with("../") {
+            return normalize(importer_dir.join(import))
+        }
+
+        // the longest matching context wins, then the longest matching prefix
+        let relative_importer = importer.strip_prefix(&self.root).unwrap_or(importer);
+        let remapping = self
+            .remappings
+            .iter()
+            .filter(|remapping| {
+                import.starts_with(&remapping.name) &&
+                    remapping.context.as_ref().map_or(true, |context| {
+                        relative_importer.to_string_lossy().starts_with(context.as_str())
+                    })
+            })
+            .max_by_key(|remapping| {
+                (remapping.context.as_ref().map_or(0, String::len), remapping.name.len())
+            });
+        if let Some(remapping) = remapping {
+            let path = Path::new(&remapping.path).join(&import[remapping.name.len()..]);
+            return normalize(self.root.join(path))
+        }
+
+        let path = self.root.join(import);
+        if path.exists() {
+            return normalize(path)
+        }
+        for lib in self.libs.iter() {
+            let path = self.root.join(lib).join(import);
+            if path.exists() {
+                return normalize(path)
+            }
+        }
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_build_relative_imports() {
+        let importer = Path::new("/project/test/Counter.t.sol");
+        assert_eq!(
+            relative_import(importer, Path::new("/project/src/Counter.sol")),
+            "../src/Counter.sol"
+        );
+        assert_eq!(
+            relative_import(importer, Path::new("/project/test/utils/Utils.sol")),
+            "./utils/Utils.sol"
+        );
+    }
+}
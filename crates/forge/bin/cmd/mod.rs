@@ -42,6 +42,7 @@
 pub mod bind;
 pub mod build;
 pub mod cache;
+pub mod clone;
 pub mod config;
 pub mod coverage;
 pub mod create;
@@ -49,16 +50,20 @@ pub mod debug;
 pub mod doc;
 pub mod flatten;
 pub mod fmt;
+pub mod gas_diff;
 pub mod geiger;
 pub mod generate;
 pub mod init;
 pub mod inspect;
 pub mod install;
+pub mod lint;
+pub mod pipeline;
 pub mod remappings;
 pub mod remove;
 pub mod script;
 pub mod selectors;
 pub mod snapshot;
+pub mod storage_check;
 pub mod test;
 pub mod tree;
 pub mod update;
@@ -0,0 +1,225 @@
+//! `forge watch`: runs a pipeline of forge commands whenever the project changes.
+
+use super::{test::ChangedTests, watch::WatchArgs};
+use clap::Parser;
+use eyre::Result;
+use foundry_config::Config;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+use yansi::Paint;
+
+/// Describes the files the watcher passes to the pipeline, see [ChangedTests::from_files].
+const SINCE_LAST_RUN: &str = "the last run";
+
+/// CLI arguments for `forge watch`.
+#[derive(Clone, Debug, Parser)]
+pub struct PipelineArgs {
+    /// A stage of the pipeline: the arguments of a forge command, e.g. `--run 'fmt --check'`.
+    ///
+    /// The stages run in the given order, and the pipeline stops at the first stage that fails.
+    /// Arguments are split on whitespace.
+    ///
+    /// In a `test` stage, `--changed` without a revision only runs the tests that import, directly
+    /// or transitively, a file that changed since the last run. `build` stages only recompile
+    /// the changed files and the files that import them.
+    #[arg(long = "run", value_name = "COMMAND", required = true)]
+    pub stages: Vec<String>,
+
+    /// Run the pipeline once and exit, instead of watching for changes.
+    #[arg(long)]
+    pub once: bool,
+
+    /// The files that changed since the last run, passed by the watcher.
+    #[arg(long = "changed-file", value_name = "PATH", hide = true)]
+    pub changed_files: Vec<PathBuf>,
+
+    #[command(flatten)]
+    pub watch: WatchArgs,
+}
+
+impl PipelineArgs {
+    /// Runs the stages of the pipeline, and exits with the status of the first failing stage.
+    pub fn run(self) -> Result<()> {
+        let stages =
+            self.stages.iter().map(|stage| parse_stage(stage)).collect::<Result<Vec<_>>>()?;
+
+        let config = Config::load();
+        let root = &config.__root.0;
+        let changed = self.changed_tests(&config, &stages)?;
+
+        let forge = std::env::current_exe()?;
+        for (i, stage) in stages.iter().enumerate() {
+            let mut args = stage.clone();
+            if let Some(changed) = &changed {
+                if let Some(pos) = changed_flag_position(&args).filter(|_| is_test_stage(&args)) {
+                    println!("{}", changed.display(root));
+                    match select_changed_tests(args, pos, changed, root) {
+                        Some(selected) => args = selected,
+                        None => continue,
+                    }
+                }
+            }
+
+            println!(
+                "{}",
+                Paint::cyan(format!("[{}/{}] forge {}", i + 1, stages.len(), args.join(" ")))
+                    .bold()
+            );
+            let status = Command::new(&forge).args(&args).status()?;
+            if !status.success() {
+                let skipped =
+                    stages[i + 1..].iter().map(|stage| stage.join(" ")).collect::<Vec<_>>();
+                eprintln!(
+                    "\n{} `forge {}` failed{}",
+                    Paint::red("Pipeline failed:").bold(),
+                    args.join(" "),
+                    if skipped.is_empty() {
+                        String::new()
+                    } else {
+                        format!(", skipped: {}", skipped.join(", "))
+                    }
+                );
+                std::process::exit(status.code().unwrap_or(1));
+            }
+        }
+
+        println!("\n{}", Paint::green(format!("Pipeline passed: {} stages", stages.len())).bold());
+        Ok(())
+    }
+
+    /// Returns the tests affected by the files changed since the last run, if the watcher passed
+    /// them and a `test` stage selects the changed tests.
+    fn changed_tests(
+        &self,
+        config: &Config,
+        stages: &[Vec<String>],
+    ) -> Result<Option<ChangedTests>> {
+        let selects_changed = stages
+            .iter()
+            .any(|stage| is_test_stage(stage) && changed_flag_position(stage).is_some());
+        if self.changed_files.is_empty() || !selects_changed {
+            return Ok(None)
+        }
+        if self.watch.run_all {
+            let reason = "--run-all is set".to_string();
+            return Ok(Some(ChangedTests::All { revision: SINCE_LAST_RUN.to_string(), reason }))
+        }
+        let changed = ChangedTests::from_files(
+            config,
+            SINCE_LAST_RUN.to_string(),
+            self.changed_files.iter().cloned(),
+        )?;
+        Ok(Some(changed))
+    }
+}
+
+/// Splits a stage into the arguments of the forge command, without the leading `forge`.
+fn parse_stage(stage: &str) -> Result<Vec<String>> {
+    let mut args = stage.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("forge") {
+        args.remove(0);
+    }
+    if args.is_empty() {
+        eyre::bail!("empty pipeline stage: `{stage}`")
+    }
+    Ok(args)
+}
+
+/// Returns `true` if the stage runs `forge test`.
+fn is_test_stage(args: &[String]) -> bool {
+    matches!(args.first().map(String::as_str), Some("test" | "t"))
+}
+
+/// Returns the position of a `--changed` flag without a revision.
+fn changed_flag_position(args: &[String]) -> Option<usize> {
+    let pos = args.iter().position(|arg| arg == "--changed")?;
+    args.get(pos + 1).map_or(true, |next| next.starts_with('-')).then_some(pos)
+}
+
+/// Replaces the `--changed` flag at `pos` with a `--match-path` glob of the selected tests.
+///
+/// Returns `None` if no tests are selected, and the stage is skipped.
+fn select_changed_tests(
+    mut args: Vec<String>,
+    pos: usize,
+    changed: &ChangedTests,
+    root: &Path,
+) -> Option<Vec<String>> {
+    args.remove(pos);
+    let Some(files) = changed.files() else { return Some(args) };
+    let files = files
+        .iter()
+        .map(|file| file.strip_prefix(root).unwrap_or(file).display().to_string())
+        .collect::<Vec<_>>();
+    let glob = match files.as_slice() {
+        [] => return None,
+        [file] => file.clone(),
+        files => format!("{{{}}}", files.join(",")),
+    };
+    args.splice(pos..pos, ["--match-path".to_string(), glob]);
+    Some(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn parses_stages() {
+        assert_eq!(parse_stage("fmt --check").unwrap(), args("fmt --check"));
+        assert_eq!(parse_stage(" forge  build ").unwrap(), args("build"));
+        assert!(parse_stage("forge").is_err());
+        assert!(parse_stage("").is_err());
+    }
+
+    #[test]
+    fn finds_changed_flag() {
+        assert_eq!(changed_flag_position(&args("test --changed")), Some(1));
+        assert_eq!(changed_flag_position(&args("test --changed -vvv")), Some(1));
+        assert_eq!(changed_flag_position(&args("test --changed main")), None);
+        assert_eq!(changed_flag_position(&args("test -vvv")), None);
+    }
+
+    #[test]
+    fn selects_changed_tests() {
+        let root = Path::new("/project");
+        let revision = SINCE_LAST_RUN.to_string();
+        let selected = |files: &[&str]| ChangedTests::Selected {
+            revision: revision.clone(),
+            files: files
+                .iter()
+                .map(|file| (root.join(file), root.join("src/Counter.sol")))
+                .collect::<BTreeMap<_, _>>(),
+        };
+
+        assert_eq!(
+            select_changed_tests(
+                args("test --changed -vvv"),
+                1,
+                &selected(&["test/A.t.sol"]),
+                root
+            ),
+            Some(args("test --match-path test/A.t.sol -vvv"))
+        );
+        assert_eq!(
+            select_changed_tests(
+                args("test --changed"),
+                1,
+                &selected(&["test/A.t.sol", "test/B.t.sol"]),
+                root
+            ),
+            Some(args("test --match-path {test/A.t.sol,test/B.t.sol}"))
+        );
+        assert_eq!(select_changed_tests(args("test --changed"), 1, &selected(&[]), root), None);
+
+        let all = ChangedTests::All { revision, reason: "foundry.toml changed".to_string() };
+        assert_eq!(select_changed_tests(args("test --changed"), 1, &all, root), Some(args("test")));
+    }
+}
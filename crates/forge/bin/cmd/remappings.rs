@@ -14,9 +14,15 @@ pub struct RemappingArgs {
     /// or the current working directory.
     #[arg(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
     root: Option<PathBuf>,
-    /// Pretty-print the remappings, grouping each of them by context.
+    /// Pretty-print the remappings, grouping each of them by context and showing whether they were
+    /// configured or inferred.
     #[arg(long)]
     pretty: bool,
+    /// Check that no inferred remapping conflicts with a configured one.
+    ///
+    /// Exits with an error and prints the conflicting remappings otherwise.
+    #[arg(long)]
+    check: bool,
 }
 impl_figment_convert_basic!(RemappingArgs);
 
@@ -24,10 +30,22 @@ impl RemappingArgs {
     pub fn run(self) -> Result<()> {
         let config = self.try_load_config_emit_warnings()?;
 
+        if self.check {
+            let (_, conflicts) = config.remappings_with_origin();
+            if conflicts.is_empty() {
+                return Ok(())
+            }
+            for conflict in conflicts.iter() {
+                eprintln!("{conflict}");
+            }
+            eyre::bail!("found {} conflicting remapping(s)", conflicts.len())
+        }
+
         if self.pretty {
-            let mut groups = HashMap::<_, Vec<_>>::with_capacity(config.remappings.len());
-            for remapping in config.remappings {
-                groups.entry(remapping.context.clone()).or_default().push(remapping);
+            let (remappings, _) = config.remappings_with_origin();
+            let mut groups = HashMap::<_, Vec<_>>::with_capacity(remappings.len());
+            for (remapping, origin) in remappings {
+                groups.entry(remapping.context.clone()).or_default().push((remapping, origin));
             }
             for (group, remappings) in groups {
                 if let Some(group) = group {
@@ -36,9 +54,9 @@ impl RemappingArgs {
                     println!("Global:");
                 }
 
-                for mut remapping in remappings.into_iter() {
+                for (mut remapping, origin) in remappings.into_iter() {
                     remapping.context = None; // avoid writing context twice
-                    println!("- {remapping}");
+                    println!("- {remapping} ({origin})");
                 }
                 println!();
             }
@@ -1,18 +1,23 @@
 use super::{
-    multi::MultiChainSequence, providers::ProvidersManager, receipts::clear_pendings,
-    sequence::ScriptSequence, transaction::TransactionWithMetadata, verify::VerifyBundle,
-    NestedValue, ScriptArgs, ScriptConfig, ScriptResult,
+    checks::SimulatedBalances,
+    multi::MultiChainSequence,
+    providers::{ProviderInfo, ProvidersManager},
+    receipts::clear_pendings,
+    sequence::ScriptSequence,
+    transaction::TransactionWithMetadata,
+    verify::VerifyBundle,
+    NestedValue, NonceSource, ScriptArgs, ScriptConfig, ScriptResult,
 };
 use alloy_primitives::{utils::format_units, Address, TxHash, U256};
 use ethers_core::types::transaction::eip2718::TypedTransaction;
 use ethers_providers::{JsonRpcClient, Middleware, Provider};
 use ethers_signers::Signer;
 use eyre::{bail, Context, ContextCompat, Result};
-use forge::{inspectors::cheatcodes::BroadcastableTransactions, traces::CallTraceDecoder};
-use foundry_cli::{
-    init_progress, update_progress,
-    utils::{has_batch_support, has_different_gas_calc},
+use forge::{
+    inspectors::cheatcodes::{BroadcastBalanceAssertion, BroadcastableTransactions},
+    traces::CallTraceDecoder,
 };
+use foundry_cli::{init_progress, update_progress, utils::has_batch_support};
 use foundry_common::{
     provider::{
         alloy::RpcUrl,
@@ -28,7 +33,7 @@ use foundry_wallets::WalletSigner;
 use futures::StreamExt;
 use std::{
     cmp::min,
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     sync::Arc,
 };
 
@@ -39,6 +44,8 @@ impl ScriptArgs {
         deployment_sequence: &mut ScriptSequence,
         fork_url: &str,
         signers: &HashMap<Address, WalletSigner>,
+        config: &Config,
+        decoder: &CallTraceDecoder,
     ) -> Result<()> {
         let provider = Arc::new(try_get_http_provider(fork_url)?);
         let already_broadcasted = deployment_sequence.receipts.len();
@@ -50,6 +57,14 @@ impl ScriptArgs {
                 .map(|tx| (*tx.from().expect("No sender for onchain transaction!")).to_alloy())
                 .collect();
 
+            if self.resume &&
+                deployment_sequence.nonce_source == NonceSource::Pending &&
+                !self.allow_pending_nonce
+            {
+                eyre::bail!("The sequence was broadcast after in-flight transactions of the sender, pass --allow-pending-nonce to resume it.");
+            }
+            self.check_in_flight_nonces(deployment_sequence, already_broadcasted, fork_url).await?;
+
             let (send_kind, chain) = if self.unlocked {
                 let chain = provider.get_chainid().await?;
                 let mut senders = HashSet::from([self
@@ -81,6 +96,13 @@ impl ScriptArgs {
                         error_msg += "\nYou seem to be using Foundry's default sender. Be sure to set your own --sender.\n";
                     }
 
+                    if self.wallets.mnemonics.is_some() {
+                        error_msg += &format!(
+                            "\nSearched mnemonic derivation paths: {}\n",
+                            self.wallets.mnemonic_derivation_paths().join(", ")
+                        );
+                    }
+
                     eyre::bail!(
                         "{}No associated wallet for addresses: {:?}. Unlocked wallets: {:?}",
                         error_msg,
@@ -100,6 +122,13 @@ impl ScriptArgs {
             let sequential_broadcast =
                 send_kind.signers_count() != 1 || self.slow || !has_batch_support(chain);
 
+            // Chains which use `eth_estimateGas` are being sent sequentially and require their gas
+            // to be re-estimated right before broadcasting.
+            let gas_estimate_multiplier = self.gas_estimate_multiplier_for(config, chain);
+            let reestimate_gas = (self.estimates_gas_via_rpc(config, chain) ||
+                self.skip_simulation)
+                .then_some(gas_estimate_multiplier);
+
             // Make a one-time gas price estimation
             let (gas_price, eip1559_fees) = {
                 match deployment_sequence.transactions.front().unwrap().typed_tx() {
@@ -181,6 +210,7 @@ impl ScriptArgs {
                         sequential_broadcast,
                         fork_url,
                         is_fixed_gas_limit,
+                        reestimate_gas,
                     );
 
                     if sequential_broadcast {
@@ -190,8 +220,13 @@ impl ScriptArgs {
                         update_progress!(pb, (index + already_broadcasted));
                         index += 1;
 
-                        clear_pendings(provider.clone(), deployment_sequence, Some(vec![tx_hash]))
-                            .await?;
+                        clear_pendings(
+                            provider.clone(),
+                            deployment_sequence,
+                            Some(vec![tx_hash]),
+                            decoder,
+                        )
+                        .await?;
                     } else {
                         pending_transactions.push(tx_hash);
                     }
@@ -213,7 +248,8 @@ impl ScriptArgs {
 
                     if !sequential_broadcast {
                         shell::println("##\nWaiting for receipts.")?;
-                        clear_pendings(provider.clone(), deployment_sequence, None).await?;
+                        clear_pendings(provider.clone(), deployment_sequence, None, decoder)
+                            .await?;
                     }
                 }
 
@@ -225,28 +261,55 @@ impl ScriptArgs {
         shell::println("\n\n==========================")?;
         shell::println("\nONCHAIN EXECUTION COMPLETE & SUCCESSFUL.")?;
 
-        let (total_gas, total_gas_price, total_paid) = deployment_sequence.receipts.iter().fold(
-            (U256::ZERO, U256::ZERO, U256::ZERO),
-            |acc, receipt| {
-                let gas_used = receipt.gas_used.unwrap_or_default().to_alloy();
-                let gas_price = receipt.effective_gas_price.unwrap_or_default().to_alloy();
-                (acc.0 + gas_used, acc.1 + gas_price, acc.2 + gas_used * gas_price)
-            },
-        );
-        let paid = format_units(total_paid, 18).unwrap_or_else(|_| "N/A".to_string());
-        let avg_gas_price =
-            format_units(total_gas_price / U256::from(deployment_sequence.receipts.len()), 9)
-                .unwrap_or_else(|_| "N/A".to_string());
-        shell::println(format!(
-            "Total Paid: {} ETH ({} gas * avg {} gwei)",
-            paid.trim_end_matches('0'),
-            total_gas,
-            avg_gas_price.trim_end_matches('0').trim_end_matches('.')
-        ))?;
+        self.summarize(deployment_sequence).await?;
+        self.check_events(deployment_sequence, decoder, &config.script_settings.expected_events)
+            .await?;
 
         Ok(())
     }
 
+    /// Ensures the first transaction to send of each sender is at its pending nonce, so it
+    /// neither replaces in-flight transactions sent by someone else nor fails with "nonce too low".
+    async fn check_in_flight_nonces(
+        &self,
+        deployment_sequence: &ScriptSequence,
+        already_broadcasted: usize,
+        fork_url: &str,
+    ) -> Result<()> {
+        let mut first_nonces = BTreeMap::new();
+        for tx in deployment_sequence.typed_transactions().skip(already_broadcasted) {
+            let (Some(from), Some(nonce)) = (tx.from(), tx.nonce()) else { continue };
+            first_nonces.entry((*from).to_alloy()).or_insert(nonce.as_u64());
+        }
+
+        for (from, nonce) in first_nonces {
+            let pending = forge::next_pending_nonce(from, fork_url)
+                .await
+                .map_err(|_| eyre::eyre!("Not able to query the EOA nonce."))?;
+            if nonce >= pending {
+                continue;
+            }
+
+            let latest = forge::next_nonce(from, fork_url, None)
+                .await
+                .map_err(|_| eyre::eyre!("Not able to query the EOA nonce."))?;
+            if nonce < latest {
+                eyre::bail!("The nonce {nonce} of the next transaction of {from} was already used, the account is at nonce {latest}.");
+            }
+
+            let mut msg = format!(
+                "The next transaction of {from} would replace one of its {} in-flight transaction(s), with nonces {latest} to {}:",
+                pending - latest,
+                pending - 1
+            );
+            for hash in forge::pending_transactions(from, fork_url).await {
+                msg.push_str(&format!("\n  {hash}"));
+            }
+            eyre::bail!("{msg}\nWait for them to be mined, then run the script again, with --allow-pending-nonce to broadcast after any remaining ones.");
+        }
+        Ok(())
+    }
+
     async fn send_transaction(
         &self,
         provider: Arc<RetryProvider>,
@@ -255,11 +318,13 @@ impl ScriptArgs {
         sequential_broadcast: bool,
         fork_url: &str,
         is_fixed_gas_limit: bool,
+        reestimate_gas: Option<u64>,
     ) -> Result<TxHash> {
         let from = tx.from().expect("no sender");
 
         if sequential_broadcast {
-            let nonce = forge::next_nonce((*from).to_alloy(), fork_url, None)
+            // the previous transactions are confirmed, so in-flight ones were sent by someone else
+            let nonce = forge::next_pending_nonce((*from).to_alloy(), fork_url)
                 .await
                 .map_err(|_| eyre::eyre!("Not able to query the EOA nonce."))?;
 
@@ -275,13 +340,8 @@ impl ScriptArgs {
             SendTransactionKind::Unlocked(addr) => {
                 debug!("sending transaction from unlocked account {:?}: {:?}", addr, tx);
 
-                // Chains which use `eth_estimateGas` are being sent sequentially and require their
-                // gas to be re-estimated right before broadcasting.
-                if !is_fixed_gas_limit &&
-                    (has_different_gas_calc(provider.get_chainid().await?.as_u64()) ||
-                        self.skip_simulation)
-                {
-                    self.estimate_gas(&mut tx, &provider).await?;
+                if let Some(multiplier) = reestimate_gas.filter(|_| !is_fixed_gas_limit) {
+                    self.estimate_gas(&mut tx, &provider, multiplier).await?;
                 }
 
                 // Submit the transaction
@@ -289,7 +349,9 @@ impl ScriptArgs {
 
                 Ok(pending.tx_hash().to_alloy())
             }
-            SendTransactionKind::Raw(signer) => self.broadcast(provider, signer, tx).await,
+            SendTransactionKind::Raw(signer) => {
+                self.broadcast(provider, signer, tx, reestimate_gas).await
+            }
         }
     }
 
@@ -331,6 +393,7 @@ impl ScriptArgs {
                         script_config.target_contract(),
                         &script_config.config,
                         self.broadcast,
+                        &self.sequence_encryption(&script_config.config),
                     )?;
 
                     if self.broadcast {
@@ -340,6 +403,7 @@ impl ScriptArgs {
                             &script_config.config,
                             verify,
                             signers,
+                            decoder,
                         )
                         .await?;
                     }
@@ -350,6 +414,7 @@ impl ScriptArgs {
                         libraries,
                         verify,
                         signers,
+                        decoder,
                     )
                     .await?;
                 }
@@ -372,6 +437,7 @@ impl ScriptArgs {
         libraries: Libraries,
         verify: VerifyBundle,
         signers: &HashMap<Address, WalletSigner>,
+        decoder: &CallTraceDecoder,
     ) -> Result<()> {
         trace!(target: "script", "broadcasting single chain deployment");
 
@@ -383,7 +449,8 @@ impl ScriptArgs {
 
         deployment_sequence.add_libraries(libraries);
 
-        self.send_transactions(deployment_sequence, &rpc, signers).await?;
+        self.send_transactions(deployment_sequence, &rpc, signers, &script_config.config, decoder)
+            .await?;
 
         if self.verify {
             return deployment_sequence.verify_contracts(&script_config.config, verify).await;
@@ -405,20 +472,47 @@ impl ScriptArgs {
         known_contracts: &ContractsByArtifact,
     ) -> Result<Vec<ScriptSequence>> {
         if !txs.is_empty() {
-            let gas_filled_txs = self
-                .fills_transactions_with_gas(txs, script_config, decoder, known_contracts)
+            let (gas_filled_txs, balances) = self
+                .fills_transactions_with_gas(
+                    txs,
+                    &script_result.balance_assertions,
+                    script_config,
+                    decoder,
+                    known_contracts,
+                )
                 .await?;
 
             let returns = self.get_returns(&*script_config, &script_result.returned)?;
 
-            return self
+            let mut deployments = self
                 .bundle_transactions(
                     gas_filled_txs,
                     &script_config.target_contract().clone(),
                     &mut script_config.config,
                     returns,
                 )
-                .await;
+                .await?;
+            for deployment in &mut deployments {
+                deployment.nonce_source = script_config.nonce_source;
+            }
+
+            if self.skip_simulation {
+                if !script_result.balance_assertions.is_empty() {
+                    shell::println(
+                        "\nSkipping the broadcast balance assertions, they require the on-chain simulation.",
+                    )?;
+                }
+            } else {
+                self.check_balances(
+                    &deployments,
+                    &balances,
+                    &script_result.balance_assertions,
+                    &script_config.config,
+                )
+                .await?;
+            }
+
+            return Ok(deployments);
         } else if self.broadcast {
             eyre::bail!("No onchain transactions generated in script");
         }
@@ -428,26 +522,31 @@ impl ScriptArgs {
 
     /// Takes the collected transactions and executes them locally before converting them to
     /// [`TransactionWithMetadata`] with the appropriate gas execution estimation. If
-    /// `--skip-simulation` is passed, then it will skip the execution.
+    /// `--skip-simulation` is passed, then it will skip the execution and no balances are
+    /// simulated.
     async fn fills_transactions_with_gas(
         &self,
         txs: BroadcastableTransactions,
+        balance_assertions: &[BroadcastBalanceAssertion],
         script_config: &ScriptConfig,
         decoder: &CallTraceDecoder,
         known_contracts: &ContractsByArtifact,
-    ) -> Result<VecDeque<TransactionWithMetadata>> {
+    ) -> Result<(VecDeque<TransactionWithMetadata>, SimulatedBalances)> {
         let gas_filled_txs = if self.skip_simulation {
             shell::println("\nSKIPPING ON CHAIN SIMULATION.")?;
-            txs.into_iter()
+            let txs = txs
+                .into_iter()
                 .map(|btx| {
                     let mut tx = TransactionWithMetadata::from_tx_request(btx.transaction);
                     tx.rpc = btx.rpc;
                     tx
                 })
-                .collect()
+                .collect();
+            (txs, SimulatedBalances::new())
         } else {
             self.onchain_simulation(
                 txs,
+                balance_assertions,
                 script_config,
                 decoder,
                 known_contracts,
@@ -500,7 +599,8 @@ impl ScriptArgs {
                 }
             };
 
-            let provider_info = manager.get_or_init_provider(&tx_rpc, self.legacy).await?;
+            let provider_info =
+                manager.get_or_init_provider(&tx_rpc, self.legacy, &config.chains).await?;
 
             // Handles chain specific requirements.
             tx.change_type(provider_info.is_legacy);
@@ -509,7 +609,7 @@ impl ScriptArgs {
             if !self.skip_simulation {
                 let typed_tx = tx.typed_tx_mut();
 
-                if has_different_gas_calc(provider_info.chain) {
+                if self.estimates_gas_via_rpc(config, provider_info.chain) {
                     trace!("estimating with different gas calculation");
                     let gas = *typed_tx.gas().expect("gas is set by simulation.");
 
@@ -521,10 +621,13 @@ impl ScriptArgs {
                     // since tx1 hasn't been broadcasted yet.
                     //
                     // Not exiting here will not be a problem when actually broadcasting, because
-                    // for chains where `has_different_gas_calc` returns true,
+                    // for chains where `estimates_gas_via_rpc` returns true,
                     // we await each transaction before broadcasting the next
                     // one.
-                    if let Err(err) = self.estimate_gas(typed_tx, &provider_info.provider).await {
+                    let multiplier = self.gas_estimate_multiplier_for(config, provider_info.chain);
+                    if let Err(err) =
+                        self.estimate_gas(typed_tx, &provider_info.provider, multiplier).await
+                    {
                         trace!("gas estimation failed: {err}");
 
                         // Restore gas value, since `estimate_gas` will remove it.
@@ -554,6 +657,7 @@ impl ScriptArgs {
                 config,
                 self.broadcast,
                 is_multi_deployment,
+                &self.sequence_encryption(config),
             )?;
 
             deployments.push(sequence);
@@ -571,11 +675,7 @@ impl ScriptArgs {
 
                 // We don't store it in the transactions, since we want the most updated value.
                 // Right before broadcasting.
-                let per_gas = if let Some(gas_price) = self.with_gas_price {
-                    gas_price
-                } else {
-                    provider_info.gas_price()?
-                };
+                let per_gas = self.per_gas_price(provider_info)?;
 
                 shell::println("\n==========================")?;
                 shell::println(format!("\nChain {}", provider_info.chain))?;
@@ -588,6 +688,11 @@ impl ScriptArgs {
                         .trim_end_matches('.')
                 ))?;
                 shell::println(format!("\nEstimated total gas used for script: {total_gas}"))?;
+                if let Some(overrides) = config.script_settings.gas_overrides(provider_info.chain) {
+                    shell::println(format!(
+                        "\nGas estimated with the gas overrides of the chain: {overrides}"
+                    ))?;
+                }
                 shell::println(format!(
                     "\nEstimated amount required: {} ETH",
                     format_units(total_gas.saturating_mul(per_gas), 18)
@@ -600,6 +705,14 @@ impl ScriptArgs {
         Ok(deployments)
     }
 
+    /// Returns the `--with-gas-price` or else the current gas price of the chain.
+    pub(super) fn per_gas_price(&self, provider_info: &ProviderInfo) -> Result<U256> {
+        match self.with_gas_price {
+            Some(gas_price) => Ok(gas_price),
+            None => provider_info.gas_price(),
+        }
+    }
+
     /// Uses the signer to submit a transaction to the network. If it fails, it tries to retrieve
     /// the transaction hash that can be used on a later run with `--resume`.
     async fn broadcast(
@@ -607,18 +720,17 @@ impl ScriptArgs {
         provider: Arc<RetryProvider>,
         signer: &WalletSigner,
         mut legacy_or_1559: TypedTransaction,
+        reestimate_gas: Option<u64>,
     ) -> Result<TxHash> {
         debug!("sending transaction: {:?}", legacy_or_1559);
 
-        // Chains which use `eth_estimateGas` are being sent sequentially and require their gas
-        // to be re-estimated right before broadcasting.
-        if has_different_gas_calc(signer.chain_id()) || self.skip_simulation {
+        if let Some(multiplier) = reestimate_gas {
             // if already set, some RPC endpoints might simply return the gas value that is
             // already set in the request and omit the estimate altogether, so
             // we remove it here
             let _ = legacy_or_1559.gas_mut().take();
 
-            self.estimate_gas(&mut legacy_or_1559, &provider).await?;
+            self.estimate_gas(&mut legacy_or_1559, &provider, multiplier).await?;
         }
 
         // Signing manually so we skip `fill_transaction` and its `eth_createAccessList`
@@ -634,7 +746,13 @@ impl ScriptArgs {
         Ok(pending.tx_hash().to_alloy())
     }
 
-    async fn estimate_gas<T>(&self, tx: &mut TypedTransaction, provider: &Provider<T>) -> Result<()>
+    /// Estimates the gas of the transaction, inflated by `multiplier` percent.
+    async fn estimate_gas<T>(
+        &self,
+        tx: &mut TypedTransaction,
+        provider: &Provider<T>,
+        multiplier: u64,
+    ) -> Result<()>
     where
         T: JsonRpcClient,
     {
@@ -647,7 +765,7 @@ impl ScriptArgs {
                 .estimate_gas(tx, None)
                 .await
                 .wrap_err_with(|| format!("Failed to estimate gas for tx: {:?}", tx.sighash()))? *
-                self.gas_estimate_multiplier /
+                multiplier /
                 100,
         );
         Ok(())
@@ -1,8 +1,12 @@
 use super::{ScriptArgs, ScriptConfig};
 use alloy_primitives::{Address, Bytes};
 use eyre::{Context, ContextCompat, Result};
+use forge::traces::StackTraces;
 use foundry_cli::utils::get_cached_entry_by_name;
-use foundry_common::compile::{self, ContractSources, ProjectCompiler};
+use foundry_common::{
+    compile::{self, ContractSources, ProjectCompiler},
+    vyper::compile_vyper,
+};
 use foundry_compilers::{
     artifacts::{ContractBytecode, ContractBytecodeSome, Libraries},
     cache::SolFilesCache,
@@ -15,19 +19,36 @@ use std::str::FromStr;
 
 impl ScriptArgs {
     /// Compiles the file or project and the verify metadata.
-    pub fn compile(&mut self, script_config: &mut ScriptConfig) -> Result<BuildOutput> {
+    pub async fn compile(&mut self, script_config: &mut ScriptConfig) -> Result<BuildOutput> {
         trace!(target: "script", "compiling script");
 
-        self.build(script_config)
+        self.build(script_config).await
     }
 
     /// Compiles the file with auto-detection and compiler params.
-    pub fn build(&mut self, script_config: &mut ScriptConfig) -> Result<BuildOutput> {
+    pub async fn build(&mut self, script_config: &mut ScriptConfig) -> Result<BuildOutput> {
         let (project, output) = self.get_project_and_output(script_config)?;
         let root = project.root();
         let output = output.with_stripped_file_prefixes(root);
         let sources = ContractSources::from_project_output(&output, root)?;
-        let contracts = output.into_artifacts().collect();
+        // The Vyper sources aren't part of the solc project, their artifacts are written alongside
+        // so they can be deployed with `deployCode`.
+        let vyper_artifacts = compile_vyper(&script_config.config, self.opts.args.silent)
+            .await?
+            .map(|output| output.artifacts)
+            .unwrap_or_default();
+        let artifacts = output.into_artifacts().chain(vyper_artifacts).collect::<Vec<_>>();
+        let stack_traces = self.stack_traces.then(|| {
+            StackTraces::new(
+                artifacts.iter().map(|(id, artifact)| (id.clone(), artifact)),
+                &sources,
+            )
+        });
+        script_config.storage_layouts = artifacts
+            .iter()
+            .filter_map(|(id, artifact)| Some((id.identifier(), artifact.storage_layout.clone()?)))
+            .collect();
+        let contracts = artifacts.into_iter().collect();
 
         let target = self.find_target(&project, &contracts)?.clone();
         script_config.target_contract = Some(target.clone());
@@ -53,6 +74,7 @@ impl ScriptArgs {
             libraries,
             predeploy_libraries,
             sources,
+            stack_traces,
         })
     }
 
@@ -205,4 +227,6 @@ pub struct BuildOutput {
     pub libraries: Libraries,
     pub predeploy_libraries: Vec<Bytes>,
     pub sources: ContractSources,
+    /// The source locations of the compiled contracts, with `--stack-traces`
+    pub stack_traces: Option<StackTraces>,
 }
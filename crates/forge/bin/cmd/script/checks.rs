@@ -0,0 +1,151 @@
+use super::{providers::ProvidersManager, sequence::ScriptSequence, ScriptArgs};
+use alloy_primitives::{utils::format_units, Address, U256};
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use eyre::Result;
+use forge::inspectors::cheatcodes::BroadcastBalanceAssertion;
+use foundry_common::{provider::alloy::RpcUrl, shell, types::ToAlloy};
+use foundry_config::Config;
+use std::collections::BTreeMap;
+
+/// The balance of an account before and after the on-chain simulation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SimulatedBalance {
+    pub start: U256,
+    pub end: U256,
+}
+
+/// Simulated balances, keyed by the RPC URL of the chain and the account.
+pub type SimulatedBalances = BTreeMap<(RpcUrl, Address), SimulatedBalance>;
+
+/// The ETH a sender spends with its transactions on a chain.
+#[derive(Default)]
+struct Spending {
+    value: U256,
+    /// The gas limits of the transactions at their maximum price per gas.
+    gas_cost: U256,
+}
+
+impl ScriptArgs {
+    /// Prints the balances of the senders on every chain, and fails if they can't afford their
+    /// transactions while `script.checks.sender_balance` is set, or if a balance asserted with
+    /// `assertBroadcastBalanceAbove` doesn't hold.
+    ///
+    /// The on-chain simulation doesn't charge for gas, so the ending balance of a sender is its
+    /// simulated balance minus the worst-case gas cost.
+    pub(super) async fn check_balances(
+        &self,
+        sequences: &[ScriptSequence],
+        balances: &SimulatedBalances,
+        assertions: &[BroadcastBalanceAssertion],
+        config: &Config,
+    ) -> Result<()> {
+        let mut manager = ProvidersManager::default();
+        let mut spendings = BTreeMap::<(RpcUrl, Address), Spending>::new();
+        let mut chains = BTreeMap::new();
+        for sequence in sequences {
+            for tx in &sequence.transactions {
+                let typed_tx = tx.typed_tx();
+                let (Some(rpc), Some(from)) = (&tx.rpc, typed_tx.from()) else { continue };
+                chains.insert(rpc.clone(), sequence.chain);
+
+                let per_gas = match typed_tx.gas_price() {
+                    Some(gas_price) => gas_price.to_alloy(),
+                    None => self.per_gas_price(
+                        manager.get_or_init_provider(rpc, self.legacy, &config.chains).await?,
+                    )?,
+                };
+                let gas = typed_tx.gas().map(|gas| gas.to_alloy()).unwrap_or_default();
+                let value = typed_tx.value().map(|value| value.to_alloy()).unwrap_or_default();
+
+                let spending = spendings.entry((rpc.clone(), from.to_alloy())).or_default();
+                spending.value = spending.value.saturating_add(value);
+                spending.gas_cost = spending.gas_cost.saturating_add(gas.saturating_mul(per_gas));
+            }
+        }
+
+        let mut failures = vec![];
+        for (rpc, chain) in &chains {
+            let mut table = Table::new();
+            table.load_preset(ASCII_MARKDOWN);
+            table.set_header([
+                "Sender",
+                "Starting balance",
+                "Value sent",
+                "Worst-case gas cost",
+                "Ending balance",
+            ]);
+            for ((_, sender), spending) in spendings.iter().filter(|((r, _), _)| r == rpc) {
+                let Some(balance) = balances.get(&(rpc.clone(), *sender)) else { continue };
+                table.add_row([
+                    sender.to_string(),
+                    format_eth(balance.start),
+                    format_eth(spending.value),
+                    format_eth(spending.gas_cost),
+                    format_eth(balance.end.saturating_sub(spending.gas_cost)),
+                ]);
+
+                let spent = spending.value.saturating_add(spending.gas_cost);
+                if config.script_settings.checks.sender_balance && spent > balance.start {
+                    failures.push(format!(
+                        "{sender} spends up to {} ETH on chain {chain}, but its balance is {} ETH",
+                        format_eth(spent),
+                        format_eth(balance.start)
+                    ));
+                }
+            }
+            shell::println(format!("\nSender balances on chain {chain}:\n\n{table}"))?;
+        }
+
+        for assertion in assertions {
+            let account = assertion.account;
+            let Some(balance) =
+                assertion.rpc.as_ref().and_then(|rpc| balances.get(&(rpc.clone(), account)))
+            else {
+                failures.push(format!(
+                    "the balance of {account} can't be checked, it wasn't asserted on a chain \
+                     the transactions are broadcast to"
+                ));
+                continue
+            };
+            let gas_cost = assertion
+                .rpc
+                .as_ref()
+                .and_then(|rpc| spendings.get(&(rpc.clone(), account)))
+                .map_or(U256::ZERO, |spending| spending.gas_cost);
+            let ending = balance.end.saturating_sub(gas_cost);
+            if ending < assertion.min_balance {
+                failures.push(format!(
+                    "the balance of {account} after the broadcast is {} ETH, expected at least \
+                     {} ETH",
+                    format_eth(ending),
+                    format_eth(assertion.min_balance)
+                ));
+            }
+        }
+
+        if !failures.is_empty() {
+            eyre::bail!(
+                "Refusing to broadcast, the simulated transactions failed the balance checks:\n{}",
+                failures.join("\n")
+            );
+        }
+        Ok(())
+    }
+}
+
+fn format_eth(wei: U256) -> String {
+    let eth = format_units(wei, 18).unwrap_or_else(|_| "[Could not calculate]".to_string());
+    eth.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_eth() {
+        assert_eq!(format_eth(U256::from(1_500_000_000_000_000_000u128)), "1.5");
+        assert_eq!(format_eth(U256::from(2_000_000_000_000_000_000u128)), "2");
+        assert_eq!(format_eth(U256::ZERO), "0");
+    }
+}
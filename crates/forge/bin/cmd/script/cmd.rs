@@ -1,29 +1,34 @@
 use super::{
-    multi::MultiChainSequence, sequence::ScriptSequence, verify::VerifyBundle, ScriptArgs,
-    ScriptConfig, ScriptResult,
+    encryption::SequenceDecryptionError, multi::MultiChainSequence, sequence::ScriptSequence,
+    verify::VerifyBundle, NonceSource, ScriptArgs, ScriptConfig, ScriptResult,
 };
 use crate::cmd::script::{build::BuildOutput, receipts};
 use alloy_primitives::{Address, Bytes};
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
 use ethers_providers::Middleware;
 use ethers_signers::Signer;
 use eyre::{OptionExt, Result};
 use forge::traces::CallTraceDecoder;
 use foundry_cli::utils::LoadConfig;
 use foundry_common::{
-    contracts::flatten_contracts, provider::ethers::try_get_http_provider, types::ToAlloy,
+    contracts::flatten_contracts, provider::ethers::try_get_http_provider, shell, types::ToAlloy,
 };
 use foundry_compilers::{
     artifacts::{ContractBytecodeSome, Libraries},
     contracts::ArtifactContracts,
 };
-use foundry_debugger::Debugger;
-use foundry_evm::inspectors::cheatcodes::{BroadcastableTransaction, ScriptWallets};
+use foundry_debugger::{Debugger, BREAKPOINTS_FILE};
+use foundry_evm::{
+    decode::RevertDecoder,
+    inspectors::cheatcodes::{BroadcastableTransaction, ScriptWallets},
+};
 use foundry_linking::Linker;
 use foundry_wallets::WalletSigner;
 use std::{collections::HashMap, sync::Arc};
+use yansi::Paint;
 
 /// Helper alias type for the collection of data changed due to the new sender.
-type NewSenderChanges = (CallTraceDecoder, Libraries, ArtifactContracts<ContractBytecodeSome>);
+type NewSenderChanges = (Libraries, ArtifactContracts<ContractBytecodeSome>);
 
 impl ScriptArgs {
     /// Executes the script
@@ -40,20 +45,55 @@ impl ScriptArgs {
             ..Default::default()
         };
 
+        if self.status {
+            return self.sequence_status(script_config)
+        }
+
         if let Some(sender) = self.maybe_load_private_key()? {
             script_config.evm_opts.sender = sender;
         }
 
-        if let Some(ref fork_url) = script_config.evm_opts.fork_url {
+        if let Some(fork_url) = script_config.evm_opts.fork_url.clone() {
             // when forking, override the sender's nonce to the onchain value
+            let sender = script_config.evm_opts.sender;
             script_config.sender_nonce =
-                forge::next_nonce(script_config.evm_opts.sender, fork_url, None).await?
+                self.resolve_sender_nonce(&mut script_config, sender, &fork_url).await?;
         } else {
             // if not forking, then ignore any pre-deployed library addresses
             script_config.config.libraries = Default::default();
         }
 
-        let build_output = self.compile(&mut script_config)?;
+        let mut build_output = self.compile(&mut script_config).await?;
+
+        if !build_output.predeploy_libraries.is_empty() {
+            let sender = script_config.evm_opts.sender;
+            let nonce = script_config.sender_nonce;
+            let libraries = self
+                .reuse_deployed_libraries(
+                    &script_config,
+                    &build_output.linker,
+                    script_config.config.libraries_with_remappings()?,
+                    sender,
+                    nonce,
+                )
+                .await?;
+            let target = script_config.target_contract().clone();
+            let (highlevel_known_contracts, libraries, predeploy_libraries) = self
+                .link_script_target(
+                    &build_output.linker,
+                    libraries,
+                    sender,
+                    nonce,
+                    target.clone(),
+                )?;
+            build_output.contract = highlevel_known_contracts
+                .get(&target)
+                .ok_or_eyre("target not found in linked artifacts")?
+                .clone();
+            build_output.highlevel_known_contracts = highlevel_known_contracts;
+            build_output.libraries = libraries;
+            build_output.predeploy_libraries = predeploy_libraries;
+        }
 
         let mut verify = VerifyBundle::new(
             &build_output.project,
@@ -70,13 +110,41 @@ impl ScriptArgs {
             linker,
             sources,
             mut libraries,
+            stack_traces,
             ..
         } = build_output;
 
+        if let Some(tx) = self.debug_tx {
+            let known_contracts = flatten_contracts(&highlevel_known_contracts, true);
+            return self
+                .debug_sequence_transaction(script_config, tx, &known_contracts, sources)
+                .await
+        }
+
+        script_config.revert_decoder = RevertDecoder::new()
+            .with_abis(highlevel_known_contracts.values().map(|contract| &contract.abi));
+
         // Execute once with default sender.
         let sender = script_config.evm_opts.sender;
 
-        let multi_wallet = self.wallets.get_multi_wallet().await?;
+        let mut multi_wallet = self.wallets.get_multi_wallet().await?;
+        if self.wallets.mnemonic_discover.is_some() {
+            let fork_url = script_config
+                .evm_opts
+                .fork_url
+                .as_deref()
+                .ok_or_eyre("`--mnemonic-discover` requires an RPC URL, set with --fork-url")?;
+            let discovered = self.wallets.discover_mnemonic_signers(fork_url).await?;
+
+            let mut table = Table::new();
+            table.load_preset(ASCII_MARKDOWN);
+            table.set_header(["Address", "Derivation path"]);
+            for (signer, path) in discovered {
+                table.add_row([signer.address().to_alloy().to_string(), path]);
+                multi_wallet.add_signer(signer);
+            }
+            shell::println(format!("Discovered funded mnemonic accounts:\n{table}"))?;
+        }
         let script_wallets = ScriptWallets::new(multi_wallet, self.evm_opts.sender);
 
         // We need to execute the script even if just resuming, in case we need to collect private
@@ -91,47 +159,71 @@ impl ScriptArgs {
             )
             .await?;
 
+        let known_contracts = flatten_contracts(&highlevel_known_contracts, true);
+        let mut decoder = self.decode_traces(&script_config, &mut result, &known_contracts)?;
+
         if self.resume || (self.verify && !self.broadcast) {
             let signers = script_wallets.into_multi_wallet().into_signers()?;
-            return self.resume_deployment(script_config, linker, libraries, verify, &signers).await;
+            return self
+                .resume_deployment(script_config, linker, libraries, verify, &signers, &decoder)
+                .await;
         }
 
-        let known_contracts = flatten_contracts(&highlevel_known_contracts, true);
-        let mut decoder = self.decode_traces(&script_config, &mut result, &known_contracts)?;
-
         if self.debug {
             let mut debugger = Debugger::builder()
                 .debug_arenas(result.debug.as_deref().unwrap_or_default())
                 .decoder(&decoder)
                 .sources(sources)
                 .breakpoints(result.breakpoints.clone())
+                .breakpoints_file(script_config.config.cache_path.join(BREAKPOINTS_FILE))
                 .build();
             debugger.try_run()?;
         }
 
-        if let Some((new_traces, updated_libraries, updated_contracts)) = self
+        if let Some((updated_libraries, updated_contracts)) = self
             .maybe_prepare_libraries(
                 &mut script_config,
                 linker,
                 predeploy_libraries,
                 &mut result,
+                &mut decoder,
                 script_wallets.clone(),
             )
             .await?
         {
-            decoder = new_traces;
             highlevel_known_contracts = updated_contracts;
             libraries = updated_libraries;
         }
 
+        if let Some(saved) = &self.diff_against {
+            if !self.json {
+                self.show_traces(&script_config, &decoder, stack_traces.as_ref(), &mut result)
+                    .await?;
+            }
+            let known_contracts = flatten_contracts(&highlevel_known_contracts, false);
+            return self
+                .diff_sequence(
+                    &script_config,
+                    saved,
+                    result.transactions.as_ref().unwrap_or(&Default::default()),
+                    &decoder,
+                    &known_contracts,
+                )
+                .await
+        }
+
         if self.json {
             self.show_json(&script_config, &result)?;
         } else {
-            self.show_traces(&script_config, &decoder, &mut result).await?;
+            self.show_traces(&script_config, &decoder, stack_traces.as_ref(), &mut result).await?;
         }
 
         verify.known_contracts = flatten_contracts(&highlevel_known_contracts, false);
-        self.check_contract_sizes(&result, &highlevel_known_contracts)?;
+        self.check_contract_sizes(
+            &result,
+            &highlevel_known_contracts,
+            script_config.config.evm_version,
+        )?;
 
         let signers = script_wallets.into_multi_wallet().into_signers()?;
 
@@ -154,6 +246,7 @@ impl ScriptArgs {
         linker: Linker,
         predeploy_libraries: Vec<Bytes>,
         result: &mut ScriptResult,
+        decoder: &mut CallTraceDecoder,
         script_wallets: ScriptWallets,
     ) -> Result<Option<NewSenderChanges>> {
         if let Some(new_sender) = self.maybe_new_sender(
@@ -167,13 +260,14 @@ impl ScriptArgs {
                 .await?;
 
             // redo traces for the new addresses
-            let new_traces = self.decode_traces(
+            self.identify_traces(
                 &*script_config,
                 result,
                 &flatten_contracts(&highlevel_known_contracts, true),
+                decoder,
             )?;
 
-            return Ok(Some((new_traces, libraries, highlevel_known_contracts)));
+            return Ok(Some((libraries, highlevel_known_contracts)));
         }
 
         // Add predeploy libraries to the list of broadcastable transactions.
@@ -205,6 +299,7 @@ impl ScriptArgs {
         libraries: Libraries,
         verify: VerifyBundle,
         signers: &HashMap<Address, WalletSigner>,
+        decoder: &CallTraceDecoder,
     ) -> Result<()> {
         if self.multi {
             return self
@@ -213,11 +308,13 @@ impl ScriptArgs {
                         &script_config.config,
                         &self.sig,
                         script_config.target_contract(),
+                        &self.sequence_encryption(&script_config.config),
                     )?,
                     libraries,
                     &script_config.config,
                     verify,
                     signers,
+                    decoder,
                 )
                 .await;
         }
@@ -226,6 +323,7 @@ impl ScriptArgs {
             linker,
             verify,
             signers,
+            decoder,
         )
         .await
         .map_err(|err| {
@@ -240,6 +338,7 @@ impl ScriptArgs {
         linker: Linker,
         mut verify: VerifyBundle,
         signers: &HashMap<Address, WalletSigner>,
+        decoder: &CallTraceDecoder,
     ) -> Result<()> {
         trace!(target: "script", "resuming single deployment");
 
@@ -254,23 +353,28 @@ impl ScriptArgs {
         verify.set_chain(&script_config.config, chain.into());
 
         let broadcasted = self.broadcast || self.resume;
+        let encryption = self.sequence_encryption(&script_config.config);
         let mut deployment_sequence = match ScriptSequence::load(
             &script_config.config,
             &self.sig,
             script_config.target_contract(),
             chain,
             broadcasted,
+            &encryption,
         ) {
             Ok(seq) => seq,
             // If the script was simulated, but there was no attempt to broadcast yet,
             // try to read the script sequence from the `dry-run/` folder
-            Err(_) if broadcasted => ScriptSequence::load(
-                &script_config.config,
-                &self.sig,
-                script_config.target_contract(),
-                chain,
-                false,
-            )?,
+            Err(err) if broadcasted && !err.is::<SequenceDecryptionError>() => {
+                ScriptSequence::load(
+                    &script_config.config,
+                    &self.sig,
+                    script_config.target_contract(),
+                    chain,
+                    false,
+                    &encryption,
+                )?
+            }
             Err(err) => eyre::bail!(err),
         };
 
@@ -278,10 +382,17 @@ impl ScriptArgs {
             deployment_sequence.verify_preflight_check(&script_config.config, &verify)?;
         }
 
-        receipts::wait_for_pending(provider, &mut deployment_sequence).await?;
+        receipts::wait_for_pending(provider, &mut deployment_sequence, decoder).await?;
 
         if self.resume {
-            self.send_transactions(&mut deployment_sequence, fork_url, signers).await?;
+            self.send_transactions(
+                &mut deployment_sequence,
+                fork_url,
+                signers,
+                &script_config.config,
+                decoder,
+            )
+            .await?;
         }
 
         if self.verify {
@@ -322,18 +433,22 @@ impl ScriptArgs {
     ) -> Result<(Libraries, ArtifactContracts<ContractBytecodeSome>)> {
         // if we had a new sender that requires relinking, we need to
         // get the nonce mainnet for accurate addresses for predeploy libs
-        let nonce = forge::next_nonce(
-            new_sender,
-            script_config.evm_opts.fork_url.as_ref().ok_or_else(|| {
-                eyre::eyre!("You must provide an RPC URL (see --fork-url) when broadcasting.")
-            })?,
-            None,
-        )
-        .await?;
+        let fork_url = script_config.evm_opts.fork_url.clone().ok_or_else(|| {
+            eyre::eyre!("You must provide an RPC URL (see --fork-url) when broadcasting.")
+        })?;
+        let nonce = self.resolve_sender_nonce(script_config, new_sender, &fork_url).await?;
         script_config.sender_nonce = nonce;
         let target = script_config.target_contract();
 
-        let libraries = script_config.config.libraries_with_remappings()?;
+        let libraries = self
+            .reuse_deployed_libraries(
+                script_config,
+                &linker,
+                script_config.config.libraries_with_remappings()?,
+                new_sender,
+                nonce,
+            )
+            .await?;
 
         let (highlevel_known_contracts, libraries, predeploy_libraries) =
             self.link_script_target(&linker, libraries, new_sender, nonce, target.clone())?;
@@ -369,6 +484,56 @@ impl ScriptArgs {
         Ok((libraries, highlevel_known_contracts))
     }
 
+    /// Returns the nonce of the sender the script is simulated with.
+    ///
+    /// If the sender has in-flight transactions, i.e. its pending nonce is ahead of the latest
+    /// one, broadcasting requires `--allow-pending-nonce` to start after them at the pending nonce.
+    async fn resolve_sender_nonce(
+        &self,
+        script_config: &mut ScriptConfig,
+        sender: Address,
+        fork_url: &str,
+    ) -> Result<u64> {
+        let latest = forge::next_nonce(sender, fork_url, None).await?;
+        // resuming doesn't simulate the script again, the saved nonces are checked when sending
+        if self.resume || (self.verify && !self.broadcast) {
+            return Ok(latest);
+        }
+
+        let pending = forge::next_pending_nonce(sender, fork_url).await?;
+        if pending <= latest {
+            script_config.nonce_source = NonceSource::Latest;
+            return Ok(latest);
+        }
+
+        let hashes = forge::pending_transactions(sender, fork_url).await;
+        let mut msg = format!(
+            "The sender {sender} has {} in-flight transaction(s), with nonces {latest} to {}.",
+            pending - latest,
+            pending - 1
+        );
+        for hash in &hashes {
+            msg.push_str(&format!("\n  {hash}"));
+        }
+
+        if self.allow_pending_nonce {
+            let action = if self.broadcast { "Broadcasting" } else { "Simulating" };
+            shell::println(format!("{msg}\n{action} after them at nonce {pending}."))?;
+            script_config.nonce_source = NonceSource::Pending;
+            return Ok(pending);
+        }
+
+        let advice =
+            "Wait for them to be mined, or pass --allow-pending-nonce to broadcast after them.";
+        if self.broadcast {
+            eyre::bail!(
+                "{msg}\nBroadcasting at nonce {latest} would replace them or fail with \"nonce too low\". {advice}"
+            );
+        }
+        shell::println(Paint::yellow(format!("{msg}\n{advice}")))?;
+        Ok(latest)
+    }
+
     /// In case the user has loaded *only* one private-key, we can assume that he's using it as the
     /// `--sender`
     fn maybe_load_private_key(&mut self) -> Result<Option<Address>> {
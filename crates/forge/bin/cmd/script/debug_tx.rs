@@ -0,0 +1,254 @@
+use super::{
+    encryption::SequenceDecryptionError, sequence::ScriptSequence, ScriptArgs, ScriptConfig,
+};
+use alloy_primitives::{TxHash, U256};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use forge::{
+    executors::{EvmError, TracingExecutor},
+    revm::primitives::{Env, TransactTo},
+    traces::{
+        identifier::{LocalTraceIdentifier, SignaturesIdentifier},
+        CallTraceDecoderBuilder,
+    },
+};
+use foundry_cli::utils::TraceResult;
+use foundry_common::{
+    compile::ContractSources,
+    provider::ethers::try_get_http_provider,
+    types::{ToAlloy, ToEthers},
+    ContractsByArtifact,
+};
+use foundry_config::Config;
+use foundry_debugger::{Debugger, BREAKPOINTS_FILE};
+use foundry_evm::utils::configure_tx_env;
+use std::str::FromStr;
+
+/// A transaction of a script sequence, identified by its index or its hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceTx {
+    Index(usize),
+    Hash(TxHash),
+}
+
+impl FromStr for SequenceTx {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") {
+            s.parse().map(Self::Hash).map_err(|err| format!("invalid transaction hash: {err}"))
+        } else {
+            s.parse()
+                .map(Self::Index)
+                .map_err(|_| format!("expected a transaction index or hash, found `{s}`"))
+        }
+    }
+}
+
+impl ScriptArgs {
+    /// Opens the debugger on a single transaction of the script's sequence.
+    ///
+    /// Broadcast transactions are replayed on a fork of the block they were included in, after
+    /// the transactions that preceded them in that block. Transactions of a dry run are replayed
+    /// on top of the sequence's preceding transactions instead.
+    pub async fn debug_sequence_transaction(
+        &self,
+        script_config: ScriptConfig,
+        tx: SequenceTx,
+        known_contracts: &ContractsByArtifact,
+        sources: ContractSources,
+    ) -> Result<()> {
+        let fork_url = script_config
+            .evm_opts
+            .fork_url
+            .as_deref()
+            .ok_or_else(|| eyre::eyre!("Missing `--fork-url` field."))?;
+        let provider = try_get_http_provider(fork_url)?;
+        let chain = provider.get_chainid().await?.as_u64();
+
+        let target = script_config.target_contract();
+        let config = &script_config.config;
+        let encryption = self.sequence_encryption(config);
+        let (sequence, broadcasted) =
+            match ScriptSequence::load(config, &self.sig, target, chain, true, &encryption) {
+                Ok(sequence) => (sequence, true),
+                Err(err) if err.is::<SequenceDecryptionError>() => return Err(err),
+                Err(_) => (
+                    ScriptSequence::load(config, &self.sig, target, chain, false, &encryption)?,
+                    false,
+                ),
+            };
+
+        let index = match tx {
+            SequenceTx::Index(index) => index,
+            SequenceTx::Hash(hash) => sequence
+                .transactions
+                .iter()
+                .position(|tx| tx.hash == Some(hash))
+                .ok_or_else(|| eyre::eyre!("Transaction {hash} is not part of the sequence"))?,
+        };
+        let Some(tx) = sequence.transactions.get(index) else {
+            eyre::bail!(
+                "Transaction index {index} is out of bounds, the sequence has {} transactions",
+                sequence.transactions.len()
+            )
+        };
+
+        let mut result = match tx.hash.filter(|_| broadcasted) {
+            Some(hash) => replay_broadcast_transaction(&script_config, &provider, hash).await?,
+            None => replay_sequence_transaction(&script_config, &sequence, index).await?,
+        };
+
+        let mut local_identifier = LocalTraceIdentifier::new(known_contracts);
+        let mut decoder = CallTraceDecoderBuilder::new()
+            .with_labels(sequence.transactions.iter().filter_map(|tx| {
+                Some((tx.contract_address?, tx.contract_name.clone().filter(|n| !n.is_empty())?))
+            }))
+            .with_local_identifier_abis(&local_identifier)
+            .with_signature_identifier(SignaturesIdentifier::new(
+                Config::foundry_cache_dir(),
+                script_config.config.offline,
+            )?)
+            .build();
+        for (_, trace) in &mut result.traces {
+            decoder.identify(trace, &mut local_identifier);
+        }
+
+        let mut debugger = Debugger::builder()
+            .debug_arena(&result.debug)
+            .decoder(&decoder)
+            .sources(sources)
+            .breakpoints_file(script_config.config.cache_path.join(BREAKPOINTS_FILE))
+            .build();
+        debugger.try_run()?;
+
+        Ok(())
+    }
+}
+
+/// Replays the broadcast transaction on a fork of the block it was included in.
+async fn replay_broadcast_transaction(
+    script_config: &ScriptConfig,
+    provider: &impl Middleware,
+    hash: TxHash,
+) -> Result<TraceResult> {
+    let tx = provider
+        .get_transaction(hash.to_ethers())
+        .await
+        .map_err(|err| eyre::eyre!("{err}"))?
+        .ok_or_else(|| eyre::eyre!("Transaction {hash} not found"))?
+        .to_alloy();
+    let block_number = tx
+        .block_number
+        .ok_or_else(|| eyre::eyre!("Transaction {hash} may still be pending"))?
+        .to::<u64>();
+
+    // fork off the parent block
+    let mut config = script_config.config.clone();
+    config.fork_block_number = Some(block_number - 1);
+    let (mut env, fork, _) =
+        TracingExecutor::get_fork_material(&config, script_config.evm_opts.clone()).await?;
+    let mut executor = TracingExecutor::new(env.clone(), fork, Some(config.evm_version), true);
+
+    env.block.number = U256::from(block_number);
+    let block = provider
+        .get_block_with_txs(block_number)
+        .await
+        .map_err(|err| eyre::eyre!("{err}"))?
+        .ok_or_else(|| eyre::eyre!("Block {block_number} not found"))?;
+    env.block.timestamp = block.timestamp.to_alloy();
+    env.block.coinbase = block.author.unwrap_or_default().to_alloy();
+    env.block.difficulty = block.difficulty.to_alloy();
+    env.block.prevrandao = Some(block.mix_hash.unwrap_or_default().to_alloy());
+    env.block.basefee = block.base_fee_per_gas.unwrap_or_default().to_alloy();
+    env.block.gas_limit = block.gas_limit.to_alloy();
+
+    // set the state to the moment right before the transaction
+    for previous in block.transactions.into_iter().map(ToAlloy::to_alloy) {
+        if previous.hash == hash {
+            break
+        }
+        configure_tx_env(&mut env, &previous);
+        commit(&mut executor, env.clone())
+            .wrap_err_with(|| format!("Failed to execute transaction {}", previous.hash))?;
+    }
+
+    configure_tx_env(&mut env, &tx);
+    execute(&mut executor, env)
+}
+
+/// Replays the transactions of the sequence that precede the one at `index` on a fork of the
+/// current block, then the transaction itself.
+async fn replay_sequence_transaction(
+    script_config: &ScriptConfig,
+    sequence: &ScriptSequence,
+    index: usize,
+) -> Result<TraceResult> {
+    let config = &script_config.config;
+    let (mut env, fork, _) =
+        TracingExecutor::get_fork_material(config, script_config.evm_opts.clone()).await?;
+    let mut executor = TracingExecutor::new(env.clone(), fork, Some(config.evm_version), true);
+
+    for (i, tx) in sequence.transactions.iter().take(index).enumerate() {
+        configure_sequence_tx_env(&mut env, tx.typed_tx());
+        commit(&mut executor, env.clone())
+            .wrap_err_with(|| format!("Failed to execute transaction {i} of the sequence"))?;
+    }
+
+    configure_sequence_tx_env(&mut env, sequence.transactions[index].typed_tx());
+    execute(&mut executor, env)
+}
+
+/// Configures the env for a transaction of the sequence, which doesn't have pricing info if it
+/// wasn't broadcast.
+fn configure_sequence_tx_env(env: &mut Env, tx: &TypedTransaction) {
+    env.tx.caller = tx.from().copied().unwrap_or_default().to_alloy();
+    env.tx.transact_to = match tx.to().and_then(|to| to.as_address()) {
+        Some(to) => TransactTo::Call(to.to_alloy()),
+        None => TransactTo::create(),
+    };
+    env.tx.data = tx.data().cloned().unwrap_or_default().to_alloy();
+    env.tx.value = tx.value().copied().unwrap_or_default().to_alloy();
+    env.tx.gas_limit = tx.gas().map_or(env.block.gas_limit.to(), |gas| gas.as_u64());
+    env.tx.gas_price = U256::ZERO;
+    env.tx.gas_priority_fee = None;
+    env.tx.nonce = None;
+}
+
+/// Executes the transaction and commits its state changes, ignoring reverted deployments.
+fn commit(executor: &mut TracingExecutor, env: Env) -> Result<()> {
+    if matches!(env.tx.transact_to, TransactTo::Call(_)) {
+        executor.commit_tx_with_env(env)?;
+    } else if let Err(err) = executor.deploy_with_env(env, None) {
+        if !matches!(err, EvmError::Execution(_)) {
+            return Err(err.into())
+        }
+    }
+    Ok(())
+}
+
+/// Executes the transaction to debug.
+fn execute(executor: &mut TracingExecutor, env: Env) -> Result<TraceResult> {
+    if matches!(env.tx.transact_to, TransactTo::Call(_)) {
+        Ok(executor.commit_tx_with_env(env)?.into())
+    } else {
+        match executor.deploy_with_env(env, None) {
+            Ok(result) => Ok(result.into()),
+            Err(err) => Ok(TraceResult::try_from(err)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_sequence_tx() {
+        assert_eq!("17".parse::<SequenceTx>(), Ok(SequenceTx::Index(17)));
+        let hash = "0x6f0c8cb6b1ed1ae4c62d1ae3ac3bc6f0f3e3dc8d06a8e4d6c7b3b1b1a1f0e0d0";
+        assert_eq!(hash.parse::<SequenceTx>(), Ok(SequenceTx::Hash(hash.parse().unwrap())));
+        assert!("latest".parse::<SequenceTx>().is_err());
+    }
+}
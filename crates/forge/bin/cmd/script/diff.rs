@@ -0,0 +1,598 @@
+//! Diff of the transactions of a script against a saved sequence.
+
+use super::{
+    encryption::SequenceDecryptionError, sequence::ScriptSequence,
+    transaction::TransactionWithMetadata, ScriptArgs, ScriptConfig,
+};
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_primitives::{hex, keccak256, Address, Bytes, Selector, B256, U256};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, NameOrAddress};
+use ethers_providers::Middleware;
+use eyre::Result;
+use forge::traces::CallTraceDecoder;
+use foundry_common::{
+    bytecode_diff_score, fmt::format_token_raw, get_contract_name, metadata_len,
+    provider::ethers::try_get_http_provider, shell, strip_bytecode_metadata, types::ToAlloy,
+    ContractsByArtifact, SELECTOR_LEN,
+};
+use foundry_evm::{
+    constants::DEFAULT_CREATE2_DEPLOYER, inspectors::cheatcodes::BroadcastableTransactions,
+};
+use serde::Serialize;
+use std::{cmp::Reverse, fmt, path::PathBuf, str::FromStr};
+use yansi::Paint;
+
+/// The saved sequence `forge script --diff-against` compares the transactions with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SavedSequence {
+    /// The latest sequence of the script: the broadcast one, or else the dry run.
+    Latest,
+    /// The sequence file at the path.
+    Path(PathBuf),
+}
+
+impl FromStr for SavedSequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latest" => Ok(Self::Latest),
+            path => Ok(Self::Path(path.into())),
+        }
+    }
+}
+
+/// The differences between the transactions of a script and a saved sequence.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SequenceDiff {
+    /// The saved sequence the transactions are compared with.
+    pub sequence: PathBuf,
+    pub transactions: Vec<TransactionDiff>,
+}
+
+/// A transaction that differs from the saved sequence, with its index in the sequences.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TransactionDiff {
+    Added {
+        index: usize,
+        transaction: String,
+    },
+    Removed {
+        index: usize,
+        transaction: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Changed {
+        old_index: usize,
+        index: usize,
+        transaction: String,
+        changes: Vec<FieldChange>,
+    },
+}
+
+/// A field of a transaction that changed, e.g. `to` or `arguments[0]`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl SequenceDiff {
+    /// Compares the `new` transactions with the `old` ones of the saved sequence, aligning the
+    /// transactions that deploy the same contract or call the same function.
+    fn new(sequence: PathBuf, old: &[DiffedTransaction], new: &[DiffedTransaction]) -> Self {
+        let mut transactions = vec![];
+        for pair in align(old, new) {
+            match pair {
+                (Some(old_index), None) => transactions.push(TransactionDiff::Removed {
+                    index: old_index,
+                    transaction: old[old_index].to_string(),
+                }),
+                (None, Some(index)) => transactions
+                    .push(TransactionDiff::Added { index, transaction: new[index].to_string() }),
+                (Some(old_index), Some(index)) => {
+                    let changes = old[old_index].changes(&new[index]);
+                    if !changes.is_empty() {
+                        transactions.push(TransactionDiff::Changed {
+                            old_index,
+                            index,
+                            transaction: new[index].to_string(),
+                            changes,
+                        });
+                    }
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+        Self { sequence, transactions }
+    }
+
+    /// Returns `true` if the transactions are the same as the saved ones.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+}
+
+impl fmt::Display for SequenceDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sequence = self.sequence.display();
+        if self.is_empty() {
+            return write!(f, "No differences with {sequence}")
+        }
+        writeln!(f, "Differences with {sequence}:")?;
+        for diff in &self.transactions {
+            match diff {
+                TransactionDiff::Added { index, transaction } => {
+                    writeln!(f, "{}", Paint::green(format!("+ [{index}] {transaction}")))?
+                }
+                TransactionDiff::Removed { index, transaction } => {
+                    writeln!(f, "{}", Paint::red(format!("- [{index}] {transaction}")))?
+                }
+                TransactionDiff::Changed { old_index, index, transaction, changes } => {
+                    let index = if old_index == index {
+                        index.to_string()
+                    } else {
+                        format!("{old_index} -> {index}")
+                    };
+                    writeln!(f, "{}", Paint::yellow(format!("~ [{index}] {transaction}")))?;
+                    for change in changes {
+                        writeln!(
+                            f,
+                            "    {}: {} -> {}",
+                            change.field,
+                            Paint::red(&change.old),
+                            Paint::green(&change.new)
+                        )?;
+                    }
+                }
+            }
+        }
+        write!(f, "{} transactions differ", self.transactions.len())
+    }
+}
+
+/// A transaction of a sequence, decoded with the ABIs of the project.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct DiffedTransaction {
+    from: Option<Address>,
+    /// The called address, `None` for deployments.
+    to: Option<Address>,
+    value: U256,
+    /// The salt of deployments through the CREATE2 deployer.
+    salt: Option<B256>,
+    /// The calldata of calls, or the init code of deployments.
+    data: Bytes,
+    contract: Option<String>,
+    function: Option<String>,
+    arguments: Option<Vec<String>>,
+}
+
+impl DiffedTransaction {
+    /// Decodes the transaction, with the creation code of the known contracts for deployments.
+    fn new(
+        tx: &TypedTransaction,
+        decoder: &CallTraceDecoder,
+        known_contracts: &ContractsByArtifact,
+    ) -> Self {
+        let mut data = tx.data().map(|data| Bytes::from(data.to_vec())).unwrap_or_default();
+        let mut to = match tx.to() {
+            Some(NameOrAddress::Address(to)) => Some(to.to_alloy()),
+            _ => None,
+        };
+        let mut salt = None;
+        if to == Some(DEFAULT_CREATE2_DEPLOYER) && data.len() >= 32 {
+            salt = Some(B256::from_slice(&data[..32]));
+            data = data.slice(32..);
+            to = None;
+        }
+
+        let mut diffed = Self {
+            from: tx.from().map(|from| from.to_alloy()),
+            to,
+            value: tx.value().map(|value| value.to_alloy()).unwrap_or_default(),
+            salt,
+            data,
+            ..Default::default()
+        };
+        match diffed.to {
+            Some(to) => diffed.decode_call(to, decoder),
+            None => diffed.decode_deployment(known_contracts),
+        }
+        diffed
+    }
+
+    /// Decodes the transaction of the saved sequence, falling back to its saved metadata.
+    fn saved(
+        tx: &TransactionWithMetadata,
+        decoder: &CallTraceDecoder,
+        known_contracts: &ContractsByArtifact,
+    ) -> Self {
+        let mut diffed = Self::new(tx.typed_tx(), decoder, known_contracts);
+        if diffed.contract.is_none() {
+            diffed.contract = tx.contract_name.clone().filter(|name| !name.is_empty());
+        }
+        if diffed.function.is_none() && diffed.arguments.is_none() {
+            diffed.function = tx.function.clone();
+            diffed.arguments = tx.arguments.clone().filter(|args| !args.is_empty());
+        }
+        diffed
+    }
+
+    fn decode_call(&mut self, to: Address, decoder: &CallTraceDecoder) {
+        self.contract = decoder.contracts.get(&to).map(|id| get_contract_name(id).to_string());
+        if self.data.len() < SELECTOR_LEN {
+            return
+        }
+        let (selector, args) = self.data.split_at(SELECTOR_LEN);
+        let selector = Selector::from_slice(selector);
+        let Some(function) = decoder.functions.get(&selector).and_then(|f| f.first()) else {
+            return
+        };
+        self.function = Some(function.signature());
+        self.arguments = function
+            .abi_decode_input(args, false)
+            .ok()
+            .map(|values| values.iter().map(format_token_raw).collect());
+    }
+
+    fn decode_deployment(&mut self, known_contracts: &ContractsByArtifact) {
+        let (code, args) = split_init_code(&self.data);
+        let Some((id, (abi, _))) = known_contracts.iter().find(|(_, (_, known))| {
+            known.len() == code.len() && bytecode_diff_score(known, code) <= 0.1
+        }) else {
+            return
+        };
+        self.contract = Some(id.name.clone());
+        self.arguments = match abi.constructor() {
+            Some(constructor) => constructor
+                .abi_decode_input(args, false)
+                .ok()
+                .map(|values| values.iter().map(format_token_raw).collect()),
+            None => Some(vec![]),
+        };
+    }
+
+    /// The key of the transaction when aligning sequences: deployments of the same contract, and
+    /// calls of the same function, are compared with each other.
+    fn key(&self) -> (bool, Option<&str>) {
+        match self.to {
+            None => (true, self.contract.as_deref()),
+            Some(_) => (false, self.function.as_deref()),
+        }
+    }
+
+    /// Returns the fields that differ in `new`.
+    fn changes(&self, new: &Self) -> Vec<FieldChange> {
+        let mut changes = vec![];
+        let mut change = |field: &str, old: String, new: String| {
+            if old != new {
+                changes.push(FieldChange { field: field.to_string(), old, new });
+            }
+        };
+        let address = |address: Option<Address>| address.map(|a| a.to_string()).unwrap_or_default();
+        change("from", address(self.from), address(new.from));
+        change("to", address(self.to), address(new.to));
+        change("value", self.value.to_string(), new.value.to_string());
+        let salt = |salt: Option<B256>| salt.map(|s| s.to_string()).unwrap_or_default();
+        change("salt", salt(self.salt), salt(new.salt));
+
+        if self.data == new.data {
+            return changes
+        }
+        let (old_data, new_data) = if self.to.is_none() {
+            let ((old_code, old_args), (new_code, new_args)) =
+                (split_init_code(&self.data), split_init_code(&new.data));
+            if old_code != new_code {
+                let field =
+                    if strip_bytecode_metadata(old_code) == strip_bytecode_metadata(new_code) {
+                        "bytecode (metadata hash only)"
+                    } else {
+                        "bytecode"
+                    };
+                change(field, describe_code(old_code), describe_code(new_code));
+            }
+            if old_args == new_args {
+                return changes
+            }
+            (old_args, new_args)
+        } else {
+            change(
+                "function",
+                self.function.clone().unwrap_or_default(),
+                new.function.clone().unwrap_or_default(),
+            );
+            (&self.data[..], &new.data[..])
+        };
+
+        match (&self.arguments, &new.arguments) {
+            (Some(old_args), Some(new_args))
+                if self.function == new.function && old_args.len() == new_args.len() =>
+            {
+                for (i, (old, new)) in old_args.iter().zip(new_args).enumerate() {
+                    change(&format!("arguments[{i}]"), old.clone(), new.clone());
+                }
+            }
+            _ => {
+                let field = if self.to.is_none() { "constructor arguments" } else { "calldata" };
+                change(field, hex::encode_prefixed(old_data), hex::encode_prefixed(new_data));
+            }
+        }
+        changes
+    }
+}
+
+impl fmt::Display for DiffedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let contract = self.contract.as_deref();
+        match self.to {
+            None => {
+                write!(f, "new {}", contract.unwrap_or("contract"))?;
+                if self.salt.is_some() {
+                    write!(f, " (CREATE2)")?;
+                }
+                Ok(())
+            }
+            Some(to) => match (contract, &self.function) {
+                (Some(contract), Some(function)) => write!(f, "{contract}::{function}"),
+                (None, Some(function)) => write!(f, "{to}::{function}"),
+                (_, None) if self.data.is_empty() => {
+                    write!(f, "transfer {} wei to {to}", self.value)
+                }
+                (_, None) => write!(f, "call {to}"),
+            },
+        }
+    }
+}
+
+/// Aligns the transactions of two sequences on their longest common subsequence of keys,
+/// returning the pairs of indices in order. Unpaired transactions were removed or added.
+fn align(
+    old: &[DiffedTransaction],
+    new: &[DiffedTransaction],
+) -> Vec<(Option<usize>, Option<usize>)> {
+    let (n, m) = (old.len(), new.len());
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i].key() == new[j].key() {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i].key() == new[j].key() {
+            pairs.push((Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            pairs.push((Some(i), None));
+            i += 1;
+        } else {
+            pairs.push((None, Some(j)));
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// The CBOR markers of the metadata solc appends to the runtime code.
+const METADATA_MARKERS: &[&[u8]] =
+    &[b"\x64ipfs\x58\x22", b"\x65bzzr0\x58\x20", b"\x65bzzr1\x58\x20"];
+
+/// Splits init code into the creation code and the constructor arguments, after the last
+/// metadata of the code. Init code without metadata has no known arguments.
+fn split_init_code(init_code: &[u8]) -> (&[u8], &[u8]) {
+    // The CBOR encoded metadata is a map starting with one of the markers, followed by its
+    // length as 2 big endian bytes, so each split point only ends the metadata at one start.
+    let is_metadata = |start: usize, end: usize| {
+        matches!(init_code[start], 0xa1 | 0xa2) &&
+            METADATA_MARKERS
+                .iter()
+                .any(|marker| init_code[start + 1..end - 2].starts_with(marker))
+    };
+    let split = (3..=init_code.len())
+        .filter_map(|end| {
+            let start = end - metadata_len(&init_code[..end]);
+            (start + 2 < end && is_metadata(start, end)).then_some((start, end))
+        })
+        .max_by_key(|&(start, end)| (start, Reverse(end)));
+    match split {
+        Some((_, end)) => init_code.split_at(end),
+        None => (init_code, &[]),
+    }
+}
+
+/// Describes code by its length and hash.
+fn describe_code(code: &[u8]) -> String {
+    format!("{} bytes, keccak {}", code.len(), keccak256(code))
+}
+
+impl ScriptArgs {
+    /// Compares the transactions of the script with the saved sequence and prints the diff.
+    ///
+    /// Exits with code 1 if they differ.
+    pub(super) async fn diff_sequence(
+        &self,
+        script_config: &ScriptConfig,
+        saved: &SavedSequence,
+        transactions: &BroadcastableTransactions,
+        decoder: &CallTraceDecoder,
+        known_contracts: &ContractsByArtifact,
+    ) -> Result<()> {
+        let sequence = self.load_saved_sequence(script_config, saved, transactions).await?;
+
+        let old = sequence
+            .transactions
+            .iter()
+            .map(|tx| DiffedTransaction::saved(tx, decoder, known_contracts))
+            .collect::<Vec<_>>();
+        let new = transactions
+            .iter()
+            .map(|tx| {
+                let tx = TransactionWithMetadata::from_tx_request(tx.transaction.clone());
+                DiffedTransaction::new(tx.typed_tx(), decoder, known_contracts)
+            })
+            .collect::<Vec<_>>();
+        let diff = SequenceDiff::new(sequence.path, &old, &new);
+
+        if self.json {
+            shell::println(serde_json::to_string(&diff)?)?;
+        } else {
+            shell::println(format!("\n{diff}"))?;
+        }
+        if !diff.is_empty() {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+
+    /// Loads the saved sequence, on the chain of the transactions for the latest one.
+    async fn load_saved_sequence(
+        &self,
+        script_config: &ScriptConfig,
+        saved: &SavedSequence,
+        transactions: &BroadcastableTransactions,
+    ) -> Result<ScriptSequence> {
+        let config = &script_config.config;
+        let target = script_config.target_contract();
+        let encryption = self.sequence_encryption(config);
+        match saved {
+            SavedSequence::Path(path) => {
+                let (mut sequence, cipher): (ScriptSequence, _) =
+                    encryption.read(path, &target.identifier())?;
+                sequence.path = path.clone();
+                sequence.cipher = cipher;
+                Ok(sequence)
+            }
+            SavedSequence::Latest => {
+                let rpc = transactions
+                    .iter()
+                    .find_map(|tx| tx.rpc.clone())
+                    .or_else(|| script_config.evm_opts.fork_url.clone());
+                let chain = match rpc {
+                    Some(rpc) => try_get_http_provider(rpc)?.get_chainid().await?.as_u64(),
+                    None => config.chain.unwrap_or_default().id(),
+                };
+                match ScriptSequence::load(config, &self.sig, target, chain, true, &encryption) {
+                    Ok(sequence) => Ok(sequence),
+                    Err(err) if err.is::<SequenceDecryptionError>() => Err(err),
+                    Err(_) => {
+                        ScriptSequence::load(config, &self.sig, target, chain, false, &encryption)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(function: &str, arguments: &[&str]) -> DiffedTransaction {
+        DiffedTransaction {
+            to: Some(Address::repeat_byte(1)),
+            data: Bytes::from(format!("{function}{arguments:?}").into_bytes()),
+            contract: Some("Counter".to_string()),
+            function: Some(function.to_string()),
+            arguments: Some(arguments.iter().map(|arg| arg.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn deployment(code: &[u8], args: &[u8]) -> DiffedTransaction {
+        DiffedTransaction {
+            data: [code, args].concat().into(),
+            contract: Some("Counter".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Runtime code followed by solc's ipfs metadata with the given hash byte.
+    fn code_with_metadata(hash: u8) -> Vec<u8> {
+        let mut code = vec![0x60, 0x80, 0x60, 0x40];
+        code.extend([0xa2, 0x64]);
+        code.extend(b"ipfs\x58\x22");
+        code.extend([hash; 34]);
+        code.extend(b"\x64solc\x43\x00\x08\x18");
+        code.extend([0x00, 0x33]);
+        code
+    }
+
+    #[test]
+    fn splits_init_code() {
+        let code = code_with_metadata(1);
+        let args = [0u8; 32];
+        let init_code = [&code[..], &args].concat();
+        assert_eq!(split_init_code(&init_code), (&code[..], &args[..]));
+        assert_eq!(split_init_code(&[0x60, 0x80]), (&[0x60, 0x80][..], &[][..]));
+
+        // arguments that look like the end of metadata
+        let args = [&[0xa2, 0x64][..], &[0u8; 30], &[0x00, 0x20]].concat();
+        let init_code = [&code[..], &args].concat();
+        assert_eq!(split_init_code(&init_code), (&code[..], &args[..]));
+
+        assert_ne!(code_with_metadata(1), code_with_metadata(2));
+        assert_eq!(
+            strip_bytecode_metadata(&code_with_metadata(1)),
+            strip_bytecode_metadata(&code_with_metadata(2))
+        );
+    }
+
+    #[test]
+    fn diffs_sequences() {
+        let old = [
+            deployment(&code_with_metadata(1), &[1]),
+            call("setNumber(uint256)", &["1"]),
+            call("increment()", &[]),
+        ];
+        let new = [
+            deployment(&code_with_metadata(2), &[1]),
+            call("setNumber(uint256)", &["2"]),
+            call("reset()", &[]),
+        ];
+        let diff = SequenceDiff::new("run-latest.json".into(), &old, &new);
+        assert_eq!(
+            diff.transactions,
+            [
+                TransactionDiff::Changed {
+                    old_index: 0,
+                    index: 0,
+                    transaction: "new Counter".to_string(),
+                    changes: vec![FieldChange {
+                        field: "bytecode (metadata hash only)".to_string(),
+                        old: describe_code(&code_with_metadata(1)),
+                        new: describe_code(&code_with_metadata(2)),
+                    }],
+                },
+                TransactionDiff::Changed {
+                    old_index: 1,
+                    index: 1,
+                    transaction: "Counter::setNumber(uint256)".to_string(),
+                    changes: vec![FieldChange {
+                        field: "arguments[0]".to_string(),
+                        old: "1".to_string(),
+                        new: "2".to_string(),
+                    }],
+                },
+                TransactionDiff::Removed {
+                    index: 2,
+                    transaction: "Counter::increment()".to_string()
+                },
+                TransactionDiff::Added { index: 2, transaction: "Counter::reset()".to_string() },
+            ]
+        );
+
+        let diff = SequenceDiff::new("run-latest.json".into(), &old, &old);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences with run-latest.json");
+    }
+}
@@ -0,0 +1,300 @@
+//! Encryption of the broadcast sequences at rest.
+
+use super::ScriptArgs;
+use age::{
+    armor::{ArmoredReader, ArmoredWriter, Format},
+    secrecy::{Secret, SecretString},
+    x25519,
+};
+use eyre::{Result, WrapErr};
+use foundry_common::fs;
+use foundry_config::Config;
+use once_cell::sync::OnceCell;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    io::{BufWriter, IsTerminal, Read, Write},
+    iter,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The environment variable of the passphrase the sequences are encrypted with.
+pub const SEQUENCE_PASSWORD_ENV: &str = "FOUNDRY_SEQUENCE_PASSWORD";
+
+/// The plaintext details of an encrypted sequence, kept for listing deployments.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SequenceHeader {
+    pub chains: Vec<u64>,
+    pub script: String,
+    pub timestamp: u64,
+    pub transactions: usize,
+}
+
+/// A sequence file encrypted with age, whose payload is ASCII armored.
+#[derive(Serialize, Deserialize)]
+struct EncryptedSequence {
+    encrypted: SequenceHeader,
+    payload: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SequenceDecryptionError {
+    #[error("{} is encrypted to an X25519 recipient, pass its identity with `--sequence-identity`", .0.display())]
+    MissingIdentity(PathBuf),
+    #[error("{} is encrypted with a passphrase, set {SEQUENCE_PASSWORD_ENV} to decrypt it", .0.display())]
+    MissingPassphrase(PathBuf),
+    #[error("could not decrypt {}: {1}", .0.display())]
+    Decrypt(PathBuf, age::DecryptError),
+}
+
+/// The key the sequences are encrypted with.
+#[derive(Clone)]
+pub enum SequenceKey {
+    Passphrase(SecretString),
+    /// An X25519 identity, whose recipient the sequences are encrypted to.
+    Identity(x25519::Identity),
+    /// An X25519 recipient. Sequences encrypted to it can only be decrypted with its identity.
+    Recipient(x25519::Recipient),
+}
+
+impl SequenceKey {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<String> {
+        let encryptor = match self {
+            Self::Passphrase(passphrase) => {
+                age::Encryptor::with_user_passphrase(passphrase.clone())
+            }
+            Self::Identity(identity) => encrypt_to(identity.to_public()),
+            Self::Recipient(recipient) => encrypt_to(recipient.clone()),
+        };
+
+        let mut armored = vec![];
+        let mut writer =
+            encryptor.wrap_output(ArmoredWriter::wrap_output(&mut armored, Format::AsciiArmor)?)?;
+        writer.write_all(plaintext)?;
+        writer.finish()?.finish()?;
+        Ok(String::from_utf8(armored)?)
+    }
+}
+
+/// Decrypts the payload with the key it was encrypted with: the passphrase if it's encrypted with
+/// one, or else the X25519 identity.
+fn decrypt(
+    path: &Path,
+    payload: &str,
+    passphrase: impl FnOnce() -> Result<Option<SecretString>>,
+    identity: impl FnOnce() -> Result<Option<x25519::Identity>>,
+) -> Result<(Vec<u8>, SequenceKey)> {
+    let decrypt_err = |err| SequenceDecryptionError::Decrypt(path.to_path_buf(), err);
+    let decryptor =
+        age::Decryptor::new(ArmoredReader::new(payload.as_bytes())).map_err(decrypt_err)?;
+    let (mut reader, key) = match decryptor {
+        age::Decryptor::Passphrase(decryptor) => {
+            let passphrase = passphrase()?
+                .ok_or_else(|| SequenceDecryptionError::MissingPassphrase(path.into()))?;
+            let reader = decryptor.decrypt(&passphrase, None).map_err(decrypt_err)?;
+            (reader, SequenceKey::Passphrase(passphrase))
+        }
+        age::Decryptor::Recipients(decryptor) => {
+            let identity =
+                identity()?.ok_or_else(|| SequenceDecryptionError::MissingIdentity(path.into()))?;
+            let reader = decryptor
+                .decrypt(iter::once(&identity as &dyn age::Identity))
+                .map_err(decrypt_err)?;
+            (reader, SequenceKey::Identity(identity))
+        }
+    };
+    let mut plaintext = vec![];
+    reader.read_to_end(&mut plaintext)?;
+    Ok((plaintext, key))
+}
+
+fn encrypt_to(recipient: x25519::Recipient) -> age::Encryptor {
+    let recipients: Vec<Box<dyn age::Recipient + Send>> = vec![Box::new(recipient)];
+    age::Encryptor::with_recipients(recipients).expect("has a recipient")
+}
+
+/// The key a sequence is encrypted with, and the name of its script for the plaintext header.
+#[derive(Clone)]
+pub struct SequenceCipher {
+    key: SequenceKey,
+    script: String,
+}
+
+/// Writes the sequence as JSON, encrypted with a plaintext header of its details if `cipher` is
+/// set.
+pub fn write_sequence<T: Serialize>(
+    path: &Path,
+    sequence: &T,
+    cipher: Option<&SequenceCipher>,
+    chains: Vec<u64>,
+    timestamp: u64,
+    transactions: usize,
+) -> Result<()> {
+    let mut writer = BufWriter::new(fs::create_file(path)?);
+    if let Some(SequenceCipher { key, script }) = cipher {
+        let encrypted = SequenceHeader { chains, script: script.clone(), timestamp, transactions };
+        let payload = key.encrypt(&serde_json::to_vec(sequence)?)?;
+        serde_json::to_writer_pretty(&mut writer, &EncryptedSequence { encrypted, payload })?;
+    } else {
+        serde_json::to_writer_pretty(&mut writer, sequence)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// How the sequences of a script are encrypted, as configured with `--encrypt-sequence`,
+/// `--sequence-recipient`, `--sequence-identity` and `script.encrypt_sequences`.
+#[derive(Clone, Debug, Default)]
+pub struct SequenceEncryption {
+    /// Whether new sequences are encrypted.
+    pub encrypt: bool,
+    /// The X25519 recipient to encrypt new sequences to, instead of a passphrase.
+    pub recipient: Option<String>,
+    /// The file of the X25519 identity to encrypt and decrypt sequences with.
+    pub identity: Option<PathBuf>,
+}
+
+impl SequenceEncryption {
+    /// Returns the cipher to encrypt new sequences of `script` with, if they're encrypted.
+    pub fn cipher(&self, script: &str) -> Result<Option<SequenceCipher>> {
+        let key = if let Some(identity) = self.read_identity()? {
+            SequenceKey::Identity(identity)
+        } else if let Some(recipient) = &self.recipient {
+            let recipient = x25519::Recipient::from_str(recipient)
+                .map_err(|err| eyre::eyre!("invalid sequence recipient: {err}"))?;
+            SequenceKey::Recipient(recipient)
+        } else if self.encrypt {
+            let passphrase = passphrase()?.ok_or_else(|| {
+                eyre::eyre!(
+                    "encrypting sequences requires a passphrase, set {SEQUENCE_PASSWORD_ENV}"
+                )
+            })?;
+            SequenceKey::Passphrase(passphrase)
+        } else {
+            return Ok(None)
+        };
+        Ok(Some(SequenceCipher { key, script: script.to_string() }))
+    }
+
+    /// Reads the sequence file, decrypting it if it's encrypted.
+    ///
+    /// Returns the sequence with the cipher to save it with: the one it was decrypted with, or the
+    /// one of new sequences if it wasn't encrypted.
+    pub fn read<T: DeserializeOwned>(
+        &self,
+        path: &Path,
+        script: &str,
+    ) -> Result<(T, Option<SequenceCipher>)> {
+        let value: serde_json::Value = fs::read_json_file(path)?;
+        if value.get("encrypted").is_none() {
+            return Ok((serde_json::from_value(value)?, self.cipher(script)?))
+        }
+
+        let EncryptedSequence { payload, .. } = serde_json::from_value(value)?;
+        let (plaintext, key) = decrypt(path, &payload, passphrase, || self.read_identity())?;
+        let sequence = serde_json::from_slice(&plaintext)
+            .wrap_err_with(|| format!("invalid decrypted sequence {}", path.display()))?;
+        Ok((sequence, Some(SequenceCipher { key, script: script.to_string() })))
+    }
+
+    fn read_identity(&self) -> Result<Option<x25519::Identity>> {
+        let Some(path) = &self.identity else { return Ok(None) };
+        let identity = fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("AGE-SECRET-KEY-"))
+            .ok_or_else(|| eyre::eyre!("no X25519 identity found in {}", path.display()))
+            .and_then(|line| {
+                x25519::Identity::from_str(line)
+                    .map_err(|err| eyre::eyre!("invalid identity in {}: {err}", path.display()))
+            })?;
+        Ok(Some(identity))
+    }
+}
+
+impl ScriptArgs {
+    /// Returns how the saved sequences of the script are encrypted.
+    pub(super) fn sequence_encryption(&self, config: &Config) -> SequenceEncryption {
+        SequenceEncryption {
+            encrypt: self.encrypt_sequence || config.script_settings.encrypt_sequences,
+            recipient: self.sequence_recipient.clone(),
+            identity: self.sequence_identity.clone(),
+        }
+    }
+}
+
+/// Returns the passphrase of [`SEQUENCE_PASSWORD_ENV`], or prompts for it once if stdin is a
+/// terminal.
+fn passphrase() -> Result<Option<SecretString>> {
+    static PASSPHRASE: OnceCell<Option<SecretString>> = OnceCell::new();
+    PASSPHRASE
+        .get_or_try_init(|| {
+            if let Ok(passphrase) = std::env::var(SEQUENCE_PASSWORD_ENV) {
+                return Ok(Some(Secret::new(passphrase)))
+            }
+            if !std::io::stdin().is_terminal() {
+                return Ok(None)
+            }
+            let passphrase = rpassword::prompt_password("Enter the sequence passphrase: ")?;
+            Ok(Some(Secret::new(passphrase)))
+        })
+        .map(Option::clone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+
+    #[test]
+    fn encrypts_and_decrypts_sequences() {
+        let identity = x25519::Identity::generate();
+        let passphrase = || Ok(Some(Secret::new("passphrase".to_string())));
+        let path = Path::new("run-latest.json");
+        for key in [
+            SequenceKey::Passphrase(Secret::new("passphrase".to_string())),
+            SequenceKey::Recipient(identity.to_public()),
+        ] {
+            let payload = key.encrypt(b"{\"transactions\":[]}").unwrap();
+            assert!(payload.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+
+            let (plaintext, decryption_key) =
+                decrypt(path, &payload, passphrase, || Ok(Some(identity.clone()))).unwrap();
+            assert_eq!(plaintext, b"{\"transactions\":[]}");
+
+            // only the key of the payload's kind decrypts it
+            let missing_key = match key {
+                SequenceKey::Passphrase(_) => {
+                    assert!(matches!(decryption_key, SequenceKey::Passphrase(_)));
+                    decrypt(path, &payload, || Ok(None), || Ok(Some(identity.clone())))
+                }
+                _ => {
+                    assert!(matches!(decryption_key, SequenceKey::Identity(_)));
+                    decrypt(path, &payload, passphrase, || Ok(None))
+                }
+            };
+            assert!(missing_key.is_err());
+        }
+    }
+
+    #[test]
+    fn reads_passphrase_encrypted_sequence_with_identity() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run-latest.json");
+        let identity = dir.path().join("identity.txt");
+        fs::write(&identity, x25519::Identity::generate().to_string().expose_secret()).unwrap();
+
+        let sequence = serde_json::json!({ "transactions": [] });
+        let cipher = SequenceCipher {
+            key: SequenceKey::Passphrase(Secret::new("passphrase".to_string())),
+            script: "Script".to_string(),
+        };
+        write_sequence(&path, &sequence, Some(&cipher), vec![1], 0, 0).unwrap();
+
+        std::env::set_var(SEQUENCE_PASSWORD_ENV, "passphrase");
+        let encryption = SequenceEncryption { identity: Some(identity), ..Default::default() };
+        let (read, cipher) = encryption.read::<serde_json::Value>(&path, "Script").unwrap();
+        assert_eq!(read, sequence);
+        assert!(matches!(cipher.unwrap().key, SequenceKey::Passphrase(_)));
+    }
+}
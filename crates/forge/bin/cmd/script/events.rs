@@ -0,0 +1,217 @@
+//! Decoded events of the broadcast transactions, and the checks of the expected ones.
+
+use super::{sequence::ScriptSequence, summary::describe, ScriptArgs};
+use alloy_primitives::{Address, LogData, TxHash};
+use ethers_core::types::{Log, TransactionReceipt};
+use eyre::{Context, Result};
+use forge::traces::{CallTraceDecoder, DecodedCallLog};
+use foundry_common::{abi::get_event, types::ToAlloy};
+use foundry_config::ExpectedEvent;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A log of a receipt, decoded with the ABIs of the known contracts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedReceiptLog {
+    pub address: Address,
+    /// The name of the emitting contract, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emitter: Option<String>,
+    /// The decoded event, e.g. `Transfer(from: 0x..., to: 0x..., value: 1)`, or its topics and
+    /// data if its ABI is unknown.
+    pub event: String,
+}
+
+impl fmt::Display for DecodedReceiptLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.emitter {
+            Some(emitter) => write!(f, "{emitter} [{}]: {}", self.address, self.event),
+            None => write!(f, "{}: {}", self.address, self.event),
+        }
+    }
+}
+
+impl ScriptArgs {
+    /// Saves the decoded events of the confirmed transactions into the sequence, if they weren't
+    /// decoded when their receipt was printed, and checks the expected events.
+    pub(super) async fn check_events(
+        &self,
+        sequence: &mut ScriptSequence,
+        decoder: &CallTraceDecoder,
+        expected: &[ExpectedEvent],
+    ) -> Result<()> {
+        for receipt in &sequence.receipts {
+            let hash = receipt.transaction_hash.to_alloy();
+            if receipt.logs.is_empty() || sequence.decoded_logs.contains_key(&hash) {
+                continue
+            }
+            let logs = decode_receipt_logs(decoder, receipt).await;
+            sequence.decoded_logs.insert(hash, logs);
+        }
+
+        let missing = missing_events(sequence, decoder, expected)?;
+        if !missing.is_empty() {
+            eyre::bail!("expected events are missing from the receipts:\n{}", missing.join("\n"))
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the logs of a receipt.
+pub(super) async fn decode_receipt_logs(
+    decoder: &CallTraceDecoder,
+    receipt: &TransactionReceipt,
+) -> Vec<DecodedReceiptLog> {
+    let mut logs = Vec::with_capacity(receipt.logs.len());
+    for log in &receipt.logs {
+        logs.push(decode_log(decoder, log).await);
+    }
+    logs
+}
+
+/// Decodes the log of a receipt.
+async fn decode_log(decoder: &CallTraceDecoder, log: &Log) -> DecodedReceiptLog {
+    let address = log.address.to_alloy();
+    let data = LogData::new_unchecked(
+        log.topics.iter().map(|topic| topic.to_alloy()).collect(),
+        log.data.0.clone().into(),
+    );
+    let event = match decoder.decode_event(&data).await {
+        DecodedCallLog::Decoded(name, params) => format!(
+            "{name}({})",
+            params
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        DecodedCallLog::Raw(data) => format!(
+            "topics: [{}], data: {}",
+            data.topics().iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+            data.data
+        ),
+    };
+    DecodedReceiptLog { address, emitter: decoder.labels.get(&address).cloned(), event }
+}
+
+/// Returns the expected events missing from the receipts of the sequence.
+///
+/// The events are matched by the selector of their signature, which may name and index its
+/// parameters, e.g. `Upgraded(address indexed implementation)`.
+fn missing_events(
+    sequence: &ScriptSequence,
+    decoder: &CallTraceDecoder,
+    expected: &[ExpectedEvent],
+) -> Result<Vec<String>> {
+    let mut missing = vec![];
+    for expected in expected {
+        let hashes = sequence
+            .transactions
+            .iter()
+            .filter(|tx| {
+                describe(tx.contract_name.as_deref(), tx.function.as_deref()).as_deref() ==
+                    Some(expected.transaction.as_str())
+            })
+            .filter_map(|tx| tx.hash)
+            .collect::<Vec<_>>();
+        if hashes.is_empty() {
+            // the transaction might have been broadcast on another chain
+            if !sequence.multi {
+                missing.push(format!("  no transaction labeled `{}`", expected.transaction));
+            }
+            continue
+        }
+
+        let topic = get_event(&expected.event)
+            .wrap_err_with(|| format!("invalid expected event `{}`", expected.event))?
+            .selector();
+        let emitted = sequence
+            .receipts
+            .iter()
+            .filter(|receipt| hashes.contains(&receipt.transaction_hash.to_alloy()))
+            .flat_map(|receipt| &receipt.logs)
+            .any(|log| {
+                log.topics.first().map(|t| t.to_alloy()) == Some(topic) &&
+                    expected.emitter.as_deref().map_or(true, |emitter| {
+                        is_emitter(decoder, log.address.to_alloy(), emitter)
+                    })
+            });
+        if !emitted {
+            let emitter =
+                expected.emitter.as_deref().map(|e| format!(" by {e}")).unwrap_or_default();
+            missing.push(format!("  {}{emitter} in `{}`", expected.event, expected.transaction));
+        }
+    }
+    Ok(missing)
+}
+
+/// Returns whether `emitter`, an address or a contract name, designates `address`.
+fn is_emitter(decoder: &CallTraceDecoder, address: Address, emitter: &str) -> bool {
+    if let Ok(emitter) = emitter.parse::<Address>() {
+        return emitter == address
+    }
+    decoder.labels.get(&address).is_some_and(|label| label == emitter) ||
+        decoder.contracts.get(&address).is_some_and(|contract| {
+            contract == emitter || contract.rsplit(':').next() == Some(emitter)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::script::transaction::TransactionWithMetadata;
+    use alloy_primitives::keccak256;
+    use foundry_common::types::ToEthers;
+
+    #[test]
+    fn finds_missing_events() {
+        let upgraded = keccak256("Upgraded(address)");
+        let proxy = Address::repeat_byte(1);
+        let log = Log {
+            address: proxy.to_ethers(),
+            topics: vec![upgraded.to_ethers()],
+            ..Default::default()
+        };
+
+        // multi chain sequences aren't saved on drop
+        let mut sequence = ScriptSequence { multi: true, ..Default::default() };
+        sequence.transactions = [TransactionWithMetadata {
+            hash: Some(TxHash::repeat_byte(1)),
+            contract_name: Some("Proxy".to_string()),
+            ..Default::default()
+        }]
+        .into();
+        sequence.receipts = vec![TransactionReceipt {
+            transaction_hash: [1; 32].into(),
+            logs: vec![log],
+            ..Default::default()
+        }];
+
+        let mut decoder = CallTraceDecoder::default();
+        decoder.labels.insert(proxy, "Proxy".to_string());
+
+        let expected = |event: &str, emitter: Option<&str>| ExpectedEvent {
+            transaction: "new Proxy".to_string(),
+            event: event.to_string(),
+            emitter: emitter.map(str::to_string),
+        };
+        let missing = |expected| missing_events(&sequence, &decoder, &[expected]).unwrap();
+        assert!(missing(expected("Upgraded(address)", None)).is_empty());
+        assert!(missing(expected("Upgraded(address)", Some("Proxy"))).is_empty());
+        assert!(missing(expected("Upgraded(address)", Some(&proxy.to_string()))).is_empty());
+        // parameter names and `indexed` aren't part of the selector
+        assert!(
+            missing(expected("event Upgraded(address indexed implementation)", None)).is_empty()
+        );
+        assert_eq!(
+            missing(expected("Upgraded(address)", Some("Token"))),
+            ["  Upgraded(address) by Token in `new Proxy`"]
+        );
+        assert_eq!(
+            missing(expected("AdminChanged(address,address)", None)),
+            ["  AdminChanged(address,address) in `new Proxy`"]
+        );
+        assert!(missing_events(&sequence, &decoder, &[expected("Upgraded(", None)]).is_err());
+    }
+}
@@ -1,26 +1,31 @@
 use super::{
     artifacts::ArtifactInfo,
+    checks::{SimulatedBalance, SimulatedBalances},
     runner::{ScriptRunner, SimulationStage},
     transaction::{AdditionalContract, TransactionWithMetadata},
-    ScriptArgs, ScriptConfig, ScriptResult,
+    ScriptArgs, ScriptConfig, ScriptFork, ScriptResult,
 };
 use alloy_primitives::{Address, Bytes, U256};
 use eyre::{Context, Result};
 use forge::{
     backend::Backend,
     executors::ExecutorBuilder,
-    inspectors::{cheatcodes::BroadcastableTransactions, CheatsConfig},
+    inspectors::{
+        cheatcodes::{BroadcastBalanceAssertion, BroadcastableTransactions},
+        CheatsConfig,
+    },
     traces::{render_trace_arena, CallTraceDecoder},
 };
 use foundry_cli::utils::{ensure_clean_constructor, needs_setup};
 use foundry_common::{get_contract_name, provider::ethers::RpcUrl, shell, ContractsByArtifact};
 use foundry_compilers::artifacts::ContractBytecodeSome;
-use foundry_evm::inspectors::cheatcodes::ScriptWallets;
+use foundry_evm::{fork::FetchStats, inspectors::cheatcodes::ScriptWallets};
 use futures::future::join_all;
 use parking_lot::RwLock;
 use std::{
-    collections::{BTreeMap, HashMap, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 impl ScriptArgs {
@@ -44,9 +49,14 @@ impl ScriptArgs {
 
         ensure_clean_constructor(&abi)?;
 
+        let start = Instant::now();
         let mut runner = self
             .prepare_runner(script_config, sender, SimulationStage::Local, Some(script_wallets))
             .await?;
+        let setup_time = start.elapsed();
+
+        FetchStats::take();
+        let start = Instant::now();
         let (address, mut result) = runner.setup(
             predeploy_libraries,
             bytecode,
@@ -71,6 +81,7 @@ impl ScriptArgs {
             result.labeled_addresses.extend(script_result.labeled_addresses);
             result.returned = script_result.returned;
             result.breakpoints = script_result.breakpoints;
+            result.balance_assertions = script_result.balance_assertions;
 
             match (&mut result.transactions, script_result.transactions) {
                 (Some(txs), Some(new_txs)) => {
@@ -83,20 +94,27 @@ impl ScriptArgs {
             }
         }
 
+        if script_config.evm_opts.fork_url.is_some() {
+            print_timings(script_config, "Script execution", setup_time, start.elapsed())?;
+        }
+
         Ok(result)
     }
 
     /// Simulates onchain state by executing a list of transactions locally and persisting their
-    /// state. Returns the transactions and any CREATE2 contract address created.
+    /// state. Returns the transactions and any CREATE2 contract address created, along with the
+    /// simulated balances of their senders and of the accounts of `balance_assertions`.
     pub async fn onchain_simulation(
         &self,
         transactions: BroadcastableTransactions,
+        balance_assertions: &[BroadcastBalanceAssertion],
         script_config: &ScriptConfig,
         decoder: &CallTraceDecoder,
         contracts: &ContractsByArtifact,
-    ) -> Result<VecDeque<TransactionWithMetadata>> {
+    ) -> Result<(VecDeque<TransactionWithMetadata>, SimulatedBalances)> {
         trace!(target: "script", "executing onchain simulation");
 
+        let start = Instant::now();
         let runners = Arc::new(
             self.build_runners(script_config)
                 .await?
@@ -104,6 +122,10 @@ impl ScriptArgs {
                 .map(|(rpc, runner)| (rpc, Arc::new(RwLock::new(runner))))
                 .collect::<HashMap<_, _>>(),
         );
+        let setup_time = start.elapsed();
+
+        FetchStats::take();
+        let start = Instant::now();
 
         if script_config.evm_opts.verbosity > 3 {
             println!("==========================");
@@ -130,6 +152,22 @@ impl ScriptArgs {
             })
             .collect();
 
+        let accounts = transactions
+            .iter()
+            .filter_map(|tx| Some((tx.rpc.clone()?, tx.transaction.from?)))
+            .chain(
+                balance_assertions
+                    .iter()
+                    .filter_map(|assertion| Some((assertion.rpc.clone()?, assertion.account))),
+            )
+            .collect::<BTreeSet<_>>();
+        let mut balances = SimulatedBalances::new();
+        for (rpc, account) in &accounts {
+            let Some(runner) = runners.get(rpc) else { continue };
+            let start = runner.read().executor.get_balance(*account)?;
+            balances.insert((rpc.clone(), *account), SimulatedBalance { start, end: start });
+        }
+
         let mut final_txs = VecDeque::new();
 
         // Executes all transactions from the different forks concurrently.
@@ -140,6 +178,7 @@ impl ScriptArgs {
                 let mut runner = runners.get(rpc).expect("invalid rpc url").write();
 
                 let mut tx = transaction.transaction;
+                let calldata = tx.input.clone().into_input().unwrap_or_default();
                 let result = runner
                     .simulate(
                         tx.from
@@ -184,7 +223,22 @@ impl ScriptArgs {
                     }
                     // We inflate the gas used by the user specified percentage
                     None => {
-                        let gas = U256::from(result.gas_used * self.gas_estimate_multiplier / 100);
+                        let chain = runner.executor.env.cfg.chain_id;
+                        let multiplier =
+                            self.gas_estimate_multiplier_for(&script_config.config, chain);
+                        // The EVM's gas costs are replaced by the ones of the chain, if it
+                        // overrides them.
+                        let config = &script_config.config;
+                        let gas_used = match config.script_settings.gas_overrides(chain) {
+                            Some(overrides) => overrides.apply(
+                                result.gas_used,
+                                &calldata,
+                                tx.to.is_none(),
+                                config.evm_version,
+                            ),
+                            None => result.gas_used,
+                        };
+                        let gas = U256::from(gas_used * multiplier / 100);
                         tx.gas = Some(gas);
                     }
                 }
@@ -232,7 +286,13 @@ impl ScriptArgs {
             eyre::bail!("Simulated execution failed.")
         }
 
-        Ok(final_txs)
+        for ((rpc, account), balance) in balances.iter_mut() {
+            balance.end = runners[rpc].read().executor.get_balance(*account)?;
+        }
+
+        print_timings(script_config, "On-chain simulation", setup_time, start.elapsed())?;
+
+        Ok((final_txs, balances))
     }
 
     /// Build the multiple runners from different forks.
@@ -273,30 +333,53 @@ impl ScriptArgs {
         script_wallets: Option<ScriptWallets>,
     ) -> Result<ScriptRunner> {
         trace!("preparing script runner");
-        let env = script_config.evm_opts.evm_env().await?;
-
-        // The db backend that serves all the data.
-        let db = match &script_config.evm_opts.fork_url {
-            Some(url) => match script_config.backends.get(url) {
-                Some(db) => db.clone(),
-                None => {
-                    let fork = script_config.evm_opts.get_fork(&script_config.config, env.clone());
-                    let backend = Backend::spawn(fork);
-                    script_config.backends.insert(url.clone(), backend.clone());
-                    backend
-                }
-            },
+
+        // The db backend that serves all the data, and the environment of its block.
+        let (env, db) = match script_config.cached_fork() {
+            // The fork is pinned to a block, so the state fetched by a previous execution is still
+            // valid and doesn't need to be fetched again.
+            Some(fork) => {
+                trace!(target: "script", url=?script_config.evm_opts.fork_url, "reusing fork backend");
+                let mut env = fork.env.clone();
+                env.tx.caller = script_config.evm_opts.sender;
+                (env, fork.backend.clone())
+            }
             None => {
-                // It's only really `None`, when we don't pass any `--fork-url`. And if so, there is
-                // no need to cache it, since there won't be any onchain simulation that we'd need
-                // to cache the backend for.
-                Backend::spawn(script_config.evm_opts.get_fork(&script_config.config, env.clone()))
+                let env = script_config.evm_opts.evm_env().await?;
+                let backend = Backend::spawn(
+                    script_config.evm_opts.get_fork(&script_config.config, env.clone()),
+                );
+                // It's only really `None`, when we don't pass any `--fork-url`. And if so, there
+                // is no need to cache it, since there won't be any onchain simulation that we'd
+                // need to cache the backend for.
+                if let Some(url) = &script_config.evm_opts.fork_url {
+                    script_config.backends.insert(
+                        url.clone(),
+                        ScriptFork { env: env.clone(), backend: backend.clone() },
+                    );
+                }
+                (env, backend)
             }
         };
 
+        // Each fork of a multi-chain run is executed with the settings of its chain, so they have
+        // to be applied to the environment before the executor is built from it.
+        if script_config.evm_opts.fork_url.is_some() {
+            let chain = env.cfg.chain_id;
+            script_config.config.apply_chain_overrides(chain);
+            if let Some(gas_limit) =
+                script_config.config.chains.get(chain).and_then(|chain| chain.gas_limit)
+            {
+                script_config.evm_opts.env.gas_limit = gas_limit.0;
+                env.block.gas_limit = U256::from(gas_limit.0);
+            }
+        }
+
         // We need to enable tracing to decode contract names: local or external.
         let mut builder = ExecutorBuilder::new()
-            .inspectors(|stack| stack.trace(true))
+            .inspectors(|stack| {
+                stack.trace(true).trace_state_changes(script_config.evm_opts.verbosity >= 5)
+            })
             .spec(script_config.config.evm_spec_id())
             .gas_limit(script_config.evm_opts.gas_limit());
 
@@ -305,21 +388,50 @@ impl ScriptArgs {
                 stack
                     .debug(self.debug)
                     .cheatcodes(
-                        CheatsConfig::new(
-                            &script_config.config,
-                            script_config.evm_opts.clone(),
-                            script_wallets,
-                        )
+                        CheatsConfig {
+                            revert_decoder: script_config.revert_decoder.clone(),
+                            ..CheatsConfig::new(
+                                &script_config.config,
+                                script_config.evm_opts.clone(),
+                                script_wallets,
+                            )
+                        }
                         .into(),
                     )
                     .enable_isolation(script_config.evm_opts.isolate)
+                    .isolate_block_advance(script_config.evm_opts.isolate_block_advance)
             });
         }
 
-        Ok(ScriptRunner::new(
-            builder.build(env, db),
-            script_config.evm_opts.initial_balance,
-            sender,
-        ))
+        let mut executor = builder.build(env, db);
+        // The stack of the steps is needed to resolve the stack trace of a failure.
+        if let SimulationStage::Local = stage {
+            executor.inspector.tracing_internal_calls(self.stack_traces);
+        }
+
+        Ok(ScriptRunner::new(executor, script_config.evm_opts.initial_balance, sender))
+    }
+}
+
+/// Prints, at `-vvv`, how long `stage` spent fetching state from the forks and executing.
+///
+/// Setting up the forks and waiting for the values missing from their caches is counted as
+/// fetching, the rest as execution.
+fn print_timings(
+    script_config: &ScriptConfig,
+    stage: &str,
+    setup_time: Duration,
+    elapsed: Duration,
+) -> Result<()> {
+    let stats = FetchStats::take();
+    if script_config.evm_opts.verbosity < 3 {
+        return Ok(())
     }
+    shell::println(format!(
+        "{stage}: {:?} state fetch ({} values fetched), {:?} execution",
+        setup_time + stats.wait_time,
+        stats.fetches,
+        elapsed.saturating_sub(stats.wait_time),
+    ))?;
+    Ok(())
 }
@@ -0,0 +1,259 @@
+//! Reuse of the libraries deployed by previous broadcasts.
+
+use super::{encryption::SequenceEncryption, ScriptArgs, ScriptConfig};
+use alloy_primitives::{Address, Bytes, TxHash};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, U64};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{
+    contracts::has_call_protection,
+    provider::ethers::try_get_http_provider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_compilers::{artifacts::Libraries, ArtifactId};
+use foundry_linking::{LinkOutput, Linker};
+use revm_inspectors::tracing::types::CallKind;
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    str::FromStr,
+};
+
+/// The fields of a saved sequence needed to find the contracts it deployed.
+#[derive(Deserialize)]
+struct SavedSequence {
+    transactions: Vec<SavedTransaction>,
+    #[serde(default)]
+    receipts: Vec<SavedReceipt>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedTransaction {
+    hash: Option<TxHash>,
+    #[serde(rename = "transactionType")]
+    opcode: CallKind,
+    contract_address: Option<Address>,
+    transaction: TypedTransaction,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedReceipt {
+    transaction_hash: TxHash,
+    status: Option<U64>,
+}
+
+impl ScriptArgs {
+    /// Adds the libraries that previous broadcasts already deployed on the chain of the fork to
+    /// `libraries`, so they're linked instead of deployed again.
+    ///
+    /// A library is reused if a broadcast sequence of the project on the same chain successfully
+    /// deployed its exact creation code, and the code at the deployment is still the library's
+    /// runtime code. Disabled with `--force-deploy-libraries`.
+    pub(super) async fn reuse_deployed_libraries(
+        &self,
+        script_config: &ScriptConfig,
+        linker: &Linker,
+        mut libraries: Libraries,
+        sender: Address,
+        nonce: u64,
+    ) -> Result<Libraries> {
+        if self.force_deploy_libraries {
+            return Ok(libraries)
+        }
+        let Some(fork_url) = script_config.evm_opts.fork_url.as_deref() else {
+            return Ok(libraries)
+        };
+        let provider = try_get_http_provider(fork_url)?;
+        let chain = provider.get_chainid().await?.as_u64();
+
+        let encryption = self.sequence_encryption(&script_config.config);
+        let deployed = deployed_contracts(&script_config.config.broadcast, chain, &encryption);
+        if deployed.is_empty() {
+            return Ok(libraries)
+        }
+
+        let target = script_config.target_contract();
+        let mut mismatched = HashSet::new();
+        let mut reused = vec![];
+        loop {
+            let LinkOutput { libraries: linked, libs_to_deploy } =
+                linker.link_with_nonce_or_address(libraries.clone(), sender, nonce, target)?;
+
+            let mut found = None;
+            for (i, code) in libs_to_deploy.iter().enumerate() {
+                let Some(&address) = deployed.get(code) else { continue };
+                if mismatched.contains(&address) {
+                    continue
+                }
+                // the library that would have been deployed at the i-th nonce
+                let planned = sender.create(nonce + i as u64);
+                let Some((file, name)) = linked.libs.iter().find_map(|(file, libs)| {
+                    libs.iter()
+                        .find(|(_, lib)| Address::from_str(lib).ok() == Some(planned))
+                        .map(|(name, _)| (file.clone(), name.clone()))
+                }) else {
+                    continue
+                };
+
+                // the deployment may have been reverted since, e.g. by a reorg or on a fork
+                let expected = runtime_code(linker, target, &linked, &file, &name, address);
+                let onchain = provider.get_code(address.to_ethers(), None).await?.to_alloy();
+                if expected.as_ref() != Some(&onchain) {
+                    mismatched.insert(address);
+                    continue
+                }
+                found = Some((file, name, address));
+                break
+            }
+
+            let Some((file, name, address)) = found else { break };
+            reused.push(format!("{}:{name} at {address}", file.display()));
+            libraries.libs.entry(file).or_default().insert(name, address.to_checksum(None));
+        }
+
+        if !reused.is_empty() {
+            shell::println(format!(
+                "Reusing libraries deployed on chain {chain}, pass `--force-deploy-libraries` to deploy them again:\n  {}",
+                reused.join("\n  ")
+            ))?;
+        }
+        Ok(libraries)
+    }
+}
+
+/// Returns the runtime code of the library `file:name` of `target`'s compiler version, linked with
+/// `libraries` and deployed at `address`.
+fn runtime_code(
+    linker: &Linker,
+    target: &ArtifactId,
+    libraries: &Libraries,
+    file: &Path,
+    name: &str,
+    address: Address,
+) -> Option<Bytes> {
+    let id = linker.contracts.keys().find(|id| {
+        id.version == target.version &&
+            id.source.strip_prefix(&linker.root).unwrap_or(&id.source) == file &&
+            id.name.split('.').next() == Some(name)
+    })?;
+    let contract = linker.link(id, libraries).ok()?;
+    let mut code = contract.deployed_bytecode?.bytecode?.object.as_bytes()?.to_vec();
+    // the call protection of libraries pushes the address they're deployed at
+    if has_call_protection(&code) {
+        code[1..21].copy_from_slice(address.as_slice());
+    }
+    Some(code.into())
+}
+
+/// Returns the addresses of the contracts successfully deployed by the broadcast sequences of
+/// every script on `chain`, by creation code.
+///
+/// Sequences that can't be read, e.g. encrypted with another key, are skipped.
+fn deployed_contracts(
+    broadcast: &Path,
+    chain: u64,
+    encryption: &SequenceEncryption,
+) -> HashMap<Bytes, Address> {
+    let mut deployed = HashMap::new();
+    let Ok(scripts) = std::fs::read_dir(broadcast) else { return deployed };
+    for script in scripts.filter_map(Result::ok) {
+        let Ok(files) = std::fs::read_dir(script.path().join(chain.to_string())) else { continue };
+        for file in files.filter_map(Result::ok) {
+            let path = file.path();
+            // dry runs are in a subdirectory
+            if path.extension().map_or(true, |ext| ext != "json") {
+                continue
+            }
+            let Ok((sequence, _)) =
+                encryption.read::<SavedSequence>(&path, &script.file_name().to_string_lossy())
+            else {
+                continue
+            };
+            deployed.extend(sequence_deployments(&sequence));
+        }
+    }
+    deployed
+}
+
+/// Returns the contracts deployed with a successful receipt in the sequence, by creation code.
+fn sequence_deployments(sequence: &SavedSequence) -> impl Iterator<Item = (Bytes, Address)> + '_ {
+    let succeeded = sequence
+        .receipts
+        .iter()
+        .filter(|receipt| receipt.status.map_or(false, |status| !status.is_zero()))
+        .map(|receipt| receipt.transaction_hash)
+        .collect::<HashSet<_>>();
+    sequence.transactions.iter().filter_map(move |tx| {
+        if !matches!(tx.opcode, CallKind::Create | CallKind::Create2) ||
+            !succeeded.contains(&tx.hash?)
+        {
+            return None
+        }
+        Some((tx.transaction.data()?.clone().to_alloy(), tx.contract_address?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_successful_deployments() {
+        let sequence: SavedSequence = serde_json::from_value(serde_json::json!({
+            "transactions": [
+                {
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    "transactionType": "CREATE",
+                    "contractAddress": "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+                    "transaction": { "type": "0x02", "data": "0x6001" }
+                },
+                {
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                    "transactionType": "CREATE",
+                    "contractAddress": "0xe7f1725e7734ce288f8367e1bb143e90bb3f0512",
+                    "transaction": { "type": "0x02", "data": "0x6002" }
+                },
+                {
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000003",
+                    "transactionType": "CALL",
+                    "contractAddress": "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+                    "transaction": { "type": "0x02", "data": "0x6003" }
+                },
+                {
+                    "hash": null,
+                    "transactionType": "CREATE",
+                    "contractAddress": "0x9fe46736679d2d9a65f0992f2272de9f3c7fa6e0",
+                    "transaction": { "type": "0x02", "data": "0x6004" }
+                }
+            ],
+            "receipts": [
+                {
+                    "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    "status": "0x1"
+                },
+                {
+                    "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                    "status": "0x0"
+                },
+                {
+                    "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000003",
+                    "status": "0x1"
+                }
+            ]
+        }))
+        .unwrap();
+
+        let deployed = sequence_deployments(&sequence).collect::<Vec<_>>();
+        assert_eq!(
+            deployed,
+            vec![(
+                Bytes::from_static(&[0x60, 0x01]),
+                "0x5fbdb2315678afecb367f032d93f642f64180aa3".parse().unwrap()
+            )]
+        );
+    }
+}
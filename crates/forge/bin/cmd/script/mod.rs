@@ -4,31 +4,36 @@ use alloy_json_abi::{Function, InternalType, JsonAbi};
 use alloy_primitives::{Address, Bytes, Log, U256, U64};
 use alloy_rpc_types::request::TransactionRequest;
 use clap::{Parser, ValueHint};
+use debug_tx::SequenceTx;
 use dialoguer::Confirm;
-use ethers_providers::{Http, Middleware};
+use diff::SavedSequence;
+use ethers_providers::Middleware;
 use eyre::{ContextCompat, Result, WrapErr};
 use forge::{
     backend::Backend,
     debug::DebugArena,
     decode::decode_console_logs,
     opts::EvmOpts,
+    revm::primitives::Env,
     traces::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
-        render_trace_arena, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind, Traces,
+        render_trace_arena, state_changes, CallStateChanges, CallTraceDecoder,
+        CallTraceDecoderBuilder, StackTraces, TraceKind, Traces,
     },
 };
 use forge_verify::RetryArgs;
+use foundry_cli::utils::has_different_gas_calc;
 use foundry_common::{
     abi::{encode_function_args, get_func},
     errors::UnlinkedByteCode,
     evm::{Breakpoints, EvmArgs},
     fmt::{format_token, format_token_raw},
-    provider::ethers::RpcUrl,
-    shell, ContractsByArtifact, CONTRACT_MAX_SIZE, SELECTOR_LEN,
+    provider::ethers::{try_get_http_provider, RpcUrl},
+    shell, ContractsByArtifact, CONTRACT_MAX_INITCODE_SIZE, CONTRACT_MAX_SIZE, SELECTOR_LEN,
 };
 use foundry_compilers::{
-    artifacts::{ContractBytecodeSome, Libraries},
-    ArtifactId,
+    artifacts::{ContractBytecodeSome, Libraries, StorageLayout},
+    ArtifactId, EvmVersion,
 };
 use foundry_config::{
     figment,
@@ -41,25 +46,38 @@ use foundry_config::{
 use foundry_evm::{
     constants::DEFAULT_CREATE2_DEPLOYER,
     decode::RevertDecoder,
-    inspectors::cheatcodes::{BroadcastableTransaction, BroadcastableTransactions},
+    inspectors::cheatcodes::{
+        BroadcastBalanceAssertion, BroadcastableTransaction, BroadcastableTransactions,
+    },
 };
 use foundry_wallets::MultiWalletOpts;
 use futures::future;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::PathBuf,
+};
 use yansi::Paint;
 
 mod artifacts;
 mod broadcast;
 mod build;
+mod checks;
 mod cmd;
+mod debug_tx;
+mod diff;
+mod encryption;
+mod events;
 mod executor;
+mod libraries;
 mod multi;
 mod providers;
 mod receipts;
 mod runner;
 mod sequence;
+mod status;
+mod summary;
 pub mod transaction;
 mod verify;
 
@@ -106,6 +124,11 @@ pub struct ScriptArgs {
     #[arg(long)]
     pub broadcast: bool,
 
+    /// Deploys all the linked libraries, even those a previous broadcast already deployed on the
+    /// chain with the same bytecode.
+    #[arg(long)]
+    pub force_deploy_libraries: bool,
+
     /// Skips on-chain simulation.
     #[arg(long)]
     pub skip_simulation: bool,
@@ -131,6 +154,14 @@ pub struct ScriptArgs {
     #[arg(long)]
     pub resume: bool,
 
+    /// Broadcast after the in-flight transactions of the sender, starting at its pending nonce.
+    ///
+    /// Without it, the script fails to broadcast if the sender has pending transactions, e.g. sent
+    /// by another process, since they would be replaced or the broadcast would fail with "nonce
+    /// too low".
+    #[arg(long)]
+    pub allow_pending_nonce: bool,
+
     /// If present, --resume or --verify will be assumed to be a multi chain deployment.
     #[arg(long)]
     pub multi: bool,
@@ -141,6 +172,48 @@ pub struct ScriptArgs {
     #[arg(long)]
     pub debug: bool,
 
+    /// Print the Solidity stack trace of the script if it fails.
+    ///
+    /// Each frame is the contract, the function or modifier and the source line it was executing,
+    /// from the revert up to the script's entry point. Calls to contracts without local sources
+    /// are shown as their address and selector.
+    #[arg(long)]
+    pub stack_traces: bool,
+
+    /// Open a single transaction of the saved sequence in the debugger, by index or hash.
+    ///
+    /// Broadcast transactions are replayed on a fork of their block, dry-run ones on top of the
+    /// preceding transactions of the sequence. Requires `--fork-url`.
+    #[arg(
+        long,
+        value_name = "INDEX|HASH",
+        conflicts_with_all = &["debug", "broadcast", "resume", "verify"],
+    )]
+    pub debug_tx: Option<SequenceTx>,
+
+    /// Prints the progress of the saved sequences of the script instead of running it.
+    ///
+    /// Shows the sent, confirmed, failed and pending transactions of every chain, and the
+    /// verification status of the deployed contracts. Add `--multi` for multi chain deployments.
+    #[arg(
+        long,
+        conflicts_with_all = &["debug", "debug_tx", "broadcast", "resume", "verify"],
+    )]
+    pub status: bool,
+
+    /// Compares the transactions of the script with a saved sequence instead of saving them, and
+    /// exits with code 1 if they differ.
+    ///
+    /// Either the path of a sequence file, or `latest` for the latest sequence of the script:
+    /// the broadcast one, or else the dry run. Reports the added and removed transactions, and
+    /// the changed addresses, arguments, calldata and deployment bytecode.
+    #[arg(
+        long,
+        value_name = "SEQUENCE|latest",
+        conflicts_with_all = &["debug", "debug_tx", "broadcast", "resume", "verify", "status"],
+    )]
+    pub diff_against: Option<SavedSequence>,
+
     /// Makes sure a transaction is sent,
     /// only after its previous one has been confirmed and succeeded.
     #[arg(long)]
@@ -164,6 +237,22 @@ pub struct ScriptArgs {
     #[arg(long)]
     pub json: bool,
 
+    /// Encrypts the saved transaction sequences with a passphrase.
+    ///
+    /// The passphrase is read from `FOUNDRY_SEQUENCE_PASSWORD`, or prompted for.
+    #[arg(long)]
+    pub encrypt_sequence: bool,
+
+    /// Encrypts the saved transaction sequences to an age X25519 recipient (`age1...`) instead of
+    /// a passphrase.
+    #[arg(long, value_name = "RECIPIENT")]
+    pub sequence_recipient: Option<String>,
+
+    /// The file of the age X25519 identity to encrypt and decrypt the saved transaction sequences
+    /// with.
+    #[arg(long, env = "FOUNDRY_SEQUENCE_IDENTITY", value_name = "PATH")]
+    pub sequence_identity: Option<PathBuf>,
+
     /// Gas price for legacy transactions, or max fee per gas for EIP1559 transactions.
     #[arg(
         long,
@@ -173,6 +262,15 @@ pub struct ScriptArgs {
     )]
     pub with_gas_price: Option<U256>,
 
+    /// The USD price of the native token, to show the approximate USD cost of the broadcast.
+    #[arg(long, value_name = "PRICE", conflicts_with = "price_feed")]
+    pub native_usd_price: Option<f64>,
+
+    /// An Ethereum mainnet RPC URL to read the ETH/USD price from the Chainlink price feed, to
+    /// show the approximate USD cost of the broadcast on chains whose native token is ETH.
+    #[arg(long, value_name = "URL")]
+    pub price_feed: Option<String>,
+
     #[command(flatten)]
     pub opts: BuildArgs,
 
@@ -198,22 +296,40 @@ impl ScriptArgs {
         result: &mut ScriptResult,
         known_contracts: &ContractsByArtifact,
     ) -> Result<CallTraceDecoder> {
-        let verbosity = script_config.evm_opts.verbosity;
-        let mut etherscan_identifier = EtherscanIdentifier::new(
-            &script_config.config,
-            script_config.evm_opts.get_remote_chain_id(),
-        )?;
-
-        let mut local_identifier = LocalTraceIdentifier::new(known_contracts);
         let mut decoder = CallTraceDecoderBuilder::new()
-            .with_labels(result.labeled_addresses.clone())
-            .with_verbosity(verbosity)
-            .with_local_identifier_abis(&local_identifier)
+            .with_verbosity(script_config.evm_opts.verbosity)
+            .with_gas_price(U256::from(script_config.evm_opts.env.gas_price.unwrap_or_default()))
+            .with_local_identifier_abis(&LocalTraceIdentifier::new(known_contracts))
+            .with_storage_layouts(script_config.storage_layouts.clone())
             .with_signature_identifier(SignaturesIdentifier::new(
                 Config::foundry_cache_dir(),
                 script_config.config.offline,
             )?)
             .build();
+        self.identify_traces(script_config, result, known_contracts, &mut decoder)?;
+        Ok(decoder)
+    }
+
+    /// Identifies the addresses of the traces of `result`, replacing the addresses `decoder`
+    /// identified before.
+    ///
+    /// Relinking only changes the addresses of the contracts, so the decoder, its ABIs and its
+    /// resolved signatures are reused.
+    fn identify_traces(
+        &self,
+        script_config: &ScriptConfig,
+        result: &mut ScriptResult,
+        known_contracts: &ContractsByArtifact,
+        decoder: &mut CallTraceDecoder,
+    ) -> Result<()> {
+        let mut etherscan_identifier = EtherscanIdentifier::new(
+            &script_config.config,
+            script_config.evm_opts.get_remote_chain_id(),
+        )?;
+        let mut local_identifier = LocalTraceIdentifier::new(known_contracts);
+
+        decoder.clear_addresses();
+        decoder.labels.extend(result.labeled_addresses.iter().map(|(k, v)| (*k, v.clone())));
 
         // Decoding traces using etherscan is costly as we run into rate limits,
         // causing scripts to run for a very long time unnecessarily.
@@ -226,7 +342,7 @@ impl ScriptArgs {
                 decoder.identify(trace, &mut etherscan_identifier);
             }
         }
-        Ok(decoder)
+        Ok(())
     }
 
     fn get_returns(
@@ -273,6 +389,7 @@ impl ScriptArgs {
         &self,
         script_config: &ScriptConfig,
         decoder: &CallTraceDecoder,
+        stack_traces: Option<&StackTraces>,
         result: &mut ScriptResult,
     ) -> Result<()> {
         let verbosity = script_config.evm_opts.verbosity;
@@ -298,6 +415,22 @@ impl ScriptArgs {
             shell::println(String::new())?;
         }
 
+        if let Some(stack_traces) = stack_traces.filter(|_| !result.success) {
+            let frames = result
+                .traces
+                .iter()
+                .rev()
+                .map(|(_, arena)| stack_traces.frames(arena, decoder))
+                .find(|frames| !frames.is_empty());
+            if let Some(frames) = frames {
+                shell::println("Stack trace:")?;
+                for frame in frames {
+                    shell::println(format!("  {frame}"))?;
+                }
+                shell::println(String::new())?;
+            }
+        }
+
         if result.success {
             shell::println(format!("{}", Paint::green("Script ran successfully.")))?;
         }
@@ -346,7 +479,7 @@ impl ScriptArgs {
         if !result.success {
             return Err(eyre::eyre!(
                 "script failed: {}",
-                RevertDecoder::new().decode(&result.returned[..], None)
+                script_config.revert_decoder.decode(&result.returned[..], None)
             ));
         }
 
@@ -357,14 +490,28 @@ impl ScriptArgs {
         let returns = self.get_returns(script_config, &result.returned)?;
 
         let console_logs = decode_console_logs(&result.logs);
-        let output = JsonResult { logs: console_logs, gas_used: result.gas_used, returns };
+        let state_changes = if script_config.evm_opts.verbosity >= 5 {
+            {
+                let gas_price =
+                    U256::from(script_config.evm_opts.env.gas_price.unwrap_or_default());
+                result
+                    .traces
+                    .iter()
+                    .map(|(kind, arena)| (*kind, state_changes(arena, gas_price)))
+                    .collect()
+            }
+        } else {
+            Vec::new()
+        };
+        let output =
+            JsonResult { logs: console_logs, gas_used: result.gas_used, returns, state_changes };
         let j = serde_json::to_string(&output)?;
         shell::println(j)?;
 
         if !result.success {
             return Err(eyre::eyre!(
                 "script failed: {}",
-                RevertDecoder::new().decode(&result.returned[..], None)
+                script_config.revert_decoder.decode(&result.returned[..], None)
             ));
         }
 
@@ -461,15 +608,44 @@ impl ScriptArgs {
         Ok((func.clone(), data))
     }
 
+    /// Returns the percentage to multiply the gas estimates on `chain` by, which is the chain's
+    /// `gas_multiplier` if set, otherwise `--gas-estimate-multiplier`.
+    fn gas_estimate_multiplier_for(&self, config: &Config, chain: u64) -> u64 {
+        match config.chains.get(chain).and_then(|chain| chain.gas_multiplier) {
+            Some(multiplier) => {
+                if multiplier != self.gas_estimate_multiplier {
+                    debug!(chain, multiplier, "chain overrides the gas estimate multiplier");
+                }
+                multiplier
+            }
+            None => self.gas_estimate_multiplier,
+        }
+    }
+
+    /// Returns `true` if the gas of the transactions on `chain` is estimated with
+    /// `eth_estimateGas` instead of computed from the simulation, either because the chain is
+    /// known to compute gas differently or because of its `estimate_via_rpc_only` gas override.
+    fn estimates_gas_via_rpc(&self, config: &Config, chain: u64) -> bool {
+        has_different_gas_calc(chain) ||
+            config
+                .script_settings
+                .gas_overrides(chain)
+                .map_or(false, |overrides| overrides.estimate_via_rpc_only)
+    }
+
     /// Checks if the transaction is a deployment with either a size above the `CONTRACT_MAX_SIZE`
     /// or specified `code_size_limit`.
     ///
+    /// From Shanghai onwards, the initcode size is also checked against the EIP-3860 limit, which
+    /// is twice the contract size limit.
+    ///
     /// If `self.broadcast` is enabled, it asks confirmation of the user. Otherwise, it just warns
     /// the user.
     fn check_contract_sizes(
         &self,
         result: &ScriptResult,
         known_contracts: &BTreeMap<ArtifactId, ContractBytecodeSome>,
+        evm_version: EvmVersion,
     ) -> Result<()> {
         // (name, &init, &deployed)[]
         let mut bytecodes: Vec<(String, &[u8], &[u8])> = vec![];
@@ -510,6 +686,11 @@ impl ScriptArgs {
             Some(size) => size,
             None => CONTRACT_MAX_SIZE,
         };
+        let max_initcode_size = match self.evm_opts.env.code_size_limit {
+            Some(size) => size * 2,
+            None => CONTRACT_MAX_INITCODE_SIZE,
+        };
+        let check_initcode_size = evm_version >= EvmVersion::Shanghai;
 
         for (data, to) in result.transactions.iter().flat_map(|txes| {
             txes.iter().filter_map(|tx| {
@@ -533,9 +714,25 @@ impl ScriptArgs {
                 continue;
             }
 
+            let init_code = &data[offset..];
+            if check_initcode_size && init_code.len() > max_initcode_size {
+                prompt_user = self.broadcast;
+                let name = bytecodes
+                    .iter()
+                    .find(|(_, code, _)| *code == init_code)
+                    .map_or("Unknown", |(name, _, _)| name.as_str());
+                shell::println(format!(
+                    "{}",
+                    Paint::red(format!(
+                        "`{name}` is above the EIP-3860 initcode size limit ({} > {max_initcode_size}).",
+                        init_code.len()
+                    ))
+                ))?;
+            }
+
             // Find artifact with a deployment code same as the data.
             if let Some((name, _, deployed_code)) =
-                bytecodes.iter().find(|(_, init_code, _)| *init_code == &data[offset..])
+                bytecodes.iter().find(|(_, code, _)| *code == init_code)
             {
                 let deployment_size = deployed_code.len();
 
@@ -544,7 +741,7 @@ impl ScriptArgs {
                     shell::println(format!(
                         "{}",
                         Paint::red(format!(
-                            "`{name}` is above the contract size limit ({deployment_size} > {max_size})."
+                            "`{name}` is above the EIP-170 contract size limit ({deployment_size} > {max_size})."
                         ))
                     ))?;
                 }
@@ -594,6 +791,7 @@ pub struct ScriptResult {
     pub returned: Bytes,
     pub address: Option<Address>,
     pub breakpoints: Breakpoints,
+    pub balance_assertions: Vec<BroadcastBalanceAssertion>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -601,6 +799,8 @@ struct JsonResult {
     logs: Vec<String>,
     gas_used: u64,
     returns: HashMap<String, NestedValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    state_changes: Vec<(TraceKind, Vec<CallStateChanges>)>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -614,18 +814,24 @@ pub struct ScriptConfig {
     pub config: Config,
     pub evm_opts: EvmOpts,
     pub sender_nonce: u64,
-    /// Maps a rpc url to a backend
-    pub backends: HashMap<RpcUrl, Backend>,
+    /// Where the nonce of the sender comes from
+    pub nonce_source: NonceSource,
+    /// Maps a rpc url to the fork backend shared by all the executions against it
+    pub backends: HashMap<RpcUrl, ScriptFork>,
     /// Script target contract
     pub target_contract: Option<ArtifactId>,
     /// Function called by the script
     pub called_function: Option<Function>,
+    /// Storage layouts by contract identifier, if they were part of the compiler output
+    pub storage_layouts: BTreeMap<String, StorageLayout>,
     /// Unique list of rpc urls present
     pub total_rpcs: HashSet<RpcUrl>,
     /// If true, one of the transactions did not have a rpc
     pub missing_rpc: bool,
     /// Should return some debug information
     pub debug: bool,
+    /// Decoder of the project's custom errors
+    pub revert_decoder: RevertDecoder,
 }
 
 impl ScriptConfig {
@@ -640,6 +846,15 @@ impl ScriptConfig {
         }
     }
 
+    /// Returns the cached fork of the current `fork_url`, if it's pinned to the requested block.
+    ///
+    /// Forks created by the script itself, e.g. with `vm.createSelectFork`, are not cached, so
+    /// other RPCs always get a fresh backend.
+    fn cached_fork(&self) -> Option<&ScriptFork> {
+        let url = self.evm_opts.fork_url.as_ref()?;
+        self.backends.get(url).filter(|fork| fork.is_pinned_to(self.evm_opts.fork_block_number))
+    }
+
     fn has_multiple_rpcs(&self) -> bool {
         self.total_rpcs.len() > 1
     }
@@ -672,7 +887,7 @@ impl ScriptConfig {
     /// If not, warns the user.
     async fn check_shanghai_support(&self) -> Result<()> {
         let chain_ids = self.total_rpcs.iter().map(|rpc| async move {
-            let provider = ethers_providers::Provider::<Http>::try_from(rpc).ok()?;
+            let provider = try_get_http_provider(rpc).ok()?;
             let id = provider.get_chainid().await.ok()?;
             let id_u64: u64 = id.try_into().ok()?;
             NamedChain::try_from(id_u64).ok()
@@ -697,6 +912,36 @@ For more information, please see https://eips.ethereum.org/EIPS/eip-3855",
     }
 }
 
+/// The block tag the nonce of the sender of a script was queried at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NonceSource {
+    /// The latest block.
+    #[default]
+    Latest,
+    /// The pending block, i.e. after the in-flight transactions of the sender, acknowledged with
+    /// `--allow-pending-nonce`.
+    Pending,
+}
+
+/// A fork backend along with the environment of the block it's pinned to.
+///
+/// The backends of a script are reused by the local executions and the on-chain simulation, so the
+/// remote state fetched by one is served from memory to the others.
+#[derive(Clone, Debug)]
+pub struct ScriptFork {
+    pub env: Env,
+    pub backend: Backend,
+}
+
+impl ScriptFork {
+    /// Returns whether the fork can serve `block`, `None` meaning the block the fork was created
+    /// at.
+    fn is_pinned_to(&self, block: Option<u64>) -> bool {
+        block.map_or(true, |block| self.env.block.number == U256::from(block))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -705,6 +950,17 @@ mod tests {
     use std::fs;
     use tempfile::tempdir;
 
+    #[test]
+    fn reuses_fork_pinned_to_block() {
+        let mut env = Env::default();
+        env.block.number = U256::from(100);
+        let fork = ScriptFork { env, backend: Backend::spawn(None) };
+
+        assert!(fork.is_pinned_to(None));
+        assert!(fork.is_pinned_to(Some(100)));
+        assert!(!fork.is_pinned_to(Some(99)));
+    }
+
     #[test]
     fn can_parse_sig() {
         let sig = "0x522bb704000000000000000000000000f39fd6e51aad88f6f4ce6ab8827279cfFFb92266";
@@ -764,10 +1020,7 @@ mod tests {
             "--verify",
             "-vvvvv",
         ]);
-        assert_eq!(
-            args.verifier.verifier_url,
-            Some("http://localhost:3000/api/verify".to_string())
-        );
+        assert_eq!(args.verifier.url(), Some("http://localhost:3000/api/verify"));
     }
 
     #[test]
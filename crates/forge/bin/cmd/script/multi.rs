@@ -1,11 +1,13 @@
 use super::{
+    encryption::{write_sequence, SequenceCipher, SequenceDecryptionError, SequenceEncryption},
     receipts,
     sequence::{sig_to_file_name, ScriptSequence, SensitiveScriptSequence, DRY_RUN_DIR},
     verify::VerifyBundle,
     ScriptArgs,
 };
 use alloy_primitives::Address;
-use eyre::{ContextCompat, Report, Result, WrapErr};
+use eyre::{ContextCompat, Report, Result};
+use forge::traces::CallTraceDecoder;
 use foundry_cli::utils::now;
 use foundry_common::{fs, provider::ethers::get_http_provider};
 use foundry_compilers::{artifacts::Libraries, ArtifactId};
@@ -15,7 +17,6 @@ use futures::future::join_all;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
-    io::{BufWriter, Write},
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -28,6 +29,9 @@ pub struct MultiChainSequence {
     pub path: PathBuf,
     #[serde(skip)]
     pub sensitive_path: PathBuf,
+    /// The cipher the sequences are encrypted with at rest, if any
+    #[serde(skip)]
+    pub cipher: Option<SequenceCipher>,
     pub timestamp: u64,
 }
 
@@ -57,6 +61,7 @@ impl MultiChainSequence {
         target: &ArtifactId,
         config: &Config,
         broadcasted: bool,
+        encryption: &SequenceEncryption,
     ) -> Result<Self> {
         let (path, sensitive_path) = MultiChainSequence::get_paths(
             &config.broadcast,
@@ -65,8 +70,15 @@ impl MultiChainSequence {
             target,
             broadcasted,
         )?;
-
-        Ok(MultiChainSequence { deployments, path, sensitive_path, timestamp: now().as_secs() })
+        let cipher = encryption.cipher(&target.identifier())?;
+
+        Ok(MultiChainSequence {
+            deployments,
+            path,
+            sensitive_path,
+            cipher,
+            timestamp: now().as_secs(),
+        })
     }
 
     /// Gets paths in the formats
@@ -112,7 +124,12 @@ impl MultiChainSequence {
     }
 
     /// Loads the sequences for the multi chain deployment.
-    pub fn load(config: &Config, sig: &str, target: &ArtifactId) -> Result<Self> {
+    pub fn load(
+        config: &Config,
+        sig: &str,
+        target: &ArtifactId,
+        encryption: &SequenceEncryption,
+    ) -> Result<Self> {
         let (path, sensitive_path) = MultiChainSequence::get_paths(
             &config.broadcast,
             &config.cache_path,
@@ -120,11 +137,23 @@ impl MultiChainSequence {
             target,
             true,
         )?;
-        let mut sequence: MultiChainSequence = foundry_compilers::utils::read_json_file(&path)
-            .wrap_err("Multi-chain deployment not found.")?;
-        let sensitive_sequence: SensitiveMultiChainSequence =
-            foundry_compilers::utils::read_json_file(&sensitive_path)
-                .wrap_err("Multi-chain deployment sensitive details not found.")?;
+        let script = target.identifier();
+        let (mut sequence, cipher): (MultiChainSequence, _) =
+            encryption.read(&path, &script).map_err(|err| {
+                if err.is::<SequenceDecryptionError>() {
+                    err
+                } else {
+                    err.wrap_err("Multi-chain deployment not found.")
+                }
+            })?;
+        let (sensitive_sequence, _): (SensitiveMultiChainSequence, _) =
+            encryption.read(&sensitive_path, &script).map_err(|err| {
+                if err.is::<SequenceDecryptionError>() {
+                    err
+                } else {
+                    err.wrap_err("Multi-chain deployment sensitive details not found.")
+                }
+            })?;
 
         sequence.deployments.iter_mut().enumerate().for_each(|(i, sequence)| {
             sequence.fill_sensitive(&sensitive_sequence.deployments[i]);
@@ -132,6 +161,7 @@ impl MultiChainSequence {
 
         sequence.path = path;
         sequence.sensitive_path = sensitive_path;
+        sequence.cipher = cipher;
 
         Ok(sequence)
     }
@@ -142,11 +172,13 @@ impl MultiChainSequence {
 
         let sensitive_sequence: SensitiveMultiChainSequence = to_sensitive(self);
 
+        let cipher = self.cipher.as_ref();
+        let chains = self.deployments.iter().map(|sequence| sequence.chain).collect::<Vec<_>>();
+        let tx_count = self.deployments.iter().map(|sequence| sequence.transactions.len()).sum();
+
         // broadcast writes
         //../Contract-latest/run.json
-        let mut writer = BufWriter::new(fs::create_file(&self.path)?);
-        serde_json::to_writer_pretty(&mut writer, &self)?;
-        writer.flush()?;
+        write_sequence(&self.path, &self, cipher, chains.clone(), self.timestamp, tx_count)?;
 
         //../Contract-[timestamp]/run.json
         let path = self.path.to_string_lossy();
@@ -156,9 +188,14 @@ impl MultiChainSequence {
 
         // cache writes
         //../Contract-latest/run.json
-        let mut writer = BufWriter::new(fs::create_file(&self.sensitive_path)?);
-        serde_json::to_writer_pretty(&mut writer, &sensitive_sequence)?;
-        writer.flush()?;
+        write_sequence(
+            &self.sensitive_path,
+            &sensitive_sequence,
+            cipher,
+            chains,
+            self.timestamp,
+            tx_count,
+        )?;
 
         //../Contract-[timestamp]/run.json
         let path = self.sensitive_path.to_string_lossy();
@@ -183,6 +220,7 @@ impl ScriptArgs {
         config: &Config,
         verify: VerifyBundle,
         signers: &HashMap<Address, WalletSigner>,
+        decoder: &CallTraceDecoder,
     ) -> Result<()> {
         if !libraries.is_empty() {
             eyre::bail!("Libraries are currently not supported on multi deployment setups.");
@@ -203,7 +241,7 @@ impl ScriptArgs {
                 .map(|sequence| async move {
                     let rpc_url = sequence.rpc_url().unwrap();
                     let provider = Arc::new(get_http_provider(rpc_url));
-                    receipts::wait_for_pending(provider, sequence).await
+                    receipts::wait_for_pending(provider, sequence, decoder).await
                 })
                 .collect::<Vec<_>>();
 
@@ -221,11 +259,12 @@ impl ScriptArgs {
 
         for sequence in deployments.deployments.iter_mut() {
             let rpc_url = sequence.rpc_url().unwrap().to_string();
-            let result = match self.send_transactions(sequence, &rpc_url, signers).await {
-                Ok(_) if self.verify => sequence.verify_contracts(config, verify.clone()).await,
-                Ok(_) => Ok(()),
-                Err(err) => Err(err),
-            };
+            let result =
+                match self.send_transactions(sequence, &rpc_url, signers, config, decoder).await {
+                    Ok(_) if self.verify => sequence.verify_contracts(config, verify.clone()).await,
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(err),
+                };
             results.push(result);
         }
 
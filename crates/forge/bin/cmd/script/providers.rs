@@ -6,7 +6,7 @@ use foundry_common::{
     runtime_client::RuntimeClient,
     types::ToAlloy,
 };
-use foundry_config::Chain;
+use foundry_config::{Chain, ChainsConfig};
 use std::{
     collections::{hash_map::Entry, HashMap},
     ops::Deref,
@@ -25,11 +25,12 @@ impl ProvidersManager {
         &mut self,
         rpc: &str,
         is_legacy: bool,
+        chains: &ChainsConfig,
     ) -> Result<&ProviderInfo> {
         Ok(match self.inner.entry(rpc.to_string()) {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => {
-                let info = ProviderInfo::new(rpc, is_legacy).await?;
+                let info = ProviderInfo::new(rpc, is_legacy, chains).await?;
                 entry.insert(info)
             }
         })
@@ -61,11 +62,19 @@ pub enum GasPrice {
 }
 
 impl ProviderInfo {
-    pub async fn new(rpc: &str, mut is_legacy: bool) -> Result<ProviderInfo> {
+    /// Connects to `rpc`, sending legacy transactions if `is_legacy` is set or the chain requires
+    /// them, which the chain's `legacy` setting in `chains` overrides.
+    pub async fn new(
+        rpc: &str,
+        mut is_legacy: bool,
+        chains: &ChainsConfig,
+    ) -> Result<ProviderInfo> {
         let provider = Arc::new(get_http_provider(rpc));
         let chain = provider.get_chainid().await?.as_u64();
 
-        if let Some(chain) = Chain::from(chain).named() {
+        if let Some(legacy) = chains.get(chain).and_then(|chain| chain.legacy) {
+            is_legacy |= legacy;
+        } else if let Some(chain) = Chain::from(chain).named() {
             is_legacy |= chain.is_legacy();
         };
 
@@ -1,16 +1,22 @@
-use super::sequence::ScriptSequence;
+use super::{events::decode_receipt_logs, sequence::ScriptSequence};
 use alloy_primitives::TxHash;
 use ethers_core::types::TransactionReceipt;
 use ethers_providers::{Middleware, PendingTransaction};
 use eyre::Result;
+use forge::traces::CallTraceDecoder;
 use foundry_cli::{init_progress, update_progress, utils::print_receipt};
 use foundry_common::{
     provider::ethers::RetryProvider,
+    shell,
     types::{ToAlloy, ToEthers},
 };
 use futures::StreamExt;
 use std::sync::Arc;
 
+/// How many times the `newHeads` subscription is resubscribed when it drops, before falling back
+/// to polling for a receipt.
+const MAX_RESUBSCRIPTIONS: usize = 3;
+
 /// Convenience enum for internal signalling of transaction status
 enum TxStatus {
     Dropped,
@@ -34,12 +40,13 @@ impl From<TransactionReceipt> for TxStatus {
 pub async fn wait_for_pending(
     provider: Arc<RetryProvider>,
     deployment_sequence: &mut ScriptSequence,
+    decoder: &CallTraceDecoder,
 ) -> Result<()> {
     if deployment_sequence.pending.is_empty() {
         return Ok(());
     }
     println!("##\nChecking previously pending transactions.");
-    clear_pendings(provider, deployment_sequence, None).await
+    clear_pendings(provider, deployment_sequence, None, decoder).await
 }
 
 /// Traverses a set of pendings and either finds receipts, or clears them from
@@ -53,10 +60,13 @@ pub async fn wait_for_pending(
 /// has not confirmed, and cannot be found in the mempool, we remove it from
 /// the `deploy_sequence.pending` vector so that it will be rebroadcast in
 /// later steps.
+///
+/// The events of the confirmed transactions are decoded and printed under their receipt.
 pub async fn clear_pendings(
     provider: Arc<RetryProvider>,
     deployment_sequence: &mut ScriptSequence,
     tx_hashes: Option<Vec<TxHash>>,
+    decoder: &CallTraceDecoder,
 ) -> Result<()> {
     let to_query = tx_hashes.unwrap_or_else(|| deployment_sequence.pending.clone());
 
@@ -110,6 +120,15 @@ pub async fn clear_pendings(
     // print all receipts
     for receipt in receipts {
         print_receipt(deployment_sequence.chain.into(), &receipt);
+        if !receipt.logs.is_empty() {
+            let logs = decode_receipt_logs(decoder, &receipt).await;
+            shell::println("Events:")?;
+            for log in &logs {
+                shell::println(format!("  {log}"))?;
+            }
+            shell::println("")?;
+            deployment_sequence.decoded_logs.insert(receipt.transaction_hash.to_alloy(), logs);
+        }
         deployment_sequence.add_receipt(receipt);
     }
 
@@ -141,6 +160,23 @@ async fn check_tx_status(
             return Ok(receipt.into());
         }
 
+        // Over a WebSocket or IPC, wait for the receipt on new blocks instead of polling, as long
+        // as the tx is present in the mempool
+        if provider.as_ref().is_pubsub() {
+            if provider.get_transaction(hash.to_ethers()).await?.is_none() {
+                return Ok(TxStatus::Dropped);
+            }
+            match wait_on_new_heads(provider, hash).await {
+                Ok(Some(status)) => return Ok(status),
+                Ok(None) => {
+                    warn!(tx_hash=?hash, "newHeads subscription dropped, polling for the receipt")
+                }
+                Err(err) => {
+                    warn!(tx_hash=?hash, %err, "could not subscribe to newHeads, polling for the receipt")
+                }
+            }
+        }
+
         // If the tx is present in the mempool, run the pending tx future, and
         // assume the next drop is really really real
         let pending_res = PendingTransaction::new(hash.to_ethers(), provider).await?;
@@ -153,3 +189,27 @@ async fn check_tx_status(
 
     (hash, result)
 }
+
+/// Checks the status of a transaction on every new block of a `newHeads` subscription, until it's
+/// included or dropped from the mempool.
+///
+/// Resubscribes when the subscription drops, e.g. after a reconnection, and returns `None` when
+/// it keeps dropping.
+async fn wait_on_new_heads(provider: &RetryProvider, hash: TxHash) -> Result<Option<TxStatus>> {
+    for _ in 0..=MAX_RESUBSCRIPTIONS {
+        let mut heads = provider.subscribe_blocks().await?;
+        // The tx might have been included before the subscription started.
+        if let Some(receipt) = provider.get_transaction_receipt(hash.to_ethers()).await? {
+            return Ok(Some(receipt.into()));
+        }
+        while heads.next().await.is_some() {
+            if let Some(receipt) = provider.get_transaction_receipt(hash.to_ethers()).await? {
+                return Ok(Some(receipt.into()));
+            }
+            if provider.get_transaction(hash.to_ethers()).await?.is_none() {
+                return Ok(Some(TxStatus::Dropped));
+            }
+        }
+    }
+    Ok(None)
+}
@@ -266,7 +266,10 @@ impl ScriptRunner {
         }
 
         let RawCallResult { result, reverted, logs, traces, labels, debug, transactions, .. } = res;
-        let breakpoints = res.cheatcodes.map(|cheats| cheats.breakpoints).unwrap_or_default();
+        let (breakpoints, balance_assertions) = res
+            .cheatcodes
+            .map(|cheats| (cheats.breakpoints, cheats.broadcast_balance_assertions))
+            .unwrap_or_default();
 
         Ok(ScriptResult {
             returned: result,
@@ -285,6 +288,7 @@ impl ScriptRunner {
             transactions,
             address: None,
             breakpoints,
+            balance_assertions,
         })
     }
 
@@ -1,15 +1,18 @@
-use super::NestedValue;
+use super::{NestedValue, NonceSource};
 use crate::cmd::{
     init::get_commit_hash,
     script::{
+        encryption::{write_sequence, SequenceCipher, SequenceDecryptionError, SequenceEncryption},
+        events::DecodedReceiptLog,
+        summary::SequenceSummary,
         transaction::{wrapper, AdditionalContract, TransactionWithMetadata},
         verify::VerifyBundle,
     },
 };
 use alloy_primitives::{Address, TxHash};
 use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionReceipt};
-use eyre::{ContextCompat, Result, WrapErr};
-use forge_verify::provider::VerificationProviderType;
+use eyre::{ContextCompat, Result};
+use forge_verify::{bytecode::BytecodeReport, provider::VerificationProviderType};
 use foundry_cli::utils::now;
 use foundry_common::{
     fs, shell,
@@ -18,10 +21,11 @@ use foundry_common::{
 };
 use foundry_compilers::{artifacts::Libraries, ArtifactId};
 use foundry_config::Config;
+use futures::FutureExt;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, VecDeque},
-    io::{BufWriter, Write},
+    collections::{BTreeMap, HashMap, VecDeque},
     path::{Path, PathBuf},
 };
 use yansi::Paint;
@@ -41,12 +45,27 @@ pub struct ScriptSequence {
     pub path: PathBuf,
     #[serde(skip)]
     pub sensitive_path: PathBuf,
+    /// The cipher the sequence is encrypted with at rest, if any
+    #[serde(skip)]
+    pub cipher: Option<SequenceCipher>,
     pub returns: HashMap<String, NestedValue>,
     pub timestamp: u64,
     pub chain: u64,
     /// If `True`, the sequence belongs to a `MultiChainSequence` and won't save to disk as usual.
     pub multi: bool,
     pub commit: Option<String>,
+    /// The provider that verified each of the deployed contracts
+    #[serde(default)]
+    pub verified: HashMap<Address, VerificationProviderType>,
+    /// The gas usage and cost of the confirmed transactions, after a broadcast
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SequenceSummary>,
+    /// The decoded logs of the receipts, by transaction hash
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub decoded_logs: BTreeMap<TxHash, Vec<DecodedReceiptLog>>,
+    /// Where the nonce of the sender was queried, so `--resume` requires the same acknowledgment
+    #[serde(default)]
+    pub nonce_source: NonceSource,
 }
 
 /// Sensitive values from the transactions in a script sequence
@@ -82,6 +101,7 @@ impl ScriptSequence {
         config: &Config,
         broadcasted: bool,
         is_multi: bool,
+        encryption: &SequenceEncryption,
     ) -> Result<Self> {
         let chain = config.chain.unwrap_or_default().id();
 
@@ -95,6 +115,7 @@ impl ScriptSequence {
         )?;
 
         let commit = get_commit_hash(&config.__root.0);
+        let cipher = if is_multi { None } else { encryption.cipher(&target.identifier())? };
 
         Ok(ScriptSequence {
             transactions,
@@ -103,11 +124,16 @@ impl ScriptSequence {
             pending: vec![],
             path,
             sensitive_path,
+            cipher,
             timestamp: now().as_secs(),
             libraries: vec![],
             chain,
             multi: is_multi,
             commit,
+            verified: HashMap::new(),
+            summary: None,
+            decoded_logs: BTreeMap::new(),
+            nonce_source: NonceSource::default(),
         })
     }
 
@@ -118,6 +144,7 @@ impl ScriptSequence {
         target: &ArtifactId,
         chain_id: u64,
         broadcasted: bool,
+        encryption: &SequenceEncryption,
     ) -> Result<Self> {
         let (path, sensitive_path) = ScriptSequence::get_paths(
             &config.broadcast,
@@ -128,18 +155,32 @@ impl ScriptSequence {
             broadcasted,
         )?;
 
-        let mut script_sequence: Self = foundry_compilers::utils::read_json_file(&path)
-            .wrap_err(format!("Deployment not found for chain `{chain_id}`."))?;
-
-        let sensitive_script_sequence: SensitiveScriptSequence =
-            foundry_compilers::utils::read_json_file(&sensitive_path).wrap_err(format!(
-                "Deployment's sensitive details not found for chain `{chain_id}`."
-            ))?;
+        let script = target.identifier();
+        let (mut script_sequence, cipher): (Self, _) =
+            encryption.read(&path, &script).map_err(|err| {
+                if err.is::<SequenceDecryptionError>() {
+                    err
+                } else {
+                    err.wrap_err(format!("Deployment not found for chain `{chain_id}`."))
+                }
+            })?;
+
+        let (sensitive_script_sequence, _): (SensitiveScriptSequence, _) =
+            encryption.read(&sensitive_path, &script).map_err(|err| {
+                if err.is::<SequenceDecryptionError>() {
+                    err
+                } else {
+                    err.wrap_err(format!(
+                        "Deployment's sensitive details not found for chain `{chain_id}`."
+                    ))
+                }
+            })?;
 
         script_sequence.fill_sensitive(&sensitive_script_sequence);
 
         script_sequence.path = path;
         script_sequence.sensitive_path = sensitive_path;
+        script_sequence.cipher = cipher;
 
         Ok(script_sequence)
     }
@@ -155,19 +196,26 @@ impl ScriptSequence {
 
         let sensitive_script_sequence: SensitiveScriptSequence = self.into();
 
+        let cipher = self.cipher.as_ref();
+        let chains = vec![self.chain];
+        let tx_count = self.transactions.len();
+
         // broadcast folder writes
         //../run-latest.json
-        let mut writer = BufWriter::new(fs::create_file(&self.path)?);
-        serde_json::to_writer_pretty(&mut writer, &self)?;
-        writer.flush()?;
+        write_sequence(&self.path, &self, cipher, chains.clone(), self.timestamp, tx_count)?;
         //../run-[timestamp].json
         fs::copy(&self.path, self.path.with_file_name(&ts_name))?;
 
         // cache folder writes
         //../run-latest.json
-        let mut writer = BufWriter::new(fs::create_file(&self.sensitive_path)?);
-        serde_json::to_writer_pretty(&mut writer, &sensitive_script_sequence)?;
-        writer.flush()?;
+        write_sequence(
+            &self.sensitive_path,
+            &sensitive_script_sequence,
+            cipher,
+            chains,
+            self.timestamp,
+            tx_count,
+        )?;
         //../run-[timestamp].json
         fs::copy(&self.sensitive_path, self.sensitive_path.with_file_name(&ts_name))?;
 
@@ -247,8 +295,9 @@ impl ScriptSequence {
 
     /// Checks that there is an Etherscan key for the chain id of this sequence.
     pub fn verify_preflight_check(&self, config: &Config, verify: &VerifyBundle) -> Result<()> {
+        let verifier = verify.verifier_for(config, self.chain.into());
         if config.get_etherscan_api_key(Some(self.chain.into())).is_none() &&
-            verify.verifier.verifier == VerificationProviderType::Etherscan
+            verifier.verifier.iter().all(|p| *p == VerificationProviderType::Etherscan)
         {
             eyre::bail!(
                 "Etherscan API key wasn't found for chain id {}. On-chain execution aborted",
@@ -271,11 +320,11 @@ impl ScriptSequence {
         verify.set_chain(config, self.chain.into());
 
         if verify.etherscan.has_key() ||
-            verify.verifier.verifier != VerificationProviderType::Etherscan
+            verify.verifier.verifier.iter().any(|p| *p != VerificationProviderType::Etherscan)
         {
             trace!(target: "script", "prepare future verifications");
 
-            let mut future_verifications = Vec::with_capacity(self.receipts.len());
+            let mut pending_verifications = Vec::with_capacity(self.receipts.len());
             let mut unverifiable_contracts = vec![];
 
             // Make sure the receipts have the right order first.
@@ -294,32 +343,75 @@ impl ScriptSequence {
                 if let (Some(address), Some(data)) =
                     (receipt.contract_address.map(|h| h.to_alloy()), tx.typed_tx().data())
                 {
-                    match verify.get_verify_args(address, offset, &data.0, &self.libraries) {
-                        Some(verify) => future_verifications.push(verify.run()),
-                        None => unverifiable_contracts.push(address),
-                    };
+                    if !self.verified.contains_key(&address) {
+                        match verify.get_verify_args(address, offset, &data.0, &self.libraries) {
+                            Some(verify) => pending_verifications.push((address, verify)),
+                            None => unverifiable_contracts.push(address),
+                        };
+                    }
                 }
 
                 // Verify potential contracts created during the transaction execution
                 for AdditionalContract { address, init_code, .. } in &tx.additional_contracts {
+                    if self.verified.contains_key(address) {
+                        continue
+                    }
                     match verify.get_verify_args(*address, 0, init_code, &self.libraries) {
-                        Some(verify) => future_verifications.push(verify.run()),
+                        Some(verify) => pending_verifications.push((*address, verify)),
                         None => unverifiable_contracts.push(*address),
                     };
                 }
             }
 
-            trace!(target: "script", "collected {} verification jobs and {} unverifiable contracts", future_verifications.len(), unverifiable_contracts.len());
+            trace!(target: "script", "collected {} verification jobs and {} unverifiable contracts", pending_verifications.len(), unverifiable_contracts.len());
 
             self.check_unverified(unverifiable_contracts, verify);
 
+            // Don't submit the contracts that don't compile to their on-chain code
+            let rpc_url = self.rpc_url().map(str::to_string);
+            let mut future_verifications = Vec::with_capacity(pending_verifications.len());
+            let mut failed_preflight = vec![];
+            for (address, mut verify) in pending_verifications {
+                verify.rpc.url = rpc_url.clone();
+                let preflight = forge_verify::bytecode::preflight(&verify).await;
+                if let Err(err) = &preflight {
+                    println!(
+                        "{}",
+                        Paint::yellow(format!(
+                            "Could not run the preflight check of {address}, submitting it anyway: {err}"
+                        ))
+                    );
+                }
+                match preflight_mismatch(preflight) {
+                    Some((contract, reason)) => failed_preflight.push((address, contract, reason)),
+                    None => future_verifications
+                        .push(verify.run_with_fallback().map(move |res| (address, res))),
+                }
+            }
+
             let num_verifications = future_verifications.len();
             println!("##\nStart verification for ({num_verifications}) contracts",);
             for verification in future_verifications {
-                verification.await?;
+                let (address, provider) = verification.await;
+                self.verified.insert(address, provider?);
             }
 
-            println!("All ({num_verifications}) contracts were verified!");
+            if failed_preflight.is_empty() {
+                println!("All ({num_verifications}) contracts were verified!");
+            } else {
+                println!("({num_verifications}) contracts were verified.");
+                println!(
+                    "\n{}",
+                    Paint::yellow(format!(
+                        "Skipped the verification of ({}) contracts that failed the preflight check:",
+                        failed_preflight.len()
+                    ))
+                    .bold(),
+                );
+                for (address, contract, reason) in failed_preflight {
+                    println!("\t{address} `{contract}`: {reason}");
+                }
+            }
         }
 
         Ok(())
@@ -397,9 +489,50 @@ pub fn sig_to_file_name(sig: &str) -> String {
     sig.to_string()
 }
 
+/// Returns the contract and the mismatching regions if the preflight check found that the
+/// contract doesn't compile to its on-chain code.
+///
+/// A preflight check that couldn't run doesn't hold back the verification.
+fn preflight_mismatch(preflight: Result<BytecodeReport>) -> Option<(String, String)> {
+    let report = preflight.ok().filter(|report| !report.matches)?;
+    let reason = report.mismatches().map(ToString::to_string).join("; ");
+    Some((report.contract, reason))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use forge_verify::bytecode::{Region, RegionKind, RegionStatus};
+
+    #[test]
+    fn skips_verification_on_preflight_mismatch() {
+        let region = |status| Region {
+            kind: RegionKind::Code,
+            name: None,
+            status,
+            offsets: vec![0],
+            value: None,
+            decoded: None,
+            detail: None,
+        };
+        let report = |matches, status| BytecodeReport {
+            address: Address::ZERO,
+            proxy: None,
+            contract: "Counter".to_string(),
+            matches,
+            runtime: vec![region(status)],
+            creation: None,
+            creation_skipped: None,
+        };
+
+        assert_eq!(preflight_mismatch(Ok(report(true, RegionStatus::Match))), None);
+        let (contract, reason) =
+            preflight_mismatch(Ok(report(false, RegionStatus::Mismatch))).unwrap();
+        assert_eq!(contract, "Counter");
+        assert!(reason.contains("mismatch"), "{reason}");
+        // the contract is still submitted if the check couldn't run
+        assert_eq!(preflight_mismatch(Err(eyre::eyre!("no RPC URL"))), None);
+    }
 
     #[test]
     fn can_convert_sig() {
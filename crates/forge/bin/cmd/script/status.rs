@@ -0,0 +1,461 @@
+//! Progress of the saved sequences of a script, see `forge script --status`.
+
+use super::{
+    encryption::SequenceEncryption,
+    sequence::{sig_to_file_name, DRY_RUN_DIR},
+    transaction::AdditionalContract,
+    ScriptArgs, ScriptConfig,
+};
+use alloy_primitives::{Address, TxHash};
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use ethers_core::types::U64;
+use eyre::{ContextCompat, Result, WrapErr};
+use forge_verify::provider::VerificationProviderType;
+use foundry_cli::utils::{get_cached_entry_by_name, now};
+use foundry_common::shell;
+use foundry_compilers::{cache::SolFilesCache, info::ContractInfo};
+use foundry_config::Config;
+use revm_inspectors::tracing::types::CallKind;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The fields of a saved [`ScriptSequence`](super::sequence::ScriptSequence) the status is made
+/// of.
+///
+/// The sequence types themselves are saved again when dropped, which a read-only command must not
+/// do.
+#[derive(Deserialize)]
+struct SavedSequence {
+    transactions: Vec<SavedTransaction>,
+    #[serde(default)]
+    receipts: Vec<SavedReceipt>,
+    #[serde(default)]
+    pending: Vec<TxHash>,
+    timestamp: u64,
+    chain: u64,
+    #[serde(default)]
+    verified: HashMap<Address, VerificationProviderType>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedTransaction {
+    hash: Option<TxHash>,
+    #[serde(rename = "transactionType")]
+    opcode: CallKind,
+    contract_name: Option<String>,
+    contract_address: Option<Address>,
+    #[serde(default)]
+    additional_contracts: Vec<AdditionalContract>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedReceipt {
+    transaction_hash: TxHash,
+    status: Option<U64>,
+}
+
+#[derive(Deserialize)]
+struct SavedMultiChainSequence {
+    deployments: Vec<SavedSequence>,
+}
+
+/// Whether a sequence was broadcast or only simulated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SequenceKind {
+    Broadcast,
+    DryRun,
+}
+
+impl fmt::Display for SequenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Broadcast => f.write_str("broadcast"),
+            Self::DryRun => f.write_str("dry run"),
+        }
+    }
+}
+
+/// A transaction that was sent without a receipt yet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PendingTransaction {
+    pub hash: TxHash,
+    /// Seconds since the sequence was last saved with the transaction pending.
+    pub age: u64,
+}
+
+/// A contract deployed by the sequence, and the provider that verified it, if any.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DeployedContract {
+    pub name: Option<String>,
+    pub address: Address,
+    pub verified: Option<VerificationProviderType>,
+}
+
+/// The progress of the sequence of a single chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ChainStatus {
+    pub chain: u64,
+    pub kind: SequenceKind,
+    pub path: PathBuf,
+    pub timestamp: u64,
+    pub total: usize,
+    pub sent: usize,
+    pub confirmed: usize,
+    pub failed: usize,
+    pub pending: Vec<PendingTransaction>,
+    /// The deployed contracts, only set if any of them was verified.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub contracts: Vec<DeployedContract>,
+}
+
+impl ChainStatus {
+    fn new(sequence: SavedSequence, kind: SequenceKind, path: PathBuf, now: u64) -> Self {
+        let failed = sequence.receipts.iter().filter(|receipt| receipt_failed(receipt)).count();
+        let pending = sequence
+            .pending
+            .iter()
+            .map(|hash| PendingTransaction {
+                hash: *hash,
+                age: now.saturating_sub(sequence.timestamp),
+            })
+            .collect();
+
+        let contracts = if sequence.verified.is_empty() {
+            vec![]
+        } else {
+            let receipts = sequence
+                .receipts
+                .iter()
+                .map(|receipt| (receipt.transaction_hash, receipt))
+                .collect::<HashMap<_, _>>();
+            sequence
+                .transactions
+                .iter()
+                // contracts of failed transactions were never deployed
+                .filter(|tx| {
+                    tx.hash
+                        .and_then(|hash| receipts.get(&hash))
+                        .map_or(false, |receipt| !receipt_failed(receipt))
+                })
+                .flat_map(|tx| {
+                    let created = matches!(tx.opcode, CallKind::Create | CallKind::Create2)
+                        .then_some(tx.contract_address)
+                        .flatten()
+                        .map(|address| (tx.contract_name.clone(), address));
+                    let additional =
+                        tx.additional_contracts.iter().map(|contract| (None, contract.address));
+                    created.into_iter().chain(additional)
+                })
+                .map(|(name, address)| DeployedContract {
+                    name: name.filter(|name| !name.is_empty()),
+                    address,
+                    verified: sequence.verified.get(&address).copied(),
+                })
+                .collect()
+        };
+
+        Self {
+            chain: sequence.chain,
+            kind,
+            path,
+            timestamp: sequence.timestamp,
+            total: sequence.transactions.len(),
+            sent: sequence.transactions.iter().filter(|tx| tx.hash.is_some()).count(),
+            confirmed: sequence.receipts.len() - failed,
+            failed,
+            pending,
+            contracts,
+        }
+    }
+
+    /// Returns a short description of how far along the sequence is.
+    pub fn state(&self) -> &'static str {
+        if self.kind == SequenceKind::DryRun {
+            "not broadcast"
+        } else if self.sent == 0 {
+            "not started"
+        } else if self.confirmed + self.failed == self.total {
+            if self.failed == 0 {
+                "done"
+            } else {
+                "done with failures"
+            }
+        } else if !self.pending.is_empty() {
+            "awaiting receipts"
+        } else {
+            "incomplete"
+        }
+    }
+}
+
+fn receipt_failed(receipt: &SavedReceipt) -> bool {
+    receipt.status.map_or(false, |status| status.is_zero())
+}
+
+impl ScriptArgs {
+    /// Prints the progress of the saved sequences of the script, without broadcasting anything.
+    ///
+    /// Loads the multi chain sequence with `--multi`, otherwise the sequence of every chain the
+    /// script was run on. Broadcast sequences take precedence over dry runs.
+    ///
+    /// The sequences are only located by the file name of the script, which isn't compiled.
+    pub fn sequence_status(self, script_config: ScriptConfig) -> Result<()> {
+        let config = &script_config.config;
+        let script = self.script_file_name(config)?;
+        let encryption = self.sequence_encryption(config);
+
+        let statuses = if self.multi {
+            multi_chain_status(&config.broadcast, &self.sig, &script, &encryption)?
+        } else {
+            chain_statuses(&config.broadcast, &self.sig, &script, &encryption)?
+        };
+
+        if self.json {
+            shell::print_json(&statuses)?;
+        } else {
+            for status in &statuses {
+                shell::println(format_status(status))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the file name of the script, looking a bare contract name up in the compiler cache.
+    fn script_file_name(&self, config: &Config) -> Result<String> {
+        let path = if Path::new(&self.path).is_file() {
+            PathBuf::from(&self.path)
+        } else {
+            let contract = ContractInfo::from_str(&self.path)?;
+            match contract.path {
+                Some(path) => PathBuf::from(path),
+                None => {
+                    let cache = SolFilesCache::read_joined(&config.project_paths())
+                        .wrap_err("Could not open compiler cache")?;
+                    get_cached_entry_by_name(&cache, &contract.name)
+                        .wrap_err("Could not find target contract in cache")?
+                        .0
+                }
+            }
+        };
+        Ok(path
+            .file_name()
+            .wrap_err_with(|| format!("No filename for {}", path.display()))?
+            .to_string_lossy()
+            .to_string())
+    }
+}
+
+/// Loads the multi chain sequence of `./broadcast/multi/[dry-run/]<script>-latest/<sig>.json`.
+fn multi_chain_status(
+    broadcast: &Path,
+    sig: &str,
+    script: &str,
+    encryption: &SequenceEncryption,
+) -> Result<Vec<ChainStatus>> {
+    let dir = broadcast.join("multi");
+    let file =
+        PathBuf::from(format!("{script}-latest")).join(format!("{}.json", sig_to_file_name(sig)));
+
+    let (path, kind) = [
+        (dir.join(&file), SequenceKind::Broadcast),
+        (dir.join(DRY_RUN_DIR).join(&file), SequenceKind::DryRun),
+    ]
+    .into_iter()
+    .find(|(path, _)| path.is_file())
+    .wrap_err("Multi-chain deployment not found.")?;
+
+    let (sequence, _): (SavedMultiChainSequence, _) = encryption.read(&path, script)?;
+    let now = now().as_secs();
+    Ok(sequence
+        .deployments
+        .into_iter()
+        .map(|sequence| ChainStatus::new(sequence, kind, path.clone(), now))
+        .collect())
+}
+
+/// Loads the sequence of every chain in `./broadcast/<script>/<chain>/[dry-run/]<sig>-latest.json`.
+fn chain_statuses(
+    broadcast: &Path,
+    sig: &str,
+    script: &str,
+    encryption: &SequenceEncryption,
+) -> Result<Vec<ChainStatus>> {
+    let dir = broadcast.join(script);
+    let file = format!("{}-latest.json", sig_to_file_name(sig));
+
+    let mut chains = if dir.is_dir() {
+        std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u64>().ok())
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+    chains.sort_unstable();
+
+    let now = now().as_secs();
+    let mut statuses = vec![];
+    for chain in chains {
+        let chain_dir = dir.join(chain.to_string());
+        let Some((path, kind)) = [
+            (chain_dir.join(&file), SequenceKind::Broadcast),
+            (chain_dir.join(DRY_RUN_DIR).join(&file), SequenceKind::DryRun),
+        ]
+        .into_iter()
+        .find(|(path, _)| path.is_file()) else {
+            continue
+        };
+        let (sequence, _): (SavedSequence, _) = encryption.read(&path, script)?;
+        statuses.push(ChainStatus::new(sequence, kind, path, now));
+    }
+
+    if statuses.is_empty() {
+        eyre::bail!(
+            "No deployments found for {script} in {}. If the script was run on multiple chains, add `--multi`.",
+            dir.display()
+        )
+    }
+    Ok(statuses)
+}
+
+fn format_status(status: &ChainStatus) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    table.set_header(["Total", "Sent", "Confirmed", "Failed", "Pending"]);
+    table.add_row([
+        status.total.to_string(),
+        status.sent.to_string(),
+        status.confirmed.to_string(),
+        status.failed.to_string(),
+        status.pending.len().to_string(),
+    ]);
+    let mut out = format!(
+        "Chain {} ({}, {}): {}\n{table}",
+        status.chain,
+        status.kind,
+        status.path.display(),
+        status.state()
+    );
+
+    for tx in &status.pending {
+        out.push_str(&format!("\n  pending {} for {}", tx.hash, format_age(tx.age)));
+    }
+
+    if !status.contracts.is_empty() {
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header(["Contract", "Address", "Verified"]);
+        for contract in &status.contracts {
+            table.add_row([
+                contract.name.clone().unwrap_or_default(),
+                contract.address.to_string(),
+                contract.verified.map_or_else(|| "no".to_string(), |p| format!("{p:?}")),
+            ]);
+        }
+        out.push_str(&format!("\n\n{table}"));
+    }
+    out.push('\n');
+    out
+}
+
+/// Formats seconds as the two most significant units, e.g. `3h 12m`.
+fn format_age(secs: u64) -> String {
+    let (days, hours, mins) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else if mins > 0 {
+        format!("{mins}m {}s", secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_chain_status() {
+        let sequence: SavedSequence = serde_json::from_value(serde_json::json!({
+            "transactions": [
+                {
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    "transactionType": "CREATE",
+                    "contractName": "Counter",
+                    "contractAddress": "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+                    "additionalContracts": []
+                },
+                {
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                    "transactionType": "CALL",
+                    "contractName": "Counter",
+                    "contractAddress": "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+                    "additionalContracts": []
+                },
+                {
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000003",
+                    "transactionType": "CALL",
+                    "contractName": "Counter",
+                    "contractAddress": "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+                    "additionalContracts": []
+                },
+                {
+                    "hash": null,
+                    "transactionType": "CALL",
+                    "contractName": "Counter",
+                    "contractAddress": "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+                    "additionalContracts": []
+                }
+            ],
+            "receipts": [
+                {
+                    "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    "status": "0x1"
+                },
+                {
+                    "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                    "status": "0x0"
+                }
+            ],
+            "pending": ["0x0000000000000000000000000000000000000000000000000000000000000003"],
+            "timestamp": 100,
+            "chain": 1,
+            "verified": { "0x5fbdb2315678afecb367f032d93f642f64180aa3": "etherscan" }
+        }))
+        .unwrap();
+
+        let status = ChainStatus::new(sequence, SequenceKind::Broadcast, PathBuf::new(), 250);
+        assert_eq!(status.total, 4);
+        assert_eq!(status.sent, 3);
+        assert_eq!(status.confirmed, 1);
+        assert_eq!(status.failed, 1);
+        assert_eq!(status.pending.len(), 1);
+        assert_eq!(status.pending[0].age, 150);
+        assert_eq!(status.state(), "awaiting receipts");
+        assert_eq!(
+            status.contracts,
+            vec![DeployedContract {
+                name: Some("Counter".to_string()),
+                address: "0x5fbdb2315678afecb367f032d93f642f64180aa3".parse().unwrap(),
+                verified: Some(VerificationProviderType::Etherscan),
+            }]
+        );
+    }
+
+    #[test]
+    fn formats_ages() {
+        assert_eq!(format_age(42), "42s");
+        assert_eq!(format_age(150), "2m 30s");
+        assert_eq!(format_age(3 * 3600 + 12 * 60), "3h 12m");
+        assert_eq!(format_age(2 * 86400 + 3600), "2d 1h");
+    }
+}
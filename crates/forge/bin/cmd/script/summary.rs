@@ -0,0 +1,281 @@
+//! Gas usage and cost summary of a broadcast sequence.
+
+use super::{sequence::ScriptSequence, ScriptArgs};
+use alloy_primitives::{address, utils::format_units, Address, TxHash, U256};
+use comfy_table::Table;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+use ethers_providers::Middleware;
+use eyre::{ContextCompat, Result, WrapErr};
+use foundry_common::{
+    provider::ethers::try_get_http_provider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The Chainlink ETH/USD price feed on Ethereum mainnet.
+const ETH_USD_FEED: Address = address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419");
+
+/// The chains whose native token is ETH: Ethereum, Optimism, Arbitrum, Base, zkSync Era, Linea,
+/// Scroll and Zora, and their testnets.
+const ETH_NATIVE_CHAINS: &[u64] = &[
+    1, 5, 17000, 11155111, 10, 420, 11155420, 42161, 42170, 421613, 421614, 8453, 84531, 84532,
+    324, 280, 300, 59144, 59140, 534352, 534351, 7777777, 999999999,
+];
+
+/// The selector of `latestRoundData()`.
+const LATEST_ROUND_DATA: [u8; 4] = [0xfe, 0xaf, 0x96, 0x8c];
+
+/// The number of transactions listed as the most expensive ones.
+const MOST_EXPENSIVE: usize = 3;
+
+/// The gas used and paid by the confirmed transactions of a sequence.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceSummary {
+    pub chain: u64,
+    pub transactions: usize,
+    pub gas_used: U256,
+    /// The average effective gas price, in wei.
+    pub avg_gas_price: U256,
+    /// The cost of the transactions in the native token, in wei.
+    pub total_cost: U256,
+    /// The USD price of the native token, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_usd_price: Option<f64>,
+    pub most_expensive: Vec<TransactionCost>,
+}
+
+/// The gas used and paid by a transaction of a sequence.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionCost {
+    pub hash: TxHash,
+    /// The called function or created contract, e.g. `Counter::increment()` or `new Counter`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    pub gas_used: U256,
+    pub cost: U256,
+}
+
+impl SequenceSummary {
+    /// Sums the receipts of the sequence.
+    pub fn new(sequence: &ScriptSequence, native_usd_price: Option<f64>) -> Self {
+        let mut summary = Self { chain: sequence.chain, native_usd_price, ..Default::default() };
+        let mut total_gas_price = U256::ZERO;
+        let mut costs = vec![];
+        for receipt in &sequence.receipts {
+            let gas_used = receipt.gas_used.unwrap_or_default().to_alloy();
+            let gas_price = receipt.effective_gas_price.unwrap_or_default().to_alloy();
+            let cost = gas_used * gas_price;
+            summary.transactions += 1;
+            summary.gas_used += gas_used;
+            summary.total_cost += cost;
+            total_gas_price += gas_price;
+
+            let hash = receipt.transaction_hash.to_alloy();
+            let function = sequence
+                .transactions
+                .iter()
+                .find(|tx| tx.hash == Some(hash))
+                .and_then(|tx| describe(tx.contract_name.as_deref(), tx.function.as_deref()));
+            costs.push(TransactionCost { hash, function, gas_used, cost });
+        }
+        if summary.transactions > 0 {
+            summary.avg_gas_price = total_gas_price / U256::from(summary.transactions);
+        }
+
+        costs.sort_by(|a, b| b.cost.cmp(&a.cost));
+        costs.truncate(MOST_EXPENSIVE);
+        summary.most_expensive = costs;
+        summary
+    }
+
+    /// Returns the approximate cost of the transactions in USD, if the price of the native token
+    /// is known.
+    pub fn usd_cost(&self) -> Option<f64> {
+        Some(wei_to_native(self.total_cost) * self.native_usd_price?)
+    }
+
+    /// Returns the cost of `wei`, and its approximate USD value if known.
+    fn format_cost(&self, wei: U256) -> String {
+        let native = format_units(wei, 18).unwrap_or_else(|_| "N/A".to_string());
+        let native = native.trim_end_matches('0').trim_end_matches('.');
+        match self.native_usd_price {
+            Some(price) => format!("{native} ETH (~${:.2})", wei_to_native(wei) * price),
+            None => format!("{native} ETH"),
+        }
+    }
+}
+
+impl fmt::Display for SequenceSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let avg_gas_price = format_units(self.avg_gas_price, 9).unwrap_or_else(|_| "N/A".into());
+
+        let mut table = Table::new();
+        table.set_header([format!("Chain {}", self.chain), String::new()]);
+        table.add_row(["Transactions".to_string(), self.transactions.to_string()]);
+        table.add_row(["Gas used".to_string(), self.gas_used.to_string()]);
+        table.add_row([
+            "Avg gas price".to_string(),
+            format!("{} gwei", avg_gas_price.trim_end_matches('0').trim_end_matches('.')),
+        ]);
+        table.add_row(["Total paid".to_string(), self.format_cost(self.total_cost)]);
+        writeln!(f, "{table}")?;
+
+        if !self.most_expensive.is_empty() {
+            let mut table = Table::new();
+            table.set_header(["Most expensive", "Function", "Gas used", "Cost"]);
+            for tx in &self.most_expensive {
+                table.add_row([
+                    tx.hash.to_string(),
+                    tx.function.clone().unwrap_or_default(),
+                    tx.gas_used.to_string(),
+                    self.format_cost(tx.cost),
+                ]);
+            }
+            write!(f, "{table}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ScriptArgs {
+    /// Prints the gas usage and cost summary of the sequence, and saves it into the sequence.
+    pub(super) async fn summarize(&self, sequence: &mut ScriptSequence) -> Result<()> {
+        if sequence.receipts.is_empty() {
+            return Ok(())
+        }
+        let summary = SequenceSummary::new(sequence, self.native_usd_price(sequence.chain).await);
+        shell::println(format!("\n{summary}"))?;
+        sequence.summary = Some(summary);
+        Ok(())
+    }
+
+    /// Returns the USD price of the native token of `chain`, from `--native-usd-price` or the
+    /// price feed.
+    ///
+    /// The feed only prices ETH, so there's no USD figure for chains with another native token.
+    async fn native_usd_price(&self, chain: u64) -> Option<f64> {
+        if let Some(price) = self.native_usd_price {
+            return Some(price)
+        }
+        let url = self.price_feed.as_deref()?;
+        let Some(feed) = native_usd_feed(chain) else {
+            let _ = shell::eprintln(format!(
+                "No USD price feed for the native token of chain {chain}, skipping the USD cost"
+            ));
+            return None
+        };
+        match feed_usd_price(url, feed).await {
+            Ok(price) => Some(price),
+            Err(err) => {
+                let _ = shell::eprintln(format!("Could not read the USD price feed: {err}"));
+                None
+            }
+        }
+    }
+}
+
+/// Returns the Chainlink feed on the Ethereum mainnet of the USD price of the native token of
+/// `chain`, if known.
+fn native_usd_feed(chain: u64) -> Option<Address> {
+    ETH_NATIVE_CHAINS.contains(&chain).then_some(ETH_USD_FEED)
+}
+
+/// Reads the USD price from the Chainlink `feed` on the Ethereum mainnet at `url`.
+async fn feed_usd_price(url: &str, feed: Address) -> Result<f64> {
+    let provider = try_get_http_provider(url)?;
+    let tx: TypedTransaction =
+        TransactionRequest::new().to(feed.to_ethers()).data(LATEST_ROUND_DATA.to_vec()).into();
+    let output = provider.call(&tx, None).await.wrap_err("could not call the feed")?;
+    // `(uint80 roundId, int256 answer, ...)`, with 8 decimals
+    let answer = output.get(32..64).wrap_err("invalid feed response")?;
+    let answer = U256::from_be_slice(answer);
+    eyre::ensure!(answer > U256::ZERO && answer < U256::from(u64::MAX), "invalid feed answer");
+    Ok(answer.to::<u64>() as f64 / 1e8)
+}
+
+/// Converts `wei` to the native token, lossily.
+fn wei_to_native(wei: U256) -> f64 {
+    format_units(wei, 18).ok().and_then(|native| native.parse().ok()).unwrap_or_default()
+}
+
+/// Describes the call of a transaction, e.g. `Counter::increment()` or `new Counter`.
+pub(super) fn describe(contract: Option<&str>, function: Option<&str>) -> Option<String> {
+    let contract = contract.filter(|name| !name.is_empty());
+    match function.filter(|function| !function.is_empty()) {
+        Some(function) => {
+            Some(contract.map_or(function.to_string(), |name| format!("{name}::{function}")))
+        }
+        None => contract.map(|name| format!("new {name}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::script::transaction::TransactionWithMetadata;
+    use ethers_core::types::TransactionReceipt;
+
+    #[test]
+    fn summarizes_sequence() {
+        let receipt = |hash: u8, gas: u64, price: u64| TransactionReceipt {
+            transaction_hash: [hash; 32].into(),
+            gas_used: Some(gas.into()),
+            effective_gas_price: Some(price.into()),
+            ..Default::default()
+        };
+        let tx = |hash: u8, contract: &str, function: Option<&str>| TransactionWithMetadata {
+            hash: Some(TxHash::repeat_byte(hash)),
+            contract_name: Some(contract.to_string()),
+            function: function.map(str::to_string),
+            ..Default::default()
+        };
+
+        // multi chain sequences aren't saved on drop
+        let mut sequence = ScriptSequence { chain: 1, multi: true, ..Default::default() };
+        sequence.transactions = [
+            tx(1, "Counter", None),
+            tx(2, "Counter", Some("increment()")),
+            tx(3, "Counter", Some("setNumber(uint256)")),
+            tx(4, "", Some("transfer()")),
+        ]
+        .into();
+        sequence.receipts = vec![
+            receipt(1, 100_000, 10),
+            receipt(2, 30_000, 20),
+            receipt(3, 40_000, 20),
+            receipt(4, 21_000, 30),
+        ];
+
+        let summary = SequenceSummary::new(&sequence, Some(2000.0));
+
+        assert_eq!(summary.transactions, 4);
+        assert_eq!(summary.gas_used, U256::from(191_000));
+        assert_eq!(summary.avg_gas_price, U256::from(20));
+        assert_eq!(summary.total_cost, U256::from(1_000_000 + 600_000 + 800_000 + 630_000));
+        assert_eq!(
+            summary.most_expensive.iter().map(|tx| tx.function.as_deref()).collect::<Vec<_>>(),
+            [Some("new Counter"), Some("Counter::setNumber(uint256)"), Some("transfer()")]
+        );
+        assert!((summary.usd_cost().unwrap() - 3_030_000.0 * 2000.0 / 1e18).abs() < 1e-12);
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["gasUsed"], "0x2ea18");
+        assert_eq!(serde_json::from_value::<SequenceSummary>(json).unwrap(), summary);
+    }
+
+    #[test]
+    fn native_usd_feeds() {
+        // Ethereum, Optimism and Base
+        for chain in [1, 10, 8453] {
+            assert_eq!(native_usd_feed(chain), Some(ETH_USD_FEED));
+        }
+        // Polygon, BNB Smart Chain and Avalanche
+        for chain in [137, 56, 43114] {
+            assert_eq!(native_usd_feed(chain), None);
+        }
+    }
+}
@@ -196,10 +196,10 @@ impl TransactionWithMetadata {
     ) -> Result<()> {
         self.opcode = CallKind::Call;
 
-        let Some(data) = self.transaction.data() else { return Ok(()) };
-        if data.len() < SELECTOR_LEN {
+        let Some(data) = self.transaction.data().filter(|data| data.len() >= SELECTOR_LEN) else {
+            self.contract_name = decoder.labels.get(&target).cloned();
             return Ok(());
-        }
+        };
         let (selector, data) = data.split_at(SELECTOR_LEN);
 
         let function = if let Some(info) = local_contracts.get(&target) {
@@ -229,6 +229,11 @@ impl TransactionWithMetadata {
             self.arguments = Some(values.iter().map(format_token_raw).collect());
         }
 
+        // Fall back to the label of the target, e.g. one resolved from the address book.
+        if self.contract_name.is_none() {
+            self.contract_name = decoder.labels.get(&target).cloned();
+        }
+
         self.contract_address = Some(target);
 
         Ok(())
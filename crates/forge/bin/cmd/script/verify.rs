@@ -61,6 +61,12 @@ impl VerifyBundle {
         // chain_id.
         self.etherscan.key = config.get_etherscan_api_key(Some(chain));
         self.etherscan.chain = Some(chain);
+        self.verifier = self.verifier_for(config, chain);
+    }
+
+    /// Returns the verifier arguments to use on `chain`.
+    pub fn verifier_for(&self, config: &Config, chain: Chain) -> VerifierArgs {
+        self.verifier.with_chain_overrides(config, chain)
     }
 
     /// Given a `VerifyBundle` and contract details, it tries to generate a valid `VerifyArgs` to
@@ -115,6 +121,7 @@ impl VerifyBundle {
                     evm_version: None,
                     show_standard_json_input: false,
                     guess_constructor_args: false,
+                    preflight: false,
                 };
 
                 return Some(verify)
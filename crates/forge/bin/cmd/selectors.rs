@@ -1,16 +1,17 @@
+use alloy_primitives::keccak256;
 use clap::Parser;
 use comfy_table::Table;
 use eyre::Result;
 use foundry_cli::{
     opts::{CompilerArgs, CoreBuildArgs, ProjectPathsArgs},
-    utils::FoundryPathExt,
+    utils::{FoundryPathExt, LoadConfig},
 };
 use foundry_common::{
     compile::ProjectCompiler,
-    selectors::{import_selectors, SelectorImportData},
+    selectors::{RawSelectorImportData, SignEthClient},
 };
 use foundry_compilers::{artifacts::output_selection::ContractOutputSelection, info::ContractInfo};
-use std::fs::canonicalize;
+use std::{collections::BTreeSet, fs::canonicalize};
 
 /// CLI arguments for `forge selectors`.
 #[derive(Clone, Debug, Parser)]
@@ -31,16 +32,30 @@ pub enum SelectorsSubcommands {
     },
 
     /// Upload selectors to registry
+    ///
+    /// Signatures that are already registered are skipped.
     #[command(visible_alias = "up")]
     Upload {
-        /// The name of the contract to upload selectors for.
-        #[arg(required_unless_present = "all")]
-        contract: Option<String>,
+        /// The names of the contracts to upload selectors for.
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        contracts: Vec<String>,
 
         /// Upload selectors for all contracts in the project.
-        #[arg(long, required_unless_present = "contract")]
+        #[arg(long, required_unless_present = "contracts")]
         all: bool,
 
+        /// Also upload the signatures to https://www.4byte.directory.
+        #[arg(long)]
+        fourbyte: bool,
+
+        /// Only list the signatures that would be uploaded.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Do not access the network.
+        #[arg(long)]
+        offline: bool,
+
         #[command(flatten)]
         project_paths: ProjectPathsArgs,
     },
@@ -60,17 +75,32 @@ pub enum SelectorsSubcommands {
 impl SelectorsSubcommands {
     pub async fn run(self) -> Result<()> {
         match self {
-            SelectorsSubcommands::Upload { contract, all, project_paths } => {
+            SelectorsSubcommands::Upload {
+                contracts,
+                all,
+                fourbyte,
+                dry_run,
+                offline,
+                project_paths,
+            } => {
                 let build_args = CoreBuildArgs {
                     project_paths: project_paths.clone(),
                     compiler: CompilerArgs {
                         extra_output: vec![ContractOutputSelection::Abi],
                         ..Default::default()
                     },
+                    offline,
                     ..Default::default()
                 };
 
-                let project = build_args.project()?;
+                let config = build_args.try_load_config_emit_warnings()?;
+                if config.offline && !dry_run {
+                    eyre::bail!(
+                        "Cannot upload selectors in offline mode, use `--dry-run` to list them instead"
+                    )
+                }
+
+                let project = config.project()?;
                 let output = ProjectCompiler::new().quiet(true).compile(&project)?;
                 let artifacts = if all {
                     output
@@ -85,33 +115,74 @@ impl SelectorsSubcommands {
                         .map(|(_, contract, artifact)| (contract, artifact))
                         .collect()
                 } else {
-                    let contract = contract.unwrap();
-                    let found_artifact = output.find_first(&contract);
-                    let artifact = found_artifact
-                        .ok_or_else(|| {
-                            eyre::eyre!(
-                                "Could not find artifact `{contract}` in the compiled artifacts"
-                            )
-                        })?
-                        .clone();
-                    vec![(contract, artifact)]
+                    contracts
+                        .into_iter()
+                        .map(|contract| {
+                            let artifact = output.find_first(&contract).ok_or_else(|| {
+                                eyre::eyre!(
+                                    "Could not find artifact `{contract}` in the compiled artifacts"
+                                )
+                            })?;
+                            Ok((contract, artifact.clone()))
+                        })
+                        .collect::<Result<Vec<_>>>()?
                 };
 
-                let mut artifacts = artifacts.into_iter().peekable();
-                while let Some((contract, artifact)) = artifacts.next() {
+                let mut abis = Vec::with_capacity(artifacts.len());
+                let (mut functions, mut events, mut errors) =
+                    (BTreeSet::new(), BTreeSet::new(), BTreeSet::new());
+                for (contract, artifact) in artifacts {
                     let abi = artifact.abi.ok_or_else(|| eyre::eyre!("Unable to fetch abi"))?;
                     if abi.functions.is_empty() && abi.events.is_empty() && abi.errors.is_empty() {
                         continue
                     }
+                    functions.extend(abi.functions().map(|func| func.signature()));
+                    events.extend(abi.events().map(|event| event.signature()));
+                    errors.extend(abi.errors().map(|error| error.signature()));
+                    abis.push((contract, abi));
+                }
+                let signatures = RawSelectorImportData {
+                    function: functions.into_iter().collect(),
+                    event: events.into_iter().collect(),
+                    error: errors.into_iter().collect(),
+                };
 
-                    println!("Uploading selectors for {contract}...");
+                if signatures.is_empty() {
+                    println!("No selectors to upload.");
+                    return Ok(())
+                }
 
-                    // upload abi to selector database
-                    import_selectors(SelectorImportData::Abi(vec![abi])).await?.describe();
+                if dry_run {
+                    println!("Would upload {} signature(s):", signatures.len());
+                    println!("{}", signatures_table(&signatures));
+                    return Ok(())
+                }
 
-                    if artifacts.peek().is_some() {
-                        println!()
+                let client = SignEthClient::new()?;
+                let (known, unknown) = client.partition_registered(signatures).await?;
+
+                let mut imported = 0;
+                let mut duplicated = known.len();
+                for response in client.import_signatures(unknown).await? {
+                    imported += response.imported();
+                    duplicated += response.duplicated();
+                }
+                println!(
+                    "Uploaded selectors to https://api.openchain.xyz: {imported} newly registered, {duplicated} already known"
+                );
+
+                if fourbyte {
+                    let (mut imported, mut duplicated) = (0, 0);
+                    for (contract, abi) in abis.iter() {
+                        let response = client.import_fourbyte(abi).await.map_err(|err| {
+                            eyre::eyre!("Failed to upload selectors of {contract} to 4byte: {err}")
+                        })?;
+                        imported += response.num_imported;
+                        duplicated += response.num_duplicates;
                     }
+                    println!(
+                        "Uploaded selectors to https://www.4byte.directory: {imported} newly registered, {duplicated} already known"
+                    );
                 }
             }
             SelectorsSubcommands::Collision { mut first_contract, mut second_contract, build } => {
@@ -260,3 +331,21 @@ impl SelectorsSubcommands {
         Ok(())
     }
 }
+
+/// Returns a table of the given signatures and their selectors.
+fn signatures_table(signatures: &RawSelectorImportData) -> Table {
+    let mut table = Table::new();
+    table.set_header(["Type", "Signature", "Selector"]);
+    let rows = [
+        ("Function", &signatures.function, 4),
+        ("Event", &signatures.event, 32),
+        ("Error", &signatures.error, 4),
+    ];
+    for (kind, signatures, len) in rows {
+        for signature in signatures {
+            let selector = hex::encode_prefixed(&keccak256(signature)[..len]);
+            table.add_row([kind, signature, &selector]);
+        }
+    }
+    table
+}
@@ -1,14 +1,21 @@
 use super::test;
 use alloy_primitives::U256;
 use clap::{builder::RangedU64ValueParser, Parser, ValueHint};
+use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Color, Table};
 use eyre::{Context, Result};
-use forge::result::{SuiteTestResult, TestKindReport, TestOutcome};
-use foundry_cli::utils::STATIC_FUZZ_SEED;
+use forge::{
+    result::{SuiteTestResult, TestKindReport, TestOutcome},
+    TestFilter,
+};
+use foundry_cli::utils::{LoadConfig, STATIC_FUZZ_SEED};
+use foundry_common::get_contract_name;
+use foundry_config::Config;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::{
-    cmp::Ordering,
-    collections::HashMap,
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Write},
     fs,
     io::{self, BufRead},
     path::{Path, PathBuf},
@@ -20,9 +27,21 @@ use yansi::Paint;
 /// A regex that matches a basic snapshot entry like
 /// `Test:testDeposit() (gas: 58804)`
 pub static RE_BASIC_SNAPSHOT_ENTRY: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?P<file>(.*?)):(?P<sig>(\w+)\s*\((.*?)\))\s*\(((gas:)?\s*(?P<gas>\d+)|(runs:\s*(?P<runs>\d+),\s*μ:\s*(?P<avg>\d+),\s*~:\s*(?P<med>\d+))|(runs:\s*(?P<invruns>\d+),\s*calls:\s*(?P<calls>\d+),\s*reverts:\s*(?P<reverts>\d+)))\)").unwrap()
+    Regex::new(r"(?P<file>(.*?)):(?P<sig>(\w+)\s*\((.*?)\))\s*\(((gas:)?\s*(?P<gas>\d+)|(runs:\s*(?P<runs>\d+),\s*μ:\s*(?P<avg>\d+),\s*~:\s*(?P<med>\d+))|(runs:\s*(?P<invruns>\d+),\s*calls:\s*(?P<calls>\d+),\s*reverts:\s*(?P<reverts>\d+)(,\s*time:\s*(?P<time>\d+)s)?))\)").unwrap()
+});
+
+/// A regex that matches a named gas snapshot entry, taken with the `snapshotGas` cheatcodes, like
+/// `Test:testDeposit():transfer (gas: 21000)`
+pub static RE_NAMED_SNAPSHOT_ENTRY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<file>(.*?)):(?P<sig>(\w+)\s*\((.*?)\)):(?P<name>(.+?))\s*\(gas:\s*(?P<gas>\d+)\)$",
+    )
+    .unwrap()
 });
 
+/// The header of the section of the snapshot file holding the named gas snapshots.
+pub const NAMED_SNAPSHOTS_SECTION: &str = "[snapshotGas]";
+
 /// CLI arguments for `forge snapshot`.
 #[derive(Clone, Debug, Parser)]
 pub struct SnapshotArgs {
@@ -50,9 +69,14 @@ pub struct SnapshotArgs {
     )]
     check: Option<Option<PathBuf>>,
 
-    // Hidden because there is only one option
-    /// How to format the output.
-    #[arg(long, hide(true))]
+    /// Include the tests whose gas usage didn't change in the diff.
+    #[arg(long, requires = "diff")]
+    include_unchanged: bool,
+
+    /// How to format the diff.
+    ///
+    /// `markdown` emits a table that can be posted as a pull request comment.
+    #[arg(long, value_name = "FORMAT")]
     format: Option<Format>,
 
     /// Output file for the snapshot.
@@ -89,41 +113,66 @@ impl SnapshotArgs {
 
     /// Returns the [`watchexec::InitConfig`] and [`watchexec::RuntimeConfig`] necessary to
     /// bootstrap a new [`watchexe::Watchexec`] loop.
-    pub(crate) fn watchexec_config(&self) -> Result<(InitConfig, RuntimeConfig)> {
-        self.test.watchexec_config()
+    pub(crate) fn watchexec_config(&self, config: &Config) -> Result<(InitConfig, RuntimeConfig)> {
+        self.test.watchexec_config(config)
     }
 
     pub async fn run(mut self) -> Result<()> {
         // Set fuzz seed so gas snapshots are deterministic
         self.test.fuzz_seed = Some(U256::from_be_bytes(STATIC_FUZZ_SEED));
 
+        let filter = self.test.filter(&self.test.load_config());
         let outcome = self.test.execute_tests().await?;
         outcome.ensure_ok()?;
+        let ran = outcome
+            .results
+            .iter()
+            .flat_map(|(id, suite)| {
+                let contract_name = get_contract_name(id);
+                suite.test_results.keys().map(move |sig| (contract_name.to_string(), sig.clone()))
+            })
+            .collect::<HashSet<_>>();
         let tests = self.config.apply(outcome);
 
         if let Some(path) = self.diff {
             let snap = path.as_ref().unwrap_or(&self.snap);
-            let snaps = read_snapshot(snap)?;
-            diff(tests, snaps)?;
+            let (mut snaps, mut named) = read_snapshot(snap)?;
+            // tests of the snapshot that weren't selected for this run can't be compared
+            let filters_paths = filter.filters_paths() || self.test.changed.is_some();
+            retain_selected(&mut snaps, &filter, filters_paths, &ran, &tests);
+            let selected = snaps
+                .iter()
+                .map(|snap| (snap.contract_name.as_str(), snap.signature.as_str()))
+                .chain(tests.iter().map(|test| (test.contract_name(), test.signature.as_str())))
+                .collect::<HashSet<_>>();
+            named.retain(|entry| {
+                selected.contains(&(entry.contract_name.as_str(), entry.signature.as_str()))
+            });
+            let report = SnapshotDiffReport::new(tests, snaps, named, self.include_unchanged);
+            match self.format {
+                Some(Format::Markdown) => print!("{}", report.markdown()),
+                _ => print!("{report}"),
+            }
         } else if let Some(path) = self.check {
             let snap = path.as_ref().unwrap_or(&self.snap);
-            let snaps = read_snapshot(snap)?;
-            if check(tests, snaps, self.tolerance) {
+            let (snaps, named) = read_snapshot(snap)?;
+            if check(tests, snaps, named, self.tolerance) {
                 std::process::exit(0)
             } else {
                 std::process::exit(1)
             }
         } else {
-            write_to_snapshot_file(&tests, self.snap, self.format)?;
+            write_to_snapshot_file(&tests, self.snap)?;
         }
         Ok(())
     }
 }
 
-// TODO implement pretty tables
+/// The output format of a snapshot diff
 #[derive(Clone, Debug)]
 pub enum Format {
     Table,
+    Markdown,
 }
 
 impl FromStr for Format {
@@ -132,6 +181,7 @@ impl FromStr for Format {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "t" | "table" => Ok(Format::Table),
+            "md" | "markdown" => Ok(Format::Markdown),
             _ => Err(format!("Unrecognized format `{s}`")),
         }
     }
@@ -244,6 +294,9 @@ impl FromStr for SnapshotEntry {
                                         runs: runs.as_str().parse().unwrap(),
                                         calls: calls.as_str().parse().unwrap(),
                                         reverts: reverts.as_str().parse().unwrap(),
+                                        time_elapsed: cap
+                                            .name("time")
+                                            .map_or(0, |time| time.as_str().parse().unwrap()),
                                     },
                                 })
                         }
@@ -254,27 +307,73 @@ impl FromStr for SnapshotEntry {
     }
 }
 
-/// Reads a list of snapshot entries from a snapshot file
-fn read_snapshot(path: impl AsRef<Path>) -> Result<Vec<SnapshotEntry>> {
+/// A named gas snapshot entry, taken with the `snapshotGas` cheatcodes
+///
+/// Has the form `<signature>:<name> (gas: 21000)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamedSnapshotEntry {
+    pub contract_name: String,
+    pub signature: String,
+    pub name: String,
+    pub gas_used: u64,
+}
+
+impl FromStr for NamedSnapshotEntry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RE_NAMED_SNAPSHOT_ENTRY
+            .captures(s)
+            .map(|cap| NamedSnapshotEntry {
+                contract_name: cap["file"].to_string(),
+                signature: cap["sig"].to_string(),
+                name: cap["name"].to_string(),
+                gas_used: cap["gas"].parse().unwrap(),
+            })
+            .ok_or_else(|| format!("Could not extract named Snapshot Entry for {s}"))
+    }
+}
+
+impl fmt::Display for NamedSnapshotEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{} (gas: {})",
+            self.contract_name, self.signature, self.name, self.gas_used
+        )
+    }
+}
+
+/// Reads the test entries and the named entries from a snapshot file
+fn read_snapshot(path: impl AsRef<Path>) -> Result<(Vec<SnapshotEntry>, Vec<NamedSnapshotEntry>)> {
     let path = path.as_ref();
     let mut entries = Vec::new();
+    let mut named = Vec::new();
+    let mut in_named_section = false;
     for line in io::BufReader::new(
         fs::File::open(path)
             .wrap_err(format!("failed to read snapshot file \"{}\"", path.display()))?,
     )
     .lines()
     {
-        entries.push(SnapshotEntry::from_str(line?.as_str()).map_err(|err| eyre::eyre!("{err}"))?);
+        let line = line?;
+        if line.is_empty() {
+            continue
+        }
+        if line == NAMED_SNAPSHOTS_SECTION {
+            in_named_section = true;
+        } else if in_named_section {
+            named.push(NamedSnapshotEntry::from_str(&line).map_err(|err| eyre::eyre!("{err}"))?);
+        } else {
+            entries.push(SnapshotEntry::from_str(&line).map_err(|err| eyre::eyre!("{err}"))?);
+        }
     }
-    Ok(entries)
+    Ok((entries, named))
 }
 
-/// Writes a series of tests to a snapshot file after sorting them
-fn write_to_snapshot_file(
-    tests: &[SuiteTestResult],
-    path: impl AsRef<Path>,
-    _format: Option<Format>,
-) -> Result<()> {
+/// Writes a series of tests to a snapshot file after sorting them, followed by their named gas
+/// snapshots, if any
+fn write_to_snapshot_file(tests: &[SuiteTestResult], path: impl AsRef<Path>) -> Result<()> {
     let mut reports = tests
         .iter()
         .map(|test| {
@@ -285,42 +384,366 @@ fn write_to_snapshot_file(
     // sort all reports
     reports.sort();
 
+    let mut named = named_snapshots(tests).map(|entry| entry.to_string()).collect::<Vec<_>>();
+    if !named.is_empty() {
+        named.sort();
+        reports.push(String::new());
+        reports.push(NAMED_SNAPSHOTS_SECTION.to_string());
+        reports.extend(named);
+    }
+
     let content = reports.join("\n");
     Ok(fs::write(path, content)?)
 }
 
+/// Returns the named gas snapshots of the tests
+fn named_snapshots(tests: &[SuiteTestResult]) -> impl Iterator<Item = NamedSnapshotEntry> + '_ {
+    tests.iter().flat_map(|test| {
+        test.result.gas_snapshots.iter().map(|(name, gas_used)| NamedSnapshotEntry {
+            contract_name: test.contract_name().to_string(),
+            signature: test.signature.clone(),
+            name: name.clone(),
+            gas_used: *gas_used,
+        })
+    })
+}
+
 /// A Snapshot entry diff
+///
+/// Fuzz tests have a diff for their mean and their median gas.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SnapshotDiff {
     pub signature: String,
-    pub source_gas_used: TestKindReport,
-    pub target_gas_used: TestKindReport,
+    /// The compared fuzz metric, [MEAN] or [MEDIAN]
+    pub metric: Option<&'static str>,
+    /// The gas used in the snapshot
+    pub old_gas: u64,
+    /// The gas used in the current run
+    pub new_gas: u64,
 }
 
+/// The label of the mean gas of a fuzz test
+const MEAN: &str = "μ";
+/// The label of the median gas of a fuzz test
+const MEDIAN: &str = "~";
+
 impl SnapshotDiff {
+    /// Returns the diffs between the gas reports of a test, none for invariant tests.
+    fn new(signature: &str, old: &TestKindReport, new: &TestKindReport) -> Vec<Self> {
+        let diff = |metric, old_gas, new_gas| SnapshotDiff {
+            signature: signature.to_string(),
+            metric,
+            old_gas,
+            new_gas,
+        };
+        match (old, new) {
+            (TestKindReport::Invariant { .. }, TestKindReport::Invariant { .. }) => vec![],
+            (
+                TestKindReport::Fuzz { mean_gas: old_mean, median_gas: old_median, .. },
+                TestKindReport::Fuzz { mean_gas, median_gas, .. },
+            ) => vec![
+                diff(Some(MEAN), *old_mean, *mean_gas),
+                diff(Some(MEDIAN), *old_median, *median_gas),
+            ],
+            _ => vec![diff(None, old.gas(), new.gas())],
+        }
+    }
+
+    /// Returns the name of the test, with the fuzz metric if any
+    fn name(&self) -> String {
+        match self.metric {
+            Some(metric) => format!("{} ({metric})", self.signature),
+            None => self.signature.clone(),
+        }
+    }
+
     /// Returns the gas diff
     ///
-    /// `> 0` if the source used more gas
-    /// `< 0` if the target used more gas
+    /// `> 0` if the current run used more gas
+    /// `< 0` if the snapshot used more gas
     fn gas_change(&self) -> i128 {
-        self.source_gas_used.gas() as i128 - self.target_gas_used.gas() as i128
+        self.new_gas as i128 - self.old_gas as i128
     }
 
     /// Determines the percentage change
     fn gas_diff(&self) -> f64 {
-        self.gas_change() as f64 / self.target_gas_used.gas() as f64
+        if self.old_gas == 0 {
+            return 0.0
+        }
+        self.gas_change() as f64 / self.old_gas as f64
+    }
+}
+
+/// The diff between the current test results and a snapshot, grouped by test contract
+#[derive(Clone, Debug, Default, PartialEq)]
+struct SnapshotDiffReport {
+    /// Diffs of the tests in both the snapshot and the current run, sorted by absolute gas change
+    contracts: BTreeMap<String, Vec<SnapshotDiff>>,
+    /// Tests that aren't in the snapshot
+    added: Vec<SnapshotEntry>,
+    /// Tests of the snapshot that weren't run
+    removed: Vec<SnapshotEntry>,
+    /// Named gas snapshots that aren't in the snapshot
+    added_named: Vec<NamedSnapshotEntry>,
+    /// Named gas snapshots of the snapshot that weren't recorded
+    removed_named: Vec<NamedSnapshotEntry>,
+    /// The overall gas change of all tests in both the snapshot and the current run, named gas
+    /// snapshots are part of their test's gas and not counted again
+    overall_gas_change: i128,
+    /// The overall gas used in the snapshot by all tests in the current run
+    overall_gas_used: i128,
+}
+
+impl SnapshotDiffReport {
+    fn new(
+        tests: Vec<SuiteTestResult>,
+        snaps: Vec<SnapshotEntry>,
+        named: Vec<NamedSnapshotEntry>,
+        include_unchanged: bool,
+    ) -> Self {
+        let mut snaps = snaps
+            .into_iter()
+            .map(|s| ((s.contract_name.clone(), s.signature.clone()), s))
+            .collect::<BTreeMap<_, _>>();
+        let mut named = named
+            .into_iter()
+            .map(|s| ((s.contract_name.clone(), s.signature.clone(), s.name.clone()), s))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut report = Self::default();
+        for entry in named_snapshots(&tests) {
+            let key = (entry.contract_name.clone(), entry.signature.clone(), entry.name.clone());
+            let Some(snap) = named.remove(&key) else {
+                report.added_named.push(entry);
+                continue
+            };
+            let diff = SnapshotDiff {
+                signature: format!("{}:{}", entry.signature, entry.name),
+                metric: None,
+                old_gas: snap.gas_used,
+                new_gas: entry.gas_used,
+            };
+            if include_unchanged || diff.gas_change() != 0 {
+                report.contracts.entry(entry.contract_name).or_default().push(diff);
+            }
+        }
+        report.removed_named = named.into_values().collect();
+
+        for test in tests {
+            let (contract_name, signature) =
+                (test.contract_name().to_string(), test.signature.clone());
+            let gas_used = test.result.kind.report();
+            let Some(snap) = snaps.remove(&(contract_name.clone(), signature.clone())) else {
+                report.added.push(SnapshotEntry { contract_name, signature, gas_used });
+                continue
+            };
+
+            for diff in SnapshotDiff::new(&signature, &snap.gas_used, &gas_used) {
+                // the median is compared for the overall change
+                if diff.metric != Some(MEAN) {
+                    report.overall_gas_change += diff.gas_change();
+                    report.overall_gas_used += diff.old_gas as i128;
+                }
+                if include_unchanged || diff.gas_change() != 0 {
+                    report.contracts.entry(contract_name.clone()).or_default().push(diff);
+                }
+            }
+        }
+        report.removed = snaps.into_values().collect();
+
+        for diffs in report.contracts.values_mut() {
+            diffs.sort_by_key(|diff| Reverse(diff.gas_change().unsigned_abs()));
+        }
+        report.added.sort_by(|a, b| {
+            (&a.contract_name, &a.signature).cmp(&(&b.contract_name, &b.signature))
+        });
+        report.added_named.sort_by(|a, b| {
+            (&a.contract_name, &a.signature, &a.name).cmp(&(
+                &b.contract_name,
+                &b.signature,
+                &b.name,
+            ))
+        });
+        report
+    }
+
+    fn overall_gas_diff(&self) -> f64 {
+        if self.overall_gas_used == 0 {
+            return 0.0
+        }
+        self.overall_gas_change as f64 / self.overall_gas_used as f64
+    }
+
+    /// Formats the report as GitHub flavored markdown
+    fn markdown(&self) -> String {
+        let mut out = String::from("## Gas snapshot diff\n");
+        if self.contracts.is_empty() {
+            out.push_str("\nNo gas changes.\n");
+        }
+        for (contract, diffs) in self.contracts.iter() {
+            let _ = writeln!(out, "\n### `{contract}`\n");
+            out.push_str("| Test | Old gas | New gas | Δ | % |\n");
+            out.push_str("| :--- | ---: | ---: | ---: | ---: |\n");
+            for diff in diffs {
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} | {:+} | {:+.3}% |",
+                    diff.name(),
+                    diff.old_gas,
+                    diff.new_gas,
+                    diff.gas_change(),
+                    diff.gas_diff() * 100.0
+                );
+            }
+        }
+        for (title, entries) in [("New tests", &self.added), ("Removed tests", &self.removed)] {
+            if entries.is_empty() {
+                continue
+            }
+            let _ = writeln!(out, "\n### {title}\n");
+            out.push_str("| Test | Gas |\n| :--- | :--- |\n");
+            for entry in entries {
+                let _ = writeln!(
+                    out,
+                    "| `{}:{}` | {} |",
+                    entry.contract_name, entry.signature, entry.gas_used
+                );
+            }
+        }
+        for (title, entries) in [
+            ("New gas snapshots", &self.added_named),
+            ("Removed gas snapshots", &self.removed_named),
+        ] {
+            if entries.is_empty() {
+                continue
+            }
+            let _ = writeln!(out, "\n### {title}\n");
+            out.push_str("| Gas snapshot | Gas |\n| :--- | :--- |\n");
+            for entry in entries {
+                let _ = writeln!(
+                    out,
+                    "| `{}:{}:{}` | (gas: {}) |",
+                    entry.contract_name, entry.signature, entry.name, entry.gas_used
+                );
+            }
+        }
+        let _ = writeln!(
+            out,
+            "\n**Overall gas change:** {:+} ({:+.3}%)",
+            self.overall_gas_change,
+            self.overall_gas_diff() * 100.0
+        );
+        out
+    }
+}
+
+impl fmt::Display for SnapshotDiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (contract, diffs) in self.contracts.iter() {
+            let mut table = Table::new();
+            table.load_preset(ASCII_MARKDOWN);
+            table.set_header(
+                ["Test", "Old gas", "New gas", "Δ", "%"]
+                    .map(|header| Cell::new(header).add_attribute(Attribute::Bold)),
+            );
+            for diff in diffs {
+                let color = change_color(diff.gas_change());
+                table.add_row([
+                    Cell::new(diff.name()),
+                    Cell::new(diff.old_gas),
+                    Cell::new(diff.new_gas),
+                    Cell::new(format!("{:+}", diff.gas_change())).fg(color),
+                    Cell::new(format!("{:+.3}%", diff.gas_diff() * 100.0)).fg(color),
+                ]);
+            }
+            writeln!(f, "{}", Paint::new(contract).bold())?;
+            writeln!(f, "{table}\n")?;
+        }
+        for (title, entries) in [("New tests", &self.added), ("Removed tests", &self.removed)] {
+            if entries.is_empty() {
+                continue
+            }
+            writeln!(f, "{}", Paint::new(title).bold())?;
+            for entry in entries {
+                writeln!(f, "  {}:{} {}", entry.contract_name, entry.signature, entry.gas_used)?;
+            }
+            writeln!(f)?;
+        }
+        for (title, entries) in [
+            ("New gas snapshots", &self.added_named),
+            ("Removed gas snapshots", &self.removed_named),
+        ] {
+            if entries.is_empty() {
+                continue
+            }
+            writeln!(f, "{}", Paint::new(title).bold())?;
+            for entry in entries {
+                writeln!(f, "  {entry}")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(
+            f,
+            "Overall gas change: {} ({})",
+            fmt_change(self.overall_gas_change),
+            fmt_pct_change(self.overall_gas_diff())
+        )
     }
 }
 
 /// Compares the set of tests with an existing snapshot
 ///
-/// Returns true all tests match
-fn check(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>, tolerance: Option<u32>) -> bool {
+/// Returns true all tests and their named gas snapshots match
+fn check(
+    tests: Vec<SuiteTestResult>,
+    snaps: Vec<SnapshotEntry>,
+    named: Vec<NamedSnapshotEntry>,
+    tolerance: Option<u32>,
+) -> bool {
     let snaps = snaps
         .into_iter()
         .map(|s| ((s.contract_name, s.signature), s.gas_used))
         .collect::<HashMap<_, _>>();
+    let mut named = named
+        .into_iter()
+        .map(|s| ((s.contract_name, s.signature, s.name), s.gas_used))
+        .collect::<BTreeMap<_, _>>();
     let mut has_diff = false;
+    for entry in named_snapshots(&tests) {
+        let key = (entry.contract_name, entry.signature, entry.name);
+        match named.remove(&key) {
+            Some(target_gas) => {
+                if !within_tolerance(entry.gas_used, target_gas, tolerance) {
+                    eprintln!(
+                        "Diff in \"{}::{}:{}\": consumed \"{}\" gas, expected \"{}\" gas ",
+                        key.0, key.1, key.2, entry.gas_used, target_gas
+                    );
+                    has_diff = true;
+                }
+            }
+            None => {
+                eprintln!(
+                    "No matching gas snapshot entry found for \"{}::{}:{}\" in snapshot file",
+                    key.0, key.1, key.2
+                );
+                has_diff = true;
+            }
+        }
+    }
+    // named gas snapshots of tests that ran but didn't record them anymore
+    let ran = tests
+        .iter()
+        .map(|test| (test.contract_name(), test.signature.as_str()))
+        .collect::<HashSet<_>>();
+    for (contract_name, signature, name) in named.keys() {
+        if ran.contains(&(contract_name.as_str(), signature.as_str())) {
+            eprintln!(
+                "Gas snapshot entry \"{contract_name}::{signature}:{name}\" of the snapshot file \
+                 wasn't recorded by its test"
+            );
+            has_diff = true;
+        }
+    }
     for test in tests {
         if let Some(target_gas) =
             snaps.get(&(test.contract_name().to_string(), test.signature.clone())).cloned()
@@ -348,53 +771,6 @@ fn check(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>, tolerance: Opti
     !has_diff
 }
 
-/// Compare the set of tests with an existing snapshot
-fn diff(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>) -> Result<()> {
-    let snaps = snaps
-        .into_iter()
-        .map(|s| ((s.contract_name, s.signature), s.gas_used))
-        .collect::<HashMap<_, _>>();
-    let mut diffs = Vec::with_capacity(tests.len());
-    for test in tests.into_iter() {
-        if let Some(target_gas_used) =
-            snaps.get(&(test.contract_name().to_string(), test.signature.clone())).cloned()
-        {
-            diffs.push(SnapshotDiff {
-                source_gas_used: test.result.kind.report(),
-                signature: test.signature,
-                target_gas_used,
-            });
-        }
-    }
-    let mut overall_gas_change = 0i128;
-    let mut overall_gas_used = 0i128;
-
-    diffs.sort_by(|a, b| {
-        a.gas_diff().abs().partial_cmp(&b.gas_diff().abs()).unwrap_or(Ordering::Equal)
-    });
-
-    for diff in diffs {
-        let gas_change = diff.gas_change();
-        overall_gas_change += gas_change;
-        overall_gas_used += diff.target_gas_used.gas() as i128;
-        let gas_diff = diff.gas_diff();
-        println!(
-            "{} (gas: {} ({})) ",
-            diff.signature,
-            fmt_change(gas_change),
-            fmt_pct_change(gas_diff)
-        );
-    }
-
-    let overall_gas_diff = overall_gas_change as f64 / overall_gas_used as f64;
-    println!(
-        "Overall gas change: {} ({})",
-        fmt_change(overall_gas_change),
-        fmt_pct_change(overall_gas_diff)
-    );
-    Ok(())
-}
-
 fn fmt_pct_change(change: f64) -> String {
     let change_pct = change * 100.0;
     match change.partial_cmp(&0.0).unwrap_or(Ordering::Equal) {
@@ -406,6 +782,14 @@ fn fmt_pct_change(change: f64) -> String {
     }
 }
 
+pub(crate) fn change_color(change: i128) -> Color {
+    match change.cmp(&0) {
+        Ordering::Less => Color::Green,
+        Ordering::Equal => Color::Reset,
+        Ordering::Greater => Color::Red,
+    }
+}
+
 fn fmt_change(change: i128) -> String {
     match change.cmp(&0) {
         Ordering::Less => Paint::green(format!("{change}")).to_string(),
@@ -416,6 +800,35 @@ fn fmt_change(change: i128) -> String {
     }
 }
 
+/// Retains only the snapshot entries of the tests that were selected for the current run.
+///
+/// Entries of tests that ran but were left out by the gas range, or that didn't run because the
+/// test filters excluded them, are dropped so they aren't reported as removed. Snapshot entries
+/// don't record the path of their test, so if the filter selects tests by path, an entry that
+/// didn't run is only kept if other tests of its contract ran.
+fn retain_selected(
+    snaps: &mut Vec<SnapshotEntry>,
+    filter: &impl TestFilter,
+    filters_paths: bool,
+    ran: &HashSet<(String, String)>,
+    tests: &[SuiteTestResult],
+) {
+    let compared = tests
+        .iter()
+        .map(|test| (test.contract_name(), test.signature.as_str()))
+        .collect::<HashSet<_>>();
+    let ran_contracts = ran.iter().map(|(contract, _)| contract.as_str()).collect::<HashSet<_>>();
+    snaps.retain(|snap| {
+        let (contract, signature) = (snap.contract_name.as_str(), snap.signature.as_str());
+        if ran.contains(&(contract.to_string(), signature.to_string())) {
+            return compared.contains(&(contract, signature))
+        }
+        filter.matches_contract(contract) &&
+            filter.matches_test(signature) &&
+            (!filters_paths || ran_contracts.contains(contract))
+    });
+}
+
 /// Returns true of the difference between the gas values exceeds the tolerance
 ///
 /// If `tolerance` is `None`, then this returns `true` if both gas values are equal
@@ -446,6 +859,131 @@ mod tests {
         assert!(within_tolerance(100, 100, None));
     }
 
+    #[test]
+    fn can_diff_fuzz_snapshot_entries() {
+        let old = TestKindReport::Fuzz { runs: 256, mean_gas: 100, median_gas: 90 };
+        let new = TestKindReport::Fuzz { runs: 256, mean_gas: 110, median_gas: 90 };
+        let diffs = SnapshotDiff::new("testFuzz(uint256)", &old, &new);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].name(), "testFuzz(uint256) (μ)");
+        assert_eq!(diffs[0].gas_change(), 10);
+        assert_eq!(diffs[1].name(), "testFuzz(uint256) (~)");
+        assert_eq!(diffs[1].gas_change(), 0);
+
+        let invariant =
+            TestKindReport::Invariant { runs: 256, calls: 100, reverts: 0, time_elapsed: 0 };
+        assert!(SnapshotDiff::new("invariant()", &invariant, &invariant).is_empty());
+    }
+
+    #[test]
+    fn retains_only_selected_snapshot_entries() {
+        let entry = |contract_name: &str, signature: &str| SnapshotEntry {
+            contract_name: contract_name.to_string(),
+            signature: signature.to_string(),
+            gas_used: TestKindReport::Standard { gas: 100 },
+        };
+        let snaps = vec![
+            entry("CounterTest", "testIncrement()"),
+            entry("CounterTest", "testRemoved()"),
+            entry("OtherTest", "testOther()"),
+        ];
+        let ran = HashSet::new();
+
+        let filter = test::FilterArgs::parse_from(["foundry", "--mc", "CounterTest"]);
+        let mut retained = snaps.clone();
+        retain_selected(&mut retained, &filter, false, &ran, &[]);
+        assert_eq!(retained, snaps[..2]);
+
+        let filter = test::FilterArgs::parse_from(["foundry", "--mt", "testIncrement"]);
+        let mut retained = snaps.clone();
+        retain_selected(&mut retained, &filter, false, &ran, &[]);
+        assert_eq!(retained, snaps[..1]);
+
+        // the contracts of the entries didn't run and may have been excluded by their path
+        let filter = test::FilterArgs::parse_from(["foundry"]);
+        let mut retained = snaps.clone();
+        retain_selected(&mut retained, &filter, true, &ran, &[]);
+        assert!(retained.is_empty());
+
+        // tests that ran but were excluded by the gas range aren't removed
+        let ran = HashSet::from([("CounterTest".to_string(), "testIncrement()".to_string())]);
+        let mut retained = snaps.clone();
+        retain_selected(&mut retained, &filter, true, &ran, &[]);
+        assert_eq!(retained, snaps[1..2]);
+    }
+
+    #[test]
+    fn can_format_markdown_diff() {
+        let report = SnapshotDiffReport {
+            contracts: BTreeMap::from([(
+                "CounterTest".to_string(),
+                vec![SnapshotDiff {
+                    signature: "testIncrement()".to_string(),
+                    metric: None,
+                    old_gas: 1000,
+                    new_gas: 1010,
+                }],
+            )]),
+            added: vec![SnapshotEntry {
+                contract_name: "CounterTest".to_string(),
+                signature: "testNew()".to_string(),
+                gas_used: TestKindReport::Standard { gas: 500 },
+            }],
+            removed: vec![],
+            added_named: vec![],
+            removed_named: vec![NamedSnapshotEntry {
+                contract_name: "CounterTest".to_string(),
+                signature: "testIncrement()".to_string(),
+                name: "increment".to_string(),
+                gas_used: 200,
+            }],
+            overall_gas_change: 10,
+            overall_gas_used: 1000,
+        };
+        assert_eq!(
+            report.markdown(),
+            "## Gas snapshot diff
+
+### `CounterTest`
+
+| Test | Old gas | New gas | Δ | % |
+| :--- | ---: | ---: | ---: | ---: |
+| `testIncrement()` | 1000 | 1010 | +10 | +1.000% |
+
+### New tests
+
+| Test | Gas |
+| :--- | :--- |
+| `CounterTest:testNew()` | (gas: 500) |
+
+### Removed gas snapshots
+
+| Gas snapshot | Gas |
+| :--- | :--- |
+| `CounterTest:testIncrement():increment` | (gas: 200) |
+
+**Overall gas change:** +10 (+1.000%)
+"
+        );
+    }
+
+    #[test]
+    fn can_parse_named_snapshot_entry() {
+        let s = "Test:testDeposit():transfer tokens (gas: 21000)";
+        let entry = NamedSnapshotEntry::from_str(s).unwrap();
+        assert_eq!(
+            entry,
+            NamedSnapshotEntry {
+                contract_name: "Test".to_string(),
+                signature: "testDeposit()".to_string(),
+                name: "transfer tokens".to_string(),
+                gas_used: 21000,
+            }
+        );
+        assert_eq!(entry.to_string(), s);
+        assert!(NamedSnapshotEntry::from_str("Test:testDeposit() (gas: 21000)").is_err());
+    }
+
     #[test]
     fn can_parse_basic_snapshot_entry() {
         let s = "Test:deposit() (gas: 7222)";
@@ -483,7 +1021,12 @@ mod tests {
             SnapshotEntry {
                 contract_name: "Test".to_string(),
                 signature: "deposit()".to_string(),
-                gas_used: TestKindReport::Invariant { runs: 256, calls: 100, reverts: 200 }
+                gas_used: TestKindReport::Invariant {
+                    runs: 256,
+                    calls: 100,
+                    reverts: 200,
+                    time_elapsed: 0
+                }
             }
         );
     }
@@ -497,8 +1040,36 @@ mod tests {
             SnapshotEntry {
                 contract_name: "ERC20Invariants".to_string(),
                 signature: "invariantBalanceSum()".to_string(),
-                gas_used: TestKindReport::Invariant { runs: 256, calls: 3840, reverts: 2388 }
+                gas_used: TestKindReport::Invariant {
+                    runs: 256,
+                    calls: 3840,
+                    reverts: 2388,
+                    time_elapsed: 0
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_invariant_snapshot_entry_with_time() {
+        let s = "Test:invariantVesting() (runs: 256, calls: 3840, reverts: 0, time: 1209600s)";
+        let entry = SnapshotEntry::from_str(s).unwrap();
+        assert_eq!(
+            entry,
+            SnapshotEntry {
+                contract_name: "Test".to_string(),
+                signature: "invariantVesting()".to_string(),
+                gas_used: TestKindReport::Invariant {
+                    runs: 256,
+                    calls: 3840,
+                    reverts: 0,
+                    time_elapsed: 1209600
+                }
             }
         );
+        assert_eq!(
+            entry.gas_used.to_string(),
+            "(runs: 256, calls: 3840, reverts: 0, time: 1209600s)"
+        );
     }
 }
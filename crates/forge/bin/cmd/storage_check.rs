@@ -0,0 +1,787 @@
+use alloy_primitives::{Address, U256};
+use clap::{Parser, ValueHint};
+use eyre::{Context, Result};
+use foundry_block_explorers::Client;
+use foundry_cli::opts::{CoreBuildArgs, EtherscanOpts};
+use foundry_common::{
+    abi::find_source,
+    compile::{etherscan_project, ProjectCompiler},
+};
+use foundry_compilers::{
+    artifacts::StorageLayout, info::ContractInfo, utils::canonicalize, Project,
+    ProjectCompileOutput,
+};
+use foundry_config::{
+    figment::{self, value::Dict, Metadata, Profile},
+    impl_figment_convert_cast, Config,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use solang_parser::pt::{CodeLocation, ContractPart, SourceUnitPart};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use yansi::Paint;
+
+/// CLI arguments for `forge storage-check`.
+#[derive(Clone, Debug, Parser)]
+pub struct StorageCheckArgs {
+    /// The identifier of the contract to check in the form `(<path>:)?<contractname>`.
+    pub contract: ContractInfo,
+
+    /// The storage layout to compare against.
+    ///
+    /// Either the identifier of another contract, the path to a layout saved with `--save`, or
+    /// the address of a contract verified on Etherscan.
+    pub baseline: Option<String>,
+
+    /// Save the storage layout of the contract to the given path, for future comparisons.
+    #[arg(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub save: Option<PathBuf>,
+
+    #[command(flatten)]
+    etherscan: EtherscanOpts,
+
+    #[command(flatten)]
+    build: CoreBuildArgs,
+}
+
+impl_figment_convert_cast!(StorageCheckArgs);
+
+impl figment::Provider for StorageCheckArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("StorageCheckArgs")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let mut map = self.build.data()?;
+        let dict = map.get_mut(&Config::selected_profile()).unwrap();
+        dict.extend(self.etherscan.dict());
+        Ok(map)
+    }
+}
+
+impl StorageCheckArgs {
+    pub async fn run(self) -> Result<()> {
+        if self.baseline.is_none() && self.save.is_none() {
+            eyre::bail!("Nothing to do: provide a baseline to compare against or `--save <PATH>`");
+        }
+
+        let config = Config::from(&self);
+        let mut project = self.build.project()?;
+        add_storage_layout_output(&mut project);
+
+        let mut contract = self.contract.clone();
+        let mut compiler = ProjectCompiler::new().quiet(true);
+        if let Some(contract_path) = &mut contract.path {
+            let target_path = canonicalize(&*contract_path)?;
+            *contract_path = target_path.to_string_lossy().to_string();
+            compiler = compiler.files([target_path]);
+        }
+        let output = compiler.compile(&project)?;
+        let current = LayoutSnapshot::from_output(&output, &project, &contract)?;
+
+        if let Some(path) = &self.save {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_string_pretty(&current)?)
+                .wrap_err_with(|| format!("failed to write layout to {}", path.display()))?;
+            println!("Saved storage layout of `{}` to {}", contract.name, path.display());
+        }
+
+        let Some(baseline) = &self.baseline else { return Ok(()) };
+        let previous = match Baseline::from_str(baseline)? {
+            Baseline::Contract(mut other) => {
+                if let Some(path) = &mut other.path {
+                    *path = canonicalize(&*path)?.to_string_lossy().to_string();
+                }
+                LayoutSnapshot::from_output(&output, &project, &other)?
+            }
+            Baseline::File(path) => {
+                let content = fs::read_to_string(&path)
+                    .wrap_err_with(|| format!("failed to read layout from {}", path.display()))?;
+                serde_json::from_str(&content)
+                    .wrap_err_with(|| format!("invalid layout in {}", path.display()))?
+            }
+            Baseline::Address(address) => {
+                let chain = self.etherscan.chain.or(config.chain).unwrap_or_default();
+                let api_key = config.get_etherscan_api_key(Some(chain)).ok_or_else(|| {
+                    eyre::eyre!(
+                        "You must provide an Etherscan API key to compare against an address"
+                    )
+                })?;
+                LayoutSnapshot::from_etherscan(Client::new(chain, api_key)?, address).await?
+            }
+        };
+
+        let report = LayoutDiff::new(&previous, &current);
+        for finding in report.findings.iter() {
+            println!("{finding}");
+        }
+
+        let errors = report.errors();
+        if errors > 0 {
+            eyre::bail!(
+                "Storage layout of `{}` is incompatible with `{baseline}`: {errors} error(s)",
+                contract.name
+            );
+        }
+        println!(
+            "{}",
+            Paint::green(format!(
+                "Storage layout of `{}` is compatible with `{baseline}`",
+                contract.name
+            ))
+        );
+        Ok(())
+    }
+}
+
+/// The storage layout to compare against.
+#[derive(Clone, Debug)]
+enum Baseline {
+    Contract(ContractInfo),
+    File(PathBuf),
+    Address(Address),
+}
+
+impl FromStr for Baseline {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(address) = Address::from_str(s) {
+            return Ok(Self::Address(address))
+        }
+        let path = Path::new(s);
+        if path.extension().map_or(false, |ext| ext == "json") || path.is_file() {
+            return Ok(Self::File(path.to_path_buf()))
+        }
+        Ok(Self::Contract(ContractInfo::from_str(s)?))
+    }
+}
+
+/// A storage layout alongside the ERC-7201 namespaces of the contract.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LayoutSnapshot {
+    #[serde(flatten)]
+    pub layout: StorageLayout,
+    /// The members of the namespaced storage structs, keyed by their ERC-7201 namespace id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub namespaces: BTreeMap<String, Vec<NamespaceMember>>,
+}
+
+impl LayoutSnapshot {
+    /// Returns the layout of the contract in the compile output.
+    fn from_output(
+        output: &ProjectCompileOutput,
+        project: &Project,
+        contract: &ContractInfo,
+    ) -> Result<Self> {
+        let artifact = output.find_contract(contract).ok_or_else(|| {
+            eyre::eyre!("Could not find artifact `{contract}` in the compiled artifacts")
+        })?;
+        let layout = artifact
+            .storage_layout
+            .clone()
+            .ok_or_else(|| eyre::eyre!("Could not get storage layout of `{contract}`"))?;
+
+        let sources = output
+            .artifact_ids()
+            .map(|(id, _)| project.root().join(id.source))
+            .collect::<HashSet<_>>();
+        let namespaces = collect_namespaces(sources, &contract.name);
+
+        Ok(Self { layout, namespaces })
+    }
+
+    /// Compiles the sources of the verified contract and returns its layout.
+    async fn from_etherscan(client: Client, address: Address) -> Result<Self> {
+        let source = find_source(client, address).await?;
+        let metadata = source.items.first().ok_or_else(|| eyre::eyre!("No source found"))?;
+        if metadata.is_vyper() {
+            eyre::bail!("Contract at provided address is not a valid Solidity contract")
+        }
+
+        let root = tempfile::tempdir()?;
+        let mut project = etherscan_project(metadata, root.path())?;
+        add_storage_layout_output(&mut project);
+        let output = ProjectCompiler::new().quiet(true).compile(&project)?;
+
+        let contract = ContractInfo { path: None, name: metadata.contract_name.clone() };
+        let snapshot = Self::from_output(&output, &project, &contract)?;
+        root.close()?;
+        Ok(snapshot)
+    }
+}
+
+/// A member of a namespaced storage struct.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceMember {
+    pub label: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// A state variable in a storage layout.
+#[derive(Clone, Debug)]
+struct Variable {
+    label: String,
+    ty: String,
+    slot: U256,
+    offset: u64,
+    /// The first byte occupied by the variable, counting from slot 0.
+    start: U256,
+    /// The size of the variable in bytes.
+    size: u64,
+}
+
+impl Variable {
+    fn end(&self) -> U256 {
+        self.start + U256::from(self.size)
+    }
+
+    /// Returns `true` if the variable overlaps with the other variable.
+    fn overlaps(&self, other: &Variable) -> bool {
+        self.start < other.end() && other.start < self.end()
+    }
+
+    /// Returns `true` if the variable is a storage gap, reserved for future variables.
+    fn is_gap(&self) -> bool {
+        self.label.starts_with("__gap")
+    }
+
+    fn location(&self) -> String {
+        format!("slot {} (offset {})", self.slot, self.offset)
+    }
+}
+
+/// Returns the state variables of the layout, ordered by their position in storage.
+fn variables(layout: &StorageLayout) -> Vec<Variable> {
+    let mut variables = layout
+        .storage
+        .iter()
+        .map(|storage| {
+            let ty = layout.types.get(&storage.storage_type);
+            let slot = U256::from_str(&storage.slot).unwrap_or_default();
+            let offset = storage.offset as u64;
+            Variable {
+                label: storage.label.clone(),
+                ty: ty.map_or_else(|| storage.storage_type.clone(), |ty| ty.label.clone()),
+                slot,
+                offset,
+                start: slot * U256::from(32) + U256::from(offset),
+                size: ty.and_then(|ty| ty.number_of_bytes.parse().ok()).unwrap_or(32),
+            }
+        })
+        .collect::<Vec<_>>();
+    variables.sort_by_key(|var| var.start);
+    variables
+}
+
+/// How severe a difference between two storage layouts is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Severity {
+    /// The new layout corrupts the existing storage.
+    Error,
+    /// The new layout is compatible, but the change is likely unintended.
+    Warning,
+    /// A compatible change.
+    Note,
+}
+
+/// A difference between two storage layouts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Finding {
+    severity: Severity,
+    message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "{}: {}", Paint::red("error"), self.message),
+            Severity::Warning => write!(f, "{}: {}", Paint::yellow("warning"), self.message),
+            Severity::Note => write!(f, "{}: {}", Paint::cyan("note"), self.message),
+        }
+    }
+}
+
+/// The differences between a previous and a new storage layout.
+///
+/// The new layout is compatible if it only appends variables, consumes storage gaps, or renames
+/// variables.
+#[derive(Clone, Debug, Default)]
+struct LayoutDiff {
+    findings: Vec<Finding>,
+}
+
+impl LayoutDiff {
+    fn new(previous: &LayoutSnapshot, current: &LayoutSnapshot) -> Self {
+        let mut diff = Self::default();
+        diff.diff_variables(&variables(&previous.layout), &variables(&current.layout));
+        diff.diff_namespaces(&previous.namespaces, &current.namespaces);
+        diff
+    }
+
+    /// Returns the number of incompatibilities.
+    fn errors(&self) -> usize {
+        self.findings.iter().filter(|finding| finding.severity == Severity::Error).count()
+    }
+
+    fn push(&mut self, severity: Severity, message: String) {
+        self.findings.push(Finding { severity, message });
+    }
+
+    fn diff_variables(&mut self, previous: &[Variable], current: &[Variable]) {
+        let previous_labels = previous.iter().map(|var| &var.label).collect::<HashSet<_>>();
+        let previous_end = previous.iter().map(Variable::end).max().unwrap_or_default();
+        let gaps = previous.iter().filter(|var| var.is_gap()).collect::<Vec<_>>();
+        let in_gap =
+            |var: &Variable| gaps.iter().any(|gap| gap.start <= var.start && var.start < gap.end());
+
+        for old in previous.iter() {
+            if old.is_gap() {
+                self.diff_gap(old, current);
+                continue
+            }
+
+            match current.iter().find(|new| new.start == old.start && !new.is_gap()) {
+                Some(new) => {
+                    if new.size != old.size {
+                        self.push(
+                            Severity::Error,
+                            format!(
+                                "`{}` at {} changed size from {} to {} bytes (`{}` -> `{}`)",
+                                old.label,
+                                old.location(),
+                                old.size,
+                                new.size,
+                                old.ty,
+                                new.ty
+                            ),
+                        );
+                    } else if new.ty != old.ty {
+                        self.push(
+                            Severity::Error,
+                            format!(
+                                "`{}` at {} changed type from `{}` to `{}`",
+                                old.label,
+                                old.location(),
+                                old.ty,
+                                new.ty
+                            ),
+                        );
+                    }
+                    if new.label == old.label {
+                        continue
+                    }
+                    if let Some(moved) = current.iter().find(|new| new.label == old.label) {
+                        self.push(
+                            Severity::Error,
+                            format!(
+                                "`{}` moved from {} to {}",
+                                old.label,
+                                old.location(),
+                                moved.location()
+                            ),
+                        );
+                    } else if !previous_labels.contains(&new.label) {
+                        self.push(
+                            Severity::Warning,
+                            format!(
+                                "`{}` at {} was renamed to `{}`",
+                                old.label,
+                                old.location(),
+                                new.label
+                            ),
+                        );
+                    }
+                }
+                None => match current.iter().find(|new| new.label == old.label) {
+                    Some(new) => self.push(
+                        Severity::Error,
+                        format!(
+                            "`{}` moved from {} to {}",
+                            old.label,
+                            old.location(),
+                            new.location()
+                        ),
+                    ),
+                    None => self.push(
+                        Severity::Error,
+                        format!("`{}` at {} was removed", old.label, old.location()),
+                    ),
+                },
+            }
+        }
+
+        for new in current.iter() {
+            let replaces = previous.iter().any(|old| old.start == new.start && !old.is_gap());
+            if replaces || previous_labels.contains(&new.label) || in_gap(new) {
+                continue
+            }
+            if let Some(old) = previous.iter().find(|old| old.overlaps(new)) {
+                self.push(
+                    Severity::Error,
+                    format!(
+                        "`{}` was inserted at {}, overlapping `{}`",
+                        new.label,
+                        new.location(),
+                        old.label
+                    ),
+                );
+            } else if new.start >= previous_end {
+                self.push(
+                    Severity::Note,
+                    format!("`{}` was appended at {}", new.label, new.location()),
+                );
+            } else {
+                self.push(
+                    Severity::Note,
+                    format!("`{}` was packed into unused space at {}", new.label, new.location()),
+                );
+            }
+        }
+    }
+
+    /// Checks that the variables placed in the storage gap are compensated by shrinking the gap.
+    fn diff_gap(&mut self, gap: &Variable, current: &[Variable]) {
+        let within = |var: &&Variable| gap.start <= var.start && var.start < gap.end();
+        let consumers =
+            current.iter().filter(within).filter(|var| !var.is_gap()).collect::<Vec<_>>();
+        let new_gap = current.iter().filter(within).find(|var| var.label == gap.label);
+
+        let end = new_gap.map_or_else(
+            || consumers.iter().map(|var| var.end()).max().unwrap_or(gap.start),
+            |new_gap| new_gap.end(),
+        );
+        if end != gap.end() {
+            let message = match new_gap {
+                Some(new_gap) => format!(
+                    "`{}` at {} now ends at byte {end} instead of {} ({} -> {} bytes)",
+                    gap.label,
+                    gap.location(),
+                    gap.end(),
+                    gap.size,
+                    new_gap.size
+                ),
+                None if consumers.is_empty() => {
+                    format!("`{}` at {} was removed", gap.label, gap.location())
+                }
+                None => format!(
+                    "`{}` at {} was replaced by variables that don't fill it",
+                    gap.label,
+                    gap.location()
+                ),
+            };
+            self.push(Severity::Error, message);
+        } else if !consumers.is_empty() {
+            let names = consumers.iter().map(|var| format!("`{}`", var.label)).collect::<Vec<_>>();
+            self.push(
+                Severity::Note,
+                format!(
+                    "`{}` at {} was consumed by {}",
+                    gap.label,
+                    gap.location(),
+                    names.join(", ")
+                ),
+            );
+        }
+    }
+
+    fn diff_namespaces(
+        &mut self,
+        previous: &BTreeMap<String, Vec<NamespaceMember>>,
+        current: &BTreeMap<String, Vec<NamespaceMember>>,
+    ) {
+        for (id, old_members) in previous.iter() {
+            let Some(new_members) = current.get(id) else {
+                self.push(Severity::Error, format!("namespace `erc7201:{id}` was removed"));
+                continue
+            };
+
+            let old_labels = old_members.iter().map(|m| &m.label).collect::<HashSet<_>>();
+            for (i, old) in old_members.iter().enumerate() {
+                let Some(new) = new_members.get(i) else {
+                    self.push(
+                        Severity::Error,
+                        format!("`{}` was removed from namespace `erc7201:{id}`", old.label),
+                    );
+                    continue
+                };
+                if new.ty != old.ty {
+                    self.push(
+                        Severity::Error,
+                        format!(
+                            "`{}` in namespace `erc7201:{id}` changed type from `{}` to `{}`",
+                            old.label, old.ty, new.ty
+                        ),
+                    );
+                }
+                if new.label != old.label {
+                    let (severity, message) = if old_labels.contains(&new.label) {
+                        (Severity::Error, "was reordered with")
+                    } else {
+                        (Severity::Warning, "was renamed to")
+                    };
+                    self.push(
+                        severity,
+                        format!(
+                            "`{}` in namespace `erc7201:{id}` {message} `{}`",
+                            old.label, new.label
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Collects the ERC-7201 namespaces of the contract and its base contracts from the sources.
+///
+/// Namespaces are the structs annotated with `@custom:storage-location erc7201:<id>`.
+fn collect_namespaces(
+    sources: impl IntoIterator<Item = PathBuf>,
+    contract: &str,
+) -> BTreeMap<String, Vec<NamespaceMember>> {
+    let mut contracts = HashMap::new();
+    for path in sources {
+        let Ok(src) = fs::read_to_string(&path) else { continue };
+        for (name, definition) in parse_namespaces(&src) {
+            contracts.entry(name).or_insert(definition);
+        }
+    }
+
+    let mut namespaces = BTreeMap::new();
+    let mut visited = HashSet::new();
+    let mut queue = vec![contract.to_string()];
+    while let Some(name) = queue.pop() {
+        if !visited.insert(name.clone()) {
+            continue
+        }
+        if let Some((bases, contract_namespaces)) = contracts.get(&name) {
+            namespaces.extend(contract_namespaces.clone());
+            queue.extend(bases.iter().cloned());
+        }
+    }
+    namespaces
+}
+
+type ContractNamespaces = (Vec<String>, BTreeMap<String, Vec<NamespaceMember>>);
+
+/// Parses the bases and ERC-7201 namespaces of every contract defined in the source.
+fn parse_namespaces(src: &str) -> Vec<(String, ContractNamespaces)> {
+    static ERC7201: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let erc7201 = ERC7201.get_or_init(|| {
+        Regex::new(r"@custom:storage-location\s+erc7201:([A-Za-z0-9_.\-]+)").unwrap()
+    });
+
+    let Ok((unit, _)) = solang_parser::parse(src, 0) else { return vec![] };
+    let mut contracts = vec![];
+    for part in unit.0.iter() {
+        let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+        let Some(name) = &contract.name else { continue };
+        let bases = contract
+            .base
+            .iter()
+            .filter_map(|base| base.name.identifiers.last().map(|ident| ident.name.clone()))
+            .collect();
+
+        let mut namespaces = BTreeMap::new();
+        let mut prev_end = contract.loc.start();
+        for part in contract.parts.iter() {
+            let loc = part.loc();
+            if let ContractPart::StructDefinition(structure) = part {
+                let id = src
+                    .get(prev_end..loc.start())
+                    .and_then(|preceding| erc7201.captures_iter(preceding).last())
+                    .map(|captures| captures[1].to_string());
+                if let Some(id) = id {
+                    let members = structure
+                        .fields
+                        .iter()
+                        .map(|field| NamespaceMember {
+                            label: field.name.as_ref().map(|n| n.name.clone()).unwrap_or_default(),
+                            ty: field.ty.to_string(),
+                        })
+                        .collect();
+                    namespaces.insert(id, members);
+                }
+            }
+            prev_end = loc.end();
+        }
+        contracts.push((name.name.clone(), (bases, namespaces)));
+    }
+    contracts
+}
+
+fn add_storage_layout_output(project: &mut Project) {
+    project.artifacts.additional_values.storage_layout = true;
+    let output_selection = project.artifacts.output_selection();
+    project.solc_config.settings.push_all(output_selection);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(label: &str, ty: &str, slot: u64, offset: u64, size: u64) -> Variable {
+        Variable {
+            label: label.to_string(),
+            ty: ty.to_string(),
+            slot: U256::from(slot),
+            offset,
+            start: U256::from(slot * 32 + offset),
+            size,
+        }
+    }
+
+    fn diff(previous: &[Variable], current: &[Variable]) -> Vec<(Severity, String)> {
+        let mut diff = LayoutDiff::default();
+        diff.diff_variables(previous, current);
+        diff.findings.into_iter().map(|f| (f.severity, f.message)).collect()
+    }
+
+    #[test]
+    fn accepts_appended_and_renamed_variables() {
+        let previous = [var("owner", "address", 0, 0, 20), var("total", "uint256", 1, 0, 32)];
+        let current = [
+            var("admin", "address", 0, 0, 20),
+            var("paused", "bool", 0, 20, 1),
+            var("total", "uint256", 1, 0, 32),
+            var("fee", "uint256", 2, 0, 32),
+        ];
+        let findings = diff(&previous, &current);
+        assert_eq!(
+            findings,
+            vec![
+                (Severity::Warning, "`owner` at slot 0 (offset 0) was renamed to `admin`".into()),
+                (
+                    Severity::Note,
+                    "`paused` was packed into unused space at slot 0 (offset 20)".into()
+                ),
+                (Severity::Note, "`fee` was appended at slot 2 (offset 0)".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_reordered_and_resized_variables() {
+        let previous = [
+            var("a", "uint256", 0, 0, 32),
+            var("b", "uint256", 1, 0, 32),
+            var("c", "uint128", 2, 0, 16),
+        ];
+        let current = [
+            var("b", "uint256", 0, 0, 32),
+            var("a", "uint256", 1, 0, 32),
+            var("c", "uint256", 2, 0, 32),
+        ];
+        let findings = diff(&previous, &current);
+        assert_eq!(
+            findings,
+            vec![
+                (Severity::Error, "`a` moved from slot 0 (offset 0) to slot 1 (offset 0)".into()),
+                (Severity::Error, "`b` moved from slot 1 (offset 0) to slot 0 (offset 0)".into()),
+                (
+                    Severity::Error,
+                    "`c` at slot 2 (offset 0) changed size from 16 to 32 bytes (`uint128` -> \
+                     `uint256`)"
+                        .into()
+                ),
+            ]
+        );
+
+        let current = [var("x", "uint256", 0, 0, 32), var("a", "uint256", 1, 0, 32)];
+        let findings = diff(&previous[..1], &current);
+        assert_eq!(
+            findings,
+            vec![(Severity::Error, "`a` moved from slot 0 (offset 0) to slot 1 (offset 0)".into())]
+        );
+    }
+
+    #[test]
+    fn checks_gap_consumption() {
+        let previous = [var("a", "uint256", 0, 0, 32), var("__gap", "uint256[49]", 1, 0, 49 * 32)];
+
+        let current = [
+            var("a", "uint256", 0, 0, 32),
+            var("b", "uint256", 1, 0, 32),
+            var("__gap", "uint256[48]", 2, 0, 48 * 32),
+        ];
+        let findings = diff(&previous, &current);
+        assert_eq!(
+            findings,
+            vec![(Severity::Note, "`__gap` at slot 1 (offset 0) was consumed by `b`".into())]
+        );
+
+        let current = [
+            var("a", "uint256", 0, 0, 32),
+            var("b", "uint256", 1, 0, 32),
+            var("__gap", "uint256[49]", 2, 0, 49 * 32),
+        ];
+        let findings = diff(&previous, &current);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].0, Severity::Error);
+    }
+
+    #[test]
+    fn can_parse_namespaces() {
+        let src = r#"
+            contract Base {
+                /// @custom:storage-location erc7201:example.base
+                struct BaseStorage {
+                    uint256 x;
+                }
+            }
+
+            contract Token is Base {
+                uint256 internal constant FOO = 1;
+
+                /** @custom:storage-location erc7201:example.token */
+                struct TokenStorage {
+                    mapping(address => uint256) balances;
+                    string name;
+                }
+
+                struct NotNamespaced {
+                    uint256 y;
+                }
+            }
+        "#;
+        let contracts = parse_namespaces(src);
+        assert_eq!(contracts.len(), 2);
+
+        let (name, (bases, namespaces)) = &contracts[1];
+        assert_eq!(name, "Token");
+        assert_eq!(bases, &vec!["Base".to_string()]);
+        assert_eq!(namespaces.len(), 1);
+        assert_eq!(
+            namespaces["example.token"],
+            vec![
+                NamespaceMember {
+                    label: "balances".into(),
+                    ty: "mapping(address => uint256)".into()
+                },
+                NamespaceMember { label: "name".into(), ty: "string".into() },
+            ]
+        );
+
+        let member = |label: &str, ty: &str| NamespaceMember { label: label.into(), ty: ty.into() };
+        let previous = BTreeMap::from([(
+            "example.token".to_string(),
+            vec![member("a", "uint256"), member("b", "uint256")],
+        )]);
+        let current = BTreeMap::from([(
+            "example.token".to_string(),
+            vec![member("b", "uint256"), member("a", "uint256"), member("c", "address")],
+        )]);
+        let mut diff = LayoutDiff::default();
+        diff.diff_namespaces(&previous, &current);
+        assert_eq!(diff.errors(), 2);
+    }
+}
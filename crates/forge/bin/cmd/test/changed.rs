@@ -0,0 +1,148 @@
+//! Selection of the tests affected by the changes since a git revision, see `forge test --changed`.
+
+use eyre::Result;
+use foundry_cli::utils::{FoundryPathExt, Git};
+use foundry_compilers::Graph;
+use foundry_config::{fs_permissions::FsAccessKind, Config};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
+
+/// Changes to these files in the project root can affect every test.
+const CONFIG_FILES: [&str; 4] = [Config::FILE_NAME, "remappings.txt", ".gitmodules", ".env"];
+
+/// The tests to run with `--changed`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangedTests {
+    /// A change can't be attributed to specific tests, so all of them run.
+    All { revision: String, reason: String },
+    /// The test files that transitively import a changed file, with the changed file that
+    /// selected each of them.
+    Selected { revision: String, files: BTreeMap<PathBuf, PathBuf> },
+}
+
+impl ChangedTests {
+    /// Selects the tests affected by the changes since `revision`.
+    pub fn new(config: &Config, revision: &str) -> Result<Self> {
+        let changed = Git::new(&config.__root.0).changed_files(revision)?;
+        Self::from_files(config, revision.to_string(), changed)
+    }
+
+    /// Selects the tests affected by changes to the given files, `revision` describes since when
+    /// they changed.
+    ///
+    /// Changes which can't be traced through the imports, to the config, the dependencies in
+    /// `lib/` or the files the tests can read, select all tests.
+    pub fn from_files(
+        config: &Config,
+        revision: String,
+        changed: impl IntoIterator<Item = PathBuf>,
+    ) -> Result<Self> {
+        let paths = &config.project_paths();
+        let graph = Graph::resolve(paths)?;
+
+        let mut changed_sources = HashSet::new();
+        for file in changed {
+            let in_graph = graph.files().contains_key(&file);
+            if !file.starts_with(&paths.root) {
+                // files outside of the project only matter if they're imported
+                if in_graph {
+                    changed_sources.insert(file);
+                }
+                continue
+            }
+
+            let relative = file.strip_prefix(&paths.root).unwrap_or(&file);
+            if CONFIG_FILES.iter().any(|name| relative == Path::new(name)) {
+                return Ok(Self::All { revision, reason: format!("{} changed", relative.display()) })
+            }
+            if paths.libraries.iter().any(|lib| file.starts_with(lib)) {
+                let reason = format!("dependency {} changed", relative.display());
+                return Ok(Self::All { revision, reason })
+            }
+            if !file.is_sol() {
+                // the build output doesn't affect the tests, but any file they can read might
+                if file.starts_with(&paths.artifacts) || file.starts_with(&paths.cache) {
+                    continue
+                }
+                if config.fs_permissions.is_path_allowed(&file, FsAccessKind::Read) {
+                    let reason = format!("{}, which tests can read, changed", relative.display());
+                    return Ok(Self::All { revision, reason })
+                }
+                continue
+            }
+            if !file.starts_with(&paths.sources) && !file.starts_with(&paths.tests) {
+                let reason =
+                    format!("{} changed outside of the sources and tests", relative.display());
+                return Ok(Self::All { revision, reason })
+            }
+            changed_sources.insert(file);
+        }
+
+        let mut files = BTreeMap::new();
+        for (file, index) in graph.files() {
+            if !file.starts_with(&paths.tests) && !file.is_sol_test() {
+                continue
+            }
+            let reason = std::iter::once(*index)
+                .chain(graph.all_imported_nodes(*index))
+                .map(|import| graph.node(import).path())
+                .find(|import| changed_sources.contains(*import));
+            if let Some(reason) = reason {
+                files.insert(file.clone(), reason.clone());
+            }
+        }
+        Ok(Self::Selected { revision, files })
+    }
+
+    /// Returns the selected test files, or `None` if all tests run.
+    pub fn files(&self) -> Option<BTreeSet<PathBuf>> {
+        match self {
+            Self::All { .. } => None,
+            Self::Selected { files, .. } => Some(files.keys().cloned().collect()),
+        }
+    }
+
+    /// Returns a [fmt::Display] of the selection, with paths relative to `root`.
+    pub fn display<'a>(&'a self, root: &'a Path) -> impl fmt::Display + 'a {
+        DisplayChangedTests { tests: self, root }
+    }
+}
+
+struct DisplayChangedTests<'a> {
+    tests: &'a ChangedTests,
+    root: &'a Path,
+}
+
+impl fmt::Display for DisplayChangedTests<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let relative =
+            |path: &Path| path.strip_prefix(self.root).unwrap_or(path).display().to_string();
+        match self.tests {
+            ChangedTests::All { revision, reason } => {
+                write!(
+                    f,
+                    "{} {reason} since {revision}, running all tests",
+                    Paint::yellow("Notice:").bold()
+                )
+            }
+            ChangedTests::Selected { revision, files } if files.is_empty() => {
+                write!(f, "No tests are affected by the changes since {revision}")
+            }
+            ChangedTests::Selected { revision, files } => {
+                write!(f, "Running tests affected by the changes since {revision}:")?;
+                for (file, reason) in files {
+                    if file == reason {
+                        write!(f, "\n  {} (changed)", relative(file))?;
+                    } else {
+                        write!(f, "\n  {} (imports {})", relative(file), relative(reason))?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
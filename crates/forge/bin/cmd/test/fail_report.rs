@@ -0,0 +1,443 @@
+//! Reproduction bundles of failed tests, see `forge test --fail-report`.
+
+use alloy_primitives::{Address, Bytes, U256};
+use eyre::Result;
+use forge::{
+    fuzz::CounterExample,
+    inspectors::cheatcodes::process_env_vars_read,
+    opts::EvmOpts,
+    result::TestResult,
+    revm::primitives::Env,
+    traces::{
+        render_trace_arena, CallKind, CallTraceArena, CallTraceDecoder, DecodedCallData,
+        DecodedCallLog,
+    },
+};
+use foundry_common::fs;
+use foundry_config::{redact_url, Config};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
+
+/// The maximum size of a written trace, larger traces are truncated.
+const MAX_TRACE_SIZE: usize = 16 * 1024 * 1024;
+
+/// The maximum size of a copied RPC cache file, larger caches are left out.
+const MAX_RPC_CACHE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The environment variable names whose values are always redacted.
+const SECRET_ENV_VAR_HINTS: [&str; 8] =
+    ["KEY", "SECRET", "TOKEN", "PASSWORD", "PRIVATE", "MNEMONIC", "RPC", "URL"];
+
+/// The length from which the values of environment variables are redacted whatever their name.
+const MIN_SECRET_LEN: usize = 20;
+
+/// The length below which the values of environment variables are never redacted, e.g. the `0` of
+/// `PRIVATE_KEY_INDEX`, as they would be replaced all over the written files.
+const MIN_REDACTED_LEN: usize = 8;
+
+/// Writes a self-contained directory for each failed test, with everything needed to reproduce
+/// it: the failure, the decoded traces, the fuzz seed and counterexample, the fork, the RPC cache
+/// of the pinned fork block, the resolved config and a `repro.sh` re-running the test.
+///
+/// The fork URLs, the configured API keys and the values of the secret looking environment
+/// variables read with `vm.env*` are redacted from all the written files, wherever they appear as
+/// a whole token, e.g. a JSON string value, rather than inside a longer word or number.
+pub struct FailReport {
+    dir: PathBuf,
+    root: PathBuf,
+    config: Config,
+    /// The fuzz seed of the run
+    seed: Option<U256>,
+    fork: Option<ForkReport>,
+    /// The test directories to copy the RPC cache into, once it's flushed at the end of the run
+    written: Vec<PathBuf>,
+}
+
+/// The fork of a failed run.
+#[derive(Clone, Debug, Serialize)]
+struct ForkReport {
+    /// The redacted fork URL
+    url: String,
+    chain_id: u64,
+    block: u64,
+    /// Whether the block was pinned with `--fork-block-number`, and its state cached
+    pinned: bool,
+}
+
+/// The machine readable summary of a failed test.
+#[derive(Serialize)]
+struct FailureJson<'a> {
+    contract: &'a str,
+    test: &'a str,
+    reason: Option<&'a str>,
+    counterexample: Option<&'a CounterExample>,
+    decoded_logs: &'a [String],
+    labeled_addresses: &'a HashMap<Address, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fuzz_seed: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fork: Option<&'a ForkReport>,
+    /// The environment variables read with `vm.env*` by any test of the run, not only this one,
+    /// whose values aren't recorded
+    run_env_vars: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    notes: Vec<String>,
+}
+
+/// A decoded call of a trace.
+#[derive(Serialize)]
+struct DecodedNode {
+    depth: usize,
+    kind: String,
+    address: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
+    /// The calldata, if it couldn't be decoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Bytes>,
+    value: U256,
+    gas_used: u64,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    returned: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    logs: Vec<String>,
+    children: Vec<usize>,
+}
+
+impl FailReport {
+    pub fn new(dir: PathBuf, config: &Config, evm_opts: &EvmOpts, env: &Env) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let fork = evm_opts.fork_url.as_ref().map(|url| ForkReport {
+            url: redact_url(url),
+            chain_id: env.cfg.chain_id,
+            block: env.block.number.to(),
+            pinned: evm_opts.fork_block_number.is_some(),
+        });
+        Ok(Self {
+            dir,
+            root: config.__root.0.clone(),
+            config: config.clone(),
+            seed: config.fuzz.seed,
+            fork,
+            written: Vec::new(),
+        })
+    }
+
+    /// Writes the directory of the failed test `test` of the contract with the identifier
+    /// `contract`, e.g. `test/Counter.t.sol:CounterTest`.
+    pub async fn write(
+        &mut self,
+        contract: &str,
+        test: &str,
+        result: &TestResult,
+        decoder: &CallTraceDecoder,
+    ) -> Result<()> {
+        let (path, contract_name) = contract.rsplit_once(':').unwrap_or(("", contract));
+        let function = test.split('(').next().unwrap_or(test);
+        let dir = self.dir.join(contract_name).join(function);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(&dir)?;
+
+        let redactions = self.redactions();
+        let redact = |s: String| {
+            redactions
+                .iter()
+                .fold(s, |s, (secret, replacement)| redact_token(&s, secret, replacement))
+        };
+        let mut notes = Vec::new();
+
+        // The rendered traces, without colors.
+        let colors = Paint::is_enabled();
+        Paint::disable();
+        let mut rendered = String::new();
+        for (kind, arena) in &result.traces {
+            rendered.push_str(&format!("{kind:?}:\n"));
+            rendered.push_str(&render_trace_arena(arena, decoder).await?);
+            rendered.push('\n');
+        }
+        let summary = result.short_result(test);
+        if colors {
+            Paint::enable();
+        }
+        write_limited(&dir.join("traces.txt"), redact(rendered), &mut notes)?;
+
+        let mut decoded = BTreeMap::<String, Vec<DecodedNode>>::new();
+        for (kind, arena) in &result.traces {
+            decoded.insert(format!("{kind:?}"), decode_arena(arena, decoder).await);
+        }
+        write_limited(
+            &dir.join("traces.json"),
+            redact(serde_json::to_string_pretty(&decoded)?),
+            &mut notes,
+        )?;
+
+        let mut text = format!("{summary}\n");
+        if !result.decoded_logs.is_empty() {
+            text.push_str("\nLogs:\n");
+            for log in &result.decoded_logs {
+                text.push_str(&format!("  {log}\n"));
+            }
+        }
+        fs::write(dir.join("failure.txt"), redact(text))?;
+
+        fs::write(dir.join("foundry.toml"), redact(self.config.to_string_pretty()?))?;
+        fs::write(dir.join("repro.sh"), redact(self.repro_script(path, contract_name, function)))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(dir.join("repro.sh"), std::fs::Permissions::from_mode(0o755))?;
+        }
+
+        if self.fork.as_ref().is_some_and(|fork| !fork.pinned) {
+            notes.push(
+                "the fork block wasn't pinned, so its state wasn't cached and is fetched again"
+                    .to_string(),
+            );
+        }
+        let failure = FailureJson {
+            contract,
+            test,
+            reason: result.reason.as_deref(),
+            counterexample: result.counterexample.as_ref(),
+            decoded_logs: &result.decoded_logs,
+            labeled_addresses: &result.labeled_addresses,
+            fuzz_seed: self.seed,
+            fork: self.fork.as_ref(),
+            run_env_vars: process_env_vars_read().into_iter().collect(),
+            notes,
+        };
+        fs::write(dir.join("failure.json"), redact(serde_json::to_string_pretty(&failure)?))?;
+
+        self.written.push(dir);
+        Ok(())
+    }
+
+    /// Copies the RPC cache of the pinned fork block into the written directories.
+    ///
+    /// The cache is only flushed to disk once the forks are dropped, at the end of the run.
+    pub fn finish(self) -> Result<()> {
+        if !self.written.is_empty() {
+            println!(
+                "Wrote the reports of {} failed test(s) to {}",
+                self.written.len(),
+                self.dir.display()
+            );
+        }
+
+        let Some(fork) = self.fork.as_ref().filter(|fork| fork.pinned) else { return Ok(()) };
+        let Some(cache) = Config::foundry_block_cache_file(fork.chain_id, fork.block) else {
+            return Ok(())
+        };
+        let Ok(metadata) = std::fs::metadata(&cache) else { return Ok(()) };
+        for dir in self.written.iter() {
+            if metadata.len() > MAX_RPC_CACHE_SIZE {
+                fs::write(
+                    dir.join("rpc-cache.txt"),
+                    format!(
+                        "The RPC cache of block {} ({} bytes) exceeds {MAX_RPC_CACHE_SIZE} bytes \
+                         and was left out.\n",
+                        fork.block,
+                        metadata.len()
+                    ),
+                )?;
+                continue
+            }
+            let target =
+                dir.join("rpc-cache").join(fork.chain_id.to_string()).join(fork.block.to_string());
+            fs::create_dir_all(&target)?;
+            fs::copy(&cache, target.join("storage.json"))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the secrets to redact and their replacements.
+    fn redactions(&self) -> Vec<(String, String)> {
+        let mut redactions = Vec::new();
+        let mut urls = self.config.eth_rpc_url.iter().cloned().collect::<Vec<_>>();
+        urls.extend(self.config.rpc_endpoints.clone().resolved().values().flatten().cloned());
+        for url in urls {
+            let redacted = redact_url(&url);
+            if redacted != url {
+                redactions.push((url, redacted));
+            }
+        }
+        if let Some(key) = &self.config.etherscan_api_key {
+            redactions.push((key.clone(), "<redacted>".to_string()));
+        }
+        for name in process_env_vars_read() {
+            let Ok(value) = std::env::var(&name) else { continue };
+            let upper = name.to_uppercase();
+            if value.len() >= MIN_SECRET_LEN ||
+                (value.len() >= MIN_REDACTED_LEN &&
+                    SECRET_ENV_VAR_HINTS.iter().any(|hint| upper.contains(hint)))
+            {
+                redactions.push((value, format!("${name}")));
+            }
+        }
+        // replace the longest secrets first, in case they contain shorter ones
+        redactions.retain(|(secret, _)| !secret.is_empty());
+        redactions.sort_by_key(|(secret, _)| std::cmp::Reverse(secret.len()));
+        redactions
+    }
+
+    /// Returns the script re-running the test with the seed and the fork of the failed run.
+    fn repro_script(&self, path: &str, contract_name: &str, function: &str) -> String {
+        let mut script = format!(
+            "#!/usr/bin/env bash\n\
+             # Re-runs {contract_name}::{function} like the failed run.\n\
+             # Set FOUNDRY_PROJECT to run it against another checkout of the project.\n\
+             set -eu\n\
+             report=\"$(cd \"$(dirname \"$0\")\" && pwd)\"\n\
+             cd \"${{FOUNDRY_PROJECT:-{}}}\"\n",
+            self.root.display()
+        );
+        let mut args = vec![
+            format!("--match-contract '^{contract_name}$'"),
+            format!("--match-test '^{function}$'"),
+        ];
+        if !path.is_empty() {
+            args.push(format!("--match-path '{path}'"));
+        }
+        if let Some(seed) = self.seed {
+            args.push(format!("--fuzz-seed {seed:#x}"));
+        }
+        if let Some(fork) = &self.fork {
+            script.push_str(
+                "# The fork URL is redacted, set FORK_URL to an endpoint of the same chain.\n",
+            );
+            if fork.pinned {
+                script.push_str(&format!(
+                    "cache=\"${{FOUNDRY_CACHE:-$HOME/.foundry/cache}}/rpc/{chain}/{block}\"\n\
+                     if [ -f \"$report/rpc-cache/{chain}/{block}/storage.json\" ] && [ ! -f \"$cache/storage.json\" ]; then\n  \
+                       mkdir -p \"$cache\" && cp \"$report/rpc-cache/{chain}/{block}/storage.json\" \"$cache/\"\n\
+                     fi\n",
+                    chain = fork.chain_id,
+                    block = fork.block,
+                ));
+            }
+            args.push("--fork-url \"${FORK_URL:?set FORK_URL to the fork endpoint}\"".to_string());
+            args.push(format!("--fork-block-number {}", fork.block));
+        }
+        args.push("-vvvv".to_string());
+        script.push_str(&format!("forge test {} \"$@\"\n", args.join(" ")));
+        script
+    }
+}
+
+/// Replaces the occurrences of `secret` in `s` that are whole tokens, i.e. not preceded or followed
+/// by an alphanumeric character or an underscore.
+fn redact_token(s: &str, secret: &str, replacement: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut redacted = String::with_capacity(s.len());
+    let mut last = 0;
+    for (start, _) in s.match_indices(secret) {
+        let end = start + secret.len();
+        if s[..start].chars().next_back().is_some_and(is_word) ||
+            s[end..].chars().next().is_some_and(is_word)
+        {
+            continue
+        }
+        redacted.push_str(&s[last..start]);
+        redacted.push_str(replacement);
+        last = end;
+    }
+    redacted.push_str(&s[last..]);
+    redacted
+}
+
+/// Returns the decoded calls of the arena, indexed like its nodes.
+async fn decode_arena(arena: &CallTraceArena, decoder: &CallTraceDecoder) -> Vec<DecodedNode> {
+    decoder.prefetch_signatures(arena.nodes()).await;
+    let mut nodes = Vec::with_capacity(arena.nodes().len());
+    for node in arena.nodes() {
+        let trace = &node.trace;
+        let decoded = decoder.decode_function(trace).await;
+        let (function, args) = match decoded.func {
+            Some(DecodedCallData { signature, args }) => (Some(signature), args),
+            None => (None, Vec::new()),
+        };
+        let mut logs = Vec::with_capacity(node.logs.len());
+        for log in &node.logs {
+            logs.push(match decoder.decode_event(log).await {
+                DecodedCallLog::Decoded(name, params) => format!(
+                    "{name}({})",
+                    params
+                        .iter()
+                        .map(|(name, value)| format!("{name}: {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                DecodedCallLog::Raw(log) => format!("{log:?}"),
+            });
+        }
+        nodes.push(DecodedNode {
+            depth: trace.depth,
+            kind: match trace.kind {
+                CallKind::Call => "call",
+                CallKind::StaticCall => "staticcall",
+                CallKind::CallCode => "callcode",
+                CallKind::DelegateCall => "delegatecall",
+                CallKind::Create => "create",
+                CallKind::Create2 => "create2",
+            }
+            .to_string(),
+            address: trace.address,
+            label: decoded.label,
+            data: function.is_none().then(|| trace.data.clone()),
+            function,
+            args,
+            value: trace.value,
+            gas_used: trace.gas_used,
+            success: trace.success,
+            returned: decoded.return_data,
+            logs,
+            children: node.children.clone(),
+        });
+    }
+    nodes
+}
+
+/// Writes `content`, truncated to [MAX_TRACE_SIZE].
+fn write_limited(path: &Path, mut content: String, notes: &mut Vec<String>) -> Result<()> {
+    if content.len() > MAX_TRACE_SIZE {
+        let mut end = MAX_TRACE_SIZE;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        content.truncate(end);
+        content.push_str("\n... truncated\n");
+        notes.push(format!(
+            "{} was truncated to {MAX_TRACE_SIZE} bytes",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ));
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_whole_tokens() {
+        let json = r#"{"key": "supersecret", "calldata": "0xsupersecret", "n": "supersecret1"}"#;
+        assert_eq!(
+            redact_token(json, "supersecret", "$API_KEY"),
+            r#"{"key": "$API_KEY", "calldata": "0xsupersecret", "n": "supersecret1"}"#
+        );
+        assert_eq!(redact_token("key=supersecret\n", "supersecret", "$K"), "key=$K\n");
+        assert_eq!(redact_token("index 0, 10, 0x0", "0", "$I"), "index $I, 10, 0x0");
+    }
+}
@@ -4,7 +4,11 @@ use foundry_cli::utils::FoundryPathExt;
 use foundry_common::glob::GlobMatcher;
 use foundry_compilers::{FileFilter, ProjectPathsConfig};
 use foundry_config::Config;
-use std::{fmt, path::Path};
+use std::{
+    collections::BTreeSet,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 /// The filter to use during testing.
 ///
@@ -73,7 +77,7 @@ impl FilterArgs {
         if self.path_pattern_inverse.is_none() {
             self.path_pattern_inverse = config.path_pattern_inverse.clone().map(Into::into);
         }
-        ProjectPathsAwareFilter { args_filter: self, paths: config.project_paths() }
+        ProjectPathsAwareFilter { args_filter: self, paths: config.project_paths(), changed: None }
     }
 }
 
@@ -170,6 +174,8 @@ impl fmt::Display for FilterArgs {
 pub struct ProjectPathsAwareFilter {
     args_filter: FilterArgs,
     paths: ProjectPathsConfig,
+    /// The git revision and the test files affected by the changes since, see `--changed`
+    changed: Option<(String, BTreeSet<PathBuf>)>,
 }
 
 // === impl ProjectPathsAwareFilter ===
@@ -177,7 +183,15 @@ pub struct ProjectPathsAwareFilter {
 impl ProjectPathsAwareFilter {
     /// Returns true if the filter is empty.
     pub fn is_empty(&self) -> bool {
-        self.args_filter.is_empty()
+        self.args_filter.is_empty() && self.changed.is_none()
+    }
+
+    /// Returns true if the filter selects the tests by their paths, see `--match-path`,
+    /// `--no-match-path` and `--changed`.
+    pub fn filters_paths(&self) -> bool {
+        self.args_filter.path_pattern.is_some() ||
+            self.args_filter.path_pattern_inverse.is_some() ||
+            self.changed.is_some()
     }
 
     /// Returns the CLI arguments.
@@ -189,6 +203,11 @@ impl ProjectPathsAwareFilter {
     pub fn args_mut(&mut self) -> &mut FilterArgs {
         &mut self.args_filter
     }
+
+    /// Only matches the given test files, affected by the changes since `revision`.
+    pub fn set_changed(&mut self, revision: String, files: BTreeSet<PathBuf>) {
+        self.changed = Some((revision, files));
+    }
 }
 
 impl FileFilter for ProjectPathsAwareFilter {
@@ -212,12 +231,20 @@ impl TestFilter for ProjectPathsAwareFilter {
 
     fn matches_path(&self, path: &Path) -> bool {
         // we don't want to test files that belong to a library
-        self.args_filter.matches_path(path) && !self.paths.has_library_ancestor(path)
+        self.args_filter.matches_path(path) &&
+            !self.paths.has_library_ancestor(path) &&
+            self.changed
+                .as_ref()
+                .map_or(true, |(_, files)| files.contains(&self.paths.root.join(path)))
     }
 }
 
 impl fmt::Display for ProjectPathsAwareFilter {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.args_filter.fmt(f)
+        self.args_filter.fmt(f)?;
+        if let Some((revision, _)) = &self.changed {
+            writeln!(f, "\tchanged: `{revision}`")?;
+        }
+        Ok(())
     }
 }
@@ -1,6 +1,6 @@
 use super::{install, test::filter::ProjectPathsAwareFilter, watch::WatchArgs};
 use alloy_primitives::U256;
-use clap::Parser;
+use clap::{Parser, ValueEnum, ValueHint};
 use eyre::Result;
 use forge::{
     decode::decode_console_logs,
@@ -9,7 +9,7 @@ use forge::{
     result::{SuiteResult, TestOutcome, TestStatus},
     traces::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
-        CallTraceDecoderBuilder, TraceKind,
+        state_changes, CallTraceDecoderBuilder, TraceKind,
     },
     MultiContractRunner, MultiContractRunnerBuilder, TestOptions, TestOptionsBuilder,
 };
@@ -20,24 +20,35 @@ use foundry_cli::{
 use foundry_common::{
     compile::{ContractSources, ProjectCompiler},
     evm::EvmArgs,
-    shell,
+    fs, shell,
+    vyper::compile_vyper,
 };
+use foundry_compilers::{EvmVersion, FileFilter, Project, ProjectCompileOutput};
 use foundry_config::{
     figment,
     figment::{
         value::{Dict, Map},
         Metadata, Profile, Provider,
     },
-    get_available_profiles, Config,
+    get_available_profiles, source_evm_versions, Config,
 };
-use foundry_debugger::Debugger;
+use foundry_debugger::{Debugger, BREAKPOINTS_FILE};
 use regex::Regex;
-use std::{sync::mpsc::channel, time::Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::mpsc::channel,
+    time::Instant,
+};
 use watchexec::config::{InitConfig, RuntimeConfig};
 use yansi::Paint;
 
+mod changed;
+mod fail_report;
 mod filter;
 mod summary;
+pub use changed::ChangedTests;
+use fail_report::FailReport;
 use summary::TestSummaryReporter;
 
 pub use filter::FilterArgs;
@@ -68,10 +79,46 @@ pub struct TestArgs {
     #[arg(long, value_name = "TEST_FUNCTION")]
     debug: Option<Regex>,
 
+    /// Decode the internal function calls in the traces of the matching tests.
+    ///
+    /// The internal calls are found with the source maps of the contracts, and their arguments
+    /// are read from the stack where possible. Recording the stack is slow, so the optional
+    /// argument limits it to the tests matching the pattern, by default all the tests that run.
+    ///
+    /// Implies at least -vvv.
+    #[arg(
+        long,
+        value_name = "TEST_PATTERN",
+        num_args = 0..=1,
+        default_missing_value = ".*"
+    )]
+    decode_internal: Option<Regex>,
+
+    /// Print the Solidity stack traces of the failed tests.
+    ///
+    /// Each frame is the contract, the function or modifier and the source line it was executing,
+    /// from the revert up to the test function. Calls to contracts without local sources are
+    /// shown as their address and selector.
+    #[arg(long)]
+    stack_traces: bool,
+
     /// Print a gas report.
     #[arg(long, env = "FORGE_GAS_REPORT")]
     gas_report: bool,
 
+    /// The format of the gas report.
+    ///
+    /// `json` prints only the report, in a stable format that can be compared with
+    /// `forge gas-diff`.
+    #[arg(
+        long,
+        value_enum,
+        default_value = "table",
+        requires = "gas_report",
+        conflicts_with = "json"
+    )]
+    gas_report_format: GasReportFormat,
+
     /// Exit with code 0 even if a test fails.
     #[arg(long, env = "FORGE_ALLOW_FAILURE")]
     allow_failure: bool,
@@ -80,7 +127,18 @@ pub struct TestArgs {
     #[arg(long, short, help_heading = "Display options")]
     json: bool,
 
+    /// Write a directory for each failed test, with everything needed to reproduce it.
+    ///
+    /// Contains the failure, the decoded traces, the fuzz seed and counterexample, the fork and
+    /// the RPC cache of its pinned block, the resolved config and a `repro.sh` re-running the
+    /// test. A fuzz seed is generated if none is set, so that fuzz failures can be replayed.
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath, conflicts_with_all = ["json", "list"])]
+    fail_report: Option<PathBuf>,
+
     /// Stop running tests after the first failure.
+    ///
+    /// Tests that are running when a test fails are cancelled, and reported as skipped along with
+    /// the tests that didn't start.
     #[arg(long)]
     pub fail_fast: bool,
 
@@ -102,6 +160,20 @@ pub struct TestArgs {
     #[command(flatten)]
     filter: FilterArgs,
 
+    /// Only run the tests affected by the changes since the git revision.
+    ///
+    /// A test file is affected if it or any of its transitive imports changed. Changes to the
+    /// config, the remappings or to Solidity files outside of the sources and tests run all tests.
+    /// Composes with the other filters.
+    #[arg(
+        long,
+        value_name = "REVISION",
+        num_args = 0..=1,
+        default_missing_value = "HEAD",
+        help_heading = "Test filtering"
+    )]
+    pub changed: Option<String>,
+
     #[command(flatten)]
     evm_opts: EvmArgs,
 
@@ -128,7 +200,9 @@ impl TestArgs {
 
     pub async fn run(self) -> Result<TestOutcome> {
         trace!(target: "forge::test", "executing test command");
-        shell::set_shell(shell::Shell::from_args(self.opts.silent, self.json))?;
+        // The JSON gas report is the only output
+        let silent = self.opts.silent || self.gas_report_format == GasReportFormat::Json;
+        shell::set_shell(shell::Shell::from_args(silent, self.json))?;
         self.execute_tests().await
     }
 
@@ -159,33 +233,87 @@ impl TestArgs {
             project = config.project()?;
         }
 
+        // Tests running against a fork are compiled and executed with the settings of its chain.
+        let fork_chain = evm_opts.fork_url.is_some().then(|| evm_opts.get_remote_chain_id());
+        let chain_gas_limit = if let Some(chain) = fork_chain.flatten() {
+            config.apply_chain_overrides(chain);
+            project = config.project()?;
+            let gas_limit = config.chains.get(chain).and_then(|chain| chain.gas_limit);
+            if let Some(gas_limit) = gas_limit {
+                evm_opts.env.gas_limit = gas_limit.0;
+            }
+            gas_limit
+        } else {
+            None
+        };
+
+        let quiet =
+            self.json || self.opts.silent || self.gas_report_format == GasReportFormat::Json;
+
         let mut filter = self.filter(&config);
+        if let Some(revision) = &self.changed {
+            let changed = ChangedTests::new(&config, revision)?;
+            if !quiet {
+                println!("{}", changed.display(project.root()));
+            }
+            if let Some(files) = changed.files() {
+                filter.set_changed(revision.clone(), files);
+            }
+        }
         trace!(target: "forge::test", ?filter, "using filter");
 
-        let mut compiler = ProjectCompiler::new().quiet_if(self.json || self.opts.silent);
-        if config.sparse_mode {
-            compiler = compiler.filter(Box::new(filter.clone()));
+        // The sources with an inline EVM version other than the profile's may not compile for the
+        // profile's, so they're only compiled for theirs.
+        let variant_files = evm_variant_files(&config, &project);
+        let evm_variants = compile_evm_variants(&config, &variant_files, quiet)?;
+
+        let mut compiler =
+            ProjectCompiler::new().quiet_if(quiet).solc_versions(config.solc_versions.clone());
+        if config.sparse_mode || !variant_files.is_empty() {
+            compiler = compiler.filter(Box::new(ExcludeFiles {
+                excluded: variant_files.into_values().flatten().collect(),
+                filter: config.sparse_mode.then(|| filter.clone()),
+            }));
         }
         let output = compiler.compile(&project)?;
+        let vyper_output = compile_vyper(&config, quiet).await?;
 
         // Create test options from general project settings and compiler output.
         let project_root = &project.paths.root;
         let toml = config.get_config_path();
         let profiles = get_available_profiles(toml)?;
 
+        // Fuzz failures can only be replayed with the seed they were found with
+        if self.fail_report.is_some() && config.fuzz.seed.is_none() {
+            config.fuzz.seed = Some(U256::from_be_bytes(rand::random::<[u8; 32]>()));
+        }
+
         let test_options: TestOptions = TestOptionsBuilder::default()
             .fuzz(config.fuzz)
             .invariant(config.invariant)
             .profiles(profiles)
-            .build(&output, project_root)?;
+            .build_with_variants(&output, &evm_variants, project_root)?;
 
         // Determine print verbosity and executor verbosity
+        if self.decode_internal.is_some() && evm_opts.verbosity < 3 {
+            evm_opts.verbosity = 3;
+        }
         let verbosity = evm_opts.verbosity;
-        if self.gas_report && evm_opts.verbosity < 3 {
+        if (self.gas_report || self.fail_report.is_some() || self.stack_traces) &&
+            evm_opts.verbosity < 3
+        {
             evm_opts.verbosity = 3;
         }
 
-        let env = evm_opts.evm_env().await?;
+        let mut env = evm_opts.evm_env().await?;
+        if let Some(gas_limit) = chain_gas_limit {
+            env.block.gas_limit = U256::from(gas_limit.0);
+        }
+        let fail_report = self
+            .fail_report
+            .clone()
+            .map(|dir| FailReport::new(dir, &config, &evm_opts, &env))
+            .transpose()?;
 
         // Prepare the test builder
         let should_debug = self.debug.is_some();
@@ -195,6 +323,8 @@ impl TestArgs {
 
         let runner = MultiContractRunnerBuilder::default()
             .set_debug(should_debug)
+            .decode_internal(self.decode_internal.clone())
+            .stack_traces(self.stack_traces)
             .initial_balance(evm_opts.initial_balance)
             .evm_spec(config.evm_spec_id())
             .sender(evm_opts.sender)
@@ -202,6 +332,9 @@ impl TestArgs {
             .with_cheats_config(CheatsConfig::new(&config, evm_opts.clone(), None))
             .with_test_options(test_options)
             .enable_isolation(evm_opts.isolate)
+            .fail_fast(self.fail_fast)
+            .with_vyper_artifacts(vyper_output.map(|output| output.artifacts).unwrap_or_default())
+            .with_evm_variants(evm_variants)
             .build(project_root, output, env, evm_opts)?;
 
         if let Some(debug_test_pattern) = &self.debug {
@@ -215,7 +348,22 @@ impl TestArgs {
             *test_pattern = Some(debug_test_pattern.clone());
         }
 
-        let outcome = self.run_tests(runner, config, verbosity, &filter).await?;
+        let rpc_cache_max_size_mb = config.rpc_cache_max_size_mb;
+        let breakpoints_file = config.cache_path.join(BREAKPOINTS_FILE);
+        let outcome = self.run_tests(runner, config, verbosity, &filter, fail_report).await?;
+
+        if let Some(max_size) = rpc_cache_max_size_mb {
+            // the test results are more important than keeping the cache within its limit
+            match Config::evict_foundry_rpc_cache(max_size * 1_000_000, false) {
+                Ok(evicted) => {
+                    trace!(target: "forge::test", evicted = evicted.len(), "evicted rpc cache blocks")
+                }
+                Err(err) => shell::eprintln(format!(
+                    "{} failed to evict RPC cache blocks: {err}",
+                    Paint::yellow("Warning:").bold()
+                ))?,
+            }
+        }
 
         if should_debug {
             // There is only one test.
@@ -232,7 +380,8 @@ impl TestArgs {
             let mut builder = Debugger::builder()
                 .debug_arenas(test.result.debug.as_slice())
                 .sources(sources)
-                .breakpoints(test.result.breakpoints);
+                .breakpoints(test.result.breakpoints)
+                .breakpoints_file(breakpoints_file);
             if let Some(decoder) = &outcome.decoder {
                 builder = builder.decoder(decoder);
             }
@@ -250,6 +399,7 @@ impl TestArgs {
         config: Config,
         verbosity: u8,
         filter: &ProjectPathsAwareFilter,
+        mut fail_report: Option<FailReport>,
     ) -> eyre::Result<TestOutcome> {
         if self.list {
             return list(runner, filter, self.json);
@@ -289,15 +439,30 @@ impl TestArgs {
         }
 
         if self.json {
-            let results = runner.test_collect(filter);
+            let mut results = runner.test_collect(filter);
+            if verbosity >= 5 {
+                for result in results.values_mut().flat_map(|suite| suite.test_results.values_mut())
+                {
+                    result.state_changes = result
+                        .traces
+                        .iter()
+                        .map(|(kind, arena)| (*kind, state_changes(arena, runner.env.tx.gas_price)))
+                        .collect();
+                }
+            }
             println!("{}", serde_json::to_string(&results)?);
             return Ok(TestOutcome::new(results, self.allow_failure));
         }
 
         // Set up trace identifiers.
         let known_contracts = runner.known_contracts.clone();
+        let storage_layouts = std::mem::take(&mut runner.storage_layouts);
+        let internal_functions = std::mem::take(&mut runner.internal_functions);
+        let stack_traces = runner.source_locations.take();
         let mut local_identifier = LocalTraceIdentifier::new(&known_contracts);
         let remote_chain_id = runner.evm_opts.get_remote_chain_id();
+        let gas_price = runner.env.tx.gas_price;
+        let evm_versions = runner.test_options.evm_versions.clone();
         let mut etherscan_identifier = EtherscanIdentifier::new(&config, remote_chain_id)?;
 
         // Run tests.
@@ -308,13 +473,18 @@ impl TestArgs {
             move || runner.test(&filter, tx)
         });
 
-        let mut gas_report =
-            self.gas_report.then(|| GasReport::new(config.gas_reports, config.gas_reports_ignore));
+        let mut gas_report = self.gas_report.then(|| {
+            GasReport::new(config.gas_reports, config.gas_reports_ignore)
+                .with_libraries(known_contracts.library_identifiers())
+        });
 
         // Build the trace decoder.
         let mut builder = CallTraceDecoderBuilder::new()
             .with_local_identifier_abis(&local_identifier)
-            .with_verbosity(verbosity);
+            .with_storage_layouts(storage_layouts)
+            .with_internal_functions(internal_functions)
+            .with_verbosity(verbosity)
+            .with_gas_price(gas_price);
         // Signatures are of no value for gas reports.
         if !self.gas_report {
             builder = builder.with_signature_identifier(SignaturesIdentifier::new(
@@ -324,8 +494,9 @@ impl TestArgs {
         }
         let mut decoder = builder.build();
 
-        // We identify addresses if we're going to print *any* trace or gas report.
-        let identify_addresses = verbosity >= 3 || self.gas_report || self.debug.is_some();
+        // Addresses are always identified for the gas report and the debugger, and otherwise
+        // only for the tests whose traces are printed.
+        let always_identify = self.gas_report || self.debug.is_some();
 
         let mut outcome = TestOutcome::empty(self.allow_failure);
 
@@ -334,30 +505,49 @@ impl TestArgs {
             let tests = &suite_result.test_results;
 
             // Print suite header.
-            println!();
+            shell::println("")?;
             for warning in suite_result.warnings.iter() {
-                eprintln!("{} {warning}", Paint::yellow("Warning:").bold());
+                shell::eprintln(format!("{} {warning}", Paint::yellow("Warning:").bold()))?;
             }
             if !tests.is_empty() {
                 let len = tests.len();
                 let tests = if len > 1 { "tests" } else { "test" };
-                println!("Ran {len} {tests} for {contract_name}");
+                shell::println(format!("Ran {len} {tests} for {contract_name}"))?;
             }
 
+            // Look up the unknown signatures of all the traces printed for the suite at once.
+            decoder
+                .prefetch_signatures(tests.values().flat_map(|result| {
+                    result
+                        .traces
+                        .iter()
+                        .filter(|(kind, _)| should_display_trace(*kind, verbosity, result.status))
+                        .flat_map(|(_, arena)| arena.nodes())
+                }))
+                .await;
+
             // Process individual test results, printing logs and traces when necessary.
             for (name, result) in tests {
                 shell::println(result.short_result(name))?;
 
+                // Show the time spent waiting on the fork endpoint at level 3 and above
+                if verbosity >= 3 && result.fetch_stats.fetches > 0 {
+                    shell::println(format!(
+                        "Fork: {} fetches, {:.2?} waiting on RPC",
+                        result.fetch_stats.fetches, result.fetch_stats.wait_time
+                    ))?;
+                }
+
                 // We only display logs at level 2 and above
                 if verbosity >= 2 {
                     // We only decode logs from Hardhat and DS-style console events
                     let console_logs = decode_console_logs(&result.logs);
                     if !console_logs.is_empty() {
-                        println!("Logs:");
+                        shell::println("Logs:")?;
                         for log in console_logs {
-                            println!("  {log}");
+                            shell::println(format!("  {log}"))?;
                         }
-                        println!();
+                        shell::println("")?;
                     }
                 }
 
@@ -365,7 +555,15 @@ impl TestArgs {
                 // processing the remaining tests and print the suite summary.
                 any_test_failed |= result.status == TestStatus::Failure;
 
-                if result.traces.is_empty() {
+                let displayed = result
+                    .traces
+                    .iter()
+                    .filter(|(kind, _)| should_display_trace(*kind, verbosity, result.status))
+                    .count();
+                let report = fail_report.is_some() && result.status == TestStatus::Failure;
+                let stack_traces =
+                    stack_traces.as_ref().filter(|_| result.status == TestStatus::Failure);
+                if displayed == 0 && !always_identify && !report && stack_traces.is_none() {
                     continue;
                 }
 
@@ -376,29 +574,12 @@ impl TestArgs {
                     .extend(result.labeled_addresses.iter().map(|(k, v)| (*k, v.clone())));
 
                 // Identify addresses and decode traces.
-                let mut decoded_traces = Vec::with_capacity(result.traces.len());
+                let mut decoded_traces = Vec::with_capacity(displayed);
                 for (kind, arena) in &result.traces {
-                    if identify_addresses {
-                        decoder.identify(arena, &mut local_identifier);
-                        decoder.identify(arena, &mut etherscan_identifier);
-                    }
-
-                    // verbosity:
-                    // - 0..3: nothing
-                    // - 3: only display traces for failed tests
-                    // - 4: also display the setup trace for failed tests
-                    // - 5..: display all traces for all tests
-                    let should_include = match kind {
-                        TraceKind::Execution => {
-                            (verbosity == 3 && result.status.is_failure()) || verbosity >= 4
-                        }
-                        TraceKind::Setup => {
-                            (verbosity == 4 && result.status.is_failure()) || verbosity >= 5
-                        }
-                        TraceKind::Deployment => false,
-                    };
+                    decoder.identify(arena, &mut local_identifier);
+                    decoder.identify(arena, &mut etherscan_identifier);
 
-                    if should_include {
+                    if should_display_trace(*kind, verbosity, result.status) {
                         decoded_traces.push(render_trace_arena(arena, &decoder).await?);
                     }
                 }
@@ -410,8 +591,31 @@ impl TestArgs {
                     }
                 }
 
+                // The stack trace of the last failed call, e.g. the test after its `setUp`.
+                let frames = stack_traces.and_then(|stack_traces| {
+                    result
+                        .traces
+                        .iter()
+                        .rev()
+                        .map(|(_, arena)| stack_traces.frames(arena, &decoder))
+                        .find(|frames| !frames.is_empty())
+                });
+                if let Some(frames) = frames {
+                    shell::println("Stack trace:")?;
+                    for frame in frames {
+                        shell::println(format!("  {frame}"))?;
+                    }
+                    shell::println("")?;
+                }
+
+                if let Some(fail_report) = fail_report.as_mut().filter(|_| report) {
+                    fail_report.write(&contract_name, name, result, &decoder).await?;
+                }
+
                 if let Some(gas_report) = &mut gas_report {
-                    gas_report.analyze(&result.traces, &decoder).await;
+                    let evm_version =
+                        evm_versions.get(&contract_name).unwrap_or(&config.evm_version);
+                    gas_report.analyze(&result.traces, &decoder, evm_version.as_str()).await;
                 }
             }
 
@@ -420,11 +624,6 @@ impl TestArgs {
 
             // Add the suite result to the outcome.
             outcome.results.insert(contract_name, suite_result);
-
-            // Stop processing the remaining suites if any test failed and `fail_fast` is set.
-            if self.fail_fast && any_test_failed {
-                break;
-            }
         }
         let duration = timer.elapsed();
 
@@ -433,7 +632,13 @@ impl TestArgs {
         outcome.decoder = Some(decoder);
 
         if let Some(gas_report) = gas_report {
-            shell::println(gas_report.finalize())?;
+            let gas_report = gas_report.finalize();
+            match self.gas_report_format {
+                GasReportFormat::Table => shell::println(gas_report)?,
+                GasReportFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&gas_report.to_json())?)
+                }
+            }
         }
 
         if !outcome.results.is_empty() {
@@ -454,6 +659,11 @@ impl TestArgs {
             }
         }
 
+        // The forks, and so their RPC caches, are only flushed once the runner is dropped.
+        if let Some(fail_report) = fail_report {
+            fail_report.finish()?;
+        }
+
         Ok(outcome)
     }
 
@@ -469,11 +679,8 @@ impl TestArgs {
 
     /// Returns the [`watchexec::InitConfig`] and [`watchexec::RuntimeConfig`] necessary to
     /// bootstrap a new [`watchexe::Watchexec`] loop.
-    pub(crate) fn watchexec_config(&self) -> Result<(InitConfig, RuntimeConfig)> {
-        self.watch.watchexec_config(|| {
-            let config = Config::from(self);
-            vec![config.src, config.test]
-        })
+    pub(crate) fn watchexec_config(&self, config: &Config) -> Result<(InitConfig, RuntimeConfig)> {
+        self.watch.watchexec_config(config, || vec![config.src.clone(), config.test.clone()])
     }
 }
 
@@ -504,6 +711,55 @@ impl Provider for TestArgs {
     }
 }
 
+/// Returns the sources with an inline EVM version other than the profile's, by EVM version.
+fn evm_variant_files(config: &Config, project: &Project) -> BTreeMap<EvmVersion, Vec<PathBuf>> {
+    let mut files = BTreeMap::<EvmVersion, Vec<PathBuf>>::new();
+    for file in project.paths.input_files() {
+        let Ok(source) = fs::read_to_string(&file) else { continue };
+        for evm_version in source_evm_versions(&source) {
+            if evm_version != config.evm_version {
+                files.entry(evm_version).or_default().push(file.clone());
+            }
+        }
+    }
+    files
+}
+
+/// Compiles the sources with an inline EVM version other than the profile's, once per EVM
+/// version, into their own artifacts and cache directories.
+fn compile_evm_variants(
+    config: &Config,
+    files: &BTreeMap<EvmVersion, Vec<PathBuf>>,
+    quiet: bool,
+) -> Result<Vec<(EvmVersion, ProjectCompileOutput)>> {
+    let mut variants = vec![];
+    for (evm_version, files) in files {
+        let mut config = config.clone();
+        config.evm_version = *evm_version;
+        config.out = config.out.join(format!("evm-{evm_version}"));
+        config.cache_path = config.cache_path.join(format!("evm-{evm_version}"));
+        let output = ProjectCompiler::new()
+            .quiet_if(quiet)
+            .files(files.clone())
+            .compile(&config.project()?)?;
+        variants.push((*evm_version, output));
+    }
+    Ok(variants)
+}
+
+/// Excludes the sources compiled for another EVM version than the profile's from a compilation,
+/// optionally only compiling the files matching the test filter.
+struct ExcludeFiles {
+    excluded: BTreeSet<PathBuf>,
+    filter: Option<ProjectPathsAwareFilter>,
+}
+
+impl FileFilter for ExcludeFiles {
+    fn is_match(&self, file: &Path) -> bool {
+        !self.excluded.contains(file) && self.filter.as_ref().map_or(true, |f| f.is_match(file))
+    }
+}
+
 /// Lists all matching tests
 fn list(
     runner: MultiContractRunner,
@@ -526,6 +782,29 @@ fn list(
     Ok(TestOutcome::empty(false))
 }
 
+/// Returns `true` if the trace of the given kind is printed for a test with `status`.
+///
+/// verbosity:
+/// - 0..3: nothing
+/// - 3: only display traces for failed tests
+/// - 4: also display the setup trace for failed tests
+/// - 5..: display all traces for all tests
+fn should_display_trace(kind: TraceKind, verbosity: u8, status: TestStatus) -> bool {
+    match kind {
+        TraceKind::Execution => (verbosity == 3 && status.is_failure()) || verbosity >= 4,
+        TraceKind::Setup => (verbosity == 4 && status.is_failure()) || verbosity >= 5,
+        TraceKind::Deployment => false,
+    }
+}
+
+/// The format of the gas report of `forge test --gas-report`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum GasReportFormat {
+    #[default]
+    Table,
+    Json,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -537,6 +816,19 @@ mod tests {
         assert!(args.watch.watch.is_some());
     }
 
+    #[test]
+    fn displays_traces_by_verbosity() {
+        let failure = TestStatus::Failure;
+        let success = TestStatus::Success;
+        assert!(!should_display_trace(TraceKind::Execution, 3, success));
+        assert!(should_display_trace(TraceKind::Execution, 3, failure));
+        assert!(!should_display_trace(TraceKind::Setup, 3, failure));
+        assert!(should_display_trace(TraceKind::Setup, 4, failure));
+        assert!(!should_display_trace(TraceKind::Setup, 4, success));
+        assert!(should_display_trace(TraceKind::Setup, 5, success));
+        assert!(!should_display_trace(TraceKind::Deployment, 5, failure));
+    }
+
     #[test]
     fn fuzz_seed() {
         let args: TestArgs = TestArgs::parse_from(["foundry-cli", "--fuzz-seed", "0x10"]);
@@ -1,9 +1,21 @@
+use super::coverage::dummy_link_deployed_bytecode;
 use clap::Parser;
 use eyre::Result;
 use foundry_cli::{opts::ProjectPathsArgs, utils::LoadConfig};
+use foundry_common::compile::ProjectCompiler;
 use foundry_compilers::{
-    resolver::{Charset, TreeOptions},
-    Graph,
+    artifacts::contract::CompactContractBytecode,
+    info::ContractInfo,
+    remappings::Remapping,
+    resolver::{parse::SolImportAlias, Charset, TreeOptions},
+    ArtifactId, Graph, ProjectCompileOutput,
+};
+use foundry_config::Config;
+use itertools::Itertools;
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
 };
 
 /// CLI arguments for `forge tree`.
@@ -19,6 +31,21 @@ pub struct TreeArgs {
     #[arg(long, default_value = "utf8")]
     charset: Charset,
 
+    /// Print the dependency graph as JSON.
+    ///
+    /// The project is compiled to attribute the deployed bytecode of its contracts to the files.
+    #[arg(long)]
+    json: bool,
+
+    /// Print the import cycles of the project.
+    #[arg(long, conflicts_with_all = ["json", "contract"])]
+    cycles: bool,
+
+    /// Only show the dependencies of the contract, and how many bytes of its deployed bytecode
+    /// come from each of them.
+    #[arg(long, value_name = "CONTRACT")]
+    contract: Option<ContractInfo>,
+
     #[command(flatten)]
     opts: ProjectPathsArgs,
 }
@@ -29,9 +56,577 @@ impl TreeArgs {
     pub fn run(self) -> Result<()> {
         let config = self.try_load_config_emit_warnings()?;
         let graph = Graph::resolve(&config.project_paths())?;
-        let opts = TreeOptions { charset: self.charset, no_dedupe: self.no_dedupe };
-        graph.print_with_options(opts);
+        if !self.json && !self.cycles && self.contract.is_none() {
+            let opts = TreeOptions { charset: self.charset, no_dedupe: self.no_dedupe };
+            graph.print_with_options(opts);
+            return Ok(())
+        }
+
+        let mut deps = DependencyGraph::new(&config, &graph);
+        if self.cycles {
+            let cycles = deps.cycles();
+            if cycles.is_empty() {
+                println!("No import cycles found.");
+            }
+            for cycle in cycles {
+                let first = cycle[0];
+                println!(
+                    "{}",
+                    cycle
+                        .iter()
+                        .chain([&first])
+                        .map(|&i| deps.nodes[i].path.display())
+                        .join(" -> ")
+                );
+            }
+            return Ok(())
+        }
+
+        let output = ProjectCompiler::new().quiet(true).compile(&config.project()?)?;
+        if let Some(contract) = &self.contract {
+            let is_target = |name: &str, source: &Path| {
+                name == contract.name &&
+                    contract
+                        .path
+                        .as_ref()
+                        .map_or(true, |path| normalize(config.__root.0.join(path)) == source)
+            };
+            let source = output
+                .artifact_ids()
+                .map(|(id, _)| (id.name, normalize(id.source)))
+                .find(|(name, source)| is_target(name, source))
+                .map(|(_, source)| source)
+                .ok_or_else(|| eyre::eyre!("Could not find contract `{contract}`"))?;
+            let root = deps
+                .index_of(&source)
+                .ok_or_else(|| eyre::eyre!("Could not find `{}` in the graph", source.display()))?;
+
+            deps = deps.restrict_to(root);
+            deps.attribute_sizes(output, |id| is_target(&id.name, &id.source));
+            if self.json {
+                println!("{}", serde_json::to_string_pretty(&deps)?);
+            } else {
+                let chars = match self.charset {
+                    Charset::Ascii => TreeChars::ASCII,
+                    _ => TreeChars::UTF8,
+                };
+                print!("{}", deps.render_tree(0, chars, self.no_dedupe));
+            }
+        } else {
+            deps.attribute_sizes(output, |_| true);
+            println!("{}", serde_json::to_string_pretty(&deps)?);
+        }
 
         Ok(())
     }
 }
+
+/// The import graph of the project's files.
+#[derive(Clone, Debug, Default, Serialize)]
+struct DependencyGraph {
+    nodes: Vec<FileNode>,
+    edges: Vec<ImportEdge>,
+    /// The import cycles, as node indices
+    cycles: Vec<Vec<usize>>,
+}
+
+/// A source file in the [DependencyGraph].
+#[derive(Clone, Debug, Default, Serialize)]
+struct FileNode {
+    /// The path of the file, relative to the project root
+    path: PathBuf,
+    #[serde(skip)]
+    absolute_path: PathBuf,
+    /// The SPDX license identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license: Option<String>,
+    /// The version requirement of the solidity pragma
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pragma: Option<String>,
+    /// The number of bytes of deployed bytecode that come from this file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<usize>,
+}
+
+/// An import directive in the [DependencyGraph].
+#[derive(Clone, Debug, Default, Serialize)]
+struct ImportEdge {
+    /// The index of the importing file
+    from: usize,
+    /// The index of the imported file, if it could be resolved
+    to: Option<usize>,
+    /// The import path as written in the source
+    path: String,
+    /// The imported symbols, empty if the whole file is imported
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    symbols: Vec<String>,
+    /// The remapping that resolved the import
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remapping: Option<String>,
+    /// The library directory that contains the imported file, if it wasn't remapped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    library: Option<PathBuf>,
+}
+
+impl DependencyGraph {
+    /// Builds the graph from the files and import edges resolved by the compiler's [Graph].
+    fn new(config: &Config, graph: &Graph) -> Self {
+        let root = &config.__root.0;
+        let remappings = config
+            .remappings
+            .iter()
+            .map(|remapping| (Remapping::from(remapping.clone()), remapping.to_string()))
+            .collect_vec();
+        let files = graph
+            .files()
+            .iter()
+            .map(|(path, index)| (normalize(path.clone()), *index))
+            .sorted()
+            .collect_vec();
+        let positions =
+            files.iter().enumerate().map(|(i, (_, index))| (*index, i)).collect::<HashMap<_, _>>();
+
+        let mut deps = Self::default();
+        for (from, (path, index)) in files.iter().enumerate() {
+            let node = graph.node(*index);
+            let relative_path = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+            deps.nodes.push(FileNode {
+                absolute_path: path.clone(),
+                license: node.license().as_ref().map(|license| license.data().trim().to_string()),
+                pragma: node.version().as_ref().map(|version| version.data().trim().to_string()),
+                size: None,
+                path: relative_path.clone(),
+            });
+
+            let imported = graph
+                .imported_nodes(*index)
+                .iter()
+                .map(|to| (positions[to], &files[positions[to]].0))
+                .collect_vec();
+            for import in node.imports() {
+                let import = import.data();
+                let specifier = import.path().to_string_lossy().to_string();
+                let remapping = find_remapping(&remappings, &relative_path, &specifier);
+                // the part of the import path that is kept in the resolved path
+                let suffix = match remapping {
+                    Some((remapping, _)) => &specifier[remapping.name.len()..],
+                    None => specifier.trim_start_matches("./").trim_start_matches("../"),
+                };
+                let to = if specifier.starts_with('.') {
+                    let resolved = normalize(path.parent().unwrap_or(root).join(&specifier));
+                    imported.iter().find(|(_, path)| **path == resolved)
+                } else {
+                    imported.iter().find(|(_, path)| path.ends_with(suffix))
+                };
+                let library = to.filter(|_| remapping.is_none()).and_then(|(_, path)| {
+                    config
+                        .libs
+                        .iter()
+                        .find(|lib| path.starts_with(root.join(lib)))
+                        .map(|lib| lib.strip_prefix(root).unwrap_or(lib).to_path_buf())
+                });
+                deps.edges.push(ImportEdge {
+                    from,
+                    to: to.map(|(to, _)| *to),
+                    path: specifier.clone(),
+                    symbols: import
+                        .aliases()
+                        .iter()
+                        .filter_map(|alias| match alias {
+                            SolImportAlias::Contract(_, symbol) => Some(symbol.clone()),
+                            SolImportAlias::File(_) => None,
+                        })
+                        .collect(),
+                    remapping: remapping.map(|(_, label)| label.clone()),
+                    library,
+                });
+            }
+        }
+        deps.cycles = deps.cycles();
+        deps
+    }
+
+    fn index_of(&self, path: &Path) -> Option<usize> {
+        self.nodes.iter().position(|node| node.absolute_path == path)
+    }
+
+    fn imports(&self, from: usize) -> impl Iterator<Item = &ImportEdge> {
+        self.edges.iter().filter(move |edge| edge.from == from)
+    }
+
+    /// Returns the indices of the file and of all the files it imports, directly or not.
+    fn reachable(&self, from: usize) -> BTreeSet<usize> {
+        let mut reachable = BTreeSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(node) = queue.pop_front() {
+            for to in self.imports(node).filter_map(|edge| edge.to) {
+                if reachable.insert(to) {
+                    queue.push_back(to);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Returns the subgraph of the file's transitive dependencies, with the file at index 0.
+    fn restrict_to(&self, root: usize) -> Self {
+        let mut kept = self.reachable(root).into_iter().collect_vec();
+        kept.retain(|&i| i != root);
+        kept.insert(0, root);
+        let index =
+            kept.iter().enumerate().map(|(new, old)| (*old, new)).collect::<HashMap<_, _>>();
+
+        let mut graph = Self {
+            nodes: kept.iter().map(|&i| self.nodes[i].clone()).collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter_map(|edge| {
+                    let from = *index.get(&edge.from)?;
+                    Some(ImportEdge { from, to: edge.to.map(|to| index[&to]), ..edge.clone() })
+                })
+                .collect(),
+            cycles: vec![],
+        };
+        graph.cycles = graph.cycles();
+        graph
+    }
+
+    /// Attributes the deployed bytecode of the matching contracts to the files it was generated
+    /// from, using the deployed source maps.
+    ///
+    /// Compiler-generated code and metadata are not attributed to any file.
+    fn attribute_sizes(
+        &mut self,
+        output: ProjectCompileOutput,
+        filter: impl Fn(&ArtifactId) -> bool,
+    ) {
+        let (artifacts, sources) = output.into_artifacts_with_sources();
+        let source_paths = sources
+            .into_sources_with_version()
+            .map(|(path, source, version)| ((version, source.id), normalize(PathBuf::from(path))))
+            .collect::<HashMap<_, _>>();
+
+        let mut sizes = HashMap::<PathBuf, usize>::new();
+        for (id, artifact) in artifacts {
+            let id = ArtifactId { source: normalize(id.source.clone()), ..id };
+            if !filter(&id) {
+                continue
+            }
+            let Some(deployed) = CompactContractBytecode::from(artifact).deployed_bytecode else {
+                continue
+            };
+            let Some(Ok(source_map)) =
+                deployed.bytecode.as_ref().and_then(|bytecode| bytecode.source_map())
+            else {
+                continue
+            };
+            let Some(code) = dummy_link_deployed_bytecode(deployed) else { continue };
+
+            let (mut pc, mut ic) = (0, 0);
+            while pc < code.len() {
+                // the metadata is not part of the source map
+                let Some(element) = source_map.get(ic) else { break };
+                let op = code[pc];
+                let len = if (0x60..=0x7f).contains(&op) { (op - 0x5f) as usize + 1 } else { 1 };
+                let path =
+                    element.index.and_then(|index| source_paths.get(&(id.version.clone(), index)));
+                if let Some(path) = path {
+                    *sizes.entry(path.clone()).or_default() += len.min(code.len() - pc);
+                }
+                pc += len;
+                ic += 1;
+            }
+        }
+
+        for node in self.nodes.iter_mut() {
+            node.size = Some(sizes.get(&node.absolute_path).copied().unwrap_or_default());
+        }
+    }
+
+    /// Returns the sum of the sizes of the file and of all the files it imports, directly or not.
+    fn cumulative_size(&self, from: usize) -> usize {
+        self.reachable(from).into_iter().filter_map(|i| self.nodes[i].size).sum()
+    }
+
+    /// Returns one import cycle per strongly connected component of the graph.
+    fn cycles(&self) -> Vec<Vec<usize>> {
+        let successors =
+            |node: usize| self.imports(node).filter_map(|edge| edge.to).collect::<BTreeSet<_>>();
+
+        let mut cycles = Vec::new();
+        for component in strongly_connected_components(self.nodes.len(), &successors) {
+            let start = component[0];
+            let is_cycle = component.len() > 1 || successors(start).contains(&start);
+            if !is_cycle {
+                continue
+            }
+
+            // find the shortest path back to the start within the component
+            let members = component.iter().copied().collect::<HashSet<_>>();
+            let mut previous = HashMap::new();
+            let mut queue = VecDeque::from([start]);
+            'search: while let Some(node) = queue.pop_front() {
+                for next in successors(node) {
+                    if !members.contains(&next) || previous.contains_key(&next) {
+                        continue
+                    }
+                    previous.insert(next, node);
+                    if next == start {
+                        break 'search
+                    }
+                    queue.push_back(next);
+                }
+            }
+
+            let mut cycle = vec![];
+            let mut node = previous[&start];
+            while node != start {
+                cycle.push(node);
+                node = previous[&node];
+            }
+            cycle.push(start);
+            cycle.reverse();
+            cycles.push(cycle);
+        }
+        cycles
+    }
+
+    /// Renders the tree of the file's dependencies with their cumulative sizes.
+    fn render_tree(&self, root: usize, chars: TreeChars, no_dedupe: bool) -> String {
+        let mut out = String::new();
+        let mut visited = HashSet::new();
+        self.render_node(root, None, "", "", chars, no_dedupe, &mut visited, &mut out);
+        out
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_node(
+        &self,
+        node: usize,
+        edge: Option<&ImportEdge>,
+        prefix: &str,
+        child_prefix: &str,
+        chars: TreeChars,
+        no_dedupe: bool,
+        visited: &mut HashSet<usize>,
+        out: &mut String,
+    ) {
+        let file = &self.nodes[node];
+        let label = match edge {
+            Some(ImportEdge { remapping: Some(remapping), .. }) => format!(" [{remapping}]"),
+            Some(ImportEdge { library: Some(library), .. }) => {
+                format!(" [{}]", library.display())
+            }
+            _ => String::new(),
+        };
+        let size = format!(
+            "({} bytes, {} own)",
+            self.cumulative_size(node),
+            file.size.unwrap_or_default()
+        );
+
+        let expand = visited.insert(node) || no_dedupe;
+        let dedupe = if expand { "" } else { " (*)" };
+        out.push_str(&format!("{prefix}{}{label} {size}{dedupe}\n", file.path.display()));
+        if !expand {
+            return
+        }
+
+        let children = self
+            .imports(node)
+            .filter_map(|edge| Some((edge.to?, edge)))
+            .unique_by(|(to, _)| *to)
+            .collect_vec();
+        for (i, (child, edge)) in children.iter().enumerate() {
+            let last = i == children.len() - 1;
+            let (branch, next) =
+                if last { (chars.last, "   ") } else { (chars.branch, chars.down) };
+            self.render_node(
+                *child,
+                Some(edge),
+                &format!("{child_prefix}{branch}"),
+                &format!("{child_prefix}{next} "),
+                chars,
+                no_dedupe,
+                visited,
+                out,
+            );
+        }
+    }
+}
+
+/// The characters used to render a tree.
+#[derive(Clone, Copy, Debug)]
+struct TreeChars {
+    branch: &'static str,
+    last: &'static str,
+    down: &'static str,
+}
+
+impl TreeChars {
+    const UTF8: Self = Self { branch: "├── ", last: "└── ", down: "│  " };
+    const ASCII: Self = Self { branch: "|-- ", last: "`-- ", down: "|  " };
+}
+
+/// Returns the remapping that applies to the import of the file, in its configured form.
+///
+/// Like solc, the longest matching context wins, then the longest matching prefix.
+fn find_remapping<'a>(
+    remappings: &'a [(Remapping, String)],
+    importer: &Path,
+    import: &str,
+) -> Option<&'a (Remapping, String)> {
+    remappings
+        .iter()
+        .filter(|(remapping, _)| {
+            import.starts_with(&remapping.name) &&
+                remapping.context.as_ref().map_or(true, |context| {
+                    importer.to_string_lossy().starts_with(context.as_str())
+                })
+        })
+        .max_by_key(|(remapping, _)| {
+            (remapping.context.as_ref().map_or(0, String::len), remapping.name.len())
+        })
+}
+
+pub(crate) fn normalize(path: PathBuf) -> PathBuf {
+    dunce::canonicalize(&path).unwrap_or(path)
+}
+
+/// Returns the strongly connected components of the graph, each sorted and in the order of their
+/// smallest node, using Tarjan's algorithm.
+fn strongly_connected_components(
+    len: usize,
+    successors: &impl Fn(usize) -> BTreeSet<usize>,
+) -> Vec<Vec<usize>> {
+    struct State {
+        index: usize,
+        indices: Vec<Option<usize>>,
+        low_links: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        components: Vec<Vec<usize>>,
+    }
+
+    fn visit(node: usize, state: &mut State, successors: &impl Fn(usize) -> BTreeSet<usize>) {
+        state.indices[node] = Some(state.index);
+        state.low_links[node] = state.index;
+        state.index += 1;
+        state.stack.push(node);
+        state.on_stack[node] = true;
+
+        for next in successors(node) {
+            match state.indices[next] {
+                None => {
+                    visit(next, state, successors);
+                    state.low_links[node] = state.low_links[node].min(state.low_links[next]);
+                }
+                Some(index) if state.on_stack[next] => {
+                    state.low_links[node] = state.low_links[node].min(index);
+                }
+                _ => {}
+            }
+        }
+
+        if Some(state.low_links[node]) == state.indices[node] {
+            let mut component = vec![];
+            while let Some(member) = state.stack.pop() {
+                state.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break
+                }
+            }
+            component.sort_unstable();
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        index: 0,
+        indices: vec![None; len],
+        low_links: vec![0; len],
+        on_stack: vec![false; len],
+        stack: vec![],
+        components: vec![],
+    };
+    for node in 0..len {
+        if state.indices[node].is_none() {
+            visit(node, &mut state, successors);
+        }
+    }
+    state.components.sort_by_key(|component| component[0]);
+    state.components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(usize, usize)], len: usize) -> DependencyGraph {
+        DependencyGraph {
+            nodes: (0..len)
+                .map(|i| FileNode { path: format!("{i}.sol").into(), ..Default::default() })
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|&(from, to)| ImportEdge { from, to: Some(to), ..Default::default() })
+                .collect(),
+            cycles: vec![],
+        }
+    }
+
+    #[test]
+    fn can_find_import_cycles() {
+        let graph = graph(&[(0, 1), (1, 2), (2, 0), (2, 3), (3, 3), (4, 0)], 5);
+        assert_eq!(graph.cycles(), vec![vec![0, 1, 2], vec![3]]);
+        assert!(super::graph(&[(0, 1), (1, 2)], 3).cycles().is_empty());
+    }
+
+    #[test]
+    fn can_restrict_and_render_tree() {
+        let mut graph = graph(&[(0, 1), (1, 2), (3, 0)], 4);
+        for (i, node) in graph.nodes.iter_mut().enumerate() {
+            node.size = Some(10 * (i + 1));
+        }
+        let graph = graph.restrict_to(1);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.cumulative_size(0), 50);
+        assert_eq!(
+            graph.render_tree(0, TreeChars::ASCII, false),
+            "1.sol (50 bytes, 20 own)\n`-- 2.sol (30 bytes, 30 own)\n"
+        );
+    }
+
+    #[test]
+    fn can_find_remapping() {
+        let remapping = |context: Option<&str>, name: &str, path: &str| {
+            let remapping = Remapping {
+                context: context.map(str::to_string),
+                name: name.to_string(),
+                path: path.to_string(),
+            };
+            let label = remapping.to_string();
+            (remapping, label)
+        };
+        let remappings = [
+            remapping(None, "@oz/", "lib/oz/"),
+            remapping(None, "@oz/token/", "lib/oz-token/"),
+            remapping(Some("test/"), "@oz/", "lib/oz-test/"),
+        ];
+        let find = |importer: &str, import: &str| {
+            find_remapping(&remappings, Path::new(importer), import).map(|(_, label)| label.clone())
+        };
+        assert_eq!(find("src/A.sol", "@oz/utils/B.sol"), Some("@oz/=lib/oz/".to_string()));
+        assert_eq!(
+            find("src/A.sol", "@oz/token/C.sol"),
+            Some("@oz/token/=lib/oz-token/".to_string())
+        );
+        assert_eq!(
+            find("test/A.sol", "@oz/token/C.sol"),
+            Some("test/:@oz/=lib/oz-test/".to_string())
+        );
+        assert_eq!(find("src/A.sol", "./B.sol"), None);
+    }
+}
@@ -1,14 +1,22 @@
-use super::{build::BuildArgs, snapshot::SnapshotArgs, test::TestArgs};
+use super::{build::BuildArgs, pipeline::PipelineArgs, snapshot::SnapshotArgs, test::TestArgs};
 use clap::Parser;
 use eyre::Result;
 use foundry_cli::utils::{self, FoundryPathExt};
-use foundry_config::Config;
-use std::{collections::HashSet, convert::Infallible, path::PathBuf, sync::Arc};
+use foundry_common::glob::GlobMatcher;
+use foundry_config::{Config, WatchConfig};
+use std::{
+    collections::{BTreeSet, HashSet},
+    convert::Infallible,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use watchexec::{
     action::{Action, Outcome, PreSpawn},
     command::Command,
     config::{InitConfig, RuntimeConfig},
+    error::RuntimeError,
     event::{Event, Priority, ProcessEnd},
+    filter::Filterer,
     handler::SyncFnHandler,
     paths::summarise_events_to_env,
     signal::source::MainSignal,
@@ -47,8 +55,9 @@ pub struct WatchArgs {
     /// given and a command is already running, the outcome of the
     /// action will be to do nothing.
     ///
-    /// Defaults to 50ms. Parses as decimal seconds by default, but
-    /// using an integer with the `ms` suffix may be more convenient.
+    /// Defaults to the `delay` of the `[watch]` config, or 50ms. Parses as
+    /// decimal seconds by default, but using an integer with the `ms`
+    /// suffix may be more convenient.
     ///
     /// When using --poll mode, you'll want a larger duration, or risk
     /// overloading disk I/O.
@@ -57,16 +66,18 @@ pub struct WatchArgs {
 }
 
 impl WatchArgs {
-    /// Returns new [InitConfig] and [RuntimeConfig] based on the [WatchArgs]
+    /// Returns new [InitConfig] and [RuntimeConfig] based on the [WatchArgs] and the `[watch]`
+    /// section of the `config`
     ///
     /// If paths were provided as arguments the these will be used as the watcher's pathset,
     /// otherwise the path the closure returns will be used
     pub fn watchexec_config(
         &self,
+        config: &Config,
         f: impl FnOnce() -> Vec<PathBuf>,
     ) -> Result<(InitConfig, RuntimeConfig)> {
         let init = init()?;
-        let mut runtime = runtime(self)?;
+        let mut runtime = runtime(self, config)?;
 
         // contains all the arguments `--watch p1, p2, p3`
         let has_paths = self.watch.as_ref().map(|paths| !paths.is_empty()).unwrap_or_default();
@@ -82,14 +93,15 @@ impl WatchArgs {
 /// Executes a [`Watchexec`] that listens for changes in the project's src dir and reruns `forge
 /// build`
 pub async fn watch_build(args: BuildArgs) -> Result<()> {
-    let (init, mut runtime) = args.watchexec_config()?;
+    let config = Config::from(&args);
+    let (init, mut runtime) = args.watchexec_config(&config)?;
     let cmd = cmd_args(args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
 
     trace!("watch build cmd={:?}", cmd);
     runtime.command(watch_command(cmd.clone()));
 
     let wx = Watchexec::new(init, runtime.clone())?;
-    on_action(args.watch, runtime, Arc::clone(&wx), cmd, (), |_| {});
+    on_action(args.watch, config.watch.clear, runtime, Arc::clone(&wx), cmd, (), |_| {});
 
     // start executing the command immediately
     wx.send_event(Event::default(), Priority::default()).await?;
@@ -101,14 +113,23 @@ pub async fn watch_build(args: BuildArgs) -> Result<()> {
 /// Executes a [`Watchexec`] that listens for changes in the project's src dir and reruns `forge
 /// snapshot`
 pub async fn watch_snapshot(args: SnapshotArgs) -> Result<()> {
-    let (init, mut runtime) = args.watchexec_config()?;
+    let config = Config::from(&args.test);
+    let (init, mut runtime) = args.watchexec_config(&config)?;
     let cmd = cmd_args(args.test.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
 
     trace!("watch snapshot cmd={:?}", cmd);
     runtime.command(watch_command(cmd.clone()));
     let wx = Watchexec::new(init, runtime.clone())?;
 
-    on_action(args.test.watch.clone(), runtime, Arc::clone(&wx), cmd, (), |_| {});
+    on_action(
+        args.test.watch.clone(),
+        config.watch.clear,
+        runtime,
+        Arc::clone(&wx),
+        cmd,
+        (),
+        |_| {},
+    );
 
     // start executing the command immediately
     wx.send_event(Event::default(), Priority::default()).await?;
@@ -120,14 +141,13 @@ pub async fn watch_snapshot(args: SnapshotArgs) -> Result<()> {
 /// Executes a [`Watchexec`] that listens for changes in the project's src dir and reruns `forge
 /// test`
 pub async fn watch_test(args: TestArgs) -> Result<()> {
-    let (init, mut runtime) = args.watchexec_config()?;
+    let config: Config = args.build_args().into();
+    let (init, mut runtime) = args.watchexec_config(&config)?;
     let cmd = cmd_args(args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
     trace!("watch test cmd={:?}", cmd);
     runtime.command(watch_command(cmd.clone()));
     let wx = Watchexec::new(init, runtime.clone())?;
 
-    let config: Config = args.build_args().into();
-
     let filter = args.filter(&config);
 
     // marker to check whether to override the command
@@ -136,12 +156,13 @@ pub async fn watch_test(args: TestArgs) -> Result<()> {
         filter.args().contract_pattern.is_some() ||
         args.watch.run_all;
 
+    let clear = config.watch.clear;
     let state = WatchTestState {
         project_root: config.__root.0,
         no_reconfigure,
         last_test_files: Default::default(),
     };
-    on_action(args.watch.clone(), runtime, Arc::clone(&wx), cmd, state, on_test);
+    on_action(args.watch.clone(), clear, runtime, Arc::clone(&wx), cmd, state, on_test);
 
     // start executing the command immediately
     wx.send_event(Event::default(), Priority::default()).await?;
@@ -150,6 +171,62 @@ pub async fn watch_test(args: TestArgs) -> Result<()> {
     Ok(())
 }
 
+/// Executes a [`Watchexec`] that listens for changes in the project and reruns the pipeline of
+/// `forge watch` with the changed files
+pub async fn watch_pipeline(args: PipelineArgs) -> Result<()> {
+    let config = Config::load();
+    let (init, mut runtime) = args.watch.watchexec_config(&config, || {
+        let root = &config.__root.0;
+        vec![
+            config.src.clone(),
+            config.test.clone(),
+            config.script.clone(),
+            root.join(Config::FILE_NAME),
+            root.join("remappings.txt"),
+        ]
+    })?;
+    let mut cmd = cmd_args(args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
+    cmd.push("--once".to_string());
+
+    trace!("watch pipeline cmd={:?}", cmd);
+    runtime.command(watch_command(cmd.clone()));
+    let wx = Watchexec::new(init, runtime.clone())?;
+
+    on_action(args.watch, config.watch.clear, runtime, Arc::clone(&wx), cmd, (), on_pipeline);
+
+    // start executing the command immediately
+    wx.send_event(Event::default(), Priority::default()).await?;
+    wx.main().await??;
+
+    Ok(())
+}
+
+/// The `on_action` hook for `forge watch`, passes the changed files to the pipeline
+fn on_pipeline(action: OnActionState<()>) {
+    let OnActionState { args, clear, runtime, action, wx, cmd, other } = action;
+
+    let changed: BTreeSet<_> =
+        action.events.iter().flat_map(|e| e.paths()).map(|(path, _)| path.to_path_buf()).collect();
+    if changed.is_empty() {
+        // the initial run
+        return
+    }
+
+    let mut new_cmd = cmd.clone();
+    for path in changed {
+        new_cmd.push("--changed-file".to_string());
+        new_cmd.push(path.display().to_string());
+    }
+    trace!("reconfigure pipeline command {:?}", new_cmd);
+
+    // reconfigure the executor with a new runtime
+    let mut config = runtime.clone();
+    config.command(watch_command(new_cmd));
+
+    // re-register the action
+    on_action(args.clone(), clear, config, wx, cmd.clone(), other, on_pipeline);
+}
+
 #[derive(Clone, Debug)]
 struct WatchTestState {
     /// the root directory of the project
@@ -164,7 +241,7 @@ struct WatchTestState {
 
 /// The `on_action` hook for `forge test --watch`
 fn on_test(action: OnActionState<WatchTestState>) {
-    let OnActionState { args, runtime, action, wx, cmd, other } = action;
+    let OnActionState { args, clear, runtime, action, wx, cmd, other } = action;
     let WatchTestState { project_root, no_reconfigure, last_test_files } = other;
 
     if no_reconfigure {
@@ -200,6 +277,7 @@ fn on_test(action: OnActionState<WatchTestState>) {
         // re-register the action
         on_action(
             args.clone(),
+            clear,
             config,
             wx,
             cmd,
@@ -240,6 +318,7 @@ fn on_test(action: OnActionState<WatchTestState>) {
     // re-register the action
     on_action(
         args.clone(),
+        clear,
         config,
         wx,
         cmd,
@@ -313,6 +392,8 @@ pub fn init() -> Result<InitConfig> {
 /// Contains all necessary context to reconfigure a [`Watchexec`] on the fly
 struct OnActionState<'a, T: Clone> {
     args: &'a WatchArgs,
+    /// whether the screen is cleared before every run
+    clear: bool,
     runtime: &'a RuntimeConfig,
     action: &'a Action,
     cmd: &'a Vec<String>,
@@ -328,6 +409,7 @@ struct OnActionState<'a, T: Clone> {
 /// [`Watchexec::reconfigure`]
 fn on_action<F, T>(
     args: WatchArgs,
+    clear: bool,
     mut config: RuntimeConfig,
     wx: Arc<Watchexec>,
     cmd: Vec<String>,
@@ -388,6 +470,7 @@ fn on_action<F, T>(
 
         f(OnActionState {
             args: &args,
+            clear,
             runtime: &runtime,
             action: &action,
             wx: w.clone(),
@@ -395,8 +478,6 @@ fn on_action<F, T>(
             other: other.clone(),
         });
 
-        // mattsse: could be made into flag to never clear the shell
-        let clear = false;
         let when_running = match (clear, on_busy) {
             (_, "do-nothing") => Outcome::DoNothing,
             (true, "restart") => {
@@ -418,15 +499,19 @@ fn on_action<F, T>(
 }
 
 /// Returns the Runtime configuration for [`Watchexec`].
-pub fn runtime(args: &WatchArgs) -> Result<RuntimeConfig> {
+pub fn runtime(args: &WatchArgs, project: &Config) -> Result<RuntimeConfig> {
     let mut config = RuntimeConfig::default();
 
     config.pathset(args.watch.clone().unwrap_or_default());
 
-    if let Some(delay) = &args.watch_delay {
+    if let Some(delay) = args.watch_delay.as_ref().or(project.watch.delay.as_ref()) {
         config.action_throttle(utils::parse_delay(delay)?);
     }
 
+    if !project.watch.ignore.is_empty() {
+        config.filterer(Arc::new(IgnoreFilterer::new(&project.__root.0, &project.watch)?));
+    }
+
     config.on_pre_spawn(move |prespawn: PreSpawn| async move {
         let envs = summarise_events_to_env(prespawn.events.iter());
         if let Some(mut command) = prespawn.command().await {
@@ -441,6 +526,37 @@ pub fn runtime(args: &WatchArgs) -> Result<RuntimeConfig> {
     Ok(config)
 }
 
+/// A [Filterer] that drops the events of the paths matching the `ignore` globs of the `[watch]`
+/// config
+#[derive(Debug)]
+struct IgnoreFilterer {
+    root: PathBuf,
+    ignore: Vec<GlobMatcher>,
+}
+
+impl IgnoreFilterer {
+    fn new(root: &Path, config: &WatchConfig) -> Result<Self> {
+        let ignore = config.ignore.iter().map(|glob| glob.parse()).collect::<Result<_, _>>()?;
+        Ok(Self { root: root.to_path_buf(), ignore })
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        let path = path.strip_prefix(&self.root).unwrap_or(path);
+        self.ignore.iter().any(|glob| glob.is_match(path))
+    }
+}
+
+impl Filterer for IgnoreFilterer {
+    fn check_event(&self, event: &Event, _priority: Priority) -> Result<bool, RuntimeError> {
+        let mut paths = event.paths().map(|(path, _)| path).peekable();
+        // events without paths are signals and process completions
+        if paths.peek().is_none() {
+            return Ok(true)
+        }
+        Ok(paths.any(|path| !self.is_ignored(path)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -5,6 +5,7 @@ use clap::{CommandFactory, Parser};
 use clap_complete::generate;
 use eyre::Result;
 use foundry_cli::{handler, utils};
+use foundry_config::GeigerSeverity;
 
 mod cmd;
 mod opts;
@@ -38,7 +39,8 @@ fn main() -> Result<()> {
         }
         ForgeSubcommand::Coverage(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::Bind(cmd) => cmd.run(),
-        ForgeSubcommand::Build(cmd) => {
+        ForgeSubcommand::Build { args: mut cmd, chain } => {
+            cmd.chain = chain;
             if cmd.is_watch() {
                 utils::block_on(watch::watch_build(cmd))
             } else {
@@ -48,10 +50,13 @@ fn main() -> Result<()> {
         ForgeSubcommand::Debug(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::VerifyContract(args) => utils::block_on(args.run()),
         ForgeSubcommand::VerifyCheck(args) => utils::block_on(args.run()),
+        ForgeSubcommand::VerifyBytecode(args) => utils::block_on(args.run()),
         ForgeSubcommand::Cache(cmd) => match cmd.sub {
             CacheSubcommands::Clean(cmd) => cmd.run(),
             CacheSubcommands::Ls(cmd) => cmd.run(),
+            CacheSubcommands::Evict(cmd) => cmd.run(),
         },
+        ForgeSubcommand::Clone(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::Create(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::Update(cmd) => cmd.run(),
         ForgeSubcommand::Install(cmd) => cmd.run(),
@@ -83,20 +88,44 @@ fn main() -> Result<()> {
                 utils::block_on(cmd.run())
             }
         }
+        ForgeSubcommand::GasDiff(cmd) => {
+            if cmd.run()? {
+                std::process::exit(1)
+            }
+            Ok(())
+        }
         ForgeSubcommand::Fmt(cmd) => cmd.run(),
         ForgeSubcommand::Config(cmd) => cmd.run(),
         ForgeSubcommand::Flatten(cmd) => cmd.run(),
         ForgeSubcommand::Inspect(cmd) => cmd.run(),
         ForgeSubcommand::Tree(cmd) => cmd.run(),
+        ForgeSubcommand::StorageCheck(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::Geiger(cmd) => {
             let check = cmd.check;
-            let n = cmd.run()?;
-            if check && n > 0 {
-                std::process::exit(n as i32);
+            let severity = cmd.run()?;
+            if check {
+                match severity {
+                    Some(GeigerSeverity::Error) => std::process::exit(2),
+                    Some(GeigerSeverity::Warn) => std::process::exit(1),
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+        ForgeSubcommand::Lint(cmd) => {
+            if cmd.run()? {
+                std::process::exit(1)
             }
             Ok(())
         }
         ForgeSubcommand::Doc(cmd) => cmd.run(),
+        ForgeSubcommand::Watch(cmd) => {
+            if cmd.once {
+                cmd.run()
+            } else {
+                utils::block_on(watch::watch_pipeline(cmd))
+            }
+        }
         ForgeSubcommand::Selectors { command } => utils::block_on(command.run()),
         ForgeSubcommand::Generate(cmd) => match cmd.sub {
             GenerateSubcommands::Test(cmd) => cmd.run(),
@@ -1,11 +1,13 @@
 use crate::cmd::{
-    bind::BindArgs, build::BuildArgs, cache::CacheArgs, config, coverage, create::CreateArgs,
-    debug::DebugArgs, doc::DocArgs, flatten, fmt::FmtArgs, geiger, generate, init::InitArgs,
-    inspect, install::InstallArgs, remappings::RemappingArgs, remove::RemoveArgs,
-    script::ScriptArgs, selectors::SelectorsSubcommands, snapshot, test, tree, update,
+    bind::BindArgs, build::BuildArgs, cache::CacheArgs, clone::CloneArgs, config, coverage,
+    create::CreateArgs, debug::DebugArgs, doc::DocArgs, flatten, fmt::FmtArgs, gas_diff, geiger,
+    generate, init::InitArgs, inspect, install::InstallArgs, lint, pipeline::PipelineArgs,
+    remappings::RemappingArgs, remove::RemoveArgs, script::ScriptArgs,
+    selectors::SelectorsSubcommands, snapshot, storage_check, test, tree, update,
 };
 use clap::{Parser, Subcommand, ValueHint};
-use forge_verify::{VerifyArgs, VerifyCheckArgs};
+use forge_verify::{VerifyArgs, VerifyBytecodeArgs, VerifyCheckArgs};
+use foundry_config::Chain;
 use std::path::PathBuf;
 
 const VERSION_MESSAGE: &str = concat!(
@@ -49,7 +51,17 @@ pub enum ForgeSubcommand {
 
     /// Build the project's smart contracts.
     #[command(visible_aliases = ["b", "compile"])]
-    Build(BuildArgs),
+    Build {
+        #[command(flatten)]
+        args: BuildArgs,
+
+        /// The chain name or EIP-155 chain ID to compile for.
+        ///
+        /// Applies the chain's overrides of the profile settings, e.g. the EVM version set in
+        /// `[profile.<name>.chains.<chain>]`.
+        #[arg(long, visible_alias = "chain-id", value_name = "CHAIN")]
+        chain: Option<Chain>,
+    },
 
     /// Debugs a single smart contract as a script.
     #[command(visible_alias = "d")]
@@ -83,6 +95,10 @@ pub enum ForgeSubcommand {
     #[command(visible_alias = "vc")]
     VerifyCheck(VerifyCheckArgs),
 
+    /// Check that the local build of a contract matches its on-chain code.
+    #[command(visible_alias = "vb")]
+    VerifyBytecode(VerifyBytecodeArgs),
+
     /// Deploy a smart contract.
     #[command(visible_alias = "c")]
     Create(CreateArgs),
@@ -115,10 +131,16 @@ pub enum ForgeSubcommand {
     /// Manage the Foundry cache.
     Cache(CacheArgs),
 
+    /// Clone a verified contract into a new Foundry project.
+    Clone(CloneArgs),
+
     /// Create a snapshot of each test's gas usage.
     #[command(visible_alias = "s")]
     Snapshot(snapshot::SnapshotArgs),
 
+    /// Compare two gas reports written with `forge test --gas-report --gas-report-format json`.
+    GasDiff(gas_diff::GasDiffArgs),
+
     /// Display the current config.
     #[command(visible_alias = "co")]
     Config(config::ConfigArgs),
@@ -134,6 +156,9 @@ pub enum ForgeSubcommand {
     #[command(visible_alias = "in")]
     Inspect(inspect::InspectArgs),
 
+    /// Check that the storage layout of a contract is compatible with a previous layout.
+    StorageCheck(storage_check::StorageCheckArgs),
+
     /// Display a tree visualization of the project's dependency graph.
     #[command(visible_alias = "tr")]
     Tree(tree::TreeArgs),
@@ -141,9 +166,15 @@ pub enum ForgeSubcommand {
     /// Detects usage of unsafe cheat codes in a project and its dependencies.
     Geiger(geiger::GeigerArgs),
 
+    /// Lint the project's sources, fixing the imports broken by moving files.
+    Lint(lint::LintArgs),
+
     /// Generate documentation for the project.
     Doc(DocArgs),
 
+    /// Run a pipeline of forge commands whenever the project changes.
+    Watch(PipelineArgs),
+
     /// Function selector utilities
     #[command(visible_alias = "se")]
     Selectors {
@@ -54,6 +54,12 @@ impl CoverageReporter for SummaryReporter {
 
         self.add_row("Total", self.total.clone());
         println!("{}", self.table);
+        if self.total.partial_count > 0 {
+            println!(
+                "{} items are only partially attributable to the source code, see `--report debug`.",
+                self.total.partial_count
+            );
+        }
         Ok(())
     }
 }
@@ -88,7 +94,11 @@ impl<'a> LcovReporter<'a> {
 
 impl<'a> CoverageReporter for LcovReporter<'a> {
     fn report(self, report: &CoverageReport) -> eyre::Result<()> {
+        let mut partial_items = Vec::new();
         for (file, items) in report.items_by_source() {
+            partial_items.extend(
+                items.iter().filter(|item| item.partial).map(|item| format!("{file}: {item}")),
+            );
             let summary = items.iter().fold(CoverageSummary::default(), |mut summary, item| {
                 summary += item;
                 summary
@@ -140,6 +150,15 @@ impl<'a> CoverageReporter for LcovReporter<'a> {
 
         println!("Wrote LCOV report.");
 
+        // LCOV can't mark items, so the ones whose hits may be wrong are listed instead
+        if !partial_items.is_empty() {
+            println!(
+                "{} items of the LCOV report are only partially attributable to the source code:",
+                partial_items.len()
+            );
+            partial_items.iter().for_each(|item| println!("- {item}"));
+        }
+
         Ok(())
     }
 }
@@ -157,6 +176,12 @@ impl CoverageReporter for DebugReporter {
                 }
             });
             println!();
+
+            if items.iter().any(|item| item.partial) {
+                println!("Partially attributable for {path}:");
+                items.iter().filter(|item| item.partial).for_each(|item| println!("- {item}"));
+                println!();
+            }
         }
 
         for (contract_id, anchors) in &report.anchors {
@@ -3,13 +3,16 @@
 use crate::{
     constants::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
     hashbrown::HashSet,
-    traces::{CallTraceArena, CallTraceDecoder, CallTraceNode, DecodedCallData, TraceKind},
+    traces::{CallTraceDecoder, CallTraceNode, DecodedCallData, TraceArena, TraceKind},
 };
 use comfy_table::{presets::ASCII_MARKDOWN, *};
 use foundry_common::{calc, TestFunctionExt};
 use foundry_evm::traces::CallKind;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt::Display};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
 
 /// Represents the gas report for a set of contracts.
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -20,6 +23,8 @@ pub struct GasReport {
     report_for: HashSet<String>,
     /// Contracts to ignore when generating the report.
     ignore: HashSet<String>,
+    /// Identifiers of the linkable libraries, whose external functions are delegatecalled
+    libraries: HashSet<String>,
     /// All contracts that were analyzed grouped by their identifier
     /// ``test/Counter.t.sol:CounterTest
     contracts: BTreeMap<String, ContractInfo>,
@@ -36,6 +41,12 @@ impl GasReport {
         Self { report_any, report_for, ignore, ..Default::default() }
     }
 
+    /// Sets the identifiers of the linkable libraries, which are reported separately.
+    pub fn with_libraries(mut self, libraries: impl IntoIterator<Item = String>) -> Self {
+        self.libraries = libraries.into_iter().collect();
+        self
+    }
+
     /// Whether the given contract should be reported.
     #[instrument(level = "trace", skip(self), ret)]
     fn should_report(&self, contract_name: &str) -> bool {
@@ -58,18 +69,27 @@ impl GasReport {
         self.report_any || self.report_for.contains(contract_name)
     }
 
-    /// Analyzes the given traces and generates a gas report.
+    /// Analyzes the given traces, executed with `evm_version`, and generates a gas report.
     pub async fn analyze(
         &mut self,
-        traces: &[(TraceKind, CallTraceArena)],
+        traces: &[(TraceKind, TraceArena)],
         decoder: &CallTraceDecoder,
+        evm_version: &str,
     ) {
-        for node in traces.iter().flat_map(|(_, arena)| arena.nodes()) {
-            self.analyze_node(node, decoder).await;
+        for (_, arena) in traces {
+            for node in arena.nodes() {
+                self.analyze_node(arena.nodes(), node, decoder, evm_version).await;
+            }
         }
     }
 
-    async fn analyze_node(&mut self, node: &CallTraceNode, decoder: &CallTraceDecoder) {
+    async fn analyze_node(
+        &mut self,
+        nodes: &[CallTraceNode],
+        node: &CallTraceNode,
+        decoder: &CallTraceDecoder,
+        evm_version: &str,
+    ) {
         let trace = &node.trace;
 
         if trace.address == CHEATCODE_ADDRESS || trace.address == HARDHAT_CONSOLE_ADDRESS {
@@ -86,6 +106,13 @@ impl GasReport {
             return;
         }
 
+        // Calls to a proxy are attributed to its implementation, when analyzing the DELEGATECALL
+        // which forwarded the calldata.
+        if node.children.iter().any(|&child| proxy_call(nodes, &nodes[child]).is_some()) {
+            return;
+        }
+        let proxy = proxy_call(nodes, node);
+
         let decoded = decoder.decode_function(&node.trace).await;
 
         let Some(name) = &decoded.contract else { return };
@@ -95,10 +122,13 @@ impl GasReport {
             return;
         }
 
+        let is_library = self.libraries.contains(name);
         let contract_info = self.contracts.entry(name.to_string()).or_default();
+        contract_info.library = is_library;
+        contract_info.evm_versions.insert(evm_version.to_string());
         if trace.kind.is_any_create() {
             trace!(contract_name, "adding create gas info");
-            contract_info.gas = trace.gas_used;
+            contract_info.deployments.calls.push(trace.gas_used);
             contract_info.size = trace.data.len();
         } else if let Some(DecodedCallData { signature, .. }) = decoded.func {
             let name = signature.split('(').next().unwrap();
@@ -112,48 +142,138 @@ impl GasReport {
                     .or_default()
                     .entry(signature.clone())
                     .or_default();
-                gas_info.calls.push(trace.gas_used);
+                match proxy {
+                    Some(proxy) => {
+                        gas_info.calls.push(proxy.trace.gas_used);
+                        if let Some(proxy_name) = decoder.contracts.get(&proxy.trace.address) {
+                            let proxy_name = proxy_name.rsplit(':').next().unwrap_or(proxy_name);
+                            gas_info.proxies.insert(proxy_name.to_string());
+                        }
+                    }
+                    None => gas_info.calls.push(trace.gas_used),
+                }
             }
         }
     }
 
-    /// Finalizes the gas report by calculating the min, max, mean, and median for each function.
+    /// Finalizes the gas report by calculating the min, max, mean, and median for each function
+    /// and the deployments of each contract.
     #[must_use]
     pub fn finalize(mut self) -> Self {
         trace!("finalizing gas report");
         for contract in self.contracts.values_mut() {
+            contract.deployments.finalize();
+            contract.gas = contract.deployments.median;
             for sigs in contract.functions.values_mut() {
                 for func in sigs.values_mut() {
-                    func.calls.sort_unstable();
-                    func.min = func.calls.first().copied().unwrap_or_default();
-                    func.max = func.calls.last().copied().unwrap_or_default();
-                    func.mean = calc::mean(&func.calls);
-                    func.median = calc::median_sorted(&func.calls);
+                    func.finalize();
                 }
             }
         }
         self
     }
+
+    /// Returns `true` if the tests of the report ran with different EVM versions, in which case
+    /// the EVM versions of each contract are reported since its gas usage depends on them.
+    fn mixes_evm_versions(&self) -> bool {
+        self.contracts
+            .values()
+            .flat_map(|contract| &contract.evm_versions)
+            .collect::<BTreeSet<_>>()
+            .len() >
+            1
+    }
+
+    /// Returns the finalized report in the stable format of `--gas-report-format json`.
+    pub fn to_json(&self) -> GasReportJson {
+        let mixes_evm_versions = self.mixes_evm_versions();
+        let contracts = self
+            .contracts
+            .iter()
+            .map(|(name, contract)| ContractGasJson {
+                contract: name.clone(),
+                kind: if contract.library { "library" } else { "contract" }.to_string(),
+                deployment: (!contract.deployments.calls.is_empty()).then(|| DeploymentGasJson {
+                    gas: GasJson::from(&contract.deployments),
+                    size: contract.size,
+                }),
+                functions: contract
+                    .functions
+                    .values()
+                    .flat_map(|sigs| sigs.iter())
+                    .map(|(signature, gas_info)| FunctionGasJson {
+                        signature: signature.clone(),
+                        gas: GasJson::from(gas_info),
+                        proxies: gas_info.proxies.iter().cloned().collect(),
+                    })
+                    .collect(),
+                evm_versions: if mixes_evm_versions {
+                    contract.evm_versions.iter().cloned().collect()
+                } else {
+                    vec![]
+                },
+            })
+            .collect();
+        GasReportJson { contracts }
+    }
+}
+
+/// Returns the top-level call to a proxy that forwarded its calldata to the DELEGATECALL `node`.
+///
+/// The calldata may have been forwarded through several proxies, e.g. a proxy which delegates to
+/// another proxy, so the whole chain of DELEGATECALLs up to the top-level call is walked.
+fn proxy_call<'a>(nodes: &'a [CallTraceNode], node: &CallTraceNode) -> Option<&'a CallTraceNode> {
+    if node.trace.kind != CallKind::DelegateCall {
+        return None;
+    }
+    let mut parent = &nodes[node.parent?];
+    while parent.trace.kind == CallKind::DelegateCall && parent.trace.data == node.trace.data {
+        parent = &nodes[parent.parent?];
+    }
+    (parent.trace.kind == CallKind::Call &&
+        parent.trace.depth == 1 &&
+        parent.trace.data == node.trace.data)
+        .then_some(parent)
 }
 
 impl Display for GasReport {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let mixes_evm_versions = self.mixes_evm_versions();
         for (name, contract) in &self.contracts {
             if contract.functions.is_empty() {
                 trace!(name, "gas report contract without functions");
                 continue
             }
 
+            let kind = if contract.library { "library" } else { "contract" };
+            let mut header = format!("{name} {kind}");
+            if mixes_evm_versions {
+                let evm_versions = contract.evm_versions.iter().cloned().collect::<Vec<_>>();
+                header = format!("{header} (EVM: {})", evm_versions.join(", "));
+            }
             let mut table = Table::new();
             table.load_preset(ASCII_MARKDOWN);
-            table.set_header([Cell::new(format!("{name} contract"))
-                .add_attribute(Attribute::Bold)
-                .fg(Color::Green)]);
-            table.add_row([
-                Cell::new("Deployment Cost").add_attribute(Attribute::Bold).fg(Color::Cyan),
-                Cell::new("Deployment Size").add_attribute(Attribute::Bold).fg(Color::Cyan),
-            ]);
-            table.add_row([contract.gas.to_string(), contract.size.to_string()]);
+            table.set_header([Cell::new(header).add_attribute(Attribute::Bold).fg(Color::Green)]);
+            // The deployment cost depends on the constructor arguments, so it's shown as a range
+            // when the tests deployed the contract with different ones.
+            let deployments = &contract.deployments;
+            if !deployments.calls.is_empty() {
+                table.add_row([
+                    Cell::new("Deployment Cost").add_attribute(Attribute::Bold).fg(Color::Cyan),
+                    Cell::new("Deployment Size").add_attribute(Attribute::Bold).fg(Color::Cyan),
+                    Cell::new("# deployments").add_attribute(Attribute::Bold),
+                ]);
+                let cost = if deployments.min == deployments.max {
+                    deployments.min.to_string()
+                } else {
+                    format!("{} - {}", deployments.min, deployments.max)
+                };
+                table.add_row([
+                    cost,
+                    contract.size.to_string(),
+                    deployments.calls.len().to_string(),
+                ]);
+            }
 
             table.add_row([
                 Cell::new("Function Name").add_attribute(Attribute::Bold).fg(Color::Magenta),
@@ -166,8 +286,12 @@ impl Display for GasReport {
             contract.functions.iter().for_each(|(fname, sigs)| {
                 sigs.iter().for_each(|(sig, gas_info)| {
                     // show function signature if overloaded else name
-                    let fn_display =
+                    let mut fn_display =
                         if sigs.len() == 1 { fname.clone() } else { sig.replace(':', "") };
+                    if !gas_info.proxies.is_empty() {
+                        let proxies = gas_info.proxies.iter().cloned().collect::<Vec<_>>();
+                        fn_display = format!("{fn_display} (via {})", proxies.join(", "));
+                    }
 
                     table.add_row([
                         Cell::new(fn_display).add_attribute(Attribute::Bold),
@@ -188,10 +312,19 @@ impl Display for GasReport {
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ContractInfo {
+    /// Whether the contract is a linked library, whose external functions are delegatecalled
+    pub library: bool,
+    /// The median deployment cost
     pub gas: u64,
+    /// The size of the init code of the last deployment, including the constructor arguments
     pub size: usize,
+    /// The cost of each deployment made by the tests
+    pub deployments: GasInfo,
     /// Function name -> Function signature -> GasInfo
     pub functions: BTreeMap<String, BTreeMap<String, GasInfo>>,
+    /// The EVM versions of the tests which called the contract
+    #[serde(default)]
+    pub evm_versions: BTreeSet<String>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -201,4 +334,77 @@ pub struct GasInfo {
     pub mean: u64,
     pub median: u64,
     pub max: u64,
+    /// The proxies the function was called through
+    pub proxies: BTreeSet<String>,
+}
+
+impl GasInfo {
+    fn finalize(&mut self) {
+        self.calls.sort_unstable();
+        self.min = self.calls.first().copied().unwrap_or_default();
+        self.max = self.calls.last().copied().unwrap_or_default();
+        self.mean = calc::mean(&self.calls);
+        self.median = calc::median_sorted(&self.calls);
+    }
+}
+
+/// A gas report in the JSON format of `--gas-report-format json`.
+///
+/// Unlike [GasReport], this format is stable so reports can be compared with `forge gas-diff`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasReportJson {
+    pub contracts: Vec<ContractGasJson>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractGasJson {
+    /// The contract identifier, e.g. `src/Counter.sol:Counter`
+    pub contract: String,
+    /// Either `contract` or `library`
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment: Option<DeploymentGasJson>,
+    pub functions: Vec<FunctionGasJson>,
+    /// The EVM versions of the tests which called the contract, only set when the tests of the
+    /// report ran with different EVM versions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evm_versions: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeploymentGasJson {
+    #[serde(flatten)]
+    pub gas: GasJson,
+    pub size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionGasJson {
+    pub signature: String,
+    #[serde(flatten)]
+    pub gas: GasJson,
+    /// The proxies the function was called through
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proxies: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasJson {
+    pub min: u64,
+    pub mean: u64,
+    pub median: u64,
+    pub max: u64,
+    pub calls: usize,
+}
+
+impl From<&GasInfo> for GasJson {
+    fn from(info: &GasInfo) -> Self {
+        Self {
+            min: info.min,
+            mean: info.mean,
+            median: info.median,
+            max: info.max,
+            calls: info.calls.len(),
+        }
+    }
 }
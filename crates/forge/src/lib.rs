@@ -1,13 +1,17 @@
 #[macro_use]
 extern crate tracing;
 
-use foundry_compilers::ProjectCompileOutput;
+use foundry_common::TestFunctionExt;
+use foundry_compilers::{EvmVersion, ProjectCompileOutput};
 use foundry_config::{
-    validate_profiles, Config, FuzzConfig, InlineConfig, InlineConfigError, InlineConfigParser,
-    InvariantConfig, NatSpec,
+    parse_config_evm_version, validate_profiles, Config, FuzzConfig, InlineConfig,
+    InlineConfigError, InlineConfigParser, InlineConfigParserError, InvariantConfig, NatSpec,
 };
 use proptest::test_runner::{RngAlgorithm, TestRng, TestRunner};
-use std::path::Path;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
 
 pub mod coverage;
 
@@ -38,27 +42,36 @@ pub struct TestOptions {
     pub inline_fuzz: InlineConfig<FuzzConfig>,
     /// Contains per-test specific "invariant" configurations.
     pub inline_invariant: InlineConfig<InvariantConfig>,
+    /// Contains per-test specific EVM versions.
+    pub inline_evm_version: InlineConfig<EvmVersion>,
+    /// The EVM versions of the test contracts whose tests run with an inline EVM version, by
+    /// contract identifier. The tests of a contract share an EVM, so they all run with it.
+    pub evm_versions: BTreeMap<String, EvmVersion>,
 }
 
 impl TestOptions {
     /// Tries to create a new instance by detecting inline configurations from the project compile
-    /// output.
+    /// outputs.
     pub fn new(
-        output: &ProjectCompileOutput,
+        outputs: &[&ProjectCompileOutput],
         root: &Path,
         profiles: Vec<String>,
         base_fuzz: FuzzConfig,
         base_invariant: InvariantConfig,
     ) -> Result<Self, InlineConfigError> {
-        let natspecs: Vec<NatSpec> = NatSpec::parse(output, root);
+        let mut natspecs: Vec<NatSpec> =
+            outputs.iter().flat_map(|output| NatSpec::parse(output, root)).collect();
         let mut inline_invariant = InlineConfig::<InvariantConfig>::default();
         let mut inline_fuzz = InlineConfig::<FuzzConfig>::default();
+        let mut inline_evm_version = InlineConfig::<EvmVersion>::default();
 
-        for natspec in natspecs {
+        // Contract configurations come first, so function configurations are merged into them.
+        natspecs.sort_by_key(|natspec| !natspec.function.is_empty());
+        for natspec in &natspecs {
             // Perform general validation
-            validate_profiles(&natspec, &profiles)?;
-            FuzzConfig::validate_configs(&natspec)?;
-            InvariantConfig::validate_configs(&natspec)?;
+            validate_profiles(natspec, &profiles)?;
+            FuzzConfig::validate_configs(natspec)?;
+            InvariantConfig::validate_configs(natspec)?;
 
             // Apply in-line configurations for the current profile
             let configs: Vec<String> = natspec.current_profile_configs().collect();
@@ -66,20 +79,37 @@ impl TestOptions {
             let f: &str = &natspec.function;
             let line: String = natspec.debug_context();
 
-            match base_fuzz.try_merge(&configs) {
+            match inline_fuzz.get(c, f).unwrap_or(&base_fuzz).try_merge(&configs) {
                 Ok(Some(conf)) => inline_fuzz.insert(c, f, conf),
                 Ok(None) => { /* No inline config found, do nothing */ }
                 Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
             }
 
-            match base_invariant.try_merge(&configs) {
+            match inline_invariant.get(c, f).unwrap_or(&base_invariant).try_merge(&configs) {
                 Ok(Some(conf)) => inline_invariant.insert(c, f, conf),
                 Ok(None) => { /* No inline config found, do nothing */ }
                 Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
             }
+
+            // Validate the EVM versions of every profile
+            let all_configs: Vec<String> = natspec.config_lines().collect();
+            match parse_config_evm_version(&all_configs).and(parse_config_evm_version(&configs)) {
+                Ok(Some(evm_version)) => inline_evm_version.insert(c, f, evm_version),
+                Ok(None) => { /* No inline config found, do nothing */ }
+                Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
+            }
         }
 
-        Ok(Self { fuzz: base_fuzz, invariant: base_invariant, inline_fuzz, inline_invariant })
+        let evm_versions = contract_evm_versions(outputs, root, &natspecs, &inline_evm_version)?;
+
+        Ok(Self {
+            fuzz: base_fuzz,
+            invariant: base_invariant,
+            inline_fuzz,
+            inline_invariant,
+            inline_evm_version,
+            evm_versions,
+        })
     }
 
     /// Returns a "fuzz" test runner instance. Parameters are used to select tight scoped fuzz
@@ -140,6 +170,15 @@ impl TestOptions {
         self.inline_invariant.get(contract_id, test_fn).unwrap_or(&self.invariant)
     }
 
+    /// Returns the inline EVM version of a test contract, if its tests don't run with the EVM
+    /// version of the profile.
+    ///
+    /// - `contract_id` is the id of the test contract, expressed as a relative path from the
+    ///   project root.
+    pub fn evm_version(&self, contract_id: &str) -> Option<EvmVersion> {
+        self.evm_versions.get(contract_id).copied()
+    }
+
     pub fn fuzzer_with_cases(&self, cases: u32) -> TestRunner {
         // TODO: Add Options to modify the persistence
         let config = proptest::test_runner::Config {
@@ -160,6 +199,57 @@ impl TestOptions {
     }
 }
 
+/// Returns the EVM version of each test contract with inline EVM versions.
+///
+/// The tests of a contract share an EVM, so every test function of the contract must resolve to
+/// the same EVM version, including the EVM version of the profile for tests without one.
+fn contract_evm_versions(
+    outputs: &[&ProjectCompileOutput],
+    root: &Path,
+    natspecs: &[NatSpec],
+    inline_evm_version: &InlineConfig<EvmVersion>,
+) -> Result<BTreeMap<String, EvmVersion>, InlineConfigError> {
+    let contracts = natspecs
+        .iter()
+        .filter(|natspec| inline_evm_version.get(&natspec.contract, &natspec.function).is_some())
+        .map(|natspec| natspec.contract.as_str())
+        .collect::<BTreeSet<_>>();
+
+    let mut evm_versions = BTreeMap::new();
+    for (id, artifact) in outputs.iter().flat_map(|output| output.artifact_ids()) {
+        let path = id.source.strip_prefix(root).unwrap_or(&id.source);
+        let contract = format!("{}:{}", path.display(), id.name);
+        if !contracts.contains(contract.as_str()) {
+            continue
+        }
+        let Some(abi) = &artifact.abi else { continue };
+
+        let mut tests = BTreeMap::<Option<EvmVersion>, Vec<&str>>::new();
+        for func in abi.functions().filter(|func| func.is_test() || func.is_invariant_test()) {
+            let evm_version = inline_evm_version.get(&contract, &func.name).copied();
+            tests.entry(evm_version).or_default().push(&func.name);
+        }
+        if tests.len() > 1 {
+            let versions = tests
+                .iter()
+                .map(|(evm_version, tests)| {
+                    let evm_version = evm_version
+                        .map_or_else(|| "the profile's".to_string(), |v| format!("`{v}`"));
+                    format!("{evm_version} for {}", tests.join(", "))
+                })
+                .collect::<Vec<_>>();
+            return Err(InlineConfigError {
+                line: contract,
+                source: InlineConfigParserError::MixedEvmVersions(versions.join("; ")),
+            })
+        }
+        if let Some((Some(evm_version), _)) = tests.into_iter().next() {
+            evm_versions.insert(contract, evm_version);
+        }
+    }
+    Ok(evm_versions)
+}
+
 /// Builder utility to create a [`TestOptions`] instance.
 #[derive(Default)]
 #[must_use = "builders do nothing unless you call `build` on them"]
@@ -199,31 +289,69 @@ impl TestOptionsBuilder {
         self,
         output: &ProjectCompileOutput,
         root: &Path,
+    ) -> Result<TestOptions, InlineConfigError> {
+        self.build_with_variants(output, &[], root)
+    }
+
+    /// Creates an instance of [`TestOptions`] like [`build`](Self::build), also extracting the
+    /// inline test configs of the sources compiled for another EVM version than the profile's.
+    pub fn build_with_variants(
+        self,
+        output: &ProjectCompileOutput,
+        evm_variants: &[(EvmVersion, ProjectCompileOutput)],
+        root: &Path,
     ) -> Result<TestOptions, InlineConfigError> {
         let profiles: Vec<String> =
             self.profiles.unwrap_or_else(|| vec![Config::selected_profile().into()]);
         let base_fuzz = self.fuzz.unwrap_or_default();
         let base_invariant = self.invariant.unwrap_or_default();
-        TestOptions::new(output, root, profiles, base_fuzz, base_invariant)
+        let outputs = std::iter::once(output)
+            .chain(evm_variants.iter().map(|(_, output)| output))
+            .collect::<Vec<_>>();
+        TestOptions::new(&outputs, root, profiles, base_fuzz, base_invariant)
     }
 }
 
 mod utils2 {
-    use alloy_primitives::Address;
-    use ethers_core::types::BlockId;
-    use ethers_providers::{Middleware, Provider};
+    use alloy_primitives::{Address, B256};
+    use ethers_core::types::{BlockId, BlockNumber};
+    use ethers_providers::Middleware;
     use eyre::Context;
-    use foundry_common::types::{ToAlloy, ToEthers};
+    use foundry_common::{
+        provider::ethers::try_get_http_provider,
+        types::{ToAlloy, ToEthers},
+    };
 
+    /// Returns the nonce of `caller`, from an HTTP, WebSocket or IPC endpoint.
     pub async fn next_nonce(
         caller: Address,
         provider_url: &str,
         block: Option<BlockId>,
     ) -> eyre::Result<u64> {
-        let provider = Provider::try_from(provider_url)
+        let provider = try_get_http_provider(provider_url)
             .wrap_err_with(|| format!("bad fork_url provider: {provider_url}"))?;
         let res = provider.get_transaction_count(caller.to_ethers(), block).await?.to_alloy();
         res.try_into().map_err(Into::into)
     }
+
+    /// Returns the nonce of `caller` after its pending transactions, from an HTTP, WebSocket or
+    /// IPC endpoint.
+    pub async fn next_pending_nonce(caller: Address, provider_url: &str) -> eyre::Result<u64> {
+        next_nonce(caller, provider_url, Some(BlockNumber::Pending.into())).await
+    }
+
+    /// Returns the hashes of the pending transactions of `caller` by nonce, if the endpoint
+    /// supports the `txpool_content` API.
+    pub async fn pending_transactions(caller: Address, provider_url: &str) -> Vec<B256> {
+        let Ok(provider) = try_get_http_provider(provider_url) else { return vec![] };
+        let Ok(content) = provider.txpool_content().await else { return vec![] };
+        let mut txs = content
+            .pending
+            .get(&caller.to_ethers())
+            .map(|txs| txs.values().collect::<Vec<_>>())
+            .unwrap_or_default();
+        txs.sort_by_key(|tx| tx.nonce);
+        txs.into_iter().map(|tx| tx.hash.to_alloy()).collect()
+    }
 }
 pub use utils2::*;
@@ -4,22 +4,30 @@ use crate::{result::SuiteResult, ContractRunner, TestFilter, TestOptions};
 use alloy_json_abi::{Function, JsonAbi};
 use alloy_primitives::{Address, Bytes, U256};
 use eyre::Result;
-use foundry_common::{get_contract_name, ContractsByArtifact, TestFunctionExt};
-use foundry_compilers::{contracts::ArtifactContracts, Artifact, ArtifactId, ProjectCompileOutput};
+use foundry_common::{
+    compile::ContractSources, get_contract_name, ContractsByArtifact, TestFunctionExt,
+};
+use foundry_compilers::{
+    artifacts::StorageLayout, contracts::ArtifactContracts, Artifact, ArtifactId,
+    ConfigurableContractArtifact, EvmVersion, ProjectCompileOutput,
+};
+use foundry_config::evm_spec_id;
 use foundry_evm::{
     backend::Backend,
     decode::RevertDecoder,
-    executors::{Executor, ExecutorBuilder},
-    fork::CreateFork,
+    executors::{Executor, ExecutorBuilder, FailFast},
+    fork::{CreateFork, MultiFork},
     inspectors::CheatsConfig,
     opts::EvmOpts,
     revm,
+    traces::{artifact_internal_functions, InternalFunctions, StackTraces},
 };
 use foundry_linking::{LinkOutput, Linker};
 use rayon::prelude::*;
+use regex::Regex;
 use revm::primitives::SpecId;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     fmt::Debug,
     path::Path,
     sync::{mpsc, Arc},
@@ -36,6 +44,12 @@ pub struct MultiContractRunner {
     pub contracts: DeployableContracts,
     /// Compiled contracts by name that have an JsonAbi and runtime bytecode
     pub known_contracts: ContractsByArtifact,
+    /// Storage layouts by contract identifier, if they were part of the compiler output
+    pub storage_layouts: BTreeMap<String, StorageLayout>,
+    /// Internal functions by artifact identifier, only collected to decode internal calls
+    pub internal_functions: HashMap<String, InternalFunctions>,
+    /// Source locations of the contracts, only collected to render the stack traces of failures
+    pub source_locations: Option<StackTraces>,
     /// The EVM instance used in the test runner
     pub evm_opts: EvmOpts,
     /// The configured evm
@@ -56,10 +70,16 @@ pub struct MultiContractRunner {
     pub coverage: bool,
     /// Whether to collect debug info
     pub debug: bool,
+    /// The tests whose internal function calls are decoded in the traces
+    pub decode_internal: Option<Regex>,
+    /// Whether to trace the internal function calls of all the tests, to resolve stack traces
+    pub stack_traces: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: TestOptions,
     /// Whether to enable call isolation
     pub isolation: bool,
+    /// Cancels the remaining tests after the first failure, if enabled
+    pub fail_fast: FailFast,
 }
 
 impl MultiContractRunner {
@@ -145,15 +165,20 @@ impl MultiContractRunner {
         trace!("running all tests");
 
         // The DB backend that serves all the data.
-        let db = Backend::spawn(self.fork.take());
+        let forks = MultiFork::spawn();
+        // With fail-fast, the first failure aborts the RPC requests the other tests wait for
+        self.fail_fast.set_forks(Some(forks.clone()));
+        let db = Backend::new(forks, self.fork.take());
         let executor = ExecutorBuilder::new()
             .inspectors(|stack| {
                 stack
                     .cheatcodes(self.cheats_config.clone())
                     .trace(self.evm_opts.verbosity >= 3 || self.debug)
+                    .trace_state_changes(self.evm_opts.verbosity >= 5)
                     .debug(self.debug)
                     .coverage(self.coverage)
                     .enable_isolation(self.isolation)
+                    .isolate_block_advance(self.evm_opts.isolate_block_advance)
             })
             .spec(self.evm_spec)
             .gas_limit(self.evm_opts.gas_limit())
@@ -171,10 +196,16 @@ impl MultiContractRunner {
 
         contracts.par_iter().for_each_with(tx, |tx, &(id, (abi, deploy_code, libs))| {
             let identifier = id.identifier();
-            let executor = executor.clone();
+            let mut executor = executor.clone();
+            if let Some(evm_version) = self.test_options.evm_version(&identifier) {
+                executor.env.cfg.spec_id = evm_spec_id(&evm_version);
+            }
             let result = self.run_tests(&identifier, abi, executor, deploy_code, libs, filter);
             let _ = tx.send((identifier, result));
-        })
+        });
+
+        // Releases the forks, so their caches are flushed
+        self.fail_fast.set_forks(None);
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -205,6 +236,9 @@ impl MultiContractRunner {
             &self.revert_decoder,
             libs,
             self.debug,
+            self.decode_internal.as_ref(),
+            self.stack_traces,
+            &self.fail_fast,
         );
         let r = runner.run_tests(filter, &self.test_options, Some(&self.known_contracts));
 
@@ -233,10 +267,20 @@ pub struct MultiContractRunnerBuilder {
     pub coverage: bool,
     /// Whether or not to collect debug info
     pub debug: bool,
+    /// The tests whose internal function calls are decoded in the traces
+    pub decode_internal: Option<Regex>,
+    /// Whether to render the stack traces of failures
+    pub stack_traces: bool,
     /// Whether to enable call isolation
     pub isolation: bool,
+    /// Whether to cancel the remaining tests after the first failure
+    pub fail_fast: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: Option<TestOptions>,
+    /// The artifacts of the Vyper sources, which aren't part of the solc output
+    pub vyper_artifacts: Vec<(ArtifactId, ConfigurableContractArtifact)>,
+    /// The test contracts compiled for another EVM version than the profile's, by EVM version
+    pub evm_variants: Vec<(EvmVersion, ProjectCompileOutput)>,
 }
 
 impl MultiContractRunnerBuilder {
@@ -280,11 +324,41 @@ impl MultiContractRunnerBuilder {
         self
     }
 
+    pub fn decode_internal(mut self, tests: Option<Regex>) -> Self {
+        self.decode_internal = tests;
+        self
+    }
+
+    pub fn stack_traces(mut self, enable: bool) -> Self {
+        self.stack_traces = enable;
+        self
+    }
+
     pub fn enable_isolation(mut self, enable: bool) -> Self {
         self.isolation = enable;
         self
     }
 
+    pub fn fail_fast(mut self, enable: bool) -> Self {
+        self.fail_fast = enable;
+        self
+    }
+
+    pub fn with_vyper_artifacts(
+        mut self,
+        artifacts: Vec<(ArtifactId, ConfigurableContractArtifact)>,
+    ) -> Self {
+        self.vyper_artifacts = artifacts;
+        self
+    }
+
+    /// Sets the outputs of the sources compiled for the inline EVM versions of [`TestOptions`]
+    /// instead of the profile's EVM version.
+    pub fn with_evm_variants(mut self, variants: Vec<(EvmVersion, ProjectCompileOutput)>) -> Self {
+        self.evm_variants = variants;
+        self
+    }
+
     /// Given an EVM, proceeds to return a runner which is able to execute all tests
     /// against that evm
     pub fn build(
@@ -294,66 +368,100 @@ impl MultiContractRunnerBuilder {
         env: revm::primitives::Env,
         evm_opts: EvmOpts,
     ) -> Result<MultiContractRunner> {
+        let output = output.with_stripped_file_prefixes(root);
+        let sources = if self.decode_internal.is_some() || self.stack_traces {
+            Some(ContractSources::from_project_output(&output, root)?)
+        } else {
+            None
+        };
+        let internal_functions = match (&self.decode_internal, &sources) {
+            (Some(_), Some(sources)) => artifact_internal_functions(output.artifact_ids(), sources),
+            _ => HashMap::new(),
+        };
+        let source_locations = sources
+            .as_ref()
+            .filter(|_| self.stack_traces)
+            .map(|sources| StackTraces::new(output.artifact_ids(), sources));
+
         // This is just the contracts compiled, but we need to merge this with the read cached
         // artifacts.
+        let mut storage_layouts = BTreeMap::new();
         let contracts = output
-            .with_stripped_file_prefixes(root)
             .into_artifacts()
-            .map(|(i, c)| (i, c.into_contract_bytecode()))
+            .chain(self.vyper_artifacts)
+            .map(|(i, c)| {
+                if let Some(layout) = &c.storage_layout {
+                    storage_layouts.insert(i.identifier(), layout.clone());
+                }
+                (i, c.into_contract_bytecode())
+            })
             .collect::<ArtifactContracts>();
 
-        let source_paths = contracts
+        let mut source_paths = contracts
             .iter()
             .map(|(i, _)| (i.identifier(), root.join(&i.source).to_string_lossy().into()))
             .collect::<BTreeMap<String, String>>();
 
-        let linker = Linker::new(root, contracts);
-
-        // Create a mapping of name => (abi, deployment code, Vec<library deployment code>)
-        let mut deployable_contracts = DeployableContracts::default();
-
-        let mut known_contracts = ContractsByArtifact::default();
-
-        for (id, contract) in &linker.contracts.0 {
-            let Some(abi) = contract.abi.as_ref() else {
-                continue;
-            };
-
-            let LinkOutput { libs_to_deploy, libraries } =
-                linker.link_with_nonce_or_address(Default::default(), evm_opts.sender, 1, id)?;
-
-            let linked_contract = linker.link(id, &libraries)?;
-
-            // get bytes if deployable, else add to known contracts and continue.
-            // interfaces and abstract contracts should be known to enable fuzzing of their ABI
-            // but they should not be deployable and their source code should be skipped by the
-            // debugger and linker.
-            let Some(bytecode) = linked_contract
-                .get_bytecode_bytes()
-                .map(|b| b.into_owned())
-                .filter(|b| !b.is_empty())
-            else {
-                known_contracts.insert(id.clone(), (abi.clone(), vec![]));
-                continue;
-            };
-
-            // if it's a test, add it to deployable contracts
-            if abi.constructor.as_ref().map(|c| c.inputs.is_empty()).unwrap_or(true) &&
-                abi.functions().any(|func| func.name.is_test() || func.name.is_invariant_test())
-            {
-                deployable_contracts.insert(id.clone(), (abi.clone(), bytecode, libs_to_deploy));
+        let (mut deployable_contracts, mut known_contracts) =
+            link_contracts(root, contracts, evm_opts.sender)?;
+
+        // Add the contracts of the sources compiled for another EVM version than the profile's,
+        // replacing the contracts which run with it. The test contracts of those sources without
+        // an inline EVM version run with the EVM version they're compiled for.
+        let mut test_options = self.test_options.unwrap_or_default();
+        for (evm_version, output) in self.evm_variants {
+            let contracts = output
+                .with_stripped_file_prefixes(root)
+                .into_artifacts()
+                .map(|(i, c)| {
+                    if let Some(layout) = &c.storage_layout {
+                        storage_layouts.entry(i.identifier()).or_insert_with(|| layout.clone());
+                    }
+                    source_paths
+                        .entry(i.identifier())
+                        .or_insert_with(|| root.join(&i.source).to_string_lossy().into());
+                    (i, c.into_contract_bytecode())
+                })
+                .collect::<ArtifactContracts>();
+            let (variants, variant_known_contracts) =
+                link_contracts(root, contracts, evm_opts.sender)?;
+
+            let mut replaced = BTreeSet::new();
+            for (id, contract) in variants {
+                let identifier = id.identifier();
+                let runs_with_variant = match test_options.evm_version(&identifier) {
+                    Some(version) => version == evm_version,
+                    None => !deployable_contracts.keys().any(|id| id.identifier() == identifier),
+                };
+                if !runs_with_variant {
+                    continue
+                }
+                test_options.evm_versions.insert(identifier.clone(), evm_version);
+                deployable_contracts.retain(|other, _| other.identifier() != identifier);
+                deployable_contracts.insert(id, contract);
+                replaced.insert(identifier);
             }
-
-            if let Some(bytes) = linked_contract.get_deployed_bytecode_bytes() {
-                known_contracts.insert(id.clone(), (abi.clone(), bytes.to_vec()));
+            for (id, contract) in variant_known_contracts.0 {
+                let identifier = id.identifier();
+                if replaced.contains(&identifier) {
+                    known_contracts.retain(|other, _| other.identifier() != identifier);
+                } else if known_contracts.keys().any(|other| other.identifier() == identifier) {
+                    continue
+                }
+                known_contracts.insert(id, contract);
             }
         }
 
         let revert_decoder =
             RevertDecoder::new().with_abis(known_contracts.values().map(|(abi, _)| abi));
+        let mut cheats_config = self.cheats_config.unwrap_or_default();
+        cheats_config.revert_decoder = revert_decoder.clone();
         Ok(MultiContractRunner {
             contracts: deployable_contracts,
             known_contracts,
+            storage_layouts,
+            internal_functions,
+            source_locations,
             evm_opts,
             env,
             evm_spec: self.evm_spec.unwrap_or(SpecId::MERGE),
@@ -361,15 +469,66 @@ impl MultiContractRunnerBuilder {
             revert_decoder,
             source_paths,
             fork: self.fork,
-            cheats_config: self.cheats_config.unwrap_or_default().into(),
+            cheats_config: cheats_config.into(),
             coverage: self.coverage,
             debug: self.debug,
-            test_options: self.test_options.unwrap_or_default(),
+            decode_internal: self.decode_internal,
+            stack_traces: self.stack_traces,
+            test_options,
             isolation: self.isolation,
+            fail_fast: FailFast::new(self.fail_fast),
         })
     }
 }
 
+/// Links the contracts, and returns the deployable test contracts and the known contracts.
+fn link_contracts(
+    root: &Path,
+    contracts: ArtifactContracts,
+    sender: Address,
+) -> Result<(DeployableContracts, ContractsByArtifact)> {
+    let linker = Linker::new(root, contracts);
+
+    // Create a mapping of name => (abi, deployment code, Vec<library deployment code>)
+    let mut deployable_contracts = DeployableContracts::default();
+
+    let mut known_contracts = ContractsByArtifact::default();
+
+    for (id, contract) in &linker.contracts.0 {
+        let Some(abi) = contract.abi.as_ref() else {
+            continue;
+        };
+
+        let LinkOutput { libs_to_deploy, libraries } =
+            linker.link_with_nonce_or_address(Default::default(), sender, 1, id)?;
+
+        let linked_contract = linker.link(id, &libraries)?;
+
+        // get bytes if deployable, else add to known contracts and continue.
+        // interfaces and abstract contracts should be known to enable fuzzing of their ABI
+        // but they should not be deployable and their source code should be skipped by the
+        // debugger and linker.
+        let Some(bytecode) =
+            linked_contract.get_bytecode_bytes().map(|b| b.into_owned()).filter(|b| !b.is_empty())
+        else {
+            known_contracts.insert(id.clone(), (abi.clone(), vec![]));
+            continue;
+        };
+
+        // if it's a test, add it to deployable contracts
+        if abi.constructor.as_ref().map(|c| c.inputs.is_empty()).unwrap_or(true) &&
+            abi.functions().any(|func| func.name.is_test() || func.name.is_invariant_test())
+        {
+            deployable_contracts.insert(id.clone(), (abi.clone(), bytecode, libs_to_deploy));
+        }
+
+        if let Some(bytes) = linked_contract.get_deployed_bytecode_bytes() {
+            known_contracts.insert(id.clone(), (abi.clone(), bytes.to_vec()));
+        }
+    }
+    Ok((deployable_contracts, known_contracts))
+}
+
 fn matches_contract(id: &ArtifactId, abi: &JsonAbi, filter: &dyn TestFilter) -> bool {
     (filter.matches_path(&id.source) && filter.matches_contract(&id.name)) &&
         abi.functions().any(|func| is_matching_test(func, filter))
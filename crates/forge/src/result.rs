@@ -5,9 +5,10 @@ use foundry_common::{evm::Breakpoints, get_contract_name, get_file_name, shell};
 use foundry_evm::{
     coverage::HitMaps,
     debug::DebugArena,
-    executors::EvmError,
+    executors::{EvmError, FailFast},
+    fork::FetchStats,
     fuzz::{CounterExample, FuzzCase},
-    traces::{CallTraceDecoder, TraceKind, Traces},
+    traces::{CallStateChanges, CallTraceDecoder, TraceKind, Traces},
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -372,13 +373,29 @@ pub struct TestResult {
 
     /// pc breakpoint char map
     pub breakpoints: Breakpoints,
+
+    /// The named gas measurements taken with the `snapshotGas` cheatcodes
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub gas_snapshots: BTreeMap<String, u64>,
+
+    /// The values fetched from the fork endpoint during the test
+    #[serde(skip)]
+    pub fetch_stats: FetchStats,
+
+    /// The state changes of each call of the traces, only collected for the JSON output with
+    /// the highest verbosity
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub state_changes: Vec<(TraceKind, Vec<CallStateChanges>)>,
 }
 
 impl fmt::Display for TestResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.status {
             TestStatus::Success => Paint::green("[PASS]").fmt(f),
-            TestStatus::Skipped => Paint::yellow("[SKIP]").fmt(f),
+            TestStatus::Skipped => match &self.reason {
+                Some(reason) => Paint::yellow(format!("[SKIP ({reason})]")).fmt(f),
+                None => Paint::yellow("[SKIP]").fmt(f),
+            },
             TestStatus::Failure => {
                 let mut s = String::from("[FAIL. Reason: ");
 
@@ -412,6 +429,17 @@ impl TestResult {
         Self { status: TestStatus::Failure, reason: Some(reason), ..Default::default() }
     }
 
+    /// Returns the result of a test that was cancelled, or not started, because another test
+    /// failed with `--fail-fast`.
+    pub fn cancelled(kind: TestKind) -> Self {
+        Self {
+            status: TestStatus::Skipped,
+            reason: Some(FailFast::REASON.to_string()),
+            kind,
+            ..Default::default()
+        }
+    }
+
     /// Returns `true` if this is the result of a fuzz test
     pub fn is_fuzz(&self) -> bool {
         matches!(self.kind, TestKind::Fuzz { .. })
@@ -428,7 +456,7 @@ impl TestResult {
 pub enum TestKindReport {
     Standard { gas: u64 },
     Fuzz { runs: usize, mean_gas: u64, median_gas: u64 },
-    Invariant { runs: usize, calls: usize, reverts: usize },
+    Invariant { runs: usize, calls: usize, reverts: usize, time_elapsed: u64 },
 }
 
 impl fmt::Display for TestKindReport {
@@ -440,8 +468,12 @@ impl fmt::Display for TestKindReport {
             TestKindReport::Fuzz { runs, mean_gas, median_gas } => {
                 write!(f, "(runs: {runs}, μ: {mean_gas}, ~: {median_gas})")
             }
-            TestKindReport::Invariant { runs, calls, reverts } => {
-                write!(f, "(runs: {runs}, calls: {calls}, reverts: {reverts})")
+            TestKindReport::Invariant { runs, calls, reverts, time_elapsed } => {
+                write!(f, "(runs: {runs}, calls: {calls}, reverts: {reverts}")?;
+                if *time_elapsed > 0 {
+                    write!(f, ", time: {time_elapsed}s")?;
+                }
+                write!(f, ")")
             }
         }
     }
@@ -476,7 +508,9 @@ pub enum TestKind {
         median_gas: u64,
     },
     /// A solidity invariant test, that stores all test cases
-    Invariant { runs: usize, calls: usize, reverts: usize },
+    ///
+    /// Holds the seconds the block jumps warped the chain by across all runs
+    Invariant { runs: usize, calls: usize, reverts: usize, time_elapsed: u64 },
 }
 
 impl Default for TestKind {
@@ -493,8 +527,13 @@ impl TestKind {
             TestKind::Fuzz { runs, mean_gas, median_gas, .. } => {
                 TestKindReport::Fuzz { runs: *runs, mean_gas: *mean_gas, median_gas: *median_gas }
             }
-            TestKind::Invariant { runs, calls, reverts } => {
-                TestKindReport::Invariant { runs: *runs, calls: *calls, reverts: *reverts }
+            TestKind::Invariant { runs, calls, reverts, time_elapsed } => {
+                TestKindReport::Invariant {
+                    runs: *runs,
+                    calls: *calls,
+                    reverts: *reverts,
+                    time_elapsed: *time_elapsed,
+                }
             }
         }
     }
@@ -20,16 +20,18 @@ use foundry_evm::{
     executors::{
         fuzz::{CaseOutcome, CounterExampleOutcome, FuzzOutcome, FuzzedExecutor},
         invariant::{replay_run, InvariantExecutor, InvariantFuzzError, InvariantFuzzTestResult},
-        CallResult, EvmError, ExecutionErr, Executor,
+        CallResult, EvmError, ExecutionErr, Executor, FailFast,
     },
+    fork::FetchStats,
     fuzz::{invariant::InvariantContract, CounterExample},
-    traces::{load_contracts, TraceKind},
+    traces::{load_contracts, TraceArena, TraceKind},
 };
 use proptest::test_runner::TestRunner;
 use rayon::prelude::*;
+use regex::Regex;
 use std::{
     collections::{BTreeMap, HashMap},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 /// A type that executes all tests of a contract
@@ -52,6 +54,13 @@ pub struct ContractRunner<'a> {
     pub sender: Address,
     /// Should generate debug traces
     pub debug: bool,
+    /// The tests whose internal function calls are decoded in the traces
+    pub decode_internal: Option<&'a Regex>,
+    /// Whether to trace the internal function calls of the deployment, `setUp` and the failed
+    /// tests, to resolve stack traces
+    pub stack_traces: bool,
+    /// Cancels the remaining tests after the first failure, if enabled
+    pub fail_fast: &'a FailFast,
 }
 
 impl<'a> ContractRunner<'a> {
@@ -66,6 +75,9 @@ impl<'a> ContractRunner<'a> {
         revert_decoder: &'a RevertDecoder,
         predeploy_libs: &'a [Bytes],
         debug: bool,
+        decode_internal: Option<&'a Regex>,
+        stack_traces: bool,
+        fail_fast: &'a FailFast,
     ) -> Self {
         Self {
             name,
@@ -77,6 +89,33 @@ impl<'a> ContractRunner<'a> {
             revert_decoder,
             predeploy_libs,
             debug,
+            decode_internal,
+            stack_traces,
+            fail_fast,
+        }
+    }
+
+    /// Returns whether the internal function calls of the test should be traced, to decode them.
+    fn decodes_internal_calls(&self, func: &Function) -> bool {
+        self.decode_internal.map_or(false, |tests| tests.is_match(&func.name))
+    }
+
+    /// Re-runs the failed unit test with its internal function calls traced, and returns its
+    /// execution traces, to resolve its stack trace.
+    fn traced_execution(&self, address: Address, func: &Function) -> Option<TraceArena> {
+        let mut executor = self.executor.clone();
+        executor.inspector.tracing_internal_calls(true);
+        match executor.execute_test::<_, _>(
+            self.sender,
+            address,
+            func.clone(),
+            vec![],
+            U256::ZERO,
+            Some(self.revert_decoder),
+        ) {
+            Ok(CallResult { traces, .. }) => traces,
+            Err(EvmError::Execution(err)) => err.traces,
+            Err(_) => None,
         }
     }
 }
@@ -182,6 +221,22 @@ impl<'a> ContractRunner<'a> {
         Ok(setup)
     }
 
+    /// Returns the suite result of the matching tests, cancelled by the failure of another test
+    fn cancelled_suite(
+        &self,
+        filter: &dyn TestFilter,
+        duration: Duration,
+        warnings: Vec<String>,
+    ) -> SuiteResult {
+        let test_results = self
+            .contract
+            .functions()
+            .filter(|func| is_matching_test(func, filter))
+            .map(|func| (func.signature(), TestResult::cancelled(test_kind(func))))
+            .collect();
+        SuiteResult::new(duration, test_results, warnings)
+    }
+
     /// Runs all tests for a contract whose names match the provided regular expression
     pub fn run_tests(
         mut self,
@@ -218,6 +273,11 @@ impl<'a> ContractRunner<'a> {
             )
         }
 
+        // Another suite already failed, so we don't even deploy the test contract
+        if self.fail_fast.should_stop() {
+            return self.cancelled_suite(filter, start.elapsed(), warnings)
+        }
+
         let has_invariants = self.contract.functions().any(|func| func.is_invariant_test());
 
         // Invariant testing requires tracing to figure out what contracts were created.
@@ -225,11 +285,26 @@ impl<'a> ContractRunner<'a> {
         if tmp_tracing {
             self.executor.set_tracing(true);
         }
+        // The deployment and `setUp` trace the internal function calls, for the stack traces of
+        // their failures.
+        let tracer = self.stack_traces.then(|| self.executor.inspector.tracer.take());
+        if self.stack_traces {
+            self.executor.inspector.tracing_internal_calls(true);
+        }
         let setup = self.setup(needs_setup);
+        if let Some(tracer) = tracer {
+            self.executor.inspector.tracer = tracer;
+        }
         if tmp_tracing {
             self.executor.set_tracing(false);
         }
 
+        // A setUp failing after another test failed may have been cancelled by it, e.g. while
+        // forking
+        if setup.reason.is_some() && !self.fail_fast.record_fail() {
+            return self.cancelled_suite(filter, start.elapsed(), warnings)
+        }
+
         if setup.reason.is_some() {
             // The setup failed, so we return a single test result for `setUp`
             return SuiteResult::new(
@@ -276,10 +351,15 @@ impl<'a> ContractRunner<'a> {
             .par_iter()
             .map(|&func| {
                 let sig = func.signature();
+                if self.fail_fast.should_stop() {
+                    return (sig, TestResult::cancelled(test_kind(func)))
+                }
 
                 let setup = setup.clone();
                 let should_fail = func.is_test_fail();
-                let res = if func.is_invariant_test() {
+                // the test runs on this thread, discard the fetches of the previous one
+                FetchStats::take();
+                let mut res = if func.is_invariant_test() {
                     let runner = test_options.invariant_runner(self.name, &func.name);
                     let invariant_config = test_options.invariant_config(self.name, &func.name);
                     self.run_invariant_test(
@@ -299,6 +379,12 @@ impl<'a> ContractRunner<'a> {
                     debug_assert!(func.is_test());
                     self.run_test(func, should_fail, setup)
                 };
+                res.fetch_stats = FetchStats::take();
+                // Failing after another test failed, the test may have been cancelled by it, e.g.
+                // while waiting for a fork's RPC
+                if res.status.is_failure() && !self.fail_fast.record_fail() {
+                    res = TestResult { duration: res.duration, ..TestResult::cancelled(res.kind) };
+                }
 
                 (sig, res)
             })
@@ -339,9 +425,10 @@ impl<'a> ContractRunner<'a> {
 
         // Run unit test
         let mut executor = self.executor.clone();
+        executor.inspector.tracing_internal_calls(self.decodes_internal_calls(func));
         let start = Instant::now();
         let debug_arena;
-        let (reverted, reason, gas, stipend, coverage, state_changeset, breakpoints) =
+        let (reverted, reason, gas, stipend, coverage, state_changeset, breakpoints, gas_snapshots) =
             match executor.execute_test::<_, _>(
                 self.sender,
                 address,
@@ -361,6 +448,7 @@ impl<'a> ContractRunner<'a> {
                     state_changeset,
                     debug,
                     breakpoints,
+                    gas_snapshots,
                     ..
                 }) => {
                     traces.extend(execution_trace.map(|traces| (TraceKind::Execution, traces)));
@@ -369,7 +457,16 @@ impl<'a> ContractRunner<'a> {
                     debug_arena = debug;
                     coverage = merge_coverages(coverage, execution_coverage);
 
-                    (reverted, None, gas, stipend, coverage, state_changeset, breakpoints)
+                    (
+                        reverted,
+                        None,
+                        gas,
+                        stipend,
+                        coverage,
+                        state_changeset,
+                        breakpoints,
+                        gas_snapshots,
+                    )
                 }
                 Err(EvmError::Execution(err)) => {
                     traces.extend(err.traces.map(|traces| (TraceKind::Execution, traces)));
@@ -384,6 +481,7 @@ impl<'a> ContractRunner<'a> {
                         None,
                         err.state_changeset,
                         HashMap::new(),
+                        BTreeMap::new(),
                     )
                 }
                 Err(EvmError::SkipError) => {
@@ -423,6 +521,18 @@ impl<'a> ContractRunner<'a> {
         let duration = start.elapsed();
         trace!(?duration, gas, reverted, should_fail, success);
 
+        // only the failed tests are re-run with their internal function calls traced
+        if !success && self.stack_traces && !self.decodes_internal_calls(func) {
+            if let Some(arena) = self.traced_execution(address, func) {
+                let execution =
+                    traces.iter_mut().rev().find(|(kind, _)| *kind == TraceKind::Execution);
+                match execution {
+                    Some((_, traces)) => *traces = arena,
+                    None => traces.push((TraceKind::Execution, arena)),
+                }
+            }
+        }
+
         TestResult {
             status: match success {
                 true => TestStatus::Success,
@@ -438,7 +548,10 @@ impl<'a> ContractRunner<'a> {
             labeled_addresses,
             debug: debug_arena,
             breakpoints,
+            gas_snapshots,
             duration,
+            state_changes: Vec::new(),
+            fetch_stats: FetchStats::default(),
         }
     }
 
@@ -473,7 +586,7 @@ impl<'a> ContractRunner<'a> {
                 decoded_logs: decode_console_logs(&logs),
                 traces,
                 labeled_addresses,
-                kind: TestKind::Invariant { runs: 1, calls: 1, reverts: 1 },
+                kind: TestKind::Invariant { runs: 1, calls: 1, reverts: 1, time_elapsed: 0 },
                 coverage,
                 duration: start.elapsed(),
                 ..Default::default()
@@ -486,14 +599,20 @@ impl<'a> ContractRunner<'a> {
             invariant_config,
             identified_contracts,
             project_contracts,
+            self.fail_fast.clone(),
         );
 
         let invariant_contract =
             InvariantContract { address, invariant_function: func, abi: self.contract };
 
-        let InvariantFuzzTestResult { error, cases, reverts, last_run_inputs } = match evm
-            .invariant_fuzz(invariant_contract.clone())
-        {
+        let InvariantFuzzTestResult {
+            error,
+            cases,
+            reverts,
+            last_run_inputs,
+            time_elapsed,
+            cancelled,
+        } = match evm.invariant_fuzz(invariant_contract.clone()) {
             Ok(x) => x,
             Err(e) => {
                 return TestResult {
@@ -502,13 +621,25 @@ impl<'a> ContractRunner<'a> {
                     decoded_logs: decode_console_logs(&logs),
                     traces,
                     labeled_addresses,
-                    kind: TestKind::Invariant { runs: 0, calls: 0, reverts: 0 },
+                    kind: TestKind::Invariant { runs: 0, calls: 0, reverts: 0, time_elapsed: 0 },
                     duration: start.elapsed(),
                     ..Default::default()
                 }
             }
         };
 
+        if cancelled {
+            return TestResult {
+                duration: start.elapsed(),
+                ..TestResult::cancelled(TestKind::Invariant {
+                    runs: cases.len(),
+                    calls: cases.iter().map(|sequence| sequence.cases().len()).sum(),
+                    reverts,
+                    time_elapsed,
+                })
+            }
+        }
+
         let mut counterexample = None;
         let mut logs = logs.clone();
         let mut traces = traces.clone();
@@ -566,6 +697,7 @@ impl<'a> ContractRunner<'a> {
                 runs: cases.len(),
                 calls: cases.iter().map(|sequence| sequence.cases().len()).sum(),
                 reverts,
+                time_elapsed,
             },
             coverage,
             traces,
@@ -601,14 +733,31 @@ impl<'a> ContractRunner<'a> {
 
         // Run fuzz test
         let start = Instant::now();
-        let fuzzed_executor =
-            FuzzedExecutor::new(self.executor.clone(), runner.clone(), self.sender, fuzz_config);
+        let fuzzed_executor = FuzzedExecutor::new(
+            self.executor.clone(),
+            runner.clone(),
+            self.sender,
+            fuzz_config,
+            self.fail_fast.clone(),
+        );
         let state = fuzzed_executor.build_fuzz_state();
-        let result = fuzzed_executor.fuzz(func, address, should_fail, self.revert_decoder);
+        let mut result = fuzzed_executor.fuzz(func, address, should_fail, self.revert_decoder);
 
         let mut debug = Default::default();
         let mut breakpoints = Default::default();
 
+        if result.cancelled {
+            return TestResult {
+                duration: start.elapsed(),
+                ..TestResult::cancelled(TestKind::Fuzz {
+                    median_gas: result.median_gas(false),
+                    mean_gas: result.mean_gas(false),
+                    first_case: result.first_case,
+                    runs: result.gas_by_case.len(),
+                })
+            }
+        }
+
         // Check the last test result and skip the test
         // if it's marked as so.
         if let Some("SKIPPED") = result.reason.as_deref() {
@@ -627,26 +776,51 @@ impl<'a> ContractRunner<'a> {
             }
         }
 
+        // the calldata of the last relevant case, only invariant tests have call sequences
+        let calldata = match &result.counterexample {
+            Some(CounterExample::Single(ce)) => Some(ce.calldata.clone()),
+            Some(CounterExample::Sequence(_)) => None,
+            None => Some(result.first_case.calldata.clone()),
+        };
+
+        // rerun the last relevant case with the internal function calls traced, to decode them or
+        // to resolve the stack trace of the failure
+        let traces_internal_calls =
+            self.decodes_internal_calls(func) || (self.stack_traces && !result.success);
+        if let Some(calldata) = calldata.as_ref().filter(|_| traces_internal_calls) {
+            let mut executor = self.executor.clone();
+            executor.inspector.tracing_internal_calls(true);
+            let outcome = FuzzedExecutor::new(
+                executor,
+                runner.clone(),
+                self.sender,
+                fuzz_config,
+                Default::default(),
+            )
+            .single_fuzz(&state, address, should_fail, calldata.clone());
+            match outcome {
+                Ok(FuzzOutcome::Case(CaseOutcome { traces, .. })) => result.traces = traces,
+                Ok(FuzzOutcome::CounterExample(CounterExampleOutcome {
+                    counterexample: (_, call),
+                    ..
+                })) => result.traces = call.traces,
+                Err(_) => {}
+            }
+        }
+
         // if should debug
-        if self.debug {
+        if let Some(calldata) = calldata.filter(|_| self.debug) {
             let mut debug_executor = self.executor.clone();
             // turn the debug traces on
             debug_executor.inspector.enable_debugger(true);
             debug_executor.inspector.tracing(true);
-            let calldata = if let Some(counterexample) = result.counterexample.as_ref() {
-                match counterexample {
-                    CounterExample::Single(ce) => ce.calldata.clone(),
-                    _ => unimplemented!(),
-                }
-            } else {
-                result.first_case.calldata.clone()
-            };
             // rerun the last relevant test with traces
             let debug_result = FuzzedExecutor::new(
                 debug_executor,
                 runner,
                 self.sender,
                 fuzz_config,
+                Default::default(),
             )
             .single_fuzz(&state, address, should_fail, calldata);
 
@@ -697,7 +871,10 @@ impl<'a> ContractRunner<'a> {
             labeled_addresses,
             debug,
             breakpoints,
+            gas_snapshots: BTreeMap::new(),
             duration,
+            state_changes: Vec::new(),
+            fetch_stats: FetchStats::default(),
         }
     }
 }
@@ -712,3 +889,14 @@ fn merge_coverages(mut coverage: Option<HitMaps>, other: Option<HitMaps>) -> Opt
         (None, None) => None,
     }
 }
+
+/// Returns the empty [TestKind] of a test that didn't run
+fn test_kind(func: &Function) -> TestKind {
+    if func.is_invariant_test() {
+        TestKind::Invariant { runs: 0, calls: 0, reverts: 0, time_elapsed: 0 }
+    } else if func.is_fuzz_test() {
+        TestKind::Fuzz { first_case: Default::default(), runs: 0, mean_gas: 0, median_gas: 0 }
+    } else {
+        TestKind::Standard(0)
+    }
+}
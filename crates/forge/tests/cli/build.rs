@@ -1,4 +1,10 @@
-use foundry_test_utils::{forgetest, util::OutputExt};
+use foundry_compilers::EvmVersion;
+use foundry_config::{ChainConfig, ChainsConfig, Config};
+use foundry_test_utils::{
+    forgetest,
+    util::{OutputExt, TestProject},
+};
+use semver::Version;
 use std::path::PathBuf;
 
 // tests that json is printed when --json is passed
@@ -31,3 +37,72 @@ forgetest_init!(exact_build_output, |prj, cmd| {
     let stdout = cmd.stdout_lossy();
     assert!(stdout.contains("Compiling"), "\n{stdout}");
 });
+
+// tests that `--chain` compiles with the EVM version of the chain's overrides
+forgetest!(build_with_chain_overrides, |prj, cmd| {
+    prj.add_source(
+        "Counter.sol",
+        "pragma solidity ^0.8.20; contract Counter { uint256 public x; }",
+    )
+    .unwrap();
+    prj.write_config(Config {
+        evm_version: EvmVersion::Shanghai,
+        chains: ChainsConfig(
+            [(
+                "mainnet".to_string(),
+                ChainConfig { evm_version: Some(EvmVersion::Paris), ..Default::default() },
+            )]
+            .into(),
+        ),
+        ..Default::default()
+    });
+
+    let evm_version = |prj: &TestProject| {
+        let artifact: serde_json::Value = foundry_compilers::utils::read_json_file(
+            prj.paths().artifacts.join("Counter.sol/Counter.json"),
+        )
+        .unwrap();
+        artifact["metadata"]["settings"]["evmVersion"].as_str().unwrap().to_string()
+    };
+
+    cmd.args(["build", "--extra-output", "metadata"]).assert_success();
+    assert_eq!(evm_version(&prj), "shanghai");
+
+    cmd.args(["--force", "--chain", "1"]).assert_success();
+    assert_eq!(evm_version(&prj), "paris");
+});
+
+// tests that every file is compiled with the newest configured solc version satisfying its pragma,
+// ignoring the pragmas in comments, and that the cache tracks the files of every version
+forgetest!(can_build_with_solc_versions, |prj, cmd| {
+    prj.add_source("Legacy.sol", "pragma solidity 0.7.6; contract Legacy {}").unwrap();
+    prj.add_source(
+        "Modern.sol",
+        r"
+// pragma solidity 0.7.6;
+/* pragma solidity 0.7.6; */
+pragma solidity ^0.8.0;
+contract Modern {}
+",
+    )
+    .unwrap();
+    prj.write_config(Config {
+        solc_versions: vec![Version::new(0, 7, 6), Version::new(0, 8, 19)],
+        ..Default::default()
+    });
+
+    let compiler_version = |prj: &TestProject, name: &str| {
+        let artifact: serde_json::Value = foundry_compilers::utils::read_json_file(
+            prj.paths().artifacts.join(format!("{name}.sol/{name}.json")),
+        )
+        .unwrap();
+        artifact["metadata"]["compiler"]["version"].as_str().unwrap().to_string()
+    };
+
+    cmd.args(["build", "--extra-output", "metadata"]).assert_success();
+    assert!(compiler_version(&prj, "Legacy").starts_with("0.7.6"));
+    assert!(compiler_version(&prj, "Modern").starts_with("0.8.19"));
+
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("No files changed, compilation skipped"), "{stdout}");
+});
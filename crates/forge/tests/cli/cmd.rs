@@ -2,13 +2,17 @@
 
 use crate::constants::*;
 use foundry_compilers::{artifacts::Metadata, remappings::Remapping, ConfigurableContractArtifact};
-use foundry_config::{parse_with_profile, BasicConfig, Chain, Config, SolidityErrorCode};
+use foundry_config::{
+    parse_with_profile, BasicConfig, Chain, Config, FormatterConfig, FormatterOverride,
+    GeigerConfig, GeigerOverride, GeigerSeverity, SolidityErrorCode,
+};
 use foundry_test_utils::{
     foundry_compilers::PathStyle,
     util::{pretty_err, read_string, OutputExt, TestCommand},
 };
 use semver::Version;
 use std::{
+    collections::BTreeMap,
     env, fs,
     path::{Path, PathBuf},
     process::{Command, Stdio},
@@ -432,6 +436,63 @@ forgetest!(fail_init_nonexistent_template, |prj, cmd| {
     cmd.assert_non_empty_stderr();
 });
 
+// checks that forge can init with a bundled template, offline
+forgetest!(can_init_bundled_template, |prj, cmd| {
+    prj.wipe();
+    cmd.args(["init", "--template", "library", "--offline", "--var", "license=Apache-2.0"])
+        .arg(prj.root());
+    cmd.assert_non_empty_stdout();
+    assert!(prj.root().join("foundry.toml").exists());
+    assert!(!prj.root().join("template.toml").exists());
+    assert!(!prj.root().join("src/Math.sol.tmpl").exists());
+    let math = std::fs::read_to_string(prj.root().join("src/Math.sol")).unwrap();
+    assert!(math.contains("SPDX-License-Identifier: Apache-2.0"), "{math}");
+});
+
+// checks that forge renders the variables of a git template and runs its hooks if allowed
+forgetest!(can_init_template_with_vars_and_hooks, |prj, cmd| {
+    let template = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(template.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    std::fs::write(
+        template.path().join("template.toml"),
+        "hooks = [\"git config hooked.by template\"]\n\n[variables.token]\ndescription = \"The token symbol\"\n",
+    )
+    .unwrap();
+    std::fs::write(template.path().join("README.md.tmpl"), "# {{token}}").unwrap();
+    git(&["init"]);
+    git(&["add", "."]);
+    git(&["commit", "-m", "template"]);
+    let url = format!("file://{}", template.path().display());
+
+    prj.wipe();
+    cmd.args(["init", "--template", &url]).arg(prj.root());
+    let output = cmd.unchecked_output_lossy();
+    assert!(output.1.contains("token: The token symbol"), "{}", output.1);
+
+    prj.wipe();
+    cmd.forge_fuse().args(["init", "--template", &url, "--var", "token=ABC"]).arg(prj.root());
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("git config hooked.by template"), "{stdout}");
+    assert_eq!(std::fs::read_to_string(prj.root().join("README.md")).unwrap(), "# ABC");
+    let git_config = || std::fs::read_to_string(prj.root().join(".git/config")).unwrap();
+    assert!(!git_config().contains("hooked"));
+
+    prj.wipe();
+    cmd.forge_fuse()
+        .args(["init", "--template", &url, "--var", "token=ABC", "--allow-hooks"])
+        .arg(prj.root());
+    cmd.assert_non_empty_stdout();
+    assert!(git_config().contains("[hooked]"));
+});
+
 // checks that `clean` removes dapptools style paths
 forgetest!(can_clean, |prj, cmd| {
     prj.assert_create_dirs_exists();
@@ -640,6 +701,90 @@ contract ATest is DSTest {
     let _ = cmd.output();
 });
 
+// test that named gas snapshots are written to, checked and diffed against the snapshot file
+forgetest!(can_check_named_gas_snapshots, |prj, cmd| {
+    prj.insert_ds_test();
+
+    let test = |loops: u32, name: &str| {
+        format!(
+            r#"
+import "./test.sol";
+
+interface Vm {{
+    function startSnapshotGas(string calldata name) external;
+    function stopSnapshotGas(string calldata name) external returns (uint256);
+    function snapshotGasLastCall(string calldata name) external returns (uint256);
+}}
+
+contract Counter {{
+    uint256 public number;
+    function increment(uint256 times) public {{
+        for (uint256 i = 0; i < times; i++) number++;
+    }}
+}}
+
+contract CounterTest is DSTest {{
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testIncrement() public {{
+        Counter counter = new Counter();
+        vm.startSnapshotGas("{name}");
+        counter.increment({loops});
+        vm.stopSnapshotGas("{name}");
+        counter.increment(1);
+        vm.snapshotGasLastCall("increment once");
+    }}
+}}
+"#
+        )
+    };
+    prj.add_source("CounterTest.t.sol", &test(1, "increment")).unwrap();
+
+    cmd.arg("snapshot");
+    cmd.assert_non_empty_stdout();
+
+    let snapshot = std::fs::read_to_string(prj.root().join(".gas-snapshot")).unwrap();
+    let mut lines = snapshot.lines();
+    assert!(lines.next().unwrap().starts_with("CounterTest:testIncrement() (gas: "), "{snapshot}");
+    assert_eq!(lines.next(), Some(""));
+    assert_eq!(lines.next(), Some("[snapshotGas]"));
+    assert!(lines.next().unwrap().starts_with("CounterTest:testIncrement():increment (gas: "));
+    assert!(lines.next().unwrap().starts_with("CounterTest:testIncrement():increment once (gas: "));
+
+    cmd.arg("--check");
+    cmd.assert_success();
+
+    prj.add_source("CounterTest.t.sol", &test(5, "increment")).unwrap();
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Diff in \"CounterTest::testIncrement():increment\""), "{stderr}");
+
+    cmd.forge_fuse().args(["snapshot", "--diff"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("testIncrement():increment "), "{stdout}");
+
+    // a named snapshot that the test doesn't record anymore is stale
+    prj.add_source("CounterTest.t.sol", &test(1, "renamed")).unwrap();
+    cmd.forge_fuse().args(["snapshot", "--check"]);
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "Gas snapshot entry \"CounterTest::testIncrement():increment\" of the snapshot file \
+             wasn't recorded by its test"
+        ),
+        "{stderr}"
+    );
+
+    cmd.forge_fuse().args(["snapshot", "--diff"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Removed gas snapshots"), "{stdout}");
+    assert!(stdout.contains("CounterTest:testIncrement():increment (gas: "), "{stdout}");
+    assert!(stdout.contains("New gas snapshots"), "{stdout}");
+});
+
 // test that `forge build` does not print `(with warnings)` if file path is ignored
 forgetest!(can_compile_without_warnings_ignored_file_paths, |prj, cmd| {
     // Ignoring path and setting empty error_codes as default would set would set some error codes
@@ -1425,6 +1570,98 @@ contract ContractThreeTest is DSTest {
     assert!(third_out.contains("foo") && third_out.contains("bar") && third_out.contains("baz"));
 });
 
+forgetest!(gas_report_json_attributes_proxied_and_library_calls, |prj, cmd| {
+    prj.insert_ds_test();
+    prj.add_source(
+        "Contracts.sol",
+        r#"
+//SPDX-license-identifier: MIT
+
+import "./test.sol";
+
+library Math {
+    function double(uint256 x) external pure returns (uint256) {
+        return x * 2;
+    }
+}
+
+contract Counter {
+    uint256 public number;
+
+    constructor(uint256 start) {
+        number = start;
+    }
+
+    function increment() public {
+        number = Math.double(number) + 1;
+    }
+}
+
+contract Proxy {
+    address immutable implementation;
+
+    constructor(address impl) {
+        implementation = impl;
+    }
+
+    fallback() external {
+        (bool success,) = implementation.delegatecall(msg.data);
+        require(success);
+    }
+}
+
+contract Ignored {
+    function ignored() public {}
+}
+
+contract CounterTest is DSTest {
+    function testIncrement() public {
+        Counter counter = new Counter(1);
+        counter.increment();
+        new Counter(type(uint256).max / 4);
+        Counter(address(new Proxy(address(counter)))).increment();
+        Counter(address(new Proxy(address(new Proxy(address(counter)))))).increment();
+        new Ignored().ignored();
+    }
+}
+    "#,
+    )
+    .unwrap();
+
+    prj.write_config(Config {
+        gas_reports_ignore: vec!["Ignored".to_string()],
+        ..Default::default()
+    });
+    cmd.args(["test", "--gas-report", "--gas-report-format", "json"]);
+    let out = cmd.stdout_lossy();
+    let report: serde_json::Value = serde_json::from_str(&out).unwrap();
+    let contracts = report["contracts"].as_array().unwrap();
+    let contract = |name: &str| {
+        contracts.iter().find(|c| c["contract"].as_str().unwrap().ends_with(name)).cloned()
+    };
+
+    let counter = contract(":Counter").unwrap();
+    assert_eq!(counter["kind"], "contract");
+    assert_eq!(counter["deployment"]["calls"], 2);
+    let increment = &counter["functions"][0];
+    assert_eq!(increment["signature"], "increment()");
+    assert_eq!(increment["calls"], 3);
+    assert_eq!(increment["proxies"], serde_json::json!(["Proxy"]));
+
+    let math = contract(":Math").unwrap();
+    assert_eq!(math["kind"], "library");
+    assert!(contract(":Proxy").unwrap()["functions"].as_array().unwrap().is_empty());
+    assert_eq!(math["functions"][0]["signature"], "double(uint256)");
+
+    assert!(contract(":Ignored").is_none());
+
+    // a report has no changes against itself
+    let report = prj.root().join("gas.json");
+    std::fs::write(&report, out).unwrap();
+    cmd.forge_fuse().arg("gas-diff").arg(&report).arg(&report).args(["--threshold", "0"]);
+    assert!(cmd.stdout_lossy().contains("No gas changes."));
+});
+
 forgetest_init!(can_use_absolute_imports, |prj, cmd| {
     let remapping = prj.paths().libraries[0].join("myDependency");
     let config = Config {
@@ -1518,12 +1755,109 @@ forgetest_init!(can_inspect_ir_optimized, |_prj, cmd| {
     cmd.assert_success();
 });
 
+// checks that `forge fmt --check` applies the overrides of matching paths
+forgetest!(can_check_fmt_with_overrides, |prj, cmd| {
+    let source = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+contract Short {
+    function short() public { revert(); }
+}
+"#;
+    prj.add_test("Short.t.sol", source).unwrap();
+    prj.write_config(Config {
+        fmt: FormatterConfig {
+            overrides: vec![FormatterOverride {
+                paths: vec!["test/**".to_string()],
+                allow_single_line_functions: Some(true),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    cmd.args(["fmt", "--check"]);
+    cmd.assert_success();
+
+    // the override doesn't apply to sources
+    prj.add_source("Short.sol", source).unwrap();
+    cmd.assert_err();
+});
+
 // checks forge bind works correctly on the default project
 forgetest_init!(can_bind, |_prj, cmd| {
     cmd.arg("bind");
     cmd.assert_non_empty_stdout();
 });
 
+// checks forge bind can generate alloy `sol!` bindings for selected contracts
+forgetest_init!(can_bind_alloy_sol_macro, |prj, cmd| {
+    cmd.args(["bind", "--alloy-sol-macro", "--module", "--select", "^Counter$"]);
+    cmd.assert_non_empty_stdout();
+
+    let bindings = fs::read_to_string(prj.root().join("out/bindings/mod.rs")).unwrap();
+    assert!(bindings.contains("sol!(\n    Counter,"), "{bindings}");
+    assert!(!bindings.contains("CounterTest"), "{bindings}");
+
+    // the generated bindings are consistent
+    cmd.forge_fuse().args(["bind", "--alloy-sol-macro", "--module", "--select", "^Counter$"]);
+    assert!(cmd.stdout_lossy().contains("OK."));
+});
+
+// checks forge bind wraps each `sol!` invocation in its own module
+forgetest_init!(can_bind_alloy_sol_macro_module_per_contract, |prj, cmd| {
+    cmd.args([
+        "bind",
+        "--alloy-sol-macro",
+        "--module",
+        "--module-per-contract",
+        "--select",
+        "^Counter$",
+    ]);
+    cmd.assert_non_empty_stdout();
+
+    let bindings = fs::read_to_string(prj.root().join("out/bindings/mod.rs")).unwrap();
+    assert!(bindings.contains("pub mod counter {"), "{bindings}");
+
+    // ethers bindings always use a module per contract
+    cmd.forge_fuse().args(["bind", "--overwrite", "--module-per-contract"]);
+    cmd.assert_err();
+});
+
+// checks forge clone reproduces a verified contract
+forgetest!(can_clone, |prj, cmd| {
+    let root = prj.root().join("weth");
+    let rpc = foundry_common::rpc::next_http_rpc_endpoint();
+    cmd.args([
+        "clone",
+        "--etherscan-api-key",
+        "JQNGFHINKS1W7Y5FRXU4SPBYF43J3NYK46",
+        "--rpc-url",
+        rpc.as_str(),
+        "--no-commit",
+        "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+    ])
+    .arg(&root);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("The cloned project matches the on-chain runtime code"), "{output}");
+    assert!(output.contains("Cloned `WETH9`"), "{output}");
+
+    let config = Config::load_with_root(&root);
+    assert_eq!(config.solc, Some(foundry_config::SolcReq::Version(Version::new(0, 4, 19))));
+    let meta: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(root.join(".clone.meta")).unwrap()).unwrap();
+    assert_eq!(meta["targetContract"], "WETH9");
+    assert_eq!(meta["chainId"], 1);
+    assert!(!root.join(".git").exists());
+
+    // cloning into a non-empty directory fails
+    cmd.forge_fuse()
+        .args(["clone", "--no-commit", "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"])
+        .arg(&root);
+    cmd.assert_err();
+});
+
 // checks missing dependencies are auto installed
 forgetest_init!(can_install_missing_deps_test, |prj, cmd| {
     // wipe forge-std
@@ -1590,6 +1924,36 @@ function test_run() external {}
     );
 });
 
+// checks that skipped files and the files only they import aren't compiled, and that their cache
+// entries are kept for the next full build
+forgetest_init!(can_build_skip_tests_sparse, |prj, cmd| {
+    prj.clear();
+
+    cmd.args(["build"]);
+    cmd.assert_non_empty_stdout();
+    let artifacts = prj.paths().artifacts.clone();
+    assert!(artifacts.join("Counter.t.sol").exists());
+
+    cmd.forge_fuse().args(["build", "--skip-tests", "--skip-scripts"]);
+    cmd.assert_non_empty_stdout();
+    assert!(artifacts.join("Counter.sol").exists());
+    let cache = fs::read_to_string(&prj.paths().cache).unwrap();
+    assert!(cache.contains("test/Counter.t.sol"), "{cache}");
+
+    // the full build reuses the artifacts of the skipped files
+    cmd.forge_fuse().args(["build"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("No files changed, compilation skipped"), "{out}");
+
+    // `forge-std/Test.sol` is only imported by the skipped test
+    prj.clear();
+    cmd.forge_fuse().args(["build", "--skip-tests", "--skip-scripts"]);
+    cmd.assert_non_empty_stdout();
+    assert!(artifacts.join("Counter.sol").exists());
+    assert!(!artifacts.join("Test.sol").exists());
+    assert!(!artifacts.join("Script.sol").exists());
+});
+
 // checks that build --sizes includes all contracts even if unchanged
 forgetest_init!(can_build_sizes_repeatedly, |prj, cmd| {
     prj.clear_cache();
@@ -1607,6 +1971,65 @@ forgetest_init!(can_build_sizes_repeatedly, |prj, cmd| {
     assert!(unchanged.contains(table), "{}", table);
 });
 
+// checks that build --sizes --json reports both the runtime and the initcode size
+forgetest_init!(can_build_sizes_json, |prj, cmd| {
+    prj.clear_cache();
+
+    cmd.args(["build", "--sizes", "--json", "--constructor-args-size", "64"]);
+    let sizes: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let counter = &sizes[TEMPLATE_CONTRACT];
+
+    let runtime_size = counter["runtime_size"].as_u64().unwrap();
+    let init_size = counter["init_size"].as_u64().unwrap();
+    assert!(init_size > runtime_size + 64);
+    assert_eq!(counter["runtime_margin"].as_i64().unwrap(), 24576 - runtime_size as i64);
+    assert_eq!(counter["init_margin"].as_i64().unwrap(), 49152 - init_size as i64);
+    assert_eq!(counter["exceeds_init_size_limit"], false);
+
+    // test contracts are excluded
+    assert!(sizes.get("CounterTest").is_none());
+});
+
+// checks that storage layouts can be checked for append-only compatibility
+forgetest!(can_check_storage_layout, |prj, cmd| {
+    prj.add_source(
+        "Vault",
+        r#"
+contract VaultV1 {
+    address public owner;
+    uint256 public total;
+    uint256[49] private __gap;
+}
+
+contract VaultV2 {
+    address public admin;
+    uint256 public total;
+    uint256 public fee;
+    uint256[48] private __gap;
+}
+
+contract VaultV3 {
+    uint256 public total;
+    address public owner;
+}
+"#,
+    )
+    .unwrap();
+
+    let layout = prj.root().join("layouts/VaultV1.json");
+    cmd.args(["storage-check", "VaultV1", "--save"]).arg(&layout);
+    cmd.assert_success();
+    assert!(layout.exists());
+
+    cmd.forge_fuse().args(["storage-check", "VaultV2"]).arg(&layout);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("`owner` at slot 0 (offset 0) was renamed to `admin`"), "{out}");
+    assert!(out.contains("`__gap` at slot 2 (offset 0) was consumed by `fee`"), "{out}");
+
+    cmd.forge_fuse().args(["storage-check", "VaultV3", "VaultV1"]);
+    cmd.assert_err();
+});
+
 // checks that build --names includes all contracts even if unchanged
 forgetest_init!(can_build_names_repeatedly, |prj, cmd| {
     prj.clear_cache();
@@ -1636,3 +2059,184 @@ forgetest_init!(can_inspect_counter_pretty, |prj, cmd| {
 }"
     );
 });
+
+// checks that `selectors upload --dry-run` lists the signatures without uploading them
+forgetest_init!(can_dry_run_selectors_upload, |prj, cmd| {
+    cmd.args(["selectors", "upload", "--all", "--dry-run", "--offline"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("Would upload 3 signature(s)"), "{output}");
+    assert!(output.contains("setNumber(uint256)"));
+    assert!(output.contains("0x3fb5c1cb"));
+
+    // uploading is not possible in offline mode
+    cmd.forge_fuse().args(["selectors", "upload", "Counter", "--offline"]);
+    cmd.assert_err();
+});
+
+// checks that geiger resolves aliased and wrapped cheatcodes and applies the configured policies
+forgetest!(can_check_geiger_policies, |prj, cmd| {
+    prj.write_config(Config {
+        geiger: GeigerConfig {
+            cheatcodes: BTreeMap::from([
+                ("ffi".to_string(), GeigerSeverity::Warn),
+                ("etch".to_string(), GeigerSeverity::Error),
+            ]),
+            overrides: vec![GeigerOverride {
+                paths: vec!["src/Allowed.sol".to_string()],
+                cheatcodes: BTreeMap::from([("etch".to_string(), GeigerSeverity::Allow)]),
+            }],
+        },
+        ..Default::default()
+    });
+    prj.add_source(
+        "Cheats",
+        r#"
+interface Vm {
+    function ffi(string[] calldata) external returns (bytes memory);
+    function etch(address, bytes calldata) external;
+}
+
+contract Deployer {
+    Vm constant hevm = Vm(address(uint160(uint256(keccak256("hevm cheat code")))));
+
+    function _etch(address target, bytes memory code) internal {
+        hevm.etch(target, code);
+    }
+
+    function run() external {
+        hevm.ffi(new string[](0));
+        _etch(address(0), "");
+    }
+}
+"#,
+    )
+    .unwrap();
+    prj.add_source(
+        "Allowed",
+        r#"
+import {Vm as Hevm} from "./Cheats.sol";
+
+contract Allowed {
+    function run() external {
+        Hevm(address(0)).etch(address(0), "");
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["geiger", "--json"]);
+    let findings: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let findings = findings.as_array().unwrap();
+    assert_eq!(findings.len(), 3, "{findings:?}");
+    assert!(findings.iter().all(|finding| finding["file"] == "src/Cheats.sol"));
+    assert!(findings.iter().any(|f| f["cheatcode"] == "ffi" && f["severity"] == "warn"));
+    assert!(findings.iter().any(|f| f["cheatcode"] == "etch" && f["via"] == "_etch"));
+
+    cmd.arg("--check");
+    let output = cmd.unchecked_output();
+    assert_eq!(output.status.code(), Some(2));
+});
+
+// checks that `forge tree` detects import cycles and prints the graph as json
+forgetest_init!(can_print_import_graph, |prj, cmd| {
+    prj.add_source(
+        "A",
+        r#"
+import {B} from "./B.sol";
+
+contract A {}
+"#,
+    )
+    .unwrap();
+    prj.add_source(
+        "B",
+        r#"
+import "./A.sol";
+
+contract B {}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["tree", "--cycles"]);
+    assert!(cmd.stdout_lossy().contains("src/A.sol -> src/B.sol -> src/A.sol"));
+
+    cmd.forge_fuse().args(["tree", "--json"]);
+    let graph: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let nodes = graph["nodes"].as_array().unwrap();
+    let a = nodes.iter().position(|node| node["path"] == "src/A.sol").unwrap();
+    assert_eq!(nodes[a]["license"], "MIT OR Apache-2.0");
+    let edge = graph["edges"].as_array().unwrap().iter().find(|edge| edge["from"] == a).unwrap();
+    assert_eq!(edge["path"], "./B.sol");
+    assert_eq!(edge["symbols"], serde_json::json!(["B"]));
+    assert_eq!(graph["cycles"].as_array().unwrap().len(), 1);
+
+    cmd.forge_fuse().args(["tree", "--contract", "Counter"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.starts_with("src/Counter.sol ("), "{out}");
+});
+
+// checks that `forge lint` rewrites the imports of moved files, keeping the imported
+// symbols and the imports in comments, and only reports them with `--check`
+forgetest_init!(can_fix_moved_imports, |prj, cmd| {
+    prj.add_source("token/Token.sol", "contract Token {}").unwrap();
+    let script = prj
+        .add_script(
+            "Deploy.s.sol",
+            r#"
+import {
+    Token
+} from "../src/Token.sol";
+import "./Missing.sol";
+// import "../src/Token.sol";
+/* import {Token} from "../src/Token.sol"; */
+
+contract Deploy {
+    string constant IMPORT = 'import "../src/Token.sol";';
+}
+"#,
+        )
+        .unwrap();
+
+    cmd.args(["lint", "--check"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("Would rewrite 1 import(s)."), "{stdout}");
+    assert!(stdout.contains("1 unresolvable import(s):"), "{stdout}");
+    assert!(fs::read_to_string(&script).unwrap().contains("\"../src/Token.sol\""));
+
+    cmd.forge_fuse().args(["lint", "--style", "remapped"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(
+        stdout.contains(r#"script/Deploy.s.sol: "../src/Token.sol" -> "src/token/Token.sol""#),
+        "{stdout}"
+    );
+    let content = fs::read_to_string(&script).unwrap();
+    assert!(content.contains("import {\n    Token\n} from \"src/token/Token.sol\";"), "{content}");
+    assert!(content.contains("// import \"../src/Token.sol\";"), "{content}");
+    assert!(content.contains("/* import {Token} from \"../src/Token.sol\"; */"), "{content}");
+    assert!(content.contains("'import \"../src/Token.sol\";'"), "{content}");
+});
+
+// checks that `forge watch` stops the pipeline at the first failing stage, and only runs the tests
+// affected by the changed files
+forgetest_init!(can_run_watch_pipeline_once, |prj, cmd| {
+    prj.add_source("Unformatted.sol", "contract Unformatted { function f() public {} }").unwrap();
+
+    cmd.args(["watch", "--once", "--run", "fmt --check", "--run", "build"]);
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    assert!(
+        stderr.contains("Pipeline failed: `forge fmt --check` failed, skipped: build"),
+        "{stderr}"
+    );
+    assert!(!prj.root().join("out").exists());
+
+    fs::remove_file(prj.root().join("src/Unformatted.sol")).unwrap();
+    let counter = prj.root().join("src/Counter.sol");
+    cmd.forge_fuse().args(["watch", "--once", "--run", "build", "--run", "test --changed"]);
+    cmd.args(["--changed-file", counter.to_str().unwrap()]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("test/Counter.t.sol (imports src/Counter.sol)"), "{stdout}");
+    assert!(stdout.contains("forge test --match-path test/Counter.t.sol"), "{stdout}");
+    assert!(stdout.contains("Pipeline passed: 2 stages"), "{stdout}");
+});
@@ -41,6 +41,7 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         gas_reports_ignore: vec![],
         solc: Some(SolcReq::Local(PathBuf::from("custom-solc"))),
         auto_detect_solc: false,
+        solc_versions: vec![],
         auto_detect_remappings: true,
         offline: true,
         optimizer: false,
@@ -91,6 +92,8 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         eth_rpc_jwt: None,
         etherscan_api_key: None,
         etherscan: Default::default(),
+        etherscan_api_version: Default::default(),
+        etherscan_requests_per_second: 5,
         verbosity: 4,
         remappings: vec![Remapping::from_str("forge-std=lib/forge-std/").unwrap().into()],
         libraries: vec![
@@ -106,6 +109,7 @@ forgetest!(can_extract_config_values, |prj, cmd| {
             endpoints: CachedEndpoints::Remote,
         },
         no_storage_caching: true,
+        rpc_cache_max_size_mb: None,
         no_rpc_rate_limit: true,
         use_literal_content: false,
         bytecode_hash: Default::default(),
@@ -119,10 +123,17 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         build_info_path: None,
         fmt: Default::default(),
         doc: Default::default(),
+        geiger: Default::default(),
+        vyper: Default::default(),
+        script_settings: Default::default(),
+        watch: Default::default(),
         fs_permissions: Default::default(),
         labels: Default::default(),
+        address_book: "addresses.json".into(),
+        chains: Default::default(),
         cancun: true,
         isolate: true,
+        isolate_block_advance: Some(12),
         __non_exhaustive: (),
         __warnings: vec![],
     };
@@ -682,3 +693,43 @@ forgetest!(normalize_config_evm_version, |_prj, cmd| {
     let config: Config = serde_json::from_str(&output).unwrap();
     assert_eq!(config.evm_version, EvmVersion::Istanbul);
 });
+
+// tests that `forge config --diff --provenance` annotates the values that differ from the defaults
+forgetest_init!(can_show_config_provenance, |prj, cmd| {
+    prj.create_file(
+        "foundry.toml",
+        r#"
+[profile.default]
+optimizer_runs = 1000
+
+[fuzz]
+runs = 512
+
+[etherscan]
+mainnet = { key = "SECRETKEY" }
+"#,
+    );
+    cmd.env("FOUNDRY_VERBOSITY", "3");
+    cmd.args(["config", "--diff", "--provenance", "--via-ir"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("optimizer_runs = 1000 # "), "{output}");
+    assert!(output.contains("foundry.toml:3"), "{output}");
+    assert!(output.contains("fuzz.runs = 512 # "), "{output}");
+    assert!(output.contains("verbosity = 3 # env `FOUNDRY_VERBOSITY`"), "{output}");
+    assert!(output.contains("via_ir = true # CLI flag"), "{output}");
+    assert!(output.contains("etherscan.mainnet.key = \"<redacted>\""), "{output}");
+    assert!(!output.contains("SECRETKEY"), "{output}");
+    // unchanged values are omitted
+    assert!(!output.lines().any(|line| line.starts_with("ffi =")), "{output}");
+
+    cmd.forge_fuse().args(["config", "get", "fuzz.runs"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "512");
+
+    // secrets are masked like in the annotated output
+    cmd.forge_fuse().args(["config", "get", "etherscan.mainnet.key"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "<redacted>");
+    cmd.forge_fuse().args(["config", "get", "etherscan"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("\"key\": \"<redacted>\""), "{output}");
+    assert!(!output.contains("SECRETKEY"), "{output}");
+});
@@ -1,3 +1,4 @@
+use foundry_config::Config;
 use regex::Regex;
 
 forgetest!(basic_coverage, |_prj, cmd| {
@@ -15,6 +16,47 @@ forgetest!(report_file_coverage, |prj, cmd| {
     cmd.assert_success();
 });
 
+forgetest!(optimized_coverage, |prj, cmd| {
+    prj.insert_ds_test();
+    prj.add_source(
+        "AContract.sol",
+        r#"
+contract AContract {
+    int public i;
+
+    function foo() public {
+        i = 1;
+    }
+}
+    "#,
+    )
+    .unwrap();
+
+    prj.add_source(
+        "AContractTest.sol",
+        r#"
+import "./test.sol";
+import {AContract} from "./AContract.sol";
+
+contract AContractTest is DSTest {
+    function testFoo() public {
+        AContract a = new AContract();
+        a.foo();
+    }
+}
+    "#,
+    )
+    .unwrap();
+
+    let config = Config { optimizer: true, optimizer_runs: 200, ..Default::default() };
+    prj.write_config(config);
+
+    cmd.arg("coverage").arg("--optimized");
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("AContract.sol"), "{stdout}");
+    assert!(stdout.contains("100.00% (1/1)"), "{stdout}");
+});
+
 forgetest!(test_setup_coverage, |prj, cmd| {
     prj.insert_ds_test();
     prj.add_source(
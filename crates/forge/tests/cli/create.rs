@@ -308,3 +308,40 @@ contract UniswapV2Swap {
     let (stdout, _) = cmd.output_lossy();
     assert!(stdout.contains("Deployed to: 0x5FbDB2315678afecb367f032d93F642f64180aa3"));
 });
+
+// tests that we can deploy deterministically through the CREATE2 deployer
+forgetest_async!(can_create_with_salt, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let rpc = handle.http_endpoint();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let pk = hex::encode(wallet.signer().to_bytes());
+
+    let args = [
+        "create",
+        format!("./src/{TEMPLATE_CONTRACT}.sol:{TEMPLATE_CONTRACT}").as_str(),
+        "--rpc-url",
+        rpc.as_str(),
+        "--private-key",
+        pk.as_str(),
+        "--salt",
+        "0x0000000000000000000000000000000000000000000000000000000000000001",
+    ]
+    .map(str::to_string);
+
+    cmd.forge_fuse().args(&args).args(["--dry-run", "--json"]);
+    let stdout = cmd.stdout_lossy();
+    let output: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+    let predicted = output["predictedAddress"].as_str().unwrap().to_string();
+
+    cmd.forge_fuse().args(&args);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains(&format!("Predicted address: {predicted}")));
+    assert!(stdout.contains(&format!("Deployed to: {predicted}")));
+
+    // the same salt and init code can't be deployed twice
+    cmd.forge_fuse().args(&args);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("already deployed at the predicted address"), "{err}");
+});
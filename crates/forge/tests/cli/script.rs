@@ -1,9 +1,11 @@
 //! Contains various tests related to `forge script`.
 
 use crate::constants::TEMPLATE_CONTRACT;
-use alloy_primitives::{Address, Bytes};
+use alloy_primitives::{Address, Bytes, U256, U64};
+use alloy_rpc_types::request::TransactionRequest;
 use anvil::{spawn, NodeConfig};
 use foundry_common::rpc;
+use foundry_config::{Config, GasOverrides, ScriptSettings};
 use foundry_test_utils::{util::OutputExt, ScriptOutcome, ScriptTester};
 use regex::Regex;
 use serde_json::Value;
@@ -148,6 +150,57 @@ forgetest_async!(assert_exit_code_error_on_failure_script_with_json, |prj, cmd|
     assert!(output.contains("script failed: revert: failed"));
 });
 
+// Tests that `--stack-traces` prints the Solidity stack trace of a failed script.
+forgetest_async!(can_print_stack_trace_of_failed_script, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    prj.add_source(
+        "Counter.sol",
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity 0.8.24;
+
+contract Counter {
+    uint256 public count;
+
+    function decrement() external {
+        _sub(1);
+    }
+
+    function _sub(uint256 amount) internal {
+        require(count >= amount, "underflow");
+        count -= amount;
+    }
+}
+"#,
+    )
+    .unwrap();
+    let script = prj
+        .add_source(
+            "CounterScript.sol",
+            r#"// SPDX-License-Identifier: MIT
+pragma solidity 0.8.24;
+import "forge-std/Script.sol";
+import {Counter} from "./Counter.sol";
+
+contract CounterScript is Script {
+    function run() external {
+        Counter counter = new Counter();
+        counter.decrement();
+    }
+}
+"#,
+        )
+        .unwrap();
+
+    cmd.arg("script").arg(script).arg("--stack-traces");
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(
+        stdout.contains(
+            "Stack trace:\n  Counter._sub (src/Counter.sol:12)\n  Counter.decrement (src/Counter.sol:8)\n  CounterScript.run (src/CounterScript.sol:9)\n"
+        ),
+        "{stdout}"
+    );
+});
+
 // Tests that the manually specified gas limit is used when using the --unlocked option
 forgetest_async!(can_execute_script_command_with_manual_gas_limit_unlocked, |prj, cmd| {
     foundry_test_utils::util::initialize(prj.root());
@@ -201,6 +254,708 @@ contract DeployScript is Script {
     assert!(output.contains("Gas limit was set in script to 500000"));
 });
 
+// Tests that the script isn't broadcast if a balance asserted with
+// `assertBroadcastBalanceAbove` doesn't hold after the on-chain simulation.
+forgetest_async!(can_refuse_broadcast_on_failed_balance_assertion, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "BalanceScript",
+            r#"
+import "forge-std/Script.sol";
+
+interface BroadcastChecks {
+    function assertBroadcastBalanceAbove(address account, uint256 minBalance) external;
+}
+
+contract BalanceScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        payable(address(0xdead)).transfer(1 ether);
+        vm.stopBroadcast();
+        BroadcastChecks(address(vm)).assertBroadcastBalanceAbove(msg.sender, type(uint256).max);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    cmd.set_current_dir(prj.root());
+
+    cmd.args([
+        "script",
+        &(script.display().to_string() + ":BalanceScript"),
+        "--root",
+        prj.root().to_str().unwrap(),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--sender",
+        format!("{dev:?}").as_str(),
+        "--broadcast",
+        "--unlocked",
+    ]);
+
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("Sender balances on chain"), "{stdout}");
+    assert!(stderr.contains("Refusing to broadcast"), "{stderr}");
+    assert!(!stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"));
+});
+
+// Tests that the script isn't broadcast over in-flight transactions of the sender unless
+// `--allow-pending-nonce` is passed.
+forgetest_async!(can_refuse_broadcast_with_in_flight_transactions, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "TransferScript",
+            r#"
+import "forge-std/Script.sol";
+
+contract TransferScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        payable(address(0xdead)).transfer(1 ether);
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (api, handle) = spawn(NodeConfig::test().with_no_mining(true)).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    let in_flight = api
+        .send_transaction(TransactionRequest {
+            from: Some(dev),
+            to: Some(Address::random()),
+            value: Some(U256::from(1)),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    cmd.set_current_dir(prj.root());
+
+    let args = [
+        "script",
+        &(script.display().to_string() + ":TransferScript"),
+        "--root",
+        prj.root().to_str().unwrap(),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--sender",
+        &format!("{dev:?}"),
+        "--unlocked",
+    ];
+
+    cmd.args(args).arg("--broadcast");
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("has 1 in-flight transaction(s), with nonces 0 to 0"), "{stderr}");
+    assert!(stderr.contains(&format!("{in_flight:?}")), "{stderr}");
+    assert!(stderr.contains("--allow-pending-nonce"), "{stderr}");
+    assert!(!stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"));
+
+    cmd.forge_fuse().args(args).arg("--allow-pending-nonce");
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Simulating after them at nonce 1"), "{stdout}");
+    assert!(!stdout.contains("Broadcasting after them"), "{stdout}");
+
+    // mine the in-flight transaction along with the broadcast one
+    let miner = api.clone();
+    tokio::spawn(async move {
+        while miner.txpool_status().await.unwrap().pending < U64::from(2) {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        miner.mine_one().await;
+    });
+
+    cmd.forge_fuse().args(args).args(["--broadcast", "--allow-pending-nonce"]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("Broadcasting after them at nonce 1"), "{stdout}\n{stderr}");
+    assert!(stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"), "{stdout}\n{stderr}");
+
+    let sequence_path = prj.root().join("broadcast/TransferScript.sol/31337/run-latest.json");
+    let mut sequence: Value =
+        serde_json::from_str(&std::fs::read_to_string(&sequence_path).unwrap()).unwrap();
+    assert_eq!(sequence["nonce_source"], "pending");
+    assert_eq!(sequence["transactions"][0]["transaction"]["nonce"], "0x1");
+
+    // resuming a sequence broadcast after in-flight transactions requires the same acknowledgment
+    sequence["receipts"] = Value::Array(Vec::new());
+    std::fs::write(&sequence_path, serde_json::to_string(&sequence).unwrap()).unwrap();
+    cmd.forge_fuse().args(args).args(["--broadcast", "--resume"]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("pass --allow-pending-nonce to resume it"), "{stdout}\n{stderr}");
+});
+
+forgetest_async!(prints_broadcast_cost_summary, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "TransferScript",
+            r#"
+import "forge-std/Script.sol";
+
+contract TransferScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        payable(address(0xdead)).transfer(1 ether);
+        payable(address(0xbeef)).transfer(1 ether);
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    cmd.set_current_dir(prj.root());
+
+    cmd.args([
+        "script",
+        &(script.display().to_string() + ":TransferScript"),
+        "--root",
+        prj.root().to_str().unwrap(),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--sender",
+        &format!("{dev:?}"),
+        "--unlocked",
+        "--broadcast",
+        "--native-usd-price",
+        "2000",
+    ]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"), "{stdout}\n{stderr}");
+    assert!(stdout.contains("Chain 31337"), "{stdout}");
+    assert!(stdout.contains("Most expensive"), "{stdout}");
+
+    let sequence: Value = serde_json::from_str(
+        &std::fs::read_to_string(
+            prj.root().join("broadcast/TransferScript.sol/31337/run-latest.json"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let summary = &sequence["summary"];
+    assert_eq!(summary["transactions"], 2);
+    assert_eq!(summary["gasUsed"], "0xa410");
+    assert_eq!(summary["nativeUsdPrice"], 2000.0);
+    assert_eq!(summary["mostExpensive"].as_array().unwrap().len(), 2);
+});
+
+forgetest_async!(can_encrypt_broadcast_sequence, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "EncryptedScript",
+            r#"
+import "forge-std/Script.sol";
+
+contract EncryptedScript is Script {
+    function run() external {
+        vm.broadcast();
+        payable(address(0xdead)).transfer(1 ether);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    cmd.set_current_dir(prj.root());
+
+    let args = [
+        "script",
+        &(script.display().to_string() + ":EncryptedScript"),
+        "--root",
+        prj.root().to_str().unwrap(),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--sender",
+        &format!("{dev:?}"),
+        "--unlocked",
+    ];
+    cmd.env("FOUNDRY_SEQUENCE_PASSWORD", "secret");
+    cmd.args(args).args(["--broadcast", "--encrypt-sequence"]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"), "{stdout}\n{stderr}");
+
+    let sequence: Value = serde_json::from_str(
+        &std::fs::read_to_string(
+            prj.root().join("broadcast/EncryptedScript.sol/31337/run-latest.json"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(sequence["encrypted"]["transactions"], 1);
+    assert!(sequence.get("transactions").is_none());
+
+    // resuming requires the passphrase
+    cmd.forge_fuse().args(args).arg("--resume");
+    cmd.unset_env("FOUNDRY_SEQUENCE_PASSWORD");
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("is encrypted, set FOUNDRY_SEQUENCE_PASSWORD"), "{stderr}");
+
+    cmd.forge_fuse().args(args).arg("--resume");
+    cmd.env("FOUNDRY_SEQUENCE_PASSWORD", "secret");
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    assert!(!stderr.contains("encrypted"), "{stderr}");
+});
+
+forgetest_async!(can_show_sequence_status, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "StatusScript",
+            r#"
+import "forge-std/Script.sol";
+
+contract StatusScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        payable(address(0xdead)).transfer(1 ether);
+        payable(address(0xbeef)).transfer(1 ether);
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    cmd.set_current_dir(prj.root());
+
+    let target = script.display().to_string() + ":StatusScript";
+    let root = ["--root", prj.root().to_str().unwrap()];
+    let args =
+        ["--fork-url", &handle.http_endpoint(), "--sender", &format!("{dev:?}"), "--unlocked"];
+
+    // dry run
+    cmd.args(["script", &target]).args(root).args(args);
+    cmd.assert_non_empty_stdout();
+
+    cmd.forge_fuse().args(["script", &target, "--status", "--json"]).args(root);
+    let status: Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(status[0]["kind"], "dry-run");
+    assert_eq!(status[0]["chain"], 31337);
+    assert_eq!(status[0]["total"], 2);
+    assert_eq!(status[0]["sent"], 0);
+
+    cmd.forge_fuse().args(["script", &target, "--broadcast"]).args(root).args(args);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"), "{stdout}\n{stderr}");
+
+    // the status doesn't compile the script, nor requires a wallet
+    std::fs::write(&script, "contract StatusScript {").unwrap();
+    cmd.forge_fuse().args(["script", &target, "--status"]).args(root);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Chain 31337 (broadcast"), "{stdout}");
+    assert!(stdout.contains(": done"), "{stdout}");
+    assert!(!stdout.contains("Compiling"), "{stdout}");
+});
+
+forgetest_async!(can_apply_script_gas_overrides, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "GasScript",
+            r#"
+import "forge-std/Script.sol";
+
+contract Counter {
+    uint256 public count;
+
+    function increment() external {
+        count++;
+    }
+}
+
+contract GasScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        Counter counter = new Counter();
+        counter.increment();
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    cmd.set_current_dir(prj.root());
+
+    let args = [
+        "script",
+        &(script.display().to_string() + ":GasScript"),
+        "--root",
+        prj.root().to_str().unwrap(),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--sender",
+        &format!("{dev:?}"),
+    ];
+    let sequence_path = prj.root().join("broadcast/GasScript.sol/31337/dry-run/run-latest.json");
+    let gas_limits = || -> Vec<U256> {
+        let sequence: Value =
+            serde_json::from_str(&std::fs::read_to_string(&sequence_path).unwrap()).unwrap();
+        sequence["transactions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tx| U256::from_str(tx["transaction"]["gas"].as_str().unwrap()).unwrap())
+            .collect()
+    };
+
+    cmd.args(args);
+    let stdout = cmd.stdout_lossy();
+    assert!(!stdout.contains("gas overrides"), "{stdout}");
+    let evm_gas_limits = gas_limits();
+    assert_eq!(evm_gas_limits.len(), 2);
+
+    // both the creation and the call are charged the overridden base cost, inflated by the
+    // default gas estimate multiplier of 130%
+    prj.write_config(Config {
+        script_settings: ScriptSettings {
+            gas_overrides: [(
+                "31337".to_string(),
+                GasOverrides { intrinsic_gas: Some(121_000), ..Default::default() },
+            )]
+            .into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    cmd.forge_fuse().args(args);
+    let stdout = cmd.stdout_lossy();
+    assert!(
+        stdout
+            .contains("Gas estimated with the gas overrides of the chain: intrinsic_gas = 121000"),
+        "{stdout}"
+    );
+    let overridden_gas_limits = gas_limits();
+    for (evm, overridden) in evm_gas_limits.iter().zip(&overridden_gas_limits) {
+        assert_eq!(*overridden, *evm + U256::from(130_000));
+    }
+});
+
+forgetest_async!(can_reuse_deployed_libraries, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "LibScript",
+            r#"
+import "forge-std/Script.sol";
+
+library Math {
+    function double(uint256 x) public pure returns (uint256) {
+        return 2 * x;
+    }
+}
+
+contract Doubler {
+    function double(uint256 x) external pure returns (uint256) {
+        return Math.double(x);
+    }
+}
+
+contract LibScript is Script {
+    function run() external {
+        vm.broadcast();
+        new Doubler();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    cmd.set_current_dir(prj.root());
+
+    let args = [
+        "script",
+        &(script.display().to_string() + ":LibScript"),
+        "--root",
+        prj.root().to_str().unwrap(),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--sender",
+        &format!("{dev:?}"),
+        "--unlocked",
+        "--broadcast",
+    ];
+    let sequence_path = prj.root().join("broadcast/LibScript.sol/31337/run-latest.json");
+    let read_sequence = || -> Value {
+        serde_json::from_str(&std::fs::read_to_string(&sequence_path).unwrap()).unwrap()
+    };
+
+    cmd.args(args);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"), "{stdout}\n{stderr}");
+    assert!(!stdout.contains("Reusing libraries"), "{stdout}");
+    let sequence = read_sequence();
+    assert_eq!(sequence["transactions"].as_array().unwrap().len(), 2);
+    let library = sequence["libraries"][0].as_str().unwrap().to_string();
+
+    // the library deployed by the first broadcast is linked
+    cmd.forge_fuse().args(args);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"), "{stdout}\n{stderr}");
+    assert!(stdout.contains("Reusing libraries deployed on chain 31337"), "{stdout}");
+    let sequence = read_sequence();
+    assert_eq!(sequence["transactions"].as_array().unwrap().len(), 1);
+    assert_eq!(sequence["libraries"][0], library);
+
+    // a library whose code changed on-chain is deployed again
+    let address = Address::from_str(library.rsplit(':').next().unwrap()).unwrap();
+    api.anvil_set_code(address, Bytes::from_static(&[0x00])).await.unwrap();
+    cmd.forge_fuse().args(args);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"), "{stdout}\n{stderr}");
+    assert!(!stdout.contains("Reusing libraries"), "{stdout}");
+    let sequence = read_sequence();
+    assert_eq!(sequence["transactions"].as_array().unwrap().len(), 2);
+    assert_ne!(sequence["libraries"][0], library);
+});
+
+const FORK_SCRIPT_LIBS: &str = r#"
+library Math {
+    function double(uint256 x) public pure returns (uint256) {
+        return 2 * x;
+    }
+}
+
+contract Doubler {
+    function double(uint256 x) external pure returns (uint256) {
+        return Math.double(x);
+    }
+}
+"#;
+
+// Tests that the run with the new deployer and the on-chain simulation are served the state the
+// first run fetched from the fork
+forgetest_async!(reuses_fork_state_across_script_stages, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "ForkScript",
+            &format!(
+                r#"
+import "forge-std/Script.sol";
+{FORK_SCRIPT_LIBS}
+contract ForkScript is Script {{
+    function run() external {{
+        vm.broadcast(0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266);
+        new Doubler();
+    }}
+}}
+   "#
+            ),
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    cmd.set_current_dir(prj.root());
+
+    // no `--sender`, so the libraries are relinked for the broadcasting account in a second run
+    cmd.args([
+        "script",
+        &(script.display().to_string() + ":ForkScript"),
+        "--root",
+        prj.root().to_str().unwrap(),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "-vvv",
+    ]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("SIMULATION COMPLETE"), "{stdout}\n{stderr}");
+
+    let re =
+        Regex::new(r"(Script execution|On-chain simulation): .* \((\d+) values fetched\)").unwrap();
+    let stages = re
+        .captures_iter(&stdout)
+        .map(|caps| (caps[1].to_string(), caps[2].parse::<u64>().unwrap()))
+        .collect::<Vec<_>>();
+    let names = stages.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["Script execution", "Script execution", "On-chain simulation"], "{stdout}");
+
+    let first = stages[0].1;
+    assert!(first > 0, "{stdout}");
+    for (_, fetches) in &stages[1..] {
+        assert!(*fetches < first, "{stdout}");
+    }
+});
+
+// Tests that forks created by the script on other RPCs are not served by the cached backend of
+// `--fork-url`
+forgetest_async!(create_select_fork_bypasses_script_fork_cache, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "ForkScript",
+            &format!(
+                r#"
+import "forge-std/Script.sol";
+{FORK_SCRIPT_LIBS}
+contract ForkScript is Script {{
+    function run() external {{
+        uint256 main = vm.activeFork();
+        require(block.chainid == 31337, "wrong --fork-url chain");
+
+        vm.createSelectFork(vm.envString("OTHER_RPC"));
+        require(block.chainid == 1337, "other RPC served by the --fork-url backend");
+
+        vm.selectFork(main);
+        require(block.chainid == 31337, "wrong --fork-url chain");
+
+        vm.broadcast(0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266);
+        new Doubler();
+    }}
+}}
+   "#
+            ),
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let (_other_api, other_handle) = spawn(NodeConfig::test().with_chain_id(Some(1337u64))).await;
+    cmd.set_current_dir(prj.root());
+
+    cmd.env("OTHER_RPC", other_handle.http_endpoint());
+    cmd.args([
+        "script",
+        &(script.display().to_string() + ":ForkScript"),
+        "--root",
+        prj.root().to_str().unwrap(),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "-vvv",
+    ]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("SIMULATION COMPLETE"), "{stdout}\n{stderr}");
+    // both runs of the script went through the checks
+    assert_eq!(stdout.matches("Script execution:").count(), 2, "{stdout}");
+});
+
+forgetest_async!(can_broadcast_over_ws_and_ipc, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "TransferScript",
+            r#"
+import "forge-std/Script.sol";
+
+contract TransferScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        payable(address(0xdead)).transfer(1 ether);
+        payable(address(0xbeef)).transfer(1 ether);
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    // IPC endpoints are named pipes on Windows
+    let ipc = cfg!(unix).then(|| prj.root().join("anvil.ipc").display().to_string());
+    let (_api, handle) = spawn(NodeConfig::test().with_ipc(ipc.map(Some))).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    cmd.set_current_dir(prj.root());
+
+    for endpoint in std::iter::once(handle.ws_endpoint()).chain(handle.ipc_path()) {
+        cmd.forge_fuse().args([
+            "script",
+            &(script.display().to_string() + ":TransferScript"),
+            "--root",
+            prj.root().to_str().unwrap(),
+            "--fork-url",
+            &endpoint,
+            "--sender",
+            &format!("{dev:?}"),
+            "--unlocked",
+            "--broadcast",
+            "--slow",
+        ]);
+        let (stdout, stderr) = cmd.unchecked_output_lossy();
+        assert!(
+            stdout.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"),
+            "{endpoint}\n{stdout}\n{stderr}"
+        );
+    }
+});
+
+forgetest_async!(can_diff_script_against_saved_sequence, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "DiffScript",
+            r#"
+import "forge-std/Script.sol";
+
+contract Counter {
+    uint256 public number;
+
+    function setNumber(uint256 newNumber) public {
+        number = newNumber;
+    }
+}
+
+contract DiffScript is Script {
+    function run(uint256 number) external {
+        vm.startBroadcast();
+        Counter counter = new Counter();
+        counter.setNumber(number);
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    cmd.set_current_dir(prj.root());
+
+    let args = [
+        "script",
+        &(script.display().to_string() + ":DiffScript"),
+        "--root",
+        prj.root().to_str().unwrap(),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--sender",
+        &format!("{dev:?}"),
+        "--sig",
+        "run(uint256)",
+    ];
+    cmd.args(args).arg("1").assert_success();
+
+    cmd.forge_fuse().args(args).args(["1", "--diff-against", "latest"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("No differences with"), "{stdout}");
+
+    cmd.forge_fuse().args(args).args(["2", "--diff-against", "latest", "--json"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    let diff: Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    let transactions = diff["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 1, "{diff}");
+    assert_eq!(transactions[0]["kind"], "changed");
+    assert_eq!(transactions[0]["transaction"], "Counter::setNumber(uint256)");
+    assert_eq!(transactions[0]["changes"][0]["field"], "arguments[0]");
+    assert_eq!(transactions[0]["changes"][0]["new"], "2");
+
+    // the saved sequence isn't overwritten
+    cmd.forge_fuse().args(args).args(["1", "--diff-against", "latest"]).assert_success();
+});
+
 // Tests that the manually specified gas limit is used.
 forgetest_async!(can_execute_script_command_with_manual_gas_limit, |prj, cmd| {
     foundry_test_utils::util::initialize(prj.root());
@@ -1,8 +1,8 @@
 //! Contains various tests for checking `forge test`
 use foundry_common::rpc;
-use foundry_config::Config;
+use foundry_config::{fs_permissions::PathPermission, Config, FsPermissions};
 use foundry_test_utils::util::{OutputExt, OTHER_SOLC_VERSION, SOLC_VERSION};
-use std::{path::PathBuf, str::FromStr};
+use std::{fs, path::PathBuf, str::FromStr};
 
 // tests that test filters are handled correctly
 forgetest!(can_set_filter_values, |prj, cmd| {
@@ -343,6 +343,95 @@ forgetest_init!(exit_code_error_on_fail_fast_with_json, |prj, cmd| {
     cmd.assert_err();
 });
 
+// tests that `--fail-fast` cancels the running fuzz campaigns after the first failure
+forgetest_init!(fail_fast_cancels_running_tests, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_test(
+        "FailFast.t.sol",
+        r#"
+import "forge-std/Test.sol";
+
+// The failing test sorts first, so it runs before or alongside the campaign whatever the number of
+// threads.
+contract FailFastTest is Test {
+    function testAssertFails() public {
+        assertTrue(false);
+    }
+
+    /// forge-config: default.fuzz.runs = 10000000
+    function testFuzzLongCampaign(uint256 x) public {
+        assertTrue(x == x);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--fail-fast"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("[FAIL. Reason: assertion failed] testAssertFails()"), "{stdout}");
+    assert!(stdout.contains("[SKIP (fail-fast)] testFuzzLongCampaign(uint256)"), "{stdout}");
+    cmd.assert_err();
+
+    cmd.arg("--json");
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    let results: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let tests = &results["test/FailFast.t.sol:FailFastTest"]["test_results"];
+    assert_eq!(tests["testAssertFails()"]["status"], "Failure");
+    assert_eq!(tests["testFuzzLongCampaign(uint256)"]["status"], "Skipped");
+    assert_eq!(tests["testFuzzLongCampaign(uint256)"]["reason"], "fail-fast");
+});
+
+// tests that expected revert mismatches show the names of the project's custom errors
+forgetest_init!(expect_revert_decodes_custom_errors, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_test(
+        "RevertNames.t.sol",
+        r#"
+import "forge-std/Test.sol";
+
+interface PartialRevert {
+    function expectPartialRevert(bytes4 revertData) external;
+}
+
+contract Bank {
+    error InsufficientBalance(uint256 balance, uint256 amount);
+    error NotOwner(address caller);
+
+    function withdraw(uint256 amount) public pure {
+        revert InsufficientBalance(0, amount);
+    }
+}
+
+contract RevertNamesTest is Test {
+    function testExpectedError() public {
+        Bank bank = new Bank();
+        vm.expectRevert(abi.encodeWithSelector(Bank.NotOwner.selector, address(1)));
+        bank.withdraw(1);
+    }
+
+    function testExpectedSelector() public {
+        Bank bank = new Bank();
+        PartialRevert(address(vm)).expectPartialRevert(Bank.NotOwner.selector);
+        bank.withdraw(1);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["test"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(
+        stdout.contains("Error != expected error: InsufficientBalance(0, 1) != NotOwner(0x0000000000000000000000000000000000000001)] testExpectedError()"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Error != expected error: InsufficientBalance(0, 1) != NotOwner(address)] testExpectedSelector()"),
+        "{stdout}"
+    );
+});
+
 // <https://github.com/foundry-rs/foundry/issues/6531>
 forgetest_init!(repro_6531, |prj, cmd| {
     prj.wipe_contracts();
@@ -491,6 +580,48 @@ contract TransientTest is Test {
     cmd.args(["test", "-vvvv", "--isolate", "--evm-version", "cancun"]).assert_success();
 });
 
+forgetest_init!(can_advance_nonce_and_block_with_isolation, |prj, cmd| {
+    prj.wipe_contracts();
+
+    prj.add_test(
+        "Contract.t.sol",
+        r#"pragma solidity 0.8.24;
+import {Test} from "forge-std/Test.sol";
+
+contract Counter {
+    uint256 public number;
+
+    function increment() public {
+        number++;
+    }
+}
+
+contract IsolationTest is Test {
+    function test() public {
+        Counter counter = new Counter();
+        uint64 nonce = vm.getNonce(address(this));
+        uint256 blockNumber = block.number;
+        uint256 timestamp = block.timestamp;
+
+        counter.increment();
+        assertEq(vm.getNonce(address(this)), nonce + 1);
+        assertEq(block.number, blockNumber + 1);
+        assertEq(block.timestamp, timestamp + 12);
+
+        // view calls and cheatcodes aren't separate transactions
+        counter.number();
+        vm.getNonce(address(this));
+        assertEq(vm.getNonce(address(this)), nonce + 1);
+        assertEq(block.number, blockNumber + 1);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "-vvvv", "--isolate", "--isolate-block-advance", "12"]).assert_success();
+});
+
 forgetest_init!(can_disable_block_gas_limit, |prj, cmd| {
     prj.wipe_contracts();
 
@@ -526,3 +657,388 @@ contract GasLimitTest is Test {
 
     cmd.args(["test", "-vvvv", "--isolate", "--disable-block-gas-limit"]).assert_success();
 });
+
+// tests that `--changed` only runs the tests affected by the changes since the git revision
+forgetest!(can_run_changed_tests, |prj, cmd| {
+    prj.insert_ds_test();
+    prj.add_source("Counter.sol", "pragma solidity ^0.8.0; contract Counter { uint256 public x; }")
+        .unwrap();
+    prj.add_source("Other.sol", "pragma solidity ^0.8.0; contract Other {}").unwrap();
+    prj.add_test(
+        "Counter.t.sol",
+        r#"pragma solidity ^0.8.0;
+import "../src/Counter.sol";
+contract CounterTest {
+    function testCounter() public { new Counter(); }
+}
+"#,
+    )
+    .unwrap();
+    prj.add_test(
+        "Other.t.sol",
+        r#"pragma solidity ^0.8.0;
+import "../src/Other.sol";
+contract OtherTest {
+    function testOther() public { new Other(); }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.git_init();
+    cmd.git_add().unwrap();
+    cmd.git_commit("Initial commit").unwrap();
+
+    cmd.args(["test", "--changed"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("No tests are affected by the changes since HEAD"), "{stdout}");
+    assert!(!stdout.contains("testCounter"), "{stdout}");
+
+    prj.add_source("Counter.sol", "pragma solidity ^0.8.0; contract Counter { uint256 public y; }")
+        .unwrap();
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("test/Counter.t.sol (imports src/Counter.sol)"), "{stdout}");
+    assert!(stdout.contains("testCounter"), "{stdout}");
+    assert!(!stdout.contains("testOther"), "{stdout}");
+
+    // composes with the other filters
+    cmd.forge_fuse().args(["test", "--changed", "HEAD", "--match-contract", "OtherTest"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("No tests match the provided pattern"), "{stdout}");
+
+    // changes which can't be traced through the imports run all tests
+    let gitmodules = prj.root().join(".gitmodules");
+    fs::write(&gitmodules, "").unwrap();
+    cmd.forge_fuse().args(["test", "--changed"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains(".gitmodules changed since HEAD, running all tests"), "{stdout}");
+    assert!(stdout.contains("testCounter") && stdout.contains("testOther"), "{stdout}");
+    fs::remove_file(&gitmodules).unwrap();
+
+    let data = prj.root().join("test/fixtures/data.json");
+    fs::create_dir_all(data.parent().unwrap()).unwrap();
+    fs::write(&data, "{}").unwrap();
+    cmd.forge_fuse().args(["test", "--changed"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(!stdout.contains("running all tests"), "{stdout}");
+    prj.write_config(Config {
+        fs_permissions: FsPermissions::new([PathPermission::read("./test/fixtures")]),
+        ..Default::default()
+    });
+    cmd.git_add().unwrap();
+    cmd.git_commit("Allow reading the fixtures").unwrap();
+    fs::write(&data, "{\"x\": 1}").unwrap();
+    cmd.forge_fuse().args(["test", "--changed"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(
+        stdout.contains("test/fixtures/data.json, which tests can read, changed since HEAD"),
+        "{stdout}"
+    );
+
+    // config changes run all tests
+    prj.write_config(Config { optimizer_runs: 1000, ..Default::default() });
+    cmd.forge_fuse().args(["test", "--changed"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("foundry.toml changed since HEAD, running all tests"), "{stdout}");
+    assert!(stdout.contains("testCounter") && stdout.contains("testOther"), "{stdout}");
+});
+
+forgetest_init!(tags_mocked_calls_in_traces, |prj, cmd| {
+    prj.wipe_contracts();
+
+    prj.add_test(
+        "Contract.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract Oracle {
+    function price() public pure returns (uint256) {
+        return 1;
+    }
+}
+
+contract MockedTraceTest is Test {
+    function test() public {
+        Oracle oracle = new Oracle();
+        vm.mockCall(address(oracle), abi.encodeWithSelector(Oracle.price.selector), abi.encode(2));
+        assertEq(oracle.price(), 2);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "-vvvv"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Oracle::price() [mocked]"), "{stdout}");
+    assert!(!stdout.contains("MockedCall"), "{stdout}");
+});
+
+// tests that a contract configured with another EVM version is only compiled and run with it
+forgetest_init!(can_run_tests_with_inline_evm_version, |prj, cmd| {
+    prj.wipe_contracts();
+
+    prj.add_test(
+        "Contract.t.sol",
+        r#"pragma solidity 0.8.24;
+import {Test} from "forge-std/Test.sol";
+
+/// forge-config: default.evm_version = "cancun"
+contract TransientTest is Test {
+    function test() public {
+        assembly {
+            tstore(0, 1)
+        }
+        uint256 value;
+        assembly {
+            value := tload(0)
+        }
+        assertEq(value, 1);
+    }
+}
+   "#,
+    )
+    .unwrap();
+    prj.add_test(
+        "Profile.t.sol",
+        r#"pragma solidity 0.8.24;
+import {Test} from "forge-std/Test.sol";
+
+contract ProfileTest is Test {
+    function test() public {}
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--evm-version", "paris"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("TransientTest") && stdout.contains("ProfileTest"), "{stdout}");
+    assert!(!stdout.contains("[FAIL"), "{stdout}");
+
+    prj.add_test(
+        "Contract.t.sol",
+        r#"pragma solidity 0.8.24;
+import {Test} from "forge-std/Test.sol";
+
+contract MixedTest is Test {
+    /// forge-config: default.evm_version = "cancun"
+    function test_cancun() public {}
+
+    function test_profile() public {}
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.forge_fuse().args(["test", "--evm-version", "paris"]);
+    let stderr = cmd.stderr_lossy();
+    assert!(stderr.contains("configured with different EVM versions"), "{stderr}");
+    assert!(stderr.contains("`cancun` for test_cancun"), "{stderr}");
+});
+
+// checks that `--fail-report` writes the reproduction bundle of failed tests, with the secrets read
+// with `vm.env*` redacted
+forgetest_init!(can_write_fail_report, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_test(
+        "Report.t.sol",
+        r#"pragma solidity 0.8.24;
+import {Test, console} from "forge-std/Test.sol";
+
+contract ReportTest is Test {
+    function testFuzz_Fails(uint256 x) public {
+        console.log(vm.envString("REPORT_API_KEY"));
+        assertLt(x, 10);
+    }
+
+    function test_Passes() public {}
+}
+   "#,
+    )
+    .unwrap();
+
+    let report = prj.root().join("report");
+    cmd.env("REPORT_API_KEY", "supersecret");
+    cmd.args(["test", "--fail-report"]).arg(&report);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("Wrote the reports of 1 failed test(s)"), "{stdout}");
+
+    let dir = report.join("ReportTest").join("testFuzz_Fails");
+    assert!(!report.join("ReportTest").join("test_Passes").exists());
+    for file in ["failure.txt", "failure.json", "traces.txt", "traces.json", "foundry.toml"] {
+        let content = std::fs::read_to_string(dir.join(file)).unwrap();
+        assert!(!content.contains("supersecret"), "{file}: {content}");
+    }
+
+    let failure: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("failure.json")).unwrap()).unwrap();
+    assert!(failure["counterexample"]["Single"]["calldata"].is_string(), "{failure}");
+    assert_eq!(failure["run_env_vars"], serde_json::json!(["REPORT_API_KEY"]));
+    assert!(failure["fuzz_seed"].is_string(), "{failure}");
+
+    let repro = std::fs::read_to_string(dir.join("repro.sh")).unwrap();
+    assert!(repro.contains("--match-test '^testFuzz_Fails$'"), "{repro}");
+    assert!(repro.contains("--fuzz-seed 0x"), "{repro}");
+});
+
+// tests that a fork of an anvil instance forking another chain under its own chain ID reports the
+// chain ID of the instance
+forgetest_async!(can_fork_anvil_with_custom_chain_id, |prj, cmd| {
+    prj.insert_ds_test();
+    let (_api, handle) = anvil::spawn(
+        anvil::NodeConfig::test()
+            .with_eth_rpc_url(Some(rpc::next_http_archive_rpc_endpoint()))
+            .with_fork_block_number(Some(14_608_400u64))
+            .with_chain_id(Some(3145u64))
+            .silent(),
+    )
+    .await;
+
+    prj.add_test(
+        "ForkChainId.t.sol",
+        &r#"
+import "ds-test/test.sol";
+
+interface Vm {
+    function createSelectFork(string calldata urlOrAlias) external returns (uint256 forkId);
+}
+
+contract ForkChainIdTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testChainId() public {
+        vm.createSelectFork("<url>");
+        assertEq(block.chainid, 3145);
+    }
+}
+   "#
+        .replace("<url>", &handle.http_endpoint()),
+    )
+    .unwrap();
+
+    cmd.args(["test", "--match-test", "testChainId"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("[PASS] testChainId()"), "{stdout}");
+});
+
+// checks that `--stack-traces` prints the Solidity stack trace of a failed test, through the
+// internal functions and modifiers of the reverting contract
+forgetest_init!(can_print_stack_traces_of_failed_tests, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_source(
+        "Vault.sol",
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity 0.8.24;
+
+contract Vault {
+    mapping(address => uint256) public balances;
+
+    modifier nonZero(uint256 amount) {
+        require(amount > 0, "zero amount");
+        _;
+    }
+
+    function withdraw(uint256 amount) external nonZero(amount) {
+        _debit(msg.sender, amount);
+    }
+
+    function _debit(address account, uint256 amount) internal {
+        require(balances[account] >= amount, "insufficient balance");
+        balances[account] -= amount;
+    }
+}
+"#,
+    )
+    .unwrap();
+    prj.add_test(
+        "Vault.t.sol",
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity 0.8.24;
+import {Test} from "forge-std/Test.sol";
+import {Vault} from "../src/Vault.sol";
+
+contract VaultTest is Test {
+    function test_Withdraw() public {
+        Vault vault = new Vault();
+        vault.withdraw(1);
+    }
+
+    function test_WithdrawZero() public {
+        Vault vault = new Vault();
+        vault.withdraw(0);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--stack-traces", "--match-test", "test_Withdraw$"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(
+        stdout.contains(
+            "Stack trace:\n  Vault._debit (src/Vault.sol:17)\n  Vault.withdraw (src/Vault.sol:13)\n  VaultTest.test_Withdraw (test/Vault.t.sol:9)\n"
+        ),
+        "{stdout}"
+    );
+
+    cmd.forge_fuse().args(["test", "--stack-traces", "--match-test", "test_WithdrawZero"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("  Vault.nonZero (src/Vault.sol:8)\n"), "{stdout}");
+    assert!(stdout.contains("  VaultTest.test_WithdrawZero (test/Vault.t.sol:14)\n"), "{stdout}");
+
+    // stack traces are only printed when asked for
+    cmd.forge_fuse().args(["test", "--match-test", "test_Withdraw$"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(!stdout.contains("Stack trace:"), "{stdout}");
+});
+
+// tests that `--decode-internal` renders the internal calls of the contracts identified by their
+// artifact, even if another contract has the same name
+forgetest_init!(can_decode_internal_calls, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.write_config(Config { optimizer: false, ..Default::default() });
+    prj.add_source(
+        "Math.sol",
+        r#"
+contract Math {
+    function sub(uint256 a, uint256 b) internal pure returns (uint256) {
+        return a - b;
+    }
+
+    function compute(uint256 a, uint256 b) external pure returns (uint256) {
+        return sub(a, b);
+    }
+}
+"#,
+    )
+    .unwrap();
+    prj.add_test(
+        "Math.t.sol",
+        r#"
+import "forge-std/Test.sol";
+
+contract Math {
+    function add(uint256 a, uint256 b) internal pure returns (uint256 sum) {
+        return a + b;
+    }
+
+    function compute(uint256 a, uint256 b) external pure returns (uint256) {
+        return add(a, b);
+    }
+}
+
+contract MathTest is Test {
+    function testCompute() public {
+        assertEq(new Math().compute(1, 2), 3);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--decode-internal", "-vvvv"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("add(1, 2) [internal]"), "{stdout}");
+    assert!(!stdout.contains("sub("), "{stdout}");
+});
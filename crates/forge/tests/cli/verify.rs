@@ -2,9 +2,10 @@
 //! and Sourcify.
 
 use crate::utils::{self, EnvExternalities};
+use anvil::{spawn, NodeConfig};
 use foundry_common::retry::Retry;
 use foundry_test_utils::{
-    forgetest,
+    forgetest, forgetest_async,
     util::{TestCommand, TestProject},
 };
 use std::time::Duration;
@@ -235,3 +236,50 @@ forgetest!(can_create_verify_random_contract_sepolia, |prj, cmd| {
 forgetest!(can_guess_constructor_args, |prj, cmd| {
     guess_constructor_args(EnvExternalities::goerli(), prj, cmd);
 });
+
+// tests that `verify-contract --preflight` compares the verification input with the deployed code,
+// without submitting it
+forgetest_async!(can_preflight_verification, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let rpc = handle.http_endpoint();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let pk = hex::encode(wallet.signer().to_bytes());
+
+    cmd.args(["create", "src/Counter.sol:Counter", "--rpc-url", &rpc, "--private-key", &pk]);
+    let stdout = cmd.stdout_lossy();
+    let address = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Deployed to: "))
+        .unwrap_or_else(|| panic!("{stdout}"))
+        .trim()
+        .to_string();
+
+    let args = ["verify-contract", &address, "src/Counter.sol:Counter", "--rpc-url", &rpc];
+    cmd.forge_fuse().args(args).arg("--preflight");
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Preflight check passed: `Counter`"), "{stdout}");
+
+    // the source changed since the deployment
+    prj.add_source(
+        "Counter.sol",
+        r#"
+contract Counter {
+    uint256 public number;
+
+    function setNumber(uint256 newNumber) public {
+        number = newNumber + 1;
+    }
+
+    function increment() public {
+        number++;
+    }
+}
+   "#,
+    )
+    .unwrap();
+    cmd.forge_fuse().args(args).arg("--preflight");
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("mismatch"), "{stdout}");
+    assert!(stderr.contains("Preflight check failed"), "{stderr}");
+});
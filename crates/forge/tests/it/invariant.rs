@@ -2,7 +2,11 @@
 
 use crate::{config::*, test_helpers::TEST_OPTS};
 use alloy_primitives::U256;
-use forge::{fuzz::CounterExample, result::TestStatus, TestOptions};
+use forge::{
+    fuzz::CounterExample,
+    result::{TestKind, TestStatus},
+    TestOptions,
+};
 use foundry_test_utils::Filter;
 use std::collections::BTreeMap;
 
@@ -145,6 +149,14 @@ async fn test_invariant() {
                 "fuzz/invariant/common/InvariantAssume.t.sol:InvariantAssume",
                 vec![("invariant_dummy()", true, None, None, None)],
             ),
+            (
+                "fuzz/invariant/common/InvariantBlockJumps.t.sol:InvariantBlockJumps",
+                vec![("invariantLocked()", true, None, None, None)],
+            ),
+            (
+                "fuzz/invariant/common/InvariantCallbackReentrancy.t.sol:InvariantCallbackReentrancy",
+                vec![("invariantSingleClaim()", true, None, None, None)],
+            ),
         ]),
     );
 }
@@ -408,3 +420,56 @@ async fn test_invariant_assume_respects_restrictions() {
         )]),
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invariant_block_jumps() {
+    let filter = Filter::new(".*", ".*", ".*fuzz/invariant/common/InvariantBlockJumps.t.sol");
+    let mut runner = runner();
+    runner.test_options.invariant.time_jump_max = 86400;
+    runner.test_options.invariant.block_jump_max = 100;
+    let results = runner.test_collect(&filter);
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/invariant/common/InvariantBlockJumps.t.sol:InvariantBlockJumps",
+            vec![("invariantLocked()", false, Some("revert: withdrawn".into()), None, None)],
+        )]),
+    );
+
+    let result = results.values().last().unwrap().test_results.values().last().unwrap();
+    match result.kind {
+        TestKind::Invariant { time_elapsed, .. } => assert!(time_elapsed >= 604800),
+        _ => panic!("`InvariantBlockJumps` should be an invariant test."),
+    }
+    match result.counterexample.as_ref().unwrap() {
+        CounterExample::Single(_) => panic!("CounterExample should be a sequence."),
+        CounterExample::Sequence(sequence) => {
+            // The warps of the removed calls are kept, so the failure reproduces.
+            let warped: u64 = sequence.iter().filter_map(|call| call.warp).sum();
+            assert!(warped >= 604800);
+            // The block jumps aren't needed to reproduce the failure.
+            assert!(sequence.iter().all(|call| call.roll.is_none()));
+        }
+    };
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invariant_call_from_callbacks() {
+    let filter =
+        Filter::new(".*", ".*", ".*fuzz/invariant/common/InvariantCallbackReentrancy.t.sol");
+    let mut runner = runner();
+    runner.test_options.invariant.call_from_callbacks = true;
+    let results = runner.test_collect(&filter);
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/invariant/common/InvariantCallbackReentrancy.t.sol:InvariantCallbackReentrancy",
+            vec![
+                ("invariantCallbackRuns()", true, None, None, None),
+                ("invariantSingleClaim()", false, Some("revert: claimed twice".into()), None, None),
+            ],
+        )]),
+    );
+}
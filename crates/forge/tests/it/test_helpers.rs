@@ -112,6 +112,9 @@ pub static TEST_OPTS: Lazy<TestOptions> = Lazy::new(|| {
             shrink_run_limit: 2usize.pow(18u32),
             preserve_state: false,
             max_assume_rejects: 65536,
+            time_jump_max: 0,
+            block_jump_max: 0,
+            call_from_callbacks: false,
         })
         .build(&COMPILED, &PROJECT.paths.root)
         .expect("Config loaded")
@@ -125,5 +128,6 @@ pub fn fuzz_executor<DB: DatabaseRef>(executor: Executor) -> FuzzedExecutor {
         proptest::test_runner::TestRunner::new(cfg),
         CALLER,
         TEST_OPTS.fuzz,
+        Default::default(),
     )
 }
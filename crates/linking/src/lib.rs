@@ -16,7 +16,10 @@ use std::{
 /// Errors that can occur during linking.
 #[derive(Debug, thiserror::Error)]
 pub enum LinkerError {
-    #[error("wasn't able to find artifact for library {name} at {file}")]
+    #[error(
+        "wasn't able to find artifact for library {name} at {file}, make sure it isn't excluded \
+         from the build, e.g. by `--skip`"
+    )]
     MissingLibraryArtifact { file: String, name: String },
     #[error("target artifact is not present in provided artifacts set")]
     MissingTargetArtifact,
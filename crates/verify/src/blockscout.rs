@@ -0,0 +1,258 @@
+use super::{
+    etherscan::EtherscanVerificationProvider, provider::VerificationProvider,
+    retry::RETRY_CHECK_ON_VERIFY, VerifyArgs, VerifyCheckArgs,
+};
+use alloy_primitives::Address;
+use async_trait::async_trait;
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::verify::{CodeFormat, VerifyContract};
+use foundry_common::retry::Retry;
+use futures::FutureExt;
+use reqwest::{
+    multipart::{Form, Part},
+    Url,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The type that can verify a contract on `blockscout`, using its v2 API.
+///
+/// The verify request is built like an Etherscan one: the standard JSON input is uploaded as a
+/// file, and flattened sources are submitted as JSON.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct BlockscoutVerificationProvider {
+    etherscan: EtherscanVerificationProvider,
+}
+
+#[async_trait]
+impl VerificationProvider for BlockscoutVerificationProvider {
+    async fn preflight_check(&mut self, args: VerifyArgs) -> Result<()> {
+        let _ = api_url(args.verifier.url())?;
+        let _ = self.etherscan.create_verify_request(&args, None).await?;
+        Ok(())
+    }
+
+    async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
+        let api_url = api_url(args.verifier.url())?;
+        let client = reqwest::Client::new();
+
+        if !args.skip_is_verified_check && is_verified(&client, &api_url, args.address).await? {
+            println!(
+                "\nContract [{}] {} is already verified. Skipping verification.",
+                args.contract.name, args.address
+            );
+            return Ok(())
+        }
+
+        let request = self.etherscan.create_verify_request(&args, None).await?;
+        let url = api_url.join(&format!(
+            "smart-contracts/{}/verification/via/{}",
+            args.address,
+            match request.code_format {
+                CodeFormat::StandardJsonInput => "standard-input",
+                CodeFormat::SingleFile => "flattened-code",
+            }
+        ))?;
+
+        trace!(target: "forge::verify", ?request, %url, "submitting verification request");
+
+        let retry: Retry = args.retry.into();
+        let resp = retry
+            .run_async(|| {
+                async {
+                    println!(
+                        "\nSubmitting verification for [{}] {}.",
+                        request.contract_name, args.address
+                    );
+                    let builder = client.post(url.clone());
+                    let builder = match request.code_format {
+                        CodeFormat::StandardJsonInput => {
+                            builder.multipart(standard_input_form(&request)?)
+                        }
+                        CodeFormat::SingleFile => {
+                            builder.json(&FlattenedCodeRequest::new(&request, &args.libraries))
+                        }
+                    };
+                    let response = builder.send().await?;
+
+                    let status = response.status();
+                    let resp = response.json::<BlockscoutResponse>().await.unwrap_or_default();
+                    if !status.is_success() {
+                        eyre::bail!(
+                            "Blockscout verification request for address ({}) failed with status code {status}\nDetails: {}",
+                            args.address,
+                            resp.message,
+                        );
+                    }
+                    Ok(resp)
+                }
+                .boxed()
+            })
+            .await?;
+
+        if resp.message.to_lowercase().contains("already verified") {
+            println!("Contract source code already verified");
+            return Ok(())
+        }
+
+        println!(
+            "Submitted contract for verification:\n\tResponse: `{}`\n\tURL: {}",
+            resp.message,
+            explorer_url(&api_url, args.address)?
+        );
+
+        if args.watch {
+            let check_args = VerifyCheckArgs {
+                id: args.address.to_string(),
+                etherscan: args.etherscan,
+                retry: RETRY_CHECK_ON_VERIFY,
+                verifier: args.verifier,
+            };
+            return self.check(check_args).await
+        }
+
+        Ok(())
+    }
+
+    async fn check(&self, args: VerifyCheckArgs) -> Result<()> {
+        let api_url = api_url(args.verifier.url())?;
+        let address =
+            args.id.parse::<Address>().wrap_err("Blockscout expects a contract address")?;
+        let client = reqwest::Client::new();
+
+        let retry: Retry = args.retry.into();
+        retry
+            .run_async(|| {
+                async {
+                    if !is_verified(&client, &api_url, address).await? {
+                        return Err(eyre!("Verification is still pending..."))
+                    }
+                    println!("Contract successfully verified");
+                    Ok(())
+                }
+                .boxed()
+            })
+            .await
+            .wrap_err("Checking verification result failed:")
+    }
+}
+
+/// Returns the URL of the v2 API from the configured verifier URL.
+///
+/// The v2 API is served from the explorer's root, so the path of the verifier URL, which usually
+/// points to the Etherscan-compatible `/api` endpoint, is replaced.
+fn api_url(verifier_url: Option<&str>) -> Result<Url> {
+    let verifier_url =
+        verifier_url.ok_or_else(|| eyre!("Blockscout requires a `--verifier-url` to be set"))?;
+    let url = Url::parse(verifier_url)
+        .wrap_err_with(|| format!("Invalid URL {verifier_url} provided"))?;
+    Ok(url.join("/api/v2/")?)
+}
+
+/// Returns the explorer page of the contract.
+fn explorer_url(api_url: &Url, address: Address) -> Result<Url> {
+    Ok(api_url.join(&format!("/address/{address}"))?)
+}
+
+/// Returns whether the contract at `address` is verified.
+async fn is_verified(client: &reqwest::Client, api_url: &Url, address: Address) -> Result<bool> {
+    let url = api_url.join(&format!("smart-contracts/{address}"))?;
+    let response = client.get(url).send().await?;
+    // Unverified contracts may not be found at all
+    if !response.status().is_success() {
+        return Ok(false)
+    }
+    let contract = response.json::<BlockscoutSmartContract>().await?;
+    Ok(contract.is_verified.unwrap_or_default())
+}
+
+/// Builds the multipart form to verify a contract with its standard JSON input.
+fn standard_input_form(request: &VerifyContract) -> Result<Form> {
+    let input =
+        Part::text(request.source.clone()).file_name("input.json").mime_str("application/json")?;
+    let mut form = Form::new()
+        .text("compiler_version", request.compiler_version.clone())
+        .text("contract_name", request.contract_name.clone())
+        .text("license_type", "none")
+        .part("files[0]", input);
+    form = match &request.constructor_arguments {
+        Some(args) => {
+            form.text("autodetect_constructor_args", "false").text("constructor_args", args.clone())
+        }
+        None => form.text("autodetect_constructor_args", "true"),
+    };
+    Ok(form)
+}
+
+/// The request to verify a contract with its flattened source code.
+#[derive(Debug, Serialize)]
+struct FlattenedCodeRequest {
+    compiler_version: String,
+    contract_name: String,
+    source_code: String,
+    is_optimization_enabled: bool,
+    optimization_runs: Option<String>,
+    evm_version: String,
+    autodetect_constructor_args: bool,
+    constructor_args: Option<String>,
+    libraries: BTreeMap<String, String>,
+    license_type: &'static str,
+}
+
+impl FlattenedCodeRequest {
+    fn new(request: &VerifyContract, libraries: &[String]) -> Self {
+        // Libraries are passed as `<path>:<name>:<address>`
+        let libraries = libraries
+            .iter()
+            .filter_map(|lib| {
+                let mut parts = lib.rsplit(':');
+                let address = parts.next()?;
+                let name = parts.next()?;
+                Some((name.to_string(), address.to_string()))
+            })
+            .collect();
+        Self {
+            compiler_version: request.compiler_version.clone(),
+            contract_name: request.contract_name.clone(),
+            source_code: request.source.clone(),
+            is_optimization_enabled: request.optimization_used.as_deref() == Some("1"),
+            optimization_runs: request.runs.clone(),
+            evm_version: request.evm_version.clone().unwrap_or_else(|| "default".to_string()),
+            autodetect_constructor_args: request.constructor_arguments.is_none(),
+            constructor_args: request.constructor_arguments.clone(),
+            libraries,
+            license_type: "none",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BlockscoutResponse {
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockscoutSmartContract {
+    is_verified: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_url() {
+        let url = api_url(Some("https://eth.blockscout.com/api?")).unwrap();
+        assert_eq!(url.as_str(), "https://eth.blockscout.com/api/v2/");
+
+        let url = url.join("smart-contracts/0x0000000000000000000000000000000000000000").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://eth.blockscout.com/api/v2/smart-contracts/0x0000000000000000000000000000000000000000"
+        );
+
+        assert!(api_url(None).is_err());
+    }
+}
@@ -0,0 +1,762 @@
+use crate::{
+    etherscan::{is_vyper_contract, EtherscanVerificationProvider},
+    VerifyArgs,
+};
+use alloy_dyn_abi::{DynSolType, JsonAbiExt};
+use alloy_primitives::{Address, Bytes};
+use clap::{Parser, ValueHint};
+use ethers_providers::Middleware;
+use eyre::{OptionExt, Result};
+use foundry_block_explorers::verify::{CodeFormat, VerifyContract};
+use foundry_cli::{
+    opts::{EtherscanOpts, RpcOpts},
+    utils::{self, LoadConfig},
+};
+use foundry_common::{
+    compile::ProjectCompiler,
+    fmt::format_token,
+    metadata_len,
+    provider::ethers::RetryProvider,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_compilers::{
+    artifacts::{CompactDeployedBytecode, CompilerOutput, ConfigurableContractArtifact, Offsets},
+    info::ContractInfo,
+    ProjectCompileOutput, Solc,
+};
+use foundry_config::{figment, impl_figment_convert, Chain, Config};
+use foundry_evm::constants::DEFAULT_CREATE2_DEPLOYER;
+use semver::Version;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// CLI arguments for `forge verify-bytecode`.
+#[derive(Clone, Debug, Parser)]
+pub struct VerifyBytecodeArgs {
+    /// The address of the contract to check.
+    pub address: Address,
+
+    /// The contract identifier in the form `<path>:<contractname>`.
+    pub contract: ContractInfo,
+
+    /// The address of the implementation to check instead, if the contract is a proxy.
+    #[arg(long, value_name = "ADDRESS")]
+    pub implementation: Option<Address>,
+
+    /// Print the report as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    /// The project's root path.
+    ///
+    /// By default root of the Git repository, if in one,
+    /// or the current working directory.
+    #[arg(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    pub root: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub etherscan: EtherscanOpts,
+
+    #[command(flatten)]
+    pub rpc: RpcOpts,
+}
+
+impl_figment_convert!(VerifyBytecodeArgs);
+
+impl figment::Provider for VerifyBytecodeArgs {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::named("Verify Bytecode Provider")
+    }
+
+    fn data(
+        &self,
+    ) -> Result<figment::value::Map<figment::Profile, figment::value::Dict>, figment::Error> {
+        let mut dict = self.etherscan.dict();
+        dict.extend(self.rpc.dict());
+
+        if let Some(root) = self.root.as_ref() {
+            dict.insert("root".to_string(), figment::value::Value::serialize(root)?);
+        }
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+impl VerifyBytecodeArgs {
+    /// Compares the code deployed at the address with the local build of the contract.
+    ///
+    /// Fails if any region of the code that doesn't depend on the deployment differs.
+    pub async fn run(self) -> Result<()> {
+        let config = self.load_config_emit_warnings();
+        let project = config.project()?;
+        let root = project.root();
+        let mut output = ProjectCompiler::new().quiet(self.json).compile(&project)?;
+
+        // the ASTs resolve the names and types of the immutables, if the artifacts don't include
+        // them the project is compiled again with them, without touching the cache or the
+        // artifacts, since AST node IDs are only unique within a compilation
+        let (_, artifact) = find_artifact(&output, root, &self.contract)?;
+        let has_immutables = artifact
+            .deployed_bytecode
+            .as_ref()
+            .map_or(false, |deployed| !deployed.immutable_references.is_empty());
+        if has_immutables && artifact.ast.is_none() {
+            let mut config = config.clone();
+            config.ast = true;
+            output = ProjectCompiler::new()
+                .quiet(true)
+                .compile(&config.ephemeral_no_artifacts_project()?)?;
+        }
+        let (version, artifact) = find_artifact(&output, root, &self.contract)?;
+
+        // AST node IDs are only unique within a compilation
+        let mut asts = BTreeMap::new();
+        for (id, artifact) in output.artifact_ids().filter(|(id, _)| id.version == version) {
+            if let (Some(ast), false) = (&artifact.ast, asts.contains_key(&id.source)) {
+                asts.insert(id.source, serde_json::to_value(ast)?);
+            }
+        }
+        let asts = asts.into_values().collect::<Vec<_>>();
+
+        let provider = utils::get_provider(&config)?;
+        let address = self.implementation.unwrap_or(self.address);
+        let code = provider.get_code(address.to_ethers(), None).await?.to_alloy();
+        if code.is_empty() {
+            eyre::bail!("No code is deployed at {address}");
+        }
+
+        let metadata = config.cbor_metadata;
+        let mut report = BytecodeReport {
+            address,
+            proxy: self.implementation.map(|_| self.address),
+            contract: self.contract.name.clone(),
+            matches: true,
+            runtime: compare_runtime_code(deployed_bytecode(artifact)?, &code, &asts, metadata)?,
+            creation: None,
+            creation_skipped: None,
+        };
+
+        let chain = utils::get_chain(config.chain, provider.clone()).await?;
+        match fetch_creation_code(&config, &provider, chain, address).await {
+            Ok(creation) => {
+                report.creation = Some(compare_creation_code(artifact, &creation, metadata)?)
+            }
+            Err(reason) => report.creation_skipped = Some(reason),
+        }
+
+        report.matches = report.mismatches().next().is_none();
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print!("{report}");
+        }
+        if !report.matches {
+            eyre::bail!(
+                "The local bytecode of `{}` doesn't match the code at {address}",
+                report.contract
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The result of comparing the on-chain code of a contract with its local build.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BytecodeReport {
+    /// The address of the checked contract.
+    pub address: Address,
+    /// The address of the proxy, if the implementation was checked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Address>,
+    pub contract: String,
+    /// Whether none of the regions mismatch.
+    pub matches: bool,
+    pub runtime: Vec<Region>,
+    /// The regions of the creation code, if the creation transaction was found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creation: Option<Vec<Region>>,
+    /// Why the creation code wasn't checked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creation_skipped: Option<String>,
+}
+
+impl BytecodeReport {
+    /// Returns the regions that don't match the local build.
+    pub fn mismatches(&self) -> impl Iterator<Item = &Region> {
+        self.runtime
+            .iter()
+            .chain(self.creation.iter().flatten())
+            .filter(|region| region.status == RegionStatus::Mismatch)
+    }
+}
+
+impl fmt::Display for BytecodeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.proxy {
+            Some(proxy) => writeln!(
+                f,
+                "Runtime code of `{}` at {} (implementation of {proxy}):",
+                self.contract, self.address
+            )?,
+            None => writeln!(f, "Runtime code of `{}` at {}:", self.contract, self.address)?,
+        }
+        for region in &self.runtime {
+            writeln!(f, "  {region}")?;
+        }
+        match (&self.creation, &self.creation_skipped) {
+            (Some(creation), _) => {
+                writeln!(f, "Creation code:")?;
+                for region in creation {
+                    writeln!(f, "  {region}")?;
+                }
+            }
+            (None, Some(reason)) => writeln!(f, "Creation code: skipped ({reason})")?,
+            (None, None) => {}
+        }
+        if self.matches {
+            writeln!(f, "Bytecode matches")
+        } else {
+            writeln!(f, "Bytecode doesn't match")
+        }
+    }
+}
+
+/// A region of the code and how it compares to the local build.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub kind: RegionKind,
+    /// The name of the immutable variable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub status: RegionStatus,
+    /// The byte offsets of the region in the on-chain code.
+    pub offsets: Vec<usize>,
+    /// The on-chain bytes of the region, if it isn't code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Bytes>,
+    /// The decoded on-chain value of an immutable or of the constructor arguments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Region {
+    fn new(kind: RegionKind, status: RegionStatus, offsets: Vec<usize>) -> Self {
+        Self { kind, name: None, status, offsets, value: None, decoded: None, detail: None }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self.status {
+            RegionStatus::Match => "✓",
+            RegionStatus::Mismatch => "✗",
+            RegionStatus::Differs => "~",
+            RegionStatus::NotCompared => "•",
+        };
+        write!(f, "{symbol} {}", self.kind)?;
+        if let Some(name) = &self.name {
+            write!(f, " `{name}`")?;
+        }
+        let offsets = self.offsets.iter().map(|offset| format!("{offset:#x}"));
+        write!(f, " at {}: {}", offsets.collect::<Vec<_>>().join(", "), self.status)?;
+        match (&self.decoded, &self.value) {
+            (Some(decoded), _) => write!(f, ", {decoded}")?,
+            (None, Some(value)) if self.kind != RegionKind::Metadata => write!(f, ", {value}")?,
+            _ => {}
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, " ({detail})")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RegionKind {
+    /// The compiled code, excluding the other regions.
+    Code,
+    /// The CBOR encoded metadata appended by solc.
+    Metadata,
+    /// The value of an immutable variable, set by the constructor.
+    Immutable,
+    /// The ABI-encoded constructor arguments appended to the creation code.
+    ConstructorArgs,
+}
+
+impl fmt::Display for RegionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionKind::Code => f.write_str("code"),
+            RegionKind::Metadata => f.write_str("metadata"),
+            RegionKind::Immutable => f.write_str("immutable"),
+            RegionKind::ConstructorArgs => f.write_str("constructor args"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RegionStatus {
+    Match,
+    Mismatch,
+    /// Differs from the local build without failing the check, e.g. the metadata hash.
+    Differs,
+    /// Only known after deployment, e.g. immutables.
+    NotCompared,
+}
+
+impl fmt::Display for RegionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionStatus::Match => f.write_str("match"),
+            RegionStatus::Mismatch => f.write_str("mismatch"),
+            RegionStatus::Differs => f.write_str("differs (ignored)"),
+            RegionStatus::NotCompared => f.write_str("deployment specific"),
+        }
+    }
+}
+
+/// Compiles the source that would be submitted to verify the contract, and compares its runtime
+/// code with the code deployed at the address, without contacting the verifier.
+///
+/// The metadata and the immutables don't fail the check, they're reported separately.
+pub async fn preflight(args: &VerifyArgs) -> Result<BytecodeReport> {
+    if is_vyper_contract(args) {
+        eyre::bail!("The preflight check isn't supported for Vyper contracts")
+    }
+    let config = args.load_config_emit_warnings();
+    if config.get_rpc_url().is_none() {
+        eyre::bail!("You have to provide a valid RPC URL to run the preflight check")
+    }
+
+    let request = EtherscanVerificationProvider::default()
+        .create_verify_request(args, Some(config.clone()))
+        .await?;
+    let output = compile_verify_request(&request)?;
+
+    let (path, name) = match request.contract_name.split_once(':') {
+        Some((path, name)) => (path, name),
+        None => ("contract.sol", request.contract_name.as_str()),
+    };
+    let deployed = output
+        .contracts
+        .iter()
+        .filter(|(file, _)| file.ends_with(path))
+        .find_map(|(_, contracts)| contracts.get(name))
+        .and_then(|contract| contract.evm.as_ref()?.deployed_bytecode.clone())
+        .map(CompactDeployedBytecode::from)
+        .ok_or_else(|| eyre::eyre!("The verification input doesn't compile `{name}`"))?;
+    let asts = output
+        .sources
+        .values()
+        .filter_map(|source| source.ast.as_ref())
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let provider = utils::get_provider(&config)?;
+    let code = provider.get_code(args.address.to_ethers(), None).await?.to_alloy();
+    if code.is_empty() {
+        eyre::bail!("No code is deployed at {}", args.address);
+    }
+
+    let mut report = BytecodeReport {
+        address: args.address,
+        proxy: None,
+        contract: name.to_string(),
+        matches: true,
+        runtime: compare_runtime_code(&deployed, &code, &asts, config.cbor_metadata)?,
+        creation: None,
+        creation_skipped: None,
+    };
+    report.matches = report.mismatches().next().is_none();
+    Ok(report)
+}
+
+/// Compiles the source of the verify request with its compiler version, as the verifier would.
+fn compile_verify_request(request: &VerifyContract) -> Result<CompilerOutput> {
+    let version: Version = request.compiler_version.trim_start_matches('v').parse()?;
+    // the installed compilers don't carry the build metadata
+    let version = Version::new(version.major, version.minor, version.patch);
+    let solc = match Solc::find_svm_installed_version(version.to_string())? {
+        Some(solc) => solc,
+        None => Solc::blocking_install(&version)?,
+    };
+
+    let mut input: Value = match request.code_format {
+        CodeFormat::StandardJsonInput => serde_json::from_str(&request.source)?,
+        CodeFormat::SingleFile => {
+            let runs = request.runs.as_deref().unwrap_or("200").parse::<usize>()?;
+            let mut input = serde_json::json!({
+                "language": "Solidity",
+                "sources": { "contract.sol": { "content": request.source } },
+                "settings": {
+                    "optimizer": {
+                        "enabled": request.optimization_used.as_deref() == Some("1"),
+                        "runs": runs,
+                    },
+                    "viaIR": request.via_ir.unwrap_or_default(),
+                },
+            });
+            if let Some(evm_version) = &request.evm_version {
+                input["settings"]["evmVersion"] = evm_version.as_str().into();
+            }
+            input
+        }
+    };
+    // the output selection doesn't affect the bytecode
+    input["settings"]["outputSelection"] =
+        serde_json::json!({ "*": { "*": ["evm.deployedBytecode"], "": ["ast"] } });
+
+    let output: CompilerOutput = solc.compile_as(&input)?;
+    if output.has_error() {
+        let errors = output
+            .errors
+            .iter()
+            .filter(|err| err.severity.is_error())
+            .map(|err| err.to_string())
+            .collect::<Vec<_>>();
+        eyre::bail!("Failed to compile the verification input:\n{}", errors.join("\n"));
+    }
+    Ok(output)
+}
+
+/// Fetches the creation code of the contract from the transaction that created it, as found by
+/// Etherscan.
+///
+/// Returns why it can't be fetched otherwise.
+async fn fetch_creation_code(
+    config: &Config,
+    provider: &RetryProvider,
+    chain: Chain,
+    address: Address,
+) -> Result<Bytes, String> {
+    let key = config.get_etherscan_api_key(Some(chain)).ok_or("no Etherscan API key")?;
+    let client = EtherscanVerificationProvider::default()
+        .client(chain, None, Some(&key), config)
+        .map_err(|err| err.to_string())?;
+    let creation = client
+        .request(|client| client.contract_creation_data(address))
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let hash = creation.transaction_hash.to_ethers();
+    let tx = provider
+        .get_transaction(hash)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or("couldn't fetch the creation transaction from RPC")?;
+    let receipt = provider
+        .get_transaction_receipt(hash)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or("couldn't fetch the creation transaction receipt from RPC")?;
+
+    if receipt.contract_address == Some(address.to_ethers()) {
+        Ok(tx.input.to_alloy())
+    } else if tx.to == Some(DEFAULT_CREATE2_DEPLOYER.to_ethers()) && tx.input.len() > 32 {
+        // the deployer is called with the salt followed by the creation code
+        Ok(Bytes::copy_from_slice(&tx.input[32..]))
+    } else {
+        Err("created by a contract".to_string())
+    }
+}
+
+/// Returns the version and the artifact of the contract.
+fn find_artifact<'a>(
+    output: &'a ProjectCompileOutput,
+    root: &Path,
+    contract: &ContractInfo,
+) -> Result<(Version, &'a ConfigurableContractArtifact)> {
+    let target_path = contract.path.as_ref().map(|path| root.join(path));
+    output
+        .artifact_ids()
+        .find(|(id, _)| {
+            id.name == contract.name &&
+                target_path.as_ref().map_or(true, |path| root.join(&id.source) == *path)
+        })
+        .map(|(id, artifact)| (id.version, artifact))
+        .ok_or_else(|| eyre::eyre!("Could not find artifact `{}`", contract.name))
+}
+
+/// Returns the deployed bytecode of the artifact.
+fn deployed_bytecode(artifact: &ConfigurableContractArtifact) -> Result<&CompactDeployedBytecode> {
+    artifact.deployed_bytecode.as_ref().ok_or_eyre("Artifact has no deployed bytecode")
+}
+
+/// Compares the runtime code with the local deployed bytecode, masking out the immutables.
+fn compare_runtime_code(
+    deployed: &CompactDeployedBytecode,
+    code: &[u8],
+    asts: &[Value],
+    metadata: bool,
+) -> Result<Vec<Region>> {
+    let local =
+        deployed.bytecode.as_ref().and_then(|bytecode| bytecode.object.as_bytes()).ok_or_eyre(
+            "Artifact has unlinked libraries, they have to be provided in the config",
+        )?;
+
+    let mut immutables = Vec::new();
+    let mut masked = code.to_vec();
+    for (id, offsets) in &deployed.immutable_references {
+        let (name, ty) = id
+            .parse()
+            .ok()
+            .and_then(|id| immutable_declaration(asts, id))
+            .unwrap_or_else(|| (format!("#{id}"), None));
+        immutables.push(immutable_region(code, name, ty, offsets));
+        for offset in offsets {
+            let (start, end) = (offset.start as usize, (offset.start + offset.length) as usize);
+            if let Some(bytes) = masked.get_mut(start..end) {
+                bytes.fill(0);
+            }
+        }
+    }
+
+    let mut regions = compare_code(local, &masked, metadata);
+    regions.splice(1..1, immutables);
+    Ok(regions)
+}
+
+/// Compares the creation code with the local bytecode, and decodes the constructor arguments
+/// appended to it.
+fn compare_creation_code(
+    artifact: &ConfigurableContractArtifact,
+    creation: &[u8],
+    metadata: bool,
+) -> Result<Vec<Region>> {
+    let local =
+        artifact.bytecode.as_ref().and_then(|bytecode| bytecode.object.as_bytes()).ok_or_eyre(
+            "Artifact has unlinked libraries, they have to be provided in the config",
+        )?;
+    if creation.len() < local.len() {
+        return Ok(vec![Region {
+            detail: Some(format!(
+                "the creation code is shorter than the local bytecode ({} < {} bytes)",
+                creation.len(),
+                local.len()
+            )),
+            ..Region::new(RegionKind::Code, RegionStatus::Mismatch, vec![0])
+        }])
+    }
+
+    let (code, args) = creation.split_at(creation_code_len(local, creation, metadata));
+    let mut regions = compare_code(local, code, metadata);
+    let constructor = artifact.abi.as_ref().and_then(|abi| abi.constructor());
+    if args.is_empty() && constructor.map_or(true, |constructor| constructor.inputs.is_empty()) {
+        return Ok(regions)
+    }
+
+    let mut region = Region {
+        value: Some(Bytes::copy_from_slice(args)),
+        ..Region::new(RegionKind::ConstructorArgs, RegionStatus::NotCompared, vec![code.len()])
+    };
+    match constructor.map(|constructor| constructor.abi_decode_input(args, true)) {
+        Some(Ok(values)) => {
+            region.decoded = Some(values.iter().map(format_token).collect::<Vec<_>>().join(", "));
+        }
+        Some(Err(err)) => {
+            region.status = RegionStatus::Mismatch;
+            region.detail = Some(format!("can't decode the arguments: {err}"));
+        }
+        None => {}
+    }
+    regions.push(region);
+    Ok(regions)
+}
+
+/// Returns the length of the creation code without the constructor arguments appended to it.
+///
+/// The on-chain metadata can be longer or shorter than the local one, e.g. if it was compiled with
+/// another `bytecode_hash`, so the end of the code is found by looking for the length of the
+/// metadata that starts where the local metadata does.
+fn creation_code_len(local: &[u8], creation: &[u8], metadata: bool) -> usize {
+    let local_metadata_len = metadata_len(local);
+    if !metadata || local_metadata_len == 0 {
+        return local.len()
+    }
+    let metadata_start = local.len() - local_metadata_len;
+    (metadata_start + 2..=creation.len())
+        .find(|&end| end - metadata_len(&creation[..end]) == metadata_start)
+        .unwrap_or(local.len())
+}
+
+/// Compares the code with the local bytecode, excluding the metadata trailers.
+fn compare_code(local: &[u8], code: &[u8], metadata: bool) -> Vec<Region> {
+    let local_end = local.len() - if metadata { metadata_len(local) } else { 0 };
+    let end = code.len() - if metadata { metadata_len(code) } else { 0 };
+
+    let mut regions = Vec::with_capacity(2);
+    let code_region = match first_difference(&local[..local_end], &code[..end]) {
+        None => Region::new(RegionKind::Code, RegionStatus::Match, vec![0]),
+        Some(offset) => Region {
+            detail: Some(format!(
+                "first difference at byte {offset:#x}, the local code is {local_end} bytes, the \
+                 on-chain code {end} bytes"
+            )),
+            ..Region::new(RegionKind::Code, RegionStatus::Mismatch, vec![0])
+        },
+    };
+    regions.push(code_region);
+
+    if metadata {
+        let status = if local[local_end..] == code[end..] {
+            RegionStatus::Match
+        } else {
+            RegionStatus::Differs
+        };
+        regions.push(Region {
+            value: Some(Bytes::copy_from_slice(&code[end..])),
+            ..Region::new(RegionKind::Metadata, status, vec![end])
+        });
+    }
+    regions
+}
+
+/// Returns the region of an immutable variable with its on-chain value.
+///
+/// The value is written to every reference of the variable, so they must all be equal.
+fn immutable_region(code: &[u8], name: String, ty: Option<String>, offsets: &[Offsets]) -> Region {
+    let values = offsets
+        .iter()
+        .map(|offset| code.get(offset.start as usize..(offset.start + offset.length) as usize))
+        .collect::<Option<Vec<_>>>();
+    let starts = offsets.iter().map(|offset| offset.start as usize).collect();
+    let mut region = Region {
+        name: Some(name),
+        ..Region::new(RegionKind::Immutable, RegionStatus::NotCompared, starts)
+    };
+
+    let Some(value) = values.as_ref().and_then(|values| values.first()) else {
+        region.status = RegionStatus::Mismatch;
+        region.detail = Some("the on-chain code is too short".to_string());
+        return region
+    };
+    if values.iter().flatten().any(|other| other != value) {
+        region.status = RegionStatus::Mismatch;
+        region.detail = Some("the references have different values".to_string());
+    }
+    region.value = Some(Bytes::copy_from_slice(value));
+    region.decoded = ty
+        .and_then(|ty| DynSolType::parse(&ty).ok())
+        .and_then(|ty| ty.abi_decode(value).ok())
+        .map(|value| format_token(&value));
+    region
+}
+
+/// Returns the name and the ABI type of the variable declared by the AST node.
+fn immutable_declaration(asts: &[Value], id: u64) -> Option<(String, Option<String>)> {
+    asts.iter().find_map(|ast| find_node(ast, id)).and_then(|node| {
+        let name = node["name"].as_str()?.to_string();
+        let ty = node["typeDescriptions"]["typeString"].as_str().map(abi_type);
+        Some((name, ty))
+    })
+}
+
+/// Converts the Solidity type of an AST node to its ABI type.
+fn abi_type(ty: &str) -> String {
+    if ty.starts_with("contract ") || ty.starts_with("address") {
+        "address".to_string()
+    } else if ty.starts_with("enum ") {
+        "uint8".to_string()
+    } else {
+        ty.to_string()
+    }
+}
+
+/// Finds the AST node with the given ID.
+fn find_node(value: &Value, id: u64) -> Option<&Value> {
+    match value {
+        Value::Object(map) => {
+            if map.contains_key("nodeType") && map.get("id").and_then(Value::as_u64) == Some(id) {
+                return Some(value)
+            }
+            map.values().find_map(|value| find_node(value, id))
+        }
+        Value::Array(values) => values.iter().find_map(|value| find_node(value, id)),
+        _ => None,
+    }
+}
+
+/// Returns the offset of the first byte that differs, or the length of the shorter code if one is
+/// a prefix of the other.
+fn first_difference(a: &[u8], b: &[u8]) -> Option<usize> {
+    a.iter()
+        .zip(b)
+        .position(|(a, b)| a != b)
+        .or_else(|| (a.len() != b.len()).then(|| a.len().min(b.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+
+    #[test]
+    fn can_compare_code() {
+        // `PUSH1 0x80` followed by 4 bytes of metadata and their length
+        let local = hex!("6080010203040004");
+        let same = compare_code(&local, &local, true);
+        assert!(same.iter().all(|region| region.status == RegionStatus::Match));
+
+        let other_metadata = hex!("60800a0b0c0d0004");
+        let regions = compare_code(&local, &other_metadata, true);
+        assert_eq!(regions[0].status, RegionStatus::Match);
+        assert_eq!(regions[1].status, RegionStatus::Differs);
+        assert_eq!(regions[1].offsets, vec![2]);
+
+        let other_code = hex!("6081010203040004");
+        let regions = compare_code(&local, &other_code, true);
+        assert_eq!(regions[0].status, RegionStatus::Mismatch);
+        assert!(regions[0].detail.as_ref().unwrap().contains("byte 0x1"));
+    }
+
+    #[test]
+    fn can_split_constructor_args() {
+        // `PUSH1 0x80` followed by 4 bytes of metadata and their length
+        let local = hex!("6080010203040004");
+        let args = [0u8; 32];
+        assert_eq!(creation_code_len(&local, &[local.as_slice(), &args].concat(), true), 8);
+
+        // longer metadata, the arguments start after it
+        let creation = [hex!("60800102030405060006").as_slice(), &args].concat();
+        assert_eq!(creation_code_len(&local, &creation, true), 10);
+
+        assert_eq!(creation_code_len(&local, &creation, false), 8);
+    }
+
+    #[test]
+    fn can_decode_immutables() {
+        let mut code = vec![0u8; 70];
+        code[3..35].copy_from_slice(&[[0u8; 31].as_slice(), &[7]].concat());
+        code[38..70].copy_from_slice(&code[3..35].to_vec());
+        let offsets = [Offsets { start: 3, length: 32 }, Offsets { start: 38, length: 32 }];
+
+        let region =
+            immutable_region(&code, "fee".to_string(), Some("uint256".to_string()), &offsets);
+        assert_eq!(region.status, RegionStatus::NotCompared);
+        assert_eq!(region.offsets, vec![3, 38]);
+        assert_eq!(region.decoded.as_deref(), Some("7"));
+
+        code[69] = 8;
+        let region = immutable_region(&code, "fee".to_string(), None, &offsets);
+        assert_eq!(region.status, RegionStatus::Mismatch);
+    }
+
+    #[test]
+    fn can_convert_abi_types() {
+        assert_eq!(abi_type("contract IERC20"), "address");
+        assert_eq!(abi_type("address payable"), "address");
+        assert_eq!(abi_type("enum Vault.State"), "uint8");
+        assert_eq!(abi_type("bytes32"), "bytes32");
+    }
+}
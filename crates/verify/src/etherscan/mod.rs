@@ -1,6 +1,7 @@
 use super::{provider::VerificationProvider, VerifyArgs, VerifyCheckArgs};
 use crate::retry::RETRY_CHECK_ON_VERIFY;
 use alloy_json_abi::Function;
+use alloy_primitives::Address;
 use ethers_providers::Middleware;
 use eyre::{eyre, Context, OptionExt, Result};
 use foundry_block_explorers::{
@@ -10,7 +11,13 @@ use foundry_block_explorers::{
     Client,
 };
 use foundry_cli::utils::{self, get_cached_entry_by_name, read_constructor_args_file, LoadConfig};
-use foundry_common::{abi::encode_function_args, retry::Retry, types::ToEthers};
+use foundry_common::{
+    abi::encode_function_args,
+    etherscan::{is_rate_limited, EtherscanClient},
+    retry::Retry,
+    types::ToEthers,
+    vyper::is_vyper_file,
+};
 use foundry_compilers::{
     artifacts::{BytecodeObject, CompactContract},
     cache::CacheEntry,
@@ -30,6 +37,9 @@ use std::{
 
 mod flatten;
 mod standard_json;
+mod vyper;
+
+use vyper::VerifyVyperContract;
 
 pub static RE_BUILD_COMMIT: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?P<commit>commit\.[0-9,a-f]{8})").unwrap());
@@ -57,15 +67,24 @@ trait EtherscanSourceProvider: Send + Sync + Debug {
 #[async_trait::async_trait]
 impl VerificationProvider for EtherscanVerificationProvider {
     async fn preflight_check(&mut self, args: VerifyArgs) -> Result<()> {
+        if is_vyper_contract(&args) {
+            let config = args.try_load_config_emit_warnings()?;
+            let _ = VerifyVyperContract::new(&args, &config).await?;
+            return Ok(())
+        }
         let _ = self.prepare_request(&args).await?;
         Ok(())
     }
 
     async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
+        if is_vyper_contract(&args) {
+            return self.verify_vyper(args).await
+        }
+
         let (etherscan, verify_args) = self.prepare_request(&args).await?;
 
         if !args.skip_is_verified_check &&
-            self.is_contract_verified(&etherscan, &verify_args).await?
+            self.is_contract_verified(&etherscan, verify_args.address).await?
         {
             println!(
                 "\nContract [{}] {:?} is already verified. Skipping verification.",
@@ -78,6 +97,7 @@ impl VerificationProvider for EtherscanVerificationProvider {
 
         trace!(target: "forge::verify", ?verify_args, "submitting verification request");
 
+        // Rate limited requests are retried by the client, so they're final failures here
         let retry: Retry = args.retry.into();
         let resp = retry
             .run_async(|| async {
@@ -86,14 +106,21 @@ impl VerificationProvider for EtherscanVerificationProvider {
                     verify_args.contract_name, verify_args.address
                 );
                 let resp = etherscan
-                    .submit_contract_verification(&verify_args)
-                    .await
-                    .wrap_err_with(|| {
+                    .request(|client| client.submit_contract_verification(&verify_args))
+                    .await;
+                let resp = match resp {
+                    Ok(resp) => resp,
+                    Err(err) => {
+                        let rate_limited = is_rate_limited(&err);
                         // valid json
                         let args = serde_json::to_string(&verify_args).unwrap();
                         error!(target: "forge::verify", ?args, "Failed to submit verification");
-                        format!("Failed to submit contract verification, payload:\n{args}")
-                    })?;
+                        let err = eyre::Report::new(err).wrap_err(format!(
+                            "Failed to submit contract verification, payload:\n{args}"
+                        ));
+                        return if rate_limited { Ok(Err(err)) } else { Err(err) }
+                    }
+                };
 
                 trace!(target: "forge::verify", ?resp, "Received verification response");
 
@@ -102,7 +129,7 @@ impl VerificationProvider for EtherscanVerificationProvider {
                         // specific for blockscout response
                         || resp.result == "Smart-contract already verified."
                     {
-                        return Ok(None)
+                        return Ok(Ok(None))
                     }
 
                     if resp.result.starts_with("Unable to locate ContractCode at") {
@@ -110,17 +137,21 @@ impl VerificationProvider for EtherscanVerificationProvider {
                         return Err(eyre!("Etherscan could not detect the deployment."))
                     }
 
+                    // The submission was rejected, so there is no point in retrying it
                     warn!("Failed verify submission: {:?}", resp);
-                    eprintln!(
-                        "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
-                        resp.message, resp.result
-                    );
-                    std::process::exit(1);
                 }
 
-                Ok(Some(resp))
+                Ok(Ok(Some(resp)))
             })
-            .await?;
+            .await??;
+
+        if let Some(resp) = resp.as_ref().filter(|resp| resp.status == "0") {
+            eyre::bail!(
+                "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
+                resp.message,
+                resp.result
+            )
+        }
 
         if let Some(resp) = resp {
             println!(
@@ -152,7 +183,7 @@ impl VerificationProvider for EtherscanVerificationProvider {
         let config = args.try_load_config_emit_warnings()?;
         let etherscan = self.client(
             args.etherscan.chain.unwrap_or_default(),
-            args.verifier.verifier_url.as_deref(),
+            args.verifier.url(),
             args.etherscan.key().as_deref(),
             &config,
         )?;
@@ -161,9 +192,21 @@ impl VerificationProvider for EtherscanVerificationProvider {
             .run_async(|| {
                 async {
                     let resp = etherscan
-                        .check_contract_verification_status(args.id.clone())
-                        .await
-                        .wrap_err("Failed to request verification status")?;
+                        .request(|client| {
+                            client.check_contract_verification_status(args.id.clone())
+                        })
+                        .await;
+                    let resp = match resp {
+                        Ok(resp) => resp,
+                        // the client already retried the rate limited request
+                        Err(err) if is_rate_limited(&err) => {
+                            return Ok(Err(eyre::Report::new(err)
+                                .wrap_err("Failed to request verification status")))
+                        }
+                        Err(err) => {
+                            return Err(err).wrap_err("Failed to request verification status")
+                        }
+                    };
 
                     trace!(target: "forge::verify", ?resp, "Received verification response");
 
@@ -182,24 +225,24 @@ impl VerificationProvider for EtherscanVerificationProvider {
 
                     if resp.result == "Already Verified" {
                         println!("Contract source code already verified");
-                        return Ok(())
+                        return Ok(Ok(()))
                     }
 
+                    // A final failure, which is returned without retrying
                     if resp.status == "0" {
-                        println!("Contract failed to verify.");
-                        std::process::exit(1);
+                        return Ok(Err(eyre!("Contract failed to verify.")))
                     }
 
                     if resp.result == "Pass - Verified" {
                         println!("Contract successfully verified");
                     }
 
-                    Ok(())
+                    Ok(Ok(()))
                 }
                 .boxed()
             })
             .await
-            .wrap_err("Checking verification result failed:")
+            .wrap_err("Checking verification result failed:")?
     }
 }
 
@@ -244,11 +287,14 @@ impl EtherscanVerificationProvider {
     }
 
     /// Configures the API request to the etherscan API using the given [`VerifyArgs`].
-    async fn prepare_request(&mut self, args: &VerifyArgs) -> Result<(Client, VerifyContract)> {
+    async fn prepare_request(
+        &mut self,
+        args: &VerifyArgs,
+    ) -> Result<(EtherscanClient, VerifyContract)> {
         let config = args.try_load_config_emit_warnings()?;
         let etherscan = self.client(
             args.etherscan.chain.unwrap_or_default(),
-            args.verifier.verifier_url.as_deref(),
+            args.verifier.url(),
             args.etherscan.key().as_deref(),
             &config,
         )?;
@@ -257,13 +303,85 @@ impl EtherscanVerificationProvider {
         Ok((etherscan, verify_args))
     }
 
+    /// Submits the verification of a Vyper contract as `vyper-json` standard json input.
+    async fn verify_vyper(&mut self, args: VerifyArgs) -> Result<()> {
+        let config = args.try_load_config_emit_warnings()?;
+        let chain = args.etherscan.chain.unwrap_or_default();
+        let etherscan =
+            self.client(chain, args.verifier.url(), args.etherscan.key().as_deref(), &config)?;
+        let api_key = match args.etherscan.key() {
+            Some(key) => key,
+            None => config
+                .get_etherscan_config_with_chain(Some(chain))?
+                .map(|config| config.key)
+                .unwrap_or_default(),
+        };
+        let request = VerifyVyperContract::new(&args, &config).await?;
+
+        if !args.skip_is_verified_check &&
+            self.is_contract_verified(&etherscan, args.address).await?
+        {
+            println!(
+                "\nContract [{}] {:?} is already verified. Skipping verification.",
+                request.contract_name,
+                args.address.to_checksum(None)
+            );
+            return Ok(())
+        }
+
+        trace!(target: "forge::verify", ?request, "submitting vyper verification request");
+
+        let retry: Retry = args.retry.into();
+        let resp = retry
+            .run_async(|| async {
+                println!(
+                    "\nSubmitting verification for [{}] {}.",
+                    request.contract_name, args.address
+                );
+                request.submit(&etherscan, &api_key).await
+            })
+            .await?;
+
+        trace!(target: "forge::verify", ?resp, "Received verification response");
+
+        if resp.status == "0" {
+            if resp.result == "Contract source code already verified" {
+                println!("Contract source code already verified");
+                return Ok(())
+            }
+            eyre::bail!(
+                "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
+                resp.message,
+                resp.result
+            )
+        }
+
+        println!(
+            "Submitted contract for verification:\n\tResponse: `{}`\n\tGUID: `{}`\n\tURL: {}",
+            resp.message,
+            resp.result,
+            etherscan.address_url(args.address)
+        );
+
+        if args.watch {
+            let check_args = VerifyCheckArgs {
+                id: resp.result,
+                etherscan: args.etherscan,
+                retry: RETRY_CHECK_ON_VERIFY,
+                verifier: args.verifier,
+            };
+            return self.check(check_args).await
+        }
+        Ok(())
+    }
+
     /// Queries the etherscan API to verify if the contract is already verified.
     async fn is_contract_verified(
         &self,
-        etherscan: &Client,
-        verify_contract: &VerifyContract,
+        etherscan: &EtherscanClient,
+        address: Address,
     ) -> Result<bool> {
-        let check = etherscan.contract_abi(verify_contract.address).await;
+        let check = etherscan.request(|client| client.contract_abi(address)).await;
 
         if let Err(err) = check {
             match err {
@@ -275,14 +393,14 @@ impl EtherscanVerificationProvider {
         Ok(true)
     }
 
-    /// Create an etherscan client
+    /// Create an etherscan client, sharing the rate limit of its API key
     pub(crate) fn client(
         &self,
         chain: Chain,
         verifier_url: Option<&str>,
         etherscan_key: Option<&str>,
         config: &Config,
-    ) -> Result<Client> {
+    ) -> Result<EtherscanClient> {
         let etherscan_config = config.get_etherscan_config_with_chain(Some(chain))?;
 
         let etherscan_api_url = verifier_url
@@ -308,10 +426,16 @@ impl EtherscanVerificationProvider {
             builder.chain(chain)?
         };
 
-        builder
-            .with_api_key(etherscan_key.unwrap_or_default())
+        let etherscan_key = etherscan_key.unwrap_or_default();
+        let client = builder
+            .with_api_key(etherscan_key)
             .build()
-            .wrap_err("Failed to create etherscan client")
+            .wrap_err("Failed to create etherscan client")?;
+        Ok(EtherscanClient::with_client(
+            client,
+            etherscan_key,
+            config.etherscan_requests_per_second,
+        ))
     }
 
     /// Creates the `VerifyContract` etherscan request in order to verify the contract
@@ -488,12 +612,13 @@ impl EtherscanVerificationProvider {
         let provider = utils::get_provider(config)?;
         let client = self.client(
             args.etherscan.chain.unwrap_or_default(),
-            args.verifier.verifier_url.as_deref(),
+            args.verifier.url(),
             args.etherscan.key.as_deref(),
             config,
         )?;
 
-        let creation_data = client.contract_creation_data(args.address).await?;
+        let creation_data =
+            client.request(|client| client.contract_creation_data(args.address)).await?;
         let transaction = provider
             .get_transaction(creation_data.transaction_hash.to_ethers())
             .await?
@@ -538,6 +663,11 @@ impl EtherscanVerificationProvider {
     }
 }
 
+/// Returns `true` if the contract to verify is a Vyper contract.
+pub(crate) fn is_vyper_contract(args: &VerifyArgs) -> bool {
+    args.contract.path.as_ref().map_or(false, is_vyper_file)
+}
+
 /// Given any solc [Version] return a [Version] with build metadata
 ///
 /// # Example
@@ -595,7 +725,7 @@ mod tests {
         let client = etherscan
             .client(
                 args.etherscan.chain.unwrap_or_default(),
-                args.verifier.verifier_url.as_deref(),
+                args.verifier.url(),
                 args.etherscan.key().as_deref(),
                 &config,
             )
@@ -622,7 +752,7 @@ mod tests {
         let client = etherscan
             .client(
                 args.etherscan.chain.unwrap_or_default(),
-                args.verifier.verifier_url.as_deref(),
+                args.verifier.url(),
                 args.etherscan.key().as_deref(),
                 &config,
             )
@@ -0,0 +1,147 @@
+use super::VerifyArgs;
+use alloy_json_abi::Function;
+use alloy_primitives::Address;
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::{Client, Response};
+use foundry_cli::utils::read_constructor_args_file;
+use foundry_common::{
+    abi::encode_function_args,
+    vyper::{compile_vyper, standard_json_input, vyper_sources, Vyper},
+};
+use foundry_config::{Config, VyperOptimizationMode};
+use serde::Serialize;
+use std::path::Path;
+
+/// The `verifysourcecode` request of a Vyper contract.
+///
+/// [`VerifyContract`](foundry_block_explorers::verify::VerifyContract) can't be used since it has
+/// no `vyper-json` code format.
+#[derive(Clone, Debug, Serialize)]
+pub struct VerifyVyperContract {
+    #[serde(rename = "contractaddress")]
+    pub address: Address,
+    #[serde(rename = "sourceCode")]
+    pub source: String,
+    #[serde(rename = "codeformat")]
+    pub code_format: &'static str,
+    #[serde(rename = "contractname")]
+    pub contract_name: String,
+    #[serde(rename = "compilerversion")]
+    pub compiler_version: String,
+    #[serde(rename = "optimizationUsed")]
+    pub optimization_used: &'static str,
+    #[serde(rename = "constructorArguements", skip_serializing_if = "Option::is_none")]
+    pub constructor_arguments: Option<String>,
+}
+
+/// The form posted to the etherscan API.
+#[derive(Serialize)]
+struct VerifyVyperForm<'a> {
+    apikey: &'a str,
+    module: &'static str,
+    action: &'static str,
+    #[serde(flatten)]
+    request: &'a VerifyVyperContract,
+}
+
+impl VerifyVyperContract {
+    /// Creates the request from the standard json input of all Vyper sources of the project.
+    pub async fn new(args: &VerifyArgs, config: &Config) -> Result<Self> {
+        let paths = config.project_paths();
+        let root = &paths.root;
+        let path = args
+            .contract
+            .path
+            .as_ref()
+            .map(|path| root.join(path))
+            .ok_or_else(|| eyre!("the path of a Vyper contract must be provided"))?;
+        if !path.exists() {
+            eyre::bail!("Contract {:?} does not exist.", path);
+        }
+
+        let input = standard_json_input(root, &vyper_sources(&paths), config.vyper.optimize)?;
+        let source =
+            serde_json::to_string(&input).wrap_err("Failed to parse standard json input")?;
+        trace!(target: "forge::verify", standard_json=source, "determined vyper standard json input");
+
+        let version = if let Some(version) = &args.compiler_version {
+            version.trim_start_matches('v').parse()?
+        } else {
+            Vyper::find_or_install(&config.vyper).await?.version
+        };
+
+        let optimization_used =
+            if config.vyper.optimize == Some(VyperOptimizationMode::None) { "0" } else { "1" };
+
+        Ok(Self {
+            address: args.address,
+            source,
+            code_format: "vyper-json",
+            contract_name: format!(
+                "{}:{}",
+                path.strip_prefix(root).unwrap_or(&path).display(),
+                args.contract.name
+            ),
+            compiler_version: format!(
+                "vyper:{}.{}.{}",
+                version.major, version.minor, version.patch
+            ),
+            optimization_used,
+            constructor_arguments: constructor_args(args, config, root).await?,
+        })
+    }
+
+    /// Submits the request, the `result` of a successful response is the GUID of the verification.
+    pub async fn submit(&self, client: &Client, api_key: &str) -> Result<Response<String>> {
+        let form = VerifyVyperForm {
+            apikey: api_key,
+            module: "contract",
+            action: "verifysourcecode",
+            request: self,
+        };
+        let resp = reqwest::Client::new()
+            .post(client.etherscan_api_url().clone())
+            .form(&form)
+            .send()
+            .await
+            .wrap_err("Failed to submit contract verification")?
+            .text()
+            .await?;
+        serde_json::from_str(&resp)
+            .wrap_err_with(|| format!("Failed to parse verification response: {resp}"))
+    }
+}
+
+/// Returns the encoded constructor arguments, read from `--constructor-args-path` with the ABI of
+/// the compiled contract, or else `--constructor-args`.
+async fn constructor_args(
+    args: &VerifyArgs,
+    config: &Config,
+    root: &Path,
+) -> Result<Option<String>> {
+    if args.guess_constructor_args {
+        eyre::bail!("`--guess-constructor-args` is not supported for Vyper contracts")
+    }
+    let Some(path) = &args.constructor_args_path else { return Ok(args.constructor_args.clone()) };
+
+    let output = compile_vyper(config, true).await?;
+    let artifact = output
+        .as_ref()
+        .and_then(|output| output.find_contract(root, &args.contract))
+        .ok_or_else(|| eyre!("Contract artifact wasn't found locally"))?;
+    let constructor = artifact
+        .abi
+        .as_ref()
+        .and_then(|abi| abi.constructor())
+        .ok_or_else(|| eyre!("Can't retrieve constructor info from artifact ABI."))?;
+    #[allow(deprecated)]
+    let func = Function {
+        name: "constructor".to_string(),
+        inputs: constructor.inputs.clone(),
+        outputs: vec![],
+        state_mutability: alloy_json_abi::StateMutability::NonPayable,
+    };
+    let encoded_args =
+        encode_function_args(&func, read_constructor_args_file(path.to_path_buf())?)?;
+    Ok(Some(hex::encode(&encoded_args[4..])))
+}
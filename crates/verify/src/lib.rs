@@ -12,11 +12,13 @@ use foundry_cli::{
     utils::LoadConfig,
 };
 use foundry_compilers::{info::ContractInfo, EvmVersion};
-use foundry_config::{figment, impl_figment_convert, impl_figment_convert_cast, Config};
+use foundry_config::{figment, impl_figment_convert, impl_figment_convert_cast, Chain, Config};
 use provider::VerificationProviderType;
 use reqwest::Url;
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 
+mod blockscout;
+pub mod bytecode;
 mod etherscan;
 use etherscan::EtherscanVerificationProvider;
 
@@ -26,23 +28,117 @@ use provider::VerificationProvider;
 pub mod retry;
 mod sourcify;
 
+pub use bytecode::VerifyBytecodeArgs;
 pub use retry::RetryArgs;
 
 /// Verification provider arguments
 #[derive(Clone, Debug, Parser)]
 pub struct VerifierArgs {
-    /// The contract verification provider to use.
-    #[arg(long, help_heading = "Verifier options", default_value = "etherscan", value_enum)]
-    pub verifier: VerificationProviderType,
+    /// The contract verification providers to use, in order of priority.
+    ///
+    /// Each provider is tried in turn until one of them verifies the contract, e.g.
+    /// `--verifier etherscan,sourcify,blockscout`.
+    #[arg(
+        long,
+        help_heading = "Verifier options",
+        default_value = "etherscan",
+        value_enum,
+        value_delimiter = ','
+    )]
+    pub verifier: Vec<VerificationProviderType>,
 
-    /// The verifier URL, if using a custom provider
-    #[arg(long, help_heading = "Verifier options", env = "VERIFIER_URL")]
-    pub verifier_url: Option<String>,
+    /// The verifier URL, if using a custom provider.
+    ///
+    /// Can be passed once per provider as `<provider>=<url>`, e.g.
+    /// `--verifier-url blockscout=https://eth.blockscout.com/api`. A URL without a provider
+    /// applies to the first provider.
+    #[arg(long, help_heading = "Verifier options", env = "VERIFIER_URL", value_name = "URL")]
+    pub verifier_url: Vec<VerifierUrl>,
 }
 
 impl Default for VerifierArgs {
     fn default() -> Self {
-        VerifierArgs { verifier: VerificationProviderType::Etherscan, verifier_url: None }
+        VerifierArgs { verifier: vec![VerificationProviderType::Etherscan], verifier_url: vec![] }
+    }
+}
+
+impl VerifierArgs {
+    /// Returns the provider that is tried first.
+    pub fn primary(&self) -> VerificationProviderType {
+        self.verifier.first().copied().unwrap_or_default()
+    }
+
+    /// Returns the verifier URL of the given provider, if any.
+    pub fn url_for(&self, provider: VerificationProviderType) -> Option<&str> {
+        self.verifier_url
+            .iter()
+            .find(|url| url.provider == Some(provider))
+            .or_else(|| {
+                self.verifier_url
+                    .iter()
+                    .find(|url| url.provider.is_none() && self.primary() == provider)
+            })
+            .map(|url| url.url.as_str())
+    }
+
+    /// Returns the verifier URL of the primary provider, if any.
+    pub fn url(&self) -> Option<&str> {
+        self.url_for(self.primary())
+    }
+
+    /// Returns these arguments narrowed down to a single provider and its URL.
+    pub fn for_provider(&self, provider: VerificationProviderType) -> Self {
+        let verifier_url = self
+            .url_for(provider)
+            .map(|url| VerifierUrl { provider: None, url: url.to_string() })
+            .into_iter()
+            .collect();
+        Self { verifier: vec![provider], verifier_url }
+    }
+
+    /// Returns these arguments with the verifier settings of `chain` in the config applied.
+    ///
+    /// The verifier settings of the chain in the config take precedence over the CLI ones.
+    pub fn with_chain_overrides(&self, config: &Config, chain: Chain) -> Self {
+        let mut args = self.clone();
+        let Some(overrides) = config.chains.get(chain) else { return args };
+        if let Some(verifier) = &overrides.verifier {
+            match verifier.parse::<VerificationProviderType>() {
+                Ok(verifier) => {
+                    debug!(%chain, ?verifier, "chain overrides the verifier");
+                    args.verifier = vec![verifier];
+                }
+                Err(err) => warn!(%chain, %err, "invalid verifier in the chain config"),
+            }
+        }
+        if let Some(url) = &overrides.verifier_url {
+            debug!(%chain, %url, "chain overrides the verifier URL");
+            let provider = Some(args.primary());
+            args.verifier_url.insert(0, VerifierUrl { provider, url: url.clone() });
+        }
+        args
+    }
+}
+
+/// A verifier URL, optionally scoped to a single provider as `<provider>=<url>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierUrl {
+    /// The provider this URL applies to. If unset, it applies to the primary provider.
+    pub provider: Option<VerificationProviderType>,
+    /// The URL of the provider's API.
+    pub url: String,
+}
+
+impl FromStr for VerifierUrl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((provider, url)) = s.split_once('=') {
+            if let Ok(provider) = provider.parse() {
+                return Ok(Self { provider: Some(provider), url: url.to_string() })
+            }
+        }
+        Ok(Self { provider: None, url: s.to_string() })
     }
 }
 
@@ -114,6 +210,13 @@ pub struct VerifyArgs {
     #[arg(long, conflicts_with = "flatten")]
     pub show_standard_json_input: bool,
 
+    /// Only check locally that the contract would verify, without submitting it.
+    ///
+    /// Compiles the source that would be submitted, and compares its runtime code with the code
+    /// deployed at the address, ignoring the metadata and the immutables.
+    #[arg(long, conflicts_with = "show_standard_json_input")]
+    pub preflight: bool,
+
     /// Use the Yul intermediate representation compilation pipeline.
     #[arg(long)]
     pub via_ir: bool,
@@ -172,7 +275,15 @@ impl figment::Provider for VerifyArgs {
 
 impl VerifyArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
-    pub async fn run(mut self) -> Result<()> {
+    pub async fn run(self) -> Result<()> {
+        self.run_with_fallback().await.map(drop)
+    }
+
+    /// Submits the contract's source code to each of the configured providers in order, until one
+    /// of them verifies it.
+    ///
+    /// Returns the provider that verified the contract.
+    pub async fn run_with_fallback(mut self) -> Result<VerificationProviderType> {
         let config = self.load_config_emit_warnings();
 
         if self.guess_constructor_args && config.get_rpc_url().is_none() {
@@ -193,41 +304,138 @@ impl VerifyArgs {
 
         self.etherscan.chain = Some(chain);
         self.etherscan.key = config.get_etherscan_config_with_chain(Some(chain))?.map(|c| c.key);
+        self.verifier = self.verifier.with_chain_overrides(&config, chain);
 
         if self.show_standard_json_input {
             let args =
                 EtherscanVerificationProvider::default().create_verify_request(&self, None).await?;
             println!("{}", args.source);
-            return Ok(())
+            return Ok(self.verifier.primary())
+        }
+
+        if self.preflight {
+            let report = bytecode::preflight(&self).await?;
+            print!("{report}");
+            if !report.matches {
+                eyre::bail!(
+                    "Preflight check failed: the verification input of `{}` doesn't compile to the code at {}",
+                    report.contract,
+                    self.address
+                );
+            }
+            println!(
+                "Preflight check passed: `{}` at {} would verify",
+                report.contract, self.address
+            );
+            return Ok(self.verifier.primary())
         }
 
-        let verifier_url = self.verifier.verifier_url.clone();
         println!("Start verifying contract `{}` deployed on {chain}", self.address);
-        self.verifier.verifier.client(&self.etherscan.key())?.verify(self).await.map_err(|err| {
-            if let Some(verifier_url) = verifier_url {
-                 match Url::parse(&verifier_url) {
-                    Ok(url) => {
-                        if is_host_only(&url) {
-                            return err.wrap_err(format!(
-                                "Provided URL `{verifier_url}` is host only.\n Did you mean to use the API endpoint`{verifier_url}/api` ?"
-                            ))
-                        }
-                    }
-                    Err(url_err) => {
-                        return err.wrap_err(format!(
-                            "Invalid URL {verifier_url} provided: {url_err}"
-                        ))
-                    }
-                }
+
+        let mut results = Vec::with_capacity(self.verifier.verifier.len());
+        for provider in self.verifier.verifier.clone() {
+            let mut args = self.clone();
+            args.verifier = self.verifier.for_provider(provider);
+            let verifier_url = args.verifier.url().map(str::to_string);
+
+            let result = match provider.client(&args.etherscan.key()) {
+                Ok(mut client) => client
+                    .verify(args)
+                    .await
+                    .map_err(|err| wrap_verifier_url_err(err, verifier_url.as_deref())),
+                Err(err) => Err(err),
+            };
+            let verified = result.is_ok();
+            results.push((provider, result));
+            if verified {
+                break
+            }
+            if results.len() < self.verifier.verifier.len() {
+                println!("Verification with {provider} failed, trying the next provider...");
             }
+        }
 
-            err
-        })
+        if results.len() > 1 {
+            print_verification_results(&self.address, &results);
+        }
+
+        // Verification stops at the first provider that succeeds
+        match results.pop() {
+            Some((provider, Ok(()))) => Ok(provider),
+            Some((_, Err(err))) if results.is_empty() => Err(err),
+            _ => eyre::bail!(
+                "Failed to verify contract `{}` with any of the providers: {}",
+                self.address,
+                self.verifier
+                    .verifier
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
     }
 
-    /// Returns the configured verification provider
-    pub fn verification_provider(&self) -> Result<Box<dyn VerificationProvider>> {
-        self.verifier.verifier.client(&self.etherscan.key())
+    /// Ensures that the verify request can be prepared for at least one of the configured
+    /// providers.
+    ///
+    /// See [VerificationProvider::preflight_check].
+    pub async fn preflight_check(self) -> Result<()> {
+        let mut errors = Vec::with_capacity(self.verifier.verifier.len());
+        for provider in self.verifier.verifier.clone() {
+            let mut args = self.clone();
+            args.verifier = self.verifier.for_provider(provider);
+            let result = match provider.client(&args.etherscan.key()) {
+                Ok(mut client) => client.preflight_check(args).await,
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(()) => return Ok(()),
+                Err(err) => errors.push((provider, err)),
+            }
+        }
+
+        if errors.len() == 1 {
+            return Err(errors.remove(0).1)
+        }
+        eyre::bail!(
+            "Verification can't be prepared for any of the providers:\n{}",
+            errors
+                .iter()
+                .map(|(provider, err)| format!("\t{provider}: {err}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    }
+}
+
+/// Prints whether each of the tried providers verified the contract.
+fn print_verification_results(
+    address: &Address,
+    results: &[(VerificationProviderType, Result<()>)],
+) {
+    println!("\nVerification results for `{address}`:");
+    for (provider, result) in results {
+        match result {
+            Ok(()) => println!("\t{provider}: verified"),
+            Err(err) => println!("\t{provider}: failed ({err})"),
+        }
+    }
+}
+
+/// Adds a hint to a verification error if the verifier URL looks misconfigured.
+fn wrap_verifier_url_err(err: eyre::Report, verifier_url: Option<&str>) -> eyre::Report {
+    let Some(verifier_url) = verifier_url else { return err };
+    match Url::parse(verifier_url) {
+        Ok(url) => {
+            if is_host_only(&url) {
+                return err.wrap_err(format!(
+                    "Provided URL `{verifier_url}` is host only.\n Did you mean to use the API endpoint`{verifier_url}/api` ?"
+                ))
+            }
+            err
+        }
+        Err(url_err) => err.wrap_err(format!("Invalid URL {verifier_url} provided: {url_err}")),
     }
 }
 
@@ -257,7 +465,7 @@ impl VerifyCheckArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
     pub async fn run(self) -> Result<()> {
         println!("Checking verification status on {}", self.etherscan.chain.unwrap_or_default());
-        self.verifier.verifier.client(&self.etherscan.key())?.check(self).await
+        self.verifier.primary().client(&self.etherscan.key())?.check(self).await
     }
 }
 
@@ -292,6 +500,38 @@ mod tests {
         assert!(is_host_only(&Url::parse("https://blockscout.net").unwrap()));
     }
 
+    #[test]
+    fn can_parse_verifier_priority_list() {
+        let args: VerifyArgs = VerifyArgs::parse_from([
+            "foundry-cli",
+            "0x0000000000000000000000000000000000000000",
+            "src/Domains.sol:Domains",
+            "--verifier",
+            "etherscan,sourcify,blockscout",
+            "--verifier-url",
+            "blockscout=https://eth.blockscout.com/api",
+            "--verifier-url",
+            "https://api.etherscan.io/api",
+        ]);
+        assert_eq!(
+            args.verifier.verifier,
+            vec![
+                VerificationProviderType::Etherscan,
+                VerificationProviderType::Sourcify,
+                VerificationProviderType::Blockscout
+            ]
+        );
+        assert_eq!(
+            args.verifier.url_for(VerificationProviderType::Etherscan),
+            Some("https://api.etherscan.io/api")
+        );
+        assert_eq!(args.verifier.url_for(VerificationProviderType::Sourcify), None);
+
+        let blockscout = args.verifier.for_provider(VerificationProviderType::Blockscout);
+        assert_eq!(blockscout.primary(), VerificationProviderType::Blockscout);
+        assert_eq!(blockscout.url(), Some("https://eth.blockscout.com/api"));
+    }
+
     #[test]
     fn can_parse_verify_contract() {
         let args: VerifyArgs = VerifyArgs::parse_from([
@@ -299,7 +539,9 @@ mod tests {
             "0x0000000000000000000000000000000000000000",
             "src/Domains.sol:Domains",
             "--via-ir",
+            "--preflight",
         ]);
         assert!(args.via_ir);
+        assert!(args.preflight);
     }
 }
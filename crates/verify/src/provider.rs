@@ -1,9 +1,10 @@
 use super::{
-    etherscan::EtherscanVerificationProvider, sourcify::SourcifyVerificationProvider, VerifyArgs,
-    VerifyCheckArgs,
+    blockscout::BlockscoutVerificationProvider, etherscan::EtherscanVerificationProvider,
+    sourcify::SourcifyVerificationProvider, VerifyArgs, VerifyCheckArgs,
 };
 use async_trait::async_trait;
 use eyre::Result;
+use serde::{Deserialize, Serialize};
 use std::{fmt, str::FromStr};
 
 /// An abstraction for various verification providers such as etherscan, sourcify, blockscout
@@ -55,7 +56,10 @@ impl fmt::Display for VerificationProviderType {
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
 pub enum VerificationProviderType {
     #[default]
     Etherscan,
@@ -77,7 +81,7 @@ impl VerificationProviderType {
                 Ok(Box::<SourcifyVerificationProvider>::default())
             }
             VerificationProviderType::Blockscout => {
-                Ok(Box::<EtherscanVerificationProvider>::default())
+                Ok(Box::<BlockscoutVerificationProvider>::default())
             }
         }
     }
@@ -40,7 +40,7 @@ impl VerificationProvider for SourcifyVerificationProvider {
                         args.address.to_string()
                     );
                     let response = client
-                        .post(args.verifier.verifier_url.as_deref().unwrap_or(SOURCIFY_URL))
+                        .post(args.verifier.url().unwrap_or(SOURCIFY_URL))
                         .header("Content-Type", "application/json")
                         .body(serde_json::to_string(&body)?)
                         .send()
@@ -70,9 +70,7 @@ impl VerificationProvider for SourcifyVerificationProvider {
         let resp = retry
             .run_async(|| {
                 async {
-                    let url = Url::from_str(
-                        args.verifier.verifier_url.as_deref().unwrap_or(SOURCIFY_URL),
-                    )?;
+                    let url = Url::from_str(args.verifier.url().unwrap_or(SOURCIFY_URL))?;
                     let query = format!(
                         "check-by-addresses?addresses={}&chainIds={}",
                         args.id,
@@ -1,3 +1,4 @@
+use crate::gcp::GcpSignerError;
 use ethers_signers::{AwsSignerError, LedgerError, TrezorError, WalletError};
 use hex::FromHexError;
 
@@ -20,6 +21,8 @@ pub enum WalletSignerError {
     #[error(transparent)]
     Aws(#[from] AwsSignerError),
     #[error(transparent)]
+    Gcp(#[from] GcpSignerError),
+    #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
     InvalidHex(#[from] FromHexError),
@@ -0,0 +1,331 @@
+//! Signer backed by a secp256k1 key in Google Cloud KMS.
+
+use crate::kms::{is_retryable_status, with_retry};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ethers_core::{
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature, H256, U256,
+    },
+    utils::{hash_message, keccak256},
+};
+use ethers_signers::{to_eip155_v, Signer};
+use k256::{
+    ecdsa::{RecoveryId, Signature as KSig, VerifyingKey},
+    pkcs8::DecodePublicKey,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt,
+    process::Command,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+/// The base URL of the Cloud KMS API.
+const GCP_KMS_API_URL: &str = "https://cloudkms.googleapis.com/v1";
+
+/// The environment variable of the OAuth access token used for the Cloud KMS API.
+///
+/// If unset, the token of the active `gcloud` account is used.
+pub const GCP_ACCESS_TOKEN_ENV: &str = "GOOGLE_OAUTH_ACCESS_TOKEN";
+
+/// How long an access token is used before it's fetched again. `gcloud` tokens expire after an
+/// hour, which long-running scripts easily exceed.
+const ACCESS_TOKEN_LIFETIME: Duration = Duration::from_secs(45 * 60);
+
+#[derive(Debug, thiserror::Error)]
+pub enum GcpSignerError {
+    #[error("failed to get an access token for the Cloud KMS API, set {GCP_ACCESS_TOKEN_ENV} or log in with `gcloud auth login`: {0}")]
+    AccessToken(String),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error("Cloud KMS request failed with status {status}: {body}")]
+    Api { status: u16, body: String },
+    #[error("key {0} is not a secp256k1 signing key")]
+    InvalidKey(String),
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    #[error("invalid public key: {0}")]
+    Spki(String),
+    #[error(transparent)]
+    K256(#[from] k256::ecdsa::Error),
+    #[error("could not recover the signer of the Cloud KMS signature")]
+    Recovery,
+    #[error("{0}")]
+    Eip712(String),
+}
+
+type Result<T, E = GcpSignerError> = std::result::Result<T, E>;
+
+/// A signer whose key is a Cloud KMS `EC_SIGN_SECP256K1_SHA256` key version, named
+/// `projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/*`.
+///
+/// Digests are signed remotely, the public key is only fetched once to derive the address. The
+/// access token is refreshed when it's about to expire or rejected by the API.
+#[derive(Clone)]
+pub struct GcpSigner {
+    client: reqwest::Client,
+    key_name: String,
+    access_token: Arc<Mutex<AccessToken>>,
+    public_key: VerifyingKey,
+    address: Address,
+    chain_id: u64,
+}
+
+impl fmt::Debug for GcpSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GcpSigner")
+            .field("key_name", &self.key_name)
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+/// An OAuth access token for the Cloud KMS API.
+struct AccessToken {
+    value: String,
+    fetched_at: Instant,
+}
+
+impl AccessToken {
+    /// Fetches the token of [`GCP_ACCESS_TOKEN_ENV`] or the active `gcloud` account.
+    fn fetch() -> Result<Self> {
+        Ok(Self { value: access_token()?, fetched_at: Instant::now() })
+    }
+
+    fn is_expired(&self) -> bool {
+        self.fetched_at.elapsed() >= ACCESS_TOKEN_LIFETIME
+    }
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+    algorithm: String,
+}
+
+#[derive(Serialize)]
+struct AsymmetricSignRequest {
+    digest: Digest,
+}
+
+#[derive(Serialize)]
+struct Digest {
+    sha256: String,
+}
+
+#[derive(Deserialize)]
+struct AsymmetricSignResponse {
+    signature: String,
+}
+
+impl GcpSigner {
+    /// Creates the signer of the key version, fetching its public key.
+    pub async fn new(key_name: impl Into<String>, chain_id: u64) -> Result<Self> {
+        let key_name = key_name.into();
+        let access_token = Arc::new(Mutex::new(AccessToken::fetch()?));
+        let client = reqwest::Client::new();
+
+        let url = format!("{GCP_KMS_API_URL}/{key_name}/publicKey");
+        let resp: PublicKeyResponse =
+            send_authorized(&access_token, |token| client.get(&url).bearer_auth(token)).await?;
+        if resp.algorithm != "EC_SIGN_SECP256K1_SHA256" {
+            return Err(GcpSignerError::InvalidKey(key_name))
+        }
+        let public_key = decode_pem(&resp.pem)?;
+        let address = public_key_to_address(&public_key);
+        trace!(%key_name, ?address, "instantiated GCP KMS signer");
+
+        Ok(Self { client, key_name, access_token, public_key, address, chain_id })
+    }
+
+    /// Signs the digest, returning the low-s signature with its recovery id.
+    pub async fn sign_digest(&self, digest: H256) -> Result<(KSig, RecoveryId)> {
+        let url = format!("{GCP_KMS_API_URL}/{}:asymmetricSign", self.key_name);
+        let request = AsymmetricSignRequest { digest: Digest { sha256: BASE64.encode(digest) } };
+        let resp: AsymmetricSignResponse = send_authorized(&self.access_token, |token| {
+            self.client.post(&url).bearer_auth(token).json(&request)
+        })
+        .await?;
+
+        let sig = KSig::from_der(&BASE64.decode(resp.signature)?)?;
+        let sig = sig.normalize_s().unwrap_or(sig);
+        let recovery_id = [0, 1]
+            .into_iter()
+            .filter_map(RecoveryId::from_byte)
+            .find(|id| {
+                VerifyingKey::recover_from_prehash(digest.as_bytes(), &sig, *id)
+                    .map_or(false, |key| key == self.public_key)
+            })
+            .ok_or(GcpSignerError::Recovery)?;
+        Ok((sig, recovery_id))
+    }
+
+    /// Signs the digest, with `v` set to `27 + recovery id`.
+    async fn sign_digest_with_v(&self, digest: H256) -> Result<Signature> {
+        let (sig, recovery_id) = self.sign_digest(digest).await?;
+        Ok(to_signature(&sig, 27 + recovery_id.to_byte() as u64))
+    }
+}
+
+#[async_trait]
+impl Signer for GcpSigner {
+    type Error = GcpSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature> {
+        self.sign_digest_with_v(hash_message(message)).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature> {
+        let mut tx = tx.clone();
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        tx.set_chain_id(chain_id);
+
+        let (sig, recovery_id) = self.sign_digest(tx.sighash()).await?;
+        Ok(to_signature(&sig, to_eip155_v(recovery_id.to_byte(), chain_id)))
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature> {
+        let digest =
+            payload.encode_eip712().map_err(|err| GcpSignerError::Eip712(err.to_string()))?;
+        self.sign_digest_with_v(digest.into()).await
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// Returns the access token of [`GCP_ACCESS_TOKEN_ENV`] or the active `gcloud` account.
+fn access_token() -> Result<String> {
+    if let Ok(token) = std::env::var(GCP_ACCESS_TOKEN_ENV) {
+        return Ok(token)
+    }
+    let output = Command::new("gcloud")
+        .args(["auth", "print-access-token"])
+        .output()
+        .map_err(|err| GcpSignerError::AccessToken(err.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GcpSignerError::AccessToken(stderr.trim().to_string()))
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the current access token, fetching a new one if it expired or is the `rejected` one.
+async fn current_token(token: &Mutex<AccessToken>, rejected: Option<&str>) -> Result<String> {
+    let mut token = token.lock().await;
+    if token.is_expired() || rejected == Some(token.value.as_str()) {
+        trace!("refreshing GCP access token");
+        *token = AccessToken::fetch()?;
+    }
+    Ok(token.value.clone())
+}
+
+/// Sends the request with the current access token, retrying it once with a new token if the API
+/// rejects it.
+async fn send_authorized<T: DeserializeOwned>(
+    token: &Mutex<AccessToken>,
+    request: impl Fn(&str) -> reqwest::RequestBuilder,
+) -> Result<T> {
+    let current = current_token(token, None).await?;
+    match with_retry(|| send(request(&current)), is_retryable).await {
+        Err(GcpSignerError::Api { status: 401, .. }) => {
+            let refreshed = current_token(token, Some(&current)).await?;
+            with_retry(|| send(request(&refreshed)), is_retryable).await
+        }
+        result => result,
+    }
+}
+
+async fn send<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T> {
+    let resp = request.send().await?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(GcpSignerError::Api { status: status.as_u16(), body })
+    }
+    Ok(resp.json().await?)
+}
+
+fn is_retryable(err: &GcpSignerError) -> bool {
+    match err {
+        GcpSignerError::Api { status, .. } => is_retryable_status(*status),
+        GcpSignerError::Request(err) => err.is_timeout() || err.is_connect(),
+        _ => false,
+    }
+}
+
+/// Decodes the PEM encoded SubjectPublicKeyInfo of a secp256k1 key.
+fn decode_pem(pem: &str) -> Result<VerifyingKey> {
+    let base64 = pem.lines().filter(|line| !line.starts_with("-----")).collect::<String>();
+    let der = BASE64.decode(base64)?;
+    VerifyingKey::from_public_key_der(&der).map_err(|err| GcpSignerError::Spki(err.to_string()))
+}
+
+fn public_key_to_address(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    Address::from_slice(&keccak256(&point.as_bytes()[1..])[12..])
+}
+
+pub(crate) fn to_signature(sig: &KSig, v: u64) -> Signature {
+    Signature {
+        r: U256::from_big_endian(sig.r().to_bytes().as_slice()),
+        s: U256::from_big_endian(sig.s().to_bytes().as_slice()),
+        v,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::{ecdsa::SigningKey, pkcs8::EncodePublicKey};
+
+    #[tokio::test]
+    async fn refreshes_expired_and_rejected_tokens() {
+        std::env::set_var(GCP_ACCESS_TOKEN_ENV, "new");
+
+        let token =
+            Mutex::new(AccessToken { value: "fresh".to_string(), fetched_at: Instant::now() });
+        assert_eq!(current_token(&token, None).await.unwrap(), "fresh");
+        // another request already refreshed the rejected token
+        assert_eq!(current_token(&token, Some("rejected")).await.unwrap(), "fresh");
+        assert_eq!(current_token(&token, Some("fresh")).await.unwrap(), "new");
+
+        let token = Mutex::new(AccessToken {
+            value: "expired".to_string(),
+            fetched_at: Instant::now() - ACCESS_TOKEN_LIFETIME,
+        });
+        assert_eq!(current_token(&token, None).await.unwrap(), "new");
+    }
+
+    #[test]
+    fn decodes_public_key() {
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let der = key.verifying_key().to_public_key_der().unwrap();
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            BASE64.encode(der.as_bytes())
+        );
+
+        let public_key = decode_pem(&pem).unwrap();
+        assert_eq!(&public_key, key.verifying_key());
+        assert_eq!(
+            public_key_to_address(&public_key),
+            "0x1a642f0e3c3af545e7acbd38b07251b3990914f1".parse::<Address>().unwrap()
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Throttling and retrying of the requests to remote key management services.
+
+use ethers_signers::AwsSignerError;
+use once_cell::sync::Lazy;
+use rusoto_core::RusotoError;
+use rusoto_kms::{GetPublicKeyError, SignError};
+use std::{fmt, future::Future, time::Duration};
+use tokio::sync::Semaphore;
+
+/// The maximum number of concurrent requests to key management services, which rate limit
+/// cryptographic operations.
+const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// The number of times a failed request is retried.
+const MAX_RETRIES: u32 = 5;
+
+/// The delay before the first retry, doubled on every attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+
+static REQUESTS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(MAX_CONCURRENT_REQUESTS));
+
+/// Sends the request, retrying it with an exponential backoff for as long as its error is
+/// retryable.
+pub(crate) async fn with_retry<T, E, F, Fut>(
+    mut request: F,
+    is_retryable: impl Fn(&E) -> bool,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: fmt::Display,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    let mut retries = 0;
+    loop {
+        let result = {
+            let _permit = REQUESTS.acquire().await.expect("semaphore is never closed");
+            request().await
+        };
+        match result {
+            Err(err) if retries < MAX_RETRIES && is_retryable(&err) => {
+                warn!(%err, ?backoff, "KMS request failed, retrying");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Returns `true` if the AWS KMS request was throttled, timed out or failed on the server.
+pub(crate) fn is_retryable_aws(err: &AwsSignerError) -> bool {
+    match err {
+        AwsSignerError::SignError(err) => match err {
+            RusotoError::Service(SignError::KMSInternal(_) | SignError::DependencyTimeout(_)) => {
+                true
+            }
+            err => is_retryable_rusoto(err),
+        },
+        AwsSignerError::GetPublicKeyError(err) => match err {
+            RusotoError::Service(
+                GetPublicKeyError::KMSInternal(_) | GetPublicKeyError::DependencyTimeout(_),
+            ) => true,
+            err => is_retryable_rusoto(err),
+        },
+        _ => false,
+    }
+}
+
+fn is_retryable_rusoto<E>(err: &RusotoError<E>) -> bool {
+    match err {
+        RusotoError::HttpDispatch(_) => true,
+        // throttling isn't modeled by the service errors
+        RusotoError::Unknown(resp) => {
+            resp.status.as_u16() == 429 ||
+                resp.status.is_server_error() ||
+                resp.body_as_str().contains("ThrottlingException")
+        }
+        _ => false,
+    }
+}
+
+/// Returns `true` if the HTTP status of a failed request to a KMS API is worth retrying.
+pub(crate) fn is_retryable_status(status: u16) -> bool {
+    status == 429 || status >= 500
+}
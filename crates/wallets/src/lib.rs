@@ -2,6 +2,8 @@
 extern crate tracing;
 
 pub mod error;
+pub mod gcp;
+mod kms;
 pub mod multi_wallet;
 pub mod raw_wallet;
 pub mod utils;
@@ -5,12 +5,17 @@ use crate::{
 use alloy_primitives::Address;
 use clap::Parser;
 use derive_builder::Builder;
+use ethers_providers::Middleware;
 use ethers_signers::Signer;
-use eyre::Result;
-use foundry_common::types::ToAlloy;
+use eyre::{Context, Result};
+use foundry_common::{provider::ethers::try_get_http_provider, types::ToAlloy};
 use foundry_config::Config;
 use serde::Serialize;
-use std::{collections::HashMap, iter::repeat, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    iter::repeat,
+    path::PathBuf,
+};
 
 /// Container for multiple wallets.
 #[derive(Debug, Default)]
@@ -88,6 +93,7 @@ macro_rules! create_hw_wallets {
 /// 5. Private Keys (cleartext in CLI)
 /// 6. Private Keys (interactively via secure prompt)
 /// 7. AWS KMS
+/// 8. GCP KMS
 #[derive(Builder, Clone, Debug, Default, Serialize, Parser)]
 #[command(next_help_heading = "Wallet options", about = None, long_about = None)]
 pub struct MultiWalletOpts {
@@ -142,9 +148,11 @@ pub struct MultiWalletOpts {
 
     /// The wallet derivation path.
     ///
-    /// Works with both --mnemonic-path and hardware wallets.
+    /// Works with both --mnemonic-path and hardware wallets. Can be repeated, and a component
+    /// can be a range of indexes to derive, e.g. `m/44'/60'/0'/0/0-9`.
     #[arg(
         long = "mnemonic-derivation-paths",
+        visible_alias = "mnemonic-derivation-path",
         alias = "hd-paths",
         help_heading = "Wallet options - raw",
         value_name = "PATH"
@@ -164,6 +172,20 @@ pub struct MultiWalletOpts {
     )]
     pub mnemonic_indexes: Option<Vec<u32>>,
 
+    /// Derive the first NUM accounts of the mnemonics on the common derivation paths, and use the
+    /// ones funded on the target chain.
+    #[arg(
+        long,
+        requires = "mnemonics",
+        help_heading = "Wallet options - raw",
+        num_args = 0..=1,
+        default_missing_value = "10",
+        value_name = "NUM",
+        value_parser = clap::value_parser!(u32).range(1..=utils::MAX_DERIVED_ACCOUNTS as i64)
+    )]
+    #[builder(default = "None")]
+    pub mnemonic_discover: Option<u32>,
+
     /// Use the keystore in the given folder or file.
     #[arg(
         long = "keystore",
@@ -221,8 +243,21 @@ pub struct MultiWalletOpts {
     pub trezor: bool,
 
     /// Use AWS Key Management Service.
+    ///
+    /// The keys are read from the comma separated `AWS_KMS_KEY_IDS` environment variable.
     #[arg(long, help_heading = "Wallet options - remote")]
     pub aws: bool,
+
+    /// Use the given key versions of GCP Key Management Service, in the form
+    /// `projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/*`.
+    #[arg(
+        long,
+        help_heading = "Wallet options - remote",
+        value_name = "KEY_RESOURCE_NAMES",
+        value_delimiter = ','
+    )]
+    #[builder(default = "None")]
+    pub gcp_kms: Option<Vec<String>>,
 }
 
 impl MultiWalletOpts {
@@ -240,6 +275,9 @@ impl MultiWalletOpts {
         if let Some(aws_signers) = self.aws_signers().await? {
             signers.extend(aws_signers);
         }
+        if let Some(gcp_signers) = self.gcp_signers().await? {
+            signers.extend(gcp_signers);
+        }
         if let Some((pending_keystores, unlocked)) = self.keystores()? {
             pending.extend(pending_keystores);
             signers.extend(unlocked);
@@ -327,28 +365,100 @@ impl MultiWalletOpts {
     }
 
     pub fn mnemonics(&self) -> Result<Option<Vec<WalletSigner>>> {
-        if let Some(ref mnemonics) = self.mnemonics {
-            let mut wallets = vec![];
-
-            let mut hd_paths_iter = self.hd_paths.clone().unwrap_or_default().into_iter();
+        if self.mnemonics.is_some() {
+            let wallets = self.mnemonic_derivations()?.into_iter().map(|(wallet, _)| wallet);
+            return Ok(Some(wallets.collect()));
+        }
+        Ok(None)
+    }
 
-            let mut passphrases_iter =
-                self.mnemonic_passphrases.clone().unwrap_or_default().into_iter();
+    /// Returns the signers derived from the mnemonics, with their derivation paths.
+    ///
+    /// A single mnemonic is derived at all the derivation paths, or else at all the indexes.
+    /// Multiple mnemonics are each derived at the derivation path, or else the index, at the same
+    /// position.
+    fn mnemonic_derivations(&self) -> Result<Vec<(WalletSigner, String)>> {
+        let mnemonics = self.mnemonics.clone().unwrap_or_default();
+        let hd_paths = self.hd_paths.clone().unwrap_or_default();
+        let indexes = self.mnemonic_indexes.clone().unwrap_or_default();
+        let mut passphrases_iter =
+            self.mnemonic_passphrases.clone().unwrap_or_default().into_iter();
+
+        let mut derivations = vec![];
+        for (i, mnemonic) in mnemonics.iter().enumerate() {
+            let passphrase = passphrases_iter.next();
+            let paths = if mnemonics.len() == 1 && !hd_paths.is_empty() {
+                let mut paths = vec![];
+                for path in &hd_paths {
+                    paths.extend(utils::expand_derivation_path(path)?);
+                }
+                paths
+            } else if let Some(path) = hd_paths.get(i) {
+                utils::expand_derivation_path(path)?
+            } else if mnemonics.len() == 1 && !indexes.is_empty() {
+                indexes.iter().map(|index| utils::default_derivation_path(*index)).collect()
+            } else {
+                vec![utils::default_derivation_path(indexes.get(i).copied().unwrap_or(0))]
+            };
+
+            for path in paths {
+                let wallet =
+                    utils::create_mnemonic_signer(mnemonic, passphrase.as_deref(), Some(&path), 0)?;
+                derivations.push((wallet, path));
+            }
+        }
+        Ok(derivations)
+    }
 
-            let mut indexes_iter = self.mnemonic_indexes.clone().unwrap_or_default().into_iter();
+    /// Returns the derivation paths the mnemonic signers are derived at, including the ones
+    /// probed by `--mnemonic-discover`.
+    pub fn mnemonic_derivation_paths(&self) -> Vec<String> {
+        let mut paths = self
+            .mnemonic_derivations()
+            .map(|derivations| derivations.into_iter().map(|(_, path)| path).collect::<Vec<_>>())
+            .unwrap_or_default();
+        if let Some(count) = self.mnemonic_discover {
+            paths.extend(discovery_paths(count));
+        }
+        paths.dedup();
+        paths
+    }
 
-            for mnemonic in mnemonics {
-                let wallet = utils::create_mnemonic_signer(
-                    mnemonic,
-                    passphrases_iter.next().as_deref(),
-                    hd_paths_iter.next().as_deref(),
-                    indexes_iter.next().unwrap_or(0),
-                )?;
-                wallets.push(wallet);
+    /// Derives the first `--mnemonic-discover` accounts of the mnemonics on the common derivation
+    /// paths, returning the signers of the accounts with a balance on the chain of the RPC, with
+    /// their derivation paths.
+    pub async fn discover_mnemonic_signers(
+        &self,
+        rpc_url: &str,
+    ) -> Result<Vec<(WalletSigner, String)>> {
+        let (Some(mnemonics), Some(count)) = (&self.mnemonics, self.mnemonic_discover) else {
+            return Ok(vec![])
+        };
+        let provider = try_get_http_provider(rpc_url)?;
+        let mut passphrases_iter =
+            self.mnemonic_passphrases.clone().unwrap_or_default().into_iter();
+
+        let mut discovered = vec![];
+        let mut seen = HashSet::new();
+        for mnemonic in mnemonics {
+            let passphrase = passphrases_iter.next();
+            for path in discovery_paths(count) {
+                let wallet =
+                    utils::create_mnemonic_signer(mnemonic, passphrase.as_deref(), Some(&path), 0)?;
+                let address = wallet.address();
+                if !seen.insert(address) {
+                    continue
+                }
+                let balance = provider
+                    .get_balance(address, None)
+                    .await
+                    .wrap_err_with(|| format!("failed to get the balance of {address:?}"))?;
+                if !balance.is_zero() {
+                    discovered.push((wallet, path));
+                }
             }
-            return Ok(Some(wallets));
         }
-        Ok(None)
+        Ok(discovered)
     }
 
     pub async fn ledgers(&self) -> Result<Option<Vec<WalletSigner>>> {
@@ -380,9 +490,11 @@ impl MultiWalletOpts {
         if self.aws {
             let mut wallets = vec![];
             let aws_keys = std::env::var("AWS_KMS_KEY_IDS")
-                .or(std::env::var("AWS_KMS_KEY_ID"))?
+                .or(std::env::var("AWS_KMS_KEY_ID"))
+                .map_err(|_| eyre::eyre!("`--aws` requires the AWS_KMS_KEY_IDS env var to be set"))?
                 .split(',')
-                .map(|k| k.to_string())
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
                 .collect::<Vec<_>>();
 
             for key in aws_keys {
@@ -394,6 +506,31 @@ impl MultiWalletOpts {
         }
         Ok(None)
     }
+
+    pub async fn gcp_signers(&self) -> Result<Option<Vec<WalletSigner>>> {
+        if let Some(key_names) = &self.gcp_kms {
+            let mut wallets = vec![];
+            for key_name in key_names {
+                wallets.push(WalletSigner::from_gcp(key_name).await?);
+            }
+            return Ok(Some(wallets));
+        }
+        Ok(None)
+    }
+}
+
+/// The common derivation paths of the first `count` accounts of a mnemonic: BIP44, Ledger Live
+/// and Ledger legacy.
+fn discovery_paths(count: u32) -> Vec<String> {
+    (0..count)
+        .flat_map(|index| {
+            [
+                utils::default_derivation_path(index),
+                format!("m/44'/60'/{index}'/0/0"),
+                format!("m/44'/60'/0'/{index}"),
+            ]
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -443,6 +580,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_gcp_kms_keys() {
+        let key = "projects/p/locations/global/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1";
+        let args: MultiWalletOpts =
+            MultiWalletOpts::parse_from(["foundry-cli", "--gcp-kms", &format!("{key},{key}2")]);
+        assert_eq!(args.gcp_kms, Some(vec![key.to_string(), format!("{key}2")]));
+    }
+
+    #[test]
+    fn derives_mnemonic_path_ranges() {
+        let mnemonic = "test test test test test test test test test test test junk";
+        let args: MultiWalletOpts = MultiWalletOpts::parse_from([
+            "foundry-cli",
+            "--mnemonics",
+            mnemonic,
+            "--mnemonic-derivation-path",
+            "m/44'/60'/0'/0/0-1",
+            "--mnemonic-derivation-path",
+            "m/44'/60'/1'/0/0",
+        ]);
+        assert_eq!(
+            args.mnemonic_derivation_paths(),
+            ["m/44'/60'/0'/0/0", "m/44'/60'/0'/0/1", "m/44'/60'/1'/0/0"]
+        );
+
+        let wallets = args.mnemonics().unwrap().unwrap();
+        assert_eq!(wallets.len(), 3);
+        assert_eq!(
+            wallets[0].address().to_alloy(),
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse::<Address>().unwrap()
+        );
+        assert_eq!(
+            wallets[1].address().to_alloy(),
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse::<Address>().unwrap()
+        );
+    }
+
+    #[test]
+    fn expands_derivation_paths() {
+        assert_eq!(
+            utils::expand_derivation_path("m/44'/60'/0-2'/0/0").unwrap(),
+            ["m/44'/60'/0'/0/0", "m/44'/60'/1'/0/0", "m/44'/60'/2'/0/0"]
+        );
+        assert_eq!(
+            utils::expand_derivation_path("m/44'/60'/0'/0/3").unwrap(),
+            ["m/44'/60'/0'/0/3"]
+        );
+        assert!(utils::expand_derivation_path("m/44'/60'/0'/0/3-1").is_err());
+        assert!(utils::expand_derivation_path("m/44'/60'/0-1'/0/0-1").is_err());
+        assert_eq!(utils::expand_derivation_path("m/44'/60'/0'/0/0-99").unwrap().len(), 100);
+        assert!(utils::expand_derivation_path("m/44'/60'/0'/0/0-100").is_err());
+        assert!(utils::expand_derivation_path("m/44'/60'/0'/0/0-4294967295").is_err());
+        assert!(MultiWalletOpts::try_parse_from([
+            "foundry-cli",
+            "--mnemonics",
+            "test",
+            "--mnemonic-discover",
+            "101"
+        ])
+        .is_err());
+    }
+
     // https://github.com/foundry-rs/foundry/issues/5179
     #[test]
     fn should_not_require_the_mnemonics_flag_with_mnemonic_indexes() {
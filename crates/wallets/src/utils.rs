@@ -57,6 +57,52 @@ pub fn create_mnemonic_signer(
     Ok(WalletSigner::from_mnemonic(&mnemonic, passphrase, hd_path, index)?)
 }
 
+/// The maximum number of accounts derived from a range of a derivation path, or probed on each
+/// path by `--mnemonic-discover`, as every derivation is slow and a typo like `0-1000000` would
+/// never finish.
+pub const MAX_DERIVED_ACCOUNTS: u32 = 100;
+
+/// The derivation path of the account at `index` that mnemonic indexes are derived at.
+pub fn default_derivation_path(index: u32) -> String {
+    format!("m/44'/60'/0'/0/{index}")
+}
+
+/// Expands a derivation path with a range component, e.g. `m/44'/60'/0'/0/0-9` or
+/// `m/44'/60'/0-4'/0/0`, into the paths of all the indexes of the inclusive range.
+pub fn expand_derivation_path(path: &str) -> Result<Vec<String>> {
+    let components = path.split('/').collect::<Vec<_>>();
+    let Some(position) = components.iter().position(|component| component.contains('-')) else {
+        return Ok(vec![path.to_string()])
+    };
+
+    let range = components[position];
+    let (range, hardened) = match range.strip_suffix('\'') {
+        Some(range) => (range, "'"),
+        None => (range, ""),
+    };
+    let (start, end) = range
+        .split_once('-')
+        .and_then(|(start, end)| Some((start.parse::<u32>().ok()?, end.parse::<u32>().ok()?)))
+        .filter(|(start, end)| start <= end)
+        .ok_or_else(|| eyre::eyre!("invalid range `{range}` in derivation path `{path}`"))?;
+    if end - start >= MAX_DERIVED_ACCOUNTS {
+        eyre::bail!(
+            "range `{range}` in derivation path `{path}` has more than {MAX_DERIVED_ACCOUNTS} indexes"
+        );
+    }
+    if components[position + 1..].iter().any(|component| component.contains('-')) {
+        eyre::bail!("derivation path `{path}` can only have a single range");
+    }
+
+    Ok((start..=end)
+        .map(|index| {
+            let mut components = components.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+            components[position] = format!("{index}{hardened}");
+            components.join("/")
+        })
+        .collect())
+}
+
 /// Creates [WalletSigner] instance from given Ledger parameters.
 pub async fn create_ledger_signer(
     hd_path: Option<&str>,
@@ -12,6 +12,7 @@ use serde::Serialize;
 /// 3. Trezor
 /// 4. Keystore (via file path)
 /// 5. AWS KMS
+/// 6. GCP KMS
 #[derive(Clone, Debug, Default, Serialize, Parser)]
 #[command(next_help_heading = "Wallet options", about = None, long_about = None)]
 pub struct WalletOpts {
@@ -79,8 +80,15 @@ pub struct WalletOpts {
     pub trezor: bool,
 
     /// Use AWS Key Management Service.
+    ///
+    /// The key is read from the `AWS_KMS_KEY_ID` environment variable.
     #[arg(long, help_heading = "Wallet options - AWS KMS")]
     pub aws: bool,
+
+    /// Use the given key version of GCP Key Management Service, in the form
+    /// `projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/*`.
+    #[arg(long, help_heading = "Wallet options - GCP KMS", value_name = "KEY_RESOURCE_NAME")]
+    pub gcp_kms: Option<String>,
 }
 
 impl WalletOpts {
@@ -94,8 +102,12 @@ impl WalletOpts {
             utils::create_trezor_signer(self.raw.hd_path.as_deref(), self.raw.mnemonic_index)
                 .await?
         } else if self.aws {
-            let key_id = std::env::var("AWS_KMS_KEY_ID")?;
+            let key_id = std::env::var("AWS_KMS_KEY_ID").map_err(|_| {
+                eyre::eyre!("`--aws` requires the AWS_KMS_KEY_ID env var to be set")
+            })?;
             WalletSigner::from_aws(&key_id).await?
+        } else if let Some(key_name) = &self.gcp_kms {
+            WalletSigner::from_gcp(key_name).await?
         } else if let Some(raw_wallet) = self.raw.signer()? {
             raw_wallet
         } else if let Some(path) = utils::maybe_get_keystore_path(
@@ -120,7 +132,7 @@ impl WalletOpts {
 Error accessing local wallet. Did you set a private key, mnemonic or keystore?
 Run `cast send --help` or `forge create --help` and use the corresponding CLI
 flag to set your key via:
---private-key, --mnemonic-path, --aws, --interactive, --trezor or --ledger.
+--private-key, --mnemonic-path, --aws, --gcp-kms, --interactive, --trezor or --ledger.
 Alternatively, if you're using a local node with unlocked accounts,
 use the --unlocked flag and either set the `ETH_FROM` environment variable to the address
 of the unlocked account you want to use, or provide the --from flag with the address directly."
@@ -200,6 +212,7 @@ mod tests {
             ledger: false,
             trezor: false,
             aws: false,
+            gcp_kms: None,
         };
         match wallet.signer().await {
             Ok(_) => {
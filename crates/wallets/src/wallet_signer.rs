@@ -1,4 +1,8 @@
-use crate::error::WalletSignerError;
+use crate::{
+    error::WalletSignerError,
+    gcp::{to_signature, GcpSigner},
+    kms::{is_retryable_aws, with_retry},
+};
 use alloy_primitives::B256;
 use async_trait::async_trait;
 use ethers_core::types::{
@@ -29,6 +33,8 @@ pub enum WalletSigner {
     Trezor(Trezor),
     /// Wrapper around AWS KMS signer.
     Aws(AwsSigner),
+    /// Wrapper around GCP KMS signer.
+    Gcp(GcpSigner),
 }
 
 impl WalletSigner {
@@ -49,7 +55,13 @@ impl WalletSigner {
 
         let kms = KmsClient::new_with_client(client, AwsRegion::default());
 
-        Ok(Self::Aws(AwsSigner::new(kms, key_id, 1).await?))
+        let signer =
+            with_retry(|| AwsSigner::new(kms.clone(), key_id, 1), is_retryable_aws).await?;
+        Ok(Self::Aws(signer))
+    }
+
+    pub async fn from_gcp(key_name: &str) -> Result<Self> {
+        Ok(Self::Gcp(GcpSigner::new(key_name, 1).await?))
     }
 
     pub fn from_private_key(private_key: impl AsRef<[u8]>) -> Result<Self> {
@@ -62,7 +74,7 @@ impl WalletSigner {
     /// - for Ledger and Trezor signers the number of addresses to retrieve is specified as argument
     /// - the result for Ledger signers includes addresses available for both LedgerLive and Legacy
     ///   derivation paths
-    /// - for Local and AWS signers the result contains a single address
+    /// - for Local, AWS and GCP signers the result contains a single address
     pub async fn available_senders(&self, max: usize) -> Result<Vec<ethers_core::types::Address>> {
         let mut senders = Vec::new();
         match self {
@@ -97,6 +109,9 @@ impl WalletSigner {
             WalletSigner::Aws(aws) => {
                 senders.push(aws.address());
             }
+            WalletSigner::Gcp(gcp) => {
+                senders.push(gcp.address());
+            }
         }
         Ok(senders)
     }
@@ -130,6 +145,7 @@ macro_rules! delegate {
             Self::Ledger($inner) => $e,
             Self::Trezor($inner) => $e,
             Self::Aws($inner) => $e,
+            Self::Gcp($inner) => $e,
         }
     };
 }
@@ -138,16 +154,36 @@ macro_rules! delegate {
 impl Signer for WalletSigner {
     type Error = WalletSignerError;
 
+    // AWS KMS requests are throttled and retried here, `GcpSigner` does so on its own
     async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature> {
-        delegate!(self, inner => inner.sign_message(message).await.map_err(Into::into))
+        match self {
+            Self::Aws(inner) => {
+                with_retry(|| inner.sign_message(message.as_ref()), is_retryable_aws)
+                    .await
+                    .map_err(Into::into)
+            }
+            _ => delegate!(self, inner => inner.sign_message(message).await.map_err(Into::into)),
+        }
     }
 
     async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature> {
-        delegate!(self, inner => inner.sign_transaction(message).await.map_err(Into::into))
+        match self {
+            Self::Aws(inner) => with_retry(|| inner.sign_transaction(message), is_retryable_aws)
+                .await
+                .map_err(Into::into),
+            _ => {
+                delegate!(self, inner => inner.sign_transaction(message).await.map_err(Into::into))
+            }
+        }
     }
 
     async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature> {
-        delegate!(self, inner => inner.sign_typed_data(payload).await.map_err(Into::into))
+        match self {
+            Self::Aws(inner) => with_retry(|| inner.sign_typed_data(payload), is_retryable_aws)
+                .await
+                .map_err(Into::into),
+            _ => delegate!(self, inner => inner.sign_typed_data(payload).await.map_err(Into::into)),
+        }
     }
 
     fn address(&self) -> ethers_core::types::Address {
@@ -164,6 +200,7 @@ impl Signer for WalletSigner {
             Self::Ledger(inner) => Self::Ledger(inner.with_chain_id(chain_id)),
             Self::Trezor(inner) => Self::Trezor(inner.with_chain_id(chain_id)),
             Self::Aws(inner) => Self::Aws(inner.with_chain_id(chain_id)),
+            Self::Gcp(inner) => Self::Gcp(inner.with_chain_id(chain_id)),
         }
     }
 }
@@ -207,6 +244,10 @@ impl WalletSigner {
             Self::Ledger(_) => Err(WalletSignerError::CannotSignRawHash("Ledger")),
             Self::Local(wallet) => wallet.sign_hash(hash.0.into()).map_err(Into::into),
             Self::Trezor(_) => Err(WalletSignerError::CannotSignRawHash("Trezor")),
+            Self::Gcp(gcp) => {
+                let (sig, recovery_id) = gcp.sign_digest(hash.0.into()).await?;
+                Ok(to_signature(&sig, 27 + recovery_id.to_byte() as u64))
+            }
         }
     }
 }
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract AddressBookTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    address constant WETH_MAINNET = 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2;
+    address constant WETH_OPTIMISM = 0x4200000000000000000000000000000000000006;
+
+    function setUp() public {
+        vm.setAddressBookPath("fixtures/AddressBook/addresses.json");
+    }
+
+    function testGetAddress() public {
+        vm.chainId(1);
+        assertEq(vm.getAddress("WETH"), WETH_MAINNET);
+        assertEq(vm.getLabel(WETH_MAINNET), "WETH");
+
+        vm.chainId(10);
+        assertEq(vm.getAddress("WETH"), WETH_OPTIMISM);
+    }
+
+    function testGetAddressOn() public {
+        assertEq(vm.getAddressOn(1, "WETH"), WETH_MAINNET);
+        assertEq(vm.getAddressOn(10, "WETH"), WETH_OPTIMISM);
+        assertEq(vm.getLabel(WETH_OPTIMISM), "WETH");
+    }
+
+    function testGetAddressMissingLabel() public {
+        vm._expectCheatcodeRevert();
+        vm.getAddressOn(1, "USDC");
+    }
+
+    function testGetAddressMissingChain() public {
+        vm._expectCheatcodeRevert();
+        vm.getAddressOn(5, "WETH");
+    }
+
+    function testMalformedAddressBook() public {
+        vm.setAddressBookPath("fixtures/AddressBook/malformed.json");
+        vm._expectCheatcodeRevert();
+        vm.getAddressOn(1, "WETH");
+    }
+}
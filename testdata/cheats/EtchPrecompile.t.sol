@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+interface WorkingContract {
+    function secret() external view returns (uint256);
+}
+
+contract EtchPrecompileTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    address public constant precompile = 0x0000000000000000000000000000000000000100;
+
+    function testEtchPrecompileFromArtifact() public {
+        vm.etchPrecompile(precompile, "fixtures/GetCode/WorkingContract.json");
+        assertEq(vm.getLabel(precompile), "Precompile(WorkingContract)");
+
+        // the precompile is executed instead of the code of the account, which is left untouched
+        assertEq(WorkingContract(precompile).secret(), 42);
+        assertEq(precompile.code.length, 0);
+    }
+
+    function testEtchPrecompileBypassesCode() public {
+        vm.etch(precompile, hex"60006000fd");
+        vm.etchPrecompile(precompile, "fixtures/GetCode/WorkingContract.json");
+        assertEq(WorkingContract(precompile).secret(), 42);
+    }
+
+    function testEtchPrecompileMissingPlugin() public {
+        vm._expectCheatcodeRevert();
+        vm.etchPrecompile(precompile, "fixtures/Precompiles/missing.so");
+    }
+
+    function testEtchPrecompileNotAvailableOnPrecompiles() public {
+        vm._expectCheatcodeRevert(
+            bytes("cannot call `etchPrecompile` on precompile 0x0000000000000000000000000000000000000001")
+        );
+        vm.etchPrecompile(address(1), "fixtures/GetCode/WorkingContract.json");
+    }
+}
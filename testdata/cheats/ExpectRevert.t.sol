@@ -203,3 +203,89 @@ contract ExpectRevertTest is DSTest {
         new ConstructorReverter("some message");
     }
 }
+
+contract Bank {
+    error InsufficientBalance(uint256 balance, uint256 amount, uint256 timestamp);
+
+    function withdraw(uint256 amount) public view {
+        revert InsufficientBalance(0, amount, block.timestamp);
+    }
+}
+
+contract BankHelper {
+    Bank public bank;
+
+    constructor(Bank _bank) {
+        bank = _bank;
+    }
+
+    /// Calls the bank twice, reverting only on the second call.
+    function withdrawTwice(uint256 amount) public view {
+        try bank.withdraw(amount) {} catch {}
+        bank.withdraw(amount);
+    }
+
+    /// Calls the bank, swallowing its revert.
+    function tryWithdraw(uint256 amount) public view returns (bool success) {
+        try bank.withdraw(amount) {
+            success = true;
+        } catch {}
+    }
+
+    function withdrawOwnError(uint256) public pure {
+        revert("helper error");
+    }
+}
+
+contract ExpectRevertVariantsTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    Bank bank;
+    BankHelper helper;
+
+    function setUp() public {
+        bank = new Bank();
+        helper = new BankHelper(bank);
+    }
+
+    function testExpectPartialRevert() public {
+        vm.expectPartialRevert(Bank.InsufficientBalance.selector);
+        bank.withdraw(1);
+    }
+
+    function testFailExpectPartialRevertWrongSelector() public {
+        vm.expectPartialRevert(Reverter.CustomError.selector);
+        bank.withdraw(1);
+    }
+
+    function testFailExpectRevertSelectorIsNotPartial() public {
+        vm.expectRevert(Bank.InsufficientBalance.selector);
+        bank.withdraw(1);
+    }
+
+    function testExpectRevertWithReverter() public {
+        vm.expectRevert(address(bank));
+        helper.withdrawTwice(1);
+
+        vm.expectPartialRevert(Bank.InsufficientBalance.selector, address(bank));
+        helper.withdrawTwice(1);
+
+        vm.expectRevert("helper error", address(helper));
+        helper.withdrawOwnError(1);
+    }
+
+    function testFailExpectRevertWrongReverter() public {
+        vm.expectRevert("helper error", address(bank));
+        helper.withdrawOwnError(1);
+    }
+
+    function testExpectRevertAtDepth() public {
+        vm.expectRevert(abi.encodeWithSelector(Bank.InsufficientBalance.selector, 0, 1, block.timestamp), 1);
+        assertTrue(helper.tryWithdraw(1));
+    }
+
+    function testFailExpectRevertAtDepthWrongData() public {
+        vm.expectRevert(abi.encodeWithSelector(Bank.InsufficientBalance.selector, 0, 2, block.timestamp), 1);
+        helper.tryWithdraw(1);
+    }
+}
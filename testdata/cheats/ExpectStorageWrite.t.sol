@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract Store {
+    uint256 public a;
+    uint256 public b;
+    address public lastSender;
+
+    function setA(uint256 value) public {
+        a = value;
+    }
+
+    function setB(uint256 value) public {
+        b = value;
+    }
+
+    function setBoth(uint256 value) public {
+        a = value;
+        b = value;
+    }
+
+    function setAFromSender(uint256 value) public {
+        a = value;
+        lastSender = msg.sender;
+    }
+
+    function setAndRevert(uint256 value) public {
+        a = value;
+        revert("reverted");
+    }
+}
+
+contract Caller {
+    Store public store;
+
+    constructor(Store _store) {
+        store = _store;
+    }
+
+    function setAIgnoringReverts(uint256 value) public {
+        try store.setAndRevert(value) {} catch {}
+        store.setB(value);
+    }
+
+    function delegateSetA(uint256 value) public {
+        (bool success,) = address(store).delegatecall(abi.encodeCall(Store.setA, (value)));
+        require(success);
+    }
+}
+
+contract ExpectStorageWriteTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    Store store;
+    Caller caller;
+
+    function setUp() public {
+        store = new Store();
+        caller = new Caller(store);
+    }
+
+    function testExpectStorageWrite() public {
+        vm.expectStorageWrite(address(store), bytes32(uint256(0)));
+        store.setA(1);
+    }
+
+    function testExpectStorageWriteValue() public {
+        vm.expectStorageWrite(address(store), bytes32(uint256(0)), bytes32(uint256(2)));
+        vm.expectStorageWrite(address(store), bytes32(uint256(1)), bytes32(uint256(2)));
+        store.setBoth(2);
+    }
+
+    function testFailExpectStorageWriteWrongValue() public {
+        vm.expectStorageWrite(address(store), bytes32(uint256(0)), bytes32(uint256(2)));
+        store.setA(3);
+    }
+
+    function testFailExpectStorageWriteUnexpectedSlot() public {
+        vm.expectStorageWrite(address(store), bytes32(uint256(0)));
+        store.setBoth(1);
+    }
+
+    function testFailExpectStorageWriteNotMade() public {
+        vm.expectStorageWrite(address(store), bytes32(uint256(1)));
+        store.setA(1);
+    }
+
+    function testFailExpectStorageWriteNoCall() public {
+        vm.expectStorageWrite(address(store), bytes32(uint256(0)));
+    }
+
+    function testExpectNoStorageWrites() public {
+        vm.expectNoStorageWrites(address(store));
+        store.a();
+    }
+
+    function testFailExpectNoStorageWrites() public {
+        vm.expectNoStorageWrites(address(store));
+        store.setA(1);
+    }
+
+    function testExpectStorageWriteIgnoresRevertedWrites() public {
+        vm.expectStorageWrite(address(store), bytes32(uint256(1)), bytes32(uint256(1)));
+        caller.setAIgnoringReverts(1);
+    }
+
+    function testExpectNoStorageWritesThroughDelegatecall() public {
+        vm.expectNoStorageWrites(address(store));
+        caller.delegateSetA(1);
+    }
+
+    function testFailExpectNoStorageWritesToDelegatingContract() public {
+        vm.expectNoStorageWrites(address(caller));
+        caller.delegateSetA(1);
+    }
+
+    function testExpectStorageWriteWithPrank() public {
+        vm.prank(address(0xbeef));
+        vm.expectStorageWrite(address(store), bytes32(uint256(0)));
+        vm.expectStorageWrite(address(store), bytes32(uint256(2)), bytes32(uint256(uint160(0xbeef))));
+        store.setAFromSender(1);
+    }
+
+    function testExpectNoStorageWritesWithExpectRevert() public {
+        vm.expectNoStorageWrites(address(store));
+        vm.expectRevert("reverted");
+        store.setAndRevert(1);
+    }
+}
@@ -28,4 +28,28 @@ contract FfiTest is DSTest {
         bytes memory res = vm.ffi(inputs);
         assertEq(string(res), "gm");
     }
+
+    function testFfiExt() public {
+        string[] memory inputs = new string[](3);
+        inputs[0] = "bash";
+        inputs[1] = "-c";
+        inputs[2] = "cat; echo -n $GM";
+
+        Vm.FfiEnvVar[] memory env = new Vm.FfiEnvVar[](1);
+        env[0] = Vm.FfiEnvVar("GM", "wagmi");
+
+        Vm.FfiExtResult memory res = vm.ffiExt(Vm.FfiRequest(inputs, bytes("gm "), 0, "", env));
+        assertEq(res.exitCode, 0);
+        assertEq(string(res.stdout), "gm wagmi");
+        assertTrue(!res.timedOut);
+    }
+
+    function testFfiExtTimeout() public {
+        string[] memory inputs = new string[](2);
+        inputs[0] = "sleep";
+        inputs[1] = "10";
+
+        Vm.FfiExtResult memory res = vm.ffiExt(Vm.FfiRequest(inputs, "", 100, "", new Vm.FfiEnvVar[](0)));
+        assertTrue(res.timedOut);
+    }
 }
@@ -220,6 +220,63 @@ contract ForkTest is DSTest {
         }
     }
 
+    function testGetProof() public {
+        vm.createSelectFork("rpcAlias", 18332681);
+        address weth = address(0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2);
+
+        bytes32[] memory slots = new bytes32[](2);
+        // `decimals`
+        slots[0] = bytes32(uint256(2));
+        // `balanceOf[weth]`
+        slots[1] = keccak256(abi.encode(weth, uint256(3)));
+        // the proof is of the state at the fork's block, regardless of the current block number
+        vm.roll(block.number + 100);
+        Vm.EthGetProof memory proof = vm.getProof(weth, slots);
+
+        assertEq(proof.account, weth);
+        assertEq(proof.balance, weth.balance);
+        assertEq(proof.codeHash, weth.codehash);
+        assertGt(proof.accountProof.length, 0);
+        assertEq(proof.storageProof.length, 2);
+        assertEq(proof.storageProof[0].value, 18);
+        for (uint256 i = 0; i < slots.length; i++) {
+            assertEq(proof.storageProof[i].key, slots[i]);
+            assertEq(bytes32(proof.storageProof[i].value), vm.load(weth, slots[i]));
+            assertGt(proof.storageProof[i].proof.length, 0);
+        }
+    }
+
+    function testPrefetch() public {
+        vm.createSelectFork("rpcAlias", 18332681);
+        address weth = address(0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2);
+
+        address[] memory accounts = new address[](2);
+        accounts[0] = weth;
+        accounts[1] = address(0x6B175474E89094C44Da98b954EedeAC495271d0F);
+        bytes32[][] memory slots = new bytes32[][](2);
+        slots[0] = new bytes32[](4);
+        // `name`, `symbol` and `decimals`
+        for (uint256 i = 0; i < 3; i++) {
+            slots[0][i] = bytes32(i);
+        }
+        // `balanceOf[weth]`
+        slots[0][3] = keccak256(abi.encode(weth, uint256(3)));
+        vm.prefetch(accounts, slots);
+
+        // the prefetched values are the ones of the fork's block
+        Vm.EthGetProof memory proof = vm.getProof(weth, slots[0]);
+        assertEq(uint256(vm.load(weth, slots[0][2])), 18);
+        for (uint256 i = 0; i < slots[0].length; i++) {
+            assertEq(vm.load(weth, slots[0][i]), bytes32(proof.storageProof[i].value));
+        }
+        assertEq(weth.balance, proof.balance);
+        assertGt(accounts[1].code.length, 0);
+
+        // prefetching cached values again is a no-op
+        vm.prefetch(accounts, slots);
+        assertEq(uint256(vm.load(weth, slots[0][2])), 18);
+    }
+
     function testRpc() public {
         // balance at block <https://etherscan.io/block/18332681>
         vm.selectFork(mainnetFork);
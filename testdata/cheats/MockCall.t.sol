@@ -155,6 +155,77 @@ contract MockCallTest is DSTest {
         assertEq(mock.add(1, 2), 10);
         mock.noReturnValue();
     }
+
+    function testMockCalls() public {
+        Mock mock = new Mock();
+
+        bytes[] memory calldatas = new bytes[](2);
+        calldatas[0] = abi.encodeWithSelector(mock.numberA.selector);
+        calldatas[1] = abi.encodeWithSelector(mock.add.selector, 1, 2);
+        bytes[] memory returnDatas = new bytes[](2);
+        returnDatas[0] = abi.encode(10);
+        returnDatas[1] = abi.encode(20);
+        vm.mockCalls(address(mock), calldatas, returnDatas);
+
+        assertEq(mock.numberA(), 10);
+        assertEq(mock.numberB(), 2);
+        assertEq(mock.add(1, 2), 20);
+        assertEq(mock.add(2, 2), 4);
+    }
+
+    function testMockCallsEmptyAccount() public {
+        Mock mock = Mock(address(100));
+
+        bytes[] memory calldatas = new bytes[](1);
+        calldatas[0] = abi.encodeWithSelector(mock.numberA.selector);
+        bytes[] memory returnDatas = new bytes[](1);
+        returnDatas[0] = abi.encode(10);
+        vm.mockCalls(address(mock), calldatas, returnDatas);
+
+        assertEq(mock.numberA(), 10);
+    }
+
+    function testMockCallsLengthMismatch() public {
+        bytes[] memory calldatas = new bytes[](2);
+        bytes[] memory returnDatas = new bytes[](1);
+        vm._expectCheatcodeRevert("calldata and return data lengths don't match: 2 != 1");
+        vm.mockCalls(address(100), calldatas, returnDatas);
+    }
+
+    function testMockCallAny() public {
+        Mock a = new Mock();
+        Mock b = new Mock();
+
+        vm.mockCallAny(a.numberA.selector, abi.encode(7));
+
+        assertEq(a.numberA(), 7);
+        assertEq(b.numberA(), 7);
+        assertEq(a.numberB(), 2);
+    }
+
+    function testMockCallPrecedence() public {
+        Mock target = new Mock();
+        Mock other = new Mock();
+
+        vm.mockCallAny(target.add.selector, abi.encode(1));
+        vm.mockCall(address(target), abi.encodeWithSelector(target.add.selector), abi.encode(2));
+        vm.mockCall(address(target), abi.encodeWithSelector(target.add.selector, 5, 5), abi.encode(3));
+
+        // exact calldata > selector on the callee > selector on any callee
+        assertEq(target.add(5, 5), 3);
+        assertEq(target.add(1, 1), 2);
+        assertEq(other.add(5, 5), 1);
+    }
+
+    function testClearMockedCallsAny() public {
+        Mock target = new Mock();
+
+        vm.mockCallAny(target.numberB.selector, abi.encode(10));
+        assertEq(target.numberB(), 10);
+
+        vm.clearMockedCalls();
+        assertEq(target.numberB(), 2);
+    }
 }
 
 contract MockCallRevertTest is DSTest {
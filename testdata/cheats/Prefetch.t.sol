@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract PrefetchTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testRevertIfNotForked() public {
+        address[] memory accounts = new address[](1);
+        accounts[0] = address(this);
+        bytes32[][] memory slots = new bytes32[][](1);
+
+        vm._expectCheatcodeRevert("no active fork to prefetch from");
+        vm.prefetch(accounts, slots);
+    }
+
+    function testRevertIfLengthMismatch() public {
+        address[] memory accounts = new address[](2);
+        bytes32[][] memory slots = new bytes32[][](1);
+
+        vm._expectCheatcodeRevert("accounts and slots must have the same length: 2 != 1");
+        vm.prefetch(accounts, slots);
+    }
+}
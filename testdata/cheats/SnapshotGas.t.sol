@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+import "../logs/console.sol";
+
+contract Flare {
+    uint256[] public data;
+
+    function run(uint256 n) public {
+        for (uint256 i = 0; i < n; i++) {
+            data.push(i);
+        }
+    }
+}
+
+contract SnapshotGasTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    Flare public flare;
+
+    function setUp() public {
+        flare = new Flare();
+    }
+
+    function testSnapshotGas() public {
+        vm.startSnapshotGas("run");
+        flare.run(1);
+        uint256 gasUsed = vm.stopSnapshotGas("run");
+        assertGt(gasUsed, 0);
+
+        vm.startSnapshotGas("empty");
+        uint256 empty = vm.stopSnapshotGas("empty");
+        assertLt(empty, 100);
+    }
+
+    function testSnapshotGasScalesWithWork() public {
+        vm.startSnapshotGas("one");
+        flare.run(1);
+        uint256 one = vm.stopSnapshotGas("one");
+
+        vm.startSnapshotGas("five");
+        flare.run(5);
+        uint256 five = vm.stopSnapshotGas("five");
+
+        assertGt(five, one);
+    }
+
+    function testSnapshotGasLastCall() public {
+        flare.run(2);
+        uint256 gasUsed = vm.snapshotGasLastCall("run");
+        assertGt(gasUsed, 0);
+    }
+
+    function testSnapshotGasLastCallIgnoresConsoleLogs() public {
+        flare.run(1);
+        uint256 one = vm.snapshotGasLastCall("one");
+
+        flare.run(5);
+        console.log("logging after the call");
+        vm.getNonce(address(flare));
+        uint256 five = vm.snapshotGasLastCall("five");
+        assertGt(five, one);
+    }
+
+    function testNestedSnapshotGas() public {
+        vm.startSnapshotGas("outer");
+        vm.startSnapshotGas("inner");
+        flare.run(1);
+        uint256 inner = vm.stopSnapshotGas("inner");
+        flare.run(1);
+        uint256 outer = vm.stopSnapshotGas("outer");
+        assertGt(outer, inner);
+    }
+
+    function testRevertIfDuplicateName() public {
+        flare.run(1);
+        vm.snapshotGasLastCall("run");
+        vm._expectCheatcodeRevert("gas snapshot `run` was already recorded");
+        vm.snapshotGasLastCall("run");
+    }
+
+    function testRevertIfNotStarted() public {
+        vm._expectCheatcodeRevert("gas snapshot `run` was not started");
+        vm.stopSnapshotGas("run");
+    }
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract TxContextTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function unchanged() internal pure returns (Vm.TxContext memory) {
+        return Vm.TxContext({
+            origin: address(type(uint160).max),
+            gasPrice: type(uint256).max,
+            basefee: type(uint256).max,
+            blobBaseFee: type(uint256).max,
+            coinbase: address(type(uint160).max),
+            prevrandao: bytes32(type(uint256).max),
+            chainId: type(uint256).max,
+            blockNumber: type(uint256).max,
+            timestamp: type(uint256).max
+        });
+    }
+
+    function testGetTxContext() public {
+        vm.txGasPrice(10);
+        vm.fee(20);
+        vm.coinbase(address(0xc0ffee));
+        vm.prevrandao(bytes32(uint256(42)));
+        vm.chainId(31);
+        vm.roll(100);
+        vm.warp(1000);
+
+        Vm.TxContext memory context = vm.getTxContext();
+        assertEq(context.origin, tx.origin);
+        assertEq(context.gasPrice, 10);
+        assertEq(context.basefee, 20);
+        assertEq(context.coinbase, address(0xc0ffee));
+        assertEq(context.prevrandao, bytes32(uint256(42)));
+        assertEq(context.chainId, 31);
+        assertEq(context.blockNumber, 100);
+        assertEq(context.timestamp, 1000);
+    }
+
+    function testSetTxContext() public {
+        Vm.TxContext memory context = vm.getTxContext();
+        context.gasPrice = 11;
+        context.basefee = 21;
+        context.coinbase = address(0xbeef);
+        context.prevrandao = bytes32(uint256(43));
+        context.chainId = 32;
+        context.blockNumber = 101;
+        context.timestamp = 1001;
+        vm.setTxContext(context);
+
+        assertEq(tx.gasprice, 11);
+        assertEq(block.basefee, 21);
+        assertEq(block.coinbase, address(0xbeef));
+        assertEq(block.prevrandao, 43);
+        assertEq(block.chainid, 32);
+        assertEq(vm.getBlockNumber(), 101);
+        assertEq(vm.getBlockTimestamp(), 1001);
+    }
+
+    function testSetTxContextSkipsSentinels() public {
+        vm.roll(100);
+        vm.warp(1000);
+
+        Vm.TxContext memory context = unchanged();
+        context.timestamp = 2000;
+        vm.setTxContext(context);
+
+        assertEq(vm.getBlockNumber(), 100);
+        assertEq(vm.getBlockTimestamp(), 2000);
+    }
+
+    function testRestoreTxContext() public {
+        Vm.TxContext memory saved = vm.getTxContext();
+
+        vm.fee(99);
+        vm.warp(12345);
+        vm.coinbase(address(0xdead));
+
+        vm.setTxContext(saved);
+        Vm.TxContext memory restored = vm.getTxContext();
+        assertEq(restored.basefee, saved.basefee);
+        assertEq(restored.timestamp, saved.timestamp);
+        assertEq(restored.coinbase, saved.coinbase);
+        assertEq(keccak256(abi.encode(restored)), keccak256(abi.encode(saved)));
+    }
+
+    function testSetTxContextChainIdTooLarge() public {
+        Vm.TxContext memory context = unchanged();
+        context.chainId = uint256(type(uint64).max) + 1;
+        vm._expectCheatcodeRevert("chain ID must be less than 2^64 - 1");
+        vm.setTxContext(context);
+    }
+}
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+
+contract Timelock {
+    uint256 public unlockTime;
+    bool public withdrawn;
+
+    constructor() {
+        unlockTime = block.timestamp + 1 weeks;
+    }
+
+    function withdraw() public {
+        if (block.timestamp >= unlockTime) {
+            withdrawn = true;
+        }
+    }
+}
+
+contract InvariantBlockJumps is DSTest {
+    Timelock timelock;
+
+    function setUp() public {
+        timelock = new Timelock();
+    }
+
+    function invariantLocked() public {
+        require(!timelock.withdrawn(), "withdrawn");
+    }
+}
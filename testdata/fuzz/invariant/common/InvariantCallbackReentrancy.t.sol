@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+
+contract Receiver {
+    address public immutable drop = msg.sender;
+    uint256 public received;
+
+    function onERC721Received(address, address, uint256, bytes calldata) external returns (bytes4) {
+        if (msg.sender == drop) received += 1;
+        return this.onERC721Received.selector;
+    }
+}
+
+contract Drop {
+    Receiver public receiver;
+    bool public claimed;
+    uint256 public minted;
+
+    constructor() {
+        receiver = new Receiver();
+    }
+
+    function claim() public {
+        require(!claimed, "claimed");
+        minted += 1;
+        // The receiver is notified before the claim is recorded.
+        bytes4 retval = receiver.onERC721Received(msg.sender, address(0), minted, "");
+        require(retval == Receiver.onERC721Received.selector, "rejected");
+        claimed = true;
+    }
+}
+
+contract InvariantCallbackReentrancy is DSTest {
+    Drop drop;
+
+    function setUp() public {
+        drop = new Drop();
+    }
+
+    function invariantSingleClaim() public {
+        require(drop.minted() <= 1, "claimed twice");
+    }
+
+    function invariantCallbackRuns() public {
+        require(drop.receiver().received() == drop.minted(), "callback skipped");
+    }
+}